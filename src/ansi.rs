@@ -0,0 +1,263 @@
+//! ANSI escape parsing: colored output of external tools (`--pipe-to`
+//! renderers, `:git`/`:jj` commands) turned into ratatui spans so it shows
+//! with its own colors inside tuicr's panels.
+//!
+//! SGR escapes (`ESC [ ... m`) set colors and attributes, in both the `;`
+//! and the `:` separated forms. Every other escape, such as cursor
+//! movement, line erasing or OSC 8 hyperlinks, is dropped and only its
+//! visible text kept. A carriage return starts the line over, as it would
+//! on a terminal redrawing a progress line.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Split one line of terminal output into styled spans.
+pub fn parse_line(line: &str) -> Vec<(Style, String)> {
+    let mut spans: Vec<(Style, String)> = Vec::new();
+    let mut style = Style::default();
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ESC => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut last = None;
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            last = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    if last == Some('m') {
+                        if !text.is_empty() {
+                            spans.push((style, std::mem::take(&mut text)));
+                        }
+                        style = apply_sgr(style, &params);
+                    }
+                }
+                // OSC (window titles, hyperlinks): up to BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == BEL || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more character
+                Some('(' | ')') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\r' => {
+                spans.clear();
+                text.clear();
+            }
+            '\t' => text.push_str("    "),
+            _ if c.is_control() => {}
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        spans.push((style, text));
+    }
+    spans
+}
+
+/// Parse multi-line output into lines of spans.
+pub fn to_lines(output: &str) -> Vec<Line<'static>> {
+    output
+        .lines()
+        .map(|line| {
+            Line::from(
+                parse_line(line)
+                    .into_iter()
+                    .map(|(style, text)| Span::styled(text, style))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    // `;` separates parameters; `:` separates the parts of one parameter,
+    // as in `38:2::255:0:0`
+    let groups: Vec<Vec<u16>> = params
+        .split(';')
+        .map(|group| {
+            group
+                .split(':')
+                .map(|code| code.parse().unwrap_or(0))
+                .collect()
+        })
+        .collect();
+    let mut groups = groups.iter();
+    while let Some(group) = groups.next() {
+        let code = group[0];
+        if group.len() > 1 {
+            style = match code {
+                38 | 48 => with_color(style, code, extended_color(&group[1..], true)),
+                4 => match group[1] {
+                    0 => without(style, Modifier::UNDERLINED),
+                    _ => style.add_modifier(Modifier::UNDERLINED),
+                },
+                _ => style,
+            };
+            continue;
+        }
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 | 21 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            6 => style.add_modifier(Modifier::RAPID_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => without(style, Modifier::BOLD | Modifier::DIM),
+            23 => without(style, Modifier::ITALIC),
+            24 => without(style, Modifier::UNDERLINED),
+            25 => without(style, Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            27 => without(style, Modifier::REVERSED),
+            28 => without(style, Modifier::HIDDEN),
+            29 => without(style, Modifier::CROSSED_OUT),
+            30..=37 => style.fg(named_color(code - 30, false)),
+            90..=97 => style.fg(named_color(code - 90, true)),
+            40..=47 => style.bg(named_color(code - 40, false)),
+            100..=107 => style.bg(named_color(code - 100, true)),
+            39 => Style { fg: None, ..style },
+            49 => Style { bg: None, ..style },
+            38 | 48 => {
+                let rest: Vec<u16> = match groups.clone().next().map(|g| g[0]) {
+                    Some(5) => groups.by_ref().take(2).map(|g| g[0]).collect(),
+                    Some(2) => groups.by_ref().take(4).map(|g| g[0]).collect(),
+                    _ => Vec::new(),
+                };
+                with_color(style, code, extended_color(&rest, false))
+            }
+            _ => style,
+        };
+    }
+    style
+}
+
+/// The color after a `38`/`48`: `5;n` (256-color palette) or `2;r;g;b`.
+/// The `:` form may put a color space id before `r:g:b`.
+fn extended_color(parts: &[u16], colon_form: bool) -> Option<Color> {
+    match parts {
+        [5, n, ..] => Some(Color::Indexed(*n as u8)),
+        [2, _, r, g, b, ..] if colon_form => Some(Color::Rgb(*r as u8, *g as u8, *b as u8)),
+        [2, r, g, b] => Some(Color::Rgb(*r as u8, *g as u8, *b as u8)),
+        _ => None,
+    }
+}
+
+/// `style` without `modifier`. Unlike `Style::remove_modifier` this
+/// doesn't mark it as explicitly off, so the span can still be patched
+/// onto a style that sets it.
+fn without(style: Style, modifier: Modifier) -> Style {
+    Style {
+        add_modifier: style.add_modifier - modifier,
+        ..style
+    }
+}
+
+fn with_color(style: Style, code: u16, color: Option<Color>) -> Style {
+    match (code, color) {
+        (38, Some(color)) => style.fg(color),
+        (48, Some(color)) => style.bg(color),
+        _ => style,
+    }
+}
+
+fn named_color(index: u16, bright: bool) -> Color {
+    match (index, bright) {
+        (0, false) => Color::Black,
+        (1, false) => Color::Red,
+        (2, false) => Color::Green,
+        (3, false) => Color::Yellow,
+        (4, false) => Color::Blue,
+        (5, false) => Color::Magenta,
+        (6, false) => Color::Cyan,
+        (7, false) => Color::Gray,
+        (0, true) => Color::DarkGray,
+        (1, true) => Color::LightRed,
+        (2, true) => Color::LightGreen,
+        (3, true) => Color::LightYellow,
+        (4, true) => Color::LightBlue,
+        (5, true) => Color::LightMagenta,
+        (6, true) => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_sgr_colors_and_attributes() {
+        // when
+        let spans = parse_line("\x1b[1;38;5;208mfn\x1b[0m \x1b[48;2;0;64;0;3mmain\x1b[m()\x1b[K");
+
+        // then
+        assert_eq!(
+            spans,
+            vec![
+                (
+                    Style::default()
+                        .fg(Color::Indexed(208))
+                        .add_modifier(Modifier::BOLD),
+                    "fn".to_string()
+                ),
+                (Style::default(), " ".to_string()),
+                (
+                    Style::default()
+                        .bg(Color::Rgb(0, 64, 0))
+                        .add_modifier(Modifier::ITALIC),
+                    "main".to_string()
+                ),
+                (Style::default(), "()".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_colon_form_and_drop_other_escapes() {
+        // given
+        let line = "50%\rdone \x1b]8;;https://example.com\x1b\\\x1b[38:2::255:0:0;4:3mlink\x1b[24m\x1b]8;;\x07!\x1b(B\tend";
+
+        // when
+        let spans = parse_line(line);
+
+        // then
+        let red = Style::default().fg(Color::Rgb(255, 0, 0));
+        assert_eq!(
+            spans,
+            vec![
+                (Style::default(), "done ".to_string()),
+                (red.add_modifier(Modifier::UNDERLINED), "link".to_string()),
+                (red, "!    end".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_split_output_into_lines() {
+        // when
+        let lines = to_lines("\x1b[32mok\x1b[0m\n\nplain\n");
+
+        // then
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].spans[0].style, Style::default().fg(Color::Green));
+        assert!(lines[1].spans.is_empty());
+        assert_eq!(lines[2].spans[0].content, "plain");
+    }
+}
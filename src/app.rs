@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher, RandomState};
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
@@ -6,23 +7,38 @@ use chrono::Utc;
 use ratatui::style::Color;
 
 use crate::config::CommentTypeConfig;
+use crate::diff_filter::DiffFilter;
 use crate::error::{Result, TuicrError};
+use crate::filetypes::FiletypeRules;
 use crate::forge::context::{ContextProvider, ForgeContextProvider, VcsContextProvider};
 use crate::forge::selector::PullRequestsTab;
 use crate::forge::traits::{ForgeBackend, ForgeRepository};
+use crate::input::keybindings::HelpContext;
+use crate::model::anchor::{self, Anchor};
+use crate::model::checkpoint::Checkpoint;
+use crate::model::conflicts::ConflictIndex;
+use crate::model::word_diff::WordDiffMode;
 use crate::model::{
-    ClearScope, Comment, CommentType, DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin,
-    LineRange, LineSide, ReviewSession, SessionDiffSource,
+    ClearScope, Comment, CommentType, DiffFile, DiffHunk, DiffLine, FileModes, FileSizes,
+    FileStatus, LineOrigin, LineRange, LineSide, ReviewConfidence, ReviewEventKind, ReviewSession,
+    Reviewer, SessionCursor, SessionDiffSource, SpotCheck, conventional_label, decorated_label,
 };
+use crate::notify::Operation;
+use crate::persistence::findings::FindingsDb;
+use crate::persistence::journal::{self, JournalOp};
+use crate::persistence::last_source::ReviewSource;
 use crate::persistence::load_latest_session_for_context;
+use crate::persistence::lock::LockState;
+use crate::persistence::metrics::{IDLE_GAP_SECS, ReviewMetrics};
 use crate::syntax::SyntaxHighlighter;
 use crate::theme::Theme;
 use crate::update::UpdateInfo;
 use crate::vcs::git::calculate_gap;
 use crate::vcs::traits::VcsType;
 use crate::vcs::{
-    CommitInfo, FileBackend, GitBackendPreference, PrNoopVcs, VcsBackend, VcsChangeStatus, VcsInfo,
-    detect_vcs,
+    AuthorStats, CommitInfo, FileBackend, GitBackendPreference, IgnoreWhitespace, PatchBackend,
+    PrNoopVcs, RangeMode, RenameDetection, VcsBackend, VcsChangeStatus, VcsInfo,
+    colocated_counterpart, colocated_counterpart_type, detect_vcs,
 };
 
 const VISIBLE_COMMIT_COUNT: usize = 10;
@@ -30,6 +46,19 @@ const COMMIT_PAGE_SIZE: usize = 10;
 pub const STAGED_SELECTION_ID: &str = "__tuicr_staged__";
 pub const UNSTAGED_SELECTION_ID: &str = "__tuicr_unstaged__";
 pub const GAP_EXPAND_BATCH: usize = 20;
+/// Runs of unchanged lines inside a hunk longer than this are elided.
+pub const DEFAULT_ELIDE_CONTEXT: usize = 8;
+/// Unchanged lines kept visible on each side of an elided run.
+const ELIDE_CONTEXT_KEEP: usize = 3;
+/// Recorded findings before a file gets a hint badge in the file list.
+pub const FINDINGS_HINT_THRESHOLD: usize = 3;
+/// Where `:export patch` writes the suggestions patch.
+pub const SUGGESTIONS_PATCH_FILE: &str = "tuicr-suggestions.patch";
+/// Where `:export annotated-patch` writes the diff with comments interleaved.
+pub const ANNOTATED_PATCH_FILE: &str = "tuicr-review.annotated.patch";
+/// Where a bare `:export` writes the report when a template sets its format;
+/// the format is the extension.
+pub const REVIEW_REPORT_STEM: &str = "tuicr-review";
 
 /// Count how many annotation lines a gap produces (expanders + hidden count).
 /// `hi_char = None` means slice to the end.
@@ -105,6 +134,32 @@ pub struct GapId {
     pub hunk_idx: usize,
 }
 
+/// Identifies a run of unchanged lines inside a hunk by its first hidden line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElisionId {
+    pub file_idx: usize,
+    pub hunk_idx: usize,
+    pub start: usize,
+}
+
+/// Unchanged hunk lines `start..start + count` shown as a single row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElidedRun {
+    pub start: usize,
+    pub count: usize,
+}
+
+/// A line comment from an earlier session on this repo, shown ghosted on the
+/// same line of the current diff
+#[derive(Debug, Clone)]
+pub struct HistoryComment {
+    pub line: u32,
+    pub side: LineSide,
+    pub comment: Comment,
+    /// When the earlier session was last saved
+    pub reviewed_at: chrono::DateTime<Utc>,
+}
+
 /// Direction of gap expansion
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ExpandDirection {
@@ -201,6 +256,8 @@ pub enum AnnotatedLine {
     ReviewComment { comment_idx: usize },
     /// File header line
     FileHeader { file_idx: usize },
+    /// Mode change or symlink target row below the file header
+    FileMetadata { file_idx: usize, line_idx: usize },
     /// A file-level comment line (part of a multi-line comment box)
     FileComment { file_idx: usize, comment_idx: usize },
     /// Expander line showing hidden context with direction arrow
@@ -214,6 +271,13 @@ pub enum AnnotatedLine {
     ExpandedContext { gap_id: GapId, line_idx: usize },
     /// Hunk header (@@...@@)
     HunkHeader { file_idx: usize, hunk_idx: usize },
+    /// Placeholder for a long run of unchanged lines inside a hunk
+    ElidedContext {
+        file_idx: usize,
+        hunk_idx: usize,
+        start: usize,
+        count: usize,
+    },
     /// Actual diff line with line numbers
     DiffLine {
         file_idx: usize,
@@ -238,6 +302,8 @@ pub enum AnnotatedLine {
         side: LineSide,
         comment_idx: usize,
     },
+    /// A ghosted comment from an earlier review of the same line
+    HistoryComment { file_idx: usize, idx: usize },
     /// A read-only line of a rendered remote review thread. Cursor cannot
     /// edit or reply to these in v1; the annotation is informational so
     /// hit-testing and scroll math stay correct.
@@ -324,11 +390,14 @@ pub fn pr_commit_to_commit_info(commit: &crate::forge::traits::PullRequestCommit
 pub fn annotation_file_idx(annotation: &AnnotatedLine) -> Option<usize> {
     match annotation {
         AnnotatedLine::FileHeader { file_idx }
+        | AnnotatedLine::FileMetadata { file_idx, .. }
         | AnnotatedLine::FileComment { file_idx, .. }
         | AnnotatedLine::HunkHeader { file_idx, .. }
+        | AnnotatedLine::ElidedContext { file_idx, .. }
         | AnnotatedLine::DiffLine { file_idx, .. }
         | AnnotatedLine::SideBySideLine { file_idx, .. }
         | AnnotatedLine::LineComment { file_idx, .. }
+        | AnnotatedLine::HistoryComment { file_idx, .. }
         | AnnotatedLine::BinaryOrEmpty { file_idx } => Some(*file_idx),
         AnnotatedLine::ReviewCommentsHeader
         | AnnotatedLine::ReviewComment { .. }
@@ -419,6 +488,15 @@ pub enum InputMode {
     /// no `SubmitConfirm` follows (resolver still runs if any comment is
     /// unmappable).
     SubmitActionPicker,
+    /// Startup chooser shown over the commit selector when there are both
+    /// uncommitted changes and unpushed commits, asking what to review.
+    ReviewChooser,
+    /// `:verify` panel listing comments whose line is gone from the diff,
+    /// with actions to re-anchor, detach or delete each one.
+    VerifyPanel,
+    /// Shown when the last file is marked reviewed: the review's stats and
+    /// what to do next with it.
+    CompletionDialog,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -486,9 +564,71 @@ impl PullRequestDiffSource {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What a confirm dialog runs when answered with yes.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConfirmAction {
     CopyAndQuit,
+    /// Esc while a comment has unsaved text; opened over the comment editor.
+    DiscardComment,
+    /// `:q` with unsaved changes.
+    QuitWithoutSaving,
+    /// `dd` on a row of the Comments view.
+    DeleteComment(CommentEntry),
+    /// Startup with a saved review in progress; `n` starts a fresh one.
+    ResumeSession,
+    /// `d` on a row of the `:verify` panel, by comment id.
+    DeleteOrphanedComment(String),
+    /// `dd` on a row of the Sessions view, by session file.
+    DeleteSession(PathBuf),
+    /// Startup on a review locked by a tuicr that has exited; `n` opens it
+    /// read-only.
+    TakeOverLock,
+    /// `:checkpoint restore <label>`
+    RestoreCheckpoint(String),
+    /// `:gitlab <mr>` or `:bitbucket <pr>`
+    PostDiscussions(DiscussionTarget),
+}
+
+impl ConfirmAction {
+    /// Default question for the action, used unless the dialog overrides it.
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            ConfirmAction::CopyAndQuit => "Copy review to clipboard?",
+            ConfirmAction::DiscardComment => "Discard unsaved comment?",
+            ConfirmAction::QuitWithoutSaving => "Quit without saving?",
+            ConfirmAction::DeleteComment(_) | ConfirmAction::DeleteOrphanedComment(_) => {
+                "Delete this comment?"
+            }
+            ConfirmAction::ResumeSession => "Resume the saved review?",
+            ConfirmAction::DeleteSession(_) => "Delete this saved review?",
+            ConfirmAction::TakeOverLock => "Take over the review lock?",
+            ConfirmAction::RestoreCheckpoint(_) => "Roll back to the checkpoint?",
+            ConfirmAction::PostDiscussions(_) => "Post the review?",
+        }
+    }
+}
+
+/// A pending yes/no question: the message shown and the action `y` runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmDialog {
+    pub message: String,
+    pub action: ConfirmAction,
+}
+
+impl ConfirmDialog {
+    pub fn new(action: ConfirmAction) -> Self {
+        Self {
+            message: action.prompt().to_string(),
+            action,
+        }
+    }
+
+    pub fn with_message(message: impl Into<String>, action: ConfirmAction) -> Self {
+        Self {
+            message: message.into(),
+            action,
+        }
+    }
 }
 
 /// Push a `MappedComment` onto the appropriate bucket. Free function so the
@@ -506,7 +646,7 @@ fn bucket_mapping(
             file,
             reason,
         } => unmappable.push(UnmappableItem {
-            comment,
+            comment: *comment,
             file,
             reason,
         }),
@@ -548,6 +688,32 @@ pub const SUBMIT_PICKER_EVENTS: &[(&str, crate::forge::submit::SubmitEvent)] = &
     ("Draft", crate::forge::submit::SubmitEvent::Draft),
 ];
 
+/// What the startup chooser offers to review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewChoice {
+    LastUsed,
+    WorkingTree,
+    UnpushedCommits,
+    PickCommits,
+    PickBaseRef,
+}
+
+/// What the completion dialog offers to do with a finished review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionAction {
+    /// Like `:clip`
+    CopyReview,
+    /// Like `:chat`
+    CopySummary,
+    /// Opens the command line at `:export `
+    ExportReport,
+    /// Like bare `:submit`, in PR mode
+    PublishToGitHub,
+    /// Opens the command line at `:git commit -m "` (or `:jj commit`)
+    Commit,
+    SaveAndQuit,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusedPanel {
     FileList,
@@ -555,6 +721,100 @@ pub enum FocusedPanel {
     CommitSelector,
 }
 
+/// Top-level view, switched with `gt`/`gT` or `:view`. Each non-diff view
+/// keeps its own state on `App`, so visiting one never disturbs the diff
+/// cursor or the file list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum View {
+    #[default]
+    Diff,
+    Comments,
+    Overview,
+    Sessions,
+    History,
+    Trash,
+}
+
+impl View {
+    pub const ALL: [View; 6] = [
+        View::Diff,
+        View::Comments,
+        View::Overview,
+        View::Sessions,
+        View::History,
+        View::Trash,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            View::Diff => "Diff",
+            View::Comments => "Comments",
+            View::Overview => "Overview",
+            View::Sessions => "Sessions",
+            View::History => "History",
+            View::Trash => "Trash",
+        }
+    }
+
+    /// Parse a `:view` argument; unambiguous prefixes are accepted.
+    pub fn from_name(name: &str) -> Option<View> {
+        let name = name.to_ascii_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        View::ALL
+            .into_iter()
+            .find(|view| view.label().to_ascii_lowercase().starts_with(&name))
+    }
+
+    fn offset(self, delta: isize) -> View {
+        let len = View::ALL.len() as isize;
+        let idx = View::ALL.iter().position(|v| *v == self).unwrap_or(0) as isize;
+        View::ALL[(idx + delta).rem_euclid(len) as usize]
+    }
+}
+
+/// Cursor and scroll position of a list-style view.
+#[derive(Debug, Default, Clone)]
+pub struct ViewListState {
+    pub cursor: usize,
+    pub offset: usize,
+    pub viewport_height: usize,
+}
+
+/// A comment as listed in the Comments view. `comment_idx` indexes the
+/// session's comment vector for that location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommentEntry {
+    Review {
+        comment_idx: usize,
+    },
+    File {
+        file_idx: usize,
+        comment_idx: usize,
+    },
+    Line {
+        file_idx: usize,
+        line: u32,
+        comment_idx: usize,
+    },
+}
+
+/// State of the Comments view: every local comment in review order.
+#[derive(Debug, Default)]
+pub struct CommentsView {
+    pub list: ViewListState,
+    pub entries: Vec<CommentEntry>,
+}
+
+/// State of the Sessions view: saved sessions for this repository.
+#[derive(Debug, Default)]
+pub struct SessionsView {
+    pub list: ViewListState,
+    pub sessions: Vec<(PathBuf, ReviewSession)>,
+    pub error: Option<String>,
+}
+
 /// Active tab in the review target selector.
 ///
 /// The selector internally still goes through `InputMode::CommitSelect`,
@@ -635,6 +895,23 @@ pub struct PrReloadRequest {
     pub anchor: Option<PrCursorAnchor>,
 }
 
+/// In-flight `:e` reload outside PR mode. Drives the status-bar spinner.
+#[derive(Debug, Clone)]
+pub struct VcsReloadRequest {
+    pub started_at: Instant,
+    /// Cancels the commands the reload runs, and only those
+    pub cancel: crate::process::CancelToken,
+}
+
+/// Result delivered from the reload worker, handing back the VCS backend
+/// it borrowed from `App::vcs`.
+pub enum VcsReloadEvent {
+    Done {
+        vcs: Box<dyn VcsBackend>,
+        result: Result<Vec<DiffFile>>,
+    },
+}
+
 /// Result delivered from the PR-reload background thread.
 #[derive(Debug)]
 pub enum PrReloadEvent {
@@ -700,6 +977,39 @@ pub struct SubmitInFlightState {
     pub started_at: Instant,
 }
 
+/// Where `:gitlab` or `:bitbucket` posts the review, one discussion per
+/// comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscussionTarget {
+    GitLab(crate::forge::gitlab::glab::MergeRequestTarget),
+    Bitbucket(crate::forge::bitbucket::submit::BitbucketPullRequest),
+}
+
+impl DiscussionTarget {
+    pub fn display_name(&self) -> String {
+        match self {
+            DiscussionTarget::GitLab(target) => target.display_name(),
+            DiscussionTarget::Bitbucket(target) => target.display_name(),
+        }
+    }
+
+    fn noun(&self) -> &'static str {
+        match self {
+            DiscussionTarget::GitLab(_) => "discussion(s)",
+            DiscussionTarget::Bitbucket(_) => "comment(s)",
+        }
+    }
+}
+
+/// A `:gitlab` or `:bitbucket` post running on a background thread, kept so
+/// the status bar has a spinner to show until `poll_discussion_post_events`
+/// picks up the outcome.
+#[derive(Debug, Clone)]
+pub struct DiscussionPostState {
+    pub target: DiscussionTarget,
+    pub started_at: Instant,
+}
+
 /// Result delivered from the create-review background thread.
 #[derive(Debug)]
 pub enum PrSubmitEvent {
@@ -761,6 +1071,15 @@ pub struct App {
     pub input_mode: InputMode,
     pub focused_panel: FocusedPanel,
     pub diff_view_mode: DiffViewMode,
+    pub view: View,
+    pub comments_view: CommentsView,
+    /// Overview has no cursor; `cursor` is its scroll position.
+    pub overview_view: ViewListState,
+    pub sessions_view: SessionsView,
+    /// Cursor over the session's event log, listed newest first.
+    pub history_view: ViewListState,
+    /// Cursor over deleted comments, listed newest first.
+    pub trash_view: ViewListState,
 
     pub file_list_state: FileListState,
     pub diff_state: DiffState,
@@ -771,6 +1090,8 @@ pub struct App {
     pub comment_buffer: String,
     pub comment_cursor: usize,
     pub comment_type: CommentType,
+    /// Blocking flag of the comment being written, cycled with Ctrl-B
+    pub comment_blocking: Option<bool>,
     pub comment_types: Vec<CommentTypeDefinition>,
     pub comment_is_review_level: bool,
     pub comment_is_file_level: bool,
@@ -825,6 +1146,11 @@ pub struct App {
     pub pr_reload_state: Option<PrReloadRequest>,
     /// Background-thread channel that delivers the result of a PR reload.
     pub pr_reload_rx: Option<std::sync::mpsc::Receiver<PrReloadEvent>>,
+    /// In-flight `:e` reload of a local diff. While set, `vcs` is a
+    /// placeholder and the real backend is on the worker thread.
+    pub vcs_reload_state: Option<VcsReloadRequest>,
+    /// Background-thread channel that hands the backend and new diff back.
+    pub vcs_reload_rx: Option<std::sync::mpsc::Receiver<VcsReloadEvent>>,
     /// Forge backend instance live while in PR diff mode. Used by the
     /// context provider for gap expansion against base/head SHAs and (in a
     /// future PR) for remote comment fetch/submit.
@@ -847,12 +1173,35 @@ pub struct App {
     /// formatting on submit. Defaults to `ForgeConfig::default()` when the
     /// section is missing.
     pub forge_config: crate::config::ForgeConfig,
+    /// `[bitbucket]` settings for `:bitbucket <pr>`
+    pub bitbucket_config: crate::config::BitbucketConfig,
+    /// `[notify]` settings: which finished operations alert the terminal
+    pub notify_config: crate::config::NotifyConfig,
+    /// Notification waiting to be written after the next draw
+    pub pending_notification: Option<(crate::config::NotifyMethod, String)>,
     /// In-flight `:submit*` state. `None` outside the resolver + confirmation
     /// modal flow; preflight populates it.
     pub submit_state: Option<SubmitState>,
     /// Cursor row inside the bare-`:submit` action picker modal. Only
     /// meaningful while `input_mode == SubmitActionPicker`.
     pub submit_picker_cursor: usize,
+    /// Cursor row inside the startup review chooser
+    pub review_chooser_cursor: usize,
+    /// Open the completion dialog when the last file is marked reviewed
+    /// (config `completion_dialog`)
+    pub completion_dialog: bool,
+    pub completion_cursor: usize,
+    /// Rows of the `:verify` panel
+    pub orphaned_comments: Vec<OrphanedComment>,
+    pub verify_cursor: usize,
+    /// Commits on HEAD not yet on its upstream (oldest first), found at
+    /// startup for the chooser's "unpushed commits" row
+    pub unpushed_commits: Vec<String>,
+    /// Source last reviewed in this repository, the chooser's first row
+    pub last_source: Option<ReviewSource>,
+    /// `last_sources.json` that picked sources are recorded to; `None`
+    /// records nothing
+    pub last_source_path: Option<PathBuf>,
     /// In-flight `gh api .../reviews` call. `Some` while a background submit
     /// is running; cleared by `poll_pr_submit_events` once the result lands.
     /// Drives the status-bar spinner.
@@ -860,6 +1209,14 @@ pub struct App {
     /// Background-thread channel that delivers the create-review result.
     /// `Receiver` is only present while a submit is in flight.
     pub pr_submit_rx: Option<std::sync::mpsc::Receiver<PrSubmitEvent>>,
+    /// In-flight `:gitlab` or `:bitbucket` post; cleared by
+    /// `poll_discussion_post_events`.
+    pub discussion_post_state: Option<DiscussionPostState>,
+    discussion_post_rx: Option<
+        std::sync::mpsc::Receiver<
+            std::result::Result<crate::forge::discussions::PostOutcome, String>,
+        >,
+    >,
     /// Latest known PR head SHA from the remote. PR 5 leaves this as the
     /// open-time head so the stale-head warning never fires; PR 6 may refresh
     /// it via a pre-submit `gh pr view` to power the warning.
@@ -867,10 +1224,37 @@ pub struct App {
 
     pub should_quit: bool,
     pub dirty: bool,
+    /// How long after the last change the session saves itself; `None`
+    /// leaves saving to `:w`
+    pub autosave_delay: Option<Duration>,
+    /// When the pending auto-save is due, pushed back by every change
+    autosave_due: Option<Instant>,
+    /// When the last key, mouse or paste input arrived, for time tracking
+    last_activity: Option<Instant>,
     pub quit_warned: bool,
     pub message: Option<Message>,
-    pub pending_confirm: Option<ConfirmAction>,
+    pub pending_confirm: Option<ConfirmDialog>,
+    /// Modes covered by open overlays (help, confirm, submit modals), bottom
+    /// first. The top overlay is always `input_mode`; closing it restores the
+    /// mode beneath, so popups can nest over each other and over the comment
+    /// editor.
+    pub overlay_stack: Vec<InputMode>,
     pub supports_keyboard_enhancement: bool,
+    /// `--safe`: refuse every action that would modify the repository.
+    pub safe_mode: bool,
+    /// Viewing an archived review (`tuicr history <n>`): comments, review
+    /// marks and saving are all refused.
+    pub read_only: bool,
+    /// Whether commit ranges are diffed as `A..B` or `A...B` (merge-base).
+    pub range_mode: RangeMode,
+    /// jj/hg revset behind the current commit range review (`--revset`/`:rev`).
+    /// When set, the full range is diffed with `VcsBackend::get_revset_diff`
+    /// (`jj diff -r`, or `hg diff -c` for a single hg changeset).
+    pub revset: Option<String>,
+    /// Two versions of a patch series behind the current commit range
+    /// review (`--interdiff`/`:interdiff`). When set, the diff shows only what
+    /// changed between them via `VcsBackend::get_interdiff`.
+    pub interdiff: Option<Interdiff>,
     pub show_file_list: bool,
     pub cursor_line_highlight: bool,
     pub leader_key: char,
@@ -892,6 +1276,20 @@ pub struct App {
     pub expanded_top: HashMap<GapId, Vec<DiffLine>>,
     /// Stores lines expanded upward from the lower boundary of each gap (in ascending line order)
     pub expanded_bottom: HashMap<GapId, Vec<DiffLine>>,
+    /// Unchanged runs longer than this inside a hunk collapse to one row (0 = off)
+    pub elide_context: usize,
+    /// Elided runs the user has opened up again
+    pub expanded_elisions: HashSet<ElisionId>,
+    /// Hunks switched to a word or character diff, by `(file_idx, hunk_idx)`
+    pub word_diff_hunks: HashMap<(usize, usize), WordDiffMode>,
+    /// `[filetypes]` config rules for wrapping and collapsing
+    pub filetypes: FiletypeRules,
+    /// Files collapsed by their filetype rule that the user opened
+    pub opened_files: HashSet<PathBuf>,
+    /// Line comments from earlier sessions, keyed by display path
+    pub history_comments: HashMap<PathBuf, Vec<HistoryComment>>,
+    /// Whether `history_comments` are drawn inline
+    pub show_history_comments: bool,
     /// Cached annotations describing what each rendered line represents
     pub line_annotations: Vec<AnnotatedLine>,
     /// Output to stdout instead of clipboard when exporting
@@ -916,6 +1314,11 @@ pub struct App {
     // Inline commit selector state (shown at top of diff view for multi-commit reviews)
     /// CommitInfo for commits in the current review (display order: newest first)
     pub review_commits: Vec<CommitInfo>,
+    /// Per-author commit and line counts over `review_commits`, for the
+    /// overview. Empty unless more than one commit is under review.
+    pub author_stats: Vec<AuthorStats>,
+    /// Commit ids `author_stats` was computed for.
+    author_stats_ids: Vec<String>,
     /// Forge-side commit list for the active PR (display order: newest first).
     /// Empty outside PR mode. Used as the source of truth for resolving a
     /// `commit_selection_range` back to (start_sha, end_sha) when toggling.
@@ -937,6 +1340,60 @@ pub struct App {
     pub path_filter: Option<String>,
     /// Whether to include the "Comment types:" legend line in export
     pub export_legend: bool,
+    /// Label comments Conventional Comments style (`issue (blocking):`) in
+    /// the UI and Markdown exports (config `conventional_comments`)
+    pub conventional_comments: bool,
+    /// Git notes ref that `:w` mirrors commit-range sessions to (config
+    /// `notes_ref`). `None` keeps sessions in the local reviews dir only.
+    pub notes_ref: Option<String>,
+    /// Findings database that exports record ISSUE comments to (config
+    /// `findings_db`). `None` keeps no record.
+    pub findings_db: Option<PathBuf>,
+    /// Where completed reviews are recorded for `tuicr stats` (config
+    /// `review_metrics`). `None` keeps no record.
+    pub review_metrics: Option<PathBuf>,
+    /// Where completed sessions are copied for `tuicr history`. `None`
+    /// archives nothing.
+    pub archive_dir: Option<PathBuf>,
+    /// Where the session's crash recovery journal is kept (the reviews
+    /// directory). `None` journals nothing.
+    pub journal_dir: Option<PathBuf>,
+    /// Template that exports are rendered through (config
+    /// `export_template`). `None` uses the built-in Markdown layouts.
+    pub export_template: Option<PathBuf>,
+    /// Report format from the session template (`export_format`): a bare
+    /// `:export` writes it and `:export <path>` without an extension uses
+    /// it. `None` leaves `:export` copying to the clipboard.
+    pub export_format: Option<String>,
+    /// Incoming webhook that `:chat post` sends the summary to (config
+    /// `chat_webhook`)
+    pub chat_webhook: Option<String>,
+    /// Who is reviewing (config `reviewer`, else the VCS user), stamped on
+    /// new comments
+    pub reviewer: Option<Reviewer>,
+    /// External renderer the diff is colored through (`--pipe-to`)
+    pub diff_filter: Option<DiffFilter>,
+    /// Findings recorded against each file of this repository
+    pub findings_by_file: HashMap<PathBuf, usize>,
+    /// Conflict marker lines in the current diff, refreshed with the
+    /// annotations
+    pub conflicts: ConflictIndex,
+    /// Only list conflicted files in the file list (`:conflicts`)
+    pub conflicts_only: bool,
+    /// Only list the files one author's commits touched (`:author`)
+    pub author_filter: Option<AuthorFilter>,
+    /// Files rewritten by `:todos`, oldest first, for `:todos undo`
+    todo_edits: Vec<crate::todos::TodoEdit>,
+    /// Output of the last command that reports in a popup, shown in place
+    /// of the help text until it is closed
+    pub command_output: Option<CommandOutput>,
+    /// `:git`/`:jj` command for `main` to run on the suspended terminal
+    pub pending_passthrough: Option<VcsPassthrough>,
+    /// The idle side of a colocated jj+git repository, kept once `:vcs` has
+    /// switched away from it so switching back is instant
+    pub colocated_vcs: Option<Box<dyn VcsBackend>>,
+    /// Git backend that `:vcs git` opens a colocated repository with
+    pub git_backend_preference: GitBackendPreference,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -947,6 +1404,30 @@ pub struct CommentTypeDefinition {
     pub color: Option<Color>,
 }
 
+/// Color of `comment_type`: its configured color, else the theme's color for
+/// the built-in type.
+pub(crate) fn comment_type_color_for(
+    comment_types: &[CommentTypeDefinition],
+    theme: &Theme,
+    comment_type: &CommentType,
+) -> Color {
+    if let Some(definition) = comment_types
+        .iter()
+        .find(|definition| definition.id == comment_type.id())
+        && let Some(color) = definition.color
+    {
+        return color;
+    }
+
+    match comment_type.id() {
+        "note" => theme.comment_note,
+        "suggestion" => theme.comment_suggestion,
+        "issue" => theme.comment_issue,
+        "praise" => theme.comment_praise,
+        _ => theme.fg_secondary,
+    }
+}
+
 #[derive(Default)]
 pub struct FileListState {
     pub list_state: ratatui::widgets::ListState,
@@ -1031,6 +1512,10 @@ pub struct HelpState {
     pub scroll_offset: usize,
     pub viewport_height: usize,
     pub total_lines: usize, // Set during render
+    /// What help was opened from; `None` where there is no context help
+    pub context: Option<HelpContext>,
+    /// Show the full reference instead of the context's keys
+    pub show_all: bool,
 }
 
 /// Represents a comment location for deletion
@@ -1050,15 +1535,286 @@ enum CommentLocation {
     },
 }
 
+/// A comment `:verify` found without an anchor in the current diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanedComment {
+    pub comment_id: String,
+    pub path: PathBuf,
+    /// `None` for file comments
+    pub line: Option<u32>,
+    pub side: LineSide,
+    /// Whether the file is still in the diff. Comments of files that left
+    /// it can only be detached to the review or deleted.
+    pub file_in_diff: bool,
+    /// Still on its line, but the line or its neighbours changed; `r` keeps
+    /// it there.
+    pub drifted: bool,
+    pub content: String,
+}
+
+impl OrphanedComment {
+    /// `path:line`, as shown in the panel
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) if self.side == LineSide::Old => {
+                format!("{}:{line} (removed line)", self.path.display())
+            }
+            Some(line) => format!("{}:{line}", self.path.display()),
+            None => self.path.display().to_string(),
+        }
+    }
+}
+
+/// The line of `file` on `side` a comment left on `line` should move to:
+/// the nearest one with the comment's original text if it recorded any,
+/// otherwise the nearest one.
+fn reanchor_line(
+    file: &DiffFile,
+    line: u32,
+    side: LineSide,
+    context: Option<&crate::model::comment::LineContext>,
+) -> Option<u32> {
+    let candidates: Vec<(u32, &str)> = file
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .filter_map(|l| {
+            let lineno = match side {
+                LineSide::New => l.new_lineno,
+                LineSide::Old => l.old_lineno,
+            };
+            lineno.map(|n| (n, l.content.trim()))
+        })
+        .collect();
+    let nearest = |matching: &dyn Fn(&str) -> bool| {
+        candidates
+            .iter()
+            .filter(|(_, content)| matching(content))
+            .min_by_key(|(n, _)| n.abs_diff(line))
+            .map(|(n, _)| *n)
+    };
+    context
+        .map(|ctx| ctx.content.trim())
+        .filter(|text| !text.is_empty())
+        .and_then(|text| nearest(&|content| content == text))
+        .or_else(|| nearest(&|_| true))
+}
+
+/// Old and new version of a patch series, each a range like `main..v1` or a
+/// single commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interdiff {
+    pub old: String,
+    pub new: String,
+}
+
+/// The files changed by the reviewed commits of one author, from each
+/// commit's own diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorFilter {
+    /// What was passed to `:author`
+    pub author: String,
+    pub files: HashSet<PathBuf>,
+}
+
+/// What a command such as `:session merge` printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// The command line as typed, e.g. `session merge bob.json`
+    pub command: String,
+    /// Exit code when the command failed
+    pub failed: Option<i32>,
+    pub output: String,
+}
+
+/// A `:git`/`:jj` command waiting for `main` to hand it the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VcsPassthrough {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl VcsPassthrough {
+    /// The command line as typed, e.g. `jj squash`
+    pub fn command(&self) -> String {
+        format!("{} {}", self.program, self.args.join(" "))
+    }
+}
+
+/// Everything needed to reload the diff, detached from `App` so the load can
+/// run on a worker thread (see `App::spawn_vcs_reload`).
+struct DiffLoadSpec {
+    diff_source: DiffSource,
+    interdiff: Option<Interdiff>,
+    revset: Option<String>,
+    range_mode: RangeMode,
+    repo_root: PathBuf,
+    path_filter: Option<String>,
+}
+
+impl DiffLoadSpec {
+    fn load(&self, vcs: &dyn VcsBackend, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+        Ok(match &self.diff_source {
+            DiffSource::CommitRange(commit_ids) => match (&self.interdiff, &self.revset) {
+                (Some(interdiff), _) => App::get_interdiff_with_ignore(
+                    vcs,
+                    &self.repo_root,
+                    &interdiff.old,
+                    &interdiff.new,
+                    highlighter,
+                    self.path_filter.as_deref(),
+                )?,
+                (None, Some(revset)) => App::get_revset_diff_with_ignore(
+                    vcs,
+                    &self.repo_root,
+                    revset,
+                    highlighter,
+                    self.path_filter.as_deref(),
+                )?,
+                (None, None) => App::get_commit_range_diff_with_ignore(
+                    vcs,
+                    &self.repo_root,
+                    commit_ids,
+                    self.range_mode,
+                    highlighter,
+                    self.path_filter.as_deref(),
+                )?,
+            },
+            DiffSource::StagedUnstagedAndCommits(commit_ids) => {
+                let ids = commit_ids.clone();
+                App::get_working_tree_with_commits_diff_with_ignore(
+                    vcs,
+                    &self.repo_root,
+                    &ids,
+                    highlighter,
+                    self.path_filter.as_deref(),
+                )?
+            }
+            DiffSource::Staged => App::get_staged_diff_with_ignore(
+                vcs,
+                &self.repo_root,
+                highlighter,
+                self.path_filter.as_deref(),
+            )?,
+            DiffSource::Unstaged => App::get_unstaged_diff_with_ignore(
+                vcs,
+                &self.repo_root,
+                highlighter,
+                self.path_filter.as_deref(),
+            )?,
+            DiffSource::StagedAndUnstaged | DiffSource::WorkingTree => {
+                App::get_working_tree_diff_with_ignore(
+                    vcs,
+                    &self.repo_root,
+                    highlighter,
+                    self.path_filter.as_deref(),
+                )?
+            }
+            DiffSource::PullRequest(_) => {
+                // PR reload is a separate code path that may switch sessions
+                // when the head SHA advances; callers dispatch via
+                // `reload_pull_request` instead of going through this
+                // local-reload helper.
+                return Err(TuicrError::UnsupportedOperation(
+                    "Use :reload from the command line in PR mode".to_string(),
+                ));
+            }
+        })
+    }
+}
+
 pub struct AppStartupOptions<'a> {
     pub revisions: Option<&'a str>,
+    /// jj/hg revset reviewed as one combined diff. Reviewed like
+    /// `revisions`, but the diff comes from the revset instead of a range.
+    pub revset: Option<&'a str>,
+    /// Old version of the patch series to interdiff `revisions` against.
+    pub interdiff: Option<&'a str>,
     pub working_tree: bool,
     pub path_filter: Option<&'a str>,
     pub file_path: Option<&'a str>,
+    /// Unified diff to review instead of a repository (`-` for stdin).
+    pub patch_path: Option<&'a str>,
     pub git_backend_preference: GitBackendPreference,
+    /// Rename/copy detection applied to the initial and later diffs.
+    pub rename_detection: RenameDetection,
     /// Direct PR target (`tuicr pr <target>`). Mutually exclusive with the
     /// other selectors above; the binary validates that before reaching here.
     pub pr_target: Option<&'a str>,
+    /// Receives the startup stage as `App::new` moves through discovery and
+    /// diffing, so the binary can show progress while it waits.
+    pub progress: Option<std::sync::mpsc::Sender<StartupStage>>,
+}
+
+impl AppStartupOptions<'_> {
+    fn report(&self, stage: StartupStage) {
+        if let Some(tx) = &self.progress {
+            let _ = tx.send(stage); // Ignore send error if receiver dropped
+        }
+    }
+}
+
+/// Status line text for the current rename detection settings.
+fn describe_rename_detection(detection: &RenameDetection) -> String {
+    if !detection.enabled {
+        return "Rename detection: off".to_string();
+    }
+    let mut status = format!("Rename detection: on ({}%", detection.threshold);
+    if detection.copies {
+        status.push_str(", copies");
+    }
+    if detection.break_rewrites {
+        status.push_str(", break rewrites");
+    }
+    status.push(')');
+    status
+}
+
+/// Status line text for the current whitespace settings.
+fn describe_ignore_whitespace(whitespace: &IgnoreWhitespace) -> String {
+    if !whitespace.any() {
+        return "Whitespace: shown".to_string();
+    }
+    let ignored: Vec<&str> = [
+        (whitespace.all_space, "all space"),
+        (whitespace.space_change, "space changes"),
+        (whitespace.blank_lines, "blank lines"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, label)| enabled.then_some(label))
+    .collect();
+    format!("Whitespace: ignoring {}", ignored.join(", "))
+}
+
+/// Status line text for the unchanged-line elision threshold.
+fn describe_elide_context(threshold: usize) -> String {
+    if threshold == 0 {
+        "Unchanged lines: shown".to_string()
+    } else {
+        format!("Unchanged lines: eliding runs over {threshold}")
+    }
+}
+
+/// Coarse phases of `App::new`, shown on the loading screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupStage {
+    DiscoveringRepository,
+    ResolvingRevisions,
+    LoadingChanges,
+    LoadingCommits,
+    FetchingPullRequest,
+}
+
+impl StartupStage {
+    pub fn label(self) -> &'static str {
+        match self {
+            StartupStage::DiscoveringRepository => "Discovering repository",
+            StartupStage::ResolvingRevisions => "Resolving revisions",
+            StartupStage::LoadingChanges => "Loading changes",
+            StartupStage::LoadingCommits => "Loading commits",
+            StartupStage::FetchingPullRequest => "Fetching pull request",
+        }
+    }
 }
 
 impl App {
@@ -1072,11 +1828,13 @@ impl App {
         // selector. Errors here surface before TUI startup like other
         // startup failures.
         if let Some(target) = options.pr_target {
+            options.report(StartupStage::FetchingPullRequest);
             return Self::new_from_pr_target(theme, comment_type_configs, output_to_stdout, target);
         }
 
         // --file mode: open a single file for annotation without VCS
         if let Some(file_path) = options.file_path {
+            options.report(StartupStage::LoadingChanges);
             let vcs = Box::new(FileBackend::new(file_path)?);
             let vcs_info = vcs.info().clone();
             let highlighter = theme.syntax_highlighter();
@@ -1104,19 +1862,54 @@ impl App {
             return Ok(app);
         }
 
-        let vcs = crate::profile::time("startup.detect_vcs", || {
+        // --patch mode: review a unified diff without a checkout
+        if let Some(patch_path) = options.patch_path {
+            options.report(StartupStage::LoadingChanges);
+            let vcs = Box::new(PatchBackend::new(patch_path)?);
+            let vcs_info = vcs.info().clone();
+            let highlighter = theme.syntax_highlighter();
+            // No .tuicrignore filtering: the patch need not belong to the
+            // directory tuicr was started in.
+            let diff_files = vcs.get_working_tree_diff(highlighter)?;
+            let diff_files = match options.path_filter {
+                Some(path) => {
+                    Self::require_non_empty_diff_files(Self::filter_by_path(diff_files, path))?
+                }
+                None => diff_files,
+            };
+            let session = Self::load_or_create_session(&vcs_info, SessionDiffSource::WorkingTree);
+
+            return Self::build(
+                vcs,
+                vcs_info,
+                theme,
+                comment_type_configs,
+                output_to_stdout,
+                diff_files,
+                session,
+                DiffSource::WorkingTree,
+                InputMode::Normal,
+                Vec::new(),
+                options.path_filter,
+            );
+        }
+
+        options.report(StartupStage::DiscoveringRepository);
+        let mut vcs = crate::profile::time("startup.detect_vcs", || {
             detect_vcs(options.git_backend_preference)
         })?;
+        vcs.set_rename_detection(options.rename_detection);
         let vcs_info = vcs.info().clone();
         let highlighter =
             crate::profile::time("startup.syntax_highlighter", || theme.syntax_highlighter());
         // Determine the diff source, files, and session based on input.
         // Four paths:
         //   1. -r + -w: combined commit range and uncommitted changes
-        //   2. -r only: commit range
+        //   2. -r or --revset only: commit range
         //   3. -w only: working tree directly (skip commit selector)
         //   4. neither: commit selection UI
-        if let Some(revisions) = options.revisions {
+        if let Some(revisions) = options.revisions.or(options.revset) {
+            options.report(StartupStage::ResolvingRevisions);
             let commit_ids = crate::profile::time_with(
                 "startup.resolve_revisions",
                 || vcs.resolve_revisions(revisions),
@@ -1126,6 +1919,7 @@ impl App {
                 },
             )?;
 
+            options.report(StartupStage::LoadingChanges);
             if options.working_tree {
                 // Combined: commit range + staged/unstaged changes
                 let diff_files = Self::get_working_tree_with_commits_diff_with_ignore(
@@ -1200,13 +1994,31 @@ impl App {
             }
 
             // Resolve the revisions to commits and diff as a commit range
-            let diff_files = Self::get_commit_range_diff_with_ignore(
-                vcs.as_ref(),
-                &vcs_info.root_path,
-                &commit_ids,
-                highlighter,
-                options.path_filter,
-            )?;
+            let diff_files = match (options.interdiff, options.revset) {
+                (Some(old), _) => Self::get_interdiff_with_ignore(
+                    vcs.as_ref(),
+                    &vcs_info.root_path,
+                    old,
+                    revisions,
+                    highlighter,
+                    options.path_filter,
+                )?,
+                (None, Some(revset)) => Self::get_revset_diff_with_ignore(
+                    vcs.as_ref(),
+                    &vcs_info.root_path,
+                    revset,
+                    highlighter,
+                    options.path_filter,
+                )?,
+                (None, None) => Self::get_commit_range_diff_with_ignore(
+                    vcs.as_ref(),
+                    &vcs_info.root_path,
+                    &commit_ids,
+                    RangeMode::default(),
+                    highlighter,
+                    options.path_filter,
+                )?,
+            };
             let session = Self::load_or_create_commit_range_session(&vcs_info, &commit_ids);
             // Get commit info for the inline commit selector
             let review_commits = crate::profile::time_with(
@@ -1231,8 +2043,15 @@ impl App {
                 options.path_filter,
             )?;
 
-            // Set up inline commit selector for multi-commit reviews
-            if review_commits.len() > 1 {
+            app.revset = options.revset.map(str::to_string);
+            app.interdiff = options.interdiff.map(|old| Interdiff {
+                old: old.to_string(),
+                new: revisions.to_string(),
+            });
+
+            // Set up inline commit selector for multi-commit reviews. An
+            // interdiff isn't a diff of the selected commits, so it has none.
+            if review_commits.len() > 1 && app.interdiff.is_none() {
                 app.range_diff_files = Some(app.diff_files.clone());
                 app.commit_list = review_commits.clone();
                 app.commit_list_cursor = 0;
@@ -1252,6 +2071,7 @@ impl App {
             Ok(app)
         } else if options.working_tree {
             // Skip commit selector, go straight to working tree diff
+            options.report(StartupStage::LoadingChanges);
             let diff_files = Self::get_working_tree_diff_with_ignore(
                 vcs.as_ref(),
                 &vcs_info.root_path,
@@ -1277,6 +2097,7 @@ impl App {
 
             Ok(app)
         } else {
+            options.report(StartupStage::LoadingChanges);
             let (change_status, used_backend_status_probe) = Self::get_change_status_with_ignore(
                 vcs.as_ref(),
                 &vcs_info.root_path,
@@ -1302,6 +2123,7 @@ impl App {
                     None
                 };
 
+            options.report(StartupStage::LoadingCommits);
             let commits = crate::profile::time_with(
                 "startup.recent_commits",
                 || vcs.get_recent_commits(0, VISIBLE_COMMIT_COUNT),
@@ -1357,6 +2179,10 @@ impl App {
 
             app.has_more_commit = commits.len() >= VISIBLE_COMMIT_COUNT;
             app.visible_commit_count = app.commit_list.len();
+            if (has_staged_changes || has_unstaged_changes) && app.vcs_info.vcs_type == VcsType::Git
+            {
+                app.offer_review_chooser();
+            }
             Ok(app)
         }
     }
@@ -1404,6 +2230,12 @@ impl App {
             diff_source,
             input_mode,
             focused_panel: FocusedPanel::Diff,
+            view: View::Diff,
+            comments_view: CommentsView::default(),
+            overview_view: ViewListState::default(),
+            sessions_view: SessionsView::default(),
+            history_view: ViewListState::default(),
+            trash_view: ViewListState::default(),
             diff_view_mode: DiffViewMode::Unified,
             file_list_state: FileListState::default(),
             diff_state: DiffState::default(),
@@ -1414,6 +2246,7 @@ impl App {
             comment_buffer: String::new(),
             comment_cursor: 0,
             comment_type: default_comment_type,
+            comment_blocking: None,
             comment_types,
             comment_is_review_level: false,
             comment_is_file_level: true,
@@ -1446,17 +2279,39 @@ impl App {
             forge_review_threads_loading: false,
             pr_threads_rx: None,
             forge_config: crate::config::ForgeConfig::default(),
+            bitbucket_config: crate::config::BitbucketConfig::default(),
+            notify_config: crate::config::NotifyConfig::default(),
+            pending_notification: None,
             submit_state: None,
             submit_picker_cursor: 0,
+            review_chooser_cursor: 0,
+            completion_dialog: true,
+            completion_cursor: 0,
+            orphaned_comments: Vec::new(),
+            verify_cursor: 0,
+            unpushed_commits: Vec::new(),
+            last_source: None,
+            last_source_path: None,
             pr_submit_state: None,
             pr_submit_rx: None,
+            discussion_post_state: None,
+            discussion_post_rx: None,
             current_pr_head: None,
             should_quit: false,
             dirty: false,
+            autosave_delay: None,
+            autosave_due: None,
+            last_activity: None,
             quit_warned: false,
             message: None,
             pending_confirm: None,
+            overlay_stack: Vec::new(),
             supports_keyboard_enhancement: false,
+            safe_mode: false,
+            read_only: false,
+            range_mode: RangeMode::default(),
+            revset: None,
+            interdiff: None,
             show_file_list: true,
             cursor_line_highlight: true,
             leader_key: crate::config::DEFAULT_LEADER_KEY,
@@ -1470,6 +2325,13 @@ impl App {
             expanded_dirs: HashSet::new(),
             expanded_top: HashMap::new(),
             expanded_bottom: HashMap::new(),
+            elide_context: DEFAULT_ELIDE_CONTEXT,
+            expanded_elisions: HashSet::new(),
+            word_diff_hunks: HashMap::new(),
+            filetypes: FiletypeRules::default(),
+            opened_files: HashSet::new(),
+            history_comments: HashMap::new(),
+            show_history_comments: true,
             line_annotations: Vec::new(),
             output_to_stdout,
             pending_stdout_output: None,
@@ -1478,15 +2340,39 @@ impl App {
             update_info: None,
             pending_count: None,
             review_commits: Vec::new(),
+            author_stats: Vec::new(),
+            author_stats_ids: Vec::new(),
             pr_commits: Vec::new(),
             pr_range_reload_state: None,
             pr_range_reload_rx: None,
+            vcs_reload_state: None,
+            vcs_reload_rx: None,
             show_commit_selector: false,
             commit_diff_cache: HashMap::new(),
             range_diff_files: None,
             saved_inline_selection: None,
             path_filter: path_filter.map(|s| s.to_string()),
             export_legend: true,
+            conventional_comments: false,
+            notes_ref: None,
+            findings_db: None,
+            review_metrics: None,
+            archive_dir: None,
+            journal_dir: None,
+            export_template: None,
+            export_format: None,
+            chat_webhook: None,
+            reviewer: None,
+            diff_filter: None,
+            findings_by_file: HashMap::new(),
+            conflicts: ConflictIndex::default(),
+            conflicts_only: false,
+            author_filter: None,
+            todo_edits: Vec::new(),
+            command_output: None,
+            pending_passthrough: None,
+            colocated_vcs: None,
+            git_backend_preference: GitBackendPreference::Libgit2,
         };
         // Auto-hide file list when path filter matches exactly one file
         if app.path_filter.is_some() && app.diff_files.len() == 1 {
@@ -1495,6 +2381,7 @@ impl App {
         }
         app.sort_files_by_directory(true);
         app.expand_all_dirs();
+        app.reanchor_comments();
         app.rebuild_annotations();
         app.detect_forge_repository();
         Ok(app)
@@ -1625,23 +2512,19 @@ impl App {
         comment_type.as_str()
     }
 
+    /// Label a comment is shown with: its type label plus any blocking
+    /// decoration, in Conventional Comments form when that is turned on.
+    pub fn comment_label(&self, comment_type: &CommentType, blocking: Option<bool>) -> String {
+        let label = self.comment_type_label(comment_type);
+        if self.conventional_comments {
+            conventional_label(&label, blocking)
+        } else {
+            decorated_label(&label, blocking)
+        }
+    }
+
     pub fn comment_type_color(&self, comment_type: &CommentType) -> Color {
-        if let Some(definition) = self
-            .comment_types
-            .iter()
-            .find(|definition| definition.id == comment_type.id())
-            && let Some(color) = definition.color
-        {
-            return color;
-        }
-
-        match comment_type.id() {
-            "note" => self.theme.comment_note,
-            "suggestion" => self.theme.comment_suggestion,
-            "issue" => self.theme.comment_issue,
-            "praise" => self.theme.comment_praise,
-            _ => self.theme.fg_secondary,
-        }
+        comment_type_color_for(&self.comment_types, &self.theme, comment_type)
     }
 
     /// Load or create a session for a commit range (used by revisions and commit selection).
@@ -2286,6 +3169,7 @@ impl App {
                 self.set_error(format!("Range diff failed: {e}"));
             }
         }
+        self.notify_finished(Operation::DiffLoad, request.started_at);
     }
 
     fn finish_pr_range_reload(
@@ -2409,6 +3293,7 @@ impl App {
                 self.set_error(format!("Reload failed: {e}"));
             }
         }
+        self.notify_finished(Operation::DiffLoad, request.started_at);
     }
 
     fn finish_pr_reload(
@@ -2626,6 +3511,8 @@ impl App {
             is_too_large: false,
             is_commit_message: true,
             content_hash,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         };
         self.diff_files.insert(0, commit_msg_file);
         self.session.add_file(
@@ -2762,12 +3649,56 @@ impl App {
         vcs: &dyn VcsBackend,
         repo_root: &Path,
         commit_ids: &[String],
+        range_mode: RangeMode,
         highlighter: &SyntaxHighlighter,
         path_filter: Option<&str>,
     ) -> Result<Vec<DiffFile>> {
         let diff_files = crate::profile::time_with(
             "diff.load_commit_range",
-            || vcs.get_commit_range_diff(commit_ids, highlighter),
+            || vcs.get_commit_range_diff(commit_ids, range_mode, highlighter),
+            profile_diff_result,
+        )?;
+        let diff_files = Self::filter_ignored_diff_files(repo_root, diff_files);
+        let diff_files = if let Some(path) = path_filter {
+            Self::filter_by_path(diff_files, path)
+        } else {
+            diff_files
+        };
+        Self::require_non_empty_diff_files(diff_files)
+    }
+
+    fn get_revset_diff_with_ignore(
+        vcs: &dyn VcsBackend,
+        repo_root: &Path,
+        revset: &str,
+        highlighter: &SyntaxHighlighter,
+        path_filter: Option<&str>,
+    ) -> Result<Vec<DiffFile>> {
+        let diff_files = crate::profile::time_with(
+            "diff.load_revset",
+            || vcs.get_revset_diff(revset, highlighter),
+            profile_diff_result,
+        )?;
+        let diff_files = Self::filter_ignored_diff_files(repo_root, diff_files);
+        let diff_files = if let Some(path) = path_filter {
+            Self::filter_by_path(diff_files, path)
+        } else {
+            diff_files
+        };
+        Self::require_non_empty_diff_files(diff_files)
+    }
+
+    fn get_interdiff_with_ignore(
+        vcs: &dyn VcsBackend,
+        repo_root: &Path,
+        old: &str,
+        new: &str,
+        highlighter: &SyntaxHighlighter,
+        path_filter: Option<&str>,
+    ) -> Result<Vec<DiffFile>> {
+        let diff_files = crate::profile::time_with(
+            "diff.load_interdiff",
+            || vcs.get_interdiff(old, new, highlighter),
             profile_diff_result,
         )?;
         let diff_files = Self::filter_ignored_diff_files(repo_root, diff_files);
@@ -2882,12 +3813,14 @@ impl App {
         self.clear_expanded_gaps();
         self.sort_files_by_directory(true);
         self.expand_all_dirs();
+        self.reanchor_comments();
         self.rebuild_annotations();
 
         Ok(())
     }
 
-    fn load_staged_selection(&mut self) -> Result<()> {
+    /// Review only the staged changes, as the pre-commit hook does.
+    pub fn load_staged_selection(&mut self) -> Result<()> {
         let highlighter = self.theme.syntax_highlighter();
         let diff_files = match Self::get_staged_diff_with_ignore(
             self.vcs.as_ref(),
@@ -2917,6 +3850,7 @@ impl App {
         self.clear_expanded_gaps();
         self.sort_files_by_directory(true);
         self.expand_all_dirs();
+        self.reanchor_comments();
         self.rebuild_annotations();
 
         Ok(())
@@ -2952,6 +3886,7 @@ impl App {
         self.clear_expanded_gaps();
         self.sort_files_by_directory(true);
         self.expand_all_dirs();
+        self.reanchor_comments();
         self.rebuild_annotations();
 
         Ok(())
@@ -2960,6 +3895,145 @@ impl App {
     /// Reloads diff files from disk. Returns `(file_count, invalidated_count)` where
     /// `invalidated_count` is the number of previously reviewed files whose content changed.
     pub fn reload_diff_files(&mut self) -> Result<(usize, usize)> {
+        self.vcs_idle()?;
+        let diff_files = self
+            .diff_load_spec()?
+            .load(self.vcs.as_ref(), self.theme.syntax_highlighter())?;
+        Ok(self.apply_reloaded_diff_files(diff_files))
+    }
+
+    /// What `reload_diff_files` loads, detached from `self` so a worker
+    /// thread can run it. PR mode reloads through `spawn_pr_reload` instead.
+    fn diff_load_spec(&self) -> Result<DiffLoadSpec> {
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            // PR reload is a separate code path that may switch sessions
+            // when the head SHA advances; callers dispatch via
+            // `reload_pull_request` instead of going through this
+            // local-reload helper.
+            return Err(TuicrError::UnsupportedOperation(
+                "Use :reload from the command line in PR mode".to_string(),
+            ));
+        }
+        Ok(DiffLoadSpec {
+            diff_source: self.diff_source.clone(),
+            interdiff: self.interdiff.clone(),
+            revset: self.revset.clone(),
+            range_mode: self.range_mode,
+            repo_root: self.vcs_info.root_path.clone(),
+            path_filter: self.path_filter.clone(),
+        })
+    }
+
+    /// `:e` outside PR mode: reload on a worker thread that borrows the VCS
+    /// backend, so a slow or hung command leaves the UI responsive. Actions
+    /// that need the backend wait for it (see `vcs_idle`). The status bar
+    /// shows progress and `Esc` cancels; the result is applied in
+    /// `poll_vcs_reload_events`.
+    pub fn spawn_vcs_reload(&mut self) -> Result<()> {
+        if self.vcs_reload_state.is_some() {
+            return Ok(()); // already in flight; the existing spinner is enough
+        }
+        let spec = self.diff_load_spec()?;
+        let highlighter = self.theme.shared_syntax_highlighter();
+        let placeholder: Box<dyn VcsBackend> = Box::new(PrNoopVcs::new(self.vcs_info.clone()));
+        let vcs = std::mem::replace(&mut self.vcs, placeholder);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.vcs_reload_rx = Some(rx);
+        let cancel = crate::process::CancelToken::new();
+        self.vcs_reload_state = Some(VcsReloadRequest {
+            started_at: Instant::now(),
+            cancel: cancel.clone(),
+        });
+        std::thread::spawn(move || {
+            // A panic mid-load must still hand the backend back.
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                crate::process::with_cancel_token(cancel, || spec.load(vcs.as_ref(), &highlighter))
+            }))
+            .unwrap_or_else(|_| {
+                Err(TuicrError::VcsCommand(
+                    "the reload worker panicked".to_string(),
+                ))
+            });
+            let _ = tx.send(VcsReloadEvent::Done { vcs, result });
+        });
+        Ok(())
+    }
+
+    /// Take back the VCS backend from a finished reload and apply its diff.
+    pub fn poll_vcs_reload_events(&mut self) {
+        let Some(rx) = self.vcs_reload_rx.as_ref() else {
+            return;
+        };
+        let event = match rx.try_recv() {
+            Ok(e) => e,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                // Unreachable short of an abort: the worker catches panics
+                // to hand the backend back.
+                self.vcs_reload_rx = None;
+                self.vcs_reload_state = None;
+                self.set_error("Reload failed: worker exited; restart tuicr");
+                return;
+            }
+        };
+        self.vcs_reload_rx = None;
+        let started_at = self.vcs_reload_state.take().map(|s| s.started_at);
+        let VcsReloadEvent::Done { vcs, result } = event;
+        self.vcs = vcs;
+        match result {
+            Ok(diff_files) => {
+                let (count, invalidated) = self.apply_reloaded_diff_files(diff_files);
+                if invalidated > 0 {
+                    self.set_message(format!(
+                        "Reloaded {count} files, {invalidated} changed since last review"
+                    ));
+                } else {
+                    self.set_message(format!("Reloaded {count} files"));
+                }
+            }
+            Err(e) => self.set_error(format!("Reload failed: {e}")),
+        }
+        if let Some(started_at) = started_at {
+            self.notify_finished(Operation::DiffLoad, started_at);
+        }
+    }
+
+    /// Queue a terminal notification for an operation that started at
+    /// `started_at` and just finished, if `[notify]` asks for one and it
+    /// took at least `min_seconds`. The status message names the outcome.
+    pub fn notify_finished(&mut self, operation: Operation, started_at: Instant) {
+        let config = &self.notify_config;
+        let method = match operation {
+            Operation::DiffLoad => config.diff_load,
+            Operation::Export => config.export,
+            Operation::Push => config.push,
+        };
+        if method == crate::config::NotifyMethod::Off
+            || started_at.elapsed() < Duration::from_secs(config.min_seconds)
+        {
+            return;
+        }
+        let outcome = self
+            .message
+            .as_ref()
+            .map_or("done", |message| message.content.as_str());
+        self.pending_notification = Some((method, format!("tuicr: {outcome}")));
+    }
+
+    /// `Esc` while a reload is in flight: kill the command it is waiting on.
+    /// Returns false when there is nothing to cancel.
+    pub fn cancel_vcs_reload(&mut self) -> bool {
+        let Some(reload) = &self.vcs_reload_state else {
+            return false;
+        };
+        reload.cancel.cancel();
+        true
+    }
+
+    /// Swap in freshly loaded diff files, keeping the cursor on the same file
+    /// and relative position. Returns `(file_count, invalidated_count)`.
+    fn apply_reloaded_diff_files(&mut self, diff_files: Vec<DiffFile>) -> (usize, usize) {
         let current_path = self.current_file_path().cloned();
         let prev_file_idx = self.diff_state.current_file_idx;
         let prev_cursor_line = self.diff_state.cursor_line;
@@ -2974,56 +4048,6 @@ impl App {
             prev_cursor_line.saturating_sub(start)
         };
 
-        let highlighter = self.theme.syntax_highlighter();
-        let diff_files = match &self.diff_source {
-            DiffSource::CommitRange(commit_ids) => Self::get_commit_range_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                commit_ids,
-                highlighter,
-                self.path_filter.as_deref(),
-            )?,
-            DiffSource::StagedUnstagedAndCommits(commit_ids) => {
-                let ids = commit_ids.clone();
-                Self::get_working_tree_with_commits_diff_with_ignore(
-                    self.vcs.as_ref(),
-                    &self.vcs_info.root_path,
-                    &ids,
-                    highlighter,
-                    self.path_filter.as_deref(),
-                )?
-            }
-            DiffSource::Staged => Self::get_staged_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                highlighter,
-                self.path_filter.as_deref(),
-            )?,
-            DiffSource::Unstaged => Self::get_unstaged_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                highlighter,
-                self.path_filter.as_deref(),
-            )?,
-            DiffSource::StagedAndUnstaged | DiffSource::WorkingTree => {
-                Self::get_working_tree_diff_with_ignore(
-                    self.vcs.as_ref(),
-                    &self.vcs_info.root_path,
-                    highlighter,
-                    self.path_filter.as_deref(),
-                )?
-            }
-            DiffSource::PullRequest(_) => {
-                // PR reload is a separate code path that may switch sessions
-                // when the head SHA advances; callers dispatch via
-                // `reload_pull_request` instead of going through this
-                // local-reload helper.
-                return Err(TuicrError::UnsupportedOperation(
-                    "Use :reload from the command line in PR mode".to_string(),
-                ));
-            }
-        };
-
         let mut invalidated = 0;
         for file in &diff_files {
             let path = file.display_path().clone();
@@ -3079,8 +4103,9 @@ impl App {
             self.update_current_file_from_cursor();
         }
 
+        self.reanchor_comments();
         self.rebuild_annotations();
-        Ok((self.diff_files.len(), invalidated))
+        (self.diff_files.len(), invalidated)
     }
 
     pub fn can_stage(&self) -> bool {
@@ -3090,7 +4115,54 @@ impl App {
         )
     }
 
+    /// Gate for actions that change the open review. Returns false (and
+    /// says so) when it is read-only.
+    pub fn ensure_session_writable(&mut self) -> bool {
+        if self.read_only {
+            self.set_warning("Read-only review: changes are disabled");
+            return false;
+        }
+        true
+    }
+
+    /// Gate for actions that use the VCS backend, which an `:e` reload
+    /// lends to its worker until `poll_vcs_reload_events` takes it back.
+    fn vcs_idle(&self) -> Result<()> {
+        if self.vcs_reload_state.is_some() {
+            return Err(TuicrError::UnsupportedOperation(
+                "wait for the reload to finish (Esc cancels it)".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// [`Self::vcs_idle`] for actions that report through the status bar.
+    /// Returns false (and tells the user why) while a reload runs.
+    fn ensure_vcs_idle(&mut self) -> bool {
+        if self.vcs_reload_state.is_some() {
+            self.set_warning("Reload in progress: wait for it to finish or press Esc to cancel");
+            return false;
+        }
+        true
+    }
+
+    /// Gate for actions that write to the repository. Returns false (and
+    /// tells the user why) when `--safe` is active.
+    fn ensure_repo_writable(&mut self, action: &str) -> bool {
+        if self.safe_mode {
+            self.set_warning(format!("{action} disabled in safe mode (--safe)"));
+            return false;
+        }
+        true
+    }
+
     pub fn stage_reviewed_files(&mut self) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        if !self.ensure_repo_writable("Staging") {
+            return;
+        }
         if !self.can_stage() {
             self.set_error("Staging only available when viewing unstaged diffs");
             return;
@@ -3124,6 +4196,78 @@ impl App {
         }
     }
 
+    /// Whether only `path`'s header is shown: it is reviewed, or its
+    /// filetype collapses by default and it hasn't been opened.
+    pub fn is_file_folded(&self, path: &Path) -> bool {
+        self.is_file_reviewed(path) || self.is_collapsed_by_filetype(path)
+    }
+
+    /// The commit shown on its own when the inline commit selector narrows
+    /// a range down to one. Files marked reviewed then go to that commit's
+    /// state in `ReviewSession::commits` rather than the range's.
+    pub fn viewed_commit(&self) -> Option<&str> {
+        if self.review_commits.len() < 2 || matches!(self.diff_source, DiffSource::WorkingTree) {
+            return None;
+        }
+        let (start, end) = self.commit_selection_range?;
+        if start != end {
+            return None;
+        }
+        let commit = self.review_commits.get(start)?;
+        (!Self::is_special_commit(commit)).then_some(commit.id.as_str())
+    }
+
+    /// Whether `path` is reviewed in the diff on screen, as `file_confidence`.
+    pub fn is_file_reviewed(&self, path: &Path) -> bool {
+        match self.viewed_commit() {
+            Some(commit) => self.session.commit_file_confidence(commit, path).is_some(),
+            None => self.session.is_file_reviewed(path),
+        }
+    }
+
+    /// How closely `path` was reviewed in the diff on screen: the viewed
+    /// commit's own review, or else the whole range's.
+    pub fn file_confidence(&self, path: &Path) -> Option<ReviewConfidence> {
+        match self.viewed_commit() {
+            Some(commit) => self.session.commit_file_confidence(commit, path),
+            None => self.session.file_confidence(path),
+        }
+    }
+
+    pub fn is_collapsed_by_filetype(&self, path: &Path) -> bool {
+        self.filetypes.collapsed(path) && !self.opened_files.contains(path)
+    }
+
+    /// Enter on the header of a file its filetype collapses: open it, or
+    /// collapse it again. Returns false when the cursor isn't on one.
+    pub fn toggle_filetype_collapse_at_cursor(&mut self) -> bool {
+        let Some(AnnotatedLine::FileHeader { file_idx }) =
+            self.line_annotations.get(self.diff_state.cursor_line)
+        else {
+            return false;
+        };
+        let file_idx = *file_idx;
+        let path = self.diff_files[file_idx].display_path().clone();
+        if !self.filetypes.collapsed(&path) || self.is_file_reviewed(&path) {
+            return false;
+        }
+        if !self.opened_files.remove(&path) {
+            self.opened_files.insert(path);
+        }
+        self.rebuild_annotations();
+        self.diff_state.cursor_line = self.calculate_file_scroll_offset(file_idx);
+        self.ensure_cursor_visible();
+        true
+    }
+
+    /// Whether diff lines soft-wrap: the `[filetypes]` rule of the file
+    /// under the cursor, else the `wrap` setting.
+    pub fn wrap_lines(&self) -> bool {
+        self.current_file()
+            .and_then(|file| self.filetypes.wrap(file.display_path()))
+            .unwrap_or(self.diff_state.wrap_lines)
+    }
+
     pub fn current_file(&self) -> Option<&DiffFile> {
         self.diff_files.get(self.diff_state.current_file_idx)
     }
@@ -3145,67 +4289,304 @@ impl App {
         else {
             return;
         };
+        let was_complete = self.all_files_reviewed();
 
-        if let Some(review) = self.session.get_file_mut(&path) {
+        if let Some(commit) = self.viewed_commit().map(str::to_string) {
+            let confidence = match self.session.commit_file_confidence(&commit, &path) {
+                Some(_) => None,
+                None => Some(ReviewConfidence::default()),
+            };
+            let files = self.diff_files.len();
+            self.session
+                .set_commit_file_confidence(&commit, &path, files, confidence);
+        } else if let Some(review) = self.session.get_file_mut(&path) {
             review.reviewed = !review.reviewed;
-            self.dirty = true;
-            self.rebuild_annotations();
-
-            if adjust_cursor {
-                self.diff_state.current_file_idx = file_idx;
-                // Move cursor to the file header line
-                let header_line = self.calculate_file_scroll_offset(file_idx);
-                self.diff_state.cursor_line = header_line;
-                self.ensure_cursor_visible();
-            }
+            review.confidence = ReviewConfidence::default();
+            review.reviewed_hash = None;
+            let event = if review.reviewed {
+                ReviewEventKind::FileReviewed { path: path.clone() }
+            } else {
+                ReviewEventKind::FileUnreviewed { path: path.clone() }
+            };
+            self.session.record_event(event);
+            self.journal_file(&path);
+        } else {
+            return;
         }
-    }
+        self.mark_dirty();
+        self.rebuild_annotations();
 
-    pub fn file_count(&self) -> usize {
-        self.diff_files.len()
+        if adjust_cursor {
+            self.diff_state.current_file_idx = file_idx;
+            // Move cursor to the file header line
+            let header_line = self.calculate_file_scroll_offset(file_idx);
+            self.diff_state.cursor_line = header_line;
+            self.ensure_cursor_visible();
+        }
+        self.offer_completion_dialog(was_complete);
     }
 
-    pub fn reviewed_count(&self) -> usize {
-        self.session.reviewed_count()
+    /// `R`: step a file's confidence through skimmed → reviewed → thorough,
+    /// marking it reviewed (as skimmed) first if it isn't yet.
+    pub fn cycle_confidence_for_file_idx(&mut self, file_idx: usize) {
+        let Some(path) = self.diff_files.get(file_idx).map(|f| f.display_path()) else {
+            return;
+        };
+        let next = match self.file_confidence(path) {
+            Some(confidence) => confidence.next(),
+            None => ReviewConfidence::Skimmed,
+        };
+        self.set_file_confidence(file_idx, next);
     }
 
-    /// Returns `(total_files, total_additions, total_deletions)` across all diff files.
-    pub fn diff_stat(&self) -> (usize, usize, usize) {
-        let mut additions = 0;
-        let mut deletions = 0;
-        for file in &self.diff_files {
-            let (a, d) = file.stat();
-            additions += a;
-            deletions += d;
+    /// Mark a file reviewed with `confidence`, as `R` and `:confidence` do.
+    pub fn set_file_confidence(&mut self, file_idx: usize, confidence: ReviewConfidence) {
+        let Some(path) = self
+            .diff_files
+            .get(file_idx)
+            .map(|file| file.display_path().clone())
+        else {
+            return;
+        };
+        let was_complete = self.all_files_reviewed();
+        let was_reviewed = if let Some(commit) = self.viewed_commit().map(str::to_string) {
+            let was_reviewed = self
+                .session
+                .commit_file_confidence(&commit, &path)
+                .is_some();
+            let files = self.diff_files.len();
+            self.session
+                .set_commit_file_confidence(&commit, &path, files, Some(confidence));
+            was_reviewed
+        } else {
+            let Some(review) = self.session.get_file_mut(&path) else {
+                return;
+            };
+            let was_reviewed = review.reviewed;
+            review.reviewed = true;
+            review.confidence = confidence;
+            review.reviewed_hash = None;
+            if !was_reviewed {
+                self.session
+                    .record_event(ReviewEventKind::FileReviewed { path: path.clone() });
+            }
+            self.journal_file(&path);
+            was_reviewed
+        };
+        self.mark_dirty();
+        self.rebuild_annotations();
+        if !was_reviewed && self.focused_panel != FocusedPanel::FileList {
+            // The body folds away; keep the cursor on the file header.
+            self.diff_state.current_file_idx = file_idx;
+            self.diff_state.cursor_line = self.calculate_file_scroll_offset(file_idx);
+            self.ensure_cursor_visible();
         }
-        (self.diff_files.len(), additions, deletions)
+        self.set_message(format!("{} marked {}", path.display(), confidence.label()));
+        self.offer_completion_dialog(was_complete);
     }
 
-    /// Returns true when the cursor is in the review comments area above all files.
-    pub fn is_cursor_in_overview(&self) -> bool {
-        self.diff_state.cursor_line < self.review_comments_render_height()
+    pub fn file_count(&self) -> usize {
+        self.diff_files.len()
     }
 
-    pub fn set_message(&mut self, msg: impl Into<String>) {
-        self.set_message_inner(msg, MessageType::Info, Some(MESSAGE_TTL_INFO));
+    /// Whether `path` lost its reviewed mark to a change in its diff. Only
+    /// the file-level state tracks this, not single-commit views.
+    pub fn changed_since_review(&self, path: &Path) -> bool {
+        self.viewed_commit().is_none()
+            && self
+                .session
+                .files
+                .get(path)
+                .is_some_and(|review| review.changed_since_review())
     }
 
-    pub fn set_warning(&mut self, msg: impl Into<String>) {
-        self.set_message_inner(msg, MessageType::Warning, Some(MESSAGE_TTL_WARNING));
+    /// Reviewed files of the diff on screen, counted like `file_confidence`.
+    pub fn reviewed_count(&self) -> usize {
+        match self.viewed_commit() {
+            Some(commit) => self
+                .diff_files
+                .iter()
+                .filter(|f| {
+                    self.session
+                        .commit_file_confidence(commit, f.display_path())
+                        .is_some()
+                })
+                .count(),
+            None => self.session.reviewed_count(),
+        }
     }
 
-    pub fn set_error(&mut self, msg: impl Into<String>) {
-        self.set_message_inner(msg, MessageType::Error, None);
+    /// Every file of the diff on screen is reviewed
+    pub fn all_files_reviewed(&self) -> bool {
+        !self.diff_files.is_empty() && self.reviewed_count() >= self.diff_files.len()
     }
 
-    /// Warning that stays until something else overwrites it. Used for state-tied
-    /// messages like the dirty-quit prompt where the visual must outlive any TTL.
-    pub fn set_sticky_warning(&mut self, msg: impl Into<String>) {
-        self.set_message_inner(msg, MessageType::Warning, None);
+    /// Open the completion dialog if a reviewed mark just finished the
+    /// review, unless the config turned it off.
+    fn offer_completion_dialog(&mut self, was_complete: bool) {
+        if !self.completion_dialog || was_complete || !self.all_files_reviewed() {
+            return;
+        }
+        self.completion_cursor = 0;
+        self.open_overlay(InputMode::CompletionDialog);
     }
 
-    fn set_message_inner(
-        &mut self,
+    /// Rows of the completion dialog, in display order. Publishing needs a
+    /// pull request, and committing uncommitted git or jj changes outside
+    /// `--safe`.
+    pub fn completion_rows(&self) -> Vec<(String, CompletionAction)> {
+        let mut rows = vec![
+            (
+                "Copy review to clipboard".to_string(),
+                CompletionAction::CopyReview,
+            ),
+            (
+                "Copy chat summary".to_string(),
+                CompletionAction::CopySummary,
+            ),
+            (
+                "Export report to a file".to_string(),
+                CompletionAction::ExportReport,
+            ),
+        ];
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            rows.push((
+                "Publish review to GitHub".to_string(),
+                CompletionAction::PublishToGitHub,
+            ));
+        }
+        let uncommitted = !matches!(
+            self.diff_source,
+            DiffSource::CommitRange(_) | DiffSource::PullRequest(_)
+        );
+        if let Some(program) = self.commit_program()
+            && uncommitted
+            && !self.safe_mode
+        {
+            rows.push((
+                format!("Commit the changes (:{program} commit)"),
+                CompletionAction::Commit,
+            ));
+        }
+        rows.push(("Save and quit".to_string(), CompletionAction::SaveAndQuit));
+        rows
+    }
+
+    fn commit_program(&self) -> Option<&'static str> {
+        match self.vcs_info.vcs_type {
+            VcsType::Git => Some("git"),
+            VcsType::Jujutsu => Some("jj"),
+            _ => None,
+        }
+    }
+
+    pub fn completion_cursor_down(&mut self) {
+        let total = self.completion_rows().len();
+        self.completion_cursor = (self.completion_cursor + 1) % total;
+    }
+
+    pub fn completion_cursor_up(&mut self) {
+        let total = self.completion_rows().len();
+        self.completion_cursor = (self.completion_cursor + total - 1) % total;
+    }
+
+    /// Close the completion dialog, returning the action under its cursor.
+    pub fn take_completion_action(&mut self) -> Option<CompletionAction> {
+        let action = self
+            .completion_rows()
+            .into_iter()
+            .nth(self.completion_cursor)
+            .map(|(_, action)| action);
+        self.close_overlay();
+        action
+    }
+
+    /// Run a completion dialog action other than copying the review, which
+    /// goes through the handler's export path.
+    pub fn run_completion_action(&mut self, action: CompletionAction) {
+        match action {
+            CompletionAction::CopyReview => {}
+            CompletionAction::CopySummary => self.chat_command(""),
+            CompletionAction::ExportReport => self.prefill_command("export "),
+            CompletionAction::PublishToGitHub => self.start_submit_action_picker(),
+            CompletionAction::Commit => {
+                if let Some(program) = self.commit_program() {
+                    self.prefill_command(&format!("{program} commit -m \""));
+                }
+            }
+            CompletionAction::SaveAndQuit => match self.write_session() {
+                Ok(_) => self.should_quit = true,
+                Err(e) => self.set_error(format!("Save failed: {e}")),
+            },
+        }
+    }
+
+    /// Open the command line with `text` typed in, for a command that
+    /// still needs an argument.
+    fn prefill_command(&mut self, text: &str) {
+        self.enter_command_mode();
+        self.command_buffer.push_str(text);
+    }
+
+    /// `✓ 1 comment` / `2/5 reviewed` for the inline commit selector row of
+    /// `commit`, from its own review state. `None` until it has any.
+    pub fn commit_review_badge(&self, commit: &str) -> Option<String> {
+        let review = self.session.commits.get(commit)?;
+        let mut parts = Vec::new();
+        if review.is_complete() {
+            parts.push("✓".to_string());
+        } else if !review.reviewed.is_empty() {
+            parts.push(format!(
+                "{}/{} reviewed",
+                review.reviewed.len(),
+                review.files
+            ));
+        }
+        match self.session.commit_comment_count(commit) {
+            0 => {}
+            1 => parts.push("1 comment".to_string()),
+            n => parts.push(format!("{n} comments")),
+        }
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
+    /// Returns `(total_files, total_additions, total_deletions)` across all diff files.
+    pub fn diff_stat(&self) -> (usize, usize, usize) {
+        let mut additions = 0;
+        let mut deletions = 0;
+        for file in &self.diff_files {
+            let (a, d) = file.stat();
+            additions += a;
+            deletions += d;
+        }
+        (self.diff_files.len(), additions, deletions)
+    }
+
+    /// Returns true when the cursor is in the review comments area above all files.
+    pub fn is_cursor_in_overview(&self) -> bool {
+        self.diff_state.cursor_line < self.review_comments_render_height()
+    }
+
+    pub fn set_message(&mut self, msg: impl Into<String>) {
+        self.set_message_inner(msg, MessageType::Info, Some(MESSAGE_TTL_INFO));
+    }
+
+    pub fn set_warning(&mut self, msg: impl Into<String>) {
+        self.set_message_inner(msg, MessageType::Warning, Some(MESSAGE_TTL_WARNING));
+    }
+
+    pub fn set_error(&mut self, msg: impl Into<String>) {
+        self.set_message_inner(msg, MessageType::Error, None);
+    }
+
+    /// Warning that stays until something else overwrites it. Used for state-tied
+    /// messages like the dirty-quit prompt where the visual must outlive any TTL.
+    pub fn set_sticky_warning(&mut self, msg: impl Into<String>) {
+        self.set_message_inner(msg, MessageType::Warning, None);
+    }
+
+    fn set_message_inner(
+        &mut self,
         msg: impl Into<String>,
         message_type: MessageType,
         ttl: Option<Duration>,
@@ -3307,14 +4688,14 @@ impl App {
     }
 
     pub fn scroll_left(&mut self, cols: usize) {
-        if self.diff_state.wrap_lines {
+        if self.wrap_lines() {
             return;
         }
         self.diff_state.scroll_x = self.diff_state.scroll_x.saturating_sub(cols);
     }
 
     pub fn scroll_right(&mut self, cols: usize) {
-        if self.diff_state.wrap_lines {
+        if self.wrap_lines() {
             return;
         }
         let max_scroll_x = self
@@ -3325,620 +4706,853 @@ impl App {
             (self.diff_state.scroll_x.saturating_add(cols)).min(max_scroll_x);
     }
 
-    pub fn toggle_diff_wrap(&mut self) {
-        let enabled = !self.diff_state.wrap_lines;
-        self.set_diff_wrap(enabled);
+    pub fn toggle_rename_detection(&mut self) {
+        let enabled = self
+            .vcs
+            .rename_detection()
+            .is_none_or(|detection| !detection.enabled);
+        self.set_rename_detection(enabled);
     }
 
-    pub fn set_diff_wrap(&mut self, enabled: bool) {
-        self.diff_state.wrap_lines = enabled;
-        if enabled {
-            self.diff_state.scroll_x = 0;
+    /// Turn rename detection on or off and rebuild the current diff so the
+    /// change shows immediately.
+    pub fn set_rename_detection(&mut self, enabled: bool) {
+        if !self.ensure_vcs_idle() {
+            return;
         }
-        let status = if self.diff_state.wrap_lines {
-            "on"
-        } else {
-            "off"
+        let Some(detection) = self.vcs.rename_detection() else {
+            self.set_warning("Rename detection is not supported by this backend");
+            return;
         };
-        self.set_message(format!("Diff wrapping: {status}"));
+        self.apply_rename_detection(RenameDetection {
+            enabled,
+            ..detection
+        });
     }
 
-    /// Adjusts scroll_offset so the cursor stays within the visible viewport,
-    /// respecting the configured scroll margin (minimum lines from edge).
-    fn ensure_cursor_visible(&mut self) {
-        // Use visible_line_count which is computed during render based on actual line widths.
-        // Falls back to viewport_height if not yet set (before first render).
-        let visible_lines = self.diff_state.effective_visible_lines();
-        let max_scroll = self.max_scroll_offset();
-        let scroll_margin = self.diff_state.effective_scroll_margin(self.scroll_offset);
-        // Cursor too close to the top edge — scroll up
-        if self.diff_state.cursor_line < self.diff_state.scroll_offset + scroll_margin {
-            self.diff_state.scroll_offset =
-                self.diff_state.cursor_line.saturating_sub(scroll_margin);
+    /// `:renames [on|off|copies|nocopies|break|nobreak|<threshold>%]...`.
+    /// With no arguments, show the current settings.
+    pub fn renames_command(&mut self, args: &str) {
+        if !self.ensure_vcs_idle() {
+            return;
         }
-        // Cursor too close to the bottom edge — scroll down.
-        // Reduce the margin near EOF so we don't scroll to show empty space
-        // when the last line is already visible (matches Vim behavior).
-        let lines_below = self
-            .max_cursor_line()
-            .saturating_sub(self.diff_state.cursor_line);
-        let bottom_margin = scroll_margin.min(lines_below);
-        if self.diff_state.cursor_line + bottom_margin
-            >= self.diff_state.scroll_offset + visible_lines
-        {
-            self.diff_state.scroll_offset =
-                (self.diff_state.cursor_line + bottom_margin - visible_lines + 1).min(max_scroll);
+        let Some(mut detection) = self.vcs.rename_detection() else {
+            self.set_warning("Rename detection is not supported by this backend");
+            return;
+        };
+        if args.trim().is_empty() {
+            self.set_message(describe_rename_detection(&detection));
+            return;
+        }
+        for arg in args.split_whitespace() {
+            match arg {
+                "on" => detection.enabled = true,
+                "off" => detection.enabled = false,
+                "copies" => detection.copies = true,
+                "nocopies" => detection.copies = false,
+                "break" => detection.break_rewrites = true,
+                "nobreak" => detection.break_rewrites = false,
+                _ => match arg.trim_end_matches('%').parse::<u16>() {
+                    Ok(threshold) if threshold <= 100 => detection.threshold = threshold,
+                    _ => {
+                        self.set_warning(
+                            "Usage: :renames [on|off|copies|nocopies|break|nobreak|<0-100>%]",
+                        );
+                        return;
+                    }
+                },
+            }
         }
+        self.apply_rename_detection(detection);
     }
 
-    pub fn search_in_diff_from_cursor(&mut self) -> bool {
-        let pattern = self.search_buffer.clone();
-        if pattern.trim().is_empty() {
-            self.set_message("Search pattern is empty");
-            return false;
+    fn apply_rename_detection(&mut self, detection: RenameDetection) {
+        self.vcs.set_rename_detection(detection);
+        let status = describe_rename_detection(&detection);
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_message(format!("{status} (applies to local diffs)"));
+            return;
+        }
+        match self.reload_diff_files() {
+            Ok(_) => self.set_message(status),
+            Err(e) => self.set_error(format!("Reload failed: {e}")),
         }
-
-        self.last_search_pattern = Some(pattern.clone());
-        self.search_in_diff(&pattern, self.diff_state.cursor_line, true, true)
     }
 
-    pub fn search_next_in_diff(&mut self) -> bool {
-        let Some(pattern) = self.last_search_pattern.clone() else {
-            self.set_message("No previous search");
-            return false;
-        };
-        self.search_in_diff(&pattern, self.diff_state.cursor_line, true, false)
+    /// `<leader>w`: toggle ignoring all whitespace, the common case.
+    pub fn toggle_ignore_all_space(&mut self) {
+        self.whitespace_command("all");
     }
 
-    pub fn search_prev_in_diff(&mut self) -> bool {
-        let Some(pattern) = self.last_search_pattern.clone() else {
-            self.set_message("No previous search");
-            return false;
+    /// `:whitespace [all|change|blank|off]...`: toggle ignoring all space,
+    /// space changes or blank lines, or `off` to show every change again.
+    /// With no arguments, show the current settings.
+    pub fn whitespace_command(&mut self, args: &str) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        let Some(mut whitespace) = self.vcs.ignore_whitespace() else {
+            self.set_warning("Ignoring whitespace is not supported by this backend");
+            return;
         };
-        self.search_in_diff(&pattern, self.diff_state.cursor_line, false, false)
-    }
-
-    fn search_in_diff(
-        &mut self,
-        pattern: &str,
-        start_idx: usize,
-        forward: bool,
-        include_current: bool,
-    ) -> bool {
-        let total_lines = self.total_lines();
-        if total_lines == 0 {
-            self.set_message("No diff content to search");
-            return false;
+        if args.trim().is_empty() {
+            self.set_message(describe_ignore_whitespace(&whitespace));
+            return;
         }
-
-        if forward {
-            let mut idx = start_idx.min(total_lines.saturating_sub(1));
-            if !include_current {
-                idx = idx.saturating_add(1);
-            }
-            for line_idx in idx..total_lines {
-                if let Some(text) = self.line_text_for_search(line_idx)
-                    && text.contains(pattern)
-                {
-                    self.diff_state.cursor_line = line_idx;
-                    self.ensure_cursor_visible();
-                    self.center_cursor();
-                    self.update_current_file_from_cursor();
-                    return true;
+        for arg in args.split_whitespace() {
+            match arg {
+                "all" => whitespace.all_space = !whitespace.all_space,
+                "change" => whitespace.space_change = !whitespace.space_change,
+                // `jj diff` has no `--ignore-blank-lines`
+                "blank" if self.vcs.info().vcs_type == VcsType::Jujutsu => {
+                    self.set_warning("Ignoring blank lines is not supported by jj");
+                    return;
+                }
+                "blank" => whitespace.blank_lines = !whitespace.blank_lines,
+                "off" => whitespace = IgnoreWhitespace::default(),
+                _ => {
+                    self.set_warning("Usage: :whitespace [all|change|blank|off]");
+                    return;
                 }
             }
-        } else {
-            let mut idx = start_idx.min(total_lines.saturating_sub(1));
-            if !include_current {
-                idx = idx.saturating_sub(1);
+        }
+
+        self.vcs.set_ignore_whitespace(whitespace);
+        let status = describe_ignore_whitespace(&whitespace);
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_message(format!("{status} (applies to local diffs)"));
+            return;
+        }
+        match self.reload_diff_files() {
+            Ok(_) => self.set_message(status),
+            Err(e) => self.set_error(format!("Reload failed: {e}")),
+        }
+    }
+
+    /// `:elide [<n>|off]`: collapse runs of more than `n` unchanged lines
+    /// inside hunks, or report the current threshold when called bare.
+    pub fn elide_command(&mut self, args: &str) {
+        let threshold = match args.trim() {
+            "" => {
+                self.set_message(describe_elide_context(self.elide_context));
+                return;
             }
-            let mut line_idx = idx;
-            loop {
-                if let Some(text) = self.line_text_for_search(line_idx)
-                    && text.contains(pattern)
-                {
-                    self.diff_state.cursor_line = line_idx;
-                    self.ensure_cursor_visible();
-                    self.center_cursor();
-                    self.update_current_file_from_cursor();
-                    return true;
+            "off" => 0,
+            arg => match arg.parse::<usize>() {
+                Ok(threshold) => threshold,
+                Err(_) => {
+                    self.set_warning("Usage: :elide [<lines>|off]");
+                    return;
                 }
-                if line_idx == 0 {
-                    break;
+            },
+        };
+        self.elide_context = threshold;
+        self.expanded_elisions.clear();
+        self.rebuild_annotations();
+        self.set_message(describe_elide_context(threshold));
+    }
+
+    /// `:spotcheck [<n>|off]`: sample `n` random hunks to spot-check, jump to
+    /// the next sampled hunk, or drop the sample.
+    /// `:spotcheck <hunks>` samples that many random hunks and jumps to the
+    /// first, `:spotcheck` moves to the next sampled hunk and `:spotcheck off`
+    /// drops the sample.
+    pub fn spot_check_command(&mut self, args: &str) {
+        match args.trim() {
+            "" => self.next_sampled_hunk(),
+            "off" => {
+                if self.session.spot_check.take().is_some() {
+                    self.mark_dirty();
                 }
-                line_idx = line_idx.saturating_sub(1);
+                self.set_message("Spot-check off");
             }
+            arg => match arg.parse::<usize>() {
+                Ok(count) if count > 0 => {
+                    let seed = RandomState::new().build_hasher().finish();
+                    let spot_check = SpotCheck::sample(&self.diff_files, count, seed);
+                    if spot_check.sampled.is_empty() {
+                        self.set_warning("No hunks to sample");
+                        return;
+                    }
+                    let coverage = spot_check.coverage();
+                    self.session.spot_check = Some(spot_check);
+                    self.mark_dirty();
+                    self.diff_state.cursor_line = 0;
+                    self.next_sampled_hunk();
+                    self.set_message(format!("Spot-checking {coverage}"));
+                }
+                _ => self.set_warning("Usage: :spotcheck [<hunks>|off]"),
+            },
         }
+    }
 
-        self.set_message(format!("No matches for \"{pattern}\""));
-        false
+    pub fn is_sampled_hunk(&self, path: &Path, hunk: &DiffHunk) -> bool {
+        self.session
+            .spot_check
+            .as_ref()
+            .is_some_and(|spot_check| spot_check.contains(path, hunk))
     }
 
-    fn line_text_for_search(&self, line_idx: usize) -> Option<String> {
-        match self.line_annotations.get(line_idx)? {
-            AnnotatedLine::ReviewCommentsHeader => Some("Review comments".to_string()),
-            AnnotatedLine::ReviewComment { comment_idx } => {
-                let comment = self.session.review_comments.get(*comment_idx)?;
-                Some(comment.content.clone())
-            }
-            AnnotatedLine::FileHeader { file_idx } => {
-                let file = self.diff_files.get(*file_idx)?;
-                Some(format!(
-                    "{} [{}]",
-                    file.display_path().display(),
-                    file.status.as_char()
-                ))
-            }
-            AnnotatedLine::FileComment {
-                file_idx,
-                comment_idx,
-            } => {
-                let path = self.diff_files.get(*file_idx)?.display_path();
-                let review = self.session.files.get(path)?;
-                let comment = review.file_comments.get(*comment_idx)?;
-                Some(comment.content.clone())
-            }
-            AnnotatedLine::LineComment {
-                file_idx,
-                line,
-                comment_idx,
-                ..
-            } => {
-                let path = self.diff_files.get(*file_idx)?.display_path();
-                let review = self.session.files.get(path)?;
-                let comments = review.line_comments.get(line)?;
-                let comment = comments.get(*comment_idx)?;
-                Some(comment.content.clone())
-            }
-            AnnotatedLine::Expander { gap_id, direction } => {
-                let arrow = match direction {
-                    ExpandDirection::Down => "↓",
-                    ExpandDirection::Up => "↑",
-                    ExpandDirection::Both => "↕",
-                };
-                let gap = self.gap_size(gap_id)?;
-                let top_len = self.expanded_top.get(gap_id).map_or(0, |v| v.len());
-                let bot_len = self.expanded_bottom.get(gap_id).map_or(0, |v| v.len());
-                let remaining = (gap as usize).saturating_sub(top_len + bot_len);
-                let count = remaining.min(GAP_EXPAND_BATCH);
-                Some(format!("... {arrow} expand ({count} lines) ..."))
-            }
-            AnnotatedLine::HiddenLines { count, .. } => {
-                Some(format!("... {count} lines hidden ..."))
-            }
-            AnnotatedLine::ExpandedContext {
-                gap_id,
-                line_idx: context_idx,
-            } => {
-                let content = self.get_expanded_line(gap_id, *context_idx)?;
-                Some(content.content.clone())
-            }
-            AnnotatedLine::HunkHeader { file_idx, hunk_idx } => {
-                let file = self.diff_files.get(*file_idx)?;
-                let hunk = file.hunks.get(*hunk_idx)?;
-                Some(hunk.header.clone())
-            }
-            AnnotatedLine::DiffLine {
-                file_idx,
-                hunk_idx,
-                line_idx: diff_idx,
-                ..
-            } => {
-                let file = self.diff_files.get(*file_idx)?;
-                let hunk = file.hunks.get(*hunk_idx)?;
-                let line = hunk.lines.get(*diff_idx)?;
-                Some(line.content.clone())
-            }
-            AnnotatedLine::BinaryOrEmpty { file_idx } => {
-                let file = self.diff_files.get(*file_idx)?;
-                if file.is_too_large {
-                    Some("(file too large to display)".to_string())
-                } else if file.is_binary {
-                    Some("(binary file)".to_string())
-                } else {
-                    Some("(no changes)".to_string())
+    /// Move to the next sampled hunk header after the cursor, wrapping to
+    /// the first one. Hunks of reviewed (folded) files are skipped.
+    fn next_sampled_hunk(&mut self) {
+        if self.session.spot_check.is_none() {
+            self.set_warning("No spot-check sample (use :spotcheck <hunks>)");
+            return;
+        }
+        let sampled: Vec<usize> = self
+            .line_annotations
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, annotation)| match annotation {
+                AnnotatedLine::HunkHeader { file_idx, hunk_idx } => {
+                    let file = &self.diff_files[*file_idx];
+                    self.is_sampled_hunk(file.display_path(), &file.hunks[*hunk_idx])
+                        .then_some(idx)
                 }
-            }
-            AnnotatedLine::SideBySideLine {
-                file_idx,
-                hunk_idx,
-                del_line_idx,
-                add_line_idx,
-                ..
-            } => {
-                let file = self.diff_files.get(*file_idx)?;
-                let hunk = file.hunks.get(*hunk_idx)?;
+                _ => None,
+            })
+            .collect();
+        let cursor = self.diff_state.cursor_line;
+        let Some(&target) = sampled
+            .iter()
+            .find(|&&idx| idx > cursor)
+            .or(sampled.first())
+        else {
+            self.set_message("Every sampled hunk is in a reviewed file");
+            return;
+        };
+        self.diff_state.cursor_line = target;
+        self.ensure_cursor_visible();
+        self.update_current_file_from_cursor();
+    }
 
-                let del_content = del_line_idx
-                    .and_then(|idx| hunk.lines.get(idx))
-                    .map(|l| l.content.as_str())
-                    .unwrap_or("");
-                let add_content = add_line_idx
-                    .and_then(|idx| hunk.lines.get(idx))
-                    .map(|l| l.content.as_str())
-                    .unwrap_or("");
-                Some(format!("{} {}", del_content, add_content))
-            }
-            AnnotatedLine::RemoteThreadLine { thread_idx } => {
-                let thread = self.forge_review_threads.get(*thread_idx)?;
-                // Search matches any text in the thread (including replies).
-                let mut bodies: Vec<String> =
-                    thread.comments.iter().map(|c| c.body.clone()).collect();
-                bodies.insert(0, format!("github {}", thread.path));
-                Some(bodies.join(" "))
-            }
-            AnnotatedLine::Spacing => None,
+    /// `m`: mark the line under the cursor to revisit before finishing, or
+    /// unmark it. On a hunk header the hunk's first line is marked.
+    pub fn toggle_defer_at_cursor(&mut self) {
+        let annotation = self.line_annotations.get(self.diff_state.cursor_line);
+        let target = match annotation {
+            Some(AnnotatedLine::HunkHeader { file_idx, hunk_idx }) => self
+                .diff_files
+                .get(*file_idx)
+                .and_then(|file| file.hunks.get(*hunk_idx)?.lines.first())
+                .and_then(|line| match (line.new_lineno, line.old_lineno) {
+                    (Some(new), _) => Some((new, LineSide::New)),
+                    (None, Some(old)) => Some((old, LineSide::Old)),
+                    (None, None) => None,
+                }),
+            _ => self.get_line_at_cursor(),
+        };
+        let Some((line, side)) = target else {
+            self.set_warning("Move the cursor to a diff line or hunk header to defer it");
+            return;
+        };
+        let Some(path) = annotation
+            .and_then(annotation_file_idx)
+            .and_then(|idx| self.diff_files.get(idx))
+            .map(|file| file.display_path().clone())
+        else {
+            return;
+        };
+        let Some(review) = self.session.get_file_mut(&path) else {
+            return;
+        };
+        let deferred = review.toggle_deferred(line, side);
+        self.mark_dirty();
+        let outstanding = self.session.deferred_count();
+        if deferred {
+            self.set_message(format!(
+                "Deferred {}:{line} ({outstanding} to revisit)",
+                path.display()
+            ));
+        } else {
+            self.set_message(format!(
+                "Undeferred {}:{line} ({outstanding} to revisit)",
+                path.display()
+            ));
         }
     }
 
-    fn gap_size(&self, gap_id: &GapId) -> Option<u32> {
-        let file = self.diff_files.get(gap_id.file_idx)?;
-        let hunk = file.hunks.get(gap_id.hunk_idx)?;
-        let prev_hunk = if gap_id.hunk_idx > 0 {
-            file.hunks.get(gap_id.hunk_idx - 1)
-        } else {
-            None
+    /// Whether a diff line of `file_idx` is marked deferred. Deleted lines
+    /// are matched on their old line number, everything else on the new.
+    pub fn is_line_deferred(&self, file_idx: usize, line: &DiffLine) -> bool {
+        let Some(review) = self
+            .diff_files
+            .get(file_idx)
+            .and_then(|file| self.session.files.get(file.display_path()))
+        else {
+            return false;
         };
-        Some(calculate_gap(
-            prev_hunk.map(|h| (&h.new_start, &h.new_count)),
-            hunk.new_start,
-        ))
+        match (line.new_lineno, line.old_lineno) {
+            (Some(new), _) => review.is_deferred(new, LineSide::New),
+            (None, Some(old)) => review.is_deferred(old, LineSide::Old),
+            (None, None) => false,
+        }
     }
 
-    pub fn center_cursor(&mut self) {
-        let viewport = self.diff_state.viewport_height.max(1);
-        let half_viewport = viewport / 2;
-        let max_scroll = self.max_scroll_offset();
-        self.diff_state.scroll_offset = self
-            .diff_state
-            .cursor_line
-            .saturating_sub(half_viewport)
-            .min(max_scroll);
+    /// `]d` / `[d`: move to the next (or previous) deferred line, wrapping
+    /// around. Lines in reviewed (folded) files can't be reached.
+    pub fn jump_to_deferred(&mut self, forward: bool) {
+        if self.session.deferred_count() == 0 {
+            self.set_message("No deferred lines (m defers the line under the cursor)");
+            return;
+        }
+        let deferred: Vec<usize> = self
+            .line_annotations
+            .iter()
+            .enumerate()
+            .filter(|(_, annotation)| {
+                let (file_idx, old_lineno, new_lineno) = match annotation {
+                    AnnotatedLine::DiffLine {
+                        file_idx,
+                        old_lineno,
+                        new_lineno,
+                        ..
+                    }
+                    | AnnotatedLine::SideBySideLine {
+                        file_idx,
+                        old_lineno,
+                        new_lineno,
+                        ..
+                    } => (*file_idx, *old_lineno, *new_lineno),
+                    _ => return false,
+                };
+                let Some(review) = self
+                    .diff_files
+                    .get(file_idx)
+                    .and_then(|file| self.session.files.get(file.display_path()))
+                else {
+                    return false;
+                };
+                new_lineno.is_some_and(|ln| review.is_deferred(ln, LineSide::New))
+                    || old_lineno.is_some_and(|ln| review.is_deferred(ln, LineSide::Old))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        let cursor = self.diff_state.cursor_line;
+        let target = if forward {
+            deferred
+                .iter()
+                .find(|&&idx| idx > cursor)
+                .or(deferred.first())
+        } else {
+            deferred
+                .iter()
+                .rev()
+                .find(|&&idx| idx < cursor)
+                .or(deferred.last())
+        };
+        let Some(&target) = target else {
+            self.set_message("Every deferred line is in a reviewed file");
+            return;
+        };
+        self.diff_state.cursor_line = target;
+        self.ensure_cursor_visible();
+        self.update_current_file_from_cursor();
     }
 
-    pub fn cursor_to_top(&mut self) {
-        let scroll_margin = self.diff_state.effective_scroll_margin(self.scroll_offset);
-        let max_scroll = self.max_scroll_offset();
-        self.diff_state.scroll_offset = self
-            .diff_state
-            .cursor_line
-            .saturating_sub(scroll_margin)
-            .min(max_scroll);
+    /// `<leader>u` / `:set untracked!`: show or hide untracked files.
+    pub fn toggle_untracked(&mut self) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        match self.vcs.include_untracked() {
+            Some(include) => self.set_include_untracked(!include),
+            None => self.set_warning("Untracked files are not supported by this backend"),
+        }
     }
 
-    pub fn cursor_to_bottom(&mut self) {
-        let visible_lines = self.diff_state.effective_visible_lines();
-        let scroll_margin = self.diff_state.effective_scroll_margin(self.scroll_offset);
-        let max_scroll = self.max_scroll_offset();
-        self.diff_state.scroll_offset = self
-            .diff_state
-            .cursor_line
-            .saturating_sub(visible_lines.saturating_sub(1 + scroll_margin))
-            .min(max_scroll);
+    /// List or hide untracked files in working tree diffs and reload the
+    /// file list in place.
+    pub fn set_include_untracked(&mut self, include: bool) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        let Some(previous) = self.vcs.include_untracked() else {
+            self.set_warning("Untracked files are not supported by this backend");
+            return;
+        };
+        self.vcs.set_include_untracked(include);
+        let status = if include {
+            "Untracked files: shown"
+        } else {
+            "Untracked files: hidden"
+        };
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_message(format!("{status} (applies to local diffs)"));
+            return;
+        }
+        match self.reload_diff_files() {
+            Ok(_) => self.set_message(status),
+            Err(TuicrError::NoChanges) => {
+                // Hiding them would leave nothing to review.
+                self.vcs.set_include_untracked(previous);
+                self.set_warning("Only untracked files changed; keeping them shown");
+            }
+            Err(e) => self.set_error(format!("Reload failed: {e}")),
+        }
     }
 
-    pub fn go_to_source_line(&mut self, target_lineno: u32, side: LineSide) {
-        let current_file = self.diff_state.current_file_idx;
-        let mut result = self.find_source_line_in_diff(target_lineno, side);
-        let side_label = match side {
-            LineSide::New => "",
-            LineSide::Old => " (old)",
-        };
+    /// `:conflicts`: toggle listing only the files that contain conflict
+    /// markers, jumping to the first one when the current file is hidden.
+    pub fn toggle_conflicts_only(&mut self) {
+        if !self.conflicts_only && self.conflicts.is_empty() {
+            self.set_message("No conflicted files");
+            return;
+        }
+        self.conflicts_only = !self.conflicts_only;
+        if !self.conflicts_only {
+            self.ensure_valid_tree_selection();
+            self.set_message("Showing all files");
+            return;
+        }
+        let conflicts = std::mem::take(&mut self.conflicts);
+        self.focus_first_file_matching(|path| conflicts.is_conflicted(path));
+        self.conflicts = conflicts;
+        let count = self.conflicts.len();
+        self.set_message(format!(
+            "Showing {count} conflicted file{}",
+            if count == 1 { "" } else { "s" }
+        ));
+    }
 
-        // If the line isn't already annotated, see whether it falls inside a
-        // collapsed (or partially collapsed) gap between hunks. If so, expand
-        // *toward* the target from whichever side the cursor is on: cursor
-        // above the gap expands `Down` from the previous hunk, cursor at or
-        // below the gap expands `Up` from the next hunk. Either way the
-        // unreached half of the gap stays collapsed behind an expander.
-        if !matches!(result, FindSourceLineResult::Exact(_))
-            && let Some(gap_id) = self.find_gap_containing_lineno(current_file, target_lineno, side)
+    /// After a file-list filter was turned on, move to the first file it
+    /// keeps unless the current file is kept.
+    fn focus_first_file_matching(&mut self, keep: impl Fn(&Path) -> bool) {
+        let current_kept = self
+            .diff_files
+            .get(self.diff_state.current_file_idx)
+            .is_some_and(|file| keep(file.display_path()));
+        if !current_kept
+            && let Some(idx) = self
+                .diff_files
+                .iter()
+                .position(|file| keep(file.display_path()))
         {
-            let (direction, limit) = self.expand_plan_to_reach(&gap_id, target_lineno, side);
-            if let Err(e) = self.expand_gap(gap_id, direction, limit) {
-                self.set_error(format!("Expand failed: {e}"));
-                return;
-            }
-            result = self.find_source_line_in_diff(target_lineno, side);
+            self.jump_to_file(idx);
         }
+        self.ensure_valid_tree_selection();
+    }
 
-        match result {
-            FindSourceLineResult::Exact(idx) | FindSourceLineResult::Nearest(idx) => {
-                self.diff_state.cursor_line = idx;
-                self.ensure_cursor_visible();
-                self.center_cursor();
-                self.update_current_file_from_cursor();
-                if matches!(result, FindSourceLineResult::Nearest(_)) {
-                    self.set_message(format!(
-                        "Line {target_lineno}{side_label} not in diff, jumped to nearest"
-                    ));
+    /// The `:author` filter's files, unless none of them is in the current
+    /// diff (e.g. after narrowing the commit selection), where it would
+    /// hide every file.
+    pub fn author_files(&self) -> Option<&HashSet<PathBuf>> {
+        self.author_filter
+            .as_ref()
+            .map(|filter| &filter.files)
+            .filter(|files| {
+                self.diff_files
+                    .iter()
+                    .any(|file| files.contains(file.display_path()))
+            })
+    }
+
+    /// `:author [name|off]`: in a commit range review, list only the files
+    /// changed by the commits whose author contains `name` (ignoring case),
+    /// from each commit's own diff. `off` clears the filter; bare shows it,
+    /// or the authors of the reviewed commits.
+    pub fn author_command(&mut self, arg: &str) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        let arg = arg.trim();
+        let commits: Vec<(usize, CommitInfo)> = self
+            .review_commits
+            .iter()
+            .enumerate()
+            .filter(|(_, commit)| !Self::is_special_commit(commit))
+            .map(|(idx, commit)| (idx, commit.clone()))
+            .collect();
+        if arg.is_empty() {
+            match &self.author_filter {
+                Some(filter) => self.set_message(format!(
+                    "Showing {} file(s) changed by {}",
+                    filter.files.len(),
+                    filter.author
+                )),
+                None if commits.is_empty() => self.set_message("No author filter"),
+                None => {
+                    let mut authors: Vec<&str> = Vec::new();
+                    for (_, commit) in &commits {
+                        if !authors.contains(&commit.author.as_str()) {
+                            authors.push(&commit.author);
+                        }
+                    }
+                    self.set_message(format!("Authors: {}", authors.join(", ")));
                 }
             }
-            FindSourceLineResult::NotFound => {
-                self.set_warning(format!(
-                    "Line {target_lineno}{side_label} not found in current file"
-                ));
-            }
+            return;
+        }
+        if arg == "off" {
+            self.author_filter = None;
+            self.ensure_valid_tree_selection();
+            self.set_message("Showing all files");
+            return;
+        }
+        if !matches!(self.diff_source, DiffSource::CommitRange(_)) || commits.is_empty() {
+            self.set_warning(":author needs a commit range review");
+            return;
         }
-    }
 
-    /// Like the free `find_source_line` but also resolves `ExpandedContext`
-    /// annotations through `get_expanded_line` so newly-revealed context lines
-    /// count toward the match.
-    fn find_source_line_in_diff(&self, target_lineno: u32, side: LineSide) -> FindSourceLineResult {
-        let current_file = self.diff_state.current_file_idx;
-        let mut best: Option<(usize, u32)> = None;
+        let needle = arg.to_lowercase();
+        let authored: Vec<(usize, String)> = commits
+            .into_iter()
+            .filter(|(_, commit)| commit.author.to_lowercase().contains(&needle))
+            .map(|(idx, commit)| (idx, commit.id))
+            .collect();
+        if authored.is_empty() {
+            self.set_warning(format!("No reviewed commits by \"{arg}\""));
+            return;
+        }
 
-        for (idx, annotation) in self.line_annotations.iter().enumerate() {
-            let (file_idx, candidate) = match annotation {
-                AnnotatedLine::DiffLine {
-                    file_idx,
-                    old_lineno,
-                    new_lineno,
-                    ..
-                }
-                | AnnotatedLine::SideBySideLine {
-                    file_idx,
-                    old_lineno,
-                    new_lineno,
-                    ..
-                } => {
-                    let c = match side {
-                        LineSide::New => *new_lineno,
-                        LineSide::Old => *old_lineno,
-                    };
-                    (*file_idx, c)
-                }
-                AnnotatedLine::ExpandedContext { gap_id, line_idx } => {
-                    let Some(line) = self.get_expanded_line(gap_id, *line_idx) else {
-                        continue;
-                    };
-                    let c = match side {
-                        LineSide::New => line.new_lineno,
-                        LineSide::Old => line.old_lineno,
+        let mut files = HashSet::new();
+        for (idx, id) in authored {
+            // A single commit's diff is the same one the inline commit
+            // selector caches when that commit alone is selected
+            let diff = match self.commit_diff_cache.get(&(idx, idx)) {
+                Some(diff) => diff.clone(),
+                None => {
+                    let highlighter = self.theme.syntax_highlighter();
+                    let diff = match Self::get_commit_range_diff_with_ignore(
+                        self.vcs.as_ref(),
+                        &self.vcs_info.root_path,
+                        std::slice::from_ref(&id),
+                        self.range_mode,
+                        highlighter,
+                        self.path_filter.as_deref(),
+                    ) {
+                        Ok(diff) => diff,
+                        Err(TuicrError::NoChanges) => Vec::new(),
+                        Err(e) => {
+                            self.set_error(format!("Failed to diff {id}: {e}"));
+                            return;
+                        }
                     };
-                    (gap_id.file_idx, c)
+                    self.commit_diff_cache.insert((idx, idx), diff.clone());
+                    diff
                 }
-                _ => continue,
             };
-            if file_idx != current_file {
-                continue;
-            }
-            if let Some(ln) = candidate {
-                let dist = ln.abs_diff(target_lineno);
-                if dist == 0 {
-                    return FindSourceLineResult::Exact(idx);
-                }
-                if best.is_none() || dist < best.unwrap().1 {
-                    best = Some((idx, dist));
-                }
-            }
+            files.extend(
+                diff.iter()
+                    .filter(|file| !file.is_commit_message)
+                    .map(|file| file.display_path().clone()),
+            );
         }
 
-        match best {
-            Some((idx, _)) => FindSourceLineResult::Nearest(idx),
-            None => FindSourceLineResult::NotFound,
+        let filter = AuthorFilter {
+            author: arg.to_string(),
+            files,
+        };
+        let shown = self
+            .diff_files
+            .iter()
+            .filter(|file| filter.files.contains(file.display_path()))
+            .count();
+        if shown == 0 {
+            self.set_warning(format!("No files in this diff changed by {arg}"));
+            return;
         }
+        self.focus_first_file_matching(|path| filter.files.contains(path));
+        self.author_filter = Some(filter);
+        self.ensure_valid_tree_selection();
+        self.set_message(format!("Showing {shown} file(s) changed by {arg}"));
     }
 
-    /// Return the gap (between-hunk or pre-first-hunk region) in `file_idx`
-    /// whose line range on `side` contains `target_lineno`, if any. Used by
-    /// `go_to_source_line` to auto-expand collapsed context when the user
-    /// jumps to a line that's hidden behind an expander.
-    fn find_gap_containing_lineno(
-        &self,
-        file_idx: usize,
-        target_lineno: u32,
-        side: LineSide,
-    ) -> Option<GapId> {
-        let file = self.diff_files.get(file_idx)?;
-        for hunk_idx in 0..file.hunks.len() {
-            let hunk = &file.hunks[hunk_idx];
-            let prev_hunk = if hunk_idx > 0 {
-                Some(&file.hunks[hunk_idx - 1])
-            } else {
-                None
-            };
-            let (start, end) = match side {
-                LineSide::New => match prev_hunk {
-                    None => (1, hunk.new_start.saturating_sub(1)),
-                    Some(p) => (p.new_start + p.new_count, hunk.new_start.saturating_sub(1)),
-                },
-                LineSide::Old => match prev_hunk {
-                    None => (1, hunk.old_start.saturating_sub(1)),
-                    Some(p) => (p.old_start + p.old_count, hunk.old_start.saturating_sub(1)),
-                },
-            };
-            if start <= end && target_lineno >= start && target_lineno <= end {
-                return Some(GapId { file_idx, hunk_idx });
-            }
-        }
-        None
+    pub fn set_conventional_comments(&mut self, enabled: bool) {
+        self.conventional_comments = enabled;
+        let status = if enabled { "on" } else { "off" };
+        self.set_message(format!("Conventional Comments: {status}"));
     }
 
-    /// Choose an `ExpandDirection` and line-count limit so that expanding
-    /// `gap_id` reveals exactly the lines between the cursor and
-    /// `target_lineno` (on `side`). Cursor above the gap → `Down` from the
-    /// previous hunk; cursor at-or-below the gap → `Up` from the next hunk.
-    ///
-    /// `expand_gap` operates in new-side coordinates, so an old-side target
-    /// is translated to new-side using the offset that holds across an
-    /// unchanged-context gap: `new - old = hunk.new_start - hunk.old_start`
-    /// where `hunk` is the hunk immediately *after* the gap.
-    fn expand_plan_to_reach(
-        &self,
-        gap_id: &GapId,
-        target_lineno: u32,
-        side: LineSide,
-    ) -> (ExpandDirection, Option<usize>) {
-        let Some(file) = self.diff_files.get(gap_id.file_idx) else {
-            return (ExpandDirection::Both, None);
-        };
-        let Some(hunk) = file.hunks.get(gap_id.hunk_idx) else {
-            return (ExpandDirection::Both, None);
-        };
-        let prev_hunk = if gap_id.hunk_idx > 0 {
-            Some(&file.hunks[gap_id.hunk_idx - 1])
+    pub fn toggle_diff_wrap(&mut self) {
+        let enabled = !self.diff_state.wrap_lines;
+        self.set_diff_wrap(enabled);
+    }
+
+    pub fn set_diff_wrap(&mut self, enabled: bool) {
+        self.diff_state.wrap_lines = enabled;
+        if enabled {
+            self.diff_state.scroll_x = 0;
+        }
+        let status = if self.diff_state.wrap_lines {
+            "on"
         } else {
-            None
-        };
-        let gap_start_new = match prev_hunk {
-            None => 1,
-            Some(p) => p.new_start + p.new_count,
-        };
-        let gap_end_new = hunk.new_start.saturating_sub(1);
-        // offset := new - old, constant across the unchanged context gap
-        let offset_new_minus_old = hunk.new_start as i64 - hunk.old_start as i64;
-        let target_new = match side {
-            LineSide::New => target_lineno as i64,
-            LineSide::Old => target_lineno as i64 + offset_new_minus_old,
+            "off"
         };
+        self.set_message(format!("Diff wrapping: {status}"));
+    }
 
-        let cursor_below_gap = self
-            .find_hunk_header_annotation_idx(gap_id)
-            .is_some_and(|h| self.diff_state.cursor_line >= h);
-
-        if cursor_below_gap {
-            let bot_len = self.expanded_bottom.get(gap_id).map_or(0, |v| v.len()) as i64;
-            let inner_end = gap_end_new as i64 - bot_len;
-            let limit = (inner_end - target_new + 1).max(0) as usize;
-            (ExpandDirection::Up, Some(limit))
-        } else {
-            let top_len = self.expanded_top.get(gap_id).map_or(0, |v| v.len()) as i64;
-            let inner_start = gap_start_new as i64 + top_len;
-            let limit = (target_new - inner_start + 1).max(0) as usize;
-            (ExpandDirection::Down, Some(limit))
+    /// Adjusts scroll_offset so the cursor stays within the visible viewport,
+    /// respecting the configured scroll margin (minimum lines from edge).
+    fn ensure_cursor_visible(&mut self) {
+        // Use visible_line_count which is computed during render based on actual line widths.
+        // Falls back to viewport_height if not yet set (before first render).
+        let visible_lines = self.diff_state.effective_visible_lines();
+        let max_scroll = self.max_scroll_offset();
+        let scroll_margin = self.diff_state.effective_scroll_margin(self.scroll_offset);
+        // Cursor too close to the top edge — scroll up
+        if self.diff_state.cursor_line < self.diff_state.scroll_offset + scroll_margin {
+            self.diff_state.scroll_offset =
+                self.diff_state.cursor_line.saturating_sub(scroll_margin);
+        }
+        // Cursor too close to the bottom edge — scroll down.
+        // Reduce the margin near EOF so we don't scroll to show empty space
+        // when the last line is already visible (matches Vim behavior).
+        let lines_below = self
+            .max_cursor_line()
+            .saturating_sub(self.diff_state.cursor_line);
+        let bottom_margin = scroll_margin.min(lines_below);
+        if self.diff_state.cursor_line + bottom_margin
+            >= self.diff_state.scroll_offset + visible_lines
+        {
+            self.diff_state.scroll_offset =
+                (self.diff_state.cursor_line + bottom_margin - visible_lines + 1).min(max_scroll);
         }
     }
 
-    fn find_hunk_header_annotation_idx(&self, gap_id: &GapId) -> Option<usize> {
-        self.line_annotations
-            .iter()
-            .enumerate()
-            .find_map(|(idx, a)| match a {
-                AnnotatedLine::HunkHeader { file_idx, hunk_idx }
-                    if *file_idx == gap_id.file_idx && *hunk_idx == gap_id.hunk_idx =>
-                {
-                    Some(idx)
-                }
-                _ => None,
-            })
+    pub fn search_in_diff_from_cursor(&mut self) -> bool {
+        let pattern = self.search_buffer.clone();
+        if pattern.trim().is_empty() {
+            self.set_message("Search pattern is empty");
+            return false;
+        }
+
+        self.last_search_pattern = Some(pattern.clone());
+        self.search_in_diff(&pattern, self.diff_state.cursor_line, true, true)
     }
 
-    pub fn file_list_down(&mut self, n: usize) {
-        let visible_items = self.build_visible_items();
-        let max_idx = visible_items.len().saturating_sub(1);
-        let new_idx = (self.file_list_state.selected() + n).min(max_idx);
-        self.file_list_state.select(new_idx);
+    pub fn search_next_in_diff(&mut self) -> bool {
+        let Some(pattern) = self.last_search_pattern.clone() else {
+            self.set_message("No previous search");
+            return false;
+        };
+        self.search_in_diff(&pattern, self.diff_state.cursor_line, true, false)
     }
 
-    pub fn file_list_up(&mut self, n: usize) {
-        let new_idx = self.file_list_state.selected().saturating_sub(n);
-        self.file_list_state.select(new_idx);
+    pub fn search_prev_in_diff(&mut self) -> bool {
+        let Some(pattern) = self.last_search_pattern.clone() else {
+            self.set_message("No previous search");
+            return false;
+        };
+        self.search_in_diff(&pattern, self.diff_state.cursor_line, false, false)
     }
 
-    /// Scroll the file-list viewport down by `lines` without moving the
-    /// selection unless it would fall off the top of the viewport.
-    pub fn file_list_viewport_scroll_down(&mut self, lines: usize) {
-        let total = self.build_visible_items().len();
-        let viewport = self.file_list_state.viewport_height.max(1);
-        let max_offset = total.saturating_sub(viewport);
-        let new_offset = (self.file_list_state.list_state.offset() + lines).min(max_offset);
-        *self.file_list_state.list_state.offset_mut() = new_offset;
-        if self.file_list_state.selected() < new_offset {
-            self.file_list_state.select(new_offset);
+    fn search_in_diff(
+        &mut self,
+        pattern: &str,
+        start_idx: usize,
+        forward: bool,
+        include_current: bool,
+    ) -> bool {
+        let total_lines = self.total_lines();
+        if total_lines == 0 {
+            self.set_message("No diff content to search");
+            return false;
         }
-    }
 
-    /// Scroll the file-list viewport up by `lines` without moving the
-    /// selection unless it would fall off the bottom of the viewport.
-    pub fn file_list_viewport_scroll_up(&mut self, lines: usize) {
-        let viewport = self.file_list_state.viewport_height.max(1);
-        let new_offset = self
-            .file_list_state
-            .list_state
-            .offset()
-            .saturating_sub(lines);
-        *self.file_list_state.list_state.offset_mut() = new_offset;
-        let max_visible = (new_offset + viewport).saturating_sub(1);
-        if self.file_list_state.selected() > max_visible {
-            self.file_list_state.select(max_visible);
+        if forward {
+            let mut idx = start_idx.min(total_lines.saturating_sub(1));
+            if !include_current {
+                idx = idx.saturating_add(1);
+            }
+            for line_idx in idx..total_lines {
+                if let Some(text) = self.line_text_for_search(line_idx)
+                    && text.contains(pattern)
+                {
+                    self.diff_state.cursor_line = line_idx;
+                    self.ensure_cursor_visible();
+                    self.center_cursor();
+                    self.update_current_file_from_cursor();
+                    return true;
+                }
+            }
+        } else {
+            let mut idx = start_idx.min(total_lines.saturating_sub(1));
+            if !include_current {
+                idx = idx.saturating_sub(1);
+            }
+            let mut line_idx = idx;
+            loop {
+                if let Some(text) = self.line_text_for_search(line_idx)
+                    && text.contains(pattern)
+                {
+                    self.diff_state.cursor_line = line_idx;
+                    self.ensure_cursor_visible();
+                    self.center_cursor();
+                    self.update_current_file_from_cursor();
+                    return true;
+                }
+                if line_idx == 0 {
+                    break;
+                }
+                line_idx = line_idx.saturating_sub(1);
+            }
         }
-    }
 
-    pub fn diff_annotation_at_screen_row(&self, screen_row: u16) -> Option<usize> {
-        let inner = self.diff_inner_area?;
-        if screen_row < inner.y || screen_row >= inner.y + inner.height {
-            return None;
-        }
-        let rel = (screen_row - inner.y) as usize;
-        self.diff_row_to_annotation.get(rel).copied()
+        self.set_message(format!("No matches for \"{pattern}\""));
+        false
     }
 
-    pub fn file_list_idx_at_screen_row(&self, screen_row: u16) -> Option<usize> {
-        let inner = self.file_list_inner_area?;
-        if screen_row < inner.y || screen_row >= inner.y + inner.height {
-            return None;
+    /// Submit the search prompt against the file list, starting at the
+    /// selected row.
+    pub fn search_in_file_list_from_selection(&mut self) -> bool {
+        let pattern = self.search_buffer.clone();
+        if pattern.trim().is_empty() {
+            self.set_message("Search pattern is empty");
+            return false;
         }
-        let rel = (screen_row - inner.y) as usize;
-        let idx = self.file_list_state.list_state.offset() + rel;
-        let total = self.build_visible_items().len();
-        (idx < total).then_some(idx)
+
+        self.last_search_pattern = Some(pattern.clone());
+        self.search_in_file_list(&pattern, true, true)
     }
 
-    pub fn commit_list_idx_at_screen_row(&self, screen_row: u16) -> Option<usize> {
-        let inner = self.commit_list_inner_area?;
-        if screen_row < inner.y || screen_row >= inner.y + inner.height {
-            return None;
-        }
-        let rel = (screen_row - inner.y) as usize;
-        let idx = self.commit_list_scroll_offset + rel;
-        let total = match self.input_mode {
-            InputMode::CommitSelect => {
-                self.visible_commit_count + usize::from(self.can_show_more_commits())
-            }
-            _ => self.review_commits.len(),
+    pub fn search_next_in_file_list(&mut self) -> bool {
+        let Some(pattern) = self.last_search_pattern.clone() else {
+            self.set_message("No previous search");
+            return false;
         };
-        (idx < total).then_some(idx)
+        self.search_in_file_list(&pattern, true, false)
     }
 
-    /// Syncs `current_file_idx` so the file list selection follows when the
-    /// new cursor lands on an annotation belonging to a file.
-    pub fn move_cursor_to_annotation(&mut self, idx: usize) {
-        if idx >= self.line_annotations.len() {
-            return;
-        }
-        self.diff_state.cursor_line = idx;
-        if let Some(file_idx) = annotation_file_idx(&self.line_annotations[idx]) {
-            self.diff_state.current_file_idx = file_idx;
-        }
-        let viewport = self.diff_state.viewport_height.max(1);
-        if idx < self.diff_state.scroll_offset {
-            self.diff_state.scroll_offset = idx;
-        } else if idx >= self.diff_state.scroll_offset + viewport {
-            self.diff_state.scroll_offset = idx + 1 - viewport;
+    pub fn search_prev_in_file_list(&mut self) -> bool {
+        let Some(pattern) = self.last_search_pattern.clone() else {
+            self.set_message("No previous search");
+            return false;
+        };
+        self.search_in_file_list(&pattern, false, false)
+    }
+
+    /// Select the next visible file-list row whose path contains `pattern`.
+    /// Only the selection moves; the diff stays put until the file is opened.
+    fn search_in_file_list(&mut self, pattern: &str, forward: bool, include_current: bool) -> bool {
+        let items = self.build_visible_items();
+        let matches = |idx: &usize| match &items[*idx] {
+            FileTreeItem::Directory { path, .. } => path.contains(pattern),
+            FileTreeItem::File { file_idx, .. } => self.diff_files[*file_idx]
+                .display_path()
+                .to_string_lossy()
+                .contains(pattern),
+        };
+
+        let current = self.file_list_state.selected();
+        let found = if forward {
+            let start = if include_current {
+                current
+            } else {
+                current + 1
+            };
+            (start..items.len()).find(matches)
+        } else {
+            (0..current).rev().find(matches)
+        };
+
+        match found {
+            Some(idx) => {
+                self.file_list_state.select(idx);
+                true
+            }
+            None => {
+                self.set_message(format!("No matches for \"{pattern}\""));
+                false
+            }
         }
     }
 
-    /// In SBS, picks Old or New per `side`, falling back to the other pane
-    /// if the requested one is empty. Unified diff rows ignore `side`.
-    pub fn content_for_side(&self, ann_idx: usize, side: LineSide) -> Option<&str> {
-        let ann = self.line_annotations.get(ann_idx)?;
-        match ann {
+    fn line_text_for_search(&self, line_idx: usize) -> Option<String> {
+        match self.line_annotations.get(line_idx)? {
+            AnnotatedLine::ReviewCommentsHeader => Some("Review comments".to_string()),
+            AnnotatedLine::ReviewComment { comment_idx } => {
+                let comment = self.session.review_comments.get(*comment_idx)?;
+                Some(comment.content.clone())
+            }
+            AnnotatedLine::FileHeader { file_idx } => {
+                let file = self.diff_files.get(*file_idx)?;
+                Some(format!(
+                    "{} [{}]",
+                    file.display_path().display(),
+                    file.status.as_char()
+                ))
+            }
+            AnnotatedLine::FileMetadata {
+                file_idx,
+                line_idx: metadata_idx,
+            } => {
+                let file = self.diff_files.get(*file_idx)?;
+                file.metadata_lines().into_iter().nth(*metadata_idx)
+            }
+            AnnotatedLine::FileComment {
+                file_idx,
+                comment_idx,
+            } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                let review = self.session.files.get(path)?;
+                let comment = review.file_comments.get(*comment_idx)?;
+                Some(comment.content.clone())
+            }
+            AnnotatedLine::LineComment {
+                file_idx,
+                line,
+                comment_idx,
+                ..
+            } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                let review = self.session.files.get(path)?;
+                let comments = review.line_comments.get(line)?;
+                let comment = comments.get(*comment_idx)?;
+                Some(comment.content.clone())
+            }
+            AnnotatedLine::Expander { gap_id, direction } => {
+                let arrow = match direction {
+                    ExpandDirection::Down => "↓",
+                    ExpandDirection::Up => "↑",
+                    ExpandDirection::Both => "↕",
+                };
+                let gap = self.gap_size(gap_id)?;
+                let top_len = self.expanded_top.get(gap_id).map_or(0, |v| v.len());
+                let bot_len = self.expanded_bottom.get(gap_id).map_or(0, |v| v.len());
+                let remaining = (gap as usize).saturating_sub(top_len + bot_len);
+                let count = remaining.min(GAP_EXPAND_BATCH);
+                Some(format!("... {arrow} expand ({count} lines) ..."))
+            }
+            AnnotatedLine::HiddenLines { count, .. } => {
+                Some(format!("... {count} lines hidden ..."))
+            }
+            AnnotatedLine::ElidedContext { count, .. } => {
+                Some(format!("... {count} unchanged lines ..."))
+            }
+            AnnotatedLine::ExpandedContext {
+                gap_id,
+                line_idx: context_idx,
+            } => {
+                let content = self.get_expanded_line(gap_id, *context_idx)?;
+                Some(content.content.clone())
+            }
+            AnnotatedLine::HunkHeader { file_idx, hunk_idx } => {
+                let file = self.diff_files.get(*file_idx)?;
+                let hunk = file.hunks.get(*hunk_idx)?;
+                Some(hunk.header.clone())
+            }
             AnnotatedLine::DiffLine {
                 file_idx,
                 hunk_idx,
-                line_idx,
+                line_idx: diff_idx,
                 ..
             } => {
-                let line = self
-                    .diff_files
-                    .get(*file_idx)?
-                    .hunks
-                    .get(*hunk_idx)?
-                    .lines
-                    .get(*line_idx)?;
-                Some(line.content.as_str())
+                let file = self.diff_files.get(*file_idx)?;
+                let hunk = file.hunks.get(*hunk_idx)?;
+                let line = hunk.lines.get(*diff_idx)?;
+                Some(line.content.clone())
+            }
+            AnnotatedLine::BinaryOrEmpty { file_idx } => {
+                let file = self.diff_files.get(*file_idx)?;
+                if file.is_too_large {
+                    Some("(file too large to display)".to_string())
+                } else if file.is_binary {
+                    Some("(binary file)".to_string())
+                } else {
+                    Some(file.empty_label().to_string())
+                }
             }
             AnnotatedLine::SideBySideLine {
                 file_idx,
@@ -3947,5928 +5561,13013 @@ impl App {
                 add_line_idx,
                 ..
             } => {
-                let hunk = self.diff_files.get(*file_idx)?.hunks.get(*hunk_idx)?;
-                let add = add_line_idx
-                    .and_then(|i| hunk.lines.get(i))
-                    .map(|l| l.content.as_str());
-                let del = del_line_idx
-                    .and_then(|i| hunk.lines.get(i))
-                    .map(|l| l.content.as_str());
-                match side {
-                    LineSide::New => add.or(del),
-                    LineSide::Old => del.or(add),
-                }
+                let file = self.diff_files.get(*file_idx)?;
+                let hunk = file.hunks.get(*hunk_idx)?;
+
+                let del_content = del_line_idx
+                    .and_then(|idx| hunk.lines.get(idx))
+                    .map(|l| l.content.as_str())
+                    .unwrap_or("");
+                let add_content = add_line_idx
+                    .and_then(|idx| hunk.lines.get(idx))
+                    .map(|l| l.content.as_str())
+                    .unwrap_or("");
+                Some(format!("{} {}", del_content, add_content))
             }
-            AnnotatedLine::ExpandedContext { gap_id, line_idx } => self
-                .get_expanded_line(gap_id, *line_idx)
-                .map(|l| l.content.as_str()),
-            _ => None,
+            AnnotatedLine::HistoryComment { file_idx, idx } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                let entry = self.history_comments.get(path)?.get(*idx)?;
+                Some(entry.comment.content.clone())
+            }
+            AnnotatedLine::RemoteThreadLine { thread_idx } => {
+                let thread = self.forge_review_threads.get(*thread_idx)?;
+                // Search matches any text in the thread (including replies).
+                let mut bodies: Vec<String> =
+                    thread.comments.iter().map(|c| c.body.clone()).collect();
+                bodies.insert(0, format!("github {}", thread.path));
+                Some(bodies.join(" "))
+            }
+            AnnotatedLine::Spacing => None,
         }
     }
 
-    /// For annotations rendered outside the content gutter (hunk headers,
-    /// file headers): returns a clean copy text. The selection's char range
-    /// is meaningless for these — they're emitted whole or not at all.
-    fn atomic_text_for_annotation(&self, ann_idx: usize) -> Option<String> {
-        match self.line_annotations.get(ann_idx)? {
-            AnnotatedLine::HunkHeader { file_idx, hunk_idx } => {
-                let hunk = self.diff_files.get(*file_idx)?.hunks.get(*hunk_idx)?;
-                Some(hunk.header.clone())
-            }
-            AnnotatedLine::FileHeader { file_idx } => {
-                let file = self.diff_files.get(*file_idx)?;
-                if file.is_commit_message {
-                    Some("Commit Message".to_string())
-                } else {
-                    Some(format!(
-                        "{} [{}]",
-                        file.display_path().display(),
-                        file.status.as_char()
-                    ))
-                }
-            }
-            _ => None,
-        }
+    fn gap_size(&self, gap_id: &GapId) -> Option<u32> {
+        let file = self.diff_files.get(gap_id.file_idx)?;
+        let hunk = file.hunks.get(gap_id.hunk_idx)?;
+        let prev_hunk = if gap_id.hunk_idx > 0 {
+            file.hunks.get(gap_id.hunk_idx - 1)
+        } else {
+            None
+        };
+        Some(calculate_gap(
+            prev_hunk.map(|h| (&h.new_start, &h.new_count)),
+            hunk.new_start,
+        ))
     }
 
-    pub fn copy_visual_selection(&mut self) -> Result<usize> {
-        let Some(sel) = self.visual_selection else {
-            return Ok(0);
+    pub fn center_cursor(&mut self) {
+        let viewport = self.diff_state.viewport_height.max(1);
+        let half_viewport = viewport / 2;
+        let max_scroll = self.max_scroll_offset();
+        self.diff_state.scroll_offset = self
+            .diff_state
+            .cursor_line
+            .saturating_sub(half_viewport)
+            .min(max_scroll);
+    }
+
+    pub fn cursor_to_top(&mut self) {
+        let scroll_margin = self.diff_state.effective_scroll_margin(self.scroll_offset);
+        let max_scroll = self.max_scroll_offset();
+        self.diff_state.scroll_offset = self
+            .diff_state
+            .cursor_line
+            .saturating_sub(scroll_margin)
+            .min(max_scroll);
+    }
+
+    pub fn cursor_to_bottom(&mut self) {
+        let visible_lines = self.diff_state.effective_visible_lines();
+        let scroll_margin = self.diff_state.effective_scroll_margin(self.scroll_offset);
+        let max_scroll = self.max_scroll_offset();
+        self.diff_state.scroll_offset = self
+            .diff_state
+            .cursor_line
+            .saturating_sub(visible_lines.saturating_sub(1 + scroll_margin))
+            .min(max_scroll);
+    }
+
+    pub fn go_to_source_line(&mut self, target_lineno: u32, side: LineSide) {
+        let current_file = self.diff_state.current_file_idx;
+        let mut result = self.find_source_line_in_diff(target_lineno, side);
+        let side_label = match side {
+            LineSide::New => "",
+            LineSide::Old => " (old)",
         };
-        let (start, end) = sel.ordered();
-        let side = sel.anchor.side;
-        let mut out = String::new();
-        let mut emitted = 0usize;
-        for idx in start.annotation_idx..=end.annotation_idx {
-            let snippet = if let Some(content) = self.content_for_side(idx, side) {
-                let total = content.chars().count();
-                let (lo, hi) = sel.char_range(idx, total);
-                char_slice(content, lo, Some(hi)).to_string()
-            } else if let Some(text) = self.atomic_text_for_annotation(idx) {
-                text
-            } else {
-                continue;
-            };
-            if emitted > 0 {
-                out.push('\n');
+
+        // If the line isn't already annotated, see whether it falls inside a
+        // collapsed (or partially collapsed) gap between hunks. If so, expand
+        // *toward* the target from whichever side the cursor is on: cursor
+        // above the gap expands `Down` from the previous hunk, cursor at or
+        // below the gap expands `Up` from the next hunk. Either way the
+        // unreached half of the gap stays collapsed behind an expander.
+        if !matches!(result, FindSourceLineResult::Exact(_))
+            && self.expand_elision_containing(current_file, target_lineno, side)
+        {
+            result = self.find_source_line_in_diff(target_lineno, side);
+        }
+        if !matches!(result, FindSourceLineResult::Exact(_))
+            && let Some(gap_id) = self.find_gap_containing_lineno(current_file, target_lineno, side)
+        {
+            let (direction, limit) = self.expand_plan_to_reach(&gap_id, target_lineno, side);
+            if let Err(e) = self.expand_gap(gap_id, direction, limit) {
+                self.set_error(format!("Expand failed: {e}"));
+                return;
             }
-            out.push_str(&snippet);
-            emitted += 1;
+            result = self.find_source_line_in_diff(target_lineno, side);
         }
-        if out.is_empty() {
-            return Ok(0);
+
+        match result {
+            FindSourceLineResult::Exact(idx) | FindSourceLineResult::Nearest(idx) => {
+                self.diff_state.cursor_line = idx;
+                self.ensure_cursor_visible();
+                self.center_cursor();
+                self.update_current_file_from_cursor();
+                if matches!(result, FindSourceLineResult::Nearest(_)) {
+                    self.set_message(format!(
+                        "Line {target_lineno}{side_label} not in diff, jumped to nearest"
+                    ));
+                }
+            }
+            FindSourceLineResult::NotFound => {
+                self.set_warning(format!(
+                    "Line {target_lineno}{side_label} not found in current file"
+                ));
+            }
         }
-        let count = out.chars().count();
-        crate::output::copy_text_to_clipboard(&out)
-            .map_err(|e| TuicrError::Clipboard(format!("{e}")))?;
-        Ok(count)
     }
 
-    pub fn pane_geometry(&self, inner: ratatui::layout::Rect, side: LineSide) -> PaneGeom {
-        match self.diff_view_mode {
-            DiffViewMode::Unified => {
-                let content_width = (inner.width as usize).saturating_sub(UNIFIED_GUTTER as usize);
-                PaneGeom {
-                    content_x_start: inner.x + UNIFIED_GUTTER,
-                    content_x_end: inner.x + inner.width,
-                    content_width,
+    /// Like the free `find_source_line` but also resolves `ExpandedContext`
+    /// annotations through `get_expanded_line` so newly-revealed context lines
+    /// count toward the match.
+    fn find_source_line_in_diff(&self, target_lineno: u32, side: LineSide) -> FindSourceLineResult {
+        let current_file = self.diff_state.current_file_idx;
+        let mut best: Option<(usize, u32)> = None;
+
+        for (idx, annotation) in self.line_annotations.iter().enumerate() {
+            let (file_idx, candidate) = match annotation {
+                AnnotatedLine::DiffLine {
+                    file_idx,
+                    old_lineno,
+                    new_lineno,
+                    ..
+                }
+                | AnnotatedLine::SideBySideLine {
+                    file_idx,
+                    old_lineno,
+                    new_lineno,
+                    ..
+                } => {
+                    let c = match side {
+                        LineSide::New => *new_lineno,
+                        LineSide::Old => *old_lineno,
+                    };
+                    (*file_idx, c)
+                }
+                AnnotatedLine::ExpandedContext { gap_id, line_idx } => {
+                    let Some(line) = self.get_expanded_line(gap_id, *line_idx) else {
+                        continue;
+                    };
+                    let c = match side {
+                        LineSide::New => line.new_lineno,
+                        LineSide::Old => line.old_lineno,
+                    };
+                    (gap_id.file_idx, c)
                 }
+                _ => continue,
+            };
+            if file_idx != current_file {
+                continue;
             }
-            DiffViewMode::SideBySide => {
-                let half_w = (inner.width.saturating_sub(SBS_OVERHEAD) / 2) as usize;
-                match side {
-                    LineSide::Old => PaneGeom {
-                        content_x_start: inner.x + SBS_LEFT_GUTTER,
-                        content_x_end: inner.x + SBS_LEFT_GUTTER + half_w as u16,
-                        content_width: half_w,
-                    },
-                    LineSide::New => {
-                        let start = inner.x + SBS_OVERHEAD + half_w as u16;
-                        PaneGeom {
-                            content_x_start: start,
-                            content_x_end: start + half_w as u16,
-                            content_width: half_w,
-                        }
-                    }
+            if let Some(ln) = candidate {
+                let dist = ln.abs_diff(target_lineno);
+                if dist == 0 {
+                    return FindSourceLineResult::Exact(idx);
+                }
+                if best.is_none() || dist < best.unwrap().1 {
+                    best = Some((idx, dist));
                 }
             }
         }
+
+        match best {
+            Some((idx, _)) => FindSourceLineResult::Nearest(idx),
+            None => FindSourceLineResult::NotFound,
+        }
     }
 
-    pub fn side_at_x(
+    /// Return the gap (between-hunk or pre-first-hunk region) in `file_idx`
+    /// whose line range on `side` contains `target_lineno`, if any. Used by
+    /// `go_to_source_line` to auto-expand collapsed context when the user
+    /// jumps to a line that's hidden behind an expander.
+    fn find_gap_containing_lineno(
         &self,
-        inner: ratatui::layout::Rect,
-        x: u16,
-        ann_default: LineSide,
-    ) -> LineSide {
-        match self.diff_view_mode {
-            DiffViewMode::Unified => ann_default,
-            DiffViewMode::SideBySide => {
-                let half_w = inner.width.saturating_sub(SBS_OVERHEAD) / 2;
-                let divider = inner.x + SBS_LEFT_GUTTER + half_w;
-                if x < divider {
-                    LineSide::Old
-                } else {
-                    LineSide::New
-                }
+        file_idx: usize,
+        target_lineno: u32,
+        side: LineSide,
+    ) -> Option<GapId> {
+        let file = self.diff_files.get(file_idx)?;
+        for hunk_idx in 0..file.hunks.len() {
+            let hunk = &file.hunks[hunk_idx];
+            let prev_hunk = if hunk_idx > 0 {
+                Some(&file.hunks[hunk_idx - 1])
+            } else {
+                None
+            };
+            let (start, end) = match side {
+                LineSide::New => match prev_hunk {
+                    None => (1, hunk.new_start.saturating_sub(1)),
+                    Some(p) => (p.new_start + p.new_count, hunk.new_start.saturating_sub(1)),
+                },
+                LineSide::Old => match prev_hunk {
+                    None => (1, hunk.old_start.saturating_sub(1)),
+                    Some(p) => (p.old_start + p.old_count, hunk.old_start.saturating_sub(1)),
+                },
+            };
+            if start <= end && target_lineno >= start && target_lineno <= end {
+                return Some(GapId { file_idx, hunk_idx });
             }
         }
+        None
     }
 
-    pub fn cell_to_sel_point(&self, screen_col: u16, screen_row: u16) -> Option<SelPoint> {
-        let idx = self.diff_annotation_at_screen_row(screen_row)?;
-        let inner = self.diff_inner_area?;
-        let ann = self.line_annotations.get(idx)?;
-        let side = self.side_at_x(inner, screen_col, annotation_side_default(ann));
-
-        let zero_point = SelPoint {
-            annotation_idx: idx,
-            char_offset: 0,
-            side,
+    /// Choose an `ExpandDirection` and line-count limit so that expanding
+    /// `gap_id` reveals exactly the lines between the cursor and
+    /// `target_lineno` (on `side`). Cursor above the gap → `Down` from the
+    /// previous hunk; cursor at-or-below the gap → `Up` from the next hunk.
+    ///
+    /// `expand_gap` operates in new-side coordinates, so an old-side target
+    /// is translated to new-side using the offset that holds across an
+    /// unchanged-context gap: `new - old = hunk.new_start - hunk.old_start`
+    /// where `hunk` is the hunk immediately *after* the gap.
+    fn expand_plan_to_reach(
+        &self,
+        gap_id: &GapId,
+        target_lineno: u32,
+        side: LineSide,
+    ) -> (ExpandDirection, Option<usize>) {
+        let Some(file) = self.diff_files.get(gap_id.file_idx) else {
+            return (ExpandDirection::Both, None);
         };
-        let Some(content) = self.content_for_side(idx, side) else {
-            return Some(zero_point);
+        let Some(hunk) = file.hunks.get(gap_id.hunk_idx) else {
+            return (ExpandDirection::Both, None);
         };
-        let geom = self.pane_geometry(inner, side);
-        if geom.content_width == 0 {
-            return Some(zero_point);
-        }
-        let last_col = geom.content_x_end.saturating_sub(1);
-        let col = screen_col.clamp(geom.content_x_start, last_col);
-        let col_in_row = (col - geom.content_x_start) as usize;
-
-        let rel = (screen_row - inner.y) as usize;
-        let mut walker = rel;
-        while walker > 0 && self.diff_row_to_annotation.get(walker - 1).copied() == Some(idx) {
-            walker -= 1;
-        }
-        let which_row = rel - walker;
-        let total_chars = content.chars().count();
-        let char_offset = (which_row * geom.content_width + col_in_row).min(total_chars);
-        Some(SelPoint {
-            annotation_idx: idx,
-            char_offset,
-            side,
-        })
-    }
-
-    /// Mirrors `ensure_cursor_visible`'s notion of visibility (uses the
-    /// renderer's `visible_line_count` when present so wrapping is honored).
-    pub fn is_cursor_visible(&self) -> bool {
-        let visible = if self.diff_state.visible_line_count > 0 {
-            self.diff_state.visible_line_count
+        let prev_hunk = if gap_id.hunk_idx > 0 {
+            Some(&file.hunks[gap_id.hunk_idx - 1])
         } else {
-            self.diff_state.viewport_height.max(1)
+            None
+        };
+        let gap_start_new = match prev_hunk {
+            None => 1,
+            Some(p) => p.new_start + p.new_count,
+        };
+        let gap_end_new = hunk.new_start.saturating_sub(1);
+        // offset := new - old, constant across the unchanged context gap
+        let offset_new_minus_old = hunk.new_start as i64 - hunk.old_start as i64;
+        let target_new = match side {
+            LineSide::New => target_lineno as i64,
+            LineSide::Old => target_lineno as i64 + offset_new_minus_old,
         };
-        let cursor = self.diff_state.cursor_line;
-        cursor >= self.diff_state.scroll_offset && cursor < self.diff_state.scroll_offset + visible
-    }
-
-    pub fn jump_to_file(&mut self, idx: usize) {
-        use std::path::Path;
-
-        if idx < self.diff_files.len() {
-            self.diff_state.current_file_idx = idx;
-            self.diff_state.cursor_line = self.calculate_file_scroll_offset(idx);
-            let max_scroll = self.max_scroll_offset();
-            self.diff_state.scroll_offset = self.diff_state.cursor_line.min(max_scroll);
 
-            let file_path = self.diff_files[idx].display_path().clone();
-            let mut current = file_path.parent();
-            while let Some(parent) = current {
-                if parent != Path::new("") {
-                    self.expanded_dirs
-                        .insert(parent.to_string_lossy().to_string());
-                }
-                current = parent.parent();
-            }
+        let cursor_below_gap = self
+            .find_hunk_header_annotation_idx(gap_id)
+            .is_some_and(|h| self.diff_state.cursor_line >= h);
 
-            if let Some(tree_idx) = self.file_idx_to_tree_idx(idx) {
-                self.file_list_state.select(tree_idx);
-            }
+        if cursor_below_gap {
+            let bot_len = self.expanded_bottom.get(gap_id).map_or(0, |v| v.len()) as i64;
+            let inner_end = gap_end_new as i64 - bot_len;
+            let limit = (inner_end - target_new + 1).max(0) as usize;
+            (ExpandDirection::Up, Some(limit))
+        } else {
+            let top_len = self.expanded_top.get(gap_id).map_or(0, |v| v.len()) as i64;
+            let inner_start = gap_start_new as i64 + top_len;
+            let limit = (target_new - inner_start + 1).max(0) as usize;
+            (ExpandDirection::Down, Some(limit))
         }
     }
 
-    pub fn jump_to_bottom(&mut self) {
-        let max_line = self.max_cursor_line();
-        self.diff_state.cursor_line = max_line;
-        // Position so the last navigable line is at the bottom of the viewport
-        let viewport = self.diff_state.viewport_height.max(1);
-        self.diff_state.scroll_offset = (max_line + 1).saturating_sub(viewport);
-        self.update_current_file_from_cursor();
+    fn find_hunk_header_annotation_idx(&self, gap_id: &GapId) -> Option<usize> {
+        self.line_annotations
+            .iter()
+            .enumerate()
+            .find_map(|(idx, a)| match a {
+                AnnotatedLine::HunkHeader { file_idx, hunk_idx }
+                    if *file_idx == gap_id.file_idx && *hunk_idx == gap_id.hunk_idx =>
+                {
+                    Some(idx)
+                }
+                _ => None,
+            })
     }
 
-    pub fn next_file(&mut self) {
+    pub fn file_list_down(&mut self, n: usize) {
         let visible_items = self.build_visible_items();
-        let current_file_idx = self.diff_state.current_file_idx;
-
-        for item in &visible_items {
-            if let FileTreeItem::File { file_idx, .. } = item
-                && *file_idx > current_file_idx
-            {
-                self.jump_to_file(*file_idx);
-                return;
-            }
-        }
+        let max_idx = visible_items.len().saturating_sub(1);
+        let new_idx = (self.file_list_state.selected() + n).min(max_idx);
+        self.file_list_state.select(new_idx);
     }
 
-    pub fn prev_file(&mut self) {
-        let visible_items = self.build_visible_items();
-        let current_file_idx = self.diff_state.current_file_idx;
+    pub fn file_list_up(&mut self, n: usize) {
+        let new_idx = self.file_list_state.selected().saturating_sub(n);
+        self.file_list_state.select(new_idx);
+    }
 
-        for item in visible_items.iter().rev() {
-            if let FileTreeItem::File { file_idx, .. } = item
-                && *file_idx < current_file_idx
-            {
-                self.jump_to_file(*file_idx);
-                return;
-            }
+    /// Scroll the file-list viewport down by `lines` without moving the
+    /// selection unless it would fall off the top of the viewport.
+    pub fn file_list_viewport_scroll_down(&mut self, lines: usize) {
+        let total = self.build_visible_items().len();
+        let viewport = self.file_list_state.viewport_height.max(1);
+        let max_offset = total.saturating_sub(viewport);
+        let new_offset = (self.file_list_state.list_state.offset() + lines).min(max_offset);
+        *self.file_list_state.list_state.offset_mut() = new_offset;
+        if self.file_list_state.selected() < new_offset {
+            self.file_list_state.select(new_offset);
         }
     }
 
-    fn file_idx_to_tree_idx(&self, target_file_idx: usize) -> Option<usize> {
-        let visible_items = self.build_visible_items();
-        for (tree_idx, item) in visible_items.iter().enumerate() {
-            if let FileTreeItem::File { file_idx, .. } = item
-                && *file_idx == target_file_idx
-            {
-                return Some(tree_idx);
-            }
+    /// Scroll the file-list viewport up by `lines` without moving the
+    /// selection unless it would fall off the bottom of the viewport.
+    pub fn file_list_viewport_scroll_up(&mut self, lines: usize) {
+        let viewport = self.file_list_state.viewport_height.max(1);
+        let new_offset = self
+            .file_list_state
+            .list_state
+            .offset()
+            .saturating_sub(lines);
+        *self.file_list_state.list_state.offset_mut() = new_offset;
+        let max_visible = (new_offset + viewport).saturating_sub(1);
+        if self.file_list_state.selected() > max_visible {
+            self.file_list_state.select(max_visible);
         }
-        None
     }
 
-    pub fn next_hunk(&mut self) {
-        // Find the next hunk header position after current cursor
-        let mut cumulative = self.review_comments_render_height();
-        for file in &self.diff_files {
-            let path = file.display_path();
-
-            // File header
-            cumulative += 1;
-
-            // If file is reviewed, skip all content
-            if self.session.is_file_reviewed(path) {
-                continue;
-            }
-
-            // File comments
-            if let Some(review) = self.session.files.get(path) {
-                cumulative += review.file_comments.len();
-            }
-
-            if file.is_binary || file.hunks.is_empty() {
-                cumulative += 1; // "(binary file)" or "(no changes)"
-            } else {
-                for hunk in &file.hunks {
-                    // This is a hunk header position
-                    if cumulative > self.diff_state.cursor_line {
-                        self.diff_state.cursor_line = cumulative;
-                        self.ensure_cursor_visible();
-                        self.update_current_file_from_cursor();
-                        return;
-                    }
-                    cumulative += 1; // hunk header
-                    cumulative += hunk.lines.len(); // diff lines
-                }
-            }
-            cumulative += 1; // spacing
+    pub fn diff_annotation_at_screen_row(&self, screen_row: u16) -> Option<usize> {
+        let inner = self.diff_inner_area?;
+        if screen_row < inner.y || screen_row >= inner.y + inner.height {
+            return None;
         }
+        let rel = (screen_row - inner.y) as usize;
+        self.diff_row_to_annotation.get(rel).copied()
     }
 
-    pub fn prev_hunk(&mut self) {
-        // Find the previous hunk header position before current cursor
-        let mut hunk_positions: Vec<usize> = Vec::new();
-        let mut cumulative = self.review_comments_render_height();
-
-        for file in &self.diff_files {
-            let path = file.display_path();
-
-            cumulative += 1; // File header
+    pub fn file_list_idx_at_screen_row(&self, screen_row: u16) -> Option<usize> {
+        let inner = self.file_list_inner_area?;
+        if screen_row < inner.y || screen_row >= inner.y + inner.height {
+            return None;
+        }
+        let rel = (screen_row - inner.y) as usize;
+        let idx = self.file_list_state.list_state.offset() + rel;
+        let total = self.build_visible_items().len();
+        (idx < total).then_some(idx)
+    }
 
-            // If file is reviewed, skip all content
-            if self.session.is_file_reviewed(path) {
-                continue;
+    pub fn commit_list_idx_at_screen_row(&self, screen_row: u16) -> Option<usize> {
+        let inner = self.commit_list_inner_area?;
+        if screen_row < inner.y || screen_row >= inner.y + inner.height {
+            return None;
+        }
+        let rel = (screen_row - inner.y) as usize;
+        let idx = self.commit_list_scroll_offset + rel;
+        let total = match self.input_mode {
+            InputMode::CommitSelect => {
+                self.visible_commit_count + usize::from(self.can_show_more_commits())
             }
+            _ => self.review_commits.len(),
+        };
+        (idx < total).then_some(idx)
+    }
 
-            if let Some(review) = self.session.files.get(path) {
-                cumulative += review.file_comments.len();
-            }
+    /// Syncs `current_file_idx` so the file list selection follows when the
+    /// new cursor lands on an annotation belonging to a file.
+    pub fn move_cursor_to_annotation(&mut self, idx: usize) {
+        if idx >= self.line_annotations.len() {
+            return;
+        }
+        self.diff_state.cursor_line = idx;
+        if let Some(file_idx) = annotation_file_idx(&self.line_annotations[idx]) {
+            self.diff_state.current_file_idx = file_idx;
+        }
+        let viewport = self.diff_state.viewport_height.max(1);
+        if idx < self.diff_state.scroll_offset {
+            self.diff_state.scroll_offset = idx;
+        } else if idx >= self.diff_state.scroll_offset + viewport {
+            self.diff_state.scroll_offset = idx + 1 - viewport;
+        }
+    }
 
-            if file.is_binary || file.hunks.is_empty() {
-                cumulative += 1;
-            } else {
-                for hunk in &file.hunks {
-                    hunk_positions.push(cumulative);
-                    cumulative += 1;
-                    cumulative += hunk.lines.len();
+    /// In SBS, picks Old or New per `side`, falling back to the other pane
+    /// if the requested one is empty. Unified diff rows ignore `side`.
+    pub fn content_for_side(&self, ann_idx: usize, side: LineSide) -> Option<&str> {
+        let ann = self.line_annotations.get(ann_idx)?;
+        match ann {
+            AnnotatedLine::DiffLine {
+                file_idx,
+                hunk_idx,
+                line_idx,
+                ..
+            } => {
+                let line = self
+                    .diff_files
+                    .get(*file_idx)?
+                    .hunks
+                    .get(*hunk_idx)?
+                    .lines
+                    .get(*line_idx)?;
+                Some(line.content.as_str())
+            }
+            AnnotatedLine::SideBySideLine {
+                file_idx,
+                hunk_idx,
+                del_line_idx,
+                add_line_idx,
+                ..
+            } => {
+                let hunk = self.diff_files.get(*file_idx)?.hunks.get(*hunk_idx)?;
+                let add = add_line_idx
+                    .and_then(|i| hunk.lines.get(i))
+                    .map(|l| l.content.as_str());
+                let del = del_line_idx
+                    .and_then(|i| hunk.lines.get(i))
+                    .map(|l| l.content.as_str());
+                match side {
+                    LineSide::New => add.or(del),
+                    LineSide::Old => del.or(add),
                 }
             }
-            cumulative += 1;
+            AnnotatedLine::ExpandedContext { gap_id, line_idx } => self
+                .get_expanded_line(gap_id, *line_idx)
+                .map(|l| l.content.as_str()),
+            _ => None,
         }
+    }
 
-        // Find the last hunk position before current cursor
-        for &pos in hunk_positions.iter().rev() {
-            if pos < self.diff_state.cursor_line {
-                self.diff_state.cursor_line = pos;
-                self.ensure_cursor_visible();
-                self.update_current_file_from_cursor();
-                return;
+    /// For annotations rendered outside the content gutter (hunk headers,
+    /// file headers): returns a clean copy text. The selection's char range
+    /// is meaningless for these — they're emitted whole or not at all.
+    fn atomic_text_for_annotation(&self, ann_idx: usize) -> Option<String> {
+        match self.line_annotations.get(ann_idx)? {
+            AnnotatedLine::HunkHeader { file_idx, hunk_idx } => {
+                let hunk = self.diff_files.get(*file_idx)?.hunks.get(*hunk_idx)?;
+                Some(hunk.header.clone())
+            }
+            AnnotatedLine::FileHeader { file_idx } => {
+                let file = self.diff_files.get(*file_idx)?;
+                if file.is_commit_message {
+                    Some("Commit Message".to_string())
+                } else {
+                    Some(format!(
+                        "{} [{}]",
+                        file.display_path().display(),
+                        file.status.as_char()
+                    ))
+                }
             }
+            _ => None,
         }
-
-        // If no previous hunk, go to start
-        self.diff_state.cursor_line = 0;
-        self.ensure_cursor_visible();
-        self.update_current_file_from_cursor();
     }
 
-    fn calculate_file_scroll_offset(&self, file_idx: usize) -> usize {
-        let mut offset = self.review_comments_render_height();
-        for (i, file) in self.diff_files.iter().enumerate() {
-            if i == file_idx {
-                break;
+    pub fn copy_visual_selection(&mut self) -> Result<usize> {
+        let Some(sel) = self.visual_selection else {
+            return Ok(0);
+        };
+        let (start, end) = sel.ordered();
+        let side = sel.anchor.side;
+        let mut out = String::new();
+        let mut emitted = 0usize;
+        for idx in start.annotation_idx..=end.annotation_idx {
+            let snippet = if let Some(content) = self.content_for_side(idx, side) {
+                let total = content.chars().count();
+                let (lo, hi) = sel.char_range(idx, total);
+                char_slice(content, lo, Some(hi)).to_string()
+            } else if let Some(text) = self.atomic_text_for_annotation(idx) {
+                text
+            } else {
+                continue;
+            };
+            if emitted > 0 {
+                out.push('\n');
             }
-            offset += self.file_render_height(i, file);
+            out.push_str(&snippet);
+            emitted += 1;
         }
-        offset
+        if out.is_empty() {
+            return Ok(0);
+        }
+        let count = out.chars().count();
+        crate::output::copy_text_to_clipboard(&out)
+            .map_err(|e| TuicrError::Clipboard(format!("{e}")))?;
+        Ok(count)
     }
 
-    fn review_comments_render_height(&self) -> usize {
-        let mut height = 1; // Header line
-        for comment in &self.session.review_comments {
-            height += Self::comment_display_lines(comment);
+    /// `:export <path>`: write the review to `path`, as JSON for `.json`, a
+    /// SARIF log of ISSUE comments for `.sarif`, a JUnit report of them for
+    /// `.xml`, reviewdog diagnostics for `.rdjson`/`.rdjsonl`, a standalone
+    /// HTML page for `.html`, a quoted mailing-list reply for `.eml`, the
+    /// diff with comments interleaved for `.annotated.patch` and a Markdown
+    /// report otherwise (with frontmatter for `.notes.md`).
+    pub fn export_report(&mut self, path: &str) {
+        let started_at = Instant::now();
+        let path = match &self.export_format {
+            Some(format) if Path::new(path).extension().is_none() => format!("{path}.{format}"),
+            _ => path.to_string(),
+        };
+        self.write_report(&path);
+        self.notify_finished(Operation::Export, started_at);
+    }
+
+    fn write_report(&mut self, path: &str) {
+        if !self.ensure_vcs_idle() {
+            return;
         }
-        if self.input_mode == InputMode::Comment
-            && self.comment_is_review_level
-            && self.editing_comment_id.is_none()
-        {
-            // Header + one content line + footer
-            height += 3;
+        let path = PathBuf::from(path);
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let is_annotated_patch = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".annotated.patch"));
+        if matches!(extension, Some("patch" | "diff")) && !is_annotated_patch {
+            self.export_suggestions_patch(&path);
+            return;
+        }
+        let is_gerrit = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".gerrit.json"));
+        let result = if is_annotated_patch {
+            crate::output::export_annotated_patch_to_file(
+                &path,
+                &self.session,
+                &self.diff_source,
+                &self.diff_files,
+                &self.comment_types,
+            )
+        } else if is_gerrit {
+            crate::output::export_gerrit_to_file(
+                &path,
+                &self.session,
+                &self.diff_files,
+                &self.comment_types,
+            )
+        } else if extension == Some("json") {
+            let (base_sha, head_sha) = self.diff_shas();
+            let anchors = crate::output::json::DiffAnchors {
+                base_sha: base_sha.as_deref(),
+                head_sha: head_sha.as_deref(),
+                diff_files: &self.diff_files,
+            };
+            crate::output::export_json_to_file(&path, &self.session, &self.comment_types, anchors)
+        } else if extension == Some("sarif") {
+            crate::output::export_sarif_to_file(&path, &self.session)
+        } else if extension == Some("xml") {
+            crate::output::export_junit_to_file(&path, &self.session)
+        } else if matches!(extension, Some("rdjson" | "rdjsonl")) {
+            crate::output::export_rdjson_to_file(&path, &self.session, &self.comment_types)
+        } else if extension == Some("eml") {
+            crate::output::export_email_to_file(
+                &path,
+                &self.session,
+                &self.diff_source,
+                &self.diff_files,
+                &self.comment_types,
+            )
+        } else if matches!(extension, Some("html" | "htm")) {
+            crate::output::export_html_to_file(
+                &path,
+                &self.session,
+                &self.diff_source,
+                &self.diff_files,
+                &self.comment_types,
+                &self.theme,
+            )
+        } else if let Some(template) = self.export_template.as_ref().filter(|_| {
+            // A `.notes.md` path asks for the notes report over the template
+            crate::output::report::ReportFlavor::for_path(&path)
+                == crate::output::report::ReportFlavor::Plain
+        }) {
+            crate::output::export_template_to_file(
+                &path,
+                template,
+                &self.session,
+                &self.diff_source,
+                &self.comment_types,
+            )
+        } else {
+            crate::output::export_report_to_file(
+                &path,
+                &self.session,
+                &self.diff_source,
+                &self.diff_files,
+                &self.comment_types,
+                self.conventional_comments,
+            )
+        };
+        match result {
+            Ok(()) => {
+                self.set_message(format!("Review written to {}", path.display()));
+                self.record_findings();
+            }
+            Err(e) => self.set_warning(format!("{e}")),
         }
-        height
     }
 
-    fn file_render_height(&self, file_idx: usize, file: &DiffFile) -> usize {
-        let path = file.display_path();
+    /// `:chat [slack|teams|post]`: copy a short summary of the review for a
+    /// chat channel, or post it to the configured `chat_webhook`. The
+    /// flavor defaults to the one the webhook expects, else Slack.
+    pub fn chat_command(&mut self, arg: &str) {
+        use crate::output::chat::{ChatFlavor, generate_chat_summary, post_to_webhook};
 
-        // If reviewed, only show header (1 line total)
-        if self.session.is_file_reviewed(path) {
-            return 1;
+        let configured = self.chat_webhook.as_deref().map(ChatFlavor::for_webhook);
+        let (flavor, post) = match arg {
+            "" => (configured.unwrap_or(ChatFlavor::Slack), false),
+            "post" => match configured {
+                Some(flavor) => (flavor, true),
+                None => {
+                    self.set_warning("Set chat_webhook in the config to post summaries");
+                    return;
+                }
+            },
+            other => match ChatFlavor::parse(other) {
+                Some(flavor) => (flavor, false),
+                None => {
+                    self.set_warning(format!("Unknown chat flavor '{other}' (slack, teams)"));
+                    return;
+                }
+            },
+        };
+        let summary = generate_chat_summary(
+            &self.session,
+            &self.diff_source,
+            &self.comment_types,
+            flavor,
+        );
+        if post {
+            let url = self.chat_webhook.as_deref().unwrap_or_default();
+            match post_to_webhook(url, &summary) {
+                Ok(()) => self.set_message("Review summary posted to chat"),
+                Err(e) => self.set_error(format!("Chat post failed: {e}")),
+            }
+        } else {
+            match crate::output::copy_text_to_clipboard(&summary) {
+                Ok(_) => self.set_message("Review summary copied to clipboard"),
+                Err(e) => self.set_error(format!("Copy failed: {e}")),
+            }
         }
+    }
 
-        let header_lines = 1; // File header
-        let spacing_lines = 1; // Blank line between files
-        let mut content_lines = 0;
-        let mut comment_lines = 0;
-
-        if let Some(review) = self.session.files.get(path) {
-            for comment in &review.file_comments {
-                comment_lines += Self::comment_display_lines(comment);
+    /// Commits the diff runs between: its old side's commit and, when it
+    /// holds no uncommitted changes, its newest commit.
+    fn diff_shas(&self) -> (Option<String>, Option<String>) {
+        match &self.diff_source {
+            DiffSource::PullRequest(pr) => (
+                Some(pr.base_sha.clone()),
+                self.session
+                    .pr_session_key
+                    .as_ref()
+                    .map(|key| key.head_sha.clone()),
+            ),
+            DiffSource::CommitRange(ids) | DiffSource::StagedUnstagedAndCommits(ids) => {
+                let base = ids
+                    .first()
+                    .and_then(|oldest| self.vcs.parent_commit(oldest).ok().flatten());
+                let head = match self.diff_source {
+                    DiffSource::CommitRange(_) => ids.last().cloned(),
+                    _ => None,
+                };
+                (base, head)
             }
+            _ => (Some(self.session.base_commit.clone()), None),
         }
+    }
 
-        if file.is_binary || file.hunks.is_empty() {
-            content_lines = 1;
+    /// `:import <path>`: merge another tuicr JSON export into the session.
+    pub fn import_review(&mut self, path: &str) {
+        let path = PathBuf::from(path);
+        let result = std::fs::read_to_string(&path)
+            .map_err(crate::error::TuicrError::from)
+            .and_then(|content| {
+                crate::output::json::merge_json_review(&mut self.session, &content)
+            });
+        let summary = match result {
+            Ok(summary) => summary,
+            Err(e) => {
+                self.set_warning(format!("Failed to import {}: {e}", path.display()));
+                return;
+            }
+        };
+        if summary.comments > 0 || summary.reviewed > 0 {
+            self.mark_dirty();
+            self.rebuild_annotations();
+        }
+        let mut details = Vec::new();
+        if summary.duplicates > 0 {
+            details.push(format!("{} duplicates", summary.duplicates));
+        }
+        if summary.reviewed > 0 {
+            details.push(format!("{} files marked reviewed", summary.reviewed));
+        }
+        if summary.skipped_files > 0 {
+            details.push(format!(
+                "{} files not in this review",
+                summary.skipped_files
+            ));
+        }
+        let details = if details.is_empty() {
+            String::new()
         } else {
-            let line_comments = self.session.files.get(path).map(|r| &r.line_comments);
-
-            for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
-                // Calculate gap before this hunk
-                let prev_hunk = if hunk_idx > 0 {
-                    file.hunks.get(hunk_idx - 1)
-                } else {
-                    None
-                };
-                let gap = calculate_gap(
-                    prev_hunk.map(|h| (&h.new_start, &h.new_count)),
-                    hunk.new_start,
-                );
+            format!(" ({})", details.join(", "))
+        };
+        self.set_message(format!(
+            "Merged {} comments from {}{details}",
+            summary.comments,
+            path.display()
+        ));
+    }
 
-                let gap_id = GapId { file_idx, hunk_idx };
+    /// `:session merge [union|intersect] <file>`: fold another reviewer's
+    /// saved session (or JSON export) into this one and list where the two
+    /// disagree.
+    pub fn merge_session_file(&mut self, args: &str) {
+        use crate::model::merge::{ReviewedPolicy, merge_session};
 
-                if gap > 0 {
-                    let top_len = self.expanded_top.get(&gap_id).map_or(0, |v| v.len());
-                    let bot_len = self.expanded_bottom.get(&gap_id).map_or(0, |v| v.len());
-                    let remaining = (gap as usize).saturating_sub(top_len + bot_len);
-                    content_lines += top_len + bot_len;
-                    content_lines += gap_annotation_line_count(hunk_idx == 0, remaining);
-                }
+        let (policy, path) = match args.split_once(' ') {
+            Some((first, rest)) => match ReviewedPolicy::from_id(first) {
+                Some(policy) => (policy, rest.trim()),
+                None => (ReviewedPolicy::default(), args),
+            },
+            None => (ReviewedPolicy::default(), args),
+        };
+        if path.is_empty() {
+            self.set_warning("Usage: :session merge [union|intersect] <file>");
+            return;
+        }
+        let path = PathBuf::from(path);
+        let theirs = match crate::view::load(&path) {
+            Ok(session) => session,
+            Err(e) => {
+                self.set_warning(format!("Failed to merge {}: {e}", path.display()));
+                return;
+            }
+        };
+        let summary = merge_session(&mut self.session, &theirs, policy);
+        if summary.changed() {
+            self.mark_dirty();
+            self.rebuild_annotations();
+        }
 
-                // Hunk header + diff lines
-                content_lines += 1; // Hunk header
+        let who = theirs
+            .reviewer
+            .as_ref()
+            .map(|r| r.name.clone())
+            .unwrap_or_else(|| "the other reviewer".to_string());
+        let mut output = vec![format!(
+            "Merged {} comments from {who} ({} duplicates)",
+            summary.comments, summary.duplicates
+        )];
+        if summary.reviewed > 0 {
+            output.push(format!("{} files marked reviewed", summary.reviewed));
+        }
+        if summary.unreviewed > 0 {
+            output.push(format!("{} files no longer reviewed", summary.unreviewed));
+        }
+        if !summary.skipped_files.is_empty() {
+            output.push(String::new());
+            output.push(format!(
+                "Not in this review ({}):",
+                summary.skipped_files.len()
+            ));
+            output.extend(
+                summary
+                    .skipped_files
+                    .iter()
+                    .map(|p| format!("  {}", p.display())),
+            );
+        }
+        output.push(String::new());
+        if summary.conflicts.is_empty() {
+            output.push("No conflicts".to_string());
+        } else {
+            output.push(format!("Conflicts ({}):", summary.conflicts.len()));
+            output.extend(summary.conflicts.iter().map(|c| format!("  {c}")));
+        }
+        self.command_output = Some(CommandOutput {
+            command: format!("session merge {}", path.display()),
+            failed: None,
+            output: output.join("\n"),
+        });
+        self.open_overlay(InputMode::Help);
+        self.help_state.scroll_offset = 0;
+    }
 
-                // Count diff lines based on view mode
-                match self.diff_view_mode {
-                    DiffViewMode::Unified => {
-                        for diff_line in &hunk.lines {
-                            content_lines += 1;
-
-                            if let Some(line_comments) = line_comments {
-                                if let Some(old_ln) = diff_line.old_lineno
-                                    && let Some(comments) = line_comments.get(&old_ln)
-                                {
-                                    for comment in comments {
-                                        if comment.side == Some(LineSide::Old) {
-                                            comment_lines += Self::comment_display_lines(comment);
-                                        }
-                                    }
-                                }
-
-                                if let Some(new_ln) = diff_line.new_lineno
-                                    && let Some(comments) = line_comments.get(&new_ln)
-                                {
-                                    for comment in comments {
-                                        if comment.side != Some(LineSide::Old) {
-                                            comment_lines += Self::comment_display_lines(comment);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    DiffViewMode::SideBySide => {
-                        use crate::model::LineOrigin;
-                        // Side-by-side mode: pair deletions with following additions
-                        let lines = &hunk.lines;
-                        let mut i = 0;
-                        while i < lines.len() {
-                            let diff_line = &lines[i];
-
-                            match diff_line.origin {
-                                LineOrigin::Context => {
-                                    content_lines += 1;
-
-                                    // Comments for context line
-                                    if let Some(line_comments) = line_comments
-                                        && let Some(new_ln) = diff_line.new_lineno
-                                        && let Some(comments) = line_comments.get(&new_ln)
-                                    {
-                                        for comment in comments {
-                                            if comment.side != Some(LineSide::Old) {
-                                                comment_lines +=
-                                                    Self::comment_display_lines(comment);
-                                            }
-                                        }
-                                    }
-                                    i += 1;
-                                }
-                                LineOrigin::Deletion => {
-                                    // Find consecutive deletions
-                                    let del_start = i;
-                                    let mut del_end = i + 1;
-                                    while del_end < lines.len()
-                                        && lines[del_end].origin == LineOrigin::Deletion
-                                    {
-                                        del_end += 1;
-                                    }
-
-                                    // Find consecutive additions following deletions
-                                    let add_start = del_end;
-                                    let mut add_end = add_start;
-                                    while add_end < lines.len()
-                                        && lines[add_end].origin == LineOrigin::Addition
-                                    {
-                                        add_end += 1;
-                                    }
-
-                                    let del_count = del_end - del_start;
-                                    let add_count = add_end - add_start;
-                                    // Paired lines use max of the two counts
-                                    content_lines += del_count.max(add_count);
+    /// `:checkpoint <label>` snapshots the review state, `:checkpoint diff
+    /// <label>` shows what changed since and `:checkpoint restore <label>`
+    /// asks to roll back to it. `:checkpoints` lists them.
+    pub fn checkpoint_command(&mut self, args: &str) {
+        let (subcommand, label) = match args.trim().split_once(' ') {
+            Some((sub @ ("diff" | "restore"), label)) => (sub, label.trim()),
+            _ if matches!(args.trim(), "diff" | "restore") => {
+                self.set_warning("Usage: :checkpoint diff|restore <label>");
+                return;
+            }
+            _ => ("", args.trim()),
+        };
+        if label.is_empty() {
+            self.list_checkpoints();
+            return;
+        }
+        let found = self
+            .session
+            .checkpoints
+            .iter()
+            .find(|c| c.label == label)
+            .cloned();
+        let taken_at = |checkpoint: &Checkpoint| {
+            checkpoint
+                .at
+                .with_timezone(&chrono::Local)
+                .format("%b %-d %H:%M")
+                .to_string()
+        };
+        match (subcommand, found) {
+            ("", _) => {
+                if !self.ensure_session_writable() {
+                    return;
+                }
+                let replaced = self.session.checkpoints.iter().any(|c| c.label == label);
+                self.session.checkpoints.retain(|c| c.label != label);
+                self.session
+                    .checkpoints
+                    .push(Checkpoint::capture(label, &self.session));
+                self.mark_dirty();
+                self.set_message(if replaced {
+                    format!("Checkpoint '{label}' replaced")
+                } else {
+                    format!("Checkpoint '{label}' taken")
+                });
+            }
+            (_, None) => self.set_warning(format!("No checkpoint '{label}'")),
+            ("diff", Some(checkpoint)) => {
+                let diff = checkpoint.diff(&self.session);
+                let mut output = vec![format!(
+                    "Since checkpoint '{label}' ({}):",
+                    taken_at(&checkpoint)
+                )];
+                if diff.is_empty() {
+                    output.push("No changes".to_string());
+                } else {
+                    output.extend(diff.lines());
+                }
+                self.command_output = Some(CommandOutput {
+                    command: format!("checkpoint diff {label}"),
+                    failed: None,
+                    output: output.join("\n"),
+                });
+                self.open_overlay(InputMode::Help);
+                self.help_state.scroll_offset = 0;
+            }
+            (_, Some(checkpoint)) => {
+                if !self.ensure_session_writable() {
+                    return;
+                }
+                let message = format!(
+                    "Roll back to checkpoint '{label}' from {}?",
+                    taken_at(&checkpoint)
+                );
+                let action = ConfirmAction::RestoreCheckpoint(label.to_string());
+                self.open_confirm(ConfirmDialog::with_message(message, action));
+            }
+        }
+    }
 
-                                    // Count comments for all deletions and additions in this pair
-                                    if let Some(line_comments) = line_comments {
-                                        for line in &lines[del_start..del_end] {
-                                            if let Some(old_ln) = line.old_lineno
-                                                && let Some(comments) = line_comments.get(&old_ln)
-                                            {
-                                                for comment in comments {
-                                                    if comment.side == Some(LineSide::Old) {
-                                                        comment_lines +=
-                                                            Self::comment_display_lines(comment);
-                                                    }
-                                                }
-                                            }
-                                        }
+    fn list_checkpoints(&mut self) {
+        if self.session.checkpoints.is_empty() {
+            self.set_message("No checkpoints (take one with :checkpoint <label>)");
+            return;
+        }
+        let labels: Vec<String> = self
+            .session
+            .checkpoints
+            .iter()
+            .map(|c| {
+                let at = c.at.with_timezone(&chrono::Local).format("%H:%M");
+                format!("{} ({at})", c.label)
+            })
+            .collect();
+        self.set_message(format!("Checkpoints: {}", labels.join(", ")));
+    }
 
-                                        for line in &lines[add_start..add_end] {
-                                            if let Some(new_ln) = line.new_lineno
-                                                && let Some(comments) = line_comments.get(&new_ln)
-                                            {
-                                                for comment in comments {
-                                                    if comment.side != Some(LineSide::Old) {
-                                                        comment_lines +=
-                                                            Self::comment_display_lines(comment);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
+    /// Roll the review back to the checkpoint named `label`, after the
+    /// confirm dialog `:checkpoint restore` opened.
+    pub fn restore_checkpoint(&mut self, label: &str) {
+        let Some(checkpoint) = self
+            .session
+            .checkpoints
+            .iter()
+            .find(|c| c.label == label)
+            .cloned()
+        else {
+            self.set_warning(format!("No checkpoint '{label}'"));
+            return;
+        };
+        checkpoint.restore(&mut self.session);
+        self.mark_dirty();
+        self.rebuild_annotations();
+        self.set_message(format!(
+            "Rolled back to checkpoint '{label}'; dropped comments are in :trash"
+        ));
+    }
 
-                                    i = add_end;
-                                }
-                                LineOrigin::Addition => {
-                                    // Standalone addition (not following deletions)
-                                    content_lines += 1;
+    /// `:session export <file>`: write the open session with repo-relative
+    /// paths, for resuming on another checkout with `:session import`.
+    pub fn export_portable_session(&mut self, path: &str) {
+        if path.is_empty() {
+            self.set_warning("Usage: :session export <file>");
+            return;
+        }
+        let path = PathBuf::from(path);
+        match crate::persistence::portable::export_session(&path, &self.session) {
+            Ok(()) => self.set_message(format!("Session exported to {}", path.display())),
+            Err(e) => self.set_warning(format!("Failed to export session: {e}")),
+        }
+    }
 
-                                    if let Some(line_comments) = line_comments
-                                        && let Some(new_ln) = diff_line.new_lineno
-                                        && let Some(comments) = line_comments.get(&new_ln)
-                                    {
-                                        for comment in comments {
-                                            if comment.side != Some(LineSide::Old) {
-                                                comment_lines +=
-                                                    Self::comment_display_lines(comment);
-                                            }
-                                        }
-                                    }
+    /// `:session import <file>`: save a session written by `:session
+    /// export` as one of this checkout's, and open it over the diff it was
+    /// a review of.
+    pub fn import_portable_session(&mut self, path: &str) {
+        if path.is_empty() {
+            self.set_warning("Usage: :session import <file>");
+            return;
+        }
+        let path = PathBuf::from(path);
+        let imported = std::fs::read_to_string(&path)
+            .map_err(TuicrError::from)
+            .and_then(|contents| {
+                crate::persistence::portable::import_session(&contents, &self.vcs_info.root_path)
+            });
+        let (session, from) = match imported {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.set_warning(format!("Failed to import {}: {e}", path.display()));
+                return;
+            }
+        };
+        if let Err(e) = crate::persistence::save_session(&session) {
+            self.set_warning(format!("Failed to save the imported session: {e}"));
+            return;
+        }
+        if session.pr_session_key.is_some() {
+            self.set_message("Imported a pull request review; open the pull request to resume it");
+            return;
+        }
+        if let Err(reason) = self.load_saved_review(session) {
+            self.set_warning(format!("Imported and saved, but {reason}"));
+            return;
+        }
+        self.set_message(format!("Resumed the review from {}", from.display()));
+    }
 
-                                    i += 1;
-                                }
-                            }
-                        }
-                    }
-                }
+    /// `:export patch` / `:export <path>.patch`: write the code in SUGGESTION
+    /// comments as a patch the author can `git apply`.
+    pub fn export_suggestions_patch(&mut self, path: &Path) {
+        match crate::output::export_suggestions_patch_to_file(path, &self.session, &self.diff_files)
+        {
+            Ok(suggestions) if suggestions.applied == 0 => {
+                self.set_warning("No suggestions with a ```suggestion block to export");
+            }
+            Ok(suggestions) => {
+                let skipped = match suggestions.skipped {
+                    0 => String::new(),
+                    n => format!(", {n} skipped"),
+                };
+                self.set_message(format!(
+                    "{} suggestions written to {}{skipped}",
+                    suggestions.applied,
+                    path.display()
+                ));
             }
+            Err(e) => self.set_warning(format!("{e}")),
         }
+    }
 
-        header_lines + comment_lines + content_lines + spacing_lines
+    /// `:git <args>` / `:jj <args>`: queue a VCS command for `main`, which
+    /// suspends the TUI and runs it on the terminal so interactive ones
+    /// (`git add -p`, `jj split`) can prompt, then hands the result to
+    /// `finish_vcs_passthrough`.
+    pub fn run_vcs_passthrough(&mut self, program: &str, args: &str) {
+        let Some(args) = crate::process::split_command_args(args) else {
+            self.set_warning("Unbalanced quotes in command");
+            return;
+        };
+        if args.is_empty() {
+            self.set_warning(format!("Usage: :{program} <args>"));
+            return;
+        }
+        if !self.ensure_repo_writable(&format!(":{program}")) || !self.ensure_vcs_idle() {
+            return;
+        }
+        self.pending_passthrough = Some(VcsPassthrough {
+            program: program.to_string(),
+            args,
+        });
     }
 
-    fn update_current_file_from_cursor(&mut self) {
-        let mut cumulative = self.review_comments_render_height();
-        if self.diff_state.cursor_line < cumulative {
-            if !self.diff_files.is_empty() {
-                self.diff_state.current_file_idx = 0;
-                self.file_list_state.select(0);
+    /// Report how a `:git`/`:jj` command ended and reload the diff, since
+    /// the command may have changed what is being reviewed (`jj squash`,
+    /// `git add`, ...).
+    pub fn finish_vcs_passthrough(
+        &mut self,
+        passthrough: &VcsPassthrough,
+        result: crate::process::CommandOutputResult<Option<i32>>,
+    ) {
+        let command = passthrough.command();
+        match result {
+            Ok(None) => self.set_message(format!("{command}: done")),
+            Ok(Some(code)) => self.set_warning(format!("{command}: exited with {code}")),
+            Err(e) => {
+                self.set_error(format!("{command}: {}", e.stderr));
+                return;
             }
+        }
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
             return;
         }
-        for (i, file) in self.diff_files.iter().enumerate() {
-            let height = self.file_render_height(i, file);
-            if cumulative + height > self.diff_state.cursor_line {
-                self.diff_state.current_file_idx = i;
-                self.file_list_state.select(i);
+        if let Err(e) = self.spawn_vcs_reload() {
+            self.set_error(format!("Reload failed: {e}"));
+        }
+    }
+
+    /// `:todos [undo]`: write new-side line comments into the working tree
+    /// as `TODO(review)` comments, or put back the files the last writes
+    /// changed.
+    pub fn todos_command(&mut self, arg: &str) {
+        if !self.ensure_repo_writable("Writing TODOs") {
+            return;
+        }
+        let root = self.vcs_info.root_path.clone();
+        if arg.trim() == "undo" {
+            if self.todo_edits.is_empty() {
+                self.set_warning("No TODOs to undo");
                 return;
             }
-            cumulative += height;
+            let edits = std::mem::take(&mut self.todo_edits);
+            let (restored, kept) = crate::todos::undo_todos(&root, &edits);
+            if kept.is_empty() {
+                self.set_message(format!("Removed TODOs from {restored} file(s)"));
+            } else {
+                let kept: Vec<String> = kept.iter().map(|p| p.display().to_string()).collect();
+                self.set_warning(format!(
+                    "Removed TODOs from {restored} file(s); left {} (edited since)",
+                    kept.join(", ")
+                ));
+            }
+            return;
         }
-        if !self.diff_files.is_empty() {
-            self.diff_state.current_file_idx = self.diff_files.len() - 1;
-            self.file_list_state.select(self.diff_files.len() - 1);
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_warning(":todos writes to the working tree; not available for PRs");
+            return;
         }
-    }
 
-    pub fn total_lines(&self) -> usize {
-        self.review_comments_render_height()
-            + self
-                .diff_files
-                .iter()
-                .enumerate()
-                .map(|(i, f)| self.file_render_height(i, f))
-                .sum::<usize>()
+        let outcome = crate::todos::apply_todos(&root, &self.session, &self.diff_files);
+        let skipped = match outcome.skipped {
+            0 => String::new(),
+            n => format!(", {n} skipped"),
+        };
+        if outcome.inserted == 0 {
+            self.set_warning(format!("No TODOs written{skipped}"));
+            return;
+        }
+        let files = outcome.edits.len();
+        self.todo_edits.extend(outcome.edits);
+        self.set_message(format!(
+            "Wrote {} TODO(s) to {files} file(s){skipped} (:todos undo reverts)",
+            outcome.inserted
+        ));
     }
 
-    /// Last line the cursor can occupy. If the final annotation is a Spacing
-    /// separator it is not navigable content and is excluded.
-    pub fn max_cursor_line(&self) -> usize {
-        let total = self.total_lines();
-        if matches!(self.line_annotations.last(), Some(AnnotatedLine::Spacing)) {
-            total.saturating_sub(2)
-        } else {
-            total.saturating_sub(1)
-        }
+    /// `:gitlab <mr>`: post the review as discussions on a GitLab merge
+    /// request, given as a URL or as an iid on the `origin` project.
+    pub fn submit_to_gitlab(&mut self, target: &str) {
+        use crate::forge::gitlab::glab::MergeRequestTarget;
+
+        let origin = self.origin_remote_url();
+        let Some(target) = MergeRequestTarget::parse(target, origin.as_deref()) else {
+            self.set_warning(format!(
+                "Not a merge request: {target} (use a URL, or !<iid> with an origin remote)"
+            ));
+            return;
+        };
+        self.confirm_post_discussions(DiscussionTarget::GitLab(target));
     }
 
-    /// Calculate the maximum scroll offset.
-    ///
-    /// Allows scrolling until the last line of content is at the top of the viewport.
-    /// This permits empty space below content (e.g. when centering the cursor near EOF)
-    /// while ensuring there is always at least one line of content visible at the top.
-    pub fn max_scroll_offset(&self) -> usize {
-        self.total_lines().saturating_sub(1)
+    /// Ask before posting to `target`. Comments posted before are locked
+    /// and left out, so running the command again only sends the new ones.
+    fn confirm_post_discussions(&mut self, target: DiscussionTarget) {
+        if !self.ensure_session_writable() {
+            return;
+        }
+        if self.discussion_post_state.is_some() {
+            self.set_warning("Still posting the review; wait for it to finish");
+            return;
+        }
+        let count = crate::forge::discussions::build_discussions(
+            &self.session,
+            &self.diff_files,
+            &self.forge_config,
+        )
+        .len();
+        if count == 0 {
+            self.set_warning("No comments left to post (posted ones are locked)");
+            return;
+        }
+        let message = format!(
+            "Post {count} {} to {}?",
+            target.noun(),
+            target.display_name()
+        );
+        self.open_confirm(ConfirmDialog::with_message(
+            message,
+            ConfirmAction::PostDiscussions(target),
+        ));
     }
 
-    /// Calculate the number of display lines a comment takes (header + content + footer)
-    fn comment_display_lines(comment: &Comment) -> usize {
-        let content_lines = comment.content.split('\n').count();
-        2 + content_lines // header + content lines + footer
+    /// Post the unposted comments to `target` on a background thread. The
+    /// outcome is applied later in `poll_discussion_post_events`.
+    pub fn post_discussions(&mut self, target: DiscussionTarget) {
+        if self.discussion_post_state.is_some() {
+            return;
+        }
+        let discussions = crate::forge::discussions::build_discussions(
+            &self.session,
+            &self.diff_files,
+            &self.forge_config,
+        );
+        let bitbucket_token = crate::forge::bitbucket::api::resolve_token(&self.bitbucket_config);
+        let _ = crate::persistence::save_session(&self.session);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.discussion_post_rx = Some(rx);
+        self.discussion_post_state = Some(DiscussionPostState {
+            target: target.clone(),
+            started_at: Instant::now(),
+        });
+        std::thread::spawn(move || {
+            let result = match &target {
+                DiscussionTarget::GitLab(target) => crate::forge::gitlab::glab::submit_review(
+                    &crate::forge::gitlab::glab::SystemGlabRunner,
+                    target,
+                    &discussions,
+                ),
+                DiscussionTarget::Bitbucket(target) => match bitbucket_token {
+                    Some(token) => crate::forge::bitbucket::api::submit_review(
+                        &crate::forge::bitbucket::api::UreqTransport,
+                        target,
+                        &token,
+                        &discussions,
+                    ),
+                    None => Err(TuicrError::Forge("Bitbucket needs a token".to_string())),
+                },
+            };
+            let _ = tx.send(result.map_err(|e| e.to_string()));
+        });
     }
 
-    /// Returns the source line number and side at the current cursor position, if on a diff line
-    pub fn get_line_at_cursor(&self) -> Option<(u32, LineSide)> {
-        let target = self.diff_state.cursor_line;
-        match self.line_annotations.get(target) {
-            Some(AnnotatedLine::DiffLine {
-                old_lineno,
-                new_lineno,
-                ..
-            })
-            | Some(AnnotatedLine::SideBySideLine {
-                old_lineno,
-                new_lineno,
-                ..
-            }) => {
-                // Prefer new line number (for added/context lines), fall back to old (for deleted)
-                new_lineno
-                    .map(|ln| (ln, LineSide::New))
-                    .or_else(|| old_lineno.map(|ln| (ln, LineSide::Old)))
+    /// Pump a pending `:gitlab` or `:bitbucket` outcome.
+    pub fn poll_discussion_post_events(&mut self) {
+        let Some(rx) = self.discussion_post_rx.as_ref() else {
+            return;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("the post worker stopped without a result".to_string())
             }
-            _ => None,
-        }
+        };
+        self.discussion_post_rx = None;
+        let Some(state) = self.discussion_post_state.take() else {
+            return;
+        };
+        self.finish_discussion_post(&state.target, result);
+        self.notify_finished(Operation::Push, state.started_at);
     }
 
-    /// True when the cursor sits on a local comment whose lifecycle state
-    /// has been pushed/submitted to the forge. Such comments are locked from
-    /// edit/delete in tuicr to prevent the local state from drifting from
-    /// what GitHub now stores.
-    pub fn cursor_on_locked_comment(&self) -> bool {
-        let Some(location) = self.find_comment_at_cursor() else {
-            return false;
-        };
-        match location {
-            CommentLocation::Review { index } => self
+    /// Lock the comments that landed as `Submitted`, save, and report.
+    pub fn finish_discussion_post(
+        &mut self,
+        target: &DiscussionTarget,
+        result: std::result::Result<crate::forge::discussions::PostOutcome, String>,
+    ) {
+        use crate::model::comment::CommentLifecycleState;
+
+        if let Ok(outcome) = &result
+            && !outcome.posted_comment_ids.is_empty()
+        {
+            let posted: std::collections::HashSet<&str> = outcome
+                .posted_comment_ids
+                .iter()
+                .map(String::as_str)
+                .collect();
+            let reviews = self.session.files.values_mut();
+            let comments = self
                 .session
                 .review_comments
-                .get(index)
-                .is_some_and(|c| c.is_locked()),
-            CommentLocation::File { path, index } => self
-                .session
-                .files
-                .get(&path)
-                .and_then(|review| review.file_comments.get(index))
-                .is_some_and(|c| c.is_locked()),
-            CommentLocation::Line {
-                path,
-                line,
-                side,
-                index,
-            } => self
-                .session
-                .files
-                .get(&path)
-                .and_then(|review| review.line_comments.get(&line))
-                .and_then(|comments| {
-                    let mut side_idx = 0;
-                    for c in comments {
-                        if c.side.unwrap_or(LineSide::New) == side {
-                            if side_idx == index {
-                                return Some(c);
-                            }
-                            side_idx += 1;
-                        }
-                    }
-                    None
-                })
-                .is_some_and(|c| c.is_locked()),
+                .iter_mut()
+                .chain(reviews.flat_map(|review| {
+                    review
+                        .file_comments
+                        .iter_mut()
+                        .chain(review.line_comments.values_mut().flatten())
+                }));
+            for comment in comments {
+                if posted.contains(comment.id.as_str()) {
+                    comment.lifecycle_state = CommentLifecycleState::Submitted;
+                }
+            }
+            let _ = crate::persistence::save_session(&self.session);
         }
+        self.report_post_outcome(result, target.noun(), &target.display_name());
     }
 
-    /// Find the comment at the current cursor position
-    /// True when the cursor is on a row that belongs to a fetched-from-GitHub
-    /// review thread. Remote threads are read-only in v1; surfaced as a
-    /// distinct condition so the handler can produce a clearer message than
-    /// the generic "no comment at cursor".
-    pub fn cursor_on_remote_thread(&self) -> bool {
-        matches!(
-            self.line_annotations.get(self.diff_state.cursor_line),
-            Some(AnnotatedLine::RemoteThreadLine { .. })
-        )
-    }
+    /// `:bitbucket <pr>`: post the review as comments on a Bitbucket Cloud
+    /// or Data Center pull request, given as a URL or as an id on `origin`.
+    pub fn submit_to_bitbucket(&mut self, target: &str) {
+        use crate::forge::bitbucket::api::{TOKEN_ENV_VAR, resolve_token};
+        use crate::forge::bitbucket::submit::BitbucketPullRequest;
 
-    fn find_comment_at_cursor(&self) -> Option<CommentLocation> {
-        let target = self.diff_state.cursor_line;
-        match self.line_annotations.get(target) {
-            Some(AnnotatedLine::ReviewComment { comment_idx }) => Some(CommentLocation::Review {
-                index: *comment_idx,
-            }),
-            Some(AnnotatedLine::FileComment {
-                file_idx,
-                comment_idx,
-            }) => {
-                let path = self.diff_files.get(*file_idx)?.display_path().clone();
-                Some(CommentLocation::File {
-                    path,
-                    index: *comment_idx,
-                })
-            }
-            Some(AnnotatedLine::LineComment {
-                file_idx,
-                line,
-                side,
-                comment_idx,
-            }) => {
-                let path = self.diff_files.get(*file_idx)?.display_path().clone();
-                Some(CommentLocation::Line {
-                    path,
-                    line: *line,
-                    side: *side,
-                    index: *comment_idx,
-                })
-            }
-            _ => None,
+        let origin = self.origin_remote_url();
+        let Some(target) = BitbucketPullRequest::parse(
+            target,
+            origin.as_deref(),
+            self.bitbucket_config.base_url.as_deref(),
+        ) else {
+            self.set_warning(format!(
+                "Not a pull request: {target} (use a URL, or #<id> with an origin remote)"
+            ));
+            return;
+        };
+        if resolve_token(&self.bitbucket_config).is_none() {
+            self.set_warning(format!(
+                "Bitbucket needs a token: set {TOKEN_ENV_VAR} or `token` under [bitbucket]"
+            ));
+            return;
         }
+        self.confirm_post_discussions(DiscussionTarget::Bitbucket(target));
     }
 
-    /// Delete the comment at the current cursor position, if any
-    /// Returns true if a comment was deleted
-    pub fn delete_comment_at_cursor(&mut self) -> bool {
-        let location = self.find_comment_at_cursor();
+    fn origin_remote_url(&self) -> Option<String> {
+        let repo = git2::Repository::discover(&self.vcs_info.root_path).ok()?;
+        let remote = repo.find_remote("origin").ok()?;
+        remote.url().map(str::to_string)
+    }
 
-        match location {
-            Some(CommentLocation::Review { index })
-                if index < self.session.review_comments.len() =>
-            {
-                self.session.review_comments.remove(index);
-                self.dirty = true;
-                self.set_message("Review comment deleted");
-                self.rebuild_annotations();
-                return true;
+    fn report_post_outcome(
+        &mut self,
+        result: std::result::Result<crate::forge::discussions::PostOutcome, String>,
+        noun: &str,
+        destination: &str,
+    ) {
+        match result {
+            Ok(outcome) if outcome.stopped.is_some() => self.set_warning(format!(
+                "Posted {} {noun} to {destination}, then stopped: {}",
+                outcome.posted,
+                outcome.stopped.unwrap_or_default()
+            )),
+            Ok(outcome) if outcome.failures.is_empty() => {
+                self.set_message(format!("Posted {} {noun} to {destination}", outcome.posted))
             }
-            Some(CommentLocation::File { path, index }) => {
-                if let Some(review) = self.session.get_file_mut(&path) {
-                    review.file_comments.remove(index);
-                    self.dirty = true;
-                    self.set_message("Comment deleted");
-                    self.rebuild_annotations();
-                    return true;
+            Ok(outcome) => self.set_warning(format!(
+                "Posted {} {noun} to {destination}, {} failed: {}",
+                outcome.posted,
+                outcome.failures.len(),
+                outcome.failures[0]
+            )),
+            Err(e) => self.set_warning(e),
+        }
+    }
+
+    /// `(file_idx, hunk_idx)` of the hunk under the cursor. Comment rows
+    /// belong to the diff line they hang off.
+    pub fn hunk_at_cursor(&self) -> Option<(usize, usize)> {
+        let end = self
+            .diff_state
+            .cursor_line
+            .min(self.line_annotations.len().checked_sub(1)?);
+        for annotation in self.line_annotations[..=end].iter().rev() {
+            match annotation {
+                AnnotatedLine::LineComment { .. }
+                | AnnotatedLine::HistoryComment { .. }
+                | AnnotatedLine::RemoteThreadLine { .. } => {}
+                AnnotatedLine::HunkHeader { file_idx, hunk_idx }
+                | AnnotatedLine::ElidedContext {
+                    file_idx, hunk_idx, ..
                 }
-            }
-            Some(CommentLocation::Line {
-                path,
-                line,
-                side,
-                index,
-            }) => {
-                if let Some(review) = self.session.get_file_mut(&path)
-                    && let Some(comments) = review.line_comments.get_mut(&line)
-                {
-                    // Find the actual index by counting comments with matching side
-                    let mut side_idx = 0;
-                    let mut actual_idx = None;
-                    for (i, comment) in comments.iter().enumerate() {
-                        let comment_side = comment.side.unwrap_or(LineSide::New);
-                        if comment_side == side {
-                            if side_idx == index {
-                                actual_idx = Some(i);
-                                break;
-                            }
-                            side_idx += 1;
-                        }
-                    }
-                    if let Some(idx) = actual_idx {
-                        comments.remove(idx);
-                        if comments.is_empty() {
-                            review.line_comments.remove(&line);
-                        }
-                        self.dirty = true;
-                        self.set_message(format!("Comment on line {line} deleted"));
-                        self.rebuild_annotations();
-                        return true;
-                    }
+                | AnnotatedLine::DiffLine {
+                    file_idx, hunk_idx, ..
                 }
+                | AnnotatedLine::SideBySideLine {
+                    file_idx, hunk_idx, ..
+                } => return Some((*file_idx, *hunk_idx)),
+                _ => return None,
             }
-            Some(CommentLocation::Review { .. }) | None => {}
         }
-
-        false
+        None
     }
 
-    pub fn clear_comments(&mut self, scope: ClearScope) {
-        let (cleared, unreviewed) = self.session.clear_comments(scope);
-        if cleared == 0 && unreviewed == 0 {
-            self.set_message("No comments to clear");
+    /// `<leader>y`: copy the hunk under the cursor as a fenced Markdown
+    /// `diff` block captioned with its `path:line`.
+    pub fn copy_hunk_at_cursor(&mut self) {
+        let Some((file_idx, hunk_idx)) = self.hunk_at_cursor() else {
+            self.set_warning("Move the cursor into a hunk to copy it");
             return;
+        };
+        let file = &self.diff_files[file_idx];
+        let markdown = crate::output::format_hunk_markdown(file, &file.hunks[hunk_idx]);
+        match crate::output::copy_text_to_clipboard(&markdown) {
+            Ok(_) => self.set_message("Hunk copied to clipboard as Markdown"),
+            Err(e) => self.set_error(format!("Copy failed: {e}")),
         }
+    }
 
-        self.dirty = true;
-        self.rebuild_annotations();
-        let msg = match (cleared, unreviewed) {
-            (0, n) => format!("Unreviewed {n} files"),
-            (c, 0) => format!("Cleared {c} comments"),
-            (c, n) => format!("Cleared {c} comments, unreviewed {n} files"),
+    /// `<leader>d`: cycle the hunk under the cursor through a word diff, a
+    /// character diff and back to the plain line diff.
+    pub fn cycle_word_diff_at_cursor(&mut self) {
+        let Some(key) = self.hunk_at_cursor() else {
+            self.set_warning("Move the cursor into a hunk to refine its diff");
+            return;
         };
-        self.set_message(msg);
+        match WordDiffMode::cycle(self.word_diff_hunks.get(&key).copied()) {
+            Some(mode) => {
+                self.word_diff_hunks.insert(key, mode);
+                self.set_message(format!("Hunk shown as a {} diff", mode.label()));
+            }
+            None => {
+                self.word_diff_hunks.remove(&key);
+                self.set_message("Hunk shown as a line diff");
+            }
+        }
     }
 
-    /// Enter edit mode for the comment at the current cursor position
-    /// Returns true if a comment was found and edit mode entered
-    pub fn enter_edit_mode(&mut self) -> bool {
-        let location = self.find_comment_at_cursor();
-
-        match location {
-            Some(CommentLocation::Review { index }) => {
-                if let Some(comment) = self.session.review_comments.get(index) {
-                    self.input_mode = InputMode::Comment;
-                    self.comment_buffer = comment.content.clone();
-                    self.comment_cursor = self.comment_buffer.len();
-                    self.comment_type = comment.comment_type.clone();
-                    self.comment_is_review_level = true;
-                    self.comment_is_file_level = false;
-                    self.comment_line = None;
-                    self.editing_comment_id = Some(comment.id.clone());
-                    return true;
-                }
-            }
-            Some(CommentLocation::File { path, index }) => {
-                if let Some(review) = self.session.files.get(&path)
-                    && let Some(comment) = review.file_comments.get(index)
-                {
-                    self.input_mode = InputMode::Comment;
-                    self.comment_buffer = comment.content.clone();
-                    self.comment_cursor = self.comment_buffer.len();
-                    self.comment_type = comment.comment_type.clone();
-                    self.comment_is_review_level = false;
-                    self.comment_is_file_level = true;
-                    self.comment_line = None;
-                    self.editing_comment_id = Some(comment.id.clone());
-                    return true;
+    pub fn pane_geometry(&self, inner: ratatui::layout::Rect, side: LineSide) -> PaneGeom {
+        match self.diff_view_mode {
+            DiffViewMode::Unified => {
+                let content_width = (inner.width as usize).saturating_sub(UNIFIED_GUTTER as usize);
+                PaneGeom {
+                    content_x_start: inner.x + UNIFIED_GUTTER,
+                    content_x_end: inner.x + inner.width,
+                    content_width,
                 }
             }
-            Some(CommentLocation::Line {
-                path,
-                line,
-                side,
-                index,
-            }) => {
-                if let Some(review) = self.session.files.get(&path)
-                    && let Some(comments) = review.line_comments.get(&line)
-                {
-                    // Find the actual comment by counting comments with matching side
-                    let mut side_idx = 0;
-                    for comment in comments.iter() {
-                        let comment_side = comment.side.unwrap_or(LineSide::New);
-                        if comment_side == side {
-                            if side_idx == index {
-                                self.input_mode = InputMode::Comment;
-                                self.comment_buffer = comment.content.clone();
-                                self.comment_cursor = self.comment_buffer.len();
-                                self.comment_type = comment.comment_type.clone();
-                                self.comment_is_review_level = false;
-                                self.comment_is_file_level = false;
-                                self.comment_line = Some((line, side));
-                                self.editing_comment_id = Some(comment.id.clone());
-                                return true;
-                            }
-                            side_idx += 1;
+            DiffViewMode::SideBySide => {
+                let half_w = (inner.width.saturating_sub(SBS_OVERHEAD) / 2) as usize;
+                match side {
+                    LineSide::Old => PaneGeom {
+                        content_x_start: inner.x + SBS_LEFT_GUTTER,
+                        content_x_end: inner.x + SBS_LEFT_GUTTER + half_w as u16,
+                        content_width: half_w,
+                    },
+                    LineSide::New => {
+                        let start = inner.x + SBS_OVERHEAD + half_w as u16;
+                        PaneGeom {
+                            content_x_start: start,
+                            content_x_end: start + half_w as u16,
+                            content_width: half_w,
                         }
                     }
                 }
             }
-            None => {}
         }
-
-        false
-    }
-
-    pub fn enter_command_mode(&mut self) {
-        self.input_mode = InputMode::Command;
-        self.command_buffer.clear();
-    }
-
-    pub fn exit_command_mode(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.command_buffer.clear();
-    }
-
-    pub fn enter_search_mode(&mut self) {
-        self.input_mode = InputMode::Search;
-        self.search_buffer.clear();
-    }
-
-    pub fn exit_search_mode(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.search_buffer.clear();
-    }
-
-    pub fn enter_comment_mode(&mut self, file_level: bool, line: Option<(u32, LineSide)>) {
-        self.input_mode = InputMode::Comment;
-        self.comment_buffer.clear();
-        self.comment_cursor = 0;
-        self.comment_type = self.default_comment_type();
-        self.comment_is_review_level = false;
-        self.comment_is_file_level = file_level;
-        self.comment_line = line;
     }
 
-    pub fn enter_review_comment_mode(&mut self) {
-        self.input_mode = InputMode::Comment;
-        self.comment_buffer.clear();
-        self.comment_cursor = 0;
-        self.comment_type = self.default_comment_type();
-        self.comment_is_review_level = true;
-        self.comment_is_file_level = false;
-        self.comment_line = None;
-        self.comment_line_range = None;
-        self.editing_comment_id = None;
+    pub fn side_at_x(
+        &self,
+        inner: ratatui::layout::Rect,
+        x: u16,
+        ann_default: LineSide,
+    ) -> LineSide {
+        match self.diff_view_mode {
+            DiffViewMode::Unified => ann_default,
+            DiffViewMode::SideBySide => {
+                let half_w = inner.width.saturating_sub(SBS_OVERHEAD) / 2;
+                let divider = inner.x + SBS_LEFT_GUTTER + half_w;
+                if x < divider {
+                    LineSide::Old
+                } else {
+                    LineSide::New
+                }
+            }
+        }
     }
 
-    pub fn exit_comment_mode(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.comment_buffer.clear();
-        self.comment_cursor = 0;
-        self.comment_is_review_level = false;
-        self.editing_comment_id = None;
-        self.comment_line_range = None;
-    }
+    pub fn cell_to_sel_point(&self, screen_col: u16, screen_row: u16) -> Option<SelPoint> {
+        let idx = self.diff_annotation_at_screen_row(screen_row)?;
+        let inner = self.diff_inner_area?;
+        let ann = self.line_annotations.get(idx)?;
+        let side = self.side_at_x(inner, screen_col, annotation_side_default(ann));
 
-    pub fn enter_visual_mode_at_cursor(&mut self) {
-        let idx = self.diff_state.cursor_line;
-        let side = self
-            .get_line_at_cursor()
-            .map(|(_, s)| s)
-            .unwrap_or(LineSide::New);
-        let len = self.annotation_content_len(idx, side);
-        let anchor = SelPoint {
+        let zero_point = SelPoint {
             annotation_idx: idx,
             char_offset: 0,
             side,
         };
-        let head = SelPoint {
+        let Some(content) = self.content_for_side(idx, side) else {
+            return Some(zero_point);
+        };
+        let geom = self.pane_geometry(inner, side);
+        if geom.content_width == 0 {
+            return Some(zero_point);
+        }
+        let last_col = geom.content_x_end.saturating_sub(1);
+        let col = screen_col.clamp(geom.content_x_start, last_col);
+        let col_in_row = (col - geom.content_x_start) as usize;
+
+        let rel = (screen_row - inner.y) as usize;
+        let mut walker = rel;
+        while walker > 0 && self.diff_row_to_annotation.get(walker - 1).copied() == Some(idx) {
+            walker -= 1;
+        }
+        let which_row = rel - walker;
+        let total_chars = content.chars().count();
+        let char_offset = (which_row * geom.content_width + col_in_row).min(total_chars);
+        Some(SelPoint {
             annotation_idx: idx,
-            char_offset: len,
+            char_offset,
             side,
+        })
+    }
+
+    /// Mirrors `ensure_cursor_visible`'s notion of visibility (uses the
+    /// renderer's `visible_line_count` when present so wrapping is honored).
+    pub fn is_cursor_visible(&self) -> bool {
+        let visible = if self.diff_state.visible_line_count > 0 {
+            self.diff_state.visible_line_count
+        } else {
+            self.diff_state.viewport_height.max(1)
         };
-        self.input_mode = InputMode::VisualSelect;
-        self.visual_selection = Some(VisualSelection { anchor, head });
+        let cursor = self.diff_state.cursor_line;
+        cursor >= self.diff_state.scroll_offset && cursor < self.diff_state.scroll_offset + visible
     }
 
-    pub fn exit_visual_mode(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.visual_selection = None;
+    pub fn jump_to_file(&mut self, idx: usize) {
+        use std::path::Path;
+
+        if idx < self.diff_files.len() {
+            self.diff_state.current_file_idx = idx;
+            self.diff_state.cursor_line = self.calculate_file_scroll_offset(idx);
+            let max_scroll = self.max_scroll_offset();
+            self.diff_state.scroll_offset = self.diff_state.cursor_line.min(max_scroll);
+
+            let file_path = self.diff_files[idx].display_path().clone();
+            let mut current = file_path.parent();
+            while let Some(parent) = current {
+                if parent != Path::new("") {
+                    self.expanded_dirs
+                        .insert(parent.to_string_lossy().to_string());
+                }
+                current = parent.parent();
+            }
+
+            if let Some(tree_idx) = self.file_idx_to_tree_idx(idx) {
+                self.file_list_state.select(tree_idx);
+            }
+        }
     }
 
-    pub fn get_visual_selection(&self) -> Option<&VisualSelection> {
-        if self.input_mode != InputMode::VisualSelect {
-            return None;
+    /// `gg`: first row of the file list, or the first rendered diff line
+    /// (review comments included) depending on focus.
+    pub fn go_to_top(&mut self) {
+        if self.focused_panel == FocusedPanel::FileList {
+            self.file_list_state.select(0);
+        } else {
+            self.jump_to_top();
         }
-        self.visual_selection.as_ref()
     }
 
-    pub fn annotation_content_len(&self, idx: usize, side: LineSide) -> usize {
-        self.content_for_side(idx, side)
-            .map(|s| s.chars().count())
-            .unwrap_or(0)
+    /// `G`: last row of the file list, or the last rendered diff line.
+    pub fn go_to_bottom(&mut self) {
+        if self.focused_panel == FocusedPanel::FileList {
+            let last = self.build_visible_items().len().saturating_sub(1);
+            self.file_list_state.select(last);
+        } else {
+            self.jump_to_bottom();
+        }
     }
 
-    pub fn extend_visual_to_cursor(&mut self) {
-        let Some(sel) = self.visual_selection else {
-            return;
-        };
-        let anchor_idx = sel.anchor.annotation_idx;
-        let cursor_idx = self.diff_state.cursor_line;
-        let side = sel.anchor.side;
-        let anchor_len = self.annotation_content_len(anchor_idx, side);
-        let cursor_len = self.annotation_content_len(cursor_idx, side);
-        let (anchor_char, head_char) = if cursor_idx >= anchor_idx {
-            (0, cursor_len)
+    /// `{count}%`: jump `percent` of the way through the file list or the
+    /// rendered diff, rounding up like Vim so `100%` lands on the last line.
+    pub fn go_to_percentage(&mut self, percent: usize) {
+        let percent = percent.min(100);
+        let target = |total: usize| (percent * total).div_ceil(100).saturating_sub(1);
+        if self.focused_panel == FocusedPanel::FileList {
+            let total = self.build_visible_items().len();
+            self.file_list_state.select(target(total));
         } else {
-            (anchor_len, 0)
-        };
-        self.visual_selection = Some(VisualSelection {
-            anchor: SelPoint {
-                annotation_idx: anchor_idx,
-                char_offset: anchor_char,
-                side,
-            },
-            head: SelPoint {
-                annotation_idx: cursor_idx,
-                char_offset: head_char,
-                side,
-            },
-        });
+            self.diff_state.cursor_line = target(self.max_cursor_line() + 1);
+            self.ensure_cursor_visible();
+            self.center_cursor();
+            self.update_current_file_from_cursor();
+        }
     }
 
-    pub fn visual_selection_line_range(&self) -> Option<(LineRange, LineSide)> {
-        let sel = self.get_visual_selection()?;
-        let (start, end) = sel.ordered();
-        let start_line = self.annotation_line_for_side(start.annotation_idx, start.side);
-        let end_line = self.annotation_line_for_side(end.annotation_idx, end.side);
-        let start_ln = start_line?;
-        let end_ln = end_line?;
-        Some((LineRange::new(start_ln, end_ln), start.side))
+    pub fn jump_to_top(&mut self) {
+        self.diff_state.cursor_line = 0;
+        self.diff_state.scroll_offset = 0;
+        self.update_current_file_from_cursor();
     }
 
-    fn annotation_line_for_side(&self, idx: usize, side: LineSide) -> Option<u32> {
-        match self.line_annotations.get(idx)? {
-            AnnotatedLine::DiffLine {
-                old_lineno,
-                new_lineno,
-                ..
+    pub fn jump_to_bottom(&mut self) {
+        let max_line = self.max_cursor_line();
+        self.diff_state.cursor_line = max_line;
+        // Position so the last navigable line is at the bottom of the viewport
+        let viewport = self.diff_state.viewport_height.max(1);
+        self.diff_state.scroll_offset = (max_line + 1).saturating_sub(viewport);
+        self.update_current_file_from_cursor();
+    }
+
+    pub fn next_file(&mut self) {
+        let visible_items = self.build_visible_items();
+        let current_file_idx = self.diff_state.current_file_idx;
+
+        for item in &visible_items {
+            if let FileTreeItem::File { file_idx, .. } = item
+                && *file_idx > current_file_idx
+            {
+                self.jump_to_file(*file_idx);
+                return;
             }
-            | AnnotatedLine::SideBySideLine {
-                old_lineno,
-                new_lineno,
-                ..
-            } => match side {
-                LineSide::New => *new_lineno,
-                LineSide::Old => *old_lineno,
-            },
-            _ => None,
         }
     }
 
-    pub fn enter_comment_from_visual(&mut self) {
-        if let Some((range, side)) = self.visual_selection_line_range() {
-            self.comment_line_range = Some((range, side));
-            self.comment_line = Some((range.end, side));
-            self.input_mode = InputMode::Comment;
-            self.comment_buffer.clear();
-            self.comment_cursor = 0;
-            self.comment_type = self.default_comment_type();
-            self.comment_is_review_level = false;
-            self.comment_is_file_level = false;
-            self.visual_selection = None;
-        } else {
-            self.set_warning("Invalid visual selection");
-            self.exit_visual_mode();
+    pub fn prev_file(&mut self) {
+        let visible_items = self.build_visible_items();
+        let current_file_idx = self.diff_state.current_file_idx;
+
+        for item in visible_items.iter().rev() {
+            if let FileTreeItem::File { file_idx, .. } = item
+                && *file_idx < current_file_idx
+            {
+                self.jump_to_file(*file_idx);
+                return;
+            }
         }
     }
 
-    pub fn save_comment(&mut self) {
-        if self.comment_buffer.trim().is_empty() {
-            self.set_message("Comment cannot be empty");
-            return;
+    fn file_idx_to_tree_idx(&self, target_file_idx: usize) -> Option<usize> {
+        let visible_items = self.build_visible_items();
+        for (tree_idx, item) in visible_items.iter().enumerate() {
+            if let FileTreeItem::File { file_idx, .. } = item
+                && *file_idx == target_file_idx
+            {
+                return Some(tree_idx);
+            }
         }
+        None
+    }
 
-        let content = self.comment_buffer.trim().to_string();
+    pub fn next_hunk(&mut self) {
+        // Find the next hunk header position after current cursor
+        let mut cumulative = self.review_comments_render_height();
+        for file in &self.diff_files {
+            let path = file.display_path();
 
-        let mut message = "Error: Could not save comment".to_string();
+            // File header
+            cumulative += 1;
 
-        // Check if we're editing an existing comment
-        if let Some(editing_id) = &self.editing_comment_id {
-            if let Some(comment) = self
-                .session
-                .review_comments
-                .iter_mut()
-                .find(|c| &c.id == editing_id)
-            {
-                comment.content = content.clone();
-                comment.comment_type = self.comment_type.clone();
-                message = "Review comment updated".to_string();
-            } else if let Some(path) = self.current_file_path().cloned()
-                && let Some(review) = self.session.get_file_mut(&path)
-            {
-                if let Some(comment) = review
-                    .file_comments
-                    .iter_mut()
-                    .find(|c| &c.id == editing_id)
-                {
-                    comment.content = content.clone();
-                    comment.comment_type = self.comment_type.clone();
-                    message = "Comment updated".to_string();
-                } else {
-                    // If not found in file comments, search in line comments
-                    let mut found_comment = None;
-                    for comments in review.line_comments.values_mut() {
-                        if let Some(comment) = comments.iter_mut().find(|c| &c.id == editing_id) {
-                            found_comment = Some(comment);
-                            break;
-                        }
-                    }
+            // If file is reviewed, skip all content
+            if self.is_file_folded(path) {
+                continue;
+            }
 
-                    if let Some(comment) = found_comment {
-                        comment.content = content.clone();
-                        comment.comment_type = self.comment_type.clone();
-                        message = if let Some((line, _)) = self.comment_line {
-                            format!("Comment on line {line} updated")
-                        } else {
-                            "Comment updated".to_string()
-                        };
-                    } else {
-                        message = "Error: Comment to edit not found".to_string();
+            // File comments
+            if let Some(review) = self.session.files.get(path) {
+                cumulative += review.file_comments.len();
+            }
+
+            if file.is_binary || file.hunks.is_empty() {
+                cumulative += 1; // "(binary file)" or "(no changes)"
+            } else {
+                for hunk in &file.hunks {
+                    // This is a hunk header position
+                    if cumulative > self.diff_state.cursor_line {
+                        self.diff_state.cursor_line = cumulative;
+                        self.ensure_cursor_visible();
+                        self.update_current_file_from_cursor();
+                        return;
                     }
+                    cumulative += 1; // hunk header
+                    cumulative += hunk.lines.len(); // diff lines
                 }
             }
-        } else if self.comment_is_review_level {
-            let comment = Comment::new(content, self.comment_type.clone(), None);
-            self.session.review_comments.push(comment);
-            message = "Review comment added".to_string();
-        } else if let Some(path) = self.current_file_path().cloned()
-            && let Some(review) = self.session.get_file_mut(&path)
-        {
-            // Create new comment
-            if self.comment_is_file_level {
-                let comment = Comment::new(content, self.comment_type.clone(), None);
-                review.add_file_comment(comment);
-                message = "File comment added".to_string();
-            } else if let Some((range, side)) = self.comment_line_range {
-                // Range comment from visual selection
-                let comment =
-                    Comment::new_with_range(content, self.comment_type.clone(), Some(side), range);
-                // Store by end line of the range
-                review.add_line_comment(range.end, comment);
-                if range.is_single() {
-                    message = format!("Comment added to line {}", range.end);
-                } else {
-                    message = format!("Comment added to lines {}-{}", range.start, range.end);
-                }
-            } else if let Some((line, side)) = self.comment_line {
-                let comment = Comment::new(content, self.comment_type.clone(), Some(side));
-                review.add_line_comment(line, comment);
-                message = format!("Comment added to line {line}");
+            cumulative += 1; // spacing
+        }
+    }
+
+    pub fn prev_hunk(&mut self) {
+        // Find the previous hunk header position before current cursor
+        let mut hunk_positions: Vec<usize> = Vec::new();
+        let mut cumulative = self.review_comments_render_height();
+
+        for file in &self.diff_files {
+            let path = file.display_path();
+
+            cumulative += 1; // File header
+
+            // If file is reviewed, skip all content
+            if self.is_file_folded(path) {
+                continue;
+            }
+
+            if let Some(review) = self.session.files.get(path) {
+                cumulative += review.file_comments.len();
+            }
+
+            if file.is_binary || file.hunks.is_empty() {
+                cumulative += 1;
             } else {
-                // Fallback to file comment if no line specified
-                let comment = Comment::new(content, self.comment_type.clone(), None);
-                review.add_file_comment(comment);
-                message = "File comment added".to_string();
+                for hunk in &file.hunks {
+                    hunk_positions.push(cumulative);
+                    cumulative += 1;
+                    cumulative += hunk.lines.len();
+                }
             }
+            cumulative += 1;
         }
 
-        if !message.starts_with("Error:") {
-            self.dirty = true;
+        // Find the last hunk position before current cursor
+        for &pos in hunk_positions.iter().rev() {
+            if pos < self.diff_state.cursor_line {
+                self.diff_state.cursor_line = pos;
+                self.ensure_cursor_visible();
+                self.update_current_file_from_cursor();
+                return;
+            }
         }
-        self.set_message(message);
-        self.rebuild_annotations();
 
-        self.exit_comment_mode();
+        // If no previous hunk, go to start
+        self.diff_state.cursor_line = 0;
+        self.ensure_cursor_visible();
+        self.update_current_file_from_cursor();
     }
 
-    pub fn cycle_comment_type(&mut self) {
-        if self.comment_types.is_empty() {
-            return;
+    fn calculate_file_scroll_offset(&self, file_idx: usize) -> usize {
+        let mut offset = self.review_comments_render_height();
+        for (i, file) in self.diff_files.iter().enumerate() {
+            if i == file_idx {
+                break;
+            }
+            offset += self.file_render_height(i, file);
         }
+        offset
+    }
 
-        let current_id = self.comment_type.id();
-        let current_index = self
-            .comment_types
-            .iter()
-            .position(|comment_type| comment_type.id == current_id)
-            .unwrap_or(0);
-        let next_index = (current_index + 1) % self.comment_types.len();
-        self.comment_type = CommentType::from_id(&self.comment_types[next_index].id);
+    fn review_comments_render_height(&self) -> usize {
+        let mut height = 1; // Header line
+        for comment in &self.session.review_comments {
+            height += Self::comment_display_lines(comment);
+        }
+        if self.input_mode == InputMode::Comment
+            && self.comment_is_review_level
+            && self.editing_comment_id.is_none()
+        {
+            // Header + one content line + footer
+            height += 3;
+        }
+        height
     }
 
-    pub fn cycle_comment_type_reverse(&mut self) {
-        if self.comment_types.is_empty() {
-            return;
+    fn file_render_height(&self, file_idx: usize, file: &DiffFile) -> usize {
+        let path = file.display_path();
+
+        // If reviewed, only show header (1 line total)
+        if self.is_file_folded(path) {
+            return 1;
         }
 
-        let current_id = self.comment_type.id();
-        let current_index = self
-            .comment_types
-            .iter()
-            .position(|comment_type| comment_type.id == current_id)
-            .unwrap_or(0);
-        let prev_index = if current_index == 0 {
-            self.comment_types.len() - 1
-        } else {
-            current_index - 1
-        };
-        self.comment_type = CommentType::from_id(&self.comment_types[prev_index].id);
-    }
+        let header_lines = 1; // File header
+        let spacing_lines = 1; // Blank line between files
+        let mut content_lines = 0;
+        let mut comment_lines = 0;
 
-    pub fn toggle_help(&mut self) {
-        if self.input_mode == InputMode::Help {
-            self.input_mode = InputMode::Normal;
-        } else {
-            self.input_mode = InputMode::Help;
-            self.help_state.scroll_offset = 0;
+        if let Some(review) = self.session.files.get(path) {
+            for comment in &review.file_comments {
+                comment_lines += Self::comment_display_lines(comment);
+            }
         }
-    }
 
-    pub fn help_scroll_down(&mut self, lines: usize) {
-        let max_offset = self
-            .help_state
-            .total_lines
-            .saturating_sub(self.help_state.viewport_height);
-        self.help_state.scroll_offset = (self.help_state.scroll_offset + lines).min(max_offset);
-    }
+        if file.is_binary || file.hunks.is_empty() {
+            content_lines = 1;
+        } else {
+            let line_comments = self.session.files.get(path).map(|r| &r.line_comments);
 
-    pub fn help_scroll_up(&mut self, lines: usize) {
-        self.help_state.scroll_offset = self.help_state.scroll_offset.saturating_sub(lines);
-    }
+            for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+                // Calculate gap before this hunk
+                let prev_hunk = if hunk_idx > 0 {
+                    file.hunks.get(hunk_idx - 1)
+                } else {
+                    None
+                };
+                let gap = calculate_gap(
+                    prev_hunk.map(|h| (&h.new_start, &h.new_count)),
+                    hunk.new_start,
+                );
 
-    pub fn help_scroll_to_top(&mut self) {
-        self.help_state.scroll_offset = 0;
-    }
+                let gap_id = GapId { file_idx, hunk_idx };
 
-    pub fn help_scroll_to_bottom(&mut self) {
-        let max_offset = self
-            .help_state
-            .total_lines
-            .saturating_sub(self.help_state.viewport_height);
-        self.help_state.scroll_offset = max_offset;
-    }
+                if gap > 0 {
+                    let top_len = self.expanded_top.get(&gap_id).map_or(0, |v| v.len());
+                    let bot_len = self.expanded_bottom.get(&gap_id).map_or(0, |v| v.len());
+                    let remaining = (gap as usize).saturating_sub(top_len + bot_len);
+                    content_lines += top_len + bot_len;
+                    content_lines += gap_annotation_line_count(hunk_idx == 0, remaining);
+                }
 
-    pub fn enter_confirm_mode(&mut self, action: ConfirmAction) {
-        self.input_mode = InputMode::Confirm;
-        self.pending_confirm = Some(action);
-    }
+                // Hunk header + diff lines
+                content_lines += 1; // Hunk header
 
-    pub fn exit_confirm_mode(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.pending_confirm = None;
-    }
+                // Count diff lines based on view mode
+                match self.diff_view_mode {
+                    DiffViewMode::Unified => {
+                        for diff_line in &hunk.lines {
+                            content_lines += 1;
 
-    /// Drive `:submit*` preflight: walk every local-draft comment in the
-    /// current PR session, map each one against the displayed diff, bucket
-    /// the results, and transition into the resolver (when there are
-    /// unmappable comments) or the final-confirmation modal.
-    ///
-    /// PR 5 does not call the network; `[y]` in the confirmation modal
-    /// stubs a "PR 6 will wire the network call" info message.
-    pub fn start_submit(&mut self, event: crate::forge::submit::SubmitEvent) {
-        self.start_submit_with(event, false);
-    }
+                            if let Some(line_comments) = line_comments {
+                                if let Some(old_ln) = diff_line.old_lineno
+                                    && let Some(comments) = line_comments.get(&old_ln)
+                                {
+                                    for comment in comments {
+                                        if comment.side == Some(LineSide::Old) {
+                                            comment_lines += Self::comment_display_lines(comment);
+                                        }
+                                    }
+                                }
 
-    /// Like `start_submit`, but when `skip_confirm` is `true` the flow
-    /// bypasses `SubmitConfirm`. The action-picker path uses this because
-    /// picking IS the confirmation; the resolver (if any unmappable
-    /// comments) still runs first, then dispatches the network call
-    /// directly. `:submit <event>` callers should pass `false`.
-    pub fn start_submit_with(
-        &mut self,
-        event: crate::forge::submit::SubmitEvent,
-        skip_confirm: bool,
-    ) {
-        use crate::forge::submit::{
-            CommentAnchor, InlineComment, ResolverAction, UnmappableItem, map_comment,
-        };
+                                if let Some(new_ln) = diff_line.new_lineno
+                                    && let Some(comments) = line_comments.get(&new_ln)
+                                {
+                                    for comment in comments {
+                                        if comment.side != Some(LineSide::Old) {
+                                            comment_lines += Self::comment_display_lines(comment);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    DiffViewMode::SideBySide => {
+                        use crate::model::LineOrigin;
+                        // Side-by-side mode: pair deletions with following additions
+                        let lines = &hunk.lines;
+                        let mut i = 0;
+                        while i < lines.len() {
+                            let diff_line = &lines[i];
 
-        let DiffSource::PullRequest(pr) = &self.diff_source else {
-            self.set_warning(":submit only applies in PR mode");
-            return;
-        };
-        if pr.is_read_only() {
-            let reason = pr.read_only_reason().unwrap_or("read only");
-            self.set_warning(format!("Cannot submit: PR is {reason}"));
-            return;
-        }
-        // When the inline commit selector shows a strict subset, comments
-        // anchor to the displayed (subset) diff, so `commit_id` must be the
-        // SHA the diff was computed against — otherwise GitHub rejects with
-        // 422 because the line/position isn't present in the diff against
-        // the cumulative PR head. `pr_commits` is stored newest-first, so
-        // the head of a (start_idx..=end_idx) range is `pr_commits[start_idx]`.
-        let commit_id = match self.commit_selection_range {
-            Some((start_idx, end_idx))
-                if !self.pr_commits.is_empty()
-                    && start_idx <= end_idx
-                    && end_idx < self.pr_commits.len()
-                    && !(start_idx == 0 && end_idx + 1 == self.pr_commits.len()) =>
-            {
-                self.pr_commits[start_idx].oid.clone()
-            }
-            _ => pr.key.head_sha.clone(),
-        };
+                            match diff_line.origin {
+                                LineOrigin::Context => {
+                                    content_lines += 1;
 
-        // Source of truth for the diff: when the inline commit selector is
-        // showing a strict subset, `range_diff_files` carries the merged
-        // subset diff; otherwise `diff_files` is canonical.
-        let files: Vec<&DiffFile> = match self.range_diff_files.as_ref() {
-            Some(range) => range.iter().collect(),
-            None => self.diff_files.iter().collect(),
-        };
+                                    // Comments for context line
+                                    if let Some(line_comments) = line_comments
+                                        && let Some(new_ln) = diff_line.new_lineno
+                                        && let Some(comments) = line_comments.get(&new_ln)
+                                    {
+                                        for comment in comments {
+                                            if comment.side != Some(LineSide::Old) {
+                                                comment_lines +=
+                                                    Self::comment_display_lines(comment);
+                                            }
+                                        }
+                                    }
+                                    i += 1;
+                                }
+                                LineOrigin::Deletion => {
+                                    // Find consecutive deletions
+                                    let del_start = i;
+                                    let mut del_end = i + 1;
+                                    while del_end < lines.len()
+                                        && lines[del_end].origin == LineOrigin::Deletion
+                                    {
+                                        del_end += 1;
+                                    }
 
-        let mut mappable: Vec<InlineComment> = Vec::new();
-        let mut unmappable: Vec<UnmappableItem> = Vec::new();
-        let mut total_local_drafts = 0_usize;
+                                    // Find consecutive additions following deletions
+                                    let add_start = del_end;
+                                    let mut add_end = add_start;
+                                    while add_end < lines.len()
+                                        && lines[add_end].origin == LineOrigin::Addition
+                                    {
+                                        add_end += 1;
+                                    }
 
-        // Walk file-level and line comments in display order. Review-level
-        // comments (session.review_comments) are NOT inline-mapped; they
-        // appear in the body via `build_review_body`.
-        for file in &files {
-            let Some(review) = self.session.files.get(file.display_path()) else {
-                continue;
-            };
-            for comment in &review.file_comments {
-                if comment.is_locked() {
-                    continue;
-                }
-                total_local_drafts += 1;
-                bucket_mapping(
-                    map_comment(comment, CommentAnchor::FileLevel, file, &self.forge_config),
-                    &mut mappable,
-                    &mut unmappable,
-                );
-            }
-            let mut keys: Vec<&u32> = review.line_comments.keys().collect();
-            keys.sort();
-            for key in keys {
-                for comment in &review.line_comments[key] {
-                    if comment.is_locked() {
-                        continue;
-                    }
-                    total_local_drafts += 1;
-                    let anchor = if comment.line_range.is_some() {
-                        CommentAnchor::Range
-                    } else {
-                        CommentAnchor::Line {
-                            line: *key,
-                            side: comment.side.unwrap_or_default(),
-                        }
-                    };
-                    bucket_mapping(
-                        map_comment(comment, anchor, file, &self.forge_config),
-                        &mut mappable,
-                        &mut unmappable,
-                    );
-                }
-            }
-        }
+                                    let del_count = del_end - del_start;
+                                    let add_count = add_end - add_start;
+                                    // Paired lines use max of the two counts
+                                    content_lines += del_count.max(add_count);
 
-        // Approve is the one event that's meaningful with no comments — a
-        // bare "LGTM" approval. Every other event needs at least one local
-        // draft comment or a review-level comment, otherwise there's
-        // nothing to submit.
-        let bare_allowed = matches!(event, crate::forge::submit::SubmitEvent::Approve);
-        if !bare_allowed && total_local_drafts == 0 && self.session.review_comments.is_empty() {
-            self.set_warning("Nothing to submit — no local-draft comments");
-            return;
-        }
+                                    // Count comments for all deletions and additions in this pair
+                                    if let Some(line_comments) = line_comments {
+                                        for line in &lines[del_start..del_end] {
+                                            if let Some(old_ln) = line.old_lineno
+                                                && let Some(comments) = line_comments.get(&old_ln)
+                                            {
+                                                for comment in comments {
+                                                    if comment.side == Some(LineSide::Old) {
+                                                        comment_lines +=
+                                                            Self::comment_display_lines(comment);
+                                                    }
+                                                }
+                                            }
+                                        }
 
-        let resolver_choices = vec![ResolverAction::default(); unmappable.len()];
-        let has_unmappable = !unmappable.is_empty();
-        self.submit_state = Some(SubmitState {
-            event,
-            mappable,
-            unmappable,
-            resolver_choices,
-            resolver_cursor: 0,
-            commit_id,
-            skip_confirm,
-        });
+                                        for line in &lines[add_start..add_end] {
+                                            if let Some(new_ln) = line.new_lineno
+                                                && let Some(comments) = line_comments.get(&new_ln)
+                                            {
+                                                for comment in comments {
+                                                    if comment.side != Some(LineSide::Old) {
+                                                        comment_lines +=
+                                                            Self::comment_display_lines(comment);
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
 
-        if has_unmappable {
-            self.input_mode = InputMode::SubmitResolver;
-        } else if skip_confirm {
-            self.input_mode = InputMode::Normal;
-            self.confirm_submit();
-        } else {
-            self.input_mode = InputMode::SubmitConfirm;
+                                    i = add_end;
+                                }
+                                LineOrigin::Addition => {
+                                    // Standalone addition (not following deletions)
+                                    content_lines += 1;
+
+                                    if let Some(line_comments) = line_comments
+                                        && let Some(new_ln) = diff_line.new_lineno
+                                        && let Some(comments) = line_comments.get(&new_ln)
+                                    {
+                                        for comment in comments {
+                                            if comment.side != Some(LineSide::Old) {
+                                                comment_lines +=
+                                                    Self::comment_display_lines(comment);
+                                            }
+                                        }
+                                    }
+
+                                    i += 1;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
         }
+
+        header_lines + comment_lines + content_lines + spacing_lines
     }
 
-    /// Open the bare-`:submit` action picker. The user picks
-    /// Comment/Approve/Request changes/Draft (or cancels); the picked event
-    /// then runs through preflight with `skip_confirm = true` so no extra
-    /// confirmation modal follows.
-    pub fn start_submit_action_picker(&mut self) {
-        if !matches!(self.diff_source, DiffSource::PullRequest(_)) {
-            self.set_warning(":submit only applies in PR mode");
+    fn update_current_file_from_cursor(&mut self) {
+        let mut cumulative = self.review_comments_render_height();
+        if self.diff_state.cursor_line < cumulative {
+            if !self.diff_files.is_empty() {
+                self.diff_state.current_file_idx = 0;
+                self.file_list_state.select(0);
+            }
             return;
         }
-        self.submit_picker_cursor = 0;
-        self.input_mode = InputMode::SubmitActionPicker;
-    }
-
-    /// Move the action-picker cursor down by one row, wrapping at the end.
-    pub fn submit_picker_cursor_down(&mut self) {
-        let total = SUBMIT_PICKER_EVENTS.len();
-        if total > 0 {
-            self.submit_picker_cursor = (self.submit_picker_cursor + 1) % total;
+        for (i, file) in self.diff_files.iter().enumerate() {
+            let height = self.file_render_height(i, file);
+            if cumulative + height > self.diff_state.cursor_line {
+                self.diff_state.current_file_idx = i;
+                self.file_list_state.select(i);
+                return;
+            }
+            cumulative += height;
         }
-    }
-
-    /// Move the action-picker cursor up by one row, wrapping at the start.
-    pub fn submit_picker_cursor_up(&mut self) {
-        let total = SUBMIT_PICKER_EVENTS.len();
-        if total > 0 {
-            self.submit_picker_cursor = (self.submit_picker_cursor + total - 1) % total;
+        if !self.diff_files.is_empty() {
+            self.diff_state.current_file_idx = self.diff_files.len() - 1;
+            self.file_list_state.select(self.diff_files.len() - 1);
         }
     }
 
-    /// Confirm the action picker selection: dispatch into preflight with the
-    /// chosen event and `skip_confirm = true`.
-    pub fn submit_picker_confirm(&mut self) {
-        let Some(event) = SUBMIT_PICKER_EVENTS
-            .get(self.submit_picker_cursor)
-            .map(|(_, ev)| *ev)
-        else {
-            self.cancel_submit_action_picker();
-            return;
-        };
-        self.input_mode = InputMode::Normal;
-        self.start_submit_with(event, true);
+    pub fn total_lines(&self) -> usize {
+        self.review_comments_render_height()
+            + self
+                .diff_files
+                .iter()
+                .enumerate()
+                .map(|(i, f)| self.file_render_height(i, f))
+                .sum::<usize>()
     }
 
-    /// Cancel the action picker without entering preflight.
-    pub fn cancel_submit_action_picker(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.submit_picker_cursor = 0;
+    /// Last line the cursor can occupy. If the final annotation is a Spacing
+    /// separator it is not navigable content and is excluded.
+    pub fn max_cursor_line(&self) -> usize {
+        let total = self.total_lines();
+        if matches!(self.line_annotations.last(), Some(AnnotatedLine::Spacing)) {
+            total.saturating_sub(2)
+        } else {
+            total.saturating_sub(1)
+        }
     }
 
-    pub fn cancel_submit(&mut self) {
-        self.submit_state = None;
-        self.input_mode = InputMode::Normal;
+    /// Calculate the maximum scroll offset.
+    ///
+    /// Allows scrolling until the last line of content is at the top of the viewport.
+    /// This permits empty space below content (e.g. when centering the cursor near EOF)
+    /// while ensuring there is always at least one line of content visible at the top.
+    pub fn max_scroll_offset(&self) -> usize {
+        self.total_lines().saturating_sub(1)
     }
 
-    /// Move the resolver cursor down by one row, clamped to the last row.
-    pub fn submit_resolver_cursor_down(&mut self) {
-        if let Some(state) = self.submit_state.as_mut()
-            && state.resolver_cursor + 1 < state.unmappable.len()
-        {
-            state.resolver_cursor += 1;
-        }
+    /// Calculate the number of display lines a comment takes (header + content + footer)
+    fn comment_display_lines(comment: &Comment) -> usize {
+        let content_lines = comment.content.split('\n').count();
+        2 + content_lines // header + content lines + footer
     }
 
-    pub fn submit_resolver_cursor_up(&mut self) {
-        if let Some(state) = self.submit_state.as_mut()
-            && state.resolver_cursor > 0
-        {
-            state.resolver_cursor -= 1;
+    /// Returns the source line number and side at the current cursor position, if on a diff line
+    pub fn get_line_at_cursor(&self) -> Option<(u32, LineSide)> {
+        let target = self.diff_state.cursor_line;
+        match self.line_annotations.get(target) {
+            Some(AnnotatedLine::DiffLine {
+                old_lineno,
+                new_lineno,
+                ..
+            })
+            | Some(AnnotatedLine::SideBySideLine {
+                old_lineno,
+                new_lineno,
+                ..
+            }) => {
+                // Prefer new line number (for added/context lines), fall back to old (for deleted)
+                new_lineno
+                    .map(|ln| (ln, LineSide::New))
+                    .or_else(|| old_lineno.map(|ln| (ln, LineSide::Old)))
+            }
+            _ => None,
         }
     }
 
-    pub fn submit_resolver_toggle(&mut self) {
-        use crate::forge::submit::ResolverAction;
-        if let Some(state) = self.submit_state.as_mut()
-            && let Some(choice) = state.resolver_choices.get_mut(state.resolver_cursor)
-        {
-            *choice = match choice {
-                ResolverAction::MoveToSummary => ResolverAction::Omit,
-                ResolverAction::Omit => ResolverAction::MoveToSummary,
+    /// The new-side diff line under the cursor as `(path, lineno, line)`.
+    /// Deleted lines have no new-side text and yield `None`.
+    fn new_side_line_at_cursor(&self) -> Option<(PathBuf, u32, &DiffLine)> {
+        let (file_idx, hunk_idx, line_idx) =
+            match self.line_annotations.get(self.diff_state.cursor_line)? {
+                AnnotatedLine::DiffLine {
+                    file_idx,
+                    hunk_idx,
+                    line_idx,
+                    ..
+                } => (*file_idx, *hunk_idx, *line_idx),
+                AnnotatedLine::SideBySideLine {
+                    file_idx,
+                    hunk_idx,
+                    add_line_idx: Some(line_idx),
+                    ..
+                } => (*file_idx, *hunk_idx, *line_idx),
+                _ => return None,
             };
-        }
+        let file = self.diff_files.get(file_idx)?;
+        let line = file.hunks.get(hunk_idx)?.lines.get(line_idx)?;
+        Some((file.display_path().clone(), line.new_lineno?, line))
     }
 
-    /// Advance from the resolver. When `skip_confirm` is set (action-picker
-    /// path), dispatch the network call directly; otherwise route to
-    /// `SubmitConfirm` for the final confirmation modal.
-    pub fn submit_resolver_advance(&mut self) {
-        let Some(state) = self.submit_state.as_ref() else {
+    /// Stash a review-only replacement for the line under the cursor. The
+    /// working tree is left alone; the edit is exported as a patch.
+    pub fn fix_line_at_cursor(&mut self, replacement: &str) {
+        let Some((path, lineno, line)) = self.new_side_line_at_cursor() else {
+            self.set_warning("Move the cursor to an added or context line to fix it");
             return;
         };
-        if state.skip_confirm {
-            self.input_mode = InputMode::Normal;
-            self.confirm_submit();
-        } else {
-            self.input_mode = InputMode::SubmitConfirm;
-        }
+        let original = line.content.clone();
+        self.session
+            .set_review_edit(path.clone(), lineno, original, replacement.to_string());
+        self.mark_dirty();
+        let count = self.session.review_edits.len();
+        self.set_message(format!(
+            "Stashed fix for {}:{lineno} ({count} review fixes)",
+            path.display()
+        ));
     }
 
-    /// True iff the original review head and the latest known PR head
-    /// disagree. PR 5 cannot trigger this (the open-time head equals
-    /// `current_pr_head`), but the field is exposed so the renderer can
-    /// fold the warning in once PR 6 refreshes the remote head.
-    pub fn submit_head_is_stale(&self) -> bool {
-        let Some(state) = self.submit_state.as_ref() else {
-            return false;
-        };
-        match self.current_pr_head.as_deref() {
-            Some(latest) => latest != state.commit_id,
-            None => false,
-        }
+    /// Command-line text for editing the line under the cursor, starting
+    /// from any fix already stashed for it.
+    pub fn fix_command_prefill(&self) -> Option<String> {
+        let (path, lineno, line) = self.new_side_line_at_cursor()?;
+        let current = self
+            .session
+            .review_edits
+            .iter()
+            .find(|e| e.path == path && e.line == lineno)
+            .map_or(line.content.as_str(), |e| e.replacement.as_str());
+        Some(format!("fix {current}"))
     }
 
-    /// Confirm submit — PR 6 dispatches the async `gh api .../reviews` call.
-    /// Builds the body + payload on the main thread, saves the session, then
-    /// hands off to `spawn_pr_submit`. The modal disappears immediately; a
-    /// status-bar spinner takes over until the result lands in
-    /// `poll_pr_submit_events`.
-    pub fn confirm_submit(&mut self) {
-        if let Err(e) = self.spawn_pr_submit() {
-            self.set_error(format!("Submit failed: {e}"));
-            self.submit_state = None;
-            self.input_mode = InputMode::Normal;
+    pub fn unfix_line_at_cursor(&mut self) {
+        let Some((path, lineno, _)) = self.new_side_line_at_cursor() else {
+            self.set_warning("No review fix on this line");
+            return;
+        };
+        if self.session.remove_review_edit(&path, lineno) {
+            self.mark_dirty();
+            self.set_message(format!("Dropped fix for {}:{lineno}", path.display()));
+        } else {
+            self.set_warning("No review fix on this line");
         }
     }
 
-    /// Kick off the create-review call asynchronously. Pre-submit-saves the
-    /// session, builds the JSON payload on the main thread, then runs the
-    /// network round-trip on a background thread. The result is applied
-    /// later in `poll_pr_submit_events`.
-    pub fn spawn_pr_submit(&mut self) -> Result<()> {
-        use crate::forge::github::gh::GitHubGhBackend;
-        use crate::forge::submit::{MovedToSummaryItem, ResolverAction, build_review_body};
-        use crate::forge::traits::{CreateReviewRequest, PullRequestTarget};
-
-        // Snapshot identity from the PR diff source first so the borrow on
-        // `submit_state` below doesn't conflict.
-        let DiffSource::PullRequest(pr) = self.diff_source.clone() else {
-            return Err(TuicrError::UnsupportedOperation(
-                "Not in PR mode".to_string(),
-            ));
+    /// True when the cursor sits on a local comment whose lifecycle state
+    /// has been pushed/submitted to the forge. Such comments are locked from
+    /// edit/delete in tuicr to prevent the local state from drifting from
+    /// what GitHub now stores.
+    pub fn cursor_on_locked_comment(&self) -> bool {
+        let Some(location) = self.find_comment_at_cursor() else {
+            return false;
         };
-        if self.pr_submit_state.is_some() {
-            return Ok(()); // already in flight; ignore
+        match location {
+            CommentLocation::Review { index } => self
+                .session
+                .review_comments
+                .get(index)
+                .is_some_and(|c| c.is_locked()),
+            CommentLocation::File { path, index } => self
+                .session
+                .files
+                .get(&path)
+                .and_then(|review| review.file_comments.get(index))
+                .is_some_and(|c| c.is_locked()),
+            CommentLocation::Line {
+                path,
+                line,
+                side,
+                index,
+            } => self
+                .session
+                .files
+                .get(&path)
+                .and_then(|review| review.line_comments.get(&line))
+                .and_then(|comments| {
+                    let mut side_idx = 0;
+                    for c in comments {
+                        if c.side.unwrap_or(LineSide::New) == side {
+                            if side_idx == index {
+                                return Some(c);
+                            }
+                            side_idx += 1;
+                        }
+                    }
+                    None
+                })
+                .is_some_and(|c| c.is_locked()),
         }
+    }
 
-        let Some(state) = self.submit_state.take() else {
-            return Ok(());
-        };
-
-        let summary_items: Vec<MovedToSummaryItem> = state
-            .unmappable
-            .iter()
-            .zip(state.resolver_choices.iter())
-            .filter_map(|(item, action)| {
-                if *action == ResolverAction::MoveToSummary {
-                    Some(MovedToSummaryItem {
-                        comment: item.comment.clone(),
-                        file: item.file.clone(),
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
-        let body = build_review_body(
-            &self.session.review_comments,
-            &summary_items,
-            &self.forge_config,
-        );
+    /// Find the comment at the current cursor position
+    /// True when the cursor is on a row that belongs to a fetched-from-GitHub
+    /// review thread. Remote threads are read-only in v1; surfaced as a
+    /// distinct condition so the handler can produce a clearer message than
+    /// the generic "no comment at cursor".
+    pub fn cursor_on_remote_thread(&self) -> bool {
+        matches!(
+            self.line_annotations.get(self.diff_state.cursor_line),
+            Some(AnnotatedLine::RemoteThreadLine { .. })
+        )
+    }
 
-        // Save the session BEFORE the network call — keeps the user's
-        // local-draft work durable if anything goes sideways below.
-        let _ = crate::persistence::save_session(&self.session);
+    fn find_comment_at_cursor(&self) -> Option<CommentLocation> {
+        let target = self.diff_state.cursor_line;
+        match self.line_annotations.get(target) {
+            Some(AnnotatedLine::ReviewComment { comment_idx }) => Some(CommentLocation::Review {
+                index: *comment_idx,
+            }),
+            Some(AnnotatedLine::FileComment {
+                file_idx,
+                comment_idx,
+            }) => {
+                let path = self.diff_files.get(*file_idx)?.display_path().clone();
+                Some(CommentLocation::File {
+                    path,
+                    index: *comment_idx,
+                })
+            }
+            Some(AnnotatedLine::LineComment {
+                file_idx,
+                line,
+                side,
+                comment_idx,
+            }) => {
+                let path = self.diff_files.get(*file_idx)?.display_path().clone();
+                Some(CommentLocation::Line {
+                    path,
+                    line: *line,
+                    side: *side,
+                    index: *comment_idx,
+                })
+            }
+            _ => None,
+        }
+    }
 
-        let in_flight = SubmitInFlightState {
-            event: state.event,
-            mappable: state.mappable.clone(),
-            moved_to_summary_count: summary_items.len(),
-            head_sha_snapshot: state.commit_id.clone(),
-            repository: pr.key.repository.clone(),
-            pr_number: pr.key.number,
-            started_at: Instant::now(),
-        };
-        self.pr_submit_state = Some(in_flight.clone());
-        self.input_mode = InputMode::Normal;
+    /// Delete the comment at the current cursor position, if any
+    /// Returns true if a comment was deleted
+    pub fn delete_comment_at_cursor(&mut self) -> bool {
+        let location = self.find_comment_at_cursor();
 
-        let local_checkout = self
-            .forge_backend
-            .as_deref()
-            .and_then(|backend| backend.local_checkout_path());
+        match location {
+            Some(CommentLocation::Review { index })
+                if index < self.session.review_comments.len() =>
+            {
+                let comment = self.session.review_comments.remove(index);
+                self.session.record_event(ReviewEventKind::CommentDeleted {
+                    path: None,
+                    line: None,
+                    comment_type: comment.comment_type.clone(),
+                });
+                self.journal(JournalOp::CommentDeleted {
+                    id: comment.id.clone(),
+                });
+                self.session.trash_comment(comment, None, None);
+                self.mark_dirty();
+                self.set_message("Review comment deleted");
+                self.rebuild_annotations();
+                return true;
+            }
+            Some(CommentLocation::File { path, index }) => {
+                if let Some(review) = self.session.get_file_mut(&path) {
+                    let comment = review.file_comments.remove(index);
+                    self.session.record_event(ReviewEventKind::CommentDeleted {
+                        path: Some(path.clone()),
+                        line: None,
+                        comment_type: comment.comment_type.clone(),
+                    });
+                    self.journal(JournalOp::CommentDeleted {
+                        id: comment.id.clone(),
+                    });
+                    self.session.trash_comment(comment, Some(path), None);
+                    self.mark_dirty();
+                    self.set_message("Comment deleted");
+                    self.rebuild_annotations();
+                    return true;
+                }
+            }
+            Some(CommentLocation::Line {
+                path,
+                line,
+                side,
+                index,
+            }) => {
+                if let Some(review) = self.session.get_file_mut(&path)
+                    && let Some(comments) = review.line_comments.get_mut(&line)
+                {
+                    // Find the actual index by counting comments with matching side
+                    let mut side_idx = 0;
+                    let mut actual_idx = None;
+                    for (i, comment) in comments.iter().enumerate() {
+                        let comment_side = comment.side.unwrap_or(LineSide::New);
+                        if comment_side == side {
+                            if side_idx == index {
+                                actual_idx = Some(i);
+                                break;
+                            }
+                            side_idx += 1;
+                        }
+                    }
+                    if let Some(idx) = actual_idx {
+                        let comment = comments.remove(idx);
+                        if comments.is_empty() {
+                            review.line_comments.remove(&line);
+                        }
+                        self.session.record_event(ReviewEventKind::CommentDeleted {
+                            path: Some(path.clone()),
+                            line: Some(line),
+                            comment_type: comment.comment_type.clone(),
+                        });
+                        self.journal(JournalOp::CommentDeleted {
+                            id: comment.id.clone(),
+                        });
+                        self.session.trash_comment(comment, Some(path), Some(line));
+                        self.mark_dirty();
+                        self.set_message(format!("Comment on line {line} deleted"));
+                        self.rebuild_annotations();
+                        return true;
+                    }
+                }
+            }
+            Some(CommentLocation::Review { .. }) | None => {}
+        }
 
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.pr_submit_rx = Some(rx);
+        false
+    }
 
-        let repository = in_flight.repository.clone();
-        let pr_number = in_flight.pr_number;
-        let head_sha = in_flight.head_sha_snapshot.clone();
-        let event = in_flight.event;
-        let mappable = in_flight.mappable.clone();
-        let commit_id = state.commit_id.clone();
+    pub fn clear_comments(&mut self, scope: ClearScope) {
+        let (cleared, unreviewed) = self.session.clear_comments(scope);
+        if cleared == 0 && unreviewed == 0 {
+            self.set_message("No comments to clear");
+            return;
+        }
 
-        std::thread::spawn(move || {
-            let backend =
-                GitHubGhBackend::new(Some(repository.clone())).with_local_checkout(local_checkout);
-            // Need PR details for repo/owner routing; refetch lightly via
-            // the same target the user opened with.
-            let target = PullRequestTarget::with_repository(
-                repository.clone(),
-                pr_number,
-                pr_number.to_string(),
-            );
-            let result = match backend.get_pull_request(target) {
-                Ok(details) => backend
-                    .create_review(
-                        &details,
-                        CreateReviewRequest {
-                            event,
-                            commit_id: &commit_id,
-                            body: &body,
-                            comments: &mappable,
-                        },
-                    )
-                    .map_err(|e| e.to_string()),
-                Err(e) => Err(e.to_string()),
-            };
-            let _ = tx.send(PrSubmitEvent::Done {
-                repository,
-                pr_number,
-                head_sha,
-                result,
-            });
+        self.journal(JournalOp::Cleared {
+            reviewed: scope == ClearScope::CommentsAndReviewed,
         });
-        Ok(())
+        self.mark_dirty();
+        self.rebuild_annotations();
+        let msg = match (cleared, unreviewed) {
+            (0, n) => format!("Unreviewed {n} files"),
+            (c, 0) => format!("Cleared {c} comments"),
+            (c, n) => format!("Cleared {c} comments, unreviewed {n} files"),
+        };
+        self.set_message(msg);
     }
 
-    /// Pump a pending create-review result. Applies lifecycle writes + the
-    /// success message, or surfaces a sticky error.
-    pub fn poll_pr_submit_events(&mut self) {
-        let Some(rx) = self.pr_submit_rx.as_ref() else {
-            return;
-        };
-        let event = match rx.try_recv() {
-            Ok(e) => e,
-            Err(_) => return,
-        };
-        self.pr_submit_rx = None;
-        let in_flight = self.pr_submit_state.take();
-        let PrSubmitEvent::Done {
-            repository,
-            pr_number,
-            head_sha,
-            result,
-        } = event;
-
-        // Stale-result discard: if the user reloaded the PR mid-submit, the
-        // active head SHA may have moved. Drop the result rather than
-        // silently mutating the wrong session.
-        let Some(in_flight) = in_flight else {
-            return;
-        };
-        let stale = in_flight.repository != repository
-            || in_flight.pr_number != pr_number
-            || in_flight.head_sha_snapshot != head_sha;
-        if stale {
-            self.set_message("Discarded stale submit result (PR was reloaded)".to_string());
+    /// Summarize checklist progress in the status bar, naming the first open
+    /// item so `:check <n>` has an obvious next target.
+    pub fn show_checklist(&mut self) {
+        let total = self.session.checklist.len();
+        if total == 0 {
+            self.set_message("No checklist (start with --template <name>)");
             return;
         }
-
-        self.finish_pr_submit(in_flight, result);
+        let done = self.session.checklist.iter().filter(|i| i.done).count();
+        let next = self
+            .session
+            .checklist
+            .iter()
+            .position(|i| !i.done)
+            .map(|idx| format!(" · next {}: {}", idx + 1, self.session.checklist[idx].text))
+            .unwrap_or_default();
+        self.set_message(format!("Checklist {done}/{total}{next}"));
     }
 
-    /// Apply the create-review result on the main thread. On success: flip
-    /// each included `Comment` to `Submitted` (or `PushedDraft` for the
-    /// draft event), stamp `remote_review_id`, save the session again, and
-    /// publish a success message. On failure: keep everything as
-    /// `LocalDraft` and set a sticky error.
-    pub fn finish_pr_submit(
-        &mut self,
-        in_flight: SubmitInFlightState,
-        result: std::result::Result<crate::forge::traits::GhCreateReviewResponse, String>,
-    ) {
-        use crate::forge::submit::SubmitEvent;
-
-        let response = match result {
-            Ok(r) => r,
-            Err(e) => {
-                self.set_error(format!("Submit failed: {e}"));
-                return;
+    pub fn toggle_checklist_item(&mut self, index: usize) {
+        match self.session.toggle_checklist_item(index) {
+            Some(done) => {
+                self.mark_dirty();
+                let mark = if done { "x" } else { " " };
+                let text = &self.session.checklist[index - 1].text;
+                self.set_message(format!("[{mark}] {text}"));
             }
-        };
-
-        self.apply_submit_success(&in_flight, &response);
+            None => self.set_warning(format!("No checklist item {index}")),
+        }
+    }
 
-        // Post-submit save — captures the lifecycle transitions.
-        let _ = crate::persistence::save_session(&self.session);
+    /// Enter edit mode for the comment at the current cursor position
+    /// Returns true if a comment was found and edit mode entered
+    pub fn enter_edit_mode(&mut self) -> bool {
+        let location = self.find_comment_at_cursor();
 
-        let inline_count = in_flight.mappable.len();
-        let summary_count = in_flight.moved_to_summary_count;
-        let message = match in_flight.event {
-            SubmitEvent::Draft => {
-                let pr_url = match &self.diff_source {
-                    DiffSource::PullRequest(pr) => pr.url.clone(),
-                    _ => String::new(),
-                };
-                if pr_url.is_empty() {
-                    format!(
-                        "Pushed pending GitHub review #{}: {} inline, {} moved to summary",
-                        response.id, inline_count, summary_count,
-                    )
-                } else {
-                    format!(
-                        "Pushed pending GitHub review #{}: {} inline, {} moved to summary — Finish it in GitHub: {}",
-                        response.id, inline_count, summary_count, pr_url,
-                    )
+        match location {
+            Some(CommentLocation::Review { index }) => {
+                if let Some(comment) = self.session.review_comments.get(index) {
+                    self.input_mode = InputMode::Comment;
+                    self.comment_buffer = comment.content.clone();
+                    self.comment_cursor = self.comment_buffer.len();
+                    self.comment_type = comment.comment_type.clone();
+                    self.comment_blocking = comment.blocking;
+                    self.comment_is_review_level = true;
+                    self.comment_is_file_level = false;
+                    self.comment_line = None;
+                    self.editing_comment_id = Some(comment.id.clone());
+                    return true;
                 }
             }
-            _ => format!(
-                "Submitted GitHub review #{}: {} inline, {} moved to summary",
-                response.id, inline_count, summary_count,
-            ),
-        };
-        self.set_message(message);
-    }
-
-    /// Flip each included `Comment` from `LocalDraft` to `Submitted` (or
-    /// `PushedDraft` for `:submit draft`) and stamp `remote_review_id`.
-    /// Moved-to-summary items are NOT touched here — they embed in the
-    /// review body, not as inline comments, so GitHub doesn't assign them
-    /// individual IDs.
-    pub fn apply_submit_success(
-        &mut self,
-        in_flight: &SubmitInFlightState,
-        response: &crate::forge::traits::GhCreateReviewResponse,
-    ) {
-        use crate::forge::submit::SubmitEvent;
-        use crate::model::comment::CommentLifecycleState;
-
-        let new_state = match in_flight.event {
-            SubmitEvent::Draft => CommentLifecycleState::PushedDraft,
-            _ => CommentLifecycleState::Submitted,
-        };
-        let review_id = response.id.to_string();
-
-        // Build a set of source comment IDs we expect to lock. Looking each
-        // one up by walking `file_reviews` is O(N*M); for v1 PRs this is
-        // negligible and avoids restructuring the session indexes.
-        let target_ids: std::collections::HashSet<&str> = in_flight
-            .mappable
-            .iter()
-            .map(|c| c.comment_id.as_str())
-            .collect();
-        if target_ids.is_empty() {
-            return;
-        }
-
-        for review in self.session.files.values_mut() {
-            for comment in review.file_comments.iter_mut() {
-                if target_ids.contains(comment.id.as_str()) {
-                    comment.lifecycle_state = new_state;
-                    comment.remote_review_id = Some(review_id.clone());
+            Some(CommentLocation::File { path, index }) => {
+                if let Some(review) = self.session.files.get(&path)
+                    && let Some(comment) = review.file_comments.get(index)
+                {
+                    self.input_mode = InputMode::Comment;
+                    self.comment_buffer = comment.content.clone();
+                    self.comment_cursor = self.comment_buffer.len();
+                    self.comment_type = comment.comment_type.clone();
+                    self.comment_blocking = comment.blocking;
+                    self.comment_is_review_level = false;
+                    self.comment_is_file_level = true;
+                    self.comment_line = None;
+                    self.editing_comment_id = Some(comment.id.clone());
+                    return true;
                 }
             }
-            for comments in review.line_comments.values_mut() {
-                for comment in comments.iter_mut() {
-                    if target_ids.contains(comment.id.as_str()) {
-                        comment.lifecycle_state = new_state;
-                        comment.remote_review_id = Some(review_id.clone());
+            Some(CommentLocation::Line {
+                path,
+                line,
+                side,
+                index,
+            }) => {
+                if let Some(review) = self.session.files.get(&path)
+                    && let Some(comments) = review.line_comments.get(&line)
+                {
+                    // Find the actual comment by counting comments with matching side
+                    let mut side_idx = 0;
+                    for comment in comments.iter() {
+                        let comment_side = comment.side.unwrap_or(LineSide::New);
+                        if comment_side == side {
+                            if side_idx == index {
+                                self.input_mode = InputMode::Comment;
+                                self.comment_buffer = comment.content.clone();
+                                self.comment_cursor = self.comment_buffer.len();
+                                self.comment_type = comment.comment_type.clone();
+                                self.comment_blocking = comment.blocking;
+                                self.comment_is_review_level = false;
+                                self.comment_is_file_level = false;
+                                self.comment_line = Some((line, side));
+                                self.editing_comment_id = Some(comment.id.clone());
+                                return true;
+                            }
+                            side_idx += 1;
+                        }
                     }
                 }
             }
+            None => {}
         }
-    }
-
-    /// Open the review target selector on a specific tab.
-    ///
-    /// `Local` loads the recent-commits list (same as the historical commit
-    /// selector). `PullRequests` switches the tab; the actual fetch is
-    /// triggered lazily through `on_target_tab_entered`.
-    pub fn enter_target_selector(&mut self, initial_tab: TargetTab) -> Result<()> {
-        // Save inline selection state if we have review commits
-        if !self.review_commits.is_empty() {
-            self.saved_inline_selection = self.commit_selection_range;
-        }
-
-        let highlighter = self.theme.syntax_highlighter();
-        let (change_status, _) = Self::get_change_status_with_ignore(
-            self.vcs.as_ref(),
-            &self.vcs_info.root_path,
-            highlighter,
-            self.path_filter.as_deref(),
-        )?;
-        let has_staged_changes = change_status.staged;
-        let has_unstaged_changes = change_status.unstaged;
 
-        let commits = self.vcs.get_recent_commits(0, VISIBLE_COMMIT_COUNT)?;
-        let no_local_targets = commits.is_empty() && !has_staged_changes && !has_unstaged_changes;
-        // Allow opening the selector on the Pull Requests tab even when there
-        // are no local commits or changes — the PR tab is the user's reason
-        // for being here.
-        if no_local_targets && initial_tab == TargetTab::Local {
-            self.set_message("No commits or staged/unstaged changes found");
-            return Ok(());
-        }
+        false
+    }
 
-        // Check if there might be more commits
-        self.has_more_commit = commits.len() >= VISIBLE_COMMIT_COUNT;
-        self.commit_list = commits;
-        if has_staged_changes {
-            self.commit_list.insert(0, Self::staged_commit_entry());
-        }
-        if has_unstaged_changes {
-            self.commit_list.insert(0, Self::unstaged_commit_entry());
-        }
-        self.commit_list_cursor = 0;
-        self.commit_list_scroll_offset = 0;
-        self.commit_selection_range = None;
-        self.visible_commit_count = self.commit_list.len();
-        self.input_mode = InputMode::CommitSelect;
+    pub fn enter_command_mode(&mut self) {
+        self.input_mode = InputMode::Command;
+        self.command_buffer.clear();
+    }
 
-        // Reset the PR tab to Idle each time the selector is opened so the
-        // fetch happens lazily on first visit.
-        self.pr_tab = PullRequestsTab::new(self.forge_repository.clone());
-        self.pr_filter_draft = None;
-        self.pr_load_rx = None;
+    pub fn exit_command_mode(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.command_buffer.clear();
+    }
 
-        self.target_tab = initial_tab;
-        if initial_tab == TargetTab::PullRequests {
-            self.on_target_tab_entered();
-        }
-        Ok(())
+    pub fn enter_search_mode(&mut self) {
+        self.input_mode = InputMode::Search;
+        self.search_buffer.clear();
     }
 
-    pub fn exit_commit_select_mode(&mut self) -> Result<()> {
+    pub fn exit_search_mode(&mut self) {
         self.input_mode = InputMode::Normal;
+        self.search_buffer.clear();
+    }
 
-        // If we have review commits, restore the inline selector state
-        if !self.review_commits.is_empty() {
-            self.commit_list = self.review_commits.clone();
-            self.commit_selection_range = self.saved_inline_selection;
-            self.commit_list_cursor = 0;
-            self.commit_list_scroll_offset = 0;
-            self.visible_commit_count = self.review_commits.len();
-            self.has_more_commit = false;
-            self.saved_inline_selection = None;
-
-            // Reload diff for the restored selection
-            if self.commit_selection_range.is_some() {
-                self.reload_inline_selection()?;
-            }
-            return Ok(());
-        }
+    pub fn enter_comment_mode(&mut self, file_level: bool, line: Option<(u32, LineSide)>) {
+        self.input_mode = InputMode::Comment;
+        self.comment_buffer.clear();
+        self.comment_cursor = 0;
+        self.comment_type = self.default_comment_type();
+        self.comment_blocking = None;
+        self.comment_is_review_level = false;
+        self.comment_is_file_level = file_level;
+        self.comment_line = line;
+    }
 
-        // If we were viewing commits, try to go back to working tree
-        if matches!(
-            self.diff_source,
-            DiffSource::CommitRange(_) | DiffSource::StagedUnstagedAndCommits(_)
-        ) {
-            let highlighter = self.theme.syntax_highlighter();
-            match Self::get_working_tree_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                highlighter,
-                self.path_filter.as_deref(),
-            ) {
-                Ok(diff_files) => {
-                    self.diff_files = diff_files;
-                    self.diff_source = DiffSource::StagedAndUnstaged;
+    pub fn enter_review_comment_mode(&mut self) {
+        self.input_mode = InputMode::Comment;
+        self.comment_buffer.clear();
+        self.comment_cursor = 0;
+        self.comment_type = self.default_comment_type();
+        self.comment_blocking = None;
+        self.comment_is_review_level = true;
+        self.comment_is_file_level = false;
+        self.comment_line = None;
+        self.comment_line_range = None;
+        self.editing_comment_id = None;
+    }
 
-                    // Update session for new files
-                    for file in &self.diff_files {
-                        let path = file.display_path().clone();
-                        self.session.add_file(path, file.status, file.content_hash);
-                    }
+    /// Whether Esc would throw away typed text: a new comment with content,
+    /// or an edit that no longer matches the saved comment.
+    pub fn comment_has_unsaved_text(&self) -> bool {
+        let buffer = self.comment_buffer.trim();
+        let Some(editing_id) = &self.editing_comment_id else {
+            return !buffer.is_empty();
+        };
+        let saved = self
+            .session
+            .review_comments
+            .iter()
+            .chain(self.session.files.values().flat_map(|review| {
+                review
+                    .file_comments
+                    .iter()
+                    .chain(review.line_comments.values().flatten())
+            }))
+            .find(|c| &c.id == editing_id)
+            .map(|c| c.content.trim());
+        saved != Some(buffer)
+    }
 
-                    self.sort_files_by_directory(true);
-                    self.expand_all_dirs();
-                }
-                Err(_) => {
-                    self.set_message("No staged or unstaged changes");
-                }
-            }
+    /// Esc in the comment editor: ask before dropping unsaved text.
+    pub fn cancel_comment(&mut self) {
+        if self.comment_has_unsaved_text() {
+            self.enter_confirm_mode(ConfirmAction::DiscardComment);
+        } else {
+            self.exit_comment_mode();
         }
+    }
 
-        Ok(())
+    pub fn exit_comment_mode(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.comment_buffer.clear();
+        self.comment_cursor = 0;
+        self.comment_is_review_level = false;
+        self.editing_comment_id = None;
+        self.comment_line_range = None;
     }
 
-    /// Switch to the next/previous tab in the review target selector.
-    /// With only two tabs, forward and reverse are equivalent; the `_forward`
-    /// arg is kept so callers can pass the natural direction without a cast.
-    /// Triggers the lazy PR fetch the first time the PR tab is entered.
-    pub fn cycle_target_tab(&mut self, _forward: bool) {
-        let next = match self.target_tab {
-            TargetTab::Local => TargetTab::PullRequests,
-            TargetTab::PullRequests => TargetTab::Local,
+    pub fn enter_visual_mode_at_cursor(&mut self) {
+        let idx = self.diff_state.cursor_line;
+        let side = self
+            .get_line_at_cursor()
+            .map(|(_, s)| s)
+            .unwrap_or(LineSide::New);
+        let len = self.annotation_content_len(idx, side);
+        let anchor = SelPoint {
+            annotation_idx: idx,
+            char_offset: 0,
+            side,
         };
-        self.target_tab = next;
-        if next == TargetTab::PullRequests {
-            self.on_target_tab_entered();
-        } else {
-            // Returning to Local: clear any half-typed PR filter draft.
-            self.pr_filter_draft = None;
-        }
+        let head = SelPoint {
+            annotation_idx: idx,
+            char_offset: len,
+            side,
+        };
+        self.input_mode = InputMode::VisualSelect;
+        self.visual_selection = Some(VisualSelection { anchor, head });
     }
 
-    /// Entry-point hook called when the PR tab becomes visible.
-    /// Triggers the first network call lazily.
-    fn on_target_tab_entered(&mut self) {
-        if let Some(repo) = self.pr_tab.start_initial_load() {
-            self.spawn_pr_initial_load(repo);
-        }
+    pub fn exit_visual_mode(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.visual_selection = None;
     }
 
-    /// Spawn a background thread that fetches the initial PR list. The
-    /// resulting `PrLoadEvent::Initial` is delivered through `pr_load_rx`
-    /// and applied in the main loop via `poll_pr_load_events`.
-    fn spawn_pr_initial_load(&mut self, repository: ForgeRepository) {
-        use crate::forge::github::gh::GitHubGhBackend;
-        use crate::forge::selector::PR_PAGE_SIZE;
-        use crate::forge::traits::{ForgeBackend, PullRequestListQuery};
+    pub fn get_visual_selection(&self) -> Option<&VisualSelection> {
+        if self.input_mode != InputMode::VisualSelect {
+            return None;
+        }
+        self.visual_selection.as_ref()
+    }
 
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.pr_load_rx = Some(rx);
+    pub fn annotation_content_len(&self, idx: usize, side: LineSide) -> usize {
+        self.content_for_side(idx, side)
+            .map(|s| s.chars().count())
+            .unwrap_or(0)
+    }
 
-        std::thread::spawn(move || {
-            let backend = GitHubGhBackend::new(Some(repository.clone()));
-            let query = PullRequestListQuery::first_page(repository, PR_PAGE_SIZE);
-            let result = backend
-                .list_pull_requests(query)
-                .map(|page| (page.pull_requests, page.has_more))
-                .map_err(|err| err.to_string());
-            let _ = tx.send(PrLoadEvent::Initial(result));
+    pub fn extend_visual_to_cursor(&mut self) {
+        let Some(sel) = self.visual_selection else {
+            return;
+        };
+        let anchor_idx = sel.anchor.annotation_idx;
+        let cursor_idx = self.diff_state.cursor_line;
+        let side = sel.anchor.side;
+        let anchor_len = self.annotation_content_len(anchor_idx, side);
+        let cursor_len = self.annotation_content_len(cursor_idx, side);
+        let (anchor_char, head_char) = if cursor_idx >= anchor_idx {
+            (0, cursor_len)
+        } else {
+            (anchor_len, 0)
+        };
+        self.visual_selection = Some(VisualSelection {
+            anchor: SelPoint {
+                annotation_idx: anchor_idx,
+                char_offset: anchor_char,
+                side,
+            },
+            head: SelPoint {
+                annotation_idx: cursor_idx,
+                char_offset: head_char,
+                side,
+            },
         });
     }
 
-    /// Spawn a background thread that fetches the next page of PRs.
-    fn spawn_pr_load_more(&mut self, repository: ForgeRepository, already_loaded: usize) {
-        use crate::forge::github::gh::GitHubGhBackend;
-        use crate::forge::selector::PR_PAGE_SIZE;
-        use crate::forge::traits::{ForgeBackend, PullRequestListQuery};
-
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.pr_load_rx = Some(rx);
+    pub fn visual_selection_line_range(&self) -> Option<(LineRange, LineSide)> {
+        let sel = self.get_visual_selection()?;
+        let (start, end) = sel.ordered();
+        let start_line = self.annotation_line_for_side(start.annotation_idx, start.side);
+        let end_line = self.annotation_line_for_side(end.annotation_idx, end.side);
+        let start_ln = start_line?;
+        let end_ln = end_line?;
+        Some((LineRange::new(start_ln, end_ln), start.side))
+    }
 
-        std::thread::spawn(move || {
-            let backend = GitHubGhBackend::new(Some(repository.clone()));
-            let query = PullRequestListQuery {
-                repository,
-                already_loaded,
-                page_size: PR_PAGE_SIZE,
-            };
-            let result = backend
-                .list_pull_requests(query)
-                .map(|page| (page.pull_requests, page.has_more))
-                .map_err(|err| err.to_string());
-            let _ = tx.send(PrLoadEvent::LoadMore(result));
-        });
+    fn annotation_line_for_side(&self, idx: usize, side: LineSide) -> Option<u32> {
+        match self.line_annotations.get(idx)? {
+            AnnotatedLine::DiffLine {
+                old_lineno,
+                new_lineno,
+                ..
+            }
+            | AnnotatedLine::SideBySideLine {
+                old_lineno,
+                new_lineno,
+                ..
+            } => match side {
+                LineSide::New => *new_lineno,
+                LineSide::Old => *old_lineno,
+            },
+            _ => None,
+        }
     }
 
-    /// Pump any pending PR fetch events into the tab state.
-    /// Called from the main loop each tick; non-blocking.
-    pub fn poll_pr_load_events(&mut self) {
-        let Some(rx) = self.pr_load_rx.as_ref() else {
-            return;
-        };
-        let mut events = Vec::new();
-        while let Ok(event) = rx.try_recv() {
-            events.push(event);
+    pub fn enter_comment_from_visual(&mut self) {
+        if let Some((range, side)) = self.visual_selection_line_range() {
+            self.comment_line_range = Some((range, side));
+            self.comment_line = Some((range.end, side));
+            self.input_mode = InputMode::Comment;
+            self.comment_buffer.clear();
+            self.comment_cursor = 0;
+            self.comment_type = self.default_comment_type();
+            self.comment_blocking = None;
+            self.comment_is_review_level = false;
+            self.comment_is_file_level = false;
+            self.visual_selection = None;
+        } else {
+            self.set_warning("Invalid visual selection");
+            self.exit_visual_mode();
         }
-        if events.is_empty() {
+    }
+
+    pub fn save_comment(&mut self) {
+        if self.comment_buffer.trim().is_empty() {
+            self.set_message("Comment cannot be empty");
             return;
         }
-        // The channel is single-use per fetch; drop the receiver once a
-        // result has arrived so we don't keep checking it.
-        self.pr_load_rx = None;
-        for event in events {
-            match event {
-                PrLoadEvent::Initial(result) => self.pr_tab.apply_initial_load(result),
-                PrLoadEvent::LoadMore(result) => self.pr_tab.apply_load_more(result),
-            }
+
+        let content = self.comment_buffer.trim().to_string();
+        let reviewer = self.reviewer.clone();
+        if reviewer.is_some() {
+            self.session.reviewer.clone_from(&reviewer);
         }
-        self.pr_tab.clamp_cursor();
-    }
 
-    pub fn pr_tab_cursor_up(&mut self) {
-        self.pr_tab.cursor_up();
-        self.pr_tab
-            .ensure_cursor_visible(self.pr_list_viewport_height);
-    }
+        let mut message = "Error: Could not save comment".to_string();
+        let comment_type = self.comment_type.clone();
+        // (file, line) the event log records for the saved comment
+        let mut event_location = None;
+        // Id of a newly written comment, recorded on the viewed commit
+        let mut new_comment_id = None;
 
-    pub fn pr_tab_cursor_down(&mut self) {
-        self.pr_tab.cursor_down();
-        self.pr_tab
-            .ensure_cursor_visible(self.pr_list_viewport_height);
-    }
+        // Check if we're editing an existing comment
+        if let Some(editing_id) = &self.editing_comment_id {
+            if let Some(comment) = self
+                .session
+                .review_comments
+                .iter_mut()
+                .find(|c| &c.id == editing_id)
+            {
+                comment.content = content.clone();
+                comment.comment_type = self.comment_type.clone();
+                comment.blocking = self.comment_blocking;
+                message = "Review comment updated".to_string();
+                event_location = Some((None, None));
+            } else if let Some(path) = self.current_file_path().cloned()
+                && let Some(review) = self.session.get_file_mut(&path)
+            {
+                if let Some(comment) = review
+                    .file_comments
+                    .iter_mut()
+                    .find(|c| &c.id == editing_id)
+                {
+                    comment.content = content.clone();
+                    comment.comment_type = self.comment_type.clone();
+                    comment.blocking = self.comment_blocking;
+                    message = "Comment updated".to_string();
+                    event_location = Some((Some(path), None));
+                } else {
+                    // If not found in file comments, search in line comments
+                    let mut found_comment = None;
+                    for (line, comments) in review.line_comments.iter_mut() {
+                        if let Some(comment) = comments.iter_mut().find(|c| &c.id == editing_id) {
+                            found_comment = Some((*line, comment));
+                            break;
+                        }
+                    }
 
-    /// Handle Enter on the PR tab. Returns true when the action was handled
-    /// (load more triggered, PR open kicked off, error surfaced, etc).
-    pub fn pr_tab_select(&mut self) -> bool {
-        // Block re-entry while a previous open is still resolving — the
-        // spinner glyph on the row already tells the user something is in
-        // flight.
-        if self.pr_open_state.is_some() {
-            return true;
+                    if let Some((line, comment)) = found_comment {
+                        comment.content = content.clone();
+                        comment.comment_type = self.comment_type.clone();
+                        comment.blocking = self.comment_blocking;
+                        event_location = Some((Some(path), Some(line)));
+                        message = if let Some((line, _)) = self.comment_line {
+                            format!("Comment on line {line} updated")
+                        } else {
+                            "Comment updated".to_string()
+                        };
+                    } else {
+                        message = "Error: Comment to edit not found".to_string();
+                    }
+                }
+            }
+        } else if self.comment_is_review_level {
+            let comment = Comment::new(content, self.comment_type.clone(), None)
+                .with_blocking(self.comment_blocking)
+                .with_reviewer(reviewer.clone());
+            new_comment_id = Some(comment.id.clone());
+            self.session.review_comments.push(comment);
+            message = "Review comment added".to_string();
+            event_location = Some((None, None));
+        } else if let Some(path) = self.current_file_path().cloned()
+            && let Some(review) = self.session.get_file_mut(&path)
+        {
+            // Create new comment
+            let mut line = None;
+            let diff_file = self.diff_files.iter().find(|f| *f.display_path() == path);
+            if self.comment_is_file_level {
+                let comment = Comment::new(content, self.comment_type.clone(), None)
+                    .with_blocking(self.comment_blocking)
+                    .with_reviewer(reviewer.clone());
+                new_comment_id = Some(comment.id.clone());
+                review.add_file_comment(comment);
+                message = "File comment added".to_string();
+            } else if let Some((range, side)) = self.comment_line_range {
+                // Range comment from visual selection
+                let mut comment =
+                    Comment::new_with_range(content, self.comment_type.clone(), Some(side), range)
+                        .with_blocking(self.comment_blocking)
+                        .with_reviewer(reviewer.clone());
+                comment.line_context = diff_file.and_then(|f| anchor::capture(f, range.end, side));
+                // Store by end line of the range
+                new_comment_id = Some(comment.id.clone());
+                review.add_line_comment(range.end, comment);
+                line = Some(range.end);
+                if range.is_single() {
+                    message = format!("Comment added to line {}", range.end);
+                } else {
+                    message = format!("Comment added to lines {}-{}", range.start, range.end);
+                }
+            } else if let Some((comment_line, side)) = self.comment_line {
+                let mut comment = Comment::new(content, self.comment_type.clone(), Some(side))
+                    .with_blocking(self.comment_blocking)
+                    .with_reviewer(reviewer.clone());
+                comment.line_context =
+                    diff_file.and_then(|f| anchor::capture(f, comment_line, side));
+                new_comment_id = Some(comment.id.clone());
+                review.add_line_comment(comment_line, comment);
+                line = Some(comment_line);
+                message = format!("Comment added to line {comment_line}");
+            } else {
+                // Fallback to file comment if no line specified
+                let comment = Comment::new(content, self.comment_type.clone(), None)
+                    .with_blocking(self.comment_blocking)
+                    .with_reviewer(reviewer.clone());
+                new_comment_id = Some(comment.id.clone());
+                review.add_file_comment(comment);
+                message = "File comment added".to_string();
+            }
+            event_location = Some((Some(path), line));
         }
-        if self.pr_tab.cursor_on_load_more() {
-            if let Some((repo, already)) = self.pr_tab.start_load_more() {
-                self.spawn_pr_load_more(repo, already);
+
+        if let Some((path, line)) = event_location {
+            self.session
+                .record_event(if self.editing_comment_id.is_some() {
+                    ReviewEventKind::CommentEdited {
+                        path,
+                        line,
+                        comment_type,
+                    }
+                } else {
+                    ReviewEventKind::CommentAdded {
+                        path,
+                        line,
+                        comment_type,
+                    }
+                });
+            if let Some(id) = new_comment_id.clone().or(self.editing_comment_id.clone()) {
+                self.journal_comment(&id);
             }
-            return true;
         }
-        // Clone the summary so we drop the immutable borrow before mutating
-        // the app to enter PR mode.
-        let Some(summary) = self.pr_tab.cursor_pr().cloned() else {
-            return false;
+        if let Some(id) = new_comment_id
+            && let Some(commit) = self.viewed_commit().map(str::to_string)
+        {
+            self.session
+                .commits
+                .entry(commit)
+                .or_default()
+                .comments
+                .push(id);
+        }
+        if !message.starts_with("Error:") {
+            self.mark_dirty();
+        }
+        self.set_message(message);
+        self.rebuild_annotations();
+
+        self.exit_comment_mode();
+    }
+
+    pub fn cycle_comment_type(&mut self) {
+        if self.comment_types.is_empty() {
+            return;
+        }
+
+        let current_id = self.comment_type.id();
+        let current_index = self
+            .comment_types
+            .iter()
+            .position(|comment_type| comment_type.id == current_id)
+            .unwrap_or(0);
+        let next_index = (current_index + 1) % self.comment_types.len();
+        self.comment_type = CommentType::from_id(&self.comment_types[next_index].id);
+    }
+
+    /// Cycle the comment being written through unset, blocking and
+    /// non-blocking.
+    pub fn cycle_comment_blocking(&mut self) {
+        self.comment_blocking = match self.comment_blocking {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
         };
-        self.spawn_pr_open(&summary);
-        true
     }
 
-    /// Kick off the background fetch for a PR open. The main thread keeps
-    /// rendering and pumping events; the resulting `PrOpenEvent::Done` is
-    /// drained in `poll_pr_open_events` where parsing happens and PR mode
-    /// is entered.
-    fn spawn_pr_open(&mut self, summary: &crate::forge::traits::PullRequestSummary) {
-        use crate::forge::github::gh::GitHubGhBackend;
-        use crate::forge::pr_open::fetch_pr_data;
-        use crate::forge::traits::PullRequestTarget;
+    pub fn cycle_comment_type_reverse(&mut self) {
+        if self.comment_types.is_empty() {
+            return;
+        }
 
-        let local_checkout = Some(self.vcs_info.root_path.clone());
-        let request = PrOpenRequest {
-            repository: summary.repository.clone(),
-            pr_number: summary.number,
-            started_at: Instant::now(),
+        let current_id = self.comment_type.id();
+        let current_index = self
+            .comment_types
+            .iter()
+            .position(|comment_type| comment_type.id == current_id)
+            .unwrap_or(0);
+        let prev_index = if current_index == 0 {
+            self.comment_types.len() - 1
+        } else {
+            current_index - 1
         };
-        self.pr_open_state = Some(request.clone());
+        self.comment_type = CommentType::from_id(&self.comment_types[prev_index].id);
+    }
 
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.pr_open_rx = Some(rx);
+    /// Open `mode` as an overlay on top of the current mode, which gets input
+    /// back once the overlay closes.
+    pub fn open_overlay(&mut self, mode: InputMode) {
+        self.overlay_stack.push(self.input_mode);
+        self.input_mode = mode;
+    }
 
-        let summary_repo = summary.repository.clone();
-        let pr_number = summary.number;
-        let thread_local_checkout = local_checkout.clone();
-        std::thread::spawn(move || {
-            let backend = GitHubGhBackend::new(Some(summary_repo.clone()))
-                .with_local_checkout(thread_local_checkout);
-            let target =
-                PullRequestTarget::with_repository(summary_repo, pr_number, pr_number.to_string());
-            let outcome = fetch_pr_data(&backend, target).map_err(|e| e.to_string());
-            let _ = tx.send(PrOpenEvent::Done {
-                request,
-                result: outcome,
-            });
-        });
+    /// Close the top overlay and return input to the mode beneath it.
+    pub fn close_overlay(&mut self) {
+        self.input_mode = self.overlay_stack.pop().unwrap_or(InputMode::Normal);
     }
 
-    /// Drain any pending PR-open result and apply it. On success, parses
-    /// the diff and enters PR diff mode; on failure, routes the error
-    /// into the selector banner. Either way, clears `pr_open_state` and
-    /// the receiver so the spinner stops animating.
-    pub fn poll_pr_open_events(&mut self) {
-        let Some(rx) = self.pr_open_rx.as_ref() else {
-            return;
-        };
-        let event = match rx.try_recv() {
-            Ok(e) => e,
-            Err(_) => return,
-        };
-        self.pr_open_rx = None;
-        let in_flight = self.pr_open_state.clone();
-        self.pr_open_state = None;
-        match event {
-            PrOpenEvent::Done { request, result } => {
-                // If the user cancelled (cleared pr_open_state) but the
-                // background thread sent a result before being torn down,
-                // ignore the result rather than entering PR mode.
-                if !in_flight
-                    .as_ref()
-                    .map(|s| s.matches(&request.repository, request.pr_number))
-                    .unwrap_or(false)
-                {
-                    return;
-                }
-                match result {
-                    Ok((details, patch, commits)) => {
-                        if let Err(e) = self.finish_pr_open(details, patch, commits, &request) {
-                            self.set_error(format!(
-                                "Failed to open PR #{}: {}",
-                                request.pr_number, e
-                            ));
-                        }
-                    }
-                    Err(e) => {
-                        self.set_error(format!("Failed to open PR #{}: {}", request.pr_number, e));
-                    }
-                }
-            }
+    /// Swap the top overlay for `mode` without touching what it covers, for
+    /// modals that advance into the next step of the same flow.
+    pub fn replace_overlay(&mut self, mode: InputMode) {
+        if self.overlay_stack.is_empty() {
+            self.open_overlay(mode);
+        } else {
+            self.input_mode = mode;
         }
     }
 
-    /// Main-thread half of the PR open: parse the patch, build the
-    /// session, and enter PR diff mode. Mirrors what the previous synchronous
-    /// `open_pr_with_backend` did, but the network fetch has already
-    /// happened on the background thread.
-    fn finish_pr_open(
-        &mut self,
-        details: crate::forge::traits::PullRequestDetails,
-        patch: String,
-        commits: Vec<crate::forge::traits::PullRequestCommit>,
-        request: &PrOpenRequest,
-    ) -> Result<()> {
-        use crate::forge::github::gh::GitHubGhBackend;
-        use crate::forge::pr_open::prepare_open_pr;
+    /// Close every overlay and return to the mode under the bottom one.
+    pub fn close_all_overlays(&mut self) {
+        self.input_mode = self.base_mode();
+        self.overlay_stack.clear();
+    }
 
-        let local_checkout = Some(self.vcs_info.root_path.clone());
-        let highlighter = self.theme.syntax_highlighter();
-        let mut opened = prepare_open_pr(
-            details.clone(),
-            &patch,
-            commits,
-            local_checkout.as_deref(),
-            highlighter,
-        )?;
-        Self::load_or_apply_pr_session(&mut opened);
-        let backend = Box::new(
-            GitHubGhBackend::new(Some(request.repository.clone()))
-                .with_local_checkout(local_checkout.clone()),
-        );
-        self.enter_pr_diff_mode(backend, opened)?;
-        // Kick the remote-thread fetch off on a fresh background thread.
-        // The diff view is already up; threads fade in once they land.
-        self.spawn_pr_threads_fetch(&details, local_checkout);
-        self.set_message(format!(
-            "Opened PR {}#{}",
-            request.repository.display_name(),
-            request.pr_number,
-        ));
-        Ok(())
+    /// The mode under all overlays, which decides what the screen shows.
+    pub fn base_mode(&self) -> InputMode {
+        self.overlay_stack
+            .first()
+            .copied()
+            .unwrap_or(self.input_mode)
     }
 
-    /// Kick off a background fetch of remote review threads for `details`.
-    /// Replaces any in-flight fetch — we don't try to merge results across
-    /// concurrent fetches because the head SHA scopes everything.
-    fn spawn_pr_threads_fetch(
-        &mut self,
-        details: &crate::forge::traits::PullRequestDetails,
-        local_checkout: Option<std::path::PathBuf>,
-    ) {
-        use crate::forge::github::gh::GitHubGhBackend;
-        use crate::forge::traits::ForgeBackend;
-
-        self.forge_review_threads.clear();
-        self.forge_review_threads_loading = true;
-
-        let (tx, rx) = std::sync::mpsc::channel();
-        self.pr_threads_rx = Some(rx);
-
-        let details_clone = details.clone();
-        let repository = details.repository.clone();
-        let pr_number = details.number;
-        let head_sha = details.head_sha.clone();
-
-        std::thread::spawn(move || {
-            let backend =
-                GitHubGhBackend::new(Some(repository.clone())).with_local_checkout(local_checkout);
-            let result = backend
-                .list_review_threads(&details_clone)
-                .map_err(|e| e.to_string());
-            let _ = tx.send(PrThreadsEvent::Done {
-                repository,
-                pr_number,
-                head_sha,
-                result,
-            });
-        });
+    /// Open overlays, bottom first, in the order they should be drawn.
+    pub fn overlays(&self) -> Vec<InputMode> {
+        match self.overlay_stack.split_first() {
+            Some((_, covered)) => covered
+                .iter()
+                .copied()
+                .chain(std::iter::once(self.input_mode))
+                .collect(),
+            None => Vec::new(),
+        }
     }
 
-    /// Drain any pending remote-thread fetch result and apply it. Stale
-    /// results (a result that arrived after the user switched to a
-    /// different PR) are discarded.
-    pub fn poll_pr_threads_events(&mut self) {
-        let Some(rx) = self.pr_threads_rx.as_ref() else {
-            return;
-        };
-        let event = match rx.try_recv() {
-            Ok(e) => e,
-            Err(_) => return,
-        };
-        self.pr_threads_rx = None;
-        self.forge_review_threads_loading = false;
-
-        match event {
-            PrThreadsEvent::Done {
-                repository,
-                pr_number,
-                head_sha,
-                result,
-            } => {
-                // Validate against the currently open PR. If the user has
-                // opened a different PR (or left PR mode) while the fetch
-                // was in flight, drop the result silently.
-                let current = match &self.diff_source {
-                    DiffSource::PullRequest(pr) => Some((
-                        pr.key.repository.clone(),
-                        pr.key.number,
-                        pr.key.head_sha.clone(),
-                    )),
-                    _ => None,
-                };
-                let still_relevant = current
-                    .as_ref()
-                    .map(|(r, n, sha)| *r == repository && *n == pr_number && *sha == head_sha)
-                    .unwrap_or(false);
-                if !still_relevant {
-                    return;
-                }
-                match result {
-                    Ok(threads) => {
-                        self.forge_review_threads = threads;
-                        self.rebuild_annotations();
-                    }
-                    Err(e) => {
-                        self.forge_review_threads = Vec::new();
-                        self.set_warning(format!("Failed to load remote comments: {e}"));
-                    }
-                }
-            }
-        }
+    /// Whether `mode` is active or covered by an overlay. Renderers use this
+    /// so an editor stays visible under a popup opened over it.
+    pub fn is_mode_open(&self, mode: InputMode) -> bool {
+        self.input_mode == mode || self.overlay_stack.contains(&mode)
     }
 
-    /// Update the per-session remote comments visibility and repaint.
-    /// Returns `true` if the visibility actually changed.
-    pub fn set_remote_comments_visibility(
-        &mut self,
-        visibility: crate::forge::remote_comments::PrCommentsVisibility,
-    ) -> bool {
-        if self.session.remote_comments_visibility == visibility {
-            return false;
+    pub fn toggle_help(&mut self) {
+        if self.input_mode == InputMode::Help {
+            self.command_output = None;
+            self.close_overlay();
+        } else {
+            self.help_state.context = self.help_context();
+            self.help_state.show_all = self.help_state.context.is_none();
+            self.open_overlay(InputMode::Help);
+            self.help_state.scroll_offset = 0;
         }
-        self.session.remote_comments_visibility = visibility;
-        self.rebuild_annotations();
-        true
     }
 
-    /// Abort an in-flight PR open. Drops the receiver so the eventual
-    /// thread send becomes a no-op; clears the spinner state.
-    pub fn cancel_pr_open(&mut self) -> bool {
-        if self.pr_open_state.is_none() {
-            return false;
+    /// Switch the help popup between the context's keys and all keys.
+    pub fn toggle_help_scope(&mut self) {
+        if self.command_output.is_none() && self.help_state.context.is_some() {
+            self.help_state.show_all = !self.help_state.show_all;
+            self.help_state.scroll_offset = 0;
         }
-        self.pr_open_state = None;
-        self.pr_open_rx = None;
-        self.set_message("PR open cancelled".to_string());
-        true
     }
 
-    /// Re-fetch remote review threads for the currently open PR. Called
-    /// from `:e` so users can pull the latest discussions without
-    /// reopening the PR. No-op outside PR mode.
-    pub fn refetch_pr_threads(&mut self) {
-        let local_checkout = self
-            .forge_backend
-            .as_deref()
-            .and_then(|b| b.local_checkout_path());
-        let details = match &self.diff_source {
-            DiffSource::PullRequest(pr) => crate::forge::traits::PullRequestDetails {
-                repository: pr.key.repository.clone(),
-                number: pr.key.number,
-                title: pr.title.clone(),
-                url: pr.url.clone(),
-                state: pr.state.clone(),
-                is_draft: false,
-                author: None,
-                head_ref_name: pr.head_ref_name.clone(),
-                base_ref_name: pr.base_ref_name.clone(),
-                head_sha: pr.key.head_sha.clone(),
-                base_sha: pr.base_sha.clone(),
-                body: String::new(),
-                updated_at: None,
-                closed: pr.closed,
-                merged_at: None,
-            },
-            _ => return,
-        };
-        self.spawn_pr_threads_fetch(&details, local_checkout);
+    /// The panel or mode whose keys `?` shows.
+    fn help_context(&self) -> Option<HelpContext> {
+        match self.input_mode {
+            InputMode::Normal if self.view == View::Diff => Some(match self.focused_panel {
+                FocusedPanel::FileList => HelpContext::FileList,
+                FocusedPanel::Diff => HelpContext::Diff,
+                FocusedPanel::CommitSelector => HelpContext::CommitSelector,
+            }),
+            InputMode::VisualSelect => Some(HelpContext::VisualSelect),
+            InputMode::Comment => Some(HelpContext::CommentEditor),
+            InputMode::CommitSelect => Some(HelpContext::TargetSelector),
+            _ => None,
+        }
     }
 
-    /// Open a PR using the provided forge backend, synchronously. Exists
-    /// as a seam for tests that want to drive the open without spinning
-    /// up a background thread + mpsc round-trip. Production paths go
-    /// through `spawn_pr_open` (selector) or `new_from_pr_target` (CLI).
-    ///
-    /// Synchronously fetches `list_review_threads` from the same backend
-    /// and applies it before returning. This is the convenient seam for
-    /// integration tests; the production async path uses
-    /// `spawn_pr_threads_fetch` instead.
-    #[allow(dead_code)]
-    pub fn open_pr_with_backend(
-        &mut self,
-        summary: &crate::forge::traits::PullRequestSummary,
-        backend: Box<dyn ForgeBackend>,
-        local_checkout: Option<std::path::PathBuf>,
-    ) -> Result<()> {
-        use crate::forge::pr_open::open_pull_request;
-        use crate::forge::traits::PullRequestTarget;
-
-        let target = PullRequestTarget::with_repository(
-            summary.repository.clone(),
-            summary.number,
-            summary.number.to_string(),
-        );
-        let highlighter = self.theme.syntax_highlighter();
-        let mut opened = open_pull_request(
-            backend.as_ref(),
-            target,
-            local_checkout.as_deref(),
-            highlighter,
-        )?;
-        Self::load_or_apply_pr_session(&mut opened);
-        // Sync thread fetch — tests assert on `app.forge_review_threads`
-        // immediately after this returns.
-        let threads = backend
-            .list_review_threads(&opened.details)
-            .unwrap_or_default();
-        self.enter_pr_diff_mode(backend, opened)?;
-        self.forge_review_threads = threads;
-        self.rebuild_annotations();
-        Ok(())
+    pub fn help_scroll_down(&mut self, lines: usize) {
+        let max_offset = self
+            .help_state
+            .total_lines
+            .saturating_sub(self.help_state.viewport_height);
+        self.help_state.scroll_offset = (self.help_state.scroll_offset + lines).min(max_offset);
     }
 
-    pub fn begin_pr_filter(&mut self) {
-        if !self.pr_tab.is_loaded() {
-            return;
-        }
-        // Seed the draft from the current applied filter so the user can
-        // refine it. Starting from empty is also reasonable; preserving the
-        // current filter feels less surprising when re-opening.
-        let current = match &self.pr_tab {
-            PullRequestsTab::Loaded { filter, .. } => filter.clone(),
-            _ => String::new(),
-        };
-        self.pr_filter_draft = Some(current);
+    pub fn help_scroll_up(&mut self, lines: usize) {
+        self.help_state.scroll_offset = self.help_state.scroll_offset.saturating_sub(lines);
     }
 
-    pub fn commit_pr_filter(&mut self) {
-        if let Some(draft) = self.pr_filter_draft.take() {
-            self.pr_tab.set_filter(draft);
-        }
+    pub fn help_scroll_to_top(&mut self) {
+        self.help_state.scroll_offset = 0;
     }
 
-    pub fn cancel_pr_filter(&mut self) {
-        self.pr_filter_draft = None;
+    pub fn help_scroll_to_bottom(&mut self) {
+        let max_offset = self
+            .help_state
+            .total_lines
+            .saturating_sub(self.help_state.viewport_height);
+        self.help_state.scroll_offset = max_offset;
     }
 
-    pub fn pr_filter_insert_char(&mut self, ch: char) {
-        if let Some(draft) = self.pr_filter_draft.as_mut() {
-            draft.push(ch);
-        }
+    pub fn enter_confirm_mode(&mut self, action: ConfirmAction) {
+        self.open_confirm(ConfirmDialog::new(action));
     }
 
-    pub fn pr_filter_delete_char(&mut self) {
-        if let Some(draft) = self.pr_filter_draft.as_mut() {
-            draft.pop();
-        }
+    /// Ask `dialog`'s question over whatever is on screen.
+    pub fn open_confirm(&mut self, dialog: ConfirmDialog) {
+        self.open_overlay(InputMode::Confirm);
+        self.pending_confirm = Some(dialog);
     }
 
-    pub fn pr_filter_clear(&mut self) {
-        if let Some(draft) = self.pr_filter_draft.as_mut() {
-            draft.clear();
-        }
+    pub fn exit_confirm_mode(&mut self) {
+        self.close_overlay();
+        self.pending_confirm = None;
     }
 
-    pub fn pr_filter_editing(&self) -> bool {
-        self.pr_filter_draft.is_some()
+    /// Drive `:submit*` preflight: walk every local-draft comment in the
+    /// current PR session, map each one against the displayed diff, bucket
+    /// the results, and transition into the resolver (when there are
+    /// unmappable comments) or the final-confirmation modal.
+    ///
+    /// PR 5 does not call the network; `[y]` in the confirmation modal
+    /// stubs a "PR 6 will wire the network call" info message.
+    pub fn start_submit(&mut self, event: crate::forge::submit::SubmitEvent) {
+        self.start_submit_with(event, false);
     }
 
-    pub fn toggle_diff_view_mode(&mut self) {
-        self.diff_view_mode = match self.diff_view_mode {
-            DiffViewMode::Unified => DiffViewMode::SideBySide,
-            DiffViewMode::SideBySide => DiffViewMode::Unified,
+    /// Like `start_submit`, but when `skip_confirm` is `true` the flow
+    /// bypasses `SubmitConfirm`. The action-picker path uses this because
+    /// picking IS the confirmation; the resolver (if any unmappable
+    /// comments) still runs first, then dispatches the network call
+    /// directly. `:submit <event>` callers should pass `false`.
+    pub fn start_submit_with(
+        &mut self,
+        event: crate::forge::submit::SubmitEvent,
+        skip_confirm: bool,
+    ) {
+        use crate::forge::submit::{
+            CommentAnchor, InlineComment, ResolverAction, UnmappableItem, map_comment,
         };
-        let mode_name = match self.diff_view_mode {
-            DiffViewMode::Unified => "unified",
-            DiffViewMode::SideBySide => "side-by-side",
-        };
-        self.set_message(format!("Diff view mode: {mode_name}"));
-        self.rebuild_annotations();
-    }
 
-    pub fn toggle_file_list(&mut self) {
-        self.show_file_list = !self.show_file_list;
-        if !self.show_file_list && self.focused_panel == FocusedPanel::FileList {
-            self.focused_panel = FocusedPanel::Diff;
+        let DiffSource::PullRequest(pr) = &self.diff_source else {
+            self.set_warning(":submit only applies in PR mode");
+            return;
+        };
+        if pr.is_read_only() {
+            let reason = pr.read_only_reason().unwrap_or("read only");
+            self.set_warning(format!("Cannot submit: PR is {reason}"));
+            return;
         }
-        let status = if self.show_file_list {
-            "visible"
-        } else {
-            "hidden"
+        // When the inline commit selector shows a strict subset, comments
+        // anchor to the displayed (subset) diff, so `commit_id` must be the
+        // SHA the diff was computed against — otherwise GitHub rejects with
+        // 422 because the line/position isn't present in the diff against
+        // the cumulative PR head. `pr_commits` is stored newest-first, so
+        // the head of a (start_idx..=end_idx) range is `pr_commits[start_idx]`.
+        let commit_id = match self.commit_selection_range {
+            Some((start_idx, end_idx))
+                if !self.pr_commits.is_empty()
+                    && start_idx <= end_idx
+                    && end_idx < self.pr_commits.len()
+                    && !(start_idx == 0 && end_idx + 1 == self.pr_commits.len()) =>
+            {
+                self.pr_commits[start_idx].oid.clone()
+            }
+            _ => pr.key.head_sha.clone(),
         };
-        self.set_message(format!("File list: {status}"));
-    }
 
-    /// Whether the inline commit selector panel should be displayed.
-    pub fn has_inline_commit_selector(&self) -> bool {
-        self.show_commit_selector
-            && self.review_commits.len() > 1
-            && !matches!(&self.diff_source, DiffSource::WorkingTree)
-    }
+        // Source of truth for the diff: when the inline commit selector is
+        // showing a strict subset, `range_diff_files` carries the merged
+        // subset diff; otherwise `diff_files` is canonical.
+        let files: Vec<&DiffFile> = match self.range_diff_files.as_ref() {
+            Some(range) => range.iter().collect(),
+            None => self.diff_files.iter().collect(),
+        };
 
-    // Commit selection methods
+        let mut mappable: Vec<InlineComment> = Vec::new();
+        let mut unmappable: Vec<UnmappableItem> = Vec::new();
+        let mut total_local_drafts = 0_usize;
 
-    pub fn commit_select_up(&mut self) {
-        if self.commit_list_cursor > 0 {
-            self.commit_list_cursor -= 1;
-            // Scroll up if cursor goes above visible area
-            if self.commit_list_cursor < self.commit_list_scroll_offset {
-                self.commit_list_scroll_offset = self.commit_list_cursor;
+        // Walk file-level and line comments in display order. Review-level
+        // comments (session.review_comments) are NOT inline-mapped; they
+        // appear in the body via `build_review_body`.
+        for file in &files {
+            let Some(review) = self.session.files.get(file.display_path()) else {
+                continue;
+            };
+            for comment in &review.file_comments {
+                if comment.is_locked() {
+                    continue;
+                }
+                total_local_drafts += 1;
+                bucket_mapping(
+                    map_comment(comment, CommentAnchor::FileLevel, file, &self.forge_config),
+                    &mut mappable,
+                    &mut unmappable,
+                );
             }
-        }
-    }
-
-    pub fn commit_select_down(&mut self) {
-        let max_cursor = if self.can_show_more_commits() {
-            self.visible_commit_count
-        } else {
-            self.visible_commit_count.saturating_sub(1)
-        };
-
-        if self.commit_list_cursor < max_cursor {
-            self.commit_list_cursor += 1;
-            // Scroll down if cursor goes below visible area
-            if self.commit_list_viewport_height > 0
-                && self.commit_list_cursor
-                    >= self.commit_list_scroll_offset + self.commit_list_viewport_height
-            {
-                self.commit_list_scroll_offset =
-                    self.commit_list_cursor - self.commit_list_viewport_height + 1;
+            let mut keys: Vec<&u32> = review.line_comments.keys().collect();
+            keys.sort();
+            for key in keys {
+                for comment in &review.line_comments[key] {
+                    if comment.is_locked() {
+                        continue;
+                    }
+                    total_local_drafts += 1;
+                    let anchor = if comment.line_range.is_some() {
+                        CommentAnchor::Range
+                    } else {
+                        CommentAnchor::Line {
+                            line: *key,
+                            side: comment.side.unwrap_or_default(),
+                        }
+                    };
+                    bucket_mapping(
+                        map_comment(comment, anchor, file, &self.forge_config),
+                        &mut mappable,
+                        &mut unmappable,
+                    );
+                }
             }
         }
-    }
 
-    /// Toggle the cursor commit's membership in the selection range, then
-    /// (only if the cursor commit was newly added to the selection) move the
-    /// cursor past the end of the range. Lets the user press Enter/Space
-    /// repeatedly to sweep a contiguous run of commits.
-    ///
-    /// Other toggle outcomes leave the cursor in place: edge presses
-    /// (deselect the cursor commit), middle presses (truncate the range
-    /// without unselecting the cursor commit), and clearing the last
-    /// selection. Those aren't "sweep" actions, so advancing would surprise.
-    pub fn toggle_commit_selection_and_advance(&mut self) {
-        let cursor = self.commit_list_cursor;
-        let was_selected = self.is_commit_selected(cursor);
-        self.toggle_commit_selection();
-        let now_selected = self.is_commit_selected(cursor);
-        if was_selected || !now_selected {
+        // Approve is the one event that's meaningful with no comments — a
+        // bare "LGTM" approval. Every other event needs at least one local
+        // draft comment or a review-level comment, otherwise there's
+        // nothing to submit.
+        let bare_allowed = matches!(event, crate::forge::submit::SubmitEvent::Approve);
+        if !bare_allowed && total_local_drafts == 0 && self.session.review_comments.is_empty() {
+            self.set_warning("Nothing to submit — no local-draft comments");
             return;
         }
-        if let Some((_, end)) = self.commit_selection_range {
-            while self.commit_list_cursor <= end {
-                let before = self.commit_list_cursor;
-                self.commit_select_down();
-                if self.commit_list_cursor == before {
-                    return;
-                }
-            }
+
+        let resolver_choices = vec![ResolverAction::default(); unmappable.len()];
+        let has_unmappable = !unmappable.is_empty();
+        self.submit_state = Some(SubmitState {
+            event,
+            mappable,
+            unmappable,
+            resolver_choices,
+            resolver_cursor: 0,
+            commit_id,
+            skip_confirm,
+        });
+
+        if has_unmappable {
+            self.open_overlay(InputMode::SubmitResolver);
+        } else if skip_confirm {
+            self.confirm_submit();
+        } else {
+            self.open_overlay(InputMode::SubmitConfirm);
         }
     }
 
-    // Check if cursor is on the commit expand row
-    pub fn is_on_expand_row(&self) -> bool {
-        self.can_show_more_commits() && self.commit_list_cursor == self.visible_commit_count
+    /// Open the bare-`:submit` action picker. The user picks
+    /// Comment/Approve/Request changes/Draft (or cancels); the picked event
+    /// then runs through preflight with `skip_confirm = true` so no extra
+    /// confirmation modal follows.
+    pub fn start_submit_action_picker(&mut self) {
+        if !matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_warning(":submit only applies in PR mode");
+            return;
+        }
+        self.submit_picker_cursor = 0;
+        self.open_overlay(InputMode::SubmitActionPicker);
     }
 
-    pub fn can_show_more_commits(&self) -> bool {
-        self.visible_commit_count < self.commit_list.len() || self.has_more_commit
+    /// Move the action-picker cursor down by one row, wrapping at the end.
+    pub fn submit_picker_cursor_down(&mut self) {
+        let total = SUBMIT_PICKER_EVENTS.len();
+        if total > 0 {
+            self.submit_picker_cursor = (self.submit_picker_cursor + 1) % total;
+        }
     }
 
-    // Expand the commit list to show more commits
-    pub fn expand_commit(&mut self) -> Result<()> {
-        if self.visible_commit_count < self.commit_list.len() {
-            self.visible_commit_count =
-                (self.visible_commit_count + self.commit_page_size).min(self.commit_list.len());
-            return Ok(());
+    /// Move the action-picker cursor up by one row, wrapping at the start.
+    pub fn submit_picker_cursor_up(&mut self) {
+        let total = SUBMIT_PICKER_EVENTS.len();
+        if total > 0 {
+            self.submit_picker_cursor = (self.submit_picker_cursor + total - 1) % total;
         }
+    }
 
-        if !self.has_more_commit {
-            self.set_message("No more commits");
-            return Ok(());
-        }
+    /// Confirm the action picker selection: dispatch into preflight with the
+    /// chosen event and `skip_confirm = true`.
+    pub fn submit_picker_confirm(&mut self) {
+        let Some(event) = SUBMIT_PICKER_EVENTS
+            .get(self.submit_picker_cursor)
+            .map(|(_, ev)| *ev)
+        else {
+            self.cancel_submit_action_picker();
+            return;
+        };
+        self.close_overlay();
+        self.start_submit_with(event, true);
+    }
 
-        let offset = self.loaded_history_commit_count();
-        let limit = self.commit_page_size;
+    /// Cancel the action picker without entering preflight.
+    pub fn cancel_submit_action_picker(&mut self) {
+        self.close_overlay();
+        self.submit_picker_cursor = 0;
+    }
 
-        let new_commits = self.vcs.get_recent_commits(offset, limit)?;
+    pub fn cancel_submit(&mut self) {
+        self.submit_state = None;
+        self.close_overlay();
+    }
 
-        if new_commits.is_empty() {
-            self.has_more_commit = false;
-            self.set_message("No more commits");
-            return Ok(());
+    /// Move the resolver cursor down by one row, clamped to the last row.
+    pub fn submit_resolver_cursor_down(&mut self) {
+        if let Some(state) = self.submit_state.as_mut()
+            && state.resolver_cursor + 1 < state.unmappable.len()
+        {
+            state.resolver_cursor += 1;
         }
+    }
 
-        if new_commits.len() < limit {
-            self.has_more_commit = false;
-            self.set_message("No more commits");
+    pub fn submit_resolver_cursor_up(&mut self) {
+        if let Some(state) = self.submit_state.as_mut()
+            && state.resolver_cursor > 0
+        {
+            state.resolver_cursor -= 1;
         }
-
-        self.commit_list.extend(new_commits);
-        self.visible_commit_count = self.commit_list.len();
-
-        Ok(())
     }
 
-    pub fn toggle_commit_selection(&mut self) {
-        let cursor = self.commit_list_cursor;
-        if cursor >= self.commit_list.len() {
-            return;
-        }
-
-        match self.commit_selection_range {
-            None => {
-                // No selection yet - select just this commit
-                self.commit_selection_range = Some((cursor, cursor));
-            }
-            Some((start, end)) => {
-                let all_commits_selected =
-                    self.commit_list.len() > 1 && start == 0 && end == self.commit_list.len() - 1;
-                if all_commits_selected {
-                    self.commit_selection_range = Some((cursor, cursor));
-                    return;
-                }
-
-                if cursor >= start && cursor <= end {
-                    // Cursor is within the range - shrink or deselect
-                    if start == end {
-                        // Only one commit selected, deselect all
-                        self.commit_selection_range = None;
-                    } else if cursor == start {
-                        // At start edge - shrink from start
-                        self.commit_selection_range = Some((start + 1, end));
-                    } else if cursor == end {
-                        // At end edge - shrink from end
-                        self.commit_selection_range = Some((start, end - 1));
-                    } else {
-                        // In the middle - deselect cursor and everything after it
-                        self.commit_selection_range = Some((start, cursor - 1));
-                    }
-                } else {
-                    // Cursor is outside the range - extend to include it
-                    let new_start = start.min(cursor);
-                    let new_end = end.max(cursor);
-                    self.commit_selection_range = Some((new_start, new_end));
-                }
-            }
+    pub fn submit_resolver_toggle(&mut self) {
+        use crate::forge::submit::ResolverAction;
+        if let Some(state) = self.submit_state.as_mut()
+            && let Some(choice) = state.resolver_choices.get_mut(state.resolver_cursor)
+        {
+            *choice = match choice {
+                ResolverAction::MoveToSummary => ResolverAction::Omit,
+                ResolverAction::Omit => ResolverAction::MoveToSummary,
+            };
         }
     }
 
-    /// Check if a commit at the given index is selected
-    pub fn is_commit_selected(&self, index: usize) -> bool {
-        match self.commit_selection_range {
-            Some((start, end)) => index >= start && index <= end,
-            None => false,
+    /// Advance from the resolver. When `skip_confirm` is set (action-picker
+    /// path), dispatch the network call directly; otherwise route to
+    /// `SubmitConfirm` for the final confirmation modal.
+    pub fn submit_resolver_advance(&mut self) {
+        let Some(state) = self.submit_state.as_ref() else {
+            return;
+        };
+        if state.skip_confirm {
+            self.close_overlay();
+            self.confirm_submit();
+        } else {
+            self.replace_overlay(InputMode::SubmitConfirm);
         }
     }
 
-    /// Cycle inline commit selector to the next individual commit (`)` key).
-    /// all → last, i → i+1, last → all
-    pub fn cycle_commit_next(&mut self) {
-        if self.review_commits.is_empty() {
-            return;
+    /// True iff the original review head and the latest known PR head
+    /// disagree. PR 5 cannot trigger this (the open-time head equals
+    /// `current_pr_head`), but the field is exposed so the renderer can
+    /// fold the warning in once PR 6 refreshes the remote head.
+    pub fn submit_head_is_stale(&self) -> bool {
+        let Some(state) = self.submit_state.as_ref() else {
+            return false;
+        };
+        match self.current_pr_head.as_deref() {
+            Some(latest) => latest != state.commit_id,
+            None => false,
         }
-        let n = self.review_commits.len();
-        let all_selected = Some((0, n - 1));
+    }
 
-        if self.commit_selection_range == all_selected {
-            // all → last
-            self.commit_selection_range = Some((n - 1, n - 1));
-            self.commit_list_cursor = n - 1;
-        } else if let Some((i, j)) = self.commit_selection_range {
-            if i == j {
-                // Single commit selected
-                if i == n - 1 {
-                    // last → all
-                    self.commit_selection_range = all_selected;
-                } else {
-                    // i → i+1
-                    self.commit_selection_range = Some((i + 1, i + 1));
-                    self.commit_list_cursor = i + 1;
-                }
-            } else {
-                // Multi-commit subrange → select last of that range
-                self.commit_selection_range = Some((j, j));
-                self.commit_list_cursor = j;
-            }
-        } else {
-            // None selected → select all
-            self.commit_selection_range = all_selected;
+    /// Confirm submit — PR 6 dispatches the async `gh api .../reviews` call.
+    /// Builds the body + payload on the main thread, saves the session, then
+    /// hands off to `spawn_pr_submit`. The modal disappears immediately; a
+    /// status-bar spinner takes over until the result lands in
+    /// `poll_pr_submit_events`.
+    pub fn confirm_submit(&mut self) {
+        if let Err(e) = self.spawn_pr_submit() {
+            self.set_error(format!("Submit failed: {e}"));
+            self.submit_state = None;
+            self.close_all_overlays();
         }
     }
 
-    /// Cycle inline commit selector to the previous individual commit (`(` key).
-    /// all → first, i → i-1, first → all
-    pub fn cycle_commit_prev(&mut self) {
-        if self.review_commits.is_empty() {
-            return;
+    /// Kick off the create-review call asynchronously. Pre-submit-saves the
+    /// session, builds the JSON payload on the main thread, then runs the
+    /// network round-trip on a background thread. The result is applied
+    /// later in `poll_pr_submit_events`.
+    pub fn spawn_pr_submit(&mut self) -> Result<()> {
+        use crate::forge::github::gh::GitHubGhBackend;
+        use crate::forge::submit::{MovedToSummaryItem, ResolverAction, build_review_body};
+        use crate::forge::traits::{CreateReviewRequest, PullRequestTarget};
+
+        // Snapshot identity from the PR diff source first so the borrow on
+        // `submit_state` below doesn't conflict.
+        let DiffSource::PullRequest(pr) = self.diff_source.clone() else {
+            return Err(TuicrError::UnsupportedOperation(
+                "Not in PR mode".to_string(),
+            ));
+        };
+        if self.pr_submit_state.is_some() {
+            return Ok(()); // already in flight; ignore
         }
-        let n = self.review_commits.len();
-        let all_selected = Some((0, n - 1));
 
-        if self.commit_selection_range == all_selected {
-            // all → first
-            self.commit_selection_range = Some((0, 0));
-            self.commit_list_cursor = 0;
-        } else if let Some((i, j)) = self.commit_selection_range {
-            if i == j {
-                // Single commit selected
-                if i == 0 {
-                    // first → all
-                    self.commit_selection_range = all_selected;
+        let Some(state) = self.submit_state.take() else {
+            return Ok(());
+        };
+
+        let summary_items: Vec<MovedToSummaryItem> = state
+            .unmappable
+            .iter()
+            .zip(state.resolver_choices.iter())
+            .filter_map(|(item, action)| {
+                if *action == ResolverAction::MoveToSummary {
+                    Some(MovedToSummaryItem {
+                        comment: item.comment.clone(),
+                        file: item.file.clone(),
+                    })
                 } else {
-                    // i → i-1
-                    self.commit_selection_range = Some((i - 1, i - 1));
-                    self.commit_list_cursor = i - 1;
+                    None
                 }
-            } else {
-                // Multi-commit subrange → select first of that range
-                self.commit_selection_range = Some((i, i));
-                self.commit_list_cursor = i;
-            }
-        } else {
-            // None selected → select all
-            self.commit_selection_range = all_selected;
-        }
+            })
+            .collect();
+        let body = build_review_body(
+            &self.session.review_comments,
+            &summary_items,
+            &self.forge_config,
+        );
+
+        // Save the session BEFORE the network call — keeps the user's
+        // local-draft work durable if anything goes sideways below.
+        let _ = crate::persistence::save_session(&self.session);
+
+        let in_flight = SubmitInFlightState {
+            event: state.event,
+            mappable: state.mappable.clone(),
+            moved_to_summary_count: summary_items.len(),
+            head_sha_snapshot: state.commit_id.clone(),
+            repository: pr.key.repository.clone(),
+            pr_number: pr.key.number,
+            started_at: Instant::now(),
+        };
+        self.pr_submit_state = Some(in_flight.clone());
+        self.close_all_overlays();
+
+        let local_checkout = self
+            .forge_backend
+            .as_deref()
+            .and_then(|backend| backend.local_checkout_path());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.pr_submit_rx = Some(rx);
+
+        let repository = in_flight.repository.clone();
+        let pr_number = in_flight.pr_number;
+        let head_sha = in_flight.head_sha_snapshot.clone();
+        let event = in_flight.event;
+        let mappable = in_flight.mappable.clone();
+        let commit_id = state.commit_id.clone();
+
+        std::thread::spawn(move || {
+            let backend =
+                GitHubGhBackend::new(Some(repository.clone())).with_local_checkout(local_checkout);
+            // Need PR details for repo/owner routing; refetch lightly via
+            // the same target the user opened with.
+            let target = PullRequestTarget::with_repository(
+                repository.clone(),
+                pr_number,
+                pr_number.to_string(),
+            );
+            let result = match backend.get_pull_request(target) {
+                Ok(details) => backend
+                    .create_review(
+                        &details,
+                        CreateReviewRequest {
+                            event,
+                            commit_id: &commit_id,
+                            body: &body,
+                            comments: &mappable,
+                        },
+                    )
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(PrSubmitEvent::Done {
+                repository,
+                pr_number,
+                head_sha,
+                result,
+            });
+        });
+        Ok(())
     }
 
-    pub fn confirm_commit_selection(&mut self) -> Result<()> {
-        let selection = match self.commit_selection_range {
-            Some((start, end)) => format!(
-                "range={start}..={end}, rows={}",
-                end.saturating_sub(start) + 1
-            ),
-            None => "range=none, rows=0".to_string(),
+    /// Pump a pending create-review result. Applies lifecycle writes + the
+    /// success message, or surfaces a sticky error.
+    pub fn poll_pr_submit_events(&mut self) {
+        let Some(rx) = self.pr_submit_rx.as_ref() else {
+            return;
         };
-        crate::profile::time_with(
+        let event = match rx.try_recv() {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        self.pr_submit_rx = None;
+        let in_flight = self.pr_submit_state.take();
+        let PrSubmitEvent::Done {
+            repository,
+            pr_number,
+            head_sha,
+            result,
+        } = event;
+
+        // Stale-result discard: if the user reloaded the PR mid-submit, the
+        // active head SHA may have moved. Drop the result rather than
+        // silently mutating the wrong session.
+        let Some(in_flight) = in_flight else {
+            return;
+        };
+        let stale = in_flight.repository != repository
+            || in_flight.pr_number != pr_number
+            || in_flight.head_sha_snapshot != head_sha;
+        if stale {
+            self.set_message("Discarded stale submit result (PR was reloaded)".to_string());
+            return;
+        }
+
+        let started_at = in_flight.started_at;
+        self.finish_pr_submit(in_flight, result);
+        self.notify_finished(Operation::Push, started_at);
+    }
+
+    /// Apply the create-review result on the main thread. On success: flip
+    /// each included `Comment` to `Submitted` (or `PushedDraft` for the
+    /// draft event), stamp `remote_review_id`, save the session again, and
+    /// publish a success message. On failure: keep everything as
+    /// `LocalDraft` and set a sticky error.
+    pub fn finish_pr_submit(
+        &mut self,
+        in_flight: SubmitInFlightState,
+        result: std::result::Result<crate::forge::traits::GhCreateReviewResponse, String>,
+    ) {
+        use crate::forge::submit::SubmitEvent;
+
+        let response = match result {
+            Ok(r) => r,
+            Err(e) => {
+                self.set_error(format!("Submit failed: {e}"));
+                return;
+            }
+        };
+
+        self.apply_submit_success(&in_flight, &response);
+
+        // Post-submit save — captures the lifecycle transitions.
+        let _ = crate::persistence::save_session(&self.session);
+
+        let inline_count = in_flight.mappable.len();
+        let summary_count = in_flight.moved_to_summary_count;
+        let message = match in_flight.event {
+            SubmitEvent::Draft => {
+                let pr_url = match &self.diff_source {
+                    DiffSource::PullRequest(pr) => pr.url.clone(),
+                    _ => String::new(),
+                };
+                if pr_url.is_empty() {
+                    format!(
+                        "Pushed pending GitHub review #{}: {} inline, {} moved to summary",
+                        response.id, inline_count, summary_count,
+                    )
+                } else {
+                    format!(
+                        "Pushed pending GitHub review #{}: {} inline, {} moved to summary — Finish it in GitHub: {}",
+                        response.id, inline_count, summary_count, pr_url,
+                    )
+                }
+            }
+            _ => format!(
+                "Submitted GitHub review #{}: {} inline, {} moved to summary",
+                response.id, inline_count, summary_count,
+            ),
+        };
+        self.set_message(message);
+    }
+
+    /// Flip each included `Comment` from `LocalDraft` to `Submitted` (or
+    /// `PushedDraft` for `:submit draft`) and stamp `remote_review_id`.
+    /// Moved-to-summary items are NOT touched here — they embed in the
+    /// review body, not as inline comments, so GitHub doesn't assign them
+    /// individual IDs.
+    pub fn apply_submit_success(
+        &mut self,
+        in_flight: &SubmitInFlightState,
+        response: &crate::forge::traits::GhCreateReviewResponse,
+    ) {
+        use crate::forge::submit::SubmitEvent;
+        use crate::model::comment::CommentLifecycleState;
+
+        let new_state = match in_flight.event {
+            SubmitEvent::Draft => CommentLifecycleState::PushedDraft,
+            _ => CommentLifecycleState::Submitted,
+        };
+        let review_id = response.id.to_string();
+
+        // Build a set of source comment IDs we expect to lock. Looking each
+        // one up by walking `file_reviews` is O(N*M); for v1 PRs this is
+        // negligible and avoids restructuring the session indexes.
+        let target_ids: std::collections::HashSet<&str> = in_flight
+            .mappable
+            .iter()
+            .map(|c| c.comment_id.as_str())
+            .collect();
+        if target_ids.is_empty() {
+            return;
+        }
+
+        for review in self.session.files.values_mut() {
+            for comment in review.file_comments.iter_mut() {
+                if target_ids.contains(comment.id.as_str()) {
+                    comment.lifecycle_state = new_state;
+                    comment.remote_review_id = Some(review_id.clone());
+                }
+            }
+            for comments in review.line_comments.values_mut() {
+                for comment in comments.iter_mut() {
+                    if target_ids.contains(comment.id.as_str()) {
+                        comment.lifecycle_state = new_state;
+                        comment.remote_review_id = Some(review_id.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Open the review target selector on a specific tab.
+    ///
+    /// `Local` loads the recent-commits list (same as the historical commit
+    /// selector). `PullRequests` switches the tab; the actual fetch is
+    /// triggered lazily through `on_target_tab_entered`.
+    pub fn enter_target_selector(&mut self, initial_tab: TargetTab) -> Result<()> {
+        self.vcs_idle()?;
+        // Save inline selection state if we have review commits
+        if !self.review_commits.is_empty() {
+            self.saved_inline_selection = self.commit_selection_range;
+        }
+
+        let highlighter = self.theme.syntax_highlighter();
+        let (change_status, _) = Self::get_change_status_with_ignore(
+            self.vcs.as_ref(),
+            &self.vcs_info.root_path,
+            highlighter,
+            self.path_filter.as_deref(),
+        )?;
+        let has_staged_changes = change_status.staged;
+        let has_unstaged_changes = change_status.unstaged;
+
+        let commits = self.vcs.get_recent_commits(0, VISIBLE_COMMIT_COUNT)?;
+        let no_local_targets = commits.is_empty() && !has_staged_changes && !has_unstaged_changes;
+        // Allow opening the selector on the Pull Requests tab even when there
+        // are no local commits or changes — the PR tab is the user's reason
+        // for being here.
+        if no_local_targets && initial_tab == TargetTab::Local {
+            self.set_message("No commits or staged/unstaged changes found");
+            return Ok(());
+        }
+
+        // Check if there might be more commits
+        self.has_more_commit = commits.len() >= VISIBLE_COMMIT_COUNT;
+        self.commit_list = commits;
+        if has_staged_changes {
+            self.commit_list.insert(0, Self::staged_commit_entry());
+        }
+        if has_unstaged_changes {
+            self.commit_list.insert(0, Self::unstaged_commit_entry());
+        }
+        self.commit_list_cursor = 0;
+        self.commit_list_scroll_offset = 0;
+        self.commit_selection_range = None;
+        self.visible_commit_count = self.commit_list.len();
+        self.input_mode = InputMode::CommitSelect;
+
+        // Reset the PR tab to Idle each time the selector is opened so the
+        // fetch happens lazily on first visit.
+        self.pr_tab = PullRequestsTab::new(self.forge_repository.clone());
+        self.pr_filter_draft = None;
+        self.pr_load_rx = None;
+
+        self.target_tab = initial_tab;
+        if initial_tab == TargetTab::PullRequests {
+            self.on_target_tab_entered();
+        }
+        Ok(())
+    }
+
+    /// `:vcs [git|jj]`: in a colocated jj+git repository, switch between the
+    /// jj working-copy view (`@` vs `@-`) and git's index/worktree view, then
+    /// reopen the target selector. With no argument, flip to the other one.
+    pub fn switch_colocated_vcs(&mut self, target: Option<VcsType>) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_warning("Cannot switch VCS while reviewing a pull request");
+            return;
+        }
+        let Some(other) = self
+            .colocated_vcs
+            .as_ref()
+            .map(|vcs| vcs.info().vcs_type)
+            .or_else(|| colocated_counterpart_type(&self.vcs_info))
+        else {
+            self.set_warning("Not a colocated jj+git repository");
+            return;
+        };
+        match target {
+            Some(target) if target == self.vcs_info.vcs_type => {
+                self.set_message(format!("Already viewing the repository through {target}"));
+                return;
+            }
+            Some(target) if target != other => {
+                self.set_warning(format!("Cannot view this repository through {target}"));
+                return;
+            }
+            _ => {}
+        }
+
+        let mut next = match self.colocated_vcs.take() {
+            Some(vcs) => vcs,
+            None => match colocated_counterpart(&self.vcs_info, self.git_backend_preference) {
+                Some(Ok(vcs)) => vcs,
+                Some(Err(e)) => {
+                    self.set_warning(format!("Failed to open the {other} view: {e}"));
+                    return;
+                }
+                None => {
+                    self.set_warning("Not a colocated jj+git repository");
+                    return;
+                }
+            },
+        };
+        // Diff settings follow the user across views where both backends
+        // support them
+        if let Some(whitespace) = self.vcs.ignore_whitespace() {
+            next.set_ignore_whitespace(whitespace);
+        }
+        if let Some(detection) = self.vcs.rename_detection() {
+            next.set_rename_detection(detection);
+        }
+        if let Some(include) = self.vcs.include_untracked() {
+            next.set_include_untracked(include);
+        }
+
+        if !self.read_only {
+            let _ = crate::persistence::save_session(&self.session);
+        }
+        let previous = std::mem::replace(&mut self.vcs, next);
+        self.colocated_vcs = Some(previous);
+        self.vcs_info = self.vcs.info().clone();
+        // Commit ids differ between the two views (change ids vs shas)
+        self.review_commits.clear();
+        self.saved_inline_selection = None;
+        self.commit_diff_cache.clear();
+        self.range_diff_files = None;
+
+        let vcs_type = self.vcs_info.vcs_type;
+        match self.enter_target_selector(TargetTab::Local) {
+            Ok(()) if self.input_mode == InputMode::CommitSelect => {
+                self.set_message(format!("Viewing the repository through {vcs_type}"));
+            }
+            Ok(()) => self.set_message(format!(
+                "Viewing the repository through {vcs_type}: no commits or changes found"
+            )),
+            Err(e) => self.set_warning(format!("Viewing the repository through {vcs_type}: {e}")),
+        }
+    }
+
+    pub fn exit_commit_select_mode(&mut self) -> Result<()> {
+        self.vcs_idle()?;
+        self.input_mode = InputMode::Normal;
+
+        // If we have review commits, restore the inline selector state
+        if !self.review_commits.is_empty() {
+            self.commit_list = self.review_commits.clone();
+            self.commit_selection_range = self.saved_inline_selection;
+            self.commit_list_cursor = 0;
+            self.commit_list_scroll_offset = 0;
+            self.visible_commit_count = self.review_commits.len();
+            self.has_more_commit = false;
+            self.saved_inline_selection = None;
+
+            // Reload diff for the restored selection
+            if self.commit_selection_range.is_some() {
+                self.reload_inline_selection()?;
+            }
+            return Ok(());
+        }
+
+        // If we were viewing commits, try to go back to working tree
+        if matches!(
+            self.diff_source,
+            DiffSource::CommitRange(_) | DiffSource::StagedUnstagedAndCommits(_)
+        ) {
+            let highlighter = self.theme.syntax_highlighter();
+            match Self::get_working_tree_diff_with_ignore(
+                self.vcs.as_ref(),
+                &self.vcs_info.root_path,
+                highlighter,
+                self.path_filter.as_deref(),
+            ) {
+                Ok(diff_files) => {
+                    self.diff_files = diff_files;
+                    self.diff_source = DiffSource::StagedAndUnstaged;
+
+                    // Update session for new files
+                    for file in &self.diff_files {
+                        let path = file.display_path().clone();
+                        self.session.add_file(path, file.status, file.content_hash);
+                    }
+
+                    self.sort_files_by_directory(true);
+                    self.expand_all_dirs();
+                }
+                Err(_) => {
+                    self.set_message("No staged or unstaged changes");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Switch to the next/previous tab in the review target selector.
+    /// With only two tabs, forward and reverse are equivalent; the `_forward`
+    /// arg is kept so callers can pass the natural direction without a cast.
+    /// Triggers the lazy PR fetch the first time the PR tab is entered.
+    pub fn cycle_target_tab(&mut self, _forward: bool) {
+        let next = match self.target_tab {
+            TargetTab::Local => TargetTab::PullRequests,
+            TargetTab::PullRequests => TargetTab::Local,
+        };
+        self.target_tab = next;
+        if next == TargetTab::PullRequests {
+            self.on_target_tab_entered();
+        } else {
+            // Returning to Local: clear any half-typed PR filter draft.
+            self.pr_filter_draft = None;
+        }
+    }
+
+    /// Entry-point hook called when the PR tab becomes visible.
+    /// Triggers the first network call lazily.
+    fn on_target_tab_entered(&mut self) {
+        if let Some(repo) = self.pr_tab.start_initial_load() {
+            self.spawn_pr_initial_load(repo);
+        }
+    }
+
+    /// Spawn a background thread that fetches the initial PR list. The
+    /// resulting `PrLoadEvent::Initial` is delivered through `pr_load_rx`
+    /// and applied in the main loop via `poll_pr_load_events`.
+    fn spawn_pr_initial_load(&mut self, repository: ForgeRepository) {
+        use crate::forge::github::gh::GitHubGhBackend;
+        use crate::forge::selector::PR_PAGE_SIZE;
+        use crate::forge::traits::{ForgeBackend, PullRequestListQuery};
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.pr_load_rx = Some(rx);
+
+        std::thread::spawn(move || {
+            let backend = GitHubGhBackend::new(Some(repository.clone()));
+            let query = PullRequestListQuery::first_page(repository, PR_PAGE_SIZE);
+            let result = backend
+                .list_pull_requests(query)
+                .map(|page| (page.pull_requests, page.has_more))
+                .map_err(|err| err.to_string());
+            let _ = tx.send(PrLoadEvent::Initial(result));
+        });
+    }
+
+    /// Spawn a background thread that fetches the next page of PRs.
+    fn spawn_pr_load_more(&mut self, repository: ForgeRepository, already_loaded: usize) {
+        use crate::forge::github::gh::GitHubGhBackend;
+        use crate::forge::selector::PR_PAGE_SIZE;
+        use crate::forge::traits::{ForgeBackend, PullRequestListQuery};
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.pr_load_rx = Some(rx);
+
+        std::thread::spawn(move || {
+            let backend = GitHubGhBackend::new(Some(repository.clone()));
+            let query = PullRequestListQuery {
+                repository,
+                already_loaded,
+                page_size: PR_PAGE_SIZE,
+            };
+            let result = backend
+                .list_pull_requests(query)
+                .map(|page| (page.pull_requests, page.has_more))
+                .map_err(|err| err.to_string());
+            let _ = tx.send(PrLoadEvent::LoadMore(result));
+        });
+    }
+
+    /// Pump any pending PR fetch events into the tab state.
+    /// Called from the main loop each tick; non-blocking.
+    pub fn poll_pr_load_events(&mut self) {
+        let Some(rx) = self.pr_load_rx.as_ref() else {
+            return;
+        };
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        if events.is_empty() {
+            return;
+        }
+        // The channel is single-use per fetch; drop the receiver once a
+        // result has arrived so we don't keep checking it.
+        self.pr_load_rx = None;
+        for event in events {
+            match event {
+                PrLoadEvent::Initial(result) => self.pr_tab.apply_initial_load(result),
+                PrLoadEvent::LoadMore(result) => self.pr_tab.apply_load_more(result),
+            }
+        }
+        self.pr_tab.clamp_cursor();
+    }
+
+    pub fn pr_tab_cursor_up(&mut self) {
+        self.pr_tab.cursor_up();
+        self.pr_tab
+            .ensure_cursor_visible(self.pr_list_viewport_height);
+    }
+
+    pub fn pr_tab_cursor_down(&mut self) {
+        self.pr_tab.cursor_down();
+        self.pr_tab
+            .ensure_cursor_visible(self.pr_list_viewport_height);
+    }
+
+    /// Handle Enter on the PR tab. Returns true when the action was handled
+    /// (load more triggered, PR open kicked off, error surfaced, etc).
+    pub fn pr_tab_select(&mut self) -> bool {
+        // Block re-entry while a previous open is still resolving — the
+        // spinner glyph on the row already tells the user something is in
+        // flight.
+        if self.pr_open_state.is_some() {
+            return true;
+        }
+        if self.pr_tab.cursor_on_load_more() {
+            if let Some((repo, already)) = self.pr_tab.start_load_more() {
+                self.spawn_pr_load_more(repo, already);
+            }
+            return true;
+        }
+        // Clone the summary so we drop the immutable borrow before mutating
+        // the app to enter PR mode.
+        let Some(summary) = self.pr_tab.cursor_pr().cloned() else {
+            return false;
+        };
+        self.spawn_pr_open(&summary);
+        true
+    }
+
+    /// Kick off the background fetch for a PR open. The main thread keeps
+    /// rendering and pumping events; the resulting `PrOpenEvent::Done` is
+    /// drained in `poll_pr_open_events` where parsing happens and PR mode
+    /// is entered.
+    fn spawn_pr_open(&mut self, summary: &crate::forge::traits::PullRequestSummary) {
+        use crate::forge::github::gh::GitHubGhBackend;
+        use crate::forge::pr_open::fetch_pr_data;
+        use crate::forge::traits::PullRequestTarget;
+
+        let local_checkout = Some(self.vcs_info.root_path.clone());
+        let request = PrOpenRequest {
+            repository: summary.repository.clone(),
+            pr_number: summary.number,
+            started_at: Instant::now(),
+        };
+        self.pr_open_state = Some(request.clone());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.pr_open_rx = Some(rx);
+
+        let summary_repo = summary.repository.clone();
+        let pr_number = summary.number;
+        let thread_local_checkout = local_checkout.clone();
+        std::thread::spawn(move || {
+            let backend = GitHubGhBackend::new(Some(summary_repo.clone()))
+                .with_local_checkout(thread_local_checkout);
+            let target =
+                PullRequestTarget::with_repository(summary_repo, pr_number, pr_number.to_string());
+            let outcome = fetch_pr_data(&backend, target).map_err(|e| e.to_string());
+            let _ = tx.send(PrOpenEvent::Done {
+                request,
+                result: outcome,
+            });
+        });
+    }
+
+    /// Drain any pending PR-open result and apply it. On success, parses
+    /// the diff and enters PR diff mode; on failure, routes the error
+    /// into the selector banner. Either way, clears `pr_open_state` and
+    /// the receiver so the spinner stops animating.
+    pub fn poll_pr_open_events(&mut self) {
+        let Some(rx) = self.pr_open_rx.as_ref() else {
+            return;
+        };
+        let event = match rx.try_recv() {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        self.pr_open_rx = None;
+        let in_flight = self.pr_open_state.clone();
+        self.pr_open_state = None;
+        match event {
+            PrOpenEvent::Done { request, result } => {
+                // If the user cancelled (cleared pr_open_state) but the
+                // background thread sent a result before being torn down,
+                // ignore the result rather than entering PR mode.
+                if !in_flight
+                    .as_ref()
+                    .map(|s| s.matches(&request.repository, request.pr_number))
+                    .unwrap_or(false)
+                {
+                    return;
+                }
+                match result {
+                    Ok((details, patch, commits)) => {
+                        if let Err(e) = self.finish_pr_open(details, patch, commits, &request) {
+                            self.set_error(format!(
+                                "Failed to open PR #{}: {}",
+                                request.pr_number, e
+                            ));
+                        }
+                    }
+                    Err(e) => {
+                        self.set_error(format!("Failed to open PR #{}: {}", request.pr_number, e));
+                    }
+                }
+                self.notify_finished(Operation::DiffLoad, request.started_at);
+            }
+        }
+    }
+
+    /// Main-thread half of the PR open: parse the patch, build the
+    /// session, and enter PR diff mode. Mirrors what the previous synchronous
+    /// `open_pr_with_backend` did, but the network fetch has already
+    /// happened on the background thread.
+    fn finish_pr_open(
+        &mut self,
+        details: crate::forge::traits::PullRequestDetails,
+        patch: String,
+        commits: Vec<crate::forge::traits::PullRequestCommit>,
+        request: &PrOpenRequest,
+    ) -> Result<()> {
+        use crate::forge::github::gh::GitHubGhBackend;
+        use crate::forge::pr_open::prepare_open_pr;
+
+        let local_checkout = Some(self.vcs_info.root_path.clone());
+        let highlighter = self.theme.syntax_highlighter();
+        let mut opened = prepare_open_pr(
+            details.clone(),
+            &patch,
+            commits,
+            local_checkout.as_deref(),
+            highlighter,
+        )?;
+        Self::load_or_apply_pr_session(&mut opened);
+        let backend = Box::new(
+            GitHubGhBackend::new(Some(request.repository.clone()))
+                .with_local_checkout(local_checkout.clone()),
+        );
+        self.enter_pr_diff_mode(backend, opened)?;
+        // Kick the remote-thread fetch off on a fresh background thread.
+        // The diff view is already up; threads fade in once they land.
+        self.spawn_pr_threads_fetch(&details, local_checkout);
+        self.set_message(format!(
+            "Opened PR {}#{}",
+            request.repository.display_name(),
+            request.pr_number,
+        ));
+        Ok(())
+    }
+
+    /// Kick off a background fetch of remote review threads for `details`.
+    /// Replaces any in-flight fetch — we don't try to merge results across
+    /// concurrent fetches because the head SHA scopes everything.
+    fn spawn_pr_threads_fetch(
+        &mut self,
+        details: &crate::forge::traits::PullRequestDetails,
+        local_checkout: Option<std::path::PathBuf>,
+    ) {
+        use crate::forge::github::gh::GitHubGhBackend;
+        use crate::forge::traits::ForgeBackend;
+
+        self.forge_review_threads.clear();
+        self.forge_review_threads_loading = true;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.pr_threads_rx = Some(rx);
+
+        let details_clone = details.clone();
+        let repository = details.repository.clone();
+        let pr_number = details.number;
+        let head_sha = details.head_sha.clone();
+
+        std::thread::spawn(move || {
+            let backend =
+                GitHubGhBackend::new(Some(repository.clone())).with_local_checkout(local_checkout);
+            let result = backend
+                .list_review_threads(&details_clone)
+                .map_err(|e| e.to_string());
+            let _ = tx.send(PrThreadsEvent::Done {
+                repository,
+                pr_number,
+                head_sha,
+                result,
+            });
+        });
+    }
+
+    /// Drain any pending remote-thread fetch result and apply it. Stale
+    /// results (a result that arrived after the user switched to a
+    /// different PR) are discarded.
+    pub fn poll_pr_threads_events(&mut self) {
+        let Some(rx) = self.pr_threads_rx.as_ref() else {
+            return;
+        };
+        let event = match rx.try_recv() {
+            Ok(e) => e,
+            Err(_) => return,
+        };
+        self.pr_threads_rx = None;
+        self.forge_review_threads_loading = false;
+
+        match event {
+            PrThreadsEvent::Done {
+                repository,
+                pr_number,
+                head_sha,
+                result,
+            } => {
+                // Validate against the currently open PR. If the user has
+                // opened a different PR (or left PR mode) while the fetch
+                // was in flight, drop the result silently.
+                let current = match &self.diff_source {
+                    DiffSource::PullRequest(pr) => Some((
+                        pr.key.repository.clone(),
+                        pr.key.number,
+                        pr.key.head_sha.clone(),
+                    )),
+                    _ => None,
+                };
+                let still_relevant = current
+                    .as_ref()
+                    .map(|(r, n, sha)| *r == repository && *n == pr_number && *sha == head_sha)
+                    .unwrap_or(false);
+                if !still_relevant {
+                    return;
+                }
+                match result {
+                    Ok(threads) => {
+                        self.forge_review_threads = threads;
+                        self.rebuild_annotations();
+                    }
+                    Err(e) => {
+                        self.forge_review_threads = Vec::new();
+                        self.set_warning(format!("Failed to load remote comments: {e}"));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Update the per-session remote comments visibility and repaint.
+    /// Returns `true` if the visibility actually changed.
+    pub fn set_remote_comments_visibility(
+        &mut self,
+        visibility: crate::forge::remote_comments::PrCommentsVisibility,
+    ) -> bool {
+        if self.session.remote_comments_visibility == visibility {
+            return false;
+        }
+        self.session.remote_comments_visibility = visibility;
+        self.rebuild_annotations();
+        true
+    }
+
+    /// `:comments import`: copy the visible remote threads into the session
+    /// as locked comments marked with their author.
+    pub fn import_remote_comments(&mut self) {
+        if self.forge_review_threads_loading {
+            self.set_warning("Remote comments are still loading");
+            return;
+        }
+        let summary = crate::forge::remote_comments::import_threads(
+            &mut self.session,
+            &self.forge_review_threads,
+        );
+        if summary.imported == 0 && summary.already_imported == 0 {
+            self.set_warning(format!(
+                "No {} remote comments to import",
+                self.session.remote_comments_visibility.label()
+            ));
+            return;
+        }
+        let mut message = format!("Imported {} remote comments", summary.imported);
+        if summary.already_imported > 0 {
+            message.push_str(&format!(", {} already imported", summary.already_imported));
+        }
+        if summary.skipped > 0 {
+            message.push_str(&format!(", {} on files outside the diff", summary.skipped));
+        }
+        if summary.imported > 0 {
+            self.mark_dirty();
+            self.rebuild_annotations();
+        }
+        self.set_message(message);
+    }
+
+    /// Abort an in-flight PR open. Drops the receiver so the eventual
+    /// thread send becomes a no-op; clears the spinner state.
+    pub fn cancel_pr_open(&mut self) -> bool {
+        if self.pr_open_state.is_none() {
+            return false;
+        }
+        self.pr_open_state = None;
+        self.pr_open_rx = None;
+        self.set_message("PR open cancelled".to_string());
+        true
+    }
+
+    /// Re-fetch remote review threads for the currently open PR. Called
+    /// from `:e` so users can pull the latest discussions without
+    /// reopening the PR. No-op outside PR mode.
+    pub fn refetch_pr_threads(&mut self) {
+        let local_checkout = self
+            .forge_backend
+            .as_deref()
+            .and_then(|b| b.local_checkout_path());
+        let details = match &self.diff_source {
+            DiffSource::PullRequest(pr) => crate::forge::traits::PullRequestDetails {
+                repository: pr.key.repository.clone(),
+                number: pr.key.number,
+                title: pr.title.clone(),
+                url: pr.url.clone(),
+                state: pr.state.clone(),
+                is_draft: false,
+                author: None,
+                head_ref_name: pr.head_ref_name.clone(),
+                base_ref_name: pr.base_ref_name.clone(),
+                head_sha: pr.key.head_sha.clone(),
+                base_sha: pr.base_sha.clone(),
+                body: String::new(),
+                updated_at: None,
+                closed: pr.closed,
+                merged_at: None,
+            },
+            _ => return,
+        };
+        self.spawn_pr_threads_fetch(&details, local_checkout);
+    }
+
+    /// Open a PR using the provided forge backend, synchronously. Exists
+    /// as a seam for tests that want to drive the open without spinning
+    /// up a background thread + mpsc round-trip. Production paths go
+    /// through `spawn_pr_open` (selector) or `new_from_pr_target` (CLI).
+    ///
+    /// Synchronously fetches `list_review_threads` from the same backend
+    /// and applies it before returning. This is the convenient seam for
+    /// integration tests; the production async path uses
+    /// `spawn_pr_threads_fetch` instead.
+    #[allow(dead_code)]
+    pub fn open_pr_with_backend(
+        &mut self,
+        summary: &crate::forge::traits::PullRequestSummary,
+        backend: Box<dyn ForgeBackend>,
+        local_checkout: Option<std::path::PathBuf>,
+    ) -> Result<()> {
+        use crate::forge::pr_open::open_pull_request;
+        use crate::forge::traits::PullRequestTarget;
+
+        let target = PullRequestTarget::with_repository(
+            summary.repository.clone(),
+            summary.number,
+            summary.number.to_string(),
+        );
+        let highlighter = self.theme.syntax_highlighter();
+        let mut opened = open_pull_request(
+            backend.as_ref(),
+            target,
+            local_checkout.as_deref(),
+            highlighter,
+        )?;
+        Self::load_or_apply_pr_session(&mut opened);
+        // Sync thread fetch — tests assert on `app.forge_review_threads`
+        // immediately after this returns.
+        let threads = backend
+            .list_review_threads(&opened.details)
+            .unwrap_or_default();
+        self.enter_pr_diff_mode(backend, opened)?;
+        self.forge_review_threads = threads;
+        self.rebuild_annotations();
+        Ok(())
+    }
+
+    pub fn begin_pr_filter(&mut self) {
+        if !self.pr_tab.is_loaded() {
+            return;
+        }
+        // Seed the draft from the current applied filter so the user can
+        // refine it. Starting from empty is also reasonable; preserving the
+        // current filter feels less surprising when re-opening.
+        let current = match &self.pr_tab {
+            PullRequestsTab::Loaded { filter, .. } => filter.clone(),
+            _ => String::new(),
+        };
+        self.pr_filter_draft = Some(current);
+    }
+
+    pub fn commit_pr_filter(&mut self) {
+        if let Some(draft) = self.pr_filter_draft.take() {
+            self.pr_tab.set_filter(draft);
+        }
+    }
+
+    pub fn cancel_pr_filter(&mut self) {
+        self.pr_filter_draft = None;
+    }
+
+    pub fn pr_filter_insert_char(&mut self, ch: char) {
+        if let Some(draft) = self.pr_filter_draft.as_mut() {
+            draft.push(ch);
+        }
+    }
+
+    pub fn pr_filter_delete_char(&mut self) {
+        if let Some(draft) = self.pr_filter_draft.as_mut() {
+            draft.pop();
+        }
+    }
+
+    pub fn pr_filter_clear(&mut self) {
+        if let Some(draft) = self.pr_filter_draft.as_mut() {
+            draft.clear();
+        }
+    }
+
+    pub fn pr_filter_editing(&self) -> bool {
+        self.pr_filter_draft.is_some()
+    }
+
+    pub fn toggle_diff_view_mode(&mut self) {
+        self.diff_view_mode = match self.diff_view_mode {
+            DiffViewMode::Unified => DiffViewMode::SideBySide,
+            DiffViewMode::SideBySide => DiffViewMode::Unified,
+        };
+        let mode_name = match self.diff_view_mode {
+            DiffViewMode::Unified => "unified",
+            DiffViewMode::SideBySide => "side-by-side",
+        };
+        self.set_message(format!("Diff view mode: {mode_name}"));
+        self.rebuild_annotations();
+    }
+
+    pub fn toggle_file_list(&mut self) {
+        self.show_file_list = !self.show_file_list;
+        if !self.show_file_list && self.focused_panel == FocusedPanel::FileList {
+            self.focused_panel = FocusedPanel::Diff;
+        }
+        let status = if self.show_file_list {
+            "visible"
+        } else {
+            "hidden"
+        };
+        self.set_message(format!("File list: {status}"));
+    }
+
+    /// Whether the inline commit selector panel should be displayed.
+    pub fn has_inline_commit_selector(&self) -> bool {
+        self.show_commit_selector
+            && self.review_commits.len() > 1
+            && !matches!(&self.diff_source, DiffSource::WorkingTree)
+    }
+
+    // Commit selection methods
+
+    pub fn commit_select_up(&mut self) {
+        if self.commit_list_cursor > 0 {
+            self.commit_list_cursor -= 1;
+            // Scroll up if cursor goes above visible area
+            if self.commit_list_cursor < self.commit_list_scroll_offset {
+                self.commit_list_scroll_offset = self.commit_list_cursor;
+            }
+        }
+    }
+
+    pub fn commit_select_down(&mut self) {
+        let max_cursor = if self.can_show_more_commits() {
+            self.visible_commit_count
+        } else {
+            self.visible_commit_count.saturating_sub(1)
+        };
+
+        if self.commit_list_cursor < max_cursor {
+            self.commit_list_cursor += 1;
+            // Scroll down if cursor goes below visible area
+            if self.commit_list_viewport_height > 0
+                && self.commit_list_cursor
+                    >= self.commit_list_scroll_offset + self.commit_list_viewport_height
+            {
+                self.commit_list_scroll_offset =
+                    self.commit_list_cursor - self.commit_list_viewport_height + 1;
+            }
+        }
+    }
+
+    /// Toggle the cursor commit's membership in the selection range, then
+    /// (only if the cursor commit was newly added to the selection) move the
+    /// cursor past the end of the range. Lets the user press Enter/Space
+    /// repeatedly to sweep a contiguous run of commits.
+    ///
+    /// Other toggle outcomes leave the cursor in place: edge presses
+    /// (deselect the cursor commit), middle presses (truncate the range
+    /// without unselecting the cursor commit), and clearing the last
+    /// selection. Those aren't "sweep" actions, so advancing would surprise.
+    pub fn toggle_commit_selection_and_advance(&mut self) {
+        let cursor = self.commit_list_cursor;
+        let was_selected = self.is_commit_selected(cursor);
+        self.toggle_commit_selection();
+        let now_selected = self.is_commit_selected(cursor);
+        if was_selected || !now_selected {
+            return;
+        }
+        if let Some((_, end)) = self.commit_selection_range {
+            while self.commit_list_cursor <= end {
+                let before = self.commit_list_cursor;
+                self.commit_select_down();
+                if self.commit_list_cursor == before {
+                    return;
+                }
+            }
+        }
+    }
+
+    // Check if cursor is on the commit expand row
+    pub fn is_on_expand_row(&self) -> bool {
+        self.can_show_more_commits() && self.commit_list_cursor == self.visible_commit_count
+    }
+
+    pub fn can_show_more_commits(&self) -> bool {
+        self.visible_commit_count < self.commit_list.len() || self.has_more_commit
+    }
+
+    // Expand the commit list to show more commits
+    pub fn expand_commit(&mut self) -> Result<()> {
+        self.vcs_idle()?;
+        if self.visible_commit_count < self.commit_list.len() {
+            self.visible_commit_count =
+                (self.visible_commit_count + self.commit_page_size).min(self.commit_list.len());
+            return Ok(());
+        }
+
+        if !self.has_more_commit {
+            self.set_message("No more commits");
+            return Ok(());
+        }
+
+        let offset = self.loaded_history_commit_count();
+        let limit = self.commit_page_size;
+
+        let new_commits = self.vcs.get_recent_commits(offset, limit)?;
+
+        if new_commits.is_empty() {
+            self.has_more_commit = false;
+            self.set_message("No more commits");
+            return Ok(());
+        }
+
+        if new_commits.len() < limit {
+            self.has_more_commit = false;
+            self.set_message("No more commits");
+        }
+
+        self.commit_list.extend(new_commits);
+        self.visible_commit_count = self.commit_list.len();
+
+        Ok(())
+    }
+
+    pub fn toggle_commit_selection(&mut self) {
+        let cursor = self.commit_list_cursor;
+        if cursor >= self.commit_list.len() {
+            return;
+        }
+
+        match self.commit_selection_range {
+            None => {
+                // No selection yet - select just this commit
+                self.commit_selection_range = Some((cursor, cursor));
+            }
+            Some((start, end)) => {
+                let all_commits_selected =
+                    self.commit_list.len() > 1 && start == 0 && end == self.commit_list.len() - 1;
+                if all_commits_selected {
+                    self.commit_selection_range = Some((cursor, cursor));
+                    return;
+                }
+
+                if cursor >= start && cursor <= end {
+                    // Cursor is within the range - shrink or deselect
+                    if start == end {
+                        // Only one commit selected, deselect all
+                        self.commit_selection_range = None;
+                    } else if cursor == start {
+                        // At start edge - shrink from start
+                        self.commit_selection_range = Some((start + 1, end));
+                    } else if cursor == end {
+                        // At end edge - shrink from end
+                        self.commit_selection_range = Some((start, end - 1));
+                    } else {
+                        // In the middle - deselect cursor and everything after it
+                        self.commit_selection_range = Some((start, cursor - 1));
+                    }
+                } else {
+                    // Cursor is outside the range - extend to include it
+                    let new_start = start.min(cursor);
+                    let new_end = end.max(cursor);
+                    self.commit_selection_range = Some((new_start, new_end));
+                }
+            }
+        }
+    }
+
+    /// Check if a commit at the given index is selected
+    pub fn is_commit_selected(&self, index: usize) -> bool {
+        match self.commit_selection_range {
+            Some((start, end)) => index >= start && index <= end,
+            None => false,
+        }
+    }
+
+    /// Cycle inline commit selector to the next individual commit (`)` key).
+    /// all → last, i → i+1, last → all
+    pub fn cycle_commit_next(&mut self) {
+        if self.review_commits.is_empty() {
+            return;
+        }
+        let n = self.review_commits.len();
+        let all_selected = Some((0, n - 1));
+
+        if self.commit_selection_range == all_selected {
+            // all → last
+            self.commit_selection_range = Some((n - 1, n - 1));
+            self.commit_list_cursor = n - 1;
+        } else if let Some((i, j)) = self.commit_selection_range {
+            if i == j {
+                // Single commit selected
+                if i == n - 1 {
+                    // last → all
+                    self.commit_selection_range = all_selected;
+                } else {
+                    // i → i+1
+                    self.commit_selection_range = Some((i + 1, i + 1));
+                    self.commit_list_cursor = i + 1;
+                }
+            } else {
+                // Multi-commit subrange → select last of that range
+                self.commit_selection_range = Some((j, j));
+                self.commit_list_cursor = j;
+            }
+        } else {
+            // None selected → select all
+            self.commit_selection_range = all_selected;
+        }
+    }
+
+    /// Cycle inline commit selector to the previous individual commit (`(` key).
+    /// all → first, i → i-1, first → all
+    pub fn cycle_commit_prev(&mut self) {
+        if self.review_commits.is_empty() {
+            return;
+        }
+        let n = self.review_commits.len();
+        let all_selected = Some((0, n - 1));
+
+        if self.commit_selection_range == all_selected {
+            // all → first
+            self.commit_selection_range = Some((0, 0));
+            self.commit_list_cursor = 0;
+        } else if let Some((i, j)) = self.commit_selection_range {
+            if i == j {
+                // Single commit selected
+                if i == 0 {
+                    // first → all
+                    self.commit_selection_range = all_selected;
+                } else {
+                    // i → i-1
+                    self.commit_selection_range = Some((i - 1, i - 1));
+                    self.commit_list_cursor = i - 1;
+                }
+            } else {
+                // Multi-commit subrange → select first of that range
+                self.commit_selection_range = Some((i, i));
+                self.commit_list_cursor = i;
+            }
+        } else {
+            // None selected → select all
+            self.commit_selection_range = all_selected;
+        }
+    }
+
+    /// Flip between `A..B` and `A...B` range diffing. Takes effect the next
+    /// time a commit range is loaded.
+    pub fn toggle_range_mode(&mut self) {
+        self.range_mode = self.range_mode.toggled();
+        self.commit_diff_cache.clear();
+        let label = match self.range_mode {
+            RangeMode::TwoDot => "A..B",
+            RangeMode::ThreeDot => "A...B (merge-base)",
+        };
+        self.set_message(format!("Range diff: {label}"));
+    }
+
+    /// The ref expression a commit range (oldest first) is diffed as, e.g.
+    /// `1a2b3c4^..5d6e7f8`.
+    pub fn range_expression(&self, commit_ids: &[String]) -> Option<String> {
+        let short = |id: &String| id[..7.min(id.len())].to_string();
+        let oldest = commit_ids.first()?;
+        let newest = commit_ids.last()?;
+        Some(format!(
+            "{}^{}{}",
+            short(oldest),
+            self.range_mode.separator(),
+            short(newest)
+        ))
+    }
+
+    /// Range expression for the rows selected in `rows` (newest first),
+    /// ignoring the staged/unstaged entries.
+    pub fn selected_range_expression(&self, rows: &[CommitInfo]) -> Option<String> {
+        let (start, end) = self.commit_selection_range?;
+        let ids: Vec<String> = (start..=end)
+            .rev()
+            .filter_map(|i| rows.get(i))
+            .filter(|c| !Self::is_special_commit(c))
+            .map(|c| c.id.clone())
+            .collect();
+        self.range_expression(&ids)
+    }
+
+    /// Ask what to review when HEAD also has commits its upstream lacks,
+    /// rather than leaving the choice to the commit selector alone.
+    fn offer_review_chooser(&mut self) {
+        match self.vcs.resolve_revisions("@{u}..HEAD") {
+            Ok(ids) if !ids.is_empty() => {
+                self.unpushed_commits = ids;
+                self.review_chooser_cursor = 0;
+                self.open_overlay(InputMode::ReviewChooser);
+            }
+            // No upstream, or nothing unpushed
+            _ => {}
+        }
+    }
+
+    /// Start `tuicr` here with the source last reviewed in this repository
+    /// recorded to and read from `path`, offering it in the chooser.
+    pub fn enable_last_source(&mut self, path: PathBuf) {
+        match crate::persistence::last_source::load_last_source(&path, &self.vcs_info.root_path) {
+            Ok(Some(source)) => self.offer_last_source(source),
+            Ok(None) => {}
+            Err(e) => self.set_warning(format!("Failed to read last review source: {e}")),
+        }
+        self.last_source_path = Some(path);
+    }
+
+    /// Open the chooser with `source` as its first row, when the commit
+    /// selector is what startup landed on and the source still applies.
+    fn offer_last_source(&mut self, source: ReviewSource) {
+        let specials = &self.commit_list[..self.special_commit_count()];
+        let applies = match source {
+            ReviewSource::Staged => specials.iter().any(Self::is_staged_commit),
+            ReviewSource::Unstaged => specials.iter().any(Self::is_unstaged_commit),
+            ReviewSource::StagedAndUnstaged => !specials.is_empty(),
+            _ => true,
+        };
+        if self.base_mode() != InputMode::CommitSelect || !applies {
+            return;
+        }
+        self.last_source = Some(source);
+        self.review_chooser_cursor = 0;
+        if self.input_mode != InputMode::ReviewChooser {
+            self.open_overlay(InputMode::ReviewChooser);
+        }
+    }
+
+    /// Record `source` as the one to offer next time tuicr starts here.
+    fn remember_source(&mut self, source: ReviewSource) {
+        let Some(path) = self.last_source_path.as_deref() else {
+            return;
+        };
+        if let Err(e) = crate::persistence::last_source::save_last_source(
+            path,
+            &self.vcs_info.root_path,
+            &source,
+        ) {
+            self.set_warning(format!("Failed to record review source: {e}"));
+        }
+    }
+
+    /// Rows of the startup chooser, in display order: the last used
+    /// source, the working tree and unpushed commits when there are any,
+    /// then the commit selector and a base ref prompt.
+    pub fn review_chooser_rows(&self) -> Vec<(String, ReviewChoice)> {
+        let mut rows = Vec::new();
+        if let Some(source) = &self.last_source {
+            let label = format!("Last used: {}", source.describe());
+            rows.push((label, ReviewChoice::LastUsed));
+        }
+        if self.special_commit_count() > 0 {
+            rows.push(("Review working tree".to_string(), ReviewChoice::WorkingTree));
+        }
+        if !self.unpushed_commits.is_empty() {
+            let label = format!(
+                "Review unpushed commits vs upstream ({})",
+                self.unpushed_commits.len()
+            );
+            rows.push((label, ReviewChoice::UnpushedCommits));
+        }
+        rows.push(("Pick commits".to_string(), ReviewChoice::PickCommits));
+        rows.push(("Pick base ref".to_string(), ReviewChoice::PickBaseRef));
+        rows
+    }
+
+    pub fn review_chooser_cursor_down(&mut self) {
+        let total = self.review_chooser_rows().len();
+        self.review_chooser_cursor = (self.review_chooser_cursor + 1) % total;
+    }
+
+    pub fn review_chooser_cursor_up(&mut self) {
+        let total = self.review_chooser_rows().len();
+        self.review_chooser_cursor = (self.review_chooser_cursor + total - 1) % total;
+    }
+
+    /// Act on the chooser row under the cursor. "Pick commits" and Esc
+    /// leave the commit selector underneath open.
+    pub fn review_chooser_confirm(&mut self) {
+        let Some((_, choice)) = self
+            .review_chooser_rows()
+            .into_iter()
+            .nth(self.review_chooser_cursor)
+        else {
+            return;
+        };
+        self.close_overlay();
+        match choice {
+            ReviewChoice::LastUsed => {
+                if let Some(source) = self.last_source.clone() {
+                    self.review_source(source);
+                }
+            }
+            ReviewChoice::WorkingTree => {
+                let specials = self.special_commit_count();
+                if specials > 0 {
+                    self.commit_selection_range = Some((0, specials - 1));
+                }
+                if let Err(e) = self.confirm_commit_selection() {
+                    self.set_error(format!("Failed to load changes: {e}"));
+                }
+            }
+            ReviewChoice::UnpushedCommits => {
+                let commit_ids = self.unpushed_commits.clone();
+                if self.review_commit_ids(commit_ids, "unpushed commits") {
+                    self.remember_source(ReviewSource::Base {
+                        base: "@{u}".to_string(),
+                    });
+                }
+            }
+            ReviewChoice::PickCommits => {}
+            ReviewChoice::PickBaseRef => {
+                self.input_mode = InputMode::Command;
+                self.command_buffer = "base ".to_string();
+            }
+        }
+    }
+
+    /// Switch to a remembered review source.
+    fn review_source(&mut self, source: ReviewSource) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        let loaded = match &source {
+            ReviewSource::Staged => self.load_staged_selection(),
+            ReviewSource::Unstaged => self.load_unstaged_selection(),
+            ReviewSource::StagedAndUnstaged => self.load_staged_and_unstaged_selection(),
+            ReviewSource::Commits { ids } => {
+                self.review_commit_ids(ids.clone(), "commits");
+                Ok(())
+            }
+            ReviewSource::Base { base } if base == "@{u}" => {
+                match self.vcs.resolve_revisions("@{u}..HEAD") {
+                    Ok(ids) => {
+                        self.review_commit_ids(ids, "unpushed commits");
+                    }
+                    Err(e) => self.set_error(format!("Failed to find unpushed commits: {e}")),
+                }
+                Ok(())
+            }
+            ReviewSource::Base { base } => {
+                self.review_base(base);
+                Ok(())
+            }
+            ReviewSource::Revset { revset } => {
+                self.review_revset(revset);
+                Ok(())
+            }
+        };
+        if let Err(e) = loaded {
+            self.set_error(format!("Failed to load {}: {e}", source.describe()));
+        }
+    }
+
+    /// `:base <ref>`: review every commit since `ref` up to HEAD.
+    pub fn review_base(&mut self, base: &str) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        let base = base.trim();
+        if base.is_empty() {
+            self.set_warning("Usage: :base <ref>");
+            return;
+        }
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_warning("Base refs are not available when reviewing a pull request");
+            return;
+        }
+        let revisions = match self.vcs_info.vcs_type {
+            VcsType::Git => format!("{base}..HEAD"),
+            VcsType::Jujutsu => format!("{base}..@"),
+            VcsType::Mercurial => format!("only(., {base})"),
+            VcsType::File | VcsType::Patch => {
+                self.set_warning("Base refs are not supported by this backend");
+                return;
+            }
+        };
+        match self.vcs.resolve_revisions(&revisions) {
+            Ok(commit_ids) => {
+                if self.review_commit_ids(commit_ids, &format!("commits since {base}")) {
+                    self.remember_source(ReviewSource::Base {
+                        base: base.to_string(),
+                    });
+                }
+            }
+            Err(TuicrError::NoChanges) => self.set_message(format!("No commits since {base}")),
+            Err(e) => self.set_error(format!("Failed to resolve {base}: {e}")),
+        }
+    }
+
+    /// Switch to reviewing `commit_ids` (oldest first), described as `what`
+    /// in the status message. Returns whether the review switched.
+    fn review_commit_ids(&mut self, commit_ids: Vec<String>, what: &str) -> bool {
+        if !self.ensure_vcs_idle() {
+            return false;
+        }
+        let highlighter = self.theme.syntax_highlighter();
+        let diff_files = match Self::get_commit_range_diff_with_ignore(
+            self.vcs.as_ref(),
+            &self.vcs_info.root_path,
+            &commit_ids,
+            self.range_mode,
+            highlighter,
+            self.path_filter.as_deref(),
+        ) {
+            Ok(files) => files,
+            Err(TuicrError::NoChanges) => {
+                self.set_message(format!("No changes in {what}"));
+                return false;
+            }
+            Err(e) => {
+                self.set_error(format!("Failed to load {what}: {e}"));
+                return false;
+            }
+        };
+        let review_commits = match self.vcs.get_commits_info(&commit_ids) {
+            Ok(commits) => commits.into_iter().rev().collect(),
+            Err(e) => {
+                self.set_error(format!("Failed to load commits: {e}"));
+                return false;
+            }
+        };
+
+        if self.dirty {
+            let _ = crate::persistence::save_session(&self.session);
+            self.dirty = false;
+        }
+
+        let commit_count = commit_ids.len();
+        self.revset = None;
+        self.interdiff = None;
+        self.show_commit_range(commit_ids, diff_files, review_commits);
+        self.insert_commit_message_if_single();
+        self.set_message(format!(
+            "Reviewing {what} ({commit_count} commit{})",
+            if commit_count == 1 { "" } else { "s" }
+        ));
+        true
+    }
+
+    pub fn confirm_commit_selection(&mut self) -> Result<()> {
+        let selection = match self.commit_selection_range {
+            Some((start, end)) => format!(
+                "range={start}..={end}, rows={}",
+                end.saturating_sub(start) + 1
+            ),
+            None => "range=none, rows=0".to_string(),
+        };
+        crate::profile::time_with(
             "commit_select.confirm_selection",
             || self.confirm_commit_selection_inner(),
             |result| format!("{selection}, {}", profile_unit_result(result)),
         )
     }
 
-    fn confirm_commit_selection_inner(&mut self) -> Result<()> {
-        let Some((start, end)) = self.commit_selection_range else {
-            self.set_message("Select at least one commit");
-            return Ok(());
-        };
+    fn confirm_commit_selection_inner(&mut self) -> Result<()> {
+        self.vcs_idle()?;
+        let Some((start, end)) = self.commit_selection_range else {
+            self.set_message("Select at least one commit");
+            return Ok(());
+        };
+
+        // Collect selected entries in order from oldest to newest (end..start).
+        let selected_commits: Vec<&CommitInfo> = (start..=end)
+            .rev()
+            .filter_map(|i| self.commit_list.get(i))
+            .collect();
+
+        if selected_commits.is_empty() {
+            self.set_message("Select at least one commit");
+            return Ok(());
+        }
+
+        let selected_staged = selected_commits.iter().any(|c| Self::is_staged_commit(c));
+        let selected_unstaged = selected_commits.iter().any(|c| Self::is_unstaged_commit(c));
+        let selected_ids: Vec<String> = selected_commits
+            .iter()
+            .filter(|c| !Self::is_special_commit(c))
+            .map(|c| c.id.clone())
+            .collect();
+
+        if (selected_staged || selected_unstaged) && !selected_ids.is_empty() {
+            let all_selected: Vec<CommitInfo> = selected_commits.into_iter().cloned().collect();
+            return self.load_staged_unstaged_and_commits_selection(selected_ids, all_selected);
+        }
+
+        let working_tree_source = match (selected_staged, selected_unstaged) {
+            (true, true) => Some(ReviewSource::StagedAndUnstaged),
+            (true, false) => Some(ReviewSource::Staged),
+            (false, true) => Some(ReviewSource::Unstaged),
+            (false, false) => None,
+        };
+        if let Some(source) = working_tree_source {
+            match source {
+                ReviewSource::StagedAndUnstaged => self.load_staged_and_unstaged_selection()?,
+                ReviewSource::Staged => self.load_staged_selection()?,
+                _ => self.load_unstaged_selection()?,
+            }
+            // The selector stays open when there was nothing to load
+            if self.input_mode == InputMode::Normal {
+                self.remember_source(source);
+            }
+            return Ok(());
+        }
+
+        // Get the diff for the selected commits
+        let highlighter = self.theme.syntax_highlighter();
+        let diff_files = Self::get_commit_range_diff_with_ignore(
+            self.vcs.as_ref(),
+            &self.vcs_info.root_path,
+            &selected_ids,
+            self.range_mode,
+            highlighter,
+            self.path_filter.as_deref(),
+        )?;
+
+        if diff_files.is_empty() {
+            self.set_message("No changes in selected commits");
+            return Ok(());
+        }
+
+        let review_commits = selected_commits.into_iter().rev().cloned().collect();
+        self.revset = None;
+        self.interdiff = None;
+        self.remember_source(ReviewSource::Commits {
+            ids: selected_ids.clone(),
+        });
+        self.show_commit_range(selected_ids, diff_files, review_commits);
+        Ok(())
+    }
+
+    /// Switch to reviewing `commit_ids` (oldest first) with the already loaded
+    /// `diff_files`, resuming the range's saved session when one exists.
+    /// `review_commits` feeds the inline commit selector, newest first.
+    fn show_commit_range(
+        &mut self,
+        commit_ids: Vec<String>,
+        diff_files: Vec<DiffFile>,
+        review_commits: Vec<CommitInfo>,
+    ) {
+        self.session = Self::load_or_create_commit_range_session(&self.vcs_info, &commit_ids);
+
+        // Add files to session
+        for file in &diff_files {
+            let path = file.display_path().clone();
+            self.session.add_file(path, file.status, file.content_hash);
+        }
+
+        // Update app state
+        self.diff_files = diff_files;
+        self.diff_source = DiffSource::CommitRange(commit_ids);
+        self.input_mode = InputMode::Normal;
+        self.author_filter = None;
+
+        // Reset navigation state
+        self.diff_state = DiffState::default();
+        self.file_list_state = FileListState::default();
+
+        // Set up inline commit selector for multi-commit reviews
+        self.review_commits = review_commits;
+        self.range_diff_files = Some(self.diff_files.clone());
+        self.commit_list = self.review_commits.clone();
+        self.commit_list_cursor = 0;
+        self.commit_selection_range = if self.review_commits.is_empty() {
+            None
+        } else {
+            Some((0, self.review_commits.len() - 1))
+        };
+        self.commit_list_scroll_offset = 0;
+        self.visible_commit_count = self.review_commits.len();
+        self.has_more_commit = false;
+        self.show_commit_selector = self.review_commits.len() > 1;
+        self.commit_diff_cache.clear();
+        self.saved_inline_selection = None;
+
+        self.sort_files_by_directory(true);
+        self.expand_all_dirs();
+        self.reanchor_comments();
+        self.rebuild_annotations();
+    }
+
+    /// Note an unsaved change to the session and, with auto-save on,
+    /// (re)start its countdown.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+        if let Some(delay) = self.autosave_delay {
+            self.autosave_due = Some(Instant::now() + delay);
+        }
+    }
+
+    /// Append `op` to the session's crash recovery journal (see
+    /// `persistence::journal`), locking the session when it starts one.
+    /// Read-only reviews have nothing to recover.
+    fn journal(&mut self, op: JournalOp) {
+        let Some(dir) = self.journal_dir.as_deref().filter(|_| !self.read_only) else {
+            return;
+        };
+        if !journal::started(dir, &self.session.id)
+            && let Err(e) = crate::persistence::lock::acquire(&self.session.id)
+        {
+            self.set_warning(format!("Not journaling unsaved changes: {e}"));
+            return;
+        }
+        if let Err(e) = journal::append(dir, &self.session, op) {
+            self.set_warning(format!("Failed to write the recovery journal: {e}"));
+        }
+    }
+
+    /// Journal the comment with `id` as it now is, wherever it now sits.
+    fn journal_comment(&mut self, id: &str) {
+        let Some((path, line, comment)) = self.session.find_comment(id) else {
+            return;
+        };
+        let op = JournalOp::CommentSaved {
+            path: path.cloned(),
+            line,
+            comment: Box::new(comment.clone()),
+        };
+        self.journal(op);
+    }
+
+    /// Journal the reviewed mark of `path`.
+    fn journal_file(&mut self, path: &Path) {
+        let Some(review) = self.session.files.get(path) else {
+            return;
+        };
+        let op = JournalOp::FileReviewed {
+            path: path.to_path_buf(),
+            reviewed: review.reviewed,
+            confidence: review.confidence,
+        };
+        self.journal(op);
+    }
+
+    /// Credit the time since the previous input to the session and to the
+    /// file on screen. A gap of [`IDLE_GAP_SECS`] or more is a break and
+    /// counts for nothing. Like the cursor, the time is saved with the
+    /// session's next save rather than marking it modified.
+    pub fn track_activity(&mut self, now: Instant) {
+        let Some(last) = self.last_activity.replace(now) else {
+            return;
+        };
+        let gap = now.saturating_duration_since(last);
+        if self.read_only || gap >= Duration::from_secs(IDLE_GAP_SECS as u64) {
+            return;
+        }
+        let path = match self.view {
+            View::Diff => self.current_file_path().cloned(),
+            _ => None,
+        };
+        self.session.add_active_time(path.as_deref(), gap);
+    }
+
+    /// Save the session once the auto-save countdown has run out. Only the
+    /// session file is written; notes refs still wait for `:w`.
+    pub fn poll_autosave(&mut self) {
+        if self.autosave_due.is_none_or(|due| Instant::now() < due) {
+            return;
+        }
+        self.autosave_due = None;
+        if !self.dirty {
+            return;
+        }
+        self.stamp_cursor();
+        self.session.updated_at = chrono::Utc::now();
+        match crate::persistence::save_session(&self.session) {
+            Ok(_) => {
+                self.dirty = false;
+                self.record_review_metrics();
+                self.archive_if_complete();
+            }
+            Err(e) => self.set_warning(format!("Auto-save failed: {e}")),
+        }
+    }
+
+    /// Record the session for `tuicr stats` once every file is reviewed.
+    /// Called after each save; failures only warn.
+    fn record_review_metrics(&mut self) {
+        let Some(path) = self.review_metrics.as_deref() else {
+            return;
+        };
+        let Some(metrics) = ReviewMetrics::from_session(&self.session, &self.diff_files) else {
+            return;
+        };
+        if let Err(e) = crate::persistence::metrics::record_metrics(path, &metrics) {
+            self.set_warning(format!("Failed to record review metrics: {e}"));
+        }
+    }
+
+    /// Copy the session to the `tuicr history` archive once every file is
+    /// reviewed. Called after each save; failures only warn.
+    fn archive_if_complete(&mut self) {
+        let Some(dir) = self.archive_dir.as_deref() else {
+            return;
+        };
+        if !self.session.is_complete() {
+            return;
+        }
+        if let Err(e) = crate::persistence::archive::archive_session(dir, &self.session) {
+            self.set_warning(format!("Failed to archive the review: {e}"));
+        }
+    }
+
+    /// Note the cursor's file and line in the session before saving it.
+    fn stamp_cursor(&mut self) {
+        let Some(path) = self.current_file_path().cloned() else {
+            return;
+        };
+        let line = self.get_line_at_cursor();
+        self.session.cursor = Some(SessionCursor {
+            path,
+            line: line.map(|(line, _)| line),
+            side: line.map(|(_, side)| side),
+        });
+    }
+
+    /// Ask whether to pick up the saved review tuicr opened with, when it
+    /// already has comments or reviewed files. Only offered when startup
+    /// went straight to a diff.
+    pub fn offer_resume(&mut self) {
+        let comments = self.session.comment_count();
+        let reviewed = self.session.reviewed_count();
+        if self.input_mode != InputMode::Normal || (comments == 0 && reviewed == 0) {
+            return;
+        }
+        let saved_at = self
+            .session
+            .updated_at
+            .with_timezone(&chrono::Local)
+            .format("%b %-d %H:%M");
+        let message = format!(
+            "Resume the review saved {saved_at}? ({comments} comment{}, {reviewed}/{} files reviewed; No starts fresh)",
+            if comments == 1 { "" } else { "s" },
+            self.session.files.len()
+        );
+        self.open_confirm(ConfirmDialog::with_message(
+            message,
+            ConfirmAction::ResumeSession,
+        ));
+    }
+
+    /// Lock the session tuicr opened with. A review another running tuicr
+    /// has open is opened read-only; one left locked by a tuicr that exited
+    /// asks whether to take it over.
+    pub fn claim_session_lock(&mut self) {
+        match crate::persistence::lock::inspect(&self.session.id) {
+            Ok(LockState::Free) => {
+                if let Err(e) = crate::persistence::lock::acquire(&self.session.id) {
+                    self.set_warning(format!("Failed to lock the review: {e}"));
+                }
+            }
+            Ok(LockState::Held(owner)) => {
+                self.read_only = true;
+                self.dirty = false;
+                self.set_warning(format!(
+                    "This review is open in another tuicr ({}); opened read-only",
+                    owner.describe()
+                ));
+            }
+            Ok(LockState::Stale(owner)) => {
+                let message = format!(
+                    "A tuicr that exited ({}) left this review locked. Take it over? (No opens it read-only)",
+                    owner.describe()
+                );
+                self.open_confirm(ConfirmDialog::with_message(
+                    message,
+                    ConfirmAction::TakeOverLock,
+                ));
+            }
+            Ok(LockState::Unreadable) => {
+                self.open_confirm(ConfirmDialog::with_message(
+                    "This review's lock file is unreadable, maybe left by a tuicr that crashed. Take it over? (No opens it read-only)",
+                    ConfirmAction::TakeOverLock,
+                ));
+            }
+            Err(e) => self.set_warning(format!("Failed to lock the review: {e}")),
+        }
+    }
+
+    /// Take over a stale session lock, then offer to resume the review.
+    pub fn take_over_session_lock(&mut self) {
+        if let Err(e) = crate::persistence::lock::take_over(&self.session.id) {
+            self.read_only = true;
+            self.set_error(format!("Failed to take over the review: {e}"));
+            return;
+        }
+        self.offer_resume();
+    }
+
+    /// Put the cursor back where the resumed session was saved.
+    pub fn restore_session_cursor(&mut self) {
+        let Some(cursor) = self.session.cursor.clone() else {
+            return;
+        };
+        let Some(idx) = self
+            .diff_files
+            .iter()
+            .position(|f| *f.display_path() == cursor.path)
+        else {
+            return;
+        };
+        self.jump_to_file(idx);
+        if let (Some(line), Some(side)) = (cursor.line, cursor.side) {
+            self.go_to_source_line(line, side);
+        }
+    }
+
+    /// Set the saved review aside and start over on the same diff. The old
+    /// session file is kept; the fresh one supersedes it once saved.
+    pub fn start_fresh_session(&mut self) {
+        let old = &self.session;
+        let mut session = ReviewSession::new(
+            old.repo_path.clone(),
+            old.base_commit.clone(),
+            old.branch_name.clone(),
+            old.diff_source,
+        );
+        session.commit_range = old.commit_range.clone();
+        session.pr_session_key = old.pr_session_key.clone();
+        session.reviewer = self.reviewer.clone().or_else(|| old.reviewer.clone());
+        for file in &self.diff_files {
+            session.add_file(file.display_path().clone(), file.status, file.content_hash);
+        }
+        self.session = session;
+        self.dirty = false;
+        self.rebuild_annotations();
+        self.set_message("Started a fresh review");
+    }
+
+    /// Save the session file and, with `notes_ref` set in a git repo, mirror
+    /// it as a note on each reviewed commit (not under `--safe`, which leaves
+    /// the repository alone). Returns the status line to show; a notes
+    /// failure is reported there rather than failing the save.
+    pub fn write_session(&mut self) -> Result<String> {
+        if self.read_only {
+            return Err(TuicrError::UnsupportedOperation(
+                "this review is read-only".to_string(),
+            ));
+        }
+        self.stamp_cursor();
+        self.session.updated_at = chrono::Utc::now();
+        let path = crate::persistence::save_session(&self.session)?;
+        let mut message = format!("Saved to {}", path.display());
+        if let Some(notes_ref) = self.notes_ref.as_deref()
+            && self.vcs_info.vcs_type == VcsType::Git
+        {
+            if self.safe_mode {
+                message.push_str(&format!("; {notes_ref} not written in safe mode (--safe)"));
+            } else {
+                match crate::persistence::git_notes::write_session_notes(
+                    &self.vcs_info.root_path,
+                    notes_ref,
+                    &self.session,
+                ) {
+                    Ok(0) => {}
+                    Ok(count) => message.push_str(&format!(" and {notes_ref} ({count} commits)")),
+                    Err(e) => message.push_str(&format!("; writing {notes_ref} failed: {e}")),
+                }
+            }
+        }
+        self.dirty = false;
+        self.record_review_metrics();
+        self.archive_if_complete();
+        Ok(message)
+    }
+
+    /// Start recording exported ISSUE comments to the findings database at
+    /// `path` and load the counts already recorded for this repository.
+    pub fn enable_findings_db(&mut self, path: PathBuf) {
+        match FindingsDb::load(&path) {
+            Ok(db) => self.findings_by_file = db.counts_for_repo(&self.vcs_info.root_path),
+            Err(e) => self.set_warning(format!("Failed to read findings database: {e}")),
+        }
+        self.findings_db = Some(path);
+    }
+
+    /// Add the session's ISSUE comments to the findings database. Called
+    /// after a successful export; failures only warn.
+    pub fn record_findings(&mut self) {
+        let Some(path) = self.findings_db.clone() else {
+            return;
+        };
+        let result = FindingsDb::load(&path).and_then(|mut db| {
+            if db.record_session(&self.session, &self.diff_files) > 0 {
+                db.save(&path)?;
+            }
+            Ok(db)
+        });
+        match result {
+            Ok(db) => self.findings_by_file = db.counts_for_repo(&self.vcs_info.root_path),
+            Err(e) => self.set_warning(format!("Failed to record findings: {e}")),
+        }
+    }
+
+    /// Findings recorded against `path` when there are enough to flag it
+    pub fn findings_hint(&self, path: &Path) -> Option<usize> {
+        self.findings_by_file
+            .get(path)
+            .copied()
+            .filter(|&count| count >= FINDINGS_HINT_THRESHOLD)
+    }
+
+    /// Adopt a teammate's review from git notes when this commit range has
+    /// no local comments yet. Called once at startup after config is applied.
+    pub fn load_session_from_notes(&mut self) {
+        let Some(notes_ref) = self.notes_ref.clone() else {
+            return;
+        };
+        if self.vcs_info.vcs_type != VcsType::Git || self.session.has_comments() {
+            return;
+        }
+        let Some(newest) = self
+            .session
+            .commit_range
+            .as_ref()
+            .and_then(|range| range.last())
+        else {
+            return;
+        };
+        let noted = match crate::persistence::git_notes::read_session_note(
+            &self.vcs_info.root_path,
+            &notes_ref,
+            newest,
+        ) {
+            Ok(Some(noted)) => noted,
+            Ok(None) => return,
+            Err(e) => {
+                self.set_warning(format!("Failed to read {notes_ref}: {e}"));
+                return;
+            }
+        };
+        if noted.commit_range != self.session.commit_range || !noted.has_comments() {
+            return;
+        }
+
+        let mut session = noted;
+        session.repo_path = self.session.repo_path.clone();
+        for file in &self.diff_files {
+            session.add_file(file.display_path().clone(), file.status, file.content_hash);
+        }
+        self.session = session;
+        self.rebuild_annotations();
+        self.set_message(format!("Loaded review from {notes_ref}"));
+    }
+
+    /// Collect line comments from earlier saved sessions of this repository
+    /// so they can be shown ghosted on the same lines. Called once at startup.
+    pub fn load_history_comments(&mut self) {
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            return;
+        }
+        match crate::persistence::list_sessions_for_repo(&self.vcs_info.root_path) {
+            Ok(sessions) => {
+                let sessions: Vec<ReviewSession> =
+                    sessions.into_iter().map(|(_, session)| session).collect();
+                self.set_history_from_sessions(&sessions);
+            }
+            Err(e) => self.set_warning(format!("Failed to read earlier reviews: {e}")),
+        }
+    }
+
+    /// Index the line comments of `sessions` (newest first) against the files
+    /// in the current diff. The current session and repeats of the same
+    /// comment across sessions are skipped.
+    pub fn set_history_from_sessions(&mut self, sessions: &[ReviewSession]) {
+        let mut history: HashMap<PathBuf, Vec<HistoryComment>> = HashMap::new();
+        let mut seen_ids = HashSet::new();
+        for session in sessions {
+            if session.id == self.session.id {
+                continue;
+            }
+            for file in &self.diff_files {
+                let path = file.display_path();
+                let Some(review) = session.files.get(path) else {
+                    continue;
+                };
+                let mut lines: Vec<_> = review.line_comments.iter().collect();
+                lines.sort_by_key(|(line, _)| **line);
+                for (&line, comments) in lines {
+                    for comment in comments {
+                        if !seen_ids.insert(comment.id.clone()) {
+                            continue;
+                        }
+                        let side = comment.side.unwrap_or(LineSide::New);
+                        let entries = history.entry(path.clone()).or_default();
+                        let duplicate = entries.iter().any(|entry| {
+                            entry.line == line
+                                && entry.side == side
+                                && entry.comment.content == comment.content
+                        });
+                        if !duplicate {
+                            entries.push(HistoryComment {
+                                line,
+                                side,
+                                comment: comment.clone(),
+                                reviewed_at: session.updated_at,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        self.history_comments = history;
+        self.rebuild_annotations();
+    }
+
+    /// Earlier-session comments for `path`, or nothing while they are hidden
+    pub fn visible_history_comments(&self, path: &Path) -> &[HistoryComment] {
+        if !self.show_history_comments {
+            return &[];
+        }
+        self.history_comments.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn set_show_history_comments(&mut self, show: bool) {
+        self.show_history_comments = show;
+        self.rebuild_annotations();
+        self.set_message(if show {
+            "Previous review comments: shown"
+        } else {
+            "Previous review comments: hidden"
+        });
+    }
+
+    /// `:rev <revset>`: review the combined diff of a jj revset or hg
+    /// revision. With no argument, show the revset currently under review.
+    pub fn review_revset(&mut self, revset: &str) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        let revset = revset.trim();
+        if revset.is_empty() {
+            match &self.revset {
+                Some(current) => self.set_message(format!("Reviewing revset {current}")),
+                None => self.set_warning("Usage: :rev <revset>"),
+            }
+            return;
+        }
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_warning("Revsets are not available when reviewing a pull request");
+            return;
+        }
+
+        let highlighter = self.theme.syntax_highlighter();
+        let diff_files = match Self::get_revset_diff_with_ignore(
+            self.vcs.as_ref(),
+            &self.vcs_info.root_path,
+            revset,
+            highlighter,
+            self.path_filter.as_deref(),
+        ) {
+            Ok(files) => files,
+            Err(TuicrError::UnsupportedOperation(_)) => {
+                self.set_warning("Revsets are not supported by this backend");
+                return;
+            }
+            Err(TuicrError::NoChanges) => {
+                self.set_message(format!("No changes in revset {revset}"));
+                return;
+            }
+            Err(e) => {
+                self.set_error(format!("Failed to diff revset: {e}"));
+                return;
+            }
+        };
+        let commit_ids = match self.vcs.resolve_revisions(revset) {
+            Ok(ids) => ids,
+            Err(e) => {
+                self.set_error(format!("Failed to resolve revset: {e}"));
+                return;
+            }
+        };
+        let review_commits = match self.vcs.get_commits_info(&commit_ids) {
+            Ok(commits) => commits.into_iter().rev().collect(),
+            Err(e) => {
+                self.set_error(format!("Failed to load commits: {e}"));
+                return;
+            }
+        };
+
+        // Save the current session before switching so its work isn't lost.
+        // A read-only review is never written.
+        if !self.read_only {
+            let _ = crate::persistence::save_session(&self.session);
+        }
+        self.dirty = false;
+
+        let commit_count = commit_ids.len();
+        self.revset = Some(revset.to_string());
+        self.interdiff = None;
+        self.show_commit_range(commit_ids, diff_files, review_commits);
+        self.insert_commit_message_if_single();
+        self.remember_source(ReviewSource::Revset {
+            revset: revset.to_string(),
+        });
+        self.set_message(format!(
+            "Reviewing revset {revset} ({commit_count} commit{})",
+            if commit_count == 1 { "" } else { "s" }
+        ));
+    }
+
+    /// `:interdiff <old> [new]`: review only what changed between two
+    /// versions of a patch series. `new` defaults to the commits under
+    /// review; with no arguments, show the interdiff currently under review.
+    pub fn review_interdiff(&mut self, args: &str) {
+        if !self.ensure_vcs_idle() {
+            return;
+        }
+        let mut parts = args.split_whitespace();
+        let (old, new) = match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => {
+                match &self.interdiff {
+                    Some(current) => self.set_message(format!(
+                        "Reviewing interdiff {} \u{2192} {}",
+                        current.old, current.new
+                    )),
+                    None => self.set_warning("Usage: :interdiff <old> [new]"),
+                }
+                return;
+            }
+            (Some(old), Some(new), None) => (old.to_string(), new.to_string()),
+            (Some(old), None, _) => match self.current_range_spec() {
+                Some(new) => (old.to_string(), new),
+                None => {
+                    self.set_warning("Review some commits first, or use :interdiff <old> <new>");
+                    return;
+                }
+            },
+            _ => {
+                self.set_warning("Usage: :interdiff <old> [new]");
+                return;
+            }
+        };
+        if matches!(self.diff_source, DiffSource::PullRequest(_)) {
+            self.set_warning("Interdiffs are not available when reviewing a pull request");
+            return;
+        }
+
+        let highlighter = self.theme.syntax_highlighter();
+        let diff_files = match Self::get_interdiff_with_ignore(
+            self.vcs.as_ref(),
+            &self.vcs_info.root_path,
+            &old,
+            &new,
+            highlighter,
+            self.path_filter.as_deref(),
+        ) {
+            Ok(files) => files,
+            Err(TuicrError::UnsupportedOperation(_)) => {
+                self.set_warning("Interdiffs are not supported by this backend");
+                return;
+            }
+            Err(TuicrError::NoChanges) => {
+                self.set_message(format!("No changes between {old} and {new}"));
+                return;
+            }
+            Err(e) => {
+                self.set_error(format!("Failed to compute interdiff: {e}"));
+                return;
+            }
+        };
+        let commit_ids = match self.vcs.resolve_revisions(&new) {
+            Ok(ids) => ids,
+            Err(e) => {
+                self.set_error(format!("Failed to resolve {new}: {e}"));
+                return;
+            }
+        };
+        let review_commits = match self.vcs.get_commits_info(&commit_ids) {
+            Ok(commits) => commits.into_iter().rev().collect(),
+            Err(e) => {
+                self.set_error(format!("Failed to load commits: {e}"));
+                return;
+            }
+        };
+
+        // Save the current session before switching so its work isn't lost.
+        // A read-only review is never written.
+        if !self.read_only {
+            let _ = crate::persistence::save_session(&self.session);
+        }
+        self.dirty = false;
+
+        self.revset = None;
+        self.show_commit_range(commit_ids, diff_files, review_commits);
+        // Subset selection would diff plain commits, not the interdiff.
+        self.show_commit_selector = false;
+        self.set_message(format!("Reviewing interdiff {old} \u{2192} {new}"));
+        self.interdiff = Some(Interdiff { old, new });
+    }
+
+    /// The commits under review as a revision spec, for `:interdiff <old>`.
+    fn current_range_spec(&self) -> Option<String> {
+        if let Some(interdiff) = &self.interdiff {
+            return Some(interdiff.new.clone());
+        }
+        if let Some(revset) = &self.revset {
+            return Some(revset.clone());
+        }
+        let DiffSource::CommitRange(ids) = &self.diff_source else {
+            return None;
+        };
+        match ids.as_slice() {
+            [] => None,
+            [only] => Some(only.clone()),
+            [first, .., last] => Some(format!("{first}^..{last}")),
+        }
+    }
+
+    /// Switch the top-level view, refreshing the target view's contents.
+    pub fn set_view(&mut self, view: View) {
+        match view {
+            View::Diff => {}
+            View::Comments => self.refresh_comments_view(),
+            View::Overview => {
+                // The overview's per-type comment tally reads these entries.
+                self.refresh_comments_view();
+                self.refresh_author_stats();
+                self.overview_view.cursor = 0;
+            }
+            View::Sessions => self.refresh_sessions_view(),
+            View::History => self.history_view.cursor = 0,
+            View::Trash => self.trash_view.cursor = 0,
+        }
+        self.view = view;
+    }
+
+    pub fn next_view(&mut self) {
+        self.set_view(self.view.offset(1));
+    }
+
+    pub fn prev_view(&mut self) {
+        self.set_view(self.view.offset(-1));
+    }
+
+    /// `:view [name]`: switch views by name, or report the current one.
+    pub fn view_command(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            self.set_message(format!("Viewing {}", self.view.label()));
+            return;
+        }
+        match View::from_name(name) {
+            Some(view) => self.set_view(view),
+            None => self.set_warning(format!(
+                "Unknown view \"{name}\" (diff, comments, overview, sessions, history, trash)"
+            )),
+        }
+    }
+
+    fn refresh_comments_view(&mut self) {
+        let mut entries: Vec<CommentEntry> = (0..self.session.review_comments.len())
+            .map(|comment_idx| CommentEntry::Review { comment_idx })
+            .collect();
+        for (file_idx, file) in self.diff_files.iter().enumerate() {
+            let Some(review) = self.session.files.get(file.display_path()) else {
+                continue;
+            };
+            entries.extend(
+                (0..review.file_comments.len()).map(|comment_idx| CommentEntry::File {
+                    file_idx,
+                    comment_idx,
+                }),
+            );
+            let mut lines: Vec<&u32> = review.line_comments.keys().collect();
+            lines.sort();
+            for line in lines {
+                entries.extend((0..review.line_comments[line].len()).map(|comment_idx| {
+                    CommentEntry::Line {
+                        file_idx,
+                        line: *line,
+                        comment_idx,
+                    }
+                }));
+            }
+        }
+        let list = &mut self.comments_view.list;
+        list.cursor = list.cursor.min(entries.len().saturating_sub(1));
+        self.comments_view.entries = entries;
+    }
+
+    /// Recount `author_stats` when the reviewed commits changed since the
+    /// last count. Line counts are dropped, not the commit tally, when the
+    /// backend can't provide them (e.g. PR commits not fetched locally).
+    fn refresh_author_stats(&mut self) {
+        if self.vcs_reload_state.is_some() {
+            return;
+        }
+        let commits: Vec<CommitInfo> = self
+            .review_commits
+            .iter()
+            .filter(|commit| !Self::is_special_commit(commit))
+            .cloned()
+            .collect();
+        let ids: Vec<String> = commits.iter().map(|commit| commit.id.clone()).collect();
+        if ids == self.author_stats_ids {
+            return;
+        }
+        self.author_stats = if commits.len() > 1 {
+            let lines = self.vcs.get_commit_line_stats(&ids).ok();
+            AuthorStats::collect(&commits, lines.as_deref())
+        } else {
+            Vec::new()
+        };
+        self.author_stats_ids = ids;
+    }
+
+    fn refresh_sessions_view(&mut self) {
+        match crate::persistence::list_sessions_for_repo(&self.vcs_info.root_path) {
+            Ok(sessions) => {
+                self.sessions_view.sessions = sessions;
+                self.sessions_view.error = None;
+            }
+            Err(e) => {
+                self.sessions_view.sessions.clear();
+                self.sessions_view.error = Some(e.to_string());
+            }
+        }
+        let list = &mut self.sessions_view.list;
+        list.cursor = list
+            .cursor
+            .min(self.sessions_view.sessions.len().saturating_sub(1));
+    }
+
+    fn selected_saved_session(&self) -> Option<&(PathBuf, ReviewSession)> {
+        self.sessions_view
+            .sessions
+            .get(self.sessions_view.list.cursor)
+    }
+
+    /// Enter in the Sessions view: load the diff the selected session was
+    /// saved for and carry on with it where it was left.
+    pub fn open_selected_session(&mut self) {
+        let Some((_, picked)) = self.selected_saved_session().cloned() else {
+            return;
+        };
+        if picked.id == self.session.id {
+            self.set_view(View::Diff);
+            return;
+        }
+        if !self.ensure_session_writable() {
+            return;
+        }
+        if let Err(reason) = self.load_saved_review(picked) {
+            self.set_warning(reason);
+            return;
+        }
+        let saved_at = self
+            .session
+            .updated_at
+            .with_timezone(&chrono::Local)
+            .format("%b %-d %H:%M");
+        self.set_message(format!("Opened the review saved {saved_at}"));
+    }
+
+    /// `tuicr history <n>`: show an archived review over the changes it was
+    /// of, with editing and saving disabled.
+    pub fn open_archived_review(
+        &mut self,
+        archived: ReviewSession,
+    ) -> std::result::Result<(), String> {
+        self.open_read_only(archived)?;
+        let completed_at = self
+            .session
+            .updated_at
+            .with_timezone(&chrono::Local)
+            .format("%b %-d %Y %H:%M");
+        self.set_message(format!(
+            "Viewing the review completed {completed_at} (read-only)"
+        ));
+        Ok(())
+    }
+
+    /// Show `session` over the changes it was of, with editing and saving
+    /// disabled. Files keep the reviewed marks recorded in it even where the
+    /// diff has changed since, as the point is to see what the reviewer saw.
+    pub fn open_read_only(&mut self, session: ReviewSession) -> std::result::Result<(), String> {
+        let reviewed: Vec<PathBuf> = session
+            .files
+            .iter()
+            .filter(|(_, review)| review.reviewed)
+            .map(|(path, _)| path.clone())
+            .collect();
+        self.load_saved_review(session)?;
+        for path in &reviewed {
+            if let Some(review) = self.session.get_file_mut(path) {
+                review.reviewed = true;
+            }
+        }
+        self.read_only = true;
+        self.dirty = false;
+        self.rebuild_annotations();
+        Ok(())
+    }
+
+    /// Reload the diff `picked` was a review of and make it the open
+    /// session. Fails with the reason when those changes can't be diffed
+    /// any more.
+    fn load_saved_review(&mut self, picked: ReviewSession) -> std::result::Result<(), String> {
+        self.vcs_idle().map_err(|e| e.to_string())?;
+        let commits = picked.commit_range.clone().unwrap_or_default();
+        let diff_source = match picked.diff_source {
+            SessionDiffSource::WorkingTree => DiffSource::WorkingTree,
+            SessionDiffSource::Staged => DiffSource::Staged,
+            SessionDiffSource::Unstaged => DiffSource::Unstaged,
+            SessionDiffSource::StagedAndUnstaged => DiffSource::StagedAndUnstaged,
+            SessionDiffSource::CommitRange if !commits.is_empty() => {
+                DiffSource::CommitRange(commits)
+            }
+            SessionDiffSource::WorkingTreeAndCommits
+            | SessionDiffSource::StagedUnstagedAndCommits
+                if !commits.is_empty() =>
+            {
+                DiffSource::StagedUnstagedAndCommits(commits)
+            }
+            _ => {
+                return Err("This review can't be reopened: it doesn't record its commits".into());
+            }
+        };
+        let spec = DiffLoadSpec {
+            diff_source: diff_source.clone(),
+            interdiff: None,
+            revset: None,
+            range_mode: self.range_mode,
+            repo_root: self.vcs_info.root_path.clone(),
+            path_filter: self.path_filter.clone(),
+        };
+        let diff_files = match spec.load(self.vcs.as_ref(), self.theme.syntax_highlighter()) {
+            Ok(files) => files,
+            Err(TuicrError::NoChanges) => {
+                return Err("The changes this review was of are gone".into());
+            }
+            Err(e) => {
+                return Err(format!("Failed to load the review: {e}"));
+            }
+        };
+
+        if self.dirty {
+            let _ = crate::persistence::save_session(&self.session);
+            self.dirty = false;
+        }
+        self.revset = None;
+        self.interdiff = None;
+        if let DiffSource::CommitRange(ids) = diff_source {
+            let review_commits = match self.vcs.get_commits_info(&ids) {
+                Ok(commits) => commits.into_iter().rev().collect(),
+                Err(_) => Vec::new(),
+            };
+            self.show_commit_range(ids, diff_files, review_commits);
+            self.insert_commit_message_if_single();
+        } else {
+            self.diff_files = diff_files;
+            self.diff_source = diff_source;
+            self.show_commit_selector = false;
+            self.diff_state = DiffState::default();
+            self.file_list_state = FileListState::default();
+            self.clear_expanded_gaps();
+            self.sort_files_by_directory(true);
+            self.expand_all_dirs();
+        }
+        self.session = picked;
+        for file in &self.diff_files {
+            let path = file.display_path().clone();
+            self.session.add_file(path, file.status, file.content_hash);
+        }
+        self.view = View::Diff;
+        self.focused_panel = FocusedPanel::Diff;
+        self.rebuild_annotations();
+        self.restore_session_cursor();
+        self.reanchor_comments();
+        self.rebuild_annotations();
+        Ok(())
+    }
+
+    /// `dd` in the Sessions view: ask before deleting the selected session
+    /// file. The open session can't be deleted.
+    pub fn confirm_delete_selected_session(&mut self) {
+        let Some((path, session)) = self.selected_saved_session() else {
+            self.set_message("No session selected");
+            return;
+        };
+        if session.id == self.session.id {
+            self.set_warning("The open review can't be deleted");
+            return;
+        }
+        let saved_at = session
+            .updated_at
+            .with_timezone(&chrono::Local)
+            .format("%b %-d %H:%M");
+        let message = format!("Delete the review saved {saved_at}?");
+        let action = ConfirmAction::DeleteSession(path.clone());
+        self.open_confirm(ConfirmDialog::with_message(message, action));
+    }
+
+    /// Delete a saved session and drop it from the Sessions view.
+    pub fn delete_saved_session(&mut self, path: &Path) {
+        match crate::persistence::storage::delete_session(path) {
+            Ok(()) => {
+                self.sessions_view.sessions.retain(|(p, _)| p != path);
+                let list = &mut self.sessions_view.list;
+                list.cursor = list
+                    .cursor
+                    .min(self.sessions_view.sessions.len().saturating_sub(1));
+                self.set_message("Saved review deleted");
+            }
+            Err(e) => self.set_error(format!("Failed to delete {}: {e}", path.display())),
+        }
+    }
+
+    /// `r` in the Sessions view: start `:rename` with the selected
+    /// session's name filled in.
+    pub fn begin_session_rename(&mut self) {
+        let Some((_, session)) = self.selected_saved_session() else {
+            return;
+        };
+        self.command_buffer = format!("rename {}", session.name.as_deref().unwrap_or_default());
+        self.input_mode = InputMode::Command;
+    }
+
+    /// `:rename <name>`: name the session selected in the Sessions view, or
+    /// the open one from anywhere else. An empty name clears it.
+    pub fn rename_session(&mut self, name: &str) {
+        let name = Some(name.trim().to_string()).filter(|n| !n.is_empty());
+        let selected = match self.view {
+            View::Sessions => self.selected_saved_session().cloned(),
+            _ => None,
+        };
+        let id = match selected {
+            Some((path, mut session)) if session.id != self.session.id => {
+                if let Ok(LockState::Held(owner)) = crate::persistence::lock::inspect(&session.id) {
+                    self.set_warning(format!(
+                        "That review is open in another tuicr ({})",
+                        owner.describe()
+                    ));
+                    return;
+                }
+                session.name = name.clone();
+                if let Err(e) = crate::persistence::storage::write_session_file(&path, &session) {
+                    self.set_error(format!("Failed to rename the review: {e}"));
+                    return;
+                }
+                session.id
+            }
+            _ => {
+                self.session.name = name.clone();
+                self.mark_dirty();
+                self.session.id.clone()
+            }
+        };
+        if let Some((_, listed)) = self
+            .sessions_view
+            .sessions
+            .iter_mut()
+            .find(|(_, s)| s.id == id)
+        {
+            listed.name = name.clone();
+        }
+        match name {
+            Some(name) => self.set_message(format!("Review renamed to \"{name}\"")),
+            None => self.set_message("Review name cleared"),
+        }
+    }
+
+    /// Look up the comment a Comments view entry refers to.
+    pub fn comment_for_entry(&self, entry: &CommentEntry) -> Option<&Comment> {
+        match entry {
+            CommentEntry::Review { comment_idx } => self.session.review_comments.get(*comment_idx),
+            CommentEntry::File {
+                file_idx,
+                comment_idx,
+            } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                self.session
+                    .files
+                    .get(path)?
+                    .file_comments
+                    .get(*comment_idx)
+            }
+            CommentEntry::Line {
+                file_idx,
+                line,
+                comment_idx,
+            } => {
+                let path = self.diff_files.get(*file_idx)?.display_path();
+                self.session
+                    .files
+                    .get(path)?
+                    .line_comments
+                    .get(line)?
+                    .get(*comment_idx)
+            }
+        }
+    }
+
+    /// Where a Comments view entry lives, as shown in its row.
+    pub fn comment_entry_location(&self, entry: &CommentEntry) -> String {
+        match entry {
+            CommentEntry::Review { .. } => "review".to_string(),
+            CommentEntry::File { file_idx, .. } => self.diff_files[*file_idx]
+                .display_path()
+                .display()
+                .to_string(),
+            CommentEntry::Line { file_idx, line, .. } => format!(
+                "{}:{line}",
+                self.diff_files[*file_idx].display_path().display()
+            ),
+        }
+    }
+
+    /// `dd` in the Comments view: confirm, then delete the selected comment.
+    pub fn confirm_delete_view_comment(&mut self) {
+        let Some(entry) = self
+            .comments_view
+            .entries
+            .get(self.comments_view.list.cursor)
+            .cloned()
+        else {
+            self.set_message("No comment selected");
+            return;
+        };
+        let message = format!("Delete comment on {}?", self.comment_entry_location(&entry));
+        self.open_confirm(ConfirmDialog::with_message(
+            message,
+            ConfirmAction::DeleteComment(entry),
+        ));
+    }
+
+    /// Delete the comment behind a Comments view entry.
+    pub fn delete_comment_entry(&mut self, entry: &CommentEntry) -> bool {
+        let entry_path = |file_idx: &usize| {
+            self.diff_files
+                .get(*file_idx)
+                .map(|f| f.display_path().clone())
+        };
+        let (path, line) = match entry {
+            CommentEntry::Review { .. } => (None, None),
+            CommentEntry::File { file_idx, .. } => (entry_path(file_idx), None),
+            CommentEntry::Line { file_idx, line, .. } => (entry_path(file_idx), Some(*line)),
+        };
+        let removed = match entry {
+            CommentEntry::Review { comment_idx } => (*comment_idx
+                < self.session.review_comments.len())
+            .then(|| self.session.review_comments.remove(*comment_idx)),
+            CommentEntry::File {
+                file_idx,
+                comment_idx,
+            } => {
+                let Some(path) = self
+                    .diff_files
+                    .get(*file_idx)
+                    .map(|f| f.display_path().clone())
+                else {
+                    return false;
+                };
+                self.session.get_file_mut(&path).and_then(|review| {
+                    (*comment_idx < review.file_comments.len())
+                        .then(|| review.file_comments.remove(*comment_idx))
+                })
+            }
+            CommentEntry::Line {
+                file_idx,
+                line,
+                comment_idx,
+            } => {
+                let Some(path) = self
+                    .diff_files
+                    .get(*file_idx)
+                    .map(|f| f.display_path().clone())
+                else {
+                    return false;
+                };
+                self.session.get_file_mut(&path).and_then(|review| {
+                    let comments = review.line_comments.get_mut(line)?;
+                    let removed =
+                        (*comment_idx < comments.len()).then(|| comments.remove(*comment_idx));
+                    if comments.is_empty() {
+                        review.line_comments.remove(line);
+                    }
+                    removed
+                })
+            }
+        };
+        let Some(comment) = removed else {
+            self.set_warning("Comment no longer exists");
+            return false;
+        };
+        self.session.record_event(ReviewEventKind::CommentDeleted {
+            path: path.clone(),
+            line,
+            comment_type: comment.comment_type.clone(),
+        });
+        self.journal(JournalOp::CommentDeleted {
+            id: comment.id.clone(),
+        });
+        self.session.trash_comment(comment, path, line);
+        self.mark_dirty();
+        self.set_message("Comment deleted");
+        self.rebuild_annotations();
+        self.refresh_comments_view();
+        true
+    }
+
+    /// Follow line comments to where their line went after the diff
+    /// changed, by the text recorded with them ([`anchor::locate`]). Ones
+    /// that can't be placed stay put and are flagged in
+    /// `ReviewSession::orphaned` for `:verify`, and so are ones whose line
+    /// stayed but drifted (`ReviewSession::drifted`). Only run on a full
+    /// diff: a commit subrange would orphan every comment outside it.
+    fn reanchor_comments(&mut self) {
+        let mut moved = 0;
+        let mut orphaned = Vec::new();
+        let mut drifted = Vec::new();
+        for file in self.diff_files.iter().filter(|f| !f.is_commit_message) {
+            let Some(review) = self.session.files.get_mut(file.display_path()) else {
+                continue;
+            };
+            let mut moves = Vec::new();
+            for (line, comments) in &review.line_comments {
+                for comment in comments.iter().filter(|c| !c.is_locked()) {
+                    let side = comment.side.unwrap_or_default();
+                    let Some(context) = &comment.line_context else {
+                        continue;
+                    };
+                    match anchor::locate(file, *line, side, context) {
+                        // The line stayed but its surroundings changed: the
+                        // reviewer confirms the comment still applies in
+                        // `:verify` before its context is taken from it
+                        Anchor::Unchanged if !anchor::is_intact(file, *line, side, context) => {
+                            drifted.push(comment.id.clone())
+                        }
+                        Anchor::Unchanged => {}
+                        Anchor::Moved(to) => moves.push((*line, comment.id.clone(), to)),
+                        Anchor::Orphaned => orphaned.push(comment.id.clone()),
+                    }
+                }
+            }
+            for (from, id, to) in moves {
+                let Some(comments) = review.line_comments.get_mut(&from) else {
+                    continue;
+                };
+                let Some(idx) = comments.iter().position(|c| c.id == id) else {
+                    continue;
+                };
+                let mut comment = comments.remove(idx);
+                if comments.is_empty() {
+                    review.line_comments.remove(&from);
+                }
+                if let Some(range) = comment.line_range.as_mut() {
+                    range.start = (range.start + to).saturating_sub(from).max(1);
+                    range.end = to;
+                }
+                let side = comment.side.unwrap_or_default();
+                comment.line_context = anchor::capture(file, to, side).or(comment.line_context);
+                review.add_line_comment(to, comment);
+                moved += 1;
+            }
+        }
+        let newly_orphaned = orphaned
+            .iter()
+            .filter(|id| !self.session.orphaned.contains(id))
+            .count();
+        let newly_drifted = drifted
+            .iter()
+            .filter(|id| !self.session.drifted.contains(id))
+            .count();
+        if moved == 0 && orphaned == self.session.orphaned && drifted == self.session.drifted {
+            return;
+        }
+        self.session.orphaned = orphaned;
+        self.session.drifted = drifted;
+        self.mark_dirty();
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let mut notes = Vec::new();
+        if moved > 0 {
+            notes.push(format!(
+                "Moved {moved} comment{} along with their lines",
+                plural(moved)
+            ));
+        }
+        match (moved, newly_orphaned) {
+            (_, 0) => {}
+            (0, lost) => notes.push(format!("{lost} comment{} lost their line", plural(lost))),
+            (_, lost) => notes.push(format!("{lost} lost theirs")),
+        }
+        if newly_drifted > 0 {
+            notes.push(format!(
+                "{newly_drifted} comment{} on lines that changed around them",
+                plural(newly_drifted)
+            ));
+        }
+        if newly_orphaned > 0 || newly_drifted > 0 {
+            self.set_warning(format!("{}; :verify lists them", notes.join("; ")));
+        } else if !notes.is_empty() {
+            self.set_message(notes.join("; "));
+        }
+    }
+
+    /// Comments the current diff no longer has an anchor for: line comments
+    /// on lines outside every hunk, and any comment on a file that left the
+    /// diff. Ordered by file and line, with drifted comments among them.
+    fn find_orphaned_comments(&self) -> Vec<OrphanedComment> {
+        let mut orphans = Vec::new();
+        let mut files: Vec<_> = self.session.files.iter().collect();
+        files.sort_by_key(|(path, _)| *path);
+        for (path, review) in files {
+            let diff_file = self.diff_files.iter().find(|f| f.display_path() == path);
+            let orphan = |comment: &Comment, line: Option<u32>, side: LineSide| OrphanedComment {
+                comment_id: comment.id.clone(),
+                path: path.clone(),
+                line,
+                side,
+                file_in_diff: diff_file.is_some(),
+                drifted: false,
+                content: comment.content.clone(),
+            };
+            if diff_file.is_none() {
+                orphans.extend(
+                    review
+                        .file_comments
+                        .iter()
+                        .map(|c| orphan(c, None, LineSide::New)),
+                );
+            }
+            let mut lines: Vec<_> = review.line_comments.iter().collect();
+            lines.sort_by_key(|(line, _)| **line);
+            for (line, comments) in lines {
+                for comment in comments {
+                    let side = comment.side.unwrap_or_default();
+                    let anchored = !self.session.orphaned.contains(&comment.id)
+                        && diff_file.is_some_and(|file| {
+                            file.hunks
+                                .iter()
+                                .flat_map(|hunk| &hunk.lines)
+                                .any(|l| match side {
+                                    LineSide::New => l.new_lineno == Some(*line),
+                                    LineSide::Old => l.old_lineno == Some(*line),
+                                })
+                        });
+                    if !anchored {
+                        orphans.push(orphan(comment, Some(*line), side));
+                    } else if self.session.drifted.contains(&comment.id) {
+                        orphans.push(OrphanedComment {
+                            drifted: true,
+                            ..orphan(comment, Some(*line), side)
+                        });
+                    }
+                }
+            }
+        }
+        orphans
+    }
+
+    /// `:verify`: check every comment against the current diff and list the
+    /// ones that lost their line, or whose line changed, in the verify panel.
+    pub fn verify_comments(&mut self) {
+        self.orphaned_comments = self.find_orphaned_comments();
+        self.verify_cursor = 0;
+        if self.orphaned_comments.is_empty() {
+            self.set_message("Every comment is anchored in the current diff");
+            return;
+        }
+        self.open_overlay(InputMode::VerifyPanel);
+    }
+
+    /// Rescan after a row was acted on, closing the panel once every
+    /// comment is anchored.
+    fn refresh_orphaned_comments(&mut self) {
+        self.orphaned_comments = self.find_orphaned_comments();
+        self.verify_cursor = self
+            .verify_cursor
+            .min(self.orphaned_comments.len().saturating_sub(1));
+        if self.orphaned_comments.is_empty() && self.input_mode == InputMode::VerifyPanel {
+            self.close_overlay();
+        }
+    }
+
+    pub fn verify_cursor_down(&mut self) {
+        if self.verify_cursor + 1 < self.orphaned_comments.len() {
+            self.verify_cursor += 1;
+        }
+    }
+
+    pub fn verify_cursor_up(&mut self) {
+        self.verify_cursor = self.verify_cursor.saturating_sub(1);
+    }
+
+    fn orphaned_comment(&self, orphan: &OrphanedComment) -> Option<&Comment> {
+        let review = self.session.files.get(&orphan.path)?;
+        match orphan.line {
+            Some(line) => review.line_comments.get(&line)?,
+            None => &review.file_comments,
+        }
+        .iter()
+        .find(|c| c.id == orphan.comment_id)
+    }
+
+    /// The selected row, unless the review is read-only or its comment was
+    /// already pushed to GitHub.
+    fn editable_orphan(&mut self) -> Option<OrphanedComment> {
+        if !self.ensure_session_writable() {
+            return None;
+        }
+        let orphan = self.orphaned_comments.get(self.verify_cursor)?.clone();
+        if self
+            .orphaned_comment(&orphan)
+            .is_some_and(|c| c.is_locked())
+        {
+            self.set_message("Comment already pushed to GitHub — read only in tuicr");
+            return None;
+        }
+        Some(orphan)
+    }
+
+    /// Remove an orphaned comment from the session and return it.
+    fn take_orphaned_comment(&mut self, orphan: &OrphanedComment) -> Option<Comment> {
+        self.session.orphaned.retain(|id| *id != orphan.comment_id);
+        self.session.drifted.retain(|id| *id != orphan.comment_id);
+        let review = self.session.get_file_mut(&orphan.path)?;
+        let Some(line) = orphan.line else {
+            let idx = review
+                .file_comments
+                .iter()
+                .position(|c| c.id == orphan.comment_id)?;
+            return Some(review.file_comments.remove(idx));
+        };
+        let comments = review.line_comments.get_mut(&line)?;
+        let idx = comments.iter().position(|c| c.id == orphan.comment_id)?;
+        let comment = comments.remove(idx);
+        if comments.is_empty() {
+            review.line_comments.remove(&line);
+        }
+        Some(comment)
+    }
+
+    fn finish_verify_action(&mut self, message: String) {
+        self.mark_dirty();
+        self.rebuild_annotations();
+        self.set_message(message);
+        self.refresh_orphaned_comments();
+    }
+
+    /// `r` in the verify panel: move the selected comment to the nearest
+    /// line of its file on the same side of the diff, or keep a drifted one
+    /// on its line.
+    pub fn verify_reanchor(&mut self) {
+        let Some(orphan) = self.editable_orphan() else {
+            return;
+        };
+        let Some(line) = orphan.line else {
+            self.set_message("File comments have no line to re-anchor");
+            return;
+        };
+        if orphan.drifted {
+            self.keep_drifted_comment(&orphan, line);
+            return;
+        }
+        let context = self
+            .orphaned_comment(&orphan)
+            .and_then(|c| c.line_context.as_ref());
+        let target = self
+            .diff_files
+            .iter()
+            .find(|f| *f.display_path() == orphan.path)
+            .and_then(|file| reanchor_line(file, line, orphan.side, context));
+        let Some(target) = target else {
+            self.set_warning(format!(
+                "No line of {} to re-anchor to; detach or delete the comment",
+                orphan.path.display()
+            ));
+            return;
+        };
+        let Some(mut comment) = self.take_orphaned_comment(&orphan) else {
+            return;
+        };
+        if let Some(range) = comment.line_range.as_mut() {
+            range.start = target.saturating_sub(range.end - range.start).max(1);
+            range.end = target;
+        }
+        comment.line_context = self
+            .diff_files
+            .iter()
+            .find(|f| *f.display_path() == orphan.path)
+            .and_then(|file| anchor::capture(file, target, orphan.side));
+        let comment_type = comment.comment_type.clone();
+        let id = comment.id.clone();
+        if let Some(review) = self.session.get_file_mut(&orphan.path) {
+            review.add_line_comment(target, comment);
+        }
+        self.journal_comment(&id);
+        self.session.record_event(ReviewEventKind::CommentEdited {
+            path: Some(orphan.path.clone()),
+            line: Some(target),
+            comment_type,
+        });
+        self.finish_verify_action(format!(
+            "Re-anchored comment to {}:{target}",
+            orphan.path.display()
+        ));
+    }
+
+    /// Keep a drifted comment on `line`, taking its context from what the
+    /// line and its neighbours read now.
+    fn keep_drifted_comment(&mut self, orphan: &OrphanedComment, line: u32) {
+        let context = self
+            .diff_files
+            .iter()
+            .find(|f| *f.display_path() == orphan.path)
+            .and_then(|file| anchor::capture(file, line, orphan.side));
+        self.session.drifted.retain(|id| *id != orphan.comment_id);
+        let Some(comment) = self
+            .session
+            .get_file_mut(&orphan.path)
+            .and_then(|review| review.line_comments.get_mut(&line))
+            .and_then(|comments| comments.iter_mut().find(|c| c.id == orphan.comment_id))
+        else {
+            return;
+        };
+        if context.is_some() {
+            comment.line_context = context;
+        }
+        let id = comment.id.clone();
+        self.journal_comment(&id);
+        self.finish_verify_action(format!("Kept comment on {}", orphan.location()));
+    }
+
+    /// `f` in the verify panel: keep the selected comment without its line,
+    /// as a file comment, or as a review comment naming its old location
+    /// when the file left the diff.
+    pub fn verify_detach(&mut self) {
+        let Some(orphan) = self.editable_orphan() else {
+            return;
+        };
+        let Some(mut comment) = self.take_orphaned_comment(&orphan) else {
+            return;
+        };
+        comment.side = None;
+        comment.line_range = None;
+        let comment_type = comment.comment_type.clone();
+        let id = comment.id.clone();
+        let (path, message) = if orphan.file_in_diff {
+            if let Some(review) = self.session.get_file_mut(&orphan.path) {
+                review.add_file_comment(comment);
+            }
+            let message = format!("Detached comment to {}", orphan.path.display());
+            (Some(orphan.path.clone()), message)
+        } else {
+            comment.content = format!("{}: {}", orphan.location(), comment.content);
+            self.session.review_comments.push(comment);
+            (None, "Moved comment to the review comments".to_string())
+        };
+        self.journal_comment(&id);
+        self.session.record_event(ReviewEventKind::CommentEdited {
+            path,
+            line: None,
+            comment_type,
+        });
+        self.finish_verify_action(message);
+    }
+
+    /// `d` in the verify panel: confirm, then delete the selected comment.
+    pub fn confirm_delete_orphaned_comment(&mut self) {
+        let Some(orphan) = self.editable_orphan() else {
+            return;
+        };
+        self.open_confirm(ConfirmDialog::with_message(
+            format!("Delete comment on {}?", orphan.location()),
+            ConfirmAction::DeleteOrphanedComment(orphan.comment_id),
+        ));
+    }
+
+    pub fn delete_orphaned_comment(&mut self, comment_id: &str) {
+        let Some(orphan) = self
+            .orphaned_comments
+            .iter()
+            .find(|o| o.comment_id == comment_id)
+            .cloned()
+        else {
+            return;
+        };
+        let Some(comment) = self.take_orphaned_comment(&orphan) else {
+            return;
+        };
+        self.session.record_event(ReviewEventKind::CommentDeleted {
+            path: Some(orphan.path.clone()),
+            line: orphan.line,
+            comment_type: comment.comment_type.clone(),
+        });
+        self.journal(JournalOp::CommentDeleted {
+            id: comment.id.clone(),
+        });
+        self.session
+            .trash_comment(comment, Some(orphan.path), orphan.line);
+        self.finish_verify_action("Comment deleted".to_string());
+    }
+
+    fn active_view_list(&mut self) -> Option<(&mut ViewListState, usize)> {
+        match self.view {
+            View::Diff => None,
+            View::Comments => Some((
+                &mut self.comments_view.list,
+                self.comments_view.entries.len(),
+            )),
+            // The overview is a scrolled page rather than a list; its length
+            // is clamped when rendering.
+            View::Overview => Some((&mut self.overview_view, usize::MAX)),
+            View::Sessions => Some((
+                &mut self.sessions_view.list,
+                self.sessions_view.sessions.len(),
+            )),
+            View::History => Some((&mut self.history_view, self.session.events.len())),
+            View::Trash => Some((&mut self.trash_view, self.session.trash.len())),
+        }
+    }
+
+    pub fn view_cursor_down(&mut self, n: usize) {
+        if let Some((list, len)) = self.active_view_list() {
+            list.cursor = list.cursor.saturating_add(n).min(len.saturating_sub(1));
+        }
+    }
+
+    pub fn view_cursor_up(&mut self, n: usize) {
+        if let Some((list, _)) = self.active_view_list() {
+            list.cursor = list.cursor.saturating_sub(n);
+        }
+    }
+
+    pub fn view_cursor_top(&mut self) {
+        if let Some((list, _)) = self.active_view_list() {
+            list.cursor = 0;
+        }
+    }
+
+    pub fn view_cursor_bottom(&mut self) {
+        if let Some((list, len)) = self.active_view_list() {
+            list.cursor = len.saturating_sub(1);
+        }
+    }
+
+    pub fn view_viewport_height(&self) -> usize {
+        match self.view {
+            View::Diff => self.diff_state.viewport_height,
+            View::Comments => self.comments_view.list.viewport_height,
+            View::Overview => self.overview_view.viewport_height,
+            View::Sessions => self.sessions_view.list.viewport_height,
+            View::History => self.history_view.viewport_height,
+            View::Trash => self.trash_view.viewport_height,
+        }
+    }
+
+    /// Act on the selected row: jump to a comment in the diff, open a saved
+    /// session, or jump to the file an event is about.
+    pub fn open_view_selection(&mut self) {
+        match self.view {
+            View::Diff | View::Overview => {}
+            View::Comments => {
+                let Some(entry) = self
+                    .comments_view
+                    .entries
+                    .get(self.comments_view.list.cursor)
+                    .cloned()
+                else {
+                    return;
+                };
+                let target = self
+                    .line_annotations
+                    .iter()
+                    .position(|a| match (&entry, a) {
+                        (
+                            CommentEntry::Review { comment_idx },
+                            AnnotatedLine::ReviewComment { comment_idx: idx },
+                        ) => comment_idx == idx,
+                        (
+                            CommentEntry::File {
+                                file_idx,
+                                comment_idx,
+                            },
+                            AnnotatedLine::FileComment {
+                                file_idx: f,
+                                comment_idx: idx,
+                            },
+                        ) => file_idx == f && comment_idx == idx,
+                        (
+                            CommentEntry::Line {
+                                file_idx,
+                                line,
+                                comment_idx,
+                            },
+                            AnnotatedLine::LineComment {
+                                file_idx: f,
+                                line: l,
+                                comment_idx: idx,
+                                ..
+                            },
+                        ) => file_idx == f && line == l && comment_idx == idx,
+                        _ => false,
+                    });
+                let Some(target) = target else {
+                    self.set_warning("Comment is not visible in the current diff");
+                    return;
+                };
+                self.view = View::Diff;
+                self.focused_panel = FocusedPanel::Diff;
+                self.diff_state.cursor_line = target;
+                self.ensure_cursor_visible();
+                self.center_cursor();
+                self.update_current_file_from_cursor();
+            }
+            View::Sessions => self.open_selected_session(),
+            View::History => {
+                let Some(path) = self
+                    .session
+                    .events
+                    .iter()
+                    .rev()
+                    .nth(self.history_view.cursor)
+                    .and_then(|event| event.kind.path())
+                else {
+                    return;
+                };
+                let Some(idx) = self
+                    .diff_files
+                    .iter()
+                    .position(|f| f.display_path() == path)
+                else {
+                    self.set_warning("File is not in the current diff");
+                    return;
+                };
+                self.view = View::Diff;
+                self.focused_panel = FocusedPanel::Diff;
+                self.jump_to_file(idx);
+            }
+            View::Trash => self.restore_selected_trash(),
+        }
+    }
+
+    /// Put the comment under the Trash view cursor back where it was deleted
+    /// from. The trash is listed newest first.
+    pub fn restore_selected_trash(&mut self) {
+        if !self.ensure_session_writable() {
+            return;
+        }
+        let len = self.session.trash.len();
+        let Some(index) = len.checked_sub(self.trash_view.cursor + 1) else {
+            return;
+        };
+        let Some(entry) = self.session.restore_trashed(index) else {
+            return;
+        };
+        self.journal_comment(&entry.comment.id);
+        let in_place = entry
+            .path
+            .as_ref()
+            .is_none_or(|path| self.session.files.contains_key(path));
+        let (path, line) = if in_place {
+            (entry.path.clone(), entry.line)
+        } else {
+            (None, None)
+        };
+        self.session.record_event(ReviewEventKind::CommentAdded {
+            path,
+            line,
+            comment_type: entry.comment.comment_type.clone(),
+        });
+        self.trash_view.cursor = self
+            .trash_view
+            .cursor
+            .min(self.session.trash.len().saturating_sub(1));
+        self.mark_dirty();
+        self.rebuild_annotations();
+        if in_place {
+            self.set_message(format!("Restored comment on {}", entry.location()));
+        } else {
+            self.set_warning(format!(
+                "{} is no longer in the diff; restored as a review comment",
+                entry.location()
+            ));
+        }
+    }
+
+    /// Reload the diff for the currently selected inline commit subrange.
+    pub fn reload_inline_selection(&mut self) -> Result<()> {
+        self.vcs_idle()?;
+        let Some((start, end)) = self.commit_selection_range else {
+            self.set_message("Select at least one commit");
+            return Ok(());
+        };
+
+        // Check if all commits selected -> use cached range_diff_files
+        if start == 0
+            && end == self.review_commits.len() - 1
+            && let Some(ref files) = self.range_diff_files
+        {
+            self.diff_files = files.clone();
+            let wrap = self.diff_state.wrap_lines;
+            self.diff_state = DiffState::default();
+            self.diff_state.wrap_lines = wrap;
+            self.file_list_state = FileListState::default();
+            self.clear_expanded_gaps();
+            self.insert_commit_message_if_single();
+            self.sort_files_by_directory(true);
+            self.expand_all_dirs();
+            self.rebuild_annotations();
+            return Ok(());
+        }
+
+        // Check cache for this subrange
+        if let Some(files) = self.commit_diff_cache.get(&(start, end)) {
+            self.diff_files = files.clone();
+            let wrap = self.diff_state.wrap_lines;
+            self.diff_state = DiffState::default();
+            self.diff_state.wrap_lines = wrap;
+            self.file_list_state = FileListState::default();
+            self.clear_expanded_gaps();
+            self.insert_commit_message_if_single();
+            self.sort_files_by_directory(true);
+            self.expand_all_dirs();
+            self.rebuild_annotations();
+            return Ok(());
+        }
+
+        // Load diff for selected subrange
+        let has_staged = (start..=end).any(|i| {
+            self.review_commits
+                .get(i)
+                .is_some_and(Self::is_staged_commit)
+        });
+        let has_unstaged = (start..=end).any(|i| {
+            self.review_commits
+                .get(i)
+                .is_some_and(Self::is_unstaged_commit)
+        });
+        let selected_ids: Vec<String> = (start..=end)
+            .rev() // oldest to newest
+            .filter_map(|i| self.review_commits.get(i))
+            .filter(|c| !Self::is_special_commit(c))
+            .map(|c| c.id.clone())
+            .collect();
+
+        let highlighter = self.theme.syntax_highlighter();
+        let diff_files = if (has_staged || has_unstaged) && !selected_ids.is_empty() {
+            match Self::get_working_tree_with_commits_diff_with_ignore(
+                self.vcs.as_ref(),
+                &self.vcs_info.root_path,
+                &selected_ids,
+                highlighter,
+                self.path_filter.as_deref(),
+            ) {
+                Ok(files) => files,
+                Err(TuicrError::NoChanges) => Vec::new(),
+                Err(e) => return Err(e),
+            }
+        } else if has_staged && has_unstaged {
+            match Self::get_working_tree_diff_with_ignore(
+                self.vcs.as_ref(),
+                &self.vcs_info.root_path,
+                highlighter,
+                self.path_filter.as_deref(),
+            ) {
+                Ok(files) => files,
+                Err(TuicrError::NoChanges) => Vec::new(),
+                Err(e) => return Err(e),
+            }
+        } else if has_staged {
+            match Self::get_staged_diff_with_ignore(
+                self.vcs.as_ref(),
+                &self.vcs_info.root_path,
+                highlighter,
+                self.path_filter.as_deref(),
+            ) {
+                Ok(files) => files,
+                Err(TuicrError::NoChanges) => Vec::new(),
+                Err(e) => return Err(e),
+            }
+        } else if has_unstaged {
+            match Self::get_unstaged_diff_with_ignore(
+                self.vcs.as_ref(),
+                &self.vcs_info.root_path,
+                highlighter,
+                self.path_filter.as_deref(),
+            ) {
+                Ok(files) => files,
+                Err(TuicrError::NoChanges) => Vec::new(),
+                Err(e) => return Err(e),
+            }
+        } else {
+            match Self::get_commit_range_diff_with_ignore(
+                self.vcs.as_ref(),
+                &self.vcs_info.root_path,
+                &selected_ids,
+                self.range_mode,
+                highlighter,
+                self.path_filter.as_deref(),
+            ) {
+                Ok(files) => files,
+                Err(TuicrError::NoChanges) => Vec::new(),
+                Err(e) => return Err(e),
+            }
+        };
+        self.commit_diff_cache
+            .insert((start, end), diff_files.clone());
+        self.diff_files = diff_files;
+
+        // Reset navigation, rebuild file tree + annotations
+        let wrap = self.diff_state.wrap_lines;
+        self.diff_state = DiffState::default();
+        self.diff_state.wrap_lines = wrap;
+        self.file_list_state = FileListState::default();
+        self.clear_expanded_gaps();
+        self.insert_commit_message_if_single();
+        self.sort_files_by_directory(true);
+        self.expand_all_dirs();
+        self.rebuild_annotations();
+
+        Ok(())
+    }
+
+    fn load_staged_unstaged_and_commits_selection(
+        &mut self,
+        selected_ids: Vec<String>,
+        selected_commits: Vec<CommitInfo>,
+    ) -> Result<()> {
+        let highlighter = self.theme.syntax_highlighter();
+        let diff_files = match Self::get_working_tree_with_commits_diff_with_ignore(
+            self.vcs.as_ref(),
+            &self.vcs_info.root_path,
+            &selected_ids,
+            highlighter,
+            self.path_filter.as_deref(),
+        ) {
+            Ok(diff_files) => diff_files,
+            Err(TuicrError::NoChanges) => {
+                self.set_message("No changes in selected commits + staged/unstaged");
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.session =
+            Self::load_or_create_staged_unstaged_and_commits_session(&self.vcs_info, &selected_ids);
+
+        for file in &diff_files {
+            let path = file.display_path().clone();
+            self.session.add_file(path, file.status, file.content_hash);
+        }
+
+        self.diff_files = diff_files;
+        self.diff_source = DiffSource::StagedUnstagedAndCommits(selected_ids);
+        self.input_mode = InputMode::Normal;
+        self.diff_state = DiffState::default();
+        self.file_list_state = FileListState::default();
+
+        // Set up inline commit selector (newest-first display order)
+        self.review_commits = selected_commits.into_iter().rev().collect();
+        self.range_diff_files = Some(self.diff_files.clone());
+        self.commit_list = self.review_commits.clone();
+        self.commit_list_cursor = 0;
+        self.commit_selection_range = if self.review_commits.is_empty() {
+            None
+        } else {
+            Some((0, self.review_commits.len() - 1))
+        };
+        self.commit_list_scroll_offset = 0;
+        self.visible_commit_count = self.review_commits.len();
+        self.has_more_commit = false;
+        self.show_commit_selector = self.review_commits.len() > 1;
+        self.commit_diff_cache.clear();
+        self.saved_inline_selection = None;
+
+        self.insert_commit_message_if_single();
+        self.sort_files_by_directory(true);
+        self.expand_all_dirs();
+        self.reanchor_comments();
+        self.rebuild_annotations();
+        Ok(())
+    }
+
+    fn sort_files_by_directory(&mut self, reset_position: bool) {
+        use std::collections::BTreeMap;
+        use std::path::Path;
+
+        let current_path = if !reset_position {
+            self.current_file_path().cloned()
+        } else {
+            None
+        };
+
+        let mut dir_map: BTreeMap<String, Vec<DiffFile>> = BTreeMap::new();
+        let mut commit_msg_files: Vec<DiffFile> = Vec::new();
+
+        for file in self.diff_files.drain(..) {
+            if file.is_commit_message {
+                commit_msg_files.push(file);
+                continue;
+            }
+            let path = file.display_path();
+            let dir = if let Some(parent) = path.parent() {
+                if parent == Path::new("") {
+                    ".".to_string()
+                } else {
+                    parent.to_string_lossy().to_string()
+                }
+            } else {
+                ".".to_string()
+            };
+
+            dir_map.entry(dir).or_default().push(file);
+        }
+
+        self.diff_files.extend(commit_msg_files);
+        for (_dir, files) in dir_map {
+            self.diff_files.extend(files);
+        }
+
+        if let Some(path) = current_path
+            && let Some(idx) = self
+                .diff_files
+                .iter()
+                .position(|f| f.display_path() == &path)
+        {
+            self.jump_to_file(idx);
+            return;
+        }
+
+        // Start at the overview position (review comments header)
+        // so the diff title shows total stats on launch.
+        self.diff_state.cursor_line = 0;
+        self.diff_state.scroll_offset = 0;
+        self.diff_state.current_file_idx = 0;
+    }
+
+    pub fn expand_all_dirs(&mut self) {
+        use std::path::Path;
+
+        self.expanded_dirs.clear();
+        for file in &self.diff_files {
+            let path = file.display_path();
+            let mut current = path.parent();
+            while let Some(parent) = current {
+                if parent != Path::new("") {
+                    self.expanded_dirs
+                        .insert(parent.to_string_lossy().to_string());
+                }
+                current = parent.parent();
+            }
+        }
+        self.ensure_valid_tree_selection();
+    }
+
+    pub fn collapse_all_dirs(&mut self) {
+        self.expanded_dirs.clear();
+        self.ensure_valid_tree_selection();
+    }
+
+    pub fn toggle_directory(&mut self, dir_path: &str) {
+        if self.expanded_dirs.contains(dir_path) {
+            self.expanded_dirs.remove(dir_path);
+            self.ensure_valid_tree_selection();
+        } else {
+            self.expanded_dirs.insert(dir_path.to_string());
+        }
+    }
+
+    /// Get the line boundaries (start_line, end_line) of a gap.
+    fn gap_boundaries(&self, gap_id: &GapId) -> Option<(u32, u32)> {
+        let file = self.diff_files.get(gap_id.file_idx)?;
+        let hunk = file.hunks.get(gap_id.hunk_idx)?;
+        let prev_hunk = if gap_id.hunk_idx > 0 {
+            file.hunks.get(gap_id.hunk_idx - 1)
+        } else {
+            None
+        };
+        let (start, end) = match prev_hunk {
+            None => (1, hunk.new_start.saturating_sub(1)),
+            Some(prev) => (
+                prev.new_start + prev.new_count,
+                hunk.new_start.saturating_sub(1),
+            ),
+        };
+        if start > end {
+            None
+        } else {
+            Some((start, end))
+        }
+    }
+
+    /// Look up an expanded context line by sequential index across top + bottom.
+    fn get_expanded_line(&self, gap_id: &GapId, idx: usize) -> Option<&DiffLine> {
+        let top = self.expanded_top.get(gap_id);
+        let top_len = top.map_or(0, |v| v.len());
+        if idx < top_len {
+            top?.get(idx)
+        } else {
+            self.expanded_bottom.get(gap_id)?.get(idx - top_len)
+        }
+    }
+
+    /// Expand a gap in the given direction.
+    /// If `limit` is Some(n), expand up to n lines. If None, expand all remaining.
+    pub fn expand_gap(
+        &mut self,
+        gap_id: GapId,
+        direction: ExpandDirection,
+        limit: Option<usize>,
+    ) -> Result<()> {
+        self.vcs_idle()?;
+        let (gap_start, gap_end) = self
+            .gap_boundaries(&gap_id)
+            .ok_or_else(|| TuicrError::CorruptedSession(format!("Invalid gap: {:?}", gap_id)))?;
+
+        let file = &self.diff_files[gap_id.file_idx];
+        let old_path = file.old_path.clone();
+        let new_path = file.new_path.clone();
+        let file_status = file.status;
+
+        let top_len = self.expanded_top.get(&gap_id).map_or(0, |v| v.len()) as u32;
+        let bot_len = self.expanded_bottom.get(&gap_id).map_or(0, |v| v.len()) as u32;
+
+        // The unexpanded region runs from (gap_start + top_len) to (gap_end - bot_len)
+        let inner_start = gap_start + top_len;
+        let inner_end = gap_end.saturating_sub(bot_len);
+
+        if inner_start > inner_end {
+            return Ok(()); // Fully expanded
+        }
+
+        let fetch = |start: u32, end: u32| -> Result<Vec<DiffLine>> {
+            self.context_provider().fetch_context_lines(
+                old_path.as_ref(),
+                new_path.as_ref(),
+                file_status,
+                start,
+                end,
+            )
+        };
+
+        match direction {
+            ExpandDirection::Down => {
+                let n = limit.unwrap_or(usize::MAX) as u32;
+                let fetch_end = inner_start.saturating_add(n - 1).min(inner_end);
+                let new_lines = fetch(inner_start, fetch_end)?;
+                self.expanded_top
+                    .entry(gap_id.clone())
+                    .or_default()
+                    .extend(new_lines);
+            }
+            ExpandDirection::Up => {
+                let n = limit.unwrap_or(usize::MAX) as u32;
+                let fetch_start = inner_end.saturating_sub(n - 1).max(inner_start);
+                let new_lines = fetch(fetch_start, inner_end)?;
+                // Prepend: new lines go before existing bottom lines
+                let existing = self.expanded_bottom.remove(&gap_id).unwrap_or_default();
+                let mut combined = new_lines;
+                combined.extend(existing);
+                self.expanded_bottom.insert(gap_id.clone(), combined);
+            }
+            ExpandDirection::Both => {
+                // Fetch everything remaining
+                let new_lines = fetch(inner_start, inner_end)?;
+                self.expanded_top
+                    .entry(gap_id.clone())
+                    .or_default()
+                    .extend(new_lines);
+            }
+        }
+
+        self.rebuild_annotations();
+        Ok(())
+    }
+
+    /// Resolve the right `ContextProvider` for the current diff source.
+    /// In PR mode (with a forge backend present), expansion goes through the
+    /// forge; otherwise it goes through the local VCS backend.
+    fn context_provider(&self) -> Box<dyn ContextProvider + '_> {
+        if let (DiffSource::PullRequest(pr), Some(backend)) =
+            (&self.diff_source, self.forge_backend.as_ref())
+        {
+            Box::new(ForgeContextProvider {
+                forge: backend.as_ref(),
+                repository: pr.key.repository.clone(),
+                base_sha: pr.base_sha.clone(),
+                head_sha: pr.key.head_sha.clone(),
+            })
+        } else {
+            Box::new(VcsContextProvider {
+                vcs: self.vcs.as_ref(),
+            })
+        }
+    }
+
+    /// Collapse an expanded gap
+    pub fn collapse_gap(&mut self, gap_id: GapId) {
+        self.expanded_top.remove(&gap_id);
+        self.expanded_bottom.remove(&gap_id);
+        self.rebuild_annotations();
+    }
+
+    /// Clear all expanded gaps (called when reloading diffs)
+    pub fn clear_expanded_gaps(&mut self) {
+        self.expanded_top.clear();
+        self.expanded_bottom.clear();
+        self.expanded_elisions.clear();
+        self.word_diff_hunks.clear();
+    }
+
+    /// Runs of unchanged lines in a hunk that render as one "N unchanged
+    /// lines" row. Each run keeps a few lines of context next to the changes
+    /// around it, and lines carrying local comments or remote threads stay
+    /// visible.
+    pub fn elided_runs(&self, file_idx: usize, hunk_idx: usize) -> Vec<ElidedRun> {
+        let threshold = self.elide_context;
+        if threshold == 0 {
+            return Vec::new();
+        }
+        let Some(file) = self.diff_files.get(file_idx) else {
+            return Vec::new();
+        };
+        let Some(hunk) = file.hunks.get(hunk_idx) else {
+            return Vec::new();
+        };
+        let path = file.display_path();
+        let path_str = path.to_string_lossy();
+        let line_comments = self.session.files.get(path).map(|r| &r.line_comments);
+        let visibility = self.session.remote_comments_visibility;
+        let history = self.visible_history_comments(path);
+        let is_anchored = |line: &DiffLine| {
+            let linenos = [line.old_lineno, line.new_lineno];
+            let has_comment = line_comments.is_some_and(|comments| {
+                linenos.iter().flatten().any(|ln| comments.contains_key(ln))
+            });
+            has_comment
+                || history
+                    .iter()
+                    .any(|entry| linenos.contains(&Some(entry.line)))
+                || self.forge_review_threads.iter().any(|thread| {
+                    thread.path == path_str
+                        && visibility.render_decision(thread).is_some()
+                        && thread.line.is_some_and(|ln| linenos.contains(&Some(ln)))
+                })
+        };
+
+        let lines = &hunk.lines;
+        let mut runs = Vec::new();
+        let mut push_run = |start: usize, end: usize| {
+            let id = ElisionId {
+                file_idx,
+                hunk_idx,
+                start,
+            };
+            // A single hidden line takes as much room as its placeholder.
+            if end - start > 1 && !self.expanded_elisions.contains(&id) {
+                runs.push(ElidedRun {
+                    start,
+                    count: end - start,
+                });
+            }
+        };
+        let mut i = 0;
+        while i < lines.len() {
+            if lines[i].origin != LineOrigin::Context {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            while i < lines.len() && lines[i].origin == LineOrigin::Context {
+                i += 1;
+            }
+            if i - run_start <= threshold.max(2 * ELIDE_CONTEXT_KEEP) {
+                continue;
+            }
+            let end = i - ELIDE_CONTEXT_KEEP;
+            let mut start = run_start + ELIDE_CONTEXT_KEEP;
+            let anchors: Vec<usize> = (start..end)
+                .filter(|&idx| is_anchored(&lines[idx]))
+                .collect();
+            for anchor in anchors {
+                push_run(start, anchor);
+                start = anchor + 1;
+            }
+            if start < end {
+                push_run(start, end);
+            }
+        }
+        runs
+    }
+
+    /// Open the elided run in `file_idx` that hides `lineno` on `side`.
+    /// Returns false when no elided run covers the line.
+    fn expand_elision_containing(&mut self, file_idx: usize, lineno: u32, side: LineSide) -> bool {
+        let Some(file) = self.diff_files.get(file_idx) else {
+            return false;
+        };
+        for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+            for run in self.elided_runs(file_idx, hunk_idx) {
+                let hides_line = hunk.lines[run.start..run.start + run.count]
+                    .iter()
+                    .any(|line| match side {
+                        LineSide::New => line.new_lineno == Some(lineno),
+                        LineSide::Old => line.old_lineno == Some(lineno),
+                    });
+                if hides_line {
+                    self.expanded_elisions.insert(ElisionId {
+                        file_idx,
+                        hunk_idx,
+                        start: run.start,
+                    });
+                    self.rebuild_annotations();
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Show the unchanged lines behind the elided row under the cursor.
+    /// Returns false when the cursor is not on an elided row.
+    pub fn expand_elision_at_cursor(&mut self) -> bool {
+        let Some(AnnotatedLine::ElidedContext {
+            file_idx,
+            hunk_idx,
+            start,
+            ..
+        }) = self.line_annotations.get(self.diff_state.cursor_line)
+        else {
+            return false;
+        };
+        self.expanded_elisions.insert(ElisionId {
+            file_idx: *file_idx,
+            hunk_idx: *hunk_idx,
+            start: *start,
+        });
+        self.rebuild_annotations();
+        true
+    }
+
+    /// Replace the hunks of the deleted file under the cursor with all of
+    /// its prior content, so every removed line can be read and commented
+    /// on. A no-op elsewhere, and when the diff already shows the whole file.
+    pub fn show_deleted_file_at_cursor(&mut self) -> Result<()> {
+        self.vcs_idle()?;
+        let Some(file_idx) = self
+            .line_annotations
+            .get(self.diff_state.cursor_line)
+            .and_then(annotation_file_idx)
+        else {
+            return Ok(());
+        };
+        let Some(file) = self.diff_files.get(file_idx) else {
+            return Ok(());
+        };
+        let whole_file_shown = file.hunks.len() == 1 && file.hunks[0].old_start <= 1;
+        if file.status != FileStatus::Deleted
+            || file.is_binary
+            || file.is_too_large
+            || whole_file_shown
+        {
+            return Ok(());
+        }
+        let old_path = file
+            .old_path
+            .as_ref()
+            .unwrap_or(file.display_path())
+            .clone();
+
+        let contents: Vec<String> = self
+            .context_provider()
+            .fetch_deleted_file(&old_path)?
+            .iter()
+            .map(|line| crate::vcs::tabify(&old_path, line))
+            .collect();
+        let origins = vec![LineOrigin::Deletion; contents.len()];
+        let highlighter = self.theme.syntax_highlighter();
+        let sequences = SyntaxHighlighter::split_diff_lines_for_highlighting(&contents, &origins);
+        let highlighted = highlighter.highlight_file_lines(&old_path, &sequences.old_lines);
+        let lines: Vec<DiffLine> = contents
+            .into_iter()
+            .enumerate()
+            .map(|(i, content)| DiffLine {
+                origin: LineOrigin::Deletion,
+                highlighted_spans: highlighter.highlighted_line_for_diff_with_background(
+                    highlighted.as_deref(),
+                    None,
+                    sequences.old_line_indices[i],
+                    None,
+                    LineOrigin::Deletion,
+                ),
+                content,
+                old_lineno: Some(i as u32 + 1),
+                new_lineno: None,
+            })
+            .collect();
+
+        let old_count = lines.len() as u32;
+        self.diff_files[file_idx].hunks = vec![DiffHunk {
+            header: String::new(),
+            lines,
+            old_start: 1,
+            old_count,
+            new_start: 0,
+            new_count: 0,
+        }];
+        self.expanded_top.retain(|gap, _| gap.file_idx != file_idx);
+        self.expanded_bottom
+            .retain(|gap, _| gap.file_idx != file_idx);
+        self.expanded_elisions
+            .retain(|elision| elision.file_idx != file_idx);
+        self.rebuild_annotations();
+        Ok(())
+    }
+
+    /// Rebuild the line annotations cache. Call this when:
+    /// - Diff files change (load/reload)
+    /// - Expansion state changes (expand/collapse gap)
+    /// - Comments are added/removed
+    /// - Diff view mode changes
+    pub fn rebuild_annotations(&mut self) {
+        if let Some(filter) = self.diff_filter.as_mut()
+            && let Some(warning) = filter.apply(&self.vcs_info.root_path, &mut self.diff_files)
+        {
+            self.set_warning(warning);
+        }
+        self.line_annotations.clear();
+        self.conflicts = ConflictIndex::scan(&self.diff_files);
+
+        // Pre-index remote threads by (path, line, side) for quick lookup
+        // during the file/hunk walk. Threads whose visibility is
+        // suppressed don't appear in this map at all, so no annotations
+        // are emitted for them.
+        let remote_index = self.build_remote_thread_index();
+
+        self.line_annotations
+            .push(AnnotatedLine::ReviewCommentsHeader);
+        for (comment_idx, comment) in self.session.review_comments.iter().enumerate() {
+            let comment_lines = Self::comment_display_lines(comment);
+            for _ in 0..comment_lines {
+                self.line_annotations
+                    .push(AnnotatedLine::ReviewComment { comment_idx });
+            }
+        }
+
+        for (file_idx, file) in self.diff_files.iter().enumerate() {
+            let path = file.display_path();
+
+            // File header
+            self.line_annotations
+                .push(AnnotatedLine::FileHeader { file_idx });
+
+            // If reviewed, skip all content for this file
+            if self.is_file_folded(path) {
+                continue;
+            }
+
+            // File comments
+            if let Some(review) = self.session.files.get(path) {
+                for (comment_idx, comment) in review.file_comments.iter().enumerate() {
+                    let comment_lines = Self::comment_display_lines(comment);
+                    for _ in 0..comment_lines {
+                        self.line_annotations.push(AnnotatedLine::FileComment {
+                            file_idx,
+                            comment_idx,
+                        });
+                    }
+                }
+            }
+
+            let metadata_count = file.metadata_lines().len();
+            for line_idx in 0..metadata_count {
+                self.line_annotations
+                    .push(AnnotatedLine::FileMetadata { file_idx, line_idx });
+            }
+
+            if file.is_binary || file.hunks.is_empty() {
+                // A mode-only change already has its metadata rows.
+                if file.is_binary
+                    || file.is_too_large
+                    || metadata_count == 0
+                    || file.status == FileStatus::Deleted
+                {
+                    self.line_annotations
+                        .push(AnnotatedLine::BinaryOrEmpty { file_idx });
+                }
+            } else {
+                // Get line comments for this file
+                let line_comments = self
+                    .session
+                    .files
+                    .get(path)
+                    .map(|r| &r.line_comments)
+                    .cloned()
+                    .unwrap_or_default();
+
+                for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+                    // Calculate gap before this hunk
+                    let prev_hunk = if hunk_idx > 0 {
+                        file.hunks.get(hunk_idx - 1)
+                    } else {
+                        None
+                    };
+                    let gap = calculate_gap(
+                        prev_hunk.map(|h| (&h.new_start, &h.new_count)),
+                        hunk.new_start,
+                    );
+
+                    let gap_id = GapId { file_idx, hunk_idx };
+
+                    if gap > 0 {
+                        let top_len = self.expanded_top.get(&gap_id).map_or(0, |v| v.len());
+                        let bot_len = self.expanded_bottom.get(&gap_id).map_or(0, |v| v.len());
+                        let remaining = (gap as usize).saturating_sub(top_len + bot_len);
+                        let is_top_of_file = hunk_idx == 0;
+
+                        // Sequential line_idx counter across top + bottom
+                        let mut ctx_idx = 0;
+
+                        // --- Top expanded lines (↓ direction) ---
+                        for _ in 0..top_len {
+                            self.line_annotations.push(AnnotatedLine::ExpandedContext {
+                                gap_id: gap_id.clone(),
+                                line_idx: ctx_idx,
+                            });
+                            ctx_idx += 1;
+                        }
+
+                        // --- Expanders / hidden lines ---
+                        if remaining > 0 {
+                            if is_top_of_file {
+                                // Top-of-file: HiddenLines (if > batch) + ↑
+                                if remaining > GAP_EXPAND_BATCH {
+                                    self.line_annotations.push(AnnotatedLine::HiddenLines {
+                                        gap_id: gap_id.clone(),
+                                        count: remaining,
+                                    });
+                                }
+                                self.line_annotations.push(AnnotatedLine::Expander {
+                                    gap_id: gap_id.clone(),
+                                    direction: ExpandDirection::Up,
+                                });
+                            } else if remaining >= GAP_EXPAND_BATCH {
+                                // Between-hunk, large: ↓ + HiddenLines + ↑
+                                self.line_annotations.push(AnnotatedLine::Expander {
+                                    gap_id: gap_id.clone(),
+                                    direction: ExpandDirection::Down,
+                                });
+                                self.line_annotations.push(AnnotatedLine::HiddenLines {
+                                    gap_id: gap_id.clone(),
+                                    count: remaining,
+                                });
+                                self.line_annotations.push(AnnotatedLine::Expander {
+                                    gap_id: gap_id.clone(),
+                                    direction: ExpandDirection::Up,
+                                });
+                            } else {
+                                // Between-hunk, small: merged ↕
+                                self.line_annotations.push(AnnotatedLine::Expander {
+                                    gap_id: gap_id.clone(),
+                                    direction: ExpandDirection::Both,
+                                });
+                            }
+                        }
+
+                        // --- Bottom expanded lines (↑ direction) ---
+                        for _ in 0..bot_len {
+                            self.line_annotations.push(AnnotatedLine::ExpandedContext {
+                                gap_id: gap_id.clone(),
+                                line_idx: ctx_idx,
+                            });
+                            ctx_idx += 1;
+                        }
+                    }
+
+                    // Hunk header
+                    self.line_annotations
+                        .push(AnnotatedLine::HunkHeader { file_idx, hunk_idx });
+
+                    // Diff lines - handle differently based on view mode
+                    let elided = self.elided_runs(file_idx, hunk_idx);
+                    let history: &[HistoryComment] = if self.show_history_comments {
+                        self.history_comments.get(path).map_or(&[], Vec::as_slice)
+                    } else {
+                        &[]
+                    };
+                    match self.diff_view_mode {
+                        DiffViewMode::Unified => {
+                            Self::build_unified_diff_annotations(
+                                &mut self.line_annotations,
+                                file_idx,
+                                hunk_idx,
+                                &hunk.lines,
+                                &elided,
+                                &line_comments,
+                                path,
+                                &self.forge_review_threads,
+                                &remote_index,
+                                history,
+                            );
+                        }
+                        DiffViewMode::SideBySide => {
+                            Self::build_side_by_side_annotations(
+                                &mut self.line_annotations,
+                                file_idx,
+                                hunk_idx,
+                                &hunk.lines,
+                                &elided,
+                                &line_comments,
+                                path,
+                                &self.forge_review_threads,
+                                &remote_index,
+                                history,
+                            );
+                        }
+                    }
+                }
+            }
+
+            // Spacing line
+            self.line_annotations.push(AnnotatedLine::Spacing);
+        }
+    }
+
+    fn push_comments(
+        annotations: &mut Vec<AnnotatedLine>,
+        file_idx: usize,
+        line_no: Option<u32>,
+        line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
+        side: LineSide,
+    ) {
+        let Some(ln) = line_no else {
+            return;
+        };
+
+        let Some(comments) = line_comments.get(&ln) else {
+            return;
+        };
+
+        for (idx, comment) in comments.iter().enumerate() {
+            let matches_side =
+                comment.side == Some(side) || (side == LineSide::New && comment.side.is_none());
+
+            if !matches_side {
+                continue;
+            }
+
+            let comment_lines = Self::comment_display_lines(comment);
+            for _ in 0..comment_lines {
+                annotations.push(AnnotatedLine::LineComment {
+                    file_idx,
+                    line: ln,
+                    comment_idx: idx,
+                    side,
+                });
+            }
+        }
+    }
+
+    /// Per-file map of `(line, side)` -> indices into `forge_review_threads`.
+    /// Sides use the `RemoteCommentSide` mapping: `Right` -> `LineSide::New`,
+    /// `Left` -> `LineSide::Old`.
+    fn build_remote_thread_index(&self) -> RemoteThreadIndex {
+        use crate::forge::remote_comments::RemoteCommentSide;
+        let mut by_file: std::collections::HashMap<
+            String,
+            std::collections::HashMap<(u32, LineSide), Vec<usize>>,
+        > = std::collections::HashMap::new();
+        let visibility = self.session.remote_comments_visibility;
+        // Imported threads are already drawn as the session's own comments
+        let imported = crate::forge::remote_comments::session_remote_comment_ids(&self.session);
+
+        for (thread_idx, thread) in self.forge_review_threads.iter().enumerate() {
+            if visibility.render_decision(thread).is_none()
+                || (!thread.comments.is_empty()
+                    && thread
+                        .comments
+                        .iter()
+                        .all(|c| imported.contains(c.id.as_str())))
+            {
+                continue;
+            }
+            let Some(line) = thread.line else { continue };
+            let side = match thread.side {
+                RemoteCommentSide::Right => LineSide::New,
+                RemoteCommentSide::Left => LineSide::Old,
+            };
+            by_file
+                .entry(thread.path.clone())
+                .or_default()
+                .entry((line, side))
+                .or_default()
+                .push(thread_idx);
+        }
+
+        RemoteThreadIndex { by_file }
+    }
+
+    fn push_remote_threads(
+        annotations: &mut Vec<AnnotatedLine>,
+        threads: &[crate::forge::remote_comments::RemoteReviewThread],
+        index: &RemoteThreadIndex,
+        path: &std::path::Path,
+        line: u32,
+        side: LineSide,
+    ) {
+        let Some(file_index) = index.by_file.get(path.to_string_lossy().as_ref()) else {
+            return;
+        };
+        let Some(thread_indices) = file_index.get(&(line, side)) else {
+            return;
+        };
+        for thread_idx in thread_indices {
+            if let Some(thread) = threads.get(*thread_idx) {
+                let n = crate::forge::remote_comments::thread_display_lines(thread);
+                for _ in 0..n {
+                    annotations.push(AnnotatedLine::RemoteThreadLine {
+                        thread_idx: *thread_idx,
+                    });
+                }
+            }
+        }
+    }
+
+    fn push_history_comments(
+        annotations: &mut Vec<AnnotatedLine>,
+        file_idx: usize,
+        history: &[HistoryComment],
+        line: u32,
+        side: LineSide,
+    ) {
+        for (idx, entry) in history.iter().enumerate() {
+            if entry.line == line && entry.side == side {
+                annotations.push(AnnotatedLine::HistoryComment { file_idx, idx });
+            }
+        }
+    }
+
+    /// Build annotations for unified diff mode (one annotation per diff line)
+    #[allow(clippy::too_many_arguments)]
+    fn build_unified_diff_annotations(
+        annotations: &mut Vec<AnnotatedLine>,
+        file_idx: usize,
+        hunk_idx: usize,
+        lines: &[crate::model::DiffLine],
+        elided: &[ElidedRun],
+        line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
+        path: &std::path::Path,
+        remote_threads: &[crate::forge::remote_comments::RemoteReviewThread],
+        remote_index: &RemoteThreadIndex,
+        history: &[HistoryComment],
+    ) {
+        let mut skip_until = 0;
+        for (line_idx, diff_line) in lines.iter().enumerate() {
+            if line_idx < skip_until {
+                continue;
+            }
+            if let Some(run) = elided.iter().find(|run| run.start == line_idx) {
+                annotations.push(AnnotatedLine::ElidedContext {
+                    file_idx,
+                    hunk_idx,
+                    start: run.start,
+                    count: run.count,
+                });
+                skip_until = run.start + run.count;
+                continue;
+            }
+            annotations.push(AnnotatedLine::DiffLine {
+                file_idx,
+                hunk_idx,
+                line_idx,
+                old_lineno: diff_line.old_lineno,
+                new_lineno: diff_line.new_lineno,
+            });
+
+            // Line comments on old side (delete lines)
+            if let Some(old_ln) = diff_line.old_lineno {
+                Self::push_comments(
+                    annotations,
+                    file_idx,
+                    Some(old_ln),
+                    line_comments,
+                    LineSide::Old,
+                );
+                Self::push_remote_threads(
+                    annotations,
+                    remote_threads,
+                    remote_index,
+                    path,
+                    old_ln,
+                    LineSide::Old,
+                );
+                Self::push_history_comments(annotations, file_idx, history, old_ln, LineSide::Old);
+            }
+
+            // Line comments on new side (added/context lines)
+            if let Some(new_ln) = diff_line.new_lineno {
+                Self::push_comments(
+                    annotations,
+                    file_idx,
+                    Some(new_ln),
+                    line_comments,
+                    LineSide::New,
+                );
+                Self::push_remote_threads(
+                    annotations,
+                    remote_threads,
+                    remote_index,
+                    path,
+                    new_ln,
+                    LineSide::New,
+                );
+                Self::push_history_comments(annotations, file_idx, history, new_ln, LineSide::New);
+            }
+        }
+    }
+
+    /// Build annotations for side-by-side diff mode, pairing deletions and additions into aligned rows.
+    #[allow(clippy::too_many_arguments)]
+    fn build_side_by_side_annotations(
+        annotations: &mut Vec<AnnotatedLine>,
+        file_idx: usize,
+        hunk_idx: usize,
+        lines: &[crate::model::DiffLine],
+        elided: &[ElidedRun],
+        line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
+        path: &std::path::Path,
+        remote_threads: &[crate::forge::remote_comments::RemoteReviewThread],
+        remote_index: &RemoteThreadIndex,
+        history: &[HistoryComment],
+    ) {
+        let mut i = 0;
+        while i < lines.len() {
+            let diff_line = &lines[i];
+
+            match diff_line.origin {
+                LineOrigin::Context => {
+                    if let Some(run) = elided.iter().find(|run| run.start == i) {
+                        annotations.push(AnnotatedLine::ElidedContext {
+                            file_idx,
+                            hunk_idx,
+                            start: run.start,
+                            count: run.count,
+                        });
+                        i += run.count;
+                        continue;
+                    }
+                    annotations.push(AnnotatedLine::SideBySideLine {
+                        file_idx,
+                        hunk_idx,
+                        del_line_idx: Some(i),
+                        add_line_idx: Some(i),
+                        old_lineno: diff_line.old_lineno,
+                        new_lineno: diff_line.new_lineno,
+                    });
+
+                    Self::push_comments(
+                        annotations,
+                        file_idx,
+                        diff_line.new_lineno,
+                        line_comments,
+                        LineSide::New,
+                    );
+                    if let Some(new_ln) = diff_line.new_lineno {
+                        Self::push_remote_threads(
+                            annotations,
+                            remote_threads,
+                            remote_index,
+                            path,
+                            new_ln,
+                            LineSide::New,
+                        );
+                        Self::push_history_comments(
+                            annotations,
+                            file_idx,
+                            history,
+                            new_ln,
+                            LineSide::New,
+                        );
+                    }
+
+                    i += 1
+                }
+
+                LineOrigin::Deletion => {
+                    // Find consecutive deletions
+                    let del_start = i;
+                    let mut del_end = i + 1;
+                    while del_end < lines.len() && lines[del_end].origin == LineOrigin::Deletion {
+                        del_end += 1;
+                    }
+
+                    // Find consecutive additions following deletions
+                    let add_start = del_end;
+                    let mut add_end = add_start;
+                    while add_end < lines.len() && lines[add_end].origin == LineOrigin::Addition {
+                        add_end += 1;
+                    }
+
+                    let del_count = del_end - del_start;
+                    let add_count = add_end - add_start;
+                    let max_lines = del_count.max(add_count);
+
+                    for offset in 0..max_lines {
+                        let del_idx = if offset < del_count {
+                            Some(del_start + offset)
+                        } else {
+                            None
+                        };
+                        let add_idx = if offset < add_count {
+                            Some(add_start + offset)
+                        } else {
+                            None
+                        };
+
+                        let old_lineno = del_idx.and_then(|idx| lines[idx].old_lineno);
+                        let new_lineno = add_idx.and_then(|idx| lines[idx].new_lineno);
+
+                        annotations.push(AnnotatedLine::SideBySideLine {
+                            file_idx,
+                            hunk_idx,
+                            del_line_idx: del_idx,
+                            add_line_idx: add_idx,
+                            old_lineno,
+                            new_lineno,
+                        });
+
+                        Self::push_comments(
+                            annotations,
+                            file_idx,
+                            old_lineno,
+                            line_comments,
+                            LineSide::Old,
+                        );
+                        if let Some(old_ln) = old_lineno {
+                            Self::push_remote_threads(
+                                annotations,
+                                remote_threads,
+                                remote_index,
+                                path,
+                                old_ln,
+                                LineSide::Old,
+                            );
+                            Self::push_history_comments(
+                                annotations,
+                                file_idx,
+                                history,
+                                old_ln,
+                                LineSide::Old,
+                            );
+                        }
+                        Self::push_comments(
+                            annotations,
+                            file_idx,
+                            new_lineno,
+                            line_comments,
+                            LineSide::New,
+                        );
+                        if let Some(new_ln) = new_lineno {
+                            Self::push_remote_threads(
+                                annotations,
+                                remote_threads,
+                                remote_index,
+                                path,
+                                new_ln,
+                                LineSide::New,
+                            );
+                            Self::push_history_comments(
+                                annotations,
+                                file_idx,
+                                history,
+                                new_ln,
+                                LineSide::New,
+                            );
+                        }
+                    }
+
+                    i = add_end;
+                }
+                LineOrigin::Addition => {
+                    annotations.push(AnnotatedLine::SideBySideLine {
+                        file_idx,
+                        hunk_idx,
+                        del_line_idx: None,
+                        add_line_idx: Some(i),
+                        old_lineno: None,
+                        new_lineno: diff_line.new_lineno,
+                    });
+
+                    Self::push_comments(
+                        annotations,
+                        file_idx,
+                        diff_line.new_lineno,
+                        line_comments,
+                        LineSide::New,
+                    );
+                    if let Some(new_ln) = diff_line.new_lineno {
+                        Self::push_remote_threads(
+                            annotations,
+                            remote_threads,
+                            remote_index,
+                            path,
+                            new_ln,
+                            LineSide::New,
+                        );
+                        Self::push_history_comments(
+                            annotations,
+                            file_idx,
+                            history,
+                            new_ln,
+                            LineSide::New,
+                        );
+                    }
+
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    /// What the cursor is on in a gap region
+    pub fn get_gap_at_cursor(&self) -> Option<GapCursorHit> {
+        let target = self.diff_state.cursor_line;
+        match self.line_annotations.get(target) {
+            Some(AnnotatedLine::Expander { gap_id, direction }) => {
+                Some(GapCursorHit::Expander(gap_id.clone(), *direction))
+            }
+            Some(AnnotatedLine::HiddenLines { gap_id, .. }) => {
+                Some(GapCursorHit::HiddenLines(gap_id.clone()))
+            }
+            Some(AnnotatedLine::ExpandedContext { gap_id, .. }) => {
+                Some(GapCursorHit::ExpandedContent(gap_id.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn ensure_valid_tree_selection(&mut self) {
+        use std::path::Path;
+
+        let visible_items = self.build_visible_items();
+        if visible_items.is_empty() {
+            self.file_list_state.select(0);
+            return;
+        }
+
+        let current_file_idx = self.diff_state.current_file_idx;
+        let file_visible = visible_items.iter().any(|item| {
+            matches!(item, FileTreeItem::File { file_idx, .. } if *file_idx == current_file_idx)
+        });
+
+        if file_visible {
+            if let Some(tree_idx) = self.file_idx_to_tree_idx(current_file_idx) {
+                self.file_list_state.select(tree_idx);
+            }
+        } else {
+            if let Some(file) = self.diff_files.get(current_file_idx) {
+                let file_path = file.display_path();
+                let mut current = file_path.parent();
+                while let Some(parent) = current {
+                    if parent != Path::new("") {
+                        let parent_str = parent.to_string_lossy().to_string();
+                        for (tree_idx, item) in visible_items.iter().enumerate() {
+                            if let FileTreeItem::Directory { path, .. } = item
+                                && *path == parent_str
+                            {
+                                self.file_list_state.select(tree_idx);
+                                return;
+                            }
+                        }
+                    }
+                    current = parent.parent();
+                }
+            }
+            self.file_list_state.select(0);
+        }
+    }
+
+    pub fn build_visible_items(&self) -> Vec<FileTreeItem> {
+        use std::path::Path;
+
+        let mut items = Vec::new();
+        let mut seen_dirs: HashSet<String> = HashSet::new();
+
+        // With no conflicts left the filter would hide everything
+        let conflicts_only = self.conflicts_only && !self.conflicts.is_empty();
+        let author_files = self.author_files();
+        for (file_idx, file) in self.diff_files.iter().enumerate() {
+            let path = file.display_path();
+            if conflicts_only && !self.conflicts.is_conflicted(path) {
+                continue;
+            }
+            if author_files.is_some_and(|files| !files.contains(path)) {
+                continue;
+            }
+
+            let mut ancestors: Vec<String> = Vec::new();
+            let mut current = path.parent();
+            while let Some(parent) = current {
+                if parent != Path::new("") {
+                    ancestors.push(parent.to_string_lossy().to_string());
+                }
+                current = parent.parent();
+            }
+            ancestors.reverse();
+
+            let mut visible = true;
+            for (depth, dir) in ancestors.iter().enumerate() {
+                if !seen_dirs.contains(dir) && visible {
+                    let expanded = self.expanded_dirs.contains(dir);
+                    items.push(FileTreeItem::Directory {
+                        path: dir.clone(),
+                        depth,
+                        expanded,
+                    });
+                    seen_dirs.insert(dir.clone());
+                }
+
+                if !self.expanded_dirs.contains(dir) {
+                    visible = false;
+                }
+            }
+
+            if visible {
+                items.push(FileTreeItem::File {
+                    file_idx,
+                    depth: ancestors.len(),
+                });
+            }
+        }
+
+        items
+    }
+
+    pub fn get_selected_tree_item(&self) -> Option<FileTreeItem> {
+        let visible_items = self.build_visible_items();
+        let selected_idx = self.file_list_state.selected();
+        visible_items.get(selected_idx).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tree_tests {
+    use super::*;
+    use crate::model::{DiffFile, FileStatus};
+
+    fn make_file(path: &str) -> DiffFile {
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Modified,
+            hunks: vec![],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    struct TreeTestHarness {
+        diff_files: Vec<DiffFile>,
+        expanded_dirs: HashSet<String>,
+    }
+
+    impl TreeTestHarness {
+        fn new(paths: &[&str]) -> Self {
+            Self {
+                diff_files: paths.iter().map(|p| make_file(p)).collect(),
+                expanded_dirs: HashSet::new(),
+            }
+        }
+
+        fn expand_all(&mut self) {
+            use std::path::Path;
+            for file in &self.diff_files {
+                let path = file.display_path();
+                let mut current = path.parent();
+                while let Some(parent) = current {
+                    if parent != Path::new("") {
+                        self.expanded_dirs
+                            .insert(parent.to_string_lossy().to_string());
+                    }
+                    current = parent.parent();
+                }
+            }
+        }
+
+        fn collapse_all(&mut self) {
+            self.expanded_dirs.clear();
+        }
+
+        fn toggle(&mut self, dir: &str) {
+            if self.expanded_dirs.contains(dir) {
+                self.expanded_dirs.remove(dir);
+            } else {
+                self.expanded_dirs.insert(dir.to_string());
+            }
+        }
+
+        fn build_visible_items(&self) -> Vec<FileTreeItem> {
+            use std::path::Path;
+            let mut items = Vec::new();
+            let mut seen_dirs: HashSet<String> = HashSet::new();
+
+            for (file_idx, file) in self.diff_files.iter().enumerate() {
+                let path = file.display_path();
+                let mut ancestors: Vec<String> = Vec::new();
+                let mut current = path.parent();
+                while let Some(parent) = current {
+                    if parent != Path::new("") {
+                        ancestors.push(parent.to_string_lossy().to_string());
+                    }
+                    current = parent.parent();
+                }
+                ancestors.reverse();
+
+                let mut visible = true;
+                for (depth, dir) in ancestors.iter().enumerate() {
+                    if !seen_dirs.contains(dir) && visible {
+                        let expanded = self.expanded_dirs.contains(dir);
+                        items.push(FileTreeItem::Directory {
+                            path: dir.clone(),
+                            depth,
+                            expanded,
+                        });
+                        seen_dirs.insert(dir.clone());
+                    }
+                    if !self.expanded_dirs.contains(dir) {
+                        visible = false;
+                    }
+                }
+
+                if visible {
+                    items.push(FileTreeItem::File {
+                        file_idx,
+                        depth: ancestors.len(),
+                    });
+                }
+            }
+            items
+        }
+
+        fn visible_file_count(&self) -> usize {
+            self.build_visible_items()
+                .iter()
+                .filter(|i| matches!(i, FileTreeItem::File { .. }))
+                .count()
+        }
+
+        fn visible_dir_count(&self) -> usize {
+            self.build_visible_items()
+                .iter()
+                .filter(|i| matches!(i, FileTreeItem::Directory { .. }))
+                .count()
+        }
+    }
+
+    #[test]
+    fn test_expand_all_shows_all_files() {
+        let mut h = TreeTestHarness::new(&["src/ui/app.rs", "src/ui/help.rs", "src/main.rs"]);
+        h.expand_all();
+
+        assert_eq!(h.visible_file_count(), 3);
+    }
+
+    #[test]
+    fn test_collapse_all_hides_all_files() {
+        let mut h = TreeTestHarness::new(&["src/ui/app.rs", "src/main.rs"]);
+        h.expand_all();
+        h.collapse_all();
+
+        assert_eq!(h.visible_file_count(), 0);
+        assert_eq!(h.visible_dir_count(), 1); // only "src" visible
+    }
+
+    #[test]
+    fn test_collapse_parent_hides_nested_dirs() {
+        let mut h = TreeTestHarness::new(&["src/ui/components/button.rs"]);
+        h.expand_all();
+        assert_eq!(h.visible_dir_count(), 3); // src, src/ui, src/ui/components
+
+        h.toggle("src");
+        let items = h.build_visible_items();
+        assert_eq!(items.len(), 1); // only collapsed "src" dir
+        assert!(matches!(
+            &items[0],
+            FileTreeItem::Directory {
+                expanded: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_root_files_always_visible() {
+        let mut h = TreeTestHarness::new(&["README.md", "Cargo.toml"]);
+        h.collapse_all();
+
+        assert_eq!(h.visible_file_count(), 2);
+    }
+
+    #[test]
+    fn test_tree_depth_correct() {
+        let mut h = TreeTestHarness::new(&["a/b/c/file.rs"]);
+        h.expand_all();
+
+        let items = h.build_visible_items();
+        assert!(matches!(&items[0], FileTreeItem::Directory { depth: 0, path, .. } if path == "a"));
+        assert!(
+            matches!(&items[1], FileTreeItem::Directory { depth: 1, path, .. } if path == "a/b")
+        );
+        assert!(
+            matches!(&items[2], FileTreeItem::Directory { depth: 2, path, .. } if path == "a/b/c")
+        );
+        assert!(matches!(&items[3], FileTreeItem::File { depth: 3, .. }));
+    }
+
+    #[test]
+    fn test_toggle_expands_collapsed_dir() {
+        let mut h = TreeTestHarness::new(&["src/main.rs"]);
+        h.collapse_all();
+        assert_eq!(h.visible_file_count(), 0);
+
+        h.toggle("src");
+        assert_eq!(h.visible_file_count(), 1);
+    }
+
+    #[test]
+    fn test_sibling_dirs_independent() {
+        let mut h = TreeTestHarness::new(&["src/app.rs", "tests/test.rs"]);
+        h.expand_all();
+        h.toggle("src"); // collapse src
+
+        assert_eq!(h.visible_file_count(), 1); // only tests/test.rs
+    }
+}
+
+#[cfg(test)]
+mod commit_selection_tests {
+    use super::*;
+    use crate::model::FileStatus;
+    use crate::vcs::traits::VcsType;
+
+    struct DummyVcs {
+        info: VcsInfo,
+    }
+
+    impl VcsBackend for DummyVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
+
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn build_app(commit_list: Vec<CommitInfo>) -> App {
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "head".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
+
+        App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            Vec::new(),
+            session,
+            DiffSource::WorkingTree,
+            InputMode::CommitSelect,
+            commit_list,
+            None,
+        )
+        .expect("failed to build test app")
+    }
+
+    fn normal_commit(id: &str) -> CommitInfo {
+        CommitInfo {
+            id: id.to_string(),
+            short_id: id.to_string(),
+            branch_name: None,
+            summary: "Test commit".to_string(),
+            body: None,
+            author: "Test".to_string(),
+            time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn special_commit_count_counts_leading_special_entries() {
+        let app = build_app(vec![
+            App::staged_commit_entry(),
+            App::unstaged_commit_entry(),
+            normal_commit("abc123"),
+        ]);
+
+        assert_eq!(app.special_commit_count(), 2);
+    }
+
+    #[test]
+    fn special_commit_count_ignores_non_leading_special_entries() {
+        let app = build_app(vec![normal_commit("abc123"), App::staged_commit_entry()]);
+
+        assert_eq!(app.special_commit_count(), 0);
+    }
+
+    #[test]
+    fn toggle_commit_selection_from_all_selected_selects_only_cursor() {
+        for cursor in 0..3 {
+            let mut app = build_app(vec![
+                normal_commit("abc123"),
+                normal_commit("def456"),
+                normal_commit("789abc"),
+            ]);
+            app.commit_selection_range = Some((0, 2));
+            app.commit_list_cursor = cursor;
+
+            app.toggle_commit_selection();
+
+            assert_eq!(app.commit_selection_range, Some((cursor, cursor)));
+        }
+    }
+
+    #[test]
+    fn toggle_commit_selection_keeps_partial_range_shrink_behavior() {
+        let mut app = build_app(vec![
+            normal_commit("abc123"),
+            normal_commit("def456"),
+            normal_commit("789abc"),
+        ]);
+        app.commit_selection_range = Some((0, 1));
+        app.commit_list_cursor = 0;
+
+        app.toggle_commit_selection();
+
+        assert_eq!(app.commit_selection_range, Some((1, 1)));
+    }
+
+    #[test]
+    fn selected_range_expression_follows_range_mode() {
+        // given
+        let mut app = build_app(vec![
+            App::staged_commit_entry(),
+            normal_commit("ccccccc111"),
+            normal_commit("bbbbbbb111"),
+            normal_commit("aaaaaaa111"),
+        ]);
+        app.commit_selection_range = Some((0, 2));
+
+        // when / then
+        assert_eq!(
+            app.selected_range_expression(&app.commit_list),
+            Some("bbbbbbb^..ccccccc".to_string())
+        );
+        app.toggle_range_mode();
+        assert_eq!(app.range_mode, RangeMode::ThreeDot);
+        assert_eq!(
+            app.selected_range_expression(&app.commit_list),
+            Some("bbbbbbb^...ccccccc".to_string())
+        );
+    }
+
+    #[test]
+    fn selected_range_expression_is_none_for_working_tree_rows_only() {
+        let mut app = build_app(vec![App::staged_commit_entry(), normal_commit("abc123")]);
+        app.commit_selection_range = Some((0, 0));
+
+        assert_eq!(app.selected_range_expression(&app.commit_list), None);
+    }
+}
+
+#[cfg(test)]
+mod target_selector_tests {
+    use super::*;
+    use crate::forge::selector::PullRequestsTab;
+    use crate::forge::traits::PullRequestSummary;
+    use crate::model::FileStatus;
+    use crate::vcs::traits::{VcsChangeStatus, VcsType};
+
+    struct DummyVcs {
+        info: VcsInfo,
+        commits: Vec<CommitInfo>,
+    }
+
+    impl VcsBackend for DummyVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
+
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
+
+        fn get_change_status(&self) -> Result<VcsChangeStatus> {
+            Ok(VcsChangeStatus {
+                staged: false,
+                unstaged: false,
+            })
+        }
+
+        fn get_recent_commits(&self, offset: usize, limit: usize) -> Result<Vec<CommitInfo>> {
+            Ok(self
+                .commits
+                .iter()
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn build_app() -> App {
+        build_app_with_commits(Vec::new())
+    }
+
+    fn build_app_with_commits(commits: Vec<CommitInfo>) -> App {
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "head".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
+
+        App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+                commits,
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            Vec::new(),
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app")
+    }
+
+    fn dummy_commit(id: &str) -> CommitInfo {
+        CommitInfo {
+            id: id.to_string(),
+            short_id: id.to_string(),
+            branch_name: None,
+            summary: format!("commit {id}"),
+            body: None,
+            author: "tester".to_string(),
+            time: Utc::now(),
+        }
+    }
+
+    fn test_pr_details(number: u64, title: &str) -> crate::forge::traits::PullRequestDetails {
+        crate::forge::traits::PullRequestDetails {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            number,
+            title: title.to_string(),
+            url: format!("https://github.com/agavra/tuicr/pull/{number}"),
+            state: "OPEN".to_string(),
+            is_draft: false,
+            author: Some("alice".to_string()),
+            head_ref_name: "feat".to_string(),
+            base_ref_name: "main".to_string(),
+            head_sha: "abcdef0123456789".to_string(),
+            base_sha: "1234567890abcdef".to_string(),
+            body: String::new(),
+            updated_at: None,
+            closed: false,
+            merged_at: None,
+        }
+    }
+
+    struct FakeForgeBackend {
+        details: crate::forge::traits::PullRequestDetails,
+        patch: String,
+        commits: Vec<crate::forge::traits::PullRequestCommit>,
+        range_patch: Option<String>,
+    }
+
+    impl FakeForgeBackend {
+        fn open_pr_details(
+            details: crate::forge::traits::PullRequestDetails,
+            patch: String,
+        ) -> Self {
+            Self {
+                details,
+                patch,
+                commits: Vec::new(),
+                range_patch: None,
+            }
+        }
+    }
+
+    impl crate::forge::traits::ForgeBackend for FakeForgeBackend {
+        fn list_pull_requests(
+            &self,
+            _query: crate::forge::traits::PullRequestListQuery,
+        ) -> Result<crate::forge::traits::PagedPullRequests> {
+            unimplemented!("not used in this test")
+        }
+        fn get_pull_request(
+            &self,
+            _target: crate::forge::traits::PullRequestTarget,
+        ) -> Result<crate::forge::traits::PullRequestDetails> {
+            Ok(self.details.clone())
+        }
+        fn get_pull_request_diff(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<String> {
+            Ok(self.patch.clone())
+        }
+        fn fetch_file_lines(
+            &self,
+            _request: crate::forge::traits::ForgeFileLinesRequest,
+        ) -> Result<Vec<crate::model::DiffLine>> {
+            Ok(Vec::new())
+        }
+        fn list_review_threads(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<Vec<crate::forge::remote_comments::RemoteReviewThread>> {
+            Ok(Vec::new())
+        }
+        fn list_pull_request_commits(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<Vec<crate::forge::traits::PullRequestCommit>> {
+            Ok(self.commits.clone())
+        }
+        fn get_pull_request_commit_range_diff(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+            _start_sha: &str,
+            _end_sha: &str,
+        ) -> Result<String> {
+            Ok(self
+                .range_patch
+                .clone()
+                .unwrap_or_else(|| self.patch.clone()))
+        }
+        fn create_review(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+            _request: crate::forge::traits::CreateReviewRequest<'_>,
+        ) -> Result<crate::forge::traits::GhCreateReviewResponse> {
+            unimplemented!("FakeForgeBackend does not implement create_review")
+        }
+    }
+
+    fn sample_pr(number: u64, title: &str) -> PullRequestSummary {
+        PullRequestSummary {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            number,
+            title: title.to_string(),
+            author: Some("alice".to_string()),
+            head_ref_name: "feat".to_string(),
+            base_ref_name: "main".to_string(),
+            updated_at: None,
+            url: format!("https://github.com/agavra/tuicr/pull/{number}"),
+            state: "OPEN".to_string(),
+            is_draft: false,
+        }
+    }
+
+    #[test]
+    fn should_default_to_local_tab_after_build() {
+        // given / when
+        let app = build_app();
+        // then
+        assert_eq!(app.target_tab, TargetTab::Local);
+        assert!(!app.pr_filter_editing());
+    }
+
+    #[test]
+    fn should_cycle_between_local_and_pull_requests_on_tab_keypress() {
+        // given
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        // when
+        app.cycle_target_tab(true);
+        // then
+        assert_eq!(app.target_tab, TargetTab::PullRequests);
+        // when
+        app.cycle_target_tab(false);
+        // then
+        assert_eq!(app.target_tab, TargetTab::Local);
+    }
+
+    #[test]
+    fn should_transition_pr_tab_to_loading_on_first_visit() {
+        // given
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        // when
+        app.cycle_target_tab(true);
+        // then — a background fetch is in flight
+        assert!(app.pr_tab.is_loading());
+        assert!(app.pr_load_rx.is_some());
+        // The spawned thread holds a backend that may fail without a real
+        // `gh` binary; cancel by dropping the receiver to avoid touching it.
+        app.pr_load_rx = None;
+    }
+
+    #[test]
+    fn should_keep_pr_tab_disabled_when_no_forge_remote() {
+        // given
+        let mut app = build_app();
+        // No forge_repository set up; default new app has None.
+        // when
+        app.cycle_target_tab(true);
+        // then
+        assert_eq!(app.target_tab, TargetTab::PullRequests);
+        assert!(matches!(app.pr_tab, PullRequestsTab::Disabled { .. }));
+        assert!(app.pr_load_rx.is_none());
+    }
+
+    #[test]
+    fn should_set_filter_after_typing_and_committing() {
+        // given
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        app.pr_tab.apply_initial_load(Ok((
+            vec![sample_pr(125, "Forge"), sample_pr(148, "Review")],
+            false,
+        )));
+        app.target_tab = TargetTab::PullRequests;
+        // when
+        app.begin_pr_filter();
+        app.pr_filter_insert_char('f');
+        app.pr_filter_insert_char('o');
+        app.commit_pr_filter();
+        // then
+        assert!(!app.pr_filter_editing());
+        assert_eq!(app.pr_tab.view().rows.len(), 1);
+        assert_eq!(app.pr_tab.view().rows[0].summary.number, 125);
+    }
+
+    #[test]
+    fn should_discard_filter_draft_on_cancel() {
+        // given
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        app.pr_tab
+            .apply_initial_load(Ok((vec![sample_pr(1, "alpha")], false)));
+        app.target_tab = TargetTab::PullRequests;
+        // when
+        app.begin_pr_filter();
+        app.pr_filter_insert_char('z');
+        app.cancel_pr_filter();
+        // then
+        assert!(!app.pr_filter_editing());
+        assert_eq!(app.pr_tab.view().filter, "");
+    }
+
+    #[test]
+    fn should_enter_pr_mode_when_opening_pr_via_fake_backend() {
+        // given a selector with a single PR row and a fake forge backend
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        let summary = sample_pr(42, "answer");
+        app.pr_tab
+            .apply_initial_load(Ok((vec![summary.clone()], false)));
+        app.target_tab = TargetTab::PullRequests;
+        let backend = Box::new(FakeForgeBackend::open_pr_details(
+            test_pr_details(42, "answer"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        ));
+        // when
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // then
+        assert!(matches!(app.diff_source, DiffSource::PullRequest(_)));
+        if let DiffSource::PullRequest(pr) = &app.diff_source {
+            assert_eq!(pr.key.number, 42);
+            assert_eq!(pr.title, "answer");
+            assert_eq!(pr.head_ref_name, "feat");
+            assert_eq!(pr.base_ref_name, "main");
+            assert_eq!(pr.key.head_sha, "abcdef0123456789");
+            assert_eq!(pr.base_sha, "1234567890abcdef");
+        }
+        // and the session is keyed by the PR
+        assert!(app.session.pr_session_key.is_some());
+        // and PR diff files were parsed
+        assert_eq!(app.diff_files.len(), 1);
+        // and the forge backend is wired for context expansion / submit
+        assert!(app.forge_backend.is_some());
+    }
+
+    fn sample_pr_commit(oid: &str, summary: &str) -> crate::forge::traits::PullRequestCommit {
+        crate::forge::traits::PullRequestCommit {
+            oid: oid.to_string(),
+            short_oid: oid.chars().take(7).collect(),
+            summary: summary.to_string(),
+            author: "Alice".to_string(),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn should_populate_inline_selector_when_pr_has_multiple_commits() {
+        // given a PR open path where the forge returns 3 commits
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        let summary = sample_pr(42, "multi-commit");
+        app.pr_tab
+            .apply_initial_load(Ok((vec![summary.clone()], false)));
+        let mut backend = FakeForgeBackend::open_pr_details(
+            test_pr_details(42, "multi-commit"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        );
+        // Forge returns oldest-first; pr_open reverses to newest-first.
+        backend.commits = vec![
+            sample_pr_commit("aaaaaaa1111", "first"),
+            sample_pr_commit("bbbbbbb2222", "second"),
+            sample_pr_commit("ccccccc3333", "third"),
+        ];
+        // when
+        app.open_pr_with_backend(&summary, Box::new(backend), None)
+            .unwrap();
+        // then — selector is visible and pr_commits is in newest-first order.
+        assert!(app.show_commit_selector, "selector should be visible");
+        assert_eq!(app.pr_commits.len(), 3);
+        assert_eq!(app.pr_commits[0].summary, "third");
+        assert_eq!(app.review_commits.len(), 3);
+        // and — default selection covers all commits.
+        assert_eq!(app.commit_selection_range, Some((0, 2)));
+    }
+
+    #[test]
+    fn should_hide_inline_selector_for_single_commit_pr() {
+        // given a PR with exactly one commit
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        let summary = sample_pr(42, "solo");
+        app.pr_tab
+            .apply_initial_load(Ok((vec![summary.clone()], false)));
+        let mut backend = FakeForgeBackend::open_pr_details(
+            test_pr_details(42, "solo"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        );
+        backend.commits = vec![sample_pr_commit("aaaaaaa1111", "only")];
+        // when
+        app.open_pr_with_backend(&summary, Box::new(backend), None)
+            .unwrap();
+        // then
+        assert!(!app.show_commit_selector);
+        assert!(app.commit_list.is_empty());
+        assert_eq!(app.commit_selection_range, None);
+    }
+
+    #[test]
+    fn should_resolve_pr_range_to_parent_sha_and_head_sha() {
+        // given a multi-commit PR open with the middle commit selected
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        let summary = sample_pr(42, "ranges");
+        app.pr_tab
+            .apply_initial_load(Ok((vec![summary.clone()], false)));
+        let mut backend = FakeForgeBackend::open_pr_details(
+            test_pr_details(42, "ranges"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        );
+        backend.commits = vec![
+            sample_pr_commit("first11", "first"),
+            sample_pr_commit("middle2", "middle"),
+            sample_pr_commit("last333", "last"),
+        ];
+        app.open_pr_with_backend(&summary, Box::new(backend), None)
+            .unwrap();
+        // After open: pr_commits = [last, middle, first] (newest-first).
+        // Select only the middle commit (index 1).
+        app.commit_selection_range = Some((1, 1));
+        // when
+        let pair = app.pr_range_sha_pair();
+        // then — start = parent (first), end = newest selected (middle).
+        assert_eq!(pair, Some(("first11".to_string(), "middle2".to_string())));
+    }
+
+    #[test]
+    fn should_resolve_pr_range_to_pr_base_when_oldest_commit_selected() {
+        // given a multi-commit PR with only the oldest commit selected
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        let summary = sample_pr(42, "base");
+        app.pr_tab
+            .apply_initial_load(Ok((vec![summary.clone()], false)));
+        let mut backend = FakeForgeBackend::open_pr_details(
+            test_pr_details(42, "base"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        );
+        backend.commits = vec![
+            sample_pr_commit("aaa", "first"),
+            sample_pr_commit("bbb", "second"),
+        ];
+        app.open_pr_with_backend(&summary, Box::new(backend), None)
+            .unwrap();
+        // pr_commits = [second, first]. Select only the oldest (index 1).
+        app.commit_selection_range = Some((1, 1));
+        // when
+        let pair = app.pr_range_sha_pair();
+        // then — start falls back to the PR's base_sha.
+        let expected_base = test_pr_details(42, "base").base_sha;
+        assert_eq!(pair, Some((expected_base, "aaa".to_string())));
+    }
+
+    #[test]
+    fn should_warn_when_opening_closed_pr() {
+        // given
+        let mut app = build_app();
+        let summary = sample_pr(42, "old");
+        let mut details = test_pr_details(42, "old");
+        details.state = "CLOSED".to_string();
+        details.closed = true;
+        let backend = Box::new(FakeForgeBackend::open_pr_details(
+            details,
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        ));
+        // when
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // then — warning message surfaces the read-only state
+        let msg = app.message.as_ref().expect("expected warning message");
+        assert!(msg.content.contains("closed"), "got: {:?}", msg.content);
+        assert!(msg.content.contains("read-only"), "got: {:?}", msg.content);
+        // and the diff source reflects the closed state
+        if let DiffSource::PullRequest(pr) = &app.diff_source {
+            assert!(pr.is_read_only());
+            assert_eq!(pr.read_only_reason(), Some("closed"));
+        } else {
+            panic!("expected PullRequest diff source");
+        }
+    }
+
+    #[test]
+    fn should_surface_pr_open_error_into_selector_state() {
+        // given a backend that fails at get_pull_request
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        let summary = sample_pr(42, "boom");
+        app.pr_tab
+            .apply_initial_load(Ok((vec![summary.clone()], false)));
+        app.target_tab = TargetTab::PullRequests;
+        let backend = Box::new(FailingForgeBackend);
+        // when
+        let result = app.open_pr_with_backend(&summary, backend, None);
+        // then
+        assert!(result.is_err());
+        // diff source did not switch
+        assert!(matches!(app.diff_source, DiffSource::WorkingTree));
+    }
+
+    #[test]
+    fn should_route_context_expansion_to_forge_provider_in_pr_mode() {
+        // given an app in PR mode with a counting fake backend
+        let mut app = build_app();
+        let summary = sample_pr(7, "ctx");
+        let backend = Box::new(FakeForgeBackend::open_pr_details(
+            test_pr_details(7, "ctx"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        ));
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // when we ask for a context provider
+        // (we can't easily trigger a real gap expansion without setting up
+        //  the full diff state, so instead we assert by construction)
+        let provider = app.context_provider();
+        // then — explicitly: the provider is the forge variant. We probe by
+        // calling fetch with a Modified file and asserting the forge backend
+        // recorded a head-side request via its trait method. The
+        // FakeForgeBackend just returns empty; we're verifying routing.
+        let res = provider
+            .fetch_context_lines(
+                None,
+                Some(&PathBuf::from("src/lib.rs")),
+                FileStatus::Modified,
+                1,
+                3,
+            )
+            .unwrap();
+        // The fake forge returns empty by default — the *call* succeeded
+        // (no error from a VCS backend would have meant VCS routing). The
+        // key signal: this didn't go through the VCS backend (DummyVcs
+        // doesn't implement fetch_context_lines and would have panicked).
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn should_switch_session_when_pr_head_advances_on_reload() {
+        // given an app already in PR mode at head A
+        let mut app = build_app();
+        let summary = sample_pr(42, "head-a");
+        let mut details_a = test_pr_details(42, "head-a");
+        details_a.head_sha = "aaaaaaaaaaaaaaaa".to_string();
+        let backend_a = Box::new(FakeForgeBackend::open_pr_details(
+            details_a.clone(),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        ));
+        app.open_pr_with_backend(&summary, backend_a, None).unwrap();
+        let old_session_id = app.session.id.clone();
+        // when reloading with a backend that reports head B
+        let mut details_b = details_a.clone();
+        details_b.head_sha = "bbbbbbbbbbbbbbbb".to_string();
+        details_b.title = "head-b".to_string();
+        let backend_b = Box::new(FakeForgeBackend::open_pr_details(
+            details_b.clone(),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        ));
+        let head_changed = app
+            .reload_pull_request_with_backend(backend_b, None)
+            .unwrap();
+        // then — the session swap happened
+        assert!(head_changed);
+        if let DiffSource::PullRequest(pr) = &app.diff_source {
+            assert_eq!(pr.key.head_sha, "bbbbbbbbbbbbbbbb");
+            assert_eq!(pr.title, "head-b");
+        } else {
+            panic!("expected PullRequest diff source");
+        }
+        // and the session changed (new session, not the old one)
+        assert_ne!(app.session.id, old_session_id);
+    }
+
+    #[test]
+    fn should_keep_session_when_pr_head_unchanged_on_reload() {
+        // given an app in PR mode
+        let mut app = build_app();
+        let summary = sample_pr(42, "same");
+        let details = test_pr_details(42, "same");
+        let backend = Box::new(FakeForgeBackend::open_pr_details(
+            details.clone(),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        ));
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        let session_id_before = app.session.id.clone();
+        // when reloading with the same head
+        let backend2 = Box::new(FakeForgeBackend::open_pr_details(
+            details,
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+        ));
+        let changed = app
+            .reload_pull_request_with_backend(backend2, None)
+            .unwrap();
+        // then
+        assert!(!changed);
+        assert_eq!(app.session.id, session_id_before);
+    }
+
+    struct FailingForgeBackend;
+
+    impl crate::forge::traits::ForgeBackend for FailingForgeBackend {
+        fn list_pull_requests(
+            &self,
+            _q: crate::forge::traits::PullRequestListQuery,
+        ) -> Result<crate::forge::traits::PagedPullRequests> {
+            unimplemented!()
+        }
+        fn get_pull_request(
+            &self,
+            _target: crate::forge::traits::PullRequestTarget,
+        ) -> Result<crate::forge::traits::PullRequestDetails> {
+            Err(crate::error::TuicrError::Forge(
+                "simulated network failure".to_string(),
+            ))
+        }
+        fn get_pull_request_diff(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<String> {
+            unreachable!()
+        }
+        fn fetch_file_lines(
+            &self,
+            _req: crate::forge::traits::ForgeFileLinesRequest,
+        ) -> Result<Vec<crate::model::DiffLine>> {
+            Ok(Vec::new())
+        }
+        fn list_review_threads(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<Vec<crate::forge::remote_comments::RemoteReviewThread>> {
+            Ok(Vec::new())
+        }
+        fn list_pull_request_commits(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<Vec<crate::forge::traits::PullRequestCommit>> {
+            Ok(Vec::new())
+        }
+        fn get_pull_request_commit_range_diff(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+            _start_sha: &str,
+            _end_sha: &str,
+        ) -> Result<String> {
+            unreachable!()
+        }
+        fn create_review(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+            _request: crate::forge::traits::CreateReviewRequest<'_>,
+        ) -> Result<crate::forge::traits::GhCreateReviewResponse> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn should_apply_initial_load_event_to_pr_tab() {
+        // given
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.pr_tab.start_initial_load();
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.pr_load_rx = Some(rx);
+        tx.send(PrLoadEvent::Initial(Ok((
+            vec![sample_pr(7, "lucky")],
+            false,
+        ))))
+        .unwrap();
+        drop(tx);
+        // when
+        app.poll_pr_load_events();
+        // then
+        assert!(app.pr_load_rx.is_none());
+        assert_eq!(app.pr_tab.view().rows.len(), 1);
+        assert_eq!(app.pr_tab.view().rows[0].summary.number, 7);
+    }
+
+    #[test]
+    fn should_open_pr_selector_on_prs_command() {
+        // given
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
+        app.command_buffer = "prs".to_string();
+        // when
+        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
+        // then
+        assert_eq!(app.target_tab, TargetTab::PullRequests);
+        assert_eq!(app.input_mode, InputMode::CommitSelect);
+        // Cancel the background fetch handle to avoid surprising real `gh` calls.
+        app.pr_load_rx = None;
+    }
+
+    #[test]
+    fn should_open_local_selector_on_targets_command() {
+        // given
+        let mut app = build_app_with_commits(vec![dummy_commit("abc")]);
+        app.command_buffer = "targets".to_string();
+        // when
+        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
+        // then
+        assert_eq!(app.target_tab, TargetTab::Local);
+        assert_eq!(app.input_mode, InputMode::CommitSelect);
+    }
+
+    #[test]
+    fn should_treat_commits_as_alias_for_local_target_selector() {
+        // given
+        let mut app = build_app_with_commits(vec![dummy_commit("abc")]);
+        app.command_buffer = "commits".to_string();
+        // when
+        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
+        // then
+        assert_eq!(app.target_tab, TargetTab::Local);
+        assert_eq!(app.input_mode, InputMode::CommitSelect);
+    }
+
+    // -- async PR open spinner tests -----------------------------------------
+
+    fn loaded_pr_tab(pr_list: Vec<PullRequestSummary>) -> PullRequestsTab {
+        let mut tab = PullRequestsTab::new(Some(ForgeRepository::github(
+            "github.com",
+            "agavra",
+            "tuicr",
+        )));
+        tab.start_initial_load();
+        tab.apply_initial_load(Ok((pr_list, false)));
+        tab
+    }
+
+    #[test]
+    fn should_set_pr_open_state_and_spawn_when_pressing_enter_on_a_pr_row() {
+        // given a loaded PR tab and no in-flight open
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = loaded_pr_tab(vec![sample_pr(42, "boom")]);
+        app.target_tab = TargetTab::PullRequests;
+        // when
+        let handled = app.pr_tab_select();
+        // then
+        assert!(handled);
+        assert!(app.pr_open_state.is_some());
+        let state = app.pr_open_state.as_ref().unwrap();
+        assert_eq!(state.pr_number, 42);
+        // Drop the receiver so the spawned thread's tx send is a no-op
+        // when it completes (the real `gh` call would block; this test
+        // does not wait for it).
+        app.pr_open_rx = None;
+        app.pr_open_state = None;
+    }
+
+    #[test]
+    fn should_be_a_noop_when_pressing_enter_during_an_in_flight_open() {
+        // given an in-flight open marker
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = loaded_pr_tab(vec![sample_pr(7, "ctx"), sample_pr(8, "next")]);
+        app.target_tab = TargetTab::PullRequests;
+        app.pr_open_state = Some(crate::app::PrOpenRequest {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 7,
+            started_at: std::time::Instant::now(),
+        });
+        // (no pr_open_rx is fine — the function never touches it on this path)
+        // when — Enter on a different row
+        if let crate::forge::selector::PullRequestsTab::Loaded { cursor, .. } = &mut app.pr_tab {
+            *cursor = 1;
+        }
+        let handled = app.pr_tab_select();
+        // then — handled but state unchanged (no new spawn for #8)
+        assert!(handled);
+        let state = app.pr_open_state.as_ref().unwrap();
+        assert_eq!(state.pr_number, 7);
+    }
+
+    #[test]
+    fn should_clear_pr_open_state_on_cancel() {
+        // given
+        let mut app = build_app();
+        app.pr_open_state = Some(crate::app::PrOpenRequest {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 11,
+            started_at: std::time::Instant::now(),
+        });
+        // when
+        let cancelled = app.cancel_pr_open();
+        // then
+        assert!(cancelled);
+        assert!(app.pr_open_state.is_none());
+        assert!(app.pr_open_rx.is_none());
+    }
+
+    #[test]
+    fn should_return_false_when_cancelling_with_no_in_flight_open() {
+        // given
+        let mut app = build_app();
+        // when
+        let cancelled = app.cancel_pr_open();
+        // then
+        assert!(!cancelled);
+    }
+
+    #[test]
+    fn should_surface_pr_open_error_to_message_bar_when_done_event_carries_error() {
+        // given an app waiting on a synthetic open
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = loaded_pr_tab(vec![sample_pr(42, "boom")]);
+        app.target_tab = TargetTab::PullRequests;
+        let request = crate::app::PrOpenRequest {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 42,
+            started_at: std::time::Instant::now(),
+        };
+        app.pr_open_state = Some(request.clone());
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.pr_open_rx = Some(rx);
+        tx.send(crate::app::PrOpenEvent::Done {
+            request,
+            result: Err("auth failed".to_string()),
+        })
+        .unwrap();
+        // when
+        app.poll_pr_open_events();
+        // then — open state cleared, error surfaced to message bar, PR
+        // list is intact so the user can retry / pick a different PR
+        assert!(app.pr_open_state.is_none());
+        assert!(app.pr_open_rx.is_none());
+        assert!(matches!(app.pr_tab, PullRequestsTab::Loaded { .. }));
+        let msg = app
+            .message
+            .as_ref()
+            .expect("expected an error message on the bar");
+        assert!(matches!(msg.message_type, MessageType::Error));
+        assert!(msg.content.contains("auth failed"), "got {msg:?}");
+    }
+
+    #[test]
+    fn should_ignore_stale_done_event_after_cancel() {
+        // given an open was cancelled but the thread's send arrived anyway
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = loaded_pr_tab(vec![sample_pr(42, "boom")]);
+        app.target_tab = TargetTab::PullRequests;
+        let stale_request = crate::app::PrOpenRequest {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 42,
+            started_at: std::time::Instant::now(),
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.pr_open_rx = Some(rx);
+        // pr_open_state is None — the user already cancelled.
+        tx.send(crate::app::PrOpenEvent::Done {
+            request: stale_request,
+            result: Err("would-have-failed".to_string()),
+        })
+        .unwrap();
+        // when
+        app.poll_pr_open_events();
+        // then — the stale error does not produce a user-visible message
+        assert!(matches!(app.pr_tab, PullRequestsTab::Loaded { .. }));
+        assert!(
+            app.message.is_none()
+                || !app
+                    .message
+                    .as_ref()
+                    .unwrap()
+                    .content
+                    .contains("would-have-failed")
+        );
+    }
+
+    #[test]
+    fn should_cancel_in_flight_open_when_pressing_esc_in_selector() {
+        // given
+        let mut app = build_app();
+        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
+        app.pr_tab = loaded_pr_tab(vec![sample_pr(99, "x")]);
+        app.target_tab = TargetTab::PullRequests;
+        app.pr_open_state = Some(crate::app::PrOpenRequest {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 99,
+            started_at: std::time::Instant::now(),
+        });
+        // when
+        crate::handler::handle_commit_select_action(&mut app, crate::input::Action::ExitMode);
+        // then
+        assert!(app.pr_open_state.is_none());
+    }
+
+    // -----------------------------------------------------------------
+    // Remote review threads (PR 4)
+    // -----------------------------------------------------------------
+
+    use crate::forge::remote_comments::{
+        PrCommentsVisibility, RemoteCommentSide, RemoteReviewComment, RemoteReviewThread,
+    };
+
+    struct ThreadAwareForgeBackend {
+        details: crate::forge::traits::PullRequestDetails,
+        patch: String,
+        threads: Vec<RemoteReviewThread>,
+        calls: std::cell::Cell<u32>,
+    }
+
+    impl ThreadAwareForgeBackend {
+        fn new(
+            details: crate::forge::traits::PullRequestDetails,
+            patch: String,
+            threads: Vec<RemoteReviewThread>,
+        ) -> Self {
+            Self {
+                details,
+                patch,
+                threads,
+                calls: std::cell::Cell::new(0),
+            }
+        }
+    }
+
+    impl crate::forge::traits::ForgeBackend for ThreadAwareForgeBackend {
+        fn list_pull_requests(
+            &self,
+            _q: crate::forge::traits::PullRequestListQuery,
+        ) -> Result<crate::forge::traits::PagedPullRequests> {
+            unimplemented!()
+        }
+        fn get_pull_request(
+            &self,
+            _t: crate::forge::traits::PullRequestTarget,
+        ) -> Result<crate::forge::traits::PullRequestDetails> {
+            Ok(self.details.clone())
+        }
+        fn get_pull_request_diff(
+            &self,
+            _p: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<String> {
+            Ok(self.patch.clone())
+        }
+        fn fetch_file_lines(
+            &self,
+            _r: crate::forge::traits::ForgeFileLinesRequest,
+        ) -> Result<Vec<crate::model::DiffLine>> {
+            Ok(Vec::new())
+        }
+        fn list_review_threads(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<Vec<RemoteReviewThread>> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(self.threads.clone())
+        }
+        fn list_pull_request_commits(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+        ) -> Result<Vec<crate::forge::traits::PullRequestCommit>> {
+            Ok(Vec::new())
+        }
+        fn get_pull_request_commit_range_diff(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+            _start_sha: &str,
+            _end_sha: &str,
+        ) -> Result<String> {
+            unreachable!()
+        }
+        fn create_review(
+            &self,
+            _pr: &crate::forge::traits::PullRequestDetails,
+            _request: crate::forge::traits::CreateReviewRequest<'_>,
+        ) -> Result<crate::forge::traits::GhCreateReviewResponse> {
+            unimplemented!()
+        }
+    }
+
+    fn sample_thread(line: u32, body: &str, resolved: bool, outdated: bool) -> RemoteReviewThread {
+        RemoteReviewThread {
+            id: "T".to_string(),
+            path: "src/lib.rs".to_string(),
+            line: Some(line),
+            side: RemoteCommentSide::Right,
+            is_resolved: resolved,
+            is_outdated: outdated,
+            comments: vec![RemoteReviewComment {
+                id: "C".to_string(),
+                author: Some("alice".to_string()),
+                body: body.to_string(),
+                created_at: None,
+                in_reply_to: None,
+                url: "https://example.com/c".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn should_populate_remote_threads_when_opening_pr_through_test_seam() {
+        // given
+        let mut app = build_app();
+        let summary = sample_pr(42, "answer");
+        let backend = Box::new(ThreadAwareForgeBackend::new(
+            test_pr_details(42, "answer"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+            vec![sample_thread(2, "remote body", false, false)],
+        ));
+        // when
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // then
+        assert_eq!(app.forge_review_threads.len(), 1);
+        assert_eq!(app.forge_review_threads[0].comments[0].body, "remote body");
+        // default visibility is Unresolved on a fresh PR session
+        assert_eq!(
+            app.session.remote_comments_visibility,
+            PrCommentsVisibility::Unresolved
+        );
+    }
+
+    #[test]
+    fn should_clear_remote_threads_without_refetch_when_setting_visibility_hide() {
+        // given a PR open with one fetched thread
+        let mut app = build_app();
+        let summary = sample_pr(42, "answer");
+        let backend = Box::new(ThreadAwareForgeBackend::new(
+            test_pr_details(42, "answer"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+            vec![sample_thread(2, "remote", false, false)],
+        ));
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        assert_eq!(app.forge_review_threads.len(), 1);
+        // when — switch to hide
+        let changed = app.set_remote_comments_visibility(PrCommentsVisibility::Hide);
+        // then
+        assert!(changed);
+        assert_eq!(
+            app.session.remote_comments_visibility,
+            PrCommentsVisibility::Hide
+        );
+        // We don't drop the cache on visibility change — only filtering changes.
+        // Switching back to Unresolved should restore the rendered comments
+        // without making a new network call.
+        assert_eq!(app.forge_review_threads.len(), 1);
+    }
+
+    #[test]
+    fn should_route_comments_unresolved_command_through_command_handler() {
+        use crate::handler::handle_command_action;
+        use crate::input::Action;
+        // given
+        let mut app = build_app();
+        let summary = sample_pr(42, "answer");
+        let backend = Box::new(ThreadAwareForgeBackend::new(
+            test_pr_details(42, "answer"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+            vec![sample_thread(2, "remote", false, false)],
+        ));
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // when — enter command mode then submit `:comments all`
+        app.input_mode = crate::app::InputMode::Command;
+        app.command_buffer = "comments all".to_string();
+        handle_command_action(&mut app, Action::SubmitInput);
+        // then
+        assert_eq!(
+            app.session.remote_comments_visibility,
+            PrCommentsVisibility::All
+        );
+    }
+
+    #[test]
+    fn should_merge_a_session_file_and_show_the_summary() {
+        use crate::handler::handle_command_action;
+        use crate::input::Action;
+        // given
+        let mut app = build_app();
+        let dir = tempfile::tempdir().unwrap();
+        let path = PathBuf::from("src/lib.rs");
+        app.session
+            .add_file(path.clone(), crate::model::FileStatus::Modified, 1);
+        let mut theirs = app.session.clone();
+        theirs.reviewer = crate::model::Reviewer::parse("Bob");
+        theirs
+            .get_file_mut(&path)
+            .unwrap()
+            .add_file_comment(Comment::new(
+                "from bob".to_string(),
+                CommentType::Note,
+                None,
+            ));
+        let file = dir.path().join("bob.json");
+        std::fs::write(&file, serde_json::to_string(&theirs).unwrap()).unwrap();
+        // when
+        app.input_mode = crate::app::InputMode::Command;
+        app.command_buffer = format!("session merge {}", file.display());
+        handle_command_action(&mut app, Action::SubmitInput);
+        // then
+        let merged = &app.session.files[&path].file_comments[0];
+        assert_eq!(merged.reviewer.as_ref().unwrap().name, "Bob");
+        assert!(app.dirty);
+        assert_eq!(app.input_mode, crate::app::InputMode::Help);
+        let output = &app.command_output.as_ref().unwrap().output;
+        assert!(output.starts_with("Merged 1 comments from Bob (0 duplicates)"));
+        assert!(output.ends_with("No conflicts"));
+    }
+
+    #[test]
+    fn should_import_remote_threads_as_authored_session_comments() {
+        use crate::handler::handle_command_action;
+        use crate::input::Action;
+        // given
+        let mut app = build_app();
+        let summary = sample_pr(42, "answer");
+        let backend = Box::new(ThreadAwareForgeBackend::new(
+            test_pr_details(42, "answer"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+            vec![sample_thread(2, "remote", false, false)],
+        ));
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // when
+        app.input_mode = crate::app::InputMode::Command;
+        app.command_buffer = "comments import".to_string();
+        handle_command_action(&mut app, Action::SubmitInput);
+        // then
+        let review = &app.session.files[&PathBuf::from("src/lib.rs")];
+        let comment = &review.line_comments[&2][0];
+        assert_eq!(comment.content, "remote");
+        assert_eq!(comment.author.as_deref(), Some("alice"));
+        assert!(comment.is_locked());
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Imported 1 remote comments")
+        );
+        // the thread now renders as the local comment only
+        assert!(
+            !app.line_annotations
+                .iter()
+                .any(|a| matches!(a, AnnotatedLine::RemoteThreadLine { .. }))
+        );
+    }
+
+    #[test]
+    fn should_warn_when_comments_command_used_outside_pr_mode() {
+        use crate::handler::handle_command_action;
+        use crate::input::Action;
+        // given — plain local working-tree session
+        let mut app = build_app();
+        app.input_mode = crate::app::InputMode::Command;
+        app.command_buffer = "comments all".to_string();
+        // when
+        handle_command_action(&mut app, Action::SubmitInput);
+        // then — visibility unchanged, a warning surfaced on the message bar
+        assert_eq!(
+            app.session.remote_comments_visibility,
+            PrCommentsVisibility::Unresolved
+        );
+        let msg = app
+            .message
+            .as_ref()
+            .expect("expected warning on message bar");
+        assert!(matches!(msg.message_type, MessageType::Warning));
+        assert!(
+            msg.content.contains("PR mode"),
+            "got message: {}",
+            msg.content
+        );
+    }
+
+    #[test]
+    fn should_apply_remote_threads_event_when_relevant() {
+        // given a PR session is open at head=`headsha`
+        let mut app = build_app();
+        let summary = sample_pr(42, "answer");
+        let backend = Box::new(ThreadAwareForgeBackend::new(
+            test_pr_details(42, "answer"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+            // open with empty threads — we'll deliver via the channel
+            Vec::new(),
+        ));
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // simulate a background fetch that finished after open
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.pr_threads_rx = Some(rx);
+        app.forge_review_threads_loading = true;
+        let pr_key = match &app.diff_source {
+            DiffSource::PullRequest(pr) => pr.key.clone(),
+            _ => panic!("expected PR mode"),
+        };
+        tx.send(crate::app::PrThreadsEvent::Done {
+            repository: pr_key.repository.clone(),
+            pr_number: pr_key.number,
+            head_sha: pr_key.head_sha.clone(),
+            result: Ok(vec![sample_thread(2, "delayed", false, false)]),
+        })
+        .unwrap();
+        // when
+        app.poll_pr_threads_events();
+        // then
+        assert!(!app.forge_review_threads_loading);
+        assert_eq!(app.forge_review_threads.len(), 1);
+        assert_eq!(app.forge_review_threads[0].comments[0].body, "delayed");
+    }
+
+    #[test]
+    fn should_discard_stale_remote_threads_event_after_switching_pr() {
+        // given a PR open, then user switches to a different PR while a
+        // fetch is in flight
+        let mut app = build_app();
+        let summary = sample_pr(42, "answer");
+        let backend = Box::new(ThreadAwareForgeBackend::new(
+            test_pr_details(42, "answer"),
+            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+            Vec::new(),
+        ));
+        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        // simulate a stale event from a different PR head
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.pr_threads_rx = Some(rx);
+        tx.send(crate::app::PrThreadsEvent::Done {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 999,                         // wrong number
+            head_sha: "definitely-not-this".into(), // wrong head
+            result: Ok(vec![sample_thread(2, "stale", false, false)]),
+        })
+        .unwrap();
+        // when
+        app.poll_pr_threads_events();
+        // then — stale result was dropped
+        assert!(app.forge_review_threads.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod scroll_tests {
+    /// max_scroll_offset is simply total_lines - 1 (last line can be at top).
+    fn calc_max_scroll(total_lines: usize) -> usize {
+        total_lines.saturating_sub(1)
+    }
+
+    #[test]
+    fn should_calculate_max_scroll() {
+        // Last line can be scrolled to the top of the viewport
+        assert_eq!(calc_max_scroll(103), 102);
+        assert_eq!(calc_max_scroll(20), 19);
+    }
+
+    #[test]
+    fn should_handle_small_content() {
+        // Even with few lines, can scroll last line to top
+        assert_eq!(calc_max_scroll(13), 12);
+        assert_eq!(calc_max_scroll(1), 0);
+    }
+
+    #[test]
+    fn should_handle_empty_content() {
+        assert_eq!(calc_max_scroll(0), 0);
+    }
+}
+
+#[cfg(test)]
+mod scroll_behavior_tests {
+    use super::*;
+    use crate::model::FileStatus;
+    use crate::vcs::traits::VcsType;
 
-        // Collect selected entries in order from oldest to newest (end..start).
-        let selected_commits: Vec<&CommitInfo> = (start..=end)
-            .rev()
-            .filter_map(|i| self.commit_list.get(i))
-            .collect();
+    struct DummyVcs {
+        info: VcsInfo,
+    }
 
-        if selected_commits.is_empty() {
-            self.set_message("Select at least one commit");
-            return Ok(());
+    impl VcsBackend for DummyVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
         }
 
-        let selected_staged = selected_commits.iter().any(|c| Self::is_staged_commit(c));
-        let selected_unstaged = selected_commits.iter().any(|c| Self::is_unstaged_commit(c));
-        let selected_ids: Vec<String> = selected_commits
-            .iter()
-            .filter(|c| !Self::is_special_commit(c))
-            .map(|c| c.id.clone())
-            .collect();
-
-        if (selected_staged || selected_unstaged) && !selected_ids.is_empty() {
-            let all_selected: Vec<CommitInfo> = selected_commits.into_iter().cloned().collect();
-            return self.load_staged_unstaged_and_commits_selection(selected_ids, all_selected);
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
         }
 
-        if selected_staged && selected_unstaged {
-            return self.load_staged_and_unstaged_selection();
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
         }
+    }
 
-        if selected_staged {
-            return self.load_staged_selection();
-        }
+    /// Build a test App with a single file containing `n` context lines.
+    /// Total rendered lines = 1 (review header) + 1 (file header) + 1 (spacing)
+    ///                       + 1 (hunk header) + n (diff lines) = n + 4.
+    /// The viewport is set to `viewport` lines.
+    fn build_scroll_app(n: usize, viewport: usize, scroll_offset_config: usize) -> App {
+        let lines: Vec<DiffLine> = (1..=n)
+            .map(|i| DiffLine {
+                origin: crate::model::LineOrigin::Context,
+                content: format!("line {i}"),
+                old_lineno: Some(i as u32),
+                new_lineno: Some(i as u32),
+                highlighted_spans: None,
+            })
+            .collect();
 
-        if selected_unstaged {
-            return self.load_unstaged_selection();
-        }
+        let hunk = DiffHunk {
+            header: "@@ -1,N +1,N @@".to_string(),
+            lines,
+            old_start: 1,
+            old_count: n as u32,
+            new_start: 1,
+            new_count: n as u32,
+        };
 
-        // Get the diff for the selected commits
-        let highlighter = self.theme.syntax_highlighter();
-        let diff_files = Self::get_commit_range_diff_with_ignore(
-            self.vcs.as_ref(),
-            &self.vcs_info.root_path,
-            &selected_ids,
-            highlighter,
-            self.path_filter.as_deref(),
-        )?;
+        let file = DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from("test.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![hunk],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
 
-        if diff_files.is_empty() {
-            self.set_message("No changes in selected commits");
-            return Ok(());
-        }
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
 
-        // Update session with the newest commit as base
-        let newest_commit_id = selected_ids.last().unwrap().clone();
-        let loaded_session = load_latest_session_for_context(
-            &self.vcs_info.root_path,
-            self.vcs_info.branch_name.as_deref(),
-            &newest_commit_id,
-            SessionDiffSource::CommitRange,
-            Some(selected_ids.as_slice()),
+        let mut app = App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![file],
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
         )
-        .ok()
-        .and_then(|found| found.map(|(_path, session)| session));
+        .expect("failed to build test app");
 
-        let mut session = loaded_session.unwrap_or_else(|| {
-            let mut session = ReviewSession::new(
-                self.vcs_info.root_path.clone(),
-                newest_commit_id,
-                self.vcs_info.branch_name.clone(),
-                SessionDiffSource::CommitRange,
-            );
-            session.commit_range = Some(selected_ids.clone());
-            session
-        });
+        app.diff_state.viewport_height = viewport;
+        app.diff_state.visible_line_count = viewport;
+        app.scroll_offset = scroll_offset_config;
+        app
+    }
 
-        if session.commit_range.is_none() {
-            session.commit_range = Some(selected_ids.clone());
-            session.updated_at = chrono::Utc::now();
-        }
+    #[test]
+    fn should_go_to_top_of_rendered_diff_not_first_file() {
+        // given
+        let mut app = build_scroll_app(40, 20, 0);
+        app.jump_to_bottom();
 
-        self.session = session;
+        // when
+        app.go_to_top();
 
-        // Add files to session
-        for file in &diff_files {
-            let path = file.display_path().clone();
-            self.session.add_file(path, file.status, file.content_hash);
-        }
+        // then: line 0 is the review header, above the first file's header
+        assert_eq!(app.diff_state.cursor_line, 0);
+        assert_eq!(app.diff_state.scroll_offset, 0);
+        assert!(app.calculate_file_scroll_offset(0) > 0);
+    }
 
-        // Update app state
-        self.diff_files = diff_files;
-        self.diff_source = DiffSource::CommitRange(selected_ids);
-        self.input_mode = InputMode::Normal;
+    #[test]
+    fn should_go_to_percentage_of_rendered_diff() {
+        // given: 96 diff lines + 4 overhead = 100 total, 99 navigable
+        let mut app = build_scroll_app(96, 20, 0);
 
-        // Reset navigation state
-        self.diff_state = DiffState::default();
-        self.file_list_state = FileListState::default();
+        // when / then
+        app.go_to_percentage(50);
+        assert_eq!(app.diff_state.cursor_line, 49);
+        assert_eq!(app.diff_state.scroll_offset, 39);
 
-        // Set up inline commit selector for multi-commit reviews (newest-first display order)
-        self.review_commits = selected_commits
-            .iter()
-            .rev()
-            .map(|c| (*c).clone())
-            .collect();
-        self.range_diff_files = Some(self.diff_files.clone());
-        self.commit_list = self.review_commits.clone();
-        self.commit_list_cursor = 0;
-        self.commit_selection_range = if self.review_commits.is_empty() {
-            None
-        } else {
-            Some((0, self.review_commits.len() - 1))
-        };
-        self.commit_list_scroll_offset = 0;
-        self.visible_commit_count = self.review_commits.len();
-        self.has_more_commit = false;
-        self.show_commit_selector = self.review_commits.len() > 1;
-        self.commit_diff_cache.clear();
-        self.saved_inline_selection = None;
+        app.go_to_percentage(100);
+        assert_eq!(app.diff_state.cursor_line, app.max_cursor_line());
 
-        self.sort_files_by_directory(true);
-        self.expand_all_dirs();
-        self.rebuild_annotations();
+        app.go_to_percentage(250);
+        assert_eq!(app.diff_state.cursor_line, app.max_cursor_line());
+    }
 
-        Ok(())
+    #[test]
+    fn should_move_file_list_selection_when_file_list_focused() {
+        // given
+        let mut app = build_scroll_app(40, 20, 0);
+        app.focused_panel = FocusedPanel::FileList;
+        let rows = app.build_visible_items().len();
+
+        // when
+        app.go_to_bottom();
+
+        // then: only the selection moves, like j/k in the file list
+        assert_eq!(app.file_list_state.selected(), rows - 1);
+        assert_eq!(app.diff_state.cursor_line, 0);
+
+        // when
+        app.go_to_percentage(1);
+
+        // then
+        assert_eq!(app.file_list_state.selected(), 0);
     }
 
-    /// Reload the diff for the currently selected inline commit subrange.
-    pub fn reload_inline_selection(&mut self) -> Result<()> {
-        let Some((start, end)) = self.commit_selection_range else {
-            self.set_message("Select at least one commit");
-            return Ok(());
+    /// Build a test App whose file list holds one file per path.
+    fn build_file_list_app(paths: &[&str]) -> App {
+        let files = paths
+            .iter()
+            .map(|path| DiffFile {
+                old_path: None,
+                new_path: Some(PathBuf::from(path)),
+                status: FileStatus::Modified,
+                hunks: Vec::new(),
+                is_binary: false,
+                is_too_large: false,
+                is_commit_message: false,
+                content_hash: 0,
+                modes: FileModes::default(),
+                sizes: FileSizes::default(),
+            })
+            .collect();
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
         };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
+        let mut app = App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            files,
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        app.focused_panel = FocusedPanel::FileList;
+        app
+    }
 
-        // Check if all commits selected -> use cached range_diff_files
-        if start == 0
-            && end == self.review_commits.len() - 1
-            && let Some(ref files) = self.range_diff_files
-        {
-            self.diff_files = files.clone();
-            let wrap = self.diff_state.wrap_lines;
-            self.diff_state = DiffState::default();
-            self.diff_state.wrap_lines = wrap;
-            self.file_list_state = FileListState::default();
-            self.expanded_top.clear();
-            self.expanded_bottom.clear();
-            self.insert_commit_message_if_single();
-            self.sort_files_by_directory(true);
-            self.expand_all_dirs();
-            self.rebuild_annotations();
-            return Ok(());
-        }
-
-        // Check cache for this subrange
-        if let Some(files) = self.commit_diff_cache.get(&(start, end)) {
-            self.diff_files = files.clone();
-            let wrap = self.diff_state.wrap_lines;
-            self.diff_state = DiffState::default();
-            self.diff_state.wrap_lines = wrap;
-            self.file_list_state = FileListState::default();
-            self.expanded_top.clear();
-            self.expanded_bottom.clear();
-            self.insert_commit_message_if_single();
-            self.sort_files_by_directory(true);
-            self.expand_all_dirs();
-            self.rebuild_annotations();
-            return Ok(());
+    fn selected_file_path(app: &App) -> Option<PathBuf> {
+        match app.get_selected_tree_item()? {
+            FileTreeItem::File { file_idx, .. } => {
+                Some(app.diff_files[file_idx].display_path().clone())
+            }
+            FileTreeItem::Directory { .. } => None,
         }
+    }
 
-        // Load diff for selected subrange
-        let has_staged = (start..=end).any(|i| {
-            self.review_commits
-                .get(i)
-                .is_some_and(Self::is_staged_commit)
-        });
-        let has_unstaged = (start..=end).any(|i| {
-            self.review_commits
-                .get(i)
-                .is_some_and(Self::is_unstaged_commit)
-        });
-        let selected_ids: Vec<String> = (start..=end)
-            .rev() // oldest to newest
-            .filter_map(|i| self.review_commits.get(i))
-            .filter(|c| !Self::is_special_commit(c))
-            .map(|c| c.id.clone())
-            .collect();
+    #[test]
+    fn should_search_file_list_without_moving_diff() {
+        // given
+        let mut app = build_file_list_app(&["a.rs", "b_test.rs", "c.rs", "d_test.rs"]);
+        app.search_buffer = "_test".to_string();
 
-        let highlighter = self.theme.syntax_highlighter();
-        let diff_files = if (has_staged || has_unstaged) && !selected_ids.is_empty() {
-            match Self::get_working_tree_with_commits_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                &selected_ids,
-                highlighter,
-                self.path_filter.as_deref(),
-            ) {
-                Ok(files) => files,
-                Err(TuicrError::NoChanges) => Vec::new(),
-                Err(e) => return Err(e),
-            }
-        } else if has_staged && has_unstaged {
-            match Self::get_working_tree_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                highlighter,
-                self.path_filter.as_deref(),
-            ) {
-                Ok(files) => files,
-                Err(TuicrError::NoChanges) => Vec::new(),
-                Err(e) => return Err(e),
-            }
-        } else if has_staged {
-            match Self::get_staged_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                highlighter,
-                self.path_filter.as_deref(),
-            ) {
-                Ok(files) => files,
-                Err(TuicrError::NoChanges) => Vec::new(),
-                Err(e) => return Err(e),
-            }
-        } else if has_unstaged {
-            match Self::get_unstaged_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                highlighter,
-                self.path_filter.as_deref(),
-            ) {
-                Ok(files) => files,
-                Err(TuicrError::NoChanges) => Vec::new(),
-                Err(e) => return Err(e),
-            }
-        } else {
-            match Self::get_commit_range_diff_with_ignore(
-                self.vcs.as_ref(),
-                &self.vcs_info.root_path,
-                &selected_ids,
-                highlighter,
-                self.path_filter.as_deref(),
-            ) {
-                Ok(files) => files,
-                Err(TuicrError::NoChanges) => Vec::new(),
-                Err(e) => return Err(e),
-            }
-        };
-        self.commit_diff_cache
-            .insert((start, end), diff_files.clone());
-        self.diff_files = diff_files;
+        // when
+        let found = app.search_in_file_list_from_selection();
 
-        // Reset navigation, rebuild file tree + annotations
-        let wrap = self.diff_state.wrap_lines;
-        self.diff_state = DiffState::default();
-        self.diff_state.wrap_lines = wrap;
-        self.file_list_state = FileListState::default();
-        self.expanded_top.clear();
-        self.expanded_bottom.clear();
-        self.insert_commit_message_if_single();
-        self.sort_files_by_directory(true);
-        self.expand_all_dirs();
-        self.rebuild_annotations();
+        // then
+        assert!(found);
+        assert_eq!(selected_file_path(&app), Some(PathBuf::from("b_test.rs")));
+        assert_eq!(app.diff_state.current_file_idx, 0);
 
-        Ok(())
+        // when / then: n and N step between matches
+        assert!(app.search_next_in_file_list());
+        assert_eq!(selected_file_path(&app), Some(PathBuf::from("d_test.rs")));
+        assert!(!app.search_next_in_file_list());
+        assert_eq!(selected_file_path(&app), Some(PathBuf::from("d_test.rs")));
+        assert!(app.search_prev_in_file_list());
+        assert_eq!(selected_file_path(&app), Some(PathBuf::from("b_test.rs")));
     }
 
-    fn load_staged_unstaged_and_commits_selection(
-        &mut self,
-        selected_ids: Vec<String>,
-        selected_commits: Vec<CommitInfo>,
-    ) -> Result<()> {
-        let highlighter = self.theme.syntax_highlighter();
-        let diff_files = match Self::get_working_tree_with_commits_diff_with_ignore(
-            self.vcs.as_ref(),
-            &self.vcs_info.root_path,
-            &selected_ids,
-            highlighter,
-            self.path_filter.as_deref(),
-        ) {
-            Ok(diff_files) => diff_files,
-            Err(TuicrError::NoChanges) => {
-                self.set_message("No changes in selected commits + staged/unstaged");
-                return Ok(());
-            }
-            Err(e) => return Err(e),
-        };
+    #[test]
+    fn should_page_file_list_when_file_list_focused() {
+        // given
+        let paths: Vec<String> = (0..30).map(|i| format!("file{i:02}.rs")).collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let mut app = build_file_list_app(&paths);
+        app.file_list_state.viewport_height = 10;
+        app.diff_state.viewport_height = 10;
 
-        self.session =
-            Self::load_or_create_staged_unstaged_and_commits_session(&self.vcs_info, &selected_ids);
+        // when
+        crate::handler::handle_file_list_action(&mut app, crate::input::Action::HalfPageDown);
 
-        for file in &diff_files {
-            let path = file.display_path().clone();
-            self.session.add_file(path, file.status, file.content_hash);
-        }
+        // then
+        assert_eq!(app.file_list_state.selected(), 5);
 
-        self.diff_files = diff_files;
-        self.diff_source = DiffSource::StagedUnstagedAndCommits(selected_ids);
-        self.input_mode = InputMode::Normal;
-        self.diff_state = DiffState::default();
-        self.file_list_state = FileListState::default();
+        // when
+        crate::handler::handle_file_list_action(&mut app, crate::input::Action::PageDown);
+        crate::handler::handle_file_list_action(&mut app, crate::input::Action::PageDown);
+        crate::handler::handle_file_list_action(&mut app, crate::input::Action::PageDown);
 
-        // Set up inline commit selector (newest-first display order)
-        self.review_commits = selected_commits.into_iter().rev().collect();
-        self.range_diff_files = Some(self.diff_files.clone());
-        self.commit_list = self.review_commits.clone();
-        self.commit_list_cursor = 0;
-        self.commit_selection_range = if self.review_commits.is_empty() {
-            None
-        } else {
-            Some((0, self.review_commits.len() - 1))
-        };
-        self.commit_list_scroll_offset = 0;
-        self.visible_commit_count = self.review_commits.len();
-        self.has_more_commit = false;
-        self.show_commit_selector = self.review_commits.len() > 1;
-        self.commit_diff_cache.clear();
-        self.saved_inline_selection = None;
+        // then: clamped to the last row, and the diff never scrolled
+        assert_eq!(app.file_list_state.selected(), 29);
+        assert_eq!(app.diff_state.cursor_line, 0);
+        assert_eq!(app.diff_state.scroll_offset, 0);
 
-        self.insert_commit_message_if_single();
-        self.sort_files_by_directory(true);
-        self.expand_all_dirs();
-        self.rebuild_annotations();
-        Ok(())
+        // when
+        crate::handler::handle_file_list_action(&mut app, crate::input::Action::HalfPageUp);
+
+        // then
+        assert_eq!(app.file_list_state.selected(), 24);
     }
 
-    fn sort_files_by_directory(&mut self, reset_position: bool) {
-        use std::collections::BTreeMap;
-        use std::path::Path;
+    #[test]
+    fn should_report_missing_file_list_match() {
+        // given
+        let mut app = build_file_list_app(&["a.rs", "b.rs"]);
+        app.search_buffer = "zzz".to_string();
 
-        let current_path = if !reset_position {
-            self.current_file_path().cloned()
-        } else {
-            None
-        };
+        // when
+        let found = app.search_in_file_list_from_selection();
 
-        let mut dir_map: BTreeMap<String, Vec<DiffFile>> = BTreeMap::new();
-        let mut commit_msg_files: Vec<DiffFile> = Vec::new();
+        // then
+        assert!(!found);
+        assert_eq!(app.file_list_state.selected(), 0);
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("No matches for \"zzz\"")
+        );
+    }
 
-        for file in self.diff_files.drain(..) {
-            if file.is_commit_message {
-                commit_msg_files.push(file);
-                continue;
-            }
-            let path = file.display_path();
-            let dir = if let Some(parent) = path.parent() {
-                if parent == Path::new("") {
-                    ".".to_string()
-                } else {
-                    parent.to_string_lossy().to_string()
-                }
-            } else {
-                ".".to_string()
-            };
+    #[test]
+    fn zz_on_last_line_centers_cursor() {
+        // 40 diff lines + 4 overhead = 44 total. max_cursor = 42. Viewport = 20.
+        let mut app = build_scroll_app(40, 20, 5);
+        assert_eq!(app.total_lines(), 44);
+        let last = app.max_cursor_line(); // 42
 
-            dir_map.entry(dir).or_default().push(file);
-        }
+        app.diff_state.cursor_line = last;
+        app.center_cursor();
 
-        self.diff_files.extend(commit_msg_files);
-        for (_dir, files) in dir_map {
-            self.diff_files.extend(files);
-        }
+        // scroll = cursor - viewport/2 = 42 - 10 = 32
+        assert_eq!(app.diff_state.scroll_offset, 32);
+        assert_eq!(app.diff_state.cursor_line, 42);
+    }
 
-        if let Some(path) = current_path
-            && let Some(idx) = self
-                .diff_files
-                .iter()
-                .position(|f| f.display_path() == &path)
-        {
-            self.jump_to_file(idx);
-            return;
-        }
+    #[test]
+    fn after_zz_on_last_line_j_does_not_change_scroll() {
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
 
-        // Start at the overview position (review comments header)
-        // so the diff title shows total stats on launch.
-        self.diff_state.cursor_line = 0;
-        self.diff_state.scroll_offset = 0;
-        self.diff_state.current_file_idx = 0;
+        app.diff_state.cursor_line = last;
+        app.center_cursor();
+        let scroll_after_zz = app.diff_state.scroll_offset;
+
+        // Press j — cursor is already at max, and it's centered (not near bottom margin)
+        app.cursor_down(1);
+
+        assert_eq!(app.diff_state.cursor_line, last);
+        assert_eq!(
+            app.diff_state.scroll_offset, scroll_after_zz,
+            "j after zz on last line should not change scroll"
+        );
+    }
+
+    #[test]
+    fn after_zz_on_last_line_k_does_not_change_scroll() {
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
+
+        app.diff_state.cursor_line = last;
+        app.center_cursor();
+        let scroll_after_zz = app.diff_state.scroll_offset;
+
+        // Press k — cursor moves up 1, still in free zone
+        app.cursor_up(1);
+
+        assert_eq!(app.diff_state.cursor_line, last - 1);
+        assert_eq!(
+            app.diff_state.scroll_offset, scroll_after_zz,
+            "k after zz on last line should not change scroll"
+        );
     }
 
-    pub fn expand_all_dirs(&mut self) {
-        use std::path::Path;
+    #[test]
+    fn after_zz_no_oscillation_with_k_then_j() {
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
 
-        self.expanded_dirs.clear();
-        for file in &self.diff_files {
-            let path = file.display_path();
-            let mut current = path.parent();
-            while let Some(parent) = current {
-                if parent != Path::new("") {
-                    self.expanded_dirs
-                        .insert(parent.to_string_lossy().to_string());
-                }
-                current = parent.parent();
-            }
-        }
-        self.ensure_valid_tree_selection();
-    }
+        app.diff_state.cursor_line = last;
+        app.center_cursor();
+        let scroll_after_zz = app.diff_state.scroll_offset;
 
-    pub fn collapse_all_dirs(&mut self) {
-        self.expanded_dirs.clear();
-        self.ensure_valid_tree_selection();
-    }
+        // k then j should return to the same state
+        app.cursor_up(1);
+        app.cursor_down(1);
 
-    pub fn toggle_directory(&mut self, dir_path: &str) {
-        if self.expanded_dirs.contains(dir_path) {
-            self.expanded_dirs.remove(dir_path);
-            self.ensure_valid_tree_selection();
-        } else {
-            self.expanded_dirs.insert(dir_path.to_string());
-        }
+        assert_eq!(app.diff_state.cursor_line, last);
+        assert_eq!(
+            app.diff_state.scroll_offset, scroll_after_zz,
+            "k then j after zz should not cause oscillation"
+        );
     }
 
-    /// Get the line boundaries (start_line, end_line) of a gap.
-    fn gap_boundaries(&self, gap_id: &GapId) -> Option<(u32, u32)> {
-        let file = self.diff_files.get(gap_id.file_idx)?;
-        let hunk = file.hunks.get(gap_id.hunk_idx)?;
-        let prev_hunk = if gap_id.hunk_idx > 0 {
-            file.hunks.get(gap_id.hunk_idx - 1)
-        } else {
-            None
-        };
-        let (start, end) = match prev_hunk {
-            None => (1, hunk.new_start.saturating_sub(1)),
-            Some(prev) => (
-                prev.new_start + prev.new_count,
-                hunk.new_start.saturating_sub(1),
-            ),
-        };
-        if start > end {
-            None
-        } else {
-            Some((start, end))
-        }
-    }
+    #[test]
+    fn j_scrolls_one_line_at_a_time() {
+        // Viewport 20, total 44. Start at the middle and scroll down.
+        let mut app = build_scroll_app(40, 20, 5);
 
-    /// Look up an expanded context line by sequential index across top + bottom.
-    fn get_expanded_line(&self, gap_id: &GapId, idx: usize) -> Option<&DiffLine> {
-        let top = self.expanded_top.get(gap_id);
-        let top_len = top.map_or(0, |v| v.len());
-        if idx < top_len {
-            top?.get(idx)
-        } else {
-            self.expanded_bottom.get(gap_id)?.get(idx - top_len)
+        // Position cursor and scroll in steady state near the bottom margin
+        app.diff_state.cursor_line = 20;
+        app.diff_state.scroll_offset = 6;
+        // steady state: cursor at bottom margin = scroll + visible - margin - 1
+
+        // Scroll down multiple times and verify single-line increments
+        for _ in 0..10 {
+            let prev_scroll = app.diff_state.scroll_offset;
+            let prev_cursor = app.diff_state.cursor_line;
+            app.cursor_down(1);
+            let scroll_delta = app.diff_state.scroll_offset - prev_scroll;
+            let cursor_delta = app.diff_state.cursor_line - prev_cursor;
+            assert_eq!(cursor_delta, 1, "cursor should advance by exactly 1");
+            assert!(
+                scroll_delta <= 1,
+                "scroll should advance by at most 1, got {scroll_delta}"
+            );
         }
     }
 
-    /// Expand a gap in the given direction.
-    /// If `limit` is Some(n), expand up to n lines. If None, expand all remaining.
-    pub fn expand_gap(
-        &mut self,
-        gap_id: GapId,
-        direction: ExpandDirection,
-        limit: Option<usize>,
-    ) -> Result<()> {
-        let (gap_start, gap_end) = self
-            .gap_boundaries(&gap_id)
-            .ok_or_else(|| TuicrError::CorruptedSession(format!("Invalid gap: {:?}", gap_id)))?;
+    #[test]
+    fn j_on_last_line_near_bottom_does_not_scroll() {
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
 
-        let file = &self.diff_files[gap_id.file_idx];
-        let old_path = file.old_path.clone();
-        let new_path = file.new_path.clone();
-        let file_status = file.status;
+        // Put cursor at last line with it near the bottom of viewport
+        app.diff_state.cursor_line = last;
+        app.diff_state.scroll_offset = last.saturating_sub(19); // cursor at bottom of viewport
 
-        let top_len = self.expanded_top.get(&gap_id).map_or(0, |v| v.len()) as u32;
-        let bot_len = self.expanded_bottom.get(&gap_id).map_or(0, |v| v.len()) as u32;
+        let prev_scroll = app.diff_state.scroll_offset;
+        app.cursor_down(1);
 
-        // The unexpanded region runs from (gap_start + top_len) to (gap_end - bot_len)
-        let inner_start = gap_start + top_len;
-        let inner_end = gap_end.saturating_sub(bot_len);
+        assert_eq!(app.diff_state.cursor_line, last);
+        assert_eq!(
+            app.diff_state.scroll_offset, prev_scroll,
+            "j on last line should never scroll the view"
+        );
+    }
 
-        if inner_start > inner_end {
-            return Ok(()); // Fully expanded
-        }
+    #[test]
+    fn j_on_last_line_centered_does_not_scroll() {
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
 
-        let fetch = |start: u32, end: u32| -> Result<Vec<DiffLine>> {
-            self.context_provider().fetch_context_lines(
-                old_path.as_ref(),
-                new_path.as_ref(),
-                file_status,
-                start,
-                end,
-            )
-        };
+        // Center cursor on last line
+        app.diff_state.cursor_line = last;
+        app.center_cursor();
+        let scroll_after_center = app.diff_state.scroll_offset;
 
-        match direction {
-            ExpandDirection::Down => {
-                let n = limit.unwrap_or(usize::MAX) as u32;
-                let fetch_end = inner_start.saturating_add(n - 1).min(inner_end);
-                let new_lines = fetch(inner_start, fetch_end)?;
-                self.expanded_top
-                    .entry(gap_id.clone())
-                    .or_default()
-                    .extend(new_lines);
-            }
-            ExpandDirection::Up => {
-                let n = limit.unwrap_or(usize::MAX) as u32;
-                let fetch_start = inner_end.saturating_sub(n - 1).max(inner_start);
-                let new_lines = fetch(fetch_start, inner_end)?;
-                // Prepend: new lines go before existing bottom lines
-                let existing = self.expanded_bottom.remove(&gap_id).unwrap_or_default();
-                let mut combined = new_lines;
-                combined.extend(existing);
-                self.expanded_bottom.insert(gap_id.clone(), combined);
-            }
-            ExpandDirection::Both => {
-                // Fetch everything remaining
-                let new_lines = fetch(inner_start, inner_end)?;
-                self.expanded_top
-                    .entry(gap_id.clone())
-                    .or_default()
-                    .extend(new_lines);
-            }
-        }
+        app.cursor_down(1);
 
-        self.rebuild_annotations();
-        Ok(())
+        assert_eq!(
+            app.diff_state.scroll_offset, scroll_after_center,
+            "j on last line when centered should not scroll"
+        );
     }
 
-    /// Resolve the right `ContextProvider` for the current diff source.
-    /// In PR mode (with a forge backend present), expansion goes through the
-    /// forge; otherwise it goes through the local VCS backend.
-    fn context_provider(&self) -> Box<dyn ContextProvider + '_> {
-        if let (DiffSource::PullRequest(pr), Some(backend)) =
-            (&self.diff_source, self.forge_backend.as_ref())
-        {
-            Box::new(ForgeContextProvider {
-                forge: backend.as_ref(),
-                repository: pr.key.repository.clone(),
-                base_sha: pr.base_sha.clone(),
-                head_sha: pr.key.head_sha.clone(),
-            })
-        } else {
-            Box::new(VcsContextProvider {
-                vcs: self.vcs.as_ref(),
-            })
-        }
-    }
+    #[test]
+    fn k_reclaims_empty_space_below() {
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
 
-    /// Collapse an expanded gap
-    pub fn collapse_gap(&mut self, gap_id: GapId) {
-        self.expanded_top.remove(&gap_id);
-        self.expanded_bottom.remove(&gap_id);
-        self.rebuild_annotations();
-    }
+        // Put cursor at last line at top of view (maximum empty space below)
+        app.diff_state.cursor_line = last;
+        app.diff_state.scroll_offset = last; // only 1 line visible
 
-    /// Clear all expanded gaps (called when reloading diffs)
-    pub fn clear_expanded_gaps(&mut self) {
-        self.expanded_top.clear();
-        self.expanded_bottom.clear();
+        // Press k — should immediately reclaim space (reduce scroll)
+        app.cursor_up(1);
+
+        assert_eq!(app.diff_state.cursor_line, last - 1);
+        assert!(
+            app.diff_state.scroll_offset < last,
+            "k should reclaim empty space below, scroll was {} expected less than {}",
+            app.diff_state.scroll_offset,
+            last
+        );
     }
 
-    /// Rebuild the line annotations cache. Call this when:
-    /// - Diff files change (load/reload)
-    /// - Expansion state changes (expand/collapse gap)
-    /// - Comments are added/removed
-    /// - Diff view mode changes
-    pub fn rebuild_annotations(&mut self) {
-        self.line_annotations.clear();
+    #[test]
+    fn max_scroll_allows_last_line_at_top() {
+        let app = build_scroll_app(40, 20, 5);
+        let total = app.total_lines();
 
-        // Pre-index remote threads by (path, line, side) for quick lookup
-        // during the file/hunk walk. Threads whose visibility is
-        // suppressed don't appear in this map at all, so no annotations
-        // are emitted for them.
-        let remote_index = self.build_remote_thread_index();
+        assert_eq!(
+            app.max_scroll_offset(),
+            total - 1,
+            "max scroll should allow last line at top of viewport"
+        );
+    }
+
+    #[test]
+    fn smooth_scroll_to_end_no_jumps() {
+        // Start at beginning, scroll all the way to the end with j presses
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
 
-        self.line_annotations
-            .push(AnnotatedLine::ReviewCommentsHeader);
-        for (comment_idx, comment) in self.session.review_comments.iter().enumerate() {
-            let comment_lines = Self::comment_display_lines(comment);
-            for _ in 0..comment_lines {
-                self.line_annotations
-                    .push(AnnotatedLine::ReviewComment { comment_idx });
+        app.diff_state.cursor_line = 0;
+        app.diff_state.scroll_offset = 0;
+
+        let mut max_scroll_delta = 0;
+        for _ in 0..last {
+            let prev_scroll = app.diff_state.scroll_offset;
+            app.cursor_down(1);
+            let delta = app.diff_state.scroll_offset.saturating_sub(prev_scroll);
+            if delta > max_scroll_delta {
+                max_scroll_delta = delta;
             }
         }
 
-        for (file_idx, file) in self.diff_files.iter().enumerate() {
-            let path = file.display_path();
+        assert_eq!(app.diff_state.cursor_line, last);
+        assert!(
+            max_scroll_delta <= 1,
+            "scroll should never jump more than 1 line at a time, max was {max_scroll_delta}"
+        );
+    }
 
-            // File header
-            self.line_annotations
-                .push(AnnotatedLine::FileHeader { file_idx });
+    #[test]
+    fn k_below_midpoint_only_moves_cursor() {
+        // After G, cursor is near the bottom of viewport. Pressing k should
+        // only move the cursor, not also scroll the view (which would cause
+        // a visual 2-line jump).
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
 
-            // If reviewed, skip all content for this file
-            if self.session.is_file_reviewed(path) {
-                continue;
-            }
+        // Simulate G: cursor at last line, scroll positions it at bottom
+        app.diff_state.cursor_line = last;
+        app.diff_state.scroll_offset = last.saturating_sub(19);
+        let scroll_before = app.diff_state.scroll_offset;
 
-            // File comments
-            if let Some(review) = self.session.files.get(path) {
-                for (comment_idx, comment) in review.file_comments.iter().enumerate() {
-                    let comment_lines = Self::comment_display_lines(comment);
-                    for _ in 0..comment_lines {
-                        self.line_annotations.push(AnnotatedLine::FileComment {
-                            file_idx,
-                            comment_idx,
-                        });
-                    }
-                }
-            }
+        // k should only move cursor, not scroll
+        app.cursor_up(1);
+        assert_eq!(app.diff_state.cursor_line, last - 1);
+        assert_eq!(
+            app.diff_state.scroll_offset, scroll_before,
+            "k when cursor is below midpoint should not change scroll"
+        );
+    }
 
-            if file.is_binary || file.hunks.is_empty() {
-                self.line_annotations
-                    .push(AnnotatedLine::BinaryOrEmpty { file_idx });
-            } else {
-                // Get line comments for this file
-                let line_comments = self
-                    .session
-                    .files
-                    .get(path)
-                    .map(|r| &r.line_comments)
-                    .cloned()
-                    .unwrap_or_default();
+    #[test]
+    fn no_scroll_when_last_line_visible() {
+        // When the last content line is visible, cursor should descend
+        // to it without the view scrolling (no bottom margin near EOF).
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line(); // 42
 
-                for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
-                    // Calculate gap before this hunk
-                    let prev_hunk = if hunk_idx > 0 {
-                        file.hunks.get(hunk_idx - 1)
-                    } else {
-                        None
-                    };
-                    let gap = calculate_gap(
-                        prev_hunk.map(|h| (&h.new_start, &h.new_count)),
-                        hunk.new_start,
-                    );
+        // Position so last line is visible at viewport bottom: scroll=23, shows lines 23-42
+        app.diff_state.scroll_offset = last.saturating_sub(19); // 23
+        app.diff_state.cursor_line = last - 5; // 37, viewport position 14
 
-                    let gap_id = GapId { file_idx, hunk_idx };
+        // Descend toward the last line — scroll should not change
+        for i in 0..5 {
+            let scroll_before = app.diff_state.scroll_offset;
+            app.cursor_down(1);
+            assert_eq!(
+                app.diff_state.scroll_offset, scroll_before,
+                "scroll should not change on step {i} (cursor near EOF with last line visible)"
+            );
+        }
+        assert_eq!(app.diff_state.cursor_line, last);
+    }
 
-                    if gap > 0 {
-                        let top_len = self.expanded_top.get(&gap_id).map_or(0, |v| v.len());
-                        let bot_len = self.expanded_bottom.get(&gap_id).map_or(0, |v| v.len());
-                        let remaining = (gap as usize).saturating_sub(top_len + bot_len);
-                        let is_top_of_file = hunk_idx == 0;
+    #[test]
+    fn cursor_cannot_go_past_last_content_line() {
+        let mut app = build_scroll_app(40, 20, 5);
+        let last = app.max_cursor_line();
+        let total = app.total_lines();
 
-                        // Sequential line_idx counter across top + bottom
-                        let mut ctx_idx = 0;
+        // max_cursor should be strictly less than total_lines - 1
+        // (total-1 is the trailing Spacing line)
+        assert_eq!(last, total - 2);
 
-                        // --- Top expanded lines (↓ direction) ---
-                        for _ in 0..top_len {
-                            self.line_annotations.push(AnnotatedLine::ExpandedContext {
-                                gap_id: gap_id.clone(),
-                                line_idx: ctx_idx,
-                            });
-                            ctx_idx += 1;
-                        }
+        // cursor_down from last line should not advance
+        app.diff_state.cursor_line = last;
+        app.cursor_down(1);
+        assert_eq!(app.diff_state.cursor_line, last);
+    }
 
-                        // --- Expanders / hidden lines ---
-                        if remaining > 0 {
-                            if is_top_of_file {
-                                // Top-of-file: HiddenLines (if > batch) + ↑
-                                if remaining > GAP_EXPAND_BATCH {
-                                    self.line_annotations.push(AnnotatedLine::HiddenLines {
-                                        gap_id: gap_id.clone(),
-                                        count: remaining,
-                                    });
-                                }
-                                self.line_annotations.push(AnnotatedLine::Expander {
-                                    gap_id: gap_id.clone(),
-                                    direction: ExpandDirection::Up,
-                                });
-                            } else if remaining >= GAP_EXPAND_BATCH {
-                                // Between-hunk, large: ↓ + HiddenLines + ↑
-                                self.line_annotations.push(AnnotatedLine::Expander {
-                                    gap_id: gap_id.clone(),
-                                    direction: ExpandDirection::Down,
-                                });
-                                self.line_annotations.push(AnnotatedLine::HiddenLines {
-                                    gap_id: gap_id.clone(),
-                                    count: remaining,
-                                });
-                                self.line_annotations.push(AnnotatedLine::Expander {
-                                    gap_id: gap_id.clone(),
-                                    direction: ExpandDirection::Up,
-                                });
-                            } else {
-                                // Between-hunk, small: merged ↕
-                                self.line_annotations.push(AnnotatedLine::Expander {
-                                    gap_id: gap_id.clone(),
-                                    direction: ExpandDirection::Both,
-                                });
-                            }
-                        }
+    #[test]
+    fn effective_scroll_margin_prevents_oscillation() {
+        // With viewport 21 (odd), margin should be at most 9 (= 21/2 - 1 = 9)
+        // so that after centering at position 10 (= 21/2), there's free space
+        let state = DiffState {
+            visible_line_count: 21,
+            viewport_height: 21,
+            ..DiffState::default()
+        };
+        let margin = state.effective_scroll_margin(100);
+        assert!(
+            margin < 21 / 2,
+            "margin ({margin}) must be strictly less than half viewport ({})",
+            21 / 2
+        );
+    }
 
-                        // --- Bottom expanded lines (↑ direction) ---
-                        for _ in 0..bot_len {
-                            self.line_annotations.push(AnnotatedLine::ExpandedContext {
-                                gap_id: gap_id.clone(),
-                                line_idx: ctx_idx,
-                            });
-                            ctx_idx += 1;
-                        }
-                    }
+    #[test]
+    fn scroll_offset_zero_means_no_margin() {
+        // When scroll_offset is 0, effective margin should be 0 (no margin at file start)
+        let state = DiffState {
+            visible_line_count: 20,
+            viewport_height: 20,
+            ..DiffState::default()
+        };
+        let margin = state.effective_scroll_margin(0);
+        assert_eq!(margin, 0, "margin should be 0 when scroll_offset is 0");
+    }
+}
 
-                    // Hunk header
-                    self.line_annotations
-                        .push(AnnotatedLine::HunkHeader { file_idx, hunk_idx });
+#[cfg(test)]
+mod find_source_line_tests {
+    use super::*;
 
-                    // Diff lines - handle differently based on view mode
-                    match self.diff_view_mode {
-                        DiffViewMode::Unified => {
-                            Self::build_unified_diff_annotations(
-                                &mut self.line_annotations,
-                                file_idx,
-                                hunk_idx,
-                                &hunk.lines,
-                                &line_comments,
-                                path,
-                                &self.forge_review_threads,
-                                &remote_index,
-                            );
-                        }
-                        DiffViewMode::SideBySide => {
-                            Self::build_side_by_side_annotations(
-                                &mut self.line_annotations,
-                                file_idx,
-                                hunk_idx,
-                                &hunk.lines,
-                                &line_comments,
-                                path,
-                                &self.forge_review_threads,
-                                &remote_index,
-                            );
-                        }
-                    }
-                }
-            }
+    fn make_diff_line(file_idx: usize, new_lineno: Option<u32>) -> AnnotatedLine {
+        AnnotatedLine::DiffLine {
+            file_idx,
+            hunk_idx: 0,
+            line_idx: 0,
+            old_lineno: None,
+            new_lineno,
+        }
+    }
 
-            // Spacing line
-            self.line_annotations.push(AnnotatedLine::Spacing);
+    fn make_diff_line_with_old(
+        file_idx: usize,
+        old_lineno: Option<u32>,
+        new_lineno: Option<u32>,
+    ) -> AnnotatedLine {
+        AnnotatedLine::DiffLine {
+            file_idx,
+            hunk_idx: 0,
+            line_idx: 0,
+            old_lineno,
+            new_lineno,
         }
     }
 
-    fn push_comments(
-        annotations: &mut Vec<AnnotatedLine>,
-        file_idx: usize,
-        line_no: Option<u32>,
-        line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
-        side: LineSide,
-    ) {
-        let Some(ln) = line_no else {
-            return;
-        };
+    fn make_sbs_line(file_idx: usize, new_lineno: Option<u32>) -> AnnotatedLine {
+        AnnotatedLine::SideBySideLine {
+            file_idx,
+            hunk_idx: 0,
+            del_line_idx: None,
+            add_line_idx: None,
+            old_lineno: None,
+            new_lineno,
+        }
+    }
 
-        let Some(comments) = line_comments.get(&ln) else {
-            return;
-        };
+    #[test]
+    fn should_find_exact_match() {
+        let annotations = vec![
+            AnnotatedLine::FileHeader { file_idx: 0 },
+            make_diff_line(0, Some(10)),
+            make_diff_line(0, Some(11)),
+            make_diff_line(0, Some(12)),
+        ];
 
-        for (idx, comment) in comments.iter().enumerate() {
-            let matches_side =
-                comment.side == Some(side) || (side == LineSide::New && comment.side.is_none());
+        let result = find_source_line(&annotations, 0, 11, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Exact(2));
+    }
 
-            if !matches_side {
-                continue;
-            }
+    #[test]
+    fn should_find_nearest_when_no_exact_match() {
+        let annotations = vec![
+            make_diff_line(0, Some(10)),
+            make_diff_line(0, Some(15)),
+            make_diff_line(0, Some(20)),
+        ];
 
-            let comment_lines = Self::comment_display_lines(comment);
-            for _ in 0..comment_lines {
-                annotations.push(AnnotatedLine::LineComment {
-                    file_idx,
-                    line: ln,
-                    comment_idx: idx,
-                    side,
-                });
-            }
-        }
+        // Target 12 is closest to line 10 (dist=2) vs 15 (dist=3) vs 20 (dist=8)
+        let result = find_source_line(&annotations, 0, 12, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Nearest(0));
     }
 
-    /// Per-file map of `(line, side)` -> indices into `forge_review_threads`.
-    /// Sides use the `RemoteCommentSide` mapping: `Right` -> `LineSide::New`,
-    /// `Left` -> `LineSide::Old`.
-    fn build_remote_thread_index(&self) -> RemoteThreadIndex {
-        use crate::forge::remote_comments::RemoteCommentSide;
-        let mut by_file: std::collections::HashMap<
-            String,
-            std::collections::HashMap<(u32, LineSide), Vec<usize>>,
-        > = std::collections::HashMap::new();
-        let visibility = self.session.remote_comments_visibility;
-
-        for (thread_idx, thread) in self.forge_review_threads.iter().enumerate() {
-            if visibility.render_decision(thread).is_none() {
-                continue;
-            }
-            let Some(line) = thread.line else { continue };
-            let side = match thread.side {
-                RemoteCommentSide::Right => LineSide::New,
-                RemoteCommentSide::Left => LineSide::Old,
-            };
-            by_file
-                .entry(thread.path.clone())
-                .or_default()
-                .entry((line, side))
-                .or_default()
-                .push(thread_idx);
-        }
+    #[test]
+    fn should_find_nearest_above_target() {
+        let annotations = vec![
+            make_diff_line(0, Some(10)),
+            make_diff_line(0, Some(15)),
+            make_diff_line(0, Some(20)),
+        ];
 
-        RemoteThreadIndex { by_file }
+        // Target 18 is closest to line 20 (dist=2) vs 15 (dist=3) vs 10 (dist=8)
+        let result = find_source_line(&annotations, 0, 18, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Nearest(2));
     }
 
-    fn push_remote_threads(
-        annotations: &mut Vec<AnnotatedLine>,
-        threads: &[crate::forge::remote_comments::RemoteReviewThread],
-        index: &RemoteThreadIndex,
-        path: &std::path::Path,
-        line: u32,
-        side: LineSide,
-    ) {
-        let Some(file_index) = index.by_file.get(path.to_string_lossy().as_ref()) else {
-            return;
-        };
-        let Some(thread_indices) = file_index.get(&(line, side)) else {
-            return;
-        };
-        for thread_idx in thread_indices {
-            if let Some(thread) = threads.get(*thread_idx) {
-                let n = crate::forge::remote_comments::thread_display_lines(thread);
-                for _ in 0..n {
-                    annotations.push(AnnotatedLine::RemoteThreadLine {
-                        thread_idx: *thread_idx,
-                    });
-                }
-            }
-        }
+    #[test]
+    fn should_return_not_found_for_empty_annotations() {
+        let annotations: Vec<AnnotatedLine> = vec![];
+        let result = find_source_line(&annotations, 0, 42, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::NotFound);
     }
 
-    /// Build annotations for unified diff mode (one annotation per diff line)
-    #[allow(clippy::too_many_arguments)]
-    fn build_unified_diff_annotations(
-        annotations: &mut Vec<AnnotatedLine>,
-        file_idx: usize,
-        hunk_idx: usize,
-        lines: &[crate::model::DiffLine],
-        line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
-        path: &std::path::Path,
-        remote_threads: &[crate::forge::remote_comments::RemoteReviewThread],
-        remote_index: &RemoteThreadIndex,
-    ) {
-        for (line_idx, diff_line) in lines.iter().enumerate() {
-            annotations.push(AnnotatedLine::DiffLine {
-                file_idx,
-                hunk_idx,
-                line_idx,
-                old_lineno: diff_line.old_lineno,
-                new_lineno: diff_line.new_lineno,
-            });
-
-            // Line comments on old side (delete lines)
-            if let Some(old_ln) = diff_line.old_lineno {
-                Self::push_comments(
-                    annotations,
-                    file_idx,
-                    Some(old_ln),
-                    line_comments,
-                    LineSide::Old,
-                );
-                Self::push_remote_threads(
-                    annotations,
-                    remote_threads,
-                    remote_index,
-                    path,
-                    old_ln,
-                    LineSide::Old,
-                );
-            }
+    #[test]
+    fn should_return_not_found_when_no_lines_in_current_file() {
+        let annotations = vec![make_diff_line(1, Some(10)), make_diff_line(1, Some(20))];
 
-            // Line comments on new side (added/context lines)
-            if let Some(new_ln) = diff_line.new_lineno {
-                Self::push_comments(
-                    annotations,
-                    file_idx,
-                    Some(new_ln),
-                    line_comments,
-                    LineSide::New,
-                );
-                Self::push_remote_threads(
-                    annotations,
-                    remote_threads,
-                    remote_index,
-                    path,
-                    new_ln,
-                    LineSide::New,
-                );
-            }
-        }
+        // File 0 has no lines
+        let result = find_source_line(&annotations, 0, 10, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::NotFound);
     }
 
-    /// Build annotations for side-by-side diff mode, pairing deletions and additions into aligned rows.
-    #[allow(clippy::too_many_arguments)]
-    fn build_side_by_side_annotations(
-        annotations: &mut Vec<AnnotatedLine>,
-        file_idx: usize,
-        hunk_idx: usize,
-        lines: &[crate::model::DiffLine],
-        line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
-        path: &std::path::Path,
-        remote_threads: &[crate::forge::remote_comments::RemoteReviewThread],
-        remote_index: &RemoteThreadIndex,
-    ) {
-        let mut i = 0;
-        while i < lines.len() {
-            let diff_line = &lines[i];
+    #[test]
+    fn should_skip_lines_from_other_files() {
+        let annotations = vec![
+            make_diff_line(0, Some(100)), // file 0, line 100
+            make_diff_line(1, Some(42)),  // file 1, exact match but wrong file
+            make_diff_line(0, Some(50)),  // file 0, line 50
+        ];
 
-            match diff_line.origin {
-                LineOrigin::Context => {
-                    annotations.push(AnnotatedLine::SideBySideLine {
-                        file_idx,
-                        hunk_idx,
-                        del_line_idx: Some(i),
-                        add_line_idx: Some(i),
-                        old_lineno: diff_line.old_lineno,
-                        new_lineno: diff_line.new_lineno,
-                    });
+        // Searching file 0 for line 42 — should find nearest (50, dist=8) not file 1's exact match
+        let result = find_source_line(&annotations, 0, 42, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Nearest(2));
+    }
 
-                    Self::push_comments(
-                        annotations,
-                        file_idx,
-                        diff_line.new_lineno,
-                        line_comments,
-                        LineSide::New,
-                    );
-                    if let Some(new_ln) = diff_line.new_lineno {
-                        Self::push_remote_threads(
-                            annotations,
-                            remote_threads,
-                            remote_index,
-                            path,
-                            new_ln,
-                            LineSide::New,
-                        );
-                    }
+    #[test]
+    fn should_skip_non_diff_line_annotations() {
+        let annotations = vec![
+            AnnotatedLine::FileHeader { file_idx: 0 },
+            AnnotatedLine::HunkHeader {
+                file_idx: 0,
+                hunk_idx: 0,
+            },
+            AnnotatedLine::Spacing,
+            make_diff_line(0, Some(42)),
+        ];
 
-                    i += 1
-                }
+        let result = find_source_line(&annotations, 0, 42, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Exact(3));
+    }
 
-                LineOrigin::Deletion => {
-                    // Find consecutive deletions
-                    let del_start = i;
-                    let mut del_end = i + 1;
-                    while del_end < lines.len() && lines[del_end].origin == LineOrigin::Deletion {
-                        del_end += 1;
-                    }
+    #[test]
+    fn should_skip_diff_lines_with_no_new_lineno() {
+        // Deletion-only lines have new_lineno = None
+        let annotations = vec![make_diff_line(0, None), make_diff_line(0, Some(20))];
 
-                    // Find consecutive additions following deletions
-                    let add_start = del_end;
-                    let mut add_end = add_start;
-                    while add_end < lines.len() && lines[add_end].origin == LineOrigin::Addition {
-                        add_end += 1;
-                    }
+        let result = find_source_line(&annotations, 0, 5, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Nearest(1));
+    }
 
-                    let del_count = del_end - del_start;
-                    let add_count = add_end - add_start;
-                    let max_lines = del_count.max(add_count);
+    #[test]
+    fn should_work_with_side_by_side_lines() {
+        let annotations = vec![
+            make_sbs_line(0, Some(10)),
+            make_sbs_line(0, Some(20)),
+            make_sbs_line(0, Some(30)),
+        ];
 
-                    for offset in 0..max_lines {
-                        let del_idx = if offset < del_count {
-                            Some(del_start + offset)
-                        } else {
-                            None
-                        };
-                        let add_idx = if offset < add_count {
-                            Some(add_start + offset)
-                        } else {
-                            None
-                        };
+        let result = find_source_line(&annotations, 0, 20, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Exact(1));
+    }
 
-                        let old_lineno = del_idx.and_then(|idx| lines[idx].old_lineno);
-                        let new_lineno = add_idx.and_then(|idx| lines[idx].new_lineno);
+    #[test]
+    fn should_handle_mixed_diff_and_sbs_lines() {
+        let annotations = vec![
+            make_diff_line(0, Some(10)),
+            make_sbs_line(0, Some(20)),
+            make_diff_line(0, Some(30)),
+        ];
 
-                        annotations.push(AnnotatedLine::SideBySideLine {
-                            file_idx,
-                            hunk_idx,
-                            del_line_idx: del_idx,
-                            add_line_idx: add_idx,
-                            old_lineno,
-                            new_lineno,
-                        });
+        let result = find_source_line(&annotations, 0, 25, LineSide::New);
+        // Nearest is line 20 (dist=5) or line 30 (dist=5), first match wins
+        assert_eq!(result, FindSourceLineResult::Nearest(1));
+    }
 
-                        Self::push_comments(
-                            annotations,
-                            file_idx,
-                            old_lineno,
-                            line_comments,
-                            LineSide::Old,
-                        );
-                        if let Some(old_ln) = old_lineno {
-                            Self::push_remote_threads(
-                                annotations,
-                                remote_threads,
-                                remote_index,
-                                path,
-                                old_ln,
-                                LineSide::Old,
-                            );
-                        }
-                        Self::push_comments(
-                            annotations,
-                            file_idx,
-                            new_lineno,
-                            line_comments,
-                            LineSide::New,
-                        );
-                        if let Some(new_ln) = new_lineno {
-                            Self::push_remote_threads(
-                                annotations,
-                                remote_threads,
-                                remote_index,
-                                path,
-                                new_ln,
-                                LineSide::New,
-                            );
-                        }
-                    }
+    #[test]
+    fn should_return_not_found_when_only_non_line_annotations() {
+        let annotations = vec![
+            AnnotatedLine::FileHeader { file_idx: 0 },
+            AnnotatedLine::Spacing,
+            AnnotatedLine::HunkHeader {
+                file_idx: 0,
+                hunk_idx: 0,
+            },
+        ];
 
-                    i = add_end;
-                }
-                LineOrigin::Addition => {
-                    annotations.push(AnnotatedLine::SideBySideLine {
-                        file_idx,
-                        hunk_idx,
-                        del_line_idx: None,
-                        add_line_idx: Some(i),
-                        old_lineno: None,
-                        new_lineno: diff_line.new_lineno,
-                    });
+        let result = find_source_line(&annotations, 0, 42, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::NotFound);
+    }
 
-                    Self::push_comments(
-                        annotations,
-                        file_idx,
-                        diff_line.new_lineno,
-                        line_comments,
-                        LineSide::New,
-                    );
-                    if let Some(new_ln) = diff_line.new_lineno {
-                        Self::push_remote_threads(
-                            annotations,
-                            remote_threads,
-                            remote_index,
-                            path,
-                            new_ln,
-                            LineSide::New,
-                        );
-                    }
+    #[test]
+    fn should_prefer_exact_match_over_earlier_nearest() {
+        let annotations = vec![
+            make_diff_line(0, Some(41)), // dist=1 from target 42
+            make_diff_line(0, Some(42)), // exact match
+            make_diff_line(0, Some(43)), // dist=1 from target 42
+        ];
 
-                    i += 1;
-                }
-            }
-        }
+        let result = find_source_line(&annotations, 0, 42, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Exact(1));
     }
 
-    /// What the cursor is on in a gap region
-    pub fn get_gap_at_cursor(&self) -> Option<GapCursorHit> {
-        let target = self.diff_state.cursor_line;
-        match self.line_annotations.get(target) {
-            Some(AnnotatedLine::Expander { gap_id, direction }) => {
-                Some(GapCursorHit::Expander(gap_id.clone(), *direction))
-            }
-            Some(AnnotatedLine::HiddenLines { gap_id, .. }) => {
-                Some(GapCursorHit::HiddenLines(gap_id.clone()))
-            }
-            Some(AnnotatedLine::ExpandedContext { gap_id, .. }) => {
-                Some(GapCursorHit::ExpandedContent(gap_id.clone()))
-            }
-            _ => None,
-        }
+    #[test]
+    fn should_find_nearest_for_target_zero() {
+        // target_lineno = 0 is out-of-range (lines are 1-indexed) but should
+        // still return the nearest line rather than panicking.
+        let annotations = vec![make_diff_line(0, Some(1)), make_diff_line(0, Some(5))];
+
+        let result = find_source_line(&annotations, 0, 0, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Nearest(0));
     }
 
-    fn ensure_valid_tree_selection(&mut self) {
-        use std::path::Path;
+    #[test]
+    fn should_tie_break_nearest_by_iteration_order() {
+        // When two lines are equidistant, the first one encountered wins.
+        // Here lines are in descending order; line 30 (idx 0) and line 10 (idx 2)
+        // are both dist=10 from target 20, so idx 0 should win.
+        let annotations = vec![
+            make_diff_line(0, Some(30)),
+            make_diff_line(0, Some(50)),
+            make_diff_line(0, Some(10)),
+        ];
 
-        let visible_items = self.build_visible_items();
-        if visible_items.is_empty() {
-            self.file_list_state.select(0);
-            return;
-        }
+        let result = find_source_line(&annotations, 0, 20, LineSide::New);
+        assert_eq!(result, FindSourceLineResult::Nearest(0));
+    }
 
-        let current_file_idx = self.diff_state.current_file_idx;
-        let file_visible = visible_items.iter().any(|item| {
-            matches!(item, FileTreeItem::File { file_idx, .. } if *file_idx == current_file_idx)
-        });
+    #[test]
+    fn should_match_old_lineno_when_side_is_old() {
+        // Deletion-only lines carry old_lineno but no new_lineno. `:o<n>`
+        // must match those.
+        let annotations = vec![
+            make_diff_line_with_old(0, Some(5), None),
+            make_diff_line_with_old(0, Some(10), None),
+            make_diff_line(0, Some(50)), // new-side line — should be ignored when side=Old
+        ];
 
-        if file_visible {
-            if let Some(tree_idx) = self.file_idx_to_tree_idx(current_file_idx) {
-                self.file_list_state.select(tree_idx);
-            }
-        } else {
-            if let Some(file) = self.diff_files.get(current_file_idx) {
-                let file_path = file.display_path();
-                let mut current = file_path.parent();
-                while let Some(parent) = current {
-                    if parent != Path::new("") {
-                        let parent_str = parent.to_string_lossy().to_string();
-                        for (tree_idx, item) in visible_items.iter().enumerate() {
-                            if let FileTreeItem::Directory { path, .. } = item
-                                && *path == parent_str
-                            {
-                                self.file_list_state.select(tree_idx);
-                                return;
-                            }
-                        }
-                    }
-                    current = parent.parent();
-                }
-            }
-            self.file_list_state.select(0);
-        }
+        let exact = find_source_line(&annotations, 0, 10, LineSide::Old);
+        assert_eq!(exact, FindSourceLineResult::Exact(1));
+
+        let nearest = find_source_line(&annotations, 0, 7, LineSide::Old);
+        assert_eq!(nearest, FindSourceLineResult::Nearest(0));
+    }
+
+    #[test]
+    fn should_not_match_new_lineno_when_side_is_old() {
+        // A pure-addition line has no old_lineno; searching old-side should
+        // not fall back to its new_lineno.
+        let annotations = vec![make_diff_line(0, Some(42))];
+
+        let result = find_source_line(&annotations, 0, 42, LineSide::Old);
+        assert_eq!(result, FindSourceLineResult::NotFound);
     }
+}
 
-    pub fn build_visible_items(&self) -> Vec<FileTreeItem> {
-        use std::path::Path;
+#[cfg(test)]
+mod change_status_tests {
+    use std::fs;
 
-        let mut items = Vec::new();
-        let mut seen_dirs: HashSet<String> = HashSet::new();
+    use tempfile::tempdir;
 
-        for (file_idx, file) in self.diff_files.iter().enumerate() {
-            let path = file.display_path();
+    use super::*;
+    use crate::vcs::traits::VcsType;
 
-            let mut ancestors: Vec<String> = Vec::new();
-            let mut current = path.parent();
-            while let Some(parent) = current {
-                if parent != Path::new("") {
-                    ancestors.push(parent.to_string_lossy().to_string());
-                }
-                current = parent.parent();
-            }
-            ancestors.reverse();
+    struct StatusProbeMock {
+        info: VcsInfo,
+        status: VcsChangeStatus,
+        staged_files: Vec<DiffFile>,
+        unstaged_files: Vec<DiffFile>,
+    }
 
-            let mut visible = true;
-            for (depth, dir) in ancestors.iter().enumerate() {
-                if !seen_dirs.contains(dir) && visible {
-                    let expanded = self.expanded_dirs.contains(dir);
-                    items.push(FileTreeItem::Directory {
-                        path: dir.clone(),
-                        depth,
-                        expanded,
-                    });
-                    seen_dirs.insert(dir.clone());
-                }
+    impl VcsBackend for StatusProbeMock {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
 
-                if !self.expanded_dirs.contains(dir) {
-                    visible = false;
-                }
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn get_staged_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            if self.staged_files.is_empty() {
+                Err(TuicrError::NoChanges)
+            } else {
+                Ok(self.staged_files.clone())
             }
+        }
 
-            if visible {
-                items.push(FileTreeItem::File {
-                    file_idx,
-                    depth: ancestors.len(),
-                });
+        fn get_unstaged_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            if self.unstaged_files.is_empty() {
+                Err(TuicrError::NoChanges)
+            } else {
+                Ok(self.unstaged_files.clone())
             }
         }
 
-        items
-    }
+        fn get_change_status(&self) -> Result<VcsChangeStatus> {
+            Ok(self.status)
+        }
 
-    pub fn get_selected_tree_item(&self) -> Option<FileTreeItem> {
-        let visible_items = self.build_visible_items();
-        let selected_idx = self.file_list_state.selected();
-        visible_items.get(selected_idx).cloned()
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
     }
-}
-
-#[cfg(test)]
-mod tree_tests {
-    use super::*;
-    use crate::model::{DiffFile, FileStatus};
 
-    fn make_file(path: &str) -> DiffFile {
+    fn diff_file(path: &str) -> DiffFile {
         DiffFile {
             old_path: None,
             new_path: Some(PathBuf::from(path)),
             status: FileStatus::Modified,
-            hunks: vec![],
+            hunks: Vec::new(),
             is_binary: false,
             is_too_large: false,
             is_commit_message: false,
             content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         }
     }
 
-    struct TreeTestHarness {
-        diff_files: Vec<DiffFile>,
-        expanded_dirs: HashSet<String>,
+    fn mock_vcs(root_path: PathBuf) -> StatusProbeMock {
+        StatusProbeMock {
+            info: VcsInfo {
+                root_path,
+                head_commit: "HEAD".to_string(),
+                branch_name: Some("main".to_string()),
+                vcs_type: VcsType::Git,
+            },
+            status: VcsChangeStatus {
+                staged: true,
+                unstaged: true,
+            },
+            staged_files: Vec::new(),
+            unstaged_files: Vec::new(),
+        }
     }
 
-    impl TreeTestHarness {
-        fn new(paths: &[&str]) -> Self {
-            Self {
-                diff_files: paths.iter().map(|p| make_file(p)).collect(),
-                expanded_dirs: HashSet::new(),
-            }
-        }
+    #[test]
+    fn status_probe_rechecks_positive_rows_when_ignore_rules_exist() {
+        let dir = tempdir().expect("failed to create temp dir");
+        fs::write(dir.path().join(".tuicrignore"), "ignored/\n")
+            .expect("failed to write .tuicrignore");
+        let mut vcs = mock_vcs(dir.path().to_path_buf());
+        vcs.staged_files = vec![diff_file("ignored/generated.rs")];
+        vcs.unstaged_files = vec![diff_file("src/lib.rs")];
 
-        fn expand_all(&mut self) {
-            use std::path::Path;
-            for file in &self.diff_files {
-                let path = file.display_path();
-                let mut current = path.parent();
-                while let Some(parent) = current {
-                    if parent != Path::new("") {
-                        self.expanded_dirs
-                            .insert(parent.to_string_lossy().to_string());
-                    }
-                    current = parent.parent();
-                }
+        let (status, used_probe) = App::get_change_status_with_ignore(
+            &vcs,
+            dir.path(),
+            &SyntaxHighlighter::default(),
+            None,
+        )
+        .expect("failed to get change status");
+
+        assert!(used_probe);
+        assert_eq!(
+            status,
+            VcsChangeStatus {
+                staged: false,
+                unstaged: true,
             }
-        }
+        );
+    }
 
-        fn collapse_all(&mut self) {
-            self.expanded_dirs.clear();
-        }
+    #[test]
+    fn status_probe_does_not_load_diffs_without_ignore_rules() {
+        let dir = tempdir().expect("failed to create temp dir");
+        let vcs = mock_vcs(dir.path().to_path_buf());
 
-        fn toggle(&mut self, dir: &str) {
-            if self.expanded_dirs.contains(dir) {
-                self.expanded_dirs.remove(dir);
-            } else {
-                self.expanded_dirs.insert(dir.to_string());
+        let (status, used_probe) = App::get_change_status_with_ignore(
+            &vcs,
+            dir.path(),
+            &SyntaxHighlighter::default(),
+            None,
+        )
+        .expect("failed to get change status");
+
+        assert!(used_probe);
+        assert_eq!(
+            status,
+            VcsChangeStatus {
+                staged: true,
+                unstaged: true,
             }
-        }
+        );
+    }
+}
 
-        fn build_visible_items(&self) -> Vec<FileTreeItem> {
-            use std::path::Path;
-            let mut items = Vec::new();
-            let mut seen_dirs: HashSet<String> = HashSet::new();
+#[cfg(test)]
+mod expand_gap_tests {
+    use super::*;
+    use crate::model::{DiffHunk, DiffLine, FileStatus, LineOrigin};
+    use crate::vcs::traits::VcsType;
 
-            for (file_idx, file) in self.diff_files.iter().enumerate() {
-                let path = file.display_path();
-                let mut ancestors: Vec<String> = Vec::new();
-                let mut current = path.parent();
-                while let Some(parent) = current {
-                    if parent != Path::new("") {
-                        ancestors.push(parent.to_string_lossy().to_string());
-                    }
-                    current = parent.parent();
-                }
-                ancestors.reverse();
+    struct MockVcs {
+        info: VcsInfo,
+        /// Total lines available in the "file" (1-indexed)
+        total_lines: u32,
+    }
 
-                let mut visible = true;
-                for (depth, dir) in ancestors.iter().enumerate() {
-                    if !seen_dirs.contains(dir) && visible {
-                        let expanded = self.expanded_dirs.contains(dir);
-                        items.push(FileTreeItem::Directory {
-                            path: dir.clone(),
-                            depth,
-                            expanded,
-                        });
-                        seen_dirs.insert(dir.clone());
-                    }
-                    if !self.expanded_dirs.contains(dir) {
-                        visible = false;
-                    }
-                }
+    impl VcsBackend for MockVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
 
-                if visible {
-                    items.push(FileTreeItem::File {
-                        file_idx,
-                        depth: ancestors.len(),
-                    });
-                }
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            start_line: u32,
+            end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            let mut result = Vec::new();
+            for line_num in start_line..=end_line.min(self.total_lines) {
+                result.push(DiffLine {
+                    origin: LineOrigin::Context,
+                    content: format!("line {line_num}"),
+                    old_lineno: Some(line_num),
+                    new_lineno: Some(line_num),
+                    highlighted_spans: None,
+                });
             }
-            items
+            Ok(result)
         }
 
-        fn visible_file_count(&self) -> usize {
-            self.build_visible_items()
-                .iter()
-                .filter(|i| matches!(i, FileTreeItem::File { .. }))
-                .count()
+        fn fetch_deleted_file(&self, _file_path: &Path) -> Result<String> {
+            Ok((1..=self.total_lines)
+                .map(|n| format!("line {n}\n"))
+                .collect())
         }
+    }
 
-        fn visible_dir_count(&self) -> usize {
-            self.build_visible_items()
-                .iter()
-                .filter(|i| matches!(i, FileTreeItem::Directory { .. }))
-                .count()
+    fn make_hunk(new_start: u32, new_count: u32) -> DiffHunk {
+        let mut lines = Vec::new();
+        for i in 0..new_count {
+            lines.push(DiffLine {
+                origin: LineOrigin::Context,
+                content: format!("hunk line {}", new_start + i),
+                old_lineno: Some(new_start + i),
+                new_lineno: Some(new_start + i),
+                highlighted_spans: None,
+            });
+        }
+        DiffHunk {
+            header: format!("@@ -{new_start},{new_count} +{new_start},{new_count} @@"),
+            lines,
+            old_start: new_start,
+            old_count: new_count,
+            new_start,
+            new_count,
         }
     }
 
-    #[test]
-    fn test_expand_all_shows_all_files() {
-        let mut h = TreeTestHarness::new(&["src/ui/app.rs", "src/ui/help.rs", "src/main.rs"]);
-        h.expand_all();
+    fn build_app_with_files(files: Vec<DiffFile>, total_lines: u32) -> App {
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
 
-        assert_eq!(h.visible_file_count(), 3);
+        App::build(
+            Box::new(MockVcs {
+                info: vcs_info.clone(),
+                total_lines,
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            files,
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app")
+    }
+
+    fn make_file_with_hunks(path: &str, hunks: Vec<DiffHunk>) -> DiffFile {
+        let content_hash = DiffFile::compute_content_hash(&hunks);
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Modified,
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
     }
 
     #[test]
-    fn test_collapse_all_hides_all_files() {
-        let mut h = TreeTestHarness::new(&["src/ui/app.rs", "src/main.rs"]);
-        h.expand_all();
-        h.collapse_all();
+    fn should_show_every_line_of_a_deleted_file_without_hunks() {
+        // given: a deleted file whose diff carried no hunks
+        let mut file = make_file_with_hunks("gone.rs", Vec::new());
+        file.status = FileStatus::Deleted;
+        file.old_path = file.new_path.take();
+        let mut app = build_app_with_files(vec![file], 30);
+        let placeholder = app
+            .line_annotations
+            .iter()
+            .position(|a| matches!(a, AnnotatedLine::BinaryOrEmpty { .. }))
+            .expect("deleted file placeholder row");
+        app.diff_state.cursor_line = placeholder;
 
-        assert_eq!(h.visible_file_count(), 0);
-        assert_eq!(h.visible_dir_count(), 1); // only "src" visible
+        // when
+        app.show_deleted_file_at_cursor().unwrap();
+
+        // then: one hunk removing all 30 lines, each on the old side
+        let hunks = &app.diff_files[0].hunks;
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].old_start, hunks[0].old_count), (1, 30));
+        assert!(
+            hunks[0]
+                .lines
+                .iter()
+                .all(|l| l.origin == LineOrigin::Deletion)
+        );
+        assert_eq!(hunks[0].lines[29].old_lineno, Some(30));
+        assert_eq!(hunks[0].lines[29].new_lineno, None);
+        app.diff_state.cursor_line = app
+            .line_annotations
+            .iter()
+            .rposition(|a| matches!(a, AnnotatedLine::DiffLine { .. }))
+            .unwrap();
+        assert_eq!(app.get_line_at_cursor(), Some((30, LineSide::Old)));
     }
 
     #[test]
-    fn test_collapse_parent_hides_nested_dirs() {
-        let mut h = TreeTestHarness::new(&["src/ui/components/button.rs"]);
-        h.expand_all();
-        assert_eq!(h.visible_dir_count(), 3); // src, src/ui, src/ui/components
+    fn should_expand_up_from_first_hunk() {
+        // given: file with 50-line gap before first hunk (hunk starts at line 51)
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
 
-        h.toggle("src");
-        let items = h.build_visible_items();
-        assert_eq!(items.len(), 1); // only collapsed "src" dir
-        assert!(matches!(
-            &items[0],
-            FileTreeItem::Directory {
-                expanded: false,
-                ..
-            }
-        ));
+        // when: expand Up with limit 20 (reveals lines closest to hunk)
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
+            .unwrap();
+
+        // then: 20 lines expanded from the bottom of the gap (lines 31-50)
+        let content = app.expanded_bottom.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 20);
+        assert_eq!(content[0].new_lineno, Some(31));
+        assert_eq!(content[19].new_lineno, Some(50));
     }
 
     #[test]
-    fn test_root_files_always_visible() {
-        let mut h = TreeTestHarness::new(&["README.md", "Cargo.toml"]);
-        h.collapse_all();
+    fn should_expand_all_lines_with_both_direction() {
+        // given: file with 50-line gap before first hunk
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
 
-        assert_eq!(h.visible_file_count(), 2);
+        // when: expand Both (all remaining)
+        app.expand_gap(gap_id.clone(), ExpandDirection::Both, None)
+            .unwrap();
+
+        // then: all 50 lines in expanded_top
+        let content = app.expanded_top.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 50);
+        assert_eq!(content[0].new_lineno, Some(1));
+        assert_eq!(content[49].new_lineno, Some(50));
     }
 
     #[test]
-    fn test_tree_depth_correct() {
-        let mut h = TreeTestHarness::new(&["a/b/c/file.rs"]);
-        h.expand_all();
+    fn should_expand_down_from_upper_hunk() {
+        // given: file with two hunks, gap of 24 lines (6..29) between them
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(30, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
+        };
 
-        let items = h.build_visible_items();
-        assert!(matches!(&items[0], FileTreeItem::Directory { depth: 0, path, .. } if path == "a"));
-        assert!(
-            matches!(&items[1], FileTreeItem::Directory { depth: 1, path, .. } if path == "a/b")
-        );
-        assert!(
-            matches!(&items[2], FileTreeItem::Directory { depth: 2, path, .. } if path == "a/b/c")
-        );
-        assert!(matches!(&items[3], FileTreeItem::File { depth: 3, .. }));
+        // when: expand Down with limit 10
+        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(10))
+            .unwrap();
+
+        // then: 10 lines from top of gap (lines 6-15)
+        let content = app.expanded_top.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 10);
+        assert_eq!(content[0].new_lineno, Some(6));
+        assert_eq!(content[9].new_lineno, Some(15));
     }
 
     #[test]
-    fn test_toggle_expands_collapsed_dir() {
-        let mut h = TreeTestHarness::new(&["src/main.rs"]);
-        h.collapse_all();
-        assert_eq!(h.visible_file_count(), 0);
+    fn should_expand_up_from_lower_hunk() {
+        // given: file with two hunks, gap of 24 lines (6..29) between them
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(30, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
+        };
 
-        h.toggle("src");
-        assert_eq!(h.visible_file_count(), 1);
+        // when: expand Up with limit 10
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(10))
+            .unwrap();
+
+        // then: 10 lines from bottom of gap (lines 20-29)
+        let content = app.expanded_bottom.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 10);
+        assert_eq!(content[0].new_lineno, Some(20));
+        assert_eq!(content[9].new_lineno, Some(29));
     }
 
     #[test]
-    fn test_sibling_dirs_independent() {
-        let mut h = TreeTestHarness::new(&["src/app.rs", "tests/test.rs"]);
-        h.expand_all();
-        h.toggle("src"); // collapse src
+    fn should_append_on_subsequent_down_expand() {
+        // given: already expanded 20 lines down
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(50, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
+        };
+        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(20))
+            .unwrap();
 
-        assert_eq!(h.visible_file_count(), 1); // only tests/test.rs
+        // when: expand Down 20 more
+        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(20))
+            .unwrap();
+
+        // then: 40 lines total in top
+        let content = app.expanded_top.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 40);
+        assert_eq!(content[0].new_lineno, Some(6));
+        assert_eq!(content[39].new_lineno, Some(45));
     }
-}
 
-#[cfg(test)]
-mod commit_selection_tests {
-    use super::*;
-    use crate::model::FileStatus;
-    use crate::vcs::traits::VcsType;
+    #[test]
+    fn should_prepend_on_subsequent_up_expand() {
+        // given: already expanded 10 lines up from bottom
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(50, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
+        };
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(10))
+            .unwrap();
 
-    struct DummyVcs {
-        info: VcsInfo,
+        // when: expand Up 10 more
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(10))
+            .unwrap();
+
+        // then: 20 lines total in bottom, in ascending order
+        let content = app.expanded_bottom.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 20);
+        assert_eq!(content[0].new_lineno, Some(30));
+        assert_eq!(content[19].new_lineno, Some(49));
     }
 
-    impl VcsBackend for DummyVcs {
-        fn info(&self) -> &VcsInfo {
-            &self.info
-        }
+    #[test]
+    fn should_cap_at_gap_boundaries() {
+        // given: file with 50-line gap, already expanded 40 up
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(40))
+            .unwrap();
 
-        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-            Err(TuicrError::NoChanges)
-        }
+        // when: expand Up 20 more (only 10 remain)
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
+            .unwrap();
 
-        fn fetch_context_lines(
-            &self,
-            _file_path: &Path,
-            _file_status: FileStatus,
-            _start_line: u32,
-            _end_line: u32,
-        ) -> Result<Vec<DiffLine>> {
-            Ok(Vec::new())
-        }
+        // then: all 50 lines in bottom
+        let content = app.expanded_bottom.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 50);
+        assert_eq!(content[0].new_lineno, Some(1));
     }
 
-    fn build_app(commit_list: Vec<CommitInfo>) -> App {
-        let vcs_info = VcsInfo {
-            root_path: PathBuf::from("/tmp"),
-            head_commit: "head".to_string(),
-            branch_name: Some("main".to_string()),
-            vcs_type: VcsType::Git,
+    #[test]
+    fn should_show_up_expander_for_top_of_file_partial() {
+        // given: file with 50-line gap, expanded 20 lines up
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
         };
-        let session = ReviewSession::new(
-            vcs_info.root_path.clone(),
-            vcs_info.head_commit.clone(),
-            vcs_info.branch_name.clone(),
-            SessionDiffSource::WorkingTree,
-        );
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
+            .unwrap();
 
-        App::build(
-            Box::new(DummyVcs {
-                info: vcs_info.clone(),
-            }),
-            vcs_info,
-            Theme::dark(),
-            None,
-            false,
-            Vec::new(),
-            session,
-            DiffSource::WorkingTree,
-            InputMode::CommitSelect,
-            commit_list,
-            None,
-        )
-        .expect("failed to build test app")
-    }
+        // then: should have ↑ expander + hidden lines annotation
+        let expander_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Up } if *g == gap_id))
+            .count();
+        assert_eq!(expander_count, 1);
 
-    fn normal_commit(id: &str) -> CommitInfo {
-        CommitInfo {
-            id: id.to_string(),
-            short_id: id.to_string(),
-            branch_name: None,
-            summary: "Test commit".to_string(),
-            body: None,
-            author: "Test".to_string(),
-            time: Utc::now(),
-        }
+        let hidden_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::HiddenLines { gap_id: g, .. } if *g == gap_id))
+            .count();
+        assert_eq!(hidden_count, 1, "should show hidden lines count");
+
+        let expanded_count = app
+            .line_annotations
+            .iter()
+            .filter(
+                |a| matches!(a, AnnotatedLine::ExpandedContext { gap_id: g, .. } if *g == gap_id),
+            )
+            .count();
+        assert_eq!(expanded_count, 20);
     }
 
     #[test]
-    fn special_commit_count_counts_leading_special_entries() {
-        let app = build_app(vec![
-            App::staged_commit_entry(),
-            App::unstaged_commit_entry(),
-            normal_commit("abc123"),
-        ]);
+    fn should_not_show_expander_when_fully_expanded() {
+        // given: file with 50-line gap, fully expanded
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
+        app.expand_gap(gap_id.clone(), ExpandDirection::Both, None)
+            .unwrap();
 
-        assert_eq!(app.special_commit_count(), 2);
+        // then: no expander or hidden lines
+        let expander_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, .. } if *g == gap_id))
+            .count();
+        assert_eq!(expander_count, 0);
     }
 
     #[test]
-    fn special_commit_count_ignores_non_leading_special_entries() {
-        let app = build_app(vec![normal_commit("abc123"), App::staged_commit_entry()]);
+    fn should_show_merged_expander_for_small_between_hunk_gap() {
+        // given: file with two hunks and a 15-line gap between them
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(21, 5)]);
+        let app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
+        };
 
-        assert_eq!(app.special_commit_count(), 0);
+        // then: should show single ↕ expander (gap=15, < 20)
+        let both_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Both } if *g == gap_id))
+            .count();
+        assert_eq!(both_count, 1, "small gap should show merged ↕ expander");
     }
 
     #[test]
-    fn toggle_commit_selection_from_all_selected_selects_only_cursor() {
-        for cursor in 0..3 {
-            let mut app = build_app(vec![
-                normal_commit("abc123"),
-                normal_commit("def456"),
-                normal_commit("789abc"),
-            ]);
-            app.commit_selection_range = Some((0, 2));
-            app.commit_list_cursor = cursor;
-
-            app.toggle_commit_selection();
+    fn should_show_split_expanders_for_large_between_hunk_gap() {
+        // given: file with two hunks and a 30-line gap between them
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(36, 5)]);
+        let app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
+        };
 
-            assert_eq!(app.commit_selection_range, Some((cursor, cursor)));
-        }
+        // then: should show ↓ + hidden + ↑
+        let down_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Down } if *g == gap_id))
+            .count();
+        let up_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Up } if *g == gap_id))
+            .count();
+        let hidden_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::HiddenLines { gap_id: g, .. } if *g == gap_id))
+            .count();
+        assert_eq!(down_count, 1);
+        assert_eq!(up_count, 1);
+        assert_eq!(hidden_count, 1);
     }
 
     #[test]
-    fn toggle_commit_selection_keeps_partial_range_shrink_behavior() {
-        let mut app = build_app(vec![
-            normal_commit("abc123"),
-            normal_commit("def456"),
-            normal_commit("789abc"),
-        ]);
-        app.commit_selection_range = Some((0, 1));
-        app.commit_list_cursor = 0;
+    fn should_expand_gap_in_correct_file_not_adjacent_file() {
+        // given: two files, each with a gap before the first hunk
+        let file0 = make_file_with_hunks("a.rs", vec![make_hunk(31, 5)]);
+        let file1 = make_file_with_hunks("b.rs", vec![make_hunk(21, 5)]);
+        let mut app = build_app_with_files(vec![file0, file1], 100);
 
-        app.toggle_commit_selection();
+        let gap_id_file1 = GapId {
+            file_idx: 1,
+            hunk_idx: 0,
+        };
 
-        assert_eq!(app.commit_selection_range, Some((1, 1)));
-    }
-}
+        // when: expand gap in file1
+        app.expand_gap(gap_id_file1.clone(), ExpandDirection::Up, Some(10))
+            .unwrap();
 
-#[cfg(test)]
-mod target_selector_tests {
-    use super::*;
-    use crate::forge::selector::PullRequestsTab;
-    use crate::forge::traits::PullRequestSummary;
-    use crate::model::FileStatus;
-    use crate::vcs::traits::{VcsChangeStatus, VcsType};
+        // then: expanded content is for file1's gap (10 lines from bottom)
+        let content = app.expanded_bottom.get(&gap_id_file1).unwrap();
+        assert_eq!(content.len(), 10);
+        assert_eq!(content[9].new_lineno, Some(20));
 
-    struct DummyVcs {
-        info: VcsInfo,
-        commits: Vec<CommitInfo>,
+        // and file0's gap should not be expanded
+        let gap_id_file0 = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
+        assert!(
+            !app.expanded_top.contains_key(&gap_id_file0)
+                && !app.expanded_bottom.contains_key(&gap_id_file0)
+        );
     }
 
-    impl VcsBackend for DummyVcs {
-        fn info(&self) -> &VcsInfo {
-            &self.info
-        }
+    #[test]
+    fn should_noop_when_already_fully_expanded() {
+        // given: file with 10-line gap, fully expanded
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(11, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
+        app.expand_gap(gap_id.clone(), ExpandDirection::Both, None)
+            .unwrap();
+        let len_before = app.expanded_top.get(&gap_id).unwrap().len();
+
+        // when: try to expand again
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
+            .unwrap();
 
-        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-            Err(TuicrError::NoChanges)
-        }
+        // then: no change
+        let len_after = app.expanded_top.get(&gap_id).unwrap().len();
+        assert_eq!(len_before, len_after);
+    }
 
-        fn fetch_context_lines(
-            &self,
-            _file_path: &Path,
-            _file_status: FileStatus,
-            _start_line: u32,
-            _end_line: u32,
-        ) -> Result<Vec<DiffLine>> {
-            Ok(Vec::new())
-        }
+    #[test]
+    fn should_expand_small_gap_fully_even_with_large_limit() {
+        // given: file with 5-line gap
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(6, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
 
-        fn get_change_status(&self) -> Result<VcsChangeStatus> {
-            Ok(VcsChangeStatus {
-                staged: false,
-                unstaged: false,
-            })
-        }
+        // when: expand Up with limit 20 (gap is only 5 lines)
+        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
+            .unwrap();
 
-        fn get_recent_commits(&self, offset: usize, limit: usize) -> Result<Vec<CommitInfo>> {
-            Ok(self
-                .commits
-                .iter()
-                .skip(offset)
-                .take(limit)
-                .cloned()
-                .collect())
-        }
-    }
+        // then: all 5 lines expanded, no expander remaining
+        let content = app.expanded_bottom.get(&gap_id).unwrap();
+        assert_eq!(content.len(), 5);
 
-    fn build_app() -> App {
-        build_app_with_commits(Vec::new())
+        let expander_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, .. } if *g == gap_id))
+            .count();
+        assert_eq!(expander_count, 0);
     }
 
-    fn build_app_with_commits(commits: Vec<CommitInfo>) -> App {
-        let vcs_info = VcsInfo {
-            root_path: PathBuf::from("/tmp"),
-            head_commit: "head".to_string(),
-            branch_name: Some("main".to_string()),
-            vcs_type: VcsType::Git,
+    #[test]
+    fn should_merge_to_both_when_remaining_drops_below_batch() {
+        // given: 30-line between-hunk gap, expand 20 down => 10 remaining
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(36, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
         };
-        let session = ReviewSession::new(
-            vcs_info.root_path.clone(),
-            vcs_info.head_commit.clone(),
-            vcs_info.branch_name.clone(),
-            SessionDiffSource::WorkingTree,
-        );
+        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(20))
+            .unwrap();
 
-        App::build(
-            Box::new(DummyVcs {
-                info: vcs_info.clone(),
-                commits,
-            }),
-            vcs_info,
-            Theme::dark(),
-            None,
-            false,
-            Vec::new(),
-            session,
-            DiffSource::WorkingTree,
-            InputMode::Normal,
-            Vec::new(),
-            None,
-        )
-        .expect("failed to build test app")
+        // then: remaining=10, should show ↕ merged expander
+        let both_count = app
+            .line_annotations
+            .iter()
+            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Both } if *g == gap_id))
+            .count();
+        assert_eq!(both_count, 1, "should merge to ↕ when <20 remaining");
     }
 
-    fn dummy_commit(id: &str) -> CommitInfo {
-        CommitInfo {
-            id: id.to_string(),
-            short_id: id.to_string(),
-            branch_name: None,
-            summary: format!("commit {id}"),
-            body: None,
-            author: "tester".to_string(),
-            time: Utc::now(),
+    fn cursor_new_lineno(app: &App) -> Option<u32> {
+        match &app.line_annotations[app.diff_state.cursor_line] {
+            AnnotatedLine::DiffLine { new_lineno, .. }
+            | AnnotatedLine::SideBySideLine { new_lineno, .. } => *new_lineno,
+            AnnotatedLine::ExpandedContext { gap_id, line_idx } => app
+                .get_expanded_line(gap_id, *line_idx)
+                .and_then(|l| l.new_lineno),
+            _ => None,
         }
     }
 
-    fn test_pr_details(number: u64, title: &str) -> crate::forge::traits::PullRequestDetails {
-        crate::forge::traits::PullRequestDetails {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            number,
-            title: title.to_string(),
-            url: format!("https://github.com/agavra/tuicr/pull/{number}"),
-            state: "OPEN".to_string(),
-            is_draft: false,
-            author: Some("alice".to_string()),
-            head_ref_name: "feat".to_string(),
-            base_ref_name: "main".to_string(),
-            head_sha: "abcdef0123456789".to_string(),
-            base_sha: "1234567890abcdef".to_string(),
-            body: String::new(),
-            updated_at: None,
-            closed: false,
-            merged_at: None,
+    fn cursor_old_lineno(app: &App) -> Option<u32> {
+        match &app.line_annotations[app.diff_state.cursor_line] {
+            AnnotatedLine::DiffLine { old_lineno, .. }
+            | AnnotatedLine::SideBySideLine { old_lineno, .. } => *old_lineno,
+            AnnotatedLine::ExpandedContext { gap_id, line_idx } => app
+                .get_expanded_line(gap_id, *line_idx)
+                .and_then(|l| l.old_lineno),
+            _ => None,
         }
     }
 
-    struct FakeForgeBackend {
-        details: crate::forge::traits::PullRequestDetails,
-        patch: String,
-        commits: Vec<crate::forge::traits::PullRequestCommit>,
-        range_patch: Option<String>,
-    }
+    #[test]
+    fn should_expand_collapsed_gap_when_jumping_into_it() {
+        // given: file with a 50-line gap before the first hunk (hunk @ line 51)
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
+        assert!(!app.expanded_top.contains_key(&gap_id));
+        assert!(!app.expanded_bottom.contains_key(&gap_id));
 
-    impl FakeForgeBackend {
-        fn open_pr_details(
-            details: crate::forge::traits::PullRequestDetails,
-            patch: String,
-        ) -> Self {
-            Self {
-                details,
-                patch,
-                commits: Vec::new(),
-                range_patch: None,
-            }
-        }
-    }
+        // when: jump to line 30, which lives inside the collapsed gap
+        app.go_to_source_line(30, LineSide::New);
 
-    impl crate::forge::traits::ForgeBackend for FakeForgeBackend {
-        fn list_pull_requests(
-            &self,
-            _query: crate::forge::traits::PullRequestListQuery,
-        ) -> Result<crate::forge::traits::PagedPullRequests> {
-            unimplemented!("not used in this test")
-        }
-        fn get_pull_request(
-            &self,
-            _target: crate::forge::traits::PullRequestTarget,
-        ) -> Result<crate::forge::traits::PullRequestDetails> {
-            Ok(self.details.clone())
-        }
-        fn get_pull_request_diff(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<String> {
-            Ok(self.patch.clone())
-        }
-        fn fetch_file_lines(
-            &self,
-            _request: crate::forge::traits::ForgeFileLinesRequest,
-        ) -> Result<Vec<crate::model::DiffLine>> {
-            Ok(Vec::new())
-        }
-        fn list_review_threads(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<Vec<crate::forge::remote_comments::RemoteReviewThread>> {
-            Ok(Vec::new())
-        }
-        fn list_pull_request_commits(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<Vec<crate::forge::traits::PullRequestCommit>> {
-            Ok(self.commits.clone())
-        }
-        fn get_pull_request_commit_range_diff(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-            _start_sha: &str,
-            _end_sha: &str,
-        ) -> Result<String> {
-            Ok(self
-                .range_patch
-                .clone()
-                .unwrap_or_else(|| self.patch.clone()))
-        }
-        fn create_review(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-            _request: crate::forge::traits::CreateReviewRequest<'_>,
-        ) -> Result<crate::forge::traits::GhCreateReviewResponse> {
-            unimplemented!("FakeForgeBackend does not implement create_review")
-        }
+        // then: the gap was expanded and the cursor sits on the line whose
+        // new_lineno is exactly 30
+        assert_eq!(cursor_new_lineno(&app), Some(30));
     }
 
-    fn sample_pr(number: u64, title: &str) -> PullRequestSummary {
-        PullRequestSummary {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            number,
-            title: title.to_string(),
-            author: Some("alice".to_string()),
-            head_ref_name: "feat".to_string(),
-            base_ref_name: "main".to_string(),
-            updated_at: None,
-            url: format!("https://github.com/agavra/tuicr/pull/{number}"),
-            state: "OPEN".to_string(),
-            is_draft: false,
-        }
+    #[test]
+    fn should_not_expand_when_line_is_already_in_a_hunk() {
+        // Line 52 lives inside the hunk's own range, not in a gap — no
+        // expansion should occur.
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
+
+        app.go_to_source_line(52, LineSide::New);
+
+        assert!(!app.expanded_top.contains_key(&gap_id));
+        assert!(!app.expanded_bottom.contains_key(&gap_id));
     }
 
     #[test]
-    fn should_default_to_local_tab_after_build() {
-        // given / when
-        let app = build_app();
-        // then
-        assert_eq!(app.target_tab, TargetTab::Local);
-        assert!(!app.pr_filter_editing());
+    fn should_expand_old_side_gap_when_jumping_with_o_prefix() {
+        // Symmetric gap (offset = 0): `make_hunk` keeps old_start == new_start
+        // and the mock VCS returns context lines with old_lineno == new_lineno.
+        // We verify the side=Old path of go_to_source_line works end-to-end:
+        // the gap auto-expands and the cursor lands on old line 30.
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+
+        app.go_to_source_line(30, LineSide::Old);
+
+        assert_eq!(cursor_old_lineno(&app), Some(30));
     }
 
     #[test]
-    fn should_cycle_between_local_and_pull_requests_on_tab_keypress() {
-        // given
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        // when
-        app.cycle_target_tab(true);
-        // then
-        assert_eq!(app.target_tab, TargetTab::PullRequests);
-        // when
-        app.cycle_target_tab(false);
-        // then
-        assert_eq!(app.target_tab, TargetTab::Local);
+    fn should_expand_up_when_cursor_is_below_the_gap() {
+        // Two hunks: hunk0 at lines 1-5, hunk1 at lines 50-54. Gap between
+        // them spans new lines 6..=49. Move the cursor onto hunk1, then
+        // jump to line 30 — expansion should come from the bottom of the
+        // gap (Up) since the cursor sits below it.
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(50, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 1,
+        };
+
+        let hunk1_header_idx = app
+            .line_annotations
+            .iter()
+            .enumerate()
+            .find_map(|(i, a)| match a {
+                AnnotatedLine::HunkHeader { hunk_idx: 1, .. } => Some(i),
+                _ => None,
+            })
+            .expect("hunk 1 header should exist");
+        app.diff_state.cursor_line = hunk1_header_idx + 1;
+
+        app.go_to_source_line(30, LineSide::New);
+
+        assert_eq!(cursor_new_lineno(&app), Some(30));
+        assert!(
+            !app.expanded_top.contains_key(&gap_id),
+            "no top expansion when cursor is below the gap"
+        );
+        // Gap covers new lines 6..=49 (44 lines). Up expansion to reach
+        // line 30 reveals lines 30..=49 = 20 lines.
+        let bot_len = app.expanded_bottom.get(&gap_id).map_or(0, |v| v.len());
+        assert_eq!(bot_len, 20);
+        let has_down_expander = app.line_annotations.iter().any(|a| {
+            matches!(
+                a,
+                AnnotatedLine::Expander {
+                    gap_id: g,
+                    direction: ExpandDirection::Down,
+                } if *g == gap_id
+            )
+        });
+        assert!(
+            has_down_expander,
+            "remaining hidden lines need a `↓` expander above the cursor"
+        );
     }
 
     #[test]
-    fn should_transition_pr_tab_to_loading_on_first_visit() {
-        // given
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        // when
-        app.cycle_target_tab(true);
-        // then — a background fetch is in flight
-        assert!(app.pr_tab.is_loading());
-        assert!(app.pr_load_rx.is_some());
-        // The spawned thread holds a backend that may fail without a real
-        // `gh` binary; cancel by dropping the receiver to avoid touching it.
-        app.pr_load_rx = None;
+    fn should_expand_only_up_to_target_line_not_full_gap() {
+        // Gap before hunk spans new lines 1..=50. Jumping to line 20 should
+        // reveal lines 1..=20 and leave 21..=50 collapsed behind an
+        // `↑` expander.
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let gap_id = GapId {
+            file_idx: 0,
+            hunk_idx: 0,
+        };
+
+        app.go_to_source_line(20, LineSide::New);
+
+        assert_eq!(cursor_new_lineno(&app), Some(20));
+        let top_len = app.expanded_top.get(&gap_id).map_or(0, |v| v.len());
+        assert_eq!(top_len, 20, "only the lines up to the target should expand");
+        assert!(
+            !app.expanded_bottom.contains_key(&gap_id),
+            "no bottom expansion should happen for a downward jump"
+        );
+        // The unexpanded remainder (30 lines) should still be reachable through
+        // an `↑` expander between the cursor and the next hunk.
+        let has_up_expander = app.line_annotations.iter().any(|a| {
+            matches!(
+                a,
+                AnnotatedLine::Expander {
+                    gap_id: g,
+                    direction: ExpandDirection::Up,
+                } if *g == gap_id
+            )
+        });
+        assert!(
+            has_up_expander,
+            "remaining hidden lines need an `↑` expander"
+        );
     }
 
-    #[test]
-    fn should_keep_pr_tab_disabled_when_no_forge_remote() {
-        // given
-        let mut app = build_app();
-        // No forge_repository set up; default new app has None.
-        // when
-        app.cycle_target_tab(true);
-        // then
-        assert_eq!(app.target_tab, TargetTab::PullRequests);
-        assert!(matches!(app.pr_tab, PullRequestsTab::Disabled { .. }));
-        assert!(app.pr_load_rx.is_none());
+    /// Hunk of `context` unchanged lines followed by one added line.
+    fn make_hunk_with_long_context(context: u32) -> DiffHunk {
+        let mut hunk = make_hunk(1, context);
+        hunk.lines.push(DiffLine {
+            origin: LineOrigin::Addition,
+            content: "added".to_string(),
+            old_lineno: None,
+            new_lineno: Some(context + 1),
+            highlighted_spans: None,
+        });
+        hunk.new_count += 1;
+        hunk
     }
 
-    #[test]
-    fn should_set_filter_after_typing_and_committing() {
-        // given
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        app.pr_tab.apply_initial_load(Ok((
-            vec![sample_pr(125, "Forge"), sample_pr(148, "Review")],
-            false,
-        )));
-        app.target_tab = TargetTab::PullRequests;
-        // when
-        app.begin_pr_filter();
-        app.pr_filter_insert_char('f');
-        app.pr_filter_insert_char('o');
-        app.commit_pr_filter();
-        // then
-        assert!(!app.pr_filter_editing());
-        assert_eq!(app.pr_tab.view().rows.len(), 1);
-        assert_eq!(app.pr_tab.view().rows[0].summary.number, 125);
+    fn elided_rows(app: &App) -> Vec<(usize, usize)> {
+        app.line_annotations
+            .iter()
+            .filter_map(|a| match a {
+                AnnotatedLine::ElidedContext { start, count, .. } => Some((*start, *count)),
+                _ => None,
+            })
+            .collect()
     }
 
     #[test]
-    fn should_discard_filter_draft_on_cancel() {
+    fn should_elide_long_unchanged_run_inside_hunk() {
         // given
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        app.pr_tab
-            .apply_initial_load(Ok((vec![sample_pr(1, "alpha")], false)));
-        app.target_tab = TargetTab::PullRequests;
+        let file = make_file_with_hunks("test.rs", vec![make_hunk_with_long_context(12)]);
+
         // when
-        app.begin_pr_filter();
-        app.pr_filter_insert_char('z');
-        app.cancel_pr_filter();
-        // then
-        assert!(!app.pr_filter_editing());
-        assert_eq!(app.pr_tab.view().filter, "");
+        let app = build_app_with_files(vec![file], 100);
+
+        // then: three lines of context stay visible at each end of the run
+        assert_eq!(elided_rows(&app), vec![(3, 6)]);
     }
 
     #[test]
-    fn should_enter_pr_mode_when_opening_pr_via_fake_backend() {
-        // given a selector with a single PR row and a fake forge backend
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        let summary = sample_pr(42, "answer");
-        app.pr_tab
-            .apply_initial_load(Ok((vec![summary.clone()], false)));
-        app.target_tab = TargetTab::PullRequests;
-        let backend = Box::new(FakeForgeBackend::open_pr_details(
-            test_pr_details(42, "answer"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        ));
+    fn should_keep_commented_lines_visible_when_eliding() {
+        // given
+        let file = make_file_with_hunks("test.rs", vec![make_hunk_with_long_context(12)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        app.session
+            .get_file_mut(&PathBuf::from("test.rs"))
+            .expect("file should be in session")
+            .add_line_comment(
+                6,
+                crate::model::Comment::new(
+                    "check this".to_string(),
+                    crate::model::CommentType::Note,
+                    Some(LineSide::New),
+                ),
+            );
+
         // when
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        // then
-        assert!(matches!(app.diff_source, DiffSource::PullRequest(_)));
-        if let DiffSource::PullRequest(pr) = &app.diff_source {
-            assert_eq!(pr.key.number, 42);
-            assert_eq!(pr.title, "answer");
-            assert_eq!(pr.head_ref_name, "feat");
-            assert_eq!(pr.base_ref_name, "main");
-            assert_eq!(pr.key.head_sha, "abcdef0123456789");
-            assert_eq!(pr.base_sha, "1234567890abcdef");
-        }
-        // and the session is keyed by the PR
-        assert!(app.session.pr_session_key.is_some());
-        // and PR diff files were parsed
-        assert_eq!(app.diff_files.len(), 1);
-        // and the forge backend is wired for context expansion / submit
-        assert!(app.forge_backend.is_some());
-    }
+        app.rebuild_annotations();
 
-    fn sample_pr_commit(oid: &str, summary: &str) -> crate::forge::traits::PullRequestCommit {
-        crate::forge::traits::PullRequestCommit {
-            oid: oid.to_string(),
-            short_oid: oid.chars().take(7).collect(),
-            summary: summary.to_string(),
-            author: "Alice".to_string(),
-            timestamp: None,
-        }
+        // then: the run splits around line 6 (hunk index 5)
+        assert_eq!(elided_rows(&app), vec![(3, 2), (6, 3)]);
     }
 
     #[test]
-    fn should_populate_inline_selector_when_pr_has_multiple_commits() {
-        // given a PR open path where the forge returns 3 commits
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        let summary = sample_pr(42, "multi-commit");
-        app.pr_tab
-            .apply_initial_load(Ok((vec![summary.clone()], false)));
-        let mut backend = FakeForgeBackend::open_pr_details(
-            test_pr_details(42, "multi-commit"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        );
-        // Forge returns oldest-first; pr_open reverses to newest-first.
-        backend.commits = vec![
-            sample_pr_commit("aaaaaaa1111", "first"),
-            sample_pr_commit("bbbbbbb2222", "second"),
-            sample_pr_commit("ccccccc3333", "third"),
-        ];
+    fn should_expand_elided_run_under_cursor() {
+        // given
+        let file = make_file_with_hunks("test.rs", vec![make_hunk_with_long_context(12)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        app.diff_state.cursor_line = app
+            .line_annotations
+            .iter()
+            .position(|a| matches!(a, AnnotatedLine::ElidedContext { .. }))
+            .expect("run should be elided");
+
+        // when
+        let expanded = app.expand_elision_at_cursor();
+
+        // then
+        assert!(expanded);
+        assert!(elided_rows(&app).is_empty());
+        assert_eq!(cursor_new_lineno(&app), Some(4));
+    }
+
+    #[test]
+    fn should_expand_elided_run_when_jumping_to_hidden_line() {
+        // given
+        let file = make_file_with_hunks("test.rs", vec![make_hunk_with_long_context(12)]);
+        let mut app = build_app_with_files(vec![file], 100);
+
         // when
-        app.open_pr_with_backend(&summary, Box::new(backend), None)
-            .unwrap();
-        // then — selector is visible and pr_commits is in newest-first order.
-        assert!(app.show_commit_selector, "selector should be visible");
-        assert_eq!(app.pr_commits.len(), 3);
-        assert_eq!(app.pr_commits[0].summary, "third");
-        assert_eq!(app.review_commits.len(), 3);
-        // and — default selection covers all commits.
-        assert_eq!(app.commit_selection_range, Some((0, 2)));
+        app.go_to_source_line(7, LineSide::New);
+
+        // then
+        assert!(elided_rows(&app).is_empty());
+        assert_eq!(cursor_new_lineno(&app), Some(7));
     }
 
     #[test]
-    fn should_hide_inline_selector_for_single_commit_pr() {
-        // given a PR with exactly one commit
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        let summary = sample_pr(42, "solo");
-        app.pr_tab
-            .apply_initial_load(Ok((vec![summary.clone()], false)));
-        let mut backend = FakeForgeBackend::open_pr_details(
-            test_pr_details(42, "solo"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        );
-        backend.commits = vec![sample_pr_commit("aaaaaaa1111", "only")];
+    fn should_show_mode_change_instead_of_no_changes() {
+        // given
+        let mut file = make_file_with_hunks("run.sh", Vec::new());
+        file.modes = FileModes {
+            old: Some(0o100644),
+            new: Some(0o100755),
+        };
+
         // when
-        app.open_pr_with_backend(&summary, Box::new(backend), None)
-            .unwrap();
+        let app = build_app_with_files(vec![file], 0);
+
         // then
-        assert!(!app.show_commit_selector);
-        assert!(app.commit_list.is_empty());
-        assert_eq!(app.commit_selection_range, None);
+        let rows: Vec<_> = app
+            .line_annotations
+            .iter()
+            .filter(|a| annotation_file_idx(a) == Some(0))
+            .collect();
+        assert!(matches!(
+            rows.as_slice(),
+            [
+                AnnotatedLine::FileHeader { .. },
+                AnnotatedLine::FileMetadata { line_idx: 0, .. }
+            ]
+        ));
     }
 
     #[test]
-    fn should_resolve_pr_range_to_parent_sha_and_head_sha() {
-        // given a multi-commit PR open with the middle commit selected
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        let summary = sample_pr(42, "ranges");
-        app.pr_tab
-            .apply_initial_load(Ok((vec![summary.clone()], false)));
-        let mut backend = FakeForgeBackend::open_pr_details(
-            test_pr_details(42, "ranges"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        );
-        backend.commits = vec![
-            sample_pr_commit("first11", "first"),
-            sample_pr_commit("middle2", "middle"),
-            sample_pr_commit("last333", "last"),
-        ];
-        app.open_pr_with_backend(&summary, Box::new(backend), None)
+    fn should_find_hunk_under_cursor() {
+        // given
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 3), make_hunk(40, 3)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let second_hunk_line = app
+            .line_annotations
+            .iter()
+            .position(|a| matches!(a, AnnotatedLine::DiffLine { hunk_idx: 1, .. }))
             .unwrap();
-        // After open: pr_commits = [last, middle, first] (newest-first).
-        // Select only the middle commit (index 1).
-        app.commit_selection_range = Some((1, 1));
-        // when
-        let pair = app.pr_range_sha_pair();
-        // then — start = parent (first), end = newest selected (middle).
-        assert_eq!(pair, Some(("first11".to_string(), "middle2".to_string())));
+
+        // when / then
+        app.diff_state.cursor_line = second_hunk_line;
+        assert_eq!(app.hunk_at_cursor(), Some((0, 1)));
+
+        app.diff_state.cursor_line = app
+            .line_annotations
+            .iter()
+            .position(|a| matches!(a, AnnotatedLine::FileHeader { .. }))
+            .unwrap();
+        assert_eq!(app.hunk_at_cursor(), None);
+    }
+
+    fn earlier_session(app: &App, comments: Vec<(u32, Comment)>) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            app.vcs_info.root_path.clone(),
+            "def456".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        let path = PathBuf::from("test.rs");
+        session.add_file(path.clone(), FileStatus::Modified, 0);
+        let review = session.get_file_mut(&path).unwrap();
+        for (line, comment) in comments {
+            review.add_line_comment(line, comment);
+        }
+        session
     }
 
     #[test]
-    fn should_resolve_pr_range_to_pr_base_when_oldest_commit_selected() {
-        // given a multi-commit PR with only the oldest commit selected
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        let summary = sample_pr(42, "base");
-        app.pr_tab
-            .apply_initial_load(Ok((vec![summary.clone()], false)));
-        let mut backend = FakeForgeBackend::open_pr_details(
-            test_pr_details(42, "base"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
+    fn should_show_earlier_session_comments_on_matching_lines() {
+        // given
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let earlier = earlier_session(
+            &app,
+            vec![
+                (
+                    2,
+                    Comment::new("check bounds".to_string(), CommentType::Issue, None),
+                ),
+                (
+                    4,
+                    Comment::new(
+                        "old name".to_string(),
+                        CommentType::Note,
+                        Some(LineSide::Old),
+                    ),
+                ),
+                (
+                    99,
+                    Comment::new("gone".to_string(), CommentType::Note, None),
+                ),
+            ],
         );
-        backend.commits = vec![
-            sample_pr_commit("aaa", "first"),
-            sample_pr_commit("bbb", "second"),
-        ];
-        app.open_pr_with_backend(&summary, Box::new(backend), None)
-            .unwrap();
-        // pr_commits = [second, first]. Select only the oldest (index 1).
-        app.commit_selection_range = Some((1, 1));
+
         // when
-        let pair = app.pr_range_sha_pair();
-        // then — start falls back to the PR's base_sha.
-        let expected_base = test_pr_details(42, "base").base_sha;
-        assert_eq!(pair, Some((expected_base, "aaa".to_string())));
+        app.set_history_from_sessions(&[earlier]);
+
+        // then
+        let rows: Vec<_> = app
+            .line_annotations
+            .iter()
+            .filter(|a| !matches!(a, AnnotatedLine::DiffLine { .. }))
+            .skip_while(|a| !matches!(a, AnnotatedLine::HunkHeader { .. }))
+            .collect();
+        assert_eq!(rows.len(), 4);
+        assert!(matches!(
+            rows.as_slice(),
+            [
+                AnnotatedLine::HunkHeader { .. },
+                AnnotatedLine::HistoryComment { idx: 0, .. },
+                AnnotatedLine::HistoryComment { idx: 1, .. },
+                AnnotatedLine::Spacing
+            ]
+        ));
+        let row_after = |line: u32, side: LineSide| {
+            let pos = app
+                .line_annotations
+                .iter()
+                .position(|a| match (a, side) {
+                    (AnnotatedLine::DiffLine { new_lineno, .. }, LineSide::New) => {
+                        *new_lineno == Some(line)
+                    }
+                    (AnnotatedLine::DiffLine { old_lineno, .. }, LineSide::Old) => {
+                        *old_lineno == Some(line)
+                    }
+                    _ => false,
+                })
+                .unwrap();
+            app.line_annotations[pos + 1].clone()
+        };
+        assert!(matches!(
+            row_after(2, LineSide::New),
+            AnnotatedLine::HistoryComment { idx: 0, .. }
+        ));
+        assert!(matches!(
+            row_after(4, LineSide::Old),
+            AnnotatedLine::HistoryComment { idx: 1, .. }
+        ));
+
+        app.set_show_history_comments(false);
+        assert!(
+            !app.line_annotations
+                .iter()
+                .any(|a| matches!(a, AnnotatedLine::HistoryComment { .. }))
+        );
     }
 
     #[test]
-    fn should_warn_when_opening_closed_pr() {
+    fn should_skip_current_session_and_repeated_history_comments() {
         // given
-        let mut app = build_app();
-        let summary = sample_pr(42, "old");
-        let mut details = test_pr_details(42, "old");
-        details.state = "CLOSED".to_string();
-        details.closed = true;
-        let backend = Box::new(FakeForgeBackend::open_pr_details(
-            details,
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        ));
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        let comment = Comment::new("check bounds".to_string(), CommentType::Issue, None);
+        let newer = earlier_session(&app, vec![(2, comment.clone())]);
+        let older = earlier_session(&app, vec![(2, comment)]);
+        let mut current = earlier_session(
+            &app,
+            vec![(3, Comment::new("mine".to_string(), CommentType::Note, None))],
+        );
+        current.id = app.session.id.clone();
+
         // when
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        // then — warning message surfaces the read-only state
-        let msg = app.message.as_ref().expect("expected warning message");
-        assert!(msg.content.contains("closed"), "got: {:?}", msg.content);
-        assert!(msg.content.contains("read-only"), "got: {:?}", msg.content);
-        // and the diff source reflects the closed state
-        if let DiffSource::PullRequest(pr) = &app.diff_source {
-            assert!(pr.is_read_only());
-            assert_eq!(pr.read_only_reason(), Some("closed"));
-        } else {
-            panic!("expected PullRequest diff source");
-        }
+        app.set_history_from_sessions(&[current, newer, older]);
+
+        // then
+        let history = &app.history_comments[&PathBuf::from("test.rs")];
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].line, 2);
+        assert_eq!(history[0].comment.content, "check bounds");
     }
 
     #[test]
-    fn should_surface_pr_open_error_into_selector_state() {
-        // given a backend that fails at get_pull_request
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        let summary = sample_pr(42, "boom");
-        app.pr_tab
-            .apply_initial_load(Ok((vec![summary.clone()], false)));
-        app.target_tab = TargetTab::PullRequests;
-        let backend = Box::new(FailingForgeBackend);
+    fn should_flag_files_with_recorded_findings_after_export() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("findings.json");
+        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5)]);
+        let mut app = build_app_with_files(vec![file], 100);
+        app.enable_findings_db(db_path.clone());
+        for line in 1..=FINDINGS_HINT_THRESHOLD as u32 {
+            app.session
+                .get_file_mut(&PathBuf::from("test.rs"))
+                .unwrap()
+                .add_line_comment(
+                    line,
+                    Comment::new("off by one".to_string(), CommentType::Issue, None),
+                );
+        }
+        assert_eq!(app.findings_hint(Path::new("test.rs")), None);
+
         // when
-        let result = app.open_pr_with_backend(&summary, backend, None);
+        app.record_findings();
+
         // then
-        assert!(result.is_err());
-        // diff source did not switch
-        assert!(matches!(app.diff_source, DiffSource::WorkingTree));
+        assert_eq!(
+            app.findings_hint(Path::new("test.rs")),
+            Some(FINDINGS_HINT_THRESHOLD)
+        );
+        assert!(db_path.exists());
     }
 
     #[test]
-    fn should_route_context_expansion_to_forge_provider_in_pr_mode() {
-        // given an app in PR mode with a counting fake backend
-        let mut app = build_app();
-        let summary = sample_pr(7, "ctx");
-        let backend = Box::new(FakeForgeBackend::open_pr_details(
-            test_pr_details(7, "ctx"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        ));
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        // when we ask for a context provider
-        // (we can't easily trigger a real gap expansion without setting up
-        //  the full diff state, so instead we assert by construction)
-        let provider = app.context_provider();
-        // then — explicitly: the provider is the forge variant. We probe by
-        // calling fetch with a Modified file and asserting the forge backend
-        // recorded a head-side request via its trait method. The
-        // FakeForgeBackend just returns empty; we're verifying routing.
-        let res = provider
-            .fetch_context_lines(
-                None,
-                Some(&PathBuf::from("src/lib.rs")),
-                FileStatus::Modified,
-                1,
-                3,
-            )
-            .unwrap();
-        // The fake forge returns empty by default — the *call* succeeded
-        // (no error from a VCS backend would have meant VCS routing). The
-        // key signal: this didn't go through the VCS backend (DummyVcs
-        // doesn't implement fetch_context_lines and would have panicked).
-        assert!(res.is_empty());
+    fn should_change_elision_threshold_with_elide_command() {
+        // given
+        let file = make_file_with_hunks("test.rs", vec![make_hunk_with_long_context(12)]);
+        let mut app = build_app_with_files(vec![file], 100);
+
+        // when / then
+        app.elide_command("off");
+        assert!(elided_rows(&app).is_empty());
+        assert_eq!(app.elide_context, 0);
+
+        app.elide_command("6");
+        assert_eq!(elided_rows(&app), vec![(3, 6)]);
+
+        app.elide_command("12");
+        assert!(elided_rows(&app).is_empty());
+
+        app.elide_command("lots");
+        assert_eq!(app.elide_context, 12);
     }
+}
 
-    #[test]
-    fn should_switch_session_when_pr_head_advances_on_reload() {
-        // given an app already in PR mode at head A
-        let mut app = build_app();
-        let summary = sample_pr(42, "head-a");
-        let mut details_a = test_pr_details(42, "head-a");
-        details_a.head_sha = "aaaaaaaaaaaaaaaa".to_string();
-        let backend_a = Box::new(FakeForgeBackend::open_pr_details(
-            details_a.clone(),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        ));
-        app.open_pr_with_backend(&summary, backend_a, None).unwrap();
-        let old_session_id = app.session.id.clone();
-        // when reloading with a backend that reports head B
-        let mut details_b = details_a.clone();
-        details_b.head_sha = "bbbbbbbbbbbbbbbb".to_string();
-        details_b.title = "head-b".to_string();
-        let backend_b = Box::new(FakeForgeBackend::open_pr_details(
-            details_b.clone(),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        ));
-        let head_changed = app
-            .reload_pull_request_with_backend(backend_b, None)
-            .unwrap();
-        // then — the session swap happened
-        assert!(head_changed);
-        if let DiffSource::PullRequest(pr) = &app.diff_source {
-            assert_eq!(pr.key.head_sha, "bbbbbbbbbbbbbbbb");
-            assert_eq!(pr.title, "head-b");
-        } else {
-            panic!("expected PullRequest diff source");
+#[cfg(test)]
+mod visual_selection_tests {
+    use super::*;
+
+    fn p(idx: usize, off: usize) -> SelPoint {
+        SelPoint {
+            annotation_idx: idx,
+            char_offset: off,
+            side: LineSide::New,
         }
-        // and the session changed (new session, not the old one)
-        assert_ne!(app.session.id, old_session_id);
     }
 
     #[test]
-    fn should_keep_session_when_pr_head_unchanged_on_reload() {
-        // given an app in PR mode
-        let mut app = build_app();
-        let summary = sample_pr(42, "same");
-        let details = test_pr_details(42, "same");
-        let backend = Box::new(FakeForgeBackend::open_pr_details(
-            details.clone(),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        ));
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        let session_id_before = app.session.id.clone();
-        // when reloading with the same head
-        let backend2 = Box::new(FakeForgeBackend::open_pr_details(
-            details,
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-        ));
-        let changed = app
-            .reload_pull_request_with_backend(backend2, None)
-            .unwrap();
-        // then
-        assert!(!changed);
-        assert_eq!(app.session.id, session_id_before);
+    fn collapsed_starts_at_point() {
+        let sel = VisualSelection::collapsed(p(5, 3));
+        assert_eq!(sel.anchor, p(5, 3));
+        assert_eq!(sel.head, p(5, 3));
     }
 
-    struct FailingForgeBackend;
+    #[test]
+    fn ordered_returns_anchor_head_when_already_in_order() {
+        let sel = VisualSelection {
+            anchor: p(1, 0),
+            head: p(4, 8),
+        };
+        let (start, end) = sel.ordered();
+        assert_eq!(start, p(1, 0));
+        assert_eq!(end, p(4, 8));
+    }
 
-    impl crate::forge::traits::ForgeBackend for FailingForgeBackend {
-        fn list_pull_requests(
-            &self,
-            _q: crate::forge::traits::PullRequestListQuery,
-        ) -> Result<crate::forge::traits::PagedPullRequests> {
-            unimplemented!()
-        }
-        fn get_pull_request(
-            &self,
-            _target: crate::forge::traits::PullRequestTarget,
-        ) -> Result<crate::forge::traits::PullRequestDetails> {
-            Err(crate::error::TuicrError::Forge(
-                "simulated network failure".to_string(),
-            ))
-        }
-        fn get_pull_request_diff(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<String> {
-            unreachable!()
-        }
-        fn fetch_file_lines(
-            &self,
-            _req: crate::forge::traits::ForgeFileLinesRequest,
-        ) -> Result<Vec<crate::model::DiffLine>> {
-            Ok(Vec::new())
+    #[test]
+    fn ordered_swaps_when_head_before_anchor_by_idx() {
+        let sel = VisualSelection {
+            anchor: p(4, 0),
+            head: p(1, 0),
+        };
+        let (start, end) = sel.ordered();
+        assert_eq!(start, p(1, 0));
+        assert_eq!(end, p(4, 0));
+    }
+
+    #[test]
+    fn ordered_breaks_ties_on_idx_by_char_offset() {
+        let sel = VisualSelection {
+            anchor: p(7, 20),
+            head: p(7, 5),
+        };
+        let (start, end) = sel.ordered();
+        assert_eq!(start, p(7, 5));
+        assert_eq!(end, p(7, 20));
+    }
+}
+
+#[cfg(test)]
+mod submit_flow_tests {
+    //! Tests for the `:submit*` preflight / resolver / confirmation
+    //! orchestration. Driven through the App methods rather than the key
+    //! handlers so we exercise the state machine directly.
+    use super::*;
+    use crate::forge::submit::{ResolverAction, SubmitEvent, UnmappableReason};
+    use crate::forge::traits::{ForgeRepository, PrSessionKey};
+    use crate::model::comment::{Comment, CommentLifecycleState, CommentType, LineContext};
+    use crate::model::diff_types::{DiffHunk, DiffLine, FileStatus, LineOrigin};
+    use crate::vcs::traits::{VcsChangeStatus, VcsType};
+
+    struct DummyVcs {
+        info: VcsInfo,
+    }
+
+    impl VcsBackend for DummyVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
         }
-        fn list_review_threads(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<Vec<crate::forge::remote_comments::RemoteReviewThread>> {
-            Ok(Vec::new())
+        fn get_working_tree_diff(&self, _h: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
         }
-        fn list_pull_request_commits(
+        fn fetch_context_lines(
             &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<Vec<crate::forge::traits::PullRequestCommit>> {
+            _p: &Path,
+            _s: FileStatus,
+            _start: u32,
+            _end: u32,
+        ) -> Result<Vec<DiffLine>> {
             Ok(Vec::new())
         }
-        fn get_pull_request_commit_range_diff(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-            _start_sha: &str,
-            _end_sha: &str,
-        ) -> Result<String> {
-            unreachable!()
-        }
-        fn create_review(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-            _request: crate::forge::traits::CreateReviewRequest<'_>,
-        ) -> Result<crate::forge::traits::GhCreateReviewResponse> {
-            unimplemented!()
+        fn get_change_status(&self) -> Result<VcsChangeStatus> {
+            Ok(VcsChangeStatus {
+                staged: false,
+                unstaged: false,
+            })
         }
     }
 
-    #[test]
-    fn should_apply_initial_load_event_to_pr_tab() {
-        // given
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.pr_tab.start_initial_load();
-        let (tx, rx) = std::sync::mpsc::channel();
-        app.pr_load_rx = Some(rx);
-        tx.send(PrLoadEvent::Initial(Ok((
-            vec![sample_pr(7, "lucky")],
+    fn make_pr_app_with_single_modified_file(file_path: &str) -> App {
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp/repo"),
+            head_commit: "abcdef0123".to_string(),
+            branch_name: Some("feat".to_string()),
+            vcs_type: VcsType::File,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::PullRequest,
+        );
+        let diff_file = DiffFile {
+            old_path: Some(PathBuf::from(file_path)),
+            new_path: Some(PathBuf::from(file_path)),
+            status: FileStatus::Modified,
+            hunks: vec![DiffHunk {
+                header: "@@".to_string(),
+                old_start: 1,
+                old_count: 0,
+                new_start: 1,
+                new_count: 0,
+                lines: vec![
+                    DiffLine {
+                        origin: LineOrigin::Context,
+                        content: "a".to_string(),
+                        old_lineno: Some(10),
+                        new_lineno: Some(10),
+                        highlighted_spans: None,
+                    },
+                    DiffLine {
+                        origin: LineOrigin::Addition,
+                        content: "b".to_string(),
+                        old_lineno: None,
+                        new_lineno: Some(11),
+                        highlighted_spans: None,
+                    },
+                ],
+            }],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
+        let pr_source = PullRequestDiffSource {
+            key: PrSessionKey::new(
+                ForgeRepository::github("github.com", "agavra", "tuicr"),
+                125,
+                "abcdef0123".to_string(),
+            ),
+            base_sha: "0000".to_string(),
+            title: "test pr".to_string(),
+            url: "https://github.com/agavra/tuicr/pull/125".to_string(),
+            head_ref_name: "feat".to_string(),
+            base_ref_name: "main".to_string(),
+            state: "OPEN".to_string(),
+            closed: false,
+            merged: false,
+        };
+        let mut app = App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
             false,
-        ))))
-        .unwrap();
-        drop(tx);
-        // when
-        app.poll_pr_load_events();
-        // then
-        assert!(app.pr_load_rx.is_none());
-        assert_eq!(app.pr_tab.view().rows.len(), 1);
-        assert_eq!(app.pr_tab.view().rows[0].summary.number, 7);
+            vec![diff_file],
+            session,
+            DiffSource::PullRequest(Box::new(pr_source)),
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("build app");
+        app.current_pr_head = Some("abcdef0123".to_string());
+        app
+    }
+
+    fn line_comment(side: LineSide, new: Option<u32>, old: Option<u32>) -> Comment {
+        let mut c = Comment::new("body".to_string(), CommentType::Issue, Some(side));
+        c.line_context = Some(LineContext {
+            new_line: new,
+            old_line: old,
+            content: String::new(),
+            before: Vec::new(),
+            after: Vec::new(),
+            hash: None,
+        });
+        c
     }
 
-    #[test]
-    fn should_open_pr_selector_on_prs_command() {
-        // given
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = PullRequestsTab::new(app.forge_repository.clone());
-        app.command_buffer = "prs".to_string();
-        // when
-        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
-        // then
-        assert_eq!(app.target_tab, TargetTab::PullRequests);
-        assert_eq!(app.input_mode, InputMode::CommitSelect);
-        // Cancel the background fetch handle to avoid surprising real `gh` calls.
-        app.pr_load_rx = None;
+    fn add_line_comment(app: &mut App, path: &str, line: u32, comment: Comment) {
+        let pb = PathBuf::from(path);
+        let review = app.session.get_file_mut(&pb).expect("file in session");
+        review.line_comments.entry(line).or_default().push(comment);
     }
 
     #[test]
-    fn should_open_local_selector_on_targets_command() {
-        // given
-        let mut app = build_app_with_commits(vec![dummy_commit("abc")]);
-        app.command_buffer = "targets".to_string();
-        // when
-        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
-        // then
-        assert_eq!(app.target_tab, TargetTab::Local);
-        assert_eq!(app.input_mode, InputMode::CommitSelect);
+    fn should_use_subset_head_sha_as_commit_id_when_inline_selector_is_strict_subset() {
+        // Regression for HTTP 422 when reviewing a subset of commits: the
+        // payload's `commit_id` must match the SHA the displayed diff was
+        // computed against — using the cumulative PR head causes GitHub to
+        // reject inline comments whose lines aren't in that diff.
+        use crate::forge::traits::PullRequestCommit;
+
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        // Newest-first: [C3, C2, C1]. PR head SHA is C3 ("abcdef0123").
+        app.pr_commits = vec![
+            PullRequestCommit {
+                oid: "abcdef0123".to_string(),
+                short_oid: "abcdef0".to_string(),
+                summary: "C3".to_string(),
+                author: "me".to_string(),
+                timestamp: None,
+            },
+            PullRequestCommit {
+                oid: "deadbeef02".to_string(),
+                short_oid: "deadbee".to_string(),
+                summary: "C2".to_string(),
+                author: "me".to_string(),
+                timestamp: None,
+            },
+            PullRequestCommit {
+                oid: "facecafe01".to_string(),
+                short_oid: "facecaf".to_string(),
+                summary: "C1".to_string(),
+                author: "me".to_string(),
+                timestamp: None,
+            },
+        ];
+        // Strict subset: only middle commit C2 selected (start=1, end=1).
+        app.commit_selection_range = Some((1, 1));
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            Comment::new(
+                "comment on C2".to_string(),
+                CommentType::Issue,
+                Some(LineSide::New),
+            ),
+        );
+
+        app.start_submit(SubmitEvent::Comment);
+
+        let state = app.submit_state.as_ref().expect("submit state");
+        assert_eq!(
+            state.commit_id, "deadbeef02",
+            "subset → commit_id should be the newest selected commit (start_idx), not the PR head",
+        );
     }
 
     #[test]
-    fn should_treat_commits_as_alias_for_local_target_selector() {
-        // given
-        let mut app = build_app_with_commits(vec![dummy_commit("abc")]);
-        app.command_buffer = "commits".to_string();
-        // when
-        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
-        // then
-        assert_eq!(app.target_tab, TargetTab::Local);
-        assert_eq!(app.input_mode, InputMode::CommitSelect);
-    }
+    fn should_use_pr_head_sha_as_commit_id_when_full_commit_range_selected() {
+        // Counterpart to the subset regression: full-range selection should
+        // continue to use the cumulative PR head SHA.
+        use crate::forge::traits::PullRequestCommit;
 
-    // -- async PR open spinner tests -----------------------------------------
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        app.pr_commits = vec![
+            PullRequestCommit {
+                oid: "abcdef0123".to_string(),
+                short_oid: "abcdef0".to_string(),
+                summary: "C2".to_string(),
+                author: "me".to_string(),
+                timestamp: None,
+            },
+            PullRequestCommit {
+                oid: "facecafe01".to_string(),
+                short_oid: "facecaf".to_string(),
+                summary: "C1".to_string(),
+                author: "me".to_string(),
+                timestamp: None,
+            },
+        ];
+        app.commit_selection_range = Some((0, 1));
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            Comment::new(
+                "comment".to_string(),
+                CommentType::Issue,
+                Some(LineSide::New),
+            ),
+        );
 
-    fn loaded_pr_tab(pr_list: Vec<PullRequestSummary>) -> PullRequestsTab {
-        let mut tab = PullRequestsTab::new(Some(ForgeRepository::github(
-            "github.com",
-            "agavra",
-            "tuicr",
-        )));
-        tab.start_initial_load();
-        tab.apply_initial_load(Ok((pr_list, false)));
-        tab
+        app.start_submit(SubmitEvent::Comment);
+
+        let state = app.submit_state.as_ref().expect("submit state");
+        assert_eq!(state.commit_id, "abcdef0123");
     }
 
     #[test]
-    fn should_set_pr_open_state_and_spawn_when_pressing_enter_on_a_pr_row() {
-        // given a loaded PR tab and no in-flight open
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = loaded_pr_tab(vec![sample_pr(42, "boom")]);
-        app.target_tab = TargetTab::PullRequests;
-        // when
-        let handled = app.pr_tab_select();
-        // then
-        assert!(handled);
-        assert!(app.pr_open_state.is_some());
-        let state = app.pr_open_state.as_ref().unwrap();
-        assert_eq!(state.pr_number, 42);
-        // Drop the receiver so the spawned thread's tx send is a no-op
-        // when it completes (the real `gh` call would block; this test
-        // does not wait for it).
-        app.pr_open_rx = None;
-        app.pr_open_state = None;
+    fn should_anchor_line_comments_via_hashmap_key_when_line_context_missing() {
+        // Regression: in production, comments are created via Comment::new
+        // which does NOT populate line_context — the line lives only in the
+        // line_comments HashMap key. Before the CommentAnchor refactor, the
+        // mapper treated these as file-level and posted everything with
+        // position 1 plus the "File-level:" body prefix.
+        use crate::forge::submit::GhSide;
+
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let bare = Comment::new(
+            "real line comment".to_string(),
+            CommentType::Issue,
+            Some(LineSide::New),
+        );
+        assert!(bare.line_context.is_none(), "fixture contract");
+        add_line_comment(&mut app, "src/lib.rs", 11, bare);
+
+        app.start_submit(SubmitEvent::Comment);
+
+        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
+        let state = app.submit_state.as_ref().expect("submit state");
+        assert_eq!(state.mappable.len(), 1);
+        let inline = &state.mappable[0];
+        assert_eq!(inline.line, 11);
+        assert_eq!(inline.side, GhSide::Right);
+        assert!(
+            !inline.body.contains("File-level:"),
+            "regression: body should not be prefixed File-level (got: {})",
+            inline.body
+        );
     }
 
     #[test]
-    fn should_be_a_noop_when_pressing_enter_during_an_in_flight_open() {
-        // given an in-flight open marker
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = loaded_pr_tab(vec![sample_pr(7, "ctx"), sample_pr(8, "next")]);
-        app.target_tab = TargetTab::PullRequests;
-        app.pr_open_state = Some(crate::app::PrOpenRequest {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 7,
-            started_at: std::time::Instant::now(),
-        });
-        // (no pr_open_rx is fine — the function never touches it on this path)
-        // when — Enter on a different row
-        if let crate::forge::selector::PullRequestsTab::Loaded { cursor, .. } = &mut app.pr_tab {
-            *cursor = 1;
-        }
-        let handled = app.pr_tab_select();
-        // then — handled but state unchanged (no new spawn for #8)
-        assert!(handled);
-        let state = app.pr_open_state.as_ref().unwrap();
-        assert_eq!(state.pr_number, 7);
+    fn should_open_confirm_directly_when_all_comments_map() {
+        // given a PR session with one mappable line comment
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
+        );
+        // when
+        app.start_submit(SubmitEvent::Comment);
+        // then — went straight to confirmation, no resolver
+        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
+        let state = app.submit_state.as_ref().expect("submit state");
+        assert_eq!(state.mappable.len(), 1);
+        assert!(state.unmappable.is_empty());
+        assert_eq!(state.commit_id, "abcdef0123");
+        assert_eq!(state.event, SubmitEvent::Comment);
     }
 
     #[test]
-    fn should_clear_pr_open_state_on_cancel() {
-        // given
-        let mut app = build_app();
-        app.pr_open_state = Some(crate::app::PrOpenRequest {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 11,
-            started_at: std::time::Instant::now(),
-        });
+    fn should_open_resolver_when_any_comment_is_unmappable() {
+        // given a PR session with one mappable + one file-level on a
+        // binary file (unmappable).
+        let mut app = make_pr_app_with_single_modified_file("img.png");
+        // mark file binary in diff_files
+        app.diff_files[0].is_binary = true;
+        // file-level comment in session
+        let pb = PathBuf::from("img.png");
+        let review = app.session.get_file_mut(&pb).expect("file in session");
+        review
+            .file_comments
+            .push(Comment::new("oof".to_string(), CommentType::Note, None));
         // when
-        let cancelled = app.cancel_pr_open();
-        // then
-        assert!(cancelled);
-        assert!(app.pr_open_state.is_none());
-        assert!(app.pr_open_rx.is_none());
+        app.start_submit(SubmitEvent::Comment);
+        // then — resolver entered with one unmappable
+        assert_eq!(app.input_mode, InputMode::SubmitResolver);
+        let state = app.submit_state.as_ref().expect("submit state");
+        assert_eq!(state.unmappable.len(), 1);
+        assert_eq!(state.unmappable[0].reason, UnmappableReason::BinaryFile);
+        assert_eq!(state.resolver_choices.len(), 1);
+        // Default action is MoveToSummary per spec
+        assert_eq!(state.resolver_choices[0], ResolverAction::MoveToSummary);
     }
 
     #[test]
-    fn should_return_false_when_cancelling_with_no_in_flight_open() {
-        // given
-        let mut app = build_app();
+    fn should_skip_locked_comments_during_preflight() {
+        // given a single locked comment
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let mut c = line_comment(LineSide::New, Some(11), None);
+        c.lifecycle_state = CommentLifecycleState::Submitted;
+        add_line_comment(&mut app, "src/lib.rs", 11, c);
         // when
-        let cancelled = app.cancel_pr_open();
-        // then
-        assert!(!cancelled);
+        app.start_submit(SubmitEvent::Comment);
+        // then — preflight aborted with the "nothing to submit" warning
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
     }
 
     #[test]
-    fn should_surface_pr_open_error_to_message_bar_when_done_event_carries_error() {
-        // given an app waiting on a synthetic open
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = loaded_pr_tab(vec![sample_pr(42, "boom")]);
-        app.target_tab = TargetTab::PullRequests;
-        let request = crate::app::PrOpenRequest {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 42,
-            started_at: std::time::Instant::now(),
-        };
-        app.pr_open_state = Some(request.clone());
-        let (tx, rx) = std::sync::mpsc::channel();
-        app.pr_open_rx = Some(rx);
-        tx.send(crate::app::PrOpenEvent::Done {
-            request,
-            result: Err("auth failed".to_string()),
-        })
-        .unwrap();
+    fn should_warn_when_no_local_drafts_exist() {
+        // given a PR session with zero comments
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
         // when
-        app.poll_pr_open_events();
-        // then — open state cleared, error surfaced to message bar, PR
-        // list is intact so the user can retry / pick a different PR
-        assert!(app.pr_open_state.is_none());
-        assert!(app.pr_open_rx.is_none());
-        assert!(matches!(app.pr_tab, PullRequestsTab::Loaded { .. }));
-        let msg = app
-            .message
-            .as_ref()
-            .expect("expected an error message on the bar");
-        assert!(matches!(msg.message_type, MessageType::Error));
-        assert!(msg.content.contains("auth failed"), "got {msg:?}");
+        app.start_submit(SubmitEvent::Comment);
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
     }
 
     #[test]
-    fn should_ignore_stale_done_event_after_cancel() {
-        // given an open was cancelled but the thread's send arrived anyway
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = loaded_pr_tab(vec![sample_pr(42, "boom")]);
-        app.target_tab = TargetTab::PullRequests;
-        let stale_request = crate::app::PrOpenRequest {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 42,
-            started_at: std::time::Instant::now(),
-        };
-        let (tx, rx) = std::sync::mpsc::channel();
-        app.pr_open_rx = Some(rx);
-        // pr_open_state is None — the user already cancelled.
-        tx.send(crate::app::PrOpenEvent::Done {
-            request: stale_request,
-            result: Err("would-have-failed".to_string()),
-        })
-        .unwrap();
-        // when
-        app.poll_pr_open_events();
-        // then — the stale error does not produce a user-visible message
-        assert!(matches!(app.pr_tab, PullRequestsTab::Loaded { .. }));
+    fn should_allow_bare_approve_through_action_picker_with_no_comments() {
+        // Regression: bare `:submit` → picker → cursor on Approve → Enter
+        // should NOT warn "Nothing to submit". Approve is the one event
+        // meaningful with no comments. Picker uses skip_confirm = true, so
+        // the flow goes straight to network dispatch.
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        app.start_submit_action_picker();
+        // Walk cursor to the Approve row (index 1).
+        app.submit_picker_cursor_down();
+        assert_eq!(app.submit_picker_cursor, 1);
+        app.submit_picker_confirm();
+        // No warning was emitted; the network call was dispatched.
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
         assert!(
-            app.message.is_none()
-                || !app
-                    .message
-                    .as_ref()
-                    .unwrap()
-                    .content
-                    .contains("would-have-failed")
+            app.pr_submit_state.is_some(),
+            "picker-confirm should dispatch when Approve is bare-allowed"
         );
     }
 
     #[test]
-    fn should_cancel_in_flight_open_when_pressing_esc_in_selector() {
-        // given
-        let mut app = build_app();
-        app.forge_repository = Some(ForgeRepository::github("github.com", "agavra", "tuicr"));
-        app.pr_tab = loaded_pr_tab(vec![sample_pr(99, "x")]);
-        app.target_tab = TargetTab::PullRequests;
-        app.pr_open_state = Some(crate::app::PrOpenRequest {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 99,
-            started_at: std::time::Instant::now(),
-        });
-        // when
-        crate::handler::handle_commit_select_action(&mut app, crate::input::Action::ExitMode);
-        // then
-        assert!(app.pr_open_state.is_none());
-    }
-
-    // -----------------------------------------------------------------
-    // Remote review threads (PR 4)
-    // -----------------------------------------------------------------
-
-    use crate::forge::remote_comments::{
-        PrCommentsVisibility, RemoteCommentSide, RemoteReviewComment, RemoteReviewThread,
-    };
-
-    struct ThreadAwareForgeBackend {
-        details: crate::forge::traits::PullRequestDetails,
-        patch: String,
-        threads: Vec<RemoteReviewThread>,
-        calls: std::cell::Cell<u32>,
-    }
-
-    impl ThreadAwareForgeBackend {
-        fn new(
-            details: crate::forge::traits::PullRequestDetails,
-            patch: String,
-            threads: Vec<RemoteReviewThread>,
-        ) -> Self {
-            Self {
-                details,
-                patch,
-                threads,
-                calls: std::cell::Cell::new(0),
-            }
-        }
-    }
-
-    impl crate::forge::traits::ForgeBackend for ThreadAwareForgeBackend {
-        fn list_pull_requests(
-            &self,
-            _q: crate::forge::traits::PullRequestListQuery,
-        ) -> Result<crate::forge::traits::PagedPullRequests> {
-            unimplemented!()
-        }
-        fn get_pull_request(
-            &self,
-            _t: crate::forge::traits::PullRequestTarget,
-        ) -> Result<crate::forge::traits::PullRequestDetails> {
-            Ok(self.details.clone())
-        }
-        fn get_pull_request_diff(
-            &self,
-            _p: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<String> {
-            Ok(self.patch.clone())
-        }
-        fn fetch_file_lines(
-            &self,
-            _r: crate::forge::traits::ForgeFileLinesRequest,
-        ) -> Result<Vec<crate::model::DiffLine>> {
-            Ok(Vec::new())
-        }
-        fn list_review_threads(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<Vec<RemoteReviewThread>> {
-            self.calls.set(self.calls.get() + 1);
-            Ok(self.threads.clone())
-        }
-        fn list_pull_request_commits(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-        ) -> Result<Vec<crate::forge::traits::PullRequestCommit>> {
-            Ok(Vec::new())
-        }
-        fn get_pull_request_commit_range_diff(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-            _start_sha: &str,
-            _end_sha: &str,
-        ) -> Result<String> {
-            unreachable!()
-        }
-        fn create_review(
-            &self,
-            _pr: &crate::forge::traits::PullRequestDetails,
-            _request: crate::forge::traits::CreateReviewRequest<'_>,
-        ) -> Result<crate::forge::traits::GhCreateReviewResponse> {
-            unimplemented!()
-        }
+    fn should_allow_bare_approve_without_any_comments() {
+        // Approve is the one event meaningful with no comments (a plain
+        // LGTM). Preflight should proceed; the user lands in the confirm
+        // modal with zero mappable + zero unmappable.
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        app.start_submit(SubmitEvent::Approve);
+        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
+        let state = app.submit_state.as_ref().expect("submit state");
+        assert!(state.mappable.is_empty());
+        assert!(state.unmappable.is_empty());
+        assert_eq!(state.event, SubmitEvent::Approve);
     }
 
-    fn sample_thread(line: u32, body: &str, resolved: bool, outdated: bool) -> RemoteReviewThread {
-        RemoteReviewThread {
-            id: "T".to_string(),
-            path: "src/lib.rs".to_string(),
-            line: Some(line),
-            side: RemoteCommentSide::Right,
-            is_resolved: resolved,
-            is_outdated: outdated,
-            comments: vec![RemoteReviewComment {
-                id: "C".to_string(),
-                author: Some("alice".to_string()),
-                body: body.to_string(),
-                created_at: None,
-                in_reply_to: None,
-                url: "https://example.com/c".to_string(),
-            }],
-        }
+    #[test]
+    fn should_warn_when_submitting_without_pr_mode() {
+        // given an app NOT in PR mode
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "head".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
+        let mut app = App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            Vec::new(),
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("build app");
+        // when
+        app.start_submit(SubmitEvent::Comment);
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
     }
 
     #[test]
-    fn should_populate_remote_threads_when_opening_pr_through_test_seam() {
-        // given
-        let mut app = build_app();
-        let summary = sample_pr(42, "answer");
-        let backend = Box::new(ThreadAwareForgeBackend::new(
-            test_pr_details(42, "answer"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-            vec![sample_thread(2, "remote body", false, false)],
-        ));
+    fn should_warn_when_pr_is_closed_or_merged() {
+        // given a closed PR
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        if let DiffSource::PullRequest(pr) = &mut app.diff_source {
+            pr.closed = true;
+        }
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
+        );
         // when
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
+        app.start_submit(SubmitEvent::Comment);
         // then
-        assert_eq!(app.forge_review_threads.len(), 1);
-        assert_eq!(app.forge_review_threads[0].comments[0].body, "remote body");
-        // default visibility is Unresolved on a fresh PR session
-        assert_eq!(
-            app.session.remote_comments_visibility,
-            PrCommentsVisibility::Unresolved
-        );
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
     }
 
     #[test]
-    fn should_clear_remote_threads_without_refetch_when_setting_visibility_hide() {
-        // given a PR open with one fetched thread
-        let mut app = build_app();
-        let summary = sample_pr(42, "answer");
-        let backend = Box::new(ThreadAwareForgeBackend::new(
-            test_pr_details(42, "answer"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-            vec![sample_thread(2, "remote", false, false)],
-        ));
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        assert_eq!(app.forge_review_threads.len(), 1);
-        // when — switch to hide
-        let changed = app.set_remote_comments_visibility(PrCommentsVisibility::Hide);
-        // then
-        assert!(changed);
-        assert_eq!(
-            app.session.remote_comments_visibility,
-            PrCommentsVisibility::Hide
+    fn should_cancel_submit_clears_state_and_returns_to_normal() {
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
         );
-        // We don't drop the cache on visibility change — only filtering changes.
-        // Switching back to Unresolved should restore the rendered comments
-        // without making a new network call.
-        assert_eq!(app.forge_review_threads.len(), 1);
+        app.start_submit(SubmitEvent::Comment);
+        // when
+        app.cancel_submit();
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
     }
 
     #[test]
-    fn should_route_comments_unresolved_command_through_command_handler() {
-        use crate::handler::handle_command_action;
-        use crate::input::Action;
-        // given
-        let mut app = build_app();
-        let summary = sample_pr(42, "answer");
-        let backend = Box::new(ThreadAwareForgeBackend::new(
-            test_pr_details(42, "answer"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-            vec![sample_thread(2, "remote", false, false)],
-        ));
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        // when — enter command mode then submit `:comments all`
-        app.input_mode = crate::app::InputMode::Command;
-        app.command_buffer = "comments all".to_string();
-        handle_command_action(&mut app, Action::SubmitInput);
+    fn should_toggle_resolver_action_between_move_and_omit() {
+        let mut app = make_pr_app_with_single_modified_file("img.png");
+        app.diff_files[0].is_binary = true;
+        let pb = PathBuf::from("img.png");
+        let review = app.session.get_file_mut(&pb).expect("file in session");
+        review
+            .file_comments
+            .push(Comment::new("a".to_string(), CommentType::Note, None));
+        review
+            .file_comments
+            .push(Comment::new("b".to_string(), CommentType::Note, None));
+        app.start_submit(SubmitEvent::Comment);
+        // when — toggle row 0
+        app.submit_resolver_toggle();
         // then
-        assert_eq!(
-            app.session.remote_comments_visibility,
-            PrCommentsVisibility::All
-        );
+        let state = app.submit_state.as_ref().unwrap();
+        assert_eq!(state.resolver_choices[0], ResolverAction::Omit);
+        assert_eq!(state.resolver_choices[1], ResolverAction::MoveToSummary);
+        // when toggle again
+        app.submit_resolver_toggle();
+        let state = app.submit_state.as_ref().unwrap();
+        assert_eq!(state.resolver_choices[0], ResolverAction::MoveToSummary);
     }
 
     #[test]
-    fn should_warn_when_comments_command_used_outside_pr_mode() {
-        use crate::handler::handle_command_action;
-        use crate::input::Action;
-        // given — plain local working-tree session
-        let mut app = build_app();
-        app.input_mode = crate::app::InputMode::Command;
-        app.command_buffer = "comments all".to_string();
+    fn should_advance_from_resolver_to_confirm() {
+        let mut app = make_pr_app_with_single_modified_file("img.png");
+        app.diff_files[0].is_binary = true;
+        let pb = PathBuf::from("img.png");
+        let review = app.session.get_file_mut(&pb).expect("file in session");
+        review
+            .file_comments
+            .push(Comment::new("a".to_string(), CommentType::Note, None));
+        app.start_submit(SubmitEvent::Comment);
+        assert_eq!(app.input_mode, InputMode::SubmitResolver);
         // when
-        handle_command_action(&mut app, Action::SubmitInput);
-        // then — visibility unchanged, a warning surfaced on the message bar
-        assert_eq!(
-            app.session.remote_comments_visibility,
-            PrCommentsVisibility::Unresolved
-        );
-        let msg = app
-            .message
-            .as_ref()
-            .expect("expected warning on message bar");
-        assert!(matches!(msg.message_type, MessageType::Warning));
-        assert!(
-            msg.content.contains("PR mode"),
-            "got message: {}",
-            msg.content
+        app.submit_resolver_advance();
+        // then
+        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
+    }
+
+    #[test]
+    fn should_skip_confirm_modal_when_action_picker_dispatches_with_no_unmappable() {
+        // Bare `:submit` → action picker → pick Comment → directly dispatch
+        // the network call without SubmitConfirm. submit_state is cleared
+        // and pr_submit_state populated, same end state as the explicit
+        // `:submit comment` + [y] flow.
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
         );
+
+        app.start_submit_action_picker();
+        assert_eq!(app.input_mode, InputMode::SubmitActionPicker);
+        app.submit_picker_confirm();
+
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
+        assert!(app.pr_submit_state.is_some());
     }
 
     #[test]
-    fn should_apply_remote_threads_event_when_relevant() {
-        // given a PR session is open at head=`headsha`
-        let mut app = build_app();
-        let summary = sample_pr(42, "answer");
-        let backend = Box::new(ThreadAwareForgeBackend::new(
-            test_pr_details(42, "answer"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-            // open with empty threads — we'll deliver via the channel
-            Vec::new(),
+    fn should_route_picker_through_resolver_then_skip_confirm() {
+        // Bare `:submit` picker with one unmappable comment → resolver
+        // appears → `s` advances and dispatches the network call directly,
+        // bypassing SubmitConfirm. skip_confirm is the flag that makes
+        // submit_resolver_advance bypass the confirm modal.
+        let mut app = make_pr_app_with_single_modified_file("img.png");
+        app.diff_files[0].is_binary = true;
+        let pb = PathBuf::from("img.png");
+        let review = app.session.get_file_mut(&pb).expect("file in session");
+        review.file_comments.push(Comment::new(
+            "binary art".to_string(),
+            CommentType::Note,
+            None,
         ));
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        // simulate a background fetch that finished after open
-        let (tx, rx) = std::sync::mpsc::channel();
-        app.pr_threads_rx = Some(rx);
-        app.forge_review_threads_loading = true;
-        let pr_key = match &app.diff_source {
-            DiffSource::PullRequest(pr) => pr.key.clone(),
-            _ => panic!("expected PR mode"),
-        };
-        tx.send(crate::app::PrThreadsEvent::Done {
-            repository: pr_key.repository.clone(),
-            pr_number: pr_key.number,
-            head_sha: pr_key.head_sha.clone(),
-            result: Ok(vec![sample_thread(2, "delayed", false, false)]),
-        })
-        .unwrap();
+
+        app.start_submit_action_picker();
+        app.submit_picker_cursor = 0; // Comment
+        app.submit_picker_confirm();
+
+        // Picker dispatched → resolver visible because the comment is
+        // unmappable, but the underlying skip_confirm flag is set.
+        assert_eq!(app.input_mode, InputMode::SubmitResolver);
+        let state = app.submit_state.as_ref().expect("submit state");
+        assert!(state.skip_confirm);
+
+        // Advance from resolver → goes straight to network call, no
+        // SubmitConfirm.
+        app.submit_resolver_advance();
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
+        assert!(app.pr_submit_state.is_some());
+    }
+
+    #[test]
+    fn should_dispatch_async_submit_on_confirm_and_clear_modal_state() {
+        // given a PR session with one mappable line comment
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
+        );
+        app.start_submit(SubmitEvent::Comment);
         // when
-        app.poll_pr_threads_events();
-        // then
-        assert!(!app.forge_review_threads_loading);
-        assert_eq!(app.forge_review_threads.len(), 1);
-        assert_eq!(app.forge_review_threads[0].comments[0].body, "delayed");
+        app.confirm_submit();
+        // then — PR 6: confirmation modal disappears immediately; the
+        // background thread is running with state captured on
+        // pr_submit_state so the spinner has something to render.
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.submit_state.is_none());
+        assert!(
+            app.pr_submit_state.is_some(),
+            "spawn_pr_submit should populate pr_submit_state"
+        );
+        assert!(app.pr_submit_rx.is_some(), "rx must be present in-flight");
     }
 
-    #[test]
-    fn should_discard_stale_remote_threads_event_after_switching_pr() {
-        // given a PR open, then user switches to a different PR while a
-        // fetch is in flight
-        let mut app = build_app();
-        let summary = sample_pr(42, "answer");
-        let backend = Box::new(ThreadAwareForgeBackend::new(
-            test_pr_details(42, "answer"),
-            crate::forge::github::gh::tests_fixture::SIMPLE_PATCH.to_string(),
-            Vec::new(),
-        ));
-        app.open_pr_with_backend(&summary, backend, None).unwrap();
-        // simulate a stale event from a different PR head
-        let (tx, rx) = std::sync::mpsc::channel();
-        app.pr_threads_rx = Some(rx);
-        tx.send(crate::app::PrThreadsEvent::Done {
+    fn make_in_flight(
+        event: SubmitEvent,
+        comment_ids: &[&str],
+        head_sha: &str,
+        moved_to_summary_count: usize,
+    ) -> SubmitInFlightState {
+        let mappable = comment_ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| crate::forge::submit::InlineComment {
+                path: PathBuf::from("src/lib.rs"),
+                line: 11 + i as u32,
+                side: crate::forge::submit::GhSide::Right,
+                start_line: None,
+                start_side: None,
+                body: "x".to_string(),
+                comment_id: (*id).to_string(),
+            })
+            .collect();
+        SubmitInFlightState {
+            event,
+            mappable,
+            moved_to_summary_count,
+            head_sha_snapshot: head_sha.to_string(),
             repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 999,                         // wrong number
-            head_sha: "definitely-not-this".into(), // wrong head
-            result: Ok(vec![sample_thread(2, "stale", false, false)]),
-        })
-        .unwrap();
-        // when
-        app.poll_pr_threads_events();
-        // then — stale result was dropped
-        assert!(app.forge_review_threads.is_empty());
+            pr_number: 125,
+            started_at: Instant::now(),
+        }
     }
-}
 
-#[cfg(test)]
-mod scroll_tests {
-    /// max_scroll_offset is simply total_lines - 1 (last line can be at top).
-    fn calc_max_scroll(total_lines: usize) -> usize {
-        total_lines.saturating_sub(1)
+    fn make_response(
+        id: u64,
+        html_url: &str,
+        state: &str,
+    ) -> crate::forge::traits::GhCreateReviewResponse {
+        crate::forge::traits::GhCreateReviewResponse {
+            id,
+            html_url: html_url.to_string(),
+            state: state.to_string(),
+        }
     }
 
     #[test]
-    fn should_calculate_max_scroll() {
-        // Last line can be scrolled to the top of the viewport
-        assert_eq!(calc_max_scroll(103), 102);
-        assert_eq!(calc_max_scroll(20), 19);
+    fn should_flip_comments_to_submitted_and_stamp_review_id_on_success() {
+        // given an app with one line comment that we'll claim got submitted
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let mut comment = line_comment(LineSide::New, Some(11), None);
+        let comment_id = comment.id.clone();
+        comment.lifecycle_state = CommentLifecycleState::LocalDraft;
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        let in_flight = make_in_flight(
+            SubmitEvent::Comment,
+            &[comment_id.as_str()],
+            "abcdef0123",
+            0,
+        );
+        let response = make_response(987654, "https://example.com/r", "COMMENTED");
+        // when
+        app.apply_submit_success(&in_flight, &response);
+        // then — the comment lifecycle moved to Submitted and remote_review_id is set.
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
+        let saved = &review.line_comments.get(&11).unwrap()[0];
+        assert_eq!(saved.lifecycle_state, CommentLifecycleState::Submitted);
+        assert_eq!(saved.remote_review_id.as_deref(), Some("987654"));
     }
 
     #[test]
-    fn should_handle_small_content() {
-        // Even with few lines, can scroll last line to top
-        assert_eq!(calc_max_scroll(13), 12);
-        assert_eq!(calc_max_scroll(1), 0);
+    fn should_flip_comments_to_pushed_draft_for_draft_submission() {
+        // given
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = line_comment(LineSide::New, Some(11), None);
+        let comment_id = comment.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        let in_flight = make_in_flight(SubmitEvent::Draft, &[comment_id.as_str()], "abcdef0123", 0);
+        let response = make_response(42, "https://example.com/r", "PENDING");
+        // when
+        app.apply_submit_success(&in_flight, &response);
+        // then
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
+        let saved = &review.line_comments.get(&11).unwrap()[0];
+        assert_eq!(saved.lifecycle_state, CommentLifecycleState::PushedDraft);
+        assert_eq!(saved.remote_review_id.as_deref(), Some("42"));
     }
 
     #[test]
-    fn should_handle_empty_content() {
-        assert_eq!(calc_max_scroll(0), 0);
-    }
-}
-
-#[cfg(test)]
-mod scroll_behavior_tests {
-    use super::*;
-    use crate::model::FileStatus;
-    use crate::vcs::traits::VcsType;
-
-    struct DummyVcs {
-        info: VcsInfo,
-    }
-
-    impl VcsBackend for DummyVcs {
-        fn info(&self) -> &VcsInfo {
-            &self.info
-        }
-
-        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-            Err(TuicrError::NoChanges)
-        }
-
-        fn fetch_context_lines(
-            &self,
-            _file_path: &Path,
-            _file_status: FileStatus,
-            _start_line: u32,
-            _end_line: u32,
-        ) -> Result<Vec<DiffLine>> {
-            Ok(Vec::new())
-        }
+    fn should_only_flip_comments_whose_ids_were_submitted() {
+        // given — one matching id and one stray local-draft that wasn't in the submit
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = line_comment(LineSide::New, Some(11), None);
+        let target_id = comment.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        let untouched = line_comment(LineSide::New, Some(11), None);
+        let untouched_id = untouched.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, untouched);
+        let in_flight =
+            make_in_flight(SubmitEvent::Comment, &[target_id.as_str()], "abcdef0123", 0);
+        let response = make_response(1, "u", "COMMENTED");
+        // when
+        app.apply_submit_success(&in_flight, &response);
+        // then — only the target id moved
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
+        let comments = review.line_comments.get(&11).unwrap();
+        let target = comments.iter().find(|c| c.id == target_id).unwrap();
+        let other = comments.iter().find(|c| c.id == untouched_id).unwrap();
+        assert_eq!(target.lifecycle_state, CommentLifecycleState::Submitted);
+        assert_eq!(other.lifecycle_state, CommentLifecycleState::LocalDraft);
+        assert!(other.remote_review_id.is_none());
     }
 
-    /// Build a test App with a single file containing `n` context lines.
-    /// Total rendered lines = 1 (review header) + 1 (file header) + 1 (spacing)
-    ///                       + 1 (hunk header) + n (diff lines) = n + 4.
-    /// The viewport is set to `viewport` lines.
-    fn build_scroll_app(n: usize, viewport: usize, scroll_offset_config: usize) -> App {
-        let lines: Vec<DiffLine> = (1..=n)
-            .map(|i| DiffLine {
-                origin: crate::model::LineOrigin::Context,
-                content: format!("line {i}"),
-                old_lineno: Some(i as u32),
-                new_lineno: Some(i as u32),
-                highlighted_spans: None,
-            })
-            .collect();
-
-        let hunk = DiffHunk {
-            header: "@@ -1,N +1,N @@".to_string(),
-            lines,
-            old_start: 1,
-            old_count: n as u32,
-            new_start: 1,
-            new_count: n as u32,
-        };
-
-        let file = DiffFile {
-            old_path: None,
-            new_path: Some(PathBuf::from("test.rs")),
-            status: FileStatus::Modified,
-            hunks: vec![hunk],
-            is_binary: false,
-            is_too_large: false,
-            is_commit_message: false,
-            content_hash: 0,
-        };
-
-        let vcs_info = VcsInfo {
-            root_path: PathBuf::from("/tmp"),
-            head_commit: "abc".to_string(),
-            branch_name: Some("main".to_string()),
-            vcs_type: VcsType::Git,
-        };
-        let session = ReviewSession::new(
-            vcs_info.root_path.clone(),
-            vcs_info.head_commit.clone(),
-            vcs_info.branch_name.clone(),
-            SessionDiffSource::WorkingTree,
+    #[test]
+    fn should_emit_success_message_with_review_id_and_counts_for_published_submit() {
+        // given
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = line_comment(LineSide::New, Some(11), None);
+        let comment_id = comment.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        let in_flight = make_in_flight(
+            SubmitEvent::Comment,
+            &[comment_id.as_str()],
+            "abcdef0123",
+            2,
         );
-
-        let mut app = App::build(
-            Box::new(DummyVcs {
-                info: vcs_info.clone(),
-            }),
-            vcs_info,
-            Theme::dark(),
-            None,
-            false,
-            vec![file],
-            session,
-            DiffSource::WorkingTree,
-            InputMode::Normal,
-            Vec::new(),
-            None,
-        )
-        .expect("failed to build test app");
-
-        app.diff_state.viewport_height = viewport;
-        app.diff_state.visible_line_count = viewport;
-        app.scroll_offset = scroll_offset_config;
-        app
+        let response = make_response(123456, "https://example.com/r", "COMMENTED");
+        // when
+        app.finish_pr_submit(in_flight, Ok(response));
+        // then
+        let msg = app.message.as_ref().expect("info message");
+        assert_eq!(msg.message_type, MessageType::Info);
+        assert!(msg.content.contains("Submitted GitHub review #123456"));
+        assert!(msg.content.contains("1 inline"));
+        assert!(msg.content.contains("2 moved to summary"));
     }
 
     #[test]
-    fn zz_on_last_line_centers_cursor() {
-        // 40 diff lines + 4 overhead = 44 total. max_cursor = 42. Viewport = 20.
-        let mut app = build_scroll_app(40, 20, 5);
-        assert_eq!(app.total_lines(), 44);
-        let last = app.max_cursor_line(); // 42
-
-        app.diff_state.cursor_line = last;
-        app.center_cursor();
-
-        // scroll = cursor - viewport/2 = 42 - 10 = 32
-        assert_eq!(app.diff_state.scroll_offset, 32);
-        assert_eq!(app.diff_state.cursor_line, 42);
+    fn should_emit_draft_message_with_pr_url_for_draft_submit() {
+        // given
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = line_comment(LineSide::New, Some(11), None);
+        let comment_id = comment.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        let in_flight = make_in_flight(SubmitEvent::Draft, &[comment_id.as_str()], "abcdef0123", 0);
+        let response = make_response(
+            999,
+            "https://github.com/agavra/tuicr/pull/125#pullrequestreview-999",
+            "PENDING",
+        );
+        // when
+        app.finish_pr_submit(in_flight, Ok(response));
+        // then
+        let msg = app.message.as_ref().expect("info message");
+        assert!(msg.content.contains("Pushed pending GitHub review #999"));
+        assert!(
+            msg.content
+                .contains("https://github.com/agavra/tuicr/pull/125"),
+            "draft message should include the PR URL — got: {}",
+            msg.content
+        );
     }
 
     #[test]
-    fn after_zz_on_last_line_j_does_not_change_scroll() {
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
-
-        app.diff_state.cursor_line = last;
-        app.center_cursor();
-        let scroll_after_zz = app.diff_state.scroll_offset;
-
-        // Press j — cursor is already at max, and it's centered (not near bottom margin)
-        app.cursor_down(1);
-
-        assert_eq!(app.diff_state.cursor_line, last);
-        assert_eq!(
-            app.diff_state.scroll_offset, scroll_after_zz,
-            "j after zz on last line should not change scroll"
+    fn should_keep_comments_as_local_draft_on_submit_failure() {
+        // given a local-draft comment in the session
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = line_comment(LineSide::New, Some(11), None);
+        let comment_id = comment.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        let in_flight = make_in_flight(
+            SubmitEvent::Comment,
+            &[comment_id.as_str()],
+            "abcdef0123",
+            0,
+        );
+        // when — network failure
+        app.finish_pr_submit(
+            in_flight,
+            Err(
+                "Cannot submit review: GitHub token lacks pull request write permission."
+                    .to_string(),
+            ),
         );
+        // then — the comment is still LocalDraft
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
+        let saved = &review.line_comments.get(&11).unwrap()[0];
+        assert_eq!(saved.lifecycle_state, CommentLifecycleState::LocalDraft);
+        assert!(saved.remote_review_id.is_none());
+        // and — a sticky error message is set
+        let msg = app.message.as_ref().expect("error message");
+        assert_eq!(msg.message_type, MessageType::Error);
+        assert!(msg.content.contains("Submit failed"));
+        assert!(msg.content.contains("pull request write permission"));
     }
 
     #[test]
-    fn after_zz_on_last_line_k_does_not_change_scroll() {
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
-
-        app.diff_state.cursor_line = last;
-        app.center_cursor();
-        let scroll_after_zz = app.diff_state.scroll_offset;
-
-        // Press k — cursor moves up 1, still in free zone
-        app.cursor_up(1);
-
-        assert_eq!(app.diff_state.cursor_line, last - 1);
-        assert_eq!(
-            app.diff_state.scroll_offset, scroll_after_zz,
-            "k after zz on last line should not change scroll"
+    fn should_discard_stale_submit_result_when_head_sha_changed() {
+        // given a PR session pretending we already swapped heads after submit dispatched.
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = line_comment(LineSide::New, Some(11), None);
+        let comment_id = comment.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        // record that a submit was in flight for an *older* head.
+        let in_flight = make_in_flight(SubmitEvent::Comment, &[comment_id.as_str()], "OLD_HEAD", 0);
+        app.pr_submit_state = Some(in_flight);
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.pr_submit_rx = Some(rx);
+        // simulate the bg thread coming back for the OLD head, but with
+        // a fresh-head session note: poll path checks repository/pr_number/head_sha.
+        tx.send(PrSubmitEvent::Done {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 125,
+            head_sha: "DIFFERENT_HEAD".to_string(),
+            result: Ok(make_response(42, "u", "COMMENTED")),
+        })
+        .unwrap();
+        drop(tx);
+        // when
+        app.poll_pr_submit_events();
+        // then — comment lifecycle untouched, info message about discarded result.
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
+        let saved = &review.line_comments.get(&11).unwrap()[0];
+        assert_eq!(saved.lifecycle_state, CommentLifecycleState::LocalDraft);
+        let msg = app.message.as_ref().expect("info message");
+        assert!(
+            msg.content.contains("Discarded stale submit result"),
+            "got: {}",
+            msg.content
         );
     }
 
     #[test]
-    fn after_zz_no_oscillation_with_k_then_j() {
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
-
-        app.diff_state.cursor_line = last;
-        app.center_cursor();
-        let scroll_after_zz = app.diff_state.scroll_offset;
-
-        // k then j should return to the same state
-        app.cursor_up(1);
-        app.cursor_down(1);
-
-        assert_eq!(app.diff_state.cursor_line, last);
-        assert_eq!(
-            app.diff_state.scroll_offset, scroll_after_zz,
-            "k then j after zz should not cause oscillation"
+    fn should_apply_result_via_poll_when_head_sha_matches() {
+        // given a session with a local-draft comment ready to be locked
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = line_comment(LineSide::New, Some(11), None);
+        let comment_id = comment.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, comment);
+        let in_flight = make_in_flight(
+            SubmitEvent::Comment,
+            &[comment_id.as_str()],
+            "abcdef0123",
+            0,
         );
+        app.pr_submit_state = Some(in_flight);
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.pr_submit_rx = Some(rx);
+        tx.send(PrSubmitEvent::Done {
+            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
+            pr_number: 125,
+            head_sha: "abcdef0123".to_string(),
+            result: Ok(make_response(123, "u", "COMMENTED")),
+        })
+        .unwrap();
+        drop(tx);
+        // when
+        app.poll_pr_submit_events();
+        // then — lifecycle moved and the spinner state is cleared.
+        assert!(app.pr_submit_state.is_none());
+        assert!(app.pr_submit_rx.is_none());
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
+        let saved = &review.line_comments.get(&11).unwrap()[0];
+        assert_eq!(saved.lifecycle_state, CommentLifecycleState::Submitted);
+        assert_eq!(saved.remote_review_id.as_deref(), Some("123"));
     }
 
     #[test]
-    fn j_scrolls_one_line_at_a_time() {
-        // Viewport 20, total 44. Start at the middle and scroll down.
-        let mut app = build_scroll_app(40, 20, 5);
-
-        // Position cursor and scroll in steady state near the bottom margin
-        app.diff_state.cursor_line = 20;
-        app.diff_state.scroll_offset = 6;
-        // steady state: cursor at bottom margin = scroll + visible - margin - 1
-
-        // Scroll down multiple times and verify single-line increments
-        for _ in 0..10 {
-            let prev_scroll = app.diff_state.scroll_offset;
-            let prev_cursor = app.diff_state.cursor_line;
-            app.cursor_down(1);
-            let scroll_delta = app.diff_state.scroll_offset - prev_scroll;
-            let cursor_delta = app.diff_state.cursor_line - prev_cursor;
-            assert_eq!(cursor_delta, 1, "cursor should advance by exactly 1");
-            assert!(
-                scroll_delta <= 1,
-                "scroll should advance by at most 1, got {scroll_delta}"
-            );
+    fn should_lock_file_level_comment_via_submit_success() {
+        // given — a file-level comment lives in `file_comments`, not `line_comments`
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let comment = Comment::new("file-level".to_string(), CommentType::Note, None);
+        let comment_id = comment.id.clone();
+        {
+            let review = app
+                .session
+                .get_file_mut(&PathBuf::from("src/lib.rs"))
+                .unwrap();
+            review.file_comments.push(comment);
         }
-    }
-
-    #[test]
-    fn j_on_last_line_near_bottom_does_not_scroll() {
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
-
-        // Put cursor at last line with it near the bottom of viewport
-        app.diff_state.cursor_line = last;
-        app.diff_state.scroll_offset = last.saturating_sub(19); // cursor at bottom of viewport
-
-        let prev_scroll = app.diff_state.scroll_offset;
-        app.cursor_down(1);
-
-        assert_eq!(app.diff_state.cursor_line, last);
+        let in_flight = make_in_flight(
+            SubmitEvent::Comment,
+            &[comment_id.as_str()],
+            "abcdef0123",
+            0,
+        );
+        let response = make_response(7, "u", "COMMENTED");
+        // when
+        app.apply_submit_success(&in_flight, &response);
+        // then
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
         assert_eq!(
-            app.diff_state.scroll_offset, prev_scroll,
-            "j on last line should never scroll the view"
+            review.file_comments[0].lifecycle_state,
+            CommentLifecycleState::Submitted
+        );
+        assert_eq!(
+            review.file_comments[0].remote_review_id.as_deref(),
+            Some("7")
         );
     }
 
-    #[test]
-    fn j_on_last_line_centered_does_not_scroll() {
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
-
-        // Center cursor on last line
-        app.diff_state.cursor_line = last;
-        app.center_cursor();
-        let scroll_after_center = app.diff_state.scroll_offset;
-
-        app.cursor_down(1);
-
-        assert_eq!(
-            app.diff_state.scroll_offset, scroll_after_center,
-            "j on last line when centered should not scroll"
+    #[test]
+    fn should_report_stale_head_when_current_differs_from_session_head() {
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
         );
+        // simulate a refresh having spotted a newer remote head
+        app.current_pr_head = Some("ffff5678".to_string());
+        app.start_submit(SubmitEvent::Comment);
+        assert!(app.submit_head_is_stale());
     }
 
     #[test]
-    fn k_reclaims_empty_space_below() {
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
-
-        // Put cursor at last line at top of view (maximum empty space below)
-        app.diff_state.cursor_line = last;
-        app.diff_state.scroll_offset = last; // only 1 line visible
-
-        // Press k — should immediately reclaim space (reduce scroll)
-        app.cursor_up(1);
-
-        assert_eq!(app.diff_state.cursor_line, last - 1);
-        assert!(
-            app.diff_state.scroll_offset < last,
-            "k should reclaim empty space below, scroll was {} expected less than {}",
-            app.diff_state.scroll_offset,
-            last
+    fn should_report_head_not_stale_when_current_matches_session_head() {
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
         );
+        app.start_submit(SubmitEvent::Comment);
+        assert!(!app.submit_head_is_stale());
     }
 
     #[test]
-    fn max_scroll_allows_last_line_at_top() {
-        let app = build_scroll_app(40, 20, 5);
-        let total = app.total_lines();
-
-        assert_eq!(
-            app.max_scroll_offset(),
-            total - 1,
-            "max scroll should allow last line at top of viewport"
-        );
+    fn should_detect_locked_comment_under_cursor_for_dd_path() {
+        // given an app with a locked line comment registered against the
+        // diff. We just verify the App helper sees the lock — exercising
+        // the handler keypath itself is covered in integration tests.
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let mut c = line_comment(LineSide::New, Some(11), None);
+        c.lifecycle_state = CommentLifecycleState::PushedDraft;
+        add_line_comment(&mut app, "src/lib.rs", 11, c);
+        // No cursor positioning here — `cursor_on_locked_comment` resolves
+        // through `find_comment_at_cursor` which depends on annotations.
+        // The annotation indices use 0..N; with a single line comment on
+        // line 11 there's exactly one LineComment annotation. We point the
+        // cursor at it via diff_state.
+        app.rebuild_annotations();
+        // Find the LineComment annotation index.
+        let idx = app
+            .line_annotations
+            .iter()
+            .position(|a| matches!(a, AnnotatedLine::LineComment { .. }))
+            .expect("expected a LineComment annotation");
+        app.diff_state.cursor_line = idx;
+        assert!(app.cursor_on_locked_comment());
     }
 
     #[test]
-    fn smooth_scroll_to_end_no_jumps() {
-        // Start at beginning, scroll all the way to the end with j presses
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
+    fn should_ask_before_posting_to_gitlab() {
+        // given
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
+        );
 
-        app.diff_state.cursor_line = 0;
-        app.diff_state.scroll_offset = 0;
+        // when
+        app.submit_to_gitlab("https://gitlab.com/g/p/-/merge_requests/3");
 
-        let mut max_scroll_delta = 0;
-        for _ in 0..last {
-            let prev_scroll = app.diff_state.scroll_offset;
-            app.cursor_down(1);
-            let delta = app.diff_state.scroll_offset.saturating_sub(prev_scroll);
-            if delta > max_scroll_delta {
-                max_scroll_delta = delta;
-            }
-        }
+        // then
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        let dialog = app.pending_confirm.as_ref().unwrap();
+        assert_eq!(dialog.message, "Post 1 discussion(s) to g/p!3?");
+        assert!(matches!(dialog.action, ConfirmAction::PostDiscussions(_)));
+        assert!(app.discussion_post_state.is_none());
+    }
 
-        assert_eq!(app.diff_state.cursor_line, last);
-        assert!(
-            max_scroll_delta <= 1,
-            "scroll should never jump more than 1 line at a time, max was {max_scroll_delta}"
+    #[test]
+    fn should_ask_before_posting_to_bitbucket() {
+        // given
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        app.bitbucket_config.token = Some("secret".to_string());
+        add_line_comment(
+            &mut app,
+            "src/lib.rs",
+            11,
+            line_comment(LineSide::New, Some(11), None),
         );
+
+        // when
+        app.submit_to_bitbucket("https://bitbucket.org/acme/widgets/pull-requests/5");
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        let dialog = app.pending_confirm.as_ref().unwrap();
+        assert_eq!(dialog.message, "Post 1 comment(s) to acme/widgets#5?");
+        assert!(app.discussion_post_state.is_none());
     }
 
     #[test]
-    fn k_below_midpoint_only_moves_cursor() {
-        // After G, cursor is near the bottom of viewport. Pressing k should
-        // only move the cursor, not also scroll the view (which would cause
-        // a visual 2-line jump).
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
+    fn should_lock_posted_discussions_and_leave_them_out_of_the_next_post() {
+        // given
+        let _reviews = crate::persistence::storage::tests::with_test_reviews_dir();
+        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
+        let posted = line_comment(LineSide::New, Some(11), None);
+        let posted_id = posted.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, posted);
+        let rejected = line_comment(LineSide::New, Some(11), None);
+        let rejected_id = rejected.id.clone();
+        add_line_comment(&mut app, "src/lib.rs", 11, rejected);
+        let target = DiscussionTarget::GitLab(
+            crate::forge::gitlab::glab::MergeRequestTarget::parse(
+                "https://gitlab.com/g/p/-/merge_requests/3",
+                None,
+            )
+            .unwrap(),
+        );
+        let outcome = crate::forge::discussions::PostOutcome {
+            posted: 1,
+            posted_comment_ids: vec![posted_id.clone()],
+            failures: vec!["HTTP 400".to_string()],
+            stopped: None,
+        };
 
-        // Simulate G: cursor at last line, scroll positions it at bottom
-        app.diff_state.cursor_line = last;
-        app.diff_state.scroll_offset = last.saturating_sub(19);
-        let scroll_before = app.diff_state.scroll_offset;
+        // when
+        app.finish_discussion_post(&target, Ok(outcome));
 
-        // k should only move cursor, not scroll
-        app.cursor_up(1);
-        assert_eq!(app.diff_state.cursor_line, last - 1);
-        assert_eq!(
-            app.diff_state.scroll_offset, scroll_before,
-            "k when cursor is below midpoint should not change scroll"
+        // then
+        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
+        let state = |id: &str| {
+            review.line_comments[&11]
+                .iter()
+                .find(|c| c.id == id)
+                .unwrap()
+                .lifecycle_state
+        };
+        assert_eq!(state(&posted_id), CommentLifecycleState::Submitted);
+        assert_eq!(state(&rejected_id), CommentLifecycleState::LocalDraft);
+        let next = crate::forge::discussions::build_discussions(
+            &app.session,
+            &app.diff_files,
+            &app.forge_config,
         );
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].comment_ids, vec![rejected_id]);
+        assert!(app.message.as_ref().unwrap().content.contains("1 failed"));
     }
+}
 
-    #[test]
-    fn no_scroll_when_last_line_visible() {
-        // When the last content line is visible, cursor should descend
-        // to it without the view scrolling (no bottom margin near EOF).
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line(); // 42
+#[cfg(test)]
+mod review_test_app {
+    //! A one-file app shared by the per-feature tests below: `src/lib.rs`
+    //! with a context line, a deleted line and an added line.
+    use super::*;
+    use crate::model::{FileStatus, LineOrigin};
+    use crate::vcs::traits::VcsType;
 
-        // Position so last line is visible at viewport bottom: scroll=23, shows lines 23-42
-        app.diff_state.scroll_offset = last.saturating_sub(19); // 23
-        app.diff_state.cursor_line = last - 5; // 37, viewport position 14
+    struct DummyVcs {
+        info: VcsInfo,
+    }
 
-        // Descend toward the last line — scroll should not change
-        for i in 0..5 {
-            let scroll_before = app.diff_state.scroll_offset;
-            app.cursor_down(1);
-            assert_eq!(
-                app.diff_state.scroll_offset, scroll_before,
-                "scroll should not change on step {i} (cursor near EOF with last line visible)"
-            );
+    impl VcsBackend for DummyVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
         }
-        assert_eq!(app.diff_state.cursor_line, last);
-    }
 
-    #[test]
-    fn cursor_cannot_go_past_last_content_line() {
-        let mut app = build_scroll_app(40, 20, 5);
-        let last = app.max_cursor_line();
-        let total = app.total_lines();
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
 
-        // max_cursor should be strictly less than total_lines - 1
-        // (total-1 is the trailing Spacing line)
-        assert_eq!(last, total - 2);
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
+    }
 
-        // cursor_down from last line should not advance
-        app.diff_state.cursor_line = last;
-        app.cursor_down(1);
-        assert_eq!(app.diff_state.cursor_line, last);
+    pub(super) fn line(
+        origin: LineOrigin,
+        content: &str,
+        old: Option<u32>,
+        new: Option<u32>,
+    ) -> DiffLine {
+        DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        }
     }
 
-    #[test]
-    fn effective_scroll_margin_prevents_oscillation() {
-        // With viewport 21 (odd), margin should be at most 9 (= 21/2 - 1 = 9)
-        // so that after centering at position 10 (= 21/2), there's free space
-        let state = DiffState {
-            visible_line_count: 21,
-            viewport_height: 21,
-            ..DiffState::default()
+    pub(super) fn build_app() -> App {
+        let hunk = DiffHunk {
+            header: "@@ -1,2 +1,2 @@".to_string(),
+            lines: vec![
+                line(LineOrigin::Context, "// teh header", Some(1), Some(1)),
+                line(LineOrigin::Deletion, "old", Some(2), None),
+                line(LineOrigin::Addition, "let x = 1;", None, Some(2)),
+            ],
+            old_start: 1,
+            old_count: 2,
+            new_start: 1,
+            new_count: 2,
         };
-        let margin = state.effective_scroll_margin(100);
-        assert!(
-            margin < 21 / 2,
-            "margin ({margin}) must be strictly less than half viewport ({})",
-            21 / 2
+        let file = DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![hunk],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
         );
+        App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![file],
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app")
     }
 
-    #[test]
-    fn scroll_offset_zero_means_no_margin() {
-        // When scroll_offset is 0, effective margin should be 0 (no margin at file start)
-        let state = DiffState {
-            visible_line_count: 20,
-            viewport_height: 20,
-            ..DiffState::default()
-        };
-        let margin = state.effective_scroll_margin(0);
-        assert_eq!(margin, 0, "margin should be 0 when scroll_offset is 0");
+    pub(super) fn move_to_line(app: &mut App, content: &str) {
+        let idx = app
+            .line_annotations
+            .iter()
+            .position(|a| match a {
+                AnnotatedLine::DiffLine {
+                    file_idx,
+                    hunk_idx,
+                    line_idx,
+                    ..
+                } => app.diff_files[*file_idx].hunks[*hunk_idx].lines[*line_idx].content == content,
+                _ => false,
+            })
+            .expect("line should be annotated");
+        app.diff_state.cursor_line = idx;
     }
 }
 
 #[cfg(test)]
-mod find_source_line_tests {
+mod review_fix_tests {
+    use super::review_test_app::{build_app, move_to_line};
     use super::*;
 
-    fn make_diff_line(file_idx: usize, new_lineno: Option<u32>) -> AnnotatedLine {
-        AnnotatedLine::DiffLine {
-            file_idx,
-            hunk_idx: 0,
-            line_idx: 0,
-            old_lineno: None,
-            new_lineno,
-        }
+    #[test]
+    fn should_stash_fix_for_context_line_without_touching_diff() {
+        // given
+        let mut app = build_app();
+        move_to_line(&mut app, "// teh header");
+        // when
+        app.fix_line_at_cursor("// the header");
+        // then
+        assert_eq!(app.session.review_edits.len(), 1);
+        let edit = &app.session.review_edits[0];
+        assert_eq!(edit.path, PathBuf::from("src/lib.rs"));
+        assert_eq!(edit.line, 1);
+        assert_eq!(edit.original, "// teh header");
+        assert_eq!(app.diff_files[0].hunks[0].lines[0].content, "// teh header");
+        assert!(app.dirty);
     }
 
-    fn make_diff_line_with_old(
-        file_idx: usize,
-        old_lineno: Option<u32>,
-        new_lineno: Option<u32>,
-    ) -> AnnotatedLine {
-        AnnotatedLine::DiffLine {
-            file_idx,
-            hunk_idx: 0,
-            line_idx: 0,
-            old_lineno,
-            new_lineno,
-        }
-    }
+    #[test]
+    fn should_refuse_fix_on_deleted_line() {
+        let mut app = build_app();
+        move_to_line(&mut app, "old");
 
-    fn make_sbs_line(file_idx: usize, new_lineno: Option<u32>) -> AnnotatedLine {
-        AnnotatedLine::SideBySideLine {
-            file_idx,
-            hunk_idx: 0,
-            del_line_idx: None,
-            add_line_idx: None,
-            old_lineno: None,
-            new_lineno,
-        }
+        app.fix_line_at_cursor("new");
+
+        assert!(app.session.review_edits.is_empty());
+        assert!(app.fix_command_prefill().is_none());
     }
 
     #[test]
-    fn should_find_exact_match() {
-        let annotations = vec![
-            AnnotatedLine::FileHeader { file_idx: 0 },
-            make_diff_line(0, Some(10)),
-            make_diff_line(0, Some(11)),
-            make_diff_line(0, Some(12)),
-        ];
+    fn should_prefill_fix_command_with_stashed_replacement() {
+        let mut app = build_app();
+        move_to_line(&mut app, "let x = 1;");
+        assert_eq!(app.fix_command_prefill().as_deref(), Some("fix let x = 1;"));
 
-        let result = find_source_line(&annotations, 0, 11, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Exact(2));
+        app.fix_line_at_cursor("let x = 2;");
+        assert_eq!(app.fix_command_prefill().as_deref(), Some("fix let x = 2;"));
+
+        app.unfix_line_at_cursor();
+        assert!(app.session.review_edits.is_empty());
     }
+}
+
+#[cfg(test)]
+mod defer_tests {
+    use super::review_test_app::{build_app, move_to_line};
+    use super::*;
 
     #[test]
-    fn should_find_nearest_when_no_exact_match() {
-        let annotations = vec![
-            make_diff_line(0, Some(10)),
-            make_diff_line(0, Some(15)),
-            make_diff_line(0, Some(20)),
-        ];
+    fn should_defer_lines_and_jump_between_them() {
+        // given
+        let mut app = build_app();
+        move_to_line(&mut app, "old");
+        app.toggle_defer_at_cursor();
+        move_to_line(&mut app, "let x = 1;");
+        app.toggle_defer_at_cursor();
+        let deleted = app.diff_state.cursor_line - 1;
+        let added = app.diff_state.cursor_line;
 
-        // Target 12 is closest to line 10 (dist=2) vs 15 (dist=3) vs 20 (dist=8)
-        let result = find_source_line(&annotations, 0, 12, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Nearest(0));
-    }
+        // when
+        app.jump_to_deferred(true);
+        let wrapped = app.diff_state.cursor_line;
+        app.jump_to_deferred(false);
+        let back = app.diff_state.cursor_line;
 
-    #[test]
-    fn should_find_nearest_above_target() {
-        let annotations = vec![
-            make_diff_line(0, Some(10)),
-            make_diff_line(0, Some(15)),
-            make_diff_line(0, Some(20)),
-        ];
+        // then
+        let review = &app.session.files[&PathBuf::from("src/lib.rs")];
+        assert!(review.is_deferred(2, LineSide::Old));
+        assert!(review.is_deferred(2, LineSide::New));
+        assert_eq!(wrapped, deleted);
+        assert_eq!(back, added);
 
-        // Target 18 is closest to line 20 (dist=2) vs 15 (dist=3) vs 10 (dist=8)
-        let result = find_source_line(&annotations, 0, 18, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Nearest(2));
-    }
+        // when
+        app.toggle_defer_at_cursor();
 
-    #[test]
-    fn should_return_not_found_for_empty_annotations() {
-        let annotations: Vec<AnnotatedLine> = vec![];
-        let result = find_source_line(&annotations, 0, 42, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::NotFound);
+        // then
+        assert_eq!(app.session.deferred_count(), 1);
+        assert!(app.dirty);
     }
+}
+
+#[cfg(test)]
+mod resume_tests {
+    use super::review_test_app::{build_app, move_to_line};
+    use super::*;
 
     #[test]
-    fn should_return_not_found_when_no_lines_in_current_file() {
-        let annotations = vec![make_diff_line(1, Some(10)), make_diff_line(1, Some(20))];
+    fn should_offer_to_resume_saved_review_and_restore_its_cursor() {
+        // given
+        let mut app = build_app();
+        app.session
+            .get_file_mut(&PathBuf::from("src/lib.rs"))
+            .unwrap()
+            .add_line_comment(
+                2,
+                Comment::new("Why 1?".to_string(), CommentType::Note, None),
+            );
+        app.rebuild_annotations();
+        move_to_line(&mut app, "let x = 1;");
+        app.stamp_cursor();
+        let saved = app.session.clone();
 
-        // File 0 has no lines
-        let result = find_source_line(&annotations, 0, 10, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::NotFound);
-    }
+        // when
+        app.offer_resume();
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        app.exit_confirm_mode();
+        app.start_fresh_session();
 
-    #[test]
-    fn should_skip_lines_from_other_files() {
-        let annotations = vec![
-            make_diff_line(0, Some(100)), // file 0, line 100
-            make_diff_line(1, Some(42)),  // file 1, exact match but wrong file
-            make_diff_line(0, Some(50)),  // file 0, line 50
-        ];
+        // then
+        assert!(!app.session.has_comments());
+        assert!(app.session.cursor.is_none());
+        assert_ne!(app.session.id, saved.id);
 
-        // Searching file 0 for line 42 — should find nearest (50, dist=8) not file 1's exact match
-        let result = find_source_line(&annotations, 0, 42, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Nearest(2));
+        // when
+        app.session = saved;
+        app.rebuild_annotations();
+        app.diff_state.cursor_line = 0;
+        app.restore_session_cursor();
+
+        // then
+        assert_eq!(app.get_line_at_cursor(), Some((2, LineSide::New)));
     }
+}
+
+#[cfg(test)]
+mod reanchor_tests {
+    use super::review_test_app::{build_app, line};
+    use super::*;
+    use crate::model::LineOrigin;
 
     #[test]
-    fn should_skip_non_diff_line_annotations() {
-        let annotations = vec![
-            AnnotatedLine::FileHeader { file_idx: 0 },
-            AnnotatedLine::HunkHeader {
-                file_idx: 0,
-                hunk_idx: 0,
-            },
-            AnnotatedLine::Spacing,
-            make_diff_line(0, Some(42)),
+    fn should_move_comments_with_their_lines_when_the_diff_changes() {
+        // given
+        let mut app = build_app();
+        let lib = PathBuf::from("src/lib.rs");
+        let context = |line| anchor::capture(&app.diff_files[0], line, LineSide::New);
+        let mut header = Comment::new("typo".to_string(), CommentType::Note, None);
+        header.line_context = context(1);
+        let mut x = Comment::new("Why 1?".to_string(), CommentType::Note, None);
+        x.line_context = context(2);
+        let review = app.session.get_file_mut(&lib).unwrap();
+        review.add_line_comment(1, header);
+        review.add_line_comment(2, x);
+        let mut changed = app.diff_files[0].clone();
+        changed.hunks[0].lines = vec![
+            line(LineOrigin::Addition, "use std::io;", None, Some(1)),
+            line(LineOrigin::Addition, "", None, Some(2)),
+            line(LineOrigin::Addition, "let x = 1;", None, Some(3)),
         ];
 
-        let result = find_source_line(&annotations, 0, 42, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Exact(3));
-    }
+        // when
+        app.apply_reloaded_diff_files(vec![changed]);
 
-    #[test]
-    fn should_skip_diff_lines_with_no_new_lineno() {
-        // Deletion-only lines have new_lineno = None
-        let annotations = vec![make_diff_line(0, None), make_diff_line(0, Some(20))];
+        // then
+        let review = &app.session.files[&lib];
+        assert_eq!(review.line_comments[&3][0].content, "Why 1?");
+        assert_eq!(review.line_comments[&1][0].content, "typo");
+        assert_eq!(app.session.orphaned.len(), 1);
+        assert!(
+            app.message
+                .as_ref()
+                .unwrap()
+                .content
+                .contains("1 lost theirs")
+        );
 
-        let result = find_source_line(&annotations, 0, 5, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Nearest(1));
+        // when
+        app.verify_comments();
+
+        // then
+        let locations: Vec<String> = app
+            .orphaned_comments
+            .iter()
+            .map(OrphanedComment::location)
+            .collect();
+        assert_eq!(locations, vec!["src/lib.rs:1"]);
     }
+}
+
+#[cfg(test)]
+mod verify_tests {
+    use super::review_test_app::build_app;
+    use super::*;
+    use crate::model::FileStatus;
 
     #[test]
-    fn should_work_with_side_by_side_lines() {
-        let annotations = vec![
-            make_sbs_line(0, Some(10)),
-            make_sbs_line(0, Some(20)),
-            make_sbs_line(0, Some(30)),
-        ];
+    fn should_flag_drifted_comments_and_keep_their_context_until_confirmed() {
+        // given
+        let mut app = build_app();
+        let lib = PathBuf::from("src/lib.rs");
+        let mut x = Comment::new("Why 1?".to_string(), CommentType::Note, None);
+        x.line_context = anchor::capture(&app.diff_files[0], 2, LineSide::New);
+        let recorded = x.line_context.clone();
+        app.session
+            .get_file_mut(&lib)
+            .unwrap()
+            .add_line_comment(2, x);
+        app.dirty = false;
+        let mut changed = app.diff_files[0].clone();
+        changed.hunks[0].lines[0].content = "// the header".to_string();
 
-        let result = find_source_line(&annotations, 0, 20, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Exact(1));
-    }
+        // when
+        app.apply_reloaded_diff_files(vec![changed]);
 
-    #[test]
-    fn should_handle_mixed_diff_and_sbs_lines() {
-        let annotations = vec![
-            make_diff_line(0, Some(10)),
-            make_sbs_line(0, Some(20)),
-            make_diff_line(0, Some(30)),
-        ];
+        // then
+        let review = &app.session.files[&lib];
+        assert_eq!(review.line_comments[&2][0].line_context, recorded);
+        assert_eq!(app.session.drifted.len(), 1);
+        assert!(app.dirty);
 
-        let result = find_source_line(&annotations, 0, 25, LineSide::New);
-        // Nearest is line 20 (dist=5) or line 30 (dist=5), first match wins
-        assert_eq!(result, FindSourceLineResult::Nearest(1));
+        // when
+        app.verify_comments();
+        app.verify_reanchor();
+
+        // then
+        let review = &app.session.files[&lib];
+        assert_ne!(review.line_comments[&2][0].line_context, recorded);
+        assert!(app.session.drifted.is_empty());
+        assert!(app.orphaned_comments.is_empty());
     }
 
     #[test]
-    fn should_return_not_found_when_only_non_line_annotations() {
-        let annotations = vec![
-            AnnotatedLine::FileHeader { file_idx: 0 },
-            AnnotatedLine::Spacing,
-            AnnotatedLine::HunkHeader {
-                file_idx: 0,
-                hunk_idx: 0,
-            },
-        ];
+    fn should_verify_comments_and_fix_the_orphaned_ones() {
+        // given
+        let mut app = build_app();
+        let lib = PathBuf::from("src/lib.rs");
+        let gone = PathBuf::from("src/gone.rs");
+        let review = app.session.get_file_mut(&lib).unwrap();
+        review.add_line_comment(
+            2,
+            Comment::new("Why 1?".to_string(), CommentType::Note, None),
+        );
+        review.add_line_comment(
+            9,
+            Comment::new("Off by one".to_string(), CommentType::Issue, None),
+        );
+        app.session.add_file(gone.clone(), FileStatus::Modified, 0);
+        app.session
+            .get_file_mut(&gone)
+            .unwrap()
+            .add_file_comment(Comment::new(
+                "Split this".to_string(),
+                CommentType::Note,
+                None,
+            ));
 
-        let result = find_source_line(&annotations, 0, 42, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::NotFound);
+        // when
+        app.verify_comments();
+
+        // then
+        assert_eq!(app.input_mode, InputMode::VerifyPanel);
+        let locations: Vec<String> = app
+            .orphaned_comments
+            .iter()
+            .map(OrphanedComment::location)
+            .collect();
+        assert_eq!(locations, vec!["src/gone.rs", "src/lib.rs:9"]);
+
+        // when
+        app.verify_detach();
+        app.verify_reanchor();
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!(
+            app.session.review_comments[0].content,
+            "src/gone.rs: Split this"
+        );
+        let review = &app.session.files[&lib];
+        assert_eq!(review.line_comments[&2].len(), 2);
+        assert!(!review.line_comments.contains_key(&9));
+        assert!(app.dirty);
     }
+}
+
+#[cfg(test)]
+mod export_format_tests {
+    use super::review_test_app::build_app;
+    use super::*;
 
     #[test]
-    fn should_prefer_exact_match_over_earlier_nearest() {
-        let annotations = vec![
-            make_diff_line(0, Some(41)), // dist=1 from target 42
-            make_diff_line(0, Some(42)), // exact match
-            make_diff_line(0, Some(43)), // dist=1 from target 42
-        ];
+    fn should_export_in_the_template_format_when_the_path_has_no_extension() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app();
+        app.export_format = Some("json".to_string());
+        app.session.review_comments.push(Comment::new(
+            "Needs tests".to_string(),
+            CommentType::Issue,
+            None,
+        ));
 
-        let result = find_source_line(&annotations, 0, 42, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Exact(1));
+        // when
+        app.export_report(dir.path().join("review").to_str().unwrap());
+
+        // then
+        assert!(dir.path().join("review.json").exists());
+        assert!(!dir.path().join("review").exists());
     }
+}
+
+#[cfg(test)]
+mod checkpoint_tests {
+    use super::review_test_app::build_app;
 
     #[test]
-    fn should_find_nearest_for_target_zero() {
-        // target_lineno = 0 is out-of-range (lines are 1-indexed) but should
-        // still return the nearest line rather than panicking.
-        let annotations = vec![make_diff_line(0, Some(1)), make_diff_line(0, Some(5))];
+    fn should_not_take_a_checkpoint_named_after_a_bare_subcommand() {
+        // given
+        let mut app = build_app();
 
-        let result = find_source_line(&annotations, 0, 0, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Nearest(0));
+        for subcommand in ["diff", "restore"] {
+            // when
+            app.checkpoint_command(subcommand);
+
+            // then
+            assert!(app.session.checkpoints.is_empty());
+            assert_eq!(
+                app.message.as_ref().map(|m| m.content.as_str()),
+                Some("Usage: :checkpoint diff|restore <label>")
+            );
+        }
     }
+}
+
+#[cfg(test)]
+mod autosave_tests {
+    use super::review_test_app::build_app;
+    use super::*;
 
     #[test]
-    fn should_tie_break_nearest_by_iteration_order() {
-        // When two lines are equidistant, the first one encountered wins.
-        // Here lines are in descending order; line 30 (idx 0) and line 10 (idx 2)
-        // are both dist=10 from target 20, so idx 0 should win.
-        let annotations = vec![
-            make_diff_line(0, Some(30)),
-            make_diff_line(0, Some(50)),
-            make_diff_line(0, Some(10)),
-        ];
+    fn should_push_back_autosave_on_every_change() {
+        // given
+        let mut app = build_app();
+        app.autosave_delay = Some(Duration::from_secs(60));
 
-        let result = find_source_line(&annotations, 0, 20, LineSide::New);
-        assert_eq!(result, FindSourceLineResult::Nearest(0));
+        // when
+        app.toggle_reviewed();
+        let first_due = app.autosave_due;
+        app.toggle_reviewed();
+        app.poll_autosave();
+
+        // then
+        assert!(app.dirty);
+        assert!(first_due.is_some());
+        assert!(app.autosave_due >= first_due);
     }
+}
+
+#[cfg(test)]
+mod read_only_tests {
+    use super::review_test_app::build_app;
+    use super::*;
 
     #[test]
-    fn should_match_old_lineno_when_side_is_old() {
-        // Deletion-only lines carry old_lineno but no new_lineno. `:o<n>`
-        // must match those.
-        let annotations = vec![
-            make_diff_line_with_old(0, Some(5), None),
-            make_diff_line_with_old(0, Some(10), None),
-            make_diff_line(0, Some(50)), // new-side line — should be ignored when side=Old
-        ];
+    fn should_refuse_editing_and_saving_a_read_only_review() {
+        // given
+        let mut app = build_app();
+        app.toggle_reviewed();
+        app.read_only = true;
+        app.dirty = false;
+        app.command_buffer = "clear".to_string();
+        app.input_mode = InputMode::Command;
 
-        let exact = find_source_line(&annotations, 0, 10, LineSide::Old);
-        assert_eq!(exact, FindSourceLineResult::Exact(1));
+        // when
+        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
+        let saved = app.write_session();
 
-        let nearest = find_source_line(&annotations, 0, 7, LineSide::Old);
-        assert_eq!(nearest, FindSourceLineResult::Nearest(0));
+        // then
+        assert_eq!(app.session.reviewed_count(), 1);
+        assert!(saved.is_err());
+        assert!(!app.dirty);
+        let message = app.message.as_ref().expect("warning should be shown");
+        assert!(message.content.contains("Read-only"));
     }
 
     #[test]
-    fn should_not_match_new_lineno_when_side_is_old() {
-        // A pure-addition line has no old_lineno; searching old-side should
-        // not fall back to its new_lineno.
-        let annotations = vec![make_diff_line(0, Some(42))];
+    fn should_refuse_fixing_orphaned_comments_in_a_read_only_review() {
+        // given
+        let mut app = build_app();
+        let comment = Comment::new("Off by one".to_string(), CommentType::Issue, None);
+        app.session
+            .get_file_mut(&PathBuf::from("src/lib.rs"))
+            .unwrap()
+            .add_line_comment(9, comment);
+        app.verify_comments();
+        app.read_only = true;
+        app.dirty = false;
 
-        let result = find_source_line(&annotations, 0, 42, LineSide::Old);
-        assert_eq!(result, FindSourceLineResult::NotFound);
+        // when
+        app.verify_reanchor();
+        app.verify_detach();
+        app.confirm_delete_orphaned_comment();
+
+        // then
+        let review = &app.session.files[&PathBuf::from("src/lib.rs")];
+        assert_eq!(review.line_comments[&9].len(), 1);
+        assert_eq!(app.orphaned_comments.len(), 1);
+        assert!(app.pending_confirm.is_none());
+        assert!(!app.dirty);
+        let message = app.message.as_ref().expect("warning should be shown");
+        assert!(message.content.contains("Read-only"));
     }
 }
 
 #[cfg(test)]
-mod change_status_tests {
-    use std::fs;
-
-    use tempfile::tempdir;
-
+mod safe_mode_tests {
     use super::*;
+    use crate::model::FileStatus;
     use crate::vcs::traits::VcsType;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-    struct StatusProbeMock {
+    struct StagingVcs {
         info: VcsInfo,
-        status: VcsChangeStatus,
-        staged_files: Vec<DiffFile>,
-        unstaged_files: Vec<DiffFile>,
+        staged: Arc<AtomicUsize>,
     }
 
-    impl VcsBackend for StatusProbeMock {
+    impl VcsBackend for StagingVcs {
         fn info(&self) -> &VcsInfo {
             &self.info
         }
@@ -9877,26 +18576,6 @@ mod change_status_tests {
             Err(TuicrError::NoChanges)
         }
 
-        fn get_staged_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-            if self.staged_files.is_empty() {
-                Err(TuicrError::NoChanges)
-            } else {
-                Ok(self.staged_files.clone())
-            }
-        }
-
-        fn get_unstaged_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-            if self.unstaged_files.is_empty() {
-                Err(TuicrError::NoChanges)
-            } else {
-                Ok(self.unstaged_files.clone())
-            }
-        }
-
-        fn get_change_status(&self) -> Result<VcsChangeStatus> {
-            Ok(self.status)
-        }
-
         fn fetch_context_lines(
             &self,
             _file_path: &Path,
@@ -9906,176 +18585,253 @@ mod change_status_tests {
         ) -> Result<Vec<DiffLine>> {
             Ok(Vec::new())
         }
+
+        fn stage_file(&self, _path: &Path) -> Result<()> {
+            self.staged.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
     }
 
-    fn diff_file(path: &str) -> DiffFile {
-        DiffFile {
+    fn build_unstaged_app(staged: Arc<AtomicUsize>) -> App {
+        let file = DiffFile {
             old_path: None,
-            new_path: Some(PathBuf::from(path)),
+            new_path: Some(PathBuf::from("src/lib.rs")),
             status: FileStatus::Modified,
             hunks: Vec::new(),
             is_binary: false,
             is_too_large: false,
             is_commit_message: false,
             content_hash: 0,
-        }
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::Unstaged,
+        );
+        let mut app = App::build(
+            Box::new(StagingVcs {
+                info: vcs_info.clone(),
+                staged,
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![file],
+            session,
+            DiffSource::Unstaged,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        app.toggle_reviewed();
+        app
     }
 
-    fn mock_vcs(root_path: PathBuf) -> StatusProbeMock {
-        StatusProbeMock {
-            info: VcsInfo {
-                root_path,
-                head_commit: "HEAD".to_string(),
-                branch_name: Some("main".to_string()),
-                vcs_type: VcsType::Git,
-            },
-            status: VcsChangeStatus {
-                staged: true,
-                unstaged: true,
-            },
-            staged_files: Vec::new(),
-            unstaged_files: Vec::new(),
-        }
+    #[test]
+    fn should_refuse_staging_in_safe_mode() {
+        // given
+        let staged = Arc::new(AtomicUsize::new(0));
+        let mut app = build_unstaged_app(staged.clone());
+        app.safe_mode = true;
+        // when
+        app.stage_reviewed_files();
+        // then
+        assert_eq!(staged.load(Ordering::SeqCst), 0);
+        let message = app.message.as_ref().expect("warning should be shown");
+        assert!(message.content.contains("safe mode"));
     }
 
     #[test]
-    fn status_probe_rechecks_positive_rows_when_ignore_rules_exist() {
-        let dir = tempdir().expect("failed to create temp dir");
-        fs::write(dir.path().join(".tuicrignore"), "ignored/\n")
-            .expect("failed to write .tuicrignore");
-        let mut vcs = mock_vcs(dir.path().to_path_buf());
-        vcs.staged_files = vec![diff_file("ignored/generated.rs")];
-        vcs.unstaged_files = vec![diff_file("src/lib.rs")];
+    fn should_save_without_writing_git_notes_in_safe_mode() {
+        // given
+        let _reviews = crate::persistence::storage::tests::with_test_reviews_dir();
+        let mut app = build_unstaged_app(Arc::new(AtomicUsize::new(0)));
+        app.safe_mode = true;
+        app.notes_ref = Some("refs/notes/tuicr".to_string());
+        // when
+        let message = app.write_session().unwrap();
+        // then
+        assert!(message.ends_with("; refs/notes/tuicr not written in safe mode (--safe)"));
+        assert!(!app.dirty);
+    }
 
-        let (status, used_probe) = App::get_change_status_with_ignore(
-            &vcs,
-            dir.path(),
-            &SyntaxHighlighter::default(),
-            None,
-        )
-        .expect("failed to get change status");
+    #[test]
+    fn should_refuse_writing_todos_in_safe_mode() {
+        // given
+        let mut app = build_unstaged_app(Arc::new(AtomicUsize::new(0)));
+        app.safe_mode = true;
+        // when
+        app.todos_command("");
+        // then
+        let message = app.message.as_ref().expect("warning should be shown");
+        assert!(message.content.contains("safe mode"));
+    }
 
-        assert!(used_probe);
-        assert_eq!(
-            status,
-            VcsChangeStatus {
-                staged: false,
-                unstaged: true,
-            }
-        );
+    #[test]
+    fn should_stage_reviewed_files_outside_safe_mode() {
+        let staged = Arc::new(AtomicUsize::new(0));
+        let mut app = build_unstaged_app(staged.clone());
+
+        app.stage_reviewed_files();
+
+        assert_eq!(staged.load(Ordering::SeqCst), 1);
     }
+}
+
+#[cfg(test)]
+mod startup_progress_tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
 
     #[test]
-    fn status_probe_does_not_load_diffs_without_ignore_rules() {
-        let dir = tempdir().expect("failed to create temp dir");
-        let vcs = mock_vcs(dir.path().to_path_buf());
+    fn should_report_startup_stages_while_building_app() {
+        // given
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "hello\n").unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
 
-        let (status, used_probe) = App::get_change_status_with_ignore(
-            &vcs,
-            dir.path(),
-            &SyntaxHighlighter::default(),
+        // when
+        let app = App::new(
+            Theme::dark(),
             None,
-        )
-        .expect("failed to get change status");
-
-        assert!(used_probe);
-        assert_eq!(
-            status,
-            VcsChangeStatus {
-                staged: true,
-                unstaged: true,
-            }
+            false,
+            AppStartupOptions {
+                revisions: None,
+                revset: None,
+                interdiff: None,
+                working_tree: false,
+                path_filter: None,
+                file_path: file.to_str(),
+                patch_path: None,
+                git_backend_preference: GitBackendPreference::Libgit2,
+                rename_detection: RenameDetection::default(),
+                pr_target: None,
+                progress: Some(tx),
+            },
         );
+
+        // then
+        assert!(app.is_ok());
+        let stages: Vec<StartupStage> = rx.try_iter().collect();
+        assert_eq!(stages, vec![StartupStage::LoadingChanges]);
     }
 }
 
 #[cfg(test)]
-mod expand_gap_tests {
+mod diff_option_tests {
     use super::*;
-    use crate::model::{DiffHunk, DiffLine, FileStatus, LineOrigin};
-    use crate::vcs::traits::VcsType;
+    use crate::model::{DiffLine, FileStatus};
 
-    struct MockVcs {
+    /// Reports one renamed file while detection is on, and a delete plus an
+    /// add once it is switched off.
+    struct RenamingVcs {
         info: VcsInfo,
-        /// Total lines available in the "file" (1-indexed)
-        total_lines: u32,
+        renames: RenameDetection,
+        whitespace: IgnoreWhitespace,
     }
 
-    impl VcsBackend for MockVcs {
+    impl VcsBackend for RenamingVcs {
         fn info(&self) -> &VcsInfo {
             &self.info
         }
 
-        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-            Err(TuicrError::NoChanges)
+        fn set_rename_detection(&mut self, detection: RenameDetection) {
+            self.renames = detection;
         }
 
-        fn fetch_context_lines(
-            &self,
-            _file_path: &Path,
-            _file_status: FileStatus,
-            start_line: u32,
-            end_line: u32,
-        ) -> Result<Vec<DiffLine>> {
-            let mut result = Vec::new();
-            for line_num in start_line..=end_line.min(self.total_lines) {
-                result.push(DiffLine {
-                    origin: LineOrigin::Context,
-                    content: format!("line {line_num}"),
-                    old_lineno: Some(line_num),
-                    new_lineno: Some(line_num),
-                    highlighted_spans: None,
-                });
-            }
-            Ok(result)
+        fn rename_detection(&self) -> Option<RenameDetection> {
+            Some(self.renames)
         }
-    }
 
-    fn make_hunk(new_start: u32, new_count: u32) -> DiffHunk {
-        let mut lines = Vec::new();
-        for i in 0..new_count {
-            lines.push(DiffLine {
-                origin: LineOrigin::Context,
-                content: format!("hunk line {}", new_start + i),
-                old_lineno: Some(new_start + i),
-                new_lineno: Some(new_start + i),
-                highlighted_spans: None,
-            });
+        fn set_ignore_whitespace(&mut self, whitespace: IgnoreWhitespace) {
+            self.whitespace = whitespace;
         }
-        DiffHunk {
-            header: format!("@@ -{new_start},{new_count} +{new_start},{new_count} @@"),
-            lines,
-            old_start: new_start,
-            old_count: new_count,
-            new_start,
-            new_count,
+
+        fn ignore_whitespace(&self) -> Option<IgnoreWhitespace> {
+            Some(self.whitespace)
+        }
+
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            let file = |old: Option<&str>, new: Option<&str>, status| DiffFile {
+                old_path: old.map(PathBuf::from),
+                new_path: new.map(PathBuf::from),
+                status,
+                hunks: Vec::new(),
+                is_binary: false,
+                is_too_large: false,
+                is_commit_message: false,
+                content_hash: 0,
+                modes: FileModes::default(),
+                sizes: FileSizes::default(),
+            };
+            if self.renames.enabled {
+                Ok(vec![file(
+                    Some("old.rs"),
+                    Some("new.rs"),
+                    FileStatus::Renamed,
+                )])
+            } else {
+                Ok(vec![
+                    file(None, Some("new.rs"), FileStatus::Added),
+                    file(Some("old.rs"), None, FileStatus::Deleted),
+                ])
+            }
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
         }
     }
 
-    fn build_app_with_files(files: Vec<DiffFile>, total_lines: u32) -> App {
+    fn build_app() -> App {
         let vcs_info = VcsInfo {
             root_path: PathBuf::from("/tmp"),
             head_commit: "abc123".to_string(),
             branch_name: Some("main".to_string()),
             vcs_type: VcsType::Git,
         };
+        let vcs = RenamingVcs {
+            info: vcs_info.clone(),
+            renames: RenameDetection::default(),
+            whitespace: IgnoreWhitespace::default(),
+        };
+        let diff_files = vcs
+            .get_working_tree_diff(&SyntaxHighlighter::default())
+            .unwrap();
         let session = ReviewSession::new(
             vcs_info.root_path.clone(),
             vcs_info.head_commit.clone(),
             vcs_info.branch_name.clone(),
             SessionDiffSource::WorkingTree,
         );
-
         App::build(
-            Box::new(MockVcs {
-                info: vcs_info.clone(),
-                total_lines,
-            }),
+            Box::new(vcs),
             vcs_info,
             Theme::dark(),
             None,
             false,
-            files,
+            diff_files,
             session,
             DiffSource::WorkingTree,
             InputMode::Normal,
@@ -10085,973 +18841,1111 @@ mod expand_gap_tests {
         .expect("failed to build test app")
     }
 
-    fn make_file_with_hunks(path: &str, hunks: Vec<DiffHunk>) -> DiffFile {
-        let content_hash = DiffFile::compute_content_hash(&hunks);
-        DiffFile {
-            old_path: None,
-            new_path: Some(PathBuf::from(path)),
-            status: FileStatus::Modified,
-            hunks,
-            is_binary: false,
-            is_too_large: false,
-            is_commit_message: false,
-            content_hash,
-        }
-    }
-
     #[test]
-    fn should_expand_up_from_first_hunk() {
-        // given: file with 50-line gap before first hunk (hunk starts at line 51)
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
-
-        // when: expand Up with limit 20 (reveals lines closest to hunk)
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
-            .unwrap();
+    fn should_reload_diff_when_rename_detection_is_toggled() {
+        // given
+        let mut app = build_app();
+        assert_eq!(app.diff_files.len(), 1);
 
-        // then: 20 lines expanded from the bottom of the gap (lines 31-50)
-        let content = app.expanded_bottom.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 20);
-        assert_eq!(content[0].new_lineno, Some(31));
-        assert_eq!(content[19].new_lineno, Some(50));
-    }
+        // when
+        app.toggle_rename_detection();
 
-    #[test]
-    fn should_expand_all_lines_with_both_direction() {
-        // given: file with 50-line gap before first hunk
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
+        // then
+        assert_eq!(app.diff_files.len(), 2);
+        assert!(!app.vcs.rename_detection().unwrap().enabled);
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Rename detection: off")
+        );
 
-        // when: expand Both (all remaining)
-        app.expand_gap(gap_id.clone(), ExpandDirection::Both, None)
-            .unwrap();
+        // when
+        app.set_rename_detection(true);
 
-        // then: all 50 lines in expanded_top
-        let content = app.expanded_top.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 50);
-        assert_eq!(content[0].new_lineno, Some(1));
-        assert_eq!(content[49].new_lineno, Some(50));
+        // then
+        assert_eq!(app.diff_files.len(), 1);
+        assert_eq!(app.diff_files[0].status, FileStatus::Renamed);
     }
 
     #[test]
-    fn should_expand_down_from_upper_hunk() {
-        // given: file with two hunks, gap of 24 lines (6..29) between them
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(30, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
-        };
+    fn should_update_rename_settings_from_renames_command() {
+        // given
+        let mut app = build_app();
 
-        // when: expand Down with limit 10
-        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(10))
-            .unwrap();
+        // when
+        app.renames_command("copies break 70%");
 
-        // then: 10 lines from top of gap (lines 6-15)
-        let content = app.expanded_top.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 10);
-        assert_eq!(content[0].new_lineno, Some(6));
-        assert_eq!(content[9].new_lineno, Some(15));
+        // then
+        let detection = app.vcs.rename_detection().unwrap();
+        assert!(detection.copies);
+        assert!(detection.break_rewrites);
+        assert_eq!(detection.threshold, 70);
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Rename detection: on (70%, copies, break rewrites)")
+        );
     }
 
     #[test]
-    fn should_expand_up_from_lower_hunk() {
-        // given: file with two hunks, gap of 24 lines (6..29) between them
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(30, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
-        };
+    fn should_reject_invalid_renames_argument() {
+        // given
+        let mut app = build_app();
 
-        // when: expand Up with limit 10
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(10))
-            .unwrap();
+        // when
+        app.renames_command("copies 150");
 
-        // then: 10 lines from bottom of gap (lines 20-29)
-        let content = app.expanded_bottom.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 10);
-        assert_eq!(content[0].new_lineno, Some(20));
-        assert_eq!(content[9].new_lineno, Some(29));
+        // then
+        assert!(!app.vcs.rename_detection().unwrap().copies);
+        assert_eq!(
+            app.message.as_ref().map(|m| m.message_type.clone()),
+            Some(MessageType::Warning)
+        );
     }
 
     #[test]
-    fn should_append_on_subsequent_down_expand() {
-        // given: already expanded 20 lines down
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(50, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
-        };
-        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(20))
-            .unwrap();
-
-        // when: expand Down 20 more
-        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(20))
-            .unwrap();
+    fn should_toggle_whitespace_options_from_command() {
+        // given
+        let mut app = build_app();
 
-        // then: 40 lines total in top
-        let content = app.expanded_top.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 40);
-        assert_eq!(content[0].new_lineno, Some(6));
-        assert_eq!(content[39].new_lineno, Some(45));
-    }
+        // when
+        app.toggle_ignore_all_space();
+        app.whitespace_command("blank");
 
-    #[test]
-    fn should_prepend_on_subsequent_up_expand() {
-        // given: already expanded 10 lines up from bottom
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(50, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
-        };
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(10))
-            .unwrap();
+        // then
+        assert_eq!(
+            app.vcs.ignore_whitespace(),
+            Some(IgnoreWhitespace {
+                all_space: true,
+                space_change: false,
+                blank_lines: true,
+            })
+        );
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Whitespace: ignoring all space, blank lines")
+        );
 
-        // when: expand Up 10 more
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(10))
-            .unwrap();
+        // when
+        app.whitespace_command("off");
 
-        // then: 20 lines total in bottom, in ascending order
-        let content = app.expanded_bottom.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 20);
-        assert_eq!(content[0].new_lineno, Some(30));
-        assert_eq!(content[19].new_lineno, Some(49));
+        // then
+        assert_eq!(
+            app.vcs.ignore_whitespace(),
+            Some(IgnoreWhitespace::default())
+        );
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Whitespace: shown")
+        );
     }
 
     #[test]
-    fn should_cap_at_gap_boundaries() {
-        // given: file with 50-line gap, already expanded 40 up
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(40))
-            .unwrap();
+    fn should_refuse_to_ignore_blank_lines_on_jj() {
+        // given
+        let mut app = build_app();
+        app.vcs = Box::new(RenamingVcs {
+            info: VcsInfo {
+                vcs_type: VcsType::Jujutsu,
+                ..app.vcs_info.clone()
+            },
+            renames: RenameDetection::default(),
+            whitespace: IgnoreWhitespace::default(),
+        });
 
-        // when: expand Up 20 more (only 10 remain)
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
-            .unwrap();
+        // when
+        app.whitespace_command("all blank");
 
-        // then: all 50 lines in bottom
-        let content = app.expanded_bottom.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 50);
-        assert_eq!(content[0].new_lineno, Some(1));
+        // then
+        assert_eq!(
+            app.vcs.ignore_whitespace(),
+            Some(IgnoreWhitespace::default())
+        );
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Ignoring blank lines is not supported by jj")
+        );
     }
 
-    #[test]
-    fn should_show_up_expander_for_top_of_file_partial() {
-        // given: file with 50-line gap, expanded 20 lines up
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
-            .unwrap();
+    /// Answers revset diffs and commit range diffs with different files so
+    /// tests can tell which one a reload used.
+    struct RevsetVcs {
+        info: VcsInfo,
+    }
 
-        // then: should have ↑ expander + hidden lines annotation
-        let expander_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Up } if *g == gap_id))
-            .count();
-        assert_eq!(expander_count, 1);
+    fn diff_file(path: &str) -> DiffFile {
+        DiffFile {
+            old_path: Some(PathBuf::from(path)),
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Modified,
+            hunks: Vec::new(),
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
 
-        let hidden_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::HiddenLines { gap_id: g, .. } if *g == gap_id))
-            .count();
-        assert_eq!(hidden_count, 1, "should show hidden lines count");
+    impl VcsBackend for RevsetVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
 
-        let expanded_count = app
-            .line_annotations
-            .iter()
-            .filter(
-                |a| matches!(a, AnnotatedLine::ExpandedContext { gap_id: g, .. } if *g == gap_id),
-            )
-            .count();
-        assert_eq!(expanded_count, 20);
-    }
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn get_commit_range_diff(
+            &self,
+            _commit_ids: &[String],
+            _range_mode: RangeMode,
+            _highlighter: &SyntaxHighlighter,
+        ) -> Result<Vec<DiffFile>> {
+            Ok(vec![diff_file("range.rs")])
+        }
 
-    #[test]
-    fn should_not_show_expander_when_fully_expanded() {
-        // given: file with 50-line gap, fully expanded
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
-        app.expand_gap(gap_id.clone(), ExpandDirection::Both, None)
-            .unwrap();
+        fn resolve_revisions(&self, _revisions: &str) -> Result<Vec<String>> {
+            Ok(vec!["c1".to_string(), "c2".to_string()])
+        }
 
-        // then: no expander or hidden lines
-        let expander_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, .. } if *g == gap_id))
-            .count();
-        assert_eq!(expander_count, 0);
-    }
+        fn get_revset_diff(
+            &self,
+            _revset: &str,
+            _highlighter: &SyntaxHighlighter,
+        ) -> Result<Vec<DiffFile>> {
+            Ok(vec![diff_file("revset.rs")])
+        }
 
-    #[test]
-    fn should_show_merged_expander_for_small_between_hunk_gap() {
-        // given: file with two hunks and a 15-line gap between them
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(21, 5)]);
-        let app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
-        };
+        fn get_interdiff(
+            &self,
+            _old: &str,
+            _new: &str,
+            _highlighter: &SyntaxHighlighter,
+        ) -> Result<Vec<DiffFile>> {
+            Ok(vec![diff_file("interdiff.rs")])
+        }
 
-        // then: should show single ↕ expander (gap=15, < 20)
-        let both_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Both } if *g == gap_id))
-            .count();
-        assert_eq!(both_count, 1, "small gap should show merged ↕ expander");
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
     }
 
     #[test]
-    fn should_show_split_expanders_for_large_between_hunk_gap() {
-        // given: file with two hunks and a 30-line gap between them
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(36, 5)]);
-        let app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
-        };
+    fn should_warn_when_backend_cannot_diff_revsets() {
+        // given
+        let mut app = build_app();
 
-        // then: should show ↓ + hidden + ↑
-        let down_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Down } if *g == gap_id))
-            .count();
-        let up_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Up } if *g == gap_id))
-            .count();
-        let hidden_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::HiddenLines { gap_id: g, .. } if *g == gap_id))
-            .count();
-        assert_eq!(down_count, 1);
-        assert_eq!(up_count, 1);
-        assert_eq!(hidden_count, 1);
+        // when
+        app.review_revset("@--::@");
+
+        // then
+        assert_eq!(app.revset, None);
+        assert_eq!(app.diff_source, DiffSource::WorkingTree);
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Revsets are not supported by this backend")
+        );
     }
 
-    #[test]
-    fn should_expand_gap_in_correct_file_not_adjacent_file() {
-        // given: two files, each with a gap before the first hunk
-        let file0 = make_file_with_hunks("a.rs", vec![make_hunk(31, 5)]);
-        let file1 = make_file_with_hunks("b.rs", vec![make_hunk(21, 5)]);
-        let mut app = build_app_with_files(vec![file0, file1], 100);
+    /// Lists `untracked.rs` next to any tracked change while untracked files
+    /// are included.
+    struct UntrackedVcs {
+        info: VcsInfo,
+        include: bool,
+        tracked_change: bool,
+    }
 
-        let gap_id_file1 = GapId {
-            file_idx: 1,
-            hunk_idx: 0,
-        };
+    impl VcsBackend for UntrackedVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
 
-        // when: expand gap in file1
-        app.expand_gap(gap_id_file1.clone(), ExpandDirection::Up, Some(10))
-            .unwrap();
+        fn set_include_untracked(&mut self, include: bool) {
+            self.include = include;
+        }
 
-        // then: expanded content is for file1's gap (10 lines from bottom)
-        let content = app.expanded_bottom.get(&gap_id_file1).unwrap();
-        assert_eq!(content.len(), 10);
-        assert_eq!(content[9].new_lineno, Some(20));
+        fn include_untracked(&self) -> Option<bool> {
+            Some(self.include)
+        }
 
-        // and file0's gap should not be expanded
-        let gap_id_file0 = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            let mut files = Vec::new();
+            if self.tracked_change {
+                files.push(diff_file("tracked.rs"));
+            }
+            if self.include {
+                files.push(diff_file("untracked.rs"));
+            }
+            if files.is_empty() {
+                return Err(TuicrError::NoChanges);
+            }
+            Ok(files)
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn build_untracked_app(tracked_change: bool) -> App {
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
         };
-        assert!(
-            !app.expanded_top.contains_key(&gap_id_file0)
-                && !app.expanded_bottom.contains_key(&gap_id_file0)
+        let vcs = UntrackedVcs {
+            info: vcs_info.clone(),
+            include: true,
+            tracked_change,
+        };
+        let files = vcs
+            .get_working_tree_diff(&SyntaxHighlighter::default())
+            .unwrap();
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "abc123".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
         );
+        App::build(
+            Box::new(vcs),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            files,
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app")
     }
 
     #[test]
-    fn should_noop_when_already_fully_expanded() {
-        // given: file with 10-line gap, fully expanded
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(11, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
-        app.expand_gap(gap_id.clone(), ExpandDirection::Both, None)
-            .unwrap();
-        let len_before = app.expanded_top.get(&gap_id).unwrap().len();
+    fn should_hide_untracked_files_and_reload() {
+        // given
+        let mut app = build_untracked_app(true);
+        assert_eq!(app.diff_files.len(), 2);
 
-        // when: try to expand again
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
-            .unwrap();
+        // when
+        app.toggle_untracked();
 
-        // then: no change
-        let len_after = app.expanded_top.get(&gap_id).unwrap().len();
-        assert_eq!(len_before, len_after);
+        // then
+        assert_eq!(app.vcs.include_untracked(), Some(false));
+        assert_eq!(app.diff_files.len(), 1);
+        assert_eq!(
+            app.diff_files[0].display_path(),
+            &PathBuf::from("tracked.rs")
+        );
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Untracked files: hidden")
+        );
     }
 
     #[test]
-    fn should_expand_small_gap_fully_even_with_large_limit() {
-        // given: file with 5-line gap
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(6, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
-
-        // when: expand Up with limit 20 (gap is only 5 lines)
-        app.expand_gap(gap_id.clone(), ExpandDirection::Up, Some(20))
-            .unwrap();
+    fn should_keep_untracked_files_when_they_are_the_only_changes() {
+        // given
+        let mut app = build_untracked_app(false);
 
-        // then: all 5 lines expanded, no expander remaining
-        let content = app.expanded_bottom.get(&gap_id).unwrap();
-        assert_eq!(content.len(), 5);
+        // when
+        app.set_include_untracked(false);
 
-        let expander_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, .. } if *g == gap_id))
-            .count();
-        assert_eq!(expander_count, 0);
+        // then
+        assert_eq!(app.vcs.include_untracked(), Some(true));
+        assert_eq!(app.diff_files.len(), 1);
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Only untracked files changed; keeping them shown")
+        );
     }
 
     #[test]
-    fn should_merge_to_both_when_remaining_drops_below_batch() {
-        // given: 30-line between-hunk gap, expand 20 down => 10 remaining
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(36, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
-        };
-        app.expand_gap(gap_id.clone(), ExpandDirection::Down, Some(20))
-            .unwrap();
+    fn should_warn_when_backend_has_no_untracked_files() {
+        // given
+        let mut app = build_app();
 
-        // then: remaining=10, should show ↕ merged expander
-        let both_count = app
-            .line_annotations
-            .iter()
-            .filter(|a| matches!(a, AnnotatedLine::Expander { gap_id: g, direction: ExpandDirection::Both } if *g == gap_id))
-            .count();
-        assert_eq!(both_count, 1, "should merge to ↕ when <20 remaining");
+        // when
+        app.toggle_untracked();
+
+        // then
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Untracked files are not supported by this backend")
+        );
     }
 
-    fn cursor_new_lineno(app: &App) -> Option<u32> {
-        match &app.line_annotations[app.diff_state.cursor_line] {
-            AnnotatedLine::DiffLine { new_lineno, .. }
-            | AnnotatedLine::SideBySideLine { new_lineno, .. } => *new_lineno,
-            AnnotatedLine::ExpandedContext { gap_id, line_idx } => app
-                .get_expanded_line(gap_id, *line_idx)
-                .and_then(|l| l.new_lineno),
-            _ => None,
-        }
+    #[test]
+    fn should_warn_when_backend_cannot_diff_interdiffs() {
+        // given
+        let mut app = build_app();
+
+        // when
+        app.review_interdiff("v1 v2");
+
+        // then
+        assert_eq!(app.interdiff, None);
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Interdiffs are not supported by this backend")
+        );
     }
 
-    fn cursor_old_lineno(app: &App) -> Option<u32> {
-        match &app.line_annotations[app.diff_state.cursor_line] {
-            AnnotatedLine::DiffLine { old_lineno, .. }
-            | AnnotatedLine::SideBySideLine { old_lineno, .. } => *old_lineno,
-            AnnotatedLine::ExpandedContext { gap_id, line_idx } => app
-                .get_expanded_line(gap_id, *line_idx)
-                .and_then(|l| l.old_lineno),
-            _ => None,
-        }
+    #[test]
+    fn should_show_interdiff_usage_without_revisions() {
+        // given
+        let mut app = build_app();
+
+        // when
+        app.review_interdiff("");
+
+        // then
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Usage: :interdiff <old> [new]")
+        );
     }
 
     #[test]
-    fn should_expand_collapsed_gap_when_jumping_into_it() {
-        // given: file with a 50-line gap before the first hunk (hunk @ line 51)
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
-        };
-        assert!(!app.expanded_top.contains_key(&gap_id));
-        assert!(!app.expanded_bottom.contains_key(&gap_id));
+    fn should_prompt_for_revision_on_bare_rev_command() {
+        // given
+        let mut app = build_app();
+        app.enter_command_mode();
+        app.command_buffer = "rev".to_string();
 
-        // when: jump to line 30, which lives inside the collapsed gap
-        app.go_to_source_line(30, LineSide::New);
+        // when
+        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
 
-        // then: the gap was expanded and the cursor sits on the line whose
-        // new_lineno is exactly 30
-        assert_eq!(cursor_new_lineno(&app), Some(30));
+        // then
+        assert_eq!(app.input_mode, InputMode::Command);
+        assert_eq!(app.command_buffer, "rev ");
     }
 
     #[test]
-    fn should_not_expand_when_line_is_already_in_a_hunk() {
-        // Line 52 lives inside the hunk's own range, not in a gap — no
-        // expansion should occur.
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
+    fn should_reload_revset_review_with_revset_diff() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Jujutsu,
         };
+        let commit_ids = vec!["c1".to_string(), "c2".to_string()];
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
+        );
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("range.rs")],
+            session,
+            DiffSource::CommitRange(commit_ids),
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        app.revset = Some("@--::@".to_string());
 
-        app.go_to_source_line(52, LineSide::New);
+        // when
+        app.reload_diff_files().unwrap();
 
-        assert!(!app.expanded_top.contains_key(&gap_id));
-        assert!(!app.expanded_bottom.contains_key(&gap_id));
+        // then
+        assert_eq!(
+            app.diff_files[0].display_path(),
+            &PathBuf::from("revset.rs")
+        );
     }
 
     #[test]
-    fn should_expand_old_side_gap_when_jumping_with_o_prefix() {
-        // Symmetric gap (offset = 0): `make_hunk` keeps old_start == new_start
-        // and the mock VCS returns context lines with old_lineno == new_lineno.
-        // We verify the side=Old path of go_to_source_line works end-to-end:
-        // the gap auto-expands and the cursor lands on old line 30.
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
+    fn should_not_save_a_read_only_review_when_switching_to_a_revset() {
+        // given
+        let reviews = crate::persistence::storage::tests::with_test_reviews_dir();
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Jujutsu,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "abc123".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        let session_id = session.id.clone();
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("working.rs")],
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        app.read_only = true;
+        app.enter_command_mode();
+        app.command_buffer = "rev @--::@".to_string();
 
-        app.go_to_source_line(30, LineSide::Old);
+        // when
+        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
 
-        assert_eq!(cursor_old_lineno(&app), Some(30));
+        // then
+        assert_eq!(app.revset.as_deref(), Some("@--::@"));
+        let id_fragment = session_id.split('-').next().unwrap();
+        let written: Vec<String> = std::fs::read_dir(&reviews.path)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.contains(id_fragment))
+            .collect();
+        assert!(written.is_empty(), "wrote {written:?}");
     }
 
     #[test]
-    fn should_expand_up_when_cursor_is_below_the_gap() {
-        // Two hunks: hunk0 at lines 1-5, hunk1 at lines 50-54. Gap between
-        // them spans new lines 6..=49. Move the cursor onto hunk1, then
-        // jump to line 30 — expansion should come from the bottom of the
-        // gap (Up) since the cursor sits below it.
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(1, 5), make_hunk(50, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 1,
+    fn should_offer_unpushed_commits_over_the_commit_selector() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "c2".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
         };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::Unstaged,
+        );
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            Vec::new(),
+            session,
+            DiffSource::Unstaged,
+            InputMode::CommitSelect,
+            vec![App::unstaged_commit_entry()],
+            None,
+        )
+        .expect("failed to build test app");
 
-        let hunk1_header_idx = app
-            .line_annotations
-            .iter()
-            .enumerate()
-            .find_map(|(i, a)| match a {
-                AnnotatedLine::HunkHeader { hunk_idx: 1, .. } => Some(i),
-                _ => None,
-            })
-            .expect("hunk 1 header should exist");
-        app.diff_state.cursor_line = hunk1_header_idx + 1;
+        // when
+        app.offer_review_chooser();
 
-        app.go_to_source_line(30, LineSide::New);
+        // then
+        assert_eq!(app.input_mode, InputMode::ReviewChooser);
+        assert_eq!(app.base_mode(), InputMode::CommitSelect);
+        assert_eq!(app.unpushed_commits, vec!["c1", "c2"]);
 
-        assert_eq!(cursor_new_lineno(&app), Some(30));
-        assert!(
-            !app.expanded_top.contains_key(&gap_id),
-            "no top expansion when cursor is below the gap"
+        // when
+        app.review_chooser_cursor_down();
+        app.review_chooser_confirm();
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.overlays().is_empty());
+        assert_eq!(
+            app.diff_source,
+            DiffSource::CommitRange(vec!["c1".to_string(), "c2".to_string()])
         );
-        // Gap covers new lines 6..=49 (44 lines). Up expansion to reach
-        // line 30 reveals lines 30..=49 = 20 lines.
-        let bot_len = app.expanded_bottom.get(&gap_id).map_or(0, |v| v.len());
-        assert_eq!(bot_len, 20);
-        let has_down_expander = app.line_annotations.iter().any(|a| {
-            matches!(
-                a,
-                AnnotatedLine::Expander {
-                    gap_id: g,
-                    direction: ExpandDirection::Down,
-                } if *g == gap_id
-            )
-        });
-        assert!(
-            has_down_expander,
-            "remaining hidden lines need a `↓` expander above the cursor"
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Reviewing unpushed commits (2 commits)")
         );
     }
 
     #[test]
-    fn should_expand_only_up_to_target_line_not_full_gap() {
-        // Gap before hunk spans new lines 1..=50. Jumping to line 20 should
-        // reveal lines 1..=20 and leave 21..=50 collapsed behind an
-        // `↑` expander.
-        let file = make_file_with_hunks("test.rs", vec![make_hunk(51, 5)]);
-        let mut app = build_app_with_files(vec![file], 100);
-        let gap_id = GapId {
-            file_idx: 0,
-            hunk_idx: 0,
+    fn should_offer_and_record_last_used_source() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("last_sources.json");
+        let vcs_info = VcsInfo {
+            root_path: dir.path().to_path_buf(),
+            head_commit: "c2".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
         };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::Unstaged,
+        );
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            Vec::new(),
+            session,
+            DiffSource::Unstaged,
+            InputMode::CommitSelect,
+            vec![App::unstaged_commit_entry()],
+            None,
+        )
+        .expect("failed to build test app");
+        app.last_source_path = Some(path.clone());
 
-        app.go_to_source_line(20, LineSide::New);
+        // when
+        app.offer_last_source(ReviewSource::Staged);
 
-        assert_eq!(cursor_new_lineno(&app), Some(20));
-        let top_len = app.expanded_top.get(&gap_id).map_or(0, |v| v.len());
-        assert_eq!(top_len, 20, "only the lines up to the target should expand");
-        assert!(
-            !app.expanded_bottom.contains_key(&gap_id),
-            "no bottom expansion should happen for a downward jump"
+        // then
+        assert_eq!(app.input_mode, InputMode::CommitSelect);
+
+        // when
+        app.offer_last_source(ReviewSource::Base {
+            base: "main".to_string(),
+        });
+        app.review_chooser_confirm();
+
+        // then
+        assert_eq!(
+            app.diff_source,
+            DiffSource::CommitRange(vec!["c1".to_string(), "c2".to_string()])
         );
-        // The unexpanded remainder (30 lines) should still be reachable through
-        // an `↑` expander between the cursor and the next hunk.
-        let has_up_expander = app.line_annotations.iter().any(|a| {
-            matches!(
-                a,
-                AnnotatedLine::Expander {
-                    gap_id: g,
-                    direction: ExpandDirection::Up,
-                } if *g == gap_id
-            )
+        assert_eq!(
+            app.message.as_ref().map(|m| m.content.as_str()),
+            Some("Reviewing commits since main (2 commits)")
+        );
+        assert_eq!(
+            crate::persistence::last_source::load_last_source(&path, dir.path()).unwrap(),
+            Some(ReviewSource::Base {
+                base: "main".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn should_reload_interdiff_review_with_interdiff() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
+        );
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("range.rs")],
+            session,
+            DiffSource::CommitRange(vec!["c1".to_string(), "c2".to_string()]),
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        app.interdiff = Some(Interdiff {
+            old: "v1".to_string(),
+            new: "c1^..c2".to_string(),
         });
-        assert!(
-            has_up_expander,
-            "remaining hidden lines need an `↑` expander"
+
+        // when
+        app.reload_diff_files().unwrap();
+
+        // then
+        assert_eq!(
+            app.diff_files[0].display_path(),
+            &PathBuf::from("interdiff.rs")
         );
     }
-}
 
-#[cfg(test)]
-mod visual_selection_tests {
-    use super::*;
+    #[test]
+    fn should_reload_on_worker_and_hand_back_the_backend() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Jujutsu,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
+        );
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("range.rs")],
+            session,
+            DiffSource::CommitRange(vec!["c1".to_string(), "c2".to_string()]),
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        app.revset = Some("@--::@".to_string());
 
-    fn p(idx: usize, off: usize) -> SelPoint {
-        SelPoint {
-            annotation_idx: idx,
-            char_offset: off,
-            side: LineSide::New,
+        // when
+        app.spawn_vcs_reload().unwrap();
+        let started = Instant::now();
+        while app.vcs_reload_state.is_some() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+            app.poll_vcs_reload_events();
         }
-    }
 
-    #[test]
-    fn collapsed_starts_at_point() {
-        let sel = VisualSelection::collapsed(p(5, 3));
-        assert_eq!(sel.anchor, p(5, 3));
-        assert_eq!(sel.head, p(5, 3));
+        // then
+        assert!(app.vcs_reload_state.is_none());
+        assert_eq!(
+            app.diff_files[0].display_path(),
+            &PathBuf::from("revset.rs")
+        );
+        assert!(app.reload_diff_files().is_ok(), "backend was handed back");
+        assert_eq!(app.message.as_ref().unwrap().content, "Reloaded 1 files");
     }
 
     #[test]
-    fn ordered_returns_anchor_head_when_already_in_order() {
-        let sel = VisualSelection {
-            anchor: p(1, 0),
-            head: p(4, 8),
+    fn should_hold_vcs_actions_until_a_reload_hands_back_the_backend() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
         };
-        let (start, end) = sel.ordered();
-        assert_eq!(start, p(1, 0));
-        assert_eq!(end, p(4, 8));
-    }
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
+        );
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("range.rs")],
+            session,
+            DiffSource::CommitRange(vec!["c1".to_string(), "c2".to_string()]),
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
 
-    #[test]
-    fn ordered_swaps_when_head_before_anchor_by_idx() {
-        let sel = VisualSelection {
-            anchor: p(4, 0),
-            head: p(1, 0),
-        };
-        let (start, end) = sel.ordered();
-        assert_eq!(start, p(1, 0));
-        assert_eq!(end, p(4, 0));
-    }
+        // when
+        app.spawn_vcs_reload().unwrap();
+        app.review_base("main");
 
-    #[test]
-    fn ordered_breaks_ties_on_idx_by_char_offset() {
-        let sel = VisualSelection {
-            anchor: p(7, 20),
-            head: p(7, 5),
-        };
-        let (start, end) = sel.ordered();
-        assert_eq!(start, p(7, 5));
-        assert_eq!(end, p(7, 20));
+        // then
+        assert_eq!(
+            app.message.as_ref().unwrap().content,
+            "Reload in progress: wait for it to finish or press Esc to cancel"
+        );
+        assert!(app.reload_diff_files().is_err());
+        let started = Instant::now();
+        while app.vcs_reload_state.is_some() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+            app.poll_vcs_reload_events();
+        }
+        app.review_base("main");
+        assert_eq!(
+            app.message.as_ref().unwrap().content,
+            "Reviewing commits since main (2 commits)"
+        );
     }
-}
-
-#[cfg(test)]
-mod submit_flow_tests {
-    //! Tests for the `:submit*` preflight / resolver / confirmation
-    //! orchestration. Driven through the App methods rather than the key
-    //! handlers so we exercise the state machine directly.
-    use super::*;
-    use crate::forge::submit::{ResolverAction, SubmitEvent, UnmappableReason};
-    use crate::forge::traits::{ForgeRepository, PrSessionKey};
-    use crate::model::comment::{Comment, CommentLifecycleState, CommentType, LineContext};
-    use crate::model::diff_types::{DiffHunk, DiffLine, FileStatus, LineOrigin};
-    use crate::vcs::traits::{VcsChangeStatus, VcsType};
 
-    struct DummyVcs {
+    struct PanickingVcs {
         info: VcsInfo,
     }
 
-    impl VcsBackend for DummyVcs {
+    impl VcsBackend for PanickingVcs {
         fn info(&self) -> &VcsInfo {
             &self.info
         }
-        fn get_working_tree_diff(&self, _h: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-            Err(TuicrError::NoChanges)
+
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            panic!("diff exploded");
         }
+
         fn fetch_context_lines(
             &self,
-            _p: &Path,
-            _s: FileStatus,
-            _start: u32,
-            _end: u32,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
         ) -> Result<Vec<DiffLine>> {
             Ok(Vec::new())
         }
-        fn get_change_status(&self) -> Result<VcsChangeStatus> {
-            Ok(VcsChangeStatus {
-                staged: false,
-                unstaged: false,
-            })
+
+        fn resolve_revisions(&self, _revisions: &str) -> Result<Vec<String>> {
+            Ok(vec!["c1".to_string()])
         }
     }
 
-    fn make_pr_app_with_single_modified_file(file_path: &str) -> App {
+    #[test]
+    fn should_hand_back_the_backend_when_a_reload_panics() {
+        // given
         let vcs_info = VcsInfo {
-            root_path: PathBuf::from("/tmp/repo"),
-            head_commit: "abcdef0123".to_string(),
-            branch_name: Some("feat".to_string()),
-            vcs_type: VcsType::File,
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
         };
         let session = ReviewSession::new(
             vcs_info.root_path.clone(),
-            vcs_info.head_commit.clone(),
-            vcs_info.branch_name.clone(),
-            SessionDiffSource::PullRequest,
+            "abc123".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
         );
-        let diff_file = DiffFile {
-            old_path: Some(PathBuf::from(file_path)),
-            new_path: Some(PathBuf::from(file_path)),
-            status: FileStatus::Modified,
-            hunks: vec![DiffHunk {
-                header: "@@".to_string(),
-                old_start: 1,
-                old_count: 0,
-                new_start: 1,
-                new_count: 0,
-                lines: vec![
-                    DiffLine {
-                        origin: LineOrigin::Context,
-                        content: "a".to_string(),
-                        old_lineno: Some(10),
-                        new_lineno: Some(10),
-                        highlighted_spans: None,
-                    },
-                    DiffLine {
-                        origin: LineOrigin::Addition,
-                        content: "b".to_string(),
-                        old_lineno: None,
-                        new_lineno: Some(11),
-                        highlighted_spans: None,
-                    },
-                ],
-            }],
-            is_binary: false,
-            is_too_large: false,
-            is_commit_message: false,
-            content_hash: 0,
-        };
-        let pr_source = PullRequestDiffSource {
-            key: PrSessionKey::new(
-                ForgeRepository::github("github.com", "agavra", "tuicr"),
-                125,
-                "abcdef0123".to_string(),
-            ),
-            base_sha: "0000".to_string(),
-            title: "test pr".to_string(),
-            url: "https://github.com/agavra/tuicr/pull/125".to_string(),
-            head_ref_name: "feat".to_string(),
-            base_ref_name: "main".to_string(),
-            state: "OPEN".to_string(),
-            closed: false,
-            merged: false,
+        let mut app = App::build(
+            Box::new(PanickingVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("lib.rs")],
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+
+        // when
+        app.spawn_vcs_reload().unwrap();
+        let started = Instant::now();
+        while app.vcs_reload_state.is_some() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+            app.poll_vcs_reload_events();
+        }
+
+        // then
+        assert_eq!(
+            app.message.as_ref().unwrap().content,
+            "Reload failed: VCS command failed: the reload worker panicked"
+        );
+        assert!(
+            app.vcs.resolve_revisions("HEAD").is_ok(),
+            "backend was handed back"
+        );
+    }
+
+    #[test]
+    fn should_leave_passthrough_commands_to_the_terminal_and_reload_after() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
         };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "abc123".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
         let mut app = App::build(
-            Box::new(DummyVcs {
+            Box::new(RevsetVcs {
                 info: vcs_info.clone(),
             }),
             vcs_info,
             Theme::dark(),
             None,
             false,
-            vec![diff_file],
+            vec![diff_file("lib.rs")],
             session,
-            DiffSource::PullRequest(Box::new(pr_source)),
+            DiffSource::WorkingTree,
             InputMode::Normal,
             Vec::new(),
             None,
         )
-        .expect("build app");
-        app.current_pr_head = Some("abcdef0123".to_string());
-        app
-    }
-
-    fn line_comment(side: LineSide, new: Option<u32>, old: Option<u32>) -> Comment {
-        let mut c = Comment::new("body".to_string(), CommentType::Issue, Some(side));
-        c.line_context = Some(LineContext {
-            new_line: new,
-            old_line: old,
-            content: String::new(),
-        });
-        c
-    }
-
-    fn add_line_comment(app: &mut App, path: &str, line: u32, comment: Comment) {
-        let pb = PathBuf::from(path);
-        let review = app.session.get_file_mut(&pb).expect("file in session");
-        review.line_comments.entry(line).or_default().push(comment);
-    }
+        .expect("failed to build test app");
 
-    #[test]
-    fn should_use_subset_head_sha_as_commit_id_when_inline_selector_is_strict_subset() {
-        // Regression for HTTP 422 when reviewing a subset of commits: the
-        // payload's `commit_id` must match the SHA the displayed diff was
-        // computed against — using the cumulative PR head causes GitHub to
-        // reject inline comments whose lines aren't in that diff.
-        use crate::forge::traits::PullRequestCommit;
+        // when
+        app.enter_command_mode();
+        app.command_buffer = "git add -p".to_string();
+        crate::handler::handle_command_action(&mut app, crate::input::Action::SubmitInput);
 
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        // Newest-first: [C3, C2, C1]. PR head SHA is C3 ("abcdef0123").
-        app.pr_commits = vec![
-            PullRequestCommit {
-                oid: "abcdef0123".to_string(),
-                short_oid: "abcdef0".to_string(),
-                summary: "C3".to_string(),
-                author: "me".to_string(),
-                timestamp: None,
-            },
-            PullRequestCommit {
-                oid: "deadbeef02".to_string(),
-                short_oid: "deadbee".to_string(),
-                summary: "C2".to_string(),
-                author: "me".to_string(),
-                timestamp: None,
-            },
-            PullRequestCommit {
-                oid: "facecafe01".to_string(),
-                short_oid: "facecaf".to_string(),
-                summary: "C1".to_string(),
-                author: "me".to_string(),
-                timestamp: None,
-            },
-        ];
-        // Strict subset: only middle commit C2 selected (start=1, end=1).
-        app.commit_selection_range = Some((1, 1));
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            Comment::new(
-                "comment on C2".to_string(),
-                CommentType::Issue,
-                Some(LineSide::New),
-            ),
-        );
+        // then
+        let passthrough = app.pending_passthrough.take().unwrap();
+        assert_eq!(passthrough.command(), "git add -p");
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.vcs_reload_state.is_none());
 
-        app.start_submit(SubmitEvent::Comment);
+        // when
+        app.finish_vcs_passthrough(&passthrough, Ok(Some(1)));
 
-        let state = app.submit_state.as_ref().expect("submit state");
+        // then
         assert_eq!(
-            state.commit_id, "deadbeef02",
-            "subset → commit_id should be the newest selected commit (start_idx), not the PR head",
+            app.message.as_ref().unwrap().content,
+            "git add -p: exited with 1"
         );
+        assert!(app.vcs_reload_state.is_some());
+        let started = Instant::now();
+        while app.vcs_reload_state.is_some() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+            app.poll_vcs_reload_events();
+        }
     }
 
     #[test]
-    fn should_use_pr_head_sha_as_commit_id_when_full_commit_range_selected() {
-        // Counterpart to the subset regression: full-range selection should
-        // continue to use the cumulative PR head SHA.
-        use crate::forge::traits::PullRequestCommit;
-
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        app.pr_commits = vec![
-            PullRequestCommit {
-                oid: "abcdef0123".to_string(),
-                short_oid: "abcdef0".to_string(),
-                summary: "C2".to_string(),
-                author: "me".to_string(),
-                timestamp: None,
-            },
-            PullRequestCommit {
-                oid: "facecafe01".to_string(),
-                short_oid: "facecaf".to_string(),
-                summary: "C1".to_string(),
-                author: "me".to_string(),
-                timestamp: None,
-            },
-        ];
-        app.commit_selection_range = Some((0, 1));
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            Comment::new(
-                "comment".to_string(),
-                CommentType::Issue,
-                Some(LineSide::New),
-            ),
+    fn should_list_only_files_changed_by_author() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
         );
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("a.rs"), diff_file("b.rs")],
+            session,
+            DiffSource::CommitRange(vec!["c1".to_string(), "c2".to_string()]),
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        let commit = |id: &str, author: &str| CommitInfo {
+            id: id.to_string(),
+            short_id: id.to_string(),
+            branch_name: None,
+            summary: String::new(),
+            body: None,
+            author: author.to_string(),
+            time: Utc::now(),
+        };
+        app.review_commits = vec![commit("c2", "Bob"), commit("c1", "Alice Smith")];
+        app.commit_diff_cache
+            .insert((0, 0), vec![diff_file("a.rs")]);
+        app.commit_diff_cache
+            .insert((1, 1), vec![diff_file("b.rs")]);
 
-        app.start_submit(SubmitEvent::Comment);
-
-        let state = app.submit_state.as_ref().expect("submit state");
-        assert_eq!(state.commit_id, "abcdef0123");
-    }
-
-    #[test]
-    fn should_anchor_line_comments_via_hashmap_key_when_line_context_missing() {
-        // Regression: in production, comments are created via Comment::new
-        // which does NOT populate line_context — the line lives only in the
-        // line_comments HashMap key. Before the CommentAnchor refactor, the
-        // mapper treated these as file-level and posted everything with
-        // position 1 plus the "File-level:" body prefix.
-        use crate::forge::submit::GhSide;
-
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let bare = Comment::new(
-            "real line comment".to_string(),
-            CommentType::Issue,
-            Some(LineSide::New),
-        );
-        assert!(bare.line_context.is_none(), "fixture contract");
-        add_line_comment(&mut app, "src/lib.rs", 11, bare);
+        // when
+        app.author_command("alice");
 
-        app.start_submit(SubmitEvent::Comment);
+        // then
+        let files: Vec<usize> = app
+            .build_visible_items()
+            .iter()
+            .filter_map(|item| match item {
+                FileTreeItem::File { file_idx, .. } => Some(*file_idx),
+                FileTreeItem::Directory { .. } => None,
+            })
+            .collect();
+        assert_eq!(files, vec![1]);
+        assert_eq!(app.diff_state.current_file_idx, 1);
 
-        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
-        let state = app.submit_state.as_ref().expect("submit state");
-        assert_eq!(state.mappable.len(), 1);
-        let inline = &state.mappable[0];
-        assert_eq!(inline.line, 11);
-        assert_eq!(inline.side, GhSide::Right);
-        assert!(
-            !inline.body.contains("File-level:"),
-            "regression: body should not be prefixed File-level (got: {})",
-            inline.body
-        );
+        app.author_command("off");
+        assert_eq!(app.build_visible_items().len(), 2);
     }
 
     #[test]
-    fn should_open_confirm_directly_when_all_comments_map() {
-        // given a PR session with one mappable line comment
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            line_comment(LineSide::New, Some(11), None),
+    fn should_keep_reviewed_marks_per_commit_apart_from_the_squashed_view() {
+        // given
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc123".to_string(),
+            branch_name: None,
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            "c2".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
         );
-        // when
-        app.start_submit(SubmitEvent::Comment);
-        // then — went straight to confirmation, no resolver
-        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
-        let state = app.submit_state.as_ref().expect("submit state");
-        assert_eq!(state.mappable.len(), 1);
-        assert!(state.unmappable.is_empty());
-        assert_eq!(state.commit_id, "abcdef0123");
-        assert_eq!(state.event, SubmitEvent::Comment);
-    }
-
-    #[test]
-    fn should_open_resolver_when_any_comment_is_unmappable() {
-        // given a PR session with one mappable + one file-level on a
-        // binary file (unmappable).
-        let mut app = make_pr_app_with_single_modified_file("img.png");
-        // mark file binary in diff_files
-        app.diff_files[0].is_binary = true;
-        // file-level comment in session
-        let pb = PathBuf::from("img.png");
-        let review = app.session.get_file_mut(&pb).expect("file in session");
-        review
-            .file_comments
-            .push(Comment::new("oof".to_string(), CommentType::Note, None));
-        // when
-        app.start_submit(SubmitEvent::Comment);
-        // then — resolver entered with one unmappable
-        assert_eq!(app.input_mode, InputMode::SubmitResolver);
-        let state = app.submit_state.as_ref().expect("submit state");
-        assert_eq!(state.unmappable.len(), 1);
-        assert_eq!(state.unmappable[0].reason, UnmappableReason::BinaryFile);
-        assert_eq!(state.resolver_choices.len(), 1);
-        // Default action is MoveToSummary per spec
-        assert_eq!(state.resolver_choices[0], ResolverAction::MoveToSummary);
-    }
+        let mut app = App::build(
+            Box::new(RevsetVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            vec![diff_file("a.rs"), diff_file("b.rs")],
+            session,
+            DiffSource::CommitRange(vec!["c1".to_string(), "c2".to_string()]),
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app");
+        let commit = |id: &str| CommitInfo {
+            id: id.to_string(),
+            short_id: id.to_string(),
+            branch_name: None,
+            summary: String::new(),
+            body: None,
+            author: "Alice".to_string(),
+            time: Utc::now(),
+        };
+        app.review_commits = vec![commit("c2"), commit("c1")];
+        app.range_diff_files = Some(app.diff_files.clone());
+        app.commit_diff_cache
+            .insert((1, 1), vec![diff_file("b.rs")]);
+        let path = PathBuf::from("b.rs");
+        let select = |app: &mut App, range| {
+            app.commit_selection_range = Some(range);
+            app.reload_inline_selection().unwrap();
+        };
+        let file_idx = |app: &App| {
+            app.diff_files
+                .iter()
+                .position(|f| *f.display_path() == path)
+                .unwrap()
+        };
 
-    #[test]
-    fn should_skip_locked_comments_during_preflight() {
-        // given a single locked comment
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let mut c = line_comment(LineSide::New, Some(11), None);
-        c.lifecycle_state = CommentLifecycleState::Submitted;
-        add_line_comment(&mut app, "src/lib.rs", 11, c);
         // when
-        app.start_submit(SubmitEvent::Comment);
-        // then — preflight aborted with the "nothing to submit" warning
-        assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
-    }
+        select(&mut app, (1, 1));
+        app.toggle_reviewed_for_file_idx(file_idx(&app), false);
 
-    #[test]
-    fn should_warn_when_no_local_drafts_exist() {
-        // given a PR session with zero comments
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        // when
-        app.start_submit(SubmitEvent::Comment);
         // then
-        assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
+        assert_eq!(app.viewed_commit(), Some("c1"));
+        assert!(app.is_file_reviewed(&path));
+        assert!(!app.session.is_file_reviewed(&path));
+        select(&mut app, (0, 1));
+        assert_eq!(app.viewed_commit(), None);
+        assert!(!app.is_file_reviewed(&path));
+        select(&mut app, (1, 1));
+        assert!(app.is_file_reviewed(&path));
+        assert!(app.commit_review_badge("c1").is_some());
+        assert_eq!(app.commit_review_badge("c2"), None);
     }
+}
 
-    #[test]
-    fn should_allow_bare_approve_through_action_picker_with_no_comments() {
-        // Regression: bare `:submit` → picker → cursor on Approve → Enter
-        // should NOT warn "Nothing to submit". Approve is the one event
-        // meaningful with no comments. Picker uses skip_confirm = true, so
-        // the flow goes straight to network dispatch.
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        app.start_submit_action_picker();
-        // Walk cursor to the Approve row (index 1).
-        app.submit_picker_cursor_down();
-        assert_eq!(app.submit_picker_cursor, 1);
-        app.submit_picker_confirm();
-        // No warning was emitted; the network call was dispatched.
-        assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
-        assert!(
-            app.pr_submit_state.is_some(),
-            "picker-confirm should dispatch when Approve is bare-allowed"
-        );
+#[cfg(test)]
+mod view_tests {
+    use super::*;
+    use crate::model::{FileStatus, LineOrigin};
+    use crate::vcs::traits::VcsType;
+
+    struct DummyVcs {
+        info: VcsInfo,
     }
 
-    #[test]
-    fn should_allow_bare_approve_without_any_comments() {
-        // Approve is the one event meaningful with no comments (a plain
-        // LGTM). Preflight should proceed; the user lands in the confirm
-        // modal with zero mappable + zero unmappable.
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        app.start_submit(SubmitEvent::Approve);
-        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
-        let state = app.submit_state.as_ref().expect("submit state");
-        assert!(state.mappable.is_empty());
-        assert!(state.unmappable.is_empty());
-        assert_eq!(state.event, SubmitEvent::Approve);
+    impl VcsBackend for DummyVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
+
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
     }
 
-    #[test]
-    fn should_warn_when_submitting_without_pr_mode() {
-        // given an app NOT in PR mode
+    fn file(path: &str, n: u32) -> DiffFile {
+        let lines = (1..=n)
+            .map(|i| DiffLine {
+                origin: LineOrigin::Addition,
+                content: format!("line {i}"),
+                old_lineno: None,
+                new_lineno: Some(i),
+                highlighted_spans: None,
+            })
+            .collect();
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Added,
+            hunks: vec![DiffHunk {
+                header: format!("@@ -0,0 +1,{n} @@"),
+                lines,
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: n,
+            }],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    fn build_app() -> App {
         let vcs_info = VcsInfo {
             root_path: PathBuf::from("/tmp"),
-            head_commit: "head".to_string(),
+            head_commit: "abc".to_string(),
             branch_name: Some("main".to_string()),
             vcs_type: VcsType::Git,
         };
@@ -11069,529 +19963,564 @@ mod submit_flow_tests {
             Theme::dark(),
             None,
             false,
-            Vec::new(),
+            vec![file("a.rs", 40), file("b.rs", 40)],
             session,
             DiffSource::WorkingTree,
             InputMode::Normal,
             Vec::new(),
             None,
         )
-        .expect("build app");
+        .expect("failed to build test app");
+        app.diff_state.viewport_height = 10;
+        app.diff_state.visible_line_count = 10;
+        app
+    }
+
+    fn add_comments(app: &mut App) {
+        let comment = |text: &str| Comment::new(text.to_string(), CommentType::Note, None);
+        app.session.review_comments.push(comment("overall"));
+        let b = app.session.get_file_mut(&PathBuf::from("b.rs")).unwrap();
+        b.add_line_comment(30, comment("late line"));
+        b.add_line_comment(3, comment("early line"));
+        b.add_file_comment(comment("file note"));
+        app.rebuild_annotations();
+    }
+
+    #[test]
+    fn should_collapse_and_wrap_files_per_filetype_rule() {
+        use crate::config::FiletypeConfig;
+        // given
+        let mut app = build_app();
+        app.diff_state.wrap_lines = false;
+        app.filetypes = FiletypeRules::new(std::collections::BTreeMap::from([
+            (
+                "a.rs".to_string(),
+                FiletypeConfig {
+                    wrap: Some(true),
+                    ..Default::default()
+                },
+            ),
+            (
+                "b.rs".to_string(),
+                FiletypeConfig {
+                    collapse: Some(true),
+                    ..Default::default()
+                },
+            ),
+        ]));
+        app.rebuild_annotations();
+        let b_lines = |app: &App| {
+            app.line_annotations
+                .iter()
+                .filter(|a| matches!(a, AnnotatedLine::DiffLine { file_idx: 1, .. }))
+                .count()
+        };
+        let b_header = app
+            .line_annotations
+            .iter()
+            .position(|a| matches!(a, AnnotatedLine::FileHeader { file_idx: 1 }))
+            .unwrap();
+
+        // when / then: only b.rs's header shows until Enter opens it
+        assert!(app.wrap_lines());
+        assert_eq!(b_lines(&app), 0);
+        app.diff_state.cursor_line = b_header;
+        app.update_current_file_from_cursor();
+        assert!(!app.wrap_lines());
+        crate::handler::handle_diff_action(&mut app, crate::input::Action::SelectFile);
+        assert_eq!(b_lines(&app), 40);
+        crate::handler::handle_diff_action(&mut app, crate::input::Action::SelectFile);
+        assert_eq!(b_lines(&app), 0);
+    }
+
+    #[test]
+    fn should_cycle_views_in_both_directions() {
+        // given
+        let mut app = build_app();
+
+        // when / then
+        app.next_view();
+        assert_eq!(app.view, View::Comments);
+        app.next_view();
+        assert_eq!(app.view, View::Overview);
+        app.prev_view();
+        app.prev_view();
+        assert_eq!(app.view, View::Diff);
+    }
+
+    #[test]
+    fn should_list_comments_in_review_order() {
+        // given
+        let mut app = build_app();
+        add_comments(&mut app);
+
         // when
-        app.start_submit(SubmitEvent::Comment);
+        app.set_view(View::Comments);
+
+        // then: review comments first, then per file with lines in order
+        assert_eq!(
+            app.comments_view.entries,
+            vec![
+                CommentEntry::Review { comment_idx: 0 },
+                CommentEntry::File {
+                    file_idx: 1,
+                    comment_idx: 0
+                },
+                CommentEntry::Line {
+                    file_idx: 1,
+                    line: 3,
+                    comment_idx: 0
+                },
+                CommentEntry::Line {
+                    file_idx: 1,
+                    line: 30,
+                    comment_idx: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_jump_to_selected_comment_in_diff() {
+        // given
+        let mut app = build_app();
+        add_comments(&mut app);
+        app.set_view(View::Comments);
+
+        // when
+        crate::handler::handle_view_action(&mut app, crate::input::Action::GoToBottom);
+        crate::handler::handle_view_action(&mut app, crate::input::Action::SelectFile);
+
         // then
-        assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
+        assert_eq!(app.view, View::Diff);
+        assert_eq!(app.diff_state.current_file_idx, 1);
+        assert!(matches!(
+            app.line_annotations[app.diff_state.cursor_line],
+            AnnotatedLine::LineComment { line: 30, .. }
+        ));
     }
 
     #[test]
-    fn should_warn_when_pr_is_closed_or_merged() {
-        // given a closed PR
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        if let DiffSource::PullRequest(pr) = &mut app.diff_source {
-            pr.closed = true;
-        }
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            line_comment(LineSide::New, Some(11), None),
-        );
+    fn should_keep_diff_cursor_while_browsing_other_views() {
+        // given
+        let mut app = build_app();
+        add_comments(&mut app);
+        app.cursor_down(7);
+        let cursor = app.diff_state.cursor_line;
+
         // when
-        app.start_submit(SubmitEvent::Comment);
+        app.set_view(View::Comments);
+        crate::handler::handle_view_action(&mut app, crate::input::Action::CursorDown(2));
+        crate::handler::handle_view_action(&mut app, crate::input::Action::ExitMode);
+
         // then
-        assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
+        assert_eq!(app.view, View::Diff);
+        assert_eq!(app.diff_state.cursor_line, cursor);
+        assert_eq!(app.comments_view.list.cursor, 2);
     }
 
     #[test]
-    fn should_cancel_submit_clears_state_and_returns_to_normal() {
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            line_comment(LineSide::New, Some(11), None),
+    fn should_delete_comment_from_comments_view_after_confirming() {
+        // given
+        let mut app = build_app();
+        add_comments(&mut app);
+        app.set_view(View::Comments);
+        app.view_cursor_down(2);
+
+        // when
+        app.confirm_delete_view_comment();
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        assert_eq!(
+            app.pending_confirm.as_ref().map(|d| d.message.as_str()),
+            Some("Delete comment on b.rs:3?")
         );
-        app.start_submit(SubmitEvent::Comment);
+
         // when
-        app.cancel_submit();
+        crate::handler::handle_confirm_action(&mut app, crate::input::Action::ConfirmYes);
+
         // then
         assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
+        let b = &app.session.files[&PathBuf::from("b.rs")];
+        assert!(!b.line_comments.contains_key(&3));
+        assert_eq!(app.comments_view.entries.len(), 3);
+        assert!(app.dirty);
     }
 
     #[test]
-    fn should_toggle_resolver_action_between_move_and_omit() {
-        let mut app = make_pr_app_with_single_modified_file("img.png");
-        app.diff_files[0].is_binary = true;
-        let pb = PathBuf::from("img.png");
-        let review = app.session.get_file_mut(&pb).expect("file in session");
-        review
-            .file_comments
-            .push(Comment::new("a".to_string(), CommentType::Note, None));
-        review
-            .file_comments
-            .push(Comment::new("b".to_string(), CommentType::Note, None));
-        app.start_submit(SubmitEvent::Comment);
-        // when — toggle row 0
-        app.submit_resolver_toggle();
+    fn should_restore_deleted_comment_from_trash_view() {
+        // given
+        let mut app = build_app();
+        add_comments(&mut app);
+        app.set_view(View::Comments);
+        app.view_cursor_down(2);
+        app.confirm_delete_view_comment();
+        crate::handler::handle_confirm_action(&mut app, crate::input::Action::ConfirmYes);
+        app.set_view(View::Trash);
+
+        // when
+        app.open_view_selection();
+
         // then
-        let state = app.submit_state.as_ref().unwrap();
-        assert_eq!(state.resolver_choices[0], ResolverAction::Omit);
-        assert_eq!(state.resolver_choices[1], ResolverAction::MoveToSummary);
-        // when toggle again
-        app.submit_resolver_toggle();
-        let state = app.submit_state.as_ref().unwrap();
-        assert_eq!(state.resolver_choices[0], ResolverAction::MoveToSummary);
+        let b = &app.session.files[&PathBuf::from("b.rs")];
+        assert!(b.line_comments.contains_key(&3));
+        assert!(app.session.trash.is_empty());
+        assert!(matches!(
+            app.session.events.last().map(|e| &e.kind),
+            Some(ReviewEventKind::CommentAdded { line: Some(3), .. })
+        ));
     }
 
     #[test]
-    fn should_advance_from_resolver_to_confirm() {
-        let mut app = make_pr_app_with_single_modified_file("img.png");
-        app.diff_files[0].is_binary = true;
-        let pb = PathBuf::from("img.png");
-        let review = app.session.get_file_mut(&pb).expect("file in session");
-        review
-            .file_comments
-            .push(Comment::new("a".to_string(), CommentType::Note, None));
-        app.start_submit(SubmitEvent::Comment);
-        assert_eq!(app.input_mode, InputMode::SubmitResolver);
+    fn should_rename_and_delete_saved_sessions_from_sessions_view() {
+        // given
+        let mut app = build_app();
+        let dir = tempfile::tempdir().unwrap();
+        let other_path = dir.path().join("other.json");
+        let other = ReviewSession::new(
+            PathBuf::from("/tmp"),
+            "abc1234".to_string(),
+            Some("feature".to_string()),
+            SessionDiffSource::Staged,
+        );
+        crate::persistence::storage::write_session_file(&other_path, &other).unwrap();
+        app.view = View::Sessions;
+        app.sessions_view.sessions = vec![
+            (other_path.clone(), other),
+            (dir.path().join("open.json"), app.session.clone()),
+        ];
+
         // when
-        app.submit_resolver_advance();
+        app.begin_session_rename();
+        app.rename_session("release prep");
+
         // then
-        assert_eq!(app.input_mode, InputMode::SubmitConfirm);
+        assert_eq!(app.input_mode, InputMode::Command);
+        let saved = crate::persistence::storage::load_session(&other_path).unwrap();
+        assert_eq!(saved.name.as_deref(), Some("release prep"));
+        assert_eq!(app.session.name, None);
+
+        // when
+        app.input_mode = InputMode::Normal;
+        app.view_cursor_down(1);
+        app.confirm_delete_selected_session();
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!(app.sessions_view.sessions.len(), 2);
+
+        // when
+        app.view_cursor_up(1);
+        app.confirm_delete_selected_session();
+        crate::handler::handle_confirm_action(&mut app, crate::input::Action::ConfirmYes);
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(!other_path.exists());
+        assert_eq!(app.sessions_view.sessions.len(), 1);
     }
 
     #[test]
-    fn should_skip_confirm_modal_when_action_picker_dispatches_with_no_unmappable() {
-        // Bare `:submit` → action picker → pick Comment → directly dispatch
-        // the network call without SubmitConfirm. submit_state is cleared
-        // and pr_submit_state populated, same end state as the explicit
-        // `:submit comment` + [y] flow.
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            line_comment(LineSide::New, Some(11), None),
+    fn should_log_review_events_and_jump_to_their_file_from_history() {
+        // given
+        let mut app = build_app();
+        add_comments(&mut app);
+        app.toggle_reviewed_for_file_idx(0, false);
+        app.set_view(View::Comments);
+        app.view_cursor_down(2);
+        app.confirm_delete_view_comment();
+        crate::handler::handle_confirm_action(&mut app, crate::input::Action::ConfirmYes);
+
+        // when
+        app.set_view(View::History);
+
+        // then
+        let kinds: Vec<_> = app.session.events.iter().map(|e| &e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &ReviewEventKind::FileReviewed {
+                    path: PathBuf::from("a.rs")
+                },
+                &ReviewEventKind::CommentDeleted {
+                    path: Some(PathBuf::from("b.rs")),
+                    line: Some(3),
+                    comment_type: CommentType::Note,
+                },
+            ]
         );
 
-        app.start_submit_action_picker();
-        assert_eq!(app.input_mode, InputMode::SubmitActionPicker);
-        app.submit_picker_confirm();
+        // when: the newest event is listed first
+        app.open_view_selection();
 
-        assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
-        assert!(app.pr_submit_state.is_some());
+        // then
+        assert_eq!(app.view, View::Diff);
+        assert_eq!(app.diff_state.current_file_idx, 1);
     }
 
     #[test]
-    fn should_route_picker_through_resolver_then_skip_confirm() {
-        // Bare `:submit` picker with one unmappable comment → resolver
-        // appears → `s` advances and dispatches the network call directly,
-        // bypassing SubmitConfirm. skip_confirm is the flag that makes
-        // submit_resolver_advance bypass the confirm modal.
-        let mut app = make_pr_app_with_single_modified_file("img.png");
-        app.diff_files[0].is_binary = true;
-        let pb = PathBuf::from("img.png");
-        let review = app.session.get_file_mut(&pb).expect("file in session");
-        review.file_comments.push(Comment::new(
-            "binary art".to_string(),
-            CommentType::Note,
-            None,
-        ));
-
-        app.start_submit_action_picker();
-        app.submit_picker_cursor = 0; // Comment
-        app.submit_picker_confirm();
+    fn should_open_the_completion_dialog_when_the_last_file_is_reviewed() {
+        // given
+        let mut app = build_app();
 
-        // Picker dispatched → resolver visible because the comment is
-        // unmappable, but the underlying skip_confirm flag is set.
-        assert_eq!(app.input_mode, InputMode::SubmitResolver);
-        let state = app.submit_state.as_ref().expect("submit state");
-        assert!(state.skip_confirm);
+        // when
+        app.toggle_reviewed_for_file_idx(0, false);
 
-        // Advance from resolver → goes straight to network call, no
-        // SubmitConfirm.
-        app.submit_resolver_advance();
+        // then
         assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
-        assert!(app.pr_submit_state.is_some());
-    }
 
-    #[test]
-    fn should_dispatch_async_submit_on_confirm_and_clear_modal_state() {
-        // given a PR session with one mappable line comment
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            line_comment(LineSide::New, Some(11), None),
-        );
-        app.start_submit(SubmitEvent::Comment);
         // when
-        app.confirm_submit();
-        // then — PR 6: confirmation modal disappears immediately; the
-        // background thread is running with state captured on
-        // pr_submit_state so the spinner has something to render.
+        app.set_file_confidence(1, ReviewConfidence::Thorough);
+
+        // then
+        assert_eq!(app.input_mode, InputMode::CompletionDialog);
+        let actions: Vec<_> = app.completion_rows().into_iter().map(|(_, a)| a).collect();
+        assert!(actions.contains(&CompletionAction::Commit));
+        assert!(!actions.contains(&CompletionAction::PublishToGitHub));
+
+        // when
+        app.completion_cursor_up();
+        let action = app.take_completion_action();
+
+        // then
+        assert_eq!(action, Some(CompletionAction::SaveAndQuit));
         assert_eq!(app.input_mode, InputMode::Normal);
-        assert!(app.submit_state.is_none());
-        assert!(
-            app.pr_submit_state.is_some(),
-            "spawn_pr_submit should populate pr_submit_state"
-        );
-        assert!(app.pr_submit_rx.is_some(), "rx must be present in-flight");
-    }
 
-    fn make_in_flight(
-        event: SubmitEvent,
-        comment_ids: &[&str],
-        head_sha: &str,
-        moved_to_summary_count: usize,
-    ) -> SubmitInFlightState {
-        let mappable = comment_ids
-            .iter()
-            .enumerate()
-            .map(|(i, id)| crate::forge::submit::InlineComment {
-                path: PathBuf::from("src/lib.rs"),
-                line: 11 + i as u32,
-                side: crate::forge::submit::GhSide::Right,
-                start_line: None,
-                start_side: None,
-                body: "x".to_string(),
-                comment_id: (*id).to_string(),
-            })
-            .collect();
-        SubmitInFlightState {
-            event,
-            mappable,
-            moved_to_summary_count,
-            head_sha_snapshot: head_sha.to_string(),
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 125,
-            started_at: Instant::now(),
-        }
-    }
+        // when: turned off in the config
+        app.completion_dialog = false;
+        app.toggle_reviewed_for_file_idx(1, false);
+        app.toggle_reviewed_for_file_idx(1, false);
 
-    fn make_response(
-        id: u64,
-        html_url: &str,
-        state: &str,
-    ) -> crate::forge::traits::GhCreateReviewResponse {
-        crate::forge::traits::GhCreateReviewResponse {
-            id,
-            html_url: html_url.to_string(),
-            state: state.to_string(),
-        }
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
     }
 
     #[test]
-    fn should_flip_comments_to_submitted_and_stamp_review_id_on_success() {
-        // given an app with one line comment that we'll claim got submitted
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let mut comment = line_comment(LineSide::New, Some(11), None);
-        let comment_id = comment.id.clone();
-        comment.lifecycle_state = CommentLifecycleState::LocalDraft;
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        let in_flight = make_in_flight(
-            SubmitEvent::Comment,
-            &[comment_id.as_str()],
-            "abcdef0123",
-            0,
-        );
-        let response = make_response(987654, "https://example.com/r", "COMMENTED");
+    fn should_credit_active_time_to_the_file_on_screen_and_skip_idle_gaps() {
+        // given
+        let mut app = build_app();
+        let start = Instant::now();
+        let idle = Duration::from_secs(IDLE_GAP_SECS as u64);
+
         // when
-        app.apply_submit_success(&in_flight, &response);
-        // then — the comment lifecycle moved to Submitted and remote_review_id is set.
-        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
-        let saved = &review.line_comments.get(&11).unwrap()[0];
-        assert_eq!(saved.lifecycle_state, CommentLifecycleState::Submitted);
-        assert_eq!(saved.remote_review_id.as_deref(), Some("987654"));
+        app.track_activity(start);
+        app.track_activity(start + Duration::from_secs(30));
+        app.diff_state.current_file_idx = 1;
+        app.track_activity(start + Duration::from_secs(40));
+        app.track_activity(start + Duration::from_secs(40) + idle);
+
+        // then
+        let file_ms = |path: &str| app.session.files[&PathBuf::from(path)].active_ms;
+        assert_eq!(app.session.active_ms, 40_000);
+        assert_eq!(file_ms("a.rs"), 30_000);
+        assert_eq!(file_ms("b.rs"), 10_000);
+        assert!(!app.dirty);
     }
 
     #[test]
-    fn should_flip_comments_to_pushed_draft_for_draft_submission() {
+    fn should_switch_views_by_name() {
         // given
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = line_comment(LineSide::New, Some(11), None);
-        let comment_id = comment.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        let in_flight = make_in_flight(SubmitEvent::Draft, &[comment_id.as_str()], "abcdef0123", 0);
-        let response = make_response(42, "https://example.com/r", "PENDING");
+        let mut app = build_app();
+
         // when
-        app.apply_submit_success(&in_flight, &response);
+        app.view_command("over");
+
         // then
-        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
-        let saved = &review.line_comments.get(&11).unwrap()[0];
-        assert_eq!(saved.lifecycle_state, CommentLifecycleState::PushedDraft);
-        assert_eq!(saved.remote_review_id.as_deref(), Some("42"));
-    }
+        assert_eq!(app.view, View::Overview);
 
-    #[test]
-    fn should_only_flip_comments_whose_ids_were_submitted() {
-        // given — one matching id and one stray local-draft that wasn't in the submit
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = line_comment(LineSide::New, Some(11), None);
-        let target_id = comment.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        let untouched = line_comment(LineSide::New, Some(11), None);
-        let untouched_id = untouched.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, untouched);
-        let in_flight =
-            make_in_flight(SubmitEvent::Comment, &[target_id.as_str()], "abcdef0123", 0);
-        let response = make_response(1, "u", "COMMENTED");
         // when
-        app.apply_submit_success(&in_flight, &response);
-        // then — only the target id moved
-        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
-        let comments = review.line_comments.get(&11).unwrap();
-        let target = comments.iter().find(|c| c.id == target_id).unwrap();
-        let other = comments.iter().find(|c| c.id == untouched_id).unwrap();
-        assert_eq!(target.lifecycle_state, CommentLifecycleState::Submitted);
-        assert_eq!(other.lifecycle_state, CommentLifecycleState::LocalDraft);
-        assert!(other.remote_review_id.is_none());
+        app.view_command("nope");
+
+        // then
+        assert_eq!(app.view, View::Overview);
+        let message = app.message.as_ref().expect("warning");
+        assert_eq!(message.message_type, MessageType::Warning);
+        assert!(message.content.contains("Unknown view"));
+    }
+}
+
+#[cfg(test)]
+mod overlay_tests {
+    use super::*;
+    use crate::input::Action;
+    use crate::model::FileStatus;
+    use crate::vcs::traits::VcsType;
+
+    struct DummyVcs {
+        info: VcsInfo,
+    }
+
+    impl VcsBackend for DummyVcs {
+        fn info(&self) -> &VcsInfo {
+            &self.info
+        }
+
+        fn get_working_tree_diff(&self, _highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+            Err(TuicrError::NoChanges)
+        }
+
+        fn fetch_context_lines(
+            &self,
+            _file_path: &Path,
+            _file_status: FileStatus,
+            _start_line: u32,
+            _end_line: u32,
+        ) -> Result<Vec<DiffLine>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn build_app() -> App {
+        let vcs_info = VcsInfo {
+            root_path: PathBuf::from("/tmp"),
+            head_commit: "abc".to_string(),
+            branch_name: Some("main".to_string()),
+            vcs_type: VcsType::Git,
+        };
+        let session = ReviewSession::new(
+            vcs_info.root_path.clone(),
+            vcs_info.head_commit.clone(),
+            vcs_info.branch_name.clone(),
+            SessionDiffSource::WorkingTree,
+        );
+        App::build(
+            Box::new(DummyVcs {
+                info: vcs_info.clone(),
+            }),
+            vcs_info,
+            Theme::dark(),
+            None,
+            false,
+            Vec::new(),
+            session,
+            DiffSource::WorkingTree,
+            InputMode::Normal,
+            Vec::new(),
+            None,
+        )
+        .expect("failed to build test app")
     }
 
     #[test]
-    fn should_emit_success_message_with_review_id_and_counts_for_published_submit() {
+    fn should_open_help_for_focused_panel_and_toggle_full_reference() {
         // given
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = line_comment(LineSide::New, Some(11), None);
-        let comment_id = comment.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        let in_flight = make_in_flight(
-            SubmitEvent::Comment,
-            &[comment_id.as_str()],
-            "abcdef0123",
-            2,
-        );
-        let response = make_response(123456, "https://example.com/r", "COMMENTED");
+        let mut app = build_app();
+        app.focused_panel = FocusedPanel::FileList;
+
         // when
-        app.finish_pr_submit(in_flight, Ok(response));
+        app.toggle_help();
+
         // then
-        let msg = app.message.as_ref().expect("info message");
-        assert_eq!(msg.message_type, MessageType::Info);
-        assert!(msg.content.contains("Submitted GitHub review #123456"));
-        assert!(msg.content.contains("1 inline"));
-        assert!(msg.content.contains("2 moved to summary"));
+        assert_eq!(app.help_state.context, Some(HelpContext::FileList));
+        assert!(!app.help_state.show_all);
+
+        // when
+        app.toggle_help_scope();
+
+        // then
+        assert!(app.help_state.show_all);
     }
 
     #[test]
-    fn should_emit_draft_message_with_pr_url_for_draft_submit() {
+    fn should_return_to_covered_mode_when_overlay_closes() {
         // given
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = line_comment(LineSide::New, Some(11), None);
-        let comment_id = comment.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        let in_flight = make_in_flight(SubmitEvent::Draft, &[comment_id.as_str()], "abcdef0123", 0);
-        let response = make_response(
-            999,
-            "https://github.com/agavra/tuicr/pull/125#pullrequestreview-999",
-            "PENDING",
-        );
+        let mut app = build_app();
+        app.input_mode = InputMode::CommitSelect;
+
         // when
-        app.finish_pr_submit(in_flight, Ok(response));
+        app.toggle_help();
+
         // then
-        let msg = app.message.as_ref().expect("info message");
-        assert!(msg.content.contains("Pushed pending GitHub review #999"));
-        assert!(
-            msg.content
-                .contains("https://github.com/agavra/tuicr/pull/125"),
-            "draft message should include the PR URL — got: {}",
-            msg.content
-        );
-    }
+        assert_eq!(app.input_mode, InputMode::Help);
+        assert_eq!(app.base_mode(), InputMode::CommitSelect);
+        assert_eq!(app.overlays(), vec![InputMode::Help]);
 
-    #[test]
-    fn should_keep_comments_as_local_draft_on_submit_failure() {
-        // given a local-draft comment in the session
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = line_comment(LineSide::New, Some(11), None);
-        let comment_id = comment.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        let in_flight = make_in_flight(
-            SubmitEvent::Comment,
-            &[comment_id.as_str()],
-            "abcdef0123",
-            0,
-        );
-        // when — network failure
-        app.finish_pr_submit(
-            in_flight,
-            Err(
-                "Cannot submit review: GitHub token lacks pull request write permission."
-                    .to_string(),
-            ),
-        );
-        // then — the comment is still LocalDraft
-        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
-        let saved = &review.line_comments.get(&11).unwrap()[0];
-        assert_eq!(saved.lifecycle_state, CommentLifecycleState::LocalDraft);
-        assert!(saved.remote_review_id.is_none());
-        // and — a sticky error message is set
-        let msg = app.message.as_ref().expect("error message");
-        assert_eq!(msg.message_type, MessageType::Error);
-        assert!(msg.content.contains("Submit failed"));
-        assert!(msg.content.contains("pull request write permission"));
+        // when
+        app.toggle_help();
+
+        // then
+        assert_eq!(app.input_mode, InputMode::CommitSelect);
+        assert!(app.overlays().is_empty());
     }
 
     #[test]
-    fn should_discard_stale_submit_result_when_head_sha_changed() {
-        // given a PR session pretending we already swapped heads after submit dispatched.
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = line_comment(LineSide::New, Some(11), None);
-        let comment_id = comment.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        // record that a submit was in flight for an *older* head.
-        let in_flight = make_in_flight(SubmitEvent::Comment, &[comment_id.as_str()], "OLD_HEAD", 0);
-        app.pr_submit_state = Some(in_flight);
-        let (tx, rx) = std::sync::mpsc::channel();
-        app.pr_submit_rx = Some(rx);
-        // simulate the bg thread coming back for the OLD head, but with
-        // a fresh-head session note: poll path checks repository/pr_number/head_sha.
-        tx.send(PrSubmitEvent::Done {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 125,
-            head_sha: "DIFFERENT_HEAD".to_string(),
-            result: Ok(make_response(42, "u", "COMMENTED")),
-        })
-        .unwrap();
-        drop(tx);
+    fn should_confirm_before_discarding_unsaved_comment() {
+        // given
+        let mut app = build_app();
+        app.enter_review_comment_mode();
+        app.comment_buffer = "half a thought".to_string();
+
         // when
-        app.poll_pr_submit_events();
-        // then — comment lifecycle untouched, info message about discarded result.
-        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
-        let saved = &review.line_comments.get(&11).unwrap()[0];
-        assert_eq!(saved.lifecycle_state, CommentLifecycleState::LocalDraft);
-        let msg = app.message.as_ref().expect("info message");
-        assert!(
-            msg.content.contains("Discarded stale submit result"),
-            "got: {}",
-            msg.content
-        );
-    }
+        crate::handler::handle_comment_action(&mut app, Action::ExitMode);
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        assert!(app.is_mode_open(InputMode::Comment));
 
-    #[test]
-    fn should_apply_result_via_poll_when_head_sha_matches() {
-        // given a session with a local-draft comment ready to be locked
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = line_comment(LineSide::New, Some(11), None);
-        let comment_id = comment.id.clone();
-        add_line_comment(&mut app, "src/lib.rs", 11, comment);
-        let in_flight = make_in_flight(
-            SubmitEvent::Comment,
-            &[comment_id.as_str()],
-            "abcdef0123",
-            0,
-        );
-        app.pr_submit_state = Some(in_flight);
-        let (tx, rx) = std::sync::mpsc::channel();
-        app.pr_submit_rx = Some(rx);
-        tx.send(PrSubmitEvent::Done {
-            repository: ForgeRepository::github("github.com", "agavra", "tuicr"),
-            pr_number: 125,
-            head_sha: "abcdef0123".to_string(),
-            result: Ok(make_response(123, "u", "COMMENTED")),
-        })
-        .unwrap();
-        drop(tx);
         // when
-        app.poll_pr_submit_events();
-        // then — lifecycle moved and the spinner state is cleared.
-        assert!(app.pr_submit_state.is_none());
-        assert!(app.pr_submit_rx.is_none());
-        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
-        let saved = &review.line_comments.get(&11).unwrap()[0];
-        assert_eq!(saved.lifecycle_state, CommentLifecycleState::Submitted);
-        assert_eq!(saved.remote_review_id.as_deref(), Some("123"));
-    }
+        crate::handler::handle_confirm_action(&mut app, Action::ConfirmNo);
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Comment);
+        assert_eq!(app.comment_buffer, "half a thought");
 
-    #[test]
-    fn should_lock_file_level_comment_via_submit_success() {
-        // given — a file-level comment lives in `file_comments`, not `line_comments`
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let comment = Comment::new("file-level".to_string(), CommentType::Note, None);
-        let comment_id = comment.id.clone();
-        {
-            let review = app
-                .session
-                .get_file_mut(&PathBuf::from("src/lib.rs"))
-                .unwrap();
-            review.file_comments.push(comment);
-        }
-        let in_flight = make_in_flight(
-            SubmitEvent::Comment,
-            &[comment_id.as_str()],
-            "abcdef0123",
-            0,
-        );
-        let response = make_response(7, "u", "COMMENTED");
         // when
-        app.apply_submit_success(&in_flight, &response);
+        crate::handler::handle_comment_action(&mut app, Action::ExitMode);
+        crate::handler::handle_confirm_action(&mut app, Action::ConfirmYes);
+
         // then
-        let review = app.session.files.get(&PathBuf::from("src/lib.rs")).unwrap();
-        assert_eq!(
-            review.file_comments[0].lifecycle_state,
-            CommentLifecycleState::Submitted
-        );
-        assert_eq!(
-            review.file_comments[0].remote_review_id.as_deref(),
-            Some("7")
-        );
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.comment_buffer.is_empty());
+        assert!(!app.should_quit);
     }
 
     #[test]
-    fn should_report_stale_head_when_current_differs_from_session_head() {
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            line_comment(LineSide::New, Some(11), None),
-        );
-        // simulate a refresh having spotted a newer remote head
-        app.current_pr_head = Some("ffff5678".to_string());
-        app.start_submit(SubmitEvent::Comment);
-        assert!(app.submit_head_is_stale());
-    }
+    fn should_ask_before_quitting_with_unsaved_changes() {
+        // given
+        let mut app = build_app();
+        app.dirty = true;
+        app.enter_command_mode();
+        app.command_buffer = "q".to_string();
 
-    #[test]
-    fn should_report_head_not_stale_when_current_matches_session_head() {
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        add_line_comment(
-            &mut app,
-            "src/lib.rs",
-            11,
-            line_comment(LineSide::New, Some(11), None),
-        );
-        app.start_submit(SubmitEvent::Comment);
-        assert!(!app.submit_head_is_stale());
+        // when
+        crate::handler::handle_command_action(&mut app, Action::SubmitInput);
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Confirm);
+        assert!(!app.should_quit);
+
+        // when
+        crate::handler::handle_confirm_action(&mut app, Action::ConfirmNo);
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(!app.should_quit);
+
+        // when
+        app.enter_confirm_mode(ConfirmAction::QuitWithoutSaving);
+        crate::handler::handle_confirm_action(&mut app, Action::ConfirmYes);
+
+        // then
+        assert!(app.should_quit);
     }
 
     #[test]
-    fn should_detect_locked_comment_under_cursor_for_dd_path() {
-        // given an app with a locked line comment registered against the
-        // diff. We just verify the App helper sees the lock — exercising
-        // the handler keypath itself is covered in integration tests.
-        let mut app = make_pr_app_with_single_modified_file("src/lib.rs");
-        let mut c = line_comment(LineSide::New, Some(11), None);
-        c.lifecycle_state = CommentLifecycleState::PushedDraft;
-        add_line_comment(&mut app, "src/lib.rs", 11, c);
-        // No cursor positioning here — `cursor_on_locked_comment` resolves
-        // through `find_comment_at_cursor` which depends on annotations.
-        // The annotation indices use 0..N; with a single line comment on
-        // line 11 there's exactly one LineComment annotation. We point the
-        // cursor at it via diff_state.
-        app.rebuild_annotations();
-        // Find the LineComment annotation index.
-        let idx = app
-            .line_annotations
-            .iter()
-            .position(|a| matches!(a, AnnotatedLine::LineComment { .. }))
-            .expect("expected a LineComment annotation");
-        app.diff_state.cursor_line = idx;
-        assert!(app.cursor_on_locked_comment());
+    fn should_close_comment_without_prompt_when_nothing_changed() {
+        // given
+        let mut app = build_app();
+        let comment = Comment::new("keep".to_string(), CommentType::Note, None);
+        app.editing_comment_id = Some(comment.id.clone());
+        app.session.review_comments.push(comment);
+        app.input_mode = InputMode::Comment;
+        app.comment_is_review_level = true;
+        app.comment_buffer = "keep".to_string();
+
+        // when
+        crate::handler::handle_comment_action(&mut app, Action::ExitMode);
+
+        // then
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!(app.pending_confirm, None);
     }
 }
@@ -0,0 +1,325 @@
+//! `tuicr check`: headless evaluation of a saved review for CI. It reads a
+//! session file or a `:export <path>.json` export, prints one line per
+//! criterion and exits non-zero when any of them fails, so a merge can be
+//! gated on a completed review artifact.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, TuicrError};
+use crate::model::{Comment, CommentType, ReviewSession};
+use crate::persistence::storage::load_session;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckCriterion {
+    /// `--require all-reviewed`: every file is marked reviewed
+    AllReviewed,
+    /// `--forbid unresolved-issues`: no ISSUE comment is left in the review
+    UnresolvedIssues,
+    /// `--forbid deferred-lines`: no line is still marked to revisit
+    DeferredLines,
+}
+
+impl CheckCriterion {
+    /// Parse the value of `--require` (`forbid = false`) or `--forbid`.
+    pub fn from_name(name: &str, forbid: bool) -> Option<Self> {
+        match (name, forbid) {
+            ("all-reviewed", false) => Some(Self::AllReviewed),
+            ("unresolved-issues", true) => Some(Self::UnresolvedIssues),
+            ("deferred-lines", true) => Some(Self::DeferredLines),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::AllReviewed => "require all-reviewed",
+            Self::UnresolvedIssues => "forbid unresolved-issues",
+            Self::DeferredLines => "forbid deferred-lines",
+        }
+    }
+}
+
+/// `tuicr check` arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckArgs {
+    pub session: PathBuf,
+    /// Criteria to enforce; empty means all of them
+    pub criteria: Vec<CheckCriterion>,
+}
+
+/// What the check needs to know about a review, whichever file it came from
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ReviewState {
+    total_files: usize,
+    unreviewed: Vec<String>,
+    /// `path:line` (or `path`, or `review`) of each ISSUE comment
+    issues: Vec<String>,
+    /// `path:line` of each deferred line
+    deferred: Vec<String>,
+}
+
+impl ReviewState {
+    fn from_session(session: &ReviewSession) -> Self {
+        let mut state = Self {
+            total_files: session.files.len(),
+            ..Self::default()
+        };
+        let is_issue = |c: &&Comment| c.comment_type == CommentType::Issue;
+        for _ in session.review_comments.iter().filter(is_issue) {
+            state.issues.push("review".to_string());
+        }
+        let mut files: Vec<_> = session.files.iter().collect();
+        files.sort_by_key(|(path, _)| *path);
+        for (path, review) in files {
+            let path = path.display().to_string();
+            if !review.reviewed {
+                state.unreviewed.push(path.clone());
+            }
+            for _ in review.file_comments.iter().filter(is_issue) {
+                state.issues.push(path.clone());
+            }
+            let mut lines: Vec<_> = review.line_comments.iter().collect();
+            lines.sort_by_key(|(line, _)| **line);
+            for (line, comments) in lines {
+                for _ in comments.iter().filter(is_issue) {
+                    state.issues.push(format!("{path}:{line}"));
+                }
+            }
+            let mut deferred = review.deferred.clone();
+            deferred.sort_by_key(|d| d.line);
+            for marker in deferred {
+                state.deferred.push(format!("{path}:{}", marker.line));
+            }
+        }
+        state
+    }
+
+    /// Read the versioned JSON export (see `output::json`).
+    fn from_export(value: &serde_json::Value) -> Self {
+        let items = |v: &serde_json::Value| v.as_array().cloned().unwrap_or_default();
+        let mut state = Self::default();
+        for comment in items(&value["review_comments"]) {
+            if comment["type"] == "issue" {
+                state.issues.push("review".to_string());
+            }
+        }
+        for file in items(&value["files"]) {
+            let path = file["path"].as_str().unwrap_or_default().to_string();
+            state.total_files += 1;
+            if file["reviewed"] != true {
+                state.unreviewed.push(path.clone());
+            }
+            for comment in items(&file["comments"]) {
+                if comment["type"] != "issue" {
+                    continue;
+                }
+                match comment["line"].as_u64() {
+                    Some(line) => state.issues.push(format!("{path}:{line}")),
+                    None => state.issues.push(path.clone()),
+                }
+            }
+            for marker in items(&file["deferred"]) {
+                if let Some(line) = marker["line"].as_u64() {
+                    state.deferred.push(format!("{path}:{line}"));
+                }
+            }
+        }
+        state
+    }
+
+    /// `None` when `criterion` holds, otherwise why it fails
+    fn failure(&self, criterion: CheckCriterion) -> Option<String> {
+        let (count, what, items) = match criterion {
+            CheckCriterion::AllReviewed if !self.unreviewed.is_empty() => (
+                self.unreviewed.len(),
+                format!("of {} files not reviewed", self.total_files),
+                &self.unreviewed,
+            ),
+            CheckCriterion::UnresolvedIssues if !self.issues.is_empty() => {
+                let plural = if self.issues.len() == 1 { "" } else { "s" };
+                (
+                    self.issues.len(),
+                    format!("ISSUE comment{plural}"),
+                    &self.issues,
+                )
+            }
+            CheckCriterion::DeferredLines if !self.deferred.is_empty() => {
+                let plural = if self.deferred.len() == 1 { "" } else { "s" };
+                (
+                    self.deferred.len(),
+                    format!("deferred line{plural}"),
+                    &self.deferred,
+                )
+            }
+            _ => return None,
+        };
+        Some(format!("{count} {what} ({})", items.join(", ")))
+    }
+}
+
+fn load_state(path: &Path) -> Result<ReviewState> {
+    let contents = std::fs::read_to_string(path)?;
+    let value: serde_json::Value =
+        serde_json::from_str(&contents).map_err(|e| TuicrError::CorruptedSession(e.to_string()))?;
+    if value.get("schema_version").is_some() {
+        return Ok(ReviewState::from_export(&value));
+    }
+    Ok(ReviewState::from_session(&load_session(
+        &path.to_path_buf(),
+    )?))
+}
+
+const ALL_CRITERIA: &[CheckCriterion] = &[
+    CheckCriterion::AllReviewed,
+    CheckCriterion::UnresolvedIssues,
+    CheckCriterion::DeferredLines,
+];
+
+/// Why `session` fails a criterion, or `None` when it passes them all. Git
+/// hooks block on this, so they hold a review to the same bar as CI.
+pub fn incomplete_reason(session: &ReviewSession) -> Option<String> {
+    let state = ReviewState::from_session(session);
+    let reasons: Vec<String> = ALL_CRITERIA
+        .iter()
+        .filter_map(|&criterion| state.failure(criterion))
+        .collect();
+    (!reasons.is_empty()).then(|| reasons.join("; "))
+}
+
+/// The report lines for `criteria` (all of them when empty), and whether
+/// every one held.
+fn evaluate(state: &ReviewState, criteria: &[CheckCriterion]) -> (Vec<String>, bool) {
+    let criteria = if criteria.is_empty() {
+        ALL_CRITERIA
+    } else {
+        criteria
+    };
+    let mut passed = true;
+    let lines = criteria
+        .iter()
+        .map(|&criterion| match state.failure(criterion) {
+            Some(reason) => {
+                passed = false;
+                format!("FAIL {}: {reason}", criterion.label())
+            }
+            None => format!("ok   {}", criterion.label()),
+        })
+        .collect();
+    (lines, passed)
+}
+
+/// Run the check and return the process exit code: 0 when every criterion
+/// holds, 1 when one fails, 2 when the file cannot be read.
+pub fn run(args: &CheckArgs) -> i32 {
+    let state = match load_state(&args.session) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("tuicr check: {}: {e}", args.session.display());
+            return 2;
+        }
+    };
+    let (lines, passed) = evaluate(&state, &args.criteria);
+    for line in lines {
+        println!("{line}");
+    }
+    if passed { 0 } else { 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{FileStatus, LineSide};
+    use crate::output::tests::create_test_session;
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 0);
+        session
+    }
+
+    #[test]
+    fn should_report_each_failing_criterion() {
+        // given
+        let state = ReviewState::from_session(&session());
+
+        // when
+        let (lines, passed) = evaluate(&state, &[]);
+        let (only_reviewed, _) = evaluate(&state, &[CheckCriterion::AllReviewed]);
+
+        // then
+        assert!(!passed);
+        assert_eq!(
+            lines,
+            vec![
+                "FAIL require all-reviewed: 1 of 2 files not reviewed (src/lib.rs)",
+                "FAIL forbid unresolved-issues: 1 ISSUE comment (src/main.rs:42)",
+                "ok   forbid deferred-lines",
+            ]
+        );
+        assert_eq!(only_reviewed.len(), 1);
+    }
+
+    #[test]
+    fn should_give_hooks_the_reason_a_review_fails_the_check() {
+        // given
+        let mut session = session();
+        session
+            .get_file_mut(&PathBuf::from("src/main.rs"))
+            .unwrap()
+            .toggle_deferred(43, LineSide::New);
+
+        // when
+        let blocked = incomplete_reason(&session);
+        for review in session.files.values_mut() {
+            review.reviewed = true;
+            review.line_comments.clear();
+            review.deferred.clear();
+        }
+        let complete = incomplete_reason(&session);
+
+        // then
+        assert_eq!(
+            blocked.as_deref(),
+            Some(
+                "1 of 2 files not reviewed (src/lib.rs); 1 ISSUE comment (src/main.rs:42); \
+                 1 deferred line (src/main.rs:43)"
+            )
+        );
+        assert_eq!(complete, None);
+    }
+
+    #[test]
+    fn should_read_session_files_and_json_exports_alike() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = session();
+        for review in session.files.values_mut() {
+            review.reviewed = true;
+            review.toggle_deferred(3, LineSide::Old);
+        }
+        let session_path = dir.path().join("session.json");
+        let export_path = dir.path().join("review.json");
+        std::fs::write(&session_path, serde_json::to_string(&session).unwrap()).unwrap();
+        crate::output::export_json_to_file(
+            &export_path,
+            &session,
+            &[],
+            crate::output::json::DiffAnchors::default(),
+        )
+        .unwrap();
+
+        // when
+        let from_session = load_state(&session_path).unwrap();
+        let from_export = load_state(&export_path).unwrap();
+
+        // then
+        assert_eq!(from_session, from_export);
+        let (lines, passed) = evaluate(&from_export, &[]);
+        assert!(!passed);
+        assert_eq!(lines[0], "ok   require all-reviewed");
+        assert_eq!(
+            lines[2],
+            "FAIL forbid deferred-lines: 2 deferred lines (src/lib.rs:3, src/main.rs:3)"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
@@ -6,6 +7,8 @@ use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use toml::Value;
 
+use crate::model::Reviewer;
+
 pub const DEFAULT_LEADER_KEY: char = ';';
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
@@ -38,6 +41,123 @@ impl Default for ForgeConfig {
     }
 }
 
+/// `[renames]` section: how deleted/added file pairs are matched up as
+/// renames when building diffs.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RenamesConfig {
+    /// Detect renames at all. Defaults to `true`.
+    pub enabled: bool,
+    /// Also detect copies of files modified in the same diff. Defaults to
+    /// `false`.
+    pub copies: bool,
+    /// Break heavily rewritten files apart so their old content can be
+    /// matched as a rename source (`git diff -B`). Defaults to `false`.
+    pub break_rewrites: bool,
+    /// Minimum similarity percentage (0-100). Defaults to `50`, like git.
+    pub threshold: u16,
+    /// Maximum number of candidate files before detection is skipped; `0`
+    /// means no limit. Defaults to `1000`.
+    pub limit: usize,
+}
+
+impl Default for RenamesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            copies: false,
+            break_rewrites: false,
+            threshold: 50,
+            limit: 1000,
+        }
+    }
+}
+
+/// How the terminal is told that a slow operation finished.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyMethod {
+    #[default]
+    Off,
+    /// Ring the terminal bell; tmux flags the window.
+    Bell,
+    /// Desktop notification via OSC 9.
+    Osc,
+}
+
+/// `[notify]` section: which finished operations ring the bell or raise a
+/// desktop notification.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Background diff loads: `:e` reloads, opening a PR, commit ranges.
+    pub diff_load: NotifyMethod,
+    /// `:export <path>` and the clipboard export.
+    pub export: NotifyMethod,
+    /// Submitting or pushing a GitHub review.
+    pub push: NotifyMethod,
+    /// Operations quicker than this many seconds don't notify. Defaults to
+    /// `5`; `0` notifies every time.
+    pub min_seconds: u64,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            diff_load: NotifyMethod::Off,
+            export: NotifyMethod::Off,
+            push: NotifyMethod::Off,
+            min_seconds: 5,
+        }
+    }
+}
+
+/// `[bitbucket]` section: where `:bitbucket <pr>` posts and how it
+/// authenticates.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct BitbucketConfig {
+    /// REST API root. Defaults to `https://api.bitbucket.org/2.0` for Cloud
+    /// and `<server>/rest/api/1.0` for Data Center.
+    pub base_url: Option<String>,
+    /// Access token, or `user:app_password` for basic auth. The
+    /// `BITBUCKET_TOKEN` environment variable takes precedence.
+    pub token: Option<String>,
+}
+
+/// A named `[templates.<name>]` preset for a recurring kind of review,
+/// selected at startup with `--template <name>`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SessionTemplate {
+    /// Checklist items seeded into a fresh session.
+    pub checklist: Vec<String>,
+    /// File or directory filter, equivalent to `--path`.
+    pub path: Option<String>,
+    /// Overrides the top-level `export_legend` for this review.
+    pub export_legend: Option<bool>,
+    /// Report format of a bare `:export`, as a file extension such as
+    /// `sarif` (see [`TEMPLATE_EXPORT_FORMATS`]).
+    pub export_format: Option<String>,
+}
+
+/// A `[filetypes.<ext>]` section: display overrides for files with that
+/// extension (or that exact file name, e.g. `Makefile`).
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct FiletypeConfig {
+    /// Columns a tab expands to. Defaults to `4`.
+    pub tab_width: Option<usize>,
+    /// Soft-wrap long lines while the cursor is in such a file, overriding
+    /// `wrap` and `:set wrap`.
+    pub wrap: Option<bool>,
+    /// Show only the file header until Enter opens it.
+    pub collapse: Option<bool>,
+    /// Syntax to highlight with, by name (`Markdown`) or extension (`md`);
+    /// `"none"` turns highlighting off.
+    pub syntax: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(default)]
 pub struct AppConfig {
@@ -56,9 +176,65 @@ pub struct AppConfig {
     pub leader: Option<char>,
     pub transparent_background: Option<bool>,
     pub scroll_offset: Option<usize>,
+    /// Runs of more unchanged lines than this inside a hunk are collapsed;
+    /// `0` keeps every line.
+    pub elide_context: Option<usize>,
+    /// Show line comments from earlier sessions of the repository ghosted
+    /// on the same lines. Defaults to on.
+    pub history_comments: Option<bool>,
+    /// Git notes ref that saved sessions are mirrored to, e.g.
+    /// `refs/notes/tuicr`. `None` keeps sessions local.
+    pub notes_ref: Option<String>,
+    /// Record ISSUE comments from exported reviews in a local findings
+    /// database and flag files that keep collecting them. Defaults to off.
+    pub findings_db: Option<bool>,
+    /// Record each completed review (time spent, lines changed, comments)
+    /// for `tuicr stats`. Defaults to off.
+    pub review_metrics: Option<bool>,
+    /// Seconds an external command (git, hg, jj, gh, glab) may run before
+    /// it is killed; `0` waits forever.
+    pub command_timeout: Option<usize>,
+    /// Where sessions are saved: `"json"` files (the default) or a
+    /// `"sqlite"` database, which needs the `sqlite` build feature.
+    pub session_store: Option<String>,
+    /// Where session files are kept: `"data"` (tuicr's data directory, the
+    /// default), `"repo"` (`.tuicr/` at the repository root) or `"git-dir"`.
+    pub session_location: Option<String>,
+    /// Days after its last update that a session is pruned on startup and
+    /// by `tuicr sessions gc`; `0` keeps sessions forever. Defaults to 7.
+    pub session_max_age_days: Option<usize>,
+    /// Seconds after the last change that the session is saved on its
+    /// own; `0` (the default) saves only on `:w`.
+    pub autosave: Option<usize>,
+    /// Template file that the clipboard/stdout export and Markdown
+    /// `:export <path>` reports are rendered through instead of the
+    /// built-in layout.
+    pub export_template: Option<String>,
+    /// Incoming webhook URL (Slack or Teams) that `:chat post` sends the
+    /// review summary to.
+    pub chat_webhook: Option<String>,
+    /// Reviewer identity (`Name <email>`) stamped on comments and exports,
+    /// instead of the VCS's user name and email.
+    pub reviewer: Option<Reviewer>,
+    /// Label comments in Conventional Comments form (`issue (blocking):`)
+    /// in the UI and Markdown exports. Defaults to off.
+    pub conventional_comments: Option<bool>,
+    /// Open a dialog with the review's stats and next actions when the last
+    /// file is marked reviewed. Defaults to on.
+    pub completion_dialog: Option<bool>,
     /// `[forge]` section settings. Always present; `None` means "no override"
     /// and downstream code should treat it as `ForgeConfig::default()`.
     pub forge: Option<ForgeConfig>,
+    /// `[renames]` section settings; `None` means "no override".
+    pub renames: Option<RenamesConfig>,
+    /// `[bitbucket]` section settings; `None` means "no override".
+    pub bitbucket: Option<BitbucketConfig>,
+    /// `[notify]` section settings; `None` means "no override".
+    pub notify: Option<NotifyConfig>,
+    /// `[templates.<name>]` sections, keyed by template name.
+    pub templates: BTreeMap<String, SessionTemplate>,
+    /// `[filetypes.<ext>]` sections, keyed by extension or file name.
+    pub filetypes: BTreeMap<String, FiletypeConfig>,
 }
 
 /// Known top-level config keys. Used to warn about typos.
@@ -78,11 +254,61 @@ const KNOWN_KEYS: &[&str] = &[
     "leader",
     "transparent_background",
     "scroll_offset",
+    "elide_context",
+    "history_comments",
+    "notes_ref",
+    "findings_db",
+    "review_metrics",
+    "command_timeout",
+    "session_store",
+    "session_location",
+    "session_max_age_days",
+    "autosave",
+    "export_template",
+    "chat_webhook",
+    "reviewer",
+    "conventional_comments",
+    "completion_dialog",
     "forge",
+    "renames",
+    "bitbucket",
+    "notify",
+    "templates",
+    "filetypes",
 ];
 
 const FORGE_KNOWN_KEYS: &[&str] = &["comment_type_prefix", "review_footer"];
 
+const RENAMES_KNOWN_KEYS: &[&str] = &["enabled", "copies", "break_rewrites", "threshold", "limit"];
+
+const BITBUCKET_KNOWN_KEYS: &[&str] = &["base_url", "token"];
+
+const NOTIFY_KNOWN_KEYS: &[&str] = &["diff_load", "export", "push", "min_seconds"];
+
+const TEMPLATE_KNOWN_KEYS: &[&str] = &["checklist", "path", "export_legend", "export_format"];
+
+/// Extensions `:export <path>` writes a dedicated report for, accepted as a
+/// template's `export_format`
+pub const TEMPLATE_EXPORT_FORMATS: &[&str] = &[
+    "md",
+    "notes.md",
+    "json",
+    "gerrit.json",
+    "sarif",
+    "xml",
+    "rdjson",
+    "rdjsonl",
+    "eml",
+    "html",
+    "patch",
+    "annotated.patch",
+];
+
+const FILETYPE_KNOWN_KEYS: &[&str] = &["tab_width", "wrap", "collapse", "syntax"];
+
+/// Widest `filetypes.<ext>.tab_width` accepted
+const MAX_TAB_WIDTH: i64 = 16;
+
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ConfigLoadOutcome {
     pub config: Option<AppConfig>,
@@ -140,6 +366,23 @@ pub fn load_config() -> Result<ConfigLoadOutcome> {
     load_config_from_path(&path)
 }
 
+/// Look up the `--template <name>` section, naming the available templates
+/// when it doesn't exist.
+pub fn find_template<'a>(
+    config: Option<&'a AppConfig>,
+    name: &str,
+) -> std::result::Result<&'a SessionTemplate, String> {
+    let templates = config.map(|cfg| &cfg.templates);
+    if let Some(template) = templates.and_then(|t| t.get(name)) {
+        return Ok(template);
+    }
+    let available = templates
+        .filter(|t| !t.is_empty())
+        .map(|t| t.keys().cloned().collect::<Vec<_>>().join(", "))
+        .unwrap_or_else(|| format!("none defined in {}", config_path_hint()));
+    Err(format!("Unknown template '{name}'. Available: {available}"))
+}
+
 /// Read a string value from the table, pushing a warning if the type is wrong.
 fn read_string(table: &toml::Table, key: &str, warnings: &mut Vec<String>) -> Option<String> {
     let val = table.get(key)?;
@@ -169,6 +412,44 @@ fn read_leader(table: &toml::Table, warnings: &mut Vec<String>) -> Option<char>
     }
 }
 
+/// Read the `notes_ref` key, which must name a ref under `refs/notes/`.
+fn read_notes_ref(table: &toml::Table, warnings: &mut Vec<String>) -> Option<String> {
+    let raw = read_string(table, "notes_ref", warnings)?;
+    if raw.starts_with("refs/notes/") && raw.len() > "refs/notes/".len() {
+        Some(raw)
+    } else {
+        warnings.push(format!(
+            "Warning: Config key 'notes_ref' must start with \"refs/notes/\"; got \"{raw}\", ignoring"
+        ));
+        None
+    }
+}
+
+/// Read `session_store`, falling back to JSON files when this build has no
+/// SQLite support.
+fn read_session_store(table: &toml::Table, warnings: &mut Vec<String>) -> Option<String> {
+    let store = read_enum(table, "session_store", &["json", "sqlite"], warnings)?;
+    if store == "sqlite" && !cfg!(feature = "sqlite") {
+        warnings.push(
+            "Warning: Config key 'session_store' is \"sqlite\" but tuicr was built without the sqlite feature; using JSON files"
+                .to_string(),
+        );
+        return None;
+    }
+    Some(store)
+}
+
+fn read_reviewer(table: &toml::Table, warnings: &mut Vec<String>) -> Option<Reviewer> {
+    let raw = read_string(table, "reviewer", warnings)?;
+    let reviewer = Reviewer::parse(&raw);
+    if reviewer.is_none() {
+        warnings.push(format!(
+            "Warning: Config key 'reviewer' must be a name, optionally followed by <email>; got \"{raw}\", ignoring"
+        ));
+    }
+    reviewer
+}
+
 /// Read a boolean value from the table, pushing a warning if the type is wrong.
 fn read_bool(table: &toml::Table, key: &str, warnings: &mut Vec<String>) -> Option<bool> {
     let val = table.get(key)?;
@@ -263,9 +544,46 @@ fn load_config_from_path(path: &Path) -> Result<ConfigLoadOutcome> {
         leader: read_leader(table, &mut warnings),
         transparent_background: read_bool(table, "transparent_background", &mut warnings),
         scroll_offset: read_usize(table, "scroll_offset", &mut warnings),
+        elide_context: read_usize(table, "elide_context", &mut warnings),
+        history_comments: read_bool(table, "history_comments", &mut warnings),
+        notes_ref: read_notes_ref(table, &mut warnings),
+        findings_db: read_bool(table, "findings_db", &mut warnings),
+        review_metrics: read_bool(table, "review_metrics", &mut warnings),
+        command_timeout: read_usize(table, "command_timeout", &mut warnings),
+        session_store: read_session_store(table, &mut warnings),
+        session_location: read_enum(
+            table,
+            "session_location",
+            &["data", "repo", "git-dir"],
+            &mut warnings,
+        ),
+        session_max_age_days: read_usize(table, "session_max_age_days", &mut warnings),
+        autosave: read_usize(table, "autosave", &mut warnings),
+        export_template: read_string(table, "export_template", &mut warnings),
+        chat_webhook: read_string(table, "chat_webhook", &mut warnings),
+        reviewer: read_reviewer(table, &mut warnings),
+        conventional_comments: read_bool(table, "conventional_comments", &mut warnings),
+        completion_dialog: read_bool(table, "completion_dialog", &mut warnings),
         forge: table
             .get("forge")
             .and_then(|v| parse_forge(v, &mut warnings)),
+        renames: table
+            .get("renames")
+            .and_then(|v| parse_renames(v, &mut warnings)),
+        bitbucket: table
+            .get("bitbucket")
+            .and_then(|v| parse_bitbucket(v, &mut warnings)),
+        notify: table
+            .get("notify")
+            .and_then(|v| parse_notify(v, &mut warnings)),
+        templates: table
+            .get("templates")
+            .map(|v| parse_templates(v, &mut warnings))
+            .unwrap_or_default(),
+        filetypes: table
+            .get("filetypes")
+            .map(|v| parse_filetypes(v, &mut warnings))
+            .unwrap_or_default(),
     };
 
     for key in table.keys() {
@@ -327,6 +645,306 @@ fn read_forge_bool(table: &toml::Table, key: &str, warnings: &mut Vec<String>) -
     }
 }
 
+/// Parse the `[renames]` section, returning `Some` when any value overrides
+/// the defaults. Bad values are dropped with a `renames.<key>` warning.
+fn parse_renames(value: &Value, warnings: &mut Vec<String>) -> Option<RenamesConfig> {
+    let Some(table) = value.as_table() else {
+        warnings.push("Warning: Config key 'renames' must be a table; ignoring value".to_string());
+        return None;
+    };
+
+    for key in table.keys() {
+        if !RENAMES_KNOWN_KEYS.contains(&key.as_str()) {
+            warnings.push(format!(
+                "Warning: Unknown config key 'renames.{key}', ignoring"
+            ));
+        }
+    }
+
+    let mut cfg = RenamesConfig::default();
+    let mut any_override = false;
+
+    for (key, slot) in [
+        ("enabled", &mut cfg.enabled),
+        ("copies", &mut cfg.copies),
+        ("break_rewrites", &mut cfg.break_rewrites),
+    ] {
+        let Some(val) = table.get(key) else {
+            continue;
+        };
+        match val.as_bool() {
+            Some(b) => {
+                *slot = b;
+                any_override = true;
+            }
+            None => warnings.push(format!(
+                "Warning: Config key 'renames.{key}' must be a boolean; ignoring value"
+            )),
+        }
+    }
+    if let Some(val) = table.get("threshold") {
+        match val.as_integer() {
+            Some(n @ 0..=100) => {
+                cfg.threshold = n as u16;
+                any_override = true;
+            }
+            _ => warnings.push(
+                "Warning: Config key 'renames.threshold' must be an integer from 0 to 100; ignoring value"
+                    .to_string(),
+            ),
+        }
+    }
+    if let Some(val) = table.get("limit") {
+        match val.as_integer() {
+            Some(n) if n >= 0 => {
+                cfg.limit = n as usize;
+                any_override = true;
+            }
+            _ => warnings.push(
+                "Warning: Config key 'renames.limit' must be a non-negative integer; ignoring value"
+                    .to_string(),
+            ),
+        }
+    }
+
+    if any_override { Some(cfg) } else { None }
+}
+
+/// Parse the `[notify]` section, returning `Some` when any value overrides
+/// the defaults. Bad values are dropped with a `notify.<key>` warning.
+fn parse_notify(value: &Value, warnings: &mut Vec<String>) -> Option<NotifyConfig> {
+    let Some(table) = value.as_table() else {
+        warnings.push("Warning: Config key 'notify' must be a table; ignoring value".to_string());
+        return None;
+    };
+
+    for key in table.keys() {
+        if !NOTIFY_KNOWN_KEYS.contains(&key.as_str()) {
+            warnings.push(format!(
+                "Warning: Unknown config key 'notify.{key}', ignoring"
+            ));
+        }
+    }
+
+    let mut cfg = NotifyConfig::default();
+    let mut any_override = false;
+
+    for (key, slot) in [
+        ("diff_load", &mut cfg.diff_load),
+        ("export", &mut cfg.export),
+        ("push", &mut cfg.push),
+    ] {
+        let Some(val) = table.get(key) else {
+            continue;
+        };
+        let method = match val.as_str() {
+            Some("off") => Some(NotifyMethod::Off),
+            Some("bell") => Some(NotifyMethod::Bell),
+            Some("osc") => Some(NotifyMethod::Osc),
+            _ => None,
+        };
+        match method {
+            Some(method) => {
+                *slot = method;
+                any_override = true;
+            }
+            None => warnings.push(format!(
+                "Warning: Config key 'notify.{key}' must be \"off\", \"bell\" or \"osc\"; ignoring value"
+            )),
+        }
+    }
+    if let Some(val) = table.get("min_seconds") {
+        match val.as_integer() {
+            Some(n) if n >= 0 => {
+                cfg.min_seconds = n as u64;
+                any_override = true;
+            }
+            _ => warnings.push(
+                "Warning: Config key 'notify.min_seconds' must be a non-negative integer; ignoring value"
+                    .to_string(),
+            ),
+        }
+    }
+
+    if any_override { Some(cfg) } else { None }
+}
+
+/// Parse the `[bitbucket]` section, returning `None` when it sets nothing.
+fn parse_bitbucket(value: &Value, warnings: &mut Vec<String>) -> Option<BitbucketConfig> {
+    let Some(table) = value.as_table() else {
+        warnings
+            .push("Warning: Config key 'bitbucket' must be a table; ignoring value".to_string());
+        return None;
+    };
+
+    for key in table.keys() {
+        if !BITBUCKET_KNOWN_KEYS.contains(&key.as_str()) {
+            warnings.push(format!(
+                "Warning: Unknown config key 'bitbucket.{key}', ignoring"
+            ));
+        }
+    }
+
+    let mut read = |key: &str| {
+        let val = table.get(key)?;
+        match val.as_str() {
+            Some(s) if !s.is_empty() => Some(s.to_string()),
+            _ => {
+                warnings.push(format!(
+                    "Warning: Config key 'bitbucket.{key}' must be a non-empty string; ignoring value"
+                ));
+                None
+            }
+        }
+    };
+    let cfg = BitbucketConfig {
+        base_url: read("base_url").map(|url| url.trim_end_matches('/').to_string()),
+        token: read("token"),
+    };
+
+    (cfg != BitbucketConfig::default()).then_some(cfg)
+}
+
+/// Parse the `[templates.<name>]` sections. Malformed templates and fields
+/// are dropped with a warning so one typo doesn't disable the others.
+fn parse_templates(value: &Value, warnings: &mut Vec<String>) -> BTreeMap<String, SessionTemplate> {
+    let mut templates = BTreeMap::new();
+    let Some(table) = value.as_table() else {
+        warnings
+            .push("Warning: Config key 'templates' must be a table; ignoring value".to_string());
+        return templates;
+    };
+
+    for (name, entry) in table {
+        let Some(entry) = entry.as_table() else {
+            warnings.push(format!(
+                "Warning: Config key 'templates.{name}' must be a table; ignoring value"
+            ));
+            continue;
+        };
+
+        for key in entry.keys() {
+            if !TEMPLATE_KNOWN_KEYS.contains(&key.as_str()) {
+                warnings.push(format!(
+                    "Warning: Unknown config key 'templates.{name}.{key}', ignoring"
+                ));
+            }
+        }
+
+        let mut template = SessionTemplate::default();
+        if let Some(raw) = entry.get("checklist") {
+            match raw.as_array() {
+                Some(items) => {
+                    for item in items {
+                        match item.as_str().map(str::trim) {
+                            Some(text) if !text.is_empty() => {
+                                template.checklist.push(text.to_string())
+                            }
+                            _ => warnings.push(format!(
+                                "Warning: Config key 'templates.{name}.checklist' must contain non-empty strings; ignoring entry"
+                            )),
+                        }
+                    }
+                }
+                None => warnings.push(format!(
+                    "Warning: Config key 'templates.{name}.checklist' must be an array of strings; ignoring value"
+                )),
+            }
+        }
+        match entry.get("path").map(Value::as_str) {
+            None => {}
+            Some(Some(path)) if !path.is_empty() => template.path = Some(path.to_string()),
+            Some(_) => warnings.push(format!(
+                "Warning: Config key 'templates.{name}.path' must be a non-empty string; ignoring value"
+            )),
+        }
+        match entry.get("export_legend").map(Value::as_bool) {
+            None => {}
+            Some(Some(v)) => template.export_legend = Some(v),
+            Some(None) => warnings.push(format!(
+                "Warning: Config key 'templates.{name}.export_legend' must be a boolean; ignoring value"
+            )),
+        }
+        match entry.get("export_format").map(Value::as_str) {
+            None => {}
+            Some(Some(format)) if TEMPLATE_EXPORT_FORMATS.contains(&format) => {
+                template.export_format = Some(format.to_string())
+            }
+            Some(_) => warnings.push(format!(
+                "Warning: Config key 'templates.{name}.export_format' must be one of {}; ignoring value",
+                TEMPLATE_EXPORT_FORMATS.join(", ")
+            )),
+        }
+
+        templates.insert(name.clone(), template);
+    }
+
+    templates
+}
+
+/// Parse the `[filetypes.<ext>]` sections. Keys may be written `md`, `.md`
+/// or `*.md`; they are stored as `md`. Bad fields are dropped with a warning.
+fn parse_filetypes(value: &Value, warnings: &mut Vec<String>) -> BTreeMap<String, FiletypeConfig> {
+    let mut filetypes = BTreeMap::new();
+    let Some(table) = value.as_table() else {
+        warnings
+            .push("Warning: Config key 'filetypes' must be a table; ignoring value".to_string());
+        return filetypes;
+    };
+
+    for (name, entry) in table {
+        let Some(entry) = entry.as_table() else {
+            warnings.push(format!(
+                "Warning: Config key 'filetypes.{name}' must be a table; ignoring value"
+            ));
+            continue;
+        };
+
+        for key in entry.keys() {
+            if !FILETYPE_KNOWN_KEYS.contains(&key.as_str()) {
+                warnings.push(format!(
+                    "Warning: Unknown config key 'filetypes.{name}.{key}', ignoring"
+                ));
+            }
+        }
+
+        let mut filetype = FiletypeConfig::default();
+        match entry.get("tab_width").map(Value::as_integer) {
+            None => {}
+            Some(Some(n @ 1..=MAX_TAB_WIDTH)) => filetype.tab_width = Some(n as usize),
+            Some(_) => warnings.push(format!(
+                "Warning: Config key 'filetypes.{name}.tab_width' must be an integer from 1 to {MAX_TAB_WIDTH}; ignoring value"
+            )),
+        }
+        for (key, slot) in [
+            ("wrap", &mut filetype.wrap),
+            ("collapse", &mut filetype.collapse),
+        ] {
+            match entry.get(key).map(Value::as_bool) {
+                None => {}
+                Some(Some(v)) => *slot = Some(v),
+                Some(None) => warnings.push(format!(
+                    "Warning: Config key 'filetypes.{name}.{key}' must be a boolean; ignoring value"
+                )),
+            }
+        }
+        match entry.get("syntax").map(Value::as_str) {
+            None => {}
+            Some(Some(syntax)) if !syntax.trim().is_empty() => {
+                filetype.syntax = Some(syntax.trim().to_string())
+            }
+            Some(_) => warnings.push(format!(
+                "Warning: Config key 'filetypes.{name}.syntax' must be a non-empty string; ignoring value"
+            )),
+        }
+
+        let key = name.trim_start_matches('*').trim_start_matches('.');
+        filetypes.insert(key.to_string(), filetype);
+    }
+
+    filetypes
+}
+
 fn parse_comment_types(
     value: &Value,
     warnings: &mut Vec<String>,
@@ -867,6 +1485,202 @@ mod tests {
         assert_eq!(outcome.warnings.len(), 1);
     }
 
+    // elide_context
+
+    #[test]
+    fn should_parse_elide_context() {
+        let outcome = parse_config("elide_context = 0\n");
+        assert_eq!(
+            outcome.config.as_ref().and_then(|cfg| cfg.elide_context),
+            Some(0)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_session_max_age_days() {
+        let outcome = parse_config("session_max_age_days = 30\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.session_max_age_days),
+            Some(30)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_command_timeout() {
+        let outcome = parse_config("command_timeout = 30\n");
+        assert_eq!(
+            outcome.config.as_ref().and_then(|cfg| cfg.command_timeout),
+            Some(30)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_session_store() {
+        let outcome = parse_config("session_store = \"json\"\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.session_store.as_deref()),
+            Some("json")
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_autosave() {
+        let outcome = parse_config("autosave = 5\n");
+        assert_eq!(
+            outcome.config.as_ref().and_then(|cfg| cfg.autosave),
+            Some(5)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_export_template() {
+        let outcome = parse_config("export_template = \"~/.config/tuicr/review.md\"\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.export_template.as_deref()),
+            Some("~/.config/tuicr/review.md")
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_reviewer() {
+        let outcome = parse_config("reviewer = \"Jane Doe <jane@example.com>\"\n");
+        assert_eq!(
+            outcome.config.and_then(|cfg| cfg.reviewer),
+            Some(Reviewer {
+                name: "Jane Doe".to_string(),
+                email: Some("jane@example.com".to_string()),
+            })
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_chat_webhook() {
+        let outcome = parse_config("chat_webhook = \"https://hooks.slack.com/services/T0/B0/x\"\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.chat_webhook.as_deref()),
+            Some("https://hooks.slack.com/services/T0/B0/x")
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_conventional_comments() {
+        let outcome = parse_config("conventional_comments = true\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.conventional_comments),
+            Some(true)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_completion_dialog() {
+        let outcome = parse_config("completion_dialog = false\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.completion_dialog),
+            Some(false)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_history_comments() {
+        let outcome = parse_config("history_comments = false\n");
+        assert_eq!(
+            outcome.config.as_ref().and_then(|cfg| cfg.history_comments),
+            Some(false)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_review_metrics() {
+        let outcome = parse_config("review_metrics = true\n");
+        assert_eq!(
+            outcome.config.as_ref().and_then(|cfg| cfg.review_metrics),
+            Some(true)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_parse_findings_db() {
+        let outcome = parse_config("findings_db = true\n");
+        assert_eq!(
+            outcome.config.as_ref().and_then(|cfg| cfg.findings_db),
+            Some(true)
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_warn_and_ignore_negative_elide_context() {
+        let outcome = parse_config("elide_context = -1\n");
+        assert_eq!(
+            outcome.config.as_ref().and_then(|cfg| cfg.elide_context),
+            None
+        );
+        assert_eq!(outcome.warnings.len(), 1);
+    }
+
+    // notes_ref
+
+    #[test]
+    fn should_parse_notes_ref() {
+        let outcome = parse_config("notes_ref = \"refs/notes/review\"\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.notes_ref.as_deref()),
+            Some("refs/notes/review")
+        );
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_warn_and_ignore_notes_ref_outside_notes_namespace() {
+        let outcome = parse_config("notes_ref = \"refs/heads/main\"\n");
+        assert_eq!(
+            outcome
+                .config
+                .as_ref()
+                .and_then(|cfg| cfg.notes_ref.as_deref()),
+            None
+        );
+        assert_eq!(
+            outcome.warnings,
+            vec![
+                "Warning: Config key 'notes_ref' must start with \"refs/notes/\"; got \"refs/heads/main\", ignoring"
+            ]
+        );
+    }
+
     // comment_types
 
     #[test]
@@ -916,6 +1730,35 @@ mod tests {
         assert_eq!(outcome.warnings.len(), 3);
     }
 
+    // bitbucket
+
+    #[test]
+    fn should_parse_bitbucket_section_and_warn_on_bad_values() {
+        let outcome = parse_config(
+            r#"[bitbucket]
+base_url = "https://bitbucket.example.com/"
+token = 42
+"#,
+        );
+        let bitbucket = outcome
+            .config
+            .as_ref()
+            .and_then(|cfg| cfg.bitbucket.clone())
+            .expect("bitbucket section should parse");
+        assert_eq!(
+            bitbucket.base_url.as_deref(),
+            Some("https://bitbucket.example.com")
+        );
+        assert_eq!(bitbucket.token, None);
+        assert_eq!(
+            outcome.warnings,
+            vec![
+                "Warning: Config key 'bitbucket.token' must be a non-empty string; ignoring value"
+                    .to_string()
+            ]
+        );
+    }
+
     // forge
 
     #[test]
@@ -1029,6 +1872,202 @@ comment_type_prefix = "yes"
         assert!(cfg.review_footer);
     }
 
+    // renames
+
+    #[test]
+    fn should_parse_renames_section_overriding_defaults() {
+        let outcome = parse_config(
+            r#"[renames]
+copies = true
+break_rewrites = true
+threshold = 80
+"#,
+        );
+        let renames = outcome
+            .config
+            .as_ref()
+            .and_then(|cfg| cfg.renames.clone())
+            .expect("renames section should parse");
+        assert!(renames.enabled);
+        assert!(renames.copies);
+        assert!(renames.break_rewrites);
+        assert_eq!(renames.threshold, 80);
+        assert_eq!(renames.limit, 1000);
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_warn_on_out_of_range_rename_threshold() {
+        let outcome = parse_config(
+            r#"[renames]
+threshold = 150
+enabled = "no"
+limit = 10
+"#,
+        );
+        let renames = outcome
+            .config
+            .as_ref()
+            .and_then(|cfg| cfg.renames.clone())
+            .expect("limit override should still apply");
+        assert_eq!(renames.threshold, 50);
+        assert!(renames.enabled);
+        assert_eq!(renames.limit, 10);
+        assert_eq!(outcome.warnings.len(), 2);
+        assert!(
+            outcome
+                .warnings
+                .iter()
+                .any(|w| w.contains("renames.threshold"))
+        );
+        assert!(
+            outcome
+                .warnings
+                .iter()
+                .any(|w| w.contains("renames.enabled"))
+        );
+    }
+
+    // notify
+
+    #[test]
+    fn should_parse_notify_section_and_warn_on_bad_methods() {
+        let outcome = parse_config(
+            r#"[notify]
+diff_load = "bell"
+push = "osc"
+export = "beep"
+min_seconds = 0
+"#,
+        );
+        let notify = outcome
+            .config
+            .as_ref()
+            .and_then(|cfg| cfg.notify.clone())
+            .expect("notify section should parse");
+        assert_eq!(notify.diff_load, NotifyMethod::Bell);
+        assert_eq!(notify.push, NotifyMethod::Osc);
+        assert_eq!(notify.export, NotifyMethod::Off);
+        assert_eq!(notify.min_seconds, 0);
+        assert!(outcome.warnings.iter().any(|w| w.contains("notify.export")));
+    }
+
+    // templates
+
+    #[test]
+    fn should_parse_template_sections() {
+        let outcome = parse_config(
+            r#"[templates.security]
+checklist = ["Inputs are validated", "No secrets in logs"]
+path = "src/auth"
+export_legend = false
+export_format = "sarif"
+
+[templates.release]
+checklist = ["CHANGELOG updated"]
+"#,
+        );
+        let templates = &outcome.config.as_ref().expect("config").templates;
+        assert_eq!(templates.len(), 2);
+        let security = &templates["security"];
+        assert_eq!(
+            security.checklist,
+            vec!["Inputs are validated", "No secrets in logs"]
+        );
+        assert_eq!(security.path.as_deref(), Some("src/auth"));
+        assert_eq!(security.export_legend, Some(false));
+        assert_eq!(security.export_format.as_deref(), Some("sarif"));
+        assert_eq!(templates["release"].path, None);
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn should_warn_on_invalid_template_fields() {
+        let outcome = parse_config(
+            r#"[templates.security]
+checklist = ["ok", 3, ""]
+path = 42
+export_format = "pdf"
+scanners = ["semgrep"]
+"#,
+        );
+        let security = &outcome.config.as_ref().expect("config").templates["security"];
+        assert_eq!(security.checklist, vec!["ok"]);
+        assert_eq!(security.path, None);
+        assert_eq!(security.export_format, None);
+        assert_eq!(outcome.warnings.len(), 5);
+        assert!(outcome.warnings.contains(
+            &"Warning: Unknown config key 'templates.security.scanners', ignoring".to_string()
+        ));
+    }
+
+    // filetypes
+
+    #[test]
+    fn should_parse_filetype_sections() {
+        let outcome = parse_config(
+            r#"[filetypes.md]
+wrap = true
+syntax = "Markdown"
+
+[filetypes."*.go"]
+tab_width = 8
+
+[filetypes."Cargo.lock"]
+collapse = true
+tab_width = 0
+indent = 2
+"#,
+        );
+        let filetypes = &outcome.config.as_ref().expect("config").filetypes;
+        assert_eq!(
+            filetypes["md"],
+            FiletypeConfig {
+                wrap: Some(true),
+                syntax: Some("Markdown".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(filetypes["go"].tab_width, Some(8));
+        assert_eq!(filetypes["Cargo.lock"].collapse, Some(true));
+        assert_eq!(filetypes["Cargo.lock"].tab_width, None);
+        assert_eq!(outcome.warnings.len(), 2);
+        assert!(outcome.warnings.contains(
+            &"Warning: Unknown config key 'filetypes.Cargo.lock.indent', ignoring".to_string()
+        ));
+    }
+
+    #[test]
+    fn should_find_template_by_name_or_list_available() {
+        let outcome = parse_config("[templates.security]\n[templates.release]\n");
+        let config = outcome.config.as_ref();
+
+        assert!(find_template(config, "security").is_ok());
+        let err = find_template(config, "perf").unwrap_err();
+        assert_eq!(err, "Unknown template 'perf'. Available: release, security");
+        assert!(find_template(None, "security").is_err());
+    }
+
+    #[test]
+    fn should_warn_when_template_is_not_a_table() {
+        let outcome = parse_config("[templates]\nsecurity = true\n");
+        assert!(
+            outcome
+                .config
+                .as_ref()
+                .expect("config")
+                .templates
+                .is_empty()
+        );
+        assert_eq!(
+            outcome.warnings,
+            vec![
+                "Warning: Config key 'templates.security' must be a table; ignoring value"
+                    .to_string()
+            ]
+        );
+    }
+
     #[test]
     fn should_warn_and_ignore_invalid_comment_type_color() {
         let outcome = parse_config(
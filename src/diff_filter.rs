@@ -0,0 +1,253 @@
+//! `--pipe-to <cmd>`: color the diff with an external renderer such as
+//! delta or diff-so-fancy instead of the built-in syntax highlighting.
+//!
+//! Each file's diff is written to the command as a plain unified diff and
+//! its ANSI-colored output is read back line by line into the lines'
+//! `highlighted_spans`. The diff keeps its own layout, so comments, cursor
+//! and line numbers work as usual; that needs a filter that keeps one
+//! output line per input line, the same contract as git's
+//! `interactive.diffFilter` (`delta --color-only`, `diff-so-fancy --patch`).
+//! Lines whose text doesn't match keep the built-in highlighting.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use ratatui::style::Style;
+
+use crate::model::{DiffFile, LineOrigin};
+use crate::process::{CommandOutputErrorKind, run_command_output_with_stdin, split_command_args};
+
+type Spans = Vec<(Style, String)>;
+
+pub struct DiffFilter {
+    program: String,
+    args: Vec<String>,
+    /// Colored hunk lines of each file by path and content hash, `None`
+    /// when the command's output couldn't be mapped back onto the diff
+    cache: HashMap<(PathBuf, u64), Option<Vec<Option<Spans>>>>,
+    /// Set once the command can't be run at all, so it isn't retried for
+    /// every file
+    broken: bool,
+}
+
+impl DiffFilter {
+    /// Filter for a command line such as `delta --color-only`; `None` when
+    /// it is empty or leaves a quote open.
+    pub fn parse(command: &str) -> Option<Self> {
+        let mut args = split_command_args(command)?.into_iter();
+        let program = args.next()?;
+        Some(Self {
+            program,
+            args: args.collect(),
+            cache: HashMap::new(),
+            broken: false,
+        })
+    }
+
+    /// Color the hunk lines of `files` with the command's output, running
+    /// it (in `root`) once per file not seen before. Returns a warning for
+    /// the first file that newly failed.
+    pub fn apply(&mut self, root: &Path, files: &mut [DiffFile]) -> Option<String> {
+        let mut warning = None;
+        for file in files
+            .iter_mut()
+            .filter(|f| !f.is_binary && !f.is_commit_message && !f.hunks.is_empty())
+        {
+            let key = (file.display_path().clone(), file.content_hash);
+            if !self.cache.contains_key(&key) {
+                if self.broken {
+                    continue;
+                }
+                let colored = match self.run(root, file) {
+                    Ok(output) => {
+                        let colored = map_output(file, &output);
+                        if colored.is_none() {
+                            warning.get_or_insert_with(|| {
+                                format!(
+                                    "--pipe-to: {} changed the diff's layout; use a line-preserving \
+                                     filter such as `delta --color-only`",
+                                    self.program
+                                )
+                            });
+                        }
+                        colored
+                    }
+                    Err(e) => {
+                        warning.get_or_insert(e);
+                        None
+                    }
+                };
+                self.cache.insert(key.clone(), colored);
+            }
+            let Some(Some(colored)) = self.cache.get(&key) else {
+                continue;
+            };
+            let lines = file.hunks.iter_mut().flat_map(|hunk| &mut hunk.lines);
+            for (line, spans) in lines.zip(colored) {
+                if let Some(spans) = spans {
+                    line.highlighted_spans = Some(spans.clone());
+                }
+            }
+        }
+        warning
+    }
+
+    fn run(&mut self, root: &Path, file: &DiffFile) -> Result<String, String> {
+        run_command_output_with_stdin(&self.program, Some(root), &self.args, &unified_diff(file))
+            .map_err(|e| {
+                if matches!(
+                    e.kind,
+                    CommandOutputErrorKind::NotFound | CommandOutputErrorKind::SpawnFailed
+                ) {
+                    self.broken = true;
+                }
+                let detail = e.stderr.lines().next().unwrap_or_default().to_string();
+                format!("--pipe-to: {} failed: {detail}", self.program)
+            })
+    }
+}
+
+/// `file` as a plain unified diff: headers, then each hunk
+fn unified_diff(file: &DiffFile) -> String {
+    let path = file.display_path().display().to_string();
+    let old = file
+        .old_path
+        .as_ref()
+        .map_or_else(|| "/dev/null".to_string(), |p| format!("a/{}", p.display()));
+    let new = file
+        .new_path
+        .as_ref()
+        .map_or_else(|| "/dev/null".to_string(), |p| format!("b/{}", p.display()));
+    let mut diff = String::new();
+    let _ = writeln!(diff, "diff --git a/{path} b/{path}");
+    let _ = writeln!(diff, "--- {old}");
+    let _ = writeln!(diff, "+++ {new}");
+    for hunk in &file.hunks {
+        let _ = writeln!(diff, "{}", hunk.header_line());
+        for line in &hunk.lines {
+            let _ = writeln!(diff, "{}{}", marker(line.origin), line.content);
+        }
+    }
+    diff
+}
+
+const HEADER_LINES: usize = 3;
+
+fn marker(origin: LineOrigin) -> char {
+    match origin {
+        LineOrigin::Addition => '+',
+        LineOrigin::Deletion => '-',
+        LineOrigin::Context => ' ',
+    }
+}
+
+/// Spans for each hunk line of `file` from the filter's `output`, or `None`
+/// when the output has a different number of lines than the diff.
+fn map_output(file: &DiffFile, output: &str) -> Option<Vec<Option<Spans>>> {
+    let output: Vec<&str> = output.lines().collect();
+    let expected = HEADER_LINES + file.hunks.iter().map(|h| 1 + h.lines.len()).sum::<usize>();
+    if output.len() != expected {
+        return None;
+    }
+
+    let mut colored = Vec::new();
+    let mut idx = HEADER_LINES;
+    for hunk in &file.hunks {
+        idx += 1;
+        for line in &hunk.lines {
+            colored.push(line_spans(output[idx], marker(line.origin), &line.content));
+            idx += 1;
+        }
+    }
+    Some(colored)
+}
+
+/// The spans of one output line with its diff marker removed, if its text
+/// is still `content`.
+fn line_spans(output: &str, marker: char, content: &str) -> Option<Spans> {
+    let mut spans = crate::ansi::parse_line(output);
+    if let Some((_, text)) = spans.iter_mut().find(|(_, text)| !text.is_empty())
+        && text.starts_with(marker)
+    {
+        text.remove(0);
+    }
+    spans.retain(|(_, text)| !text.is_empty());
+    let text: String = spans.iter().map(|(_, text)| text.as_str()).collect();
+    (text.trim_end() == content.trim_end()).then_some(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiffHunk, DiffLine, FileModes, FileSizes, FileStatus};
+    use ratatui::style::Color;
+
+    fn line(origin: LineOrigin, content: &str) -> DiffLine {
+        DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: None,
+            new_lineno: None,
+            highlighted_spans: None,
+        }
+    }
+
+    fn file() -> DiffFile {
+        DiffFile {
+            old_path: Some(PathBuf::from("src/lib.rs")),
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![DiffHunk {
+                header: "@@ -1,2 +1,2 @@".to_string(),
+                lines: vec![
+                    line(LineOrigin::Context, "fn main() {"),
+                    line(LineOrigin::Deletion, "    old();"),
+                    line(LineOrigin::Addition, "    new();"),
+                ],
+                old_start: 1,
+                old_count: 2,
+                new_start: 1,
+                new_count: 2,
+            }],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    #[test]
+    fn should_map_filter_output_back_onto_hunk_lines() {
+        // given
+        let file = file();
+        let input = unified_diff(&file);
+        let output = input
+            .lines()
+            .map(|l| match l.strip_prefix('+') {
+                Some(rest) if !l.starts_with("+++") => format!("\x1b[32m+{rest}\x1b[0m"),
+                _ => l.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // when
+        let colored = map_output(&file, &output).unwrap();
+
+        // then
+        assert_eq!(
+            colored[0],
+            Some(vec![(Style::default(), "fn main() {".to_string())])
+        );
+        assert_eq!(
+            colored[2],
+            Some(vec![(
+                Style::default().fg(Color::Green),
+                "    new();".to_string()
+            )])
+        );
+        assert!(map_output(&file, "only\nthree\nlines").is_none());
+    }
+}
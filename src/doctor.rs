@@ -0,0 +1,368 @@
+//! `tuicr doctor`: check what tuicr relies on — the VCS and forge command
+//! line tools, the terminal, the config file, the clipboard and the session
+//! directory — and print a fix next to anything that is missing or broken.
+
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use crate::config::{ConfigLoadOutcome, config_path, load_config};
+use crate::process::{CommandOutputErrorKind, run_command_output_with_timeout};
+
+/// How long a `<tool> --version` may take before it counts as broken
+const TOOL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A glyph terminals disagree on the width of (East Asian "ambiguous"),
+/// as drawn in tuicr's file list and status bar
+const AMBIGUOUS_GLYPH: &str = "✓";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    /// Optional and not set up; only matters for some workflows
+    Skipped,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "ok  ",
+            Status::Skipped => "-   ",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Check {
+    status: Status,
+    name: &'static str,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn new(status: Status, name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            name,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// `<program> --version`, or why it can't be run. `needed_for` names what
+/// the tool is used for when it is missing.
+fn tool(program: &'static str, needed_for: &str, install: &str) -> Check {
+    match run_command_output_with_timeout(program, None, ["--version"], Some(TOOL_TIMEOUT)) {
+        Ok(output) => {
+            let version = output.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+            Check::new(Status::Ok, program, version.trim())
+        }
+        Err(e) if e.kind == CommandOutputErrorKind::NotFound => Check::new(
+            Status::Skipped,
+            program,
+            format!("not installed ({needed_for})"),
+        )
+        .fix(install.to_string()),
+        Err(e) => Check::new(
+            Status::Warn,
+            program,
+            format!("`{program} --version` failed: {}", e.stderr.trim()),
+        )
+        .fix(format!(
+            "check that `{program}` on PATH is a working install"
+        )),
+    }
+}
+
+fn tools() -> Vec<Check> {
+    vec![
+        tool(
+            "git",
+            "sparse checkouts, hooks and :git; the rest uses libgit2",
+            "install git from https://git-scm.com",
+        ),
+        tool(
+            "jj",
+            "only needed in jj repositories",
+            "install jj from https://jj-vcs.github.io/jj",
+        ),
+        tool(
+            "hg",
+            "only needed in Mercurial repositories",
+            "install Mercurial from https://www.mercurial-scm.org",
+        ),
+        tool(
+            "gh",
+            "only needed for `tuicr pr` and :submit",
+            "install the GitHub CLI from https://cli.github.com and run `gh auth login`",
+        ),
+        tool(
+            "glab",
+            "only needed for :gitlab",
+            "install the GitLab CLI from https://gitlab.com/gitlab-org/cli and run `glab auth login`",
+        ),
+    ]
+}
+
+/// The checks that only read environment variables, kept apart from the
+/// terminal probe so they can be tested.
+fn environment_checks(var: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let term = var("TERM").unwrap_or_default();
+    checks.push(if term.is_empty() || term == "dumb" {
+        Check::new(Status::Warn, "terminal", format!("TERM is {term:?}"))
+            .fix("run tuicr in a terminal emulator, or set TERM (e.g. xterm-256color)")
+    } else {
+        Check::new(Status::Ok, "terminal", format!("TERM={term}"))
+    });
+
+    let colorterm = var("COLORTERM").unwrap_or_default();
+    checks.push(if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+        Check::new(Status::Ok, "truecolor", format!("COLORTERM={colorterm}"))
+    } else {
+        Check::new(
+            Status::Warn,
+            "truecolor",
+            "COLORTERM doesn't advertise 24-bit color; themes may look washed out",
+        )
+        .fix("export COLORTERM=truecolor if your terminal supports it")
+    });
+
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|v| !v.is_empty()))
+        .unwrap_or_default();
+    let utf8 = locale
+        .to_ascii_lowercase()
+        .replace('-', "")
+        .contains("utf8");
+    checks.push(if utf8 {
+        Check::new(Status::Ok, "unicode", format!("locale {locale}"))
+    } else {
+        Check::new(
+            Status::Warn,
+            "unicode",
+            format!("locale {locale:?} is not UTF-8; box drawing and icons may break"),
+        )
+        .fix("export LANG=en_US.UTF-8 (or another UTF-8 locale)")
+    });
+
+    checks
+}
+
+/// Print an ambiguous-width glyph and ask the terminal where the cursor
+/// ended up. Two columns means the terminal draws such glyphs wide, which
+/// shifts every row that contains one.
+fn glyph_width() -> Option<Check> {
+    use crossterm::{cursor, terminal};
+
+    let mut stdout = std::io::stdout();
+    if !stdout.is_terminal() || !std::io::stdin().is_terminal() {
+        return None;
+    }
+    terminal::enable_raw_mode().ok()?;
+    let width = write!(stdout, "\r{AMBIGUOUS_GLYPH}")
+        .and_then(|_| stdout.flush())
+        .and_then(|_| cursor::position());
+    let _ = write!(stdout, "\r\x1b[2K");
+    let _ = stdout.flush();
+    let _ = terminal::disable_raw_mode();
+    let (column, _) = width.ok()?;
+    Some(if column <= 1 {
+        Check::new(
+            Status::Ok,
+            "glyph width",
+            "ambiguous-width glyphs take one column",
+        )
+    } else {
+        Check::new(
+            Status::Warn,
+            "glyph width",
+            format!("{AMBIGUOUS_GLYPH} takes {column} columns; rows with icons will misalign"),
+        )
+        .fix("turn off \"treat ambiguous-width characters as double width\" in the terminal")
+    })
+}
+
+fn config_check(outcome: Result<ConfigLoadOutcome, String>, path: &str) -> Vec<Check> {
+    match outcome {
+        Err(e) => vec![
+            Check::new(Status::Fail, "config", format!("{path}: {e}"))
+                .fix("fix the TOML syntax; see docs/CONFIG.md"),
+        ],
+        Ok(ConfigLoadOutcome { config: None, .. }) => vec![Check::new(
+            Status::Skipped,
+            "config",
+            format!("no {path}, using defaults"),
+        )],
+        Ok(ConfigLoadOutcome { warnings, .. }) if warnings.is_empty() => {
+            vec![Check::new(Status::Ok, "config", path.to_string())]
+        }
+        Ok(ConfigLoadOutcome { warnings, .. }) => warnings
+            .into_iter()
+            .map(|warning| {
+                Check::new(Status::Warn, "config", warning)
+                    .fix(format!("edit {path}; see docs/CONFIG.md"))
+            })
+            .collect(),
+    }
+}
+
+fn clipboard() -> Check {
+    if crate::output::markdown::should_prefer_osc52() {
+        return Check::new(
+            Status::Ok,
+            "clipboard",
+            "copies through the terminal (OSC 52) under tmux, SSH or zellij; \
+             tmux needs `set -g set-clipboard on`",
+        );
+    }
+    match arboard::Clipboard::new() {
+        Ok(_) => Check::new(Status::Ok, "clipboard", "system clipboard available"),
+        Err(e) => Check::new(
+            Status::Warn,
+            "clipboard",
+            format!("no system clipboard ({e}); copying falls back to OSC 52"),
+        )
+        .fix("use a terminal with OSC 52 support, or `--stdout` to print the review"),
+    }
+}
+
+/// Create and remove a file in `dir`, where sessions are saved.
+fn storage_check(dir: std::result::Result<std::path::PathBuf, String>) -> Check {
+    let dir = match dir {
+        Ok(dir) => dir,
+        Err(e) => {
+            return Check::new(Status::Fail, "sessions", e)
+                .fix("set HOME (or XDG_DATA_HOME) so tuicr has a data directory");
+        }
+    };
+    let probe = dir.join(".tuicr-doctor");
+    match std::fs::write(&probe, b"").and_then(|_| std::fs::remove_file(&probe)) {
+        Ok(()) => Check::new(
+            Status::Ok,
+            "sessions",
+            format!("{} is writable", dir.display()),
+        ),
+        Err(e) => Check::new(
+            Status::Fail,
+            "sessions",
+            format!("can't write to {}: {e}", dir.display()),
+        )
+        .fix(format!(
+            "make {} writable by your user; reviews can't be saved until then",
+            dir.display()
+        )),
+    }
+}
+
+/// One line per check, with its fix indented below it, and whether any
+/// check failed.
+fn report(checks: &[Check]) -> (Vec<String>, bool) {
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for check in checks {
+        lines.push(format!(
+            "{}  {:width$}  {}",
+            check.status.label(),
+            check.name,
+            check.detail
+        ));
+        if let Some(fix) = &check.fix
+            && check.status != Status::Ok
+        {
+            lines.push(format!("      {:width$}  fix: {fix}", ""));
+        }
+    }
+    let failed = checks.iter().any(|c| c.status == Status::Fail);
+    (lines, failed)
+}
+
+/// Run every check, print the report and return the process exit code: 0,
+/// or 1 when something tuicr can't work without is broken.
+pub fn run() -> i32 {
+    let mut checks = tools();
+    checks.extend(environment_checks(|name| std::env::var(name).ok()));
+    checks.extend(glyph_width());
+    let path = config_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| crate::config::config_path_hint().to_string());
+    checks.extend(config_check(
+        load_config().map_err(|e| e.to_string()),
+        &path,
+    ));
+    checks.push(clipboard());
+    checks.push(storage_check(
+        crate::persistence::storage::get_reviews_dir().map_err(|e| e.to_string()),
+    ));
+    let (lines, failed) = report(&checks);
+    for line in lines {
+        println!("{line}");
+    }
+    if failed { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_report_fixes_for_what_is_missing() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut checks = environment_checks(|name| match name {
+            "TERM" => Some("xterm-256color".to_string()),
+            "LANG" => Some("C".to_string()),
+            _ => None,
+        });
+        checks.push(storage_check(Ok(dir.path().to_path_buf())));
+        checks.push(storage_check(Ok(dir.path().join("missing"))));
+
+        // when
+        let (lines, failed) = report(&checks);
+
+        // then
+        assert!(failed);
+        assert_eq!(lines[0], "ok    terminal   TERM=xterm-256color");
+        assert!(lines[1].starts_with("warn  truecolor  "));
+        assert_eq!(
+            lines[2],
+            "                 fix: export COLORTERM=truecolor if your terminal supports it"
+        );
+        assert!(lines[3].starts_with("warn  unicode    locale \"C\""));
+        assert!(lines[5].starts_with("ok    sessions   "));
+        assert!(lines[6].starts_with("FAIL  sessions   can't write to "));
+        assert!(lines[7].contains("fix: make "));
+    }
+
+    #[test]
+    fn should_treat_a_missing_config_as_defaults_and_bad_keys_as_warnings() {
+        // when
+        let missing = config_check(Ok(ConfigLoadOutcome::default()), "config.toml");
+        let warned = config_check(
+            Ok(ConfigLoadOutcome {
+                config: Some(Default::default()),
+                warnings: vec!["unknown key `thme`".to_string()],
+            }),
+            "config.toml",
+        );
+        let broken = config_check(Err("expected `=`".to_string()), "config.toml");
+
+        // then
+        assert_eq!(missing[0].status, Status::Skipped);
+        assert_eq!(warned[0].status, Status::Warn);
+        assert_eq!(warned[0].detail, "unknown key `thme`");
+        assert_eq!(broken[0].status, Status::Fail);
+    }
+}
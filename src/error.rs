@@ -34,6 +34,16 @@ pub enum TuicrError {
 
     #[error("Unsupported operation: {0}")]
     UnsupportedOperation(String),
+
+    #[error("Template error: {0}")]
+    Template(String),
+
+    #[error("Session locked: {0}")]
+    SessionLocked(String),
+
+    #[cfg(feature = "sqlite")]
+    #[error("Session database error: {0}")]
+    Database(#[from] rusqlite::Error),
 }
 
 pub type Result<T> = std::result::Result<T, TuicrError>;
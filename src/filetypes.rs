@@ -0,0 +1,119 @@
+//! Per-filetype display overrides from the `[filetypes.<ext>]` config
+//! sections: tab width, soft-wrap, collapsing and the syntax highlighter.
+//!
+//! Tabs are expanded and lines highlighted while diffs are parsed, deep in
+//! the VCS backends, so the rules are installed once at startup (like the
+//! command timeout) for `vcs::tabify` and `SyntaxHighlighter` to consult.
+//! `App` keeps its own copy for wrapping and collapsing.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::FiletypeConfig;
+
+/// Columns a tab expands to when no rule says otherwise
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+static RULES: OnceLock<FiletypeRules> = OnceLock::new();
+
+#[derive(Debug, Clone, Default)]
+pub struct FiletypeRules {
+    by_key: BTreeMap<String, FiletypeConfig>,
+}
+
+impl FiletypeRules {
+    pub fn new(by_key: BTreeMap<String, FiletypeConfig>) -> Self {
+        Self { by_key }
+    }
+
+    /// The rule for `path`: one keyed by its exact file name (`Makefile`,
+    /// `Cargo.lock`, `.gitignore` as `gitignore`), else by its extension,
+    /// compared case-insensitively.
+    fn for_path(&self, path: &Path) -> Option<&FiletypeConfig> {
+        if self.by_key.is_empty() {
+            return None;
+        }
+        let name = path.file_name().and_then(|n| n.to_str())?;
+        self.by_key
+            .get(name)
+            .or_else(|| {
+                name.strip_prefix('.')
+                    .and_then(|dotfile| self.by_key.get(dotfile))
+            })
+            .or_else(|| {
+                let ext = path.extension().and_then(|e| e.to_str())?;
+                self.by_key
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(ext))
+                    .map(|(_, rule)| rule)
+            })
+    }
+
+    pub fn tab_width(&self, path: &Path) -> usize {
+        self.for_path(path)
+            .and_then(|rule| rule.tab_width)
+            .unwrap_or(DEFAULT_TAB_WIDTH)
+    }
+
+    /// Whether lines of `path` soft-wrap, when a rule decides it
+    pub fn wrap(&self, path: &Path) -> Option<bool> {
+        self.for_path(path).and_then(|rule| rule.wrap)
+    }
+
+    pub fn collapsed(&self, path: &Path) -> bool {
+        self.for_path(path)
+            .and_then(|rule| rule.collapse)
+            .unwrap_or(false)
+    }
+
+    /// The syntax named for `path`, if a rule picks one
+    pub fn syntax(&self, path: &Path) -> Option<&str> {
+        self.for_path(path).and_then(|rule| rule.syntax.as_deref())
+    }
+}
+
+/// Install the rules the diff parsers use. Only the first call has effect.
+pub fn install(rules: FiletypeRules) {
+    let _ = RULES.set(rules);
+}
+
+/// The installed rules, or none when no config set any.
+pub fn rules() -> &'static FiletypeRules {
+    RULES.get_or_init(FiletypeRules::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_rules_by_file_name_before_extension() {
+        // given
+        let rule = |tab_width| FiletypeConfig {
+            tab_width: Some(tab_width),
+            ..Default::default()
+        };
+        let rules = FiletypeRules::new(BTreeMap::from([
+            ("go".to_string(), rule(8)),
+            ("Makefile".to_string(), rule(2)),
+            ("gitignore".to_string(), rule(3)),
+            (
+                "lock".to_string(),
+                FiletypeConfig {
+                    collapse: Some(true),
+                    ..Default::default()
+                },
+            ),
+        ]));
+
+        // when / then
+        assert_eq!(rules.tab_width(Path::new("cmd/main.GO")), 8);
+        assert_eq!(rules.tab_width(Path::new("Makefile")), 2);
+        assert_eq!(rules.tab_width(Path::new(".gitignore")), 3);
+        assert_eq!(rules.tab_width(Path::new("src/lib.rs")), DEFAULT_TAB_WIDTH);
+        assert!(rules.collapsed(Path::new("Cargo.lock")));
+        assert!(!rules.collapsed(Path::new("main.go")));
+        assert_eq!(rules.wrap(Path::new("main.go")), None);
+    }
+}
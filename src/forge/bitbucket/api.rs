@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde_json::Value;
+use ureq::Agent;
+
+use crate::config::BitbucketConfig;
+use crate::error::{Result, TuicrError};
+use crate::forge::discussions::{Discussion, PostOutcome};
+
+use super::submit::{BitbucketPullRequest, build_comment_payload};
+
+pub const TOKEN_ENV_VAR: &str = "BITBUCKET_TOKEN";
+
+/// A failed HTTP request: the status when the server answered, and a
+/// message either way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpFailure {
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+pub trait BitbucketTransport {
+    fn post_json(
+        &self,
+        url: &str,
+        authorization: &str,
+        body: &Value,
+    ) -> std::result::Result<(), HttpFailure>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UreqTransport;
+
+impl BitbucketTransport for UreqTransport {
+    fn post_json(
+        &self,
+        url: &str,
+        authorization: &str,
+        body: &Value,
+    ) -> std::result::Result<(), HttpFailure> {
+        let agent: Agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(15)))
+            .build()
+            .into();
+        match agent
+            .post(url)
+            .header("Authorization", authorization)
+            .send_json(body)
+        {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::StatusCode(status)) => Err(HttpFailure {
+                status: Some(status),
+                message: format!("HTTP {status}"),
+            }),
+            Err(e) => Err(HttpFailure {
+                status: None,
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// The token to use: `BITBUCKET_TOKEN` if set, else the `[bitbucket]` one.
+pub fn resolve_token(config: &BitbucketConfig) -> Option<String> {
+    std::env::var(TOKEN_ENV_VAR)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .or_else(|| config.token.clone())
+}
+
+/// `Authorization` header value. `user:app_password` pairs use basic auth,
+/// anything else is sent as a bearer (access or HTTP) token.
+fn authorization_header(token: &str) -> String {
+    if token.contains(':') {
+        format!("Basic {}", BASE64.encode(token))
+    } else {
+        format!("Bearer {token}")
+    }
+}
+
+/// Post `discussions` to `target`, one request each. Fails when there is
+/// nothing to post. Rejected credentials stop the submit, and the outcome
+/// says so next to what already landed; other rejected comments are
+/// reported in the outcome so the rest still land.
+pub fn submit_review(
+    transport: &dyn BitbucketTransport,
+    target: &BitbucketPullRequest,
+    token: &str,
+    discussions: &[Discussion],
+) -> Result<PostOutcome> {
+    if discussions.is_empty() {
+        return Err(TuicrError::NoComments);
+    }
+
+    let url = target.comments_url();
+    let authorization = authorization_header(token);
+    let mut outcome = PostOutcome::default();
+    for discussion in discussions {
+        let payload = build_comment_payload(discussion, target.flavor);
+        match transport.post_json(&url, &authorization, &payload) {
+            Ok(()) => outcome.record_posted(discussion),
+            Err(HttpFailure {
+                status: Some(status @ (401 | 403)),
+                ..
+            }) => {
+                outcome.stopped = Some(format!(
+                    "Bitbucket rejected the credentials (HTTP {status}).\nSet {TOKEN_ENV_VAR} \
+                     to a token with pull request write access."
+                ));
+                break;
+            }
+            Err(failure) => outcome
+                .failures
+                .push(format!("Bitbucket request failed: {}", failure.message)),
+        }
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ForgeConfig;
+    use crate::forge::discussions::build_discussions;
+    use crate::model::{
+        Comment, CommentType, DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus,
+        LineOrigin, LineSide, ReviewSession, SessionDiffSource,
+    };
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
+    struct FakeTransport {
+        requests: RefCell<Vec<(String, String, Value)>>,
+        status: Option<u16>,
+        /// Requests before this one succeed regardless of `status`
+        failing_from: usize,
+    }
+
+    impl BitbucketTransport for FakeTransport {
+        fn post_json(
+            &self,
+            url: &str,
+            authorization: &str,
+            body: &Value,
+        ) -> std::result::Result<(), HttpFailure> {
+            let mut requests = self.requests.borrow_mut();
+            requests.push((url.to_string(), authorization.to_string(), body.clone()));
+            match self.status {
+                _ if requests.len() <= self.failing_from => Ok(()),
+                None => Ok(()),
+                status => Err(HttpFailure {
+                    status,
+                    message: "nope".to_string(),
+                }),
+            }
+        }
+    }
+
+    fn fixture() -> Vec<Discussion> {
+        let hunks = vec![DiffHunk {
+            header: "@@ -0,0 +1,1 @@".to_string(),
+            lines: vec![DiffLine {
+                origin: LineOrigin::Addition,
+                content: "x".to_string(),
+                old_lineno: None,
+                new_lineno: Some(1),
+                highlighted_spans: None,
+            }],
+            old_start: 0,
+            old_count: 0,
+            new_start: 1,
+            new_count: 1,
+        }];
+        let file = DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from("a.rs")),
+            status: FileStatus::Added,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
+        );
+        session.add_file(PathBuf::from("a.rs"), FileStatus::Added, 0);
+        session
+            .get_file_mut(&PathBuf::from("a.rs"))
+            .unwrap()
+            .add_line_comment(
+                1,
+                Comment::new("boom".to_string(), CommentType::Issue, Some(LineSide::New)),
+            );
+        build_discussions(&session, &[file], &ForgeConfig::default())
+    }
+
+    #[test]
+    fn should_post_inline_comments_with_bearer_token() {
+        // given
+        let discussions = fixture();
+        let transport = FakeTransport {
+            requests: RefCell::new(Vec::new()),
+            status: None,
+            failing_from: 0,
+        };
+        let target = BitbucketPullRequest::parse(
+            "https://bitbucket.org/acme/widgets/pull-requests/5",
+            None,
+            None,
+        )
+        .unwrap();
+
+        // when
+        let outcome = submit_review(&transport, &target, "secret", &discussions).unwrap();
+
+        // then
+        assert_eq!(outcome.posted, 1);
+        let requests = transport.requests.borrow();
+        let (url, authorization, body) = &requests[0];
+        assert_eq!(url, &target.comments_url());
+        assert_eq!(authorization, "Bearer secret");
+        assert_eq!(body["inline"]["to"], 1);
+        assert_eq!(body["content"]["raw"], "[ISSUE] boom");
+    }
+
+    #[test]
+    fn should_stop_on_rejected_credentials_and_use_basic_auth_for_app_passwords() {
+        // given
+        let discussions = [fixture(), fixture(), fixture()].concat();
+        let transport = FakeTransport {
+            requests: RefCell::new(Vec::new()),
+            status: Some(401),
+            failing_from: 1,
+        };
+        let target =
+            BitbucketPullRequest::parse("5", Some("git@bitbucket.org:acme/widgets.git"), None)
+                .unwrap();
+
+        // when
+        let outcome = submit_review(&transport, &target, "me:app-pass", &discussions).unwrap();
+
+        // then
+        assert_eq!(outcome.posted, 1);
+        assert_eq!(outcome.posted_comment_ids, discussions[0].comment_ids);
+        let stopped = outcome.stopped.unwrap();
+        assert!(stopped.contains("HTTP 401"));
+        assert!(stopped.contains(TOKEN_ENV_VAR));
+        assert_eq!(transport.requests.borrow().len(), 2);
+        assert_eq!(
+            transport.requests.borrow()[0].1,
+            format!("Basic {}", BASE64.encode("me:app-pass"))
+        );
+    }
+}
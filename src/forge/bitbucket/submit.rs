@@ -0,0 +1,278 @@
+//! Bitbucket pull request targets and comment payloads, for both Bitbucket
+//! Cloud (`api.bitbucket.org/2.0`) and Data Center (`/rest/api/1.0`).
+
+use serde_json::{Value, json};
+
+use crate::forge::discussions::Discussion;
+use crate::forge::github::gh::split_remote_url;
+
+const CLOUD_HOST: &str = "bitbucket.org";
+const CLOUD_API: &str = "https://api.bitbucket.org/2.0";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitbucketFlavor {
+    Cloud,
+    DataCenter,
+}
+
+/// A pull request on Bitbucket Cloud or a Data Center server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitbucketPullRequest {
+    pub flavor: BitbucketFlavor,
+    /// REST API root, without a trailing slash
+    pub api_base: String,
+    /// Workspace on Cloud, project key on Data Center
+    pub owner: String,
+    pub repo: String,
+    pub id: u64,
+}
+
+impl BitbucketPullRequest {
+    /// Parse `:bitbucket <target>`: a pull request URL, or an id (`12` or
+    /// `#12`) on the repository behind `remote_url`. `base_url` overrides
+    /// the API root derived from the target.
+    pub fn parse(target: &str, remote_url: Option<&str>, base_url: Option<&str>) -> Option<Self> {
+        let target = target.trim();
+        let (flavor, server, owner, repo, id) = if let Some(rest) = target
+            .strip_prefix("https://")
+            .or_else(|| target.strip_prefix("http://"))
+        {
+            let scheme = &target[..target.len() - rest.len()];
+            let (host, path) = rest.split_once('/')?;
+            let parts: Vec<_> = path.split(['/', '?', '#']).collect();
+            if host == CLOUD_HOST {
+                // bitbucket.org/<workspace>/<repo>/pull-requests/<id>
+                match parts.as_slice() {
+                    [owner, repo, "pull-requests", id, ..] => (
+                        BitbucketFlavor::Cloud,
+                        None,
+                        owner.to_string(),
+                        repo.to_string(),
+                        id.parse().ok()?,
+                    ),
+                    _ => return None,
+                }
+            } else {
+                // <server>/projects/<KEY>/repos/<slug>/pull-requests/<id>,
+                // where the server may carry a context path.
+                let at = parts.iter().position(|part| *part == "projects")?;
+                match &parts[at..] {
+                    ["projects", key, "repos", slug, "pull-requests", id, ..] => (
+                        BitbucketFlavor::DataCenter,
+                        Some(format!("{scheme}{host}{}", context_path(&parts[..at]))),
+                        key.to_string(),
+                        slug.to_string(),
+                        id.parse().ok()?,
+                    ),
+                    _ => return None,
+                }
+            }
+        } else {
+            let id = target.strip_prefix('#').unwrap_or(target).parse().ok()?;
+            let (host, path) = split_remote_url(remote_url?)?;
+            let host = host.split(':').next().unwrap_or(&host).to_string();
+            let path = path.trim_matches('/');
+            let path = path.strip_suffix(".git").unwrap_or(path);
+            // Data Center https remotes live under /scm/.
+            let path = path.strip_prefix("scm/").unwrap_or(path);
+            let (owner, repo) = path.rsplit_once('/')?;
+            let owner = owner.rsplit('/').next().unwrap_or(owner);
+            if host == CLOUD_HOST {
+                (
+                    BitbucketFlavor::Cloud,
+                    None,
+                    owner.to_string(),
+                    repo.to_string(),
+                    id,
+                )
+            } else {
+                (
+                    BitbucketFlavor::DataCenter,
+                    Some(format!("https://{host}")),
+                    owner.to_string(),
+                    repo.to_string(),
+                    id,
+                )
+            }
+        };
+        if id == 0 || owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+        let api_base = match (base_url, flavor, server) {
+            (Some(base), _, _) => base.trim_end_matches('/').to_string(),
+            (None, BitbucketFlavor::Cloud, _) => CLOUD_API.to_string(),
+            (None, BitbucketFlavor::DataCenter, server) => {
+                format!("{}/rest/api/1.0", server.unwrap_or_default())
+            }
+        };
+        Some(Self {
+            flavor,
+            api_base,
+            owner,
+            repo,
+            id,
+        })
+    }
+
+    pub fn display_name(&self) -> String {
+        format!("{}/{}#{}", self.owner, self.repo, self.id)
+    }
+
+    /// Endpoint that creates one comment per POST
+    pub fn comments_url(&self) -> String {
+        match self.flavor {
+            BitbucketFlavor::Cloud => format!(
+                "{}/repositories/{}/{}/pullrequests/{}/comments",
+                self.api_base, self.owner, self.repo, self.id
+            ),
+            BitbucketFlavor::DataCenter => format!(
+                "{}/projects/{}/repos/{}/pull-requests/{}/comments",
+                self.api_base, self.owner, self.repo, self.id
+            ),
+        }
+    }
+}
+
+fn context_path(parts: &[&str]) -> String {
+    parts
+        .iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| format!("/{part}"))
+        .collect()
+}
+
+/// JSON body for one comment. Cloud anchors with `inline.to` (new line) or
+/// `inline.from` (removed line); Data Center with an `anchor` naming the line
+/// type and which side of the diff it is on.
+pub fn build_comment_payload(discussion: &Discussion, flavor: BitbucketFlavor) -> Value {
+    let body = discussion.body.as_str();
+    let Some(position) = &discussion.position else {
+        return match flavor {
+            BitbucketFlavor::Cloud => json!({ "content": { "raw": body } }),
+            BitbucketFlavor::DataCenter => json!({ "text": body }),
+        };
+    };
+    let path = position.new_path.to_string_lossy().replace('\\', "/");
+    match flavor {
+        BitbucketFlavor::Cloud => {
+            let inline = match position.new_line {
+                Some(line) => json!({ "path": path, "to": line }),
+                None => json!({ "path": path, "from": position.old_line }),
+            };
+            json!({ "content": { "raw": body }, "inline": inline })
+        }
+        BitbucketFlavor::DataCenter => {
+            let (line, line_type, file_type) = match (position.old_line, position.new_line) {
+                (Some(_), Some(new)) => (new, "CONTEXT", "TO"),
+                (None, Some(new)) => (new, "ADDED", "TO"),
+                (old, None) => (old.unwrap_or_default(), "REMOVED", "FROM"),
+            };
+            let mut anchor = json!({
+                "path": path,
+                "line": line,
+                "lineType": line_type,
+                "fileType": file_type,
+                "diffType": "EFFECTIVE",
+            });
+            if position.old_path != position.new_path {
+                anchor["srcPath"] = Value::from(position.old_path.to_string_lossy().to_string());
+            }
+            json!({ "text": body, "anchor": anchor })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::position::DiffPosition;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_parse_cloud_and_data_center_targets() {
+        // when
+        let cloud = BitbucketPullRequest::parse(
+            "https://bitbucket.org/acme/widgets/pull-requests/5/diff",
+            None,
+            None,
+        )
+        .unwrap();
+        let server = BitbucketPullRequest::parse(
+            "https://git.acme.io/bitbucket/projects/WID/repos/widgets/pull-requests/9/overview",
+            None,
+            None,
+        )
+        .unwrap();
+        let from_remote = BitbucketPullRequest::parse(
+            "#3",
+            Some("https://git.acme.io/scm/wid/widgets.git"),
+            Some("https://git.acme.io/rest/api/1.0/"),
+        )
+        .unwrap();
+
+        // then
+        assert_eq!(cloud.flavor, BitbucketFlavor::Cloud);
+        assert_eq!(
+            cloud.comments_url(),
+            "https://api.bitbucket.org/2.0/repositories/acme/widgets/pullrequests/5/comments"
+        );
+        assert_eq!(server.flavor, BitbucketFlavor::DataCenter);
+        assert_eq!(
+            server.comments_url(),
+            "https://git.acme.io/bitbucket/rest/api/1.0/projects/WID/repos/widgets/pull-requests/9/comments"
+        );
+        assert_eq!(
+            from_remote.comments_url(),
+            "https://git.acme.io/rest/api/1.0/projects/wid/repos/widgets/pull-requests/3/comments"
+        );
+        assert_eq!(
+            BitbucketPullRequest::parse("3", Some("git@bitbucket.org:acme/widgets.git"), None)
+                .map(|pr| pr.display_name()),
+            Some("acme/widgets#3".to_string())
+        );
+        assert_eq!(BitbucketPullRequest::parse("3", None, None), None);
+    }
+
+    #[test]
+    fn should_anchor_comments_by_side_for_each_flavor() {
+        // given
+        let removed = Discussion {
+            body: "[ISSUE] why?".to_string(),
+            position: Some(DiffPosition {
+                old_path: PathBuf::from("src/old.rs"),
+                new_path: PathBuf::from("src/new.rs"),
+                old_line: Some(4),
+                new_line: None,
+            }),
+            comment_ids: Vec::new(),
+        };
+        let context = Discussion {
+            body: "ok".to_string(),
+            position: Some(DiffPosition {
+                old_path: PathBuf::from("a.rs"),
+                new_path: PathBuf::from("a.rs"),
+                old_line: Some(2),
+                new_line: Some(3),
+            }),
+            comment_ids: Vec::new(),
+        };
+
+        // when
+        let cloud = build_comment_payload(&removed, BitbucketFlavor::Cloud);
+        let server = build_comment_payload(&removed, BitbucketFlavor::DataCenter);
+        let server_context = build_comment_payload(&context, BitbucketFlavor::DataCenter);
+
+        // then
+        assert_eq!(cloud["content"]["raw"], "[ISSUE] why?");
+        assert_eq!(cloud["inline"]["path"], "src/new.rs");
+        assert_eq!(cloud["inline"]["from"], 4);
+        assert!(cloud["inline"].get("to").is_none());
+        assert_eq!(server["text"], "[ISSUE] why?");
+        assert_eq!(server["anchor"]["lineType"], "REMOVED");
+        assert_eq!(server["anchor"]["fileType"], "FROM");
+        assert_eq!(server["anchor"]["srcPath"], "src/old.rs");
+        assert_eq!(server_context["anchor"]["line"], 3);
+        assert_eq!(server_context["anchor"]["lineType"], "CONTEXT");
+        assert!(server_context["anchor"].get("srcPath").is_none());
+    }
+}
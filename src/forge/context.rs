@@ -32,8 +32,15 @@ pub trait ContextProvider {
         start_line: u32,
         end_line: u32,
     ) -> Result<Vec<DiffLine>>;
+
+    /// Every line a deleted file had before it was removed.
+    fn fetch_deleted_file(&self, old_path: &Path) -> Result<Vec<String>>;
 }
 
+/// Lines requested from a forge for a deleted file's full content; forge
+/// backends fetch the blob whole and slice it, so this only bounds the slice.
+const MAX_DELETED_FILE_LINES: u32 = 100_000;
+
 /// Adapter over a `VcsBackend`. Picks the appropriate display path (new on
 /// the head side, old on the base side) and forwards to the backend.
 pub struct VcsContextProvider<'a> {
@@ -59,6 +66,11 @@ impl ContextProvider for VcsContextProvider<'_> {
         self.vcs
             .fetch_context_lines(path, file_status, start_line, end_line)
     }
+
+    fn fetch_deleted_file(&self, old_path: &Path) -> Result<Vec<String>> {
+        let content = self.vcs.fetch_deleted_file(old_path)?;
+        Ok(content.lines().map(str::to_string).collect())
+    }
 }
 
 /// Adapter over a `ForgeBackend` for PR review mode.
@@ -113,6 +125,18 @@ impl ContextProvider for ForgeContextProvider<'_> {
         };
         self.forge.fetch_file_lines(request)
     }
+
+    fn fetch_deleted_file(&self, old_path: &Path) -> Result<Vec<String>> {
+        let old_path = old_path.to_path_buf();
+        let lines = self.fetch_context_lines(
+            Some(&old_path),
+            None,
+            FileStatus::Deleted,
+            1,
+            MAX_DELETED_FILE_LINES,
+        )?;
+        Ok(lines.into_iter().map(|line| line.content).collect())
+    }
 }
 
 #[cfg(test)]
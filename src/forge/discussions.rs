@@ -0,0 +1,219 @@
+//! Turning a local review into positioned discussions for forges whose APIs
+//! take one comment per request (GitLab, Bitbucket).
+//!
+//! Line comments go through the same `map_comment` rules as GitHub submit and
+//! become positioned discussions; anything that cannot be anchored, plus the
+//! review-level comments, is posted as a general (unpositioned) discussion.
+
+use crate::config::ForgeConfig;
+use crate::forge::position::{DiffPosition, position_of};
+use crate::forge::submit::{
+    CommentAnchor, GhSide, InlineComment, MappedComment, build_review_body, map_comment,
+};
+use crate::model::{Comment, DiffFile, LineSide, ReviewSession};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discussion {
+    pub body: String,
+    /// `None` for a general discussion on the change
+    pub position: Option<DiffPosition>,
+    /// Ids of the local comments the discussion carries
+    pub comment_ids: Vec<String>,
+}
+
+/// What happened to each discussion of a submit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostOutcome {
+    pub posted: usize,
+    /// Ids of the local comments that landed
+    pub posted_comment_ids: Vec<String>,
+    /// One message per discussion the forge rejected
+    pub failures: Vec<String>,
+    /// Why posting gave up before the last discussion, leaving the rest
+    /// unsent
+    pub stopped: Option<String>,
+}
+
+impl PostOutcome {
+    pub fn record_posted(&mut self, discussion: &Discussion) {
+        self.posted += 1;
+        self.posted_comment_ids
+            .extend(discussion.comment_ids.iter().cloned());
+    }
+}
+
+/// Discussions for every comment in `session` that has not been posted yet,
+/// review-level summary first.
+pub fn build_discussions(
+    session: &ReviewSession,
+    diff_files: &[DiffFile],
+    config: &ForgeConfig,
+) -> Vec<Discussion> {
+    let mut discussions = Vec::new();
+    let review_comments: Vec<Comment> = session
+        .review_comments
+        .iter()
+        .filter(|c| !c.is_locked())
+        .cloned()
+        .collect();
+    if !review_comments.is_empty() {
+        discussions.push(Discussion {
+            body: build_review_body(&review_comments, &[], config),
+            position: None,
+            comment_ids: review_comments.iter().map(|c| c.id.clone()).collect(),
+        });
+    }
+
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+    for (path, review) in files {
+        let mut anchored: Vec<(&Comment, CommentAnchor)> = review
+            .file_comments
+            .iter()
+            .filter(|c| !c.is_locked())
+            .map(|c| (c, CommentAnchor::FileLevel))
+            .collect();
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, comments) in line_comments {
+            anchored.extend(comments.iter().filter(|c| !c.is_locked()).map(|c| {
+                let anchor = if c.line_range.is_some_and(|r| !r.is_single()) {
+                    CommentAnchor::Range
+                } else {
+                    CommentAnchor::Line {
+                        line: *line,
+                        side: c.side.unwrap_or(LineSide::New),
+                    }
+                };
+                (c, anchor)
+            }));
+        }
+
+        let Some(file) = diff_files.iter().find(|f| f.display_path() == path) else {
+            continue;
+        };
+        for (comment, anchor) in anchored {
+            let comment_ids = vec![comment.id.clone()];
+            discussions.push(match map_comment(comment, anchor, file, config) {
+                MappedComment::Inline(inline) => Discussion {
+                    position: Some(position_for(file, &inline)),
+                    body: inline.body,
+                    comment_ids,
+                },
+                MappedComment::Unmappable { comment, file, .. } => {
+                    let prefix = if config.comment_type_prefix {
+                        format!("[{}] ", comment.comment_type.as_str())
+                    } else {
+                        String::new()
+                    };
+                    Discussion {
+                        body: format!("{prefix}`{}`: {}", file.display(), comment.content),
+                        position: None,
+                        comment_ids,
+                    }
+                }
+            });
+        }
+    }
+    discussions
+}
+
+fn position_for(file: &DiffFile, inline: &InlineComment) -> DiffPosition {
+    let side = match inline.side {
+        GhSide::Left => LineSide::Old,
+        GhSide::Right => LineSide::New,
+    };
+    position_of(file, inline.line, side)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        CommentType, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin,
+        SessionDiffSource,
+    };
+    use std::path::PathBuf;
+
+    fn diff_file() -> DiffFile {
+        let line = |origin, old, new| DiffLine {
+            origin,
+            content: String::new(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        };
+        let hunks = vec![DiffHunk {
+            header: "@@ -1,2 +1,2 @@".to_string(),
+            lines: vec![
+                line(LineOrigin::Context, Some(1), Some(1)),
+                line(LineOrigin::Deletion, Some(2), None),
+                line(LineOrigin::Addition, None, Some(2)),
+            ],
+            old_start: 1,
+            old_count: 2,
+            new_start: 1,
+            new_count: 2,
+        }];
+        DiffFile {
+            old_path: Some(PathBuf::from("src/old.rs")),
+            new_path: Some(PathBuf::from("src/new.rs")),
+            status: FileStatus::Renamed,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    fn session() -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
+        );
+        session.review_comments.push(Comment::new(
+            "Looks good overall".to_string(),
+            CommentType::Praise,
+            None,
+        ));
+        let path = PathBuf::from("src/new.rs");
+        session.add_file(path.clone(), FileStatus::Renamed, 0);
+        let review = session.get_file_mut(&path).unwrap();
+        let comment =
+            |text: &str, side| Comment::new(text.to_string(), CommentType::Issue, Some(side));
+        review.add_line_comment(1, comment("context", LineSide::New));
+        review.add_line_comment(2, comment("removed", LineSide::Old));
+        review.add_line_comment(9, comment("nowhere", LineSide::New));
+        session
+    }
+
+    #[test]
+    fn should_position_line_comments_and_fall_back_to_general_discussions() {
+        // when
+        let discussions = build_discussions(
+            &session(),
+            &[diff_file()],
+            &ForgeConfig {
+                comment_type_prefix: true,
+                review_footer: false,
+            },
+        );
+
+        // then
+        assert_eq!(discussions.len(), 4);
+        assert_eq!(discussions[0].body, "[PRAISE] Looks good overall");
+        assert_eq!(discussions[0].position, None);
+        let context = discussions[1].position.as_ref().unwrap();
+        assert_eq!((context.old_line, context.new_line), (Some(1), Some(1)));
+        assert_eq!(context.old_path, PathBuf::from("src/old.rs"));
+        let removed = discussions[2].position.as_ref().unwrap();
+        assert_eq!((removed.old_line, removed.new_line), (Some(2), None));
+        assert_eq!(discussions[3].body, "[ISSUE] `src/new.rs`: nowhere");
+        assert_eq!(discussions[3].position, None);
+    }
+}
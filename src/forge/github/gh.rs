@@ -70,12 +70,13 @@ impl From<CommandOutputError> for GhCommandError {
     fn from(error: CommandOutputError) -> Self {
         match error.kind {
             CommandOutputErrorKind::NotFound => Self::MissingGh,
-            CommandOutputErrorKind::SpawnFailed | CommandOutputErrorKind::Unsuccessful => {
-                Self::Failed {
-                    status: error.status,
-                    stderr: error.stderr,
-                }
-            }
+            CommandOutputErrorKind::SpawnFailed
+            | CommandOutputErrorKind::Unsuccessful
+            | CommandOutputErrorKind::TimedOut
+            | CommandOutputErrorKind::Cancelled => Self::Failed {
+                status: error.status,
+                stderr: error.stderr,
+            },
         }
     }
 }
@@ -194,7 +195,7 @@ where
 
 impl<R> ForgeBackend for GitHubGhBackend<R>
 where
-    R: GhCommandRunner,
+    R: GhCommandRunner + Send,
 {
     fn list_pull_requests(&self, query: PullRequestListQuery) -> Result<PagedPullRequests> {
         let page_size = query.page_size.max(1);
@@ -528,14 +529,20 @@ pub fn parse_pull_request_target(input: &str) -> Result<PullRequestTarget> {
 }
 
 pub fn parse_github_remote_url(remote_url: &str) -> Option<ForgeRepository> {
+    let (host, path) = split_remote_url(remote_url)?;
+    repository_from_path(&host, path)
+}
+
+/// Host and repository path of an https, ssh or scp-like git remote URL.
+/// SSH host aliases are resolved through `~/.ssh/config`.
+pub(crate) fn split_remote_url(remote_url: &str) -> Option<(String, &str)> {
     let trimmed = trim_url_suffix(remote_url.trim());
     if trimmed.is_empty() {
         return None;
     }
 
     if let Some((host, path)) = parse_scp_like_remote(trimmed) {
-        let resolved = resolve_ssh_hostname(host);
-        return repository_from_path(&resolved, path);
+        return Some((resolve_ssh_hostname(host), path));
     }
 
     let without_scheme = strip_scheme(trimmed).unwrap_or(trimmed);
@@ -544,7 +551,7 @@ pub fn parse_github_remote_url(remote_url: &str) -> Option<ForgeRepository> {
         .map(|(_, rest)| rest)
         .unwrap_or(without_scheme);
     let (host, path) = without_user.split_once('/')?;
-    repository_from_path(host, path)
+    Some((host.to_string(), path))
 }
 
 fn parse_numeric_target(target: &str) -> Option<PullRequestTarget> {
@@ -713,7 +720,8 @@ fn map_gh_error(error: GhCommandError, host: &str) -> TuicrError {
         ),
         GhCommandError::Failed { stderr, .. } if looks_like_auth_failure(&stderr) => {
             TuicrError::Forge(format!(
-                "GitHub authentication failed.\nRun `gh auth login` for {host}."
+                "GitHub authentication failed.\nRun `gh auth login` for {host}, or set {} to a token.",
+                token_env_var(host)
             ))
         }
         GhCommandError::Failed { stderr, status } => {
@@ -729,6 +737,15 @@ fn map_gh_error(error: GhCommandError, host: &str) -> TuicrError {
     }
 }
 
+/// Environment variable `gh` reads an access token from for `host`
+fn token_env_var(host: &str) -> &'static str {
+    if host == DEFAULT_GITHUB_HOST {
+        "GH_TOKEN"
+    } else {
+        "GH_ENTERPRISE_TOKEN"
+    }
+}
+
 fn looks_like_auth_failure(stderr: &str) -> bool {
     let lower = stderr.to_ascii_lowercase();
     lower.contains("gh auth login")
@@ -1500,6 +1517,7 @@ Match host github-work
         );
         assert!(err.to_string().contains("GitHub authentication failed"));
         assert!(err.to_string().contains("github.example.com"));
+        assert!(err.to_string().contains("GH_ENTERPRISE_TOKEN"));
     }
 
     // create_review tests
@@ -0,0 +1,329 @@
+use std::ffi::OsStr;
+
+use crate::error::{Result, TuicrError};
+use crate::forge::github::gh::split_remote_url;
+use crate::process::{
+    CommandOutputErrorKind, CommandOutputResult, run_command_output, run_command_output_with_stdin,
+};
+
+use crate::forge::discussions::{Discussion, PostOutcome};
+
+use super::submit::{DiffRefs, build_discussion_payload};
+
+/// A merge request on a GitLab instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeRequestTarget {
+    pub host: String,
+    /// Full project path, e.g. `group/subgroup/project`
+    pub project: String,
+    pub iid: u64,
+}
+
+impl MergeRequestTarget {
+    /// Parse `:gitlab <target>`: a merge request URL, or an iid (`12` or
+    /// `!12`) on the project behind `remote_url`.
+    pub fn parse(target: &str, remote_url: Option<&str>) -> Option<Self> {
+        let target = target.trim();
+        if let Some(rest) = target
+            .strip_prefix("https://")
+            .or_else(|| target.strip_prefix("http://"))
+        {
+            let (host, path) = rest.split_once('/')?;
+            let (project, tail) = path.split_once("/-/merge_requests/")?;
+            let iid = tail.split(['/', '?', '#']).next()?.parse().ok()?;
+            return Some(Self {
+                host: host.to_string(),
+                project: project.to_string(),
+                iid,
+            });
+        }
+        let iid = target.strip_prefix('!').unwrap_or(target).parse().ok()?;
+        let (host, path) = split_remote_url(remote_url?)?;
+        // glab wants a bare hostname; ssh remotes may carry a port.
+        let host = host.split(':').next().unwrap_or(&host).to_string();
+        let project = path.trim_matches('/');
+        let project = project.strip_suffix(".git").unwrap_or(project);
+        (!project.is_empty() && iid > 0).then(|| Self {
+            host,
+            project: project.to_string(),
+            iid,
+        })
+    }
+
+    pub fn display_name(&self) -> String {
+        format!("{}!{}", self.project, self.iid)
+    }
+
+    fn api_path(&self) -> String {
+        format!(
+            "projects/{}/merge_requests/{}",
+            self.project.replace('/', "%2F"),
+            self.iid
+        )
+    }
+}
+
+pub trait GlabCommandRunner {
+    fn run(&self, args: &[String]) -> CommandOutputResult<String>;
+    fn run_with_stdin(&self, args: &[String], stdin: &str) -> CommandOutputResult<String>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemGlabRunner;
+
+impl GlabCommandRunner for SystemGlabRunner {
+    fn run(&self, args: &[String]) -> CommandOutputResult<String> {
+        run_command_output(
+            "glab",
+            None,
+            args.iter().map(|arg| OsStr::new(arg.as_str())),
+        )
+    }
+
+    fn run_with_stdin(&self, args: &[String], stdin: &str) -> CommandOutputResult<String> {
+        run_command_output_with_stdin(
+            "glab",
+            None,
+            args.iter().map(|arg| OsStr::new(arg.as_str())),
+            stdin,
+        )
+    }
+}
+
+/// Post `discussions` on `target`. Fails only when the merge request cannot
+/// be read; rejected discussions are reported in the outcome so the rest
+/// still land.
+pub fn submit_review(
+    runner: &dyn GlabCommandRunner,
+    target: &MergeRequestTarget,
+    discussions: &[Discussion],
+) -> Result<PostOutcome> {
+    if discussions.is_empty() {
+        return Err(TuicrError::NoComments);
+    }
+
+    let api_args = |extra: &[&str], path: String| {
+        let mut args = vec![
+            "api".to_string(),
+            "--hostname".to_string(),
+            target.host.clone(),
+        ];
+        args.extend(extra.iter().map(|s| s.to_string()));
+        args.push(path);
+        args
+    };
+    let mr_json = runner
+        .run(&api_args(&[], target.api_path()))
+        .map_err(|e| map_glab_error(e.kind, e.stderr, &target.host))?;
+    let refs: DiffRefs = serde_json::from_str::<serde_json::Value>(&mr_json)
+        .ok()
+        .and_then(|mr| serde_json::from_value(mr["diff_refs"].clone()).ok())
+        .ok_or_else(|| {
+            TuicrError::Forge(format!(
+                "GitLab returned no diff refs for {}",
+                target.display_name()
+            ))
+        })?;
+
+    let post_args = api_args(
+        &[
+            "--method",
+            "POST",
+            "--header",
+            "Content-Type: application/json",
+            "--input",
+            "-",
+        ],
+        format!("{}/discussions", target.api_path()),
+    );
+    let mut outcome = PostOutcome::default();
+    for discussion in discussions {
+        let payload = build_discussion_payload(discussion, &refs).to_string();
+        match runner.run_with_stdin(&post_args, &payload) {
+            Ok(_) => outcome.record_posted(discussion),
+            Err(e) => {
+                outcome
+                    .failures
+                    .push(map_glab_error(e.kind, e.stderr, &target.host).to_string());
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+fn map_glab_error(kind: CommandOutputErrorKind, stderr: String, host: &str) -> TuicrError {
+    let lower = stderr.to_ascii_lowercase();
+    if kind == CommandOutputErrorKind::NotFound {
+        TuicrError::Forge(
+            "GitLab integration requires `glab`.\nInstall GitLab CLI and run `glab auth login`, \
+             or set GITLAB_TOKEN."
+                .to_string(),
+        )
+    } else if lower.contains("401") || lower.contains("unauthorized") {
+        TuicrError::Forge(format!(
+            "GitLab authentication failed.\nRun `glab auth login --hostname {host}`, or set \
+             GITLAB_TOKEN to a token with the api scope."
+        ))
+    } else {
+        TuicrError::Forge(format!("GitLab command failed: {}", stderr.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ForgeConfig;
+    use crate::forge::discussions::build_discussions;
+    use crate::model::{
+        Comment, CommentType, DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus,
+        LineOrigin, LineSide, ReviewSession, SessionDiffSource,
+    };
+    use crate::process::CommandOutputError;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
+    struct FakeGlab {
+        calls: RefCell<Vec<(Vec<String>, Option<String>)>>,
+    }
+
+    impl GlabCommandRunner for FakeGlab {
+        fn run(&self, args: &[String]) -> CommandOutputResult<String> {
+            self.calls.borrow_mut().push((args.to_vec(), None));
+            Ok(
+                r#"{"iid": 12, "diff_refs": {"base_sha": "b", "start_sha": "s", "head_sha": "h"}}"#
+                    .to_string(),
+            )
+        }
+
+        fn run_with_stdin(&self, args: &[String], stdin: &str) -> CommandOutputResult<String> {
+            self.calls
+                .borrow_mut()
+                .push((args.to_vec(), Some(stdin.to_string())));
+            if stdin.contains("rejected") {
+                return Err(CommandOutputError {
+                    kind: CommandOutputErrorKind::Unsuccessful,
+                    status: Some(1),
+                    stderr: "HTTP 400: line_code can't be blank".to_string(),
+                });
+            }
+            Ok("{}".to_string())
+        }
+    }
+
+    #[test]
+    fn should_parse_merge_request_urls_and_iids_on_the_remote_project() {
+        // when
+        let url = MergeRequestTarget::parse(
+            "https://gitlab.example.com/group/sub/proj/-/merge_requests/42/diffs",
+            None,
+        );
+        let iid = MergeRequestTarget::parse(
+            "!7",
+            Some("ssh://git@gitlab.example.com:2222/group/proj.git"),
+        );
+
+        // then
+        let url = url.unwrap();
+        assert_eq!(
+            (url.host.as_str(), url.project.as_str(), url.iid),
+            ("gitlab.example.com", "group/sub/proj", 42)
+        );
+        assert_eq!(
+            url.api_path(),
+            "projects/group%2Fsub%2Fproj/merge_requests/42"
+        );
+        let iid = iid.unwrap();
+        assert_eq!(
+            (iid.host.as_str(), iid.project.as_str(), iid.iid),
+            ("gitlab.example.com", "group/proj", 7)
+        );
+        assert_eq!(MergeRequestTarget::parse("7", None), None);
+        assert_eq!(
+            MergeRequestTarget::parse("abc", Some("git@h:g/p.git")),
+            None
+        );
+    }
+
+    #[test]
+    fn should_post_each_discussion_with_the_merge_request_diff_refs() {
+        // given
+        let hunks = vec![DiffHunk {
+            header: "@@ -0,0 +1,1 @@".to_string(),
+            lines: vec![DiffLine {
+                origin: LineOrigin::Addition,
+                content: "x".to_string(),
+                old_lineno: None,
+                new_lineno: Some(1),
+                highlighted_spans: None,
+            }],
+            old_start: 0,
+            old_count: 0,
+            new_start: 1,
+            new_count: 1,
+        }];
+        let file = DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from("a.rs")),
+            status: FileStatus::Added,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::CommitRange,
+        );
+        session.review_comments.push(Comment::new(
+            "rejected".to_string(),
+            CommentType::Note,
+            None,
+        ));
+        session.add_file(PathBuf::from("a.rs"), FileStatus::Added, 0);
+        let boom = Comment::new("boom".to_string(), CommentType::Issue, Some(LineSide::New));
+        let boom_id = boom.id.clone();
+        session
+            .get_file_mut(&PathBuf::from("a.rs"))
+            .unwrap()
+            .add_line_comment(1, boom);
+        let runner = FakeGlab {
+            calls: RefCell::new(Vec::new()),
+        };
+        let target =
+            MergeRequestTarget::parse("https://gl.io/g/p/-/merge_requests/12", None).unwrap();
+
+        // when
+        let discussions = build_discussions(&session, &[file], &ForgeConfig::default());
+        let outcome = submit_review(&runner, &target, &discussions).unwrap();
+
+        // then
+        assert_eq!(outcome.posted, 1);
+        assert_eq!(outcome.posted_comment_ids, vec![boom_id]);
+        assert_eq!(outcome.failures.len(), 1);
+        assert!(outcome.failures[0].contains("line_code can't be blank"));
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[0].0,
+            vec![
+                "api",
+                "--hostname",
+                "gl.io",
+                "projects/g%2Fp/merge_requests/12"
+            ]
+        );
+        let (post_args, body) = &calls[2];
+        assert_eq!(
+            post_args.last().map(String::as_str),
+            Some("projects/g%2Fp/merge_requests/12/discussions")
+        );
+        let payload: serde_json::Value = serde_json::from_str(body.as_ref().unwrap()).unwrap();
+        assert_eq!(payload["body"], "[ISSUE] boom");
+        assert_eq!(payload["position"]["head_sha"], "h");
+        assert_eq!(payload["position"]["new_line"], 1);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod glab;
+pub mod submit;
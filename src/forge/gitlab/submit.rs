@@ -0,0 +1,89 @@
+//! GitLab merge request discussion payloads.
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::forge::discussions::Discussion;
+
+/// The `diff_refs` of a merge request, which every position must quote.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DiffRefs {
+    pub base_sha: String,
+    pub start_sha: String,
+    pub head_sha: String,
+}
+
+/// JSON body for `POST projects/:id/merge_requests/:iid/discussions`.
+pub fn build_discussion_payload(discussion: &Discussion, refs: &DiffRefs) -> Value {
+    let mut payload = Map::new();
+    payload.insert("body".to_string(), Value::from(discussion.body.clone()));
+    if let Some(position) = &discussion.position {
+        let mut obj = Map::new();
+        obj.insert("position_type".to_string(), Value::from("text"));
+        obj.insert("base_sha".to_string(), Value::from(refs.base_sha.clone()));
+        obj.insert("start_sha".to_string(), Value::from(refs.start_sha.clone()));
+        obj.insert("head_sha".to_string(), Value::from(refs.head_sha.clone()));
+        obj.insert(
+            "old_path".to_string(),
+            Value::from(position.old_path.to_string_lossy().to_string()),
+        );
+        obj.insert(
+            "new_path".to_string(),
+            Value::from(position.new_path.to_string_lossy().to_string()),
+        );
+        if let Some(line) = position.old_line {
+            obj.insert("old_line".to_string(), Value::from(line));
+        }
+        if let Some(line) = position.new_line {
+            obj.insert("new_line".to_string(), Value::from(line));
+        }
+        payload.insert("position".to_string(), Value::Object(obj));
+    }
+    Value::Object(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::position::DiffPosition;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_build_position_payload_with_diff_refs() {
+        // given
+        let refs = DiffRefs {
+            base_sha: "base".to_string(),
+            start_sha: "start".to_string(),
+            head_sha: "head".to_string(),
+        };
+        let discussion = Discussion {
+            body: "[ISSUE] boom".to_string(),
+            position: Some(DiffPosition {
+                old_path: PathBuf::from("a.rs"),
+                new_path: PathBuf::from("a.rs"),
+                old_line: None,
+                new_line: Some(7),
+            }),
+            comment_ids: Vec::new(),
+        };
+
+        // when
+        let payload = build_discussion_payload(&discussion, &refs);
+        let general = build_discussion_payload(
+            &Discussion {
+                body: "hi".to_string(),
+                position: None,
+                comment_ids: Vec::new(),
+            },
+            &refs,
+        );
+
+        // then
+        assert_eq!(payload["body"], "[ISSUE] boom");
+        assert_eq!(payload["position"]["position_type"], "text");
+        assert_eq!(payload["position"]["head_sha"], "head");
+        assert_eq!(payload["position"]["new_line"], 7);
+        assert!(payload["position"].get("old_line").is_none());
+        assert!(general.get("position").is_none());
+    }
+}
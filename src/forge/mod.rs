@@ -5,8 +5,12 @@
 //! instead of shelling out to forge-specific tools directly.
 #![allow(dead_code)]
 
+pub mod bitbucket;
 pub mod context;
+pub mod discussions;
 pub mod github;
+pub mod gitlab;
+pub mod position;
 pub mod pr_open;
 pub mod remote_comments;
 pub mod selector;
@@ -0,0 +1,194 @@
+//! Where a local `(file, side, line)` anchor lands in a forge's diff.
+//!
+//! Forges only take inline comments on lines their diff shows, and GitHub
+//! also requires both ends of a multi-line comment to sit in one hunk. These
+//! helpers answer both questions from the parsed `DiffFile`, so GitHub
+//! submit and the one-comment-per-request forges (GitLab, Bitbucket) place
+//! comments the same way. Renamed files keep their old line numbers on the
+//! removed side while the comment names the new path; positions carry both
+//! paths for the forges that want them.
+
+use std::path::PathBuf;
+
+use crate::model::{DiffFile, DiffLine, LineOrigin, LineRange, LineSide};
+
+/// Where a positioned comment sits in the diff. Unchanged lines carry both
+/// line numbers, added or removed lines only the one on their side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffPosition {
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+}
+
+/// `line`'s number on `side`, for any line that has one.
+fn lineno(line: &DiffLine, side: LineSide) -> Option<u32> {
+    match side {
+        LineSide::Old => line.old_lineno,
+        LineSide::New => line.new_lineno,
+    }
+}
+
+/// `line`'s number on `side` when it can end a multi-line comment there:
+/// unchanged or added lines on the new side, removed lines on the old.
+fn range_lineno(line: &DiffLine, side: LineSide) -> Option<u32> {
+    match (side, line.origin) {
+        (LineSide::New, LineOrigin::Context | LineOrigin::Addition) => line.new_lineno,
+        (LineSide::Old, LineOrigin::Deletion) => line.old_lineno,
+        _ => None,
+    }
+}
+
+fn find_line(file: &DiffFile, line: u32, side: LineSide) -> Option<&DiffLine> {
+    file.hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .find(|l| lineno(l, side) == Some(line))
+}
+
+/// True when `line` appears on `side` somewhere in the file's hunks. Lines
+/// in the gaps between hunks (expanded context) are not part of the diff.
+pub fn line_in_diff(file: &DiffFile, line: u32, side: LineSide) -> bool {
+    find_line(file, line, side).is_some()
+}
+
+/// The part of `range` a forge can take as one multi-line comment on `side`:
+/// the lines of the hunk holding the range's end (or, when the end fell in
+/// a gap, the last hunk the range touches). `None` when no hunk has a line
+/// of the range on `side`.
+pub fn range_in_hunk(file: &DiffFile, range: LineRange, side: LineSide) -> Option<LineRange> {
+    let in_range = |line: &DiffLine| {
+        range_lineno(line, side).filter(|n| (range.start..=range.end).contains(n))
+    };
+    let hunk = file
+        .hunks
+        .iter()
+        .rev()
+        .find(|hunk| {
+            hunk.lines
+                .iter()
+                .any(|line| range_lineno(line, side) == Some(range.end))
+        })
+        .or_else(|| {
+            file.hunks
+                .iter()
+                .rev()
+                .find(|hunk| hunk.lines.iter().any(|line| in_range(line).is_some()))
+        })?;
+    let lines: Vec<u32> = hunk.lines.iter().filter_map(in_range).collect();
+    Some(LineRange::new(*lines.iter().min()?, *lines.iter().max()?))
+}
+
+/// The position of `line` on `side`, naming both of the file's paths.
+pub fn position_of(file: &DiffFile, line: u32, side: LineSide) -> DiffPosition {
+    let (old_line, new_line) = match find_line(file, line, side) {
+        Some(l) if l.origin == LineOrigin::Context => (l.old_lineno, l.new_lineno),
+        _ => match side {
+            LineSide::Old => (Some(line), None),
+            LineSide::New => (None, Some(line)),
+        },
+    };
+    let new_path = file.display_path().clone();
+    DiffPosition {
+        old_path: file.old_path.clone().unwrap_or_else(|| new_path.clone()),
+        new_path,
+        old_line,
+        new_line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::SyntaxHighlighter;
+    use crate::vcs::diff_parser::{DiffFormat, parse_unified_diff};
+
+    const RENAMED_MULTI_HUNK: &str = "\
+diff --git a/src/old_name.rs b/src/new_name.rs
+similarity index 90%
+rename from src/old_name.rs
+rename to src/new_name.rs
+index 1111111..2222222 100644
+--- a/src/old_name.rs
++++ b/src/new_name.rs
+@@ -1,4 +1,4 @@
+ fn one() {
+-    old();
++    new();
+ }
+ // one
+@@ -20,2 +20,4 @@ fn two() {
+ fn three() {
++    added();
++    more();
+ }
+";
+
+    fn fixture() -> DiffFile {
+        let mut files = parse_unified_diff(
+            RENAMED_MULTI_HUNK,
+            DiffFormat::GitStyle,
+            &SyntaxHighlighter::default(),
+        )
+        .unwrap();
+        assert_eq!(files.len(), 1);
+        files.remove(0)
+    }
+
+    #[test]
+    fn should_position_lines_of_a_renamed_multi_hunk_file() {
+        // given
+        let file = fixture();
+
+        // when
+        let removed = position_of(&file, 2, LineSide::Old);
+        let unchanged = position_of(&file, 23, LineSide::New);
+
+        // then
+        assert_eq!(
+            removed,
+            DiffPosition {
+                old_path: PathBuf::from("src/old_name.rs"),
+                new_path: PathBuf::from("src/new_name.rs"),
+                old_line: Some(2),
+                new_line: None,
+            }
+        );
+        assert_eq!(
+            (unchanged.old_line, unchanged.new_line),
+            (Some(21), Some(23))
+        );
+        assert!(line_in_diff(&file, 22, LineSide::New));
+        assert!(!line_in_diff(&file, 10, LineSide::New));
+        assert!(!line_in_diff(&file, 22, LineSide::Old));
+    }
+
+    #[test]
+    fn should_clamp_a_range_to_the_hunk_holding_its_end() {
+        // given
+        let file = fixture();
+
+        // when / then
+        assert_eq!(
+            range_in_hunk(&file, LineRange::new(2, 22), LineSide::New),
+            Some(LineRange::new(20, 22))
+        );
+        assert_eq!(
+            range_in_hunk(&file, LineRange::new(1, 3), LineSide::New),
+            Some(LineRange::new(1, 3))
+        );
+        assert_eq!(
+            range_in_hunk(&file, LineRange::new(3, 15), LineSide::New),
+            Some(LineRange::new(3, 4))
+        );
+        assert_eq!(
+            range_in_hunk(&file, LineRange::new(5, 19), LineSide::New),
+            None
+        );
+        assert_eq!(
+            range_in_hunk(&file, LineRange::new(2, 2), LineSide::Old),
+            Some(LineRange::single(2))
+        );
+    }
+}
@@ -2,12 +2,19 @@
 //!
 //! These types carry existing GitHub review discussions into the App for
 //! read-only display, filtering, and export. They are deliberately
-//! source-of-truth-on-remote: we never mutate, reply to, or persist them
-//! locally past the in-memory cache.
+//! source-of-truth-on-remote: we never mutate or reply to them, and they are
+//! only persisted once `:comments import` copies them into the session as
+//! locked local comments.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::model::comment::CommentLifecycleState;
+use crate::model::{Comment, CommentType, LineSide, ReviewSession};
+
 /// Which side of the diff a remote comment anchors to.
 ///
 /// Mirrors GitHub's submission model: `RIGHT` is the head side (added/context
@@ -177,9 +184,102 @@ pub fn group_threads_by_path(
     groups
 }
 
+/// Outcome of `import_threads`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Comments already in the session from an earlier import
+    pub already_imported: usize,
+    /// Comments on files that are not part of the session
+    pub skipped: usize,
+}
+
+/// Remote comment IDs already present in `session`, either imported or
+/// submitted from tuicr.
+pub fn session_remote_comment_ids(session: &ReviewSession) -> HashSet<&str> {
+    session
+        .files
+        .values()
+        .flat_map(|f| {
+            f.file_comments
+                .iter()
+                .chain(f.line_comments.values().flatten())
+        })
+        .filter_map(|c| c.remote_comment_id.as_deref())
+        .collect()
+}
+
+/// Copy the comments of the threads visible under the session's
+/// `remote_comments_visibility` into `session`, so a review started on the
+/// forge can be continued locally. Each remote comment becomes a `Submitted`
+/// (and therefore locked) comment carrying its author and remote ID, which
+/// keeps it out of the next submit and makes re-importing a no-op. A
+/// `[TYPE] ` prefix written by tuicr's submit is turned back into the
+/// comment type.
+pub fn import_threads(
+    session: &mut ReviewSession,
+    threads: &[RemoteReviewThread],
+) -> ImportSummary {
+    let mut known: HashSet<String> = session_remote_comment_ids(session)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut summary = ImportSummary::default();
+    for thread in filter_threads(threads, session.remote_comments_visibility) {
+        let path = PathBuf::from(&thread.path);
+        for remote in &thread.comments {
+            if known.contains(&remote.id) {
+                summary.already_imported += 1;
+                continue;
+            }
+            let Some(review) = session.get_file_mut(&path) else {
+                summary.skipped += 1;
+                continue;
+            };
+            let (comment_type, file_level, body) = split_type_prefix(&remote.body);
+            let mut comment = Comment::new(body.to_string(), comment_type, None);
+            comment.author = Some(remote.author.clone().unwrap_or_else(|| "ghost".to_string()));
+            comment.created_at = remote.created_at.unwrap_or(comment.created_at);
+            comment.lifecycle_state = CommentLifecycleState::Submitted;
+            comment.remote_comment_id = Some(remote.id.clone());
+            match thread.line.filter(|_| !file_level) {
+                Some(line) => {
+                    comment.side = Some(match thread.side {
+                        RemoteCommentSide::Right => LineSide::New,
+                        RemoteCommentSide::Left => LineSide::Old,
+                    });
+                    review.add_line_comment(line, comment);
+                }
+                None => review.add_file_comment(comment),
+            }
+            known.insert(remote.id.clone());
+            summary.imported += 1;
+        }
+    }
+    summary
+}
+
+/// Undo the `[TYPE] ` / `[TYPE] File-level: ` prefix tuicr adds on submit.
+/// Bodies without a built-in type prefix are imported as notes.
+fn split_type_prefix(body: &str) -> (CommentType, bool, &str) {
+    let parsed = body
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+        .map(|(label, rest)| (CommentType::from_id(label), rest))
+        .filter(|(comment_type, _)| !matches!(comment_type, CommentType::Custom(_)));
+    match parsed {
+        Some((comment_type, rest)) => match rest.strip_prefix("File-level: ") {
+            Some(rest) => (comment_type, true, rest),
+            None => (comment_type, false, rest),
+        },
+        None => (CommentType::Note, false, body),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::{FileStatus, SessionDiffSource};
 
     fn make_thread(
         id: &str,
@@ -308,4 +408,98 @@ mod tests {
         // unknown defaults to RIGHT (head side) — safer for display
         assert_eq!(RemoteCommentSide::parse(""), RemoteCommentSide::Right);
     }
+
+    fn pr_session() -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "head".to_string(),
+            None,
+            SessionDiffSource::PullRequest,
+        );
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 0);
+        session
+    }
+
+    #[test]
+    fn should_import_visible_threads_as_locked_comments() {
+        // given
+        let mut session = pr_session();
+        let mut thread = make_thread("a", "src/lib.rs", Some(10), false, false);
+        thread.side = RemoteCommentSide::Left;
+        thread.comments[0].body = "[ISSUE] Leaks the handle".to_string();
+        thread.comments.push(RemoteReviewComment {
+            id: "a-reply".to_string(),
+            author: None,
+            body: "Agreed".to_string(),
+            created_at: None,
+            in_reply_to: Some("a-root".to_string()),
+            url: "https://example.com/a#reply".to_string(),
+        });
+        let threads = vec![
+            thread,
+            make_thread("b", "src/lib.rs", Some(20), true, false),
+            make_thread("c", "src/other.rs", Some(5), false, false),
+        ];
+
+        // when
+        let summary = import_threads(&mut session, &threads);
+
+        // then
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 2,
+                already_imported: 0,
+                skipped: 1,
+            }
+        );
+        let review = &session.files[&PathBuf::from("src/lib.rs")];
+        let comments = &review.line_comments[&10];
+        assert_eq!(comments[0].content, "Leaks the handle");
+        assert_eq!(comments[0].comment_type, CommentType::Issue);
+        assert_eq!(comments[0].author.as_deref(), Some("alice"));
+        assert_eq!(comments[0].side, Some(LineSide::Old));
+        assert!(comments[0].is_locked());
+        assert_eq!(comments[1].content, "Agreed");
+        assert_eq!(comments[1].comment_type, CommentType::Note);
+        assert_eq!(comments[1].author.as_deref(), Some("ghost"));
+        assert!(!review.line_comments.contains_key(&20));
+    }
+
+    #[test]
+    fn should_skip_comments_imported_before() {
+        // given
+        let mut session = pr_session();
+        let threads = vec![make_thread("a", "src/lib.rs", None, false, false)];
+        import_threads(&mut session, &threads);
+
+        // when
+        let summary = import_threads(&mut session, &threads);
+
+        // then
+        assert_eq!(summary.imported, 0);
+        assert_eq!(summary.already_imported, 1);
+        assert_eq!(
+            session.files[&PathBuf::from("src/lib.rs")]
+                .file_comments
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn should_restore_file_level_prefix_as_file_comment() {
+        // given
+        let body = "[SUGGESTION] File-level: Split this module";
+
+        // when
+        let parsed = split_type_prefix(body);
+
+        // then
+        assert_eq!(parsed, (CommentType::Suggestion, true, "Split this module"));
+        assert_eq!(
+            split_type_prefix("[wip] later"),
+            (CommentType::Note, false, "[wip] later")
+        );
+    }
 }
@@ -11,6 +11,7 @@
 use std::path::PathBuf;
 
 use crate::config::ForgeConfig;
+use crate::forge::position;
 use crate::model::comment::Comment;
 use crate::model::{DiffFile, LineRange, LineSide};
 
@@ -133,7 +134,7 @@ impl UnmappableReason {
 pub enum MappedComment {
     Inline(InlineComment),
     Unmappable {
-        comment: Comment,
+        comment: Box<Comment>,
         file: PathBuf,
         reason: UnmappableReason,
     },
@@ -182,14 +183,14 @@ pub fn map_comment(
 
     if file.is_binary {
         return MappedComment::Unmappable {
-            comment: comment.clone(),
+            comment: Box::new(comment.clone()),
             file: path,
             reason: UnmappableReason::BinaryFile,
         };
     }
     if file.is_too_large {
         return MappedComment::Unmappable {
-            comment: comment.clone(),
+            comment: Box::new(comment.clone()),
             file: path,
             reason: UnmappableReason::TooLargeFile,
         };
@@ -207,7 +208,7 @@ pub fn map_comment(
                 comment_id: comment.id.clone(),
             }),
             None => MappedComment::Unmappable {
-                comment: comment.clone(),
+                comment: Box::new(comment.clone()),
                 file: path,
                 reason: UnmappableReason::FileLevelNoAnchor,
             },
@@ -215,15 +216,15 @@ pub fn map_comment(
         CommentAnchor::Range => match comment.line_range {
             Some(range) => map_range(comment, file, config, range),
             None => MappedComment::Unmappable {
-                comment: comment.clone(),
+                comment: Box::new(comment.clone()),
                 file: path,
                 reason: UnmappableReason::MixedSideRange,
             },
         },
         CommentAnchor::Line { line, side } => {
-            if !line_present_on_side(file, line, side) {
+            if !position::line_in_diff(file, line, side) {
                 return MappedComment::Unmappable {
-                    comment: comment.clone(),
+                    comment: Box::new(comment.clone()),
                     file: path,
                     reason: UnmappableReason::LineNotInDiff,
                 };
@@ -241,22 +242,6 @@ pub fn map_comment(
     }
 }
 
-/// True when `line` appears on `side` somewhere in the file's hunks.
-fn line_present_on_side(file: &DiffFile, line: u32, side: LineSide) -> bool {
-    for hunk in &file.hunks {
-        for dl in &hunk.lines {
-            let candidate = match side {
-                LineSide::Old => dl.old_lineno,
-                LineSide::New => dl.new_lineno,
-            };
-            if candidate == Some(line) {
-                return true;
-            }
-        }
-    }
-    false
-}
-
 /// Map a multi-line range comment, validating that the range sits on a
 /// single diff side.
 fn map_range(
@@ -272,23 +257,23 @@ fn map_range(
         // through the resolver rather than guessing.
         None => {
             return MappedComment::Unmappable {
-                comment: comment.clone(),
+                comment: Box::new(comment.clone()),
                 file: path,
                 reason: UnmappableReason::MixedSideRange,
             };
         }
     };
 
-    // Verify both ends of the range exist on `side`. The hunks may not
-    // contain every intermediate line (the user could have selected across
-    // a gap), but the start and end must be anchorable.
-    if !range_endpoints_present(file, range, side) {
+    // GitHub wants both ends in one hunk, so a range selected across a gap
+    // keeps only its part in the hunk holding its end. A range with no line
+    // on `side` at all straddles the sides.
+    let Some(range) = position::range_in_hunk(file, range, side) else {
         return MappedComment::Unmappable {
-            comment: comment.clone(),
+            comment: Box::new(comment.clone()),
             file: path,
             reason: UnmappableReason::MixedSideRange,
         };
-    }
+    };
 
     if range.is_single() {
         return MappedComment::Inline(InlineComment {
@@ -313,39 +298,6 @@ fn map_range(
     })
 }
 
-/// True when both the start and end of `range` appear on the requested side
-/// somewhere in `file`'s hunks. Used to detect ranges that would straddle a
-/// side boundary (e.g. user selected through deleted+added lines).
-fn range_endpoints_present(file: &DiffFile, range: LineRange, side: LineSide) -> bool {
-    let mut saw_start = false;
-    let mut saw_end = false;
-    for hunk in &file.hunks {
-        for line in &hunk.lines {
-            let lineno = match side {
-                LineSide::New => match line.origin {
-                    crate::model::LineOrigin::Context | crate::model::LineOrigin::Addition => {
-                        line.new_lineno
-                    }
-                    crate::model::LineOrigin::Deletion => None,
-                },
-                LineSide::Old => match line.origin {
-                    crate::model::LineOrigin::Deletion => line.old_lineno,
-                    _ => None,
-                },
-            };
-            if let Some(n) = lineno {
-                if n == range.start {
-                    saw_start = true;
-                }
-                if n == range.end {
-                    saw_end = true;
-                }
-            }
-        }
-    }
-    saw_start && saw_end
-}
-
 /// Output of preflight — drives the resolver and confirmation modal.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PreflightResult {
@@ -449,7 +401,9 @@ pub const REVIEW_FOOTER: &str =
 mod tests {
     use super::*;
     use crate::model::comment::{Comment, CommentType, LineContext, LineRange, LineSide};
-    use crate::model::diff_types::{DiffHunk, DiffLine, FileStatus, LineOrigin};
+    use crate::model::diff_types::{
+        DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin,
+    };
     use std::path::PathBuf;
 
     fn line(origin: LineOrigin, new: Option<u32>, old: Option<u32>) -> DiffLine {
@@ -483,6 +437,8 @@ mod tests {
             is_too_large: false,
             is_commit_message: false,
             content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         }
     }
 
@@ -505,6 +461,9 @@ mod tests {
             new_line: new,
             old_line: old,
             content: String::new(),
+            before: Vec::new(),
+            after: Vec::new(),
+            hash: None,
         });
         c
     }
@@ -298,7 +298,9 @@ pub struct PullRequestCommit {
     pub timestamp: Option<DateTime<Utc>>,
 }
 
-pub trait ForgeBackend {
+/// `Send` so a fully built `App` can be handed over from the background
+/// startup thread.
+pub trait ForgeBackend: Send {
     fn list_pull_requests(&self, query: PullRequestListQuery) -> Result<PagedPullRequests>;
     fn get_pull_request(&self, target: PullRequestTarget) -> Result<PullRequestDetails>;
     fn get_pull_request_diff(&self, pr: &PullRequestDetails) -> Result<String>;
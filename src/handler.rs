@@ -3,15 +3,17 @@ use ratatui::layout::Position;
 
 use crate::app::{
     self, App, ExpandDirection, FileTreeItem, FocusedPanel, GapCursorHit, InputMode, TargetTab,
-    VisualSelection,
+    View, VisualSelection,
 };
 use crate::input::Action;
-use crate::model::{ClearScope, LineSide};
-use crate::output::{export_to_clipboard, generate_export_content};
-use crate::persistence::save_session;
+use crate::model::{ClearScope, LineSide, ReviewConfidence};
+use crate::output::{
+    copy_text_to_clipboard, export_to_clipboard, generate_export_content, render_review_template,
+};
 use crate::text_edit::{
     delete_char_before, delete_word_before, next_char_boundary, prev_char_boundary,
 };
+use crate::vcs::traits::VcsType;
 
 const WHEEL_LINES: usize = 3;
 
@@ -177,16 +179,26 @@ fn handle_left_click(app: &mut App, pos: Position) {
 /// Export review: either to clipboard or set pending stdout output based on app.output_to_stdout.
 /// When output_to_stdout is true, stores the content and sets should_quit.
 fn handle_export(app: &mut App) {
-    if app.output_to_stdout {
+    let started_at = std::time::Instant::now();
+    write_export(app);
+    app.notify_finished(crate::notify::Operation::Export, started_at);
+}
+
+fn write_export(app: &mut App) {
+    if let Some(template) = app.export_template.clone() {
+        handle_template_export(app, &template);
+    } else if app.output_to_stdout {
         match generate_export_content(
             &app.session,
             &app.diff_source,
             &app.comment_types,
             app.export_legend,
+            app.conventional_comments,
             &app.forge_review_threads,
         ) {
             Ok(content) => {
                 app.pending_stdout_output = Some(content);
+                app.record_findings();
                 app.should_quit = true;
             }
             Err(e) => app.set_warning(format!("{e}")),
@@ -197,14 +209,52 @@ fn handle_export(app: &mut App) {
             &app.diff_source,
             &app.comment_types,
             app.export_legend,
+            app.conventional_comments,
             &app.forge_review_threads,
         ) {
-            Ok(msg) => app.set_message(msg),
+            Ok(msg) => {
+                app.set_message(msg);
+                app.record_findings();
+            }
             Err(e) => app.set_warning(format!("{e}")),
         }
     }
 }
 
+/// `handle_export` with the review rendered through the `export_template`
+/// file instead of the built-in Markdown.
+fn handle_template_export(app: &mut App, template: &std::path::Path) {
+    let content = match render_review_template(
+        template,
+        &app.session,
+        &app.diff_source,
+        &app.comment_types,
+    ) {
+        Ok(content) => content,
+        Err(e) => {
+            app.set_warning(format!("{e}"));
+            return;
+        }
+    };
+    if app.output_to_stdout {
+        app.pending_stdout_output = Some(content);
+        app.record_findings();
+        app.should_quit = true;
+        return;
+    }
+    match copy_text_to_clipboard(&content) {
+        Ok(via_terminal) => {
+            app.set_message(if via_terminal {
+                "Review copied to clipboard (via terminal)"
+            } else {
+                "Review copied to clipboard"
+            });
+            app.record_findings();
+        }
+        Err(e) => app.set_warning(format!("{e}")),
+    }
+}
+
 /// Export and quit (used by ZZ keybinding).
 /// When --stdout is set, stores export content and quits.
 /// Otherwise, exports to clipboard and quits.
@@ -323,6 +373,7 @@ pub fn handle_help_action(app: &mut App, action: Action) {
         Action::MouseScrollDown(n) => app.help_scroll_down(n),
         Action::MouseScrollUp(n) => app.help_scroll_up(n),
         Action::ToggleHelp => app.toggle_help(),
+        Action::ToggleHelpScope => app.toggle_help_scope(),
         Action::Quit => app.should_quit = true,
         _ => {}
     }
@@ -339,26 +390,28 @@ pub fn handle_command_action(app: &mut App, action: Action) {
         Action::ExitMode => app.exit_command_mode(),
         Action::SubmitInput => {
             let cmd = app.command_buffer.trim().to_string();
+            if edits_session(&cmd) && !app.ensure_session_writable() {
+                app.exit_command_mode();
+                return;
+            }
             match cmd.as_str() {
                 "q" | "quit" => {
                     if app.dirty {
-                        app.set_error("No write since last change (add ! to override)");
+                        app.exit_command_mode();
+                        app.enter_confirm_mode(app::ConfirmAction::QuitWithoutSaving);
+                        return;
                     } else {
                         app.should_quit = true;
                     }
                 }
                 "q!" | "quit!" => app.should_quit = true,
-                "w" | "write" => match save_session(&app.session) {
-                    Ok(path) => {
-                        app.dirty = false;
-                        app.set_message(format!("Saved to {}", path.display()));
-                    }
+                "w" | "write" => match app.write_session() {
+                    Ok(message) => app.set_message(message),
                     Err(e) => app.set_error(format!("Save failed: {e}")),
                 },
-                "x" | "wq" => match save_session(&app.session) {
+                "x" | "wq" => match app.write_session() {
                     Ok(_) => {
-                        app.dirty = false;
-                        if app.session.has_comments() {
+                        if app.session.has_exportable_content() {
                             if app.output_to_stdout {
                                 // Skip confirmation dialog, export directly
                                 handle_export(app);
@@ -382,23 +435,93 @@ pub fn handle_command_action(app: &mut App, action: Action) {
                             app.set_error(format!("Reload failed: {e}"));
                         }
                     } else {
-                        match app.reload_diff_files() {
-                            Ok((count, invalidated)) => {
-                                if invalidated > 0 {
-                                    app.set_message(format!(
-                                        "Reloaded {count} files, {invalidated} changed since last review"
-                                    ));
-                                } else {
-                                    app.set_message(format!("Reloaded {count} files"));
-                                }
-                            }
-                            Err(e) => app.set_error(format!("Reload failed: {e}")),
+                        // Async too, so a hung command can't freeze the UI:
+                        // the worker borrows the VCS backend and
+                        // `poll_vcs_reload_events` applies the new diff.
+                        if let Err(e) = app.spawn_vcs_reload() {
+                            app.set_error(format!("Reload failed: {e}"));
                         }
                     }
                 }
-                "clip" | "export" => handle_export(app),
+                "clip" | "copy" => handle_export(app),
+                "export" => match app.export_format.clone() {
+                    Some(format) => {
+                        app.export_report(&format!("{}.{format}", app::REVIEW_REPORT_STEM))
+                    }
+                    None => handle_export(app),
+                },
+                "export patch" => app.export_report(app::SUGGESTIONS_PATCH_FILE),
+                "export annotated-patch" => app.export_report(app::ANNOTATED_PATCH_FILE),
+                _ if cmd.starts_with("export ") => app.export_report(cmd["export ".len()..].trim()),
+                "chat" => app.chat_command(""),
+                _ if cmd.starts_with("chat ") => app.chat_command(cmd["chat ".len()..].trim()),
+                _ if cmd.starts_with("import ") => app.import_review(cmd["import ".len()..].trim()),
+                _ if cmd == "session export" || cmd.starts_with("session export ") => {
+                    app.export_portable_session(cmd["session export".len()..].trim())
+                }
+                _ if cmd == "session import" || cmd.starts_with("session import ") => {
+                    app.import_portable_session(cmd["session import".len()..].trim())
+                }
+                _ if cmd == "checkpoints"
+                    || cmd == "checkpoint"
+                    || cmd.starts_with("checkpoint ") =>
+                {
+                    // Diffs open as a popup and restores ask first, so
+                    // leave command mode before either
+                    app.exit_command_mode();
+                    let args = if cmd == "checkpoints" {
+                        ""
+                    } else {
+                        &cmd["checkpoint".len()..]
+                    };
+                    app.checkpoint_command(args);
+                    return;
+                }
+                _ if cmd == "session merge" || cmd.starts_with("session merge ") => {
+                    // The summary opens as a popup, so leave command mode first
+                    app.exit_command_mode();
+                    app.merge_session_file(cmd["session merge".len()..].trim());
+                    return;
+                }
+                _ if cmd.starts_with("gitlab ") => {
+                    app.submit_to_gitlab(cmd["gitlab ".len()..].trim())
+                }
+                _ if cmd.starts_with("bitbucket ") => {
+                    app.submit_to_bitbucket(cmd["bitbucket ".len()..].trim())
+                }
                 "clear" => app.clear_comments(ClearScope::CommentsAndReviewed),
                 "clearc" => app.clear_comments(ClearScope::CommentsOnly),
+                "checklist" => app.show_checklist(),
+                _ if cmd.starts_with("check ") => {
+                    match cmd["check ".len()..].trim().parse::<usize>() {
+                        Ok(index) => app.toggle_checklist_item(index),
+                        Err(_) => app.set_warning("Usage: :check <n>"),
+                    }
+                }
+                "confidence" => app.set_warning("Usage: :confidence <skimmed|reviewed|thorough>"),
+                _ if cmd.starts_with("confidence ") => {
+                    match ReviewConfidence::from_id(cmd["confidence ".len()..].trim()) {
+                        Some(confidence) => {
+                            // Finishing the review opens the completion
+                            // dialog, so leave command mode first
+                            app.exit_command_mode();
+                            app.set_file_confidence(app.diff_state.current_file_idx, confidence);
+                            return;
+                        }
+                        None => app.set_warning("Usage: :confidence <skimmed|reviewed|thorough>"),
+                    }
+                }
+                "fix" => {
+                    // Bare `:fix` reopens the prompt seeded with the line so
+                    // the reviewer edits it in place.
+                    if let Some(prefill) = app.fix_command_prefill() {
+                        app.command_buffer = prefill;
+                        return;
+                    }
+                    app.set_warning("Move the cursor to an added or context line to fix it");
+                }
+                "unfix" => app.unfix_line_at_cursor(),
+                _ if cmd.starts_with("fix ") => app.fix_line_at_cursor(&cmd["fix ".len()..]),
                 "version" => {
                     app.set_message(format!("tuicr v{}", env!("CARGO_PKG_VERSION")));
                 }
@@ -424,6 +547,70 @@ pub fn handle_command_action(app: &mut App, action: Action) {
                 },
                 "set wrap" => app.set_diff_wrap(true),
                 "set wrap!" => app.toggle_diff_wrap(),
+                "set renames" => app.set_rename_detection(true),
+                "set norenames" => app.set_rename_detection(false),
+                "set renames!" => app.toggle_rename_detection(),
+                "set untracked" => app.set_include_untracked(true),
+                "set nountracked" => app.set_include_untracked(false),
+                "set untracked!" => app.toggle_untracked(),
+                "set history" => app.set_show_history_comments(true),
+                "set nohistory" => app.set_show_history_comments(false),
+                "set conventional" => app.set_conventional_comments(true),
+                "set noconventional" => app.set_conventional_comments(false),
+                "set conventional!" => app.set_conventional_comments(!app.conventional_comments),
+                "set history!" => app.set_show_history_comments(!app.show_history_comments),
+                "renames" => app.renames_command(""),
+                "conflicts" => app.toggle_conflicts_only(),
+                "verify" => app.verify_comments(),
+                "git" => app.run_vcs_passthrough("git", ""),
+                "jj" => app.run_vcs_passthrough("jj", ""),
+                _ if cmd.starts_with("git ") => {
+                    app.run_vcs_passthrough("git", &cmd["git ".len()..])
+                }
+                _ if cmd.starts_with("jj ") => app.run_vcs_passthrough("jj", &cmd["jj ".len()..]),
+                "todos" => app.todos_command(""),
+                "todos undo" => app.todos_command("undo"),
+                "author" => app.author_command(""),
+                _ if cmd.starts_with("author ") => app.author_command(&cmd["author ".len()..]),
+                "vcs" => app.switch_colocated_vcs(None),
+                "vcs git" => app.switch_colocated_vcs(Some(VcsType::Git)),
+                "vcs jj" => app.switch_colocated_vcs(Some(VcsType::Jujutsu)),
+                "rev" if app.revset.is_none() => {
+                    // Keep the prompt open so the revision can be typed in.
+                    app.command_buffer = "rev ".to_string();
+                    return;
+                }
+                "rev" => app.review_revset(""),
+                "interdiff" => app.review_interdiff(""),
+                _ if cmd.starts_with("interdiff ") => {
+                    app.review_interdiff(&cmd["interdiff ".len()..])
+                }
+                "view" => app.view_command(""),
+                "history" => app.set_view(View::History),
+                "trash" => app.set_view(View::Trash),
+                _ if cmd.starts_with("view ") => app.view_command(&cmd["view ".len()..]),
+                "sessions" => app.set_view(View::Sessions),
+                "rename" => app.rename_session(""),
+                _ if cmd.starts_with("rename ") => app.rename_session(&cmd["rename ".len()..]),
+                "elide" => app.elide_command(""),
+                "spotcheck" => app.spot_check_command(""),
+                _ if cmd.starts_with("spotcheck ") => {
+                    app.spot_check_command(&cmd["spotcheck ".len()..])
+                }
+                _ if cmd.starts_with("elide ") => app.elide_command(&cmd["elide ".len()..]),
+                "whitespace" | "ws" => app.whitespace_command(""),
+                _ if cmd.starts_with("whitespace ") => {
+                    app.whitespace_command(&cmd["whitespace ".len()..])
+                }
+                _ if cmd.starts_with("ws ") => app.whitespace_command(&cmd["ws ".len()..]),
+                _ if cmd.starts_with("renames ") => app.renames_command(&cmd["renames ".len()..]),
+                _ if cmd.starts_with("rev ") => app.review_revset(&cmd["rev ".len()..]),
+                "base" => {
+                    // Keep the prompt open so the ref can be typed in.
+                    app.command_buffer = "base ".to_string();
+                    return;
+                }
+                _ if cmd.starts_with("base ") => app.review_base(&cmd["base ".len()..]),
                 "set commits" => {
                     app.show_commit_selector = true;
                     app.set_message("Commit selector: visible");
@@ -490,6 +677,13 @@ pub fn handle_command_action(app: &mut App, action: Action) {
                     app.start_submit(crate::forge::submit::SubmitEvent::Draft);
                     return;
                 }
+                "comments import" => {
+                    if matches!(app.diff_source, app::DiffSource::PullRequest(_)) {
+                        app.import_remote_comments();
+                    } else {
+                        app.set_warning(":comments only applies in PR mode");
+                    }
+                }
                 "comments unresolved" | "comments all" | "comments hide" => {
                     use crate::forge::remote_comments::PrCommentsVisibility;
                     if !matches!(app.diff_source, app::DiffSource::PullRequest(_)) {
@@ -525,6 +719,33 @@ pub fn handle_command_action(app: &mut App, action: Action) {
     }
 }
 
+/// Commands that change the open review, refused while it is read-only.
+/// Saving (`:w`) is refused by `App::write_session` itself.
+const SESSION_COMMANDS: &[&str] = &[
+    "clear",
+    "clearc",
+    "check",
+    "confidence",
+    "import",
+    "session merge",
+    "session import",
+    "rename",
+    "fix",
+    "unfix",
+    "todos",
+    "submit",
+    "gitlab",
+    "bitbucket",
+    "comments import",
+];
+
+fn edits_session(cmd: &str) -> bool {
+    SESSION_COMMANDS.iter().any(|name| {
+        cmd.strip_prefix(name)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    })
+}
+
 /// Parse `:<n>` (new-side) or `:o<n>` (old-side) jump targets. The leading `:`
 /// has already been stripped by the time we get here.
 fn parse_lineno_command(cmd: &str) -> Option<(u32, LineSide)> {
@@ -568,7 +789,11 @@ pub fn handle_search_action(app: &mut App, action: Action) {
         }
         Action::ExitMode => app.exit_search_mode(),
         Action::SubmitInput => {
-            app.search_in_diff_from_cursor();
+            if app.focused_panel == FocusedPanel::FileList {
+                app.search_in_file_list_from_selection();
+            } else {
+                app.search_in_diff_from_cursor();
+            }
             app.exit_search_mode();
         }
         Action::Quit => app.should_quit = true,
@@ -592,10 +817,11 @@ pub fn handle_comment_action(app: &mut App, action: Action) {
         Action::DeleteChar => {
             app.comment_cursor = delete_char_before(&mut app.comment_buffer, app.comment_cursor);
         }
-        Action::ExitMode => app.exit_comment_mode(),
+        Action::ExitMode => app.cancel_comment(),
         Action::SubmitInput => app.save_comment(),
         Action::CycleCommentType => app.cycle_comment_type(),
         Action::CycleCommentTypeReverse => app.cycle_comment_type_reverse(),
+        Action::CycleCommentBlocking => app.cycle_comment_blocking(),
         Action::TextCursorLeft => {
             app.comment_cursor = prev_char_boundary(&app.comment_buffer, app.comment_cursor);
         }
@@ -622,6 +848,7 @@ pub fn handle_comment_action(app: &mut App, action: Action) {
             app.comment_cursor = 0;
         }
         Action::Quit => app.should_quit = true,
+        Action::ToggleHelp => app.toggle_help(),
         _ => {}
     }
 }
@@ -630,43 +857,49 @@ pub fn handle_comment_action(app: &mut App, action: Action) {
 pub fn handle_confirm_action(app: &mut App, action: Action) {
     match action {
         Action::ConfirmYes => {
-            if let Some(app::ConfirmAction::CopyAndQuit) = app.pending_confirm {
-                if app.output_to_stdout {
-                    match generate_export_content(
-                        &app.session,
-                        &app.diff_source,
-                        &app.comment_types,
-                        app.export_legend,
-                        &app.forge_review_threads,
-                    ) {
-                        Ok(content) => app.pending_stdout_output = Some(content),
-                        Err(e) => app.set_warning(format!("{e}")),
-                    }
-                } else {
-                    match export_to_clipboard(
-                        &app.session,
-                        &app.diff_source,
-                        &app.comment_types,
-                        app.export_legend,
-                        &app.forge_review_threads,
-                    ) {
-                        Ok(msg) => app.set_message(msg),
-                        Err(e) => app.set_warning(format!("{e}")),
-                    }
-                }
-            }
+            let dialog = app.pending_confirm.take();
             app.exit_confirm_mode();
-            app.should_quit = true;
+            if let Some(dialog) = dialog {
+                run_confirmed_action(app, dialog.action);
+            }
         }
         Action::ConfirmNo => {
+            let dialog = app.pending_confirm.take();
             app.exit_confirm_mode();
-            app.should_quit = true;
+            match dialog.map(|d| d.action) {
+                // Declining the export on `:wq` still quits, just without copying.
+                Some(app::ConfirmAction::CopyAndQuit) => app.should_quit = true,
+                Some(app::ConfirmAction::ResumeSession) => app.start_fresh_session(),
+                Some(app::ConfirmAction::TakeOverLock) => {
+                    app.read_only = true;
+                    app.set_message("Opened read-only; the review stays locked");
+                }
+                _ => {}
+            }
         }
         Action::Quit => app.should_quit = true,
         _ => {}
     }
 }
 
+/// Run the action of a confirm dialog the user answered yes to.
+fn run_confirmed_action(app: &mut App, action: app::ConfirmAction) {
+    match action {
+        app::ConfirmAction::CopyAndQuit => handle_export_and_quit(app),
+        app::ConfirmAction::DiscardComment => app.exit_comment_mode(),
+        app::ConfirmAction::QuitWithoutSaving => app.should_quit = true,
+        app::ConfirmAction::DeleteComment(entry) => {
+            app.delete_comment_entry(&entry);
+        }
+        app::ConfirmAction::ResumeSession => app.restore_session_cursor(),
+        app::ConfirmAction::DeleteOrphanedComment(id) => app.delete_orphaned_comment(&id),
+        app::ConfirmAction::DeleteSession(path) => app.delete_saved_session(&path),
+        app::ConfirmAction::TakeOverLock => app.take_over_session_lock(),
+        app::ConfirmAction::RestoreCheckpoint(label) => app.restore_checkpoint(&label),
+        app::ConfirmAction::PostDiscussions(target) => app.post_discussions(target),
+    }
+}
+
 /// Handle actions in CommitSelect mode.
 ///
 /// CommitSelect actually drives the review target selector, which has two
@@ -685,6 +918,7 @@ pub fn handle_commit_select_action(app: &mut App, action: Action) {
         Action::TargetSelectorTabNext => app.cycle_target_tab(true),
         Action::TargetSelectorTabPrev => app.cycle_target_tab(false),
         Action::Quit => app.should_quit = true,
+        Action::ToggleHelp => app.toggle_help(),
         Action::ExitMode => {
             // Esc during an in-flight PR open aborts the load and stays
             // in the selector. Takes precedence over the
@@ -732,6 +966,7 @@ fn handle_local_target_action(app: &mut App, action: Action) {
                 app.set_error(format!("Failed to load commits: {e}"));
             }
         }
+        Action::ToggleRangeMode => app.toggle_range_mode(),
         _ => {}
     }
 }
@@ -828,6 +1063,7 @@ pub fn handle_visual_action(app: &mut App, action: Action) {
         }
         Action::ExitMode => app.exit_visual_mode(),
         Action::Quit => app.should_quit = true,
+        Action::ToggleHelp => app.toggle_help(),
         Action::ScrollViewDown(n) | Action::MouseScrollDown(n) => app.scroll_view_down(n),
         Action::ScrollViewUp(n) | Action::MouseScrollUp(n) => app.scroll_view_up(n),
         Action::HalfPageDown => app.scroll_down(app.diff_state.viewport_height / 2),
@@ -846,8 +1082,22 @@ pub fn handle_file_list_action(app: &mut App, action: Action) {
         Action::CursorUp(n) => app.file_list_up(n),
         Action::ScrollLeft(n) => app.file_list_state.scroll_left(n),
         Action::ScrollRight(n) => app.file_list_state.scroll_right(n),
-        Action::MouseScrollDown(n) => app.file_list_viewport_scroll_down(n),
-        Action::MouseScrollUp(n) => app.file_list_viewport_scroll_up(n),
+        Action::ScrollViewDown(n) | Action::MouseScrollDown(n) => {
+            app.file_list_viewport_scroll_down(n)
+        }
+        Action::ScrollViewUp(n) | Action::MouseScrollUp(n) => app.file_list_viewport_scroll_up(n),
+        Action::HalfPageDown => {
+            app.file_list_down((app.file_list_state.viewport_height / 2).max(1))
+        }
+        Action::HalfPageUp => app.file_list_up((app.file_list_state.viewport_height / 2).max(1)),
+        Action::PageDown => app.file_list_down(app.file_list_state.viewport_height.max(1)),
+        Action::PageUp => app.file_list_up(app.file_list_state.viewport_height.max(1)),
+        Action::SearchNext => {
+            app.search_next_in_file_list();
+        }
+        Action::SearchPrev => {
+            app.search_prev_in_file_list();
+        }
         Action::SelectFile | Action::ToggleExpand => {
             if let Some(item) = app.get_selected_tree_item() {
                 match item {
@@ -866,10 +1116,43 @@ pub fn handle_file_list_action(app: &mut App, action: Action) {
                 app.set_warning("Select a file to toggle reviewed");
             }
         }
+        Action::CycleConfidence => {
+            if let Some(FileTreeItem::File { file_idx, .. }) = app.get_selected_tree_item() {
+                app.cycle_confidence_for_file_idx(file_idx);
+            } else {
+                app.set_warning("Select a file to set its review confidence");
+            }
+        }
         _ => handle_shared_normal_action(app, action),
     }
 }
 
+/// Handle actions in the Comments, Overview and Sessions views
+pub fn handle_view_action(app: &mut App, action: Action) {
+    let page = app.view_viewport_height().max(1);
+    match action {
+        Action::CursorDown(n) | Action::ScrollViewDown(n) | Action::MouseScrollDown(n) => {
+            app.view_cursor_down(n)
+        }
+        Action::CursorUp(n) | Action::ScrollViewUp(n) | Action::MouseScrollUp(n) => {
+            app.view_cursor_up(n)
+        }
+        Action::HalfPageDown => app.view_cursor_down((page / 2).max(1)),
+        Action::HalfPageUp => app.view_cursor_up((page / 2).max(1)),
+        Action::PageDown => app.view_cursor_down(page),
+        Action::PageUp => app.view_cursor_up(page),
+        Action::GoToTop => app.view_cursor_top(),
+        Action::GoToBottom => app.view_cursor_bottom(),
+        Action::SelectFile | Action::ToggleExpand => app.open_view_selection(),
+        Action::ToggleReviewed if app.view == View::Sessions => app.begin_session_rename(),
+        Action::ExitMode => app.set_view(View::Diff),
+        Action::Quit | Action::ToggleHelp | Action::EnterCommandMode => {
+            handle_shared_normal_action(app, action)
+        }
+        _ => {}
+    }
+}
+
 /// Handle actions when diff panel is focused
 pub fn handle_diff_action(app: &mut App, action: Action) {
     match action {
@@ -881,7 +1164,11 @@ pub fn handle_diff_action(app: &mut App, action: Action) {
         Action::ScrollRight(n) => app.scroll_right(n),
         Action::MouseScrollDown(n) => app.scroll_view_down(n),
         Action::MouseScrollUp(n) => app.scroll_view_up(n),
+        Action::ToggleDefer => app.toggle_defer_at_cursor(),
         Action::SelectFile => {
+            if app.expand_elision_at_cursor() || app.toggle_filetype_collapse_at_cursor() {
+                return;
+            }
             if let Some(hit) = app.get_gap_at_cursor() {
                 match hit {
                     GapCursorHit::Expander(gap_id, dir) => {
@@ -903,9 +1190,14 @@ pub fn handle_diff_action(app: &mut App, action: Action) {
                         app.collapse_gap(gap_id);
                     }
                 }
+            } else if let Err(e) = app.show_deleted_file_at_cursor() {
+                app.set_error(format!("Failed to read deleted file: {e}"));
             }
         }
         Action::SelectFileFull => {
+            if app.expand_elision_at_cursor() {
+                return;
+            }
             if let Some(hit) = app.get_gap_at_cursor() {
                 match hit {
                     GapCursorHit::Expander(gap_id, _) | GapCursorHit::HiddenLines(gap_id) => {
@@ -943,13 +1235,16 @@ fn handle_shared_normal_action(app: &mut App, action: Action) {
         Action::HalfPageUp => app.scroll_up(app.diff_state.viewport_height / 2),
         Action::PageDown => app.scroll_down(app.diff_state.viewport_height),
         Action::PageUp => app.scroll_up(app.diff_state.viewport_height),
-        Action::GoToTop => app.jump_to_file(0),
-        Action::GoToBottom => app.jump_to_bottom(),
+        Action::GoToTop => app.go_to_top(),
+        Action::GoToBottom => app.go_to_bottom(),
         Action::NextFile => app.next_file(),
         Action::PrevFile => app.prev_file(),
         Action::NextHunk => app.next_hunk(),
         Action::PrevHunk => app.prev_hunk(),
         Action::ToggleReviewed => app.toggle_reviewed(),
+        Action::CycleConfidence => {
+            app.cycle_confidence_for_file_idx(app.diff_state.current_file_idx)
+        }
         Action::ToggleFocus => {
             let has_selector = app.has_inline_commit_selector();
             app.focused_panel = match (app.focused_panel, has_selector) {
@@ -1042,6 +1337,20 @@ pub fn handle_submit_resolver_action(app: &mut App, action: Action) {
     }
 }
 
+/// Handle actions in the `:verify` panel: re-anchor, detach or delete each
+/// comment whose line is gone from the diff.
+pub fn handle_verify_panel_action(app: &mut App, action: Action) {
+    match action {
+        Action::VerifyDown => app.verify_cursor_down(),
+        Action::VerifyUp => app.verify_cursor_up(),
+        Action::VerifyReanchor => app.verify_reanchor(),
+        Action::VerifyDetach => app.verify_detach(),
+        Action::VerifyDelete => app.confirm_delete_orphaned_comment(),
+        Action::ExitMode => app.close_overlay(),
+        _ => {}
+    }
+}
+
 /// Handle actions in the bare-`:submit` action picker. Up/down move the
 /// cursor through Comment/Approve/Request changes/Draft; Enter dispatches
 /// preflight with the picked event (skipping the confirmation modal); Esc
@@ -1057,6 +1366,36 @@ pub fn handle_submit_action_picker_action(app: &mut App, action: Action) {
     }
 }
 
+/// Handle actions in the startup review chooser. Esc falls through to the
+/// commit selector underneath.
+pub fn handle_review_chooser_action(app: &mut App, action: Action) {
+    match action {
+        Action::ReviewChooserDown => app.review_chooser_cursor_down(),
+        Action::ReviewChooserUp => app.review_chooser_cursor_up(),
+        Action::ReviewChooserConfirm => app.review_chooser_confirm(),
+        Action::ExitMode => app.close_overlay(),
+        Action::Quit => app.should_quit = true,
+        _ => {}
+    }
+}
+
+/// Handle actions in the completion dialog opened when the last file is
+/// marked reviewed.
+pub fn handle_completion_dialog_action(app: &mut App, action: Action) {
+    match action {
+        Action::CompletionDown => app.completion_cursor_down(),
+        Action::CompletionUp => app.completion_cursor_up(),
+        Action::CompletionConfirm => match app.take_completion_action() {
+            Some(app::CompletionAction::CopyReview) => handle_export(app),
+            Some(action) => app.run_completion_action(action),
+            None => {}
+        },
+        Action::ExitMode => app.close_overlay(),
+        Action::Quit => app.should_quit = true,
+        _ => {}
+    }
+}
+
 /// Handle actions in the final submit confirmation modal.
 pub fn handle_submit_confirm_action(app: &mut App, action: Action) {
     match action {
@@ -0,0 +1,119 @@
+//! `tuicr history`: the completed reviews archived for a repository, and
+//! `tuicr history <n>` to reopen one read-only.
+
+use std::path::PathBuf;
+
+use crate::model::ReviewSession;
+use crate::persistence::archive::{archive_dir, list_archived};
+use crate::ui::views::session_source_label;
+
+/// `tuicr history` arguments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryArgs {
+    /// List this repository's reviews instead of the current directory's
+    pub repo: Option<PathBuf>,
+    /// Reopen the `n`th listed review (1-based) instead of listing
+    pub open: Option<usize>,
+}
+
+/// One numbered line per archived review, newest first
+fn report(sessions: &[ReviewSession]) -> Vec<String> {
+    sessions
+        .iter()
+        .enumerate()
+        .map(|(i, session)| {
+            let name = session
+                .name
+                .as_deref()
+                .map(|name| format!("{name}  "))
+                .unwrap_or_default();
+            let base: String = session.base_commit.chars().take(7).collect();
+            format!(
+                "{:>3}  {}  {name}{} · {} @ {base} · {} files · {} comments",
+                i + 1,
+                session
+                    .updated_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M"),
+                session_source_label(session),
+                session.branch_name.as_deref().unwrap_or("detached"),
+                session.files.len(),
+                session.comment_count(),
+            )
+        })
+        .collect()
+}
+
+fn archived(args: &HistoryArgs) -> Result<Vec<ReviewSession>, String> {
+    let repo = match &args.repo {
+        Some(repo) => repo.clone(),
+        None => std::env::current_dir().map_err(|e| e.to_string())?,
+    };
+    archive_dir()
+        .and_then(|dir| list_archived(&dir, &repo))
+        .map_err(|e| e.to_string())
+}
+
+/// The review `tuicr history <n>` reopens.
+pub fn find(args: &HistoryArgs, n: usize) -> Result<ReviewSession, String> {
+    let mut sessions = archived(args)?;
+    if n == 0 || n > sessions.len() {
+        return Err(format!(
+            "no review #{n} in the history ({} archived)",
+            sessions.len()
+        ));
+    }
+    Ok(sessions.swap_remove(n - 1))
+}
+
+/// Print the list and return the process exit code: 0, or 2 when the
+/// archive can't be read.
+pub fn run(args: &HistoryArgs) -> i32 {
+    let sessions = match archived(args) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("tuicr history: {e}");
+            return 2;
+        }
+    };
+    if sessions.is_empty() {
+        println!("No completed reviews archived for this repository yet.");
+        return 0;
+    }
+    for line in report(&sessions) {
+        println!("{line}");
+    }
+    println!("\nReopen one read-only with `tuicr history <n>`.");
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::FileStatus;
+    use crate::model::review::SessionDiffSource;
+
+    #[test]
+    fn should_number_archived_reviews() {
+        // given
+        let mut session = ReviewSession::new(
+            PathBuf::from("/src/repo"),
+            "abc1234def".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::CommitRange,
+        );
+        session.commit_range = Some(vec!["abc1234def".to_string(), "fed4321cba".to_string()]);
+        session.name = Some("auth rewrite".to_string());
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 0);
+
+        // when
+        let lines = report(&[session]);
+
+        // then
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("  1  "));
+        assert!(
+            lines[0].ends_with("  auth rewrite  2 commits · main @ abc1234 · 1 files · 0 comments")
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Self-review git hooks. `tuicr hook install` writes a pre-commit or pre-push
+//! hook that opens tuicr on what is about to be committed or pushed, and the
+//! `--hook` run it starts exits non-zero until the review is complete, which
+//! makes git abort. `TUICR_SKIP_HOOK=1` bypasses the hook.
+
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::error::{Result, TuicrError};
+
+/// Environment variable that makes an installed hook exit 0 without running.
+pub const SKIP_ENV: &str = "TUICR_SKIP_HOOK";
+
+/// Marks hook scripts written by tuicr, so reinstalling may replace them.
+const HOOK_MARKER: &str = "# Installed by `tuicr hook install`";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    PreCommit,
+    PrePush,
+}
+
+impl HookKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pre-commit" => Some(Self::PreCommit),
+            "pre-push" => Some(Self::PrePush),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PreCommit => "pre-commit",
+            Self::PrePush => "pre-push",
+        }
+    }
+}
+
+/// `tuicr hook install` arguments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookInstall {
+    pub kind: HookKind,
+    /// Replace an existing hook that tuicr did not write
+    pub force: bool,
+}
+
+/// Shell script for `kind`. Git runs hooks without a terminal on stdin, so
+/// tuicr reads keys from `/dev/tty`; the pre-push hook reviews each pushed
+/// ref's new commits.
+pub fn hook_script(kind: HookKind) -> String {
+    let body = match kind {
+        HookKind::PreCommit => "exec tuicr --hook pre-commit < /dev/tty\n".to_string(),
+        HookKind::PrePush => "\
+zero=$(git hash-object --stdin < /dev/null | tr 0-9a-f 0)
+while read -r local_ref local_sha remote_ref remote_sha; do
+    [ \"$local_sha\" = \"$zero\" ] && continue
+    if [ \"$remote_sha\" = \"$zero\" ]; then
+        # New branch: review every commit no remote has yet
+        oldest=$(git rev-list --reverse \"$local_sha\" --not --remotes | head -n 1)
+        [ -z \"$oldest\" ] && continue
+        if git rev-parse -q --verify \"$oldest^\" > /dev/null; then
+            range=\"$oldest^..$local_sha\"
+        else
+            # A new history: start from the empty tree to include its root
+            range=\"$(git hash-object -t tree /dev/null)..$local_sha\"
+        fi
+    else
+        range=\"$remote_sha..$local_sha\"
+    fi
+    tuicr --hook pre-push -r \"$range\" < /dev/tty || exit 1
+done
+"
+        .to_string(),
+    };
+    format!(
+        "#!/bin/sh\n{HOOK_MARKER}: self-review before git {action}.\n\
+         # Set {SKIP_ENV}=1 to skip it.\n\
+         [ -n \"${SKIP_ENV}\" ] && exit 0\n\
+         if ! [ -t 0 ] && ! {{ [ -r /dev/tty ] && (: < /dev/tty) 2> /dev/null; }}; then\n\
+         \x20   echo \"tuicr: no terminal for the self-review; set {SKIP_ENV}=1 to skip it\" >&2\n\
+         \x20   exit 1\n\
+         fi\n\
+         {body}",
+        action = match kind {
+            HookKind::PreCommit => "commit",
+            HookKind::PrePush => "push",
+        }
+    )
+}
+
+/// Write the hook into the hooks directory of the git repository containing
+/// `repo_path` (honouring `core.hooksPath`). Returns the hook's path.
+pub fn install(repo_path: &Path, install: HookInstall) -> Result<PathBuf> {
+    let repo = Repository::discover(repo_path).map_err(|_| TuicrError::NotARepository)?;
+    let hooks_dir = match repo.config()?.get_path("core.hooksPath") {
+        Ok(dir) if dir.is_relative() => repo.workdir().unwrap_or_else(|| repo.path()).join(dir),
+        Ok(dir) => dir,
+        Err(_) => repo.path().join("hooks"),
+    };
+    std::fs::create_dir_all(&hooks_dir)?;
+    let path = hooks_dir.join(install.kind.name());
+    if let Ok(existing) = std::fs::read_to_string(&path)
+        && !existing.contains(HOOK_MARKER)
+        && !install.force
+    {
+        return Err(TuicrError::UnsupportedOperation(format!(
+            "{} already exists; rerun with --force to replace it",
+            path.display()
+        )));
+    }
+    std::fs::write(&path, hook_script(install.kind))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_install_hook_and_refuse_to_overwrite_foreign_hooks() {
+        // given
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        Repository::init(temp_dir.path()).expect("failed to init repo");
+        let hook_path = temp_dir.path().join(".git/hooks/pre-push");
+        std::fs::create_dir_all(hook_path.parent().unwrap()).unwrap();
+        std::fs::write(&hook_path, "#!/bin/sh\nexit 0\n").unwrap();
+        let pre_push = HookInstall {
+            kind: HookKind::PrePush,
+            force: false,
+        };
+
+        // when
+        let commit_hook = install(
+            temp_dir.path(),
+            HookInstall {
+                kind: HookKind::PreCommit,
+                force: false,
+            },
+        )
+        .unwrap();
+        let refused = install(temp_dir.path(), pre_push);
+        let forced = install(
+            temp_dir.path(),
+            HookInstall {
+                force: true,
+                ..pre_push
+            },
+        );
+
+        // then
+        let script = std::fs::read_to_string(&commit_hook).unwrap();
+        assert!(script.contains("tuicr --hook pre-commit"));
+        assert!(script.contains(SKIP_ENV));
+        assert!(matches!(refused, Err(TuicrError::UnsupportedOperation(_))));
+        assert_eq!(forced.unwrap(), temp_dir.path().join(".git/hooks/pre-push"));
+        // A hook tuicr wrote can be reinstalled without --force.
+        assert!(install(temp_dir.path(), pre_push).is_ok());
+    }
+
+    #[test]
+    fn should_write_hooks_that_review_new_branches_and_explain_skipping_without_a_terminal() {
+        for kind in [HookKind::PreCommit, HookKind::PrePush] {
+            // given
+            let script = hook_script(kind);
+
+            // when
+            let syntax = std::process::Command::new("sh")
+                .args(["-n", "-c", &script])
+                .status()
+                .unwrap();
+
+            // then
+            assert!(syntax.success());
+            assert!(script.contains(&format!("set {SKIP_ENV}=1 to skip it\" >&2")));
+        }
+        let pre_push = hook_script(HookKind::PrePush);
+        assert!(pre_push.contains("\"$oldest^..$local_sha\""));
+        assert!(pre_push.contains("\"$(git hash-object -t tree /dev/null)..$local_sha\""));
+    }
+}
@@ -13,12 +13,15 @@ pub enum Action {
     PageUp,
     GoToTop,
     GoToBottom,
+    /// `{count}%` — jump proportionally through the focused panel
+    GoToPercent,
     Digit(u8),
     NextFile,
     PrevFile,
     NextHunk,
     PrevHunk,
     PendingZCommand,
+    PendingGCommand,
     PendingShiftZCommand,
     PendingLeaderCommand,
     ScrollLeft(usize),
@@ -35,10 +38,13 @@ pub enum Action {
 
     // Review actions
     ToggleReviewed,
+    CycleConfidence,
     AddLineComment,
     AddFileComment,
     EditComment,
     PendingDCommand,
+    /// `m`: mark the cursor line to revisit before finishing
+    ToggleDefer,
     SearchNext,
     SearchPrev,
 
@@ -55,6 +61,7 @@ pub enum Action {
     EnterSearchMode,
     ExitMode,
     ToggleHelp,
+    ToggleHelpScope,
 
     // Text input
     InsertChar(char),
@@ -76,6 +83,7 @@ pub enum Action {
     // Comment type
     CycleCommentType,
     CycleCommentTypeReverse,
+    CycleCommentBlocking,
 
     // Confirm dialog
     ConfirmYes,
@@ -86,6 +94,8 @@ pub enum Action {
     CommitSelectDown,
     ToggleCommitSelect,
     ConfirmCommitSelect,
+    /// Switch commit ranges between `A..B` and `A...B` diffing (`m`)
+    ToggleRangeMode,
     /// Cycle inline commit selector to next individual commit (`)`)
     CycleCommitNext,
     /// Cycle inline commit selector to previous individual commit (`(`)
@@ -120,6 +130,34 @@ pub enum Action {
     /// Confirm the picker selection (Enter).
     SubmitPickerConfirm,
 
+    // Startup review chooser
+    /// Move chooser cursor down (`j` / Down).
+    ReviewChooserDown,
+    /// Move chooser cursor up (`k` / Up).
+    ReviewChooserUp,
+    /// Review what the chooser row names (Enter).
+    ReviewChooserConfirm,
+
+    // Completion dialog
+    /// Move completion dialog cursor down (`j` / Down).
+    CompletionDown,
+    /// Move completion dialog cursor up (`k` / Up).
+    CompletionUp,
+    /// Run the action under the cursor (Enter).
+    CompletionConfirm,
+
+    // `:verify` panel
+    /// Move verify cursor down (`j` / Down).
+    VerifyDown,
+    /// Move verify cursor up (`k` / Up).
+    VerifyUp,
+    /// Move the comment to the nearest line still in the diff (`r`).
+    VerifyReanchor,
+    /// Turn the comment into a file comment (`f`).
+    VerifyDetach,
+    /// Delete the comment after confirming (`d`).
+    VerifyDelete,
+
     ToggleExpand,
     ExpandAll,
     CollapseAll,
@@ -129,6 +167,23 @@ pub enum Action {
     None,
 }
 
+impl Action {
+    /// Whether the action changes the open review (comments, review marks),
+    /// so a read-only review refuses it.
+    pub fn edits_session(&self) -> bool {
+        matches!(
+            self,
+            Action::ToggleReviewed
+                | Action::CycleConfidence
+                | Action::AddLineComment
+                | Action::AddFileComment
+                | Action::EditComment
+                | Action::ToggleDefer
+                | Action::AddRangeComment
+        )
+    }
+}
+
 pub fn map_key_to_action(key: KeyEvent, mode: InputMode, leader_key: char) -> Action {
     match mode {
         InputMode::Normal => map_normal_mode(key, leader_key),
@@ -142,6 +197,9 @@ pub fn map_key_to_action(key: KeyEvent, mode: InputMode, leader_key: char) -> Ac
         InputMode::SubmitResolver => map_submit_resolver_mode(key),
         InputMode::SubmitConfirm => map_submit_confirm_mode(key),
         InputMode::SubmitActionPicker => map_submit_action_picker_mode(key),
+        InputMode::ReviewChooser => map_review_chooser_mode(key),
+        InputMode::VerifyPanel => map_verify_panel_mode(key),
+        InputMode::CompletionDialog => map_completion_dialog_mode(key),
     }
 }
 
@@ -162,8 +220,9 @@ fn map_normal_mode(key: KeyEvent, leader_key: char) -> Action {
         (KeyCode::Char('b'), KeyModifiers::CONTROL) => Action::PageUp,
         (KeyCode::PageDown, KeyModifiers::NONE) => Action::PageDown,
         (KeyCode::PageUp, KeyModifiers::NONE) => Action::PageUp,
-        (KeyCode::Char('g'), KeyModifiers::NONE) => Action::GoToTop,
+        (KeyCode::Char('g'), KeyModifiers::NONE) => Action::PendingGCommand,
         (KeyCode::Char('G'), _) => Action::GoToBottom,
+        (KeyCode::Char('%'), _) => Action::GoToPercent,
         (KeyCode::Char('z'), KeyModifiers::NONE) => Action::PendingZCommand,
         (KeyCode::Char('Z'), _) => Action::PendingShiftZCommand,
 
@@ -187,10 +246,12 @@ fn map_normal_mode(key: KeyEvent, leader_key: char) -> Action {
 
         // Review actions
         (KeyCode::Char('r'), KeyModifiers::NONE) => Action::ToggleReviewed,
+        (KeyCode::Char('R'), _) => Action::CycleConfidence,
         (KeyCode::Char('c'), KeyModifiers::NONE) => Action::AddLineComment,
         (KeyCode::Char('C'), _) => Action::AddFileComment,
         (KeyCode::Char('i'), KeyModifiers::NONE) => Action::EditComment,
         (KeyCode::Char('d'), KeyModifiers::NONE) => Action::PendingDCommand,
+        (KeyCode::Char('m'), KeyModifiers::NONE) => Action::ToggleDefer,
         (KeyCode::Char('v') | KeyCode::Char('V'), _) => Action::EnterVisualMode,
         (KeyCode::Char('y'), KeyModifiers::NONE) => Action::ExportToClipboard,
         (KeyCode::Char('n'), KeyModifiers::NONE) => Action::SearchNext,
@@ -256,6 +317,10 @@ fn map_comment_mode(key: KeyEvent) -> Action {
         // Comment type: Tab to cycle
         (KeyCode::Tab, KeyModifiers::NONE) => Action::CycleCommentType,
         (KeyCode::BackTab, _) => Action::CycleCommentTypeReverse,
+        // Blocking flag: Ctrl+B to cycle
+        (KeyCode::Char('b'), KeyModifiers::CONTROL) => Action::CycleCommentBlocking,
+        // `?` is text here, so help is on F1
+        (KeyCode::F(1), _) => Action::ToggleHelp,
         // Cursor movement
         (KeyCode::Char('a'), KeyModifiers::CONTROL) => Action::TextCursorLineStart,
         (KeyCode::Char('e'), KeyModifiers::CONTROL) => Action::TextCursorLineEnd,
@@ -304,7 +369,10 @@ fn map_help_mode(key: KeyEvent) -> Action {
         // Close help
         (KeyCode::Esc, KeyModifiers::NONE)
         | (KeyCode::Char('q'), KeyModifiers::NONE)
-        | (KeyCode::Char('?'), _) => Action::ToggleHelp,
+        | (KeyCode::Char('?'), _)
+        | (KeyCode::F(1), _) => Action::ToggleHelp,
+        // Switch between the focused panel's keys and the full reference
+        (KeyCode::Char('a'), KeyModifiers::NONE) => Action::ToggleHelpScope,
         // Scroll navigation
         (KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => Action::CursorDown(1),
         (KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => Action::CursorUp(1),
@@ -360,17 +428,52 @@ fn map_submit_action_picker_mode(key: KeyEvent) -> Action {
     }
 }
 
+fn map_review_chooser_mode(key: KeyEvent) -> Action {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => Action::ReviewChooserDown,
+        (KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => Action::ReviewChooserUp,
+        (KeyCode::Enter, KeyModifiers::NONE) => Action::ReviewChooserConfirm,
+        (KeyCode::Esc, KeyModifiers::NONE) => Action::ExitMode,
+        (KeyCode::Char('q'), KeyModifiers::NONE) => Action::Quit,
+        _ => Action::None,
+    }
+}
+
+fn map_completion_dialog_mode(key: KeyEvent) -> Action {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => Action::CompletionDown,
+        (KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => Action::CompletionUp,
+        (KeyCode::Enter, KeyModifiers::NONE) => Action::CompletionConfirm,
+        (KeyCode::Esc | KeyCode::Char('q'), KeyModifiers::NONE) => Action::ExitMode,
+        _ => Action::None,
+    }
+}
+
+fn map_verify_panel_mode(key: KeyEvent) -> Action {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => Action::VerifyDown,
+        (KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => Action::VerifyUp,
+        (KeyCode::Char('r'), KeyModifiers::NONE) => Action::VerifyReanchor,
+        (KeyCode::Char('f'), KeyModifiers::NONE) => Action::VerifyDetach,
+        (KeyCode::Char('d'), KeyModifiers::NONE) => Action::VerifyDelete,
+        (KeyCode::Esc | KeyCode::Char('q'), KeyModifiers::NONE) => Action::ExitMode,
+        _ => Action::None,
+    }
+}
+
 fn map_commit_select_mode(key: KeyEvent) -> Action {
     match (key.code, key.modifiers) {
         (KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => Action::CommitSelectDown,
         (KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => Action::CommitSelectUp,
         (KeyCode::Char(' '), KeyModifiers::NONE) => Action::ToggleCommitSelect,
         (KeyCode::Enter, KeyModifiers::NONE) => Action::ConfirmCommitSelect,
+        (KeyCode::Char('m'), KeyModifiers::NONE) => Action::ToggleRangeMode,
         (KeyCode::Esc, KeyModifiers::NONE) => Action::ExitMode,
         (KeyCode::Char('q'), KeyModifiers::NONE) => Action::Quit,
         (KeyCode::Tab, KeyModifiers::NONE) => Action::TargetSelectorTabNext,
         (KeyCode::BackTab, _) => Action::TargetSelectorTabPrev,
         (KeyCode::Char('/'), _) => Action::BeginTargetFilter,
+        (KeyCode::Char('?'), _) => Action::ToggleHelp,
         _ => Action::None,
     }
 }
@@ -402,10 +505,169 @@ fn map_visual_mode(key: KeyEvent) -> Action {
         (KeyCode::Esc, KeyModifiers::NONE) => Action::ExitMode,
         (KeyCode::Char('v') | KeyCode::Char('V'), _) => Action::ExitMode,
         (KeyCode::Char('q'), KeyModifiers::NONE) => Action::Quit,
+        (KeyCode::Char('?'), _) => Action::ToggleHelp,
         _ => Action::None,
     }
 }
 
+/// Where help was opened from. `?` shows only the keys of this context;
+/// `a` in the popup switches to the full reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpContext {
+    FileList,
+    Diff,
+    /// The inline commit selector above the diff
+    CommitSelector,
+    VisualSelect,
+    CommentEditor,
+    /// The review target selector (local commits / pull requests)
+    TargetSelector,
+}
+
+impl HelpContext {
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::FileList => "File list",
+            Self::Diff => "Diff",
+            Self::CommitSelector => "Commit selector",
+            Self::VisualSelect => "Visual mode",
+            Self::CommentEditor => "Comment editor",
+            Self::TargetSelector => "Review target selector",
+        }
+    }
+}
+
+/// One row of the context help: the keys, what they do and the contexts
+/// they apply in. `<leader>` stands for the configured leader key.
+pub struct KeyHelp {
+    pub keys: &'static str,
+    pub description: &'static str,
+    pub contexts: &'static [HelpContext],
+}
+
+const fn help(
+    keys: &'static str,
+    description: &'static str,
+    contexts: &'static [HelpContext],
+) -> KeyHelp {
+    KeyHelp {
+        keys,
+        description,
+        contexts,
+    }
+}
+
+const PANELS: &[HelpContext] = &[
+    HelpContext::FileList,
+    HelpContext::Diff,
+    HelpContext::CommitSelector,
+];
+const LISTS: &[HelpContext] = &[HelpContext::FileList, HelpContext::Diff];
+const DIFF: &[HelpContext] = &[HelpContext::Diff];
+const FILE_LIST: &[HelpContext] = &[HelpContext::FileList];
+const COMMIT_SELECTOR: &[HelpContext] = &[HelpContext::CommitSelector];
+const VISUAL: &[HelpContext] = &[HelpContext::VisualSelect];
+const EDITOR: &[HelpContext] = &[HelpContext::CommentEditor];
+const TARGETS: &[HelpContext] = &[HelpContext::TargetSelector];
+
+/// The keys of the modes above, in the order the context help lists them.
+/// Kept next to the key maps so a changed binding is updated in both.
+pub const KEY_HELP: &[KeyHelp] = &[
+    help("j/k", "Scroll down/up", DIFF),
+    help("j/k", "Select next/previous file", FILE_LIST),
+    help("j/k", "Navigate commits", COMMIT_SELECTOR),
+    help("Ctrl-e/y", "Scroll view down/up", DIFF),
+    help("Ctrl-d/u", "Half page down/up", LISTS),
+    help("Ctrl-f/b", "Full page down/up", LISTS),
+    help("gg/G", "Go to top/bottom of focused panel", LISTS),
+    help("{N}G", "Go to source line N in current file", DIFF),
+    help("{N}%", "Go to N% of the focused panel", LISTS),
+    help("{/}", "Jump to prev/next file", LISTS),
+    help("[/]", "Jump to prev/next hunk", DIFF),
+    help("[d/]d", "Jump to prev/next deferred line", DIFF),
+    help("h/l", "Scroll left/right", DIFF),
+    help("/", "Search within diff", DIFF),
+    help("/", "Search file names", FILE_LIST),
+    help("n/N", "Next/prev search match", LISTS),
+    help("Enter", "Expand/collapse context (20 lines)", DIFF),
+    help("S-Enter", "Expand/collapse all hidden context", DIFF),
+    help("Space", "Toggle expand directory", FILE_LIST),
+    help("Enter", "Expand dir / Jump to file", FILE_LIST),
+    help("o/O", "Expand/collapse all directories", FILE_LIST),
+    help(
+        "Space/Enter",
+        "Toggle commit selection (updates diff)",
+        COMMIT_SELECTOR,
+    ),
+    help(
+        "(/)",
+        "Cycle through individual commits",
+        &[HelpContext::Diff, HelpContext::CommitSelector],
+    ),
+    help("Esc", "Return focus to diff", COMMIT_SELECTOR),
+    help("r", "Toggle file reviewed", LISTS),
+    help(
+        "R",
+        "Cycle review confidence (skimmed/reviewed/thorough)",
+        LISTS,
+    ),
+    help("c", "Add line comment", DIFF),
+    help("C", "Add file comment", LISTS),
+    help("<leader>c", "Add review comment", PANELS),
+    help("i", "Edit comment at cursor", DIFF),
+    help("dd", "Delete comment at cursor", DIFF),
+    help("m", "Defer line (revisit before finishing)", DIFF),
+    help("v/V", "Enter visual mode for range comments", DIFF),
+    help(
+        "y",
+        "Yank: mouse selection if any, else review to clipboard",
+        LISTS,
+    ),
+    help("<leader>y", "Copy hunk as Markdown", DIFF),
+    help("<leader>d", "Cycle hunk word/char diff", DIFF),
+    help("Tab/S-Tab", "Toggle focus next/previous panel", PANELS),
+    help("<leader>h/<leader>l", "Focus file list/diff", PANELS),
+    help("<leader>k/<leader>j", "Focus commit selector/diff", PANELS),
+    help("<leader>e", "Toggle file list visibility", PANELS),
+    help(
+        "gt/gT",
+        "Next/previous view (diff, comments, overview, sessions)",
+        PANELS,
+    ),
+    help(":", "Enter a command", PANELS),
+    help("q", "Quit", PANELS),
+    help("j/k", "Extend selection down/up", VISUAL),
+    help("c/Enter", "Create comment for selected range", VISUAL),
+    help("y", "Copy selected lines", VISUAL),
+    help("Esc/v/V", "Cancel visual selection", VISUAL),
+    help("Tab/S-Tab", "Cycle comment type next/previous", EDITOR),
+    help("Ctrl-b", "Cycle blocking / non-blocking flag", EDITOR),
+    help("Enter/Ctrl-S", "Save comment", EDITOR),
+    help("Shift-Enter/Ctrl-J", "Insert newline", EDITOR),
+    help("Ctrl-A/E", "Line start/end", EDITOR),
+    help("Ctrl/Alt-Left/Right", "Word left/right", EDITOR),
+    help("Ctrl-W/Ctrl-U", "Delete word / clear line", EDITOR),
+    help("Esc/Ctrl-C", "Cancel", EDITOR),
+    help("Tab/S-Tab", "Switch Local / Pull Requests tab", TARGETS),
+    help("j/k", "Move row", TARGETS),
+    help(
+        "Space",
+        "Toggle local commit selection (no-op on PR tab)",
+        TARGETS,
+    ),
+    help("Enter", "Open selected target or load more", TARGETS),
+    help("m", "Toggle A..B / A...B (merge-base) range diff", TARGETS),
+    help("/", "Local filter for current tab", TARGETS),
+    help("Esc/q", "Quit / return", TARGETS),
+];
+
+/// The rows of [`KEY_HELP`] that apply in `context`.
+pub fn key_help(context: HelpContext) -> impl Iterator<Item = &'static KeyHelp> {
+    KEY_HELP
+        .iter()
+        .filter(move |entry| entry.contexts.contains(&context))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -433,6 +695,12 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_map_uppercase_r_to_cycle_confidence_in_normal_mode() {
+        let action = map_normal_mode(key_shift('R'), DEFAULT_LEADER_KEY);
+        assert_eq!(action, Action::CycleConfidence);
+    }
+
     #[test]
     fn should_map_uppercase_g_to_go_to_bottom_in_normal_mode() {
         let action = map_normal_mode(key_shift('G'), DEFAULT_LEADER_KEY);
@@ -440,9 +708,16 @@ mod tests {
     }
 
     #[test]
-    fn should_map_lowercase_g_to_go_to_top_in_normal_mode() {
+    fn should_map_lowercase_g_to_pending_g_in_normal_mode() {
+        // `gg` is resolved by the event loop once the second key arrives.
         let action = map_normal_mode(key(KeyCode::Char('g')), DEFAULT_LEADER_KEY);
-        assert_eq!(action, Action::GoToTop);
+        assert_eq!(action, Action::PendingGCommand);
+    }
+
+    #[test]
+    fn should_map_percent_to_go_to_percent_in_normal_mode() {
+        let action = map_normal_mode(key_shift('%'), DEFAULT_LEADER_KEY);
+        assert_eq!(action, Action::GoToPercent);
     }
 
     #[test]
@@ -502,6 +777,12 @@ mod tests {
         assert_eq!(action, Action::CycleCommentTypeReverse);
     }
 
+    #[test]
+    fn should_map_ctrl_b_to_blocking_flag_in_comment_mode() {
+        let action = map_comment_mode(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL));
+        assert_eq!(action, Action::CycleCommentBlocking);
+    }
+
     #[test]
     fn should_map_configured_leader_to_pending_leader_action() {
         let action = map_key_to_action(key(KeyCode::Char(',')), InputMode::Normal, ',');
@@ -631,4 +912,28 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn should_list_only_the_keys_of_the_help_context() {
+        // when
+        let editor: Vec<&str> = key_help(HelpContext::CommentEditor)
+            .map(|entry| entry.keys)
+            .collect();
+        let file_list: Vec<&str> = key_help(HelpContext::FileList)
+            .map(|entry| entry.description)
+            .collect();
+
+        // then
+        assert!(editor.contains(&"Ctrl-b"));
+        assert!(!editor.contains(&"dd"));
+        assert!(file_list.contains(&"Expand dir / Jump to file"));
+        assert!(!file_list.contains(&"Add line comment"));
+    }
+
+    #[test]
+    fn should_open_help_from_comment_editor_with_f1() {
+        let action = map_comment_mode(key(KeyCode::F(1)));
+        assert_eq!(action, Action::ToggleHelp);
+        assert_eq!(map_comment_mode(key_shift('?')), Action::InsertChar('?'));
+    }
 }
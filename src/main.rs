@@ -1,25 +1,39 @@
+mod ansi;
 mod app;
+mod check;
 mod config;
+mod diff_filter;
+mod doctor;
 mod error;
+mod filetypes;
 mod forge;
 mod handler;
 mod hash;
+mod history;
+mod hook;
 mod input;
 mod model;
+mod notify;
 mod output;
 mod persistence;
 mod process;
 mod profile;
+mod sessions;
+mod stats;
 mod syntax;
 mod text_edit;
 mod theme;
+mod todos;
 mod tuicrignore;
 mod ui;
 mod update;
 mod vcs;
+mod view;
 
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
@@ -37,17 +51,18 @@ use crossterm::{
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
 
-use app::{App, AppStartupOptions, FocusedPanel, InputMode};
+use app::{App, AppStartupOptions, FocusedPanel, InputMode, View};
 use handler::{
     handle_command_action, handle_comment_action, handle_commit_select_action,
-    handle_commit_selector_action, handle_confirm_action, handle_diff_action,
-    handle_file_list_action, handle_help_action, handle_mouse_event, handle_search_action,
-    handle_submit_action_picker_action, handle_submit_confirm_action,
-    handle_submit_resolver_action, handle_visual_action,
+    handle_commit_selector_action, handle_completion_dialog_action, handle_confirm_action,
+    handle_diff_action, handle_file_list_action, handle_help_action, handle_mouse_event,
+    handle_review_chooser_action, handle_search_action, handle_submit_action_picker_action,
+    handle_submit_confirm_action, handle_submit_resolver_action, handle_verify_panel_action,
+    handle_view_action, handle_visual_action,
 };
 use input::{Action, map_key_to_action, map_target_filter_mode};
 use theme::{parse_cli_args, resolve_theme_with_config};
-use vcs::GitBackendPreference;
+use vcs::{GitBackendPreference, RenameDetection};
 
 /// Timeout for the "press Ctrl+C again to exit" feature
 const CTRL_C_EXIT_TIMEOUT: Duration = Duration::from_secs(2);
@@ -72,6 +87,68 @@ fn main() -> anyhow::Result<()> {
     // This also configures syntax highlighting colors before diff parsing
     let mut cli_args = profile::time("startup.parse_cli_args", parse_cli_args);
 
+    if let Some(install) = cli_args.hook_install {
+        match hook::install(&std::env::current_dir()?, install) {
+            Ok(path) => {
+                println!(
+                    "Installed {} hook at {}",
+                    install.kind.name(),
+                    path.display()
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(check) = &cli_args.check {
+        std::process::exit(check::run(check));
+    }
+    if cli_args.doctor {
+        std::process::exit(doctor::run());
+    }
+    if let Some(stats) = &cli_args.stats {
+        std::process::exit(stats::run(stats));
+    }
+    if let Some(gc) = &cli_args.sessions_gc {
+        std::process::exit(sessions::run_gc(gc));
+    }
+    let archived_review = match &cli_args.history {
+        Some(history) => match history.open {
+            None => std::process::exit(history::run(history)),
+            Some(n) => match history::find(history, n) {
+                Ok(session) => Some(session),
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    std::process::exit(2);
+                }
+            },
+        },
+        None => None,
+    };
+    let viewed_review = match &cli_args.view {
+        Some(path) => match view::load(path) {
+            Ok(session) => Some((path.clone(), session)),
+            Err(e) => {
+                eprintln!("Error: failed to read {}: {e}", path.display());
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+    let hook = cli_args.hook;
+    if hook.is_some() {
+        if std::env::var_os(hook::SKIP_ENV).is_some() {
+            return Ok(());
+        }
+        // A pre-commit hook reviews what is about to be committed.
+        if hook == Some(hook::HookKind::PreCommit) && cli_args.revisions.is_none() {
+            cli_args.working_tree = true;
+        }
+    }
+
     // Check keyboard enhancement support before enabling raw mode.
     // Skip when --stdout is used because the probe writes escape sequences to stdout,
     // which would leak into the captured export output.
@@ -97,8 +174,80 @@ fn main() -> anyhow::Result<()> {
         }
     }
 
-    // --path implies --working-tree unless -r is explicitly provided
-    if cli_args.path_filter.is_some() && !cli_args.working_tree && cli_args.revisions.is_none() {
+    // --revset selects the whole review on its own
+    if cli_args.revset.is_some() {
+        let conflict = if cli_args.revisions.is_some() {
+            Some("-r/--revisions")
+        } else if cli_args.working_tree {
+            Some("-w/--working-tree")
+        } else if cli_args.file_path.is_some() {
+            Some("--file")
+        } else if cli_args.pr_target.is_some() {
+            Some("tuicr pr")
+        } else {
+            None
+        };
+        if let Some(flag) = conflict {
+            eprintln!("Error: --revset cannot be combined with {flag}");
+            std::process::exit(2);
+        }
+    }
+
+    // --interdiff compares the -r range against an older version of it
+    if cli_args.interdiff.is_some() {
+        if cli_args.revisions.is_none() {
+            eprintln!("Error: --interdiff requires -r/--revisions for the new version");
+            std::process::exit(2);
+        }
+        if cli_args.working_tree {
+            eprintln!("Error: --interdiff cannot be combined with -w/--working-tree");
+            std::process::exit(2);
+        }
+    }
+
+    // A diff piped on stdin (`git diff | tuicr`) is reviewed as a patch when
+    // nothing else selects what to review.
+    if cli_args.patch_path.is_none()
+        && cli_args.file_path.is_none()
+        && cli_args.revisions.is_none()
+        && cli_args.revset.is_none()
+        && cli_args.pr_target.is_none()
+        && cli_args.view.is_none()
+        && cli_args.history.is_none()
+        && !cli_args.working_tree
+        && stdin_has_patch()
+    {
+        cli_args.patch_path = Some("-".to_string());
+    }
+
+    // --patch is mutually exclusive with --file, -r, -w, and `tuicr pr`
+    if cli_args.patch_path.is_some() {
+        let conflict = if cli_args.file_path.is_some() {
+            Some("--file")
+        } else if cli_args.revisions.is_some() {
+            Some("-r/--revisions")
+        } else if cli_args.revset.is_some() {
+            Some("--revset")
+        } else if cli_args.working_tree {
+            Some("-w/--working-tree")
+        } else if cli_args.pr_target.is_some() {
+            Some("tuicr pr")
+        } else {
+            None
+        };
+        if let Some(flag) = conflict {
+            eprintln!("Error: --patch cannot be combined with {flag}");
+            std::process::exit(2);
+        }
+    }
+
+    // --path implies --working-tree unless -r or --revset is explicitly provided
+    if cli_args.path_filter.is_some()
+        && !cli_args.working_tree
+        && cli_args.revisions.is_none()
+        && cli_args.revset.is_none()
+        && cli_args.patch_path.is_none()
+    {
         cli_args.working_tree = true;
     }
     let mut startup_warnings = Vec::new();
@@ -110,6 +259,104 @@ fn main() -> anyhow::Result<()> {
         }
     });
     startup_warnings.extend(config_outcome.warnings);
+
+    let template = match cli_args.template.as_deref() {
+        Some(name) => match config::find_template(config_outcome.config.as_ref(), name) {
+            Ok(template) => Some((name.to_string(), template.clone())),
+            Err(err) => {
+                eprintln!("Error: {err}");
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+    let diff_filter = cli_args.pipe_to.as_deref().map(|command| {
+        diff_filter::DiffFilter::parse(command).unwrap_or_else(|| {
+            eprintln!("Error: --pipe-to command has an unclosed quote: {command}");
+            std::process::exit(2);
+        })
+    });
+    // Startup diffing already runs external commands, so the timeout has to
+    // be in place before the app is built.
+    if let Some(secs) = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.command_timeout)
+    {
+        process::set_command_timeout((secs > 0).then(|| Duration::from_secs(secs as u64)));
+    }
+    // Tabs are expanded and lines highlighted while the startup diff is
+    // parsed, so the filetype rules must be in place before that too.
+    let filetype_rules = filetypes::FiletypeRules::new(
+        config_outcome
+            .config
+            .as_ref()
+            .map(|cfg| cfg.filetypes.clone())
+            .unwrap_or_default(),
+    );
+    filetypes::install(filetype_rules.clone());
+    // The startup diff already reads and updates the rename cache.
+    if let Ok(path) = vcs::git::rename_cache::rename_cache_path() {
+        vcs::git::rename_cache::install(path);
+    }
+    if let Some(store) = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.session_store.as_deref())
+    {
+        persistence::storage::set_sqlite_store(store == "sqlite");
+    }
+    if let Some(days) = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.session_max_age_days)
+    {
+        persistence::storage::set_session_max_age_days(days as u64);
+    }
+    let session_location = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.session_location.as_deref())
+        .and_then(persistence::location::SessionLocation::from_id)
+        .unwrap_or(persistence::location::SessionLocation::Data);
+    if let Ok(cwd) = std::env::current_dir() {
+        match persistence::location::select(session_location, &cwd) {
+            Ok((dir, moved)) if moved > 0 => startup_warnings.push(format!(
+                "Moved {moved} saved review{} to {}",
+                if moved == 1 { "" } else { "s" },
+                dir.display()
+            )),
+            Ok(_) => {}
+            Err(e) => startup_warnings.push(format!(
+                "Can't keep sessions at session_location = \"{}\" ({e}); using the data directory",
+                session_location.id()
+            )),
+        }
+    }
+    if let Ok(dir) = persistence::storage::get_reviews_dir() {
+        let recovered = persistence::journal::recover(&dir);
+        if recovered > 0 {
+            startup_warnings.push(format!(
+                "Recovered {recovered} unsaved change{} from a tuicr that exited without saving",
+                if recovered == 1 { "" } else { "s" }
+            ));
+        }
+    }
+    // A template's path filter behaves like --path, but an explicit flag wins.
+    if let Some((_, template)) = template.as_ref()
+        && cli_args.path_filter.is_none()
+        && cli_args.file_path.is_none()
+        && let Some(path) = template.path.clone()
+    {
+        cli_args.path_filter = Some(path);
+        if !cli_args.working_tree
+            && cli_args.revisions.is_none()
+            && cli_args.revset.is_none()
+            && cli_args.patch_path.is_none()
+        {
+            cli_args.working_tree = true;
+        }
+    }
     let (mut theme, theme_warnings) = profile::time("startup.resolve_theme", || {
         resolve_theme_with_config(
             cli_args.theme,
@@ -155,59 +402,24 @@ fn main() -> anyhow::Result<()> {
         None
     };
 
-    // Initialize app
     let git_backend_preference = GitBackendPreference::from_config(
         config_outcome
             .config
             .as_ref()
             .and_then(|cfg| cfg.backend.as_deref()),
     );
-
-    let mut app = match profile::time("startup.app_init", || {
-        App::new(
-            theme,
-            config_outcome
-                .config
-                .as_ref()
-                .and_then(|cfg| cfg.comment_types.clone()),
-            cli_args.output_to_stdout,
-            AppStartupOptions {
-                revisions: cli_args.revisions.as_deref(),
-                working_tree: cli_args.working_tree,
-                path_filter: cli_args.path_filter.as_deref(),
-                file_path: cli_args.file_path.as_deref(),
-                git_backend_preference,
-                pr_target: cli_args.pr_target.as_deref(),
-            },
-        )
-    }) {
-        Ok(mut app) => {
-            app.supports_keyboard_enhancement = keyboard_enhancement_supported;
-            startup_warnings.extend(app.vcs.startup_warnings());
-            if let Some(cfg) = config_outcome.config.as_ref() {
-                if let Some(forge_cfg) = cfg.forge.clone() {
-                    app.forge_config = forge_cfg;
-                }
-                if let Some(leader) = cfg.leader {
-                    app.leader_key = leader;
-                }
-            }
-            app
-        }
-        Err(e) => {
-            eprintln!("Error: {e}");
-            // The "you need to be in a git repo" hint is only meaningful
-            // when the failure was the absence of a repo. For other
-            // startup errors — `tuicr pr <bad-url>`, forge auth issues,
-            // missing PR, `--file <missing-path>` — the hint is wrong.
-            if matches!(e, crate::error::TuicrError::NotARepository) {
-                eprintln!(
-                    "\nMake sure you're in a git, jujutsu, or mercurial repository with commits or staged/unstaged changes."
-                );
-            }
-            std::process::exit(1);
-        }
-    };
+    let rename_detection = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.renames.as_ref())
+        .map(|renames| RenameDetection {
+            enabled: renames.enabled,
+            copies: renames.copies,
+            break_rewrites: renames.break_rewrites,
+            threshold: renames.threshold,
+            limit: renames.limit,
+        })
+        .unwrap_or_default();
 
     // Setup terminal
     // When --stdout is used, render TUI to /dev/tty so stdout is free for export output
@@ -243,6 +455,119 @@ fn main() -> anyhow::Result<()> {
     let backend = CrosstermBackend::new(tty_output);
     let mut terminal = Terminal::new(backend)?;
 
+    // Initialize app
+    let startup_styles = ui::startup::StartupStyles {
+        panel: ui::styles::panel_style(&theme),
+        dim: ui::styles::dim_style(&theme),
+    };
+    let comment_types = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.comment_types.clone());
+    let (progress_tx, progress_rx) = mpsc::channel();
+    let startup_options = AppStartupOptions {
+        revisions: cli_args.revisions.as_deref(),
+        revset: cli_args.revset.as_deref(),
+        interdiff: cli_args.interdiff.as_deref(),
+        working_tree: cli_args.working_tree,
+        path_filter: cli_args.path_filter.as_deref(),
+        file_path: cli_args.file_path.as_deref(),
+        patch_path: cli_args.patch_path.as_deref(),
+        git_backend_preference,
+        rename_detection,
+        pr_target: cli_args.pr_target.as_deref(),
+        progress: Some(progress_tx),
+    };
+    let output_to_stdout = cli_args.output_to_stdout;
+
+    // Discovery and diffing can take a while in huge repositories, so build
+    // the app on a background thread and show a loading screen meanwhile.
+    // Keys typed while it loads are handled once the review is up.
+    let mut typed_ahead = VecDeque::new();
+    let app_result = std::thread::scope(|scope| -> anyhow::Result<_> {
+        let handle = scope.spawn(move || {
+            profile::time("startup.app_init", || {
+                App::new(theme, comment_types, output_to_stdout, startup_options)
+            })
+        });
+
+        let started = Instant::now();
+        let mut stage = app::StartupStage::DiscoveringRepository;
+        while !handle.is_finished() {
+            while let Ok(next) = progress_rx.try_recv() {
+                stage = next;
+            }
+            terminal.draw(|frame| {
+                ui::startup::render_startup(frame, startup_styles, stage, started.elapsed());
+            })?;
+            // Ctrl+C is delivered as a key event in raw mode, so honor it
+            // (and `q`) here since the main loop isn't running yet.
+            if event::poll(Duration::from_millis(50))?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                if key.code == crossterm::event::KeyCode::Char('q')
+                    || (key.code == crossterm::event::KeyCode::Char('c')
+                        && key
+                            .modifiers
+                            .contains(crossterm::event::KeyModifiers::CONTROL))
+                {
+                    restore_terminal(&mut terminal, mouse_enabled)?;
+                    std::process::exit(130);
+                }
+                typed_ahead.push_back(Event::Key(key));
+            }
+        }
+
+        match handle.join() {
+            Ok(result) => Ok(result),
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    })?;
+
+    let mut app = match app_result {
+        Ok(mut app) => {
+            app.supports_keyboard_enhancement = keyboard_enhancement_supported;
+            app.safe_mode = cli_args.safe;
+            app.git_backend_preference = git_backend_preference;
+            startup_warnings.extend(app.vcs.startup_warnings());
+            if let Some(cfg) = config_outcome.config.as_ref() {
+                if let Some(forge_cfg) = cfg.forge.clone() {
+                    app.forge_config = forge_cfg;
+                }
+                if let Some(bitbucket_cfg) = cfg.bitbucket.clone() {
+                    app.bitbucket_config = bitbucket_cfg;
+                }
+                if let Some(notify_cfg) = cfg.notify.clone() {
+                    app.notify_config = notify_cfg;
+                }
+                if let Some(leader) = cfg.leader {
+                    app.leader_key = leader;
+                }
+            }
+            app
+        }
+        Err(crate::error::TuicrError::NoChanges) if hook.is_some() => {
+            // Nothing to review, nothing to block.
+            restore_terminal(&mut terminal, mouse_enabled)?;
+            return Ok(());
+        }
+        Err(e) => {
+            restore_terminal(&mut terminal, mouse_enabled)?;
+            eprintln!("Error: {e}");
+            // The "you need to be in a git repo" hint is only meaningful
+            // when the failure was the absence of a repo. For other
+            // startup errors — `tuicr pr <bad-url>`, forge auth issues,
+            // missing PR, `--file <missing-path>` — the hint is wrong.
+            if matches!(e, crate::error::TuicrError::NotARepository) {
+                eprintln!(
+                    "\nMake sure you're in a git, jujutsu, or mercurial repository with commits or staged/unstaged changes."
+                );
+            }
+            std::process::exit(1);
+        }
+    };
+
     // Apply config-driven defaults
     if let Some(ref cfg) = config_outcome.config {
         if cfg.show_file_list == Some(false) {
@@ -258,12 +583,123 @@ fn main() -> anyhow::Result<()> {
         if cfg.export_legend == Some(false) {
             app.export_legend = false;
         }
+        if cfg.conventional_comments == Some(true) {
+            app.conventional_comments = true;
+        }
+        if cfg.completion_dialog == Some(false) {
+            app.completion_dialog = false;
+        }
         if cfg.cursor_line == Some(false) {
             app.cursor_line_highlight = false;
         }
         if let Some(scroll_offset) = cfg.scroll_offset {
             app.scroll_offset = scroll_offset;
         }
+        if let Some(elide_context) = cfg.elide_context {
+            app.elide_context = elide_context;
+            app.rebuild_annotations();
+        }
+        if !cfg.filetypes.is_empty() {
+            app.filetypes = filetype_rules;
+            app.rebuild_annotations();
+        }
+        if let Some(notes_ref) = cfg.notes_ref.clone() {
+            app.notes_ref = Some(notes_ref);
+            app.load_session_from_notes();
+        }
+        if let Some(template) = cfg.export_template.as_deref() {
+            let home = std::env::var_os("HOME").map(PathBuf::from);
+            app.export_template = Some(match (template.strip_prefix("~/"), home) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(template),
+            });
+        }
+        if let Some(webhook) = cfg.chat_webhook.clone() {
+            app.chat_webhook = Some(webhook);
+        }
+        if let Some(secs) = cfg.autosave.filter(|secs| *secs > 0) {
+            app.autosave_delay = Some(Duration::from_secs(secs as u64));
+        }
+        if cfg.findings_db == Some(true) {
+            match persistence::findings::findings_db_path() {
+                Ok(path) => app.enable_findings_db(path),
+                Err(e) => app.set_warning(format!("Failed to locate findings database: {e}")),
+            }
+        }
+        if cfg.review_metrics == Some(true) {
+            match persistence::metrics::metrics_path() {
+                Ok(path) => app.review_metrics = Some(path),
+                Err(e) => app.set_warning(format!("Failed to locate review metrics: {e}")),
+            }
+        }
+    }
+    app.reviewer = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.reviewer.clone())
+        .or_else(|| app.vcs.user_identity());
+    if app.session.reviewer.is_none() {
+        app.session.reviewer.clone_from(&app.reviewer);
+    }
+    let history_comments = config_outcome
+        .config
+        .as_ref()
+        .and_then(|cfg| cfg.history_comments);
+    if history_comments != Some(false) {
+        app.load_history_comments();
+    }
+    if hook == Some(hook::HookKind::PreCommit)
+        && let Err(e) = app.load_staged_selection()
+    {
+        app.set_warning(format!("Failed to load staged changes: {e}"));
+    }
+    if let Some((name, template)) = template {
+        if let Some(export_legend) = template.export_legend {
+            app.export_legend = export_legend;
+        }
+        app.export_format = template.export_format.clone();
+        if app.session.apply_template(&name, &template.checklist) {
+            app.mark_dirty();
+        }
+    }
+    match persistence::archive::archive_dir() {
+        Ok(dir) => app.archive_dir = Some(dir),
+        Err(e) => app.set_warning(format!("Failed to locate the review archive: {e}")),
+    }
+    match persistence::storage::get_reviews_dir() {
+        Ok(dir) => app.journal_dir = Some(dir),
+        Err(e) => app.set_warning(format!("Failed to locate the recovery journal: {e}")),
+    }
+    if cli_args.readonly {
+        app.read_only = true;
+        app.dirty = false;
+    }
+    if let Some(archived) = archived_review {
+        if let Err(e) = app.open_archived_review(archived) {
+            restore_terminal(&mut terminal, mouse_enabled)?;
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    } else if let Some((path, session)) = viewed_review {
+        if let Err(e) = app.open_read_only(session) {
+            restore_terminal(&mut terminal, mouse_enabled)?;
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        app.set_message(format!("Viewing {} (read-only)", path.display()));
+    } else {
+        if !cli_args.readonly {
+            app.claim_session_lock();
+        }
+        app.offer_resume();
+    }
+    match persistence::last_source::last_sources_path() {
+        Ok(path) => app.enable_last_source(path),
+        Err(e) => app.set_warning(format!("Failed to locate last review source: {e}")),
+    }
+    if let Some(filter) = diff_filter {
+        app.diff_filter = Some(filter);
+        app.rebuild_annotations();
     }
 
     // On narrow terminals, start with only the diff panel visible.
@@ -280,10 +716,15 @@ fn main() -> anyhow::Result<()> {
 
     // Track pending z command for zz centering
     let mut pending_z = false;
+    // Track pending g for gg (go to top)
+    let mut pending_g = false;
     // Track pending Z command for ZZ export+quit / ZQ quit
     let mut pending_shift_z = false;
     // Track pending d command for dd delete
     let mut pending_d = false;
+    // `]`/`[` jump a hunk at once; a following `d` makes it `]d`/`[d` instead,
+    // going back to the (cursor, scroll) the hunk jump started from
+    let mut pending_bracket: Option<(bool, usize, usize)> = None;
     // Track pending leader command for leader-prefixed actions.
     let mut pending_leader = false;
     // Track pending Ctrl+C for "press twice to exit" (with timestamp for 2s timeout)
@@ -313,18 +754,31 @@ fn main() -> anyhow::Result<()> {
         app.poll_pr_load_events();
         app.poll_pr_open_events();
         app.poll_pr_reload_events();
+        app.poll_vcs_reload_events();
         app.poll_pr_range_reload_events();
         app.poll_pr_threads_events();
         app.poll_pr_submit_events();
+        app.poll_discussion_post_events();
+        app.poll_autosave();
 
         // Render
         terminal.draw(|frame| {
             ui::render(frame, &mut app);
         })?;
+        if let Some((method, message)) = app.pending_notification.take() {
+            notify::emit(method, &message);
+        }
 
-        // Handle events
-        if event::poll(Duration::from_millis(100))? {
-            let event = event::read()?;
+        // Handle events, starting with any typed while the review loaded
+        let event = match typed_ahead.pop_front() {
+            Some(event) => Some(event),
+            None if event::poll(Duration::from_millis(100))? => Some(event::read()?),
+            None => None,
+        };
+        if let Some(event) = event {
+            if matches!(event, Event::Key(_) | Event::Mouse(_) | Event::Paste(_)) {
+                app.track_activity(Instant::now());
+            }
             match event {
                 Event::Key(key) if key.kind == KeyEventKind::Press => {
                     // Handle Ctrl+C twice to exit (works across all input modes)
@@ -358,6 +812,15 @@ fn main() -> anyhow::Result<()> {
                         app.message = None;
                     }
 
+                    // Esc during a local reload kills the command it waits on;
+                    // the worker then reports the cancellation.
+                    if key.code == crossterm::event::KeyCode::Esc
+                        && app.input_mode == InputMode::Normal
+                        && app.cancel_vcs_reload()
+                    {
+                        continue;
+                    }
+
                     // Handle pending z command for zz/zt/zb viewport positioning
                     if pending_z {
                         pending_z = false;
@@ -378,15 +841,49 @@ fn main() -> anyhow::Result<()> {
                         }
                     }
 
+                    // Handle pending g command for gg ({count}gg jumps to a source line)
+                    if pending_g {
+                        pending_g = false;
+                        if key.code == crossterm::event::KeyCode::Char('g') {
+                            match app.pending_count.take() {
+                                Some(count) if app.view == View::Diff => {
+                                    app.go_to_source_line(
+                                        count.max(1) as u32,
+                                        crate::model::LineSide::New,
+                                    );
+                                }
+                                _ => dispatch_action(&mut app, Action::GoToTop),
+                            }
+                            continue;
+                        }
+                        // gt/gT: cycle through the top-level views
+                        if app.input_mode == InputMode::Normal {
+                            match key.code {
+                                crossterm::event::KeyCode::Char('t') => {
+                                    app.pending_count = None;
+                                    app.next_view();
+                                    continue;
+                                }
+                                crossterm::event::KeyCode::Char('T') => {
+                                    app.pending_count = None;
+                                    app.prev_view();
+                                    continue;
+                                }
+                                _ => {}
+                            }
+                        }
+                        app.pending_count = None;
+                        // Otherwise fall through to normal handling
+                    }
+
                     // Handle pending Z command for ZZ (export+quit) / ZQ (quit)
                     if pending_shift_z {
                         pending_shift_z = false;
                         match key.code {
                             crossterm::event::KeyCode::Char('Z') => {
                                 // ZZ: save session, export, and quit (same as :wq)
-                                let _ = persistence::save_session(&app.session);
-                                app.dirty = false;
-                                if app.session.has_comments() {
+                                let _ = app.write_session();
+                                if app.session.has_exportable_content() {
                                     handler::handle_export_and_quit(&mut app);
                                 } else {
                                     app.should_quit = true;
@@ -406,7 +903,15 @@ fn main() -> anyhow::Result<()> {
                     if pending_d {
                         pending_d = false;
                         if key.code == crossterm::event::KeyCode::Char('d') {
-                            if app.cursor_on_locked_comment() {
+                            if !app.ensure_session_writable() {
+                                // A read-only review deletes nothing.
+                            } else if app.view == View::Comments {
+                                app.confirm_delete_view_comment();
+                            } else if app.view == View::Sessions {
+                                app.confirm_delete_selected_session();
+                            } else if app.view != View::Diff {
+                                app.set_message("Switch to the diff view to delete comments");
+                            } else if app.cursor_on_locked_comment() {
                                 app.set_message(
                                     "Comment already pushed to GitHub — read only in tuicr",
                                 );
@@ -422,6 +927,16 @@ fn main() -> anyhow::Result<()> {
                         // Otherwise fall through to normal handling
                     }
 
+                    // Handle pending ]/[ for ]d/[d deferred-line navigation
+                    if let Some((forward, cursor_line, scroll_offset)) = pending_bracket.take()
+                        && key.code == crossterm::event::KeyCode::Char('d')
+                    {
+                        app.diff_state.cursor_line = cursor_line;
+                        app.diff_state.scroll_offset = scroll_offset;
+                        app.jump_to_deferred(forward);
+                        continue;
+                    }
+
                     // Handle pending leader command for panel focus, file list toggle, and review comments.
                     if pending_leader {
                         pending_leader = false;
@@ -452,6 +967,22 @@ fn main() -> anyhow::Result<()> {
                                 app.enter_review_comment_mode();
                                 continue;
                             }
+                            crossterm::event::KeyCode::Char('w') => {
+                                app.toggle_ignore_all_space();
+                                continue;
+                            }
+                            crossterm::event::KeyCode::Char('u') => {
+                                app.toggle_untracked();
+                                continue;
+                            }
+                            crossterm::event::KeyCode::Char('y') => {
+                                app.copy_hunk_at_cursor();
+                                continue;
+                            }
+                            crossterm::event::KeyCode::Char('d') => {
+                                app.cycle_word_diff_at_cursor();
+                                continue;
+                            }
                             _ => {}
                         }
                         // Otherwise fall through to normal handling
@@ -475,6 +1006,11 @@ fn main() -> anyhow::Result<()> {
                             app.pending_count = None;
                             continue;
                         }
+                        Action::PendingGCommand => {
+                            // Keep any count for `{count}gg`.
+                            pending_g = true;
+                            continue;
+                        }
                         Action::PendingShiftZCommand => {
                             pending_shift_z = true;
                             app.pending_count = None;
@@ -490,6 +1026,17 @@ fn main() -> anyhow::Result<()> {
                             app.pending_count = None;
                             continue;
                         }
+                        Action::NextHunk | Action::PrevHunk
+                            if app.input_mode == InputMode::Normal
+                                && app.view == View::Diff
+                                && app.focused_panel == app::FocusedPanel::Diff =>
+                        {
+                            pending_bracket = Some((
+                                action == Action::NextHunk,
+                                app.diff_state.cursor_line,
+                                app.diff_state.scroll_offset,
+                            ));
+                        }
                         _ => {}
                     }
 
@@ -506,6 +1053,12 @@ fn main() -> anyhow::Result<()> {
                                 );
                                 continue;
                             }
+                            Action::GoToPercent => {
+                                if let Some(count) = app.pending_count.take() {
+                                    app.go_to_percentage(count);
+                                }
+                                continue;
+                            }
                             Action::GoToBottom if app.pending_count.is_some() => {
                                 let count = app.pending_count.unwrap().max(1);
                                 app.pending_count = None;
@@ -566,12 +1119,74 @@ fn main() -> anyhow::Result<()> {
             }
         }
 
+        if let Some(passthrough) = app.pending_passthrough.take() {
+            let result = with_suspended_terminal(
+                &mut terminal,
+                mouse_enabled,
+                keyboard_enhancement_supported,
+                || {
+                    process::run_command_in_terminal(
+                        &passthrough.program,
+                        Some(&app.vcs_info.root_path),
+                        &passthrough.args,
+                    )
+                },
+            )?;
+            app.finish_vcs_passthrough(&passthrough, result);
+        }
+
         if app.should_quit {
             break;
         }
     }
 
-    // Restore terminal
+    restore_terminal(&mut terminal, mouse_enabled)?;
+    // Quitting leaves unsaved changes unsaved; the journal is only for a
+    // tuicr that never got here
+    if let Some(dir) = app.journal_dir.as_deref().filter(|_| !app.read_only) {
+        let _ = persistence::journal::clear(dir, &app.session.id);
+    }
+    persistence::lock::release_all();
+
+    // Print pending stdout output if --stdout was used
+    if let Some(output) = app.pending_stdout_output.take() {
+        print!("{output}");
+    }
+
+    if let Some(kind) = hook
+        && let Some(reason) = check::incomplete_reason(&app.session)
+    {
+        eprintln!(
+            "tuicr: {reason}; {} blocked (set {}=1 to skip)",
+            kind.name(),
+            hook::SKIP_ENV
+        );
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Whether stdin carries a diff: a pipe or a redirected file, not a terminal
+/// or a device like the `/dev/null` that cron and IDE runners hand tuicr.
+fn stdin_has_patch() -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        std::fs::metadata("/dev/stdin")
+            .is_ok_and(|meta| meta.file_type().is_fifo() || meta.is_file())
+    }
+    #[cfg(not(unix))]
+    {
+        use std::io::IsTerminal;
+        !io::stdin().is_terminal()
+    }
+}
+
+fn restore_terminal(
+    terminal: &mut Terminal<CrosstermBackend<Box<dyn Write>>>,
+    mouse_enabled: bool,
+) -> io::Result<()> {
     let _ = execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags);
     let _ = execute!(terminal.backend_mut(), DisableBracketedPaste);
     if mouse_enabled {
@@ -579,16 +1194,60 @@ fn main() -> anyhow::Result<()> {
     }
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    Ok(())
+}
 
-    // Print pending stdout output if --stdout was used
-    if let Some(output) = app.pending_stdout_output {
-        print!("{output}");
+/// Give the terminal to `run` (a `:git`/`:jj` command), then wait for Enter
+/// so what it printed can be read before the TUI takes the screen back.
+fn with_suspended_terminal<T>(
+    terminal: &mut Terminal<CrosstermBackend<Box<dyn Write>>>,
+    mouse_enabled: bool,
+    keyboard_enhancement_supported: bool,
+    run: impl FnOnce() -> T,
+) -> io::Result<T> {
+    restore_terminal(terminal, mouse_enabled)?;
+    terminal.show_cursor()?;
+    let result = run();
+    wait_for_enter();
+
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    if mouse_enabled {
+        execute!(terminal.backend_mut(), EnableMouseCapture)?;
+    }
+    execute!(terminal.backend_mut(), EnableBracketedPaste)?;
+    if keyboard_enhancement_supported {
+        let _ = execute!(
+            terminal.backend_mut(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        );
     }
+    terminal.clear()?;
+    Ok(result)
+}
 
-    Ok(())
+fn wait_for_enter() {
+    const PROMPT: &str = "\nPress Enter to return to tuicr";
+    let mut line = String::new();
+    match File::options().read(true).write(true).open("/dev/tty") {
+        Ok(mut tty) => {
+            let _ = writeln!(tty, "{PROMPT}");
+            let _ = io::BufReader::new(tty).read_line(&mut line);
+        }
+        Err(_) => {
+            println!("{PROMPT}");
+            let _ = io::stdin().lock().read_line(&mut line);
+        }
+    }
 }
 
 fn dispatch_action(app: &mut App, action: Action) {
+    if matches!(app.input_mode, InputMode::Normal | InputMode::VisualSelect)
+        && action.edits_session()
+        && !app.ensure_session_writable()
+    {
+        return;
+    }
     match app.input_mode {
         InputMode::Help => handle_help_action(app, action),
         InputMode::Command => handle_command_action(app, action),
@@ -600,6 +1259,10 @@ fn dispatch_action(app: &mut App, action: Action) {
         InputMode::SubmitResolver => handle_submit_resolver_action(app, action),
         InputMode::SubmitConfirm => handle_submit_confirm_action(app, action),
         InputMode::SubmitActionPicker => handle_submit_action_picker_action(app, action),
+        InputMode::ReviewChooser => handle_review_chooser_action(app, action),
+        InputMode::VerifyPanel => handle_verify_panel_action(app, action),
+        InputMode::CompletionDialog => handle_completion_dialog_action(app, action),
+        InputMode::Normal if app.view != View::Diff => handle_view_action(app, action),
         InputMode::Normal => match app.focused_panel {
             FocusedPanel::FileList => handle_file_list_action(app, action),
             FocusedPanel::Diff => handle_diff_action(app, action),
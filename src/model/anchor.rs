@@ -0,0 +1,270 @@
+//! Finding a line comment's line again after the diff it was left on
+//! changed. The commented line's text and a few lines around it are
+//! recorded with the comment ([`capture`]); when the diff is loaded again
+//! [`locate`] looks for the line that still reads the same and sits among
+//! the same neighbours.
+//!
+//! The recorded lines are also hashed together ([`window_hash`]), so a
+//! comment whose lines still read the same where it was left is recognised
+//! without a search ([`is_intact`]), and one whose surroundings drifted is
+//! told apart from it.
+
+use std::cmp::Reverse;
+
+use crate::hash::Fnv1aHasher;
+use crate::model::comment::LineContext;
+use crate::model::{DiffFile, LineSide};
+
+/// Lines recorded above and below the commented one
+const CONTEXT_LINES: usize = 2;
+
+/// Where a comment's line is in the changed diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    /// Still the same line number
+    Unchanged,
+    /// The line now has this number
+    Moved(u32),
+    /// Nothing in the diff matches well enough to place the comment
+    Orphaned,
+}
+
+/// The numbered lines on `side` of each hunk of `file`
+fn side_lines(file: &DiffFile, side: LineSide) -> Vec<Vec<(u32, &str)>> {
+    file.hunks
+        .iter()
+        .map(|hunk| {
+            hunk.lines
+                .iter()
+                .filter_map(|l| {
+                    let lineno = match side {
+                        LineSide::New => l.new_lineno,
+                        LineSide::Old => l.old_lineno,
+                    };
+                    lineno.map(|n| (n, l.content.as_str()))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Hash of a commented line and its neighbours, each trimmed so that
+/// re-indenting doesn't count as drift.
+pub fn window_hash<'a>(lines: impl IntoIterator<Item = &'a str>) -> u64 {
+    let mut hasher = Fnv1aHasher::new();
+    for line in lines {
+        hasher.write(line.trim().as_bytes());
+        hasher.write(b"\n");
+    }
+    hasher.finish()
+}
+
+/// The hash of the recorded window of `context`.
+fn context_hash(context: &LineContext) -> u64 {
+    window_hash(
+        context
+            .before
+            .iter()
+            .chain([&context.content])
+            .chain(&context.after)
+            .map(String::as_str),
+    )
+}
+
+/// The hash of the window around `lines[idx]` as wide as the one recorded
+/// in `context`, or `None` when the hunk doesn't hold that many lines.
+fn hash_at(lines: &[(u32, &str)], idx: usize, context: &LineContext) -> Option<u64> {
+    let start = idx.checked_sub(context.before.len())?;
+    let window = lines.get(start..=idx + context.after.len())?;
+    Some(window_hash(window.iter().map(|(_, text)| *text)))
+}
+
+/// The text of `line` on `side` of `file` and of the lines around it in
+/// the same hunk, or `None` when the diff doesn't show that line.
+pub fn capture(file: &DiffFile, line: u32, side: LineSide) -> Option<LineContext> {
+    let hunks = side_lines(file, side);
+    let (lines, idx) = hunks.iter().find_map(|lines| {
+        let idx = lines.iter().position(|(n, _)| *n == line)?;
+        Some((lines, idx))
+    })?;
+    let text = |range: std::ops::Range<usize>| {
+        lines[range]
+            .iter()
+            .map(|(_, text)| text.to_string())
+            .collect()
+    };
+    let mut context = LineContext {
+        new_line: (side == LineSide::New).then_some(line),
+        old_line: (side == LineSide::Old).then_some(line),
+        content: lines[idx].1.to_string(),
+        before: text(idx.saturating_sub(CONTEXT_LINES)..idx),
+        after: text(idx + 1..(idx + 1 + CONTEXT_LINES).min(lines.len())),
+        hash: None,
+    };
+    context.hash = Some(context_hash(&context));
+    Some(context)
+}
+
+/// Whether the lines recorded in `context` still read the same around
+/// `line`. Always false for contexts recorded without a hash.
+pub fn is_intact(file: &DiffFile, line: u32, side: LineSide, context: &LineContext) -> bool {
+    let Some(hash) = context.hash else {
+        return false;
+    };
+    side_lines(file, side).iter().any(|lines| {
+        lines
+            .iter()
+            .position(|(n, _)| *n == line)
+            .is_some_and(|idx| hash_at(lines, idx, context) == Some(hash))
+    })
+}
+
+/// How many of the recorded neighbours sit around `lines[idx]`
+fn matching_neighbours(lines: &[(u32, &str)], idx: usize, context: &LineContext) -> usize {
+    let same = |i: Option<usize>, text: &String| {
+        i.and_then(|i| lines.get(i))
+            .is_some_and(|(_, t)| t.trim() == text.trim())
+    };
+    let before = context
+        .before
+        .iter()
+        .rev()
+        .enumerate()
+        .filter(|(k, text)| same(idx.checked_sub(k + 1), text))
+        .count();
+    let after = context
+        .after
+        .iter()
+        .enumerate()
+        .filter(|(k, text)| same(Some(idx + k + 1), text))
+        .count();
+    before + after
+}
+
+/// Where the line `context` was recorded for, last numbered `line`, is on
+/// `side` of `file` now. Lines still intact where they were stay put
+/// without a search. Otherwise a line with the same text wins, the one with
+/// the most matching neighbours and then the nearest; short lines such as
+/// `}` also need a matching neighbour. A line whose text changed is only
+/// taken when every recorded neighbour still surrounds it.
+pub fn locate(file: &DiffFile, line: u32, side: LineSide, context: &LineContext) -> Anchor {
+    if is_intact(file, line, side, context) {
+        return Anchor::Unchanged;
+    }
+    let content = context.content.trim();
+    let recorded = context.before.len() + context.after.len();
+    let mut best = None;
+    for lines in side_lines(file, side) {
+        for (idx, (n, text)) in lines.iter().enumerate() {
+            let neighbours = matching_neighbours(&lines, idx, context);
+            let same_text = text.trim() == content;
+            let placed = if same_text {
+                content.len() > 2 || neighbours > 0
+            } else {
+                recorded >= 2 && neighbours == recorded
+            };
+            if placed {
+                let rank = (same_text, neighbours, Reverse(n.abs_diff(line)), *n);
+                best = best.max(Some(rank));
+            }
+        }
+    }
+    match best {
+        None => Anchor::Orphaned,
+        Some((.., n)) if n == line => Anchor::Unchanged,
+        Some((.., n)) => Anchor::Moved(n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin};
+    use std::path::PathBuf;
+
+    fn file(lines: &[&str]) -> DiffFile {
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Added,
+            hunks: vec![DiffHunk {
+                header: format!("@@ -0,0 +1,{} @@", lines.len()),
+                lines: lines
+                    .iter()
+                    .zip(1..)
+                    .map(|(content, n)| DiffLine {
+                        origin: LineOrigin::Addition,
+                        content: content.to_string(),
+                        old_lineno: None,
+                        new_lineno: Some(n),
+                        highlighted_spans: None,
+                    })
+                    .collect(),
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: lines.len() as u32,
+            }],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    #[test]
+    fn should_follow_moved_and_edited_lines_and_give_up_on_removed_ones() {
+        // given
+        let before = file(&["fn main() {", "    let x = 1;", "    run(x);", "}"]);
+        let run = capture(&before, 3, LineSide::New).unwrap();
+        let x = capture(&before, 2, LineSide::New).unwrap();
+        let close = capture(&before, 4, LineSide::New).unwrap();
+
+        // when
+        let moved = file(&[
+            "use std::io;",
+            "",
+            "fn main() {",
+            "    let x = 2;",
+            "    run(x);",
+            "}",
+        ]);
+        let removed = file(&["fn other() {", "}"]);
+
+        // then
+        assert_eq!(run.before, vec!["fn main() {", "    let x = 1;"]);
+        assert_eq!(locate(&before, 3, LineSide::New, &run), Anchor::Unchanged);
+        assert_eq!(locate(&moved, 3, LineSide::New, &run), Anchor::Moved(5));
+        assert_eq!(locate(&moved, 2, LineSide::New, &x), Anchor::Moved(4));
+        assert_eq!(locate(&moved, 4, LineSide::New, &close), Anchor::Moved(6));
+        assert_eq!(locate(&removed, 3, LineSide::New, &run), Anchor::Orphaned);
+        assert_eq!(locate(&removed, 4, LineSide::New, &close), Anchor::Orphaned);
+    }
+
+    #[test]
+    fn should_tell_intact_windows_from_drifted_ones_by_hash() {
+        // given
+        let before = file(&["fn main() {", "    let x = 1;", "    run(x);", "}"]);
+        let run = capture(&before, 3, LineSide::New).unwrap();
+        let reindented = file(&["fn main() {", "  let x = 1;", "  run(x);", "}"]);
+        let drifted = file(&["fn main() {", "    let x = 2;", "    run(x);", "}"]);
+        let legacy = LineContext {
+            hash: None,
+            ..run.clone()
+        };
+
+        // when / then
+        assert!(run.hash.is_some());
+        assert!(is_intact(&before, 3, LineSide::New, &run));
+        assert!(is_intact(&reindented, 3, LineSide::New, &run));
+        assert!(!is_intact(&drifted, 3, LineSide::New, &run));
+        assert_eq!(locate(&drifted, 3, LineSide::New, &run), Anchor::Unchanged);
+        assert!(!is_intact(&before, 3, LineSide::New, &legacy));
+        assert_eq!(
+            locate(&before, 3, LineSide::New, &legacy),
+            Anchor::Unchanged
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! `:checkpoint <label>`: named snapshots of a session's review state, taken
+//! before a bulk operation (`:clear`, marking every file reviewed) so the
+//! session can be compared against one or rolled back to it.
+//!
+//! A checkpoint holds what the reviewer decided — reviewed marks, comments,
+//! notes, checklist, review fixes and per-commit marks. The event log, the
+//! trash and the time spent are left out; they only ever grow, and rolling
+//! back leaves them as they are apart from trashing the comments it drops.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::model::review::{ChecklistItem, CommitReview, FileReview, ReviewEdit};
+use crate::model::{Comment, ReviewSession};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub label: String,
+    pub at: DateTime<Utc>,
+    pub files: HashMap<PathBuf, FileReview>,
+    #[serde(default)]
+    pub review_comments: Vec<Comment>,
+    #[serde(default)]
+    pub session_notes: Option<String>,
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default)]
+    pub review_edits: Vec<ReviewEdit>,
+    #[serde(default)]
+    pub commits: BTreeMap<String, CommitReview>,
+}
+
+/// A comment with where it sits: `None` path for review comments, `None`
+/// line for file comments.
+type Located<'a> = (Option<&'a Path>, Option<u32>, &'a Comment);
+
+fn located<'a>(
+    review_comments: &'a [Comment],
+    files: &'a HashMap<PathBuf, FileReview>,
+) -> Vec<Located<'a>> {
+    let mut comments: Vec<Located> = review_comments.iter().map(|c| (None, None, c)).collect();
+    for (path, review) in files {
+        comments.extend(
+            review
+                .file_comments
+                .iter()
+                .map(|c| (Some(path.as_path()), None, c)),
+        );
+        for (line, line_comments) in &review.line_comments {
+            comments.extend(
+                line_comments
+                    .iter()
+                    .map(|c| (Some(path.as_path()), Some(*line), c)),
+            );
+        }
+    }
+    comments.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+    comments
+}
+
+/// `path:line`, `path` or `review`, then the comment's first line
+fn describe((path, line, comment): Located) -> String {
+    let location = match (path, line) {
+        (Some(path), Some(line)) => format!("{}:{line}", path.display()),
+        (Some(path), None) => path.display().to_string(),
+        (None, _) => "review".to_string(),
+    };
+    let text = comment.content.lines().next().unwrap_or_default();
+    format!("{location} {}: {text}", comment.comment_type.as_str())
+}
+
+/// What changed between a checkpoint and the session, one list per kind
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckpointDiff {
+    pub reviewed: Vec<PathBuf>,
+    pub unreviewed: Vec<PathBuf>,
+    pub added: Vec<String>,
+    pub deleted: Vec<String>,
+    pub edited: Vec<String>,
+    /// Notes, checklist or review fixes differ
+    pub other_changes: bool,
+}
+
+impl CheckpointDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The diff as sections of lines, for the output popup
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut section = |title: &str, items: Vec<String>| {
+            if !items.is_empty() {
+                lines.push(format!("{title} ({}):", items.len()));
+                lines.extend(items.into_iter().map(|item| format!("  {item}")));
+            }
+        };
+        let paths = |paths: &[PathBuf]| paths.iter().map(|p| p.display().to_string()).collect();
+        section("Marked reviewed", paths(&self.reviewed));
+        section("No longer reviewed", paths(&self.unreviewed));
+        section("Comments added", self.added.clone());
+        section("Comments deleted", self.deleted.clone());
+        section("Comments edited", self.edited.clone());
+        if self.other_changes {
+            lines.push("Notes, checklist or review fixes changed".to_string());
+        }
+        lines
+    }
+}
+
+impl Checkpoint {
+    pub fn capture(label: &str, session: &ReviewSession) -> Self {
+        Self {
+            label: label.to_string(),
+            at: Utc::now(),
+            files: session.files.clone(),
+            review_comments: session.review_comments.clone(),
+            session_notes: session.session_notes.clone(),
+            checklist: session.checklist.clone(),
+            review_edits: session.review_edits.clone(),
+            commits: session.commits.clone(),
+        }
+    }
+
+    /// What `session` changed since the checkpoint was taken
+    pub fn diff(&self, session: &ReviewSession) -> CheckpointDiff {
+        let was_reviewed = |path: &PathBuf| self.files.get(path).is_some_and(|f| f.reviewed);
+        let mut diff = CheckpointDiff::default();
+        for (path, review) in &session.files {
+            if review.reviewed && !was_reviewed(path) {
+                diff.reviewed.push(path.clone());
+            } else if !review.reviewed && was_reviewed(path) {
+                diff.unreviewed.push(path.clone());
+            }
+        }
+        diff.reviewed.sort();
+        diff.unreviewed.sort();
+
+        let then = located(&self.review_comments, &self.files);
+        let now = located(&session.review_comments, &session.files);
+        let by_id = |comments: &[Located<'_>]| -> HashMap<String, String> {
+            comments
+                .iter()
+                .map(|(_, _, c)| (c.id.clone(), c.content.clone()))
+                .collect()
+        };
+        let (then_ids, now_ids) = (by_id(&then), by_id(&now));
+        for entry in now.iter().copied() {
+            match then_ids.get(&entry.2.id) {
+                None => diff.added.push(describe(entry)),
+                Some(content) if *content != entry.2.content => diff.edited.push(describe(entry)),
+                Some(_) => {}
+            }
+        }
+        for entry in then.iter().copied() {
+            if !now_ids.contains_key(&entry.2.id) {
+                diff.deleted.push(describe(entry));
+            }
+        }
+        diff.other_changes = self.session_notes != session.session_notes
+            || self.checklist != session.checklist
+            || self.review_edits != session.review_edits;
+        diff
+    }
+
+    /// Put `session` back the way it was when the checkpoint was taken.
+    /// Comments written since go to the trash; ones deleted since come back
+    /// and leave it. A file whose diff changed since keeps its comments but
+    /// not its reviewed mark, as on a reload. Files that have joined the
+    /// diff since start over unreviewed.
+    pub fn restore(&self, session: &mut ReviewSession) {
+        let kept: HashSet<&str> = located(&self.review_comments, &self.files)
+            .into_iter()
+            .map(|(_, _, c)| c.id.as_str())
+            .collect();
+        let dropped: Vec<(Option<PathBuf>, Option<u32>, Comment)> =
+            located(&session.review_comments, &session.files)
+                .into_iter()
+                .filter(|(_, _, c)| !kept.contains(c.id.as_str()))
+                .map(|(path, line, c)| (path.map(Path::to_path_buf), line, c.clone()))
+                .collect();
+        session
+            .trash
+            .retain(|entry| !kept.contains(entry.comment.id.as_str()));
+        for (path, line, comment) in dropped {
+            session.trash_comment(comment, path, line);
+        }
+
+        let mut files = self.files.clone();
+        for (path, current) in &session.files {
+            let restored = files.entry(path.clone()).or_insert_with(|| {
+                let mut fresh = current.clone();
+                fresh.reviewed = false;
+                fresh.reviewed_hash = None;
+                fresh.file_comments.clear();
+                fresh.line_comments.clear();
+                fresh.deferred.clear();
+                fresh
+            });
+            restored.active_ms = current.active_ms;
+            restored.status = current.status;
+            if restored.content_hash != current.content_hash {
+                if restored.reviewed {
+                    restored.reviewed = false;
+                    restored.reviewed_hash = restored.content_hash;
+                }
+                restored.content_hash = current.content_hash;
+            }
+        }
+        session.files = files;
+        session.review_comments = self.review_comments.clone();
+        session.session_notes = self.session_notes.clone();
+        session.checklist = self.checklist.clone();
+        session.review_edits = self.review_edits.clone();
+        session.commits = self.commits.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::review::SessionDiffSource;
+    use crate::model::{CommentType, FileStatus, LineSide};
+
+    #[test]
+    fn should_diff_and_roll_back_to_a_checkpoint() {
+        // given
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        let (a, b) = (PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs"));
+        session.add_file(a.clone(), FileStatus::Modified, 1);
+        session.add_file(b.clone(), FileStatus::Modified, 2);
+        let kept = Comment::new("keep".to_string(), CommentType::Note, Some(LineSide::New));
+        session.get_file_mut(&a).unwrap().add_line_comment(3, kept);
+        let checkpoint = Checkpoint::capture("before-bulk", &session);
+
+        // when
+        for review in session.files.values_mut() {
+            review.reviewed = true;
+        }
+        let added = Comment::new("later".to_string(), CommentType::Issue, None);
+        session.get_file_mut(&b).unwrap().add_file_comment(added);
+        session.add_file(b.clone(), FileStatus::Modified, 3);
+        let diff = checkpoint.diff(&session);
+        checkpoint.restore(&mut session);
+
+        // then
+        assert_eq!(diff.reviewed, vec![a.clone()]);
+        assert_eq!(diff.added, vec!["src/b.rs ISSUE: later".to_string()]);
+        assert!(diff.deleted.is_empty());
+        assert!(!session.is_file_reviewed(&a));
+        assert!(!session.is_file_reviewed(&b));
+        assert_eq!(session.files[&b].content_hash, Some(3));
+        assert!(session.files[&b].file_comments.is_empty());
+        assert_eq!(session.files[&a].line_comments[&3][0].content, "keep");
+        assert_eq!(session.trash.len(), 1);
+        assert_eq!(session.trash[0].comment.content, "later");
+        assert!(checkpoint.diff(&session).is_empty());
+    }
+}
@@ -124,11 +124,82 @@ impl<'de> Deserialize<'de> for CommentType {
     }
 }
 
+/// Conventional Comments decoration for a blocking flag
+pub fn blocking_decoration(blocking: Option<bool>) -> Option<&'static str> {
+    blocking.map(|blocking| if blocking { "blocking" } else { "non-blocking" })
+}
+
+/// `label` with the blocking decoration appended, e.g. `ISSUE (blocking)`
+pub fn decorated_label(label: &str, blocking: Option<bool>) -> String {
+    match blocking_decoration(blocking) {
+        Some(decoration) => format!("{label} ({decoration})"),
+        None => label.to_string(),
+    }
+}
+
+/// A comment type label in the Conventional Comments form
+/// (<https://conventionalcomments.org>): lower case, words joined with `-`,
+/// then the blocking decoration, e.g. `suggestion (non-blocking)`.
+pub fn conventional_label(label: &str, blocking: Option<bool>) -> String {
+    let label = label
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    decorated_label(&label, blocking)
+}
+
+/// Who wrote a review: from the `reviewer_name`/`reviewer_email` config
+/// keys, else the VCS's user settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reviewer {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+impl Reviewer {
+    /// Parse a `Name <email>` identity, as Mercurial's `ui.username`.
+    pub fn parse(identity: &str) -> Option<Self> {
+        let identity = identity.trim();
+        let (name, email) = match identity.split_once('<') {
+            Some((name, rest)) => (name.trim(), rest.strip_suffix('>').map(str::trim)),
+            None => (identity, None),
+        };
+        if name.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: name.to_string(),
+            email: email.filter(|e| !e.is_empty()).map(str::to_string),
+        })
+    }
+}
+
+impl std::fmt::Display for Reviewer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.email {
+            Some(email) => write!(f, "{} <{email}>", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LineContext {
     pub new_line: Option<u32>,
     pub old_line: Option<u32>,
     pub content: String,
+    /// Lines just above the commented one, nearest last
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    /// Lines just below the commented one, nearest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+    /// Hash of the commented line and the recorded lines around it, see
+    /// `anchor::window_hash`. `None` for comments saved before it existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -158,6 +229,18 @@ pub struct Comment {
     /// inline comments; review-level / summary comments don't get one.
     #[serde(default)]
     pub remote_comment_id: Option<String>,
+    /// Forge login of whoever wrote the comment, for comments imported with
+    /// `:comments import`. `None` for comments written in tuicr.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Whether the author must address the comment before merging, shown as
+    /// a Conventional Comments decoration. `None` leaves it unstated.
+    #[serde(default)]
+    pub blocking: Option<bool>,
+    /// Who wrote the comment in tuicr; `None` for comments written before
+    /// reviewer identities were recorded, or without one configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewer: Option<Reviewer>,
 }
 
 impl Comment {
@@ -173,6 +256,9 @@ impl Comment {
             lifecycle_state: CommentLifecycleState::default(),
             remote_review_id: None,
             remote_comment_id: None,
+            author: None,
+            blocking: None,
+            reviewer: None,
         }
     }
 
@@ -194,14 +280,46 @@ impl Comment {
             lifecycle_state: CommentLifecycleState::default(),
             remote_review_id: None,
             remote_comment_id: None,
+            author: None,
+            blocking: None,
+            reviewer: None,
         }
     }
 
+    pub fn with_blocking(mut self, blocking: Option<bool>) -> Self {
+        self.blocking = blocking;
+        self
+    }
+
+    pub fn with_reviewer(mut self, reviewer: Option<Reviewer>) -> Self {
+        self.reviewer = reviewer;
+        self
+    }
+
     /// True if this comment has been pushed/submitted to the forge and is
     /// therefore locked from local edits/deletions.
     pub fn is_locked(&self) -> bool {
         self.lifecycle_state.is_locked()
     }
+
+    /// Replacement lines from a GitHub-style ```` ```suggestion ```` block in a
+    /// SUGGESTION comment. An empty block suggests deleting the commented
+    /// lines; `None` when there is no (closed) block.
+    pub fn suggested_code(&self) -> Option<Vec<&str>> {
+        if self.comment_type != CommentType::Suggestion {
+            return None;
+        }
+        let mut lines = self.content.lines();
+        lines.by_ref().find(|line| line.trim() == "```suggestion")?;
+        let mut code = Vec::new();
+        for line in lines {
+            if line.trim_start().starts_with("```") {
+                return Some(code);
+            }
+            code.push(line);
+        }
+        None
+    }
 }
 
 #[cfg(test)]
@@ -456,4 +574,77 @@ mod tests {
             assert_eq!(comment.content, "pre-pr5");
         }
     }
+
+    mod suggested_code_tests {
+        use super::*;
+
+        #[test]
+        fn should_extract_lines_of_suggestion_block() {
+            // given
+            let comment = Comment::new(
+                "Use a constant:\n```suggestion\nconst ANSWER: u32 = 42;\nlet x = ANSWER;\n```\nThanks"
+                    .to_string(),
+                CommentType::Suggestion,
+                Some(LineSide::New),
+            );
+
+            // when
+            let code = comment.suggested_code();
+
+            // then
+            assert_eq!(
+                code,
+                Some(vec!["const ANSWER: u32 = 42;", "let x = ANSWER;"])
+            );
+        }
+
+        #[test]
+        fn should_ignore_non_suggestions_and_unclosed_blocks() {
+            // given
+            let note = Comment::new("```suggestion\nx\n```".to_string(), CommentType::Note, None);
+            let unclosed = Comment::new(
+                "```suggestion\nx".to_string(),
+                CommentType::Suggestion,
+                None,
+            );
+            let empty = Comment::new(
+                "Drop this\n```suggestion\n```".to_string(),
+                CommentType::Suggestion,
+                None,
+            );
+
+            // then
+            assert_eq!(note.suggested_code(), None);
+            assert_eq!(unclosed.suggested_code(), None);
+            assert_eq!(empty.suggested_code(), Some(Vec::new()));
+        }
+    }
+
+    mod conventional_label_tests {
+        use super::*;
+
+        #[test]
+        fn should_lowercase_label_and_append_blocking_decoration() {
+            // then
+            assert_eq!(
+                conventional_label("SUGGESTION", Some(false)),
+                "suggestion (non-blocking)"
+            );
+            assert_eq!(conventional_label("ISSUE", Some(true)), "issue (blocking)");
+            assert_eq!(conventional_label("Needs Test", None), "needs-test");
+            assert_eq!(decorated_label("ISSUE", Some(true)), "ISSUE (blocking)");
+        }
+
+        #[test]
+        fn should_default_blocking_to_unset_for_old_sessions() {
+            // given
+            let json = r#"{"id":"1","content":"x","comment_type":"issue","created_at":"2024-01-01T00:00:00Z","line_context":null}"#;
+
+            // when
+            let comment: Comment = serde_json::from_str(json).unwrap();
+
+            // then
+            assert_eq!(comment.blocking, None);
+        }
+    }
 }
@@ -0,0 +1,196 @@
+//! Conflict markers materialized into working-copy files.
+//!
+//! jj records conflicts in the commit itself and writes them into the files
+//! as marker blocks, so the diff of a conflicted working copy shows them as
+//! added lines:
+//!
+//! ```text
+//! <<<<<<< conflict 1 of 1
+//! %%%%%%% diff from base to side #1
+//! -old
+//! +new
+//! +++++++ side #2
+//! other
+//! >>>>>>> conflict 1 of 1 ends
+//! ```
+//!
+//! Git-style blocks (`|||||||`, `=======`) are recognised as well. A file
+//! only counts as conflicted once a whole block, from opening to closing
+//! marker, is found, so a Markdown `=======` underline on its own is not
+//! mistaken for a conflict.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::model::{DiffFile, LineOrigin};
+
+/// The files of a diff that contain conflict markers.
+#[derive(Debug, Default)]
+pub struct ConflictIndex {
+    files: HashSet<PathBuf>,
+}
+
+impl ConflictIndex {
+    pub fn scan(files: &[DiffFile]) -> Self {
+        let files = files
+            .iter()
+            .filter(|file| has_conflict_block(file))
+            .map(|file| file.display_path().clone())
+            .collect();
+        Self { files }
+    }
+
+    pub fn is_conflicted(&self, path: &Path) -> bool {
+        self.files.contains(path)
+    }
+
+    /// Whether `content`, a line of the file at `path`, is a conflict marker
+    pub fn is_marker(&self, path: &Path, content: &str) -> bool {
+        self.is_conflicted(path) && marker(content).is_some()
+    }
+
+    /// Number of conflicted files
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Open,
+    Inner,
+    Close,
+}
+
+/// A marker is a run of at least seven identical marker characters (jj
+/// lengthens them when the file already contains such runs), then the end
+/// of the line or a space and a label.
+fn marker(content: &str) -> Option<Marker> {
+    let first = content.chars().next()?;
+    let run = content.chars().take_while(|&c| c == first).count();
+    if run < 7 {
+        return None;
+    }
+    let rest = &content[run * first.len_utf8()..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    match first {
+        '<' => Some(Marker::Open),
+        '>' => Some(Marker::Close),
+        '%' | '+' | '-' | '\\' | '|' | '=' => Some(Marker::Inner),
+        _ => None,
+    }
+}
+
+fn has_conflict_block(file: &DiffFile) -> bool {
+    let mut open = false;
+    let new_side = file
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        // The markers live in the new version of the file
+        .filter(|line| line.origin != LineOrigin::Deletion);
+    for line in new_side {
+        match marker(&line.content) {
+            Some(Marker::Open) => open = true,
+            Some(Marker::Close) if open => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiffHunk, DiffLine, FileModes, FileSizes, FileStatus};
+
+    fn file(path: &str, lines: &[(LineOrigin, &str)]) -> DiffFile {
+        let lines = lines
+            .iter()
+            .map(|(origin, content)| DiffLine {
+                origin: *origin,
+                content: content.to_string(),
+                old_lineno: None,
+                new_lineno: None,
+                highlighted_spans: None,
+            })
+            .collect();
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Modified,
+            hunks: vec![DiffHunk {
+                header: "@@ -1 +1 @@".to_string(),
+                lines,
+                old_start: 1,
+                old_count: 1,
+                new_start: 1,
+                new_count: 1,
+            }],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    #[test]
+    fn should_find_jj_conflict_markers() {
+        // given
+        let conflicted = file(
+            "src/lib.rs",
+            &[
+                (LineOrigin::Context, "fn main() {"),
+                (LineOrigin::Addition, "<<<<<<< conflict 1 of 1"),
+                (LineOrigin::Addition, "%%%%%%% diff from base to side #1"),
+                (LineOrigin::Addition, "-    old();"),
+                (LineOrigin::Addition, "+    new();"),
+                (LineOrigin::Addition, "+++++++ side #2"),
+                (LineOrigin::Addition, "    other();"),
+                (LineOrigin::Addition, ">>>>>>> conflict 1 of 1 ends"),
+                (LineOrigin::Deletion, "    old();"),
+            ],
+        );
+
+        // when
+        let index = ConflictIndex::scan(&[conflicted]);
+
+        // then
+        let path = Path::new("src/lib.rs");
+        assert!(index.is_conflicted(path));
+        assert!(index.is_marker(path, "%%%%%%% diff from base to side #1"));
+        assert!(index.is_marker(path, r"\\\\\\\        to: side #1"));
+        assert!(index.is_marker(path, "+++++++ side #2"));
+        assert!(!index.is_marker(path, "+    new();"));
+        assert!(!index.is_marker(Path::new("README.md"), "======="));
+    }
+
+    #[test]
+    fn should_ignore_marker_lookalikes_outside_a_block() {
+        // given
+        let readme = file(
+            "README.md",
+            &[
+                (LineOrigin::Addition, "Title"),
+                (LineOrigin::Addition, "======="),
+                (LineOrigin::Addition, "<<<<<<<<not a marker"),
+                (LineOrigin::Addition, "<<<<<<< unclosed"),
+                (LineOrigin::Addition, "-------"),
+            ],
+        );
+
+        // when
+        let index = ConflictIndex::scan(&[readme]);
+
+        // then
+        assert!(index.is_empty());
+    }
+}
@@ -61,6 +61,92 @@ pub struct DiffHunk {
     pub new_count: u32,
 }
 
+impl DiffHunk {
+    /// The `@@ -a,b +c,d @@` line, rebuilt from the ranges when the backend
+    /// didn't keep one.
+    pub fn header_line(&self) -> String {
+        if self.header.is_empty() {
+            format!(
+                "@@ -{},{} +{},{} @@",
+                self.old_start, self.old_count, self.new_start, self.new_count
+            )
+        } else {
+            self.header.clone()
+        }
+    }
+}
+
+/// Git file mode of a symbolic link; its blob holds the link target.
+pub const SYMLINK_MODE: u32 = 0o120000;
+
+/// File modes on each side of a diff as git writes them (`0o100644`,
+/// `0o100755`, [`SYMLINK_MODE`]). `None` when the side doesn't exist or the
+/// diff format carries no mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileModes {
+    pub old: Option<u32>,
+    pub new: Option<u32>,
+}
+
+impl FileModes {
+    pub fn is_old_symlink(&self) -> bool {
+        self.old == Some(SYMLINK_MODE)
+    }
+
+    pub fn is_new_symlink(&self) -> bool {
+        self.new == Some(SYMLINK_MODE)
+    }
+}
+
+/// Byte size of each side's content, read from the backend for binary
+/// files so the magnitude of a change can be reviewed. `None` when the side
+/// doesn't exist or the size wasn't looked up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileSizes {
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+impl FileSizes {
+    /// `1.2 KiB → 1.5 KiB (+312 B)`, or just the side that exists for an
+    /// added or deleted file. `None` when neither size is known.
+    pub fn describe(&self) -> Option<String> {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => {
+                let sign = match new.cmp(&old) {
+                    std::cmp::Ordering::Greater => "+",
+                    std::cmp::Ordering::Less => "-",
+                    std::cmp::Ordering::Equal => "±",
+                };
+                Some(format!(
+                    "{} → {} ({sign}{})",
+                    format_size(old),
+                    format_size(new),
+                    format_size(new.abs_diff(old))
+                ))
+            }
+            (None, Some(new)) => Some(format_size(new)),
+            (Some(old), None) => Some(format!("was {}", format_size(old))),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Human-readable byte count: exact below 1 KiB, one decimal above.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 #[derive(Debug, Clone)]
 pub struct DiffFile {
     pub old_path: Option<PathBuf>,
@@ -71,6 +157,8 @@ pub struct DiffFile {
     pub is_too_large: bool,
     pub is_commit_message: bool,
     pub content_hash: u64,
+    pub modes: FileModes,
+    pub sizes: FileSizes,
 }
 
 impl DiffFile {
@@ -133,6 +221,16 @@ impl DiffFile {
         None
     }
 
+    /// The row shown in place of hunks when there are none: deleted files
+    /// offer their prior content (`Enter`), anything else has no changes.
+    pub fn empty_label(&self) -> &'static str {
+        if self.status == FileStatus::Deleted {
+            "(deleted file — Enter shows its content)"
+        } else {
+            "(no changes)"
+        }
+    }
+
     /// Returns `(additions, deletions)` for this file.
     pub fn stat(&self) -> (usize, usize) {
         let mut additions = 0;
@@ -148,4 +246,179 @@ impl DiffFile {
         }
         (additions, deletions)
     }
+    /// Rows describing what the hunks don't show: a mode change such as
+    /// `mode 100644 → 100755`, where a symlink points before and after, and
+    /// how much a binary file grew or shrank.
+    pub fn metadata_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let (Some(old), Some(new)) = (self.modes.old, self.modes.new)
+            && old != new
+        {
+            lines.push(format!("mode {old:o} → {new:o}"));
+        }
+        let old_target = self
+            .modes
+            .is_old_symlink()
+            .then(|| self.first_line_with(LineOrigin::Deletion))
+            .flatten();
+        let new_target = self
+            .modes
+            .is_new_symlink()
+            .then(|| self.first_line_with(LineOrigin::Addition))
+            .flatten();
+        match (old_target, new_target) {
+            (Some(old), Some(new)) => lines.push(format!("symlink {old} → {new}")),
+            (None, Some(new)) => lines.push(format!("symlink → {new}")),
+            (Some(old), None) => lines.push(format!("was symlink → {old}")),
+            (None, None) => {}
+        }
+        if self.is_binary
+            && let Some(sizes) = self.sizes.describe()
+        {
+            lines.push(format!("size {sizes}"));
+        }
+        lines
+    }
+
+    fn first_line_with(&self, origin: LineOrigin) -> Option<&str> {
+        self.hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .find(|line| line.origin == origin)
+            .map(|line| line.content.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_with_lines(modes: FileModes, lines: &[(LineOrigin, &str)]) -> DiffFile {
+        let lines: Vec<DiffLine> = lines
+            .iter()
+            .map(|(origin, content)| DiffLine {
+                origin: *origin,
+                content: content.to_string(),
+                old_lineno: (*origin != LineOrigin::Addition).then_some(1),
+                new_lineno: (*origin != LineOrigin::Deletion).then_some(1),
+                highlighted_spans: None,
+            })
+            .collect();
+        let hunks = if lines.is_empty() {
+            Vec::new()
+        } else {
+            vec![DiffHunk {
+                header: "@@ -1 +1 @@".to_string(),
+                lines,
+                old_start: 1,
+                old_count: 1,
+                new_start: 1,
+                new_count: 1,
+            }]
+        };
+        DiffFile {
+            old_path: Some(PathBuf::from("link")),
+            new_path: Some(PathBuf::from("link")),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes,
+            sizes: FileSizes::default(),
+        }
+    }
+
+    #[test]
+    fn should_describe_mode_only_change() {
+        let file = file_with_lines(
+            FileModes {
+                old: Some(0o100644),
+                new: Some(0o100755),
+            },
+            &[],
+        );
+
+        assert_eq!(file.metadata_lines(), vec!["mode 100644 → 100755"]);
+    }
+
+    #[test]
+    fn should_describe_symlink_target_change() {
+        let file = file_with_lines(
+            FileModes {
+                old: Some(SYMLINK_MODE),
+                new: Some(SYMLINK_MODE),
+            },
+            &[
+                (LineOrigin::Deletion, "old/target"),
+                (LineOrigin::Addition, "new/target"),
+            ],
+        );
+
+        assert_eq!(
+            file.metadata_lines(),
+            vec!["symlink old/target → new/target"]
+        );
+    }
+
+    #[test]
+    fn should_describe_file_replaced_by_symlink() {
+        let file = file_with_lines(
+            FileModes {
+                old: Some(0o100644),
+                new: Some(SYMLINK_MODE),
+            },
+            &[
+                (LineOrigin::Deletion, "contents"),
+                (LineOrigin::Addition, "target"),
+            ],
+        );
+
+        assert_eq!(
+            file.metadata_lines(),
+            vec!["mode 100644 → 120000", "symlink → target"]
+        );
+    }
+
+    #[test]
+    fn should_have_no_metadata_without_modes() {
+        let file = file_with_lines(FileModes::default(), &[(LineOrigin::Addition, "x")]);
+
+        assert!(file.metadata_lines().is_empty());
+    }
+
+    #[test]
+    fn should_describe_binary_size_change() {
+        let mut file = file_with_lines(FileModes::default(), &[]);
+        file.is_binary = true;
+        file.sizes = FileSizes {
+            old: Some(1_229),
+            new: Some(1_541),
+        };
+
+        assert_eq!(
+            file.metadata_lines(),
+            vec!["size 1.2 KiB → 1.5 KiB (+312 B)"]
+        );
+    }
+
+    #[test]
+    fn should_describe_added_and_shrunk_binary_sizes() {
+        let added = FileSizes {
+            old: None,
+            new: Some(3 * 1024 * 1024),
+        };
+        let shrunk = FileSizes {
+            old: Some(2_048),
+            new: Some(0),
+        };
+
+        assert_eq!(added.describe().as_deref(), Some("3.0 MiB"));
+        assert_eq!(
+            shrunk.describe().as_deref(),
+            Some("2.0 KiB → 0 B (-2.0 KiB)")
+        );
+        assert_eq!(FileSizes::default().describe(), None);
+    }
 }
@@ -0,0 +1,399 @@
+//! `:session merge`: fold another reviewer's saved session into the open one.
+//!
+//! Comments are unioned and keep who wrote them; comments that came without
+//! a reviewer are attributed to the other session's reviewer. Reviewed marks
+//! follow a `ReviewedPolicy`. Local state wins wherever the two sessions
+//! disagree, and every disagreement is reported as a `MergeConflict` so the
+//! reviewer can go and look.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::model::{Comment, ReviewConfidence, ReviewSession, Reviewer};
+
+/// How reviewed marks combine when only one of the two reviewers marked a
+/// file reviewed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReviewedPolicy {
+    /// Reviewed when either reviewer reviewed it
+    #[default]
+    Union,
+    /// Reviewed only when both reviewers reviewed it
+    Intersect,
+}
+
+impl ReviewedPolicy {
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id.to_ascii_lowercase().as_str() {
+            "union" | "either" => Some(Self::Union),
+            "intersect" | "both" => Some(Self::Intersect),
+            _ => None,
+        }
+    }
+}
+
+/// A point where the two sessions disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeConflict {
+    /// The sessions were recorded against different base commits, so line
+    /// comments may point at the wrong lines
+    BaseCommit { mine: String, theirs: String },
+    /// Both sessions have the comment, edited differently; mine was kept
+    EditedComment { path: Option<PathBuf>, id: String },
+    /// Only one reviewer marked the file reviewed
+    Reviewed {
+        path: PathBuf,
+        by_me: bool,
+        kept: bool,
+    },
+    /// The other reviewer saw a different version of the file, so their
+    /// reviewed mark was not carried over
+    ReviewedOtherVersion { path: PathBuf },
+    /// Both reviewed the file, with different confidence; mine was kept
+    Confidence {
+        path: PathBuf,
+        mine: ReviewConfidence,
+        theirs: ReviewConfidence,
+    },
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BaseCommit { mine, theirs } => write!(
+                f,
+                "base commit differs (mine {}, theirs {}); line comments may be misplaced",
+                short(mine),
+                short(theirs)
+            ),
+            Self::EditedComment { path, id } => {
+                let place = path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "review comment".to_string());
+                write!(
+                    f,
+                    "{place}: comment {} edited differently, kept mine",
+                    short(id)
+                )
+            }
+            Self::Reviewed { path, by_me, kept } => {
+                let who = if *by_me { "me" } else { "them" };
+                let outcome = if *kept { "reviewed" } else { "not reviewed" };
+                write!(
+                    f,
+                    "{}: reviewed by {who} only, now {outcome}",
+                    path.display()
+                )
+            }
+            Self::ReviewedOtherVersion { path } => write!(
+                f,
+                "{}: they reviewed a different version, mark not carried over",
+                path.display()
+            ),
+            Self::Confidence { path, mine, theirs } => write!(
+                f,
+                "{}: {} by me, {} by them, kept mine",
+                path.display(),
+                mine.label(),
+                theirs.label()
+            ),
+        }
+    }
+}
+
+fn short(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+/// Outcome of `merge_session`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionMerge {
+    pub comments: usize,
+    /// Comments this session already has, by ID or by identical text on the
+    /// same line and side
+    pub duplicates: usize,
+    /// Files newly marked reviewed
+    pub reviewed: usize,
+    /// Files no longer marked reviewed (`ReviewedPolicy::Intersect`)
+    pub unreviewed: usize,
+    /// Files in the other session that are not part of this one
+    pub skipped_files: Vec<PathBuf>,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl SessionMerge {
+    pub fn changed(&self) -> bool {
+        self.comments > 0 || self.reviewed > 0 || self.unreviewed > 0
+    }
+}
+
+/// Where a comment is merged into, and what it is compared against.
+enum Target<'a> {
+    Review(&'a mut Vec<Comment>),
+    File(&'a mut Vec<Comment>, PathBuf),
+}
+
+struct Merger<'a> {
+    /// Attributed to their comments that don't name a reviewer
+    author: Option<&'a Reviewer>,
+    /// Every comment ID in my session, with its content
+    mine: Vec<(String, String)>,
+    seen: HashSet<String>,
+    summary: SessionMerge,
+}
+
+impl Merger<'_> {
+    fn merge(&mut self, target: Target<'_>, theirs: &Comment) {
+        let (comments, path) = match target {
+            Target::Review(comments) => (comments, None),
+            Target::File(comments, path) => (comments, Some(path)),
+        };
+        if let Some((_, content)) = self.mine.iter().find(|(id, _)| *id == theirs.id) {
+            if *content != theirs.content {
+                self.summary.conflicts.push(MergeConflict::EditedComment {
+                    path,
+                    id: theirs.id.clone(),
+                });
+            } else {
+                self.summary.duplicates += 1;
+            }
+            return;
+        }
+        let same_text = comments
+            .iter()
+            .any(|c| c.content == theirs.content && c.side == theirs.side);
+        if same_text || !self.seen.insert(theirs.id.clone()) {
+            self.summary.duplicates += 1;
+            return;
+        }
+        let mut comment = theirs.clone();
+        if comment.reviewer.is_none() {
+            comment.reviewer = self.author.cloned();
+        }
+        comments.push(comment);
+        self.summary.comments += 1;
+    }
+}
+
+/// Merge `theirs` into `mine`: add the comments `mine` doesn't have, combine
+/// reviewed marks per `policy` and report where the sessions disagree.
+pub fn merge_session(
+    mine: &mut ReviewSession,
+    theirs: &ReviewSession,
+    policy: ReviewedPolicy,
+) -> SessionMerge {
+    let ids = mine
+        .review_comments
+        .iter()
+        .chain(mine.files.values().flat_map(|f| {
+            f.file_comments
+                .iter()
+                .chain(f.line_comments.values().flatten())
+        }))
+        .map(|c| (c.id.clone(), c.content.clone()))
+        .collect::<Vec<_>>();
+    let mut merger = Merger {
+        author: theirs.reviewer.as_ref(),
+        seen: ids.iter().map(|(id, _)| id.clone()).collect(),
+        mine: ids,
+        summary: SessionMerge::default(),
+    };
+
+    if mine.base_commit != theirs.base_commit {
+        merger.summary.conflicts.push(MergeConflict::BaseCommit {
+            mine: mine.base_commit.clone(),
+            theirs: theirs.base_commit.clone(),
+        });
+    }
+
+    for comment in &theirs.review_comments {
+        merger.merge(Target::Review(&mut mine.review_comments), comment);
+    }
+
+    let mut paths: Vec<&PathBuf> = theirs.files.keys().collect();
+    paths.sort();
+    for path in paths {
+        let their_file = &theirs.files[path];
+        let Some(local) = mine.files.get_mut(path) else {
+            merger.summary.skipped_files.push(path.clone());
+            continue;
+        };
+
+        for comment in &their_file.file_comments {
+            merger.merge(
+                Target::File(&mut local.file_comments, path.clone()),
+                comment,
+            );
+        }
+        let mut lines: Vec<_> = their_file.line_comments.iter().collect();
+        lines.sort_by_key(|(line, _)| **line);
+        for (line, comments) in lines {
+            for comment in comments {
+                let existing = local.line_comments.entry(*line).or_default();
+                merger.merge(Target::File(existing, path.clone()), comment);
+            }
+            if local.line_comments.get(line).is_some_and(Vec::is_empty) {
+                local.line_comments.remove(line);
+            }
+        }
+
+        let same_version = match (local.content_hash, their_file.content_hash) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        match (local.reviewed, their_file.reviewed) {
+            (true, true) if local.confidence != their_file.confidence => {
+                merger.summary.conflicts.push(MergeConflict::Confidence {
+                    path: path.clone(),
+                    mine: local.confidence,
+                    theirs: their_file.confidence,
+                });
+            }
+            (false, true) if !same_version => {
+                merger
+                    .summary
+                    .conflicts
+                    .push(MergeConflict::ReviewedOtherVersion { path: path.clone() });
+            }
+            (false, true) => {
+                let kept = policy == ReviewedPolicy::Union;
+                if kept {
+                    local.reviewed = true;
+                    local.confidence = their_file.confidence;
+                    merger.summary.reviewed += 1;
+                }
+                merger.summary.conflicts.push(MergeConflict::Reviewed {
+                    path: path.clone(),
+                    by_me: false,
+                    kept,
+                });
+            }
+            (true, false) => {
+                let kept = policy == ReviewedPolicy::Union;
+                if !kept {
+                    local.reviewed = false;
+                    merger.summary.unreviewed += 1;
+                }
+                merger.summary.conflicts.push(MergeConflict::Reviewed {
+                    path: path.clone(),
+                    by_me: true,
+                    kept,
+                });
+            }
+            _ => {}
+        }
+    }
+    merger.summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CommentType, FileStatus, LineSide, SessionDiffSource};
+
+    fn session(reviewer: &str) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/src/repo"),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.reviewer = Reviewer::parse(reviewer);
+        for path in ["a.rs", "b.rs", "c.rs"] {
+            session.add_file(PathBuf::from(path), FileStatus::Modified, 7);
+        }
+        session
+    }
+
+    fn line_comment(content: &str) -> Comment {
+        Comment::new(content.to_string(), CommentType::Issue, Some(LineSide::New))
+    }
+
+    #[test]
+    fn should_union_comments_and_attribute_theirs() {
+        // given
+        let mut mine = session("Ann");
+        let mut theirs = session("Bob");
+        let shared = line_comment("shared");
+        let mut edited = shared.clone();
+        edited.content = "shared, reworded".to_string();
+        let a = PathBuf::from("a.rs");
+        mine.get_file_mut(&a).unwrap().add_line_comment(3, shared);
+        mine.get_file_mut(&a)
+            .unwrap()
+            .add_line_comment(4, line_comment("same text"));
+        let their_a = theirs.get_file_mut(&a).unwrap();
+        their_a.add_line_comment(3, edited);
+        their_a.add_line_comment(4, line_comment("same text"));
+        their_a.add_line_comment(9, line_comment("new"));
+        theirs.add_file(PathBuf::from("d.rs"), FileStatus::Added, 1);
+
+        // when
+        let summary = merge_session(&mut mine, &theirs, ReviewedPolicy::Union);
+
+        // then
+        assert_eq!(summary.comments, 1);
+        assert_eq!(summary.duplicates, 1);
+        assert_eq!(summary.skipped_files, vec![PathBuf::from("d.rs")]);
+        let merged = &mine.files[&a].line_comments[&9][0];
+        assert_eq!(merged.reviewer.as_ref().unwrap().name, "Bob");
+        assert_eq!(mine.files[&a].line_comments[&3][0].content, "shared");
+        assert!(matches!(
+            summary.conflicts[..],
+            [MergeConflict::EditedComment { .. }]
+        ));
+    }
+
+    #[test]
+    fn should_combine_reviewed_marks_per_policy() {
+        // given
+        let mut theirs = session("Bob");
+        let (a, b, c) = (
+            PathBuf::from("a.rs"),
+            PathBuf::from("b.rs"),
+            PathBuf::from("c.rs"),
+        );
+        theirs.get_file_mut(&b).unwrap().reviewed = true;
+        theirs.get_file_mut(&c).unwrap().reviewed = true;
+        theirs.get_file_mut(&c).unwrap().content_hash = Some(8);
+        let mine = || {
+            let mut mine = session("Ann");
+            mine.get_file_mut(&a).unwrap().reviewed = true;
+            mine
+        };
+
+        // when
+        let mut union = mine();
+        let union_summary = merge_session(&mut union, &theirs, ReviewedPolicy::Union);
+        let mut intersect = mine();
+        let intersect_summary = merge_session(&mut intersect, &theirs, ReviewedPolicy::Intersect);
+
+        // then
+        let reviewed = |s: &ReviewSession| [&a, &b, &c].map(|p| s.files[p].reviewed);
+        assert_eq!(reviewed(&union), [true, true, false]);
+        assert_eq!(reviewed(&intersect), [false, false, false]);
+        assert_eq!((union_summary.reviewed, union_summary.unreviewed), (1, 0));
+        assert_eq!(
+            (intersect_summary.reviewed, intersect_summary.unreviewed),
+            (0, 1)
+        );
+        assert_eq!(
+            intersect_summary.conflicts,
+            vec![
+                MergeConflict::Reviewed {
+                    path: a.clone(),
+                    by_me: true,
+                    kept: false
+                },
+                MergeConflict::Reviewed {
+                    path: b.clone(),
+                    by_me: false,
+                    kept: false
+                },
+                MergeConflict::ReviewedOtherVersion { path: c.clone() },
+            ]
+        );
+    }
+}
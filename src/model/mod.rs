@@ -1,7 +1,17 @@
+pub mod anchor;
+pub mod checkpoint;
 pub mod comment;
+pub mod conflicts;
 pub mod diff_types;
+pub mod merge;
 pub mod review;
+pub mod word_diff;
 
-pub use comment::{Comment, CommentType, LineRange, LineSide};
-pub use diff_types::{DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin};
-pub use review::{ClearScope, ReviewSession, SessionDiffSource};
+pub use comment::{
+    Comment, CommentType, LineRange, LineSide, Reviewer, conventional_label, decorated_label,
+};
+pub use diff_types::{DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin};
+pub use review::{
+    ClearScope, ReviewConfidence, ReviewEdit, ReviewEventKind, ReviewSession, SessionCursor,
+    SessionDiffSource, SpotCheck,
+};
@@ -1,10 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use super::comment::Comment;
-use super::diff_types::FileStatus;
+use super::checkpoint::Checkpoint;
+use super::comment::{Comment, CommentType, LineSide, Reviewer};
+use super::diff_types::{DiffFile, DiffHunk, FileStatus};
 use crate::forge::remote_comments::PrCommentsVisibility;
 use crate::forge::traits::PrSessionKey;
 
@@ -14,15 +15,120 @@ pub enum ClearScope {
     CommentsAndReviewed,
 }
 
+/// How closely a reviewed file was read, so a review can be honest about
+/// where it only skimmed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewConfidence {
+    Skimmed,
+    #[default]
+    Reviewed,
+    Thorough,
+}
+
+impl ReviewConfidence {
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id.to_ascii_lowercase().as_str() {
+            "skimmed" | "skim" => Some(Self::Skimmed),
+            "reviewed" => Some(Self::Reviewed),
+            "thorough" | "thoroughly" => Some(Self::Thorough),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Skimmed => "skimmed",
+            Self::Reviewed => "reviewed",
+            Self::Thorough => "thoroughly reviewed",
+        }
+    }
+
+    /// Check mark shown in file headers and summaries.
+    pub fn mark(self) -> &'static str {
+        match self {
+            Self::Skimmed => "~",
+            Self::Reviewed => "✓",
+            Self::Thorough => "✔✔",
+        }
+    }
+
+    /// skimmed → reviewed → thorough → skimmed
+    pub fn next(self) -> Self {
+        match self {
+            Self::Skimmed => Self::Reviewed,
+            Self::Reviewed => Self::Thorough,
+            Self::Thorough => Self::Skimmed,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileReview {
     pub path: PathBuf,
     pub reviewed: bool,
+    /// Only meaningful while `reviewed` is set
+    #[serde(default)]
+    pub confidence: ReviewConfidence,
     pub status: FileStatus,
     pub file_comments: Vec<Comment>,
     pub line_comments: HashMap<u32, Vec<Comment>>,
     #[serde(default)]
     pub content_hash: Option<u64>,
+    /// Lines the reviewer means to revisit before finishing, in the order
+    /// they were marked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deferred: Vec<DeferredLine>,
+    /// Content hash the file was reviewed at, kept once a change to its
+    /// diff dropped the reviewed mark: the file is changed since review, and
+    /// gets the mark back if its diff returns to this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewed_hash: Option<u64>,
+    /// Milliseconds spent with the file on screen, idle stretches left out
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub active_ms: u64,
+}
+
+fn is_zero(ms: &u64) -> bool {
+    *ms == 0
+}
+
+/// `1h 05m`, `42m`
+pub fn format_duration(secs: u64) -> String {
+    let minutes = secs.div_ceil(60);
+    match minutes / 60 {
+        0 => format!("{minutes}m"),
+        hours => format!("{hours}h {:02}m", minutes % 60),
+    }
+}
+
+/// Review state of one commit of a range, from viewing it on its own in the
+/// inline commit selector. The squashed view of the range keeps using
+/// `ReviewSession::files`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitReview {
+    /// Files in the commit's own diff, as of the last file marked in it
+    #[serde(default)]
+    pub files: usize,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub reviewed: BTreeMap<PathBuf, ReviewConfidence>,
+    /// Ids of comments written while the commit was shown on its own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<String>,
+}
+
+impl CommitReview {
+    pub fn is_complete(&self) -> bool {
+        self.files > 0 && self.reviewed.len() >= self.files
+    }
+}
+
+/// A line marked "revisit before finishing". Unlike a comment it is a note
+/// to the reviewer: only the JSON export carries it, for `tuicr check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeferredLine {
+    pub line: u32,
+    pub side: LineSide,
 }
 
 impl FileReview {
@@ -30,13 +136,27 @@ impl FileReview {
         Self {
             path,
             reviewed: false,
+            confidence: ReviewConfidence::default(),
             status,
             file_comments: Vec::new(),
             line_comments: HashMap::new(),
             content_hash: Some(content_hash),
+            deferred: Vec::new(),
+            reviewed_hash: None,
+            active_ms: 0,
         }
     }
 
+    /// Reviewed once, but its diff has changed since
+    pub fn changed_since_review(&self) -> bool {
+        !self.reviewed && self.reviewed_hash.is_some()
+    }
+
+    /// The confidence the file was reviewed with, `None` while unreviewed.
+    pub fn review_confidence(&self) -> Option<ReviewConfidence> {
+        self.reviewed.then_some(self.confidence)
+    }
+
     pub fn comment_count(&self) -> usize {
         self.file_comments.len() + self.line_comments.values().map(|v| v.len()).sum::<usize>()
     }
@@ -48,6 +168,204 @@ impl FileReview {
     pub fn add_line_comment(&mut self, line: u32, comment: Comment) {
         self.line_comments.entry(line).or_default().push(comment);
     }
+
+    pub fn is_deferred(&self, line: u32, side: LineSide) -> bool {
+        self.deferred.contains(&DeferredLine { line, side })
+    }
+
+    /// Marks or unmarks `line` as deferred. Returns true if it is now marked.
+    pub fn toggle_deferred(&mut self, line: u32, side: LineSide) -> bool {
+        let marker = DeferredLine { line, side };
+        if let Some(idx) = self.deferred.iter().position(|d| *d == marker) {
+            self.deferred.remove(idx);
+            false
+        } else {
+            self.deferred.push(marker);
+            true
+        }
+    }
+}
+
+/// A review checklist entry, typically seeded from a session template.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            done: false,
+        }
+    }
+}
+
+/// A reviewer's one-line fix, held in the session instead of being written to
+/// the working tree. Exported as a patch alongside the comments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewEdit {
+    pub path: PathBuf,
+    /// New-side line number the edit replaces.
+    pub line: u32,
+    pub original: String,
+    pub replacement: String,
+}
+
+/// A deleted comment kept so it can be restored from `:trash`. `path` and
+/// `line` say where it lived; both are `None` for review-level comments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashedComment {
+    pub comment: Comment,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl TrashedComment {
+    /// `path:line`, `path`, or `review` for review-level comments.
+    pub fn location(&self) -> String {
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) => format!("{}:{line}", path.display()),
+            (Some(path), None) => path.display().to_string(),
+            (None, _) => "review".to_string(),
+        }
+    }
+}
+
+/// What a `ReviewEvent` records. Comment events carry where the comment was
+/// and its type, so the log still reads sensibly after the comment is gone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReviewEventKind {
+    FileReviewed {
+        path: PathBuf,
+    },
+    FileUnreviewed {
+        path: PathBuf,
+    },
+    CommentAdded {
+        path: Option<PathBuf>,
+        line: Option<u32>,
+        comment_type: CommentType,
+    },
+    CommentEdited {
+        path: Option<PathBuf>,
+        line: Option<u32>,
+        comment_type: CommentType,
+    },
+    CommentDeleted {
+        path: Option<PathBuf>,
+        line: Option<u32>,
+        comment_type: CommentType,
+    },
+}
+
+impl ReviewEventKind {
+    /// File the event is about; `None` for review-level comments.
+    pub fn path(&self) -> Option<&PathBuf> {
+        match self {
+            Self::FileReviewed { path } | Self::FileUnreviewed { path } => Some(path),
+            Self::CommentAdded { path, .. }
+            | Self::CommentEdited { path, .. }
+            | Self::CommentDeleted { path, .. } => path.as_ref(),
+        }
+    }
+}
+
+/// One entry of the session's append-only event log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewEvent {
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: ReviewEventKind,
+}
+
+/// A hunk picked by `:spotcheck`, identified by its file and header.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampledHunk {
+    pub path: PathBuf,
+    pub old_start: u32,
+    pub new_start: u32,
+}
+
+/// A random sample of hunks for spot-checking a huge mechanical diff
+/// instead of reading all of it. Kept with the session so exports can state
+/// how much of the change was actually looked at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpotCheck {
+    /// In diff order
+    pub sampled: Vec<SampledHunk>,
+    pub total_hunks: usize,
+}
+
+impl SpotCheck {
+    /// Pick `count` distinct hunks (every hunk if there are fewer) across
+    /// `files`, skipping the commit message. `seed` makes the pick
+    /// reproducible.
+    pub fn sample(files: &[DiffFile], count: usize, seed: u64) -> Self {
+        let candidates: Vec<SampledHunk> = files
+            .iter()
+            .filter(|file| !file.is_commit_message)
+            .flat_map(|file| {
+                file.hunks.iter().map(|hunk| SampledHunk {
+                    path: file.display_path().clone(),
+                    old_start: hunk.old_start,
+                    new_start: hunk.new_start,
+                })
+            })
+            .collect();
+        let total_hunks = candidates.len();
+
+        // Partial Fisher-Yates over indices with a splitmix64 stream.
+        let mut state = seed;
+        let mut next = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        let mut indices: Vec<usize> = (0..total_hunks).collect();
+        let count = count.min(total_hunks);
+        for i in 0..count {
+            let j = i + (next() % (total_hunks - i) as u64) as usize;
+            indices.swap(i, j);
+        }
+        let mut picked = indices[..count].to_vec();
+        picked.sort_unstable();
+
+        Self {
+            sampled: picked.into_iter().map(|i| candidates[i].clone()).collect(),
+            total_hunks,
+        }
+    }
+
+    pub fn contains(&self, path: &Path, hunk: &DiffHunk) -> bool {
+        self.sampled.iter().any(|sampled| {
+            sampled.path == path
+                && sampled.old_start == hunk.old_start
+                && sampled.new_start == hunk.new_start
+        })
+    }
+
+    /// `12 of 480 hunks (2.5%)`
+    pub fn coverage(&self) -> String {
+        let percent = if self.total_hunks == 0 {
+            0.0
+        } else {
+            self.sampled.len() as f64 * 100.0 / self.total_hunks as f64
+        };
+        format!(
+            "{} of {} hunks ({percent:.1}%)",
+            self.sampled.len(),
+            self.total_hunks
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -68,6 +386,18 @@ pub enum SessionDiffSource {
     PullRequest,
 }
 
+/// Where the diff cursor was when the session was last saved, so a resumed
+/// review picks up at the same place.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionCursor {
+    pub path: PathBuf,
+    /// Source line under the cursor; `None` on a file header or hunk header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<LineSide>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReviewSession {
     pub id: String,
@@ -102,6 +432,52 @@ pub struct ReviewSession {
     pub review_comments: Vec<Comment>,
     pub files: HashMap<PathBuf, FileReview>,
     pub session_notes: Option<String>,
+    /// Name of the `--template` the session was started from, if any.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Review checklist. Sessions saved before templates existed
+    /// deserialize with an empty list.
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// Stash of review-only line edits. Never applied to the working tree.
+    #[serde(default)]
+    pub review_edits: Vec<ReviewEdit>,
+    /// Append-only log of review actions, oldest first. Sessions saved
+    /// before the log existed start with an empty one.
+    #[serde(default)]
+    pub events: Vec<ReviewEvent>,
+    /// Hunks sampled by `:spotcheck`, if the diff is being spot-checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spot_check: Option<SpotCheck>,
+    /// Who is reviewing, stamped on the comments they write
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviewer: Option<Reviewer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<SessionCursor>,
+    /// Name given in the session picker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Ids of line comments whose line wasn't found again after the diff
+    /// changed; `:verify` lists them until they are fixed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub orphaned: Vec<String>,
+    /// Ids of line comments whose line stayed put but reads differently or
+    /// sits among different lines; `:verify` lists them until the reviewer
+    /// keeps them on it (which records what the line reads now).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drifted: Vec<String>,
+    /// Deleted comments, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trash: Vec<TrashedComment>,
+    /// Per-commit review state of a commit range, by full commit id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub commits: BTreeMap<String, CommitReview>,
+    /// Milliseconds of active reviewing, idle stretches left out
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub active_ms: u64,
+    /// Snapshots taken with `:checkpoint <label>`, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<Checkpoint>,
 }
 
 impl ReviewSession {
@@ -128,23 +504,82 @@ impl ReviewSession {
             review_comments: Vec::new(),
             files: HashMap::new(),
             session_notes: None,
+            template: None,
+            checklist: Vec::new(),
+            review_edits: Vec::new(),
+            events: Vec::new(),
+            spot_check: None,
+            reviewer: None,
+            cursor: None,
+            name: None,
+            orphaned: Vec::new(),
+            drifted: Vec::new(),
+            trash: Vec::new(),
+            commits: BTreeMap::new(),
+            active_ms: 0,
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// Credit `elapsed` of active reviewing to the session and, when one
+    /// was on screen, to the file at `path`.
+    pub fn add_active_time(&mut self, path: Option<&Path>, elapsed: std::time::Duration) {
+        let ms = elapsed.as_millis() as u64;
+        self.active_ms += ms;
+        if let Some(review) = path.and_then(|path| self.files.get_mut(path)) {
+            review.active_ms += ms;
         }
     }
 
+    /// Append an event stamped with the current time.
+    pub fn record_event(&mut self, kind: ReviewEventKind) {
+        self.events.push(ReviewEvent {
+            at: Utc::now(),
+            kind,
+        });
+    }
+
     pub fn reviewed_count(&self) -> usize {
         self.files.values().filter(|f| f.reviewed).count()
     }
 
+    /// Every file in the session is reviewed
+    pub fn is_complete(&self) -> bool {
+        !self.files.is_empty() && self.reviewed_count() == self.files.len()
+    }
+
+    /// Lines still marked to revisit, across every file
+    pub fn deferred_count(&self) -> usize {
+        self.files.values().map(|f| f.deferred.len()).sum()
+    }
+
+    /// Review comments plus every file and line comment
+    pub fn comment_count(&self) -> usize {
+        self.review_comments.len()
+            + self
+                .files
+                .values()
+                .map(|f| f.comment_count())
+                .sum::<usize>()
+    }
+
     /// Registers a file in the session. Returns true if the file was previously
     /// reviewed but its content changed, causing reviewed status to be reset.
+    /// A file whose diff is back to the one it was reviewed at is marked
+    /// reviewed again.
     pub fn add_file(&mut self, path: PathBuf, status: FileStatus, content_hash: u64) -> bool {
         if let Some(review) = self.files.get_mut(&path) {
             let old_hash = review.content_hash;
             review.content_hash = Some(content_hash);
             if review.reviewed && old_hash != Some(content_hash) {
                 review.reviewed = false;
+                review.reviewed_hash = old_hash;
                 return true;
             }
+            if review.changed_since_review() && review.reviewed_hash == Some(content_hash) {
+                review.reviewed = true;
+                review.reviewed_hash = None;
+            }
             return false;
         }
         self.files
@@ -160,25 +595,242 @@ impl ReviewSession {
         !self.review_comments.is_empty() || self.files.values().any(|f| f.comment_count() > 0)
     }
 
+    /// True when an export would have something to say: comments or
+    /// stashed review fixes.
+    pub fn has_exportable_content(&self) -> bool {
+        self.has_comments() || !self.review_edits.is_empty()
+    }
+
     pub fn clear_comments(&mut self, scope: ClearScope) -> (usize, usize) {
-        let mut cleared = self.review_comments.len();
+        let now = Utc::now();
+        let mut trashed: Vec<TrashedComment> = self
+            .review_comments
+            .drain(..)
+            .map(|comment| TrashedComment {
+                comment,
+                path: None,
+                line: None,
+                deleted_at: now,
+            })
+            .collect();
         let mut unreviewed = 0;
-        self.review_comments.clear();
         for file in self.files.values_mut() {
-            cleared += file.comment_count();
-            file.file_comments.clear();
-            file.line_comments.clear();
-            if scope == ClearScope::CommentsAndReviewed && file.reviewed {
-                file.reviewed = false;
-                unreviewed += 1;
+            for comment in file.file_comments.drain(..) {
+                trashed.push(TrashedComment {
+                    comment,
+                    path: Some(file.path.clone()),
+                    line: None,
+                    deleted_at: now,
+                });
+            }
+            for (line, comments) in file.line_comments.drain() {
+                for comment in comments {
+                    trashed.push(TrashedComment {
+                        comment,
+                        path: Some(file.path.clone()),
+                        line: Some(line),
+                        deleted_at: now,
+                    });
+                }
+            }
+            if scope == ClearScope::CommentsAndReviewed {
+                file.reviewed_hash = None;
+                if file.reviewed {
+                    file.reviewed = false;
+                    unreviewed += 1;
+                }
             }
         }
+        let cleared = trashed.len();
+        self.trash.extend(trashed);
         (cleared, unreviewed)
     }
 
-    pub fn is_file_reviewed(&self, path: &PathBuf) -> bool {
+    /// Moves a deleted comment to the trash, stamped with the current time.
+    pub fn trash_comment(&mut self, comment: Comment, path: Option<PathBuf>, line: Option<u32>) {
+        self.trash.push(TrashedComment {
+            comment,
+            path,
+            line,
+            deleted_at: Utc::now(),
+        });
+    }
+
+    /// The comment with `id` and where it sits: `None` path for review
+    /// comments, `None` line for file comments.
+    pub fn find_comment(&self, id: &str) -> Option<(Option<&PathBuf>, Option<u32>, &Comment)> {
+        if let Some(comment) = self.review_comments.iter().find(|c| c.id == id) {
+            return Some((None, None, comment));
+        }
+        self.files.iter().find_map(|(path, file)| {
+            if let Some(comment) = file.file_comments.iter().find(|c| c.id == id) {
+                return Some((Some(path), None, comment));
+            }
+            file.line_comments.iter().find_map(|(line, comments)| {
+                comments
+                    .iter()
+                    .find(|c| c.id == id)
+                    .map(|comment| (Some(path), Some(*line), comment))
+            })
+        })
+    }
+
+    /// Remove the comment with `id`, returning it with where it was.
+    pub fn take_comment(&mut self, id: &str) -> Option<(Option<PathBuf>, Option<u32>, Comment)> {
+        let (path, line) = self
+            .find_comment(id)
+            .map(|(path, line, _)| (path.cloned(), line))?;
+        let comments = match (&path, line) {
+            (None, _) => &mut self.review_comments,
+            (Some(path), None) => &mut self.files.get_mut(path)?.file_comments,
+            (Some(path), Some(line)) => self.files.get_mut(path)?.line_comments.get_mut(&line)?,
+        };
+        let index = comments.iter().position(|c| c.id == id)?;
+        let comment = comments.remove(index);
+        if let (Some(path), Some(line)) = (&path, line)
+            && let Some(file) = self.files.get_mut(path)
+            && file.line_comments.get(&line).is_some_and(Vec::is_empty)
+        {
+            file.line_comments.remove(&line);
+        }
+        Some((path, line, comment))
+    }
+
+    /// Puts the trashed comment at `index` back where it was deleted from.
+    /// A comment whose file has left the diff comes back as a review comment
+    /// so it isn't lost. Returns the restored entry.
+    pub fn restore_trashed(&mut self, index: usize) -> Option<TrashedComment> {
+        if index >= self.trash.len() {
+            return None;
+        }
+        let entry = self.trash.remove(index);
+        let comment = entry.comment.clone();
+        match entry.path.as_ref().and_then(|p| self.files.get_mut(p)) {
+            Some(file) => match entry.line {
+                Some(line) => file.add_line_comment(line, comment),
+                None => file.add_file_comment(comment),
+            },
+            None => self.review_comments.push(comment),
+        }
+        Some(entry)
+    }
+
+    pub fn is_file_reviewed(&self, path: &Path) -> bool {
         self.files.get(path).map(|r| r.reviewed).unwrap_or(false)
     }
+
+    pub fn file_confidence(&self, path: &Path) -> Option<ReviewConfidence> {
+        self.files.get(path).and_then(FileReview::review_confidence)
+    }
+
+    /// How closely `path` was reviewed in `commit`'s own diff, if at all.
+    pub fn commit_file_confidence(&self, commit: &str, path: &Path) -> Option<ReviewConfidence> {
+        self.commits.get(commit)?.reviewed.get(path).copied()
+    }
+
+    /// Mark `path` reviewed with `confidence` in `commit`'s own diff of
+    /// `files` files, or unreviewed with `None`.
+    pub fn set_commit_file_confidence(
+        &mut self,
+        commit: &str,
+        path: &Path,
+        files: usize,
+        confidence: Option<ReviewConfidence>,
+    ) {
+        let review = self.commits.entry(commit.to_string()).or_default();
+        review.files = files;
+        match confidence {
+            Some(confidence) => {
+                review.reviewed.insert(path.to_path_buf(), confidence);
+            }
+            None => {
+                review.reviewed.remove(path);
+            }
+        }
+    }
+
+    /// Comments written on `commit` that haven't been deleted since.
+    pub fn commit_comment_count(&self, commit: &str) -> usize {
+        let Some(review) = self.commits.get(commit) else {
+            return 0;
+        };
+        let live: HashSet<&str> = self
+            .review_comments
+            .iter()
+            .chain(self.files.values().flat_map(|f| {
+                f.file_comments
+                    .iter()
+                    .chain(f.line_comments.values().flatten())
+            }))
+            .map(|c| c.id.as_str())
+            .collect();
+        review
+            .comments
+            .iter()
+            .filter(|id| live.contains(id.as_str()))
+            .count()
+    }
+
+    /// Seeds the checklist from a template. A session that already carries a
+    /// checklist (e.g. a resumed review) keeps its items and their state.
+    /// Returns true if the checklist was populated.
+    pub fn apply_template(&mut self, name: &str, checklist: &[String]) -> bool {
+        self.template = Some(name.to_string());
+        if !self.checklist.is_empty() {
+            return false;
+        }
+        self.checklist = checklist.iter().map(ChecklistItem::new).collect();
+        !self.checklist.is_empty()
+    }
+
+    /// Records a review-only edit for `path:line`. Re-editing a line keeps the
+    /// original text so the exported patch still applies to the reviewed tree;
+    /// editing it back to the original drops the edit.
+    pub fn set_review_edit(
+        &mut self,
+        path: PathBuf,
+        line: u32,
+        original: String,
+        replacement: String,
+    ) {
+        if let Some(idx) = self
+            .review_edits
+            .iter()
+            .position(|e| e.path == path && e.line == line)
+        {
+            if self.review_edits[idx].original == replacement {
+                self.review_edits.remove(idx);
+            } else {
+                self.review_edits[idx].replacement = replacement;
+            }
+            return;
+        }
+        if original == replacement {
+            return;
+        }
+        self.review_edits.push(ReviewEdit {
+            path,
+            line,
+            original,
+            replacement,
+        });
+    }
+
+    /// Drops the review-only edit for `path:line`. Returns true if one existed.
+    pub fn remove_review_edit(&mut self, path: &PathBuf, line: u32) -> bool {
+        let before = self.review_edits.len();
+        self.review_edits
+            .retain(|e| !(&e.path == path && e.line == line));
+        self.review_edits.len() != before
+    }
+
+    /// Flips the done state of the 1-based checklist item `index`. Returns the
+    /// new state, or `None` if the index is out of range.
+    pub fn toggle_checklist_item(&mut self, index: usize) -> Option<bool> {
+        let item = self.checklist.get_mut(index.checked_sub(1)?)?;
+        item.done = !item.done;
+        Some(item.done)
+    }
 }
 
 #[cfg(test)]
@@ -293,6 +945,36 @@ mod tests {
         assert!(!session.is_file_reviewed(&path));
     }
 
+    #[test]
+    fn should_restore_cleared_comments_from_trash() {
+        // given
+        let mut session = test_session();
+        let path = PathBuf::from("src/lib.rs");
+        session.add_file(path.clone(), FileStatus::Modified, SOME_HASH);
+        session.get_file_mut(&path).unwrap().add_line_comment(
+            7,
+            Comment::new("line".to_string(), CommentType::Issue, None),
+        );
+        session.trash_comment(
+            Comment::new("gone".to_string(), CommentType::Note, None),
+            Some(PathBuf::from("removed.rs")),
+            None,
+        );
+        session.clear_comments(ClearScope::CommentsOnly);
+
+        // when
+        let restored = session.restore_trashed(1).unwrap();
+        let orphan = session.restore_trashed(0).unwrap();
+
+        // then
+        assert_eq!(restored.line, Some(7));
+        assert_eq!(session.files[&path].line_comments[&7][0].content, "line");
+        assert_eq!(orphan.comment.content, "gone");
+        assert_eq!(session.review_comments[0].content, "gone");
+        assert!(session.trash.is_empty());
+        assert!(session.restore_trashed(0).is_none());
+    }
+
     #[test]
     fn should_preserve_reviewed_status_when_requested() {
         let mut session = test_session();
@@ -347,6 +1029,36 @@ mod tests {
         assert!(!session.is_file_reviewed(&path));
     }
 
+    #[test]
+    fn should_expose_confidence_only_for_reviewed_files() {
+        let mut session = test_session();
+        let path = PathBuf::from("deep.rs");
+        session.add_file(path.clone(), FileStatus::Modified, SOME_HASH);
+        session.get_file_mut(&path).unwrap().confidence = ReviewConfidence::Thorough;
+        assert_eq!(session.file_confidence(&path), None);
+
+        session.get_file_mut(&path).unwrap().reviewed = true;
+
+        assert_eq!(
+            session.file_confidence(&path),
+            Some(ReviewConfidence::Thorough)
+        );
+        assert_eq!(ReviewConfidence::Thorough.next(), ReviewConfidence::Skimmed);
+        assert_eq!(
+            ReviewConfidence::from_id("Skim"),
+            Some(ReviewConfidence::Skimmed)
+        );
+    }
+
+    #[test]
+    fn should_load_sessions_saved_without_confidence() {
+        let json = r#"{"path":"a.rs","reviewed":true,"status":"modified","file_comments":[],"line_comments":{}}"#;
+
+        let review: FileReview = serde_json::from_str(json).unwrap();
+
+        assert_eq!(review.review_confidence(), Some(ReviewConfidence::Reviewed));
+    }
+
     #[test]
     fn should_not_report_invalidated_for_unreviewed_file_with_changed_hash() {
         let mut session = test_session();
@@ -358,6 +1070,33 @@ mod tests {
         assert!(!session.is_file_reviewed(&path));
     }
 
+    #[test]
+    fn should_restore_reviewed_mark_when_diff_returns_to_reviewed_content() {
+        // given
+        let mut session = test_session();
+        let path = PathBuf::from("flaky.rs");
+        session.add_file(path.clone(), FileStatus::Modified, 100);
+        let review = session.get_file_mut(&path).unwrap();
+        review.reviewed = true;
+        review.confidence = ReviewConfidence::Thorough;
+
+        // when
+        session.add_file(path.clone(), FileStatus::Modified, 200);
+
+        // then
+        assert!(session.files[&path].changed_since_review());
+
+        // when
+        session.add_file(path.clone(), FileStatus::Modified, 100);
+
+        // then
+        assert!(!session.files[&path].changed_since_review());
+        assert_eq!(
+            session.file_confidence(&path),
+            Some(ReviewConfidence::Thorough)
+        );
+    }
+
     #[test]
     fn should_update_hash_even_when_not_reviewed() {
         let mut session = test_session();
@@ -487,10 +1226,14 @@ mod tests {
             FileReview {
                 path: path.clone(),
                 reviewed: true,
+                confidence: ReviewConfidence::default(),
                 status: FileStatus::Modified,
                 file_comments: Vec::new(),
                 line_comments: HashMap::new(),
                 content_hash: None,
+                deferred: Vec::new(),
+                reviewed_hash: None,
+                active_ms: 0,
             },
         );
 
@@ -499,4 +1242,161 @@ mod tests {
         assert!(!session.is_file_reviewed(&path));
         assert_eq!(session.files.get(&path).unwrap().content_hash, Some(999));
     }
+
+    #[test]
+    fn should_seed_checklist_from_template_once() {
+        // given
+        let mut session = test_session();
+        let items = vec![
+            "Inputs validated".to_string(),
+            "Secrets scrubbed".to_string(),
+        ];
+        // when
+        let seeded = session.apply_template("security", &items);
+        session.toggle_checklist_item(1);
+        let reseeded = session.apply_template("security", &items);
+        // then
+        assert!(seeded);
+        assert!(!reseeded);
+        assert_eq!(session.template.as_deref(), Some("security"));
+        assert_eq!(session.checklist.len(), 2);
+        assert!(session.checklist[0].done);
+    }
+
+    #[test]
+    fn should_toggle_checklist_item_by_one_based_index() {
+        let mut session = test_session();
+        session.apply_template("release", &["Tag pushed".to_string()]);
+
+        assert_eq!(session.toggle_checklist_item(1), Some(true));
+        assert_eq!(session.toggle_checklist_item(1), Some(false));
+        assert_eq!(session.toggle_checklist_item(0), None);
+        assert_eq!(session.toggle_checklist_item(2), None);
+    }
+
+    #[test]
+    fn should_deserialize_session_without_checklist() {
+        let session = test_session();
+        let mut json: serde_json::Value = serde_json::to_value(&session).unwrap();
+        let obj = json.as_object_mut().unwrap();
+        obj.remove("template");
+        obj.remove("checklist");
+
+        let restored: ReviewSession = serde_json::from_value(json).unwrap();
+        assert!(restored.template.is_none());
+        assert!(restored.checklist.is_empty());
+    }
+
+    #[test]
+    fn should_keep_original_text_when_reediting_a_line() {
+        // given
+        let mut session = test_session();
+        let path = PathBuf::from("src/lib.rs");
+        // when
+        session.set_review_edit(path.clone(), 3, "teh".into(), "the".into());
+        session.set_review_edit(path.clone(), 3, "the".into(), "The".into());
+        // then
+        assert_eq!(session.review_edits.len(), 1);
+        assert_eq!(session.review_edits[0].original, "teh");
+        assert_eq!(session.review_edits[0].replacement, "The");
+    }
+
+    #[test]
+    fn should_drop_review_edit_when_reverted_to_original() {
+        let mut session = test_session();
+        let path = PathBuf::from("src/lib.rs");
+
+        session.set_review_edit(path.clone(), 3, "teh".into(), "the".into());
+        session.set_review_edit(path.clone(), 3, "the".into(), "teh".into());
+        assert!(session.review_edits.is_empty());
+
+        session.set_review_edit(path.clone(), 4, "same".into(), "same".into());
+        assert!(session.review_edits.is_empty());
+    }
+
+    #[test]
+    fn should_remove_review_edit_by_location() {
+        let mut session = test_session();
+        let path = PathBuf::from("src/lib.rs");
+        session.set_review_edit(path.clone(), 3, "teh".into(), "the".into());
+
+        assert!(!session.remove_review_edit(&path, 4));
+        assert!(session.remove_review_edit(&path, 3));
+        assert!(session.review_edits.is_empty());
+    }
+
+    fn file_with_hunks(path: &str, starts: &[u32]) -> DiffFile {
+        let hunks: Vec<DiffHunk> = starts
+            .iter()
+            .map(|&start| DiffHunk {
+                header: format!("@@ -{start},1 +{start},1 @@"),
+                lines: Vec::new(),
+                old_start: start,
+                old_count: 1,
+                new_start: start,
+                new_count: 1,
+            })
+            .collect();
+        DiffFile {
+            old_path: Some(PathBuf::from(path)),
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: Default::default(),
+            sizes: Default::default(),
+        }
+    }
+
+    #[test]
+    fn should_sample_distinct_hunks_reproducibly_in_diff_order() {
+        // given
+        let files = vec![
+            file_with_hunks("a.rs", &[1, 20, 40, 60]),
+            file_with_hunks("b.rs", &[1, 10, 30, 50, 70, 90]),
+        ];
+
+        // when
+        let first = SpotCheck::sample(&files, 4, 7);
+        let second = SpotCheck::sample(&files, 4, 7);
+
+        // then
+        assert_eq!(first, second);
+        assert_eq!(first.total_hunks, 10);
+        assert_eq!(first.sampled.len(), 4);
+        let order: Vec<(PathBuf, u32)> = first
+            .sampled
+            .iter()
+            .map(|h| (h.path.clone(), h.new_start))
+            .collect();
+        let mut sorted = order.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(order, sorted);
+        let hunk = &files[0].hunks[0];
+        assert_eq!(
+            first.contains(Path::new("a.rs"), hunk),
+            order.contains(&(PathBuf::from("a.rs"), 1))
+        );
+        assert_eq!(first.coverage(), "4 of 10 hunks (40.0%)");
+    }
+
+    #[test]
+    fn should_sample_every_hunk_when_asking_for_more_than_exist() {
+        // given
+        let mut message = file_with_hunks("Commit Message", &[1]);
+        message.is_commit_message = true;
+        let files = vec![message, file_with_hunks("a.rs", &[1, 5])];
+
+        // when
+        let spot_check = SpotCheck::sample(&files, 50, 1);
+
+        // then
+        assert_eq!(spot_check.total_hunks, 2);
+        assert_eq!(spot_check.sampled.len(), 2);
+        assert_eq!(spot_check.coverage(), "2 of 2 hunks (100.0%)");
+    }
 }
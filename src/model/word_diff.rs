@@ -0,0 +1,210 @@
+//! Refining a hunk's changed lines down to the words or characters that
+//! differ. Each deleted line is paired with the added line in the same
+//! position of its change block, and the tokens outside their longest
+//! common subsequence are what changed.
+
+use std::ops::Range;
+
+use crate::model::{DiffLine, LineOrigin};
+
+/// Token pairs compared at most for one line pair; longer lines count as
+/// changed as a whole rather than stall the render
+const MAX_COMPARISONS: usize = 250_000;
+
+/// How finely a hunk's changed lines are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordDiffMode {
+    /// Identifier and whitespace runs, with punctuation one character each
+    Words,
+    Chars,
+}
+
+impl WordDiffMode {
+    /// The mode after `mode` in the off → words → chars → off cycle
+    pub fn cycle(mode: Option<Self>) -> Option<Self> {
+        match mode {
+            None => Some(WordDiffMode::Words),
+            Some(WordDiffMode::Words) => Some(WordDiffMode::Chars),
+            Some(WordDiffMode::Chars) => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WordDiffMode::Words => "word",
+            WordDiffMode::Chars => "char",
+        }
+    }
+}
+
+/// `(deleted, added)` line indices paired up within each run of deletions
+/// that is directly followed by additions.
+pub fn paired_lines(lines: &[DiffLine]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].origin != LineOrigin::Deletion {
+            i += 1;
+            continue;
+        }
+        let del_start = i;
+        while i < lines.len() && lines[i].origin == LineOrigin::Deletion {
+            i += 1;
+        }
+        let add_start = i;
+        while i < lines.len() && lines[i].origin == LineOrigin::Addition {
+            i += 1;
+        }
+        let count = (add_start - del_start).min(i - add_start);
+        pairs.extend((0..count).map(|k| (del_start + k, add_start + k)));
+    }
+    pairs
+}
+
+/// Byte ranges of each token of `line`.
+fn tokens(line: &str, mode: WordDiffMode) -> Vec<Range<usize>> {
+    let class = |ch: char| {
+        if ch.is_alphanumeric() || ch == '_' {
+            1
+        } else if ch.is_whitespace() {
+            2
+        } else {
+            0
+        }
+    };
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let mut prev_class = None;
+    for (idx, ch) in line.char_indices() {
+        let end = idx + ch.len_utf8();
+        let class = class(ch);
+        match tokens.last_mut() {
+            Some(last)
+                if mode == WordDiffMode::Words && class != 0 && prev_class == Some(class) =>
+            {
+                last.end = end;
+            }
+            _ => tokens.push(idx..end),
+        }
+        prev_class = Some(class);
+    }
+    tokens
+}
+
+/// Whether each token of `old` and of `new` is part of their longest
+/// common subsequence.
+fn common_tokens(old: &[&str], new: &[&str]) -> (Vec<bool>, Vec<bool>) {
+    let (n, m) = (old.len(), new.len());
+    // lengths[i][j]: LCS length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut old_common, mut new_common) = (vec![false; n], vec![false; m]);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            old_common[i] = true;
+            new_common[j] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (old_common, new_common)
+}
+
+/// Ranges of the tokens not in common, adjacent ones merged.
+fn changed(tokens: &[Range<usize>], common: &[bool]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (token, _) in tokens.iter().zip(common).filter(|(_, common)| !**common) {
+        match ranges.last_mut() {
+            Some(last) if last.end == token.start => last.end = token.end,
+            _ => ranges.push(token.clone()),
+        }
+    }
+    ranges
+}
+
+/// Byte ranges of `old` and of `new` that differ between the two lines.
+pub fn changed_ranges(
+    old: &str,
+    new: &str,
+    mode: WordDiffMode,
+) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokens(old, mode);
+    let new_tokens = tokens(new, mode);
+    if old_tokens.len() * new_tokens.len() > MAX_COMPARISONS {
+        let whole = |line: &str| std::iter::once(0..line.len()).collect();
+        return (whole(old), whole(new));
+    }
+    let old_text: Vec<&str> = old_tokens.iter().map(|t| &old[t.clone()]).collect();
+    let new_text: Vec<&str> = new_tokens.iter().map(|t| &new[t.clone()]).collect();
+    let (old_common, new_common) = common_tokens(&old_text, &new_text);
+    (
+        changed(&old_tokens, &old_common),
+        changed(&new_tokens, &new_common),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(origin: LineOrigin, content: &str) -> DiffLine {
+        DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: None,
+            new_lineno: None,
+            highlighted_spans: None,
+        }
+    }
+
+    #[test]
+    fn should_pair_deletions_with_the_additions_that_follow_them() {
+        // given
+        let lines = vec![
+            line(LineOrigin::Context, "fn main() {"),
+            line(LineOrigin::Deletion, "a"),
+            line(LineOrigin::Deletion, "b"),
+            line(LineOrigin::Addition, "c"),
+            line(LineOrigin::Context, "}"),
+            line(LineOrigin::Addition, "d"),
+        ];
+
+        // when
+        let pairs = paired_lines(&lines);
+
+        // then
+        assert_eq!(pairs, vec![(1, 3)]);
+    }
+
+    #[test]
+    fn should_narrow_changes_to_words_or_characters() {
+        // given
+        let old = "let total = count + 1;";
+        let new = "let total = counter + 2;";
+
+        // when
+        let words = changed_ranges(old, new, WordDiffMode::Words);
+        let chars = changed_ranges(old, new, WordDiffMode::Chars);
+
+        // then
+        let text = |line: &str, ranges: &[Range<usize>]| -> Vec<String> {
+            ranges.iter().map(|r| line[r.clone()].to_string()).collect()
+        };
+        assert_eq!(text(old, &words.0), vec!["count", "1"]);
+        assert_eq!(text(new, &words.1), vec!["counter", "2"]);
+        assert_eq!(text(old, &chars.0), vec!["1"]);
+        assert_eq!(text(new, &chars.1), vec!["er", "2"]);
+    }
+}
@@ -0,0 +1,75 @@
+//! Terminal notifications when a slow operation finishes, so a reviewer who
+//! switched to another tmux window or terminal tab hears about it. Which
+//! operations notify, and how, comes from the `[notify]` config section.
+
+use std::io::Write;
+
+use crate::config::NotifyMethod;
+
+const BEL: &str = "\x07";
+
+/// The kinds of operation `[notify]` is configured per.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    DiffLoad,
+    Export,
+    Push,
+}
+
+/// Escape sequence for `method`, or `None` for `Off`. The OSC 9 notification
+/// is wrapped in a DCS passthrough inside tmux, which otherwise swallows it
+/// (tmux needs `allow-passthrough on`). A bell needs no wrapping: tmux marks
+/// the window and forwards it itself.
+pub fn sequence(method: NotifyMethod, message: &str, in_tmux: bool) -> Option<String> {
+    match method {
+        NotifyMethod::Off => None,
+        NotifyMethod::Bell => Some(BEL.to_string()),
+        NotifyMethod::Osc => {
+            let message: String = message.chars().filter(|c| !c.is_control()).collect();
+            let osc = format!("\x1b]9;{message}{BEL}");
+            Some(if in_tmux {
+                format!("\x1bPtmux;{}\x1b\\", osc.replace('\x1b', "\x1b\x1b"))
+            } else {
+                osc
+            })
+        }
+    }
+}
+
+/// Write the notification to the terminal. Failures are ignored; a missed
+/// notification isn't worth interrupting the review for.
+pub fn emit(method: NotifyMethod, message: &str) {
+    let in_tmux = std::env::var_os("TMUX").is_some();
+    if let Some(seq) = sequence(method, message, in_tmux) {
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(seq.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_wrap_osc_notification_for_tmux() {
+        // given
+        let message = "tuicr: Reloaded 3 files\n";
+
+        // when
+        let plain = sequence(NotifyMethod::Osc, message, false);
+        let tmux = sequence(NotifyMethod::Osc, message, true);
+
+        // then
+        assert_eq!(plain.as_deref(), Some("\x1b]9;tuicr: Reloaded 3 files\x07"));
+        assert_eq!(
+            tmux.as_deref(),
+            Some("\x1bPtmux;\x1b\x1b]9;tuicr: Reloaded 3 files\x07\x1b\\")
+        );
+        assert_eq!(
+            sequence(NotifyMethod::Bell, message, true).as_deref(),
+            Some("\x07")
+        );
+        assert_eq!(sequence(NotifyMethod::Off, message, false), None);
+    }
+}
@@ -0,0 +1,339 @@
+//! The reviewed diff as a patch with the comments interleaved, written with
+//! `:export annotated-patch` or `:export <path>.annotated.patch`, so a
+//! review can travel with the patch through an email workflow.
+//!
+//! Comments are `#` lines right below the line they were left on (a range
+//! below its last line); file comments and comments on lines the diff
+//! doesn't show follow the file's headers. No diff line starts with `#`,
+//! so `grep -v '^#' <file> | git apply` applies the patch as it was
+//! reviewed. Binary files are left out with a note.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::app::{CommentTypeDefinition, DiffSource};
+use crate::error::Result;
+use crate::model::{Comment, DiffFile, FileStatus, LineOrigin, LineSide, ReviewSession};
+use crate::output::markdown::{export_comment_type_label, review_scope};
+
+/// Build the annotated patch.
+pub fn generate_annotated_patch(
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+) -> String {
+    let label = |comment: &Comment| export_comment_type_label(&comment.comment_type, comment_types);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# Review of {} from tuicr; comments are the lines starting with #.",
+        review_scope(diff_source)
+    );
+    let _ = writeln!(
+        out,
+        "# To apply the patch: grep -v '^#' <this file> | git apply"
+    );
+    if let Some(notes) = session.session_notes.as_deref().filter(|n| !n.is_empty()) {
+        let _ = writeln!(out, "#");
+        write_comment_text(&mut out, notes);
+    }
+    for comment in &session.review_comments {
+        let _ = writeln!(out, "#");
+        write_comment(&mut out, &label, None, comment);
+    }
+    // The commit message isn't part of the patch; its comments go on top.
+    if let Some(message) = diff_files.iter().find(|f| f.is_commit_message)
+        && let Some(review) = session.files.get(message.display_path())
+    {
+        for comment in review
+            .file_comments
+            .iter()
+            .chain(review.line_comments.values().flatten())
+        {
+            let _ = writeln!(out, "#");
+            write_comment(&mut out, &label, Some("commit message"), comment);
+        }
+    }
+
+    for file in diff_files.iter().filter(|f| !f.is_commit_message) {
+        write_file(&mut out, session, file, &label);
+    }
+    out
+}
+
+/// Write the annotated patch to `path`, creating parent directories as
+/// needed.
+pub fn export_annotated_patch_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+) -> Result<()> {
+    let content = generate_annotated_patch(session, diff_source, diff_files, comment_types);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn write_file(
+    out: &mut String,
+    session: &ReviewSession,
+    file: &DiffFile,
+    label: &dyn Fn(&Comment) -> String,
+) {
+    let path = file.display_path();
+    let review = session.files.get(path);
+    if file.is_binary || file.hunks.is_empty() {
+        let _ = writeln!(out, "# {} left out: binary or too large", path.display());
+        if let Some(review) = review {
+            for comment in review
+                .file_comments
+                .iter()
+                .chain(review.line_comments.values().flatten())
+            {
+                write_comment(out, label, None, comment);
+            }
+        }
+        return;
+    }
+
+    write_headers(out, file);
+    let mut line_comments: Vec<(u32, &Comment)> = review
+        .map(|r| {
+            r.line_comments
+                .iter()
+                .flat_map(|(line, comments)| comments.iter().map(|c| (*line, c)))
+                .collect()
+        })
+        .unwrap_or_default();
+    line_comments.sort_by_key(|(line, _)| *line);
+    let lines = || file.hunks.iter().flat_map(|hunk| &hunk.lines);
+    let is_shown = |line: u32, comment: &Comment| {
+        lines().any(|l| match comment.side {
+            Some(LineSide::Old) => l.old_lineno == Some(line),
+            _ => l.new_lineno == Some(line),
+        })
+    };
+
+    for comment in review
+        .map(|r| r.file_comments.as_slice())
+        .unwrap_or_default()
+    {
+        write_comment(out, label, None, comment);
+    }
+    for (line, comment) in &line_comments {
+        if !is_shown(*line, comment) {
+            let old = if comment.side == Some(LineSide::Old) {
+                " (removed)"
+            } else {
+                ""
+            };
+            write_comment(out, label, Some(&format!("line {line}{old}")), comment);
+        }
+    }
+
+    for hunk in &file.hunks {
+        let _ = writeln!(out, "{}", hunk.header_line());
+        for line in &hunk.lines {
+            let marker = match line.origin {
+                LineOrigin::Addition => '+',
+                LineOrigin::Deletion => '-',
+                LineOrigin::Context => ' ',
+            };
+            let _ = writeln!(out, "{marker}{}", line.content);
+            for (lineno, comment) in &line_comments {
+                let on = match comment.side {
+                    Some(LineSide::Old) => line.old_lineno,
+                    _ => line.new_lineno,
+                };
+                if on == Some(*lineno) {
+                    let range = comment
+                        .line_range
+                        .filter(|r| !r.is_single())
+                        .map(|r| format!("lines {}-{}", r.start, r.end));
+                    write_comment(out, label, range.as_deref(), comment);
+                }
+            }
+        }
+    }
+}
+
+/// `diff --git` and the headers `git apply` needs to create, delete,
+/// rename or copy the file.
+fn write_headers(out: &mut String, file: &DiffFile) {
+    let path = file.display_path();
+    let old: &PathBuf = file.old_path.as_ref().unwrap_or(path);
+    let _ = writeln!(out, "diff --git a/{} b/{}", old.display(), path.display());
+    match file.status {
+        FileStatus::Added => {
+            if let Some(mode) = file.modes.new {
+                let _ = writeln!(out, "new file mode {mode:o}");
+            }
+        }
+        FileStatus::Deleted => {
+            if let Some(mode) = file.modes.old {
+                let _ = writeln!(out, "deleted file mode {mode:o}");
+            }
+        }
+        FileStatus::Renamed | FileStatus::Copied => {
+            let kind = if file.status == FileStatus::Renamed {
+                "rename"
+            } else {
+                "copy"
+            };
+            let _ = writeln!(out, "{kind} from {}", old.display());
+            let _ = writeln!(out, "{kind} to {}", path.display());
+        }
+        FileStatus::Modified => {}
+    }
+    if file.status != FileStatus::Added
+        && file.status != FileStatus::Deleted
+        && let (Some(old_mode), Some(new_mode)) = (file.modes.old, file.modes.new)
+        && old_mode != new_mode
+    {
+        let _ = writeln!(out, "old mode {old_mode:o}");
+        let _ = writeln!(out, "new mode {new_mode:o}");
+    }
+    let old_side = match file.status {
+        FileStatus::Added => "/dev/null".to_string(),
+        _ => format!("a/{}", old.display()),
+    };
+    let new_side = match file.status {
+        FileStatus::Deleted => "/dev/null".to_string(),
+        _ => format!("b/{}", path.display()),
+    };
+    let _ = writeln!(out, "--- {old_side}");
+    let _ = writeln!(out, "+++ {new_side}");
+}
+
+/// `# [TYPE] where: text`, continuation lines indented under it
+fn write_comment(
+    out: &mut String,
+    label: &dyn Fn(&Comment) -> String,
+    location: Option<&str>,
+    comment: &Comment,
+) {
+    let location = location.map(|l| format!("{l}: ")).unwrap_or_default();
+    let blocking = if comment.blocking == Some(true) {
+        " (blocking)"
+    } else {
+        ""
+    };
+    write_comment_text(
+        out,
+        &format!(
+            "[{}]{blocking} {location}{}",
+            label(comment),
+            comment.content.trim_end()
+        ),
+    );
+}
+
+fn write_comment_text(out: &mut String, text: &str) {
+    for (i, line) in text.trim_end().lines().enumerate() {
+        let indent = if i == 0 { "" } else { "  " };
+        let _ = writeln!(out, "{}", format!("# {indent}{line}").trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineRange, SessionDiffSource,
+    };
+
+    fn line(origin: LineOrigin, content: &str, old: Option<u32>, new: Option<u32>) -> DiffLine {
+        DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        }
+    }
+
+    fn diff_file() -> DiffFile {
+        DiffFile {
+            old_path: Some(PathBuf::from("src/lib.rs")),
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![DiffHunk {
+                header: "@@ -1,2 +1,2 @@".to_string(),
+                lines: vec![
+                    line(LineOrigin::Context, "fn main() {", Some(1), Some(1)),
+                    line(LineOrigin::Deletion, "    old();", Some(2), None),
+                    line(LineOrigin::Addition, "    new();", None, Some(2)),
+                ],
+                old_start: 1,
+                old_count: 2,
+                new_start: 1,
+                new_count: 2,
+            }],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    #[test]
+    fn should_interleave_comments_as_hash_lines_below_their_lines() {
+        // given
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        let path = PathBuf::from("src/lib.rs");
+        session.add_file(path.clone(), FileStatus::Modified, 0);
+        let review = session.get_file_mut(&path).unwrap();
+        let mut ranged = Comment::new(
+            "Why the rename?\nOld name was fine".to_string(),
+            crate::model::CommentType::Issue,
+            Some(LineSide::New),
+        )
+        .with_blocking(Some(true));
+        ranged.line_range = Some(LineRange::new(1, 2));
+        review.add_line_comment(2, ranged);
+        review.add_line_comment(
+            2,
+            Comment::new(
+                "Was this dead?".to_string(),
+                crate::model::CommentType::Note,
+                Some(LineSide::Old),
+            ),
+        );
+
+        // when
+        let patch =
+            generate_annotated_patch(&session, &DiffSource::WorkingTree, &[diff_file()], &[]);
+
+        // then
+        let body: Vec<&str> = patch.lines().skip(2).collect();
+        assert_eq!(
+            body,
+            vec![
+                "diff --git a/src/lib.rs b/src/lib.rs",
+                "--- a/src/lib.rs",
+                "+++ b/src/lib.rs",
+                "@@ -1,2 +1,2 @@",
+                " fn main() {",
+                "-    old();",
+                "# [NOTE] Was this dead?",
+                "+    new();",
+                "# [ISSUE] (blocking) lines 1-2: Why the rename?",
+                "#   Old name was fine",
+            ]
+        );
+        let stripped: Vec<&str> = patch.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(stripped.len(), 7);
+    }
+}
@@ -0,0 +1,319 @@
+//! Short review summary for a chat channel, copied with `:chat` or posted
+//! to the incoming webhook configured as `chat_webhook` with `:chat post`.
+//!
+//! The summary is the scope and verdict, comment counts by type, and the
+//! first few ISSUE comments by location. Slack reads its own `mrkdwn`
+//! dialect (`*bold*`, `<url|text>` links, no list syntax), Teams reads
+//! Markdown, so each gets its own flavor. In pull request mode locations
+//! link to the pull request.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use ureq::Agent;
+
+use crate::app::{CommentTypeDefinition, DiffSource};
+use crate::model::{Comment, CommentType, LineSide, ReviewSession};
+use crate::output::json::verdict;
+use crate::output::markdown::{export_comment_type_label, review_scope};
+
+/// How many ISSUE comments the summary lists
+const TOP_ISSUES: usize = 5;
+
+/// Longest comment excerpt, in characters
+const EXCERPT_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatFlavor {
+    Slack,
+    Teams,
+}
+
+impl ChatFlavor {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "slack" => Some(Self::Slack),
+            "teams" => Some(Self::Teams),
+            _ => None,
+        }
+    }
+
+    /// The flavor a webhook URL expects: Teams for Microsoft-hosted hooks
+    /// (Office 365 connectors and Power Automate workflows), Slack otherwise.
+    pub fn for_webhook(url: &str) -> Self {
+        let host = url
+            .split("://")
+            .nth(1)
+            .unwrap_or(url)
+            .split(['/', '?', ':'])
+            .next()
+            .unwrap_or_default();
+        if host.ends_with("office.com") || host.ends_with("logic.azure.com") {
+            Self::Teams
+        } else {
+            Self::Slack
+        }
+    }
+
+    fn bold(self, text: &str) -> String {
+        match self {
+            Self::Slack => format!("*{}*", escape_slack(text)),
+            Self::Teams => format!("**{text}**"),
+        }
+    }
+
+    fn link(self, text: &str, url: &str) -> String {
+        match self {
+            Self::Slack => format!("<{url}|{}>", escape_slack(text)),
+            Self::Teams => format!("[{text}]({url})"),
+        }
+    }
+
+    fn text(self, text: &str) -> String {
+        match self {
+            Self::Slack => escape_slack(text),
+            Self::Teams => text.to_string(),
+        }
+    }
+
+    fn bullet(self) -> &'static str {
+        match self {
+            Self::Slack => "•",
+            Self::Teams => "-",
+        }
+    }
+}
+
+/// Build the chat summary of `session`.
+pub fn generate_chat_summary(
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    comment_types: &[CommentTypeDefinition],
+    flavor: ChatFlavor,
+) -> String {
+    let pr_url = match diff_source {
+        DiffSource::PullRequest(pr) => Some(pr.url.as_str()),
+        _ => None,
+    };
+    let mut lines = Vec::new();
+
+    let scope = review_scope(diff_source);
+    let title = match (pr_url, diff_source) {
+        (Some(url), DiffSource::PullRequest(pr)) => {
+            let text = format!("{scope}: {}", pr.title);
+            flavor.link(&text, url)
+        }
+        _ => flavor.text(&scope),
+    };
+    let verdict = match verdict(session) {
+        "changes_requested" => "changes requested",
+        other => other,
+    };
+    lines.push(format!("{} {title} — {verdict}", flavor.bold("Review of")));
+
+    let mut by_type: Vec<(String, usize)> = Vec::new();
+    let mut issues: Vec<(String, &Comment)> = Vec::new();
+    for comment in &session.review_comments {
+        count(&mut by_type, comment, comment_types);
+        if comment.comment_type == CommentType::Issue {
+            issues.push(("review".to_string(), comment));
+        }
+    }
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+    for (path, review) in files {
+        for comment in &review.file_comments {
+            count(&mut by_type, comment, comment_types);
+            if comment.comment_type == CommentType::Issue {
+                issues.push((path.display().to_string(), comment));
+            }
+        }
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, comments) in line_comments {
+            for comment in comments {
+                count(&mut by_type, comment, comment_types);
+                if comment.comment_type == CommentType::Issue {
+                    let suffix = if comment.side == Some(LineSide::Old) {
+                        " (removed line)"
+                    } else {
+                        ""
+                    };
+                    issues.push((format!("{}:{line}{suffix}", path.display()), comment));
+                }
+            }
+        }
+    }
+
+    let total: usize = by_type.iter().map(|(_, count)| count).sum();
+    let mut counts = format!("{total} comments");
+    if !by_type.is_empty() {
+        let breakdown = by_type
+            .iter()
+            .map(|(label, count)| format!("{count} {}", label.to_lowercase()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(counts, " ({breakdown})");
+    }
+    let _ = write!(
+        counts,
+        " · {} of {} files reviewed",
+        session.reviewed_count(),
+        session.files.len()
+    );
+    lines.push(flavor.text(&counts));
+
+    if !issues.is_empty() {
+        // Blocking issues first; the sort is stable, so locations stay in order
+        issues.sort_by_key(|(_, comment)| comment.blocking != Some(true));
+        if flavor == ChatFlavor::Slack {
+            lines.push(String::new());
+        }
+        lines.push(flavor.bold("Top issues"));
+        for (location, comment) in issues.iter().take(TOP_ISSUES) {
+            let location = match pr_url {
+                Some(url) => flavor.link(location, url),
+                None => format!("`{location}`"),
+            };
+            let blocking = if comment.blocking == Some(true) {
+                format!(" {}", flavor.bold("(blocking)"))
+            } else {
+                String::new()
+            };
+            lines.push(format!(
+                "{} {location}{blocking} {}",
+                flavor.bullet(),
+                flavor.text(&excerpt(&comment.content))
+            ));
+        }
+        if issues.len() > TOP_ISSUES {
+            lines.push(format!("…and {} more", issues.len() - TOP_ISSUES));
+        }
+    }
+
+    // Teams folds single newlines into one paragraph
+    let separator = match flavor {
+        ChatFlavor::Slack => "\n",
+        ChatFlavor::Teams => "\n\n",
+    };
+    lines.join(separator)
+}
+
+fn count(by_type: &mut Vec<(String, usize)>, comment: &Comment, types: &[CommentTypeDefinition]) {
+    let label = export_comment_type_label(&comment.comment_type, types);
+    match by_type.iter_mut().find(|(l, _)| *l == label) {
+        Some((_, count)) => *count += 1,
+        None => by_type.push((label, 1)),
+    }
+}
+
+/// First line of `content`, cut to [`EXCERPT_CHARS`]
+fn excerpt(content: &str) -> String {
+    let first = content.lines().next().unwrap_or_default().trim();
+    if first.chars().count() > EXCERPT_CHARS {
+        let cut: String = first.chars().take(EXCERPT_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        first.to_string()
+    }
+}
+
+/// Slack's three control characters; everything else is literal.
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Post `text` as the message body to an incoming webhook (10-second
+/// timeout). Slack and Teams hooks both take `{"text": ...}`.
+pub fn post_to_webhook(url: &str, text: &str) -> Result<(), String> {
+    let config = Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(10)))
+        .build();
+    let agent: Agent = config.into();
+    agent
+        .post(url)
+        .send_json(serde_json::json!({ "text": text }))
+        .map(|_| ())
+        .map_err(|e| format!("{e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::FileStatus;
+    use crate::output::tests::create_test_session;
+    use std::path::PathBuf;
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 0);
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.add_line_comment(
+            3,
+            Comment::new(
+                "Leaks <fd> & socket\nSee close()".to_string(),
+                CommentType::Issue,
+                Some(LineSide::New),
+            ),
+        );
+        review.add_line_comment(
+            9,
+            Comment::new(
+                "Panics on empty input".to_string(),
+                CommentType::Issue,
+                Some(LineSide::New),
+            )
+            .with_blocking(Some(true)),
+        );
+        session
+    }
+
+    #[test]
+    fn should_summarize_review_in_slack_mrkdwn() {
+        // when
+        let text =
+            generate_chat_summary(&session(), &DiffSource::WorkingTree, &[], ChatFlavor::Slack);
+
+        // then
+        assert_eq!(
+            text,
+            "*Review of* working tree changes — changes requested\n\
+             4 comments (1 suggestion, 3 issue) · 1 of 2 files reviewed\n\
+             \n\
+             *Top issues*\n\
+             • `src/main.rs:9` *(blocking)* Panics on empty input\n\
+             • `src/main.rs:3` Leaks &lt;fd&gt; &amp; socket\n\
+             • `src/main.rs:42` Magic number should be a constant"
+        );
+    }
+
+    #[test]
+    fn should_use_markdown_paragraphs_for_teams() {
+        // when
+        let text =
+            generate_chat_summary(&session(), &DiffSource::WorkingTree, &[], ChatFlavor::Teams);
+
+        // then
+        assert!(text.starts_with("**Review of** working tree changes — changes requested\n\n"));
+        assert!(text.contains("\n\n**Top issues**\n\n- `src/main.rs:9` **(blocking)**"));
+        assert!(text.contains("Leaks <fd> & socket"));
+    }
+
+    #[test]
+    fn should_pick_flavor_from_webhook_host() {
+        assert_eq!(
+            ChatFlavor::for_webhook("https://hooks.slack.com/services/T0/B0/x"),
+            ChatFlavor::Slack
+        );
+        assert_eq!(
+            ChatFlavor::for_webhook("https://acme.webhook.office.com/webhookb2/abc"),
+            ChatFlavor::Teams
+        );
+        assert_eq!(
+            ChatFlavor::for_webhook("https://prod-1.westus.logic.azure.com:443/workflows/x"),
+            ChatFlavor::Teams
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Plain-text reply in mailing-list patch review style, written with
+//! `:export <path>.eml`: the commented parts of the diff quoted with `> `,
+//! each comment on its own paragraph right below the line it was left on.
+//! The file starts with a `Subject:` header so `git send-email
+//! --in-reply-to=<msgid> <path>.eml` can send it as-is.
+//!
+//! Hunks without comments are left out, and a quoted hunk stops at its last
+//! commented line. Comments on lines the diff doesn't show (expanded
+//! context) follow the file's quote with their line number.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::app::{CommentTypeDefinition, DiffSource};
+use crate::error::{Result, TuicrError};
+use crate::model::review::FileReview;
+use crate::model::{Comment, DiffFile, DiffLine, LineOrigin, LineSide, ReviewSession};
+use crate::output::markdown::{export_comment_type_label, review_scope};
+
+/// Build the reply. Errors with `NoComments` when there is nothing to say.
+pub fn generate_email(
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+) -> Result<String> {
+    if !session.has_comments() {
+        return Err(TuicrError::NoComments);
+    }
+    let label = |comment: &Comment| export_comment_type_label(&comment.comment_type, comment_types);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Subject: Re: {}", subject(diff_source, diff_files));
+    let _ = writeln!(out);
+
+    if let Some(notes) = session.session_notes.as_deref().filter(|n| !n.is_empty()) {
+        let _ = writeln!(out, "{}", notes.trim_end());
+        let _ = writeln!(out);
+    }
+    for comment in &session.review_comments {
+        let _ = writeln!(out, "[{}] {}", label(comment), comment.content.trim_end());
+        let _ = writeln!(out);
+    }
+
+    // Diff order keeps the commit message first; files the diff no longer
+    // has follow by path.
+    let mut paths: Vec<&PathBuf> = diff_files
+        .iter()
+        .map(|f| f.display_path())
+        .filter(|p| session.files.contains_key(*p))
+        .collect();
+    let mut missing: Vec<&PathBuf> = session
+        .files
+        .keys()
+        .filter(|p| !paths.contains(p))
+        .collect();
+    missing.sort();
+    paths.extend(missing);
+
+    for path in paths {
+        let review = &session.files[path];
+        if review.comment_count() == 0 {
+            continue;
+        }
+        let diff_file = diff_files.iter().find(|f| f.display_path() == path);
+        write_file_section(&mut out, path, review, diff_file, &label);
+    }
+
+    Ok(out.trim_end().to_string() + "\n")
+}
+
+/// Write the reply to `path`, creating parent directories as needed.
+pub fn export_email_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+) -> Result<()> {
+    let content = generate_email(session, diff_source, diff_files, comment_types)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// `[PATCH] <summary>` when a commit message is part of the diff, the
+/// review scope otherwise.
+fn subject(diff_source: &DiffSource, diff_files: &[DiffFile]) -> String {
+    diff_files
+        .iter()
+        .find(|f| f.is_commit_message)
+        .and_then(|f| f.hunks.first()?.lines.first())
+        .map(|line| format!("[PATCH] {}", line.content.trim()))
+        .unwrap_or_else(|| format!("Review of {}", review_scope(diff_source)))
+}
+
+fn write_file_section<'a>(
+    out: &mut String,
+    path: &Path,
+    review: &'a FileReview,
+    diff_file: Option<&DiffFile>,
+    label: &dyn Fn(&Comment) -> String,
+) {
+    let is_commit_message = diff_file.is_some_and(|f| f.is_commit_message);
+    if !is_commit_message {
+        let old = diff_file
+            .and_then(|f| f.old_path.as_deref())
+            .unwrap_or(path);
+        let _ = writeln!(out, "> diff --git a/{} b/{}", old.display(), path.display());
+        let _ = writeln!(out);
+    }
+    for comment in &review.file_comments {
+        write_comment(out, label, comment);
+    }
+
+    let mut line_comments: Vec<(u32, &'a Comment)> = review
+        .line_comments
+        .iter()
+        .flat_map(|(line, comments)| comments.iter().map(|c| (*line, c)))
+        .collect();
+    line_comments.sort_by_key(|(line, _)| *line);
+    let mut placed: HashSet<&str> = HashSet::new();
+
+    for hunk in diff_file.map(|f| f.hunks.as_slice()).unwrap_or_default() {
+        let comments_at = |line: &DiffLine| -> Vec<&'a Comment> {
+            line_comments
+                .iter()
+                .filter(|(lineno, comment)| {
+                    let on = match comment.side {
+                        Some(LineSide::Old) => line.old_lineno,
+                        _ => line.new_lineno,
+                    };
+                    on == Some(*lineno)
+                })
+                .map(|(_, comment)| *comment)
+                .collect()
+        };
+        let Some(last) = hunk
+            .lines
+            .iter()
+            .rposition(|line| !comments_at(line).is_empty())
+        else {
+            continue;
+        };
+
+        if !is_commit_message && !hunk.header.is_empty() {
+            let _ = writeln!(out, "> {}", hunk.header);
+        }
+        for line in &hunk.lines[..=last] {
+            let text = if is_commit_message {
+                line.content.clone()
+            } else {
+                let prefix = match line.origin {
+                    LineOrigin::Addition => '+',
+                    LineOrigin::Deletion => '-',
+                    LineOrigin::Context => ' ',
+                };
+                format!("{prefix}{}", line.content)
+            };
+            let _ = writeln!(out, "{}", format!("> {text}").trim_end());
+            let comments = comments_at(line);
+            if !comments.is_empty() {
+                let _ = writeln!(out);
+                for comment in comments {
+                    placed.insert(&comment.id);
+                    write_comment(out, label, comment);
+                }
+            }
+        }
+    }
+
+    for (line, comment) in &line_comments {
+        if placed.contains(comment.id.as_str()) {
+            continue;
+        }
+        let old = if comment.side == Some(LineSide::Old) {
+            "~"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "Line {old}{line}: [{}] {}",
+            label(comment),
+            comment.content.trim_end()
+        );
+        let _ = writeln!(out);
+    }
+}
+
+fn write_comment(out: &mut String, label: &dyn Fn(&Comment) -> String, comment: &Comment) {
+    let _ = writeln!(out, "[{}] {}", label(comment), comment.content.trim_end());
+    let _ = writeln!(out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CommentType, DiffHunk, FileModes, FileSizes, FileStatus};
+    use crate::output::tests::create_test_session;
+
+    fn diff_file() -> DiffFile {
+        let line = |origin, content: &str, old, new| DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        };
+        let hunks = vec![
+            DiffHunk {
+                header: "@@ -41,3 +41,3 @@".to_string(),
+                lines: vec![
+                    line(LineOrigin::Context, "fn main() {", Some(41), Some(41)),
+                    line(LineOrigin::Deletion, "    let x = 41;", Some(42), None),
+                    line(LineOrigin::Addition, "    let x = 42;", None, Some(42)),
+                    line(LineOrigin::Context, "}", Some(43), Some(43)),
+                ],
+                old_start: 41,
+                old_count: 3,
+                new_start: 41,
+                new_count: 3,
+            },
+            DiffHunk {
+                header: "@@ -50,1 +50,1 @@".to_string(),
+                lines: vec![line(LineOrigin::Addition, "fn other() {}", None, Some(50))],
+                old_start: 50,
+                old_count: 1,
+                new_start: 50,
+                new_count: 1,
+            },
+        ];
+        DiffFile {
+            old_path: Some(PathBuf::from("src/main.rs")),
+            new_path: Some(PathBuf::from("src/main.rs")),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        session.session_notes = Some("Looks good overall.".to_string());
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.add_line_comment(
+            42,
+            Comment::new(
+                "Why was 41 wrong?".to_string(),
+                CommentType::Note,
+                Some(LineSide::Old),
+            ),
+        );
+        review.add_line_comment(
+            60,
+            Comment::new("Unused".to_string(), CommentType::Note, Some(LineSide::New)),
+        );
+        session
+    }
+
+    #[test]
+    fn should_quote_commented_hunks_with_interleaved_comments() {
+        // when
+        let email =
+            generate_email(&session(), &DiffSource::WorkingTree, &[diff_file()], &[]).unwrap();
+
+        // then
+        assert_eq!(
+            email,
+            "Subject: Re: Review of working tree changes\n\
+             \n\
+             Looks good overall.\n\
+             \n\
+             > diff --git a/src/main.rs b/src/main.rs\n\
+             \n\
+             [SUGGESTION] Consider adding documentation\n\
+             \n\
+             > @@ -41,3 +41,3 @@\n\
+             >  fn main() {\n\
+             > -    let x = 41;\n\
+             \n\
+             [NOTE] Why was 41 wrong?\n\
+             \n\
+             > +    let x = 42;\n\
+             \n\
+             [ISSUE] Magic number should be a constant\n\
+             \n\
+             Line 60: [NOTE] Unused\n"
+        );
+    }
+
+    #[test]
+    fn should_use_commit_summary_as_subject_and_fail_without_comments() {
+        // given
+        let mut message = diff_file();
+        message.is_commit_message = true;
+        message.new_path = Some(PathBuf::from("Commit Message"));
+        message.hunks[0].lines[0].content = "Fix the answer".to_string();
+        let mut empty = create_test_session();
+        empty.files.clear();
+
+        // when
+        let email = generate_email(&session(), &DiffSource::WorkingTree, &[message], &[]).unwrap();
+        let empty_result = generate_email(&empty, &DiffSource::WorkingTree, &[], &[]);
+
+        // then
+        assert!(email.starts_with("Subject: Re: [PATCH] Fix the answer\n\n"));
+        assert!(matches!(empty_result, Err(TuicrError::NoComments)));
+    }
+}
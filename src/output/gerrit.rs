@@ -0,0 +1,273 @@
+//! Gerrit `ReviewInput` export, written with `:export <path>.gerrit.json`.
+//! The file is what `ssh <host> gerrit review --json <change>,<patchset>`
+//! reads on stdin, and also the body of the REST
+//! `POST /changes/{change}/revisions/{revision}/review` call.
+//!
+//! Line comments keep their side (`PARENT` for removed lines) and range;
+//! review-level comments and session notes form the change message. The
+//! Code-Review vote follows the review verdict: -1 with any ISSUE comment,
+//! +1 once every file is reviewed, 0 otherwise.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::app::CommentTypeDefinition;
+use crate::error::Result;
+use crate::model::{Comment, CommentType, DiffFile, LineSide, ReviewSession};
+use crate::output::json::verdict;
+use crate::output::markdown::export_comment_type_label;
+
+#[derive(Debug, Serialize)]
+struct ReviewInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    labels: BTreeMap<&'static str, i8>,
+    /// Keyed by file path, as Gerrit expects
+    comments: BTreeMap<String, Vec<CommentInput>>,
+}
+
+#[derive(Debug, Serialize)]
+struct CommentInput {
+    /// Last line of the comment; absent for file comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<CommentRange>,
+    /// `PARENT` for comments on the old side; Gerrit defaults to `REVISION`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    side: Option<&'static str>,
+    message: String,
+    unresolved: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct CommentRange {
+    start_line: u32,
+    start_character: usize,
+    end_line: u32,
+    end_character: usize,
+}
+
+fn code_review_vote(session: &ReviewSession) -> i8 {
+    match verdict(session) {
+        "changes_requested" => -1,
+        "approved" => 1,
+        _ => 0,
+    }
+}
+
+fn comment_input(
+    comment: &Comment,
+    line: Option<u32>,
+    file: Option<&DiffFile>,
+    comment_types: &[CommentTypeDefinition],
+) -> CommentInput {
+    let side = comment.side.unwrap_or(LineSide::New);
+    let range = line
+        .and(comment.line_range)
+        .filter(|r| !r.is_single())
+        .map(|r| CommentRange {
+            start_line: r.start,
+            start_character: 0,
+            end_line: r.end,
+            // Gerrit ranges are character precise; cover the whole last line.
+            end_character: file.and_then(|f| line_length(f, r.end, side)).unwrap_or(0),
+        });
+    CommentInput {
+        line: range.as_ref().map(|r| r.end_line).or(line),
+        range,
+        side: (line.is_some() && side == LineSide::Old).then_some("PARENT"),
+        message: format!(
+            "[{}] {}",
+            export_comment_type_label(&comment.comment_type, comment_types),
+            comment.content
+        ),
+        unresolved: comment.comment_type == CommentType::Issue,
+    }
+}
+
+fn line_length(file: &DiffFile, line: u32, side: LineSide) -> Option<usize> {
+    file.hunks
+        .iter()
+        .flat_map(|h| &h.lines)
+        .find(|l| {
+            let lineno = match side {
+                LineSide::Old => l.old_lineno,
+                LineSide::New => l.new_lineno,
+            };
+            lineno == Some(line)
+        })
+        .map(|l| l.content.trim_end_matches(['\n', '\r']).chars().count())
+}
+
+/// Build the `ReviewInput` JSON for `session`. A review without comments
+/// still carries its vote.
+pub fn generate_gerrit_review(
+    session: &ReviewSession,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+) -> Result<String> {
+    let mut message = Vec::new();
+    if let Some(notes) = session.session_notes.as_deref().filter(|n| !n.is_empty()) {
+        message.push(notes.to_string());
+    }
+    message.extend(session.review_comments.iter().map(|c| {
+        format!(
+            "[{}] {}",
+            export_comment_type_label(&c.comment_type, comment_types),
+            c.content
+        )
+    }));
+
+    let mut comments = BTreeMap::new();
+    for (path, review) in &session.files {
+        let file = diff_files.iter().find(|f| f.display_path() == path);
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        let inputs: Vec<_> = review
+            .file_comments
+            .iter()
+            .map(|c| comment_input(c, None, file, comment_types))
+            .chain(line_comments.into_iter().flat_map(|(line, comments)| {
+                comments
+                    .iter()
+                    .map(|c| comment_input(c, Some(*line), file, comment_types))
+            }))
+            .collect();
+        if !inputs.is_empty() {
+            // Gerrit paths always use forward slashes.
+            comments.insert(path.to_string_lossy().replace('\\', "/"), inputs);
+        }
+    }
+
+    let review = ReviewInput {
+        message: (!message.is_empty()).then(|| message.join("\n\n")),
+        labels: BTreeMap::from([("Code-Review", code_review_vote(session))]),
+        comments,
+    };
+    Ok(serde_json::to_string_pretty(&review)? + "\n")
+}
+
+/// Write the `ReviewInput` JSON to `path`, creating parent directories as
+/// needed.
+pub fn export_gerrit_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+) -> Result<()> {
+    let content = generate_gerrit_review(session, diff_files, comment_types)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin, LineRange,
+    };
+    use crate::output::tests::create_test_session;
+    use std::path::PathBuf;
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        session.review_comments.push(Comment::new(
+            "Needs another pass".to_string(),
+            CommentType::Note,
+            None,
+        ));
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.line_comments.get_mut(&42).unwrap()[0].line_range = Some(LineRange::new(41, 42));
+        review.add_line_comment(
+            39,
+            Comment::new(
+                "Why drop this?".to_string(),
+                CommentType::Note,
+                Some(LineSide::Old),
+            ),
+        );
+        session
+    }
+
+    fn diff_file() -> DiffFile {
+        let line = |origin, old, new, content: &str| DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        };
+        let hunks = vec![DiffHunk {
+            header: "@@ -39,3 +41,2 @@".to_string(),
+            lines: vec![
+                line(LineOrigin::Deletion, Some(39), None, "old"),
+                line(LineOrigin::Context, Some(40), Some(41), "fn a() {"),
+                line(LineOrigin::Context, Some(41), Some(42), "}"),
+            ],
+            old_start: 39,
+            old_count: 3,
+            new_start: 41,
+            new_count: 2,
+        }];
+        DiffFile {
+            old_path: Some(PathBuf::from("src/main.rs")),
+            new_path: Some(PathBuf::from("src/main.rs")),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    #[test]
+    fn should_export_line_comments_and_vote_down_on_issues() {
+        // when
+        let json = generate_gerrit_review(&session(), &[diff_file()], &[]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(value["labels"]["Code-Review"], -1);
+        assert_eq!(value["message"], "[NOTE] Needs another pass");
+        let comments = value["comments"]["src/main.rs"].as_array().unwrap();
+        assert_eq!(comments.len(), 3);
+        assert!(comments[0].get("line").is_none());
+        assert_eq!(comments[0]["unresolved"], false);
+        assert_eq!(comments[1]["line"], 39);
+        assert_eq!(comments[1]["side"], "PARENT");
+        assert_eq!(comments[1]["message"], "[NOTE] Why drop this?");
+        assert_eq!(comments[2]["line"], 42);
+        assert_eq!(comments[2]["range"]["start_line"], 41);
+        assert_eq!(comments[2]["range"]["end_character"], 1);
+        assert_eq!(comments[2]["unresolved"], true);
+        assert!(comments[2].get("side").is_none());
+    }
+
+    #[test]
+    fn should_vote_up_a_fully_reviewed_session_without_issues() {
+        // given
+        let mut session = session();
+        session.review_comments.clear();
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.file_comments.clear();
+        review.line_comments.clear();
+
+        // when
+        let json = generate_gerrit_review(&session, &[], &[]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(value["labels"]["Code-Review"], 1);
+        assert!(value.get("message").is_none());
+        assert_eq!(value["comments"], serde_json::json!({}));
+    }
+}
@@ -0,0 +1,387 @@
+//! Self-contained HTML report, written with `:export <path>.html`. It has the
+//! same content as the Markdown report, plus a per-file summary table,
+//! collapsible file sections and diff excerpts in the active theme's syntax
+//! colors, so it can be attached to a ticket or emailed as a single file.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use ratatui::style::Color;
+
+use crate::app::{CommentTypeDefinition, DiffSource, comment_type_color_for};
+use crate::error::{Result, TuicrError};
+use crate::model::{Comment, DiffFile, DiffLine, LineOrigin, LineRange, LineSide, ReviewSession};
+use crate::output::markdown::{attribution, export_comment_type_label, review_scope};
+use crate::theme::Theme;
+
+/// Unchanged lines shown around the commented lines of each excerpt
+const EXCERPT_CONTEXT: usize = 2;
+
+/// Build the report. Errors with `NoComments` when there is nothing to say.
+pub fn generate_html(
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+    theme: &Theme,
+) -> Result<String> {
+    if !session.has_comments() {
+        return Err(TuicrError::NoComments);
+    }
+    let comment_html = |comment: &Comment| {
+        let label = export_comment_type_label(&comment.comment_type, comment_types);
+        let color = css_color(comment_type_color_for(
+            comment_types,
+            theme,
+            &comment.comment_type,
+        ))
+        .map(|c| format!(" style=\"color: {c}\""))
+        .unwrap_or_default();
+        format!(
+            "<div class=\"comment\"><span class=\"label\"{color}>[{}]</span> {}{}</div>\n",
+            escape(&label),
+            escape(&comment.content),
+            escape(&attribution(comment, session))
+        )
+    };
+
+    let title = format!("Review of {}", review_scope(diff_source));
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, "<html lang=\"en\">");
+    let _ = writeln!(html, "<head>");
+    let _ = writeln!(html, "<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>{}</title>", escape(&title));
+    let _ = writeln!(html, "<style>\n{}</style>", stylesheet(theme));
+    let _ = writeln!(html, "</head>");
+    let _ = writeln!(html, "<body>");
+    let _ = writeln!(html, "<h1>{}</h1>", escape(&title));
+    if let Some(reviewer) = &session.reviewer {
+        let _ = writeln!(
+            html,
+            "<p class=\"reviewer\">Reviewer: {}</p>",
+            escape(&reviewer.to_string())
+        );
+    }
+
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+    let _ = writeln!(html, "<table class=\"summary\">");
+    let _ = writeln!(
+        html,
+        "<tr><th>File</th><th>Status</th><th>Reviewed</th><th>Comments</th></tr>"
+    );
+    for (path, review) in &files {
+        let _ = writeln!(
+            html,
+            "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&path.display().to_string()),
+            review.status.as_char(),
+            review.review_confidence().map_or("", |c| c.mark()),
+            review.comment_count()
+        );
+    }
+    let total: usize =
+        session.review_comments.len() + files.iter().map(|(_, r)| r.comment_count()).sum::<usize>();
+    let _ = writeln!(
+        html,
+        "<tr class=\"total\"><td>{} files</td><td></td><td>{}</td><td>{total}</td></tr>",
+        files.len(),
+        session.reviewed_count()
+    );
+    let _ = writeln!(html, "</table>");
+    if let Some(spot_check) = &session.spot_check {
+        let _ = writeln!(html, "<p>Spot-checked: {}</p>", spot_check.coverage());
+    }
+
+    if let Some(notes) = &session.session_notes {
+        let _ = writeln!(html, "<h2>Summary</h2>");
+        let _ = writeln!(html, "<p class=\"notes\">{}</p>", escape(notes));
+    }
+
+    if !session.checklist.is_empty() {
+        let _ = writeln!(html, "<h2>Checklist</h2>");
+        let _ = writeln!(html, "<ul class=\"checklist\">");
+        for item in &session.checklist {
+            let mark = if item.done { "☑" } else { "☐" };
+            let _ = writeln!(html, "<li>{mark} {}</li>", escape(&item.text));
+        }
+        let _ = writeln!(html, "</ul>");
+    }
+
+    if !session.review_comments.is_empty() {
+        let _ = writeln!(html, "<h2>General comments</h2>");
+        for comment in &session.review_comments {
+            html.push_str(&comment_html(comment));
+        }
+    }
+
+    for (path, review) in files.iter().filter(|(_, r)| r.comment_count() > 0) {
+        let reviewed = review
+            .review_confidence()
+            .map(|c| format!(" ({})", c.label()))
+            .unwrap_or_default();
+        let _ = writeln!(html, "<details open>");
+        let _ = writeln!(
+            html,
+            "<summary><code>{}</code>{reviewed}</summary>",
+            escape(&path.display().to_string())
+        );
+        for comment in &review.file_comments {
+            html.push_str(&comment_html(comment));
+        }
+
+        let diff_file = diff_files.iter().find(|f| f.display_path() == *path);
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, comments) in line_comments {
+            for comment in comments {
+                let range = comment.line_range.unwrap_or(LineRange::single(*line));
+                let side = comment.side.unwrap_or(LineSide::New);
+                let _ = writeln!(html, "<h3>{}</h3>", location_heading(range, side));
+                if let Some(excerpt) = diff_file.and_then(|f| code_excerpt(f, range, side)) {
+                    html.push_str(&excerpt);
+                }
+                html.push_str(&comment_html(comment));
+            }
+        }
+        let _ = writeln!(html, "</details>");
+    }
+
+    let _ = writeln!(html, "</body>");
+    let _ = writeln!(html, "</html>");
+    Ok(html)
+}
+
+/// Write the report to `path`, creating parent directories as needed.
+pub fn export_html_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+    theme: &Theme,
+) -> Result<()> {
+    let content = generate_html(session, diff_source, diff_files, comment_types, theme)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn stylesheet(theme: &Theme) -> String {
+    let color = |c: Color, fallback: &str| css_color(c).unwrap_or_else(|| fallback.to_string());
+    format!(
+        "body {{ font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }}
+table.summary {{ border-collapse: collapse; margin-bottom: 1.5rem; }}
+table.summary th, table.summary td {{ border: 1px solid #ccc; padding: 0.25rem 0.75rem; text-align: left; }}
+tr.total {{ font-weight: bold; }}
+p.notes {{ white-space: pre-wrap; }}
+ul.checklist {{ list-style: none; padding-left: 0; }}
+details {{ border: 1px solid #ccc; border-radius: 6px; margin: 1rem 0; padding: 0.5rem 1rem; }}
+summary {{ cursor: pointer; font-weight: bold; }}
+h3 {{ font-size: 0.9rem; margin: 1rem 0 0.25rem; }}
+pre.diff {{ background: {bg}; color: {fg}; padding: 0.5rem; overflow-x: auto; margin: 0; }}
+pre.diff span.line {{ display: inline-block; width: 100%; }}
+pre.diff span.add {{ background: {add}; }}
+pre.diff span.del {{ background: {del}; }}
+pre.diff span.target {{ font-weight: bold; }}
+pre.diff span.context {{ opacity: 0.6; }}
+div.comment {{ margin: 0.5rem 0; white-space: pre-wrap; }}
+span.label {{ font-weight: bold; }}
+",
+        bg = color(theme.panel_bg, "#1e1e1e"),
+        fg = color(theme.fg_primary, "#d4d4d4"),
+        add = color(theme.diff_add_bg, "#12361f"),
+        del = color(theme.diff_del_bg, "#4b1818"),
+    )
+}
+
+/// `#rrggbb` for true colors; named and indexed colors depend on the
+/// terminal palette, so the caller falls back to a fixed value.
+fn css_color(color: Color) -> Option<String> {
+    match color {
+        Color::Rgb(r, g, b) => Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        _ => None,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn location_heading(range: LineRange, side: LineSide) -> String {
+    let old = if side == LineSide::Old { "~" } else { "" };
+    if range.is_single() {
+        format!("Line {old}{}", range.start)
+    } else {
+        format!("Lines {old}{}-{old}{}", range.start, range.end)
+    }
+}
+
+/// A `<pre>` with the diff lines of `range` on `side` plus up to
+/// `EXCERPT_CONTEXT` neighbouring lines of the same hunk on either side.
+fn code_excerpt(file: &DiffFile, range: LineRange, side: LineSide) -> Option<String> {
+    let in_range = |line: &DiffLine| {
+        let lineno = match side {
+            LineSide::New => line.new_lineno,
+            LineSide::Old => line.old_lineno,
+        };
+        lineno.is_some_and(|ln| range.start <= ln && ln <= range.end)
+    };
+    let hunk = file
+        .hunks
+        .iter()
+        .find(|hunk| hunk.lines.iter().any(in_range))?;
+    let first = hunk.lines.iter().position(in_range)?;
+    let last = hunk.lines.iter().rposition(in_range)?;
+    let start = first.saturating_sub(EXCERPT_CONTEXT);
+    let end = (last + EXCERPT_CONTEXT).min(hunk.lines.len() - 1);
+
+    let mut out = String::from("<pre class=\"diff\">");
+    for (idx, line) in hunk.lines[start..=end].iter().enumerate() {
+        let (prefix, origin) = match line.origin {
+            LineOrigin::Addition => ('+', " add"),
+            LineOrigin::Deletion => ('-', " del"),
+            LineOrigin::Context => (' ', ""),
+        };
+        let target = if (first..=last).contains(&(start + idx)) {
+            "target"
+        } else {
+            "context"
+        };
+        let _ = write!(out, "<span class=\"line{origin} {target}\">{prefix}");
+        match &line.highlighted_spans {
+            Some(spans) => {
+                for (style, text) in spans {
+                    match style.fg.and_then(css_color) {
+                        Some(fg) => {
+                            let _ =
+                                write!(out, "<span style=\"color: {fg}\">{}</span>", escape(text));
+                        }
+                        None => out.push_str(&escape(text)),
+                    }
+                }
+            }
+            None => out.push_str(&escape(&line.content)),
+        }
+        out.push_str("</span>\n");
+    }
+    out.push_str("</pre>\n");
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DiffHunk, FileModes, FileSizes, FileStatus};
+    use crate::output::tests::create_test_session;
+    use ratatui::style::Style;
+    use std::path::PathBuf;
+
+    fn diff_file() -> DiffFile {
+        let line = |origin, content: &str, old, new| DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        };
+        let mut added = line(LineOrigin::Addition, "    let x = 42;", None, Some(42));
+        added.highlighted_spans = Some(vec![
+            (
+                Style::default().fg(Color::Rgb(255, 0, 0)),
+                "    let".to_string(),
+            ),
+            (Style::default(), " x = 42;".to_string()),
+        ]);
+        let hunks = vec![DiffHunk {
+            header: "@@ -41,5 +41,5 @@".to_string(),
+            lines: vec![
+                line(LineOrigin::Context, "fn main() {", Some(41), Some(41)),
+                line(LineOrigin::Deletion, "    let x = 41;", Some(42), None),
+                added,
+                line(LineOrigin::Context, "    x < 50", Some(43), Some(43)),
+                line(LineOrigin::Context, "}", Some(44), Some(44)),
+                line(LineOrigin::Context, "", Some(45), Some(45)),
+            ],
+            old_start: 41,
+            old_count: 5,
+            new_start: 41,
+            new_count: 5,
+        }];
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from("src/main.rs")),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        session.session_notes = Some("Ship <after> fixes".to_string());
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Added, 0);
+        session
+    }
+
+    #[test]
+    fn should_generate_self_contained_html_with_summary_and_excerpts() {
+        // when
+        let html = generate_html(
+            &session(),
+            &DiffSource::WorkingTree,
+            &[diff_file()],
+            &[],
+            &Theme::dark(),
+        )
+        .unwrap();
+
+        // then
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Review of working tree changes</title>"));
+        assert!(
+            html.contains("<tr><td><code>src/lib.rs</code></td><td>A</td><td></td><td>0</td></tr>")
+        );
+        assert!(
+            html.contains("<tr class=\"total\"><td>2 files</td><td></td><td>1</td><td>2</td></tr>")
+        );
+        assert!(html.contains("Ship &lt;after&gt; fixes"));
+        assert!(
+            html.contains("<details open>\n<summary><code>src/main.rs</code> (reviewed)</summary>")
+        );
+        assert!(html.contains("<span style=\"color: #ff0000\">    let</span> x = 42;"));
+        assert!(html.contains("[ISSUE]</span> Magic number should be a constant"));
+        assert!(!html.contains("<link") && !html.contains("<script"));
+    }
+
+    #[test]
+    fn should_limit_excerpt_to_commented_lines_and_nearby_context() {
+        // when
+        let excerpt = code_excerpt(&diff_file(), LineRange::single(42), LineSide::New).unwrap();
+
+        // then
+        assert!(excerpt.contains("<span class=\"line add target\">+"));
+        assert!(excerpt.contains("<span class=\"line del context\">-    let x = 41;"));
+        assert!(excerpt.contains("<span class=\"line context\"> }</span>\n</pre>"));
+        assert_eq!(excerpt.matches("<span class=\"line").count(), 5);
+    }
+}
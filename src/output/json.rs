@@ -0,0 +1,774 @@
+//! Machine-readable JSON export, written with `:export <path>.json`. The
+//! layout is a documented, versioned schema rather than the session file
+//! format, so tooling can rely on it while sessions evolve. Bump
+//! `JSON_SCHEMA_VERSION` on any change that is not purely additive.
+//!
+//! `:import <path>` reads the same schema back and merges another
+//! reviewer's export into the open session (`merge_json_review`).
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::app::CommentTypeDefinition;
+use crate::error::{Result, TuicrError};
+use crate::model::review::DeferredLine;
+use crate::model::{
+    Comment, CommentType, DiffFile, FileStatus, LineRange, LineSide, ReviewConfidence,
+    ReviewSession, Reviewer, SessionDiffSource, SpotCheck,
+};
+use crate::output::markdown::export_comment_type_label;
+
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Where the exported diff sits in history, so integrations can re-anchor
+/// comments once the branch moves on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffAnchors<'a> {
+    /// Commit the diff's old side comes from
+    pub base_sha: Option<&'a str>,
+    /// Newest commit of the diff; `None` when it includes uncommitted changes
+    pub head_sha: Option<&'a str>,
+    pub diff_files: &'a [DiffFile],
+}
+
+#[derive(Debug, Serialize)]
+struct JsonReview<'a> {
+    schema_version: u32,
+    tool_version: &'static str,
+    session_id: &'a str,
+    repository: &'a Path,
+    branch: Option<&'a str>,
+    base_commit: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base_sha: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    head_sha: Option<&'a str>,
+    source: SessionDiffSource,
+    commits: &'a [String],
+    /// `approved`, `changes_requested` or `pending`
+    verdict: &'static str,
+    /// Who reviewed: `{"name", "email"}`; absent when no identity is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reviewer: Option<&'a Reviewer>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    summary: Option<&'a str>,
+    checklist: Vec<JsonChecklistItem<'a>>,
+    /// Hunks sampled with `:spotcheck`; absent unless the review was one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spot_check: Option<&'a SpotCheck>,
+    /// Seconds spent actively reviewing, idle stretches left out
+    active_secs: u64,
+    review_comments: Vec<JsonComment<'a>>,
+    files: Vec<JsonFile<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonChecklistItem<'a> {
+    text: &'a str,
+    done: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFile<'a> {
+    path: &'a Path,
+    status: FileStatus,
+    reviewed: bool,
+    /// `skimmed`, `reviewed` or `thorough`; absent while unreviewed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confidence: Option<ReviewConfidence>,
+    /// Seconds the file was on screen while actively reviewing
+    active_secs: u64,
+    comments: Vec<JsonComment<'a>>,
+    /// Lines the reviewer marked to revisit, for `tuicr check`
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    deferred: &'a [DeferredLine],
+}
+
+#[derive(Debug, Serialize)]
+struct JsonComment<'a> {
+    id: &'a str,
+    /// Comment type id, e.g. `issue`
+    #[serde(rename = "type")]
+    comment_type: &'a str,
+    /// Display label, e.g. `ISSUE`
+    label: String,
+    body: &'a str,
+    /// Forge login for comments brought in with `:comments import`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a str>,
+    /// Who wrote the comment in tuicr
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reviewer: Option<&'a Reviewer>,
+    /// Conventional Comments blocking flag, when the reviewer set one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blocking: Option<bool>,
+    /// First line for line comments; absent for file and review comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    side: Option<LineSide>,
+    /// Where the line comment sits in the diff; absent when the diff
+    /// doesn't show its line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anchor: Option<JsonAnchor>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonAnchor {
+    /// Header of the hunk holding the comment's last line
+    hunk: String,
+    /// Diff position of the last line: lines below the file's first hunk
+    /// header, counting later hunk headers, as GitHub's `position`
+    position: u32,
+    /// Diff position of the first line of a multi-line comment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_position: Option<u32>,
+}
+
+impl JsonAnchor {
+    fn find(file: &DiffFile, side: LineSide, start: u32, end: u32) -> Option<Self> {
+        let mut position = 0;
+        let mut start_position = None;
+        for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+            if hunk_idx > 0 {
+                position += 1;
+            }
+            for line in &hunk.lines {
+                position += 1;
+                let lineno = match side {
+                    LineSide::Old => line.old_lineno,
+                    LineSide::New => line.new_lineno,
+                };
+                if lineno == Some(start) && start_position.is_none() {
+                    start_position = Some(position);
+                }
+                if lineno == Some(end) {
+                    return Some(Self {
+                        hunk: hunk.header_line(),
+                        position,
+                        start_position: start_position.filter(|start| *start != position),
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a> JsonComment<'a> {
+    fn new(
+        comment: &'a Comment,
+        line: Option<u32>,
+        comment_types: &[CommentTypeDefinition],
+        file: Option<&DiffFile>,
+    ) -> Self {
+        let range = comment.line_range;
+        let side = comment.side.unwrap_or(LineSide::New);
+        let anchor = file.zip(line).and_then(|(file, end)| {
+            JsonAnchor::find(file, side, range.map_or(end, |r| r.start), end)
+        });
+        Self {
+            id: &comment.id,
+            comment_type: comment.comment_type.id(),
+            label: export_comment_type_label(&comment.comment_type, comment_types),
+            body: &comment.content,
+            author: comment.author.as_deref(),
+            reviewer: comment.reviewer.as_ref(),
+            blocking: comment.blocking,
+            line: range.map(|r| r.start).or(line),
+            end_line: range.filter(|r| !r.is_single()).map(|r| r.end),
+            side: line.map(|_| side),
+            anchor,
+            created_at: comment.created_at,
+        }
+    }
+}
+
+pub(crate) fn verdict(session: &ReviewSession) -> &'static str {
+    let has_issue = session
+        .review_comments
+        .iter()
+        .chain(session.files.values().flat_map(|f| {
+            f.file_comments
+                .iter()
+                .chain(f.line_comments.values().flatten())
+        }))
+        .any(|c| c.comment_type == CommentType::Issue);
+    if has_issue {
+        "changes_requested"
+    } else if session.reviewed_count() == session.files.len() {
+        "approved"
+    } else {
+        "pending"
+    }
+}
+
+/// Serialize `session` in the versioned export schema.
+pub fn generate_json(
+    session: &ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+    anchors: DiffAnchors,
+) -> Result<String> {
+    let review = json_review(session, comment_types, anchors);
+    Ok(serde_json::to_string_pretty(&review)? + "\n")
+}
+
+/// The export schema as a JSON value, which export templates render from.
+pub(crate) fn review_value(
+    session: &ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+) -> Result<serde_json::Value> {
+    let review = json_review(session, comment_types, DiffAnchors::default());
+    Ok(serde_json::to_value(review)?)
+}
+
+fn json_review<'a>(
+    session: &'a ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+    anchors: DiffAnchors<'a>,
+) -> JsonReview<'a> {
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+    let files = files
+        .into_iter()
+        .map(|(path, review)| {
+            let file = anchors
+                .diff_files
+                .iter()
+                .find(|file| file.display_path() == path);
+            let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+            line_comments.sort_by_key(|(line, _)| **line);
+            let comments = review
+                .file_comments
+                .iter()
+                .map(|c| JsonComment::new(c, None, comment_types, None))
+                .chain(line_comments.into_iter().flat_map(|(line, comments)| {
+                    comments
+                        .iter()
+                        .map(move |c| JsonComment::new(c, Some(*line), comment_types, file))
+                }))
+                .collect();
+            JsonFile {
+                path,
+                status: review.status,
+                reviewed: review.reviewed,
+                confidence: review.review_confidence(),
+                active_secs: review.active_ms / 1000,
+                comments,
+                deferred: &review.deferred,
+            }
+        })
+        .collect();
+
+    JsonReview {
+        schema_version: JSON_SCHEMA_VERSION,
+        tool_version: env!("CARGO_PKG_VERSION"),
+        session_id: &session.id,
+        repository: &session.repo_path,
+        branch: session.branch_name.as_deref(),
+        base_commit: &session.base_commit,
+        base_sha: anchors.base_sha,
+        head_sha: anchors.head_sha,
+        source: session.diff_source,
+        commits: session.commit_range.as_deref().unwrap_or_default(),
+        verdict: verdict(session),
+        reviewer: session.reviewer.as_ref(),
+        created_at: session.created_at,
+        updated_at: session.updated_at,
+        summary: session.session_notes.as_deref(),
+        checklist: session
+            .checklist
+            .iter()
+            .map(|item| JsonChecklistItem {
+                text: &item.text,
+                done: item.done,
+            })
+            .collect(),
+        spot_check: session.spot_check.as_ref(),
+        active_secs: session.active_ms / 1000,
+        review_comments: session
+            .review_comments
+            .iter()
+            .map(|c| JsonComment::new(c, None, comment_types, None))
+            .collect(),
+        files,
+    }
+}
+
+/// Write the JSON export to `path`, creating parent directories as needed.
+/// Unlike the Markdown report, a review without comments is still exported:
+/// its verdict and reviewed files are meaningful on their own.
+pub fn export_json_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+    anchors: DiffAnchors,
+) -> Result<()> {
+    let content = generate_json(session, comment_types, anchors)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// The parts of an export that `:import` merges and `tuicr view` opens.
+/// Unknown fields are ignored so newer additive fields don't break older
+/// readers.
+#[derive(Debug, Deserialize)]
+struct ImportedReview {
+    schema_version: u32,
+    session_id: Option<String>,
+    repository: Option<PathBuf>,
+    branch: Option<String>,
+    base_commit: Option<String>,
+    source: Option<SessionDiffSource>,
+    #[serde(default)]
+    commits: Vec<String>,
+    summary: Option<String>,
+    #[serde(default)]
+    review_comments: Vec<ImportedComment>,
+    #[serde(default)]
+    files: Vec<ImportedFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportedFile {
+    path: PathBuf,
+    status: Option<FileStatus>,
+    #[serde(default)]
+    reviewed: bool,
+    confidence: Option<ReviewConfidence>,
+    #[serde(default)]
+    comments: Vec<ImportedComment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportedComment {
+    id: String,
+    #[serde(rename = "type")]
+    comment_type: CommentType,
+    body: String,
+    author: Option<String>,
+    reviewer: Option<Reviewer>,
+    blocking: Option<bool>,
+    line: Option<u32>,
+    end_line: Option<u32>,
+    side: Option<LineSide>,
+    created_at: DateTime<Utc>,
+}
+
+impl ImportedComment {
+    /// The session comment and, for line comments, the line it is keyed
+    /// under (the last line of a range, as when it was written).
+    fn into_comment(self) -> (Comment, Option<u32>) {
+        let mut comment = Comment::new(self.body, self.comment_type, None);
+        comment.id = self.id;
+        comment.author = self.author;
+        comment.reviewer = self.reviewer;
+        comment.blocking = self.blocking;
+        comment.created_at = self.created_at;
+        let Some(line) = self.line else {
+            return (comment, None);
+        };
+        comment.side = Some(self.side.unwrap_or(LineSide::New));
+        let end = self.end_line.unwrap_or(line);
+        if end != line {
+            comment.line_range = Some(LineRange::new(line, end));
+        }
+        (comment, Some(end))
+    }
+}
+
+/// Outcome of `merge_json_review`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub comments: usize,
+    /// Comments the session already has, by ID or by identical text on the
+    /// same line
+    pub duplicates: usize,
+    /// Files newly marked reviewed
+    pub reviewed: usize,
+    /// Files in the export that are not part of this session
+    pub skipped_files: usize,
+}
+
+/// Merge a JSON export (another reviewer's, typically) into `session`: add
+/// the comments it doesn't have yet and mark the files reviewed there as
+/// reviewed here. Local state always wins; nothing is removed.
+pub fn merge_json_review(session: &mut ReviewSession, content: &str) -> Result<MergeSummary> {
+    let review: ImportedReview = serde_json::from_str(content)?;
+    if review.schema_version > JSON_SCHEMA_VERSION {
+        return Err(TuicrError::UnsupportedOperation(format!(
+            "export schema version {} is newer than this tuicr understands ({JSON_SCHEMA_VERSION})",
+            review.schema_version
+        )));
+    }
+
+    let mut summary = MergeSummary::default();
+    let mut seen_ids: HashSet<String> = session
+        .review_comments
+        .iter()
+        .chain(session.files.values().flat_map(|f| {
+            f.file_comments
+                .iter()
+                .chain(f.line_comments.values().flatten())
+        }))
+        .map(|c| c.id.clone())
+        .collect();
+
+    for imported in review.review_comments {
+        let (comment, _) = imported.into_comment();
+        let duplicate = seen_ids.contains(&comment.id)
+            || session
+                .review_comments
+                .iter()
+                .any(|c| c.content == comment.content);
+        if duplicate {
+            summary.duplicates += 1;
+            continue;
+        }
+        seen_ids.insert(comment.id.clone());
+        session.review_comments.push(comment);
+        summary.comments += 1;
+    }
+
+    for file in review.files {
+        let Some(local) = session.get_file_mut(&file.path) else {
+            summary.skipped_files += 1;
+            continue;
+        };
+        if file.reviewed && !local.reviewed {
+            local.reviewed = true;
+            local.confidence = file.confidence.unwrap_or_default();
+            summary.reviewed += 1;
+        }
+        for imported in file.comments {
+            let (comment, line) = imported.into_comment();
+            let existing = match line {
+                Some(line) => local.line_comments.get(&line).map(Vec::as_slice),
+                None => Some(local.file_comments.as_slice()),
+            };
+            let duplicate = seen_ids.contains(&comment.id)
+                || existing.is_some_and(|comments| {
+                    comments
+                        .iter()
+                        .any(|c| c.content == comment.content && c.side == comment.side)
+                });
+            if duplicate {
+                summary.duplicates += 1;
+                continue;
+            }
+            seen_ids.insert(comment.id.clone());
+            match line {
+                Some(line) => local.add_line_comment(line, comment),
+                None => local.add_file_comment(comment),
+            }
+            summary.comments += 1;
+        }
+    }
+    Ok(summary)
+}
+
+/// Rebuild the session a JSON export was written from, for `tuicr view`.
+/// Fails when the export doesn't record which changes it reviewed.
+pub fn session_from_json_review(content: &str) -> Result<ReviewSession> {
+    let review: ImportedReview = serde_json::from_str(content)?;
+    let (Some(repository), Some(base_commit), Some(source)) =
+        (review.repository, review.base_commit, review.source)
+    else {
+        return Err(TuicrError::CorruptedSession(
+            "the export doesn't record the changes it reviewed".to_string(),
+        ));
+    };
+    let mut session = ReviewSession::new(repository, base_commit, review.branch, source);
+    if let Some(id) = review.session_id {
+        session.id = id;
+    }
+    if !review.commits.is_empty() {
+        session.commit_range = Some(review.commits);
+    }
+    session.session_notes = review.summary;
+    for file in review.files {
+        let status = file.status.unwrap_or(FileStatus::Modified);
+        session.add_file(file.path, status, 0);
+    }
+    merge_json_review(&mut session, content)?;
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::review::SampledHunk;
+    use crate::model::{DiffHunk, DiffLine, FileModes, FileSizes, LineOrigin};
+    use crate::output::tests::create_test_session;
+    use std::path::PathBuf;
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        session.diff_source = SessionDiffSource::CommitRange;
+        session.commit_range = Some(vec!["abc1234def".to_string()]);
+        session.add_file(PathBuf::from("README.md"), FileStatus::Added, 0);
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.confidence = ReviewConfidence::Skimmed;
+        review.line_comments.get_mut(&42).unwrap()[0].line_range = Some(LineRange::new(40, 42));
+        review.add_line_comment(
+            3,
+            Comment::new(
+                "Removed check".to_string(),
+                CommentType::Issue,
+                Some(LineSide::Old),
+            ),
+        );
+        session
+    }
+
+    #[test]
+    fn should_serialize_session_in_versioned_schema() {
+        // when
+        let json = generate_json(&session(), &[], DiffAnchors::default()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(value["schema_version"], 1);
+        assert_eq!(value["source"], "commit_range");
+        assert_eq!(value["commits"][0], "abc1234def");
+        assert_eq!(value["verdict"], "changes_requested");
+        let files = value["files"].as_array().unwrap();
+        assert_eq!(files[0]["path"], "README.md");
+        assert_eq!(files[0]["status"], "added");
+        assert_eq!(files[0]["reviewed"], false);
+        assert!(files[0].get("confidence").is_none());
+        assert_eq!(files[1]["confidence"], "skimmed");
+        let comments = files[1]["comments"].as_array().unwrap();
+        assert_eq!(comments[0]["type"], "suggestion");
+        assert!(comments[0].get("line").is_none());
+        assert_eq!(comments[1]["label"], "ISSUE");
+        assert_eq!(comments[1]["line"], 3);
+        assert_eq!(comments[1]["side"], "old");
+        assert_eq!(comments[2]["line"], 40);
+        assert_eq!(comments[2]["end_line"], 42);
+        assert_eq!(comments[2]["side"], "new");
+        assert!(value.get("spot_check").is_none());
+    }
+
+    #[test]
+    fn should_record_spot_check_sample() {
+        // given
+        let mut session = session();
+        session.spot_check = Some(SpotCheck {
+            sampled: vec![SampledHunk {
+                path: PathBuf::from("src/main.rs"),
+                old_start: 3,
+                new_start: 3,
+            }],
+            total_hunks: 40,
+        });
+
+        // when
+        let json = generate_json(&session, &[], DiffAnchors::default()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(value["spot_check"]["total_hunks"], 40);
+        assert_eq!(value["spot_check"]["sampled"][0]["path"], "src/main.rs");
+        assert_eq!(value["spot_check"]["sampled"][0]["new_start"], 3);
+    }
+
+    #[test]
+    fn should_anchor_line_comments_to_diff_positions() {
+        // given
+        let line = |origin, old_lineno, new_lineno| DiffLine {
+            origin,
+            content: String::new(),
+            old_lineno,
+            new_lineno,
+            highlighted_spans: None,
+        };
+        let hunk = |header: &str, lines| DiffHunk {
+            header: header.to_string(),
+            lines,
+            old_start: 0,
+            old_count: 0,
+            new_start: 0,
+            new_count: 0,
+        };
+        let file = DiffFile {
+            old_path: Some(PathBuf::from("src/main.rs")),
+            new_path: Some(PathBuf::from("src/main.rs")),
+            status: FileStatus::Modified,
+            hunks: vec![
+                hunk(
+                    "@@ -1,4 +1,3 @@",
+                    vec![
+                        line(LineOrigin::Context, Some(1), Some(1)),
+                        line(LineOrigin::Context, Some(2), Some(2)),
+                        line(LineOrigin::Deletion, Some(3), None),
+                        line(LineOrigin::Context, Some(4), Some(3)),
+                    ],
+                ),
+                hunk(
+                    "@@ -40,2 +39,4 @@ fn main()",
+                    vec![
+                        line(LineOrigin::Context, Some(40), Some(39)),
+                        line(LineOrigin::Addition, None, Some(40)),
+                        line(LineOrigin::Addition, None, Some(41)),
+                        line(LineOrigin::Context, Some(41), Some(42)),
+                    ],
+                ),
+            ],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
+        let anchors = DiffAnchors {
+            base_sha: Some("0a1b2c"),
+            head_sha: Some("abc1234def"),
+            diff_files: std::slice::from_ref(&file),
+        };
+
+        // when
+        let json = generate_json(&session(), &[], anchors).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(value["base_sha"], "0a1b2c");
+        assert_eq!(value["head_sha"], "abc1234def");
+        let comments = value["files"][1]["comments"].as_array().unwrap();
+        assert!(comments[0].get("anchor").is_none());
+        assert_eq!(
+            comments[1]["anchor"],
+            serde_json::json!({ "hunk": "@@ -1,4 +1,3 @@", "position": 3 })
+        );
+        assert_eq!(
+            comments[2]["anchor"],
+            serde_json::json!({
+                "hunk": "@@ -40,2 +39,4 @@ fn main()",
+                "position": 9,
+                "start_position": 7
+            })
+        );
+    }
+
+    #[test]
+    fn should_report_verdict_from_review_state() {
+        // given
+        let mut session = session();
+        for review in session.files.values_mut() {
+            review.line_comments.clear();
+        }
+        assert_eq!(verdict(&session), "pending");
+
+        // when
+        for review in session.files.values_mut() {
+            review.reviewed = true;
+        }
+
+        // then
+        assert_eq!(verdict(&session), "approved");
+    }
+
+    #[test]
+    fn should_merge_exported_review_into_other_session() {
+        // given another reviewer's export of the same change
+        let export = generate_json(&session(), &[], DiffAnchors::default()).unwrap();
+        let mut mine = session();
+        for review in mine.files.values_mut() {
+            review.reviewed = false;
+            review.file_comments.clear();
+            review.line_comments.clear();
+        }
+        mine.get_file_mut(&PathBuf::from("src/main.rs"))
+            .unwrap()
+            .add_file_comment(Comment::new(
+                "Consider adding documentation".to_string(),
+                CommentType::Suggestion,
+                None,
+            ));
+
+        // when
+        let summary = merge_json_review(&mut mine, &export).unwrap();
+
+        // then
+        assert_eq!(
+            summary,
+            MergeSummary {
+                comments: 2,
+                duplicates: 1,
+                reviewed: 1,
+                skipped_files: 0,
+            }
+        );
+        let review = &mine.files[&PathBuf::from("src/main.rs")];
+        assert!(review.reviewed);
+        assert_eq!(review.confidence, ReviewConfidence::Skimmed);
+        assert_eq!(review.file_comments.len(), 1);
+        let ranged = &review.line_comments[&42][0];
+        assert_eq!(ranged.line_range, Some(LineRange::new(40, 42)));
+        assert_eq!(ranged.comment_type, CommentType::Issue);
+        assert_eq!(review.line_comments[&3][0].side, Some(LineSide::Old));
+    }
+
+    #[test]
+    fn should_keep_reviewer_of_merged_comments() {
+        // given
+        let bob = Reviewer {
+            name: "Bob".to_string(),
+            email: Some("bob@example.com".to_string()),
+        };
+        let mut theirs = session();
+        theirs.reviewer = Some(bob.clone());
+        theirs.review_comments.push(
+            Comment::new("Ship it".to_string(), CommentType::Praise, None)
+                .with_reviewer(Some(bob.clone())),
+        );
+        let export = generate_json(&theirs, &[], DiffAnchors::default()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&export).unwrap();
+        let mut mine = session();
+
+        // when
+        merge_json_review(&mut mine, &export).unwrap();
+
+        // then
+        assert_eq!(value["reviewer"]["email"], "bob@example.com");
+        assert_eq!(mine.review_comments[0].reviewer, Some(bob));
+    }
+
+    #[test]
+    fn should_not_duplicate_comments_on_repeated_merge() {
+        // given
+        let export = generate_json(&session(), &[], DiffAnchors::default()).unwrap();
+        let mut mine = session();
+
+        // when
+        let summary = merge_json_review(&mut mine, &export).unwrap();
+
+        // then
+        assert_eq!(summary.comments, 0);
+        assert_eq!(summary.duplicates, 3);
+        assert_eq!(mine.files[&PathBuf::from("src/main.rs")].comment_count(), 3);
+    }
+
+    #[test]
+    fn should_reject_exports_from_newer_schema() {
+        // given
+        let export = r#"{"schema_version": 99, "files": []}"#;
+
+        // when
+        let result = merge_json_review(&mut session(), export);
+
+        // then
+        assert!(matches!(result, Err(TuicrError::UnsupportedOperation(_))));
+    }
+}
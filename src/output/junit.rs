@@ -0,0 +1,225 @@
+//! JUnit XML report of the review's ISSUE comments, written with
+//! `:export <path>.xml`, so CI can fail a build on open findings and show
+//! them in its test UI.
+//!
+//! Every ISSUE comment is one failed test case named after its location;
+//! comments on removed lines note that in the message, since their line
+//! numbers refer to the old version. A review without issues still writes a
+//! report, with no failures.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::error::Result;
+use crate::model::{Comment, CommentType, LineSide, ReviewSession};
+
+struct Failure<'a> {
+    /// `path`, `path:line` or `path:start-end`; empty for review comments
+    location: String,
+    path: Option<&'a Path>,
+    line: Option<u32>,
+    comment: &'a Comment,
+}
+
+fn failure<'a>(path: Option<&'a Path>, line: Option<u32>, comment: &'a Comment) -> Failure<'a> {
+    let old_side = comment.side == Some(LineSide::Old);
+    let start = comment.line_range.map(|r| r.start).or(line);
+    let mut location = path.map(|p| p.display().to_string()).unwrap_or_default();
+    match (start, line) {
+        (Some(start), Some(end)) if start != end => {
+            let _ = write!(location, ":{start}-{end}");
+        }
+        (_, Some(line)) => {
+            let _ = write!(location, ":{line}");
+        }
+        _ => {}
+    }
+    if old_side && line.is_some() {
+        location.push_str(" (removed line)");
+    }
+    Failure {
+        location,
+        path,
+        line: start.filter(|_| !old_side),
+        comment,
+    }
+}
+
+/// Build the JUnit report: one `<testcase>` with a `<failure>` per ISSUE
+/// comment, review-level ones first, then by file and line.
+pub fn generate_junit(session: &ReviewSession) -> String {
+    let is_issue = |c: &&Comment| c.comment_type == CommentType::Issue;
+    let mut failures: Vec<Failure> = session
+        .review_comments
+        .iter()
+        .filter(is_issue)
+        .map(|c| failure(None, None, c))
+        .collect();
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+    for (path, review) in files {
+        failures.extend(
+            review
+                .file_comments
+                .iter()
+                .filter(is_issue)
+                .map(|c| failure(Some(path), None, c)),
+        );
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, comments) in line_comments {
+            failures.extend(
+                comments
+                    .iter()
+                    .filter(is_issue)
+                    .map(|c| failure(Some(path), Some(*line), c)),
+            );
+        }
+    }
+
+    let count = failures.len();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"tuicr\" tests=\"{count}\" failures=\"{count}\">"
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"code review\" tests=\"{count}\" failures=\"{count}\" errors=\"0\" skipped=\"0\">"
+    );
+    for failure in &failures {
+        let classname = failure
+            .path
+            .map_or_else(|| "review".to_string(), |p| p.display().to_string());
+        let name = if failure.location.is_empty() {
+            "review comment".to_string()
+        } else {
+            failure.location.clone()
+        };
+        let _ = write!(
+            xml,
+            "    <testcase classname=\"{}\" name=\"{}\"",
+            escape(&classname),
+            escape(&name)
+        );
+        if let Some(path) = failure.path {
+            let _ = write!(xml, " file=\"{}\"", escape(&path.display().to_string()));
+        }
+        if let Some(line) = failure.line {
+            let _ = write!(xml, " line=\"{line}\"");
+        }
+        xml.push_str(">\n");
+        let summary = failure.comment.content.lines().next().unwrap_or_default();
+        let message = if failure.location.is_empty() {
+            summary.to_string()
+        } else {
+            format!("{}: {summary}", failure.location)
+        };
+        let kind = match failure.comment.blocking {
+            Some(true) => "issue (blocking)",
+            _ => "issue",
+        };
+        let _ = writeln!(
+            xml,
+            "      <failure message=\"{}\" type=\"{kind}\">{}</failure>",
+            escape(&message),
+            escape(&failure.comment.content)
+        );
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Write the JUnit report to `path`, creating parent directories as needed.
+pub fn export_junit_to_file(path: &Path, session: &ReviewSession) -> Result<()> {
+    let content = generate_junit(session);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Escape text for an XML attribute or element, dropping the control
+/// characters XML 1.0 can't carry.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            _ if c.is_control() => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::LineRange;
+    use crate::output::tests::create_test_session;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_export_issue_comments_as_failed_test_cases() {
+        // given
+        let mut session = create_test_session();
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        let mut ranged = Comment::new(
+            "Unchecked <index>\nSee the caller".to_string(),
+            CommentType::Issue,
+            Some(LineSide::New),
+        )
+        .with_blocking(Some(true));
+        ranged.line_range = Some(LineRange::new(40, 42));
+        review.line_comments.insert(42, vec![ranged]);
+        review.add_line_comment(
+            2,
+            Comment::new(
+                "Dropped the bounds check".to_string(),
+                CommentType::Issue,
+                Some(LineSide::Old),
+            ),
+        );
+
+        // when
+        let xml = generate_junit(&session);
+
+        // then
+        assert!(xml.contains(r#"<testsuites name="tuicr" tests="2" failures="2">"#));
+        assert!(xml.contains(
+            r#"<testcase classname="src/main.rs" name="src/main.rs:2 (removed line)" file="src/main.rs">"#
+        ));
+        assert!(xml.contains(
+            r#"<testcase classname="src/main.rs" name="src/main.rs:40-42" file="src/main.rs" line="40">"#
+        ));
+        assert!(xml.contains(
+            r#"<failure message="src/main.rs:40-42: Unchecked &lt;index&gt;" type="issue (blocking)">Unchecked &lt;index&gt;
+See the caller</failure>"#
+        ));
+        assert!(!xml.contains("Consider adding documentation"));
+    }
+
+    #[test]
+    fn should_write_passing_report_without_issues() {
+        // given
+        let mut session = create_test_session();
+        for review in session.files.values_mut() {
+            review.line_comments.clear();
+        }
+
+        // when
+        let xml = generate_junit(&session);
+
+        // then
+        assert!(xml.contains(r#"tests="0" failures="0""#));
+        assert!(!xml.contains("<testcase"));
+    }
+}
@@ -10,10 +10,20 @@ use crate::error::{Result, TuicrError};
 use crate::forge::remote_comments::{
     PrCommentsVisibility, RemoteReviewThread, filter_threads, group_threads_by_path,
 };
-use crate::model::{CommentType, LineRange, LineSide, ReviewSession};
+use crate::model::{
+    Comment, CommentType, DiffFile, DiffHunk, LineOrigin, LineRange, LineSide, ReviewSession,
+    conventional_label, decorated_label,
+};
+use crate::output::patch::generate_review_fixes_patch;
 
-/// (file_path, line_range, side, comment_type, content)
-type CommentEntry<'a> = (String, Option<LineRange>, Option<LineSide>, String, &'a str);
+/// (file_path, line_range, side, label, comment)
+type CommentEntry<'a> = (
+    String,
+    Option<LineRange>,
+    Option<LineSide>,
+    String,
+    &'a Comment,
+);
 
 /// Generate markdown content from the review session.
 /// Returns the markdown string or an error if there are no comments.
@@ -22,6 +32,7 @@ pub fn generate_export_content(
     diff_source: &DiffSource,
     comment_types: &[CommentTypeDefinition],
     show_legend: bool,
+    conventional: bool,
     remote_threads: &[RemoteReviewThread],
 ) -> Result<String> {
     // In PR mode it's still useful to export PR identity + remote
@@ -29,7 +40,7 @@ pub fn generate_export_content(
     // we keep the existing behavior of erroring when nothing is to say.
     let has_remote = matches!(diff_source, DiffSource::PullRequest(_))
         && !filter_threads(remote_threads, PrCommentsVisibility::Unresolved).is_empty();
-    if !session.has_comments() && !has_remote {
+    if !session.has_exportable_content() && !has_remote {
         return Err(TuicrError::NoComments);
     }
     Ok(generate_markdown(
@@ -37,6 +48,7 @@ pub fn generate_export_content(
         diff_source,
         comment_types,
         show_legend,
+        conventional,
         remote_threads,
     ))
 }
@@ -46,6 +58,7 @@ pub fn export_to_clipboard(
     diff_source: &DiffSource,
     comment_types: &[CommentTypeDefinition],
     show_legend: bool,
+    conventional: bool,
     remote_threads: &[RemoteReviewThread],
 ) -> Result<String> {
     let content = generate_export_content(
@@ -53,6 +66,7 @@ pub fn export_to_clipboard(
         diff_source,
         comment_types,
         show_legend,
+        conventional,
         remote_threads,
     )?;
     let via_terminal = copy_text_to_clipboard(&content)?;
@@ -84,7 +98,7 @@ pub fn copy_text_to_clipboard(text: &str) -> Result<bool> {
 ///
 /// In tmux or SSH sessions, arboard may "succeed" but copy to an inaccessible
 /// X11 clipboard, so we use OSC 52 which works reliably in these environments.
-fn should_prefer_osc52() -> bool {
+pub(crate) fn should_prefer_osc52() -> bool {
     std::env::var("TMUX").is_ok()
         || std::env::var("SSH_TTY").is_ok()
         || std::env::var("ZELLIJ").is_ok()
@@ -146,8 +160,53 @@ fn write_osc52<W: IoWrite>(writer: &mut W, text: &str) -> Result<()> {
     Ok(())
 }
 
-fn review_scope_label(diff_source: &DiffSource) -> String {
-    let scope = match diff_source {
+/// Render one hunk as a GitHub-flavored fenced `diff` block under a
+/// `` `path:line` `` caption, ready to paste into an issue or chat thread.
+pub fn format_hunk_markdown(file: &DiffFile, hunk: &DiffHunk) -> String {
+    let line = if hunk.new_count > 0 {
+        hunk.new_start
+    } else {
+        hunk.old_start
+    };
+    let mut body = String::new();
+    if !hunk.header.is_empty() {
+        let _ = writeln!(body, "{}", hunk.header);
+    }
+    for diff_line in &hunk.lines {
+        let prefix = match diff_line.origin {
+            LineOrigin::Addition => '+',
+            LineOrigin::Deletion => '-',
+            LineOrigin::Context => ' ',
+        };
+        let _ = writeln!(body, "{prefix}{}", diff_line.content);
+    }
+
+    let fence = fence_for(&body);
+    format!(
+        "`{}:{line}`\n\n{fence}diff\n{body}{fence}\n",
+        file.display_path().display()
+    )
+}
+
+/// ` — Name` after a comment written by someone other than the session's
+/// reviewer, e.g. one merged in with `:import`; empty otherwise.
+pub(crate) fn attribution(comment: &Comment, session: &ReviewSession) -> String {
+    match &comment.reviewer {
+        Some(reviewer) if session.reviewer.as_ref() != Some(reviewer) => {
+            format!(" — {}", reviewer.name)
+        }
+        _ => String::new(),
+    }
+}
+
+/// A backtick fence longer than any backtick run inside `body`
+pub(crate) fn fence_for(body: &str) -> String {
+    let longest_run = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest_run.max(2) + 1)
+}
+
+pub(crate) fn review_scope(diff_source: &DiffSource) -> String {
+    match diff_source {
         DiffSource::WorkingTree => "working tree changes".to_string(),
         DiffSource::StagedAndUnstaged => "staged + unstaged changes".to_string(),
         DiffSource::Staged => "staged changes".to_string(),
@@ -161,9 +220,11 @@ fn review_scope_label(diff_source: &DiffSource) -> String {
             pr.key.repository.display_name(),
             pr.key.number
         ),
-    };
+    }
+}
 
-    format!("Review Comment (scope: {scope})")
+fn review_scope_label(diff_source: &DiffSource) -> String {
+    format!("Review Comment (scope: {})", review_scope(diff_source))
 }
 
 fn generate_markdown(
@@ -171,6 +232,7 @@ fn generate_markdown(
     diff_source: &DiffSource,
     comment_types: &[CommentTypeDefinition],
     show_legend: bool,
+    conventional: bool,
     remote_threads: &[RemoteReviewThread],
 ) -> String {
     let mut md = String::new();
@@ -234,7 +296,14 @@ fn generate_markdown(
         }
     }
 
-    if show_legend {
+    if show_legend && conventional {
+        let _ = writeln!(
+            md,
+            "Comments follow Conventional Comments (https://conventionalcomments.org): \
+             `label (decoration): subject`"
+        );
+        let _ = writeln!(md);
+    } else if show_legend {
         let used_ids = collect_used_comment_type_ids(session);
         let legend = if comment_types.is_empty() {
             let all = ["NOTE", "SUGGESTION", "ISSUE", "PRAISE"];
@@ -272,12 +341,31 @@ fn generate_markdown(
         let _ = writeln!(md);
     }
 
+    if let Some(reviewer) = &session.reviewer {
+        let _ = writeln!(md, "Reviewer: {reviewer}");
+        let _ = writeln!(md);
+    }
+
     // Session notes/summary
     if let Some(notes) = &session.session_notes {
         let _ = writeln!(md, "Summary: {notes}");
         let _ = writeln!(md);
     }
 
+    if let Some(spot_check) = &session.spot_check {
+        let _ = writeln!(md, "Spot-checked: {}", spot_check.coverage());
+        let _ = writeln!(md);
+    }
+
+    if !session.checklist.is_empty() {
+        let _ = writeln!(md, "Checklist:");
+        for item in &session.checklist {
+            let mark = if item.done { 'x' } else { ' ' };
+            let _ = writeln!(md, "- [{mark}] {}", item.text);
+        }
+        let _ = writeln!(md);
+    }
+
     // Collect all comments into a flat list
     let mut all_comments: Vec<CommentEntry> = Vec::new();
     let review_comment_location = review_scope_label(diff_source);
@@ -287,8 +375,8 @@ fn generate_markdown(
             review_comment_location.clone(),
             None,
             None,
-            export_comment_type_label(&comment.comment_type, comment_types),
-            &comment.content,
+            markdown_comment_label(comment, comment_types, conventional),
+            comment,
         ));
     }
 
@@ -305,8 +393,8 @@ fn generate_markdown(
                 path_str.clone(),
                 None,
                 None,
-                export_comment_type_label(&comment.comment_type, comment_types),
-                &comment.content,
+                markdown_comment_label(comment, comment_types, conventional),
+                comment,
             ));
         }
 
@@ -324,8 +412,8 @@ fn generate_markdown(
                     path_str.clone(),
                     line_range,
                     comment.side,
-                    export_comment_type_label(&comment.comment_type, comment_types),
-                    &comment.content,
+                    markdown_comment_label(comment, comment_types, conventional),
+                    comment,
                 ));
             }
         }
@@ -338,7 +426,8 @@ fn generate_markdown(
         let _ = writeln!(md);
         local_section_written = true;
     }
-    for (i, (file, line_range, side, comment_type, content)) in all_comments.iter().enumerate() {
+    for (i, (file, line_range, side, label, comment)) in all_comments.iter().enumerate() {
+        let content = format!("{}{}", comment.content, attribution(comment, session));
         let location = match (line_range, side) {
             // Range on deleted side (old lines)
             (Some(range), Some(LineSide::Old)) if range.is_single() => {
@@ -357,14 +446,12 @@ fn generate_markdown(
             // File comment
             (None, _) => format!("`{file}`"),
         };
-        let _ = writeln!(
-            md,
-            "{}. **[{}]** {} - {}",
-            i + 1,
-            comment_type,
-            location,
-            content
-        );
+        // Conventional Comments put the label right before the subject
+        if conventional {
+            let _ = writeln!(md, "{}. {location} - {label} {content}", i + 1);
+        } else {
+            let _ = writeln!(md, "{}. {label} {location} - {content}", i + 1);
+        }
     }
 
     // PR-mode-only: include unresolved remote discussions grouped by file.
@@ -411,6 +498,19 @@ fn generate_markdown(
         }
     }
 
+    if !session.review_edits.is_empty() {
+        if local_section_written {
+            let _ = writeln!(md);
+        }
+        let _ = writeln!(md, "## Review Fixes");
+        let _ = writeln!(md);
+        let _ = writeln!(md, "Apply with `git apply`:");
+        let _ = writeln!(md);
+        let _ = writeln!(md, "```diff");
+        let _ = write!(md, "{}", generate_review_fixes_patch(&session.review_edits));
+        let _ = writeln!(md, "```");
+    }
+
     md
 }
 
@@ -432,7 +532,22 @@ fn collect_used_comment_type_ids(session: &ReviewSession) -> HashSet<String> {
     ids
 }
 
-fn export_comment_type_label(
+/// A comment's label as written in Markdown exports: `**[ISSUE]**`, or in
+/// Conventional Comments form, `**issue (blocking):**`.
+pub(crate) fn markdown_comment_label(
+    comment: &Comment,
+    comment_types: &[CommentTypeDefinition],
+    conventional: bool,
+) -> String {
+    let label = export_comment_type_label(&comment.comment_type, comment_types);
+    if conventional {
+        format!("**{}:**", conventional_label(&label, comment.blocking))
+    } else {
+        format!("**[{}]**", decorated_label(&label, comment.blocking))
+    }
+}
+
+pub(crate) fn export_comment_type_label(
     comment_type: &CommentType,
     comment_types: &[CommentTypeDefinition],
 ) -> String {
@@ -450,7 +565,10 @@ fn export_comment_type_label(
 mod tests {
     use super::*;
     use crate::app::CommentTypeDefinition;
-    use crate::model::{Comment, CommentType, FileStatus, LineRange, LineSide, SessionDiffSource};
+    use crate::model::{
+        Comment, CommentType, FileStatus, LineRange, LineSide, Reviewer, SessionDiffSource,
+    };
+    use crate::output::tests::create_test_session;
     use std::path::PathBuf;
 
     fn comment_types() -> Vec<CommentTypeDefinition> {
@@ -482,36 +600,6 @@ mod tests {
         ]
     }
 
-    fn create_test_session() -> ReviewSession {
-        let mut session = ReviewSession::new(
-            PathBuf::from("/tmp/test-repo"),
-            "abc1234def".to_string(),
-            Some("main".to_string()),
-            SessionDiffSource::WorkingTree,
-        );
-        session.add_file(PathBuf::from("src/main.rs"), FileStatus::Modified, 0);
-
-        // Add a file comment
-        if let Some(review) = session.get_file_mut(&PathBuf::from("src/main.rs")) {
-            review.reviewed = true;
-            review.add_file_comment(Comment::new(
-                "Consider adding documentation".to_string(),
-                CommentType::Suggestion,
-                None,
-            ));
-            review.add_line_comment(
-                42,
-                Comment::new(
-                    "Magic number should be a constant".to_string(),
-                    CommentType::Issue,
-                    Some(LineSide::New),
-                ),
-            );
-        }
-
-        session
-    }
-
     #[test]
     fn should_generate_valid_markdown() {
         // given
@@ -519,7 +607,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("I reviewed your code and have the following comments"));
@@ -536,6 +625,76 @@ mod tests {
         assert!(markdown.contains("Magic number"));
     }
 
+    #[test]
+    fn should_name_reviewer_and_attribute_comments_by_others() {
+        // given
+        let mut session = create_test_session();
+        let alice = Reviewer {
+            name: "Alice".to_string(),
+            email: Some("alice@example.com".to_string()),
+        };
+        session.reviewer = Some(alice.clone());
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.file_comments[0].reviewer = Some(alice);
+        review.line_comments.get_mut(&42).unwrap()[0].reviewer = Some(Reviewer {
+            name: "Bob".to_string(),
+            email: None,
+        });
+
+        // when
+        let markdown = generate_markdown(
+            &session,
+            &DiffSource::WorkingTree,
+            &comment_types(),
+            false,
+            false,
+            &[],
+        );
+
+        // then
+        assert!(markdown.contains("Reviewer: Alice <alice@example.com>\n"));
+        assert!(markdown.contains("- Consider adding documentation\n"));
+        assert!(markdown.contains("- Magic number should be a constant — Bob\n"));
+    }
+
+    #[test]
+    fn should_label_comments_conventional_comments_style() {
+        // given
+        let mut session = create_test_session();
+        let path = PathBuf::from("src/main.rs");
+        let review = session.get_file_mut(&path).unwrap();
+        review.file_comments[0].blocking = Some(false);
+        review.line_comments.get_mut(&42).unwrap()[0].blocking = Some(true);
+
+        // when
+        let conventional = generate_markdown(
+            &session,
+            &DiffSource::WorkingTree,
+            &comment_types(),
+            true,
+            true,
+            &[],
+        );
+        let classic = generate_markdown(
+            &session,
+            &DiffSource::WorkingTree,
+            &comment_types(),
+            true,
+            false,
+            &[],
+        );
+
+        // then
+        assert!(conventional.contains("Comments follow Conventional Comments"));
+        assert!(conventional.contains(
+            "1. `src/main.rs` - **suggestion (non-blocking):** Consider adding documentation"
+        ));
+        assert!(conventional.contains(
+            "2. `src/main.rs:42` - **issue (blocking):** Magic number should be a constant"
+        ));
+        assert!(classic.contains("2. **[ISSUE (blocking)]** `src/main.rs:42` - Magic number"));
+    }
+
     #[test]
     fn should_use_configured_label_and_definition_in_export() {
         let mut session = ReviewSession::new(
@@ -560,8 +719,14 @@ mod tests {
             color: None,
         }];
 
-        let markdown =
-            generate_markdown(&session, &DiffSource::WorkingTree, &custom_types, true, &[]);
+        let markdown = generate_markdown(
+            &session,
+            &DiffSource::WorkingTree,
+            &custom_types,
+            true,
+            false,
+            &[],
+        );
 
         assert!(markdown.contains("Comment types: QUESTION (ask for clarification)"));
         assert!(markdown.contains("**[QUESTION]**"));
@@ -574,7 +739,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         // Should have 2 numbered comments
@@ -596,6 +762,7 @@ mod tests {
             &DiffSource::WorkingTree,
             &comment_types(),
             true,
+            false,
             &[],
         );
 
@@ -617,6 +784,7 @@ mod tests {
             &DiffSource::CommitRange(vec!["abc1234567890".to_string()]),
             &comment_types(),
             true,
+            false,
             &[],
         );
 
@@ -637,13 +805,47 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let result = export_to_clipboard(&session, &diff_source, &comment_types(), true, &[]);
+        let result =
+            export_to_clipboard(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), TuicrError::NoComments));
     }
 
+    #[test]
+    fn should_export_review_fixes_without_comments() {
+        // given
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            "abc1234def".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.set_review_edit(
+            PathBuf::from("README.md"),
+            3,
+            "Teh tool".to_string(),
+            "The tool".to_string(),
+        );
+
+        // when
+        let content = generate_export_content(
+            &session,
+            &DiffSource::WorkingTree,
+            &comment_types(),
+            true,
+            false,
+            &[],
+        )
+        .expect("review fixes alone should export");
+
+        // then
+        assert!(content.contains("## Review Fixes"));
+        assert!(content.contains("```diff\n--- a/README.md\n+++ b/README.md\n"));
+        assert!(content.contains("-Teh tool\n+The tool\n```"));
+    }
+
     #[test]
     fn should_generate_export_content_with_comments() {
         // given
@@ -651,7 +853,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let result = generate_export_content(&session, &diff_source, &comment_types(), true, &[]);
+        let result =
+            generate_export_content(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(result.is_ok());
@@ -673,7 +876,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let result = generate_export_content(&session, &diff_source, &comment_types(), true, &[]);
+        let result =
+            generate_export_content(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(result.is_err());
@@ -690,7 +894,8 @@ mod tests {
         ]);
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("Reviewing commits: abc1234, def4567"));
@@ -703,7 +908,8 @@ mod tests {
         let diff_source = DiffSource::CommitRange(vec!["abc1234567890".to_string()]);
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("Reviewing commit: abc1234"));
@@ -764,7 +970,8 @@ mod tests {
         // given - simulate what would be copied during export
         let session = create_test_session();
         let diff_source = DiffSource::WorkingTree;
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
         let mut buffer: Vec<u8> = Vec::new();
 
         // when
@@ -806,7 +1013,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("`src/main.rs:42`"));
@@ -839,7 +1047,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("`src/main.rs:10-15`"));
@@ -872,7 +1081,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("`src/main.rs:~20-~25`"));
@@ -904,7 +1114,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("`src/main.rs:~30`"));
@@ -936,7 +1147,8 @@ mod tests {
         let diff_source = DiffSource::WorkingTree;
 
         // when
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), true, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), true, false, &[]);
 
         // then
         assert!(markdown.contains("`src/main.rs:50`"));
@@ -947,13 +1159,41 @@ mod tests {
         let session = create_test_session();
         let diff_source = DiffSource::WorkingTree;
 
-        let markdown = generate_markdown(&session, &diff_source, &comment_types(), false, &[]);
+        let markdown =
+            generate_markdown(&session, &diff_source, &comment_types(), false, false, &[]);
 
         assert!(!markdown.contains("Comment types:"));
         assert!(markdown.contains("[SUGGESTION]"));
         assert!(markdown.contains("[ISSUE]"));
     }
 
+    #[test]
+    fn should_render_checklist_with_done_state() {
+        // given
+        let mut session = create_test_session();
+        session.apply_template(
+            "security",
+            &[
+                "Inputs validated".to_string(),
+                "Secrets scrubbed".to_string(),
+            ],
+        );
+        session.toggle_checklist_item(2);
+
+        // when
+        let markdown = generate_markdown(
+            &session,
+            &DiffSource::WorkingTree,
+            &comment_types(),
+            false,
+            false,
+            &[],
+        );
+
+        // then
+        assert!(markdown.contains("Checklist:\n- [ ] Inputs validated\n- [x] Secrets scrubbed\n"));
+    }
+
     #[test]
     fn should_only_list_used_comment_types_in_legend() {
         let mut session = ReviewSession::new(
@@ -976,6 +1216,7 @@ mod tests {
             &DiffSource::WorkingTree,
             &comment_types(),
             true,
+            false,
             &[],
         );
 
@@ -1063,6 +1304,7 @@ mod tests {
             &sample_pr_diff_source(),
             &comment_types(),
             true,
+            false,
             &threads,
         );
 
@@ -1097,6 +1339,7 @@ mod tests {
             &sample_pr_diff_source(),
             &comment_types(),
             true,
+            false,
             &threads,
         );
 
@@ -1130,6 +1373,7 @@ mod tests {
             &DiffSource::WorkingTree,
             &comment_types(),
             true,
+            false,
             &threads,
         );
 
@@ -1169,10 +1413,79 @@ mod tests {
             },
         ];
 
-        let markdown =
-            generate_markdown(&session, &DiffSource::WorkingTree, &custom_types, true, &[]);
+        let markdown = generate_markdown(
+            &session,
+            &DiffSource::WorkingTree,
+            &custom_types,
+            true,
+            false,
+            &[],
+        );
 
         assert!(markdown.contains("Comment types: QUESTION (ask for clarification)"));
         assert!(!markdown.contains("ISSUE"));
     }
+
+    fn hunk_with_lines(lines: &[(LineOrigin, &str)]) -> DiffHunk {
+        DiffHunk {
+            header: "@@ -10,2 +10,2 @@ fn main() {".to_string(),
+            lines: lines
+                .iter()
+                .map(|(origin, content)| crate::model::DiffLine {
+                    origin: *origin,
+                    content: content.to_string(),
+                    old_lineno: None,
+                    new_lineno: None,
+                    highlighted_spans: None,
+                })
+                .collect(),
+            old_start: 10,
+            old_count: 2,
+            new_start: 10,
+            new_count: 2,
+        }
+    }
+
+    fn file_with_hunk(hunk: DiffHunk) -> DiffFile {
+        DiffFile {
+            old_path: Some(PathBuf::from("src/main.rs")),
+            new_path: Some(PathBuf::from("src/main.rs")),
+            status: FileStatus::Modified,
+            content_hash: 0,
+            hunks: vec![hunk],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: crate::model::FileModes::default(),
+            sizes: crate::model::FileSizes::default(),
+        }
+    }
+
+    #[test]
+    fn should_format_hunk_as_fenced_diff_with_caption() {
+        let hunk = hunk_with_lines(&[
+            (LineOrigin::Context, "let a = 1;"),
+            (LineOrigin::Deletion, "let b = 2;"),
+            (LineOrigin::Addition, "let b = 3;"),
+        ]);
+        let file = file_with_hunk(hunk);
+
+        let markdown = format_hunk_markdown(&file, &file.hunks[0]);
+
+        assert_eq!(
+            markdown,
+            "`src/main.rs:10`\n\n```diff\n@@ -10,2 +10,2 @@ fn main() {\n let a = 1;\n-let b = 2;\n+let b = 3;\n```\n"
+        );
+    }
+
+    #[test]
+    fn should_lengthen_fence_past_backticks_in_hunk() {
+        let hunk = hunk_with_lines(&[(LineOrigin::Addition, "/// ```rust")]);
+        let file = file_with_hunk(hunk);
+
+        let markdown = format_hunk_markdown(&file, &file.hunks[0]);
+
+        assert!(markdown.contains("\n````diff\n"));
+        assert!(markdown.ends_with("\n````\n"));
+    }
 }
@@ -1,3 +1,69 @@
+pub mod annotated_patch;
+pub mod chat;
+pub mod email;
+pub mod gerrit;
+pub mod html;
+pub mod json;
+pub mod junit;
 pub mod markdown;
+pub mod patch;
+pub mod rdjson;
+pub mod report;
+pub mod sarif;
+pub mod template;
 
-pub use markdown::{copy_text_to_clipboard, export_to_clipboard, generate_export_content};
+pub use annotated_patch::export_annotated_patch_to_file;
+pub use email::export_email_to_file;
+pub use gerrit::export_gerrit_to_file;
+pub use html::export_html_to_file;
+pub use json::export_json_to_file;
+pub use junit::export_junit_to_file;
+pub use markdown::{
+    copy_text_to_clipboard, export_to_clipboard, format_hunk_markdown, generate_export_content,
+};
+pub use patch::export_suggestions_patch_to_file;
+pub use rdjson::export_rdjson_to_file;
+pub use report::export_report_to_file;
+pub use sarif::export_sarif_to_file;
+pub use template::{export_template_to_file, render_review_template};
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::PathBuf;
+
+    use crate::model::{
+        Comment, CommentType, FileStatus, LineSide, ReviewSession, SessionDiffSource,
+    };
+
+    /// A working-tree review on `main` with `src/main.rs` marked reviewed,
+    /// a SUGGESTION on the file and an ISSUE on new line 42.
+    pub(crate) fn create_test_session() -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            "abc1234def".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(PathBuf::from("src/main.rs"), FileStatus::Modified, 0);
+
+        // Add a file comment
+        if let Some(review) = session.get_file_mut(&PathBuf::from("src/main.rs")) {
+            review.reviewed = true;
+            review.add_file_comment(Comment::new(
+                "Consider adding documentation".to_string(),
+                CommentType::Suggestion,
+                None,
+            ));
+            review.add_line_comment(
+                42,
+                Comment::new(
+                    "Magic number should be a constant".to_string(),
+                    CommentType::Issue,
+                    Some(LineSide::New),
+                ),
+            );
+        }
+
+        session
+    }
+}
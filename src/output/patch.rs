@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::error::Result;
+use crate::model::{DiffFile, LineRange, LineSide, ReviewEdit, ReviewSession};
+
+/// Render the session's review-only edits as a unified diff that `git apply`
+/// (or `patch -p1`) accepts against the reviewed tree. Each edit becomes its
+/// own single-line hunk; files and lines are emitted in sorted order so the
+/// output is stable across runs.
+pub fn generate_review_fixes_patch(edits: &[ReviewEdit]) -> String {
+    let mut sorted: Vec<&ReviewEdit> = edits.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+
+    let mut patch = String::new();
+    let mut current_path = None;
+    for edit in sorted {
+        if current_path != Some(&edit.path) {
+            let path = edit.path.display();
+            let _ = writeln!(patch, "--- a/{path}");
+            let _ = writeln!(patch, "+++ b/{path}");
+            current_path = Some(&edit.path);
+        }
+        let _ = writeln!(patch, "@@ -{0},1 +{0},1 @@", edit.line);
+        let _ = writeln!(patch, "-{}", edit.original);
+        let _ = writeln!(patch, "+{}", edit.replacement);
+    }
+    patch
+}
+
+/// Context lines kept around each suggestion, as `git diff` does; `git
+/// apply` refuses hunks without trailing context away from the end of a file.
+const SUGGESTION_CONTEXT: u32 = 3;
+
+/// SUGGESTION comments with a ```` ```suggestion ```` block, as a patch
+/// against the reviewed tree.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SuggestionsPatch {
+    pub patch: String,
+    pub applied: usize,
+    /// Suggestions left out: on removed lines, on lines the diff doesn't
+    /// show, or overlapping an earlier suggestion.
+    pub skipped: usize,
+}
+
+struct Replacement<'a> {
+    range: LineRange,
+    lines: Vec<&'a str>,
+}
+
+/// Render every new-side suggestion as a unified diff `git apply` accepts.
+/// Context comes from the diff itself, so a suggestion is only placed when
+/// all of its lines are in a hunk.
+pub fn generate_suggestions_patch(
+    session: &ReviewSession,
+    diff_files: &[DiffFile],
+) -> SuggestionsPatch {
+    let total = session
+        .files
+        .values()
+        .flat_map(|review| review.line_comments.values().flatten())
+        .filter(|comment| comment.suggested_code().is_some())
+        .count();
+
+    let mut result = SuggestionsPatch::default();
+    for file in diff_files.iter().filter(|f| !f.is_commit_message) {
+        let path = file.display_path();
+        let Some(review) = session.files.get(path) else {
+            continue;
+        };
+        let new_lines: BTreeMap<u32, &str> = file
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .filter_map(|line| Some((line.new_lineno?, line.content.as_str())))
+            .collect();
+
+        let mut replacements: Vec<Replacement> = review
+            .line_comments
+            .iter()
+            .flat_map(|(line, comments)| comments.iter().map(move |c| (*line, c)))
+            .filter(|(_, comment)| comment.side != Some(LineSide::Old))
+            .filter_map(|(line, comment)| {
+                Some(Replacement {
+                    range: comment.line_range.unwrap_or(LineRange::single(line)),
+                    lines: comment.suggested_code()?,
+                })
+            })
+            .filter(|r| (r.range.start..=r.range.end).all(|n| new_lines.contains_key(&n)))
+            .collect();
+        replacements.sort_by_key(|r| (r.range.start, r.range.end));
+        let mut last_end = 0;
+        replacements.retain(|r| {
+            let keep = r.range.start > last_end;
+            if keep {
+                last_end = r.range.end;
+            }
+            keep
+        });
+        if replacements.is_empty() {
+            continue;
+        }
+        result.applied += replacements.len();
+        write_suggestions(&mut result.patch, path, &new_lines, &replacements);
+    }
+    result.skipped = total - result.applied;
+    result
+}
+
+/// Write the suggestions patch to `path`, creating parent directories as
+/// needed. Nothing is written when there is no suggestion to apply.
+pub fn export_suggestions_patch_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    diff_files: &[DiffFile],
+) -> Result<SuggestionsPatch> {
+    let suggestions = generate_suggestions_patch(session, diff_files);
+    if suggestions.applied > 0 {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, &suggestions.patch)?;
+    }
+    Ok(suggestions)
+}
+
+/// One file's hunks. Suggestions whose context would touch share a hunk, and
+/// later hunks' new-side starts account for the lines earlier ones added or
+/// removed.
+fn write_suggestions(
+    patch: &mut String,
+    path: &Path,
+    new_lines: &BTreeMap<u32, &str>,
+    replacements: &[Replacement],
+) {
+    let path = path.display();
+    let _ = writeln!(patch, "--- a/{path}");
+    let _ = writeln!(patch, "+++ b/{path}");
+
+    let joins = |prev: &Replacement, next: &Replacement| {
+        next.range.start - prev.range.end - 1 <= 2 * SUGGESTION_CONTEXT
+            && (prev.range.end..next.range.start).all(|n| new_lines.contains_key(&n))
+    };
+    let mut offset: i64 = 0;
+    let mut group_start = 0;
+    while group_start < replacements.len() {
+        let mut group_end = group_start + 1;
+        while group_end < replacements.len()
+            && joins(&replacements[group_end - 1], &replacements[group_end])
+        {
+            group_end += 1;
+        }
+        let group = &replacements[group_start..group_end];
+        group_start = group_end;
+
+        let mut first = group[0].range.start;
+        while first > 1 && group[0].range.start - first < SUGGESTION_CONTEXT {
+            if !new_lines.contains_key(&(first - 1)) {
+                break;
+            }
+            first -= 1;
+        }
+        let mut last = group[group.len() - 1].range.end;
+        while last - group[group.len() - 1].range.end < SUGGESTION_CONTEXT
+            && new_lines.contains_key(&(last + 1))
+        {
+            last += 1;
+        }
+
+        let mut body = String::new();
+        let mut new_count: i64 = 0;
+        let mut line = first;
+        for replacement in group {
+            while line < replacement.range.start {
+                let _ = writeln!(body, " {}", new_lines[&line]);
+                new_count += 1;
+                line += 1;
+            }
+            for n in replacement.range.start..=replacement.range.end {
+                let _ = writeln!(body, "-{}", new_lines[&n]);
+            }
+            for code in &replacement.lines {
+                let _ = writeln!(body, "+{code}");
+                new_count += 1;
+            }
+            line = replacement.range.end + 1;
+        }
+        while line <= last {
+            let _ = writeln!(body, " {}", new_lines[&line]);
+            new_count += 1;
+            line += 1;
+        }
+
+        let old_count = i64::from(last - first + 1);
+        let _ = writeln!(
+            patch,
+            "@@ -{first},{old_count} +{},{new_count} @@",
+            i64::from(first) + offset
+        );
+        patch.push_str(&body);
+        offset += new_count - old_count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        Comment, CommentType, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin,
+        SessionDiffSource,
+    };
+    use std::path::PathBuf;
+
+    fn edit(path: &str, line: u32, original: &str, replacement: &str) -> ReviewEdit {
+        ReviewEdit {
+            path: PathBuf::from(path),
+            line,
+            original: original.to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_group_hunks_by_file_in_sorted_order() {
+        // given
+        let edits = vec![
+            edit("src/b.rs", 7, "let x = teh;", "let x = the;"),
+            edit("src/a.rs", 9, "// recieve", "// receive"),
+            edit("src/a.rs", 2, "// seperate", "// separate"),
+        ];
+
+        // when
+        let patch = generate_review_fixes_patch(&edits);
+
+        // then
+        assert_eq!(
+            patch,
+            "--- a/src/a.rs\n+++ b/src/a.rs\n\
+             @@ -2,1 +2,1 @@\n-// seperate\n+// separate\n\
+             @@ -9,1 +9,1 @@\n-// recieve\n+// receive\n\
+             --- a/src/b.rs\n+++ b/src/b.rs\n\
+             @@ -7,1 +7,1 @@\n-let x = teh;\n+let x = the;\n"
+        );
+    }
+
+    #[test]
+    fn should_return_empty_patch_without_edits() {
+        assert!(generate_review_fixes_patch(&[]).is_empty());
+    }
+
+    fn context_hunk(lines: std::ops::RangeInclusive<u32>) -> DiffHunk {
+        let start = *lines.start();
+        let count = lines.end() - start + 1;
+        DiffHunk {
+            header: format!("@@ -{start},{count} +{start},{count} @@"),
+            lines: lines
+                .map(|n| DiffLine {
+                    origin: LineOrigin::Context,
+                    content: format!("line {n}"),
+                    old_lineno: Some(n),
+                    new_lineno: Some(n),
+                    highlighted_spans: None,
+                })
+                .collect(),
+            old_start: start,
+            old_count: count,
+            new_start: start,
+            new_count: count,
+        }
+    }
+
+    fn suggestions_session() -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            "abc1234def".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        let path = PathBuf::from("src/lib.rs");
+        session.add_file(path.clone(), FileStatus::Modified, 0);
+        let review = session.get_file_mut(&path).unwrap();
+        let suggestion = |content: &str| {
+            Comment::new(
+                content.to_string(),
+                CommentType::Suggestion,
+                Some(LineSide::New),
+            )
+        };
+        review.add_line_comment(2, suggestion("```suggestion\nline two\nline 2b\n```"));
+        let mut ranged = suggestion("Merge these\n```suggestion\nlines 5-6\n```");
+        ranged.line_range = Some(LineRange::new(5, 6));
+        review.add_line_comment(6, ranged);
+        review.add_line_comment(25, suggestion("```suggestion\n```"));
+        review.add_line_comment(6, suggestion("```suggestion\noverlap\n```"));
+        review.add_line_comment(15, suggestion("```suggestion\nnot in diff\n```"));
+        review.add_line_comment(
+            3,
+            Comment::new(
+                "```suggestion\nold side\n```".to_string(),
+                CommentType::Suggestion,
+                Some(LineSide::Old),
+            ),
+        );
+        review.add_line_comment(
+            4,
+            Comment::new("Just a note".to_string(), CommentType::Note, None),
+        );
+        session
+    }
+
+    #[test]
+    fn should_generate_applyable_patch_from_suggestion_blocks() {
+        // given
+        let hunks = vec![context_hunk(1..=10), context_hunk(20..=30)];
+        let file = DiffFile {
+            old_path: Some(PathBuf::from("src/lib.rs")),
+            new_path: Some(PathBuf::from("src/lib.rs")),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        };
+
+        // when
+        let suggestions = generate_suggestions_patch(&suggestions_session(), &[file]);
+
+        // then
+        assert_eq!(suggestions.applied, 3);
+        assert_eq!(suggestions.skipped, 3);
+        assert_eq!(
+            suggestions.patch,
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+             @@ -1,9 +1,9 @@\n\
+             \x20line 1\n-line 2\n+line two\n+line 2b\n\
+             \x20line 3\n\x20line 4\n-line 5\n-line 6\n+lines 5-6\n\
+             \x20line 7\n\x20line 8\n\x20line 9\n\
+             @@ -22,7 +22,6 @@\n\
+             \x20line 22\n\x20line 23\n\x20line 24\n-line 25\n\
+             \x20line 26\n\x20line 27\n\x20line 28\n"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Reviewdog Diagnostic Format export, written with `:export <path>.rdjson`
+//! (one `DiagnosticResult`) or `:export <path>.rdjsonl` (one `Diagnostic`
+//! per line), for `reviewdog -f=rdjson` / `-f=rdjsonl` pipelines.
+//!
+//! Every file-anchored comment becomes a diagnostic whose severity follows
+//! its type: ISSUE is `ERROR`, SUGGESTION `WARNING`, anything else `INFO`.
+//! Comments on removed lines point at the file without a range, since
+//! reviewdog only maps new-side lines; review-level comments are left out.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::app::CommentTypeDefinition;
+use crate::error::{Result, TuicrError};
+use crate::model::{Comment, CommentType, LineSide, ReviewSession};
+use crate::output::markdown::export_comment_type_label;
+
+#[derive(Debug, Serialize)]
+struct DiagnosticResult {
+    source: Source,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Source {
+    name: &'static str,
+    url: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Diagnostic {
+    message: String,
+    location: Location,
+    severity: &'static str,
+    /// Only set in rdjsonl, where there is no enclosing result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<Source>,
+    code: Code,
+}
+
+#[derive(Debug, Serialize)]
+struct Location {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<Range>,
+}
+
+#[derive(Debug, Serialize)]
+struct Range {
+    start: Position,
+    end: Position,
+}
+
+#[derive(Debug, Serialize)]
+struct Position {
+    line: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct Code {
+    value: String,
+}
+
+const SOURCE: Source = Source {
+    name: "tuicr",
+    url: env!("CARGO_PKG_REPOSITORY"),
+};
+
+fn severity(comment_type: &CommentType) -> &'static str {
+    match comment_type {
+        CommentType::Issue => "ERROR",
+        CommentType::Suggestion => "WARNING",
+        _ => "INFO",
+    }
+}
+
+fn diagnostic(
+    path: &Path,
+    line: Option<u32>,
+    comment: &Comment,
+    comment_types: &[CommentTypeDefinition],
+) -> Diagnostic {
+    let old_side = comment.side == Some(LineSide::Old);
+    let range = line.filter(|_| !old_side).map(|line| {
+        let range = comment.line_range;
+        Range {
+            start: Position {
+                line: range.map_or(line, |r| r.start),
+            },
+            end: Position {
+                line: range.map_or(line, |r| r.end),
+            },
+        }
+    });
+    let mut message = format!(
+        "[{}] {}",
+        export_comment_type_label(&comment.comment_type, comment_types),
+        comment.content
+    );
+    if let Some(line) = line.filter(|_| old_side) {
+        message.push_str(&format!(" (on removed line {line})"));
+    }
+    Diagnostic {
+        message,
+        location: Location {
+            // reviewdog matches paths against the diff with forward slashes.
+            path: path.to_string_lossy().replace('\\', "/"),
+            range,
+        },
+        severity: severity(&comment.comment_type),
+        source: None,
+        code: Code {
+            value: comment.comment_type.id().to_string(),
+        },
+    }
+}
+
+/// Diagnostics for every file-anchored comment, ordered by path then line.
+fn diagnostics(
+    session: &ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+) -> Result<Vec<Diagnostic>> {
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+    let mut diagnostics = Vec::new();
+    for (path, review) in files {
+        diagnostics.extend(
+            review
+                .file_comments
+                .iter()
+                .map(|c| diagnostic(path, None, c, comment_types)),
+        );
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, comments) in line_comments {
+            diagnostics.extend(
+                comments
+                    .iter()
+                    .map(|c| diagnostic(path, Some(*line), c, comment_types)),
+            );
+        }
+    }
+    if diagnostics.is_empty() {
+        return Err(TuicrError::NoComments);
+    }
+    Ok(diagnostics)
+}
+
+/// Build the rdjson `DiagnosticResult`. Errors with `NoComments` when no
+/// comment is anchored to a file.
+pub fn generate_rdjson(
+    session: &ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+) -> Result<String> {
+    let result = DiagnosticResult {
+        source: SOURCE,
+        diagnostics: diagnostics(session, comment_types)?,
+    };
+    Ok(serde_json::to_string_pretty(&result)? + "\n")
+}
+
+/// Build rdjsonl: one compact `Diagnostic` per line, each naming its source.
+pub fn generate_rdjsonl(
+    session: &ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+) -> Result<String> {
+    let mut out = String::new();
+    for mut diagnostic in diagnostics(session, comment_types)? {
+        diagnostic.source = Some(SOURCE);
+        out.push_str(&serde_json::to_string(&diagnostic)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Write rdjson to `path`, or rdjsonl when it ends in `.rdjsonl`, creating
+/// parent directories as needed.
+pub fn export_rdjson_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    comment_types: &[CommentTypeDefinition],
+) -> Result<()> {
+    let content = if path.extension().is_some_and(|ext| ext == "rdjsonl") {
+        generate_rdjsonl(session, comment_types)?
+    } else {
+        generate_rdjson(session, comment_types)?
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::LineRange;
+    use crate::output::tests::create_test_session;
+    use std::path::PathBuf;
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.line_comments.get_mut(&42).unwrap()[0].line_range = Some(LineRange::new(40, 42));
+        review.add_line_comment(
+            2,
+            Comment::new(
+                "Keep the bounds check".to_string(),
+                CommentType::Note,
+                Some(LineSide::Old),
+            ),
+        );
+        session
+    }
+
+    #[test]
+    fn should_export_comments_as_rdjson_diagnostics() {
+        // when
+        let rdjson = generate_rdjson(&session(), &[]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&rdjson).unwrap();
+
+        // then
+        assert_eq!(value["source"]["name"], "tuicr");
+        let diagnostics = value["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0]["severity"], "WARNING");
+        assert!(diagnostics[0]["location"].get("range").is_none());
+        assert_eq!(diagnostics[1]["severity"], "INFO");
+        assert_eq!(
+            diagnostics[1]["message"],
+            "[NOTE] Keep the bounds check (on removed line 2)"
+        );
+        let issue = &diagnostics[2];
+        assert_eq!(issue["severity"], "ERROR");
+        assert_eq!(issue["code"]["value"], "issue");
+        assert_eq!(issue["location"]["path"], "src/main.rs");
+        assert_eq!(issue["location"]["range"]["start"]["line"], 40);
+        assert_eq!(issue["location"]["range"]["end"]["line"], 42);
+        assert!(issue.get("source").is_none());
+    }
+
+    #[test]
+    fn should_export_one_sourced_diagnostic_per_rdjsonl_line() {
+        // when
+        let rdjsonl = generate_rdjsonl(&session(), &[]).unwrap();
+
+        // then
+        let lines: Vec<serde_json::Value> = rdjsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|d| d["source"]["name"] == "tuicr"));
+        assert_eq!(lines[2]["severity"], "ERROR");
+    }
+}
@@ -0,0 +1,458 @@
+//! Standalone Markdown report of a review, written to a file with
+//! `:export <path>`. Unlike the clipboard export, which is a flat list
+//! addressed to a coding agent, the report is meant to be read by people:
+//! a summary, then one section per file with each comment under the code it
+//! was left on.
+//!
+//! The `Notes` flavor (`:export <path>.notes.md`) is the same report for a
+//! note-taking app such as Obsidian or Notion: YAML frontmatter with the
+//! repository, branch, date, verdict and tags, and files as `[[path]]`
+//! wiki links.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::app::{CommentTypeDefinition, DiffSource};
+use crate::error::{Result, TuicrError};
+use crate::model::review::format_duration;
+use crate::model::{Comment, DiffFile, LineOrigin, LineRange, LineSide, ReviewSession};
+use crate::output::json::verdict;
+use crate::output::markdown::{
+    attribution, export_comment_type_label, fence_for, markdown_comment_label, review_scope,
+};
+
+/// Where the report is meant to be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFlavor {
+    Plain,
+    /// A note-taking app: frontmatter and wiki links
+    Notes,
+}
+
+impl ReportFlavor {
+    /// The flavor an `:export` path asks for
+    pub fn for_path(path: &Path) -> Self {
+        let is_notes = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".notes.md"));
+        if is_notes { Self::Notes } else { Self::Plain }
+    }
+}
+
+/// Build the report. Errors with `NoComments` when there is nothing to say.
+pub fn generate_report(
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+    conventional: bool,
+    flavor: ReportFlavor,
+) -> Result<String> {
+    if !session.has_comments() {
+        return Err(TuicrError::NoComments);
+    }
+    let type_label =
+        |comment: &Comment| export_comment_type_label(&comment.comment_type, comment_types);
+    let label = |comment: &Comment| markdown_comment_label(comment, comment_types, conventional);
+
+    let mut md = String::new();
+    if flavor == ReportFlavor::Notes {
+        write_frontmatter(&mut md, session, diff_source);
+    }
+    let _ = writeln!(md, "# Review of {}", review_scope(diff_source));
+    let _ = writeln!(md);
+
+    let mut by_type: Vec<(String, usize)> = Vec::new();
+    let all_comments = session
+        .review_comments
+        .iter()
+        .chain(session.files.values().flat_map(|f| {
+            f.file_comments
+                .iter()
+                .chain(f.line_comments.values().flatten())
+        }));
+    for comment in all_comments {
+        let label = type_label(comment);
+        match by_type.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, count)) => *count += 1,
+            None => by_type.push((label, 1)),
+        }
+    }
+    let total: usize = by_type.iter().map(|(_, count)| count).sum();
+    let breakdown = by_type
+        .iter()
+        .map(|(label, count)| format!("{count} {label}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let _ = writeln!(
+        md,
+        "- Files: {} of {} reviewed",
+        session.reviewed_count(),
+        session.files.len()
+    );
+    let _ = writeln!(md, "- Comments: {total} ({breakdown})");
+    if session.active_ms >= 1000 {
+        let _ = writeln!(
+            md,
+            "- Time spent: {}",
+            format_duration(session.active_ms / 1000)
+        );
+    }
+    if let Some(reviewer) = &session.reviewer {
+        let _ = writeln!(md, "- Reviewer: {reviewer}");
+    }
+    if let Some(spot_check) = &session.spot_check {
+        let _ = writeln!(md, "- Spot-checked: {}", spot_check.coverage());
+    }
+    let _ = writeln!(md);
+
+    if let Some(notes) = &session.session_notes {
+        let _ = writeln!(md, "## Summary");
+        let _ = writeln!(md);
+        let _ = writeln!(md, "{notes}");
+        let _ = writeln!(md);
+    }
+
+    if !session.checklist.is_empty() {
+        let _ = writeln!(md, "## Checklist");
+        let _ = writeln!(md);
+        for item in &session.checklist {
+            let mark = if item.done { 'x' } else { ' ' };
+            let _ = writeln!(md, "- [{mark}] {}", item.text);
+        }
+        let _ = writeln!(md);
+    }
+
+    if !session.review_comments.is_empty() {
+        let _ = writeln!(md, "## General comments");
+        let _ = writeln!(md);
+        for comment in &session.review_comments {
+            let _ = writeln!(
+                md,
+                "- {} {}{}",
+                label(comment),
+                comment.content,
+                attribution(comment, session)
+            );
+        }
+        let _ = writeln!(md);
+    }
+
+    let mut files: Vec<_> = session
+        .files
+        .iter()
+        .filter(|(_, review)| review.comment_count() > 0)
+        .collect();
+    files.sort_by_key(|(path, _)| *path);
+    for (path, review) in files {
+        let reviewed = review
+            .review_confidence()
+            .map(|c| format!(" ({})", c.label()))
+            .unwrap_or_default();
+        match flavor {
+            ReportFlavor::Plain => {
+                let _ = writeln!(md, "## `{}`{reviewed}", path.display());
+            }
+            ReportFlavor::Notes => {
+                let _ = writeln!(md, "## [[{}]]{reviewed}", path.display());
+            }
+        }
+        let _ = writeln!(md);
+        for comment in &review.file_comments {
+            let _ = writeln!(
+                md,
+                "- {} {}{}",
+                label(comment),
+                comment.content,
+                attribution(comment, session)
+            );
+        }
+        if !review.file_comments.is_empty() {
+            let _ = writeln!(md);
+        }
+
+        let diff_file = diff_files.iter().find(|f| f.display_path() == path);
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, comments) in line_comments {
+            for comment in comments {
+                let range = comment.line_range.unwrap_or(LineRange::single(*line));
+                let side = comment.side.unwrap_or(LineSide::New);
+                let _ = writeln!(md, "### {}", location_heading(range, side));
+                let _ = writeln!(md);
+                if let Some(excerpt) = diff_file.and_then(|f| code_excerpt(f, range, side)) {
+                    let fence = fence_for(&excerpt);
+                    let _ = writeln!(md, "{fence}diff\n{excerpt}{fence}");
+                    let _ = writeln!(md);
+                }
+                let _ = writeln!(
+                    md,
+                    "{} {}{}",
+                    label(comment),
+                    comment.content,
+                    attribution(comment, session)
+                );
+                let _ = writeln!(md);
+            }
+        }
+    }
+
+    Ok(md.trim_end().to_string() + "\n")
+}
+
+/// Write the report to `path`, creating parent directories as needed.
+pub fn export_report_to_file(
+    path: &Path,
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    diff_files: &[DiffFile],
+    comment_types: &[CommentTypeDefinition],
+    conventional: bool,
+) -> Result<()> {
+    let content = generate_report(
+        session,
+        diff_source,
+        diff_files,
+        comment_types,
+        conventional,
+        ReportFlavor::for_path(path),
+    )?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// YAML frontmatter for the `Notes` flavor. Strings are written as JSON
+/// strings, which YAML reads as double-quoted scalars.
+fn write_frontmatter(md: &mut String, session: &ReviewSession, diff_source: &DiffSource) {
+    let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    let repo = session
+        .repo_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| session.repo_path.display().to_string());
+    let _ = writeln!(md, "---");
+    let _ = writeln!(md, "repo: {}", quote(&repo));
+    if let Some(branch) = &session.branch_name {
+        let _ = writeln!(md, "branch: {}", quote(branch));
+    }
+    if let Some(reviewer) = &session.reviewer {
+        let _ = writeln!(md, "reviewer: {}", quote(&reviewer.to_string()));
+    }
+    let _ = writeln!(md, "date: {}", session.updated_at.format("%Y-%m-%d"));
+    let _ = writeln!(md, "scope: {}", quote(&review_scope(diff_source)));
+    let _ = writeln!(md, "verdict: {}", verdict(session));
+    let _ = writeln!(md, "tags:");
+    let _ = writeln!(md, "  - code-review");
+    // Tags can't contain spaces
+    let repo_tag: String = repo
+        .chars()
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .collect();
+    let _ = writeln!(md, "  - {}", quote(&repo_tag));
+    let _ = writeln!(md, "---");
+    let _ = writeln!(md);
+}
+
+fn location_heading(range: LineRange, side: LineSide) -> String {
+    let old = if side == LineSide::Old { "~" } else { "" };
+    if range.is_single() {
+        format!("Line {old}{}", range.start)
+    } else {
+        format!("Lines {old}{}-{old}{}", range.start, range.end)
+    }
+}
+
+/// The diff lines of `range` on `side`, one per line with its +/-/space prefix
+fn code_excerpt(file: &DiffFile, range: LineRange, side: LineSide) -> Option<String> {
+    let mut excerpt = String::new();
+    for line in file.hunks.iter().flat_map(|hunk| &hunk.lines) {
+        let lineno = match side {
+            LineSide::New => line.new_lineno,
+            LineSide::Old => line.old_lineno,
+        };
+        if !lineno.is_some_and(|ln| range.start <= ln && ln <= range.end) {
+            continue;
+        }
+        let prefix = match line.origin {
+            LineOrigin::Addition => '+',
+            LineOrigin::Deletion => '-',
+            LineOrigin::Context => ' ',
+        };
+        let _ = writeln!(excerpt, "{prefix}{}", line.content);
+    }
+    (!excerpt.is_empty()).then_some(excerpt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CommentType, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus};
+    use crate::output::tests::create_test_session;
+    use std::path::PathBuf;
+
+    fn diff_file() -> DiffFile {
+        let line = |origin, content: &str, old, new| DiffLine {
+            origin,
+            content: content.to_string(),
+            old_lineno: old,
+            new_lineno: new,
+            highlighted_spans: None,
+        };
+        let hunks = vec![DiffHunk {
+            header: "@@ -41,2 +41,2 @@".to_string(),
+            lines: vec![
+                line(LineOrigin::Context, "fn main() {", Some(41), Some(41)),
+                line(LineOrigin::Deletion, "    let x = 41;", Some(42), None),
+                line(LineOrigin::Addition, "    let x = 42;", None, Some(42)),
+            ],
+            old_start: 41,
+            old_count: 2,
+            new_start: 41,
+            new_count: 2,
+        }];
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from("src/main.rs")),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    fn session() -> ReviewSession {
+        let mut session = create_test_session();
+        session.session_notes = Some("Mostly fine.".to_string());
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 0);
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.add_line_comment(
+            42,
+            Comment::new(
+                "Why was 41 wrong?".to_string(),
+                CommentType::Note,
+                Some(LineSide::Old),
+            ),
+        );
+        session
+    }
+
+    #[test]
+    fn should_generate_report_with_summary_and_code_excerpts() {
+        // when
+        let report = generate_report(
+            &session(),
+            &DiffSource::WorkingTree,
+            &[diff_file()],
+            &[],
+            false,
+            ReportFlavor::Plain,
+        )
+        .unwrap();
+
+        // then
+        assert_eq!(
+            report,
+            "# Review of working tree changes\n\
+             \n\
+             - Files: 1 of 2 reviewed\n\
+             - Comments: 3 (1 SUGGESTION, 1 ISSUE, 1 NOTE)\n\
+             \n\
+             ## Summary\n\
+             \n\
+             Mostly fine.\n\
+             \n\
+             ## `src/main.rs` (reviewed)\n\
+             \n\
+             - **[SUGGESTION]** Consider adding documentation\n\
+             \n\
+             ### Line 42\n\
+             \n\
+             ```diff\n\
+             +    let x = 42;\n\
+             ```\n\
+             \n\
+             **[ISSUE]** Magic number should be a constant\n\
+             \n\
+             ### Line ~42\n\
+             \n\
+             ```diff\n\
+             -    let x = 41;\n\
+             ```\n\
+             \n\
+             **[NOTE]** Why was 41 wrong?\n"
+        );
+    }
+
+    #[test]
+    fn should_fail_report_without_comments_and_write_file_otherwise() {
+        // given
+        let mut empty = create_test_session();
+        empty.files.clear();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reviews").join("review.md");
+
+        // when
+        let empty_result = generate_report(
+            &empty,
+            &DiffSource::WorkingTree,
+            &[],
+            &[],
+            false,
+            ReportFlavor::Plain,
+        );
+        export_report_to_file(&path, &session(), &DiffSource::WorkingTree, &[], &[], false)
+            .unwrap();
+
+        // then
+        assert!(matches!(empty_result, Err(TuicrError::NoComments)));
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("# Review of working tree changes\n"));
+        assert!(written.contains("### Line 42\n\n**[ISSUE]**"));
+    }
+
+    #[test]
+    fn should_add_frontmatter_and_wiki_links_for_notes() {
+        // given
+        let mut session = session();
+        session.updated_at = chrono::DateTime::parse_from_rfc3339("2026-03-04T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        // when
+        let notes = generate_report(
+            &session,
+            &DiffSource::WorkingTree,
+            &[diff_file()],
+            &[],
+            false,
+            ReportFlavor::for_path(Path::new("vault/review.notes.md")),
+        )
+        .unwrap();
+
+        // then
+        assert!(notes.starts_with(
+            "---\n\
+             repo: \"test-repo\"\n\
+             branch: \"main\"\n\
+             date: 2026-03-04\n\
+             scope: \"working tree changes\"\n\
+             verdict: changes_requested\n\
+             tags:\n  - code-review\n  - \"test-repo\"\n\
+             ---\n\n\
+             # Review of working tree changes\n"
+        ));
+        assert!(notes.contains("## [[src/main.rs]] (reviewed)\n"));
+        assert_eq!(
+            ReportFlavor::for_path(Path::new("review.md")),
+            ReportFlavor::Plain
+        );
+    }
+}
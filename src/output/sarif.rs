@@ -0,0 +1,268 @@
+//! SARIF 2.1.0 log of the review's ISSUE comments, written with
+//! `:export <path>.sarif`, for CI systems and GitHub code scanning.
+//!
+//! Only comments with a file location become results. Comments on removed
+//! lines point at the file without a region, since their line numbers refer
+//! to the old version; review-level comments are left out.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{Result, TuicrError};
+use crate::model::{Comment, CommentType, LineSide, ReviewSession};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const ISSUE_RULE_ID: &str = "tuicr/issue";
+
+#[derive(Debug, Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifRun<'a> {
+    tool: SarifTool,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: &'static str,
+    short_description: SarifMessage,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'static str,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+    /// Lets code scanning track a finding across re-uploads
+    partial_fingerprints: SarifFingerprints<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifFingerprints<'a> {
+    #[serde(rename = "tuicrCommentId/v1")]
+    comment_id: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+    uri: String,
+    uri_base_id: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: u32,
+    end_line: u32,
+}
+
+/// SARIF `level` for a comment type, or `None` when the type is not exported
+fn sarif_level(comment_type: &CommentType) -> Option<&'static str> {
+    (*comment_type == CommentType::Issue).then_some("error")
+}
+
+fn sarif_result<'a>(
+    path: &Path,
+    line: Option<u32>,
+    comment: &'a Comment,
+) -> Option<SarifResult<'a>> {
+    let level = sarif_level(&comment.comment_type)?;
+    let old_side = comment.side == Some(LineSide::Old);
+    let region = line.filter(|_| !old_side).map(|line| {
+        let range = comment.line_range;
+        SarifRegion {
+            start_line: range.map_or(line, |r| r.start),
+            end_line: range.map_or(line, |r| r.end),
+        }
+    });
+    let mut text = comment.content.clone();
+    if let Some(line) = line.filter(|_| old_side) {
+        text.push_str(&format!(" (on removed line {line})"));
+    }
+    Some(SarifResult {
+        rule_id: ISSUE_RULE_ID,
+        level,
+        message: SarifMessage { text },
+        locations: vec![SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    // SARIF URIs always use forward slashes.
+                    uri: path.to_string_lossy().replace('\\', "/"),
+                    uri_base_id: "%SRCROOT%",
+                },
+                region,
+            },
+        }],
+        partial_fingerprints: SarifFingerprints {
+            comment_id: &comment.id,
+        },
+    })
+}
+
+/// Build the SARIF log. Errors with `NoComments` when the review has no
+/// ISSUE comment on a file.
+pub fn generate_sarif(session: &ReviewSession) -> Result<String> {
+    let mut files: Vec<_> = session.files.iter().collect();
+    files.sort_by_key(|(path, _)| *path);
+    let mut results = Vec::new();
+    for (path, review) in files {
+        results.extend(
+            review
+                .file_comments
+                .iter()
+                .filter_map(|c| sarif_result(path, None, c)),
+        );
+        let mut line_comments: Vec<_> = review.line_comments.iter().collect();
+        line_comments.sort_by_key(|(line, _)| **line);
+        for (line, comments) in line_comments {
+            results.extend(
+                comments
+                    .iter()
+                    .filter_map(|c| sarif_result(path, Some(*line), c)),
+            );
+        }
+    }
+    if results.is_empty() {
+        return Err(TuicrError::NoComments);
+    }
+
+    let log = SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "tuicr",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules: vec![SarifRule {
+                        id: ISSUE_RULE_ID,
+                        short_description: SarifMessage {
+                            text: "Issue raised in code review".to_string(),
+                        },
+                    }],
+                },
+            },
+            results,
+        }],
+    };
+    Ok(serde_json::to_string_pretty(&log)? + "\n")
+}
+
+/// Write the SARIF log to `path`, creating parent directories as needed.
+pub fn export_sarif_to_file(path: &Path, session: &ReviewSession) -> Result<()> {
+    let content = generate_sarif(session)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::LineRange;
+    use crate::output::tests::create_test_session;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_export_issue_comments_as_sarif_results() {
+        // given
+        let mut session = create_test_session();
+        let review = session.get_file_mut(&PathBuf::from("src/main.rs")).unwrap();
+        review.line_comments.get_mut(&42).unwrap()[0].line_range = Some(LineRange::new(40, 42));
+        review.add_line_comment(
+            2,
+            Comment::new(
+                "Dropped the bounds check".to_string(),
+                CommentType::Issue,
+                Some(LineSide::Old),
+            ),
+        );
+
+        // when
+        let sarif = generate_sarif(&session).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+
+        // then
+        assert_eq!(value["version"], "2.1.0");
+        let run = &value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "tuicr");
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["message"]["text"],
+            "Dropped the bounds check (on removed line 2)"
+        );
+        let old = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(old["artifactLocation"]["uri"], "src/main.rs");
+        assert!(old.get("region").is_none());
+        let region = &results[1]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(
+            (region["startLine"].as_u64(), region["endLine"].as_u64()),
+            (Some(40), Some(42))
+        );
+    }
+
+    #[test]
+    fn should_fail_without_issue_comments() {
+        // given
+        let mut session = create_test_session();
+        for review in session.files.values_mut() {
+            review.line_comments.clear();
+        }
+
+        // when
+        let result = generate_sarif(&session);
+
+        // then
+        assert!(matches!(result, Err(TuicrError::NoComments)));
+    }
+}
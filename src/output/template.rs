@@ -0,0 +1,581 @@
+//! User-supplied export templates, selected with the `export_template` config
+//! key. A template is plain text with a small handlebars-style syntax,
+//! rendered against the JSON export schema (see `json.rs`) plus a `scope`
+//! string describing what was reviewed:
+//!
+//! - `{{path.to.value}}` inserts a value; missing values insert nothing
+//! - `{{#each list}}…{{/each}}` repeats for every item, with `{{this}}`,
+//!   `{{@index}}`, `{{@number}}`, `{{@first}}` and `{{@last}}` in scope
+//! - `{{#if value}}…{{else}}…{{/if}}` and `{{#unless value}}…{{/unless}}`
+//! - `{{! comment }}` is dropped
+//!
+//! Names are looked up in the innermost `each` item first and then outward,
+//! so `{{path}}` inside a file's comments still finds the file's path. A
+//! block tag alone on its line takes the whole line with it, so templates
+//! can be laid out one tag per line without leaving blank lines behind.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::app::{CommentTypeDefinition, DiffSource};
+use crate::error::{Result, TuicrError};
+use crate::model::ReviewSession;
+use crate::output::json::review_value;
+use crate::output::markdown::review_scope;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Each,
+    If,
+    Unless,
+}
+
+impl BlockKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Each => "each",
+            Self::If => "if",
+            Self::Unless => "unless",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Value(String),
+    Block {
+        kind: BlockKind,
+        path: String,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Value(String),
+    Open {
+        kind: BlockKind,
+        path: String,
+        line: usize,
+    },
+    Else {
+        line: usize,
+    },
+    Close {
+        kind: BlockKind,
+        line: usize,
+    },
+    Comment,
+}
+
+impl Token {
+    /// Block tags and comments vanish from the output, so a line holding
+    /// only one of them is removed entirely.
+    fn is_standalone_kind(&self) -> bool {
+        !matches!(self, Self::Text(_) | Self::Value(_))
+    }
+}
+
+/// A parsed export template, ready to render any number of times.
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut tokens = tokenize(source)?;
+        strip_standalone_lines(&mut tokens);
+        let mut tokens = tokens.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens)?;
+        match end {
+            None => Ok(Self { nodes }),
+            Some(Token::Else { line }) => Err(template_error(line, "{{else}} outside a block")),
+            Some(Token::Close { kind, line }) => Err(template_error(
+                line,
+                &format!("{{{{/{}}}}} without a matching opening tag", kind.name()),
+            )),
+            Some(_) => unreachable!("parse_nodes only stops at else, close or the end"),
+        }
+    }
+
+    pub fn render(&self, context: &Value) -> String {
+        let mut out = String::new();
+        let mut scopes = vec![Scope {
+            value: context,
+            position: None,
+        }];
+        render_nodes(&self.nodes, &mut scopes, &mut out);
+        out
+    }
+}
+
+/// Render the review through the template at `template_path`. Errors with
+/// `NoComments` when there is nothing to export, like the built-in formats.
+pub fn render_review_template(
+    template_path: &Path,
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    comment_types: &[CommentTypeDefinition],
+) -> Result<String> {
+    if !session.has_exportable_content() {
+        return Err(TuicrError::NoComments);
+    }
+    let source = std::fs::read_to_string(template_path).map_err(|e| {
+        TuicrError::Template(format!("cannot read {}: {e}", template_path.display()))
+    })?;
+    let template = Template::parse(&source)?;
+    let mut context = review_value(session, comment_types)?;
+    if let Value::Object(fields) = &mut context {
+        fields.insert("scope".to_string(), review_scope(diff_source).into());
+    }
+    Ok(template.render(&context))
+}
+
+/// Write the templated export to `path`, creating parent directories as
+/// needed.
+pub fn export_template_to_file(
+    path: &Path,
+    template_path: &Path,
+    session: &ReviewSession,
+    diff_source: &DiffSource,
+    comment_types: &[CommentTypeDefinition],
+) -> Result<()> {
+    let content = render_review_template(template_path, session, diff_source, comment_types)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+fn template_error(line: usize, message: &str) -> TuicrError {
+    TuicrError::Template(format!("line {line}: {message}"))
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+    while let Some(start) = rest.find("{{") {
+        let (text, tag) = rest.split_at(start);
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+        line += text.matches('\n').count();
+        let Some(end) = tag.find("}}") else {
+            return Err(template_error(line, "unterminated {{"));
+        };
+        let inner = tag[2..end].trim();
+        tokens.push(parse_tag(inner, line)?);
+        line += tag[..end].matches('\n').count();
+        rest = &tag[end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+fn parse_tag(inner: &str, line: usize) -> Result<Token> {
+    if inner.starts_with('!') {
+        return Ok(Token::Comment);
+    }
+    if inner == "else" {
+        return Ok(Token::Else { line });
+    }
+    if let Some(block) = inner.strip_prefix('#') {
+        let (name, path) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+        let kind = block_kind(name, line)?;
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(template_error(
+                line,
+                &format!("{{{{#{name}}}}} needs a value to test"),
+            ));
+        }
+        return Ok(Token::Open {
+            kind,
+            path: path.to_string(),
+            line,
+        });
+    }
+    if let Some(name) = inner.strip_prefix('/') {
+        return Ok(Token::Close {
+            kind: block_kind(name.trim(), line)?,
+            line,
+        });
+    }
+    if inner.is_empty() {
+        return Err(template_error(line, "empty {{}}"));
+    }
+    Ok(Token::Value(inner.to_string()))
+}
+
+fn block_kind(name: &str, line: usize) -> Result<BlockKind> {
+    match name {
+        "each" => Ok(BlockKind::Each),
+        "if" => Ok(BlockKind::If),
+        "unless" => Ok(BlockKind::Unless),
+        _ => Err(template_error(line, &format!("unknown block \"{name}\""))),
+    }
+}
+
+/// Drop the indentation before and the newline after every block tag that
+/// is the only thing on its line.
+fn strip_standalone_lines(tokens: &mut [Token]) {
+    // Decide on the untouched text first: stripping one tag's newline must
+    // not stop the tag on the next line from counting as standalone.
+    let standalone: Vec<bool> = (0..tokens.len())
+        .map(|i| is_standalone(tokens, i))
+        .collect();
+    for (i, _) in standalone.iter().enumerate().filter(|(_, s)| **s) {
+        if let Some(Token::Text(text)) = i.checked_sub(1).map(|j| &mut tokens[j]) {
+            let keep = text.trim_end_matches([' ', '\t']).len();
+            text.truncate(keep);
+        }
+        if let Some(Token::Text(text)) = tokens.get_mut(i + 1) {
+            let cut = text.find('\n').map_or(text.len(), |newline| newline + 1);
+            text.drain(..cut);
+        }
+    }
+}
+
+fn is_standalone(tokens: &[Token], i: usize) -> bool {
+    if !tokens[i].is_standalone_kind() {
+        return false;
+    }
+    let starts_line = match i.checked_sub(1).map(|j| &tokens[j]) {
+        None => true,
+        Some(Token::Text(text)) => {
+            let last_line = text.rsplit('\n').next().unwrap_or_default();
+            last_line.trim_matches([' ', '\t']).is_empty() && (text.contains('\n') || i == 1)
+        }
+        Some(_) => false,
+    };
+    let ends_line = match tokens.get(i + 1) {
+        None => true,
+        Some(Token::Text(text)) => {
+            let first_line = text.split('\n').next().unwrap_or_default();
+            first_line
+                .trim_end_matches('\r')
+                .trim_matches([' ', '\t'])
+                .is_empty()
+                && (text.contains('\n') || i + 2 == tokens.len())
+        }
+        Some(_) => false,
+    };
+    starts_line && ends_line
+}
+
+/// Parse nodes up to the end of input or an `{{else}}`/`{{/…}}` tag, which
+/// is returned for the enclosing block to check.
+fn parse_nodes(tokens: &mut impl Iterator<Item = Token>) -> Result<(Vec<Node>, Option<Token>)> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) if text.is_empty() => {}
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Value(path) => nodes.push(Node::Value(path)),
+            Token::Comment => {}
+            Token::Open { kind, path, line } => {
+                let (body, mut end) = parse_nodes(tokens)?;
+                let mut otherwise = Vec::new();
+                if let Some(Token::Else { .. }) = end {
+                    let (nodes, after) = parse_nodes(tokens)?;
+                    otherwise = nodes;
+                    end = after;
+                }
+                match end {
+                    Some(Token::Close { kind: closed, .. }) if closed == kind => {}
+                    Some(Token::Close {
+                        kind: closed,
+                        line: close_line,
+                    }) => {
+                        return Err(template_error(
+                            close_line,
+                            &format!(
+                                "{{{{/{}}}}} closes {{{{#{}}}}} from line {line}",
+                                closed.name(),
+                                kind.name()
+                            ),
+                        ));
+                    }
+                    Some(Token::Else { line: else_line }) => {
+                        return Err(template_error(else_line, "second {{else}} in a block"));
+                    }
+                    _ => {
+                        return Err(template_error(
+                            line,
+                            &format!("{{{{#{} {path}}}}} is never closed", kind.name()),
+                        ));
+                    }
+                }
+                nodes.push(Node::Block {
+                    kind,
+                    path,
+                    body,
+                    otherwise,
+                });
+            }
+            end @ (Token::Else { .. } | Token::Close { .. }) => return Ok((nodes, Some(end))),
+        }
+    }
+    Ok((nodes, None))
+}
+
+/// One level of the lookup stack: the root context or an `each` item with
+/// its position in the list.
+struct Scope<'a> {
+    value: &'a Value,
+    /// `(index, len)` for `each` items
+    position: Option<(usize, usize)>,
+}
+
+fn render_nodes<'a>(nodes: &'a [Node], scopes: &mut Vec<Scope<'a>>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value(path) => {
+                if let Some(value) = lookup(scopes, path) {
+                    push_value(&value, out);
+                }
+            }
+            Node::Block {
+                kind: BlockKind::Each,
+                path,
+                body,
+                otherwise,
+            } => match lookup_ref(scopes, path).and_then(Value::as_array) {
+                Some(items) if !items.is_empty() => {
+                    for (index, item) in items.iter().enumerate() {
+                        scopes.push(Scope {
+                            value: item,
+                            position: Some((index, items.len())),
+                        });
+                        render_nodes(body, scopes, out);
+                        scopes.pop();
+                    }
+                }
+                _ => render_nodes(otherwise, scopes, out),
+            },
+            Node::Block {
+                kind,
+                path,
+                body,
+                otherwise,
+            } => {
+                let truthy = lookup(scopes, path).is_some_and(|value| is_truthy(&value));
+                if truthy == (*kind == BlockKind::If) {
+                    render_nodes(body, scopes, out);
+                } else {
+                    render_nodes(otherwise, scopes, out);
+                }
+            }
+        }
+    }
+}
+
+/// Resolve `path`, including the `@index`-style position variables, which
+/// are computed rather than stored in the context.
+fn lookup(scopes: &[Scope<'_>], path: &str) -> Option<Value> {
+    if let Some(variable) = path.strip_prefix('@') {
+        let (index, len) = scopes.iter().rev().find_map(|scope| scope.position)?;
+        return match variable {
+            "index" => Some(index.into()),
+            "number" => Some((index + 1).into()),
+            "first" => Some((index == 0).into()),
+            "last" => Some((index + 1 == len).into()),
+            _ => None,
+        };
+    }
+    lookup_ref(scopes, path).cloned()
+}
+
+fn lookup_ref<'a>(scopes: &[Scope<'a>], path: &str) -> Option<&'a Value> {
+    let innermost = scopes.last()?.value;
+    if path == "this" {
+        return Some(innermost);
+    }
+    let (start, tail) = match path.strip_prefix("this.") {
+        Some(tail) => (innermost, tail),
+        None => {
+            let (head, tail) = path.split_once('.').unwrap_or((path, ""));
+            let start = scopes
+                .iter()
+                .rev()
+                .find_map(|scope| scope.value.get(head))?;
+            (start, tail)
+        }
+    };
+    tail.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(start, |value, segment| match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => value.get(segment),
+        })
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+fn push_value(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => {}
+        Value::String(s) => out.push_str(s),
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Comment, CommentType, FileStatus, LineSide, SessionDiffSource};
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn render(source: &str, context: Value) -> String {
+        Template::parse(source).unwrap().render(&context)
+    }
+
+    #[test]
+    fn should_insert_values_by_dotted_path() {
+        // given
+        let context = json!({"spot_check": {"total_hunks": 40}, "verdict": "approved"});
+
+        // when
+        let out = render(
+            "{{verdict}}: {{spot_check.total_hunks}} hunks{{missing}}",
+            context,
+        );
+
+        // then
+        assert_eq!(out, "approved: 40 hunks");
+    }
+
+    #[test]
+    fn should_repeat_each_block_with_outer_names_in_scope() {
+        // given
+        let context = json!({"files": [
+            {"path": "a.rs", "comments": [{"body": "one"}, {"body": "two"}]},
+            {"path": "b.rs", "comments": []},
+        ]});
+        let source = "\
+{{#each files}}
+## {{@number}}. {{path}}
+{{#each comments}}
+- {{path}}: {{body}}{{#unless @last}},{{/unless}}
+{{else}}
+No comments.
+{{/each}}
+{{/each}}
+";
+
+        // when
+        let out = render(source, context);
+
+        // then
+        assert_eq!(
+            out,
+            "## 1. a.rs\n- a.rs: one,\n- a.rs: two\n## 2. b.rs\nNo comments.\n"
+        );
+    }
+
+    #[test]
+    fn should_pick_if_branch_by_truthiness() {
+        // given
+        let source = "{{#if summary}}S: {{summary}}{{else}}no summary{{/if}}";
+
+        // when / then
+        assert_eq!(render(source, json!({"summary": "ok"})), "S: ok");
+        assert_eq!(render(source, json!({"summary": ""})), "no summary");
+        assert_eq!(render(source, json!({"summary": null})), "no summary");
+        assert_eq!(render(source, json!({})), "no summary");
+    }
+
+    #[test]
+    fn should_drop_comments_and_keep_inline_tags_on_their_line() {
+        // given
+        let source = "{{! header }}\nA {{#if x}}yes{{/if}} B\n";
+
+        // when
+        let out = render(source, json!({"x": true}));
+
+        // then
+        assert_eq!(out, "A yes B\n");
+    }
+
+    #[test]
+    fn should_reject_mismatched_blocks_with_line_numbers() {
+        // when
+        let unclosed = Template::parse("a\n{{#each files}}\n").unwrap_err();
+        let mismatched = Template::parse("{{#if x}}\n{{/each}}").unwrap_err();
+        let unknown = Template::parse("{{#with x}}{{/with}}").unwrap_err();
+
+        // then
+        assert_eq!(
+            unclosed.to_string(),
+            "Template error: line 2: {{#each files}} is never closed"
+        );
+        assert_eq!(
+            mismatched.to_string(),
+            "Template error: line 2: {{/each}} closes {{#if}} from line 1"
+        );
+        assert_eq!(
+            unknown.to_string(),
+            "Template error: line 1: unknown block \"with\""
+        );
+    }
+
+    #[test]
+    fn should_render_review_through_template_file() {
+        // given
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/test-repo"),
+            "abc1234def".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 0);
+        session
+            .get_file_mut(&PathBuf::from("src/lib.rs"))
+            .unwrap()
+            .add_line_comment(
+                7,
+                Comment::new(
+                    "Handle the error".to_string(),
+                    CommentType::Issue,
+                    Some(LineSide::New),
+                ),
+            );
+        let dir = tempfile::tempdir().unwrap();
+        let template_path = dir.path().join("review.md.tmpl");
+        std::fs::write(
+            &template_path,
+            "Review of {{scope}} ({{verdict}})\n{{#each files}}\n{{#each comments}}\n{{path}}:{{line}} [{{label}}] {{body}}\n{{/each}}\n{{/each}}\n",
+        )
+        .unwrap();
+
+        // when
+        let out = render_review_template(&template_path, &session, &DiffSource::WorkingTree, &[])
+            .unwrap();
+
+        // then
+        assert_eq!(
+            out,
+            "Review of working tree changes (changes_requested)\nsrc/lib.rs:7 [ISSUE] Handle the error\n"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Archive of completed reviews for `tuicr history`. Once every file of a
+//! session is reviewed, saving it also copies it here, where it outlives
+//! the week that ordinary session files are kept for.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+use crate::error::{Result, TuicrError};
+use crate::model::ReviewSession;
+use crate::persistence::storage::{load_session, normalize_repo_path, write_session_file};
+
+/// `archive/` in tuicr's data directory.
+pub fn archive_dir() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
+        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+    })?;
+    Ok(proj_dirs.data_dir().join("archive"))
+}
+
+/// Copy `session` into the archive at `dir`, replacing an earlier copy of
+/// the same session.
+pub fn archive_session(dir: &Path, session: &ReviewSession) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.json", session.id));
+    write_session_file(&path, session)?;
+    Ok(path)
+}
+
+/// Archived reviews of `repo_path`, most recently completed first.
+/// Unreadable files are skipped.
+pub fn list_archived(dir: &Path, repo_path: &Path) -> Result<Vec<ReviewSession>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let repo = normalize_repo_path(repo_path);
+    let mut sessions: Vec<ReviewSession> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| load_session(&path).ok())
+        .filter(|session| normalize_repo_path(&session.repo_path) == repo)
+        .collect();
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+    Ok(sessions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::review::SessionDiffSource;
+
+    #[test]
+    fn should_list_archived_reviews_of_one_repository_newest_first() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("archive");
+        let session = |repo: &str, days_ago: i64| {
+            let mut session = ReviewSession::new(
+                dir.path().join(repo),
+                "abc1234".to_string(),
+                Some("main".to_string()),
+                SessionDiffSource::WorkingTree,
+            );
+            session.updated_at = chrono::Utc::now() - chrono::Duration::days(days_ago);
+            session
+        };
+        let older = session("repo", 3);
+        let newer = session("repo", 1);
+        archive_session(&archive, &older).unwrap();
+        archive_session(&archive, &newer).unwrap();
+        archive_session(&archive, &session("other", 0)).unwrap();
+        archive_session(&archive, &newer).unwrap();
+
+        // when
+        let listed = list_archived(&archive, &dir.path().join("repo")).unwrap();
+
+        // then
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, [newer.id.as_str(), older.id.as_str()]);
+    }
+}
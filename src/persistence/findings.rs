@@ -0,0 +1,232 @@
+//! Local knowledge base of ISSUE comments from exported reviews, so files that
+//! keep attracting findings can be flagged the next time a diff touches them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TuicrError};
+use crate::model::{CommentType, DiffFile, LineSide, ReviewSession};
+use crate::persistence::storage::normalize_repo_path;
+
+/// One ISSUE comment recorded when a review was exported
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Finding {
+    /// Comment id, so exporting the same review twice records it once
+    pub id: String,
+    /// Normalized repository root
+    pub repo: String,
+    pub file: PathBuf,
+    /// The code line the issue was left on, trimmed; empty for file comments
+    pub pattern: String,
+    pub text: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FindingsDb {
+    pub findings: Vec<Finding>,
+}
+
+/// `findings.json` next to the saved reviews in the data directory.
+pub fn findings_db_path() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
+        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+    })?;
+    Ok(proj_dirs.data_dir().join("findings.json"))
+}
+
+impl FindingsDb {
+    /// Read the database at `path`; a missing file is an empty database.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| TuicrError::CorruptedSession(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record the ISSUE comments of `session` that are not in the database
+    /// yet. `diff_files` supplies the code line each one was left on.
+    /// Returns the number of findings added.
+    pub fn record_session(&mut self, session: &ReviewSession, diff_files: &[DiffFile]) -> usize {
+        let repo = normalize_repo_path(&session.repo_path);
+        let mut added = 0;
+        let mut files: Vec<_> = session.files.iter().collect();
+        files.sort_by_key(|(path, _)| *path);
+        for (path, review) in files {
+            let diff_file = diff_files.iter().find(|f| f.display_path() == path);
+            let mut line_comments: Vec<_> = review
+                .line_comments
+                .iter()
+                .flat_map(|(line, comments)| comments.iter().map(move |c| (Some(*line), c)))
+                .collect();
+            line_comments.sort_by_key(|(line, _)| *line);
+            let comments = review
+                .file_comments
+                .iter()
+                .map(|c| (None, c))
+                .chain(line_comments);
+            for (line, comment) in comments {
+                if comment.comment_type != CommentType::Issue
+                    || self.findings.iter().any(|f| f.id == comment.id)
+                {
+                    continue;
+                }
+                let pattern = line
+                    .zip(diff_file)
+                    .and_then(|(line, file)| {
+                        code_line(file, line, comment.side.unwrap_or(LineSide::New))
+                    })
+                    .unwrap_or_default();
+                self.findings.push(Finding {
+                    id: comment.id.clone(),
+                    repo: repo.clone(),
+                    file: path.clone(),
+                    pattern,
+                    text: comment.content.clone(),
+                    recorded_at: Utc::now(),
+                });
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Number of recorded findings per file of the repository at `repo_path`
+    pub fn counts_for_repo(&self, repo_path: &Path) -> HashMap<PathBuf, usize> {
+        let repo = normalize_repo_path(repo_path);
+        let mut counts = HashMap::new();
+        for finding in self.findings.iter().filter(|f| f.repo == repo) {
+            *counts.entry(finding.file.clone()).or_default() += 1;
+        }
+        counts
+    }
+}
+
+fn code_line(file: &DiffFile, line: u32, side: LineSide) -> Option<String> {
+    file.hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .find(|l| match side {
+            LineSide::New => l.new_lineno == Some(line),
+            LineSide::Old => l.old_lineno == Some(line),
+        })
+        .map(|l| l.content.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        Comment, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin,
+        SessionDiffSource,
+    };
+
+    fn diff_file(path: &str) -> DiffFile {
+        let hunks = vec![DiffHunk {
+            header: "@@ -1,1 +1,1 @@".to_string(),
+            lines: vec![DiffLine {
+                origin: LineOrigin::Addition,
+                content: "    let x = v[i];".to_string(),
+                old_lineno: None,
+                new_lineno: Some(1),
+                highlighted_spans: None,
+            }],
+            old_start: 1,
+            old_count: 0,
+            new_start: 1,
+            new_count: 1,
+        }];
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Modified,
+            content_hash: DiffFile::compute_content_hash(&hunks),
+            hunks,
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    fn session_with_comments(repo: &Path) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            repo.to_path_buf(),
+            "abc123".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        let path = PathBuf::from("src/lib.rs");
+        session.add_file(path.clone(), FileStatus::Modified, 0);
+        let review = session.get_file_mut(&path).unwrap();
+        review.add_line_comment(
+            1,
+            Comment::new(
+                "index may be out of bounds".to_string(),
+                CommentType::Issue,
+                None,
+            ),
+        );
+        review.add_line_comment(
+            1,
+            Comment::new("nice".to_string(), CommentType::Praise, None),
+        );
+        session
+    }
+
+    #[test]
+    fn should_record_issue_comments_once_with_their_code_line() {
+        // given
+        let repo = tempfile::tempdir().unwrap();
+        let session = session_with_comments(repo.path());
+        let files = vec![diff_file("src/lib.rs")];
+        let mut db = FindingsDb::default();
+
+        // when
+        let first = db.record_session(&session, &files);
+        let second = db.record_session(&session, &files);
+
+        // then
+        assert_eq!((first, second), (1, 0));
+        assert_eq!(db.findings[0].pattern, "let x = v[i];");
+        assert_eq!(db.findings[0].text, "index may be out of bounds");
+        let counts = db.counts_for_repo(repo.path());
+        assert_eq!(counts.get(Path::new("src/lib.rs")), Some(&1));
+    }
+
+    #[test]
+    fn should_round_trip_and_treat_missing_file_as_empty() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join("findings.json");
+        let mut db = FindingsDb::load(&path).unwrap();
+        assert!(db.findings.is_empty());
+        db.record_session(
+            &session_with_comments(dir.path()),
+            &[diff_file("src/lib.rs")],
+        );
+
+        // when
+        db.save(&path).unwrap();
+        let loaded = FindingsDb::load(&path).unwrap();
+
+        // then
+        assert_eq!(loaded.findings, db.findings);
+    }
+}
@@ -0,0 +1,139 @@
+//! Git notes storage: a session saved as a note on each reviewed commit, so
+//! the review can be shared with `git push origin <notes ref>` and picked up
+//! by teammates after `git fetch origin <notes ref>:<notes ref>`.
+
+use std::path::Path;
+
+use git2::{ErrorCode, Oid, Repository, Signature};
+
+use crate::error::{Result, TuicrError};
+use crate::model::ReviewSession;
+
+fn resolve_commit(repo: &Repository, commit: &str) -> Result<Oid> {
+    Ok(repo.revparse_single(commit)?.peel_to_commit()?.id())
+}
+
+/// Attach `session` as a note on every commit in its commit range, replacing
+/// any earlier tuicr note. Returns the number of commits annotated; sessions
+/// without a commit range (working tree, staged, ...) have nothing to attach
+/// to and write no notes.
+pub fn write_session_notes(
+    repo_path: &Path,
+    notes_ref: &str,
+    session: &ReviewSession,
+) -> Result<usize> {
+    let Some(commits) = session.commit_range.as_deref() else {
+        return Ok(0);
+    };
+    let repo = Repository::open(repo_path)?;
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("tuicr", "tuicr@localhost"))?;
+    let json = serde_json::to_string_pretty(session)?;
+    for commit in commits {
+        let oid = resolve_commit(&repo, commit)?;
+        repo.note(&signature, &signature, Some(notes_ref), oid, &json, true)?;
+    }
+    Ok(commits.len())
+}
+
+/// Read the session stored in the note on `commit`, if there is one.
+pub fn read_session_note(
+    repo_path: &Path,
+    notes_ref: &str,
+    commit: &str,
+) -> Result<Option<ReviewSession>> {
+    let repo = Repository::open(repo_path)?;
+    let oid = resolve_commit(&repo, commit)?;
+    let note = match repo.find_note(Some(notes_ref), oid) {
+        Ok(note) => note,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let message = note
+        .message()
+        .ok_or_else(|| TuicrError::CorruptedSession("note is not valid UTF-8".to_string()))?;
+    let session =
+        serde_json::from_str(message).map_err(|e| TuicrError::CorruptedSession(e.to_string()))?;
+    Ok(Some(session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Comment, CommentType, SessionDiffSource};
+
+    const NOTES_REF: &str = "refs/notes/tuicr";
+
+    fn commit_file(repo: &Repository, name: &str, content: &str) -> Oid {
+        std::fs::write(repo.workdir().unwrap().join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, name, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn should_round_trip_session_through_notes_on_each_commit() {
+        // given
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("failed to init repo");
+        let first = commit_file(&repo, "a.txt", "a\n").to_string();
+        let second = commit_file(&repo, "b.txt", "b\n").to_string();
+        let mut session = ReviewSession::new(
+            temp_dir.path().to_path_buf(),
+            second.clone(),
+            Some("main".to_string()),
+            SessionDiffSource::CommitRange,
+        );
+        session.commit_range = Some(vec![first.clone(), second.clone()]);
+        session.review_comments.push(Comment::new(
+            "looks good".to_string(),
+            CommentType::Note,
+            None,
+        ));
+
+        // when
+        let written = write_session_notes(temp_dir.path(), NOTES_REF, &session).unwrap();
+
+        // then
+        assert_eq!(written, 2);
+        for commit in [&first, &second] {
+            let loaded = read_session_note(temp_dir.path(), NOTES_REF, commit)
+                .unwrap()
+                .expect("note should exist");
+            assert_eq!(loaded.id, session.id);
+            assert_eq!(loaded.review_comments[0].content, "looks good");
+        }
+    }
+
+    #[test]
+    fn should_skip_sessions_without_commits_and_report_missing_notes() {
+        // given
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("failed to init repo");
+        let head = commit_file(&repo, "a.txt", "a\n").to_string();
+        let session = ReviewSession::new(
+            temp_dir.path().to_path_buf(),
+            head.clone(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+
+        // when
+        let written = write_session_notes(temp_dir.path(), NOTES_REF, &session).unwrap();
+
+        // then
+        assert_eq!(written, 0);
+        assert!(
+            read_session_note(temp_dir.path(), NOTES_REF, &head)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
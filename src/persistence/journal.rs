@@ -0,0 +1,355 @@
+//! Crash recovery journal: an append-only log of the changes made to a
+//! session since it was last saved, so work isn't lost when tuicr or its
+//! terminal dies between saves.
+//!
+//! The first change after a save starts `.<session id>.journal` in the
+//! reviews directory, next to the session's lock, with a snapshot of the
+//! session; each comment written, edited or deleted, each reviewed mark and
+//! each `:clear` then appends one JSON line. Saving writes all of it into
+//! the session and removes the journal, and so does quitting (the user
+//! chose to leave the rest unsaved). A journal still there at startup that
+//! no running tuicr has open was left by one that never got that far: its
+//! session is rebuilt from the snapshot and the changes after it, and
+//! saved. Entries record the state a change left rather than a delta, so
+//! replaying one the snapshot already holds changes nothing.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::model::review::{ClearScope, ReviewConfidence, ReviewEvent, ReviewEventKind};
+use crate::model::{Comment, ReviewSession};
+
+/// One change to the session. Externally tagged: a flattened or internally
+/// tagged enum would buffer the snapshot, and buffered maps can't read the
+/// line-numbered keys of `line_comments` back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalOp {
+    /// The session as the journal was started, first in every journal
+    Snapshot { session: Box<ReviewSession> },
+    /// A comment was written, edited, moved or restored from the trash;
+    /// `None` path for review comments, `None` line for file comments
+    CommentSaved {
+        path: Option<PathBuf>,
+        line: Option<u32>,
+        comment: Box<Comment>,
+    },
+    /// A comment was deleted to the trash
+    CommentDeleted { id: String },
+    /// A file was marked reviewed (with `confidence`) or unreviewed
+    FileReviewed {
+        path: PathBuf,
+        reviewed: bool,
+        confidence: ReviewConfidence,
+    },
+    /// `:clear` (`reviewed: true`) or `:clearc`
+    Cleared { reviewed: bool },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub at: DateTime<Utc>,
+    pub op: JournalOp,
+}
+
+const JOURNAL_SUFFIX: &str = ".journal";
+
+fn journal_path(dir: &Path, session_id: &str) -> PathBuf {
+    dir.join(format!(".{session_id}{JOURNAL_SUFFIX}"))
+}
+
+fn write_entry(path: &Path, op: JournalOp) -> Result<()> {
+    let entry = JournalEntry { at: Utc::now(), op };
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+    // One write per entry, so a crash can at worst cut off the last line
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Whether `session_id` has a journal in `dir`, i.e. changes since its
+/// last save.
+pub fn started(dir: &Path, session_id: &str) -> bool {
+    journal_path(dir, session_id).exists()
+}
+
+/// Record `op`, a change just made to `session`, in its journal in `dir`,
+/// starting the journal with a snapshot of `session` if there is none.
+pub fn append(dir: &Path, session: &ReviewSession, op: JournalOp) -> Result<()> {
+    let path = journal_path(dir, &session.id);
+    if !path.exists() {
+        let session = Box::new(session.clone());
+        write_entry(&path, JournalOp::Snapshot { session })?;
+    }
+    write_entry(&path, op)
+}
+
+/// Entries of the journal at `path`, oldest first. Lines that don't parse
+/// (one cut off by a crash mid-write) are skipped.
+fn read(path: &Path) -> Result<Vec<JournalEntry>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Remove `session_id`'s journal in `dir`, once its changes are saved or
+/// discarded.
+pub fn clear(dir: &Path, session_id: &str) -> Result<()> {
+    match fs::remove_file(journal_path(dir, session_id)) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Sessions rebuilt from the journals in `dir` that no running tuicr has
+/// open, each with the number of changes recovered. Journals without a
+/// snapshot to rebuild from are removed.
+fn rebuild(dir: &Path) -> Vec<(ReviewSession, usize)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut recovered = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(id) = name
+            .strip_prefix('.')
+            .and_then(|name| name.strip_suffix(JOURNAL_SUFFIX))
+        else {
+            continue;
+        };
+        if crate::persistence::lock::held_in(dir, id) {
+            continue;
+        }
+        let Ok(mut journal) = read(&entry.path()) else {
+            continue;
+        };
+        let snapshot = match journal.first().map(|entry| &entry.op) {
+            Some(JournalOp::Snapshot { session }) if session.id == id => (**session).clone(),
+            _ => {
+                let _ = fs::remove_file(entry.path());
+                continue;
+            }
+        };
+        journal.remove(0);
+        let mut session = snapshot;
+        let applied = replay(&mut session, &journal);
+        recovered.push((session, applied));
+    }
+    recovered
+}
+
+/// Rebuild and save the sessions whose journals in `dir` were left by a
+/// tuicr that exited without saving, taking over the locks it left, and
+/// return how many changes were recovered. Saving removes the journals.
+pub fn recover(dir: &Path) -> usize {
+    let mut changes = 0;
+    for (mut session, recovered) in rebuild(dir) {
+        let _ = crate::persistence::lock::take_over(&session.id);
+        session.updated_at = Utc::now();
+        if crate::persistence::storage::save_session(&session).is_ok() {
+            changes += recovered;
+        }
+    }
+    changes
+}
+
+/// Apply `entries` to `session` and return how many changed it. Each change
+/// is logged in the session's events with the time it was first made.
+fn replay(session: &mut ReviewSession, entries: &[JournalEntry]) -> usize {
+    entries.iter().filter(|entry| apply(session, entry)).count()
+}
+
+/// Apply one entry. False when the session already reflects it or no
+/// longer has its file.
+fn apply(session: &mut ReviewSession, entry: &JournalEntry) -> bool {
+    let log = |session: &mut ReviewSession, kind| {
+        session.events.push(ReviewEvent { at: entry.at, kind });
+    };
+    match &entry.op {
+        JournalOp::Snapshot { .. } => false,
+        JournalOp::CommentSaved {
+            path,
+            line,
+            comment,
+        } => {
+            if path
+                .as_ref()
+                .is_some_and(|p| !session.files.contains_key(p))
+            {
+                return false;
+            }
+            let existing = session.find_comment(&comment.id);
+            if existing.is_some_and(|found| found == (path.as_ref(), *line, &**comment)) {
+                return false;
+            }
+            let edited = existing.is_some();
+            session.take_comment(&comment.id);
+            session
+                .trash
+                .retain(|trashed| trashed.comment.id != comment.id);
+            match path.as_ref().and_then(|p| session.files.get_mut(p)) {
+                Some(file) => match line {
+                    Some(line) => file.add_line_comment(*line, (**comment).clone()),
+                    None => file.add_file_comment((**comment).clone()),
+                },
+                None => session.review_comments.push((**comment).clone()),
+            }
+            let (path, line, comment_type) = (path.clone(), *line, comment.comment_type.clone());
+            log(
+                session,
+                if edited {
+                    ReviewEventKind::CommentEdited {
+                        path,
+                        line,
+                        comment_type,
+                    }
+                } else {
+                    ReviewEventKind::CommentAdded {
+                        path,
+                        line,
+                        comment_type,
+                    }
+                },
+            );
+            true
+        }
+        JournalOp::CommentDeleted { id } => {
+            let Some((path, line, comment)) = session.take_comment(id) else {
+                return false;
+            };
+            let comment_type = comment.comment_type.clone();
+            session.trash_comment(comment, path.clone(), line);
+            log(
+                session,
+                ReviewEventKind::CommentDeleted {
+                    path,
+                    line,
+                    comment_type,
+                },
+            );
+            true
+        }
+        JournalOp::FileReviewed {
+            path,
+            reviewed,
+            confidence,
+        } => {
+            let Some(file) = session.files.get_mut(path) else {
+                return false;
+            };
+            if file.reviewed == *reviewed && file.confidence == *confidence {
+                return false;
+            }
+            let was_reviewed = file.reviewed;
+            file.reviewed = *reviewed;
+            file.confidence = *confidence;
+            file.reviewed_hash = None;
+            let path = path.clone();
+            match (was_reviewed, *reviewed) {
+                (false, true) => log(session, ReviewEventKind::FileReviewed { path }),
+                (true, false) => log(session, ReviewEventKind::FileUnreviewed { path }),
+                _ => {}
+            }
+            true
+        }
+        JournalOp::Cleared { reviewed } => {
+            let scope = if *reviewed {
+                ClearScope::CommentsAndReviewed
+            } else {
+                ClearScope::CommentsOnly
+            };
+            session.clear_comments(scope) != (0, 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::review::SessionDiffSource;
+    use crate::model::{CommentType, FileStatus, LineSide};
+
+    #[test]
+    fn should_rebuild_a_session_from_the_journal_a_crash_left() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        let path = PathBuf::from("src/lib.rs");
+        session.add_file(path.clone(), FileStatus::Modified, 1);
+        let kept = Comment::new("keep".to_string(), CommentType::Issue, Some(LineSide::New));
+        let dropped = Comment::new("drop".to_string(), CommentType::Note, None);
+        let mut edited = kept.clone();
+        edited.content = "keep, reworded".to_string();
+        session
+            .get_file_mut(&path)
+            .unwrap()
+            .add_line_comment(4, kept.clone());
+        for op in [
+            JournalOp::CommentSaved {
+                path: Some(path.clone()),
+                line: Some(4),
+                comment: Box::new(kept),
+            },
+            JournalOp::CommentSaved {
+                path: None,
+                line: None,
+                comment: Box::new(dropped.clone()),
+            },
+            JournalOp::FileReviewed {
+                path: path.clone(),
+                reviewed: true,
+                confidence: ReviewConfidence::Thorough,
+            },
+            JournalOp::CommentSaved {
+                path: Some(path.clone()),
+                line: Some(4),
+                comment: Box::new(edited),
+            },
+            JournalOp::CommentDeleted { id: dropped.id },
+        ] {
+            append(dir, &session, op).unwrap();
+        }
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(journal_path(dir, &session.id))
+            .unwrap();
+        file.write_all(br#"{"at":"2026-"#).unwrap();
+
+        // when
+        let mut recovered = rebuild(dir);
+        clear(dir, &session.id).unwrap();
+
+        // then
+        assert_eq!(recovered.len(), 1);
+        let (recovered, changes) = recovered.remove(0);
+        assert_eq!(recovered.id, session.id);
+        assert_eq!(changes, 4);
+        let review = &recovered.files[&path];
+        assert!(review.reviewed);
+        assert_eq!(review.confidence, ReviewConfidence::Thorough);
+        assert_eq!(review.line_comments[&4].len(), 1);
+        assert_eq!(review.line_comments[&4][0].content, "keep, reworded");
+        assert!(recovered.review_comments.is_empty());
+        assert_eq!(recovered.trash.len(), 1);
+        assert_eq!(recovered.events.len(), 4);
+        assert!(!started(dir, &session.id));
+        assert!(rebuild(dir).is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+//! The review source last picked in each repository, offered first by the
+//! startup chooser the next time tuicr opens there without flags.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TuicrError};
+use crate::persistence::storage::normalize_repo_path;
+
+/// What was reviewed, in terms that still make sense on the next launch
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReviewSource {
+    Staged,
+    Unstaged,
+    StagedAndUnstaged,
+    /// Commit ids, oldest first
+    Commits {
+        ids: Vec<String>,
+    },
+    /// Every commit since `base` (`:base`, or `@{u}` for unpushed commits)
+    Base {
+        base: String,
+    },
+    Revset {
+        revset: String,
+    },
+}
+
+impl ReviewSource {
+    /// Short description for the chooser row
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Staged => "staged changes".to_string(),
+            Self::Unstaged => "unstaged changes".to_string(),
+            Self::StagedAndUnstaged => "staged and unstaged changes".to_string(),
+            Self::Commits { ids } => {
+                let short = |id: &String| id.chars().take(7).collect::<String>();
+                match (ids.first(), ids.last()) {
+                    (Some(first), Some(last)) if ids.len() > 1 => {
+                        format!("{} commits {}..{}", ids.len(), short(first), short(last))
+                    }
+                    (Some(only), _) => format!("commit {}", short(only)),
+                    _ => "commits".to_string(),
+                }
+            }
+            Self::Base { base } if base == "@{u}" => "unpushed commits".to_string(),
+            Self::Base { base } => format!("commits since {base}"),
+            Self::Revset { revset } => format!("revset {revset}"),
+        }
+    }
+}
+
+/// Last review source by normalized repository root
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LastSources {
+    repos: HashMap<String, ReviewSource>,
+}
+
+/// `last_sources.json` next to the saved reviews in the data directory.
+pub fn last_sources_path() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
+        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+    })?;
+    Ok(proj_dirs.data_dir().join("last_sources.json"))
+}
+
+fn load(path: &Path) -> Result<LastSources> {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            serde_json::from_str(&contents).map_err(|e| TuicrError::CorruptedSession(e.to_string()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(LastSources::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The source last recorded for the repository at `repo_path`.
+pub fn load_last_source(path: &Path, repo_path: &Path) -> Result<Option<ReviewSource>> {
+    let mut sources = load(path)?;
+    Ok(sources.repos.remove(&normalize_repo_path(repo_path)))
+}
+
+/// Record `source` as the last one reviewed in the repository at `repo_path`.
+pub fn save_last_source(path: &Path, repo_path: &Path, source: &ReviewSource) -> Result<()> {
+    let mut sources = load(path).unwrap_or_default();
+    sources
+        .repos
+        .insert(normalize_repo_path(repo_path), source.clone());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&sources)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_remember_last_source_per_repository() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("last_sources.json");
+        let repo_a = dir.path().join("a");
+        let repo_b = dir.path().join("b");
+        let base = ReviewSource::Base {
+            base: "main".to_string(),
+        };
+
+        // when
+        save_last_source(&path, &repo_a, &ReviewSource::Staged).unwrap();
+        save_last_source(&path, &repo_b, &ReviewSource::Unstaged).unwrap();
+        save_last_source(&path, &repo_a, &base).unwrap();
+
+        // then
+        assert_eq!(load_last_source(&path, &repo_a).unwrap(), Some(base));
+        assert_eq!(
+            load_last_source(&path, &repo_b).unwrap(),
+            Some(ReviewSource::Unstaged)
+        );
+        assert_eq!(
+            load_last_source(&path, &dir.path().join("c")).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn should_describe_sources_for_the_chooser() {
+        let commits = ReviewSource::Commits {
+            ids: vec!["abcdef123".to_string(), "0123456789".to_string()],
+        };
+        assert_eq!(commits.describe(), "2 commits abcdef1..0123456");
+        let unpushed = ReviewSource::Base {
+            base: "@{u}".to_string(),
+        };
+        assert_eq!(unpushed.describe(), "unpushed commits");
+    }
+}
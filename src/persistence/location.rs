@@ -0,0 +1,228 @@
+//! Where session files are kept, from the `session_location` config key:
+//! tuicr's data directory (the default), a `.tuicr/` directory at the root
+//! of the repository, or `tuicr/` inside its git directory.
+//!
+//! Like the session store, the location is picked once at startup, before
+//! the first session is looked up. Sessions of the repository found in the
+//! other locations are then moved over, so switching the key doesn't lose
+//! reviews in progress.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use directories::ProjectDirs;
+
+use crate::error::{Result, TuicrError};
+use crate::persistence::storage::{load_session, normalize_repo_path};
+
+/// Directory sessions are read from and written to, when not the data dir
+static INSTALLED: OnceLock<PathBuf> = OnceLock::new();
+
+/// Ignores everything in a `.tuicr/` directory, so sessions don't show up
+/// as changes to review or get committed by accident
+const REPO_GITIGNORE: &str = "# Review sessions written by tuicr\n*\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionLocation {
+    Data,
+    Repo,
+    GitDir,
+}
+
+impl SessionLocation {
+    pub const ALL: [SessionLocation; 3] = [
+        SessionLocation::Data,
+        SessionLocation::Repo,
+        SessionLocation::GitDir,
+    ];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "data" => Some(SessionLocation::Data),
+            "repo" => Some(SessionLocation::Repo),
+            "git-dir" => Some(SessionLocation::GitDir),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            SessionLocation::Data => "data",
+            SessionLocation::Repo => "repo",
+            SessionLocation::GitDir => "git-dir",
+        }
+    }
+
+    /// The sessions directory this location means for a repository
+    /// containing `cwd`, or `None` when there is no such repository (or,
+    /// for `git-dir`, it isn't a git repository).
+    pub fn dir(self, cwd: &Path) -> Option<PathBuf> {
+        match self {
+            SessionLocation::Data => data_reviews_dir().ok(),
+            SessionLocation::Repo => repo_root(cwd).map(|root| root.join(".tuicr")),
+            SessionLocation::GitDir => git2::Repository::discover(cwd)
+                .ok()
+                .map(|repo| repo.commondir().join("tuicr")),
+        }
+    }
+}
+
+/// `reviews/` in tuicr's data directory.
+pub(crate) fn data_reviews_dir() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
+        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+    })?;
+    Ok(proj_dirs.data_dir().join("reviews"))
+}
+
+/// The nearest directory at or above `cwd` holding a `.jj`, `.git` or
+/// `.hg`, the way the VCS backends find the repository root.
+fn repo_root(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .find(|dir| [".jj", ".git", ".hg"].iter().any(|m| dir.join(m).exists()))
+        .map(Path::to_path_buf)
+}
+
+/// Keep sessions in `dir` from now on. Only the first call has effect.
+pub fn install(dir: PathBuf) {
+    let _ = INSTALLED.set(dir);
+}
+
+/// The sessions directory set by `install`, if any.
+pub(crate) fn installed() -> Option<&'static Path> {
+    INSTALLED.get().map(PathBuf::as_path)
+}
+
+/// Create `dir` for sessions; a `.tuicr/` directory gets a `.gitignore`.
+pub(crate) fn prepare(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let gitignore = dir.join(".gitignore");
+    if dir.file_name().is_some_and(|name| name == ".tuicr") && !gitignore.exists() {
+        fs::write(gitignore, REPO_GITIGNORE)?;
+    }
+    Ok(())
+}
+
+/// Keep sessions where `location` says for the repository containing
+/// `cwd`, moving its sessions there from the other locations. Returns the
+/// directory and how many sessions were moved, or why `location` can't be
+/// used, in which case sessions stay in the data directory.
+pub fn select(location: SessionLocation, cwd: &Path) -> Result<(PathBuf, usize)> {
+    let target = match location {
+        SessionLocation::Data => data_reviews_dir()?,
+        _ => location.dir(cwd).ok_or(TuicrError::NotARepository)?,
+    };
+    install(target.clone());
+    let Some(repo) = repo_root(cwd) else {
+        return Ok((target, 0));
+    };
+    let from: Vec<PathBuf> = SessionLocation::ALL
+        .into_iter()
+        .filter(|other| *other != location)
+        .filter_map(|other| other.dir(cwd))
+        .collect();
+    let moved = migrate(&from, &target, &repo)?;
+    Ok((target, moved))
+}
+
+/// Move the sessions of the repository at `repo_path` from each of `from`
+/// into `to`. A session already in `to` is replaced only by a more
+/// recently updated copy, and sessions another tuicr has open are left
+/// alone. Returns how many were moved.
+pub fn migrate(from: &[PathBuf], to: &Path, repo_path: &Path) -> Result<usize> {
+    let repo = normalize_repo_path(repo_path);
+    let mut moved = 0;
+    for dir in from {
+        if dir == to || !dir.is_dir() {
+            continue;
+        }
+        for path in fs::read_dir(dir)?.flatten().map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Ok(session) = load_session(&path) else {
+                continue;
+            };
+            if normalize_repo_path(&session.repo_path) != repo
+                || crate::persistence::lock::held_in(dir, &session.id)
+            {
+                continue;
+            }
+            prepare(to)?;
+            let target = to.join(path.file_name().unwrap_or_default());
+            let keep_existing = load_session(&target)
+                .is_ok_and(|existing| existing.updated_at >= session.updated_at);
+            if keep_existing {
+                fs::remove_file(&path)?;
+            } else {
+                move_file(&path, &target)?;
+            }
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+/// Rename `from` to `to`, copying across file systems.
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ReviewSession;
+    use crate::model::review::SessionDiffSource;
+    use crate::persistence::storage::write_session_file;
+
+    fn session_for(repo: &Path, minutes_ago: i64) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            repo.to_path_buf(),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.updated_at = chrono::Utc::now() - chrono::Duration::minutes(minutes_ago);
+        session
+    }
+
+    #[test]
+    fn should_move_only_this_repositorys_sessions_and_keep_the_newer_copy() {
+        // given
+        let root = tempfile::tempdir().unwrap();
+        let repo = root.path().join("repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        let data = root.path().join("data");
+        let target = repo.join(".tuicr");
+        fs::create_dir_all(&data).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        let mine = session_for(&repo, 5);
+        let other = session_for(&root.path().join("elsewhere"), 5);
+        let stale_copy = session_for(&repo, 60);
+        let mut newer_copy = stale_copy.clone();
+        newer_copy.updated_at = chrono::Utc::now();
+        write_session_file(&data.join("mine.json"), &mine).unwrap();
+        write_session_file(&data.join("other.json"), &other).unwrap();
+        write_session_file(&data.join("copy.json"), &stale_copy).unwrap();
+        write_session_file(&target.join("copy.json"), &newer_copy).unwrap();
+
+        // when
+        let moved = migrate(std::slice::from_ref(&data), &target, &repo).unwrap();
+
+        // then
+        assert_eq!(moved, 2);
+        assert!(target.join("mine.json").exists());
+        assert!(!data.join("mine.json").exists());
+        assert!(data.join("other.json").exists());
+        assert!(!data.join("copy.json").exists());
+        let kept = load_session(&target.join("copy.json")).unwrap();
+        assert_eq!(kept.updated_at, newer_copy.updated_at);
+        assert!(target.join(".gitignore").exists());
+        assert_eq!(SessionLocation::Repo.dir(&repo.join("src")), Some(target));
+    }
+}
@@ -0,0 +1,300 @@
+//! Advisory lock files that stop two tuicr processes from silently saving
+//! over each other's copy of one session.
+//!
+//! A session is locked by the first process that opens or saves it: a
+//! `.<session id>.lock` file in the reviews directory records its pid and
+//! host. Saves from any other process are refused while that process runs.
+//! When it is gone without cleaning up (a crash, a killed terminal) the
+//! lock is stale and can be taken over. Locks are released on exit.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TuicrError};
+
+/// Lock files this process created, removed by `release_all`
+static HELD: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Re-reads of a lock file that doesn't parse before it counts as unreadable
+const UNREADABLE_RETRIES: usize = 5;
+const UNREADABLE_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+/// Who holds a session lock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    #[serde(default)]
+    pub host: String,
+    pub since: DateTime<Utc>,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            host: hostname(),
+            since: Utc::now(),
+        }
+    }
+
+    fn is_current(&self) -> bool {
+        self.pid == std::process::id() && self.host == hostname()
+    }
+
+    /// Whether the owner has exited. Only answerable for processes on this
+    /// host; a lock from another host is assumed live.
+    fn is_gone(&self) -> bool {
+        self.host == hostname() && !process_alive(self.pid)
+    }
+
+    pub fn describe(&self) -> String {
+        let since = self
+            .since
+            .with_timezone(&chrono::Local)
+            .format("%b %-d %H:%M");
+        if self.host.is_empty() || self.host == hostname() {
+            format!("pid {}, since {since}", self.pid)
+        } else {
+            format!("pid {} on {}, since {since}", self.pid, self.host)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockState {
+    /// Nobody holds the lock, or this process does
+    Free,
+    /// Another running tuicr holds it
+    Held(LockOwner),
+    /// The tuicr that took it has exited without releasing it
+    Stale(LockOwner),
+    /// The lock file names nobody, e.g. a tuicr crashed while writing it.
+    /// Held until the user takes it over.
+    Unreadable,
+}
+
+fn hostname() -> String {
+    gethostname::gethostname().to_string_lossy().into_owned()
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    if Path::new("/proc/self").exists() {
+        return Path::new(&format!("/proc/{pid}")).exists();
+    }
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+fn lock_path(dir: &Path, session_id: &str) -> PathBuf {
+    dir.join(format!(".{session_id}.lock"))
+}
+
+/// The owner recorded at `path`, `Ok(None)` when there is no lock file
+/// and `Err` when there is one that doesn't say who holds it.
+fn read_owner(path: &Path) -> std::result::Result<Option<LockOwner>, ()> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).map(Some).map_err(|_| ()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(_) => Err(()),
+    }
+}
+
+fn state_at(path: &Path) -> LockState {
+    // A lock another tuicr is creating reads as empty until its write
+    // lands, so give it a moment before calling it unreadable.
+    let mut owner = read_owner(path);
+    for _ in 0..UNREADABLE_RETRIES {
+        if owner.is_ok() {
+            break;
+        }
+        std::thread::sleep(UNREADABLE_RETRY_DELAY);
+        owner = read_owner(path);
+    }
+    match owner {
+        Err(()) => LockState::Unreadable,
+        Ok(None) => LockState::Free,
+        Ok(Some(owner)) if owner.is_current() => LockState::Free,
+        Ok(Some(owner)) if owner.is_gone() => LockState::Stale(owner),
+        Ok(Some(owner)) => LockState::Held(owner),
+    }
+}
+
+fn write_owner(path: &Path, exclusive: bool) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if exclusive {
+        options.create_new(true);
+    } else {
+        options.create(true).truncate(true);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(serde_json::to_string(&LockOwner::current())?.as_bytes())?;
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+    if !held.iter().any(|p| p == path) {
+        held.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Take the lock at `path` if nobody has it. Only `take_over` ever
+/// overwrites an existing lock file.
+fn acquire_at(path: &Path) -> Result<()> {
+    match state_at(path) {
+        LockState::Free if matches!(read_owner(path), Ok(Some(_))) => Ok(()),
+        LockState::Free => match write_owner(path, true) {
+            // Another process created it between our look and our write
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => acquire_at(path),
+            result => result.map_err(TuicrError::from),
+        },
+        LockState::Held(owner) | LockState::Stale(owner) => Err(locked_error(&owner)),
+        LockState::Unreadable => Err(TuicrError::SessionLocked(format!(
+            "the review's lock file {} is unreadable",
+            path.display()
+        ))),
+    }
+}
+
+fn locked_error(owner: &LockOwner) -> TuicrError {
+    TuicrError::SessionLocked(format!(
+        "the review is open in another tuicr ({})",
+        owner.describe()
+    ))
+}
+
+fn reviews_dir() -> Result<PathBuf> {
+    crate::persistence::storage::get_reviews_dir()
+}
+
+/// Who, if anyone else, holds `session_id`'s lock.
+pub fn inspect(session_id: &str) -> Result<LockState> {
+    Ok(state_at(&lock_path(&reviews_dir()?, session_id)))
+}
+
+/// Whether another running tuicr holds `session_id`'s lock in `dir`, or
+/// may: an unreadable lock counts as held.
+pub fn held_in(dir: &Path, session_id: &str) -> bool {
+    matches!(
+        state_at(&lock_path(dir, session_id)),
+        LockState::Held(_) | LockState::Unreadable
+    )
+}
+
+/// Lock `session_id` for this process, unless another one holds it.
+pub fn acquire(session_id: &str) -> Result<()> {
+    acquire_at(&lock_path(&reviews_dir()?, session_id))
+}
+
+/// Lock `session_id` for this process whoever holds it, for a stale lock
+/// the user chose to take over.
+pub fn take_over(session_id: &str) -> Result<()> {
+    write_owner(&lock_path(&reviews_dir()?, session_id), false).map_err(TuicrError::from)
+}
+
+/// Remove every lock this process holds that still names it.
+pub fn release_all() {
+    let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+    for path in held.drain(..) {
+        if read_owner(&path).is_ok_and(|owner| owner.is_some_and(|owner| owner.is_current())) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_foreign(path: &Path, pid: u32) {
+        let owner = LockOwner {
+            pid,
+            host: hostname(),
+            since: Utc::now(),
+        };
+        fs::write(path, serde_json::to_string(&owner).unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_refuse_a_lock_held_by_a_running_process() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = lock_path(dir.path(), "abc");
+        let mut running = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        write_foreign(&path, running.id());
+
+        // when
+        let state = state_at(&path);
+        let acquired = acquire_at(&path);
+        let _ = running.kill();
+        let _ = running.wait();
+
+        // then
+        assert!(matches!(state, LockState::Held(ref owner) if owner.pid == running.id()));
+        assert!(matches!(acquired, Err(TuicrError::SessionLocked(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_report_a_lock_left_by_an_exited_process_as_stale() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = lock_path(dir.path(), "abc");
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        exited.wait().unwrap();
+        write_foreign(&path, exited.id());
+
+        // when
+        let state = state_at(&path);
+        write_owner(&path, false).unwrap();
+
+        // then
+        assert!(matches!(state, LockState::Stale(_)));
+        assert_eq!(state_at(&path), LockState::Free);
+        assert!(acquire_at(&path).is_ok());
+    }
+
+    #[test]
+    fn should_keep_an_unreadable_lock_until_it_is_taken_over() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = lock_path(dir.path(), "abc");
+        fs::write(&path, "").unwrap();
+
+        // when
+        let state = state_at(&path);
+        let acquired = acquire_at(&path);
+
+        // then
+        assert_eq!(state, LockState::Unreadable);
+        assert!(matches!(acquired, Err(TuicrError::SessionLocked(_))));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        assert!(held_in(dir.path(), "abc"));
+        write_owner(&path, false).unwrap();
+        assert_eq!(state_at(&path), LockState::Free);
+    }
+
+    #[test]
+    fn should_know_this_host_without_hostname_in_the_environment() {
+        // $HOSTNAME is a shell variable that is rarely exported
+        assert!(!hostname().is_empty());
+    }
+}
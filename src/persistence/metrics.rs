@@ -0,0 +1,235 @@
+//! Local record of completed reviews, one JSON line per session, read back
+//! by `tuicr stats` for personal retrospection.
+//!
+//! A review counts as completed once every file in it is marked reviewed.
+//! Saving it again replaces its line, so late comments are still counted.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TuicrError};
+use crate::model::{CommentType, DiffFile, ReviewSession};
+use crate::persistence::storage::normalize_repo_path;
+
+/// Gaps between review actions longer than this count as a break, not as
+/// time spent reviewing
+pub const IDLE_GAP_SECS: i64 = 5 * 60;
+
+/// What one completed review amounted to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReviewMetrics {
+    pub session_id: String,
+    /// Normalized repository root
+    pub repo: String,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    /// Time between review actions, leaving out breaks
+    pub active_secs: u64,
+    pub files: usize,
+    /// Added plus removed lines, commit messages excluded
+    pub lines_changed: usize,
+    pub comments: usize,
+    pub issues: usize,
+    /// Comment count of each file that has any
+    #[serde(default)]
+    pub comments_by_file: BTreeMap<PathBuf, usize>,
+}
+
+impl ReviewMetrics {
+    /// Metrics of `session` if every file in it is reviewed
+    pub fn from_session(session: &ReviewSession, diff_files: &[DiffFile]) -> Option<Self> {
+        if !session.is_complete() {
+            return None;
+        }
+        let lines_changed = diff_files
+            .iter()
+            .filter(|f| !f.is_commit_message)
+            .map(|f| {
+                let (added, removed) = f.stat();
+                added + removed
+            })
+            .sum();
+        let mut comments = session.review_comments.len();
+        let mut issues = session
+            .review_comments
+            .iter()
+            .filter(|c| c.comment_type == CommentType::Issue)
+            .count();
+        let mut comments_by_file = BTreeMap::new();
+        for (path, review) in &session.files {
+            let all = review
+                .file_comments
+                .iter()
+                .chain(review.line_comments.values().flatten());
+            let (mut count, mut file_issues) = (0, 0);
+            for comment in all {
+                count += 1;
+                if comment.comment_type == CommentType::Issue {
+                    file_issues += 1;
+                }
+            }
+            if count > 0 {
+                comments_by_file.insert(path.clone(), count);
+            }
+            comments += count;
+            issues += file_issues;
+        }
+        Some(Self {
+            session_id: session.id.clone(),
+            repo: normalize_repo_path(&session.repo_path),
+            started_at: session.created_at,
+            completed_at: session.updated_at,
+            active_secs: active_secs(session),
+            files: session.files.len(),
+            lines_changed,
+            comments,
+            issues,
+            comments_by_file,
+        })
+    }
+}
+
+/// The session's tracked active time, or for sessions reviewed before time
+/// was tracked, the sum of the gaps between its start, its logged events
+/// and its last save, each capped at [`IDLE_GAP_SECS`]
+fn active_secs(session: &ReviewSession) -> u64 {
+    if session.active_ms > 0 {
+        return session.active_ms / 1000;
+    }
+    let mut times: Vec<DateTime<Utc>> = std::iter::once(session.created_at)
+        .chain(session.events.iter().map(|e| e.at))
+        .chain(std::iter::once(session.updated_at))
+        .collect();
+    times.sort();
+    times
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_seconds().clamp(0, IDLE_GAP_SECS) as u64)
+        .sum()
+}
+
+/// `review_metrics.ndjson` next to the saved reviews in the data directory.
+pub fn metrics_path() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
+        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+    })?;
+    Ok(proj_dirs.data_dir().join("review_metrics.ndjson"))
+}
+
+/// Every recorded review, oldest completion first; a missing file has none.
+pub fn load_metrics(path: &Path) -> Result<Vec<ReviewMetrics>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| TuicrError::CorruptedSession(e.to_string()))
+        })
+        .collect()
+}
+
+/// Record `metrics`, replacing an earlier line for the same session.
+pub fn record_metrics(path: &Path, metrics: &ReviewMetrics) -> Result<()> {
+    let mut all = load_metrics(path)?;
+    all.retain(|m| m.session_id != metrics.session_id);
+    all.push(metrics.clone());
+    all.sort_by_key(|m| m.completed_at);
+    let mut contents = String::new();
+    for m in &all {
+        contents.push_str(&serde_json::to_string(m)?);
+        contents.push('\n');
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Comment, FileStatus, ReviewEventKind, SessionDiffSource};
+
+    fn session() -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp/repo"),
+            "abc123".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        let path = PathBuf::from("src/lib.rs");
+        session.add_file(path.clone(), FileStatus::Modified, 0);
+        session.add_file(PathBuf::from("src/main.rs"), FileStatus::Modified, 0);
+        let review = session.get_file_mut(&path).unwrap();
+        review.add_line_comment(
+            3,
+            Comment::new("unchecked index".to_string(), CommentType::Issue, None),
+        );
+        review.add_file_comment(Comment::new("nice".to_string(), CommentType::Praise, None));
+        session
+    }
+
+    #[test]
+    fn should_only_measure_completed_reviews() {
+        // given
+        let mut session = session();
+        let start = session.created_at;
+        session.record_event(ReviewEventKind::FileReviewed {
+            path: PathBuf::from("src/lib.rs"),
+        });
+        session.events[0].at = start + chrono::Duration::seconds(90);
+        session.updated_at = start + chrono::Duration::hours(2);
+        assert_eq!(ReviewMetrics::from_session(&session, &[]), None);
+
+        // when
+        for review in session.files.values_mut() {
+            review.reviewed = true;
+        }
+        let metrics = ReviewMetrics::from_session(&session, &[]).unwrap();
+
+        // then
+        assert_eq!(metrics.active_secs, 90 + IDLE_GAP_SECS as u64);
+        assert_eq!((metrics.files, metrics.comments, metrics.issues), (2, 2, 1));
+        assert_eq!(
+            metrics.comments_by_file,
+            BTreeMap::from([(PathBuf::from("src/lib.rs"), 2)])
+        );
+    }
+
+    #[test]
+    fn should_replace_the_line_of_a_session_recorded_again() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join("review_metrics.ndjson");
+        let mut session = session();
+        for review in session.files.values_mut() {
+            review.reviewed = true;
+        }
+        let first = ReviewMetrics::from_session(&session, &[]).unwrap();
+        let second = ReviewMetrics {
+            comments: 5,
+            ..first.clone()
+        };
+
+        // when
+        record_metrics(&path, &first).unwrap();
+        record_metrics(&path, &second).unwrap();
+
+        // then
+        assert_eq!(load_metrics(&path).unwrap(), vec![second]);
+        assert!(
+            load_metrics(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1,3 +1,16 @@
+pub mod archive;
+pub mod findings;
+pub mod git_notes;
+pub mod journal;
+pub mod last_source;
+pub mod location;
+pub mod lock;
+pub mod metrics;
+pub mod portable;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod storage;
 
-pub use storage::{load_latest_session_for_context, load_pr_session, save_session};
+pub use storage::{
+    list_sessions_for_repo, load_latest_session_for_context, load_pr_session, save_session,
+};
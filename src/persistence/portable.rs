@@ -0,0 +1,243 @@
+//! Portable session files for `:session export` / `:session import`, so a
+//! review started on one machine can be resumed on another checkout of the
+//! same repository.
+//!
+//! Saved sessions are tied to the checkout by their absolute `repo_path`,
+//! which both the session file name and the lookup on startup go by. A
+//! portable file leaves it out and makes every file path that is under it
+//! relative to the repository; importing points the session at the
+//! checkout it is imported into.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TuicrError};
+use crate::model::ReviewSession;
+use crate::model::review::{CommitReview, FileReview};
+
+/// Layout version of portable session files
+const PORTABLE_VERSION: u32 = 1;
+
+/// Key that marks a JSON file as a portable session
+pub const PORTABLE_KEY: &str = "tuicr_portable_session";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PortableSession {
+    tuicr_portable_session: u32,
+    /// The checkout the session was exported from, for the import message
+    exported_from: PathBuf,
+    session: ReviewSession,
+}
+
+/// Rewrite every file path recorded in `session` with `remap`, leaving the
+/// ones it returns `None` for as they are.
+fn remap_paths(session: &mut ReviewSession, remap: impl Fn(&Path) -> Option<PathBuf>) {
+    let apply = |path: &mut PathBuf| {
+        if let Some(new) = remap(path) {
+            *path = new;
+        }
+    };
+    let remap_files = |files: &mut HashMap<PathBuf, FileReview>| {
+        *files = std::mem::take(files)
+            .into_iter()
+            .map(|(mut path, mut review)| {
+                apply(&mut path);
+                apply(&mut review.path);
+                (path, review)
+            })
+            .collect();
+    };
+    let remap_commits = |commits: &mut BTreeMap<String, CommitReview>| {
+        for commit in commits.values_mut() {
+            commit.reviewed = std::mem::take(&mut commit.reviewed)
+                .into_iter()
+                .map(|(mut path, confidence)| {
+                    apply(&mut path);
+                    (path, confidence)
+                })
+                .collect();
+        }
+    };
+    remap_files(&mut session.files);
+    remap_commits(&mut session.commits);
+    for checkpoint in &mut session.checkpoints {
+        remap_files(&mut checkpoint.files);
+        remap_commits(&mut checkpoint.commits);
+        checkpoint
+            .review_edits
+            .iter_mut()
+            .for_each(|edit| apply(&mut edit.path));
+    }
+    for edit in &mut session.review_edits {
+        apply(&mut edit.path);
+    }
+    for trashed in &mut session.trash {
+        trashed.path.iter_mut().for_each(apply);
+    }
+    for event in &mut session.events {
+        use crate::model::review::ReviewEventKind;
+        match &mut event.kind {
+            ReviewEventKind::FileReviewed { path } | ReviewEventKind::FileUnreviewed { path } => {
+                apply(path)
+            }
+            ReviewEventKind::CommentAdded { path, .. }
+            | ReviewEventKind::CommentEdited { path, .. }
+            | ReviewEventKind::CommentDeleted { path, .. } => path.iter_mut().for_each(apply),
+        }
+    }
+    if let Some(spot_check) = &mut session.spot_check {
+        for hunk in &mut spot_check.sampled {
+            apply(&mut hunk.path);
+        }
+    }
+    if let Some(cursor) = &mut session.cursor {
+        apply(&mut cursor.path);
+    }
+}
+
+/// `session` with its paths under `repo_path` made repo-relative and its
+/// `repo_path` cleared. Pull request sessions keep their forge `repo_path`,
+/// which doesn't depend on the checkout.
+pub fn to_portable(session: &ReviewSession) -> ReviewSession {
+    let mut portable = session.clone();
+    let repo = session.repo_path.clone();
+    remap_paths(&mut portable, |path| {
+        path.strip_prefix(&repo).ok().map(Path::to_path_buf)
+    });
+    if portable.pr_session_key.is_none() {
+        portable.repo_path = PathBuf::new();
+    }
+    portable
+}
+
+/// Write `session` to `path` as a portable session file.
+pub fn export_session(path: &Path, session: &ReviewSession) -> Result<()> {
+    let file = PortableSession {
+        tuicr_portable_session: PORTABLE_VERSION,
+        exported_from: session.repo_path.clone(),
+        session: to_portable(session),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&file)?)?;
+    Ok(())
+}
+
+/// Read the portable session file in `contents` for the checkout at
+/// `repo_root`. Returns the session and the checkout it was exported from.
+/// The session gets a fresh id: importing into the checkout it came from
+/// would otherwise replace the original and share its lock and journal.
+pub fn import_session(contents: &str, repo_root: &Path) -> Result<(ReviewSession, PathBuf)> {
+    let file: PortableSession = serde_json::from_str(contents).map_err(|e| {
+        if serde_json::from_str::<serde_json::Value>(contents)
+            .is_ok_and(|value| value.get(PORTABLE_KEY).is_none())
+        {
+            TuicrError::CorruptedSession(
+                "not a portable session (write one with :session export)".to_string(),
+            )
+        } else {
+            TuicrError::CorruptedSession(e.to_string())
+        }
+    })?;
+    if file.tuicr_portable_session > PORTABLE_VERSION {
+        return Err(TuicrError::CorruptedSession(format!(
+            "portable session version {} is newer than this tuicr reads",
+            file.tuicr_portable_session
+        )));
+    }
+    let mut session = file.session;
+    session.id = uuid::Uuid::new_v4().to_string();
+    if session.pr_session_key.is_none() {
+        session.repo_path = repo_root.to_path_buf();
+    }
+    Ok((session, file.exported_from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::review::{ReviewEventKind, SessionCursor, SessionDiffSource};
+    use crate::model::{Comment, CommentType, FileStatus, LineSide};
+
+    #[test]
+    fn should_resume_an_exported_session_in_another_checkout() {
+        // given
+        let mut session = ReviewSession::new(
+            PathBuf::from("/home/alice/src/tuicr"),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        let relative = PathBuf::from("src/lib.rs");
+        let absolute = PathBuf::from("/home/alice/src/tuicr/src/main.rs");
+        session.add_file(relative.clone(), FileStatus::Modified, 1);
+        session.add_file(absolute.clone(), FileStatus::Added, 2);
+        session.get_file_mut(&absolute).unwrap().add_line_comment(
+            4,
+            Comment::new("typo".to_string(), CommentType::Issue, Some(LineSide::New)),
+        );
+        session.record_event(ReviewEventKind::FileReviewed {
+            path: absolute.clone(),
+        });
+        session.cursor = Some(SessionCursor {
+            path: absolute,
+            line: Some(4),
+            side: Some(LineSide::New),
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review.tuicr.json");
+
+        // when
+        export_session(&path, &session).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let (imported, from) =
+            import_session(&contents, Path::new("/Users/bob/work/tuicr")).unwrap();
+
+        // then
+        assert!(!contents.contains("/home/alice/src/tuicr/src"));
+        assert_eq!(from, PathBuf::from("/home/alice/src/tuicr"));
+        assert_ne!(imported.id, session.id);
+        assert_eq!(imported.repo_path, PathBuf::from("/Users/bob/work/tuicr"));
+        let main = PathBuf::from("src/main.rs");
+        assert_eq!(imported.files[&main].path, main);
+        assert_eq!(imported.files[&main].line_comments[&4][0].content, "typo");
+        assert!(imported.files.contains_key(&relative));
+        assert_eq!(imported.events[0].kind.path(), Some(&main));
+        assert_eq!(imported.cursor.unwrap().path, main);
+        assert!(import_session(&serde_json::to_string(&session).unwrap(), Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn should_keep_the_original_when_importing_into_the_same_checkout() {
+        // given
+        let _guard = crate::persistence::storage::tests::with_test_reviews_dir();
+        let repo = PathBuf::from("/home/alice/src/tuicr");
+        let mut session = ReviewSession::new(
+            repo.clone(),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 1);
+        crate::persistence::save_session(&session).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review.tuicr.json");
+        export_session(&path, &session).unwrap();
+
+        // when
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let (imported, _) = import_session(&contents, &repo).unwrap();
+        crate::persistence::save_session(&imported).unwrap();
+
+        // then
+        let mut saved: Vec<_> = crate::persistence::list_sessions_for_repo(&repo)
+            .unwrap()
+            .into_iter()
+            .map(|(_, saved)| saved.id)
+            .collect();
+        saved.sort();
+        let mut expected = vec![session.id, imported.id];
+        expected.sort();
+        assert_eq!(saved, expected);
+    }
+}
@@ -0,0 +1,349 @@
+//! SQLite session store, used instead of JSON files when `session_store =
+//! "sqlite"` (built with the `sqlite` feature). Every session lives in one
+//! `reviews.db`: the full session as JSON in `sessions.data`, which is what
+//! gets loaded back, plus `files` and `comments` tables mirroring it so many
+//! sessions can be queried without deserializing each one. Writes go
+//! through a transaction in WAL mode, so two tuicr processes saving at once
+//! don't clobber each other.
+//!
+//! Sessions are still identified by a path, `<reviews.db>/<session id>`, so
+//! the rest of the app handles both stores alike.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{SecondsFormat, Utc};
+use directories::ProjectDirs;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+
+use crate::error::{Result, TuicrError};
+use crate::forge::traits::PrSessionKey;
+use crate::model::ReviewSession;
+use crate::model::review::SessionDiffSource;
+use crate::persistence::storage::normalize_repo_path;
+
+const DATABASE_FILENAME: &str = "reviews.db";
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    repo_path TEXT NOT NULL,
+    branch TEXT,
+    base_commit TEXT NOT NULL,
+    diff_source TEXT NOT NULL,
+    pr_key TEXT,
+    name TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_by_repo ON sessions (repo_path, diff_source, updated_at);
+CREATE INDEX IF NOT EXISTS sessions_by_pr ON sessions (pr_key);
+CREATE TABLE IF NOT EXISTS files (
+    session_id TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    status TEXT NOT NULL,
+    reviewed INTEGER NOT NULL,
+    PRIMARY KEY (session_id, path)
+);
+CREATE TABLE IF NOT EXISTS comments (
+    session_id TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    id TEXT NOT NULL,
+    path TEXT,
+    line INTEGER,
+    side TEXT,
+    comment_type TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (session_id, id)
+);
+CREATE INDEX IF NOT EXISTS comments_by_path ON comments (path);
+";
+
+/// `reviews.db` in tuicr's data directory.
+pub fn database_path() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
+        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+    })?;
+    let data_dir = proj_dirs.data_dir();
+    std::fs::create_dir_all(data_dir)?;
+    Ok(data_dir.join(DATABASE_FILENAME))
+}
+
+/// Open (creating if needed) the database at `path`.
+pub fn open(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.execute_batch(SCHEMA)?;
+    Ok(conn)
+}
+
+/// The session id in a path handed out by this store, or `None` for any
+/// other path (a JSON session file).
+pub fn session_id(path: &Path) -> Option<&str> {
+    let db = path.parent()?;
+    if db.file_name()? != DATABASE_FILENAME || db.is_dir() {
+        return None;
+    }
+    path.file_name()?.to_str()
+}
+
+fn session_path(conn: &Connection, id: &str) -> PathBuf {
+    let db = conn.path().unwrap_or(DATABASE_FILENAME);
+    Path::new(db).join(id)
+}
+
+/// A serde enum's tag (`"modified"`, `"new"`, ...) as stored in a column.
+fn tag<T: Serialize>(value: &T) -> Result<String> {
+    Ok(match serde_json::to_value(value)? {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    })
+}
+
+fn timestamp(time: chrono::DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Insert or replace `session`, with its files and comments.
+pub fn save(conn: &Connection, session: &ReviewSession) -> Result<PathBuf> {
+    let tx = conn.unchecked_transaction()?;
+    let pr_key = session
+        .pr_session_key
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+    tx.execute(
+        "INSERT OR REPLACE INTO sessions
+             (id, repo_path, branch, base_commit, diff_source, pr_key, name,
+              created_at, updated_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            session.id,
+            normalize_repo_path(&session.repo_path),
+            session.branch_name,
+            session.base_commit,
+            tag(&session.diff_source)?,
+            pr_key,
+            session.name,
+            timestamp(session.created_at),
+            timestamp(session.updated_at),
+            serde_json::to_string(session)?,
+        ],
+    )?;
+    // `INSERT OR REPLACE` deletes the old row, cascading to its files and
+    // comments, so they are simply written afresh.
+    {
+        let mut insert_file = tx.prepare(
+            "INSERT INTO files (session_id, path, status, reviewed) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut insert_comment = tx.prepare(
+            "INSERT INTO comments
+                 (session_id, id, path, line, side, comment_type, content, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for comment in &session.review_comments {
+            insert_comment.execute(params![
+                session.id,
+                comment.id,
+                None::<String>,
+                None::<u32>,
+                None::<String>,
+                comment.comment_type.id(),
+                comment.content,
+                timestamp(comment.created_at),
+            ])?;
+        }
+        for (path, file) in &session.files {
+            let path = path.to_string_lossy();
+            insert_file.execute(params![session.id, path, tag(&file.status)?, file.reviewed])?;
+            let file_comments = file.file_comments.iter().map(|c| (None, c));
+            let line_comments = file
+                .line_comments
+                .iter()
+                .flat_map(|(line, comments)| comments.iter().map(move |c| (Some(*line), c)));
+            for (line, comment) in file_comments.chain(line_comments) {
+                insert_comment.execute(params![
+                    session.id,
+                    comment.id,
+                    path,
+                    line,
+                    comment.side.as_ref().map(tag).transpose()?,
+                    comment.comment_type.id(),
+                    comment.content,
+                    timestamp(comment.created_at),
+                ])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(session_path(conn, &session.id))
+}
+
+fn parse(data: &str) -> Result<ReviewSession> {
+    serde_json::from_str(data).map_err(|e| TuicrError::CorruptedSession(e.to_string()))
+}
+
+pub fn load(conn: &Connection, id: &str) -> Result<ReviewSession> {
+    let data: Option<String> = conn
+        .query_row("SELECT data FROM sessions WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    match data {
+        Some(data) => parse(&data),
+        None => Err(TuicrError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("No saved session {id}"),
+        ))),
+    }
+}
+
+pub fn delete(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM sessions WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Run a `SELECT data ...` query, skipping rows that no longer parse.
+fn query_sessions(
+    conn: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<(PathBuf, ReviewSession)>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, |row| row.get::<_, String>(0))?;
+    let mut sessions = Vec::new();
+    for data in rows {
+        if let Ok(session) = parse(&data?) {
+            sessions.push((session_path(conn, &session.id), session));
+        }
+    }
+    Ok(sessions)
+}
+
+/// Local (non-PR) sessions for `repo_path`, most recently updated first.
+pub fn list_for_repo(conn: &Connection, repo_path: &Path) -> Result<Vec<(PathBuf, ReviewSession)>> {
+    query_sessions(
+        conn,
+        "SELECT data FROM sessions
+         WHERE repo_path = ?1 AND pr_key IS NULL
+         ORDER BY updated_at DESC",
+        [normalize_repo_path(repo_path)],
+    )
+}
+
+/// Every session in the database, most recently updated first.
+pub fn list_all(conn: &Connection) -> Result<Vec<(PathBuf, ReviewSession)>> {
+    query_sessions(
+        conn,
+        "SELECT data FROM sessions ORDER BY updated_at DESC",
+        [],
+    )
+}
+
+/// The most recently updated session saved for the PR `key`.
+pub fn load_pr(conn: &Connection, key: &PrSessionKey) -> Result<Option<(PathBuf, ReviewSession)>> {
+    let sessions = query_sessions(
+        conn,
+        "SELECT data FROM sessions WHERE pr_key = ?1 ORDER BY updated_at DESC LIMIT 1",
+        [serde_json::to_string(key)?],
+    )?;
+    Ok(sessions.into_iter().next())
+}
+
+/// Sessions of `repo_path` for `diff_source`, most recent first, after
+/// dropping every session not updated within `max_age`, if given.
+pub fn recent_for_repo(
+    conn: &Connection,
+    repo_path: &Path,
+    diff_source: SessionDiffSource,
+    max_age: Option<Duration>,
+) -> Result<Vec<(PathBuf, ReviewSession)>> {
+    if let Some(cutoff) = max_age
+        .and_then(|age| chrono::Duration::from_std(age).ok())
+        .and_then(|age| Utc::now().checked_sub_signed(age))
+    {
+        conn.execute(
+            "DELETE FROM sessions WHERE updated_at < ?1",
+            [timestamp(cutoff)],
+        )?;
+    }
+    query_sessions(
+        conn,
+        "SELECT data FROM sessions
+         WHERE repo_path = ?1 AND diff_source = ?2 AND pr_key IS NULL
+         ORDER BY updated_at DESC",
+        params![normalize_repo_path(repo_path), tag(&diff_source)?],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CommentType, FileStatus, LineSide};
+
+    fn session(repo: &Path) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            repo.to_path_buf(),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(PathBuf::from("src/lib.rs"), FileStatus::Modified, 0);
+        session
+    }
+
+    #[test]
+    fn should_save_and_query_sessions_in_the_database() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open(&dir.path().join(DATABASE_FILENAME)).unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        let mut session = session(&repo);
+        let file = session.get_file_mut(&PathBuf::from("src/lib.rs")).unwrap();
+        file.reviewed = true;
+        file.add_line_comment(
+            7,
+            crate::model::Comment::new(
+                "off by one".to_string(),
+                CommentType::Issue,
+                Some(LineSide::New),
+            ),
+        );
+
+        // when
+        let path = save(&conn, &session).unwrap();
+        save(&conn, &session).unwrap();
+
+        // then
+        assert_eq!(session_id(&path), Some(session.id.as_str()));
+        assert_eq!(load(&conn, &session.id).unwrap().id, session.id);
+        let listed = list_for_repo(&conn, &repo).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].0, path);
+        let (line, side): (u32, String) = conn
+            .query_row(
+                "SELECT line, side FROM comments WHERE comment_type = 'issue'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((line, side.as_str()), (7, "new"));
+        let reviewed: bool = conn
+            .query_row("SELECT reviewed FROM files", [], |row| row.get(0))
+            .unwrap();
+        assert!(reviewed);
+
+        delete(&conn, &session.id).unwrap();
+        assert!(list_for_repo(&conn, &repo).unwrap().is_empty());
+        let comments: u32 = conn
+            .query_row("SELECT COUNT(*) FROM comments", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(comments, 0);
+    }
+}
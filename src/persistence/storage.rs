@@ -1,6 +1,6 @@
-use directories::ProjectDirs;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{Duration, SystemTime};
 
 use crate::error::{Result, TuicrError};
@@ -9,13 +9,53 @@ use crate::hash::fnv1a_64;
 use crate::model::ReviewSession;
 use crate::model::review::SessionDiffSource;
 
-const SESSION_MAX_AGE_DAYS: u64 = 7;
+/// Days a session may go untouched before it is pruned, unless the
+/// `session_max_age_days` config key says otherwise
+pub const DEFAULT_SESSION_MAX_AGE_DAYS: u64 = 7;
 const SESSION_FILENAME_MIN_PARTS: usize = 6;
 const SESSION_FILENAME_SUFFIX_PARTS: usize = 4;
 const SESSION_FILENAME_DATE_LEN: usize = 8;
 const SESSION_FILENAME_TIME_LEN: usize = 6;
 const FINGERPRINT_HEX_LEN: usize = 8;
 
+/// Whether sessions go to the SQLite database instead of JSON files. Set
+/// once at startup from the `session_store` config key.
+static USE_SQLITE: AtomicBool = AtomicBool::new(false);
+
+/// Days after its last update that a session is pruned; `0` keeps
+/// sessions forever. Set once at startup from `session_max_age_days`.
+static SESSION_MAX_AGE_DAYS: AtomicU64 = AtomicU64::new(DEFAULT_SESSION_MAX_AGE_DAYS);
+
+/// Store new and looked-up sessions in the SQLite database (`true`) or as
+/// JSON files in the reviews directory. Sessions already identified by a
+/// path keep using the store that path belongs to.
+pub fn set_sqlite_store(enabled: bool) {
+    USE_SQLITE.store(enabled, Ordering::Relaxed);
+}
+
+/// Prune sessions not updated within `days` days; `0` never prunes by age.
+pub fn set_session_max_age_days(days: u64) {
+    SESSION_MAX_AGE_DAYS.store(days, Ordering::Relaxed);
+}
+
+/// How long a session may go untouched before it is pruned, or `None`
+/// when sessions are kept whatever their age.
+pub fn session_max_age() -> Option<Duration> {
+    match SESSION_MAX_AGE_DAYS.load(Ordering::Relaxed) {
+        0 => None,
+        days => Some(Duration::from_secs(days * 24 * 60 * 60)),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_store() -> Option<rusqlite::Connection> {
+    if !USE_SQLITE.load(Ordering::Relaxed) {
+        return None;
+    }
+    let path = crate::persistence::sqlite::database_path().ok()?;
+    crate::persistence::sqlite::open(&path).ok()
+}
+
 struct SessionFilenameParts {
     repo_fingerprints: Vec<String>,
     diff_source: String,
@@ -79,7 +119,7 @@ fn is_hex_fingerprint(part: &str) -> bool {
     part.len() == FINGERPRINT_HEX_LEN && part.chars().all(|ch| ch.is_ascii_hexdigit())
 }
 
-fn get_reviews_dir() -> Result<PathBuf> {
+pub(crate) fn get_reviews_dir() -> Result<PathBuf> {
     #[cfg(test)]
     if let Some(dir) = std::env::var_os("TUICR_REVIEWS_DIR") {
         let path = PathBuf::from(dir);
@@ -87,13 +127,12 @@ fn get_reviews_dir() -> Result<PathBuf> {
         return Ok(path);
     }
 
-    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
-        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
-    })?;
-
-    let data_dir = proj_dirs.data_dir().join("reviews");
-    fs::create_dir_all(&data_dir)?;
-    Ok(data_dir)
+    let dir = match crate::persistence::location::installed() {
+        Some(dir) => dir.to_path_buf(),
+        None => crate::persistence::location::data_reviews_dir()?,
+    };
+    crate::persistence::location::prepare(&dir)?;
+    Ok(dir)
 }
 
 const MAX_FILENAME_COMPONENT_LEN: usize = 64;
@@ -123,7 +162,7 @@ fn repo_path_fingerprint(repo_path: &Path) -> String {
     hex[..FINGERPRINT_HEX_LEN].to_string()
 }
 
-fn normalize_repo_path(repo_path: &Path) -> String {
+pub(crate) fn normalize_repo_path(repo_path: &Path) -> String {
     let canonical = fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
     let normalized = canonical.to_string_lossy().to_string();
 
@@ -182,24 +221,124 @@ fn session_filename(session: &ReviewSession) -> String {
     )
 }
 
+/// Write `session` to its file in the reviews directory. The JSON goes to
+/// a temporary file first and is renamed over the old one, so a crash or a
+/// full disk mid-write never leaves a truncated session behind. Fails while
+/// another tuicr holds the session's lock (see `persistence::lock`).
 pub fn save_session(session: &ReviewSession) -> Result<PathBuf> {
-    let reviews_dir = get_reviews_dir()?;
-    let filename = session_filename(session);
-    let path = reviews_dir.join(&filename);
+    crate::persistence::lock::acquire(&session.id)?;
+    let dir = get_reviews_dir()?;
+    #[cfg(feature = "sqlite")]
+    if let Some(conn) = sqlite_store() {
+        let path = crate::persistence::sqlite::save(&conn, session)?;
+        let _ = crate::persistence::journal::clear(&dir, &session.id);
+        return Ok(path);
+    }
+    let path = dir.join(session_filename(session));
+    write_session_file(&path, session)?;
+    let _ = crate::persistence::journal::clear(&dir, &session.id);
+    Ok(path)
+}
 
+/// Write `session` to `path` through a temporary file next to it, as
+/// [`save_session`] does.
+pub fn write_session_file(path: &Path, session: &ReviewSession) -> Result<()> {
+    #[cfg(feature = "sqlite")]
+    if crate::persistence::sqlite::session_id(path).is_some() {
+        let conn = crate::persistence::sqlite::open(path.parent().unwrap_or(path))?;
+        return crate::persistence::sqlite::save(&conn, session).map(|_| ());
+    }
     let json = serde_json::to_string_pretty(session)?;
-    fs::write(&path, json)?;
-
-    Ok(path)
+    let filename = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{filename}.tmp"));
+    fs::write(&tmp_path, json)?;
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
 }
 
 pub fn load_session(path: &PathBuf) -> Result<ReviewSession> {
+    #[cfg(feature = "sqlite")]
+    if let Some(id) = crate::persistence::sqlite::session_id(path) {
+        let conn = crate::persistence::sqlite::open(path.parent().unwrap_or(path))?;
+        return crate::persistence::sqlite::load(&conn, id);
+    }
     let contents = fs::read_to_string(path)?;
     let session: ReviewSession =
         serde_json::from_str(&contents).map_err(|e| TuicrError::CorruptedSession(e.to_string()))?;
     Ok(session)
 }
 
+/// All local sessions saved for `repo_path`, most recently updated first.
+/// Unreadable session files are skipped.
+pub fn list_sessions_for_repo(repo_path: &Path) -> Result<Vec<(PathBuf, ReviewSession)>> {
+    #[cfg(feature = "sqlite")]
+    if let Some(conn) = sqlite_store() {
+        return crate::persistence::sqlite::list_for_repo(&conn, repo_path);
+    }
+    let reviews_dir = get_reviews_dir()?;
+    let current_repo_path = normalize_repo_path(repo_path);
+    let current_fingerprint = repo_path_fingerprint(repo_path);
+
+    let mut sessions: Vec<(PathBuf, ReviewSession)> = fs::read_dir(&reviews_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let Some(filename) = path.file_name().and_then(|f| f.to_str()) else {
+                return false;
+            };
+            if !filename.to_ascii_lowercase().ends_with(".json") {
+                return false;
+            }
+            // Skip other repos' files without parsing them; legacy names
+            // carry no fingerprint and are checked after loading.
+            parse_session_filename(filename).is_none_or(|parts| {
+                parts
+                    .repo_fingerprints
+                    .iter()
+                    .any(|fingerprint| fingerprint == &current_fingerprint)
+            })
+        })
+        .filter_map(|path| {
+            let session = load_session(&path).ok()?;
+            (session.pr_session_key.is_none()
+                && normalize_repo_path(&session.repo_path) == current_repo_path)
+                .then_some((path, session))
+        })
+        .collect();
+
+    sessions.sort_by_key(|(_, session)| std::cmp::Reverse(session.updated_at));
+    Ok(sessions)
+}
+
+/// Every saved session, local and PR, most recently updated first.
+/// Unreadable session files are skipped.
+pub fn list_all_sessions() -> Result<Vec<(PathBuf, ReviewSession)>> {
+    #[cfg(feature = "sqlite")]
+    if let Some(conn) = sqlite_store() {
+        return crate::persistence::sqlite::list_all(&conn);
+    }
+    let entries = match fs::read_dir(get_reviews_dir()?) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(TuicrError::Io(e)),
+    };
+    let mut sessions: Vec<(PathBuf, ReviewSession)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        })
+        .filter_map(|path| load_session(&path).ok().map(|session| (path, session)))
+        .collect();
+    sessions.sort_by_key(|(_, session)| std::cmp::Reverse(session.updated_at));
+    Ok(sessions)
+}
+
 /// Look up the most recent persisted session for a PR keyed by forge identity,
 /// PR number, and head SHA. Returns `None` when no matching session exists.
 ///
@@ -207,6 +346,10 @@ pub fn load_session(path: &PathBuf) -> Result<ReviewSession> {
 /// other filename fields because reopening the same PR at the same head must
 /// restore the exact session that was last persisted for it.
 pub fn load_pr_session(key: &PrSessionKey) -> Result<Option<(PathBuf, ReviewSession)>> {
+    #[cfg(feature = "sqlite")]
+    if let Some(conn) = sqlite_store() {
+        return crate::persistence::sqlite::load_pr(&conn, key);
+    }
     let reviews_dir = get_reviews_dir()?;
     let entries = match fs::read_dir(&reviews_dir) {
         Ok(entries) => entries,
@@ -249,6 +392,23 @@ pub fn load_latest_session_for_context(
     diff_source: SessionDiffSource,
     commit_range: Option<&[String]>,
 ) -> Result<Option<(PathBuf, ReviewSession)>> {
+    #[cfg(feature = "sqlite")]
+    if let Some(conn) = sqlite_store() {
+        let candidates = crate::persistence::sqlite::recent_for_repo(
+            &conn,
+            repo_path,
+            diff_source,
+            session_max_age(),
+        )?;
+        return Ok(select_for_context(
+            candidates,
+            branch_name,
+            head_commit,
+            diff_source,
+            commit_range,
+        ));
+    }
+
     let current_repo_path = normalize_repo_path(repo_path);
     let current_fingerprint = repo_path_fingerprint(repo_path);
     let current_diff_source = match diff_source {
@@ -266,7 +426,7 @@ pub fn load_latest_session_for_context(
 
     let reviews_dir = get_reviews_dir()?;
     let now = SystemTime::now();
-    let max_age = Duration::from_secs(SESSION_MAX_AGE_DAYS * 24 * 60 * 60);
+    let max_age = session_max_age();
 
     let mut session_files: Vec<_> = fs::read_dir(&reviews_dir)?
         .filter_map(|entry| entry.ok())
@@ -281,8 +441,9 @@ pub fn load_latest_session_for_context(
                 return false;
             }
 
-            // Delete sessions older than 7 days
-            if let Ok(metadata) = entry.metadata()
+            // Delete sessions older than `session_max_age_days`
+            if let Some(max_age) = max_age
+                && let Ok(metadata) = entry.metadata()
                 && let Ok(modified) = metadata.modified()
                 && let Ok(age) = now.duration_since(modified)
                 && age > max_age
@@ -332,18 +493,35 @@ pub fn load_latest_session_for_context(
             .then_with(|| a.file_name().cmp(&b.file_name()))
     });
 
-    let mut legacy_candidate = None;
-
-    for entry in session_files {
+    let candidates = session_files.into_iter().filter_map(|entry| {
         let path = entry.path();
-        let Ok(session) = load_session(&path) else {
-            continue;
-        };
+        let session = load_session(&path).ok()?;
+        (normalize_repo_path(&session.repo_path) == current_repo_path).then_some((path, session))
+    });
 
-        if normalize_repo_path(&session.repo_path) != current_repo_path {
-            continue;
-        }
+    Ok(select_for_context(
+        candidates,
+        branch_name,
+        head_commit,
+        diff_source,
+        commit_range,
+    ))
+}
 
+/// Pick the session to resume from `candidates` (one repository's
+/// sessions, most recent first): the first one for the same diff source,
+/// commit range and branch, falling back to a branchless legacy session
+/// at the same commit.
+fn select_for_context(
+    candidates: impl IntoIterator<Item = (PathBuf, ReviewSession)>,
+    branch_name: Option<&str>,
+    head_commit: &str,
+    diff_source: SessionDiffSource,
+    commit_range: Option<&[String]>,
+) -> Option<(PathBuf, ReviewSession)> {
+    let mut legacy_candidate = None;
+
+    for (path, session) in candidates {
         if session.diff_source != diff_source {
             continue;
         }
@@ -365,7 +543,7 @@ pub fn load_latest_session_for_context(
                 continue;
             }
 
-            return Ok(Some((path, session)));
+            return Some((path, session));
         }
 
         let eligible_legacy = branch_name.is_some()
@@ -378,17 +556,22 @@ pub fn load_latest_session_for_context(
         }
     }
 
-    Ok(legacy_candidate)
+    legacy_candidate
 }
 
-#[cfg(test)]
-fn delete_session(path: &PathBuf) -> Result<()> {
+/// Remove a saved session, whether it is a JSON file or a database row.
+pub fn delete_session(path: &Path) -> Result<()> {
+    #[cfg(feature = "sqlite")]
+    if let Some(id) = crate::persistence::sqlite::session_id(path) {
+        let conn = crate::persistence::sqlite::open(path.parent().unwrap_or(path))?;
+        return crate::persistence::sqlite::delete(&conn, id);
+    }
     fs::remove_file(path)?;
     Ok(())
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::model::FileStatus;
     use std::path::PathBuf;
@@ -411,9 +594,9 @@ mod tests {
 
     static TEST_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 
-    struct TestReviewsDirGuard<'a> {
+    pub(crate) struct TestReviewsDirGuard<'a> {
         _lock: std::sync::MutexGuard<'a, ()>,
-        path: PathBuf,
+        pub(crate) path: PathBuf,
     }
 
     impl Drop for TestReviewsDirGuard<'_> {
@@ -425,7 +608,7 @@ mod tests {
         }
     }
 
-    fn with_test_reviews_dir() -> TestReviewsDirGuard<'static> {
+    pub(crate) fn with_test_reviews_dir() -> TestReviewsDirGuard<'static> {
         let lock = TEST_LOCK.get_or_init(|| Mutex::new(())).lock().unwrap();
         let path =
             std::env::temp_dir().join(format!("tuicr-reviews-test-{}", uuid::Uuid::new_v4()));
@@ -505,6 +688,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_list_sessions_for_repo_newest_first() {
+        let _guard = with_test_reviews_dir();
+        let repo = std::env::temp_dir().join(format!("tuicr-list-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&repo).unwrap();
+
+        let mut older = create_session(
+            repo.clone(),
+            "abc",
+            Some("main"),
+            SessionDiffSource::WorkingTree,
+            None,
+        );
+        older.updated_at = chrono::Utc::now() - chrono::Duration::hours(1);
+        let newer = create_session(
+            repo.clone(),
+            "def",
+            Some("main"),
+            SessionDiffSource::CommitRange,
+            Some(vec!["def".to_string()]),
+        );
+        let other = create_session(
+            PathBuf::from("/tmp/other-repo"),
+            "abc",
+            Some("main"),
+            SessionDiffSource::WorkingTree,
+            None,
+        );
+        save_session(&older).unwrap();
+        save_session(&newer).unwrap();
+        save_session(&other).unwrap();
+
+        let listed = list_sessions_for_repo(&repo).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|(_, s)| s.id.as_str()).collect();
+        assert_eq!(ids, vec![newer.id.as_str(), older.id.as_str()]);
+
+        let _ = fs::remove_dir_all(&repo);
+    }
+
     #[test]
     fn should_generate_correct_filename() {
         let session = create_test_session();
@@ -541,6 +763,28 @@ mod tests {
         let _ = delete_session(&path);
     }
 
+    #[test]
+    fn should_replace_saved_session_without_leaving_temp_files() {
+        // given
+        let guard = with_test_reviews_dir();
+        let mut session = create_test_session();
+        save_session(&session).unwrap();
+
+        // when
+        session.session_notes = Some("second save".to_string());
+        let path = save_session(&session).unwrap();
+
+        // then
+        let entries: Vec<_> = fs::read_dir(&guard.path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_none_or(|ext| ext != "lock"))
+            .collect();
+        assert_eq!(entries, vec![path.clone()]);
+        let loaded = load_session(&path).unwrap();
+        assert_eq!(loaded.session_notes.as_deref(), Some("second save"));
+    }
+
     #[test]
     fn should_sanitize_branch_name_in_filename() {
         let session = create_session(
@@ -1,13 +1,38 @@
+use std::cell::RefCell;
 use std::ffi::OsStr;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Default for `command_timeout`: long enough for a cold `git diff` in a
+/// huge repository, short enough that a hung `hg` server doesn't wedge the
+/// app for good.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Seconds a command may run before it is killed; `0` waits forever.
+static COMMAND_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_COMMAND_TIMEOUT.as_secs());
+static NEXT_COMMAND_ID: AtomicU64 = AtomicU64::new(0);
+/// `(id, label, started)` of every command currently waited on.
+static RUNNING: Mutex<Vec<(u64, String, Instant)>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// The token commands started on this thread answer to, if any.
+    static CANCEL_TOKEN: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CommandOutputErrorKind {
     NotFound,
     SpawnFailed,
     Unsuccessful,
+    /// Killed after running longer than the command timeout
+    TimedOut,
+    /// Killed through the `CancelToken` it ran under
+    Cancelled,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,23 +53,22 @@ where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
-    let mut command = Command::new(program);
-    if let Some(current_dir) = current_dir {
-        command.current_dir(current_dir);
-    }
+    run_command_output_with_timeout(program, current_dir, args, command_timeout())
+}
 
-    let output = command.args(args).output().map_err(|err| {
-        let kind = if err.kind() == std::io::ErrorKind::NotFound {
-            CommandOutputErrorKind::NotFound
-        } else {
-            CommandOutputErrorKind::SpawnFailed
-        };
-        CommandOutputError {
-            kind,
-            status: None,
-            stderr: err.to_string(),
-        }
-    })?;
+/// `run_command_output` with an explicit deadline instead of the configured
+/// one. `None` waits for as long as the command runs (or until cancelled).
+pub fn run_command_output_with_timeout<I, S>(
+    program: &str,
+    current_dir: Option<&Path>,
+    args: I,
+    timeout: Option<Duration>,
+) -> CommandOutputResult<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = spawn_and_wait(program, current_dir, args, timeout)?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).into_owned())
@@ -57,6 +81,92 @@ where
     }
 }
 
+/// Run a command on the user's terminal, so it can prompt (`git add -p`,
+/// `jj split`); the caller has to hand the terminal over first. No timeout
+/// applies since the command waits on the user. The exit code is `None` on
+/// success.
+pub fn run_command_in_terminal<I, S>(
+    program: &str,
+    current_dir: Option<&Path>,
+    args: I,
+) -> CommandOutputResult<Option<i32>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new(program);
+    if let Some(current_dir) = current_dir {
+        command.current_dir(current_dir);
+    }
+    // With `--stdout` the export owns stdout, so talk to the terminal itself
+    if let Ok(tty) = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        && let (Ok(stdout), Ok(stderr)) = (tty.try_clone(), tty.try_clone())
+    {
+        command
+            .stdin(Stdio::from(tty))
+            .stdout(Stdio::from(stdout))
+            .stderr(Stdio::from(stderr));
+    }
+    let status = command.args(args).status().map_err(spawn_error)?;
+    Ok((!status.success()).then(|| status.code().unwrap_or(-1)))
+}
+
+/// Split a command line (`:git`/`:jj` arguments, a diff filter) into
+/// arguments the way a shell would for simple cases: on whitespace, with
+/// `'...'` and `"..."` quoting. `None` when a quote is left open.
+pub fn split_command_args(line: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for ch in line.chars() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => current.get_or_insert_default().push(ch),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                current.get_or_insert_default();
+            }
+            (None, _) if ch.is_whitespace() => args.extend(current.take()),
+            (None, _) => current.get_or_insert_default().push(ch),
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    args.extend(current);
+    Some(args)
+}
+
+fn spawn_and_wait<I, S>(
+    program: &str,
+    current_dir: Option<&Path>,
+    args: I,
+    timeout: Option<Duration>,
+) -> CommandOutputResult<Output>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new(program);
+    if let Some(current_dir) = current_dir {
+        command.current_dir(current_dir);
+    }
+    let args: Vec<S> = args.into_iter().collect();
+    let label = command_label(program, &args);
+
+    let child = command
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+    wait_with_deadline(child, label, timeout)
+}
+
 /// Build the `stderr` field of a failed-command error from the child's
 /// stdout + stderr. `gh api` puts the JSON response body on stdout even on
 /// non-2xx, while stderr only carries a short status line — surfacing both
@@ -90,45 +200,34 @@ where
     if let Some(current_dir) = current_dir {
         command.current_dir(current_dir);
     }
+    let args: Vec<S> = args.into_iter().collect();
 
     let mut child = command
-        .args(args)
+        .args(&args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .map_err(|err| {
-            let kind = if err.kind() == std::io::ErrorKind::NotFound {
-                CommandOutputErrorKind::NotFound
-            } else {
-                CommandOutputErrorKind::SpawnFailed
-            };
-            CommandOutputError {
-                kind,
-                status: None,
-                stderr: err.to_string(),
-            }
-        })?;
-
-    // Write the stdin payload before waiting on stdout, then drop the handle
-    // so the child sees EOF and can finish.
-    if let Some(mut child_stdin) = child.stdin.take() {
-        child_stdin
-            .write_all(stdin.as_bytes())
-            .map_err(|err| CommandOutputError {
-                kind: CommandOutputErrorKind::SpawnFailed,
-                status: None,
-                stderr: err.to_string(),
-            })?;
-        // `drop(child_stdin)` happens when the value goes out of scope, which
-        // closes the pipe and signals EOF.
-    }
+        .map_err(spawn_error)?;
 
-    let output = child.wait_with_output().map_err(|err| CommandOutputError {
-        kind: CommandOutputErrorKind::SpawnFailed,
-        status: None,
-        stderr: err.to_string(),
-    })?;
+    // Write the stdin payload from a thread, then drop the handle so the
+    // child sees EOF and can finish. A child that streams its output (a
+    // diff filter) would otherwise fill the stdout pipe and block while
+    // this side is still writing.
+    let writer = child.stdin.take().map(|mut child_stdin| {
+        let payload = stdin.to_string();
+        std::thread::spawn(move || child_stdin.write_all(payload.as_bytes()))
+    });
+
+    let output = wait_with_deadline(child, command_label(program, &args), command_timeout());
+    if let Some(Ok(Err(err))) = writer.map(JoinHandle::join) {
+        return Err(CommandOutputError {
+            kind: CommandOutputErrorKind::SpawnFailed,
+            status: None,
+            stderr: err.to_string(),
+        });
+    }
+    let output = output?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).into_owned())
@@ -140,3 +239,329 @@ where
         })
     }
 }
+
+/// Set how long external commands may run before they are killed; `None`
+/// (config `command_timeout = 0`) disables the limit.
+pub fn set_command_timeout(timeout: Option<Duration>) {
+    let secs = timeout.map_or(0, |t| t.as_secs().max(1));
+    COMMAND_TIMEOUT_SECS.store(secs, Ordering::Relaxed);
+}
+
+fn command_timeout() -> Option<Duration> {
+    match COMMAND_TIMEOUT_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+/// Cancels the commands run under it with `with_cancel_token`, and no
+/// others: cancelling a reload must not kill a review submission or a
+/// `--pipe-to` command running meanwhile.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Kill the commands running under this token, and any started under
+    /// it later. Their callers get a `Cancelled` error.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Run `f` with the commands it starts on this thread cancellable through
+/// `token`.
+pub fn with_cancel_token<T>(token: CancelToken, f: impl FnOnce() -> T) -> T {
+    struct Reset(Option<CancelToken>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CANCEL_TOKEN.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+    let _reset = Reset(CANCEL_TOKEN.with(|current| current.replace(Some(token))));
+    f()
+}
+
+/// The longest-running command still in flight and how long it has run,
+/// e.g. `("hg diff", 4s)`, for progress in the status bar.
+pub fn oldest_running_command() -> Option<(String, Duration)> {
+    let running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    running
+        .iter()
+        .min_by_key(|(_, _, started)| *started)
+        .map(|(_, label, started)| (label.clone(), started.elapsed()))
+}
+
+/// A command in flight whose output the caller reads itself, such as one
+/// sent to the hg command server. Registered in `RUNNING` until dropped so
+/// the status bar shows it; `check` reports when it has outlived the
+/// timeout or was cancelled, and the caller then abandons it.
+pub struct TrackedCommand {
+    id: u64,
+    label: String,
+    cancel: Option<CancelToken>,
+    started: Instant,
+    timeout: Option<Duration>,
+}
+
+impl TrackedCommand {
+    /// Track `program args` under the configured `command_timeout`.
+    pub fn start<S: AsRef<OsStr>>(program: &str, args: &[S]) -> Self {
+        Self::with_timeout(command_label(program, args), command_timeout())
+    }
+
+    fn with_timeout(label: String, timeout: Option<Duration>) -> Self {
+        let cancel = CANCEL_TOKEN.with(|current| current.borrow().clone());
+        let started = Instant::now();
+        let id = NEXT_COMMAND_ID.fetch_add(1, Ordering::Relaxed);
+        RUNNING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((id, label.clone(), started));
+        Self {
+            id,
+            label,
+            cancel,
+            started,
+            timeout,
+        }
+    }
+
+    /// `Err` with `Cancelled` or `TimedOut` once the command should be
+    /// given up on.
+    pub fn check(&self) -> CommandOutputResult<()> {
+        let label = &self.label;
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            return Err(CommandOutputError {
+                kind: CommandOutputErrorKind::Cancelled,
+                status: None,
+                stderr: format!("`{label}` cancelled"),
+            });
+        }
+        if let Some(timeout) = self.timeout.filter(|t| self.started.elapsed() >= *t) {
+            return Err(CommandOutputError {
+                kind: CommandOutputErrorKind::TimedOut,
+                status: None,
+                stderr: format!("`{label}` timed out after {timeout:?}"),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TrackedCommand {
+    fn drop(&mut self) {
+        RUNNING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(running, _, _)| *running != self.id);
+    }
+}
+
+/// `program` plus its subcommand (the first argument that isn't a flag).
+fn command_label<S: AsRef<OsStr>>(program: &str, args: &[S]) -> String {
+    let subcommand = args
+        .iter()
+        .map(|arg| arg.as_ref().to_string_lossy())
+        .find(|arg| !arg.starts_with('-') && !arg.contains('='));
+    match subcommand {
+        Some(subcommand) => format!("{program} {subcommand}"),
+        None => program.to_string(),
+    }
+}
+
+fn spawn_error(err: std::io::Error) -> CommandOutputError {
+    let kind = if err.kind() == std::io::ErrorKind::NotFound {
+        CommandOutputErrorKind::NotFound
+    } else {
+        CommandOutputErrorKind::SpawnFailed
+    };
+    CommandOutputError {
+        kind,
+        status: None,
+        stderr: err.to_string(),
+    }
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> Option<JoinHandle<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            buf
+        })
+    })
+}
+
+/// Wait for `child` while draining its pipes, killing it once `timeout`
+/// passes or the `CancelToken` it runs under is cancelled. Registered in `RUNNING`
+/// for the duration so the UI can show what it's waiting on.
+fn wait_with_deadline(
+    mut child: Child,
+    label: String,
+    timeout: Option<Duration>,
+) -> CommandOutputResult<Output> {
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+    let tracked = TrackedCommand::with_timeout(label, timeout);
+
+    // Most commands finish in a few milliseconds; back off so slow ones
+    // don't spin.
+    let mut poll = Duration::from_millis(1);
+    let result = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) => {}
+            Err(err) => {
+                break Err(CommandOutputError {
+                    kind: CommandOutputErrorKind::SpawnFailed,
+                    status: None,
+                    stderr: err.to_string(),
+                });
+            }
+        }
+        if let Err(err) = tracked.check() {
+            break Err(err);
+        }
+        std::thread::sleep(poll);
+        poll = (poll * 2).min(Duration::from_millis(25));
+    };
+    drop(tracked);
+
+    match result {
+        Ok(status) => {
+            let join = |handle: Option<JoinHandle<Vec<u8>>>| {
+                handle
+                    .and_then(|handle| handle.join().ok())
+                    .unwrap_or_default()
+            };
+            Ok(Output {
+                status,
+                stdout: join(stdout),
+                stderr: join(stderr),
+            })
+        }
+        Err(err) => {
+            // The reader threads end once the killed child's pipes close;
+            // they are not joined in case a grandchild still holds them.
+            let _ = child.kill();
+            let _ = child.wait();
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_return_stdout_of_successful_command() {
+        // when
+        let output = run_command_output_with_timeout(
+            "sh",
+            None,
+            ["-c", "echo out; echo err >&2"],
+            Some(Duration::from_secs(10)),
+        );
+
+        // then
+        assert_eq!(output.unwrap(), "out\n");
+    }
+
+    #[test]
+    fn should_report_the_exit_code_of_a_command_run_in_the_terminal() {
+        // when
+        let failed = run_command_in_terminal("sh", None, ["-c", "exit 3"]);
+        let succeeded = run_command_in_terminal("sh", None, ["-c", "exit 0"]);
+        let missing = run_command_in_terminal("tuicr-no-such-command", None, ["x"]);
+
+        // then
+        assert_eq!(failed.unwrap(), Some(3));
+        assert_eq!(succeeded.unwrap(), None);
+        assert_eq!(missing.unwrap_err().kind, CommandOutputErrorKind::NotFound);
+    }
+
+    #[test]
+    fn should_kill_command_that_outlives_its_timeout() {
+        // given
+        let started = Instant::now();
+
+        // when
+        let error = run_command_output_with_timeout(
+            "sh",
+            None,
+            ["-c", "sleep 10"],
+            Some(Duration::from_millis(200)),
+        )
+        .unwrap_err();
+
+        // then
+        assert_eq!(error.kind, CommandOutputErrorKind::TimedOut);
+        assert_eq!(error.stderr, "`sh sleep 10` timed out after 200ms");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn should_label_commands_by_program_and_subcommand() {
+        assert_eq!(
+            command_label("hg", &["--config", "ui.color=never", "diff", "-r", "."]),
+            "hg diff"
+        );
+        assert_eq!(
+            command_label("git", &["-c", "core.quotepath=on", "show"]),
+            "git show"
+        );
+    }
+
+    #[test]
+    fn should_cancel_only_the_commands_run_under_the_token() {
+        // given
+        let token = CancelToken::new();
+        let reload = {
+            let token = token.clone();
+            std::thread::spawn(move || {
+                with_cancel_token(token, || {
+                    run_command_output_with_timeout("sleep", None, ["5"], None)
+                })
+            })
+        };
+        let submission = std::thread::spawn(|| {
+            run_command_output_with_timeout("sh", None, ["-c", "sleep 0.3; echo done"], None)
+        });
+        std::thread::sleep(Duration::from_millis(100));
+
+        // when
+        token.cancel();
+
+        // then
+        assert_eq!(
+            reload.join().unwrap().unwrap_err().kind,
+            CommandOutputErrorKind::Cancelled
+        );
+        assert_eq!(submission.join().unwrap().unwrap(), "done\n");
+    }
+
+    #[test]
+    fn should_split_command_args_on_whitespace_and_quotes() {
+        assert_eq!(
+            split_command_args(r#"describe -m "fix: a thing" -r '@-'"#),
+            Some(vec![
+                "describe".to_string(),
+                "-m".to_string(),
+                "fix: a thing".to_string(),
+                "-r".to_string(),
+                "@-".to_string(),
+            ])
+        );
+        assert_eq!(split_command_args(r#"commit -m """#).unwrap()[2], "");
+        assert_eq!(split_command_args("log -m 'open"), None);
+    }
+}
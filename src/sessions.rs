@@ -0,0 +1,247 @@
+//! `tuicr sessions gc`: prune saved sessions that can't be resumed any
+//! more — their base commit is gone from the repository (rebased away,
+//! or the checkout deleted) — or that haven't been updated in
+//! `session_max_age_days` days, so the reviews directory doesn't grow
+//! forever. Sessions another running tuicr has open are left alone.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::load_config;
+use crate::model::ReviewSession;
+use crate::persistence::location::{self, SessionLocation};
+use crate::persistence::lock::{self, LockState};
+use crate::persistence::storage::{
+    DEFAULT_SESSION_MAX_AGE_DAYS, delete_session, list_all_sessions, set_sqlite_store,
+};
+
+/// `tuicr sessions gc` arguments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcArgs {
+    /// Days a session may go untouched, instead of `session_max_age_days`
+    pub max_age_days: Option<u64>,
+    /// List what would be removed without removing it
+    pub dry_run: bool,
+}
+
+/// Why a session is pruned
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Stale {
+    /// Not updated in this many days
+    Untouched(i64),
+    /// The base commit no longer exists in the repository
+    BaseCommitGone,
+    /// The repository itself is gone
+    RepositoryGone,
+}
+
+impl Stale {
+    fn describe(&self) -> String {
+        match self {
+            Stale::Untouched(days) => format!("not updated in {days} days"),
+            Stale::BaseCommitGone => "base commit no longer exists".to_string(),
+            Stale::RepositoryGone => "repository no longer exists".to_string(),
+        }
+    }
+}
+
+/// Whether `session`'s base commit is gone. Only answerable for local
+/// sessions of git (or colocated jj) repositories with a full commit id;
+/// anything else is assumed to still exist.
+fn base_commit_gone(session: &ReviewSession) -> Option<Stale> {
+    if session.pr_session_key.is_some() {
+        return None;
+    }
+    if !session.repo_path.exists() {
+        return Some(Stale::RepositoryGone);
+    }
+    // Short ids parse as zero-padded prefixes, which would never be found
+    if session.base_commit.len() != 40 {
+        return None;
+    }
+    let oid = git2::Oid::from_str(&session.base_commit).ok()?;
+    let repo = git2::Repository::open(&session.repo_path).ok()?;
+    match repo.find_object(oid, None) {
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Some(Stale::BaseCommitGone),
+        _ => None,
+    }
+}
+
+/// Why `session` should be pruned as of `now`, if it should
+fn stale_reason(
+    session: &ReviewSession,
+    now: DateTime<Utc>,
+    max_age: Option<Duration>,
+) -> Option<Stale> {
+    let age = now - session.updated_at;
+    if let Some(max_age) = max_age
+        && age > max_age
+    {
+        return Some(Stale::Untouched(age.num_days()));
+    }
+    base_commit_gone(session)
+}
+
+/// `tuicr` repo · main @ abc1234, for the report
+fn describe_session(session: &ReviewSession) -> String {
+    let repo = match &session.pr_session_key {
+        Some(key) => format!("{} #{}", key.repository.display_name(), key.number),
+        None => session.repo_path.display().to_string(),
+    };
+    let base: String = session.base_commit.chars().take(7).collect();
+    format!(
+        "{repo} · {} @ {base}",
+        session.branch_name.as_deref().unwrap_or("detached")
+    )
+}
+
+/// Point the session store at where the config keeps sessions, the way
+/// startup does, and return the configured maximum age in days.
+fn apply_config() -> u64 {
+    let config = load_config().ok().and_then(|outcome| outcome.config);
+    let config = config.as_ref();
+    if let Some(store) = config.and_then(|cfg| cfg.session_store.as_deref()) {
+        set_sqlite_store(store == "sqlite");
+    }
+    let session_location = config
+        .and_then(|cfg| cfg.session_location.as_deref())
+        .and_then(SessionLocation::from_id);
+    if let Some(dir) = session_location
+        .filter(|loc| *loc != SessionLocation::Data)
+        .zip(std::env::current_dir().ok())
+        .and_then(|(loc, cwd)| loc.dir(&cwd))
+    {
+        location::install(dir);
+    }
+    config
+        .and_then(|cfg| cfg.session_max_age_days)
+        .map_or(DEFAULT_SESSION_MAX_AGE_DAYS, |days| days as u64)
+}
+
+/// Prune stale sessions, print what was removed and return the process
+/// exit code: 0, or 2 when sessions can't be listed or removed.
+pub fn run_gc(args: &GcArgs) -> i32 {
+    let configured = apply_config();
+    let max_age_days = args.max_age_days.unwrap_or(configured);
+    let max_age = (max_age_days > 0).then(|| Duration::days(max_age_days as i64));
+    let sessions = match list_all_sessions() {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("tuicr sessions gc: {e}");
+            return 2;
+        }
+    };
+    let now = Utc::now();
+    let total = sessions.len();
+    let mut pruned = Vec::new();
+    let mut open = 0;
+    let mut failed = false;
+    for (path, session) in sessions {
+        let Some(reason) = stale_reason(&session, now, max_age) else {
+            continue;
+        };
+        if matches!(
+            lock::inspect(&session.id),
+            Ok(LockState::Held(_) | LockState::Unreadable)
+        ) {
+            open += 1;
+            continue;
+        }
+        if !args.dry_run
+            && let Err(e) = delete_session(&path)
+        {
+            eprintln!("tuicr sessions gc: can't remove {}: {e}", path.display());
+            failed = true;
+            continue;
+        }
+        pruned.push(format!(
+            "{} ({})",
+            describe_session(&session),
+            reason.describe()
+        ));
+    }
+    let verb = if args.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    for line in &pruned {
+        println!("{verb} {line}");
+    }
+    println!(
+        "{verb} {} of {total} saved session{}{}",
+        pruned.len(),
+        if total == 1 { "" } else { "s" },
+        if open > 0 {
+            format!("; kept {open} open in another tuicr")
+        } else {
+            String::new()
+        }
+    );
+    if failed { 2 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::review::SessionDiffSource;
+    use std::path::PathBuf;
+
+    fn session_at(repo: PathBuf, base_commit: String, updated_at: DateTime<Utc>) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            repo,
+            base_commit,
+            Some("main".to_string()),
+            SessionDiffSource::WorkingTree,
+        );
+        session.updated_at = updated_at;
+        session
+    }
+
+    #[test]
+    fn should_prune_sessions_that_are_untouched_or_lost_their_base_commit() {
+        // given
+        let temp_dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(temp_dir.path()).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let head = repo
+            .commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap()
+            .to_string();
+        let repo_path = temp_dir.path().to_path_buf();
+        let rebased_away = "0123456789abcdef0123456789abcdef01234567".to_string();
+        let now = Utc::now();
+        let (recent, old) = (now - Duration::days(1), now - Duration::days(30));
+
+        // when
+        let reason = |session: ReviewSession| stale_reason(&session, now, Some(Duration::days(7)));
+
+        // then
+        assert_eq!(
+            reason(session_at(repo_path.clone(), head.clone(), recent)),
+            None
+        );
+        assert_eq!(
+            reason(session_at(repo_path.clone(), head.clone(), old)),
+            Some(Stale::Untouched(30))
+        );
+        assert_eq!(
+            reason(session_at(repo_path.clone(), rebased_away, recent)),
+            Some(Stale::BaseCommitGone)
+        );
+        assert_eq!(
+            reason(session_at(
+                temp_dir.path().join("deleted"),
+                head.clone(),
+                recent
+            )),
+            Some(Stale::RepositoryGone)
+        );
+        assert_eq!(
+            stale_reason(&session_at(repo_path, head, old), now, None),
+            None
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! `tuicr stats`: a summary of the completed reviews recorded with
+//! `review_metrics = true` — how many per week, how long they took per
+//! thousand changed lines, and which files drew the most comments.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+
+use crate::model::review::format_duration;
+use crate::persistence::metrics::{ReviewMetrics, load_metrics, metrics_path};
+use crate::persistence::storage::normalize_repo_path;
+
+/// How many files the report lists
+const TOP_FILES: usize = 10;
+
+/// `tuicr stats` arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsArgs {
+    /// Weeks shown in the per-week table, ending with the current one
+    pub weeks: usize,
+    /// Only count reviews of this repository
+    pub repo: Option<PathBuf>,
+}
+
+impl Default for StatsArgs {
+    fn default() -> Self {
+        Self {
+            weeks: 8,
+            repo: None,
+        }
+    }
+}
+
+/// Monday of the week `at` falls in, in local time
+fn week_of(at: DateTime<Utc>) -> NaiveDate {
+    let date = at.with_timezone(&Local).date_naive();
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// The report lines for `metrics` as of `now`
+fn report(metrics: &[ReviewMetrics], weeks: usize, now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut repos: Vec<&str> = metrics.iter().map(|m| m.repo.as_str()).collect();
+    repos.sort_unstable();
+    repos.dedup();
+    let total = |field: fn(&ReviewMetrics) -> usize| metrics.iter().map(field).sum::<usize>();
+    let changed = total(|m| m.lines_changed);
+    lines.push(format!(
+        "{} reviews in {} {}: {changed} lines changed, {} comments ({} issues)",
+        metrics.len(),
+        repos.len(),
+        if repos.len() == 1 {
+            "repository"
+        } else {
+            "repositories"
+        },
+        total(|m| m.comments),
+        total(|m| m.issues),
+    ));
+    let active: u64 = metrics.iter().map(|m| m.active_secs).sum();
+    if changed > 0 {
+        lines.push(format!(
+            "Average time per kLoC: {} ({} reviewing in total)",
+            format_duration(active * 1000 / changed as u64),
+            format_duration(active)
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push("Reviews per week".to_string());
+    let this_week = week_of(now);
+    let mut per_week: HashMap<NaiveDate, usize> = HashMap::new();
+    for m in metrics {
+        *per_week.entry(week_of(m.completed_at)).or_default() += 1;
+    }
+    for back in (0..weeks).rev() {
+        let week = this_week - Duration::weeks(back as i64);
+        let count = per_week.get(&week).copied().unwrap_or(0);
+        let iso = week.iso_week();
+        lines.push(format!(
+            "  {}-W{:02}  {count:>3}  {}",
+            iso.year(),
+            iso.week(),
+            "#".repeat(count)
+        ));
+    }
+
+    let mut by_file: HashMap<String, usize> = HashMap::new();
+    for m in metrics {
+        for (path, count) in &m.comments_by_file {
+            let label = match Path::new(&m.repo).file_name() {
+                Some(repo) if repos.len() > 1 => {
+                    format!("{}/{}", repo.to_string_lossy(), path.display())
+                }
+                _ => path.display().to_string(),
+            };
+            *by_file.entry(label).or_default() += count;
+        }
+    }
+    if !by_file.is_empty() {
+        let mut files: Vec<_> = by_file.into_iter().collect();
+        files.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+        lines.push(String::new());
+        lines.push("Most-commented files".to_string());
+        for (label, count) in files.into_iter().take(TOP_FILES) {
+            lines.push(format!("  {count:>4}  {label}"));
+        }
+    }
+    lines
+}
+
+/// Print the report and return the process exit code: 0, or 2 when the
+/// metrics can't be read.
+pub fn run(args: &StatsArgs) -> i32 {
+    let metrics = match metrics_path().and_then(|path| load_metrics(&path)) {
+        Ok(metrics) => metrics,
+        Err(e) => {
+            eprintln!("tuicr stats: {e}");
+            return 2;
+        }
+    };
+    let repo = args.repo.as_deref().map(normalize_repo_path);
+    let metrics: Vec<ReviewMetrics> = metrics
+        .into_iter()
+        .filter(|m| repo.as_ref().is_none_or(|repo| m.repo == *repo))
+        .collect();
+    if metrics.is_empty() {
+        println!(
+            "No completed reviews recorded yet. Set `review_metrics = true` in the config \
+             to record them."
+        );
+        return 0;
+    }
+    for line in report(&metrics, args.weeks, Utc::now()) {
+        println!("{line}");
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn metrics(repo: &str, days_ago: i64, file: &str, comments: usize) -> ReviewMetrics {
+        let completed_at = Utc::now() - Duration::days(days_ago);
+        ReviewMetrics {
+            session_id: format!("{repo}-{days_ago}"),
+            repo: repo.to_string(),
+            started_at: completed_at,
+            completed_at,
+            active_secs: 30 * 60,
+            files: 1,
+            lines_changed: 500,
+            comments,
+            issues: 1,
+            comments_by_file: BTreeMap::from([(PathBuf::from(file), comments)]),
+        }
+    }
+
+    #[test]
+    fn should_report_weekly_counts_pace_and_most_commented_files() {
+        // given
+        let all = vec![
+            metrics("/src/a", 0, "lib.rs", 2),
+            metrics("/src/a", 0, "main.rs", 1),
+            metrics("/src/b", 7, "lib.rs", 4),
+        ];
+
+        // when
+        let lines = report(&all, 3, Utc::now());
+
+        // then
+        assert_eq!(
+            lines[0],
+            "3 reviews in 2 repositories: 1500 lines changed, 7 comments (3 issues)"
+        );
+        assert_eq!(
+            lines[1],
+            "Average time per kLoC: 1h 00m (1h 30m reviewing in total)"
+        );
+        assert!(lines[4].ends_with("  0  "));
+        assert!(lines[5].ends_with("  1  #"));
+        assert!(lines[6].ends_with("  2  ##"));
+        assert_eq!(
+            &lines[9..],
+            ["     4  b/lib.rs", "     2  a/lib.rs", "     1  a/main.rs"]
+        );
+    }
+}
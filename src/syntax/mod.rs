@@ -86,7 +86,8 @@ impl SyntaxHighlighter {
 
     /// Highlight all lines in a file's content.
     ///
-    /// Returns `None` when no syntax can be resolved for the file (by path or shebang).
+    /// Returns `None` when no syntax can be resolved for the file (by its
+    /// `[filetypes]` rule, path or shebang), or the rule turns highlighting off.
     /// Otherwise returns one entry per input line:
     /// - `Some(spans)` if that line was highlighted successfully (including empty spans)
     /// - `None` if highlighting failed for that specific line
@@ -98,7 +99,11 @@ impl SyntaxHighlighter {
         use syntect::easy::HighlightLines;
 
         // Get syntax definition
-        let syntax = self.get_syntax(file_path).or_else(|| {
+        let named = match crate::filetypes::rules().syntax(file_path) {
+            Some(name) => self.named_syntax(name)?,
+            None => None,
+        };
+        let syntax = named.or_else(|| self.get_syntax(file_path)).or_else(|| {
             lines
                 .first()
                 .and_then(|line| self.syntax_set.find_syntax_by_first_line(line))
@@ -264,6 +269,20 @@ impl SyntaxHighlighter {
         }
     }
 
+    /// The syntax a `[filetypes]` rule names, by name (`Markdown`) or
+    /// extension (`md`). `None` for `"none"`, which turns highlighting off;
+    /// `Some(None)` for a name no syntax matches, so the path decides.
+    fn named_syntax(&self, name: &str) -> Option<Option<&syntect::parsing::SyntaxReference>> {
+        if name.eq_ignore_ascii_case("none") {
+            return None;
+        }
+        Some(
+            self.syntax_set
+                .find_syntax_by_name(name)
+                .or_else(|| self.syntax_set.find_syntax_by_token(name)),
+        )
+    }
+
     /// Resolve syntax from a file path using this lookup order:
     /// extension -> lowercase extension (when different) -> fallback extension ->
     /// filename token -> filename name -> fallback filename.
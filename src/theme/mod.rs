@@ -2,18 +2,27 @@
 //!
 //! Provides dark and light themes with automatic terminal background detection.
 
-use std::{process::Command, sync::OnceLock};
+use std::{
+    process::Command,
+    sync::{Arc, OnceLock},
+};
 
 use ratatui::style::Color;
 use two_face::theme::EmbeddedThemeName;
 
+use crate::check::{CheckArgs, CheckCriterion};
 use crate::config::config_path_hint;
+use crate::history::HistoryArgs;
+use crate::hook::{HookInstall, HookKind};
+use crate::sessions::GcArgs;
+use crate::stats::StatsArgs;
 use crate::syntax::SyntaxHighlighter;
 
 /// Complete color theme for the application
 pub struct Theme {
-    /// Cached syntax highlighter (lazily initialized)
-    highlighter: OnceLock<SyntaxHighlighter>,
+    /// Cached syntax highlighter (lazily initialized), shared with reload
+    /// worker threads
+    highlighter: OnceLock<Arc<SyntaxHighlighter>>,
 
     // Base colors
     pub panel_bg: Color,
@@ -1504,16 +1513,46 @@ pub struct CliArgs {
     pub output_to_stdout: bool,
     /// Skip checking for updates on startup
     pub no_update_check: bool,
+    /// Refuse any action that modifies the repository
+    pub safe: bool,
+    /// Refuse any change to the review session
+    pub readonly: bool,
     /// Commit/revision range to review
     pub revisions: Option<String>,
+    /// jj revset or hg revision whose combined diff to review
+    pub revset: Option<String>,
+    /// Older version of the `-r` patch series to interdiff against
+    pub interdiff: Option<String>,
     /// Skip commit selector and review uncommitted changes directly
     pub working_tree: bool,
     /// Filter diff to a specific file or directory path
     pub path_filter: Option<String>,
     /// Open a single file for annotation (no VCS required)
     pub file_path: Option<String>,
-    /// Direct pull request target from `tuicr pr <target>`.
+    /// Review a unified diff file (`-` reads stdin)
+    pub patch_path: Option<String>,
+    /// Direct pull request target from `tuicr pr <target>` or `--pr <target>`.
     pub pr_target: Option<String>,
+    /// Name of a `[templates.<name>]` config section to start the review from
+    pub template: Option<String>,
+    /// External command the diff is colored through (`delta --color-only`)
+    pub pipe_to: Option<String>,
+    /// `tuicr hook install [pre-commit|pre-push] [--force]`
+    pub hook_install: Option<HookInstall>,
+    /// Set by an installed hook: exit non-zero unless the review is complete
+    pub hook: Option<HookKind>,
+    /// `tuicr check --session <file> [--require ...] [--forbid ...]`
+    pub check: Option<CheckArgs>,
+    /// `tuicr stats [--weeks <n>] [--repo <path>]`
+    pub stats: Option<StatsArgs>,
+    /// `tuicr history [--repo <path>]` or `tuicr history <n>`
+    pub history: Option<HistoryArgs>,
+    /// `tuicr sessions gc [--max-age <days>] [--dry-run]`
+    pub sessions_gc: Option<GcArgs>,
+    /// `tuicr doctor`: check the environment and exit
+    pub doctor: bool,
+    /// `tuicr view <file>`: a session file or JSON export to open read-only
+    pub view: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1833,8 +1872,21 @@ pub fn resolve_theme_with_config(
 impl Theme {
     /// Get the syntax highlighter for this theme (lazily initialized, cached)
     pub fn syntax_highlighter(&self) -> &SyntaxHighlighter {
+        self.shared_highlighter()
+    }
+
+    /// The same highlighter, for use off the UI thread.
+    pub fn shared_syntax_highlighter(&self) -> Arc<SyntaxHighlighter> {
+        Arc::clone(self.shared_highlighter())
+    }
+
+    fn shared_highlighter(&self) -> &Arc<SyntaxHighlighter> {
         self.highlighter.get_or_init(|| {
-            SyntaxHighlighter::new(self.syntect_theme, self.syntax_add_bg, self.syntax_del_bg)
+            Arc::new(SyntaxHighlighter::new(
+                self.syntect_theme,
+                self.syntax_add_bg,
+                self.syntax_del_bg,
+            ))
         })
     }
 
@@ -1881,13 +1933,25 @@ fn print_help() -> ! {
     let valid_values = ThemeArg::valid_values_display();
     let appearance_values = AppearanceArg::valid_values_display();
     let config_path = config_path_hint();
+    let skip = crate::hook::SKIP_ENV;
     println!(
         "tuicr - A code review TUI with vim keybindings. Export to GitHub or clipboard.
 
 Usage: {name} [OPTIONS]
+       {name} hook install [pre-commit|pre-push] [--force]
+       {name} check --session <FILE> [--require all-reviewed] [--forbid unresolved-issues|deferred-lines]
+       {name} stats [--weeks <N>] [--repo <PATH>]
+       {name} history [--repo <PATH>] | {name} history <N>
+       {name} sessions gc [--max-age <DAYS>] [--dry-run]
+       {name} view <FILE>
+       {name} doctor
 
 Options:
   -r, --revisions <REVSET>  Commit range/Revset to review (syntax depends on VCS backend)
+  --revset <REVSET>      Review the combined diff of a jj revset (e.g. '@--::@') or an
+                         hg revision (`hg diff -c`); not supported for git
+  --interdiff <OLD>      With -r NEW, show only what changed between two versions of a
+                         patch series, e.g. `-r main..v2 --interdiff main..v1` (git)
   --theme <THEME>        Color theme to use
                           Valid values: {valid_values}
   --appearance <MODE>    Appearance mode for default theme
@@ -1898,11 +1962,41 @@ Options:
   -w, --working-tree     Include uncommitted changes (skip commit selector when used alone,
                          combine with commits when used with -r)
   --file <PATH>          Open a file for annotation (no VCS required)
+  --patch <FILE>         Review a unified diff file; `-` or piped stdin reads the diff from stdin
+  --pr <TARGET>          Review a GitHub PR through `gh` without checking it out (same as
+                         `{name} pr <TARGET>`); <number>, <owner/repo#N>, or a PR URL
+  -t, --template <NAME>  Start from a [templates.<NAME>] section in {config_path}
+  --pipe-to <CMD>        Color the diff with a line-preserving filter such as
+                         'delta --color-only' or 'diff-so-fancy --patch'
   --stdout               Output to stdout instead of clipboard when exporting
   --no-update-check      Skip checking for updates on startup
+  --safe                 Read-only: disable actions that modify the repository (e.g. :stage)
+  --readonly             Browse the review without changing or saving the session
+  --hook <HOOK>          Run as an installed git hook: exit 1 unless every file is reviewed
+                         and no ISSUE comment is open (set by `{name} hook install`)
   -V, --version          Print version
   -h, --help             Print this help message
 
+Commands:
+  hook install           Install a git hook (default pre-commit) that opens tuicr for
+                         self-review and blocks the commit or push until the review is
+                         complete; --force replaces an existing hook, {skip}=1 skips it
+  check                  Evaluate a saved session or `:export <file>.json` export for CI:
+                         prints one line per criterion and exits 1 if any fails (both
+                         criteria apply when none is given)
+  stats                  Summarize the completed reviews recorded with review_metrics =
+                         true: reviews per week (last 8 by default), time per kLoC and
+                         the most-commented files; --repo limits it to one repository
+  history                List the completed reviews archived for this repository (or
+                         --repo); `history <N>` reopens the Nth one read-only
+  sessions gc            Remove saved sessions whose base commit or repository is gone, or
+                         not updated in session_max_age_days days (or --max-age; 0 never);
+                         --dry-run only lists them
+  view                   Open a session file or `:export <file>.json` export read-only,
+                         with the diff it reviewed and its comments
+  doctor                 Check the VCS and forge CLIs, terminal, config, clipboard and
+                         session directory, and print a fix for each problem found
+
 Press ? in the application for keybinding help."
     );
     std::process::exit(0);
@@ -1940,6 +2034,127 @@ fn parse_cli_args_from(args: &[String]) -> Result<CliArgs, String> {
         cli_args.pr_target = Some(target.clone());
     }
 
+    // Subcommand form: `tuicr hook install [pre-commit|pre-push] [--force]`.
+    if args.len() >= 2 && args[1] == "hook" {
+        const USAGE: &str = "usage: tuicr hook install [pre-commit|pre-push] [--force]";
+        if args.get(2).map(String::as_str) != Some("install") {
+            return Err(USAGE.to_string());
+        }
+        let mut install = HookInstall {
+            kind: HookKind::PreCommit,
+            force: false,
+        };
+        for arg in &args[3..] {
+            if arg == "--force" {
+                install.force = true;
+            } else {
+                install.kind = HookKind::from_name(arg).ok_or_else(|| USAGE.to_string())?;
+            }
+        }
+        cli_args.hook_install = Some(install);
+        return Ok(cli_args);
+    }
+
+    // Subcommand form: `tuicr check --session <file> [--require all-reviewed]
+    // [--forbid unresolved-issues|deferred-lines]`.
+    if args.len() >= 2 && args[1] == "check" {
+        const USAGE: &str = "usage: tuicr check --session <file> [--require all-reviewed] \
+                             [--forbid unresolved-issues|deferred-lines]";
+        let mut session = None;
+        let mut criteria = Vec::new();
+        let mut rest = args[2..].iter();
+        while let Some(arg) = rest.next() {
+            let value = rest.next().ok_or_else(|| USAGE.to_string())?;
+            match arg.as_str() {
+                "--session" => session = Some(value.into()),
+                "--require" | "--forbid" => criteria.push(
+                    CheckCriterion::from_name(value, arg == "--forbid")
+                        .ok_or_else(|| USAGE.to_string())?,
+                ),
+                _ => return Err(USAGE.to_string()),
+            }
+        }
+        let session = session.ok_or_else(|| USAGE.to_string())?;
+        cli_args.check = Some(CheckArgs { session, criteria });
+        return Ok(cli_args);
+    }
+
+    // Subcommand form: `tuicr stats [--weeks <n>] [--repo <path>]`.
+    if args.len() >= 2 && args[1] == "stats" {
+        const USAGE: &str = "usage: tuicr stats [--weeks <n>] [--repo <path>]";
+        let mut stats = StatsArgs::default();
+        let mut rest = args[2..].iter();
+        while let Some(arg) = rest.next() {
+            let value = rest.next().ok_or_else(|| USAGE.to_string())?;
+            match arg.as_str() {
+                "--weeks" => stats.weeks = value.parse().map_err(|_| USAGE.to_string())?,
+                "--repo" => stats.repo = Some(value.into()),
+                _ => return Err(USAGE.to_string()),
+            }
+        }
+        cli_args.stats = Some(stats);
+        return Ok(cli_args);
+    }
+
+    // Subcommand form: `tuicr history [--repo <path>]` or `tuicr history <n>`.
+    if args.len() >= 2 && args[1] == "history" {
+        const USAGE: &str = "usage: tuicr history [--repo <path>] | tuicr history <n>";
+        let history = match &args[2..] {
+            [] => HistoryArgs::default(),
+            [flag, repo] if flag == "--repo" => HistoryArgs {
+                repo: Some(repo.into()),
+                open: None,
+            },
+            [n] => HistoryArgs {
+                repo: None,
+                open: Some(n.parse().map_err(|_| USAGE.to_string())?),
+            },
+            _ => return Err(USAGE.to_string()),
+        };
+        cli_args.history = Some(history);
+        return Ok(cli_args);
+    }
+
+    // Subcommand form: `tuicr sessions gc [--max-age <days>] [--dry-run]`.
+    if args.len() >= 2 && args[1] == "sessions" {
+        const USAGE: &str = "usage: tuicr sessions gc [--max-age <days>] [--dry-run]";
+        if args.get(2).map(String::as_str) != Some("gc") {
+            return Err(USAGE.to_string());
+        }
+        let mut gc = GcArgs::default();
+        let mut rest = args[3..].iter();
+        while let Some(arg) = rest.next() {
+            match arg.as_str() {
+                "--dry-run" => gc.dry_run = true,
+                "--max-age" => {
+                    let value = rest.next().ok_or_else(|| USAGE.to_string())?;
+                    gc.max_age_days = Some(value.parse().map_err(|_| USAGE.to_string())?);
+                }
+                _ => return Err(USAGE.to_string()),
+            }
+        }
+        cli_args.sessions_gc = Some(gc);
+        return Ok(cli_args);
+    }
+
+    // Subcommand form: `tuicr doctor`.
+    if args.len() >= 2 && args[1] == "doctor" {
+        if args.len() > 2 {
+            return Err("usage: tuicr doctor".to_string());
+        }
+        cli_args.doctor = true;
+        return Ok(cli_args);
+    }
+
+    // Subcommand form: `tuicr view <file>`.
+    if args.len() >= 2 && args[1] == "view" {
+        match &args[2..] {
+            [file] if !file.starts_with('-') => cli_args.view = Some(file.into()),
+            _ => return Err("usage: tuicr view <file>".to_string()),
+        }
+        return Ok(cli_args);
+    }
+
     for i in 0..args.len() {
         // Handle --version / -V
         if args[i] == "--version" || args[i] == "-V" {
@@ -1961,6 +2176,16 @@ fn parse_cli_args_from(args: &[String]) -> Result<CliArgs, String> {
             cli_args.no_update_check = true;
         }
 
+        // Handle --safe
+        if args[i] == "--safe" {
+            cli_args.safe = true;
+        }
+
+        // Handle --readonly
+        if args[i] == "--readonly" {
+            cli_args.readonly = true;
+        }
+
         // Handle -w / --working-tree
         if args[i] == "-w" || args[i] == "--working-tree" {
             cli_args.working_tree = true;
@@ -2061,6 +2286,89 @@ fn parse_cli_args_from(args: &[String]) -> Result<CliArgs, String> {
             cli_args.file_path = Some(value.to_string());
         }
 
+        // Handle --patch value
+        if args[i] == "--patch" {
+            let value = args
+                .get(i + 1)
+                .ok_or_else(|| "--patch requires a diff file path or -".to_string())?;
+            if value.starts_with('-') && value != "-" {
+                return Err("--patch requires a diff file path or -".to_string());
+            }
+            cli_args.patch_path = Some(value.clone());
+        }
+        // Handle --patch=value
+        if let Some(value) = args[i].strip_prefix("--patch=") {
+            if value.is_empty() {
+                return Err("--patch requires a diff file path or -".to_string());
+            }
+            cli_args.patch_path = Some(value.to_string());
+        }
+
+        // Handle --pr value
+        if args[i] == "--pr" {
+            let value = args.get(i + 1).ok_or_else(|| {
+                "--pr requires a target: <number>, <owner/repo#N>, or a PR URL".to_string()
+            })?;
+            if value.starts_with('-') {
+                return Err(
+                    "--pr requires a target: <number>, <owner/repo#N>, or a PR URL".to_string(),
+                );
+            }
+            cli_args.pr_target = Some(value.clone());
+        }
+        // Handle --pr=value
+        if let Some(value) = args[i].strip_prefix("--pr=") {
+            if value.is_empty() {
+                return Err(
+                    "--pr requires a target: <number>, <owner/repo#N>, or a PR URL".to_string(),
+                );
+            }
+            cli_args.pr_target = Some(value.to_string());
+        }
+
+        // Handle --hook value (passed by installed hooks)
+        if args[i] == "--hook" {
+            let value = args
+                .get(i + 1)
+                .and_then(|v| HookKind::from_name(v))
+                .ok_or_else(|| "--hook requires pre-commit or pre-push".to_string())?;
+            cli_args.hook = Some(value);
+        }
+
+        // Handle -t / --template value
+        if args[i] == "-t" || args[i] == "--template" {
+            let value = args
+                .get(i + 1)
+                .ok_or_else(|| "--template requires a template name".to_string())?;
+            if value.starts_with('-') {
+                return Err("--template requires a template name".to_string());
+            }
+            cli_args.template = Some(value.clone());
+        }
+        // Handle --template=value
+        if let Some(value) = args[i].strip_prefix("--template=") {
+            if value.is_empty() {
+                return Err("--template requires a template name".to_string());
+            }
+            cli_args.template = Some(value.to_string());
+        }
+
+        // Handle --pipe-to value
+        if args[i] == "--pipe-to" {
+            let value = args
+                .get(i + 1)
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| "--pipe-to requires a command".to_string())?;
+            cli_args.pipe_to = Some(value.clone());
+        }
+        // Handle --pipe-to=value
+        if let Some(value) = args[i].strip_prefix("--pipe-to=") {
+            if value.trim().is_empty() {
+                return Err("--pipe-to requires a command".to_string());
+            }
+            cli_args.pipe_to = Some(value.to_string());
+        }
+
         // Handle -r / --revisions value
         if args[i] == "-r" || args[i] == "--revisions" {
             if let Some(value) = args.get(i + 1) {
@@ -2073,6 +2381,38 @@ fn parse_cli_args_from(args: &[String]) -> Result<CliArgs, String> {
         if let Some(value) = args[i].strip_prefix("--revisions=") {
             cli_args.revisions = Some(value.to_string());
         }
+
+        // Handle --revset value
+        if args[i] == "--revset" {
+            let value = args
+                .get(i + 1)
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| "--revset requires a revset expression".to_string())?;
+            cli_args.revset = Some(value.clone());
+        }
+        // Handle --revset=value
+        if let Some(value) = args[i].strip_prefix("--revset=") {
+            if value.is_empty() {
+                return Err("--revset requires a revset expression".to_string());
+            }
+            cli_args.revset = Some(value.to_string());
+        }
+
+        // Handle --interdiff value
+        if args[i] == "--interdiff" {
+            let value = args
+                .get(i + 1)
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| "--interdiff requires the old version's revisions".to_string())?;
+            cli_args.interdiff = Some(value.clone());
+        }
+        // Handle --interdiff=value
+        if let Some(value) = args[i].strip_prefix("--interdiff=") {
+            if value.is_empty() {
+                return Err("--interdiff requires the old version's revisions".to_string());
+            }
+            cli_args.interdiff = Some(value.to_string());
+        }
     }
 
     Ok(cli_args)
@@ -2467,6 +2807,63 @@ mod tests {
         assert_eq!(parsed.path_filter, Some("src/".to_string()));
     }
 
+    #[test]
+    fn should_parse_safe_flag() {
+        let parsed = parse_for_test(&["tuicr", "--safe", "-w"]).expect("parse should succeed");
+        assert!(parsed.safe);
+        assert!(parsed.working_tree);
+
+        let parsed = parse_for_test(&["tuicr"]).expect("parse should succeed");
+        assert!(!parsed.safe);
+    }
+
+    #[test]
+    fn should_parse_patch_flag() {
+        let parsed =
+            parse_for_test(&["tuicr", "--patch", "fix.diff"]).expect("parse should succeed");
+        assert_eq!(parsed.patch_path, Some("fix.diff".to_string()));
+
+        let parsed = parse_for_test(&["tuicr", "--patch", "-"]).expect("parse should succeed");
+        assert_eq!(parsed.patch_path, Some("-".to_string()));
+
+        let parsed = parse_for_test(&["tuicr", "--patch=a.patch"]).expect("parse should succeed");
+        assert_eq!(parsed.patch_path, Some("a.patch".to_string()));
+    }
+
+    #[test]
+    fn should_reject_patch_without_value() {
+        assert!(parse_for_test(&["tuicr", "--patch"]).is_err());
+        assert!(parse_for_test(&["tuicr", "--patch", "--stdout"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_template_flag() {
+        let parsed =
+            parse_for_test(&["tuicr", "--template", "security"]).expect("parse should succeed");
+        assert_eq!(parsed.template, Some("security".to_string()));
+
+        let parsed =
+            parse_for_test(&["tuicr", "--template=release"]).expect("parse should succeed");
+        assert_eq!(parsed.template, Some("release".to_string()));
+    }
+
+    #[test]
+    fn should_parse_pipe_to_command() {
+        let parsed = parse_for_test(&["tuicr", "--pipe-to", "delta --color-only"])
+            .expect("parse should succeed");
+        assert_eq!(parsed.pipe_to, Some("delta --color-only".to_string()));
+
+        assert!(parse_for_test(&["tuicr", "--pipe-to"]).is_err());
+        assert!(parse_for_test(&["tuicr", "--pipe-to="]).is_err());
+    }
+
+    #[test]
+    fn should_reject_template_without_value() {
+        assert!(parse_for_test(&["tuicr", "--template"]).is_err());
+        assert!(parse_for_test(&["tuicr", "-t", "--stdout"]).is_err());
+        assert!(parse_for_test(&["tuicr", "--template="]).is_err());
+    }
+
     #[test]
     fn should_parse_path_equals_syntax() {
         let parsed = parse_for_test(&["tuicr", "--path=plans/current-plan.md"])
@@ -2503,6 +2900,38 @@ mod tests {
         assert!(parsed.working_tree);
     }
 
+    #[test]
+    fn should_parse_revset_in_separate_and_equals_forms() {
+        let parsed =
+            parse_for_test(&["tuicr", "--revset", "@--::@"]).expect("parse should succeed");
+        assert_eq!(parsed.revset, Some("@--::@".to_string()));
+
+        let parsed =
+            parse_for_test(&["tuicr", "--revset=trunk()..@"]).expect("parse should succeed");
+        assert_eq!(parsed.revset, Some("trunk()..@".to_string()));
+        assert_eq!(parsed.revisions, None);
+    }
+
+    #[test]
+    fn should_error_when_revset_value_missing() {
+        let err = parse_for_test(&["tuicr", "--revset"]).expect_err("parse should fail");
+        assert!(err.contains("--revset requires a revset expression"));
+    }
+
+    #[test]
+    fn should_parse_interdiff_with_revisions() {
+        let parsed = parse_for_test(&["tuicr", "-r", "main..v2", "--interdiff", "main..v1"])
+            .expect("parse should succeed");
+        assert_eq!(parsed.revisions, Some("main..v2".to_string()));
+        assert_eq!(parsed.interdiff, Some("main..v1".to_string()));
+
+        let parsed = parse_for_test(&["tuicr", "--interdiff=v1"]).expect("parse should succeed");
+        assert_eq!(parsed.interdiff, Some("v1".to_string()));
+
+        let err = parse_for_test(&["tuicr", "--interdiff"]).expect_err("parse should fail");
+        assert!(err.contains("--interdiff requires"));
+    }
+
     #[test]
     fn should_parse_path_with_revisions() {
         let parsed = parse_for_test(&["tuicr", "--path", "src/", "-r", "HEAD~3.."])
@@ -2556,6 +2985,180 @@ mod tests {
         assert!(err.contains("tuicr pr requires a target"));
     }
 
+    #[test]
+    fn should_parse_pr_flag_as_pr_target() {
+        // given/when
+        let parsed = parse_for_test(&["tuicr", "--pr", "125"]).expect("parse should succeed");
+        let inline =
+            parse_for_test(&["tuicr", "--pr=agavra/tuicr#125"]).expect("parse should succeed");
+        // then
+        assert_eq!(parsed.pr_target, Some("125".to_string()));
+        assert_eq!(inline.pr_target, Some("agavra/tuicr#125".to_string()));
+    }
+
+    #[test]
+    fn should_error_when_pr_flag_has_no_target() {
+        // given/when
+        let err = parse_for_test(&["tuicr", "--pr"]).expect_err("parse should fail");
+        // then
+        assert!(err.contains("--pr requires a target"));
+    }
+
+    #[test]
+    fn should_parse_hook_install_subcommand() {
+        // when
+        let default = parse_for_test(&["tuicr", "hook", "install"]).expect("parse should succeed");
+        let push = parse_for_test(&["tuicr", "hook", "install", "pre-push", "--force"])
+            .expect("parse should succeed");
+
+        // then
+        assert_eq!(
+            default.hook_install,
+            Some(HookInstall {
+                kind: HookKind::PreCommit,
+                force: false
+            })
+        );
+        assert_eq!(
+            push.hook_install,
+            Some(HookInstall {
+                kind: HookKind::PrePush,
+                force: true
+            })
+        );
+        assert!(parse_for_test(&["tuicr", "hook", "remove"]).is_err());
+        assert!(parse_for_test(&["tuicr", "hook", "install", "post-merge"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_check_subcommand() {
+        // when
+        let parsed = parse_for_test(&[
+            "tuicr",
+            "check",
+            "--session",
+            "review.json",
+            "--forbid",
+            "unresolved-issues",
+            "--forbid",
+            "deferred-lines",
+        ])
+        .expect("parse should succeed");
+
+        // then
+        assert_eq!(
+            parsed.check,
+            Some(CheckArgs {
+                session: std::path::PathBuf::from("review.json"),
+                criteria: vec![
+                    CheckCriterion::UnresolvedIssues,
+                    CheckCriterion::DeferredLines
+                ],
+            })
+        );
+        assert!(parse_for_test(&["tuicr", "check"]).is_err());
+        assert!(parse_for_test(&["tuicr", "check", "--session", "a", "--require", "x"]).is_err());
+        assert!(
+            parse_for_test(&[
+                "tuicr",
+                "check",
+                "--session",
+                "a",
+                "--forbid",
+                "all-reviewed"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn should_parse_stats_subcommand() {
+        // when
+        let default = parse_for_test(&["tuicr", "stats"]).expect("parse should succeed");
+        let parsed = parse_for_test(&["tuicr", "stats", "--weeks", "12", "--repo", "."])
+            .expect("parse should succeed");
+
+        // then
+        assert_eq!(default.stats, Some(StatsArgs::default()));
+        assert_eq!(
+            parsed.stats,
+            Some(StatsArgs {
+                weeks: 12,
+                repo: Some(std::path::PathBuf::from(".")),
+            })
+        );
+        assert!(parse_for_test(&["tuicr", "stats", "--weeks", "many"]).is_err());
+        assert!(parse_for_test(&["tuicr", "stats", "--repo"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_history_subcommand() {
+        // when
+        let list =
+            parse_for_test(&["tuicr", "history", "--repo", "."]).expect("parse should succeed");
+        let open = parse_for_test(&["tuicr", "history", "3"]).expect("parse should succeed");
+
+        // then
+        assert_eq!(
+            list.history,
+            Some(HistoryArgs {
+                repo: Some(std::path::PathBuf::from(".")),
+                open: None,
+            })
+        );
+        assert_eq!(open.history.and_then(|h| h.open), Some(3));
+        assert!(parse_for_test(&["tuicr", "history", "latest"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_sessions_gc_subcommand() {
+        // when
+        let default = parse_for_test(&["tuicr", "sessions", "gc"]).expect("parse should succeed");
+        let parsed = parse_for_test(&["tuicr", "sessions", "gc", "--max-age", "30", "--dry-run"])
+            .expect("parse should succeed");
+
+        // then
+        assert_eq!(default.sessions_gc, Some(GcArgs::default()));
+        assert_eq!(
+            parsed.sessions_gc,
+            Some(GcArgs {
+                max_age_days: Some(30),
+                dry_run: true,
+            })
+        );
+        assert!(parse_for_test(&["tuicr", "sessions"]).is_err());
+        assert!(parse_for_test(&["tuicr", "sessions", "gc", "--max-age"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_view_subcommand_and_readonly_flag() {
+        // when
+        let view = parse_for_test(&["tuicr", "view", "review.json"]).expect("parse should succeed");
+        let readonly =
+            parse_for_test(&["tuicr", "--readonly", "-w"]).expect("parse should succeed");
+
+        // then
+        assert_eq!(view.view, Some(std::path::PathBuf::from("review.json")));
+        assert!(readonly.readonly);
+        assert!(readonly.working_tree);
+        assert!(parse_for_test(&["tuicr", "view"]).is_err());
+        assert!(parse_for_test(&["tuicr", "view", "a.json", "b.json"]).is_err());
+        assert!(parse_for_test(&["tuicr", "doctor"]).unwrap().doctor);
+        assert!(parse_for_test(&["tuicr", "doctor", "--fix"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_hook_flag() {
+        // when
+        let parsed = parse_for_test(&["tuicr", "--hook", "pre-push", "-r", "a..b"])
+            .expect("parse should succeed");
+
+        // then
+        assert_eq!(parsed.hook, Some(HookKind::PrePush));
+        assert_eq!(parsed.revisions, Some("a..b".to_string()));
+        assert!(parse_for_test(&["tuicr", "--hook", "post-merge"]).is_err());
+    }
+
     #[test]
     fn should_combine_pr_target_with_theme_flag() {
         // given/when — flag arguments still apply after the PR target.
@@ -0,0 +1,292 @@
+//! `:todos`: write line comments into the working tree as
+//! `TODO(review): ...` comments above the lines they were left on, so the
+//! author can work through them in an editor.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::model::{DiffFile, LineSide, ReviewSession};
+
+const TODO_TAG: &str = "TODO(review):";
+
+/// One file rewritten by [`apply_todos`], kept so the write can be undone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoEdit {
+    pub path: PathBuf,
+    pub original: String,
+    pub written: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TodoOutcome {
+    pub edits: Vec<TodoEdit>,
+    pub inserted: usize,
+    /// Comments left out: on removed lines, in files without a known
+    /// comment syntax, or on lines that no longer match the diff.
+    pub skipped: usize,
+}
+
+/// Line comment delimiters for `path`, or `None` for file types without
+/// a known syntax.
+fn comment_syntax(path: &Path) -> Option<(&'static str, &'static str)> {
+    let name = path.file_name()?.to_str()?;
+    if matches!(name, "Makefile" | "Dockerfile" | "CMakeLists.txt" | "BUILD") {
+        return Some(("#", ""));
+    }
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "rs" | "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "cs" | "java" | "kt" | "kts"
+        | "scala" | "swift" | "go" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "dart"
+        | "php" | "zig" | "groovy" | "gradle" | "proto" | "scss" | "less" => ("//", ""),
+        "py" | "rb" | "sh" | "bash" | "zsh" | "fish" | "pl" | "pm" | "r" | "yaml" | "yml"
+        | "toml" | "nix" | "cmake" | "ps1" | "tf" | "hcl" | "ex" | "exs" | "jl" | "cr" | "conf" => {
+            ("#", "")
+        }
+        "sql" | "lua" | "hs" | "elm" | "ada" => ("--", ""),
+        "lisp" | "el" | "clj" | "cljs" | "scm" | "asm" => (";", ""),
+        "tex" | "erl" | "hrl" => ("%", ""),
+        "html" | "htm" | "xml" | "svg" | "md" | "markdown" | "vue" => ("<!--", " -->"),
+        "css" => ("/*", " */"),
+        _ => return None,
+    })
+}
+
+/// Insert a `TODO(review)` comment above the first line of every new-side
+/// line comment, in the files under `root`. A file is only rewritten when
+/// each commented line still reads as it does in the diff, so files edited
+/// since the diff was loaded are left alone.
+pub fn apply_todos(root: &Path, session: &ReviewSession, diff_files: &[DiffFile]) -> TodoOutcome {
+    let mut outcome = TodoOutcome::default();
+    for file in diff_files.iter().filter(|f| !f.is_commit_message) {
+        let path = file.display_path();
+        let Some(review) = session.files.get(path) else {
+            continue;
+        };
+
+        // Target line -> comment texts, in line order
+        let mut todos: BTreeMap<u32, Vec<&str>> = BTreeMap::new();
+        for (line, comments) in &review.line_comments {
+            for comment in comments {
+                if comment.side == Some(LineSide::Old) {
+                    outcome.skipped += 1;
+                    continue;
+                }
+                let start = comment.line_range.map_or(*line, |range| range.start);
+                todos.entry(start).or_default().push(&comment.content);
+            }
+        }
+        let count: usize = todos.values().map(Vec::len).sum();
+        if count == 0 {
+            continue;
+        }
+
+        match rewrite_file(&root.join(path), file, &todos) {
+            Some((original, written)) => {
+                outcome.inserted += count;
+                outcome.edits.push(TodoEdit {
+                    path: path.clone(),
+                    original,
+                    written,
+                });
+            }
+            None => outcome.skipped += count,
+        }
+    }
+    outcome
+}
+
+/// Write `todos` into the file at `full_path`, returning its old and new
+/// content, or `None` when the file can't be safely rewritten.
+fn rewrite_file(
+    full_path: &Path,
+    file: &DiffFile,
+    todos: &BTreeMap<u32, Vec<&str>>,
+) -> Option<(String, String)> {
+    let (open, close) = comment_syntax(file.display_path())?;
+    let original = std::fs::read_to_string(full_path).ok()?;
+    let new_side: BTreeMap<u32, &str> = file
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .filter_map(|line| Some((line.new_lineno?, line.content.as_str())))
+        .collect();
+    let eol = if original.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    for line in todos.keys() {
+        let on_disk = lines.get((*line as usize).checked_sub(1)?)?;
+        let on_disk = on_disk.trim_end_matches(['\n', '\r']);
+        if crate::vcs::tabify(file.display_path(), on_disk) != *new_side.get(line)? {
+            return None;
+        }
+    }
+
+    let mut written = String::with_capacity(original.len());
+    for (idx, text) in lines.iter().enumerate() {
+        if let Some(comments) = todos.get(&(idx as u32 + 1)) {
+            let indent: String = text.chars().take_while(|c| c.is_whitespace()).collect();
+            let indent = indent.trim_end_matches(['\n', '\r']);
+            for comment in comments {
+                for (line_idx, comment_line) in comment.lines().enumerate() {
+                    let body = if line_idx == 0 {
+                        format!("{TODO_TAG} {comment_line}")
+                    } else {
+                        comment_line.to_string()
+                    };
+                    written.push_str(&format!("{indent}{open} {}{close}{eol}", body.trim_end()));
+                }
+            }
+        }
+        written.push_str(text);
+    }
+    std::fs::write(full_path, &written).ok()?;
+    Some((original, written))
+}
+
+/// Put back the files `edits` rewrote, newest first, unless they changed
+/// since. Returns how many were restored and the paths left alone.
+pub fn undo_todos(root: &Path, edits: &[TodoEdit]) -> (usize, Vec<PathBuf>) {
+    let mut restored = 0;
+    let mut kept = Vec::new();
+    for edit in edits.iter().rev() {
+        let full_path = root.join(&edit.path);
+        let unchanged = std::fs::read_to_string(&full_path).is_ok_and(|c| c == edit.written);
+        if unchanged && std::fs::write(&full_path, &edit.original).is_ok() {
+            restored += 1;
+        } else {
+            kept.push(edit.path.clone());
+        }
+    }
+    (restored, kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        Comment, CommentType, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin,
+        LineRange, SessionDiffSource,
+    };
+
+    fn diff_file(path: &str, lines: &[&str]) -> DiffFile {
+        let lines = lines
+            .iter()
+            .enumerate()
+            .map(|(idx, content)| DiffLine {
+                origin: LineOrigin::Addition,
+                content: crate::vcs::tabify(Path::new(path), content),
+                old_lineno: None,
+                new_lineno: Some(idx as u32 + 1),
+                highlighted_spans: None,
+            })
+            .collect();
+        DiffFile {
+            old_path: None,
+            new_path: Some(PathBuf::from(path)),
+            status: FileStatus::Added,
+            hunks: vec![DiffHunk {
+                header: "@@ -0,0 +1 @@".to_string(),
+                lines,
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: 1,
+            }],
+            is_binary: false,
+            is_too_large: false,
+            is_commit_message: false,
+            content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
+        }
+    }
+
+    fn session_with(path: &str, comments: Vec<(u32, Comment)>) -> ReviewSession {
+        let mut session = ReviewSession::new(
+            PathBuf::from("/tmp"),
+            "abc".to_string(),
+            None,
+            SessionDiffSource::WorkingTree,
+        );
+        session.add_file(PathBuf::from(path), FileStatus::Added, 0);
+        let review = session.files.get_mut(Path::new(path)).unwrap();
+        for (line, comment) in comments {
+            review.add_line_comment(line, comment);
+        }
+        session
+    }
+
+    #[test]
+    fn should_insert_indented_todos_in_language_syntax_and_undo() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let source = "fn main() {\n\tlet x = 1;\n\tlet y = 2;\n}\n";
+        std::fs::write(dir.path().join("main.rs"), source).unwrap();
+        let file = diff_file("main.rs", &source.lines().collect::<Vec<_>>());
+        let session = session_with(
+            "main.rs",
+            vec![
+                (
+                    2,
+                    Comment::new(
+                        "Name this\nbetter".to_string(),
+                        CommentType::Note,
+                        Some(LineSide::New),
+                    ),
+                ),
+                (
+                    4,
+                    Comment::new_with_range(
+                        "Merge these".to_string(),
+                        CommentType::Issue,
+                        Some(LineSide::New),
+                        LineRange::new(3, 4),
+                    ),
+                ),
+            ],
+        );
+
+        // when
+        let outcome = apply_todos(dir.path(), &session, &[file]);
+
+        // then
+        assert_eq!(outcome.inserted, 2);
+        assert_eq!(outcome.skipped, 0);
+        let written = std::fs::read_to_string(dir.path().join("main.rs")).unwrap();
+        assert_eq!(
+            written,
+            "fn main() {\n\t// TODO(review): Name this\n\t// better\n\tlet x = 1;\n\t// TODO(review): Merge these\n\tlet y = 2;\n}\n"
+        );
+        assert_eq!(undo_todos(dir.path(), &outcome.edits), (1, Vec::new()));
+        let restored = std::fs::read_to_string(dir.path().join("main.rs")).unwrap();
+        assert_eq!(restored, source);
+    }
+
+    #[test]
+    fn should_leave_files_changed_since_the_diff_alone() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("app.py"), "x = 2\n").unwrap();
+        let file = diff_file("app.py", &["x = 1"]);
+        let session = session_with(
+            "app.py",
+            vec![(
+                1,
+                Comment::new("Why?".to_string(), CommentType::Note, Some(LineSide::New)),
+            )],
+        );
+
+        // when
+        let outcome = apply_todos(dir.path(), &session, &[file]);
+
+        // then
+        assert_eq!(outcome.inserted, 0);
+        assert_eq!(outcome.skipped, 1);
+        let content = std::fs::read_to_string(dir.path().join("app.py")).unwrap();
+        assert_eq!(content, "x = 2\n");
+    }
+}
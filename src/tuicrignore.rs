@@ -54,7 +54,7 @@ mod tests {
     use tempfile::tempdir;
 
     use super::*;
-    use crate::model::FileStatus;
+    use crate::model::{FileModes, FileSizes, FileStatus};
 
     fn make_diff_file(path: &str) -> DiffFile {
         DiffFile {
@@ -66,6 +66,8 @@ mod tests {
             is_too_large: false,
             is_commit_message: false,
             content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         }
     }
 
@@ -214,6 +216,8 @@ mod tests {
             is_too_large: false,
             is_commit_message: false,
             content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         };
         let kept = make_diff_file("src/lib.rs");
 
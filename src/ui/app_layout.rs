@@ -4,12 +4,12 @@ use ratatui::{
     widgets::Block,
 };
 
-use crate::app::{App, InputMode};
+use crate::app::{App, InputMode, View};
 use crate::ui::diff_view::render_diff_view;
 use crate::ui::file_list::render_file_list;
 use crate::ui::inline_commit_selector::render_inline_commit_selector;
-use crate::ui::selector::render_commit_select;
-use crate::ui::{comment_panel, help_popup, status_bar, styles, submit_modals};
+use crate::ui::selector::{render_commit_select, render_review_chooser};
+use crate::ui::{comment_panel, help_popup, status_bar, styles, submit_modals, views};
 
 pub fn render(frame: &mut Frame, app: &mut App) {
     frame.render_widget(
@@ -18,8 +18,9 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     );
 
     // Special handling for commit selection mode
-    if app.input_mode == InputMode::CommitSelect {
+    if app.base_mode() == InputMode::CommitSelect {
         render_commit_select(frame, app);
+        render_overlays(frame, app);
         return;
     }
 
@@ -36,31 +37,15 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         .split(frame.area());
 
     status_bar::render_header(frame, app, chunks[0]);
-    render_main_content(frame, app, chunks[1]);
-    status_bar::render_status_bar(frame, app, chunks[2]);
-
-    // Render help popup on top if in help mode
-    if app.input_mode == InputMode::Help {
-        help_popup::render_help(frame, app);
-    }
-
-    // Comment input is now rendered inline in the diff view
-
-    // Render confirm dialog if in confirm mode
-    if app.input_mode == InputMode::Confirm {
-        comment_panel::render_confirm_dialog(frame, app, "Copy review to clipboard?");
+    if app.view == View::Diff {
+        render_main_content(frame, app, chunks[1]);
+    } else {
+        views::render_view(frame, app, chunks[1]);
     }
+    status_bar::render_status_bar(frame, app, chunks[2]);
 
-    // Submit-flow modals.
-    if app.input_mode == InputMode::SubmitResolver {
-        submit_modals::render_submit_resolver(frame, app);
-    }
-    if app.input_mode == InputMode::SubmitConfirm {
-        submit_modals::render_submit_confirm(frame, app);
-    }
-    if app.input_mode == InputMode::SubmitActionPicker {
-        submit_modals::render_submit_action_picker(frame, app);
-    }
+    // Comment input is rendered inline in the diff view; popups go on top.
+    render_overlays(frame, app);
 
     // Position terminal cursor for IME when in Comment mode
     // Always set a cursor position to prevent IME from showing at (0,0)
@@ -80,6 +65,30 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     }
 }
 
+/// Draw open overlays bottom first, so a nested popup lands above the one it
+/// was opened from.
+fn render_overlays(frame: &mut Frame, app: &mut App) {
+    for mode in app.overlays() {
+        match mode {
+            InputMode::Help => help_popup::render_help(frame, app),
+            InputMode::Confirm => {
+                let message = app
+                    .pending_confirm
+                    .as_ref()
+                    .map_or_else(|| "Are you sure?".to_string(), |d| d.message.clone());
+                comment_panel::render_confirm_dialog(frame, app, &message);
+            }
+            InputMode::SubmitResolver => submit_modals::render_submit_resolver(frame, app),
+            InputMode::SubmitConfirm => submit_modals::render_submit_confirm(frame, app),
+            InputMode::SubmitActionPicker => submit_modals::render_submit_action_picker(frame, app),
+            InputMode::ReviewChooser => render_review_chooser(frame, app),
+            InputMode::VerifyPanel => comment_panel::render_verify_panel(frame, app),
+            InputMode::CompletionDialog => comment_panel::render_completion_dialog(frame, app),
+            _ => {}
+        }
+    }
+}
+
 fn render_main_content(frame: &mut Frame, app: &mut App, area: Rect) {
     let content_area = if app.has_inline_commit_selector() {
         let selector_height = (app.review_commits.len() as u16 + 2).min(8); // N items + 2 borders, capped
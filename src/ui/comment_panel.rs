@@ -3,7 +3,7 @@ use ratatui::{
     layout::{Constraint, Flex, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 use unicode_width::UnicodeWidthStr;
 
@@ -86,7 +86,7 @@ pub fn format_comment_input_lines(
         Span::styled(line_info, styles::dim_style(theme)),
         Span::styled(
             format!(
-                "(Tab/S-Tab:type Enter:save {}:newline Esc:cancel)",
+                "(Tab/S-Tab:type C-b:blocking Enter:save {}:newline Esc:cancel)",
                 newline_hint
             ),
             styles::dim_style(theme),
@@ -248,11 +248,13 @@ pub fn format_remote_thread_lines(
     result
 }
 
-/// Format a comment as multiple lines with a box border (themed version)
+/// Format a comment as multiple lines with a box border (themed version).
+/// Comments imported from a forge show their `author` after the type label.
 pub fn format_comment_lines(
     theme: &Theme,
     comment_type: CommentTypePresentation,
     content: &str,
+    author: Option<&str>,
     line_range: Option<LineRange>,
 ) -> Vec<Line<'static>> {
     let type_style = styles::comment_type_style(theme, comment_type.color);
@@ -274,6 +276,10 @@ pub fn format_comment_lines(
     result.push(Line::from(vec![
         Span::styled(top_prefix, border_style),
         Span::styled(format!("[{}] ", comment_type.label), type_style),
+        Span::styled(
+            author.map(|a| format!("@{a} ")).unwrap_or_default(),
+            styles::dim_style(theme),
+        ),
         Span::styled(line_info, styles::dim_style(theme)),
         Span::styled("─".repeat(30), border_style),
     ]));
@@ -324,7 +330,183 @@ pub fn render_confirm_dialog(frame: &mut Frame, app: &App, message: &str) {
 
     let paragraph = Paragraph::new(lines)
         .style(styles::popup_style(theme))
-        .alignment(ratatui::layout::Alignment::Center);
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, inner);
+}
+
+/// Render the completion dialog: what the finished review covered, then
+/// the next actions, moved through with j/k and run with Enter.
+pub fn render_completion_dialog(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let rows = app.completion_rows();
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let (added, removed) = app
+        .diff_files
+        .iter()
+        .map(|file| file.stat())
+        .fold((0, 0), |(a, r), (fa, fr)| (a + fa, r + fr));
+
+    let session = &app.session;
+    let all_comments = session
+        .review_comments
+        .iter()
+        .chain(session.files.values().flat_map(|f| {
+            f.file_comments
+                .iter()
+                .chain(f.line_comments.values().flatten())
+        }));
+    let mut by_type: Vec<(String, usize)> = Vec::new();
+    for comment in all_comments {
+        let label = app.comment_type_label(&comment.comment_type);
+        match by_type.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, count)) => *count += 1,
+            None => by_type.push((label, 1)),
+        }
+    }
+    let comments = if by_type.is_empty() {
+        "none".to_string()
+    } else {
+        by_type
+            .iter()
+            .map(|(label, count)| format!("{count} {label}"))
+            .collect::<Vec<_>>()
+            .join(" \u{00b7} ")
+    };
+
+    let files = app.file_count();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" Files     ", bold),
+            Span::raw(format!(
+                "{files} reviewed{}",
+                app.viewed_commit()
+                    .map(|commit| format!(" in {}", &commit[..commit.len().min(8)]))
+                    .unwrap_or_default()
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled(" Lines     ", bold),
+            Span::styled(format!("+{added}"), styles::diff_add_style(theme)),
+            Span::raw(" "),
+            Span::styled(format!("-{removed}"), styles::diff_del_style(theme)),
+        ]),
+        Line::from(vec![Span::styled(" Comments  ", bold), Span::raw(comments)]),
+    ];
+    let deferred = session.deferred_count();
+    if deferred > 0 {
+        lines.push(Line::from(vec![
+            Span::styled(" Deferred  ", bold),
+            Span::styled(
+                format!("{deferred} to revisit (]d / [d)"),
+                styles::pending_style(theme),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+    for (i, (label, _)) in rows.iter().enumerate() {
+        let is_cursor = i == app.completion_cursor;
+        let style = if is_cursor {
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let cursor = if is_cursor { ">" } else { " " };
+        lines.push(Line::from(Span::styled(
+            format!(" {cursor} {label}"),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        " Enter: run   Esc: keep reviewing",
+        Style::default().fg(theme.fg_secondary),
+    )));
+
+    let [area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Length(56)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Review complete ")
+        .title_alignment(ratatui::layout::Alignment::Center)
+        .borders(Borders::ALL)
+        .style(styles::popup_style(theme))
+        .border_style(styles::border_style(theme, true));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(
+        Paragraph::new(lines).style(styles::popup_style(theme)),
+        inner,
+    );
+}
+
+/// Render the `:verify` panel: one row per comment whose line is gone from
+/// the diff or changed around it, with its location and first line.
+pub fn render_verify_panel(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = centered_rect(70, 50, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let n = app.orphaned_comments.len();
+    let title = format!(
+        " {n} comment{} to verify against the diff ",
+        if n == 1 { "" } else { "s" }
+    );
+    let block = Block::default()
+        .title(title)
+        .title_alignment(ratatui::layout::Alignment::Center)
+        .borders(Borders::ALL)
+        .style(styles::popup_style(theme))
+        .border_style(styles::border_style(theme, true));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut lines: Vec<Line> = Vec::with_capacity(n + 3);
+    lines.push(Line::from(""));
+    for (i, orphan) in app.orphaned_comments.iter().enumerate() {
+        let selected = i == app.verify_cursor;
+        let style = if selected {
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let gone = if !orphan.file_in_diff {
+            " (file not in diff)"
+        } else if orphan.drifted {
+            " (line changed)"
+        } else {
+            ""
+        };
+        let first = orphan.content.lines().next().unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(
+                    "{} {}{gone}  ",
+                    if selected { ">" } else { " " },
+                    orphan.location()
+                ),
+                style,
+            ),
+            Span::styled(first.to_string(), style.fg(theme.fg_secondary)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "r: re-anchor to nearest line (keep if changed)   f: detach from line   d: delete   Esc: close",
+        Style::default().fg(theme.fg_secondary),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .style(styles::popup_style(theme))
+        .wrap(Wrap { trim: false });
     frame.render_widget(paragraph, inner);
 }
 
@@ -7,18 +7,21 @@ use ratatui::{
 };
 use unicode_width::UnicodeWidthStr;
 
-use crate::app::{App, ExpandDirection, FocusedPanel, GAP_EXPAND_BATCH, GapId, InputMode};
-use crate::model::{LineOrigin, LineRange, LineSide};
+use crate::app::{
+    App, ElidedRun, ExpandDirection, FocusedPanel, GAP_EXPAND_BATCH, GapId, InputMode,
+};
+use crate::model::{FileStatus, LineOrigin, LineRange, LineSide};
 use crate::theme::Theme;
 use crate::ui::comment_panel;
 use crate::ui::diff_view::{
-    apply_horizontal_scroll, comment_type_presentation, cursor_indicator, cursor_indicator_spaced,
-    diff_stat_title, is_line_highlighted, paint_visual_selection_overlay,
-    populate_row_to_annotation, render_expander_line, render_hidden_lines,
-    scroll_comment_input_into_view,
+    SAMPLED_HUNK_MARK, apply_horizontal_scroll, comment_type_presentation, cursor_indicator,
+    cursor_indicator_spaced, diff_line_indicator, diff_stat_title, is_line_highlighted,
+    paint_visual_selection_overlay, populate_row_to_annotation, refined_hunk,
+    render_elided_context, render_expander_line, render_file_metadata, render_hidden_lines,
+    render_history_comments, scroll_comment_input_into_view,
 };
 use crate::ui::styles;
-use crate::ui::text_utils::{truncate_or_pad, truncate_or_pad_spans};
+use crate::ui::text_utils::{file_header_label, truncate_or_pad, truncate_or_pad_spans};
 use crate::vcs::git::calculate_gap;
 
 /// Cursor info for the inline comment input box in side-by-side view:
@@ -53,6 +56,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
 
     let block = Block::default()
         .title(title)
+        .title_style(styles::title_style(&app.theme, focused))
         .title_top(diff_stat_title(app).right_aligned())
         .borders(Borders::ALL)
         .style(styles::panel_style(&app.theme))
@@ -73,7 +77,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
     let content_width = available_width / 2;
 
     // Determine if we're in line comment mode (not file-level)
-    let comment_input_mode = app.input_mode == InputMode::Comment
+    let comment_input_mode = app.is_mode_open(InputMode::Comment)
         && !app.comment_is_file_level
         && !app.comment_is_review_level;
 
@@ -107,7 +111,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
     let mut annotation_offset: Option<(usize, usize, usize)> = None;
 
     let is_review_comment_mode =
-        app.input_mode == InputMode::Comment && app.comment_is_review_level;
+        app.is_mode_open(InputMode::Comment) && app.comment_is_review_level;
 
     let general_indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
     lines.push(Line::from(vec![
@@ -130,7 +134,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
         if is_being_edited {
             let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                 &app.theme,
-                comment_type_presentation(app, &app.comment_type),
+                comment_type_presentation(app, &app.comment_type, app.comment_blocking),
                 &app.comment_buffer,
                 app.comment_cursor,
                 None,
@@ -156,8 +160,9 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
         } else {
             let comment_lines = comment_panel::format_comment_lines(
                 &app.theme,
-                comment_type_presentation(app, &comment.comment_type),
+                comment_type_presentation(app, &comment.comment_type, comment.blocking),
                 &comment.content,
+                comment.author.as_deref(),
                 None,
             );
             for mut comment_line in comment_lines {
@@ -175,7 +180,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
     if is_review_comment_mode && app.editing_comment_id.is_none() {
         let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
             &app.theme,
-            comment_type_presentation(app, &app.comment_type),
+            comment_type_presentation(app, &app.comment_type, app.comment_blocking),
             &app.comment_buffer,
             app.comment_cursor,
             None,
@@ -201,17 +206,30 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
     for (file_idx, file) in app.diff_files.iter().enumerate() {
         let path = file.display_path();
         let status = file.status.as_char();
-        let is_reviewed = app.session.is_file_reviewed(path);
+        let is_folded = app.is_file_folded(path);
 
         // File header
         let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
 
-        let review_mark = if is_reviewed { "✓ " } else { "" };
+        let review_mark = app
+            .file_confidence(path)
+            .map(|confidence| format!("{} ", confidence.mark()))
+            .unwrap_or_default();
 
         let header_text = if file.is_commit_message {
             format!("═══ {}Commit Message ", review_mark)
         } else {
-            format!("═══ {}{} [{}] ", review_mark, path.display(), status)
+            let collapsed = if app.is_collapsed_by_filetype(path) {
+                "(collapsed, Enter opens) "
+            } else {
+                ""
+            };
+            format!(
+                "═══ {}{} [{}] {collapsed}",
+                review_mark,
+                file_header_label(file),
+                status
+            )
         };
         lines.push(Line::from(vec![
             Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
@@ -220,13 +238,13 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
         ]));
         line_idx += 1;
 
-        // If file is reviewed, skip rendering the body
-        if is_reviewed {
+        // If file is reviewed or collapsed, skip rendering the body
+        if is_folded {
             continue;
         }
 
         // Check if we're editing/adding a file-level comment for this file
-        let is_file_comment_mode = app.input_mode == InputMode::Comment
+        let is_file_comment_mode = app.is_mode_open(InputMode::Comment)
             && app.comment_is_file_level
             && file_idx == app.diff_state.current_file_idx;
 
@@ -241,7 +259,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
                     // Render the inline input instead
                     let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                         &app.theme,
-                        comment_type_presentation(app, &app.comment_type),
+                        comment_type_presentation(app, &app.comment_type, app.comment_blocking),
                         &app.comment_buffer,
                         app.comment_cursor,
                         None,
@@ -270,8 +288,9 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
                 } else {
                     let comment_lines = comment_panel::format_comment_lines(
                         &app.theme,
-                        comment_type_presentation(app, &comment.comment_type),
+                        comment_type_presentation(app, &comment.comment_type, comment.blocking),
                         &comment.content,
+                        comment.author.as_deref(),
                         None,
                     );
                     for mut comment_line in comment_lines {
@@ -294,7 +313,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
         if is_file_comment_mode && app.editing_comment_id.is_none() {
             let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                 &app.theme,
-                comment_type_presentation(app, &app.comment_type),
+                comment_type_presentation(app, &app.comment_type, app.comment_blocking),
                 &app.comment_buffer,
                 app.comment_cursor,
                 None,
@@ -318,6 +337,15 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
             }
         }
 
+        let metadata = file.metadata_lines();
+        render_file_metadata(
+            &mut lines,
+            &mut line_idx,
+            ctx.current_line_idx,
+            &metadata,
+            &app.theme,
+        );
+
         if file.is_too_large {
             let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
             lines.push(Line::from(vec![
@@ -333,12 +361,14 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
             ]));
             line_idx += 1;
         } else if file.hunks.is_empty() {
-            let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
-            lines.push(Line::from(vec![
-                Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
-                Span::styled("(no changes)", styles::dim_style(&app.theme)),
-            ]));
-            line_idx += 1;
+            if metadata.is_empty() || file.status == FileStatus::Deleted {
+                let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
+                lines.push(Line::from(vec![
+                    Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
+                    Span::styled(file.empty_label(), styles::dim_style(&app.theme)),
+                ]));
+                line_idx += 1;
+            }
         } else {
             let line_comments = app
                 .session
@@ -349,6 +379,8 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
                 .unwrap_or_default();
 
             for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+                let refined = refined_hunk(app, file_idx, hunk_idx, hunk);
+                let hunk = refined.as_ref().unwrap_or(hunk);
                 // Calculate and render gap before this hunk
                 let prev_hunk = if hunk_idx > 0 {
                     file.hunks.get(hunk_idx - 1)
@@ -457,8 +489,14 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
 
                 // Hunk header
                 let indicator = cursor_indicator_spaced(line_idx, ctx.current_line_idx);
+                let sample_mark = if app.is_sampled_hunk(path, hunk) {
+                    SAMPLED_HUNK_MARK
+                } else {
+                    ""
+                };
                 lines.push(Line::from(vec![
                     Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
+                    Span::styled(sample_mark, styles::pending_style(&app.theme)),
                     Span::styled(
                         hunk.header.to_string(),
                         styles::diff_hunk_header_style(&app.theme),
@@ -467,8 +505,10 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
                 line_idx += 1;
 
                 // Process diff lines in side-by-side format
+                let elided = app.elided_runs(file_idx, hunk_idx);
                 let (new_line_idx, cursor_info) = render_hunk_lines_side_by_side(
                     &hunk.lines,
+                    &elided,
                     &line_comments,
                     &ctx,
                     file_idx,
@@ -534,7 +574,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
     app.diff_state.max_content_width = max_content_width;
 
     let scroll_offset = app.diff_state.scroll_offset;
-    let wrap = app.diff_state.wrap_lines;
+    let wrap = app.wrap_lines();
     app.diff_state.visible_line_count = populate_row_to_annotation(
         &mut app.diff_row_to_annotation,
         &line_widths,
@@ -548,13 +588,13 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
     if app.diff_state.scroll_x > max_scroll_x {
         app.diff_state.scroll_x = max_scroll_x;
     }
-    if app.diff_state.wrap_lines {
+    if app.wrap_lines() {
         app.diff_state.scroll_x = 0;
     }
 
     let scroll_x = app.diff_state.scroll_x;
     let visible_lines_unscrolled_for_overlay = visible_lines_unscrolled.clone();
-    let visible_lines: Vec<Line> = if app.diff_state.wrap_lines {
+    let visible_lines: Vec<Line> = if app.wrap_lines() {
         visible_lines_unscrolled
     } else {
         visible_lines_unscrolled
@@ -567,7 +607,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
         inner,
         visible_lines_unscrolled: &visible_lines_unscrolled_for_overlay,
         line_widths: &line_widths,
-        wrap_lines: app.diff_state.wrap_lines,
+        wrap_lines: app.wrap_lines(),
         viewport_width: inner.width as usize,
         scroll_x,
         scroll_offset: app.diff_state.scroll_offset,
@@ -579,7 +619,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
     crate::ui::diff_view::paint_section_highlight(frame, &overlay_ctx);
 
     let mut diff = Paragraph::new(visible_lines).style(styles::panel_style(&app.theme));
-    if app.diff_state.wrap_lines {
+    if app.wrap_lines() {
         diff = diff.wrap(Wrap { trim: false });
     }
     frame.render_widget(diff, inner);
@@ -629,7 +669,7 @@ pub(super) fn render_side_by_side_diff(frame: &mut Frame, app: &mut App, area: R
             let mut visual_row: u16 = 0;
             let viewport_width = inner.width as usize;
 
-            if app.diff_state.wrap_lines && viewport_width > 0 {
+            if app.wrap_lines() && viewport_width > 0 {
                 for i in 0..logical_offset {
                     if i < line_widths.len() {
                         let width = line_widths[i];
@@ -693,6 +733,7 @@ fn render_sbs_expanded_context_line(
 /// Returns (new_line_idx, optional cursor info for inline comment input)
 fn render_hunk_lines_side_by_side(
     hunk_lines: &[crate::model::DiffLine],
+    elided: &[ElidedRun],
     line_comments: &std::collections::HashMap<u32, Vec<crate::model::Comment>>,
     ctx: &SideBySideContext,
     file_idx: usize,
@@ -707,6 +748,17 @@ fn render_hunk_lines_side_by_side(
 
         match diff_line.origin {
             LineOrigin::Context => {
+                if let Some(run) = elided.iter().find(|run| run.start == i) {
+                    render_elided_context(
+                        lines,
+                        &mut line_idx,
+                        ctx.current_line_idx,
+                        run.count,
+                        ctx.theme,
+                    );
+                    i += run.count;
+                    continue;
+                }
                 let (new_line_idx, cursor_info) = render_context_line_side_by_side(
                     diff_line,
                     line_comments,
@@ -774,16 +826,31 @@ fn render_context_line_side_by_side(
         .map(|n| format!("{n:>4}"))
         .unwrap_or_else(|| "    ".to_string());
 
-    let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
+    let indicator = diff_line_indicator(
+        line_idx,
+        ctx.current_line_idx,
+        ctx.app.is_line_deferred(file_idx, diff_line),
+        ctx.theme,
+    );
+    let is_conflict_marker = is_conflict_marker(ctx, file_idx, diff_line);
+    let content_style = if is_conflict_marker {
+        styles::conflict_marker_style(ctx.theme)
+    } else {
+        styles::diff_context_style(ctx.theme)
+    };
+    let highlighted_spans = diff_line
+        .highlighted_spans
+        .as_ref()
+        .filter(|_| !is_conflict_marker);
 
     let mut spans = vec![
-        Span::styled(indicator, styles::current_line_indicator_style(ctx.theme)),
+        indicator,
         Span::styled(format!("{line_num} "), styles::dim_style(ctx.theme)),
         Span::styled(" ".to_string(), styles::diff_context_style(ctx.theme)),
     ];
 
     // Left side content - use syntax highlighting if available
-    if let Some(ref highlighted) = diff_line.highlighted_spans {
+    if let Some(highlighted) = highlighted_spans {
         let content_spans = truncate_or_pad_spans(
             highlighted,
             ctx.content_width,
@@ -792,7 +859,7 @@ fn render_context_line_side_by_side(
         spans.extend(content_spans);
     } else {
         let content = truncate_or_pad(&diff_line.content, ctx.content_width);
-        spans.push(Span::styled(content, styles::diff_context_style(ctx.theme)));
+        spans.push(Span::styled(content, content_style));
     }
 
     // Separator
@@ -807,7 +874,7 @@ fn render_context_line_side_by_side(
     ));
 
     // Right side content - use same highlighting
-    if let Some(ref highlighted) = diff_line.highlighted_spans {
+    if let Some(highlighted) = highlighted_spans {
         let content_spans = truncate_or_pad_spans(
             highlighted,
             ctx.content_width,
@@ -816,7 +883,7 @@ fn render_context_line_side_by_side(
         spans.extend(content_spans);
     } else {
         let content = truncate_or_pad(&diff_line.content, ctx.content_width);
-        spans.push(Span::styled(content, styles::diff_context_style(ctx.theme)));
+        spans.push(Span::styled(content, content_style));
     }
 
     lines.push(Line::from(spans));
@@ -845,6 +912,15 @@ fn render_context_line_side_by_side(
                 line_idx,
                 lines,
             );
+            render_history_comments(
+                lines,
+                &mut line_idx,
+                ctx.current_line_idx,
+                ctx.app,
+                file.display_path(),
+                new_ln,
+                LineSide::New,
+            );
         }
     }
 
@@ -882,11 +958,19 @@ fn render_deletion_addition_pair_side_by_side(
 
     // Render each pair of deletion/addition
     for offset in 0..max_lines {
-        let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
-
-        let mut spans = vec![Span::styled(
-            indicator,
-            styles::current_line_indicator_style(ctx.theme),
+        let deferred = (offset < del_count
+            && ctx
+                .app
+                .is_line_deferred(file_idx, &hunk_lines[start_idx + offset]))
+            || (offset < add_count
+                && ctx
+                    .app
+                    .is_line_deferred(file_idx, &hunk_lines[add_start + offset]));
+        let mut spans = vec![diff_line_indicator(
+            line_idx,
+            ctx.current_line_idx,
+            deferred,
+            ctx.theme,
         )];
 
         // Left side (deletion)
@@ -902,7 +986,13 @@ fn render_deletion_addition_pair_side_by_side(
         // Right side (addition)
         if offset < add_count {
             let add_line = &hunk_lines[add_start + offset];
-            add_addition_spans(ctx.theme, &mut spans, add_line, ctx.content_width);
+            add_addition_spans(
+                ctx.theme,
+                &mut spans,
+                add_line,
+                ctx.content_width,
+                is_conflict_marker(ctx, file_idx, add_line),
+            );
         } else {
             add_empty_column_spans(&mut spans, ctx.content_width);
         }
@@ -936,6 +1026,15 @@ fn render_deletion_addition_pair_side_by_side(
                         line_idx,
                         lines,
                     );
+                    render_history_comments(
+                        lines,
+                        &mut line_idx,
+                        ctx.current_line_idx,
+                        ctx.app,
+                        file.display_path(),
+                        old_ln,
+                        LineSide::Old,
+                    );
                 }
             }
         }
@@ -966,6 +1065,15 @@ fn render_deletion_addition_pair_side_by_side(
                         line_idx,
                         lines,
                     );
+                    render_history_comments(
+                        lines,
+                        &mut line_idx,
+                        ctx.current_line_idx,
+                        ctx.app,
+                        file.display_path(),
+                        new_ln,
+                        LineSide::New,
+                    );
                 }
             }
         }
@@ -984,15 +1092,21 @@ fn render_standalone_addition_side_by_side(
     mut line_idx: usize,
     lines: &mut Vec<Line>,
 ) -> (usize, Option<SideBySideCursorInfo>) {
-    let indicator = cursor_indicator(line_idx, ctx.current_line_idx);
-
-    let mut spans = vec![Span::styled(
-        indicator,
-        styles::current_line_indicator_style(ctx.theme),
+    let mut spans = vec![diff_line_indicator(
+        line_idx,
+        ctx.current_line_idx,
+        ctx.app.is_line_deferred(file_idx, diff_line),
+        ctx.theme,
     )];
     add_empty_column_spans(&mut spans, ctx.content_width);
     spans.push(Span::styled(" │ ", styles::dim_style(ctx.theme)));
-    add_addition_spans(ctx.theme, &mut spans, diff_line, ctx.content_width);
+    add_addition_spans(
+        ctx.theme,
+        &mut spans,
+        diff_line,
+        ctx.content_width,
+        is_conflict_marker(ctx, file_idx, diff_line),
+    );
 
     lines.push(Line::from(spans));
     line_idx += 1;
@@ -1020,6 +1134,15 @@ fn render_standalone_addition_side_by_side(
                 line_idx,
                 lines,
             );
+            render_history_comments(
+                lines,
+                &mut line_idx,
+                ctx.current_line_idx,
+                ctx.app,
+                file.display_path(),
+                new_ln,
+                LineSide::New,
+            );
         }
     }
 
@@ -1056,12 +1179,26 @@ fn add_deletion_spans(
     }
 }
 
+/// Whether `diff_line` of file `file_idx` is a conflict marker
+fn is_conflict_marker(
+    ctx: &SideBySideContext,
+    file_idx: usize,
+    diff_line: &crate::model::DiffLine,
+) -> bool {
+    ctx.app.diff_files.get(file_idx).is_some_and(|file| {
+        ctx.app
+            .conflicts
+            .is_marker(file.display_path(), &diff_line.content)
+    })
+}
+
 /// Add addition line spans to the spans vector
 fn add_addition_spans(
     theme: &Theme,
     spans: &mut Vec<Span>,
     diff_line: &crate::model::DiffLine,
     content_width: usize,
+    conflict_marker: bool,
 ) {
     let line_num = diff_line
         .new_lineno
@@ -1074,8 +1211,10 @@ fn add_addition_spans(
     ));
     spans.push(Span::styled("▌".to_string(), styles::diff_add_style(theme)));
 
-    // Use syntax highlighting if available
-    if let Some(ref highlighted) = diff_line.highlighted_spans {
+    if conflict_marker {
+        let content = truncate_or_pad(&diff_line.content, content_width);
+        spans.push(Span::styled(content, styles::conflict_marker_style(theme)));
+    } else if let Some(ref highlighted) = diff_line.highlighted_spans {
         let syntax_pad_style = Style::default().fg(theme.diff_add).bg(theme.syntax_add_bg);
         let content_spans = truncate_or_pad_spans(highlighted, content_width, syntax_pad_style);
         spans.extend(content_spans);
@@ -1186,7 +1325,11 @@ fn add_comments_to_line(
                         .or_else(|| Some(LineRange::single(line_num)));
                     let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                         ctx.theme,
-                        comment_type_presentation(ctx.app, &ctx.comment_type),
+                        comment_type_presentation(
+                            ctx.app,
+                            &ctx.comment_type,
+                            ctx.app.comment_blocking,
+                        ),
                         ctx.comment_buffer,
                         ctx.comment_cursor,
                         line_range,
@@ -1227,8 +1370,9 @@ fn add_comments_to_line(
                         .or_else(|| Some(LineRange::single(line_num)));
                     let comment_lines = comment_panel::format_comment_lines(
                         ctx.theme,
-                        comment_type_presentation(ctx.app, &comment.comment_type),
+                        comment_type_presentation(ctx.app, &comment.comment_type, comment.blocking),
                         &comment.content,
+                        comment.author.as_deref(),
                         line_range,
                     );
                     let box_top_row = line_idx;
@@ -1261,7 +1405,7 @@ fn add_comments_to_line(
             .or_else(|| Some(LineRange::single(line_num)));
         let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
             ctx.theme,
-            comment_type_presentation(ctx.app, &ctx.comment_type),
+            comment_type_presentation(ctx.app, &ctx.comment_type, ctx.app.comment_blocking),
             ctx.comment_buffer,
             ctx.comment_cursor,
             line_range,
@@ -1310,7 +1454,8 @@ mod remote_comments_side_by_side_snapshot_tests {
     };
     use crate::forge::traits::{ForgeRepository, PrSessionKey};
     use crate::model::{
-        DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin, ReviewSession, SessionDiffSource,
+        DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin, ReviewSession,
+        SessionDiffSource,
     };
     use crate::syntax::SyntaxHighlighter;
     use crate::theme::Theme;
@@ -1392,6 +1537,8 @@ mod remote_comments_side_by_side_snapshot_tests {
             is_too_large: false,
             is_commit_message: false,
             content_hash,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         }
     }
 
@@ -9,17 +9,19 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::app::{App, ExpandDirection, FocusedPanel, GAP_EXPAND_BATCH, GapId, InputMode};
 use crate::forge::remote_comments::PrCommentsVisibility;
-use crate::model::{LineOrigin, LineRange, LineSide};
+use crate::model::{FileStatus, LineOrigin, LineRange, LineSide};
 use crate::theme::Theme;
 use crate::ui::comment_panel;
 use crate::ui::diff_view::{
-    apply_horizontal_scroll, comment_type_presentation, cursor_indicator, cursor_indicator_spaced,
-    diff_stat_title, is_line_highlighted, paint_unified_diff_rows_with,
-    paint_visual_selection_overlay, populate_row_to_annotation, push_comment_bar,
-    render_expander_line, render_hidden_lines, scroll_comment_input_into_view,
-    unified_line_bg_style,
+    SAMPLED_HUNK_MARK, apply_horizontal_scroll, comment_type_presentation, cursor_indicator,
+    cursor_indicator_spaced, diff_line_indicator, diff_stat_title, is_line_highlighted,
+    paint_unified_diff_rows_with, paint_visual_selection_overlay, populate_row_to_annotation,
+    push_comment_bar, refined_hunk, render_elided_context, render_expander_line,
+    render_file_metadata, render_hidden_lines, render_history_comments,
+    scroll_comment_input_into_view, unified_line_bg_style,
 };
 use crate::ui::styles;
+use crate::ui::text_utils::file_header_label;
 use crate::vcs::git::calculate_gap;
 
 pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect) {
@@ -29,6 +31,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
 
     let block = Block::default()
         .title(title)
+        .title_style(styles::title_style(&app.theme, focused))
         .title_top(diff_stat_title(app).right_aligned())
         .borders(Borders::ALL)
         .style(styles::panel_style(&app.theme))
@@ -62,7 +65,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
     let mut comment_bars: Vec<crate::ui::diff_view::CommentBarAnchor> = Vec::new();
 
     let is_review_comment_mode =
-        app.input_mode == InputMode::Comment && app.comment_is_review_level;
+        app.is_mode_open(InputMode::Comment) && app.comment_is_review_level;
 
     let general_indicator = cursor_indicator_spaced(line_idx, current_line_idx);
     lines.push(Line::from(vec![
@@ -85,7 +88,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
         if is_being_edited {
             let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                 &app.theme,
-                comment_type_presentation(app, &app.comment_type),
+                comment_type_presentation(app, &app.comment_type, app.comment_blocking),
                 &app.comment_buffer,
                 app.comment_cursor,
                 None,
@@ -112,8 +115,9 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
         } else {
             let comment_lines = comment_panel::format_comment_lines(
                 &app.theme,
-                comment_type_presentation(app, &comment.comment_type),
+                comment_type_presentation(app, &comment.comment_type, comment.blocking),
                 &comment.content,
+                comment.author.as_deref(),
                 None,
             );
             for mut comment_line in comment_lines {
@@ -131,7 +135,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
     if is_review_comment_mode && app.editing_comment_id.is_none() {
         let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
             &app.theme,
-            comment_type_presentation(app, &app.comment_type),
+            comment_type_presentation(app, &app.comment_type, app.comment_blocking),
             &app.comment_buffer,
             app.comment_cursor,
             None,
@@ -157,18 +161,31 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
     for (file_idx, file) in app.diff_files.iter().enumerate() {
         let path = file.display_path();
         let status = file.status.as_char();
-        let is_reviewed = app.session.is_file_reviewed(path);
+        let is_folded = app.is_file_folded(path);
 
         // File header
         let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
 
         // Add checkmark if reviewed (using same character as file list)
-        let review_mark = if is_reviewed { "✓ " } else { "" };
+        let review_mark = app
+            .file_confidence(path)
+            .map(|confidence| format!("{} ", confidence.mark()))
+            .unwrap_or_default();
 
         let header_text = if file.is_commit_message {
             format!("═══ {}Commit Message ", review_mark)
         } else {
-            format!("═══ {}{} [{}] ", review_mark, path.display(), status)
+            let collapsed = if app.is_collapsed_by_filetype(path) {
+                "(collapsed, Enter opens) "
+            } else {
+                ""
+            };
+            format!(
+                "═══ {}{} [{}] {collapsed}",
+                review_mark,
+                file_header_label(file),
+                status
+            )
         };
         lines.push(Line::from(vec![
             Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
@@ -177,13 +194,13 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
         ]));
         line_idx += 1;
 
-        // If file is reviewed, skip rendering the body (fold it away)
-        if is_reviewed {
+        // If file is reviewed or collapsed, skip rendering the body (fold it away)
+        if is_folded {
             continue;
         }
 
         // Check if we're editing/adding a file-level comment for this file
-        let is_file_comment_mode = app.input_mode == InputMode::Comment
+        let is_file_comment_mode = app.is_mode_open(InputMode::Comment)
             && app.comment_is_file_level
             && file_idx == app.diff_state.current_file_idx;
 
@@ -198,7 +215,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                     // Render the inline input instead
                     let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                         &app.theme,
-                        comment_type_presentation(app, &app.comment_type),
+                        comment_type_presentation(app, &app.comment_type, app.comment_blocking),
                         &app.comment_buffer,
                         app.comment_cursor,
                         None,
@@ -230,8 +247,9 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                 } else {
                     let comment_lines = comment_panel::format_comment_lines(
                         &app.theme,
-                        comment_type_presentation(app, &comment.comment_type),
+                        comment_type_presentation(app, &comment.comment_type, comment.blocking),
                         &comment.content,
+                        comment.author.as_deref(),
                         None,
                     );
                     for mut comment_line in comment_lines {
@@ -254,7 +272,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
         if is_file_comment_mode && app.editing_comment_id.is_none() {
             let (input_lines, cursor_info) = comment_panel::format_comment_input_lines(
                 &app.theme,
-                comment_type_presentation(app, &app.comment_type),
+                comment_type_presentation(app, &app.comment_type, app.comment_blocking),
                 &app.comment_buffer,
                 app.comment_cursor,
                 None,
@@ -279,6 +297,15 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
             }
         }
 
+        let metadata = file.metadata_lines();
+        render_file_metadata(
+            &mut lines,
+            &mut line_idx,
+            current_line_idx,
+            &metadata,
+            &app.theme,
+        );
+
         if file.is_too_large {
             let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
             lines.push(Line::from(vec![
@@ -294,12 +321,14 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
             ]));
             line_idx += 1;
         } else if file.hunks.is_empty() {
-            let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
-            lines.push(Line::from(vec![
-                Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
-                Span::styled("(no changes)", styles::dim_style(&app.theme)),
-            ]));
-            line_idx += 1;
+            if metadata.is_empty() || file.status == FileStatus::Deleted {
+                let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
+                lines.push(Line::from(vec![
+                    Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
+                    Span::styled(file.empty_label(), styles::dim_style(&app.theme)),
+                ]));
+                line_idx += 1;
+            }
         } else {
             // Get line comments for this file
             let line_comments = app
@@ -311,6 +340,8 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                 .unwrap_or_default();
 
             for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+                let refined = refined_hunk(app, file_idx, hunk_idx, hunk);
+                let hunk = refined.as_ref().unwrap_or(hunk);
                 // Calculate and render gap before this hunk
                 let prev_hunk = if hunk_idx > 0 {
                     file.hunks.get(hunk_idx - 1)
@@ -417,8 +448,14 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
 
                 // Hunk header
                 let indicator = cursor_indicator_spaced(line_idx, current_line_idx);
+                let sample_mark = if app.is_sampled_hunk(path, hunk) {
+                    SAMPLED_HUNK_MARK
+                } else {
+                    ""
+                };
                 lines.push(Line::from(vec![
                     Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
+                    Span::styled(sample_mark, styles::pending_style(&app.theme)),
                     Span::styled(
                         hunk.header.to_string(),
                         styles::diff_hunk_header_style(&app.theme),
@@ -427,14 +464,35 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                 line_idx += 1;
 
                 // Diff lines
-                for diff_line in &hunk.lines {
+                let elided = app.elided_runs(file_idx, hunk_idx);
+                let mut skip_until = 0;
+                for (hunk_line_idx, diff_line) in hunk.lines.iter().enumerate() {
+                    if hunk_line_idx < skip_until {
+                        continue;
+                    }
+                    if let Some(run) = elided.iter().find(|run| run.start == hunk_line_idx) {
+                        render_elided_context(
+                            &mut lines,
+                            &mut line_idx,
+                            current_line_idx,
+                            run.count,
+                            &app.theme,
+                        );
+                        skip_until = run.start + run.count;
+                        continue;
+                    }
                     let (prefix, base_style) = match diff_line.origin {
                         LineOrigin::Addition => ("▌", styles::diff_add_style(&app.theme)),
                         LineOrigin::Deletion => ("▌", styles::diff_del_style(&app.theme)),
                         LineOrigin::Context => (" ", styles::diff_context_style(&app.theme)),
                     };
 
-                    let style = base_style;
+                    let is_conflict_marker = app.conflicts.is_marker(path, &diff_line.content);
+                    let style = if is_conflict_marker {
+                        styles::conflict_marker_style(&app.theme)
+                    } else {
+                        base_style
+                    };
 
                     let line_num_str = match diff_line.origin {
                         LineOrigin::Addition => diff_line
@@ -452,17 +510,24 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                             .unwrap_or_else(|| "     ".to_string()),
                     };
 
-                    let indicator = cursor_indicator(line_idx, current_line_idx);
+                    let indicator = diff_line_indicator(
+                        line_idx,
+                        current_line_idx,
+                        app.is_line_deferred(file_idx, diff_line),
+                        &app.theme,
+                    );
 
                     let line_num_style = styles::dim_style(&app.theme);
 
                     let mut line_spans = vec![
-                        Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
+                        indicator,
                         Span::styled(line_num_str, line_num_style),
                         Span::styled(format!("{prefix} "), style),
                     ];
 
-                    if let Some(ref highlighted) = diff_line.highlighted_spans {
+                    if let Some(ref highlighted) = diff_line.highlighted_spans
+                        && !is_conflict_marker
+                    {
                         for (span_style, span_text) in highlighted {
                             line_spans.push(Span::styled(span_text.clone(), *span_style));
                         }
@@ -476,7 +541,11 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                         diff_line.origin,
                         LineOrigin::Addition | LineOrigin::Deletion
                     ) {
-                        let eol_style = match diff_line.highlighted_spans.as_ref() {
+                        let eol_style = match diff_line
+                            .highlighted_spans
+                            .as_ref()
+                            .filter(|_| !is_conflict_marker)
+                        {
                             // For syntax-highlighted lines (including empty highlighted lines),
                             // use syntax diff background so row fill matches code spans.
                             Some(_) => {
@@ -502,7 +571,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                     // Old side comments (for deleted lines)
                     if let Some(old_ln) = diff_line.old_lineno {
                         // Check if we're adding/editing a comment on this line (old side)
-                        let is_line_comment_mode = app.input_mode == InputMode::Comment
+                        let is_line_comment_mode = app.is_mode_open(InputMode::Comment)
                             && !app.comment_is_file_level
                             && file_idx == app.diff_state.current_file_idx
                             && app.comment_line == Some((old_ln, LineSide::Old));
@@ -522,7 +591,11 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                                         let (input_lines, cursor_info) =
                                             comment_panel::format_comment_input_lines(
                                                 &app.theme,
-                                                comment_type_presentation(app, &app.comment_type),
+                                                comment_type_presentation(
+                                                    app,
+                                                    &app.comment_type,
+                                                    app.comment_blocking,
+                                                ),
                                                 &app.comment_buffer,
                                                 app.comment_cursor,
                                                 line_range,
@@ -571,8 +644,13 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                                             .or_else(|| Some(LineRange::single(old_ln)));
                                         let comment_lines = comment_panel::format_comment_lines(
                                             &app.theme,
-                                            comment_type_presentation(app, &comment.comment_type),
+                                            comment_type_presentation(
+                                                app,
+                                                &comment.comment_type,
+                                                comment.blocking,
+                                            ),
                                             &comment.content,
+                                            comment.author.as_deref(),
                                             line_range,
                                         );
                                         let box_top_row = line_idx;
@@ -612,6 +690,15 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                             LineSide::Old,
                             &mut comment_bars,
                         );
+                        render_history_comments(
+                            &mut lines,
+                            &mut line_idx,
+                            current_line_idx,
+                            app,
+                            path,
+                            old_ln,
+                            LineSide::Old,
+                        );
 
                         // Render inline input for new line comment (old side)
                         if is_line_comment_mode && app.editing_comment_id.is_none() {
@@ -622,7 +709,11 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                             let (input_lines, cursor_info) =
                                 comment_panel::format_comment_input_lines(
                                     &app.theme,
-                                    comment_type_presentation(app, &app.comment_type),
+                                    comment_type_presentation(
+                                        app,
+                                        &app.comment_type,
+                                        app.comment_blocking,
+                                    ),
                                     &app.comment_buffer,
                                     app.comment_cursor,
                                     line_range,
@@ -656,7 +747,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                     // New side comments (for added/context lines)
                     if let Some(new_ln) = diff_line.new_lineno {
                         // Check if we're adding/editing a comment on this line (new side)
-                        let is_line_comment_mode = app.input_mode == InputMode::Comment
+                        let is_line_comment_mode = app.is_mode_open(InputMode::Comment)
                             && !app.comment_is_file_level
                             && file_idx == app.diff_state.current_file_idx
                             && app.comment_line == Some((new_ln, LineSide::New));
@@ -676,7 +767,11 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                                         let (input_lines, cursor_info) =
                                             comment_panel::format_comment_input_lines(
                                                 &app.theme,
-                                                comment_type_presentation(app, &app.comment_type),
+                                                comment_type_presentation(
+                                                    app,
+                                                    &app.comment_type,
+                                                    app.comment_blocking,
+                                                ),
                                                 &app.comment_buffer,
                                                 app.comment_cursor,
                                                 line_range,
@@ -725,8 +820,13 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                                             .or_else(|| Some(LineRange::single(new_ln)));
                                         let comment_lines = comment_panel::format_comment_lines(
                                             &app.theme,
-                                            comment_type_presentation(app, &comment.comment_type),
+                                            comment_type_presentation(
+                                                app,
+                                                &comment.comment_type,
+                                                comment.blocking,
+                                            ),
                                             &comment.content,
+                                            comment.author.as_deref(),
                                             line_range,
                                         );
                                         let box_top_row = line_idx;
@@ -766,6 +866,15 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                             LineSide::New,
                             &mut comment_bars,
                         );
+                        render_history_comments(
+                            &mut lines,
+                            &mut line_idx,
+                            current_line_idx,
+                            app,
+                            path,
+                            new_ln,
+                            LineSide::New,
+                        );
 
                         // Render inline input for new line comment (new side)
                         if is_line_comment_mode && app.editing_comment_id.is_none() {
@@ -776,7 +885,11 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
                             let (input_lines, cursor_info) =
                                 comment_panel::format_comment_input_lines(
                                     &app.theme,
-                                    comment_type_presentation(app, &app.comment_type),
+                                    comment_type_presentation(
+                                        app,
+                                        &app.comment_type,
+                                        app.comment_blocking,
+                                    ),
                                     &app.comment_buffer,
                                     app.comment_cursor,
                                     line_range,
@@ -853,7 +966,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
     app.diff_state.max_content_width = max_content_width;
 
     let scroll_offset = app.diff_state.scroll_offset;
-    let wrap = app.diff_state.wrap_lines;
+    let wrap = app.wrap_lines();
     app.diff_state.visible_line_count = populate_row_to_annotation(
         &mut app.diff_row_to_annotation,
         &line_widths,
@@ -867,13 +980,13 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
     if app.diff_state.scroll_x > max_scroll_x {
         app.diff_state.scroll_x = max_scroll_x;
     }
-    if app.diff_state.wrap_lines {
+    if app.wrap_lines() {
         app.diff_state.scroll_x = 0;
     }
 
     let scroll_x = app.diff_state.scroll_x;
     let visible_lines_unscrolled_for_bg = visible_lines_unscrolled.clone();
-    let visible_lines: Vec<Line> = if app.diff_state.wrap_lines {
+    let visible_lines: Vec<Line> = if app.wrap_lines() {
         visible_lines_unscrolled
     } else {
         visible_lines_unscrolled
@@ -888,7 +1001,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
         inner,
         &visible_lines_unscrolled_for_bg,
         &line_widths,
-        app.diff_state.wrap_lines,
+        app.wrap_lines(),
         inner.width as usize,
         |_idx, line| unified_line_bg_style(line, &app.theme),
     );
@@ -897,7 +1010,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
         inner,
         visible_lines_unscrolled: &visible_lines_unscrolled_for_bg,
         line_widths: &line_widths,
-        wrap_lines: app.diff_state.wrap_lines,
+        wrap_lines: app.wrap_lines(),
         viewport_width: inner.width as usize,
         scroll_x,
         scroll_offset: app.diff_state.scroll_offset,
@@ -912,7 +1025,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
 
     // Keep paragraph bg unset so pre-painted per-row diff backgrounds remain visible.
     let mut diff = Paragraph::new(visible_lines).style(Style::default().fg(app.theme.fg_primary));
-    if app.diff_state.wrap_lines {
+    if app.wrap_lines() {
         diff = diff.wrap(Wrap { trim: false });
     }
     frame.render_widget(diff, inner);
@@ -925,7 +1038,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
             inner,
             &visible_lines_unscrolled_for_bg,
             &line_widths,
-            app.diff_state.wrap_lines,
+            app.wrap_lines(),
             inner.width as usize,
             |idx, _line| {
                 is_line_highlighted(app, idx).then(|| Style::default().bg(app.theme.cursor_line_bg))
@@ -962,7 +1075,7 @@ pub(super) fn render_unified_diff(frame: &mut Frame, app: &mut App, area: Rect)
             let mut visual_row: u16 = 0;
             let viewport_width = inner.width as usize;
 
-            if app.diff_state.wrap_lines && viewport_width > 0 {
+            if app.wrap_lines() && viewport_width > 0 {
                 // Calculate how many visual rows the lines before cursor take
                 // Note: line_widths is indexed from 0 and corresponds to visible lines
                 // (i.e., line_widths[0] is the first visible line after scroll)
@@ -1105,7 +1218,8 @@ mod remote_comments_snapshot_tests {
     };
     use crate::forge::traits::{ForgeRepository, PrSessionKey};
     use crate::model::{
-        DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin, ReviewSession, SessionDiffSource,
+        DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin, ReviewSession,
+        SessionDiffSource,
     };
     use crate::syntax::SyntaxHighlighter;
     use crate::theme::Theme;
@@ -1189,6 +1303,8 @@ mod remote_comments_snapshot_tests {
             is_too_large: false,
             is_commit_message: false,
             content_hash,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         }
     }
 
@@ -1,19 +1,21 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
 };
 
 use crate::app::{
     AnnotatedLine, App, DiffViewMode, ExpandDirection, GAP_EXPAND_BATCH, VisualSelection,
 };
-use crate::model::LineSide;
+use crate::model::word_diff::{changed_ranges, paired_lines};
+use crate::model::{DiffHunk, DiffLine, LineOrigin, LineSide};
 use crate::theme::Theme;
 use crate::ui::comment_panel;
 use crate::ui::diff_side_by_side::render_side_by_side_diff;
 use crate::ui::diff_unified::render_unified_diff;
 use crate::ui::styles;
+use crate::ui::text_utils::path_label;
 
 pub(super) fn render_diff_view(frame: &mut Frame, app: &mut App, area: Rect) {
     match app.diff_view_mode {
@@ -31,7 +33,7 @@ pub(super) fn diff_title(app: &App, area_width: u16) -> String {
     }
     let path = app
         .current_file_path()
-        .map(|p| p.display().to_string())
+        .map(|p| path_label(p))
         .unwrap_or_default();
 
     // Reserve room for the title's own spacing (` <path> `), the stats title
@@ -105,6 +107,98 @@ pub(super) fn diff_stat_title(app: &App) -> Line<'static> {
     ])
 }
 
+/// `hunk` with the changed words or characters of its paired lines picked
+/// out, when the reviewer switched it to a word diff with `<leader>d`.
+pub(super) fn refined_hunk(
+    app: &App,
+    file_idx: usize,
+    hunk_idx: usize,
+    hunk: &DiffHunk,
+) -> Option<DiffHunk> {
+    let mode = *app.word_diff_hunks.get(&(file_idx, hunk_idx))?;
+    let mut refined = hunk.clone();
+    for (del, add) in paired_lines(&hunk.lines) {
+        let (old, new) = changed_ranges(&hunk.lines[del].content, &hunk.lines[add].content, mode);
+        refined.lines[del].highlighted_spans = Some(emphasize(&hunk.lines[del], &old, &app.theme));
+        refined.lines[add].highlighted_spans = Some(emphasize(&hunk.lines[add], &new, &app.theme));
+    }
+    Some(refined)
+}
+
+/// The spans of `line` (its syntax highlighting, else the plain diff
+/// colors) with the byte `ranges` of its content restyled as changed.
+fn emphasize(
+    line: &DiffLine,
+    ranges: &[std::ops::Range<usize>],
+    theme: &Theme,
+) -> Vec<(Style, String)> {
+    let (plain, changed) = match line.origin {
+        LineOrigin::Deletion => (
+            Style::default().fg(theme.diff_del).bg(theme.syntax_del_bg),
+            styles::word_diff_del_style(theme),
+        ),
+        _ => (
+            Style::default().fg(theme.diff_add).bg(theme.syntax_add_bg),
+            styles::word_diff_add_style(theme),
+        ),
+    };
+    let spans = line
+        .highlighted_spans
+        .clone()
+        .filter(|spans| {
+            spans.iter().map(|(_, text)| text.len()).sum::<usize>() == line.content.len()
+        })
+        .unwrap_or_else(|| vec![(plain, line.content.clone())]);
+    let mut out = Vec::new();
+    let mut offset = 0;
+    for (style, text) in spans {
+        let end = offset + text.len();
+        let mut cuts: Vec<usize> = ranges
+            .iter()
+            .flat_map(|r| [r.start, r.end])
+            .filter(|&cut| cut > offset && cut < end)
+            .collect();
+        cuts.push(end);
+        cuts.sort_unstable();
+        cuts.dedup();
+        let mut start = offset;
+        for cut in cuts {
+            let is_changed = ranges.iter().any(|r| r.start <= start && start < r.end);
+            out.push((
+                if is_changed { changed } else { style },
+                text[start - offset..cut - offset].to_string(),
+            ));
+            start = cut;
+        }
+        offset = end;
+    }
+    out
+}
+
+/// Prefix on the header of a hunk picked by `:spotcheck`.
+pub(super) const SAMPLED_HUNK_MARK: &str = "◆ ";
+
+/// Gutter mark of a diff line the reviewer deferred with `m`.
+const DEFERRED_LINE_MARK: &str = "⚑";
+
+/// Gutter span of a diff line: the cursor arrow, else a flag on deferred
+/// lines.
+pub(super) fn diff_line_indicator(
+    line_idx: usize,
+    current_line_idx: usize,
+    deferred: bool,
+    theme: &Theme,
+) -> Span<'static> {
+    if line_idx != current_line_idx && deferred {
+        Span::styled(DEFERRED_LINE_MARK, styles::pending_style(theme))
+    } else {
+        Span::styled(
+            cursor_indicator(line_idx, current_line_idx),
+            styles::current_line_indicator_style(theme),
+        )
+    }
+}
+
 pub(super) fn cursor_indicator(line_idx: usize, current_line_idx: usize) -> &'static str {
     if line_idx == current_line_idx {
         "▶"
@@ -167,12 +261,85 @@ pub(super) fn render_hidden_lines(
     *line_idx += 1;
 }
 
+/// Render the mode change and symlink target rows below a file header
+pub(super) fn render_file_metadata(
+    lines: &mut Vec<Line<'_>>,
+    line_idx: &mut usize,
+    current_line_idx: usize,
+    metadata: &[String],
+    theme: &Theme,
+) {
+    for text in metadata {
+        let indicator = cursor_indicator_spaced(*line_idx, current_line_idx);
+        lines.push(Line::from(vec![
+            Span::styled(indicator, styles::current_line_indicator_style(theme)),
+            Span::styled(text.clone(), styles::dim_style(theme)),
+        ]));
+        *line_idx += 1;
+    }
+}
+
+/// Render the placeholder for a run of unchanged lines elided from a hunk
+pub(super) fn render_elided_context(
+    lines: &mut Vec<Line<'_>>,
+    line_idx: &mut usize,
+    current_line_idx: usize,
+    count: usize,
+    theme: &Theme,
+) {
+    let indicator = cursor_indicator_spaced(*line_idx, current_line_idx);
+    lines.push(Line::from(vec![
+        Span::styled(indicator, styles::current_line_indicator_style(theme)),
+        Span::styled(
+            format!("       ... {count} unchanged lines ..."),
+            styles::dim_style(theme),
+        ),
+    ]));
+    *line_idx += 1;
+}
+
+/// Render comments left on `line` in earlier sessions as dimmed rows
+#[allow(clippy::too_many_arguments)]
+pub(super) fn render_history_comments(
+    lines: &mut Vec<Line<'_>>,
+    line_idx: &mut usize,
+    current_line_idx: usize,
+    app: &App,
+    file_path: &std::path::Path,
+    line: u32,
+    side: LineSide,
+) {
+    let style = styles::dim_style(&app.theme).add_modifier(Modifier::ITALIC);
+    for entry in app.visible_history_comments(file_path) {
+        if entry.line != line || entry.side != side {
+            continue;
+        }
+        let mut content = entry.comment.content.lines();
+        let first_line = content.next().unwrap_or_default();
+        let more = if content.next().is_some() { " …" } else { "" };
+        let indicator = cursor_indicator_spaced(*line_idx, current_line_idx);
+        lines.push(Line::from(vec![
+            Span::styled(indicator, styles::current_line_indicator_style(&app.theme)),
+            Span::styled(
+                format!(
+                    "       ↺ [{}] {first_line}{more}  ({})",
+                    app.comment_label(&entry.comment.comment_type, entry.comment.blocking),
+                    entry.reviewed_at.format("%Y-%m-%d")
+                ),
+                style,
+            ),
+        ]));
+        *line_idx += 1;
+    }
+}
+
 pub(super) fn comment_type_presentation(
     app: &App,
     comment_type: &crate::model::CommentType,
+    blocking: Option<bool>,
 ) -> comment_panel::CommentTypePresentation {
     comment_panel::CommentTypePresentation {
-        label: app.comment_type_label(comment_type),
+        label: app.comment_label(comment_type, blocking),
         color: app.comment_type_color(comment_type),
     }
 }
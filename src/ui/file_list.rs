@@ -8,24 +8,39 @@ use std::path::Path;
 use unicode_width::UnicodeWidthStr;
 
 use crate::app::{App, FileTreeItem, FocusedPanel};
+use crate::model::{CommentType, ReviewConfidence};
 use crate::ui::diff_view::apply_horizontal_scroll;
 use crate::ui::styles;
+use crate::ui::text_utils::file_name_label;
 
 const EXPANDED_GLYPH: &str = "\u{25bc}"; // ▼
 const COLLAPSED_GLYPH: &str = "\u{25b6}"; // ▶
 const REVIEWED_BOX: &str = "\u{25a3}"; // ▣
+const SKIMMED_BOX: &str = "\u{25a8}"; // ▨
+const THOROUGH_BOX: &str = "\u{25a0}"; // ■
 const UNREVIEWED_BOX: &str = "\u{25a2}"; // ▢
+const CONFLICT_BADGE: &str = " \u{2716} conflict"; // ✖
+const CHANGED_BADGE: &str = " \u{0394} changed"; // Δ
 
 pub(super) fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
     let focused = app.focused_panel == FocusedPanel::FileList;
 
-    let title = format!(
+    let mut title = format!(
         " Files \u{00b7} {}/{} ",
         app.reviewed_count(),
         app.file_count()
     );
+    if app.conflicts_only && !app.conflicts.is_empty() {
+        title.push_str("\u{00b7} conflicts ");
+    }
+    if let Some(filter) = &app.author_filter
+        && app.author_files().is_some()
+    {
+        title.push_str(&format!("\u{00b7} {} ", filter.author));
+    }
     let block = Block::default()
         .title(title)
+        .title_style(styles::title_style(&app.theme, focused))
         .borders(Borders::ALL)
         .style(styles::panel_style(&app.theme))
         .border_style(styles::border_style(&app.theme, focused));
@@ -46,12 +61,17 @@ pub(super) fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
             }
             FileTreeItem::File { file_idx, depth } => {
                 let file = &app.diff_files[*file_idx];
-                let filename = file
-                    .display_path()
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("?");
-                depth * 2 + 4 + filename.width()
+                let path = file.display_path();
+                let filename = file_name_label(path);
+                let badge = app
+                    .findings_hint(path)
+                    .map_or(0, |count| format!(" ⚑{count}").width());
+                let conflict = if app.conflicts.is_conflicted(path) {
+                    CONFLICT_BADGE.width()
+                } else {
+                    0
+                };
+                depth * 2 + 4 + filename.width() + badge + conflict
             }
         })
         .max()
@@ -115,13 +135,14 @@ pub(super) fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 FileTreeItem::File { file_idx, depth } => {
                     let file = &app.diff_files[*file_idx];
                     let path = file.display_path();
-                    let is_reviewed = app.session.is_file_reviewed(path);
-                    let checkbox = if is_reviewed {
-                        REVIEWED_BOX
-                    } else {
-                        UNREVIEWED_BOX
+                    let confidence = app.file_confidence(path);
+                    let checkbox = match confidence {
+                        None => UNREVIEWED_BOX,
+                        Some(ReviewConfidence::Skimmed) => SKIMMED_BOX,
+                        Some(ReviewConfidence::Reviewed) => REVIEWED_BOX,
+                        Some(ReviewConfidence::Thorough) => THOROUGH_BOX,
                     };
-                    let checkbox_style = if is_reviewed {
+                    let checkbox_style = if confidence.is_some() {
                         styles::reviewed_style(&app.theme)
                     } else {
                         styles::pending_style(&app.theme)
@@ -132,10 +153,10 @@ pub(super) fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
                             Span::raw("  Commit Message".to_string()),
                         ])
                     } else {
-                        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("?");
+                        let filename = file_name_label(path);
                         let status = file.status.as_char();
                         let indent = "  ".repeat(*depth);
-                        Line::from(vec![
+                        let mut spans = vec![
                             Span::raw(indent),
                             Span::styled(format!("{checkbox} "), checkbox_style),
                             Span::styled(
@@ -143,7 +164,31 @@ pub(super) fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
                                 styles::file_status_style(&app.theme, status),
                             ),
                             Span::raw(filename.to_string()),
-                        ])
+                        ];
+                        // Reviewed before, but the diff changed since
+                        if app.changed_since_review(path) {
+                            spans.push(Span::styled(
+                                CHANGED_BADGE,
+                                styles::pending_style(&app.theme),
+                            ));
+                        }
+                        if app.conflicts.is_conflicted(path) {
+                            spans.push(Span::styled(
+                                CONFLICT_BADGE,
+                                styles::conflict_marker_style(&app.theme),
+                            ));
+                        }
+                        // Files that keep collecting ISSUE comments across reviews.
+                        if let Some(count) = app.findings_hint(path) {
+                            spans.push(Span::styled(
+                                format!(" ⚑{count}"),
+                                styles::comment_type_style(
+                                    &app.theme,
+                                    app.comment_type_color(&CommentType::Issue),
+                                ),
+                            ));
+                        }
+                        Line::from(spans)
                     }
                 }
             };
@@ -157,7 +202,7 @@ pub(super) fn render_file_list(frame: &mut Frame, app: &mut App, area: Rect) {
     // line.
     let list = List::new(items)
         .style(styles::panel_style(&app.theme))
-        .highlight_style(styles::selected_style(&app.theme))
+        .highlight_style(styles::list_selection_style(&app.theme, focused))
         .block(block);
 
     frame.render_stateful_widget(list, area, &mut app.file_list_state.list_state);
@@ -7,6 +7,7 @@ use ratatui::{
 };
 
 use crate::app::App;
+use crate::input::keybindings::{HelpContext, key_help};
 use crate::ui::styles;
 
 pub fn render_help(frame: &mut Frame, app: &mut App) {
@@ -20,8 +21,42 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
     // Clear the area behind the popup
     frame.render_widget(Clear, area);
 
+    // The output of a command such as `:session merge` takes the help text's place
+    let (title, help_text) = match &app.command_output {
+        Some(output) => (
+            match output.failed {
+                Some(code) => format!(
+                    " {} (exit {code}) - Press q or Esc to close ",
+                    output.command
+                ),
+                None => format!(" {} - Press q or Esc to close ", output.command),
+            },
+            command_output_lines(&output.output),
+        ),
+        None => match app.help_state.context {
+            Some(context) if !app.help_state.show_all => (
+                format!(
+                    " {} keys (a: all keys) - Press ? or Esc to close ",
+                    context.title()
+                ),
+                context_help_lines(context, app.leader_key),
+            ),
+            Some(context) => (
+                format!(
+                    " Help (j/k to scroll, a: {} keys) - Press ? or Esc to close ",
+                    context.title()
+                ),
+                help_lines(app),
+            ),
+            None => (
+                " Help (j/k to scroll) - Press ? or Esc to close ".to_string(),
+                help_lines(app),
+            ),
+        },
+    };
+
     let block = Block::default()
-        .title(" Help (j/k to scroll) - Press ? or Esc to close ")
+        .title(title)
         .borders(Borders::ALL)
         .style(styles::popup_style(theme))
         .border_style(styles::border_style(theme, true));
@@ -29,7 +64,86 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let help_text = vec![
+    // Update help state with total lines and viewport height
+    let total_lines = help_text.len();
+    let viewport_height = inner.height as usize;
+    app.help_state.total_lines = total_lines;
+    app.help_state.viewport_height = viewport_height;
+
+    // Calculate if we can scroll
+    let can_scroll_up = app.help_state.scroll_offset > 0;
+    let can_scroll_down = app.help_state.scroll_offset + viewport_height < total_lines;
+
+    // Apply scroll offset
+    let visible_lines: Vec<Line> = help_text
+        .into_iter()
+        .skip(app.help_state.scroll_offset)
+        .take(viewport_height)
+        .collect();
+
+    let paragraph = Paragraph::new(visible_lines).style(styles::popup_style(theme));
+    frame.render_widget(paragraph, inner);
+
+    // Render scroll indicators
+    let indicator_style = styles::help_indicator_style(theme);
+
+    if can_scroll_up {
+        let up_indicator = Paragraph::new(Line::from(Span::styled("▲ more", indicator_style)));
+        let up_area = Rect {
+            x: inner.x + inner.width.saturating_sub(8),
+            y: inner.y,
+            width: 7,
+            height: 1,
+        };
+        frame.render_widget(up_indicator, up_area);
+    }
+
+    if can_scroll_down {
+        let down_indicator = Paragraph::new(Line::from(Span::styled("▼ more", indicator_style)));
+        let down_area = Rect {
+            x: inner.x + inner.width.saturating_sub(8),
+            y: inner.y + inner.height.saturating_sub(1),
+            width: 7,
+            height: 1,
+        };
+        frame.render_widget(down_indicator, down_area);
+    }
+}
+
+/// A command's output as popup lines, in its own colors.
+fn command_output_lines(output: &str) -> Vec<Line<'static>> {
+    if output.trim().is_empty() {
+        return vec![Line::from("(no output)")];
+    }
+    crate::ansi::to_lines(output)
+}
+
+/// The keys of `context` from the key help table, aligned in one column.
+fn context_help_lines(context: HelpContext, leader_key: char) -> Vec<Line<'static>> {
+    let leader = leader_key.to_string();
+    let rows: Vec<(String, &str)> = key_help(context)
+        .map(|entry| (entry.keys.replace("<leader>", &leader), entry.description))
+        .collect();
+    let width = rows
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    rows.into_iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {keys:<width$}  "),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(description),
+            ])
+        })
+        .collect()
+}
+
+fn help_lines(app: &App) -> Vec<Line<'static>> {
+    vec![
         Line::from(Span::styled(
             "Navigation",
             Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
@@ -65,10 +179,10 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
         ]),
         Line::from(vec![
             Span::styled(
-                "  g/G       ",
+                "  gg/G      ",
                 Style::default().add_modifier(Modifier::BOLD),
             ),
-            Span::raw("Go to first/last file"),
+            Span::raw("Go to top/bottom of focused panel"),
         ]),
         Line::from(vec![
             Span::styled(
@@ -77,6 +191,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Go to source line N in current file"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  {N}%      ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Go to N% of the focused panel"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  {/}       ",
@@ -91,6 +212,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Jump to prev/next hunk"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  [d/]d     ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Jump to prev/next deferred line"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  /         ",
@@ -126,6 +254,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle focus next/previous panel"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  gt/gT     ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Next/previous view (diff, comments, overview, sessions, history, trash)"),
+        ]),
         Line::from(vec![
             Span::styled(
                 format!("  {}h/{}l     ", app.leader_key, app.leader_key),
@@ -147,6 +282,31 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle file list visibility"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                format!("  {}w        ", app.leader_key),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle ignoring whitespace"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                format!("  {}u        ", app.leader_key),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle untracked files"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                format!("  {}y        ", app.leader_key),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Copy hunk as Markdown"),
+        ]),
+        Line::from(Span::styled(
+            "  Navigation and / search act on the focused panel",
+            styles::dim_style(&app.theme),
+        )),
         Line::from(""),
         Line::from(Span::styled(
             "Commit Selector (multi-commit reviews)",
@@ -215,6 +375,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Open selected target or load more"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  m         ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle A..B / A...B (merge-base) range diff"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  /         ",
@@ -303,6 +470,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle file reviewed"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  R         ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Cycle review confidence (skimmed/reviewed/thorough)"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  c         ",
@@ -338,6 +512,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Delete comment at cursor"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  m         ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Defer line to revisit before finishing"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  y         ",
@@ -392,6 +573,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Cycle comment type next/previous"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  Ctrl-b    ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Cycle blocking / non-blocking flag"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  Enter     ",
@@ -482,7 +670,65 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
                 "  :clip     ",
                 Style::default().add_modifier(Modifier::BOLD),
             ),
-            Span::raw("Copy review to clipboard"),
+            Span::raw("Copy review to clipboard (also :copy)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :export <f> ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(
+                "Write the review to a file (.md/.json/.html/.eml/.sarif/.xml/.rdjson/.patch/.annotated.patch/.gerrit.json)",
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :git <args> ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Run git (or :jj) in the repo, show its output, reload"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :todos      ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Write line comments into files as TODO(review) (undo)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :chat [post]",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Copy a Slack/Teams summary, or post it to chat_webhook"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :import <f> ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Merge another reviewer's .json export into this review"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :session merge <f>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Merge another reviewer's session, then list conflicts"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :session export <f>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Write the session for another checkout"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :session import <f>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Resume an exported session here"),
         ]),
         Line::from(vec![
             Span::styled(
@@ -498,12 +744,145 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle line wrap in diff view"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set renames! ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle rename detection"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :renames  ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Show/adjust rename detection (copies, break, 70%)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :vcs      ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Switch colocated jj/git view (git, jj)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :author   ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Show only files changed by an author (off)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :conflicts",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Show only files with conflict markers"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :verify   ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("List comments whose line is gone from the diff or changed"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :ws       ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle ignored whitespace (all, change, blank, off)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :elide <n>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Collapse unchanged runs over n lines (off to show all)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set history! ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle comments from earlier reviews"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set conventional!",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Toggle Conventional Comments labels"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :view <v> ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Switch view (diff, comments, overview, sessions, history, trash)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :history  ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Show the review's event log"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :trash    ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Show deleted comments; Enter restores one"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :checkpoint <l>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Snapshot the review; diff/restore <l> compares or rolls back"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :sessions ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Open, rename (r) or delete (dd) saved reviews"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :rename <n>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Name the review"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :base <r> ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Review the commits since a ref"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :rev <r>  ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Review a jj revset or hg revision"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :interdiff",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" <old> [new]  Diff two versions of a patch series"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :stage    ",
                 Style::default().add_modifier(Modifier::BOLD),
             ),
-            Span::raw("Stage reviewed files"),
+            Span::raw("Stage reviewed files (disabled with --safe)"),
         ]),
         Line::from(vec![
             Span::styled(
@@ -554,6 +933,13 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("  Hide remote comments in PR mode"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :comments import",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("  Copy shown remote comments into the session"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :submit       ",
@@ -591,6 +977,20 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("  Push a pending (draft) review to GitHub"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :gitlab <mr>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("  Post the review as discussions on a GitLab MR"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :bitbucket <pr>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("  Post the review as comments on a Bitbucket PR"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :set commits",
@@ -626,6 +1026,34 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Clear comments only"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :fix      ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Stash a review-only edit of this line (:unfix drops it)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :check N  ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("Toggle checklist item N (:checklist shows progress)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :confidence <level>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Mark file skimmed/reviewed/thorough"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :spotcheck <n>",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Sample N random hunks (:spotcheck jumps to next)"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :q        ",
@@ -662,52 +1090,7 @@ pub fn render_help(frame: &mut Frame, app: &mut App) {
             ),
             Span::raw("Toggle this help"),
         ]),
-    ];
-
-    // Update help state with total lines and viewport height
-    let total_lines = help_text.len();
-    let viewport_height = inner.height as usize;
-    app.help_state.total_lines = total_lines;
-    app.help_state.viewport_height = viewport_height;
-
-    // Calculate if we can scroll
-    let can_scroll_up = app.help_state.scroll_offset > 0;
-    let can_scroll_down = app.help_state.scroll_offset + viewport_height < total_lines;
-
-    // Apply scroll offset
-    let visible_lines: Vec<Line> = help_text
-        .into_iter()
-        .skip(app.help_state.scroll_offset)
-        .take(viewport_height)
-        .collect();
-
-    let paragraph = Paragraph::new(visible_lines).style(styles::popup_style(theme));
-    frame.render_widget(paragraph, inner);
-
-    // Render scroll indicators
-    let indicator_style = styles::help_indicator_style(theme);
-
-    if can_scroll_up {
-        let up_indicator = Paragraph::new(Line::from(Span::styled("▲ more", indicator_style)));
-        let up_area = Rect {
-            x: inner.x + inner.width.saturating_sub(8),
-            y: inner.y,
-            width: 7,
-            height: 1,
-        };
-        frame.render_widget(up_indicator, up_area);
-    }
-
-    if can_scroll_down {
-        let down_indicator = Paragraph::new(Line::from(Span::styled("▼ more", indicator_style)));
-        let down_area = Rect {
-            x: inner.x + inner.width.saturating_sub(8),
-            y: inner.y + inner.height.saturating_sub(1),
-            width: 7,
-            height: 1,
-        };
-        frame.render_widget(down_indicator, down_area);
-    }
+    ]
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
@@ -1,7 +1,7 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
 
@@ -15,6 +15,7 @@ pub(super) fn render_inline_commit_selector(frame: &mut Frame, app: &mut App, ar
 
     let block = Block::default()
         .title(" Commits ")
+        .title_style(styles::title_style(theme, focused))
         .borders(Borders::ALL)
         .style(styles::panel_style(theme))
         .border_style(styles::border_style(theme, focused));
@@ -30,12 +31,19 @@ pub(super) fn render_inline_commit_selector(frame: &mut Frame, app: &mut App, ar
         .iter()
         .enumerate()
         .map(|(i, commit)| {
-            render_commit_row(&CommitRowSpec {
+            let mut row = render_commit_row(&CommitRowSpec {
                 commit,
                 is_cursor: i == app.commit_list_cursor,
                 is_selected: app.is_commit_selected(i),
                 theme,
-            })
+            });
+            if let Some(badge) = app.commit_review_badge(&commit.id) {
+                row.spans.push(Span::styled(
+                    format!("  {badge}"),
+                    styles::reviewed_style(theme),
+                ));
+            }
+            row
         })
         .collect();
 
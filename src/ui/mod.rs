@@ -8,9 +8,11 @@ pub mod file_list;
 pub mod help_popup;
 pub mod inline_commit_selector;
 pub mod selector;
+pub mod startup;
 pub mod status_bar;
 pub mod styles;
 pub mod submit_modals;
 pub mod text_utils;
+pub mod views;
 
 pub use app_layout::render;
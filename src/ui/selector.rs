@@ -1,9 +1,9 @@
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, Paragraph},
 };
 
 use crate::app::{App, TargetTab};
@@ -50,6 +50,50 @@ pub(super) fn render_commit_select(frame: &mut Frame, app: &mut App) {
     render_target_selector_footer(frame, app, chunks[2]);
 }
 
+/// Render the startup chooser over the target selector: the last used
+/// source, working tree, unpushed commits, pick commits or pick a base ref,
+/// moved through with j/k and picked with Enter.
+pub(super) fn render_review_chooser(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let rows = app.review_chooser_rows();
+    let [area] = Layout::vertical([Constraint::Length(rows.len() as u16 + 6)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Length(52)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" What do you want to review? ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .style(styles::popup_style(theme))
+        .border_style(styles::border_style(theme, true));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut lines = vec![Line::from("")];
+    for (i, (label, _)) in rows.iter().enumerate() {
+        let is_cursor = i == app.review_chooser_cursor;
+        let style = if is_cursor {
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let cursor = if is_cursor { ">" } else { " " };
+        lines.push(Line::from(Span::styled(format!("{cursor} {label}"), style)));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter: review   Esc: pick commits",
+        Style::default().fg(theme.fg_secondary),
+    )));
+
+    let paragraph = Paragraph::new(lines).style(styles::popup_style(theme));
+    frame.render_widget(paragraph, inner);
+}
+
 /// Combined top bar: brand on the left, tab chips, then a right slot
 /// carrying `git:<branch>` (Local tab) or the PR-tab status hint. The entire
 /// row uses `status_bar_bg` so the active tab's `bg_highlight` reads as a
@@ -99,7 +143,10 @@ fn render_top_bar(frame: &mut Frame, app: &App, area: Rect) {
         TargetTab::Local => {
             let vcs_type = &app.vcs_info.vcs_type;
             let branch = app.vcs_info.branch_name.as_deref().unwrap_or("detached");
-            let content = format!(" {vcs_type}:{branch} ");
+            let content = match app.selected_range_expression(&app.commit_list) {
+                Some(expr) => format!(" {expr} \u{00b7} {vcs_type}:{branch} "),
+                None => format!(" {vcs_type}:{branch} "),
+            };
             let width = content.chars().count();
             (Span::styled(content, strip_style), width)
         }
@@ -404,7 +451,7 @@ fn render_target_selector_footer(frame: &mut Frame, app: &App, area: Rect) {
     } else {
         match app.target_tab {
             TargetTab::Local => {
-                "   j/k navigate \u{00b7} space range \u{00b7} \u{21b5} confirm \u{00b7} q quit"
+                "   j/k navigate \u{00b7} space range \u{00b7} m ../... \u{00b7} \u{21b5} confirm \u{00b7} q quit"
                     .to_string()
             }
             TargetTab::PullRequests => {
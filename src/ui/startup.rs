@@ -0,0 +1,96 @@
+//! Loading screen shown while `App::new` runs on a background thread.
+
+use std::time::Duration;
+
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Flex, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+};
+
+use crate::app::StartupStage;
+
+const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(120);
+
+/// Styles captured from the theme before it is handed to the startup thread.
+#[derive(Clone, Copy)]
+pub struct StartupStyles {
+    pub panel: Style,
+    pub dim: Style,
+}
+
+pub fn render_startup(
+    frame: &mut Frame,
+    styles: StartupStyles,
+    stage: StartupStage,
+    elapsed: Duration,
+) {
+    let area = frame.area();
+    frame.render_widget(Block::default().style(styles.panel), area);
+
+    let [row] = Layout::vertical([Constraint::Length(2)])
+        .flex(Flex::Center)
+        .areas(area);
+    let frame_idx = (elapsed.as_millis() / SPINNER_INTERVAL.as_millis()) as usize;
+    let spinner = SPINNER_FRAMES[frame_idx % SPINNER_FRAMES.len()];
+    let lines = vec![
+        Line::from(Span::styled(
+            "tuicr",
+            styles.panel.add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!("{spinner} {}…", stage.label()),
+            styles.dim,
+        )),
+    ];
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), row);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn render_to_text(stage: StartupStage, elapsed: Duration) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+        let styles = StartupStyles {
+            panel: Style::default(),
+            dim: Style::default(),
+        };
+        terminal
+            .draw(|frame| render_startup(frame, styles, stage, elapsed))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                text.push_str(buffer[(x, y)].symbol());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn should_render_current_stage_label() {
+        // given / when
+        let text = render_to_text(StartupStage::LoadingChanges, Duration::ZERO);
+
+        // then
+        assert!(text.contains("tuicr"));
+        assert!(text.contains("| Loading changes…"));
+    }
+
+    #[test]
+    fn should_advance_spinner_with_elapsed_time() {
+        // given / when
+        let text = render_to_text(StartupStage::DiscoveringRepository, SPINNER_INTERVAL);
+
+        // then
+        assert!(text.contains("/ Discovering repository…"));
+    }
+}
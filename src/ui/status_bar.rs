@@ -8,9 +8,11 @@ use ratatui::{
     widgets::{Block, Paragraph},
 };
 
-use crate::app::{App, DiffSource, InputMode, Message, MessageType};
+use crate::app::{App, DiffSource, FocusedPanel, InputMode, Message, MessageType, View};
+use crate::model::review::format_duration;
 use crate::theme::Theme;
 use crate::ui::styles;
+use crate::vcs::RangeMode;
 
 pub fn build_message_span(message: Option<&Message>, theme: &Theme) -> (Span<'static>, usize) {
     if let Some(msg) = message {
@@ -74,6 +76,12 @@ pub fn render_header(frame: &mut Frame, app: &App, area: Rect) {
     if let Some(source) = header_source_chunk(app) {
         chunks.push(source);
     }
+    if app.safe_mode {
+        chunks.push("safe".to_string());
+    }
+    if app.read_only {
+        chunks.push("read-only".to_string());
+    }
     let source_text = if chunks.is_empty() {
         String::new()
     } else {
@@ -114,12 +122,28 @@ pub fn render_header(frame: &mut Frame, app: &App, area: Rect) {
         _ => (Span::raw(""), 0),
     };
 
+    // View tabs sit next to the brand; the active one uses the mode style.
+    let mut tab_spans = Vec::new();
+    for view in View::ALL {
+        let style = if view == app.view {
+            styles::mode_style(theme)
+        } else {
+            styles::dim_style(theme)
+        };
+        tab_spans.push(Span::raw(" "));
+        tab_spans.push(Span::styled(format!(" {} ", view.label()), style));
+    }
+    let tabs_width: usize = tab_spans.iter().map(|s| s.content.chars().count()).sum();
+
     let total_width = area.width as usize;
     let brand_width = brand.content.chars().count();
     let right_width = source_width + update_width;
-    let pad_width = total_width.saturating_sub(brand_width + right_width);
+    let pad_width = total_width.saturating_sub(brand_width + tabs_width + right_width);
 
-    let mut spans = vec![brand, Span::raw(" ".repeat(pad_width)), source_span];
+    let mut spans = vec![brand];
+    spans.extend(tab_spans);
+    spans.push(Span::raw(" ".repeat(pad_width)));
+    spans.push(source_span);
     if update_width > 0 {
         spans.push(update_span);
     }
@@ -132,21 +156,28 @@ pub fn render_header(frame: &mut Frame, app: &App, area: Rect) {
 
 /// Short, lowercase description of the active review source. Returns `None`
 /// for plain working-tree review (no extra label needed beyond `vcs:branch`).
-fn header_source_chunk(app: &App) -> Option<String> {
+pub(super) fn header_source_chunk(app: &App) -> Option<String> {
     match &app.diff_source {
         DiffSource::WorkingTree => None,
         DiffSource::Staged => Some("staged".to_string()),
         DiffSource::Unstaged => Some("unstaged".to_string()),
         DiffSource::StagedAndUnstaged => Some("staged + unstaged".to_string()),
         DiffSource::CommitRange(commits) => {
-            if commits.len() == 1 {
+            if commits.len() == 1 && app.range_mode == RangeMode::TwoDot {
                 Some(format!("commit {}", &commits[0][..7.min(commits[0].len())]))
             } else {
-                match app.commit_selection_range {
-                    Some((start, end)) if end - start + 1 < app.review_commits.len() => Some(
-                        format!("{}/{} commits", end - start + 1, app.review_commits.len()),
-                    ),
-                    _ => Some(format!("{} commits", commits.len())),
+                let count = match app.commit_selection_range {
+                    Some((start, end)) if end - start + 1 < app.review_commits.len() => {
+                        format!("{}/{} commits", end - start + 1, app.review_commits.len())
+                    }
+                    _ => format!("{} commits", commits.len()),
+                };
+                let expr = app
+                    .selected_range_expression(&app.review_commits)
+                    .or_else(|| app.range_expression(commits));
+                match expr {
+                    Some(expr) => Some(format!("{count} \u{00b7} {expr}")),
+                    None => Some(count),
                 }
             }
         }
@@ -234,6 +265,9 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             InputMode::SubmitResolver => " RESOLVE ".to_string(),
             InputMode::SubmitConfirm => " SUBMIT ".to_string(),
             InputMode::SubmitActionPicker => " SUBMIT ".to_string(),
+            InputMode::ReviewChooser => " REVIEW ".to_string(),
+            InputMode::VerifyPanel => " VERIFY ".to_string(),
+            InputMode::CompletionDialog => " DONE ".to_string(),
         };
 
         let mode_span = Span::styled(mode_str, styles::mode_style(theme));
@@ -242,13 +276,27 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Cow::Borrowed("")
         } else {
             match app.input_mode {
+                InputMode::Normal if app.view == View::Sessions => Cow::Borrowed(
+                    "   j/k move \u{00b7} \u{21b5} open \u{00b7} r rename \u{00b7} dd delete \u{00b7} esc diff",
+                ),
+                InputMode::Normal if app.view == View::Trash => Cow::Borrowed(
+                    "   j/k move \u{00b7} \u{21b5} restore \u{00b7} gt/gT view \u{00b7} esc diff",
+                ),
+                InputMode::Normal if app.view != View::Diff => Cow::Borrowed(
+                    "   j/k move \u{00b7} \u{21b5} open \u{00b7} gt/gT view \u{00b7} esc diff \u{00b7} ? help",
+                ),
+                InputMode::Normal if app.focused_panel == FocusedPanel::FileList => Cow::Borrowed(
+                    "   j/k select \u{00b7} \u{21b5} open \u{00b7} / search files \u{00b7} r reviewed \u{00b7} ? help",
+                ),
                 InputMode::Normal => Cow::Borrowed(
                     "   j/k scroll \u{00b7} {/} file \u{00b7} r reviewed \u{00b7} c comment \u{00b7} ? help",
                 ),
                 InputMode::Command => Cow::Borrowed("   \u{21b5} execute \u{00b7} esc cancel"),
                 InputMode::Search => Cow::Borrowed("   \u{21b5} search \u{00b7} esc cancel"),
-                InputMode::Comment => Cow::Borrowed("   ctrl-s save \u{00b7} esc cancel"),
-                InputMode::Help => Cow::Borrowed("   q/?/esc close"),
+                InputMode::Comment => {
+                    Cow::Borrowed("   ctrl-s save \u{00b7} esc cancel \u{00b7} F1 help")
+                }
+                InputMode::Help => Cow::Borrowed("   a all keys \u{00b7} q/?/esc close"),
                 InputMode::Confirm => Cow::Borrowed("   y yes \u{00b7} n no"),
                 InputMode::CommitSelect => Cow::Borrowed(
                     "   j/k navigate \u{00b7} space select \u{00b7} \u{21b5} confirm \u{00b7} esc back",
@@ -265,6 +313,15 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 InputMode::SubmitActionPicker => {
                     Cow::Borrowed("   j/k move \u{00b7} \u{21b5} submit \u{00b7} esc cancel")
                 }
+                InputMode::ReviewChooser => {
+                    Cow::Borrowed("   j/k move \u{00b7} \u{21b5} review \u{00b7} esc pick commits")
+                }
+                InputMode::VerifyPanel => Cow::Borrowed(
+                    "   j/k move \u{00b7} r re-anchor \u{00b7} f detach \u{00b7} d delete \u{00b7} esc close",
+                ),
+                InputMode::CompletionDialog => {
+                    Cow::Borrowed("   j/k move \u{00b7} \u{21b5} run \u{00b7} esc keep reviewing")
+                }
             }
         };
         let hints_span = Span::styled(hints, Style::default().fg(theme.fg_secondary));
@@ -297,6 +354,20 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             ),
             width,
         )
+    } else if let Some(post) = app.discussion_post_state.as_ref() {
+        let glyph = crate::ui::selector::pr_open_spinner_glyph(post.started_at.elapsed());
+        let content = format!(" {glyph} Posting to {}… ", post.target.display_name());
+        let width = content.chars().count();
+        (
+            Span::styled(
+                content,
+                Style::default()
+                    .fg(theme.message_info_fg)
+                    .bg(theme.message_info_bg)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            width,
+        )
     } else if let Some(reload) = app.pr_reload_state.as_ref() {
         let glyph = crate::ui::selector::pr_open_spinner_glyph(reload.started_at.elapsed());
         let content = format!(" {glyph} Reloading PR… ");
@@ -311,6 +382,29 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             ),
             width,
         )
+    } else if let Some(reload) = app.vcs_reload_state.as_ref() {
+        let glyph = crate::ui::selector::pr_open_spinner_glyph(reload.started_at.elapsed());
+        // Name the command once it is slow enough to be worth cancelling.
+        let content = match crate::process::oldest_running_command()
+            .filter(|(_, elapsed)| elapsed.as_secs() >= 1)
+        {
+            Some((label, elapsed)) => format!(
+                " {glyph} Reloading… {label} {}s \u{00b7} esc cancels ",
+                elapsed.as_secs()
+            ),
+            None => format!(" {glyph} Reloading… "),
+        };
+        let width = content.chars().count();
+        (
+            Span::styled(
+                content,
+                Style::default()
+                    .fg(theme.message_info_fg)
+                    .bg(theme.message_info_bg)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            width,
+        )
     } else if let Some(range) = app.pr_range_reload_state.as_ref() {
         let glyph = crate::ui::selector::pr_open_spinner_glyph(range.started_at.elapsed());
         let content = format!(" {glyph} Loading range diff… ");
@@ -332,15 +426,23 @@ pub fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Span::styled(content, Style::default().fg(theme.fg_dim)),
             width,
         )
-    } else if app.dirty {
-        let content = " \u{2022} modified ".to_string();
-        let width = content.chars().count();
-        (
-            Span::styled(content, Style::default().fg(theme.pending)),
-            width,
-        )
     } else {
-        (Span::raw(""), 0)
+        // Time spent reviewing, after the modified indicator when both show
+        let spent =
+            (app.session.active_ms >= 1000).then(|| format_duration(app.session.active_ms / 1000));
+        let content = match (app.dirty, spent) {
+            (true, Some(spent)) => format!(" \u{2022} modified \u{00b7} {spent} "),
+            (true, None) => " \u{2022} modified ".to_string(),
+            (false, Some(spent)) => format!(" {spent} "),
+            (false, None) => String::new(),
+        };
+        let width = content.chars().count();
+        let color = if app.dirty {
+            theme.pending
+        } else {
+            theme.fg_dim
+        };
+        (Span::styled(content, Style::default().fg(color)), width)
     };
     let total_width = area.width as usize;
     let spans = build_right_aligned_spans(left_spans, right_span, right_width, total_width);
@@ -526,6 +628,27 @@ mod pr_header_snapshot_tests {
         assert!(line.contains("Add forge-backed PR review"), "got: {line:?}");
     }
 
+    #[test]
+    fn should_render_view_tabs_after_brand() {
+        // given
+        let mut app = build_pr_app(pr_source(false, false));
+        app.view = crate::app::View::Overview;
+        // when
+        let buffer = draw_header(&app);
+        // then
+        let line = row_text(&buffer, 0);
+        assert!(
+            line.starts_with(" tuicr   Diff   Comments   Overview   Sessions "),
+            "got: {line:?}"
+        );
+        let overview_x = line.find("Overview").unwrap() as u16;
+        assert_eq!(
+            buffer[(overview_x, 0)].style().bg,
+            Some(app.theme.mode_bg),
+            "active tab should use the mode style"
+        );
+    }
+
     // Read-only badges are no longer shown in the header: the `PR Mode`
     // tag itself signals the user is on a forge-managed review; whether
     // the PR is open/closed/merged is left to the submit flow to surface
@@ -18,6 +18,15 @@ pub fn diff_del_style(theme: &Theme) -> Style {
     Style::default().fg(theme.diff_del).bg(theme.diff_del_bg)
 }
 
+/// Words or characters that changed, in a hunk switched to a word diff
+pub fn word_diff_add_style(theme: &Theme) -> Style {
+    diff_add_style(theme).add_modifier(Modifier::REVERSED | Modifier::BOLD)
+}
+
+pub fn word_diff_del_style(theme: &Theme) -> Style {
+    diff_del_style(theme).add_modifier(Modifier::REVERSED | Modifier::BOLD)
+}
+
 pub fn diff_context_style(theme: &Theme) -> Style {
     Style::default().fg(theme.diff_context)
 }
@@ -54,6 +63,27 @@ pub fn border_style(theme: &Theme, focused: bool) -> Style {
     }
 }
 
+/// Panel titles: bold in the border color when focused, dimmed otherwise.
+pub fn title_style(theme: &Theme, focused: bool) -> Style {
+    if focused {
+        Style::default()
+            .fg(theme.border_focused)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.fg_dim)
+    }
+}
+
+/// Selected row in a list panel. Unfocused panels keep the row marked but
+/// dim its text so only the focused panel's cursor stands out.
+pub fn list_selection_style(theme: &Theme, focused: bool) -> Style {
+    if focused {
+        selected_style(theme).add_modifier(Modifier::BOLD)
+    } else {
+        selected_style(theme).fg(theme.fg_dim)
+    }
+}
+
 pub fn panel_style(theme: &Theme) -> Style {
     Style::default().bg(theme.panel_bg).fg(theme.fg_primary)
 }
@@ -131,6 +161,14 @@ pub fn error_inline_style(theme: &Theme) -> Style {
         .add_modifier(Modifier::BOLD)
 }
 
+/// Conflict markers (`<<<<<<<`, `%%%%%%%`, ...) materialized into a file
+pub fn conflict_marker_style(theme: &Theme) -> Style {
+    Style::default()
+        .fg(theme.message_warning_fg)
+        .bg(theme.message_warning_bg)
+        .add_modifier(Modifier::BOLD)
+}
+
 pub fn pseudo_commit_tag_style(theme: &Theme) -> Style {
     Style::default().fg(theme.file_modified)
 }
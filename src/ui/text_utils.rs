@@ -1,6 +1,60 @@
+use std::path::Path;
+
 use ratatui::{style::Style, text::Span};
 use unicode_width::UnicodeWidthStr;
 
+use crate::model::{DiffFile, FileStatus};
+
+/// A path as shown on screen: decoded (never octal-escaped for non-ASCII),
+/// with control characters escaped the way git quotes them so a stray
+/// newline or escape sequence in a file name cannot garble the terminal.
+pub(super) fn path_label(path: &Path) -> String {
+    escape_control_chars(&path.to_string_lossy())
+}
+
+/// The path shown in a file's diff header: `old → new` for renames and
+/// copies, otherwise the file's own path.
+pub(super) fn file_header_label(file: &DiffFile) -> String {
+    let path = file.display_path();
+    match &file.old_path {
+        Some(old)
+            if matches!(file.status, FileStatus::Renamed | FileStatus::Copied) && old != path =>
+        {
+            format!("{} → {}", path_label(old), path_label(path))
+        }
+        _ => path_label(path),
+    }
+}
+
+/// The final component of `path`, escaped like [`path_label`].
+pub(super) fn file_name_label(path: &Path) -> String {
+    path.file_name()
+        .map(|name| escape_control_chars(&name.to_string_lossy()))
+        .unwrap_or_else(|| "?".to_string())
+}
+
+pub(super) fn escape_control_chars(s: &str) -> String {
+    if !s.chars().any(char::is_control) {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len() + 4);
+    for ch in s.chars() {
+        match ch {
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ if ch.is_control() => {
+                let mut buf = [0; 4];
+                for byte in ch.encode_utf8(&mut buf).bytes() {
+                    out.push_str(&format!("\\{byte:03o}"));
+                }
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
 pub(super) fn truncate_str(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -97,6 +151,17 @@ pub(super) fn truncate_or_pad_spans(
 mod tests {
     use super::*;
 
+    #[test]
+    fn should_escape_only_control_chars_in_path_labels() {
+        // given
+        let path = Path::new("docs/café\tnotes\u{1b}[31m.md");
+        // when
+        let label = path_label(path);
+        // then
+        assert_eq!(label, "docs/café\\tnotes\\033[31m.md");
+        assert_eq!(file_name_label(Path::new("src/naïve.rs")), "naïve.rs");
+    }
+
     #[test]
     fn should_return_string_unchanged_when_within_max_len() {
         // given
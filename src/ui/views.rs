@@ -0,0 +1,428 @@
+//! Rendering for the non-diff top-level views (Comments, Overview, Sessions,
+//! History, Trash).
+
+use std::path::PathBuf;
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::app::{App, View, ViewListState};
+use crate::model::{CommentType, ReviewEventKind, ReviewSession, SessionDiffSource};
+use crate::ui::status_bar::header_source_chunk;
+use crate::ui::styles;
+use crate::ui::text_utils::path_label;
+
+pub fn render_view(frame: &mut Frame, app: &mut App, area: Rect) {
+    // Nothing diff-shaped is on screen, so mouse hit-testing must not
+    // resolve against stale panel areas.
+    app.diff_area = None;
+    app.file_list_area = None;
+    app.commit_list_inner_area = None;
+
+    let block = Block::default()
+        .title(format!(" {} ", app.view.label()))
+        .title_style(styles::title_style(&app.theme, true))
+        .borders(Borders::ALL)
+        .style(styles::panel_style(&app.theme))
+        .border_style(styles::border_style(&app.theme, true));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let height = inner.height as usize;
+
+    let lines = match app.view {
+        View::Diff => return,
+        View::Comments => {
+            let lines = comment_lines(app);
+            select_rows(&mut app.comments_view.list, lines, height, &app.theme)
+        }
+        View::Overview => {
+            let lines = overview_lines(app);
+            let state = &mut app.overview_view;
+            state.viewport_height = height;
+            state.cursor = state.cursor.min(lines.len().saturating_sub(height));
+            state.offset = state.cursor;
+            lines.into_iter().skip(state.offset).take(height).collect()
+        }
+        View::Sessions => {
+            let lines = session_lines(app);
+            select_rows(&mut app.sessions_view.list, lines, height, &app.theme)
+        }
+        View::History => {
+            let lines = history_lines(app);
+            select_rows(&mut app.history_view, lines, height, &app.theme)
+        }
+        View::Trash => {
+            let lines = trash_lines(app);
+            select_rows(&mut app.trash_view, lines, height, &app.theme)
+        }
+    };
+
+    frame.render_widget(
+        Paragraph::new(lines).style(styles::panel_style(&app.theme)),
+        inner,
+    );
+}
+
+/// Scroll `list` so its cursor is on screen and highlight the cursor row.
+fn select_rows<'a>(
+    list: &mut ViewListState,
+    lines: Vec<Line<'a>>,
+    height: usize,
+    theme: &crate::theme::Theme,
+) -> Vec<Line<'a>> {
+    list.viewport_height = height;
+    if list.cursor < list.offset {
+        list.offset = list.cursor;
+    } else if height > 0 && list.cursor >= list.offset + height {
+        list.offset = list.cursor + 1 - height;
+    }
+    lines
+        .into_iter()
+        .enumerate()
+        .skip(list.offset)
+        .take(height)
+        .map(|(i, line)| {
+            if i == list.cursor {
+                line.style(styles::list_selection_style(theme, true))
+            } else {
+                line
+            }
+        })
+        .collect()
+}
+
+fn comment_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    if app.comments_view.entries.is_empty() {
+        return vec![Line::from(Span::styled(
+            " No comments yet",
+            styles::dim_style(theme),
+        ))];
+    }
+    app.comments_view
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let comment = app.comment_for_entry(entry)?;
+            let location = app.comment_entry_location(entry);
+            let first_line = comment.content.lines().next().unwrap_or_default();
+            Some(Line::from(vec![
+                Span::styled(
+                    format!(
+                        " [{}] ",
+                        app.comment_label(&comment.comment_type, comment.blocking)
+                    ),
+                    styles::comment_type_style(
+                        theme,
+                        app.comment_type_color(&comment.comment_type),
+                    ),
+                ),
+                Span::styled(format!("{location}  "), styles::dim_style(theme)),
+                Span::raw(first_line.to_string()),
+            ]))
+        })
+        .collect()
+}
+
+fn overview_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let (added, removed) = app
+        .diff_files
+        .iter()
+        .map(|file| file.stat())
+        .fold((0, 0), |(a, r), (fa, fr)| (a + fa, r + fr));
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(" Source     ", bold),
+            Span::raw(header_source_chunk(app).unwrap_or_else(|| "working tree".to_string())),
+        ]),
+        Line::from(vec![
+            Span::styled(" Files      ", bold),
+            Span::raw(format!(
+                "{} ({} reviewed)",
+                app.file_count(),
+                app.reviewed_count()
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled(" Lines      ", bold),
+            Span::styled(format!("+{added}"), styles::diff_add_style(theme)),
+            Span::raw(" "),
+            Span::styled(format!("-{removed}"), styles::diff_del_style(theme)),
+        ]),
+    ];
+
+    let mut by_type: Vec<(String, usize)> = Vec::new();
+    for entry in &app.comments_view.entries {
+        if let Some(comment) = app.comment_for_entry(entry) {
+            let label = app.comment_type_label(&comment.comment_type);
+            match by_type.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, count)) => *count += 1,
+                None => by_type.push((label, 1)),
+            }
+        }
+    }
+    let comments = if by_type.is_empty() {
+        "none".to_string()
+    } else {
+        by_type
+            .iter()
+            .map(|(label, count)| format!("{count} {label}"))
+            .collect::<Vec<_>>()
+            .join(" \u{00b7} ")
+    };
+    lines.push(Line::from(vec![
+        Span::styled(" Comments   ", bold),
+        Span::raw(comments),
+    ]));
+    let deferred = app.session.deferred_count();
+    if deferred > 0 {
+        lines.push(Line::from(vec![
+            Span::styled(" Deferred   ", bold),
+            Span::styled(
+                format!("{deferred} to revisit (]d / [d)"),
+                styles::pending_style(theme),
+            ),
+        ]));
+    }
+
+    if !app.author_stats.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::from(Span::styled(" Authors", bold)));
+        let width = app
+            .author_stats
+            .iter()
+            .map(|stats| stats.author.chars().count())
+            .max()
+            .unwrap_or(0);
+        for stats in &app.author_stats {
+            let plural = if stats.commits == 1 { "" } else { "s" };
+            let mut spans = vec![Span::raw(format!(
+                "  {:<width$}  {:>3} commit{plural:<1}",
+                stats.author, stats.commits
+            ))];
+            if let Some((added, removed)) = stats.lines {
+                spans.push(Span::styled(
+                    format!("  +{added}"),
+                    styles::diff_add_style(theme),
+                ));
+                spans.push(Span::styled(
+                    format!(" -{removed}"),
+                    styles::diff_del_style(theme),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+
+    if !app.session.checklist.is_empty() {
+        lines.push(Line::raw(""));
+        lines.push(Line::from(Span::styled(" Checklist", bold)));
+        for item in &app.session.checklist {
+            let mark = if item.done { "x" } else { " " };
+            lines.push(Line::raw(format!("  [{mark}] {}", item.text)));
+        }
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::from(Span::styled(" Files", bold)));
+    for file in &app.diff_files {
+        let path = file.display_path();
+        let (added, removed) = file.stat();
+        let confidence = app.file_confidence(path);
+        let status = file.status.as_char();
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:>3} ", confidence.map(|c| c.mark()).unwrap_or_default()),
+                styles::reviewed_style(theme),
+            ),
+            Span::styled(
+                format!("{status} "),
+                styles::file_status_style(theme, status),
+            ),
+            Span::raw(path_label(path)),
+            Span::styled(format!("  +{added}"), styles::diff_add_style(theme)),
+            Span::styled(format!(" -{removed}"), styles::diff_del_style(theme)),
+        ]));
+    }
+    lines
+}
+
+fn session_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    if let Some(error) = &app.sessions_view.error {
+        return vec![Line::from(Span::styled(
+            format!(" Failed to list sessions: {error}"),
+            styles::error_inline_style(theme),
+        ))];
+    }
+    if app.sessions_view.sessions.is_empty() {
+        return vec![Line::from(Span::styled(
+            " No saved sessions for this repository",
+            styles::dim_style(theme),
+        ))];
+    }
+    app.sessions_view
+        .sessions
+        .iter()
+        .map(|(_, session)| {
+            let current = session.id == app.session.id;
+            let mut spans = vec![
+                Span::styled(
+                    if current { " ● " } else { "   " },
+                    styles::reviewed_style(theme),
+                ),
+                Span::raw(format!("{}  ", session.updated_at.format("%Y-%m-%d %H:%M"))),
+            ];
+            if let Some(name) = &session.name {
+                spans.push(Span::styled(
+                    format!("{name}  "),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+            }
+            let base: String = session.base_commit.chars().take(7).collect();
+            spans.push(Span::styled(
+                format!(
+                    "{} · {} @ {base} ",
+                    session_source_label(session),
+                    session.branch_name.as_deref().unwrap_or("detached")
+                ),
+                styles::dim_style(theme),
+            ));
+            spans.push(Span::raw(format!(
+                "· {}/{} reviewed · {} comments",
+                session.reviewed_count(),
+                session.files.len(),
+                session.comment_count()
+            )));
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn trash_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    if app.session.trash.is_empty() {
+        return vec![Line::from(Span::styled(
+            " Trash is empty",
+            styles::dim_style(theme),
+        ))];
+    }
+    app.session
+        .trash
+        .iter()
+        .rev()
+        .map(|entry| {
+            let comment = &entry.comment;
+            let first_line = comment.content.lines().next().unwrap_or_default();
+            Line::from(vec![
+                Span::styled(
+                    format!(" {}  ", entry.deleted_at.format("%Y-%m-%d %H:%M:%S")),
+                    styles::dim_style(theme),
+                ),
+                Span::styled(
+                    format!(
+                        "[{}] ",
+                        app.comment_label(&comment.comment_type, comment.blocking)
+                    ),
+                    styles::comment_type_style(
+                        theme,
+                        app.comment_type_color(&comment.comment_type),
+                    ),
+                ),
+                Span::styled(format!("{}  ", entry.location()), styles::dim_style(theme)),
+                Span::raw(first_line.to_string()),
+            ])
+        })
+        .collect()
+}
+
+fn history_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = &app.theme;
+    if app.session.events.is_empty() {
+        return vec![Line::from(Span::styled(
+            " No review activity recorded yet",
+            styles::dim_style(theme),
+        ))];
+    }
+    app.session
+        .events
+        .iter()
+        .rev()
+        .map(|event| {
+            let (action, detail) = match &event.kind {
+                ReviewEventKind::FileReviewed { path } => ("reviewed", path_label(path)),
+                ReviewEventKind::FileUnreviewed { path } => ("unreviewed", path_label(path)),
+                ReviewEventKind::CommentAdded {
+                    path,
+                    line,
+                    comment_type,
+                } => (
+                    "commented",
+                    comment_event_detail(app, path, line, comment_type),
+                ),
+                ReviewEventKind::CommentEdited {
+                    path,
+                    line,
+                    comment_type,
+                } => (
+                    "edited",
+                    comment_event_detail(app, path, line, comment_type),
+                ),
+                ReviewEventKind::CommentDeleted {
+                    path,
+                    line,
+                    comment_type,
+                } => (
+                    "deleted",
+                    comment_event_detail(app, path, line, comment_type),
+                ),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {}  ", event.at.format("%Y-%m-%d %H:%M:%S")),
+                    styles::dim_style(theme),
+                ),
+                Span::raw(format!("{action:<11}")),
+                Span::raw(detail),
+            ])
+        })
+        .collect()
+}
+
+fn comment_event_detail(
+    app: &App,
+    path: &Option<PathBuf>,
+    line: &Option<u32>,
+    comment_type: &CommentType,
+) -> String {
+    let location = match (path, line) {
+        (Some(path), Some(line)) => format!("{}:{line}", path_label(path)),
+        (Some(path), None) => path_label(path),
+        (None, _) => "review".to_string(),
+    };
+    format!("[{}] {location}", app.comment_type_label(comment_type))
+}
+
+pub(crate) fn session_source_label(session: &ReviewSession) -> String {
+    match session.diff_source {
+        SessionDiffSource::WorkingTree => "working tree".to_string(),
+        SessionDiffSource::Staged => "staged".to_string(),
+        SessionDiffSource::Unstaged => "unstaged".to_string(),
+        SessionDiffSource::StagedAndUnstaged => "staged + unstaged".to_string(),
+        SessionDiffSource::CommitRange
+        | SessionDiffSource::WorkingTreeAndCommits
+        | SessionDiffSource::StagedUnstagedAndCommits => {
+            let count = session.commit_range.as_ref().map_or(0, Vec::len);
+            format!("{count} commits")
+        }
+        SessionDiffSource::PullRequest => "pull request".to_string(),
+    }
+}
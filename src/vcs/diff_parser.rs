@@ -5,10 +5,10 @@
 //! through this parser to avoid sparse-index limitations in libgit2.
 
 use std::borrow::Cow;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin};
+use crate::model::{DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin};
 use crate::syntax::{SyntaxHighlighter, needs_full_file_highlight};
 
 /// Diff format variants for different VCS tools.
@@ -57,7 +57,8 @@ where
     // `next_line` instead of `lines.next()` to propagate I/O errors.
     while let Some(line) = next_line(&mut lines)? {
         if line.starts_with(header_prefix) {
-            let (mut old_path, mut new_path, status) = parse_file_header(&mut lines, format)?;
+            let (mut old_path, mut new_path, status, modes) =
+                parse_file_header(&mut lines, format)?;
 
             // For git-style diffs (jj, git patches), if parse_file_header didn't find
             // ---/+++ or rename/copy lines (e.g. empty new files, mode-only changes),
@@ -76,6 +77,16 @@ where
                 }
             }
 
+            // A rename or copy that only spelled out one side of the pair
+            // takes the other side from the header.
+            if matches!(status, FileStatus::Renamed | FileStatus::Copied)
+                && (old_path.is_none() || new_path.is_none())
+                && let Some((a, b)) = parse_diff_git_header(&line)
+            {
+                old_path.get_or_insert(a);
+                new_path.get_or_insert(b);
+            }
+
             // Check if binary. `git diff --binary` can emit lowercase
             // "GIT binary patch", so keep this check explicit instead of a
             // case-sensitive substring search.
@@ -90,6 +101,8 @@ where
                     is_too_large: false,
                     is_commit_message: false,
                     content_hash: 0,
+                    modes,
+                    sizes: FileSizes::default(),
                 });
                 continue;
             }
@@ -120,6 +133,8 @@ where
                 is_too_large: false,
                 is_commit_message: false,
                 content_hash,
+                modes,
+                sizes: FileSizes::default(),
             });
         }
     }
@@ -165,18 +180,23 @@ fn is_binary_patch_line(line: &str) -> bool {
 fn parse_file_header<'a, I>(
     lines: &mut std::iter::Peekable<I>,
     format: DiffFormat,
-) -> Result<(Option<PathBuf>, Option<PathBuf>, FileStatus)>
+) -> Result<(Option<PathBuf>, Option<PathBuf>, FileStatus, FileModes)>
 where
     I: Iterator<Item = Result<Cow<'a, str>>>,
 {
     let mut old_path: Option<PathBuf> = None;
     let mut new_path: Option<PathBuf> = None;
+    // Paths from "rename/copy from/to" lines. They win over the ---/+++
+    // lines, which can be missing (pure renames) or carry a timestamp.
+    let mut from_path: Option<PathBuf> = None;
+    let mut to_path: Option<PathBuf> = None;
     let mut status = FileStatus::Modified;
+    let mut modes = FileModes::default();
 
     // Parse --- and +++ lines and metadata
     while let Some(line) = peek_line(lines)?.map(str::to_string) {
         if line.starts_with("---") {
-            let path_str = line.trim_start_matches("--- ").trim_start_matches("a/");
+            let path_str = line.trim_start_matches("--- ");
             if path_str != "/dev/null" {
                 // Hg format may include timestamps after tab
                 let path = if format == DiffFormat::Hg {
@@ -184,40 +204,59 @@ where
                 } else {
                     path_str
                 };
-                old_path = Some(PathBuf::from(path));
+                old_path = Some(strip_path_prefix(unquote_path(path), "a/"));
             }
             next_line(lines)?;
         } else if line.starts_with("+++") {
-            let path_str = line.trim_start_matches("+++ ").trim_start_matches("b/");
+            let path_str = line.trim_start_matches("+++ ");
             if path_str != "/dev/null" {
                 let path = if format == DiffFormat::Hg {
                     path_str.split('\t').next().unwrap_or(path_str)
                 } else {
                     path_str
                 };
-                new_path = Some(PathBuf::from(path));
+                new_path = Some(strip_path_prefix(unquote_path(path), "b/"));
             }
             next_line(lines)?;
             break; // Done with file header
         } else if line.starts_with("new file") {
             status = FileStatus::Added;
+            modes.new = line.strip_prefix("new file mode ").and_then(parse_mode);
             next_line(lines)?;
         } else if line.starts_with("deleted file") {
             status = FileStatus::Deleted;
+            modes.old = line.strip_prefix("deleted file mode ").and_then(parse_mode);
+            next_line(lines)?;
+        } else if let Some(mode) = line.strip_prefix("old mode ") {
+            modes.old = parse_mode(mode);
+            next_line(lines)?;
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            modes.new = parse_mode(mode);
+            next_line(lines)?;
+        } else if let Some(index) = line.strip_prefix("index ") {
+            // "index <old>..<new> <mode>" carries the mode when it's unchanged.
+            if let Some(mode) = index.split_whitespace().nth(1).and_then(parse_mode) {
+                modes.old = modes.old.or((status != FileStatus::Added).then_some(mode));
+                modes.new = modes
+                    .new
+                    .or((status != FileStatus::Deleted).then_some(mode));
+            }
             next_line(lines)?;
         } else if let Some(path) = line.strip_prefix("rename from ") {
             status = FileStatus::Renamed;
-            old_path = Some(PathBuf::from(path));
+            from_path = Some(unquote_path(path));
             next_line(lines)?;
         } else if let Some(path) = line.strip_prefix("rename to ") {
-            new_path = Some(PathBuf::from(path));
+            status = FileStatus::Renamed;
+            to_path = Some(unquote_path(path));
             next_line(lines)?;
         } else if let Some(path) = line.strip_prefix("copy from ") {
             status = FileStatus::Copied;
-            old_path = Some(PathBuf::from(path));
+            from_path = Some(unquote_path(path));
             next_line(lines)?;
         } else if let Some(path) = line.strip_prefix("copy to ") {
-            new_path = Some(PathBuf::from(path));
+            status = FileStatus::Copied;
+            to_path = Some(unquote_path(path));
             next_line(lines)?;
         } else if line.starts_with("@@") || line.starts_with("diff ") {
             break;
@@ -238,6 +277,9 @@ where
         }
     }
 
+    let old_path = from_path.or(old_path);
+    let new_path = to_path.or(new_path);
+
     // Determine status from paths if not already set by metadata
     if status == FileStatus::Modified {
         if old_path.is_none() && new_path.is_some() {
@@ -247,7 +289,12 @@ where
         }
     }
 
-    Ok((old_path, new_path, status))
+    Ok((old_path, new_path, status, modes))
+}
+
+/// Parse an octal git file mode such as `100644`.
+fn parse_mode(mode: &str) -> Option<u32> {
+    u32::from_str_radix(mode.trim(), 8).ok()
 }
 
 fn parse_hunk<'a, I>(
@@ -321,7 +368,10 @@ where
             continue;
         };
 
-        line_contents.push(super::tabify(content));
+        line_contents.push(super::tabify(
+            file_path.map_or(Path::new(""), PathBuf::as_path),
+            content,
+        ));
         line_origins.push(origin);
         line_numbers.push((old_ln, new_ln));
     }
@@ -372,6 +422,65 @@ where
     }))
 }
 
+/// Longest function context git appends to a hunk header.
+const FUNCTION_CONTEXT_MAX_CHARS: usize = 80;
+
+/// Append git-style function context (`@@ -10,6 +10,7 @@ fn main() {`) to
+/// hunk headers that lack it, for tools like jj whose diffs don't include
+/// it. `fetch_old` returns the old-side content of a file; files it returns
+/// `None` for keep their headers unchanged.
+pub(crate) fn add_function_context<F>(files: &mut [DiffFile], mut fetch_old: F)
+where
+    F: FnMut(&Path) -> Option<String>,
+{
+    for file in files {
+        if file.is_binary || !needs_function_context(file) {
+            continue;
+        }
+        let Some(content) = file.old_path.as_deref().and_then(&mut fetch_old) else {
+            continue;
+        };
+        let old_lines: Vec<&str> = content.lines().collect();
+        for hunk in &mut file.hunks {
+            if has_function_context(&hunk.header) {
+                continue;
+            }
+            if let Some(context) = function_context(&old_lines, hunk.old_start) {
+                hunk.header = format!("{} {context}", hunk.header.trim_end());
+            }
+        }
+    }
+}
+
+/// Whether any hunk of `file` is missing function context that a scan of
+/// its old side could fill in.
+pub(crate) fn needs_function_context(file: &DiffFile) -> bool {
+    file.old_path.is_some() && file.hunks.iter().any(|h| !has_function_context(&h.header))
+}
+
+fn has_function_context(header: &str) -> bool {
+    header
+        .strip_prefix("@@")
+        .and_then(|rest| rest.split_once("@@"))
+        .is_some_and(|(_, context)| !context.trim().is_empty())
+}
+
+/// Git's default funcname rule: the nearest line above the hunk that starts
+/// with a letter, `_` or `$` (i.e. not indented and not a brace or comment
+/// continuation).
+fn function_context<'a>(old_lines: &[&'a str], old_start: u32) -> Option<&'a str> {
+    let before = (old_start as usize).saturating_sub(1).min(old_lines.len());
+    let line = old_lines[..before]
+        .iter()
+        .rev()
+        .find(|line| line.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$'))?
+        .trim_end();
+    Some(match line.char_indices().nth(FUNCTION_CONTEXT_MAX_CHARS) {
+        Some((idx, _)) => &line[..idx],
+        None => line,
+    })
+}
+
 fn parse_hunk_header(line: &str) -> Option<(u32, u32, u32, u32)> {
     // Format: @@ -old_start,old_count +new_start,new_count @@
     // or: @@ -old_start +new_start @@ (count defaults to 1)
@@ -402,6 +511,14 @@ fn parse_range(s: &str) -> (u32, u32) {
 /// Returns (old_path, new_path) extracted from the a/ and b/ prefixes.
 fn parse_diff_git_header(line: &str) -> Option<(PathBuf, PathBuf)> {
     let rest = line.strip_prefix("diff --git ")?;
+    // Paths with special characters are quoted: `"a/x y" "b/x y"`.
+    if rest.starts_with('"') || rest.ends_with('"') {
+        let (old_part, new_part) = split_quoted_pair(rest)?;
+        return Some((
+            strip_path_prefix(unquote_path(old_part), "a/"),
+            strip_path_prefix(unquote_path(new_part), "b/"),
+        ));
+    }
     // The format is "a/<path> b/<path>". Since paths can contain spaces,
     // we find the " b/" separator. For paths without spaces, a simple split works.
     // Try finding " b/" as separator (handles most cases).
@@ -424,23 +541,16 @@ fn parse_binary_file_line(line: &str) -> Option<(Option<PathBuf>, Option<PathBuf
     // Git format: "Binary files a/path/to/file and b/path/to/file differ"
     if let Some(content) = line.strip_prefix("Binary files ") {
         let content = content.strip_suffix(" differ")?;
-        let (old_part, new_part) = content.split_once(" and ")?;
-
-        let old_path = if old_part == "/dev/null" {
-            None
+        let (old_part, new_part) = if content.contains('"') {
+            split_quoted_pair_with(content, " and ")?
         } else {
-            Some(PathBuf::from(
-                old_part.strip_prefix("a/").unwrap_or(old_part),
-            ))
+            content.split_once(" and ")?
         };
 
-        let new_path = if new_part == "/dev/null" {
-            None
-        } else {
-            Some(PathBuf::from(
-                new_part.strip_prefix("b/").unwrap_or(new_part),
-            ))
-        };
+        let old_path =
+            (old_part != "/dev/null").then(|| strip_path_prefix(unquote_path(old_part), "a/"));
+        let new_path =
+            (new_part != "/dev/null").then(|| strip_path_prefix(unquote_path(new_part), "b/"));
 
         return Some((old_path, new_path));
     }
@@ -456,6 +566,102 @@ fn parse_binary_file_line(line: &str) -> Option<(Option<PathBuf>, Option<PathBuf
     None
 }
 
+/// Decode a path git wrote in C-style quotes (`"caf\303\251 \"x\".txt"`),
+/// which it does for control characters, quotes and backslashes, and for
+/// every non-ASCII byte while `core.quotepath` is on. Unquoted paths pass
+/// through unchanged.
+pub(crate) fn unquote_path(raw: &str) -> PathBuf {
+    let Some(inner) = raw
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    else {
+        return PathBuf::from(raw);
+    };
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('a') => bytes.push(0x07),
+            Some('b') => bytes.push(0x08),
+            Some('t') => bytes.push(b'\t'),
+            Some('n') => bytes.push(b'\n'),
+            Some('v') => bytes.push(0x0b),
+            Some('f') => bytes.push(0x0c),
+            Some('r') => bytes.push(b'\r'),
+            Some(digit @ '0'..='3') => {
+                let mut value = digit as u32 - '0' as u32;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(d) => {
+                            value = value * 8 + d;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+    path_from_bytes(bytes)
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn strip_path_prefix(path: PathBuf, prefix: &str) -> PathBuf {
+    path.strip_prefix(prefix.trim_end_matches('/'))
+        .map(PathBuf::from)
+        .unwrap_or(path)
+}
+
+/// Split `"a/x" "b/y"` (either side may be unquoted) at the space between
+/// the two paths.
+fn split_quoted_pair(s: &str) -> Option<(&str, &str)> {
+    split_quoted_pair_with(s, " ")
+}
+
+fn split_quoted_pair_with<'a>(s: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    if s.starts_with('"') {
+        // Find the closing quote, skipping escaped characters.
+        let mut escaped = false;
+        for (i, ch) in s.char_indices().skip(1) {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    let rest = s[i + 1..].strip_prefix(separator)?;
+                    return Some((&s[..=i], rest));
+                }
+                _ => {}
+            }
+        }
+        None
+    } else {
+        // Only the second path is quoted.
+        let pos = s.find(&format!("{separator}\""))?;
+        Some((&s[..pos], &s[pos + separator.len()..]))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -493,6 +699,49 @@ mod tests {
         assert_eq!(new_count, 1);
     }
 
+    #[test]
+    fn should_add_function_context_from_old_side() {
+        let diff = r#"diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -4,2 +4,2 @@
+     let a = 1;
+-    let b = 2;
++    let b = 3;
+@@ -1,1 +1,1 @@
+-use std::fmt;
++use std::io;
+"#;
+        let old = "use std::fmt;\n\nfn compute() {\n    let a = 1;\n    let b = 2;\n}\n";
+        let mut files =
+            parse_unified_diff(diff, DiffFormat::GitStyle, &SyntaxHighlighter::default()).unwrap();
+
+        add_function_context(&mut files, |path| {
+            (path == Path::new("src/lib.rs")).then(|| old.to_string())
+        });
+
+        assert_eq!(files[0].hunks[0].header, "@@ -4,2 +4,2 @@ fn compute() {");
+        // Nothing above line 1, so the header is left alone.
+        assert_eq!(files[0].hunks[1].header, "@@ -1,1 +1,1 @@");
+    }
+
+    #[test]
+    fn should_keep_existing_function_context() {
+        let diff = r#"diff --git a/a.c b/a.c
+--- a/a.c
++++ b/a.c
+@@ -3,1 +3,1 @@ int main(void)
+-  return 0;
++  return 1;
+"#;
+        let mut files =
+            parse_unified_diff(diff, DiffFormat::GitStyle, &SyntaxHighlighter::default()).unwrap();
+
+        add_function_context(&mut files, |_| panic!("should not fetch"));
+
+        assert_eq!(files[0].hunks[0].header, "@@ -3,1 +3,1 @@ int main(void)");
+    }
+
     #[test]
     fn should_reject_invalid_hunk_header() {
         assert!(parse_hunk_header("not a hunk header").is_none());
@@ -517,6 +766,36 @@ mod tests {
         assert_eq!(parse_range("abc,def"), (1, 1));
     }
 
+    #[test]
+    fn should_decode_quoted_git_paths() {
+        // given - core.quotepath octal escapes plus an embedded tab
+        let diff = r#"diff --git "a/caf\303\251.txt" "b/caf\303\251 \"v2\".txt"
+similarity index 90%
+rename from "caf\303\251.txt"
+rename to "caf\303\251 \"v2\".txt"
+--- "a/caf\303\251.txt"
++++ "b/caf\303\251 \"v2\".txt"
+@@ -1 +1 @@
+-a
++b
+diff --git "a/tab\there.bin" "b/tab\there.bin"
+new file mode 100644
+index 0000000..e69de29
+Binary files /dev/null and "b/tab\there.bin" differ
+"#;
+
+        // when
+        let files =
+            parse_unified_diff(diff, DiffFormat::GitStyle, &SyntaxHighlighter::default()).unwrap();
+
+        // then
+        assert_eq!(files[0].old_path, Some(PathBuf::from("café.txt")));
+        assert_eq!(files[0].new_path, Some(PathBuf::from("café \"v2\".txt")));
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[1].new_path, Some(PathBuf::from("tab\there.bin")));
+        assert!(files[1].is_binary);
+    }
+
     // ============ Hg format tests ============
 
     #[test]
@@ -748,6 +1027,27 @@ copy to dest.rs
         assert_eq!(result[0].hunks.len(), 1);
     }
 
+    #[test]
+    fn hg_should_parse_renamed_file_with_content_changes() {
+        let diff = "diff --git a/old_name.rs b/new_name.rs
+rename from old_name.rs
+rename to new_name.rs
+--- a/old_name.rs\tThu Jan 01 00:00:00 1970 +0000
++++ b/new_name.rs\tThu Jan 01 00:00:00 1970 +0000
+@@ -1 +1 @@
+-old
++new
+";
+
+        let result =
+            parse_unified_diff(diff, DiffFormat::Hg, &SyntaxHighlighter::default()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].status, FileStatus::Renamed);
+        assert_eq!(result[0].old_path, Some(PathBuf::from("old_name.rs")));
+        assert_eq!(result[0].new_path, Some(PathBuf::from("new_name.rs")));
+        assert_eq!(result[0].hunks[0].lines.len(), 2);
+    }
+
     #[test]
     fn hg_should_handle_no_newline_marker() {
         let diff = r#"diff -r abc123 no_newline.rs
@@ -918,6 +1218,41 @@ rename to new.txt
         assert!(files[0].hunks.is_empty());
     }
 
+    #[test]
+    fn jj_should_parse_symlink_target_change() {
+        let diff = r#"diff --git a/link b/link
+index 1111111..2222222 120000
+--- a/link
++++ b/link
+@@ -1 +1 @@
+-a.txt
+\ No newline at end of file
++b.txt
+\ No newline at end of file
+"#;
+        let files =
+            parse_unified_diff(diff, DiffFormat::GitStyle, &SyntaxHighlighter::default()).unwrap();
+        assert_eq!(files[0].modes.old, Some(0o120000));
+        assert_eq!(files[0].metadata_lines(), vec!["symlink a.txt → b.txt"]);
+    }
+
+    #[test]
+    fn hg_should_parse_new_symlink_from_git_style_metadata() {
+        let diff = r#"diff --git a/link b/link
+new file mode 120000
+--- /dev/null
++++ b/link
+@@ -0,0 +1,1 @@
++target
+\ No newline at end of file
+"#;
+        let files =
+            parse_unified_diff(diff, DiffFormat::Hg, &SyntaxHighlighter::default()).unwrap();
+        assert_eq!(files[0].status, FileStatus::Added);
+        assert_eq!(files[0].modes.old, None);
+        assert_eq!(files[0].metadata_lines(), vec!["symlink → target"]);
+    }
+
     #[test]
     fn jj_should_parse_renamed_file_with_content_changes() {
         // Rename with content changes - has ---/+++ lines
@@ -939,6 +1274,66 @@ rename to new.txt
         assert_eq!(files[0].hunks.len(), 1);
     }
 
+    #[test]
+    fn jj_should_prefer_rename_metadata_over_file_header_paths() {
+        // git appends a tab to ---/+++ paths that contain spaces
+        let diff = "diff --git a/old name.txt b/new name.txt
+similarity index 80%
+rename from old name.txt
+rename to new name.txt
+index 1111111..2222222 100644
+--- a/old name.txt\t
++++ b/new name.txt\t
+@@ -1,2 +1,2 @@
+ kept
+-old content
++new content
+";
+        let files =
+            parse_unified_diff(diff, DiffFormat::GitStyle, &SyntaxHighlighter::default()).unwrap();
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[0].old_path, Some(PathBuf::from("old name.txt")));
+        assert_eq!(files[0].new_path, Some(PathBuf::from("new name.txt")));
+        assert_eq!(files[0].hunks[0].lines.len(), 3);
+    }
+
+    #[test]
+    fn jj_should_parse_rename_with_mode_change_before_next_file() {
+        let diff = r#"diff --git a/run.sh b/bin/run.sh
+old mode 100644
+new mode 100755
+rename from run.sh
+rename to bin/run.sh
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-a
++b
+"#;
+        let files =
+            parse_unified_diff(diff, DiffFormat::GitStyle, &SyntaxHighlighter::default()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[0].old_path, Some(PathBuf::from("run.sh")));
+        assert_eq!(files[0].new_path, Some(PathBuf::from("bin/run.sh")));
+        assert_eq!(files[0].modes.new, Some(0o100755));
+        assert!(files[0].hunks.is_empty());
+        assert_eq!(files[1].status, FileStatus::Modified);
+    }
+
+    #[test]
+    fn jj_should_take_missing_rename_side_from_diff_header() {
+        let diff = r#"diff --git a/old.txt b/new.txt
+rename to new.txt
+"#;
+        let files =
+            parse_unified_diff(diff, DiffFormat::GitStyle, &SyntaxHighlighter::default()).unwrap();
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[0].old_path, Some(PathBuf::from("old.txt")));
+        assert_eq!(files[0].new_path, Some(PathBuf::from("new.txt")));
+    }
+
     #[test]
     fn jj_should_parse_copied_file_without_content_changes() {
         // Pure copy with no content changes
@@ -1109,6 +1504,7 @@ new mode 100755
         assert_eq!(files[0].old_path, Some(PathBuf::from("script.sh")));
         assert_eq!(files[0].new_path, Some(PathBuf::from("script.sh")));
         assert!(files[0].hunks.is_empty());
+        assert_eq!(files[0].metadata_lines(), vec!["mode 100644 → 100755"]);
         let _path = files[0].display_path();
     }
 
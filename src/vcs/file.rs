@@ -1,7 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin};
+use crate::model::{DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin};
 use crate::syntax::SyntaxHighlighter;
 
 use super::traits::{VcsBackend, VcsInfo, VcsType};
@@ -63,7 +63,10 @@ impl VcsBackend for FileBackend {
         }
 
         // Build line contents and origins for syntax highlighting
-        let line_contents: Vec<String> = lines.iter().map(|l| super::tabify(l)).collect();
+        let line_contents: Vec<String> = lines
+            .iter()
+            .map(|l| super::tabify(&self.file_path, l))
+            .collect();
         let line_origins: Vec<LineOrigin> = vec![LineOrigin::Addition; line_contents.len()];
 
         // Apply syntax highlighting
@@ -123,6 +126,8 @@ impl VcsBackend for FileBackend {
             is_too_large: false,
             is_commit_message: false,
             content_hash,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         };
 
         Ok(vec![file])
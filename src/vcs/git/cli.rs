@@ -9,20 +9,22 @@ use std::process::{Command, Stdio};
 use chrono::{TimeZone, Utc};
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin, LineSide};
+use crate::model::{DiffFile, DiffLine, FileStatus, LineOrigin, LineSide, Reviewer};
 use crate::syntax::SyntaxHighlighter;
 use crate::vcs::diff_parser::{self, DiffFormat};
-use crate::vcs::{CommitInfo, VcsBackend, VcsChangeStatus, VcsInfo};
-use crate::vcs::{container_file_paths, enhance_with_full_file_highlight, tabify};
+use crate::vcs::{
+    CommitInfo, IgnoreWhitespace, RangeMode, RenameDetection, VcsBackend, VcsChangeStatus, VcsInfo,
+};
+use crate::vcs::{
+    build_untracked_diff_file, container_file_paths, enhance_with_full_file_highlight,
+    fill_binary_sizes, workdir_file_size,
+};
 
 use super::{
     GitRepoMode, git_bool_config_enabled, git_command_error, git_fsmonitor_config_enabled,
-    run_git_command,
+    rename_cache, run_git_command,
 };
 
-// Untracked files larger than this are shown in the file list but their
-// content is not parsed: they are likely logs, dumps, or build artefacts.
-const MAX_UNTRACKED_FILE_SIZE: u64 = 10 * 1_024 * 1_024;
 const EMPTY_TREE_OID: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
 const COMMIT_FORMAT: &str = "--format=%H%x00%h%x00%an%x00%ct%x00%B%x1e";
 
@@ -33,6 +35,9 @@ pub struct GitCliBackend {
     repo_mode: GitRepoMode,
     untracked_cache: bool,
     fsmonitor: bool,
+    renames: RenameDetection,
+    whitespace: IgnoreWhitespace,
+    include_untracked: bool,
 }
 
 #[derive(Clone, Copy)]
@@ -71,6 +76,9 @@ impl GitCliBackend {
             repo_mode,
             untracked_cache,
             fsmonitor,
+            renames: RenameDetection::default(),
+            whitespace: IgnoreWhitespace::default(),
+            include_untracked: true,
         })
     }
 
@@ -86,13 +94,9 @@ impl GitCliBackend {
         new_source: GitContentSource<'_>,
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
-        let mut files = match run_git_diff_command(&self.root_path, args, highlighter) {
-            Ok(files) => files,
-            Err(TuicrError::NoChanges) => Vec::new(),
-            Err(err) => return Err(err),
-        };
+        let mut files = self.run_diff_with_renames(args, highlighter)?;
 
-        if include_untracked {
+        if include_untracked && self.include_untracked {
             append_untracked_cli_diffs(&self.root_path, &mut files, highlighter)?;
         }
         normalize_git_cli_paths(&mut files);
@@ -101,6 +105,14 @@ impl GitCliBackend {
             return Err(TuicrError::NoChanges);
         }
 
+        let old_sizes = git_source_binary_sizes(&self.root_path, old_source, &files, LineSide::Old);
+        let new_sizes = git_source_binary_sizes(&self.root_path, new_source, &files, LineSide::New);
+        fill_binary_sizes(
+            &mut files,
+            |path| git_source_size(&self.root_path, old_source, &old_sizes, path),
+            |path| git_source_size(&self.root_path, new_source, &new_sizes, path),
+        );
+
         let old_cache =
             git_source_content_cache(&self.root_path, old_source, &files, LineSide::Old);
         let new_cache =
@@ -128,6 +140,69 @@ impl GitCliBackend {
         );
         Ok(files)
     }
+
+    /// Run `git diff` with `args` and the rename and whitespace settings.
+    /// When the same changes were diffed before, renames are only detected
+    /// again over the paths that paired up then; see `rename_cache`.
+    fn run_diff_with_renames(
+        &self,
+        args: Vec<String>,
+        highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        let run = |renames: &RenameDetection, paths: Option<&[PathBuf]>| {
+            let mut args = args.clone();
+            if let Some(paths) = paths {
+                // Every caller ends its revisions with `--`; any pathspecs
+                // after it cover the paired paths already.
+                let end = args
+                    .iter()
+                    .position(|arg| arg == "--")
+                    .map_or(args.len(), |i| i + 1);
+                args.truncate(end);
+                args.extend(
+                    paths
+                        .iter()
+                        .map(|path| format!(":(literal){}", path.to_string_lossy())),
+                );
+            }
+            // Every caller passes `diff` first; the detection flags follow it.
+            let mut flags = rename_args(renames);
+            flags.extend(whitespace_args(&self.whitespace));
+            args.splice(1..1, flags);
+            match run_git_diff_command(&self.root_path, args, highlighter) {
+                Ok(files) => Ok(files),
+                Err(TuicrError::NoChanges) => Ok(Vec::new()),
+                Err(err) => Err(err),
+            }
+        };
+
+        let key = self
+            .renames
+            .enabled
+            .then(|| rename_cache_key(&self.root_path, &args, &self.renames).ok())
+            .flatten();
+        let Some(key) = key else {
+            return run(&self.renames, None);
+        };
+        if let Some(pairs) = rename_cache::lookup(key) {
+            let no_renames = RenameDetection {
+                enabled: false,
+                ..self.renames
+            };
+            let files = run(&no_renames, None)?;
+            if pairs.is_empty() {
+                return Ok(files);
+            }
+            let paired = run(&self.renames, Some(&rename_cache::paired_paths(&pairs)))?;
+            if let Some(files) = rename_cache::splice(files, paired, &pairs) {
+                return Ok(files);
+            }
+        }
+
+        let files = run(&self.renames, None)?;
+        rename_cache::store(key, rename_cache::pairs(&files));
+        Ok(files)
+    }
 }
 
 impl VcsBackend for GitCliBackend {
@@ -202,13 +277,12 @@ impl VcsBackend for GitCliBackend {
         // have not already proven the "unstaged" row should be shown.
         let staged = has_diff_changes(&self.root_path, &["diff", "--quiet", "--cached", "--"])?;
         let tracked_unstaged = has_diff_changes(&self.root_path, &["diff", "--quiet", "--"])?;
-        let untracked_pathspecs = if tracked_unstaged {
-            Vec::new()
+        let unstaged = if tracked_unstaged || !self.include_untracked {
+            tracked_unstaged
         } else {
-            sparse_checkout_untracked_pathspecs(&self.root_path)?
+            let pathspecs = sparse_checkout_untracked_pathspecs(&self.root_path)?;
+            has_untracked_changes(&self.root_path, &pathspecs)?
         };
-        let unstaged =
-            tracked_unstaged || has_untracked_changes(&self.root_path, &untracked_pathspecs)?;
 
         Ok(VcsChangeStatus { staged, unstaged })
     }
@@ -225,13 +299,7 @@ impl VcsBackend for GitCliBackend {
         }
 
         let content = match file_status {
-            FileStatus::Deleted => read_git_object(
-                &self.root_path,
-                &format!("HEAD:{}", file_path.to_string_lossy()),
-            )
-            .ok_or_else(|| {
-                TuicrError::VcsCommand("failed to read deleted file from HEAD".into())
-            })?,
+            FileStatus::Deleted => self.fetch_deleted_file(file_path)?,
             _ => fs::read_to_string(self.root_path.join(file_path))?,
         };
 
@@ -252,6 +320,38 @@ impl VcsBackend for GitCliBackend {
         Ok(result)
     }
 
+    fn fetch_deleted_file(&self, file_path: &Path) -> Result<String> {
+        read_git_object(
+            &self.root_path,
+            &format!("HEAD:{}", file_path.to_string_lossy()),
+        )
+        .ok_or_else(|| TuicrError::VcsCommand("failed to read deleted file from HEAD".into()))
+    }
+
+    fn set_rename_detection(&mut self, detection: RenameDetection) {
+        self.renames = detection;
+    }
+
+    fn rename_detection(&self) -> Option<RenameDetection> {
+        Some(self.renames)
+    }
+
+    fn set_ignore_whitespace(&mut self, whitespace: IgnoreWhitespace) {
+        self.whitespace = whitespace;
+    }
+
+    fn ignore_whitespace(&self) -> Option<IgnoreWhitespace> {
+        Some(self.whitespace)
+    }
+
+    fn set_include_untracked(&mut self, include: bool) {
+        self.include_untracked = include;
+    }
+
+    fn include_untracked(&self) -> Option<bool> {
+        Some(self.include_untracked)
+    }
+
     fn get_recent_commits(&self, offset: usize, limit: usize) -> Result<Vec<CommitInfo>> {
         let branch_tip_names = get_branch_tip_names(&self.root_path);
         let output = run_git_command_args(
@@ -294,14 +394,21 @@ impl VcsBackend for GitCliBackend {
     fn get_commit_range_diff(
         &self,
         commit_ids: &[String],
+        range_mode: RangeMode,
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
         if commit_ids.is_empty() {
             return Err(TuicrError::NoChanges);
         }
 
-        let base_rev = parent_rev_or_empty(&self.root_path, &commit_ids[0]);
         let newest_rev = commit_ids.last().unwrap();
+        let mut base_rev = parent_rev_or_empty(&self.root_path, &commit_ids[0]);
+        // A root commit diffs against the empty tree, which has no merge-base.
+        if range_mode == RangeMode::ThreeDot && base_rev != EMPTY_TREE_OID {
+            base_rev = run_git_command(&self.root_path, &["merge-base", &base_rev, newest_rev])?
+                .trim()
+                .to_string();
+        }
         self.get_cli_diff(
             vec![
                 "diff".into(),
@@ -318,6 +425,66 @@ impl VcsBackend for GitCliBackend {
         )
     }
 
+    fn get_interdiff(
+        &self,
+        old: &str,
+        new: &str,
+        highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        let (old_base, old_tip) = resolve_patch_version(&self.root_path, old)?;
+        let (new_base, new_tip) = resolve_patch_version(&self.root_path, new)?;
+
+        // Replay the old version onto the new base so upstream changes
+        // cancel out; on conflicts compare the tips over the touched files.
+        let merge_base_arg = format!("--merge-base={old_base}");
+        let replayed = run_git_command(
+            &self.root_path,
+            &[
+                "merge-tree",
+                "--write-tree",
+                &merge_base_arg,
+                &new_base,
+                &old_tip,
+            ],
+        )
+        .ok()
+        .and_then(|out| out.lines().next().map(str::to_string));
+        let (old_rev, paths) = match replayed {
+            Some(tree) => (tree, Vec::new()),
+            None => {
+                let mut paths = Vec::new();
+                for (base, tip) in [(&old_base, &old_tip), (&new_base, &new_tip)] {
+                    let names = run_git_command(
+                        &self.root_path,
+                        &["diff", "--no-ext-diff", "--name-only", base, tip],
+                    )?;
+                    paths.extend(names.lines().map(str::to_string));
+                }
+                if paths.is_empty() {
+                    return Err(TuicrError::NoChanges);
+                }
+                (old_tip.clone(), paths)
+            }
+        };
+
+        let mut args = vec![
+            "diff".into(),
+            "--no-ext-diff".into(),
+            "--binary".into(),
+            old_rev.clone(),
+            new_tip.clone(),
+            "--".into(),
+        ];
+        args.extend(paths);
+        self.get_cli_diff(
+            args,
+            false,
+            GitContentSource::Revision(&old_rev),
+            GitContentSource::Revision(&new_tip),
+            highlighter,
+        )
+    }
+
     fn get_commits_info(&self, ids: &[String]) -> Result<Vec<CommitInfo>> {
         if ids.is_empty() {
             return Ok(Vec::new());
@@ -335,6 +502,54 @@ impl VcsBackend for GitCliBackend {
         Ok(parse_commit_records(&output, &branch_tip_names))
     }
 
+    fn parent_commit(&self, id: &str) -> Result<Option<String>> {
+        // `<id> <parent>...` on one line
+        let output =
+            run_git_command_args(&self.root_path, ["rev-list", "--parents", "-n", "1", id])?;
+        Ok(output.split_whitespace().nth(1).map(str::to_string))
+    }
+
+    fn user_identity(&self) -> Option<Reviewer> {
+        // `git config --get` exits 1 for an unset key
+        let get = |key: &str| {
+            run_git_command_args(&self.root_path, ["config", "--get", key])
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Some(Reviewer {
+            name: get("user.name")?,
+            email: get("user.email"),
+        })
+    }
+
+    fn get_commit_line_stats(&self, ids: &[String]) -> Result<Vec<(usize, usize)>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut args = vec![
+            "log".to_string(),
+            "--no-walk=unsorted".to_string(),
+            "--diff-merges=first-parent".to_string(),
+            "--numstat".to_string(),
+            "--format=%x00%H".to_string(),
+        ];
+        args.extend(ids.iter().cloned());
+        let output = run_git_command_strings(&self.root_path, args)?;
+
+        let stats = parse_numstat_records(&output);
+        Ok(ids
+            .iter()
+            .map(|id| {
+                stats
+                    .iter()
+                    .find(|(hash, _)| hash.starts_with(id.as_str()))
+                    .map_or((0, 0), |(_, lines)| *lines)
+            })
+            .collect())
+    }
+
     fn get_working_tree_with_commits_diff(
         &self,
         commit_ids: &[String],
@@ -486,13 +701,136 @@ fn has_untracked_changes(workdir: &Path, pathspecs: &[String]) -> Result<bool> {
     Ok(false)
 }
 
+/// `git diff` flags equivalent to `renames`.
+fn rename_args(renames: &RenameDetection) -> Vec<String> {
+    if !renames.enabled {
+        return vec!["--no-renames".to_string()];
+    }
+    let mut args = vec![
+        format!("-M{}%", renames.threshold),
+        format!("-l{}", renames.limit),
+    ];
+    if renames.copies {
+        args.push(format!("-C{}%", renames.threshold));
+    }
+    if renames.break_rewrites {
+        args.push("-B".to_string());
+    }
+    args
+}
+
+/// The rename cache key for the tracked changes `git diff` with `args`
+/// lists. Working tree files have no blob id yet, so they are hashed here.
+fn rename_cache_key(workdir: &Path, args: &[String], renames: &RenameDetection) -> Result<u64> {
+    let mut raw_args: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--binary")
+        .cloned()
+        .collect();
+    raw_args.splice(
+        1..1,
+        strings(["--raw", "-z", "--no-abbrev", "--no-renames"]),
+    );
+    let output = run_git_command_strings(workdir, raw_args)?;
+
+    // `:<old mode> <new mode> <old id> <new id> <status>` NUL `<path>` NUL
+    let mut records = Vec::new();
+    let mut fields = output.split('\0');
+    while let (Some(meta), Some(path)) = (fields.next(), fields.next()) {
+        let meta: Vec<&str> = meta.trim_start_matches(':').split(' ').collect();
+        let [_, new_mode, old_id, new_id, status] = meta[..] else {
+            break;
+        };
+        records.push((status, path, old_id, new_mode, new_id));
+    }
+
+    let is_zero = |id: &str| id.bytes().all(|b| b == b'0');
+    let unhashed: Vec<&str> = records
+        .iter()
+        .filter(|(_, _, _, new_mode, new_id)| is_zero(new_id) && !is_zero(new_mode))
+        .map(|(_, path, ..)| *path)
+        .collect();
+    let mut hashed = hash_worktree_files(workdir, &unhashed)?.into_iter();
+
+    let deltas: Vec<_> = records
+        .iter()
+        .map(|(status, path, old_id, new_mode, new_id)| {
+            let new_id = if is_zero(new_id) && !is_zero(new_mode) {
+                hashed.next().unwrap_or_default()
+            } else {
+                new_id.to_string()
+            };
+            rename_cache::DeltaKey {
+                status: status.chars().next().unwrap_or('X'),
+                old_path: path.as_bytes(),
+                old_id: old_id.to_string(),
+                new_path: path.as_bytes(),
+                new_id,
+            }
+        })
+        .collect();
+    Ok(rename_cache::key(renames, deltas))
+}
+
+/// Blob ids of the working tree files at `paths`, as `git hash-object`.
+fn hash_worktree_files(workdir: &Path, paths: &[&str]) -> Result<Vec<String>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut child = Command::new("git")
+        .current_dir(workdir)
+        .args(["hash-object", "--stdin-paths"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| TuicrError::VcsCommand(format!("Failed to run git: {e}")))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| TuicrError::VcsCommand("git hash-object stdin unavailable".into()))?;
+    let input: String = paths.iter().map(|path| format!("{path}\n")).collect();
+    // Written from another thread so a full stdout pipe can't stall both ends.
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| TuicrError::VcsCommand(format!("git hash-object failed: {e}")))?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(TuicrError::VcsCommand("git hash-object failed".into()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// `git diff` flags equivalent to `whitespace`.
+fn whitespace_args(whitespace: &IgnoreWhitespace) -> Vec<String> {
+    [
+        (whitespace.all_space, "-w"),
+        (whitespace.space_change, "-b"),
+        (whitespace.blank_lines, "--ignore-blank-lines"),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, flag)| flag.to_string())
+    .collect()
+}
+
 fn run_git_diff_command(
     workdir: &Path,
     args: Vec<String>,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
+    // Keep headers ASCII whatever the user's core.quotepath; the parser
+    // decodes the quoted paths back.
     let mut child = Command::new("git")
         .current_dir(workdir)
+        .args(["-c", "core.quotepath=on"])
         .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -595,86 +933,6 @@ fn is_simple_sparse_path(pattern: &str) -> bool {
         && !pattern.contains('\\')
 }
 
-fn build_untracked_diff_file(
-    path: &Path,
-    full_path: &Path,
-    highlighter: &SyntaxHighlighter,
-) -> Option<DiffFile> {
-    let metadata = full_path.metadata().ok()?;
-    if metadata.len() > MAX_UNTRACKED_FILE_SIZE {
-        return Some(diff_file_without_hunks(path, false, true));
-    }
-
-    let bytes = fs::read(full_path).ok()?;
-    if bytes.contains(&0) {
-        return Some(diff_file_without_hunks(path, true, false));
-    }
-
-    let content = String::from_utf8_lossy(&bytes);
-    let lines: Vec<String> = content
-        .lines()
-        .map(|line| tabify(line.trim_end_matches('\r')))
-        .collect();
-
-    if lines.is_empty() {
-        return Some(diff_file_without_hunks(path, false, false));
-    }
-
-    let highlighted = highlighter.highlight_file_lines(path, &lines);
-    let diff_lines: Vec<DiffLine> = lines
-        .into_iter()
-        .enumerate()
-        .map(|(idx, content)| DiffLine {
-            origin: LineOrigin::Addition,
-            content,
-            old_lineno: None,
-            new_lineno: Some((idx + 1) as u32),
-            highlighted_spans: highlighter.highlighted_line_for_diff_with_background(
-                None,
-                highlighted.as_deref(),
-                None,
-                Some(idx),
-                LineOrigin::Addition,
-            ),
-        })
-        .collect();
-
-    let new_count = diff_lines.len() as u32;
-    let hunks = vec![DiffHunk {
-        header: format!("@@ -0,0 +1,{new_count} @@"),
-        lines: diff_lines,
-        old_start: 0,
-        old_count: 0,
-        new_start: 1,
-        new_count,
-    }];
-    let content_hash = DiffFile::compute_content_hash(&hunks);
-
-    Some(DiffFile {
-        old_path: None,
-        new_path: Some(path.to_path_buf()),
-        status: FileStatus::Added,
-        hunks,
-        is_binary: false,
-        is_too_large: false,
-        is_commit_message: false,
-        content_hash,
-    })
-}
-
-fn diff_file_without_hunks(path: &Path, is_binary: bool, is_too_large: bool) -> DiffFile {
-    DiffFile {
-        old_path: None,
-        new_path: Some(path.to_path_buf()),
-        status: FileStatus::Added,
-        hunks: Vec::new(),
-        is_binary,
-        is_too_large,
-        is_commit_message: false,
-        content_hash: 0,
-    }
-}
-
 fn normalize_git_cli_paths(files: &mut [DiffFile]) {
     for file in files {
         match file.status {
@@ -808,6 +1066,93 @@ fn git_source_content_cache(
     }
 }
 
+/// Blob sizes of the binary files on one side of the diff, looked up in
+/// one `git cat-file --batch-check` call. Working-tree sizes are read from
+/// disk by [`git_source_size`] instead.
+fn git_source_binary_sizes(
+    workdir: &Path,
+    source: GitContentSource<'_>,
+    files: &[DiffFile],
+    side: LineSide,
+) -> HashMap<PathBuf, u64> {
+    let prefix = match source {
+        GitContentSource::Revision(rev) => format!("{rev}:"),
+        GitContentSource::Index => ":0:".to_string(),
+        GitContentSource::None | GitContentSource::Workdir => return HashMap::new(),
+    };
+    let requests: Vec<(PathBuf, String)> = files
+        .iter()
+        .filter(|f| f.is_binary)
+        .filter_map(|f| match side {
+            LineSide::Old => f.old_path.clone(),
+            LineSide::New => f.new_path.clone(),
+        })
+        .map(|path| {
+            let spec = format!("{prefix}{}", path.to_string_lossy());
+            (path, spec)
+        })
+        .collect();
+    read_git_object_sizes(workdir, requests).unwrap_or_default()
+}
+
+fn git_source_size(
+    workdir: &Path,
+    source: GitContentSource<'_>,
+    sizes: &HashMap<PathBuf, u64>,
+    path: &Path,
+) -> Option<u64> {
+    match source {
+        GitContentSource::Workdir => workdir_file_size(workdir, path),
+        _ => sizes.get(path).copied(),
+    }
+}
+
+fn read_git_object_sizes(
+    workdir: &Path,
+    requests: Vec<(PathBuf, String)>,
+) -> Result<HashMap<PathBuf, u64>> {
+    if requests.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut child = Command::new("git")
+        .current_dir(workdir)
+        .args(["cat-file", "--batch-check"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| TuicrError::VcsCommand(format!("Failed to run git: {e}")))?;
+
+    {
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| TuicrError::VcsCommand("git cat-file stdin unavailable".into()))?;
+        for (_, spec) in &requests {
+            writeln!(stdin, "{spec}")?;
+        }
+    }
+
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // One `<oid> <type> <size>` (or `<spec> missing`) line per request.
+    let sizes = requests
+        .into_iter()
+        .zip(stdout.lines())
+        .filter(|(_, line)| !line.ends_with(" missing"))
+        .filter_map(|((path, _), line)| {
+            let mut parts = line.split_whitespace();
+            let _oid = parts.next();
+            (parts.next() == Some("blob"))
+                .then(|| parts.next()?.parse::<u64>().ok())
+                .flatten()
+                .map(|size| (path, size))
+        })
+        .collect();
+    Ok(sizes)
+}
+
 fn read_git_objects(
     workdir: &Path,
     requests: Vec<(PathBuf, String)>,
@@ -976,6 +1321,38 @@ fn parse_commit_message(message: &str) -> (String, Option<String>) {
     (summary, body)
 }
 
+/// Resolve one version of a patch series to `(base, tip)` revisions. `A..B`
+/// is based at the merge-base of `A` and `B`; a single revision is that
+/// commit alone.
+fn resolve_patch_version(workdir: &Path, spec: &str) -> Result<(String, String)> {
+    let rev_parse = |rev: &str| -> Result<String> {
+        let commit = format!("{rev}^{{commit}}");
+        Ok(
+            run_git_command(workdir, &["rev-parse", "--verify", &commit])?
+                .trim()
+                .to_string(),
+        )
+    };
+    match spec.split_once("..") {
+        Some((from, to)) => {
+            let to = rev_parse(if to.is_empty() {
+                "HEAD"
+            } else {
+                to.trim_start_matches('.')
+            })?;
+            let from = rev_parse(if from.is_empty() { "HEAD" } else { from })?;
+            let base = run_git_command(workdir, &["merge-base", &from, &to])?
+                .trim()
+                .to_string();
+            Ok((base, to))
+        }
+        None => {
+            let tip = rev_parse(spec)?;
+            Ok((parent_rev_or_empty(workdir, &tip), tip))
+        }
+    }
+}
+
 fn parent_rev_or_empty(workdir: &Path, commit_id: &str) -> String {
     let parent_spec = format!("{commit_id}^");
     run_git_command(workdir, &["rev-parse", &parent_spec])
@@ -983,6 +1360,30 @@ fn parent_rev_or_empty(workdir: &Path, commit_id: &str) -> String {
         .unwrap_or_else(|_| EMPTY_TREE_OID.to_string())
 }
 
+/// `(hash, (added, removed))` per `\0<hash>` record of `git log --numstat`
+/// output. Binary files (`-` counts) add nothing.
+fn parse_numstat_records(output: &str) -> Vec<(String, (usize, usize))> {
+    output
+        .split('\0')
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let hash = lines.next()?.trim();
+            if hash.is_empty() {
+                return None;
+            }
+            let lines_changed = lines.fold((0, 0), |(added, removed), line| {
+                let mut fields = line.split('\t');
+                let count = |field: Option<&str>| field.and_then(|f| f.parse::<usize>().ok());
+                (
+                    added + count(fields.next()).unwrap_or(0),
+                    removed + count(fields.next()).unwrap_or(0),
+                )
+            });
+            Some((hash.to_string(), lines_changed))
+        })
+        .collect()
+}
+
 fn run_git_command_strings(workdir: &Path, args: Vec<String>) -> Result<String> {
     run_git_command_args(workdir, args.iter().map(String::as_str))
 }
@@ -998,6 +1399,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::FileSizes;
+    use crate::vcs::git::diff::DiffSettings;
     use crate::vcs::git::{diff, repository};
 
     fn git(workdir: &Path, args: &[&str]) {
@@ -1110,6 +1513,9 @@ mod tests {
         write_file(workdir, "staged.txt", "staged changed\n");
         git(workdir, &["add", "staged.txt"]);
         write_file(workdir, "untracked.txt", "untracked\n");
+        // `git diff` never sees untracked files, so this must not pair up
+        // with deleted.txt as a rename in either backend.
+        write_file(workdir, "moved.txt", "deleted base\n");
 
         let cli_backend =
             GitCliBackend::discover_from(workdir).expect("failed to discover cli backend");
@@ -1173,7 +1579,11 @@ mod tests {
         let (_temp_dir, backend, ids) = setup_sparse_index_repo();
 
         let files = backend
-            .get_commit_range_diff(&[ids[1].clone()], &SyntaxHighlighter::default())
+            .get_commit_range_diff(
+                &[ids[1].clone()],
+                RangeMode::TwoDot,
+                &SyntaxHighlighter::default(),
+            )
             .expect("failed to get sparse commit range diff");
 
         assert_eq!(files.len(), 1);
@@ -1214,6 +1624,37 @@ mod tests {
         assert!(!paths.contains(&Path::new("hidden/outside.txt")));
     }
 
+    #[test]
+    fn hides_untracked_files_when_toggled_off() {
+        let (temp_dir, mut backend, _ids) = setup_sparse_index_repo();
+        let workdir = temp_dir.path();
+        write_file(workdir, "keep/file.txt", "keep changed\n");
+        write_file(workdir, "keep/new.txt", "new sparse file\n");
+
+        backend.set_include_untracked(false);
+        let files = backend
+            .get_working_tree_diff(&SyntaxHighlighter::default())
+            .expect("failed to get sparse working tree diff");
+
+        assert_eq!(backend.include_untracked(), Some(false));
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].new_path.as_deref(),
+            Some(Path::new("keep/file.txt"))
+        );
+    }
+
+    #[test]
+    fn ignores_untracked_files_in_change_status_when_hidden() {
+        let (temp_dir, mut backend, _ids) = setup_sparse_index_repo();
+        write_file(temp_dir.path(), "keep/new.txt", "new sparse file\n");
+
+        backend.set_include_untracked(false);
+        let status = backend.get_change_status().expect("failed to get status");
+
+        assert!(!status.unstaged);
+    }
+
     #[test]
     fn reads_staged_diff_and_stages_files_in_sparse_index() {
         let (temp_dir, backend, _ids) = setup_sparse_index_repo();
@@ -1282,31 +1723,479 @@ mod tests {
         );
     }
 
+    #[test]
+    fn diffs_three_dot_range_from_merge_base() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+        let rev = |spec: &str| {
+            run_git_command(workdir, &["rev-parse", spec])
+                .expect("failed to resolve revision")
+                .trim()
+                .to_string()
+        };
+
+        git(workdir, &["init", "-b", "main"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "base.txt", "base\n");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "base"]);
+        git(workdir, &["checkout", "-b", "side"]);
+        write_file(workdir, "side.txt", "one\n");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "side one"]);
+        write_file(workdir, "side.txt", "two\n");
+        git(workdir, &["commit", "-am", "side two"]);
+        let side_tip = rev("HEAD");
+        git(workdir, &["checkout", "main"]);
+        write_file(workdir, "main.txt", "main\n");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "main"]);
+        let main_tip = rev("HEAD");
+
+        let cli_backend =
+            GitCliBackend::discover_from(workdir).expect("failed to discover cli backend");
+        let repo = git2::Repository::open(workdir).expect("failed to open git2 repo");
+        let highlighter = SyntaxHighlighter::default();
+        let settings = DiffSettings::default();
+        let ids = [side_tip, main_tip];
+
+        let two_dot = summarize_files(
+            cli_backend
+                .get_commit_range_diff(&ids, RangeMode::TwoDot, &highlighter)
+                .unwrap(),
+        );
+        let three_dot = summarize_files(
+            cli_backend
+                .get_commit_range_diff(&ids, RangeMode::ThreeDot, &highlighter)
+                .unwrap(),
+        );
+
+        // `side one..main` also shows side.txt going away; the merge-base
+        // form only shows what main added.
+        assert_eq!(two_dot.len(), 2);
+        assert_eq!(
+            three_dot,
+            vec![(
+                Some(PathBuf::from("main.txt")),
+                Some(PathBuf::from("main.txt")),
+                FileStatus::Added
+            )]
+        );
+        assert_eq!(
+            three_dot,
+            summarize_files(
+                diff::get_commit_range_diff(
+                    &repo,
+                    &ids,
+                    RangeMode::ThreeDot,
+                    &settings,
+                    &highlighter
+                )
+                .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn detects_renames_per_rename_detection() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+        let content = "line one\nline two\nline three\nline four\n";
+
+        git(workdir, &["init"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "old.txt", content);
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "initial"]);
+        git(workdir, &["mv", "old.txt", "new.txt"]);
+        write_file(workdir, "new.txt", &format!("{content}line five\n"));
+        git(workdir, &["add", "."]);
+
+        let mut cli_backend =
+            GitCliBackend::discover_from(workdir).expect("failed to discover cli backend");
+        let repo = git2::Repository::open(workdir).expect("failed to open git2 repo");
+        let highlighter = SyntaxHighlighter::default();
+        let renamed = vec![(
+            Some(PathBuf::from("old.txt")),
+            Some(PathBuf::from("new.txt")),
+            FileStatus::Renamed,
+        )];
+
+        let detected = RenameDetection::default();
+        assert_eq!(
+            summarize_files(
+                diff::get_staged_diff(
+                    &repo,
+                    &DiffSettings {
+                        renames: detected,
+                        ..DiffSettings::default()
+                    },
+                    &highlighter,
+                )
+                .unwrap()
+            ),
+            renamed
+        );
+        assert_eq!(
+            summarize_files(cli_backend.get_staged_diff(&highlighter).unwrap()),
+            renamed
+        );
+
+        // A threshold above the actual similarity splits the pair again.
+        let strict = RenameDetection {
+            threshold: 95,
+            ..RenameDetection::default()
+        };
+        cli_backend.set_rename_detection(strict);
+        assert_eq!(cli_backend.get_staged_diff(&highlighter).unwrap().len(), 2);
+        assert_eq!(
+            diff::get_staged_diff(
+                &repo,
+                &DiffSettings {
+                    renames: strict,
+                    ..DiffSettings::default()
+                },
+                &highlighter,
+            )
+            .unwrap()
+            .len(),
+            2
+        );
+
+        let disabled = RenameDetection {
+            enabled: false,
+            ..RenameDetection::default()
+        };
+        cli_backend.set_rename_detection(disabled);
+        assert_eq!(cli_backend.get_staged_diff(&highlighter).unwrap().len(), 2);
+        assert_eq!(
+            diff::get_staged_diff(
+                &repo,
+                &DiffSettings {
+                    renames: disabled,
+                    ..DiffSettings::default()
+                },
+                &highlighter,
+            )
+            .unwrap()
+            .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn break_rewrites_pairs_rewritten_file_as_rename_source() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+        let original: String = (1..=60).map(|i| format!("original line {i}\n")).collect();
+        let rewritten: String = (1..=60).map(|i| format!("brand new {i}\n")).collect();
+
+        git(workdir, &["init"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "lib.rs", &original);
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "initial"]);
+        // Move the file away and write something unrelated in its place.
+        write_file(workdir, "moved.rs", &original);
+        write_file(workdir, "lib.rs", &rewritten);
+        git(workdir, &["add", "."]);
+
+        let mut cli_backend =
+            GitCliBackend::discover_from(workdir).expect("failed to discover cli backend");
+        let repo = git2::Repository::open(workdir).expect("failed to open git2 repo");
+        let highlighter = SyntaxHighlighter::default();
+
+        let detection = RenameDetection::default();
+        cli_backend.set_rename_detection(detection);
+        let cli_files = summarize_files(cli_backend.get_staged_diff(&highlighter).unwrap());
+        assert!(
+            cli_files
+                .iter()
+                .all(|(_, _, status)| *status != FileStatus::Renamed)
+        );
+
+        let detection = RenameDetection {
+            break_rewrites: true,
+            ..RenameDetection::default()
+        };
+        cli_backend.set_rename_detection(detection);
+        let cli_files = summarize_files(cli_backend.get_staged_diff(&highlighter).unwrap());
+        let libgit2_files = summarize_files(
+            diff::get_staged_diff(
+                &repo,
+                &DiffSettings {
+                    renames: detection,
+                    ..DiffSettings::default()
+                },
+                &highlighter,
+            )
+            .unwrap(),
+        );
+        // git labels the pair a copy of the still-present `lib.rs`, libgit2 a
+        // rename plus a re-added `lib.rs`; either way `moved.rs` diffs
+        // against its original content and `lib.rs` shows up once.
+        for files in [&cli_files, &libgit2_files] {
+            assert!(files.iter().any(|(old, new, status)| {
+                old.as_deref() == Some(Path::new("lib.rs"))
+                    && new.as_deref() == Some(Path::new("moved.rs"))
+                    && matches!(status, FileStatus::Renamed | FileStatus::Copied)
+            }));
+            assert_eq!(
+                files
+                    .iter()
+                    .filter(|(_, new, _)| new.as_deref() == Some(Path::new("lib.rs")))
+                    .count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn hunk_headers_include_function_context() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+        let body: String = (1..=10).map(|i| format!("    let x{i} = {i};\n")).collect();
+
+        git(workdir, &["init"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "lib.rs", &format!("fn compute() {{\n{body}}}\n"));
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "initial"]);
+        let changed = body.replace("let x8 = 8;", "let x8 = 80;");
+        write_file(
+            workdir,
+            "lib.rs",
+            &format!("fn compute() {{\n{changed}}}\n"),
+        );
+
+        let cli_backend =
+            GitCliBackend::discover_from(workdir).expect("failed to discover cli backend");
+        let repo = git2::Repository::open(workdir).expect("failed to open git2 repo");
+        let highlighter = SyntaxHighlighter::default();
+
+        let cli_files = cli_backend.get_unstaged_diff(&highlighter).unwrap();
+        let libgit2_files =
+            diff::get_unstaged_diff(&repo, &DiffSettings::default(), &highlighter).unwrap();
+        for files in [cli_files, libgit2_files] {
+            assert!(
+                files[0].hunks[0].header.ends_with("@@ fn compute() {"),
+                "{}",
+                files[0].hunks[0].header
+            );
+        }
+    }
+
+    #[test]
+    fn counts_commit_lines_like_libgit2() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+
+        git(workdir, &["init"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "a.txt", "one\ntwo\n");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "root"]);
+        write_file(workdir, "a.txt", "one\n2\nthree\nfour\n");
+        fs::write(workdir.join("logo.bin"), [0u8, 1, 2, 3]).expect("failed to write file");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "second"]);
+
+        let cli_backend =
+            GitCliBackend::discover_from(workdir).expect("failed to discover cli backend");
+        let repo = git2::Repository::open(workdir).expect("failed to open git2 repo");
+        let ids = repository::resolve_revisions(&repo, "HEAD~1..HEAD")
+            .unwrap()
+            .into_iter()
+            .chain(repository::resolve_revisions(&repo, "HEAD~1").unwrap())
+            .collect::<Vec<_>>();
+
+        let cli_stats = cli_backend.get_commit_line_stats(&ids).unwrap();
+        let libgit2_stats = repository::get_commit_line_stats(&repo, &ids).unwrap();
+
+        assert_eq!(cli_stats, vec![(3, 1), (2, 0)]);
+        assert_eq!(libgit2_stats, cli_stats);
+    }
+
+    #[test]
+    fn finds_parent_commit_like_libgit2() {
+        let (_temp_dir, cli_backend, repo, ids) = setup_standard_parity_repo();
+
+        assert_eq!(
+            cli_backend.parent_commit(&ids[1]).unwrap(),
+            Some(ids[0].clone())
+        );
+        assert_eq!(cli_backend.parent_commit(&ids[0]).unwrap(), None);
+        assert_eq!(
+            repository::parent_commit(&repo, &ids[1]).unwrap(),
+            Some(ids[0].clone())
+        );
+        assert_eq!(repository::parent_commit(&repo, &ids[0]).unwrap(), None);
+    }
+
+    #[test]
+    fn reports_binary_blob_sizes_from_index_and_workdir() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+
+        git(workdir, &["init"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        fs::write(workdir.join("logo.bin"), [0u8, 1, 2, 3]).expect("failed to write file");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "initial"]);
+        fs::write(workdir.join("logo.bin"), [0u8; 10]).expect("failed to write file");
+        git(workdir, &["add", "logo.bin"]);
+        fs::write(workdir.join("logo.bin"), [0u8; 25]).expect("failed to write file");
+        fs::write(workdir.join("new.bin"), [0u8; 7]).expect("failed to write file");
+        git(workdir, &["add", "new.bin"]);
+
+        let backend = GitCliBackend::discover_from(workdir).expect("failed to discover backend");
+        let highlighter = SyntaxHighlighter::default();
+        let sizes_of = |files: Vec<DiffFile>| -> Vec<(Option<PathBuf>, FileSizes)> {
+            let mut sizes: Vec<_> = files
+                .into_iter()
+                .inspect(|file| assert!(file.is_binary))
+                .map(|file| (file.new_path, file.sizes))
+                .collect();
+            sizes.sort_by(|left, right| left.0.cmp(&right.0));
+            sizes
+        };
+
+        let staged = backend
+            .get_staged_diff(&highlighter)
+            .expect("failed to read staged diff");
+        let unstaged = backend
+            .get_unstaged_diff(&highlighter)
+            .expect("failed to read unstaged diff");
+
+        assert_eq!(
+            sizes_of(staged),
+            vec![
+                (
+                    Some(PathBuf::from("logo.bin")),
+                    FileSizes {
+                        old: Some(4),
+                        new: Some(10),
+                    },
+                ),
+                (
+                    Some(PathBuf::from("new.bin")),
+                    FileSizes {
+                        old: None,
+                        new: Some(7),
+                    },
+                ),
+            ]
+        );
+        assert_eq!(
+            sizes_of(unstaged),
+            vec![(
+                Some(PathBuf::from("logo.bin")),
+                FileSizes {
+                    old: Some(10),
+                    new: Some(25),
+                },
+            )]
+        );
+    }
+
+    #[test]
+    fn ignore_whitespace_hides_whitespace_only_hunks() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+
+        git(workdir, &["init"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "lib.rs", "fn a() {\n    one();\n}\n\nfn b() {}\n");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-m", "initial"]);
+        // Reindent `a` and change `b`; only the latter is a real change.
+        write_file(
+            workdir,
+            "lib.rs",
+            "fn a() {\n\tone();\n}\n\nfn b() { two(); }\n",
+        );
+
+        let mut cli_backend =
+            GitCliBackend::discover_from(workdir).expect("failed to discover cli backend");
+        let repo = git2::Repository::open(workdir).expect("failed to open git2 repo");
+        let highlighter = SyntaxHighlighter::default();
+        let changed_lines = |files: Vec<DiffFile>| -> Vec<String> {
+            files
+                .iter()
+                .flat_map(|file| &file.hunks)
+                .flat_map(|hunk| &hunk.lines)
+                .filter(|line| line.origin != LineOrigin::Context)
+                .map(|line| line.content.clone())
+                .collect()
+        };
+
+        let whitespace = IgnoreWhitespace {
+            all_space: true,
+            ..IgnoreWhitespace::default()
+        };
+        cli_backend.set_ignore_whitespace(whitespace);
+        let cli_lines = changed_lines(cli_backend.get_unstaged_diff(&highlighter).unwrap());
+        let libgit2_lines = changed_lines(
+            diff::get_unstaged_diff(
+                &repo,
+                &DiffSettings {
+                    whitespace,
+                    ..DiffSettings::default()
+                },
+                &highlighter,
+            )
+            .unwrap(),
+        );
+        assert_eq!(cli_lines, vec!["fn b() {}", "fn b() { two(); }"]);
+        assert_eq!(cli_lines, libgit2_lines);
+
+        cli_backend.set_ignore_whitespace(IgnoreWhitespace::default());
+        assert_eq!(
+            changed_lines(cli_backend.get_unstaged_diff(&highlighter).unwrap()).len(),
+            4
+        );
+    }
+
     #[test]
     fn cli_diff_outputs_match_libgit2_for_shared_git_operations() {
         let (_temp_dir, cli_backend, repo, ids) = setup_standard_parity_repo();
         let highlighter = SyntaxHighlighter::default();
+        let settings = DiffSettings::default();
 
         assert_eq!(
             summarize_files(cli_backend.get_working_tree_diff(&highlighter).unwrap()),
-            summarize_files(diff::get_working_tree_diff(&repo, &highlighter).unwrap())
+            summarize_files(diff::get_working_tree_diff(&repo, &settings, &highlighter).unwrap())
         );
         assert_eq!(
             summarize_files(cli_backend.get_staged_diff(&highlighter).unwrap()),
-            summarize_files(diff::get_staged_diff(&repo, &highlighter).unwrap())
+            summarize_files(diff::get_staged_diff(&repo, &settings, &highlighter).unwrap())
         );
         assert_eq!(
             summarize_files(cli_backend.get_unstaged_diff(&highlighter).unwrap()),
-            summarize_files(diff::get_unstaged_diff(&repo, &highlighter).unwrap())
+            summarize_files(diff::get_unstaged_diff(&repo, &settings, &highlighter).unwrap())
         );
         assert_eq!(
             summarize_files(
                 cli_backend
-                    .get_commit_range_diff(&[ids[1].clone()], &highlighter)
+                    .get_commit_range_diff(&[ids[1].clone()], RangeMode::TwoDot, &highlighter)
                     .unwrap()
             ),
             summarize_files(
-                diff::get_commit_range_diff(&repo, &[ids[1].clone()], &highlighter).unwrap()
+                diff::get_commit_range_diff(
+                    &repo,
+                    &[ids[1].clone()],
+                    RangeMode::TwoDot,
+                    &settings,
+                    &highlighter
+                )
+                .unwrap()
             )
         );
         assert_eq!(
@@ -1316,8 +2205,13 @@ mod tests {
                     .unwrap()
             ),
             summarize_files(
-                diff::get_working_tree_with_commits_diff(&repo, &[ids[1].clone()], &highlighter)
-                    .unwrap()
+                diff::get_working_tree_with_commits_diff(
+                    &repo,
+                    &[ids[1].clone()],
+                    &settings,
+                    &highlighter,
+                )
+                .unwrap()
             )
         );
 
@@ -1339,6 +2233,13 @@ mod tests {
             cli_backend.resolve_revisions(&revset).unwrap(),
             repository::resolve_revisions(&repo, &revset).unwrap()
         );
+        // The pre-push hook reviews a history up from its root commit this way
+        let revset = format!("{EMPTY_TREE_OID}..{}", ids[1]);
+        assert_eq!(cli_backend.resolve_revisions(&revset).unwrap(), ids[..2]);
+        assert_eq!(
+            repository::resolve_revisions(&repo, &revset).unwrap(),
+            ids[..2]
+        );
 
         let cli_commit_info = cli_backend.get_commits_info(&ids).unwrap();
         let libgit2_commit_info = repository::get_commits_info(&repo, &ids).unwrap();
@@ -1353,4 +2254,111 @@ mod tests {
                 .collect::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn should_reuse_cached_renames_for_the_same_changes() {
+        // given
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+        let body = "one\ntwo\nthree\nfour\nfive\n";
+        git(workdir, &["init", "-q"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "a.txt", "a\n");
+        write_file(workdir, "m.txt", "m\n");
+        write_file(workdir, "lib/old.rs", body);
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-q", "-m", "initial"]);
+        write_file(workdir, "a.txt", "a changed\n");
+        remove_file(workdir, "m.txt");
+        remove_file(workdir, "lib/old.rs");
+        write_file(workdir, "src/new.rs", &format!("{body}six\n"));
+        git(workdir, &["add", "-A", "lib", "src"]);
+        write_file(workdir, "c.txt", "c\n");
+        git(workdir, &["add", "c.txt"]);
+        let backend = GitCliBackend::discover_from(workdir).expect("failed to discover");
+        let repo = git2::Repository::open(workdir).expect("failed to open repo");
+        let highlighter = SyntaxHighlighter::default();
+        let summary = |files: &[DiffFile]| -> Vec<_> {
+            files
+                .iter()
+                .map(|f| {
+                    let lines: Vec<_> = f
+                        .hunks
+                        .iter()
+                        .flat_map(|h| &h.lines)
+                        .map(|l| l.content.clone())
+                        .collect();
+                    (f.old_path.clone(), f.new_path.clone(), f.status, lines)
+                })
+                .collect()
+        };
+
+        // when
+        let detected = backend.get_working_tree_diff(&highlighter).unwrap();
+        let cached = backend.get_working_tree_diff(&highlighter).unwrap();
+
+        // then
+        let pairs = vec![(PathBuf::from("lib/old.rs"), PathBuf::from("src/new.rs"))];
+        assert_eq!(rename_cache::pairs(&detected), pairs);
+        assert!(rename_cache::is_cached(&pairs));
+        assert_eq!(summary(&cached), summary(&detected));
+        assert_eq!(
+            summarize_files(cached),
+            summarize_files(
+                diff::get_working_tree_diff(&repo, &DiffSettings::default(), &highlighter).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn should_match_libgit2_interdiff_for_rebased_series() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+        let head = || {
+            run_git_command(workdir, &["rev-parse", "HEAD"])
+                .expect("failed to resolve HEAD")
+                .trim()
+                .to_string()
+        };
+
+        git(workdir, &["init", "-q"]);
+        git(workdir, &["config", "user.email", "test@example.com"]);
+        git(workdir, &["config", "user.name", "Test User"]);
+        write_file(workdir, "a.txt", "1\n2\n3\n");
+        write_file(workdir, "b.txt", "b\n");
+        git(workdir, &["add", "."]);
+        git(workdir, &["commit", "-q", "-m", "base"]);
+        let base = head();
+        git(workdir, &["checkout", "-q", "-b", "v1"]);
+        write_file(workdir, "a.txt", "one\n2\n3\n");
+        git(workdir, &["commit", "-q", "-am", "v1"]);
+        git(workdir, &["checkout", "-q", &base]);
+        write_file(workdir, "b.txt", "bee\n");
+        git(workdir, &["commit", "-q", "-am", "upstream"]);
+        let upstream = head();
+        write_file(workdir, "a.txt", "one\ntwo\n3\n");
+        git(workdir, &["commit", "-q", "-am", "v2"]);
+        let v2 = head();
+
+        let old = format!("{base}..v1");
+        let new = format!("{upstream}..{v2}");
+        let cli_backend = GitCliBackend::discover_from(workdir).expect("failed to discover");
+        let repo = git2::Repository::open(workdir).expect("failed to open repo");
+        let highlighter = SyntaxHighlighter::default();
+
+        let cli_files = cli_backend
+            .get_interdiff(&old, &new, &highlighter)
+            .expect("cli interdiff failed");
+        let libgit2_files =
+            diff::get_interdiff(&repo, &old, &new, &DiffSettings::default(), &highlighter)
+                .expect("libgit2 interdiff failed");
+
+        assert_eq!(
+            summarize_files(cli_files.clone()),
+            summarize_files(libgit2_files)
+        );
+        assert_eq!(cli_files.len(), 1);
+        assert_eq!(cli_files[0].display_path(), &PathBuf::from("a.txt"));
+    }
 }
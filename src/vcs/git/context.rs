@@ -20,10 +20,7 @@ pub fn fetch_context_lines(
     }
 
     let content = match file_status {
-        FileStatus::Deleted => {
-            // Read from HEAD blob for deleted files
-            fetch_blob_content(repo, file_path)?
-        }
+        FileStatus::Deleted => fetch_blob_content(repo, file_path)?,
         _ => {
             // Read from working tree for all other statuses
             let workdir = repo.workdir().ok_or(TuicrError::NotARepository)?;
@@ -51,8 +48,8 @@ pub fn fetch_context_lines(
     Ok(result)
 }
 
-/// Fetch content from a git blob (for deleted files)
-fn fetch_blob_content(repo: &Repository, file_path: &Path) -> Result<String> {
+/// Fetch a file's content at HEAD (for deleted files)
+pub fn fetch_blob_content(repo: &Repository, file_path: &Path) -> Result<String> {
     let head = repo.head()?.peel_to_tree()?;
     let entry = head.get_path(file_path)?;
     let blob = repo.find_blob(entry.id())?;
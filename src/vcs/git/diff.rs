@@ -1,24 +1,35 @@
-use git2::{Delta, Diff, DiffOptions, Repository};
+use git2::{Delta, Diff, DiffFindOptions, DiffOptions, Repository};
 use std::path::{Path, PathBuf};
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffHunk, DiffLine, FileStatus, LineOrigin};
+use crate::model::{DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin};
 use crate::syntax::{SyntaxHighlighter, needs_full_file_highlight};
-use crate::vcs::{enhance_with_full_file_highlight, tabify};
+use crate::vcs::git::rename_cache;
+use crate::vcs::{
+    IgnoreWhitespace, RangeMode, RenameDetection, enhance_with_full_file_highlight, tabify,
+};
+
+/// Backend-level knobs applied to every libgit2 diff.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffSettings {
+    pub renames: RenameDetection,
+    pub whitespace: IgnoreWhitespace,
+    /// Leave untracked files out of diffs against the working tree.
+    pub hide_untracked: bool,
+}
 
 pub fn get_working_tree_diff(
     repo: &Repository,
+    settings: &DiffSettings,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     let head = repo.head()?.peel_to_tree()?;
 
-    let mut opts = DiffOptions::new();
-    opts.include_untracked(true);
-    opts.show_untracked_content(true);
-    opts.recurse_untracked_dirs(true);
-
-    let diff = repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut opts))?;
-    let mut files = parse_diff(&diff, highlighter)?;
+    let mut files = parse_with_renames(repo, settings, highlighter, |paths| {
+        let mut opts = workdir_diff_options(settings);
+        limit_to_paths(&mut opts, paths);
+        Ok(repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut opts))?)
+    })?;
     enhance_with_full_file_highlight(
         &mut files,
         highlighter,
@@ -32,12 +43,16 @@ pub fn get_working_tree_diff(
 /// On repos with no commits (unborn HEAD), diffs against an empty tree.
 pub fn get_staged_diff(
     repo: &Repository,
+    settings: &DiffSettings,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
     let index = repo.index()?;
-    let diff = repo.diff_tree_to_index(head.as_ref(), Some(&index), None)?;
-    let mut files = parse_diff(&diff, highlighter)?;
+    let mut files = parse_with_renames(repo, settings, highlighter, |paths| {
+        let mut opts = diff_options(&settings.whitespace);
+        limit_to_paths(&mut opts, paths);
+        Ok(repo.diff_tree_to_index(head.as_ref(), Some(&index), Some(&mut opts))?)
+    })?;
     enhance_with_full_file_highlight(
         &mut files,
         highlighter,
@@ -53,16 +68,15 @@ pub fn get_staged_diff(
 /// Get the unstaged diff (working tree vs index)
 pub fn get_unstaged_diff(
     repo: &Repository,
+    settings: &DiffSettings,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     let index = repo.index()?;
-    let mut opts = DiffOptions::new();
-    opts.include_untracked(true);
-    opts.show_untracked_content(true);
-    opts.recurse_untracked_dirs(true);
-
-    let diff = repo.diff_index_to_workdir(Some(&index), Some(&mut opts))?;
-    let mut files = parse_diff(&diff, highlighter)?;
+    let mut files = parse_with_renames(repo, settings, highlighter, |paths| {
+        let mut opts = workdir_diff_options(settings);
+        limit_to_paths(&mut opts, paths);
+        Ok(repo.diff_index_to_workdir(Some(&index), Some(&mut opts))?)
+    })?;
     enhance_with_full_file_highlight(
         &mut files,
         highlighter,
@@ -74,10 +88,13 @@ pub fn get_unstaged_diff(
 
 /// Get the diff for a range of commits.
 /// `commit_ids` should be ordered from oldest to newest.
-/// The diff compares the oldest commit's parent to the newest commit.
+/// The diff compares the oldest commit's parent (or, in three-dot mode, its
+/// merge-base with the newest commit) to the newest commit.
 pub fn get_commit_range_diff(
     repo: &Repository,
     commit_ids: &[String],
+    range_mode: RangeMode,
+    settings: &DiffSettings,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     if commit_ids.is_empty() {
@@ -91,15 +108,23 @@ pub fn get_commit_range_diff(
     let newest_commit = repo.find_commit(newest_id)?;
 
     let old_tree = if oldest_commit.parent_count() > 0 {
-        Some(oldest_commit.parent(0)?.tree()?)
+        let parent_id = oldest_commit.parent_id(0)?;
+        let base_id = match range_mode {
+            RangeMode::TwoDot => parent_id,
+            RangeMode::ThreeDot => repo.merge_base(parent_id, newest_id)?,
+        };
+        Some(repo.find_commit(base_id)?.tree()?)
     } else {
         None
     };
 
     let new_tree = newest_commit.tree()?;
 
-    let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
-    let mut files = parse_diff(&diff, highlighter)?;
+    let mut files = parse_with_renames(repo, settings, highlighter, |paths| {
+        let mut opts = diff_options(&settings.whitespace);
+        limit_to_paths(&mut opts, paths);
+        Ok(repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), Some(&mut opts))?)
+    })?;
     enhance_with_full_file_highlight(
         &mut files,
         highlighter,
@@ -118,6 +143,7 @@ pub fn get_commit_range_diff(
 pub fn get_working_tree_with_commits_diff(
     repo: &Repository,
     commit_ids: &[String],
+    settings: &DiffSettings,
     highlighter: &SyntaxHighlighter,
 ) -> Result<Vec<DiffFile>> {
     if commit_ids.is_empty() {
@@ -133,13 +159,11 @@ pub fn get_working_tree_with_commits_diff(
         None
     };
 
-    let mut opts = DiffOptions::new();
-    opts.include_untracked(true);
-    opts.show_untracked_content(true);
-    opts.recurse_untracked_dirs(true);
-
-    let diff = repo.diff_tree_to_workdir_with_index(old_tree.as_ref(), Some(&mut opts))?;
-    let mut files = parse_diff(&diff, highlighter)?;
+    let mut files = parse_with_renames(repo, settings, highlighter, |paths| {
+        let mut opts = workdir_diff_options(settings);
+        limit_to_paths(&mut opts, paths);
+        Ok(repo.diff_tree_to_workdir_with_index(old_tree.as_ref(), Some(&mut opts))?)
+    })?;
     enhance_with_full_file_highlight(
         &mut files,
         highlighter,
@@ -153,6 +177,217 @@ pub fn get_working_tree_with_commits_diff(
     Ok(files)
 }
 
+/// Resolve one version of a patch series to its `(base, tip)` trees. `A..B`
+/// means the commits on `B` since it forked from `A`; a single revision is
+/// that commit alone.
+fn resolve_patch_version<'r>(
+    repo: &'r Repository,
+    spec: &str,
+) -> Result<(git2::Tree<'r>, git2::Tree<'r>)> {
+    let revspec = repo.revparse(spec)?;
+    let (base, tip) = if revspec.mode().contains(git2::RevparseMode::RANGE) {
+        let (Some(from), Some(to)) = (revspec.from(), revspec.to()) else {
+            return Err(TuicrError::VcsCommand(format!(
+                "Invalid revision range: {spec}"
+            )));
+        };
+        let tip = to.peel_to_commit()?;
+        let base = repo.merge_base(from.peel_to_commit()?.id(), tip.id())?;
+        (Some(repo.find_commit(base)?), tip)
+    } else {
+        let tip = revspec
+            .from()
+            .ok_or_else(|| TuicrError::VcsCommand(format!("Invalid revision: {spec}")))?
+            .peel_to_commit()?;
+        (tip.parent(0).ok(), tip)
+    };
+    let base_tree = match base {
+        Some(base) => base.tree()?,
+        None => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+    };
+    Ok((base_tree, tip.tree()?))
+}
+
+/// Paths changed between two trees, on either side of a rename.
+fn changed_paths(repo: &Repository, old: &git2::Tree, new: &git2::Tree) -> Result<Vec<PathBuf>> {
+    let diff = repo.diff_tree_to_tree(Some(old), Some(new), None)?;
+    Ok(diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// Get the interdiff between two versions of a patch series, e.g. a branch
+/// before and after a rebase: what changed in `new_spec` relative to
+/// `old_spec`. The old version is replayed onto the new version's base so
+/// upstream changes picked up by the rebase cancel out. When it doesn't
+/// replay cleanly, the two tips are compared directly, limited to the files
+/// either version touches.
+pub fn get_interdiff(
+    repo: &Repository,
+    old_spec: &str,
+    new_spec: &str,
+    settings: &DiffSettings,
+    highlighter: &SyntaxHighlighter,
+) -> Result<Vec<DiffFile>> {
+    let (old_base, old_tip) = resolve_patch_version(repo, old_spec)?;
+    let (new_base, new_tip) = resolve_patch_version(repo, new_spec)?;
+
+    let mut replayed = repo.merge_trees(&old_base, &new_base, &old_tip, None)?;
+    let (old_tree, touched) = if replayed.has_conflicts() {
+        let mut paths = changed_paths(repo, &old_base, &old_tip)?;
+        paths.extend(changed_paths(repo, &new_base, &new_tip)?);
+        if paths.is_empty() {
+            return Err(TuicrError::NoChanges);
+        }
+        (old_tip, Some(paths))
+    } else {
+        (repo.find_tree(replayed.write_tree_to(repo)?)?, None)
+    };
+
+    let mut files = parse_with_renames(repo, settings, highlighter, |paths| {
+        let mut opts = diff_options(&settings.whitespace);
+        limit_to_paths(&mut opts, paths.or(touched.as_deref()));
+        Ok(repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tip), Some(&mut opts))?)
+    })?;
+    enhance_with_full_file_highlight(
+        &mut files,
+        highlighter,
+        |path| read_path_from_tree(repo, &old_tree, path),
+        |path| read_path_from_tree(repo, &new_tip, path),
+    );
+    Ok(files)
+}
+
+fn diff_options(whitespace: &IgnoreWhitespace) -> DiffOptions {
+    let mut opts = DiffOptions::new();
+    opts.ignore_whitespace(whitespace.all_space)
+        .ignore_whitespace_change(whitespace.space_change)
+        .ignore_blank_lines(whitespace.blank_lines);
+    opts
+}
+
+/// Options for diffs against the working tree, listing untracked files
+/// unless `settings` hides them.
+fn workdir_diff_options(settings: &DiffSettings) -> DiffOptions {
+    let mut opts = diff_options(&settings.whitespace);
+    if !settings.hide_untracked {
+        opts.include_untracked(true);
+        opts.show_untracked_content(true);
+        opts.recurse_untracked_dirs(true);
+    }
+    opts
+}
+
+/// Limit a diff to exactly `paths`, when given.
+fn limit_to_paths(opts: &mut DiffOptions, paths: Option<&[PathBuf]>) {
+    let Some(paths) = paths else {
+        return;
+    };
+    for path in paths {
+        opts.pathspec(path);
+    }
+    opts.disable_pathspec_match(true);
+}
+
+/// Parse the diff `diff_for` builds, with renames detected per `settings`.
+/// `diff_for` limits the diff to the given paths, if any, so a cached
+/// result only needs detecting again over the paths that paired up.
+fn parse_with_renames<'r>(
+    repo: &'r Repository,
+    settings: &DiffSettings,
+    highlighter: &SyntaxHighlighter,
+    diff_for: impl Fn(Option<&[PathBuf]>) -> Result<Diff<'r>>,
+) -> Result<Vec<DiffFile>> {
+    let mut diff = diff_for(None)?;
+    if !settings.renames.enabled {
+        return parse_diff(&diff, highlighter);
+    }
+
+    let key = rename_cache_key(repo, &diff, &settings.renames);
+    if let Some(pairs) = rename_cache::lookup(key) {
+        let files = parse_diff(&diff, highlighter)?;
+        if pairs.is_empty() {
+            return Ok(files);
+        }
+        let mut paired = diff_for(Some(&rename_cache::paired_paths(&pairs)))?;
+        find_renames(&mut paired, &settings.renames)?;
+        let paired = parse_diff(&paired, highlighter)?;
+        if let Some(files) = rename_cache::splice(files, paired, &pairs) {
+            return Ok(files);
+        }
+    }
+
+    find_renames(&mut diff, &settings.renames)?;
+    let files = parse_diff(&diff, highlighter)?;
+    rename_cache::store(key, rename_cache::pairs(&files));
+    Ok(files)
+}
+
+/// The rename cache key for the tracked changes in `diff`. Working tree
+/// files have no blob id yet, so they are hashed here.
+fn rename_cache_key(repo: &Repository, diff: &Diff, renames: &RenameDetection) -> u64 {
+    let blob_id = |file: git2::DiffFile| {
+        let id = match (
+            file.is_valid_id() || !file.exists(),
+            repo.workdir(),
+            file.path(),
+        ) {
+            (false, Some(workdir), Some(path)) => {
+                git2::Oid::hash_file(git2::ObjectType::Blob, workdir.join(path)).ok()
+            }
+            _ => None,
+        };
+        id.unwrap_or_else(|| file.id()).to_string()
+    };
+    let deltas = diff.deltas().filter_map(|delta| {
+        let status = match delta.status() {
+            Delta::Added => 'A',
+            Delta::Deleted => 'D',
+            Delta::Modified => 'M',
+            Delta::Typechange => 'T',
+            Delta::Conflicted => 'U',
+            _ => return None,
+        };
+        Some(rename_cache::DeltaKey {
+            status,
+            old_path: delta.old_file().path_bytes().unwrap_or_default(),
+            old_id: blob_id(delta.old_file()),
+            new_path: delta.new_file().path_bytes().unwrap_or_default(),
+            new_id: blob_id(delta.new_file()),
+        })
+    });
+    rename_cache::key(renames, deltas)
+}
+
+/// Pair deleted/added files into renames (and copies) per `renames`.
+fn find_renames(diff: &mut Diff, renames: &RenameDetection) -> Result<()> {
+    if !renames.enabled {
+        return Ok(());
+    }
+    let mut opts = DiffFindOptions::new();
+    opts.renames(true)
+        .rename_threshold(renames.threshold)
+        .rename_limit(match renames.limit {
+            0 => usize::MAX,
+            limit => limit,
+        });
+    if renames.copies {
+        opts.copies(true).copy_threshold(renames.threshold);
+    }
+    if renames.break_rewrites {
+        // Broken pairs that don't end up as a rename are rejoined, matching
+        // `git diff -B -M`, so a path never shows up twice.
+        opts.rewrites(true)
+            .break_rewrites(true)
+            .break_rewrites_for_renames_only(true);
+    }
+    diff.find_similar(Some(&mut opts))?;
+    Ok(())
+}
+
 fn read_path_from_tree(repo: &Repository, tree: &git2::Tree, path: &Path) -> Option<String> {
     let entry = tree.get_path(path).ok()?;
     let blob = repo.find_blob(entry.id()).ok()?;
@@ -169,6 +404,19 @@ fn read_path_from_index(repo: &Repository, index: &git2::Index, path: &Path) ->
     Some(String::from_utf8_lossy(blob.content()).into_owned())
 }
 
+/// The delta side's git file mode, or `None` when that side doesn't exist.
+fn file_mode(file: &git2::DiffFile) -> Option<u32> {
+    match file.mode() {
+        git2::FileMode::Unreadable => None,
+        mode => Some(u32::from(mode)),
+    }
+}
+
+/// The delta side's blob size in bytes, or `None` when that side doesn't exist.
+fn file_size(file: &git2::DiffFile) -> Option<u64> {
+    file_mode(file).map(|_| file.size())
+}
+
 fn parse_diff(diff: &Diff, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
     let mut files: Vec<DiffFile> = Vec::new();
 
@@ -188,15 +436,31 @@ fn parse_diff(diff: &Diff, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFi
 
         let old_path = delta.old_file().path().map(PathBuf::from);
         let new_path = delta.new_file().path().map(PathBuf::from);
-        let is_binary = delta.old_file().is_binary() || delta.new_file().is_binary();
         let is_too_large =
             delta.status() == Delta::Untracked && delta.new_file().size() > MAX_UNTRACKED_FILE_SIZE;
 
-        let syntax_path = new_path.as_ref().or(old_path.as_ref()).map(|p| p.as_path());
-        let hunks = if is_binary || is_too_large {
-            Vec::new()
+        // Binary detection needs the blob contents, which libgit2 only
+        // loads once the patch is generated; read the flags from its delta.
+        let patch = if is_too_large {
+            None
         } else {
-            parse_hunks(diff, delta_idx, highlighter, syntax_path)?
+            git2::Patch::from_diff(diff, delta_idx)?
+        };
+        let loaded = patch.as_ref().map(|patch| patch.delta()).unwrap_or(delta);
+        let is_binary = loaded.old_file().is_binary() || loaded.new_file().is_binary();
+        let sizes = if is_binary {
+            FileSizes {
+                old: file_size(&loaded.old_file()),
+                new: file_size(&loaded.new_file()),
+            }
+        } else {
+            FileSizes::default()
+        };
+
+        let syntax_path = new_path.as_ref().or(old_path.as_ref()).map(|p| p.as_path());
+        let hunks = match &patch {
+            Some(patch) if !is_binary => parse_hunks(patch, highlighter, syntax_path)?,
+            _ => Vec::new(),
         };
 
         let content_hash = DiffFile::compute_content_hash(&hunks);
@@ -209,6 +473,11 @@ fn parse_diff(diff: &Diff, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFi
             is_too_large,
             is_commit_message: false,
             content_hash,
+            modes: FileModes {
+                old: file_mode(&loaded.old_file()),
+                new: file_mode(&loaded.new_file()),
+            },
+            sizes,
         });
     }
 
@@ -220,90 +489,88 @@ fn parse_diff(diff: &Diff, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFi
 }
 
 fn parse_hunks(
-    diff: &Diff,
-    delta_idx: usize,
+    patch: &git2::Patch,
     highlighter: &SyntaxHighlighter,
     file_path: Option<&Path>,
 ) -> Result<Vec<DiffHunk>> {
     let mut hunks: Vec<DiffHunk> = Vec::new();
 
-    let patch = git2::Patch::from_diff(diff, delta_idx)?;
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(hunk_idx)?;
 
-    if let Some(patch) = patch {
-        for hunk_idx in 0..patch.num_hunks() {
-            let (hunk, _) = patch.hunk(hunk_idx)?;
+        let header = String::from_utf8_lossy(hunk.header()).trim().to_string();
+        let old_start = hunk.old_start();
+        let old_count = hunk.old_lines();
+        let new_start = hunk.new_start();
+        let new_count = hunk.new_lines();
 
-            let header = String::from_utf8_lossy(hunk.header()).trim().to_string();
-            let old_start = hunk.old_start();
-            let old_count = hunk.old_lines();
-            let new_start = hunk.new_start();
-            let new_count = hunk.new_lines();
+        let mut line_contents: Vec<String> = Vec::new();
+        let mut line_origins: Vec<LineOrigin> = Vec::new();
+        let mut line_numbers: Vec<(Option<u32>, Option<u32>)> = Vec::new();
 
-            let mut line_contents: Vec<String> = Vec::new();
-            let mut line_origins: Vec<LineOrigin> = Vec::new();
-            let mut line_numbers: Vec<(Option<u32>, Option<u32>)> = Vec::new();
+        for line_idx in 0..patch.num_lines_in_hunk(hunk_idx)? {
+            let line = patch.line_in_hunk(hunk_idx, line_idx)?;
 
-            for line_idx in 0..patch.num_lines_in_hunk(hunk_idx)? {
-                let line = patch.line_in_hunk(hunk_idx, line_idx)?;
-
-                let origin = match line.origin() {
-                    '+' => LineOrigin::Addition,
-                    '-' => LineOrigin::Deletion,
-                    ' ' => LineOrigin::Context,
-                    _ => LineOrigin::Context,
-                };
+            let origin = match line.origin() {
+                '+' => LineOrigin::Addition,
+                '-' => LineOrigin::Deletion,
+                ' ' => LineOrigin::Context,
+                _ => LineOrigin::Context,
+            };
 
-                let raw = String::from_utf8_lossy(line.content());
-                let content = tabify(raw.trim_end_matches(['\n', '\r']));
+            let raw = String::from_utf8_lossy(line.content());
+            let content = tabify(
+                file_path.unwrap_or(Path::new("")),
+                raw.trim_end_matches(['\n', '\r']),
+            );
 
-                line_contents.push(content);
-                line_origins.push(origin);
-                line_numbers.push((line.old_lineno(), line.new_lineno()));
-            }
+            line_contents.push(content);
+            line_origins.push(origin);
+            line_numbers.push((line.old_lineno(), line.new_lineno()));
+        }
 
-            let sequences =
-                SyntaxHighlighter::split_diff_lines_for_highlighting(&line_contents, &line_origins);
-            // Container grammars skip per-hunk highlighting; the full-file
-            // post-pass overwrites these spans anyway.
-            let (old_highlighted, new_highlighted) = match file_path {
-                Some(path) if !needs_full_file_highlight(path) => (
-                    highlighter.highlight_file_lines(path, &sequences.old_lines),
-                    highlighter.highlight_file_lines(path, &sequences.new_lines),
-                ),
-                _ => (None, None),
-            };
+        let sequences =
+            SyntaxHighlighter::split_diff_lines_for_highlighting(&line_contents, &line_origins);
+        // Container grammars skip per-hunk highlighting; the full-file
+        // post-pass overwrites these spans anyway.
+        let (old_highlighted, new_highlighted) = match file_path {
+            Some(path) if !needs_full_file_highlight(path) => (
+                highlighter.highlight_file_lines(path, &sequences.old_lines),
+                highlighter.highlight_file_lines(path, &sequences.new_lines),
+            ),
+            _ => (None, None),
+        };
 
-            let mut lines: Vec<DiffLine> = Vec::with_capacity(line_contents.len());
-            for (idx, content) in line_contents.into_iter().enumerate() {
-                let origin = line_origins[idx];
-                let (old_lineno, new_lineno) = line_numbers[idx];
-
-                let highlighted_spans = highlighter.highlighted_line_for_diff_with_background(
-                    old_highlighted.as_deref(),
-                    new_highlighted.as_deref(),
-                    sequences.old_line_indices[idx],
-                    sequences.new_line_indices[idx],
-                    origin,
-                );
-
-                lines.push(DiffLine {
-                    origin,
-                    content,
-                    old_lineno,
-                    new_lineno,
-                    highlighted_spans,
-                });
-            }
+        let mut lines: Vec<DiffLine> = Vec::with_capacity(line_contents.len());
+        for (idx, content) in line_contents.into_iter().enumerate() {
+            let origin = line_origins[idx];
+            let (old_lineno, new_lineno) = line_numbers[idx];
+
+            let highlighted_spans = highlighter.highlighted_line_for_diff_with_background(
+                old_highlighted.as_deref(),
+                new_highlighted.as_deref(),
+                sequences.old_line_indices[idx],
+                sequences.new_line_indices[idx],
+                origin,
+            );
 
-            hunks.push(DiffHunk {
-                header,
-                lines,
-                old_start,
-                old_count,
-                new_start,
-                new_count,
+            lines.push(DiffLine {
+                origin,
+                content,
+                old_lineno,
+                new_lineno,
+                highlighted_spans,
             });
         }
+
+        hunks.push(DiffHunk {
+            header,
+            lines,
+            old_start,
+            old_count,
+            new_start,
+            new_count,
+        });
     }
 
     Ok(hunks)
@@ -365,8 +632,12 @@ mod tests {
         )
         .expect("failed to update file");
 
-        let files = get_working_tree_diff(&repo, &SyntaxHighlighter::default())
-            .expect("failed to get diff");
+        let files = get_working_tree_diff(
+            &repo,
+            &DiffSettings::default(),
+            &SyntaxHighlighter::default(),
+        )
+        .expect("failed to get diff");
 
         assert_eq!(files.len(), 1);
         let lines = &files[0].hunks[0].lines;
@@ -389,8 +660,12 @@ mod tests {
         let edited = "<template>\n  <div>{{ msg }}</div>\n</template>\n\n<script setup>\nimport { ref } from 'vue'\nconst msg = ref('hello')\nconst other = 1\n</script>\n";
         fs::write(temp_dir.path().join("App.vue"), edited).expect("failed to update file");
 
-        let files = get_working_tree_diff(&repo, &SyntaxHighlighter::default())
-            .expect("failed to get diff");
+        let files = get_working_tree_diff(
+            &repo,
+            &DiffSettings::default(),
+            &SyntaxHighlighter::default(),
+        )
+        .expect("failed to get diff");
         assert_eq!(files.len(), 1);
 
         let changed_lines: Vec<_> = files[0].hunks[0]
@@ -414,6 +689,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn should_report_blob_sizes_for_binary_files() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("failed to init repo");
+
+        create_initial_commit(&repo, "file.txt", "base\n");
+        fs::write(temp_dir.path().join("logo.bin"), [0u8, 1, 2, 3, 0, 5])
+            .expect("failed to write binary file");
+        let mut index = repo.index().expect("failed to open index");
+        index
+            .add_path(Path::new("logo.bin"))
+            .expect("failed to add file to index");
+        index.write().expect("failed to write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("failed to write tree"))
+            .expect("failed to find tree");
+        let sig = git2::Signature::now("Test User", "test@example.com")
+            .expect("failed to create signature");
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "add logo", &tree, &[&parent])
+            .expect("failed to create commit");
+
+        fs::write(temp_dir.path().join("logo.bin"), [0u8; 1_500])
+            .expect("failed to update binary file");
+
+        let files = get_working_tree_diff(
+            &repo,
+            &DiffSettings::default(),
+            &SyntaxHighlighter::default(),
+        )
+        .expect("failed to get diff");
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].is_binary);
+        assert_eq!(
+            files[0].sizes,
+            FileSizes {
+                old: Some(6),
+                new: Some(1_500),
+            }
+        );
+    }
+
     #[test]
     fn should_separate_staged_and_unstaged_diffs() {
         let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
@@ -425,10 +743,11 @@ mod tests {
 
         let highlighter = SyntaxHighlighter::default();
 
-        let unstaged = get_unstaged_diff(&repo, &highlighter).expect("unstaged diff failed");
+        let unstaged = get_unstaged_diff(&repo, &DiffSettings::default(), &highlighter)
+            .expect("unstaged diff failed");
         assert_eq!(unstaged.len(), 1);
         assert!(matches!(
-            get_staged_diff(&repo, &highlighter),
+            get_staged_diff(&repo, &DiffSettings::default(), &highlighter),
             Err(TuicrError::NoChanges)
         ));
 
@@ -438,11 +757,258 @@ mod tests {
             .expect("failed to add file to index");
         index.write().expect("failed to write index");
 
-        let staged = get_staged_diff(&repo, &highlighter).expect("staged diff failed");
+        let staged = get_staged_diff(&repo, &DiffSettings::default(), &highlighter)
+            .expect("staged diff failed");
         assert_eq!(staged.len(), 1);
         assert!(matches!(
-            get_unstaged_diff(&repo, &highlighter),
+            get_unstaged_diff(&repo, &DiffSettings::default(), &highlighter),
             Err(TuicrError::NoChanges)
         ));
     }
+
+    #[test]
+    fn should_leave_out_untracked_files_when_hidden() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("failed to init repo");
+
+        create_initial_commit(&repo, "file.txt", "base\n");
+        fs::write(temp_dir.path().join("file.txt"), "changed\n").expect("failed to update file");
+        fs::write(temp_dir.path().join("scratch.log"), "noise\n").expect("failed to write file");
+
+        let highlighter = SyntaxHighlighter::default();
+        let shown = get_working_tree_diff(&repo, &DiffSettings::default(), &highlighter)
+            .expect("working tree diff failed");
+        assert_eq!(shown.len(), 2);
+
+        let settings = DiffSettings {
+            hide_untracked: true,
+            ..DiffSettings::default()
+        };
+        let hidden = get_working_tree_diff(&repo, &settings, &highlighter)
+            .expect("working tree diff failed");
+        assert_eq!(hidden.len(), 1);
+        assert_eq!(hidden[0].display_path(), &PathBuf::from("file.txt"));
+        let unstaged =
+            get_unstaged_diff(&repo, &settings, &highlighter).expect("unstaged diff failed");
+        assert_eq!(unstaged.len(), 1);
+    }
+
+    #[test]
+    fn should_record_mode_changes_and_symlink_targets() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("failed to init repo");
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let commit_tree = |entries: &[(&str, &str, i32)], parents: &[&git2::Commit]| {
+            let mut builder = repo.treebuilder(None).unwrap();
+            for (name, content, mode) in entries {
+                let blob = repo.blob(content.as_bytes()).unwrap();
+                builder.insert(name, blob, *mode).unwrap();
+            }
+            let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+            repo.commit(None, &sig, &sig, "commit", &tree, parents)
+                .unwrap()
+        };
+        let base = commit_tree(
+            &[("link", "a.txt", 0o120000), ("run.sh", "echo\n", 0o100644)],
+            &[],
+        );
+        let base = repo.find_commit(base).unwrap();
+        let tip = commit_tree(
+            &[("link", "b.txt", 0o120000), ("run.sh", "echo\n", 0o100755)],
+            &[&base],
+        );
+
+        let files = get_commit_range_diff(
+            &repo,
+            &[tip.to_string()],
+            RangeMode::TwoDot,
+            &DiffSettings::default(),
+            &SyntaxHighlighter::default(),
+        )
+        .expect("commit diff failed");
+
+        assert_eq!(files.len(), 2);
+        let file = |name: &str| {
+            files
+                .iter()
+                .find(|f| f.display_path() == Path::new(name))
+                .unwrap()
+        };
+        assert_eq!(file("link").metadata_lines(), vec!["symlink a.txt → b.txt"]);
+        assert!(file("run.sh").hunks.is_empty());
+        assert_eq!(
+            file("run.sh").metadata_lines(),
+            vec!["mode 100644 → 100755"]
+        );
+    }
+
+    /// Commit `files` on top of `parent` without moving any ref.
+    fn commit_files(
+        repo: &Repository,
+        parent: Option<git2::Oid>,
+        files: &[(&str, &str)],
+    ) -> String {
+        let parent = parent.map(|id| repo.find_commit(id).unwrap());
+        let parent_tree = parent.as_ref().map(|c| c.tree().unwrap());
+        let mut builder = repo.treebuilder(parent_tree.as_ref()).unwrap();
+        for (name, content) in files {
+            let blob = repo.blob(content.as_bytes()).unwrap();
+            builder.insert(name, blob, 0o100644).unwrap();
+        }
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(None, &sig, &sig, "commit", &tree, &parents)
+            .unwrap()
+            .to_string()
+    }
+
+    fn changed_lines(file: &DiffFile) -> Vec<String> {
+        file.hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .filter_map(|l| match l.origin {
+                LineOrigin::Addition => Some(format!("+{}", l.content)),
+                LineOrigin::Deletion => Some(format!("-{}", l.content)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_interdiff_rebased_series_without_upstream_changes() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("failed to init repo");
+        let oid = |id: &str| git2::Oid::from_str(id).unwrap();
+
+        let base = commit_files(&repo, None, &[("a.txt", "1\n2\n3\n"), ("b.txt", "b\n")]);
+        let v1 = commit_files(&repo, Some(oid(&base)), &[("a.txt", "one\n2\n3\n")]);
+        // Upstream moves on, touching the reviewed file and another one.
+        let upstream = commit_files(
+            &repo,
+            Some(oid(&base)),
+            &[("a.txt", "1\n2\nthree\n"), ("b.txt", "bee\n")],
+        );
+        // v2 is v1 rebased onto upstream, plus a fix on line 2.
+        let v2 = commit_files(
+            &repo,
+            Some(oid(&upstream)),
+            &[("a.txt", "one\ntwo\nthree\n")],
+        );
+
+        let files = get_interdiff(
+            &repo,
+            &format!("{base}..{v1}"),
+            &format!("{upstream}..{v2}"),
+            &DiffSettings::default(),
+            &SyntaxHighlighter::default(),
+        )
+        .expect("interdiff failed");
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].display_path(), &PathBuf::from("a.txt"));
+        assert_eq!(changed_lines(&files[0]), vec!["-2", "+two"]);
+    }
+
+    #[test]
+    fn should_fall_back_to_touched_files_when_old_version_conflicts() {
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("failed to init repo");
+        let oid = |id: &str| git2::Oid::from_str(id).unwrap();
+
+        let base = commit_files(&repo, None, &[("a.txt", "1\n"), ("b.txt", "b\n")]);
+        let v1 = commit_files(&repo, Some(oid(&base)), &[("a.txt", "one\n")]);
+        let upstream = commit_files(
+            &repo,
+            Some(oid(&base)),
+            &[("a.txt", "uno\n"), ("b.txt", "bee\n")],
+        );
+        let v2 = commit_files(&repo, Some(oid(&upstream)), &[("a.txt", "uno one\n")]);
+
+        let files = get_interdiff(
+            &repo,
+            &format!("{base}..{v1}"),
+            &format!("{upstream}..{v2}"),
+            &DiffSettings::default(),
+            &SyntaxHighlighter::default(),
+        )
+        .expect("interdiff failed");
+
+        // b.txt only changed upstream, so it stays out of the review.
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].display_path(), &PathBuf::from("a.txt"));
+        assert_eq!(changed_lines(&files[0]), vec!["-one", "+uno one"]);
+    }
+
+    #[test]
+    fn should_reuse_cached_renames_for_the_same_changes() {
+        // given
+        let temp_dir = tempfile::tempdir().expect("failed to create temp dir");
+        let workdir = temp_dir.path();
+        let repo = Repository::init(workdir).expect("failed to init repo");
+        let body = "one\ntwo\nthree\nfour\nfive\n";
+        fs::create_dir_all(workdir.join("lib")).unwrap();
+        fs::create_dir_all(workdir.join("src")).unwrap();
+        fs::write(workdir.join("a.txt"), "a\n").unwrap();
+        fs::write(workdir.join("m.txt"), "m\n").unwrap();
+        fs::write(workdir.join("lib/old.rs"), body).unwrap();
+        let mut index = repo.index().unwrap();
+        for path in ["a.txt", "m.txt", "lib/old.rs"] {
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+        fs::write(workdir.join("a.txt"), "a changed\n").unwrap();
+        fs::remove_file(workdir.join("m.txt")).unwrap();
+        fs::remove_file(workdir.join("lib/old.rs")).unwrap();
+        fs::write(workdir.join("src/new.rs"), format!("{body}six\n")).unwrap();
+        fs::write(workdir.join("c.txt"), "c\n").unwrap();
+        index.remove_path(Path::new("lib/old.rs")).unwrap();
+        index.add_path(Path::new("src/new.rs")).unwrap();
+        index.add_path(Path::new("c.txt")).unwrap();
+        index.write().unwrap();
+        let settings = DiffSettings::default();
+        let highlighter = SyntaxHighlighter::default();
+        let summary = |files: &[DiffFile]| -> Vec<_> {
+            files
+                .iter()
+                .map(|f| {
+                    (
+                        f.old_path.clone(),
+                        f.new_path.clone(),
+                        f.status,
+                        changed_lines(f),
+                    )
+                })
+                .collect()
+        };
+
+        // when
+        let detected = get_working_tree_diff(&repo, &settings, &highlighter).unwrap();
+        let cached = get_working_tree_diff(&repo, &settings, &highlighter).unwrap();
+
+        // then
+        let pairs = vec![(PathBuf::from("lib/old.rs"), PathBuf::from("src/new.rs"))];
+        assert_eq!(rename_cache::pairs(&detected), pairs);
+        assert!(rename_cache::is_cached(&pairs));
+        assert_eq!(summary(&cached), summary(&detected));
+
+        // Detecting over the paired paths alone lands on the same diff.
+        let head = repo.head().unwrap().peel_to_tree().unwrap();
+        let diff_for = |paths: Option<&[PathBuf]>| {
+            let mut opts = workdir_diff_options(&settings);
+            limit_to_paths(&mut opts, paths);
+            repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut opts))
+                .unwrap()
+        };
+        let full = parse_diff(&diff_for(None), &highlighter).unwrap();
+        let mut paired = diff_for(Some(&rename_cache::paired_paths(&pairs)));
+        find_renames(&mut paired, &settings.renames).unwrap();
+        let paired = parse_diff(&paired, &highlighter).unwrap();
+        let spliced = rename_cache::splice(full, paired, &pairs).expect("renames paired up again");
+        assert_eq!(summary(&spliced), summary(&detected));
+    }
 }
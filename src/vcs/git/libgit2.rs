@@ -2,26 +2,34 @@ use git2::Repository;
 use std::path::Path;
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffLine, FileStatus};
+use crate::model::{DiffFile, DiffLine, FileStatus, Reviewer};
 use crate::syntax::SyntaxHighlighter;
 
 use super::{context, diff, repository, staging};
-use crate::vcs::traits::{CommitInfo, VcsBackend, VcsInfo, VcsType};
+use crate::vcs::traits::{
+    CommitInfo, IgnoreWhitespace, RangeMode, RenameDetection, VcsBackend, VcsInfo, VcsType,
+};
 
 /// Git backend implementation using the git2/libgit2 library.
 pub struct Libgit2Backend {
     repo: Repository,
     info: VcsInfo,
+    settings: diff::DiffSettings,
 }
 
 impl Libgit2Backend {
     pub(super) fn discover_from(cwd: &Path) -> Result<Self> {
         let repo = Repository::discover(cwd).map_err(|_| TuicrError::NotARepository)?;
 
+        // For a linked worktree `workdir()` is the worktree checkout (with its
+        // own HEAD and index), not the main repository, so sessions keyed by
+        // `root_path` stay separate per worktree. Collecting the components
+        // drops libgit2's trailing slash to match `git rev-parse --show-toplevel`.
         let root_path = repo
             .workdir()
             .ok_or(TuicrError::NotARepository)?
-            .to_path_buf();
+            .components()
+            .collect();
 
         let head_commit = repo
             .head()
@@ -45,7 +53,11 @@ impl Libgit2Backend {
             vcs_type: VcsType::Git,
         };
 
-        Ok(Self { repo, info })
+        Ok(Self {
+            repo,
+            info,
+            settings: diff::DiffSettings::default(),
+        })
     }
 }
 
@@ -59,15 +71,15 @@ impl VcsBackend for Libgit2Backend {
     }
 
     fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-        diff::get_working_tree_diff(&self.repo, highlighter)
+        diff::get_working_tree_diff(&self.repo, &self.settings, highlighter)
     }
 
     fn get_staged_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-        diff::get_staged_diff(&self.repo, highlighter)
+        diff::get_staged_diff(&self.repo, &self.settings, highlighter)
     }
 
     fn get_unstaged_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-        diff::get_unstaged_diff(&self.repo, highlighter)
+        diff::get_unstaged_diff(&self.repo, &self.settings, highlighter)
     }
 
     fn fetch_context_lines(
@@ -80,6 +92,34 @@ impl VcsBackend for Libgit2Backend {
         context::fetch_context_lines(&self.repo, file_path, file_status, start_line, end_line)
     }
 
+    fn fetch_deleted_file(&self, file_path: &Path) -> Result<String> {
+        context::fetch_blob_content(&self.repo, file_path)
+    }
+
+    fn set_rename_detection(&mut self, detection: RenameDetection) {
+        self.settings.renames = detection;
+    }
+
+    fn rename_detection(&self) -> Option<RenameDetection> {
+        Some(self.settings.renames)
+    }
+
+    fn set_ignore_whitespace(&mut self, whitespace: IgnoreWhitespace) {
+        self.settings.whitespace = whitespace;
+    }
+
+    fn ignore_whitespace(&self) -> Option<IgnoreWhitespace> {
+        Some(self.settings.whitespace)
+    }
+
+    fn set_include_untracked(&mut self, include: bool) {
+        self.settings.hide_untracked = !include;
+    }
+
+    fn include_untracked(&self) -> Option<bool> {
+        Some(!self.settings.hide_untracked)
+    }
+
     fn get_recent_commits(&self, offset: usize, limit: usize) -> Result<Vec<CommitInfo>> {
         let git_commits = repository::get_recent_commits(&self.repo, offset, limit)?;
         Ok(git_commits
@@ -103,9 +143,25 @@ impl VcsBackend for Libgit2Backend {
     fn get_commit_range_diff(
         &self,
         commit_ids: &[String],
+        range_mode: RangeMode,
+        highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        diff::get_commit_range_diff(
+            &self.repo,
+            commit_ids,
+            range_mode,
+            &self.settings,
+            highlighter,
+        )
+    }
+
+    fn get_interdiff(
+        &self,
+        old: &str,
+        new: &str,
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
-        diff::get_commit_range_diff(&self.repo, commit_ids, highlighter)
+        diff::get_interdiff(&self.repo, old, new, &self.settings, highlighter)
     }
 
     fn get_commits_info(&self, ids: &[String]) -> Result<Vec<CommitInfo>> {
@@ -124,12 +180,33 @@ impl VcsBackend for Libgit2Backend {
             .collect())
     }
 
+    fn parent_commit(&self, id: &str) -> Result<Option<String>> {
+        repository::parent_commit(&self.repo, id)
+    }
+
+    fn user_identity(&self) -> Option<Reviewer> {
+        let config = self.repo.config().ok()?;
+        Some(Reviewer {
+            name: config.get_string("user.name").ok()?,
+            email: config.get_string("user.email").ok(),
+        })
+    }
+
+    fn get_commit_line_stats(&self, ids: &[String]) -> Result<Vec<(usize, usize)>> {
+        repository::get_commit_line_stats(&self.repo, ids)
+    }
+
     fn get_working_tree_with_commits_diff(
         &self,
         commit_ids: &[String],
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
-        diff::get_working_tree_with_commits_diff(&self.repo, commit_ids, highlighter)
+        diff::get_working_tree_with_commits_diff(
+            &self.repo,
+            commit_ids,
+            &self.settings,
+            highlighter,
+        )
     }
 
     fn stage_file(&self, path: &Path) -> Result<()> {
@@ -2,6 +2,7 @@ mod cli;
 pub mod context;
 pub mod diff;
 mod libgit2;
+pub mod rename_cache;
 pub mod repository;
 pub mod staging;
 
@@ -9,11 +10,13 @@ use std::ffi::OsStr;
 use std::path::Path;
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffLine, FileStatus};
+use crate::model::{DiffFile, DiffLine, FileStatus, Reviewer};
 use crate::process::{CommandOutputError, CommandOutputErrorKind, run_command_output};
 use crate::syntax::SyntaxHighlighter;
 
-use super::traits::{CommitInfo, VcsBackend, VcsChangeStatus, VcsInfo};
+use super::traits::{
+    CommitInfo, IgnoreWhitespace, RangeMode, RenameDetection, VcsBackend, VcsChangeStatus, VcsInfo,
+};
 use cli::GitCliBackend;
 pub use libgit2::Libgit2Backend;
 
@@ -106,7 +109,7 @@ impl GitBackend {
         Self::discover_from(&cwd, preference)
     }
 
-    fn discover_from(cwd: &Path, preference: GitBackendPreference) -> Result<Self> {
+    pub(crate) fn discover_from(cwd: &Path, preference: GitBackendPreference) -> Result<Self> {
         if preference == GitBackendPreference::Cli {
             return Ok(Self::Cli(GitCliBackend::discover_from(cwd)?));
         }
@@ -132,7 +135,9 @@ fn run_git_command(workdir: &Path, args: &[&str]) -> Result<String> {
 
 pub(super) fn git_command_error(error: CommandOutputError) -> TuicrError {
     match error.kind {
-        CommandOutputErrorKind::Unsuccessful => TuicrError::VcsCommand(error.stderr),
+        CommandOutputErrorKind::Unsuccessful
+        | CommandOutputErrorKind::TimedOut
+        | CommandOutputErrorKind::Cancelled => TuicrError::VcsCommand(error.stderr),
         CommandOutputErrorKind::NotFound | CommandOutputErrorKind::SpawnFailed => {
             TuicrError::VcsCommand(format!("Failed to run git: {}", error.stderr))
         }
@@ -216,6 +221,41 @@ impl VcsBackend for GitBackend {
         }
     }
 
+    fn fetch_deleted_file(&self, file_path: &Path) -> Result<String> {
+        match self {
+            Self::Libgit2(backend) => backend.fetch_deleted_file(file_path),
+            Self::Cli(backend) => backend.fetch_deleted_file(file_path),
+        }
+    }
+
+    fn set_rename_detection(&mut self, detection: RenameDetection) {
+        match self {
+            Self::Libgit2(backend) => backend.set_rename_detection(detection),
+            Self::Cli(backend) => backend.set_rename_detection(detection),
+        }
+    }
+
+    fn rename_detection(&self) -> Option<RenameDetection> {
+        match self {
+            Self::Libgit2(backend) => backend.rename_detection(),
+            Self::Cli(backend) => backend.rename_detection(),
+        }
+    }
+
+    fn set_ignore_whitespace(&mut self, whitespace: IgnoreWhitespace) {
+        match self {
+            Self::Libgit2(backend) => backend.set_ignore_whitespace(whitespace),
+            Self::Cli(backend) => backend.set_ignore_whitespace(whitespace),
+        }
+    }
+
+    fn ignore_whitespace(&self) -> Option<IgnoreWhitespace> {
+        match self {
+            Self::Libgit2(backend) => backend.ignore_whitespace(),
+            Self::Cli(backend) => backend.ignore_whitespace(),
+        }
+    }
+
     fn get_recent_commits(&self, offset: usize, limit: usize) -> Result<Vec<CommitInfo>> {
         match self {
             Self::Libgit2(backend) => backend.get_recent_commits(offset, limit),
@@ -233,11 +273,16 @@ impl VcsBackend for GitBackend {
     fn get_commit_range_diff(
         &self,
         commit_ids: &[String],
+        range_mode: RangeMode,
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
         match self {
-            Self::Libgit2(backend) => backend.get_commit_range_diff(commit_ids, highlighter),
-            Self::Cli(backend) => backend.get_commit_range_diff(commit_ids, highlighter),
+            Self::Libgit2(backend) => {
+                backend.get_commit_range_diff(commit_ids, range_mode, highlighter)
+            }
+            Self::Cli(backend) => {
+                backend.get_commit_range_diff(commit_ids, range_mode, highlighter)
+            }
         }
     }
 
@@ -248,6 +293,27 @@ impl VcsBackend for GitBackend {
         }
     }
 
+    fn parent_commit(&self, id: &str) -> Result<Option<String>> {
+        match self {
+            Self::Libgit2(backend) => backend.parent_commit(id),
+            Self::Cli(backend) => backend.parent_commit(id),
+        }
+    }
+
+    fn user_identity(&self) -> Option<Reviewer> {
+        match self {
+            Self::Libgit2(backend) => backend.user_identity(),
+            Self::Cli(backend) => backend.user_identity(),
+        }
+    }
+
+    fn get_commit_line_stats(&self, ids: &[String]) -> Result<Vec<(usize, usize)>> {
+        match self {
+            Self::Libgit2(backend) => backend.get_commit_line_stats(ids),
+            Self::Cli(backend) => backend.get_commit_line_stats(ids),
+        }
+    }
+
     fn get_working_tree_with_commits_diff(
         &self,
         commit_ids: &[String],
@@ -349,6 +415,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn discovers_linked_worktree_as_its_own_root() {
+        let temp_dir = tempdir().expect("failed to create temp dir");
+        let root = temp_dir.path().join("main");
+        fs::create_dir(&root).expect("failed to create main checkout");
+        setup_standard_repo(&root);
+        let worktree = temp_dir.path().join("feature");
+        run_git_command(
+            &root,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "feature",
+                worktree.to_str().unwrap(),
+            ],
+        )
+        .expect("failed to add worktree");
+        fs::write(worktree.join("src/file.txt"), "two\n").expect("failed to write file");
+
+        for preference in [GitBackendPreference::Libgit2, GitBackendPreference::Cli] {
+            let backend = GitBackend::discover_from(&worktree.join("src"), preference)
+                .expect("failed to discover worktree");
+            let info = backend.info();
+            assert_eq!(
+                fs::canonicalize(&info.root_path).unwrap(),
+                fs::canonicalize(&worktree).unwrap()
+            );
+            assert!(!info.root_path.to_string_lossy().ends_with('/'));
+            assert_eq!(info.branch_name.as_deref(), Some("feature"));
+            assert_eq!(
+                backend
+                    .get_unstaged_diff(&SyntaxHighlighter::default())
+                    .expect("worktree changes should be visible")
+                    .len(),
+                1
+            );
+
+            // The main checkout shares the object store but not the changes.
+            let main = GitBackend::discover_from(&root, preference)
+                .expect("failed to discover main checkout");
+            assert!(matches!(
+                main.get_unstaged_diff(&SyntaxHighlighter::default()),
+                Err(TuicrError::NoChanges)
+            ));
+            assert_eq!(main.info().head_commit, info.head_commit);
+        }
+    }
+
     fn setup_standard_repo(root: &Path) {
         fs::create_dir(root.join("src")).expect("failed to create src dir");
         fs::write(root.join("src/file.txt"), "one\n").expect("failed to write file");
@@ -0,0 +1,204 @@
+//! Rename detection results, remembered across runs.
+//!
+//! Pairing deleted and added files compares every candidate with every
+//! other, which dominates startup in huge repos with many moves. The pairs
+//! found are cached by the blobs on both sides of each change plus the
+//! rename settings, so the next diff of the same changes only runs detection
+//! over the paths that actually paired.
+//!
+//! The cache lives in memory until `install` gives it a file, which `main`
+//! does with one next to the saved reviews.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TuicrError};
+use crate::hash::Fnv1aHasher;
+use crate::model::{DiffFile, FileStatus};
+use crate::vcs::RenameDetection;
+
+/// Diffs remembered; the oldest is dropped first.
+const MAX_ENTRIES: usize = 64;
+
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+static CACHE: Mutex<Option<RenameCache>> = Mutex::new(None);
+
+/// `(old path, new path)` of each renamed or copied file
+pub(super) type RenamePairs = Vec<(PathBuf, PathBuf)>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RenameCache {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    key: u64,
+    pairs: RenamePairs,
+}
+
+/// `rename-cache.json` next to the saved reviews in the data directory.
+pub fn rename_cache_path() -> Result<PathBuf> {
+    let proj_dirs = ProjectDirs::from("", "", "tuicr").ok_or_else(|| {
+        TuicrError::Io(std::io::Error::other("Could not determine data directory"))
+    })?;
+    Ok(proj_dirs.data_dir().join("rename-cache.json"))
+}
+
+/// Keep the cache in `path` from now on. Only the first call has effect.
+pub fn install(path: PathBuf) {
+    let _ = PATH.set(path);
+}
+
+/// One tracked change as rename detection sees it, with the blob ids of
+/// both sides (zero for a missing side).
+pub(super) struct DeltaKey<'a> {
+    pub status: char,
+    pub old_path: &'a [u8],
+    pub old_id: String,
+    pub new_path: &'a [u8],
+    pub new_id: String,
+}
+
+/// Cache key for detecting renames per `renames` among `deltas`
+pub(super) fn key<'a>(
+    renames: &RenameDetection,
+    deltas: impl IntoIterator<Item = DeltaKey<'a>>,
+) -> u64 {
+    let mut hasher = Fnv1aHasher::new();
+    hasher.write(&[u8::from(renames.copies), u8::from(renames.break_rewrites)]);
+    hasher.write(&renames.threshold.to_le_bytes());
+    hasher.write(&(renames.limit as u64).to_le_bytes());
+    for delta in deltas {
+        let mut buf = [0; 4];
+        hasher.write(delta.status.encode_utf8(&mut buf).as_bytes());
+        for field in [
+            delta.old_path,
+            delta.old_id.as_bytes(),
+            delta.new_path,
+            delta.new_id.as_bytes(),
+        ] {
+            hasher.write(field);
+            hasher.write(b"\0");
+        }
+    }
+    hasher.finish()
+}
+
+/// The pairs found last time for `key`
+pub(super) fn lookup(key: u64) -> Option<RenamePairs> {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache.get_or_insert_with(load);
+    cache
+        .entries
+        .iter()
+        .find(|entry| entry.key == key)
+        .map(|entry| entry.pairs.clone())
+}
+
+/// Remember `pairs` for `key`. Failing to write the file only costs the
+/// next run a full detection, so it is not reported.
+pub(super) fn store(key: u64, pairs: RenamePairs) {
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache.get_or_insert_with(load);
+    cache.entries.retain(|entry| entry.key != key);
+    cache.entries.push(Entry { key, pairs });
+    let excess = cache.entries.len().saturating_sub(MAX_ENTRIES);
+    cache.entries.drain(..excess);
+    if let Some(path) = PATH.get() {
+        let _ = save(cache, path);
+    }
+}
+
+fn load() -> RenameCache {
+    PATH.get()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save(cache: &RenameCache, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(cache)?)?;
+    Ok(())
+}
+
+/// The renames and copies among `files`
+pub(super) fn pairs(files: &[DiffFile]) -> RenamePairs {
+    files
+        .iter()
+        .filter(|file| matches!(file.status, FileStatus::Renamed | FileStatus::Copied))
+        .filter_map(|file| Some((file.old_path.clone()?, file.new_path.clone()?)))
+        .collect()
+}
+
+/// Every path on either side of `pairs`, to limit a diff to
+pub(super) fn paired_paths(pairs: &RenamePairs) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = pairs
+        .iter()
+        .flat_map(|(old, new)| [old.clone(), new.clone()])
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Put `paired`, a diff limited to the paths of `pairs` with renames
+/// detected, in place of those paths in `files`, the full diff without
+/// detection. Each entry takes the place of the file at its display path,
+/// which is where detection over the full diff would have left it.
+///
+/// `None` when `paired` did not pair up exactly as `pairs` says, in which
+/// case the full diff needs detecting after all.
+pub(super) fn splice(
+    files: Vec<DiffFile>,
+    paired: Vec<DiffFile>,
+    expected: &RenamePairs,
+) -> Option<Vec<DiffFile>> {
+    let mut found = pairs(&paired);
+    let mut expected = expected.clone();
+    found.sort();
+    expected.sort();
+    if found != expected {
+        return None;
+    }
+
+    let paths = paired_paths(&expected);
+    let mut paired: Vec<Option<DiffFile>> = paired.into_iter().map(Some).collect();
+    let mut spliced = Vec::with_capacity(files.len());
+    for file in files {
+        if paths.binary_search(file.display_path()).is_err() {
+            spliced.push(file);
+            continue;
+        }
+        spliced.extend(
+            paired
+                .iter_mut()
+                .filter(|entry| {
+                    entry
+                        .as_ref()
+                        .is_some_and(|entry| entry.display_path() == file.display_path())
+                })
+                .filter_map(Option::take),
+        );
+    }
+    // Anything left had no counterpart in the full diff.
+    if paired.iter().any(Option::is_some) {
+        return None;
+    }
+    Some(spliced)
+}
+
+#[cfg(test)]
+pub(super) fn is_cached(pairs: &RenamePairs) -> bool {
+    let cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .as_ref()
+        .is_some_and(|cache| cache.entries.iter().any(|entry| &entry.pairs == pairs))
+}
@@ -103,6 +103,16 @@ pub fn get_recent_commits(
 
 /// Get commit info for specific commit IDs.
 /// Returns CommitInfo in the same order as the input IDs.
+/// First parent of commit `id`; `None` for a root commit.
+pub fn parent_commit(repo: &Repository, id: &str) -> Result<Option<String>> {
+    let oid = Oid::from_str(id)
+        .map_err(|e| TuicrError::VcsCommand(format!("Invalid commit ID {}: {}", id, e)))?;
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|e| TuicrError::VcsCommand(format!("Commit not found {}: {}", id, e)))?;
+    Ok(commit.parent_id(0).ok().map(|parent| parent.to_string()))
+}
+
 pub fn get_commits_info(repo: &Repository, ids: &[String]) -> Result<Vec<CommitInfo>> {
     let branch_tip_names = get_branch_tip_names(repo);
     let mut commits = Vec::new();
@@ -141,6 +151,28 @@ pub fn get_commits_info(repo: &Repository, ids: &[String]) -> Result<Vec<CommitI
     Ok(commits)
 }
 
+/// Lines added and removed by each commit against its first parent (the
+/// empty tree for a root commit), in the same order as the input IDs.
+pub fn get_commit_line_stats(repo: &Repository, ids: &[String]) -> Result<Vec<(usize, usize)>> {
+    ids.iter()
+        .map(|id_str| {
+            let oid = Oid::from_str(id_str).map_err(|e| {
+                TuicrError::VcsCommand(format!("Invalid commit ID {}: {}", id_str, e))
+            })?;
+            let commit = repo.find_commit(oid).map_err(|e| {
+                TuicrError::VcsCommand(format!("Commit not found {}: {}", id_str, e))
+            })?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+            let stats = diff.stats()?;
+            Ok((stats.insertions(), stats.deletions()))
+        })
+        .collect()
+}
+
 /// Resolve a git revision range expression to a list of commit IDs (oldest first).
 ///
 /// Supports both single revisions ("HEAD~3") and ranges ("main..feature").
@@ -161,7 +193,11 @@ pub fn resolve_revisions(repo: &Repository, revisions: &str) -> Result<Vec<Strin
 
         let mut revwalk = repo.revwalk()?;
         revwalk.push(to.id())?;
-        revwalk.hide(from.id())?;
+        // The empty tree as `from` (as `git rev-list` takes it) hides nothing,
+        // so the range reaches back to the root commit
+        if from.kind() != Some(git2::ObjectType::Tree) {
+            revwalk.hide(from.id())?;
+        }
         revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
 
         let mut ids = Vec::new();
@@ -0,0 +1,287 @@
+//! Client for Mercurial's command server (`hg serve --cmdserver pipe`).
+//!
+//! Every `hg` invocation pays for a Python interpreter start, which adds up
+//! when one diff load runs `diff`, `log`, `status` and several `cat`s. The
+//! command server keeps one `hg` process per repository and runs commands
+//! over its stdin/stdout instead. The protocol is framed: the server writes
+//! `channel (1 byte) + length (u32 BE) + data`, where `o`/`e` carry output
+//! and error text, `r` the 4-byte exit code, and the upper-case `I`/`L`
+//! channels ask for input (answered with an empty block, i.e. EOF).
+//!
+//! See <https://wiki.mercurial-scm.org/CommandServer>.
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::time::Duration;
+
+use crate::process::{
+    CommandOutputError, CommandOutputErrorKind, CommandOutputResult, TrackedCommand,
+};
+
+/// How often a waiting command checks for timeout and cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Frame {
+    channel: u8,
+    /// Payload for the lower-case channels; empty for input requests
+    data: Vec<u8>,
+}
+
+/// A running `hg serve --cmdserver pipe` for one repository. Frames are read
+/// on a background thread so a command that hangs can still be timed out or
+/// cancelled; after that the server is killed and must be restarted.
+pub struct CommandServer {
+    child: Child,
+    stdin: ChildStdin,
+    frames: Receiver<io::Result<Frame>>,
+}
+
+impl CommandServer {
+    /// Spawn the server in `root` and check its hello message. Fails if `hg`
+    /// is missing or too old to offer `runcommand`.
+    pub fn start(root: &Path) -> io::Result<Self> {
+        let mut child = Command::new("hg")
+            .current_dir(root)
+            .args([
+                "serve",
+                "--cmdserver",
+                "pipe",
+                "--config",
+                "ui.interactive=False",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(mut stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(io::Error::other("hg command server pipes unavailable"));
+        };
+        let (tx, frames) = channel();
+        std::thread::spawn(move || {
+            loop {
+                let frame = read_frame(&mut stdout);
+                let failed = frame.is_err();
+                if tx.send(frame).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        let mut server = Self {
+            child,
+            stdin,
+            frames,
+        };
+        let tracked = TrackedCommand::start("hg", &["serve"]);
+        let hello = server
+            .next_frame(&tracked)
+            .map_err(|e| io::Error::other(e.stderr))?;
+        if !supports_runcommand(&hello) {
+            return Err(io::Error::other("hg command server lacks runcommand"));
+        }
+        Ok(server)
+    }
+
+    /// Run `hg args` on the server and return its stdout. A non-zero exit is
+    /// reported like a failed CLI run; any other error means the server is
+    /// unusable and should be dropped.
+    pub fn run(&mut self, args: &[&str]) -> CommandOutputResult<Vec<u8>> {
+        let tracked = TrackedCommand::start("hg", args);
+        write_runcommand(&mut self.stdin, args).map_err(protocol_error)?;
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        loop {
+            let frame = self.next_frame(&tracked)?;
+            match frame.channel {
+                b'o' => stdout.extend_from_slice(&frame.data),
+                b'e' => stderr.extend_from_slice(&frame.data),
+                b'r' => {
+                    let code = result_code(&frame.data).map_err(protocol_error)?;
+                    if code == 0 {
+                        return Ok(stdout);
+                    }
+                    return Err(CommandOutputError {
+                        kind: CommandOutputErrorKind::Unsuccessful,
+                        status: Some(code),
+                        stderr: String::from_utf8_lossy(&stderr).trim().to_string(),
+                    });
+                }
+                b'I' | b'L' => self
+                    .stdin
+                    .write_all(&0u32.to_be_bytes())
+                    .and_then(|()| self.stdin.flush())
+                    .map_err(protocol_error)?,
+                channel if channel.is_ascii_uppercase() => {
+                    return Err(protocol_error(io::Error::other(format!(
+                        "unsupported required channel '{}'",
+                        channel as char
+                    ))));
+                }
+                // Unknown optional channels may be ignored
+                _ => {}
+            }
+        }
+    }
+
+    /// Wait for the next frame, giving up (and killing the server) once
+    /// `tracked` times out or is cancelled.
+    fn next_frame(&mut self, tracked: &TrackedCommand) -> CommandOutputResult<Frame> {
+        loop {
+            match self.frames.recv_timeout(POLL_INTERVAL) {
+                Ok(frame) => return frame.map_err(protocol_error),
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Err(err) = tracked.check() {
+                        let _ = self.child.kill();
+                        return Err(err);
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(protocol_error(io::Error::other("hg command server exited")));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for CommandServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn protocol_error(err: io::Error) -> CommandOutputError {
+    CommandOutputError {
+        kind: CommandOutputErrorKind::SpawnFailed,
+        status: None,
+        stderr: format!("hg command server: {err}"),
+    }
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<Frame> {
+    let mut header = [0u8; 5];
+    reader.read_exact(&mut header)?;
+    let channel = header[0];
+    let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+    // Input requests carry the wanted size in the length field, no payload
+    if channel.is_ascii_uppercase() {
+        return Ok(Frame {
+            channel,
+            data: Vec::new(),
+        });
+    }
+    let mut data = vec![0; length as usize];
+    reader.read_exact(&mut data)?;
+    Ok(Frame { channel, data })
+}
+
+fn write_runcommand<W: Write>(writer: &mut W, args: &[&str]) -> io::Result<()> {
+    let payload = args.join("\0");
+    let length =
+        u32::try_from(payload.len()).map_err(|_| io::Error::other("hg command line too long"))?;
+    writer.write_all(b"runcommand\n")?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(payload.as_bytes())?;
+    writer.flush()
+}
+
+fn result_code(data: &[u8]) -> io::Result<i32> {
+    let bytes: [u8; 4] = data
+        .try_into()
+        .map_err(|_| io::Error::other("malformed result frame"))?;
+    Ok(i32::from_be_bytes(bytes))
+}
+
+/// The hello message is `key: value` lines on the output channel;
+/// `capabilities` lists the commands the server accepts.
+fn supports_runcommand(hello: &Frame) -> bool {
+    hello.channel == b'o'
+        && String::from_utf8_lossy(&hello.data)
+            .lines()
+            .filter_map(|line| line.strip_prefix("capabilities:"))
+            .any(|caps| caps.split_whitespace().any(|cap| cap == "runcommand"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_bytes(channel: u8, data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![channel];
+        bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn should_read_output_and_result_frames() {
+        // given
+        let mut stream = frame_bytes(b'o', b"diff --git a/x b/x\n");
+        stream.extend(frame_bytes(b'r', &1i32.to_be_bytes()));
+        let mut reader = stream.as_slice();
+
+        // when
+        let output = read_frame(&mut reader).unwrap();
+        let result = read_frame(&mut reader).unwrap();
+
+        // then
+        assert_eq!(output.channel, b'o');
+        assert_eq!(output.data, b"diff --git a/x b/x\n");
+        assert_eq!(result_code(&result.data).unwrap(), 1);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn should_not_read_payload_of_input_requests() {
+        // given
+        let mut stream = vec![b'L'];
+        stream.extend_from_slice(&4096u32.to_be_bytes());
+        stream.extend(frame_bytes(b'r', &0i32.to_be_bytes()));
+        let mut reader = stream.as_slice();
+
+        // when
+        let request = read_frame(&mut reader).unwrap();
+        let result = read_frame(&mut reader).unwrap();
+
+        // then
+        assert_eq!(request.channel, b'L');
+        assert!(request.data.is_empty());
+        assert_eq!(result.channel, b'r');
+    }
+
+    #[test]
+    fn should_encode_runcommand_with_nul_separated_args() {
+        // given
+        let mut written = Vec::new();
+
+        // when
+        write_runcommand(&mut written, &["cat", "-r", ".", "a b.txt"]).unwrap();
+
+        // then
+        let payload = b"cat\0-r\0.\0a b.txt";
+        let mut expected = b"runcommand\n".to_vec();
+        expected.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        expected.extend_from_slice(payload);
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn should_require_runcommand_capability_in_hello() {
+        // given
+        let hello = |data: &[u8]| Frame {
+            channel: b'o',
+            data: data.to_vec(),
+        };
+
+        // then
+        assert!(supports_runcommand(&hello(
+            b"capabilities: getencoding runcommand\nencoding: UTF-8\npid: 42"
+        )));
+        assert!(!supports_runcommand(&hello(
+            b"capabilities: getencoding\nencoding: UTF-8"
+        )));
+    }
+}
@@ -1,15 +1,23 @@
+mod cmdserver;
+
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 
 use chrono::{TimeZone, Utc};
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffLine, FileStatus, LineOrigin};
+use crate::model::{DiffFile, DiffLine, FileStatus, LineOrigin, Reviewer};
+use crate::process::{CommandOutputError, CommandOutputErrorKind};
 use crate::syntax::SyntaxHighlighter;
 use crate::vcs::diff_parser::{self, DiffFormat};
-use crate::vcs::traits::{CommitInfo, VcsBackend, VcsInfo, VcsType};
-use crate::vcs::{BATCH_BOUNDARY, apply_container_full_file_highlight, parse_batched_files};
+use crate::vcs::hg::cmdserver::CommandServer;
+use crate::vcs::traits::{CommitInfo, IgnoreWhitespace, RangeMode, VcsBackend, VcsInfo, VcsType};
+use crate::vcs::{
+    BATCH_BOUNDARY, apply_binary_sizes, apply_container_full_file_highlight,
+    build_untracked_diff_file, parse_batched_files,
+};
 
 /// Parse an hg description into (summary, optional body).
 fn parse_hg_description(desc: &str) -> (String, Option<String>) {
@@ -30,6 +38,21 @@ fn parse_hg_description(desc: &str) -> (String, Option<String>) {
 /// Mercurial backend implementation using hg CLI commands
 pub struct HgBackend {
     info: VcsInfo,
+    whitespace: IgnoreWhitespace,
+    /// List unknown (`?`) files in working tree diffs. Off by default, like
+    /// `hg diff`.
+    include_untracked: bool,
+    /// Command server that runs hg commands without paying for a Python
+    /// start each time; started on first use.
+    server: Mutex<ServerState>,
+}
+
+enum ServerState {
+    NotStarted,
+    Running(CommandServer),
+    /// The server failed to start or broke mid-command; every command
+    /// spawns `hg` directly from then on.
+    Unavailable,
 }
 
 impl HgBackend {
@@ -56,23 +79,150 @@ impl HgBackend {
         // Canonicalize to resolve symlinks (e.g., /var -> /private/var on macOS)
         let root_path = root_path.canonicalize().unwrap_or(root_path);
 
+        let mut backend = Self {
+            info: VcsInfo {
+                root_path,
+                head_commit: "unknown".to_string(),
+                branch_name: None,
+                vcs_type: VcsType::Mercurial,
+            },
+            whitespace: IgnoreWhitespace::default(),
+            include_untracked: false,
+            server: Mutex::new(ServerState::NotStarted),
+        };
+
         // Get current revision info
-        let head_commit = run_hg_command(&root_path, &["id", "-i"])
-            .map(|s| s.trim().trim_end_matches('+').to_string())
-            .unwrap_or_else(|_| "unknown".to_string());
-
-        let branch_name = run_hg_command(&root_path, &["branch"])
-            .ok()
-            .map(|s| s.trim().to_string());
-
-        let info = VcsInfo {
-            root_path,
-            head_commit,
-            branch_name,
-            vcs_type: VcsType::Mercurial,
+        if let Ok(id) = backend.hg(&["id", "-i"]) {
+            backend.info.head_commit = id.trim().trim_end_matches('+').to_string();
+        }
+        backend.info.branch_name = backend.hg(&["branch"]).ok().map(|s| s.trim().to_string());
+
+        Ok(backend)
+    }
+
+    /// Run an hg command and return its stdout, over the command server
+    /// when it is available.
+    fn hg(&self, args: &[&str]) -> Result<String> {
+        match self.run_on_server(args) {
+            Some(output) => output.map(|out| String::from_utf8_lossy(&out).into_owned()),
+            None => run_hg_command(&self.info.root_path, args),
+        }
+    }
+
+    /// Run `args` on the command server, starting it if needed. `None` when
+    /// there is no usable server and the caller should spawn `hg` instead.
+    fn run_on_server(&self, args: &[&str]) -> Option<Result<Vec<u8>>> {
+        let mut state = self.server.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*state, ServerState::NotStarted) {
+            *state = match CommandServer::start(&self.info.root_path) {
+                Ok(server) => ServerState::Running(server),
+                Err(_) => ServerState::Unavailable,
+            };
+        }
+        let ServerState::Running(server) = &mut *state else {
+            return None;
         };
+        match server.run(args) {
+            Ok(output) => Some(Ok(output)),
+            Err(e) if e.kind == CommandOutputErrorKind::Unsuccessful => {
+                Some(Err(hg_command_error(args, e)))
+            }
+            // The server was killed; a fresh one starts with the next command
+            Err(
+                e @ CommandOutputError {
+                    kind: CommandOutputErrorKind::TimedOut | CommandOutputErrorKind::Cancelled,
+                    ..
+                },
+            ) => {
+                *state = ServerState::NotStarted;
+                Some(Err(hg_command_error(args, e)))
+            }
+            Err(_) => {
+                *state = ServerState::Unavailable;
+                None
+            }
+        }
+    }
 
-        Ok(Self { info })
+    /// Fetch the full content of `paths` at `rev` in a single `hg cat`.
+    ///
+    /// Without the command server hg cat is dominated by Python startup
+    /// (~280 ms) regardless of file count, so batching every container file
+    /// into one call is significantly faster than fetching each one
+    /// separately.
+    fn cat_batch(&self, rev: &str, paths: &[PathBuf]) -> Result<HashMap<PathBuf, String>> {
+        if paths.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let template = format!("\n{BATCH_BOUNDARY}\n{{path}}\n{{data}}");
+        let path_strs: Vec<String> = paths
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        let mut args: Vec<&str> = vec!["cat", "-r", rev, "--template", &template];
+        args.extend(path_strs.iter().map(String::as_str));
+        let output = self.hg(&args)?;
+        Ok(parse_batched_files(&output))
+    }
+
+    /// Byte size of `path` at `rev`, read through `hg cat` so binary content
+    /// isn't mangled by a lossy UTF-8 conversion.
+    fn cat_size(&self, rev: &str, path: &Path) -> Option<u64> {
+        let path_str = path.to_string_lossy();
+        if let Some(output) = self.run_on_server(&["cat", "-r", rev, &path_str]) {
+            return output.ok().map(|out| out.len() as u64);
+        }
+        let output = Command::new("hg")
+            .current_dir(&self.info.root_path)
+            .args(["cat", "-r", rev])
+            .arg(path)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then_some(output.stdout.len() as u64)
+    }
+
+    /// Run `hg diff` with the configured whitespace flags ahead of `args`.
+    fn run_diff(&self, args: &[&str]) -> Result<String> {
+        let mut full_args = vec!["diff"];
+        full_args.extend(whitespace_args(&self.whitespace));
+        full_args.extend_from_slice(args);
+        self.hg(&full_args)
+    }
+
+    /// Parse a diff against the working directory, adding unknown files when
+    /// `include_untracked` is on.
+    fn parse_working_diff(
+        &self,
+        diff_output: &str,
+        highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        let mut files = if diff_output.trim().is_empty() {
+            Vec::new()
+        } else {
+            diff_parser::parse_unified_diff(diff_output, DiffFormat::Hg, highlighter)?
+        };
+        if self.include_untracked {
+            let unknown = self.hg(&["status", "--unknown", "--no-status", "--print0"])?;
+            files.extend(
+                unknown
+                    .split('\0')
+                    .filter(|p| !p.is_empty())
+                    .filter_map(|path| {
+                        build_untracked_diff_file(
+                            Path::new(path),
+                            &self.info.root_path.join(path),
+                            highlighter,
+                        )
+                    }),
+            );
+        }
+        if files.is_empty() {
+            return Err(TuicrError::NoChanges);
+        }
+        Ok(files)
     }
 }
 
@@ -81,22 +231,40 @@ impl VcsBackend for HgBackend {
         &self.info
     }
 
-    fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-        let diff_output = run_hg_command(&self.info.root_path, &["diff"])?;
+    fn set_ignore_whitespace(&mut self, whitespace: IgnoreWhitespace) {
+        self.whitespace = whitespace;
+    }
 
-        if diff_output.trim().is_empty() {
-            return Err(TuicrError::NoChanges);
-        }
+    fn ignore_whitespace(&self) -> Option<IgnoreWhitespace> {
+        Some(self.whitespace)
+    }
 
-        let mut files = diff_parser::parse_unified_diff(&diff_output, DiffFormat::Hg, highlighter)?;
+    fn set_include_untracked(&mut self, include: bool) {
+        self.include_untracked = include;
+    }
+
+    fn include_untracked(&self) -> Option<bool> {
+        Some(self.include_untracked)
+    }
+
+    fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+        let diff_output = self.run_diff(&["--show-function"])?;
+        let mut files = self.parse_working_diff(&diff_output, highlighter)?;
         apply_container_full_file_highlight(
             &self.info.root_path,
             ".",
             None,
             &mut files,
             highlighter,
-            hg_cat_batch,
+            |_, rev, paths| self.cat_batch(rev, paths),
         )?;
+        apply_binary_sizes(
+            &self.info.root_path,
+            ".",
+            None,
+            &mut files,
+            |_, rev, path| self.cat_size(rev, path),
+        );
         Ok(files)
     }
 
@@ -112,13 +280,7 @@ impl VcsBackend for HgBackend {
         }
 
         let content = match file_status {
-            FileStatus::Deleted => {
-                // Read from hg cat (last committed version)
-                run_hg_command(
-                    &self.info.root_path,
-                    &["cat", "-r", ".", &file_path.to_string_lossy()],
-                )?
-            }
+            FileStatus::Deleted => self.fetch_deleted_file(file_path)?,
             _ => {
                 // Read from working tree
                 let full_path = self.info.root_path.join(file_path);
@@ -145,13 +307,15 @@ impl VcsBackend for HgBackend {
         Ok(result)
     }
 
+    fn fetch_deleted_file(&self, file_path: &Path) -> Result<String> {
+        // The last committed version
+        self.hg(&["cat", "-r", ".", &file_path.to_string_lossy()])
+    }
+
     fn resolve_revisions(&self, revisions: &str) -> Result<Vec<String>> {
         // Use hg log to resolve the revset to commit hashes.
         // hg log outputs newest first; we reverse so oldest is first.
-        let output = run_hg_command(
-            &self.info.root_path,
-            &["log", "-r", revisions, "--template", "{node}\\n"],
-        )?;
+        let output = self.hg(&["log", "-r", revisions, "--template", "{node}\\n"])?;
 
         let mut commit_ids: Vec<String> = output
             .lines()
@@ -178,16 +342,13 @@ impl VcsBackend for HgBackend {
         let fetch_count = offset + limit;
         let template =
             "{node}\\x00{node|short}\\x00{desc}\\x00{author|user}\\x00{date|hgdate}\\x01";
-        let output = run_hg_command(
-            &self.info.root_path,
-            &[
-                "log",
-                "-l",
-                &fetch_count.to_string(),
-                "--template",
-                template,
-            ],
-        )?;
+        let output = self.hg(&[
+            "log",
+            "-l",
+            &fetch_count.to_string(),
+            "--template",
+            template,
+        ])?;
 
         let mut commits = Vec::new();
         for record in output.split('\x01') {
@@ -231,6 +392,7 @@ impl VcsBackend for HgBackend {
     fn get_commit_range_diff(
         &self,
         commit_ids: &[String],
+        range_mode: RangeMode,
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
         if commit_ids.is_empty() {
@@ -243,32 +405,17 @@ impl VcsBackend for HgBackend {
         // - Sapling (Meta's hg fork) has issues with full 40-char hashes in certain operations
         // - We use 12-char short hashes which work with both standard Mercurial and Sapling
         // - The parents() revset is used to find the parent commit for diffing
-        let oldest = &commit_ids[0];
-        let oldest_short = if oldest.len() > 12 {
-            &oldest[..12]
-        } else {
-            oldest.as_str()
-        };
-
-        let newest = commit_ids.last().unwrap();
-        let newest_short = if newest.len() > 12 {
-            &newest[..12]
-        } else {
-            newest.as_str()
-        };
+        let oldest_short = short_node(&commit_ids[0]);
+        let newest_short = short_node(commit_ids.last().unwrap());
 
         // First, get the parent commit of the oldest
-        // We use "log -r 'parents({oldest})'" to get the parent hash
-        let parent_output = run_hg_command(
-            &self.info.root_path,
-            &[
-                "log",
-                "-r",
-                &format!("parents({})", oldest_short),
-                "--template",
-                "{node|short}",
-            ],
-        );
+        // We use "log -r 'parents({oldest})'" to get the parent hash, or its
+        // common ancestor with the newest commit in three-dot mode
+        let base_revset = match range_mode {
+            RangeMode::TwoDot => format!("parents({oldest_short})"),
+            RangeMode::ThreeDot => format!("ancestor(p1({oldest_short}), {newest_short})"),
+        };
+        let parent_output = self.hg(&["log", "-r", &base_revset, "--template", "{node|short}"]);
 
         // If there's no parent (first commit), diff from null
         let from_rev = match parent_output {
@@ -276,10 +423,8 @@ impl VcsBackend for HgBackend {
             _ => "null".to_string(),
         };
 
-        let diff_output = run_hg_command(
-            &self.info.root_path,
-            &["diff", "-r", &from_rev, "-r", newest_short],
-        )?;
+        let diff_output =
+            self.run_diff(&["--show-function", "-r", &from_rev, "-r", newest_short])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -292,11 +437,65 @@ impl VcsBackend for HgBackend {
             Some(newest_short),
             &mut files,
             highlighter,
-            hg_cat_batch,
+            |_, rev, paths| self.cat_batch(rev, paths),
         )?;
+        apply_binary_sizes(
+            &self.info.root_path,
+            &from_rev,
+            Some(newest_short),
+            &mut files,
+            |_, rev, path| self.cat_size(rev, path),
+        );
         Ok(files)
     }
 
+    fn get_revset_diff(
+        &self,
+        revset: &str,
+        highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        let commit_ids = self.resolve_revisions(revset)?;
+        if commit_ids.len() > 1 {
+            return self.get_commit_range_diff(&commit_ids, RangeMode::TwoDot, highlighter);
+        }
+
+        // A single changeset: `hg diff -c` shows it against its first parent.
+        let rev = short_node(&commit_ids[0]);
+        let diff_output = self.run_diff(&["--show-function", "-c", rev])?;
+
+        if diff_output.trim().is_empty() {
+            return Err(TuicrError::NoChanges);
+        }
+
+        let mut files = diff_parser::parse_unified_diff(&diff_output, DiffFormat::Hg, highlighter)?;
+        apply_container_full_file_highlight(
+            &self.info.root_path,
+            &format!("p1({rev})"),
+            Some(rev),
+            &mut files,
+            highlighter,
+            |_, rev, paths| self.cat_batch(rev, paths),
+        )?;
+        apply_binary_sizes(
+            &self.info.root_path,
+            &format!("p1({rev})"),
+            Some(rev),
+            &mut files,
+            |_, rev, path| self.cat_size(rev, path),
+        );
+        Ok(files)
+    }
+
+    fn parent_commit(&self, id: &str) -> Result<Option<String>> {
+        let revset = format!("p1({id})");
+        let output = self.hg(&["log", "-r", &revset, "--template", "{node}"])?;
+        Ok(Some(output.trim().to_string()).filter(|node| !node.is_empty()))
+    }
+
+    fn user_identity(&self) -> Option<Reviewer> {
+        Reviewer::parse(&self.hg(&["config", "ui.username"]).ok()?)
+    }
+
     fn get_commits_info(&self, ids: &[String]) -> Result<Vec<CommitInfo>> {
         if ids.is_empty() {
             return Ok(Vec::new());
@@ -315,10 +514,7 @@ impl VcsBackend for HgBackend {
             .join(" | ");
         let template =
             "{node}\\x00{node|short}\\x00{desc}\\x00{author|user}\\x00{date|hgdate}\\x01";
-        let output = run_hg_command(
-            &self.info.root_path,
-            &["log", "-r", &revset, "--template", template],
-        )?;
+        let output = self.hg(&["log", "-r", &revset, "--template", template])?;
 
         let mut by_id: HashMap<String, CommitInfo> = HashMap::new();
         for record in output.split('\x01') {
@@ -376,79 +572,77 @@ impl VcsBackend for HgBackend {
         };
 
         // Get the parent of the oldest commit
-        let parent_output = run_hg_command(
-            &self.info.root_path,
-            &[
-                "log",
-                "-r",
-                &format!("parents({})", oldest_short),
-                "--template",
-                "{node|short}",
-            ],
-        );
+        let parent_output = self.hg(&[
+            "log",
+            "-r",
+            &format!("parents({})", oldest_short),
+            "--template",
+            "{node|short}",
+        ]);
 
         let from_rev = match parent_output {
             Ok(parent) if !parent.trim().is_empty() => parent.trim().to_string(),
             _ => "null".to_string(),
         };
 
-        let diff_output = run_hg_command(&self.info.root_path, &["diff", "-r", &from_rev])?;
-
-        if diff_output.trim().is_empty() {
-            return Err(TuicrError::NoChanges);
-        }
-
-        let mut files = diff_parser::parse_unified_diff(&diff_output, DiffFormat::Hg, highlighter)?;
+        let diff_output = self.run_diff(&["--show-function", "-r", &from_rev])?;
+        let mut files = self.parse_working_diff(&diff_output, highlighter)?;
         apply_container_full_file_highlight(
             &self.info.root_path,
             &from_rev,
             None,
             &mut files,
             highlighter,
-            hg_cat_batch,
+            |_, rev, paths| self.cat_batch(rev, paths),
         )?;
+        apply_binary_sizes(
+            &self.info.root_path,
+            &from_rev,
+            None,
+            &mut files,
+            |_, rev, path| self.cat_size(rev, path),
+        );
         Ok(files)
     }
 }
 
-/// Fetch the full content of `paths` at `rev` in a single `hg cat` subprocess.
-///
-/// hg cat is dominated by Python startup (~280 ms) regardless of file count,
-/// so batching every container file into one call is significantly faster than
-/// fetching each one separately.
-fn hg_cat_batch(root: &Path, rev: &str, paths: &[PathBuf]) -> Result<HashMap<PathBuf, String>> {
-    if paths.is_empty() {
-        return Ok(HashMap::new());
-    }
-    let template = format!("\n{BATCH_BOUNDARY}\n{{path}}\n{{data}}");
-    let path_strs: Vec<String> = paths
-        .iter()
-        .map(|p| p.to_string_lossy().into_owned())
-        .collect();
-    let mut args: Vec<&str> = vec!["cat", "-r", rev, "--template", &template];
-    args.extend(path_strs.iter().map(String::as_str));
-    let output = run_hg_command(root, &args)?;
-    Ok(parse_batched_files(&output))
+/// 12-character node prefix. Sapling (Meta's hg fork) has issues with full
+/// 40-char hashes in some operations; short hashes work with both.
+fn short_node(node: &str) -> &str {
+    if node.len() > 12 { &node[..12] } else { node }
 }
 
-/// Run an hg command and return its stdout
+/// `hg diff` flags equivalent to `whitespace`.
+fn whitespace_args(whitespace: &IgnoreWhitespace) -> Vec<&'static str> {
+    [
+        (whitespace.all_space, "-w"),
+        (whitespace.space_change, "-b"),
+        (whitespace.blank_lines, "-B"),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, flag)| flag)
+    .collect()
+}
+
+/// Run an hg command and return its stdout. It goes through `process`,
+/// so a hung `hg` is killed at the command timeout instead of freezing
+/// the app.
 fn run_hg_command(root: &Path, args: &[&str]) -> Result<String> {
-    let output = Command::new("hg")
-        .current_dir(root)
-        .args(args)
-        .output()
-        .map_err(|e| TuicrError::VcsCommand(format!("Failed to run hg: {}", e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(TuicrError::VcsCommand(format!(
-            "hg {} failed: {}",
-            args.join(" "),
-            stderr
-        )));
-    }
+    crate::process::run_command_output("hg", Some(root), args)
+        .map_err(|e| hg_command_error(args, e))
+}
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+fn hg_command_error(args: &[&str], e: CommandOutputError) -> TuicrError {
+    TuicrError::VcsCommand(match e.kind {
+        CommandOutputErrorKind::NotFound | CommandOutputErrorKind::SpawnFailed => {
+            format!("Failed to run hg: {}", e.stderr)
+        }
+        CommandOutputErrorKind::Unsuccessful => {
+            format!("hg {} failed: {}", args.join(" "), e.stderr)
+        }
+        CommandOutputErrorKind::TimedOut | CommandOutputErrorKind::Cancelled => e.stderr,
+    })
 }
 
 #[cfg(test)]
@@ -568,6 +762,36 @@ mod tests {
         assert_eq!(files[0].status, FileStatus::Modified);
     }
 
+    #[test]
+    fn test_hg_working_tree_diff_lists_unknown_files_when_enabled() {
+        let Some(temp) = setup_test_repo() else {
+            eprintln!("Skipping test: hg command not available");
+            return;
+        };
+        fs::write(temp.path().join("scratch.txt"), "notes\n").expect("Failed to write file");
+
+        let mut backend =
+            HgBackend::from_path(temp.path().to_path_buf()).expect("Failed to create hg backend");
+        let highlighter = SyntaxHighlighter::default();
+        assert_eq!(backend.include_untracked(), Some(false));
+        let files = backend
+            .get_working_tree_diff(&highlighter)
+            .expect("Failed to get diff");
+        assert_eq!(files.len(), 1);
+
+        backend.set_include_untracked(true);
+        let files = backend
+            .get_working_tree_diff(&highlighter)
+            .expect("Failed to get diff");
+
+        assert_eq!(files.len(), 2);
+        let scratch = files
+            .iter()
+            .find(|f| f.new_path.as_deref() == Some(Path::new("scratch.txt")))
+            .expect("unknown file should be listed");
+        assert_eq!(scratch.status, FileStatus::Added);
+    }
+
     #[test]
     fn test_hg_fetch_context_lines() {
         let Some(temp) = setup_test_repo() else {
@@ -692,7 +916,11 @@ mod tests {
 
         // Get diff for the last two commits (Second and Third)
         let commit_ids = vec![commits[1].id.clone(), commits[0].id.clone()];
-        let diff_result = backend.get_commit_range_diff(&commit_ids, &SyntaxHighlighter::default());
+        let diff_result = backend.get_commit_range_diff(
+            &commit_ids,
+            RangeMode::TwoDot,
+            &SyntaxHighlighter::default(),
+        );
 
         // Note: Sapling (Meta's hg fork) may fail with "id_dag_snapshot()" error
         // in certain temporary directory configurations. Skip the test in that case.
@@ -727,6 +955,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hg_get_revset_diff_for_single_changeset() {
+        let Some(temp) = setup_test_repo_with_commits() else {
+            eprintln!("Skipping test: hg command not available");
+            return;
+        };
+
+        let backend =
+            HgBackend::from_path(temp.path().to_path_buf()).expect("Failed to create hg backend");
+
+        // Only the second commit, which added file2.txt
+        let diff = match backend.get_revset_diff("1", &SyntaxHighlighter::default()) {
+            Ok(d) => d,
+            Err(TuicrError::VcsCommand(msg)) if msg.contains("id_dag_snapshot") => {
+                eprintln!("Skipping test: Sapling-specific issue with tempdir repos");
+                return;
+            }
+            Err(e) => panic!("Failed to get revset diff: {:?}", e),
+        };
+
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].new_path, Some(PathBuf::from("file2.txt")));
+        assert_eq!(diff[0].status, FileStatus::Added);
+    }
+
     /// Create a test repo with a renamed file (no content changes).
     fn setup_test_repo_with_rename() -> Option<tempfile::TempDir> {
         if !hg_available() {
@@ -7,11 +7,15 @@ use std::process::Command;
 use chrono::{DateTime, Utc};
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, DiffLine, FileStatus, LineOrigin};
+use crate::model::{DiffFile, DiffLine, FileStatus, LineOrigin, Reviewer};
+use crate::process::CommandOutputErrorKind;
 use crate::syntax::SyntaxHighlighter;
 use crate::vcs::diff_parser::{self, DiffFormat};
-use crate::vcs::traits::{CommitInfo, VcsBackend, VcsInfo, VcsType};
-use crate::vcs::{BATCH_BOUNDARY, apply_container_full_file_highlight, parse_batched_files};
+use crate::vcs::traits::{CommitInfo, IgnoreWhitespace, RangeMode, VcsBackend, VcsInfo, VcsType};
+use crate::vcs::{
+    BATCH_BOUNDARY, apply_binary_sizes, apply_container_full_file_highlight,
+    apply_hunk_function_context, parse_batched_files,
+};
 
 /// Parse a jj description into (summary, optional body).
 fn parse_description(desc: &str) -> (String, Option<String>) {
@@ -29,9 +33,20 @@ fn parse_description(desc: &str) -> (String, Option<String>) {
     (summary, body)
 }
 
+/// Prefix a change's summary with its `(divergent)` / `(conflict)` labels
+fn label_summary(summary: String, labels: &str) -> String {
+    let labels = labels.trim();
+    if labels.is_empty() {
+        summary
+    } else {
+        format!("{labels} {summary}")
+    }
+}
+
 /// Jujutsu backend implementation using jj CLI commands
 pub struct JjBackend {
     info: VcsInfo,
+    whitespace: IgnoreWhitespace,
 }
 
 impl JjBackend {
@@ -54,7 +69,7 @@ impl JjBackend {
     }
 
     /// Create backend from a known path (used by discover and tests)
-    fn from_path(root_path: PathBuf) -> Result<Self> {
+    pub(crate) fn from_path(root_path: PathBuf) -> Result<Self> {
         // Canonicalize to resolve symlinks (e.g., /var -> /private/var on macOS)
         let root_path = root_path.canonicalize().unwrap_or(root_path);
 
@@ -109,7 +124,18 @@ impl JjBackend {
             vcs_type: VcsType::Jujutsu,
         };
 
-        Ok(Self { info })
+        Ok(Self {
+            info,
+            whitespace: IgnoreWhitespace::default(),
+        })
+    }
+
+    /// Run `jj diff` with the configured whitespace flags ahead of `args`.
+    fn run_diff(&self, args: &[&str]) -> Result<String> {
+        let mut full_args = vec!["diff"];
+        full_args.extend(whitespace_args(&self.whitespace));
+        full_args.extend_from_slice(args);
+        run_jj_command(&self.info.root_path, &full_args)
     }
 }
 
@@ -118,8 +144,16 @@ impl VcsBackend for JjBackend {
         &self.info
     }
 
+    fn set_ignore_whitespace(&mut self, whitespace: IgnoreWhitespace) {
+        self.whitespace = whitespace;
+    }
+
+    fn ignore_whitespace(&self) -> Option<IgnoreWhitespace> {
+        Some(self.whitespace)
+    }
+
     fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
-        let diff_output = run_jj_command(&self.info.root_path, &["diff", "--git"])?;
+        let diff_output = self.run_diff(&["--git"])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -135,6 +169,8 @@ impl VcsBackend for JjBackend {
             highlighter,
             jj_show_batch,
         )?;
+        apply_binary_sizes(&self.info.root_path, "@-", None, &mut files, jj_show_size);
+        apply_hunk_function_context(&self.info.root_path, "@-", &mut files, jj_show_batch);
         Ok(files)
     }
 
@@ -150,13 +186,7 @@ impl VcsBackend for JjBackend {
         }
 
         let content = match file_status {
-            FileStatus::Deleted => {
-                // Read from jj show (parent revision)
-                run_jj_command(
-                    &self.info.root_path,
-                    &["file", "show", "-r", "@-", &file_path.to_string_lossy()],
-                )?
-            }
+            FileStatus::Deleted => self.fetch_deleted_file(file_path)?,
             _ => {
                 // Read from working tree
                 let full_path = self.info.root_path.join(file_path);
@@ -183,6 +213,14 @@ impl VcsBackend for JjBackend {
         Ok(result)
     }
 
+    fn fetch_deleted_file(&self, file_path: &Path) -> Result<String> {
+        // The parent revision
+        run_jj_command(
+            &self.info.root_path,
+            &["file", "show", "-r", "@-", &file_path.to_string_lossy()],
+        )
+    }
+
     fn resolve_revisions(&self, revisions: &str) -> Result<Vec<String>> {
         // Use jj log to resolve the revisions to commit IDs, reverse-chronological by default.
         // We reverse the result so the oldest commit is first (matching get_commit_range_diff expectations).
@@ -221,8 +259,9 @@ impl VcsBackend for JjBackend {
         //
         // jj log doesn't have a --skip option, so we fetch offset+limit commits
         // and skip the first `offset` in Rust code
+        // The last field flags divergent and conflicted changes for the selector
         let fetch_count = offset + limit;
-        let template = r#"commit_id ++ "\x00" ++ commit_id.short() ++ "\x00" ++ description ++ "\x00" ++ author.email() ++ "\x00" ++ committer.timestamp() ++ "\x01""#;
+        let template = r#"commit_id ++ "\x00" ++ commit_id.short() ++ "\x00" ++ description ++ "\x00" ++ author.email() ++ "\x00" ++ committer.timestamp() ++ "\x00" ++ separate(" ", if(divergent, "(divergent)"), if(conflict, "(conflict)")) ++ "\x01""#;
         let output = run_jj_command(
             &self.info.root_path,
             &[
@@ -252,6 +291,7 @@ impl VcsBackend for JjBackend {
             let id = parts[0].to_string();
             let short_id = parts[1].to_string();
             let (summary, body) = parse_description(parts[2]);
+            let summary = label_summary(summary, parts.get(5).copied().unwrap_or(""));
             let author = parts[3].to_string();
 
             // jj timestamp format is ISO 8601: "2024-01-15T10:30:00.000-05:00"
@@ -276,6 +316,7 @@ impl VcsBackend for JjBackend {
     fn get_commit_range_diff(
         &self,
         commit_ids: &[String],
+        range_mode: RangeMode,
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
         if commit_ids.is_empty() {
@@ -287,30 +328,106 @@ impl VcsBackend for JjBackend {
         let newest = commit_ids.last().unwrap();
 
         // Get the parent of the oldest commit to include its changes
-        // In jj, we use {commit}- to get the parent(s)
-        let from_rev = format!("{}-", oldest);
-        let diff_output = run_jj_command(
+        // In jj, we use {commit}- to get the parent(s); three-dot mode diffs
+        // from their fork point with the newest commit instead
+        let from_rev = match range_mode {
+            RangeMode::TwoDot => format!("{}-", oldest),
+            RangeMode::ThreeDot => format!("fork_point({oldest}- | {newest})"),
+        };
+        let diff_output = self.run_diff(&["--from", &from_rev, "--to", newest, "--git"])?;
+
+        if diff_output.trim().is_empty() {
+            return Err(TuicrError::NoChanges);
+        }
+
+        let mut files =
+            diff_parser::parse_unified_diff(&diff_output, DiffFormat::GitStyle, highlighter)?;
+        apply_container_full_file_highlight(
             &self.info.root_path,
-            &["diff", "--from", &from_rev, "--to", newest, "--git"],
+            &from_rev,
+            Some(newest),
+            &mut files,
+            highlighter,
+            jj_show_batch,
         )?;
+        apply_binary_sizes(
+            &self.info.root_path,
+            &from_rev,
+            Some(newest),
+            &mut files,
+            jj_show_size,
+        );
+        apply_hunk_function_context(&self.info.root_path, &from_rev, &mut files, jj_show_batch);
+        Ok(files)
+    }
+
+    fn get_revset_diff(
+        &self,
+        revset: &str,
+        highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        // `jj diff -r` shows the combined changes of every revision in the
+        // set, which also covers sets that are not a single linear range.
+        let diff_output = self.run_diff(&["-r", revset, "--git"])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
         }
 
+        // Full-file highlighting needs a single revision per side; the
+        // parents of the set's roots and its heads match what `-r` diffs.
+        let from_rev = format!("latest(roots({revset})-)");
+        let to_rev = format!("latest(heads({revset}))");
         let mut files =
             diff_parser::parse_unified_diff(&diff_output, DiffFormat::GitStyle, highlighter)?;
         apply_container_full_file_highlight(
             &self.info.root_path,
             &from_rev,
-            Some(newest),
+            Some(&to_rev),
             &mut files,
             highlighter,
             jj_show_batch,
         )?;
+        apply_binary_sizes(
+            &self.info.root_path,
+            &from_rev,
+            Some(&to_rev),
+            &mut files,
+            jj_show_size,
+        );
+        apply_hunk_function_context(&self.info.root_path, &from_rev, &mut files, jj_show_batch);
         Ok(files)
     }
 
+    fn parent_commit(&self, id: &str) -> Result<Option<String>> {
+        let revset = format!("{id}-");
+        let output = run_jj_command(
+            &self.info.root_path,
+            &[
+                "log",
+                "-r",
+                &revset,
+                "--no-graph",
+                "-T",
+                r#"commit_id ++ "\n""#,
+            ],
+        )?;
+        Ok(output.lines().next().map(str::to_string))
+    }
+
+    fn user_identity(&self) -> Option<Reviewer> {
+        let get = |key: &str| {
+            run_jj_command(&self.info.root_path, &["config", "get", key])
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Some(Reviewer {
+            name: get("user.name")?,
+            email: get("user.email"),
+        })
+    }
+
     fn get_commits_info(&self, ids: &[String]) -> Result<Vec<CommitInfo>> {
         if ids.is_empty() {
             return Ok(Vec::new());
@@ -376,10 +493,7 @@ impl VcsBackend for JjBackend {
 
         // Diff from the parent of the oldest commit to the working copy (@)
         let from_rev = format!("{}-", oldest);
-        let diff_output = run_jj_command(
-            &self.info.root_path,
-            &["diff", "--from", &from_rev, "--to", "@", "--git"],
-        )?;
+        let diff_output = self.run_diff(&["--from", &from_rev, "--to", "@", "--git"])?;
 
         if diff_output.trim().is_empty() {
             return Err(TuicrError::NoChanges);
@@ -395,6 +509,14 @@ impl VcsBackend for JjBackend {
             highlighter,
             jj_show_batch,
         )?;
+        apply_binary_sizes(
+            &self.info.root_path,
+            &from_rev,
+            None,
+            &mut files,
+            jj_show_size,
+        );
+        apply_hunk_function_context(&self.info.root_path, &from_rev, &mut files, jj_show_batch);
         Ok(files)
     }
 }
@@ -417,24 +539,49 @@ fn jj_show_batch(root: &Path, rev: &str, paths: &[PathBuf]) -> Result<HashMap<Pa
     Ok(parse_batched_files(&output))
 }
 
-/// Run a jj command and return its stdout
-fn run_jj_command(root: &Path, args: &[&str]) -> Result<String> {
+/// Byte size of `path` at `rev`, read through `jj file show` so binary content
+/// isn't mangled by a lossy UTF-8 conversion.
+fn jj_show_size(root: &Path, rev: &str, path: &Path) -> Option<u64> {
     let output = Command::new("jj")
         .current_dir(root)
-        .args(args)
+        .args(["file", "show", "-r", rev])
+        .arg(path)
         .output()
-        .map_err(|e| TuicrError::VcsCommand(format!("Failed to run jj: {}", e)))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(TuicrError::VcsCommand(format!(
-            "jj {} failed: {}",
-            args.join(" "),
-            stderr
-        )));
-    }
+        .ok()?;
+    output
+        .status
+        .success()
+        .then_some(output.stdout.len() as u64)
+}
+
+/// `jj diff` flags equivalent to `whitespace`.
+fn whitespace_args(whitespace: &IgnoreWhitespace) -> Vec<&'static str> {
+    // jj has no equivalent of `--ignore-blank-lines`.
+    [
+        (whitespace.all_space, "--ignore-all-space"),
+        (whitespace.space_change, "--ignore-space-change"),
+    ]
+    .into_iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, flag)| flag)
+    .collect()
+}
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+/// Run a jj command and return its stdout. It goes through `process`,
+/// so a hung `jj` is killed at the command timeout instead of freezing
+/// the app.
+fn run_jj_command(root: &Path, args: &[&str]) -> Result<String> {
+    crate::process::run_command_output("jj", Some(root), args).map_err(|e| {
+        TuicrError::VcsCommand(match e.kind {
+            CommandOutputErrorKind::NotFound | CommandOutputErrorKind::SpawnFailed => {
+                format!("Failed to run jj: {}", e.stderr)
+            }
+            CommandOutputErrorKind::Unsuccessful => {
+                format!("jj {} failed: {}", args.join(" "), e.stderr)
+            }
+            CommandOutputErrorKind::TimedOut | CommandOutputErrorKind::Cancelled => e.stderr,
+        })
+    })
 }
 
 #[cfg(test)]
@@ -510,6 +657,15 @@ mod tests {
         Some(temp_dir)
     }
 
+    #[test]
+    fn test_label_summary_prefixes_divergent_and_conflict_flags() {
+        assert_eq!(label_summary("Fix".to_string(), ""), "Fix");
+        assert_eq!(
+            label_summary("Fix".to_string(), "(divergent) (conflict)"),
+            "(divergent) (conflict) Fix"
+        );
+    }
+
     #[test]
     fn test_jj_discover() {
         let Some(temp) = setup_test_repo() else {
@@ -707,7 +863,11 @@ mod tests {
 
             let commit_ids = vec![oldest.id.clone(), newest.id.clone()];
             let diff = backend
-                .get_commit_range_diff(&commit_ids, &SyntaxHighlighter::default())
+                .get_commit_range_diff(
+                    &commit_ids,
+                    RangeMode::TwoDot,
+                    &SyntaxHighlighter::default(),
+                )
                 .expect("Failed to get commit range diff");
 
             // Should have changes
@@ -715,6 +875,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_jj_get_revset_diff() {
+        let Some(temp) = setup_test_repo_with_commits() else {
+            eprintln!("Skipping test: jj command not available");
+            return;
+        };
+
+        let backend =
+            JjBackend::from_path(temp.path().to_path_buf()).expect("Failed to create jj backend");
+
+        // Second and third commits: file2 is added, file1 gains a line
+        let diff = backend
+            .get_revset_diff("@--::@-", &SyntaxHighlighter::default())
+            .expect("Failed to get revset diff");
+
+        let mut paths: Vec<_> = diff
+            .iter()
+            .map(|f| (f.display_path().to_string_lossy().into_owned(), f.status))
+            .collect();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            paths,
+            vec![
+                ("file1.txt".to_string(), FileStatus::Modified),
+                ("file2.txt".to_string(), FileStatus::Added),
+            ]
+        );
+    }
+
     /// Create a test repo with a renamed file (no content changes).
     fn setup_test_repo_with_rename() -> Option<tempfile::TempDir> {
         if !jj_available() {
@@ -16,6 +16,7 @@ pub mod file;
 pub mod git;
 mod hg;
 mod jj;
+pub mod patch;
 pub mod pr_noop;
 pub(crate) mod traits;
 
@@ -23,23 +24,34 @@ pub use file::FileBackend;
 pub use git::{GitBackend, GitBackendPreference};
 pub use hg::HgBackend;
 pub use jj::JjBackend;
+pub use patch::PatchBackend;
 pub use pr_noop::PrNoopVcs;
-pub use traits::{CommitInfo, VcsBackend, VcsChangeStatus, VcsInfo};
+pub use traits::{
+    AuthorStats, CommitInfo, IgnoreWhitespace, RangeMode, RenameDetection, VcsBackend,
+    VcsChangeStatus, VcsInfo,
+};
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::error::{Result, TuicrError};
-use crate::model::{DiffFile, LineSide};
+use crate::model::{
+    DiffFile, DiffHunk, DiffLine, FileModes, FileSizes, FileStatus, LineOrigin, LineSide,
+};
 use crate::syntax::{
     HighlightedLines, HighlightedSpans, SyntaxHighlighter, needs_full_file_highlight,
 };
+use crate::vcs::traits::VcsType;
 
 /// Boundary marker emitted between files in batched `hg cat` / `jj file show`
 /// output. The long random suffix makes accidental collision with real source
 /// content effectively impossible.
 pub(crate) const BATCH_BOUNDARY: &str = "@@TUICR_BATCH_BOUNDARY_e97f2d44_8b1a@@";
 
+// Untracked files larger than this are shown in the file list but their
+// content is not parsed: they are likely logs, dumps, or build artefacts.
+const MAX_UNTRACKED_FILE_SIZE: u64 = 10 * 1_024 * 1_024;
+
 /// Collect the unique paths of files that need full-file syntax highlighting
 /// (Vue, Svelte, PHP and friends) on the given side, skipping binary, too-large,
 /// or empty entries. Used by hg / jj to know which files to batch-fetch.
@@ -61,9 +73,99 @@ pub(crate) fn container_file_paths(files: &[DiffFile], side: LineSide) -> Vec<Pa
 }
 
 /// Expand tabs to spaces in diff line content so highlighted spans line up
-/// with the displayed text in side-by-side and unified rendering.
-pub(crate) fn tabify(s: &str) -> String {
-    s.replace('\t', "    ")
+/// with the displayed text in side-by-side and unified rendering. Tabs are
+/// as wide as `path`'s `[filetypes]` rule says, 4 columns by default.
+pub(crate) fn tabify(path: &Path, s: &str) -> String {
+    if !s.contains('\t') {
+        return s.to_string();
+    }
+    s.replace('\t', &" ".repeat(crate::filetypes::rules().tab_width(path)))
+}
+
+/// Build an all-added diff for an untracked file read from `full_path`.
+/// Returns `None` when the file cannot be read.
+pub(crate) fn build_untracked_diff_file(
+    path: &Path,
+    full_path: &Path,
+    highlighter: &SyntaxHighlighter,
+) -> Option<DiffFile> {
+    let metadata = full_path.metadata().ok()?;
+    if metadata.len() > MAX_UNTRACKED_FILE_SIZE {
+        return Some(diff_file_without_hunks(path, false, true));
+    }
+
+    let bytes = std::fs::read(full_path).ok()?;
+    if bytes.contains(&0) {
+        return Some(diff_file_without_hunks(path, true, false));
+    }
+
+    let content = String::from_utf8_lossy(&bytes);
+    let lines: Vec<String> = content
+        .lines()
+        .map(|line| tabify(path, line.trim_end_matches('\r')))
+        .collect();
+
+    if lines.is_empty() {
+        return Some(diff_file_without_hunks(path, false, false));
+    }
+
+    let highlighted = highlighter.highlight_file_lines(path, &lines);
+    let diff_lines: Vec<DiffLine> = lines
+        .into_iter()
+        .enumerate()
+        .map(|(idx, content)| DiffLine {
+            origin: LineOrigin::Addition,
+            content,
+            old_lineno: None,
+            new_lineno: Some((idx + 1) as u32),
+            highlighted_spans: highlighter.highlighted_line_for_diff_with_background(
+                None,
+                highlighted.as_deref(),
+                None,
+                Some(idx),
+                LineOrigin::Addition,
+            ),
+        })
+        .collect();
+
+    let new_count = diff_lines.len() as u32;
+    let hunks = vec![DiffHunk {
+        header: format!("@@ -0,0 +1,{new_count} @@"),
+        lines: diff_lines,
+        old_start: 0,
+        old_count: 0,
+        new_start: 1,
+        new_count,
+    }];
+    let content_hash = DiffFile::compute_content_hash(&hunks);
+
+    Some(DiffFile {
+        old_path: None,
+        new_path: Some(path.to_path_buf()),
+        status: FileStatus::Added,
+        hunks,
+        is_binary: false,
+        is_too_large: false,
+        is_commit_message: false,
+        content_hash,
+        modes: FileModes::default(),
+        sizes: FileSizes::default(),
+    })
+}
+
+fn diff_file_without_hunks(path: &Path, is_binary: bool, is_too_large: bool) -> DiffFile {
+    DiffFile {
+        old_path: None,
+        new_path: Some(path.to_path_buf()),
+        status: FileStatus::Added,
+        hunks: Vec::new(),
+        is_binary,
+        is_too_large,
+        is_commit_message: false,
+        content_hash: 0,
+        modes: FileModes::default(),
+        sizes: FileSizes::default(),
+    }
 }
 
 /// Read a file from the working tree, returning `None` on any IO error.
@@ -133,6 +235,80 @@ where
     Ok(())
 }
 
+/// Record old/new byte sizes on binary files, which have no hunks to
+/// review, so their header can at least show the magnitude of the change.
+/// A side that doesn't exist (added or deleted file) stays `None`.
+pub(crate) fn fill_binary_sizes<F, G>(files: &mut [DiffFile], old_size: F, new_size: G)
+where
+    F: Fn(&Path) -> Option<u64>,
+    G: Fn(&Path) -> Option<u64>,
+{
+    for file in files.iter_mut().filter(|f| f.is_binary) {
+        let old = match file.status {
+            FileStatus::Added => None,
+            _ => file.old_path.as_deref().and_then(&old_size),
+        };
+        let new = match file.status {
+            FileStatus::Deleted => None,
+            _ => file.new_path.as_deref().and_then(&new_size),
+        };
+        file.sizes = FileSizes { old, new };
+    }
+}
+
+/// [`fill_binary_sizes`] for hg / jj: `blob_size` looks a path up at a
+/// revision (`hg cat -r REV` or `jj file show -r REV`); `new_rev = None`
+/// reads the new side from the working tree instead.
+pub(crate) fn apply_binary_sizes<F>(
+    root: &Path,
+    old_rev: &str,
+    new_rev: Option<&str>,
+    files: &mut [DiffFile],
+    blob_size: F,
+) where
+    F: Fn(&Path, &str, &Path) -> Option<u64>,
+{
+    fill_binary_sizes(
+        files,
+        |path| blob_size(root, old_rev, path),
+        |path| match new_rev {
+            Some(rev) => blob_size(root, rev, path),
+            None => workdir_file_size(root, path),
+        },
+    );
+}
+
+/// Size in bytes of a working-tree file, `None` if it can't be stat'ed.
+pub(crate) fn workdir_file_size(root: &Path, rel: &Path) -> Option<u64> {
+    std::fs::metadata(root.join(rel)).ok().map(|m| m.len())
+}
+
+/// Fill in git-style function context on hunk headers for backends whose
+/// diff output lacks it, fetching every needed old side in one
+/// `fetch_batch` call. The context is cosmetic, so a failed fetch leaves the
+/// headers as they are instead of failing the diff.
+pub(crate) fn apply_hunk_function_context<F>(
+    root: &Path,
+    old_rev: &str,
+    files: &mut [DiffFile],
+    fetch_batch: F,
+) where
+    F: Fn(&Path, &str, &[PathBuf]) -> Result<HashMap<PathBuf, String>>,
+{
+    let old_paths: Vec<PathBuf> = files
+        .iter()
+        .filter(|file| !file.is_binary && diff_parser::needs_function_context(file))
+        .filter_map(|file| file.old_path.clone())
+        .collect();
+    if old_paths.is_empty() {
+        return;
+    }
+
+    if let Ok(old_map) = fetch_batch(root, old_rev, &old_paths) {
+        diff_parser::add_function_context(files, |p| old_map.get(p).cloned());
+    }
+}
+
 /// Files larger than this skip the full-file highlight pass and fall back to
 /// per-hunk highlighting. Keeps a runaway-cost ceiling on diffs that include
 /// huge generated artefacts (lockfiles, vendored bundles, fixtures).
@@ -265,7 +441,7 @@ fn highlight_content(
     if content.len() > MAX_HIGHLIGHT_FILE_BYTES || content.as_bytes().contains(&0u8) {
         return None;
     }
-    let lines: Vec<String> = content.lines().map(tabify).collect();
+    let lines: Vec<String> = content.lines().map(|line| tabify(path, line)).collect();
     highlighter.highlight_file_lines(path, &lines)
 }
 
@@ -316,6 +492,36 @@ pub fn detect_vcs(git_backend_preference: GitBackendPreference) -> Result<Box<dy
     Err(TuicrError::NotARepository)
 }
 
+/// Which other VCS can view `info`'s repository. A colocated jj repo keeps
+/// its `.jj` and `.git` directories side by side, so the same checkout can be
+/// reviewed as jj changes (`@` vs `@-`) or as git's index and worktree.
+pub fn colocated_counterpart_type(info: &VcsInfo) -> Option<VcsType> {
+    let root = &info.root_path;
+    if !root.join(".jj").is_dir() || !root.join(".git").exists() {
+        return None;
+    }
+    match info.vcs_type {
+        VcsType::Jujutsu => Some(VcsType::Git),
+        VcsType::Git => Some(VcsType::Jujutsu),
+        _ => None,
+    }
+}
+
+/// Open the other side of a colocated jj+git repository. `None` when `info`
+/// isn't colocated.
+pub fn colocated_counterpart(
+    info: &VcsInfo,
+    git_backend_preference: GitBackendPreference,
+) -> Option<Result<Box<dyn VcsBackend>>> {
+    let backend: Result<Box<dyn VcsBackend>> = match colocated_counterpart_type(info)? {
+        VcsType::Git => GitBackend::discover_from(&info.root_path, git_backend_preference)
+            .map(|backend| Box::new(backend) as Box<dyn VcsBackend>),
+        _ => JjBackend::from_path(info.root_path.clone())
+            .map(|backend| Box::new(backend) as Box<dyn VcsBackend>),
+    };
+    Some(backend)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +555,32 @@ mod tests {
         assert_eq!(commit.id, "abc");
     }
 
+    #[test]
+    fn should_detect_colocated_jj_and_git_repo() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let info = |vcs_type| VcsInfo {
+            root_path: dir.path().to_path_buf(),
+            head_commit: "abc".to_string(),
+            branch_name: None,
+            vcs_type,
+        };
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let git_only = colocated_counterpart_type(&info(VcsType::Git));
+        std::fs::create_dir(dir.path().join(".jj")).unwrap();
+
+        // when
+        let from_git = colocated_counterpart_type(&info(VcsType::Git));
+        let from_jj = colocated_counterpart_type(&info(VcsType::Jujutsu));
+        let from_hg = colocated_counterpart_type(&info(VcsType::Mercurial));
+
+        // then
+        assert_eq!(git_only, None);
+        assert_eq!(from_git, Some(VcsType::Jujutsu));
+        assert_eq!(from_jj, Some(VcsType::Git));
+        assert_eq!(from_hg, None);
+    }
+
     #[test]
     fn detect_vcs_outside_repo_returns_error() {
         // When run outside any VCS repo, should return NotARepository
@@ -414,6 +646,8 @@ mod tests {
             is_too_large: false,
             is_commit_message: false,
             content_hash: 0,
+            modes: FileModes::default(),
+            sizes: FileSizes::default(),
         }
     }
 
@@ -0,0 +1,212 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, TuicrError};
+use crate::hash::fnv1a_64;
+use crate::model::{DiffFile, DiffLine, FileStatus};
+use crate::syntax::SyntaxHighlighter;
+
+use super::diff_parser::{self, DiffFormat};
+use super::traits::{VcsBackend, VcsInfo, VcsType};
+
+/// A backend for reviewing a unified diff without a local checkout.
+///
+/// Accepts `git diff` / `git format-patch` output (including mailed patch
+/// series), `hg diff`, and plain `diff -u` output. The diff text is parsed
+/// with the same unified diff parser the hg and jj backends use.
+pub struct PatchBackend {
+    info: VcsInfo,
+    diff_text: String,
+    format: DiffFormat,
+}
+
+impl PatchBackend {
+    /// Read a patch from `path`, or from stdin when `path` is `-`.
+    pub fn new(path: &str) -> Result<Self> {
+        let text = if path == "-" {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        } else {
+            std::fs::read_to_string(path).map_err(|e| {
+                TuicrError::Io(std::io::Error::new(
+                    e.kind(),
+                    format!("Cannot read patch '{path}': {e}"),
+                ))
+            })?
+        };
+        let root_path = std::env::current_dir()?;
+        Ok(Self::from_text(text, root_path))
+    }
+
+    fn from_text(text: String, root_path: PathBuf) -> Self {
+        let (diff_text, format) = normalize_patch(&text);
+        // Sessions are keyed by head commit; hashing the patch keeps reviews
+        // of different patches in the same directory apart.
+        let info = VcsInfo {
+            root_path,
+            head_commit: format!("patch-{:016x}", fnv1a_64(diff_text.as_bytes())),
+            branch_name: None,
+            vcs_type: VcsType::Patch,
+        };
+        Self {
+            info,
+            diff_text,
+            format,
+        }
+    }
+}
+
+impl VcsBackend for PatchBackend {
+    fn info(&self) -> &VcsInfo {
+        &self.info
+    }
+
+    fn get_working_tree_diff(&self, highlighter: &SyntaxHighlighter) -> Result<Vec<DiffFile>> {
+        diff_parser::parse_unified_diff(&self.diff_text, self.format, highlighter)
+    }
+
+    /// A patch only carries the lines in its hunks, so there is no
+    /// surrounding context to expand.
+    fn fetch_context_lines(
+        &self,
+        _file_path: &Path,
+        _file_status: FileStatus,
+        _start_line: u32,
+        _end_line: u32,
+    ) -> Result<Vec<DiffLine>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Massage patch text into something `parse_unified_diff` understands and
+/// pick the matching format:
+///
+/// - `git format-patch` signatures (`-- ` followed by the git version) are
+///   dropped so they don't parse as deleted lines.
+/// - Plain `diff -u` output without any `diff ...` line gets a synthetic
+///   header before each `---`/`+++` pair.
+fn normalize_patch(text: &str) -> (String, DiffFormat) {
+    let lines: Vec<&str> = text.lines().collect();
+    let has_git_headers = lines.iter().any(|l| l.starts_with("diff --git "));
+    let has_diff_headers = lines.iter().any(|l| l.starts_with("diff "));
+
+    let mut out = String::with_capacity(text.len());
+    let mut in_signature = false;
+    for (idx, line) in lines.iter().enumerate() {
+        let next = lines.get(idx + 1);
+        // A removed "- " line also reads "-- ", but is followed by more diff
+        // content rather than the git version string.
+        if *line == "-- " && next.is_none_or(|n| !n.starts_with([' ', '+', '-', '@', '\\'])) {
+            in_signature = true;
+            continue;
+        }
+        if in_signature {
+            // The next patch of a mailed series starts a new message.
+            if line.starts_with("From ") || line.starts_with("diff ") {
+                in_signature = false;
+            } else {
+                continue;
+            }
+        }
+        if !has_diff_headers
+            && line.starts_with("--- ")
+            && next.is_some_and(|n| n.starts_with("+++ "))
+        {
+            out.push_str("diff -u\n");
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    let format = if has_git_headers {
+        DiffFormat::GitStyle
+    } else {
+        DiffFormat::Hg
+    };
+    (out, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Vec<DiffFile> {
+        let backend = PatchBackend::from_text(text.to_string(), PathBuf::from("/tmp"));
+        backend
+            .get_working_tree_diff(&SyntaxHighlighter::default())
+            .expect("patch should parse")
+    }
+
+    #[test]
+    fn should_parse_git_diff_patch() {
+        let files = parse(
+            "diff --git a/src/lib.rs b/src/lib.rs\n\
+             index 1111111..2222222 100644\n\
+             --- a/src/lib.rs\n\
+             +++ b/src/lib.rs\n\
+             @@ -1,2 +1,2 @@\n\
+             \x20fn main() {}\n\
+             -let a = 1;\n\
+             +let a = 2;\n",
+        );
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].new_path, Some(PathBuf::from("src/lib.rs")));
+        assert_eq!(files[0].hunks[0].lines.len(), 3);
+    }
+
+    #[test]
+    fn should_drop_format_patch_signature() {
+        let files = parse(
+            "From abc Mon Sep 17 00:00:00 2001\n\
+             Subject: [PATCH] Fix typo\n\
+             \n\
+             diff --git a/README.md b/README.md\n\
+             --- a/README.md\n\
+             +++ b/README.md\n\
+             @@ -1 +1 @@\n\
+             -teh\n\
+             +the\n\
+             -- \n\
+             2.43.0\n",
+        );
+        assert_eq!(files.len(), 1);
+        let lines = &files[0].hunks[0].lines;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].content, "the");
+    }
+
+    #[test]
+    fn should_parse_plain_unified_diff_without_diff_header() {
+        let files = parse(
+            "--- a/notes.txt\t2024-01-01 00:00:00\n\
+             +++ b/notes.txt\t2024-01-02 00:00:00\n\
+             @@ -1 +1,2 @@\n\
+             \x20one\n\
+             +two\n\
+             --- a/other.txt\n\
+             +++ b/other.txt\n\
+             @@ -1 +1 @@\n\
+             -x\n\
+             +y\n",
+        );
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].new_path, Some(PathBuf::from("notes.txt")));
+        assert_eq!(files[1].old_path, Some(PathBuf::from("other.txt")));
+    }
+
+    #[test]
+    fn should_report_no_changes_for_empty_patch() {
+        let backend = PatchBackend::from_text(String::new(), PathBuf::from("/tmp"));
+        let result = backend.get_working_tree_diff(&SyntaxHighlighter::default());
+        assert!(matches!(result, Err(TuicrError::NoChanges)));
+    }
+
+    #[test]
+    fn should_key_sessions_by_patch_content() {
+        let a = PatchBackend::from_text("--- a/x\n+++ b/x\n".into(), PathBuf::from("/tmp"));
+        let b = PatchBackend::from_text("--- a/y\n+++ b/y\n".into(), PathBuf::from("/tmp"));
+        assert_ne!(a.info().head_commit, b.info().head_commit);
+        assert!(a.info().head_commit.starts_with("patch-"));
+    }
+}
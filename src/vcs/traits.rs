@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 
 use crate::error::Result;
-use crate::model::{DiffFile, DiffLine, FileStatus};
+use crate::model::{DiffFile, DiffLine, FileStatus, Reviewer};
 use crate::syntax::SyntaxHighlighter;
 
 /// Information about the VCS type
@@ -12,6 +12,7 @@ pub enum VcsType {
     Mercurial,
     Jujutsu,
     File,
+    Patch,
 }
 
 impl std::fmt::Display for VcsType {
@@ -21,10 +22,87 @@ impl std::fmt::Display for VcsType {
             VcsType::Mercurial => write!(f, "hg"),
             VcsType::Jujutsu => write!(f, "jj"),
             VcsType::File => write!(f, "file"),
+            VcsType::Patch => write!(f, "patch"),
         }
     }
 }
 
+/// How a commit range is turned into a diff, mirroring `git diff A..B`
+/// versus `git diff A...B`. `A` is the parent of the oldest selected commit
+/// and `B` the newest one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RangeMode {
+    /// Diff `A` directly against `B`.
+    #[default]
+    TwoDot,
+    /// Diff the merge-base of `A` and `B` against `B`.
+    ThreeDot,
+}
+
+impl RangeMode {
+    pub fn separator(self) -> &'static str {
+        match self {
+            RangeMode::TwoDot => "..",
+            RangeMode::ThreeDot => "...",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            RangeMode::TwoDot => RangeMode::ThreeDot,
+            RangeMode::ThreeDot => RangeMode::TwoDot,
+        }
+    }
+}
+
+/// Rename/copy detection applied when building diffs. Pairs deleted and
+/// added files whose content is similar enough, so moved files show as
+/// renames with a content diff instead of a full delete plus add.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenameDetection {
+    pub enabled: bool,
+    /// Also look for copies of files modified in the same diff.
+    pub copies: bool,
+    /// Split heavily rewritten files into a delete and an add so their old
+    /// content can pair up as the source of a rename.
+    pub break_rewrites: bool,
+    /// Minimum similarity (0-100%) for a pair to count as a rename or copy.
+    pub threshold: u16,
+    /// Skip detection when there are more candidate files than this, since
+    /// it is quadratic in the number of candidates. 0 means no limit.
+    pub limit: usize,
+}
+
+impl Default for RenameDetection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            copies: false,
+            break_rewrites: false,
+            threshold: 50,
+            limit: 1000,
+        }
+    }
+}
+
+/// Whitespace differences to hide when building diffs, like `git diff -w`,
+/// `-b` and `--ignore-blank-lines`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IgnoreWhitespace {
+    /// Ignore all whitespace when comparing lines (`-w`).
+    pub all_space: bool,
+    /// Ignore changes in the amount of whitespace (`-b`).
+    pub space_change: bool,
+    /// Ignore changes whose lines are all blank (`--ignore-blank-lines`).
+    pub blank_lines: bool,
+}
+
+impl IgnoreWhitespace {
+    pub fn any(&self) -> bool {
+        self.all_space || self.space_change || self.blank_lines
+    }
+}
+
 /// Repository information
 #[derive(Debug, Clone)]
 pub struct VcsInfo {
@@ -49,6 +127,51 @@ pub struct CommitInfo {
     pub time: DateTime<Utc>,
 }
 
+/// One author's share of a multi-commit review, shown in the overview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorStats {
+    pub author: String,
+    pub commits: usize,
+    /// Lines added and removed; `None` when the backend can't count them.
+    pub lines: Option<(usize, usize)>,
+}
+
+impl AuthorStats {
+    /// Group `commits` by author, most commits first, then most lines
+    /// changed, then by name. `lines` holds per-commit counts in the same
+    /// order as `commits`.
+    pub fn collect(commits: &[CommitInfo], lines: Option<&[(usize, usize)]>) -> Vec<Self> {
+        let mut stats: Vec<Self> = Vec::new();
+        for (idx, commit) in commits.iter().enumerate() {
+            let commit_lines = lines.and_then(|lines| lines.get(idx).copied());
+            let entry = match stats.iter_mut().position(|s| s.author == commit.author) {
+                Some(pos) => &mut stats[pos],
+                None => {
+                    stats.push(Self {
+                        author: commit.author.clone(),
+                        commits: 0,
+                        lines: lines.map(|_| (0, 0)),
+                    });
+                    stats.last_mut().expect("just pushed")
+                }
+            };
+            entry.commits += 1;
+            if let (Some((added, removed)), Some((a, r))) = (entry.lines.as_mut(), commit_lines) {
+                *added += a;
+                *removed += r;
+            }
+        }
+        let changed = |s: &Self| s.lines.map_or(0, |(a, r)| a + r);
+        stats.sort_by(|a, b| {
+            b.commits
+                .cmp(&a.commits)
+                .then(changed(b).cmp(&changed(a)))
+                .then(a.author.cmp(&b.author))
+        });
+        stats
+    }
+}
+
 /// Cheap repository change summary used by selection UIs before loading full diffs.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct VcsChangeStatus {
@@ -108,6 +231,45 @@ pub trait VcsBackend: Send {
         end_line: u32,
     ) -> Result<Vec<DiffLine>>;
 
+    /// The whole content a deleted file had before it was removed: `HEAD`
+    /// for git, `.` for hg, `@-` for jj.
+    fn fetch_deleted_file(&self, _file_path: &Path) -> Result<String> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "Reading deleted files not supported for this VCS".into(),
+        ))
+    }
+
+    /// Configure rename/copy detection for subsequent diffs.
+    /// Backends that cannot detect renames ignore this (default).
+    fn set_rename_detection(&mut self, _detection: RenameDetection) {}
+
+    /// Current rename/copy detection settings, or `None` when the backend
+    /// does not detect renames (default).
+    fn rename_detection(&self) -> Option<RenameDetection> {
+        None
+    }
+
+    /// Configure which whitespace differences subsequent diffs hide.
+    /// Backends that cannot ignore whitespace ignore this (default).
+    fn set_ignore_whitespace(&mut self, _whitespace: IgnoreWhitespace) {}
+
+    /// Current whitespace settings, or `None` when the backend cannot ignore
+    /// whitespace (default).
+    fn ignore_whitespace(&self) -> Option<IgnoreWhitespace> {
+        None
+    }
+
+    /// Configure whether working tree diffs list untracked files. Backends
+    /// without untracked files (jj snapshots every file) ignore this
+    /// (default).
+    fn set_include_untracked(&mut self, _include: bool) {}
+
+    /// Whether working tree diffs list untracked files, or `None` when the
+    /// backend has no notion of untracked files (default).
+    fn include_untracked(&self) -> Option<bool> {
+        None
+    }
+
     /// Get recent commits for commit selection UI.
     /// Returns empty vec if not supported (default).
     fn get_recent_commits(&self, _offset: usize, _limit: usize) -> Result<Vec<CommitInfo>> {
@@ -127,6 +289,7 @@ pub trait VcsBackend: Send {
     fn get_commit_range_diff(
         &self,
         _commit_ids: &[String],
+        _range_mode: RangeMode,
         _highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
         Err(crate::error::TuicrError::UnsupportedOperation(
@@ -134,12 +297,61 @@ pub trait VcsBackend: Send {
         ))
     }
 
+    /// Get the combined diff of every revision in a revset expression.
+    /// Returns error if not supported (default).
+    fn get_revset_diff(
+        &self,
+        _revset: &str,
+        _highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "Revset diff not supported for this VCS".into(),
+        ))
+    }
+
+    /// Get the interdiff between two versions of a patch series (each a
+    /// range like `main..v1` or a single commit): only what changed between
+    /// the versions, ignoring changes picked up by rebasing.
+    /// Returns error if not supported (default).
+    fn get_interdiff(
+        &self,
+        _old: &str,
+        _new: &str,
+        _highlighter: &SyntaxHighlighter,
+    ) -> Result<Vec<DiffFile>> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "Interdiff not supported for this VCS".into(),
+        ))
+    }
+
     /// Get commit info for specific commit IDs (for inline commit selector).
     /// Returns CommitInfo for each ID, in the same order as the input.
     fn get_commits_info(&self, _ids: &[String]) -> Result<Vec<CommitInfo>> {
         Ok(Vec::new())
     }
 
+    /// First parent of commit `id`, which a range starting at `id` is
+    /// diffed against. `None` for a root commit or when the backend can't
+    /// tell (default).
+    fn parent_commit(&self, _id: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// The user's name and email from the VCS configuration, used as the
+    /// reviewer identity when the config file sets none. `None` when unset
+    /// or unsupported (default).
+    fn user_identity(&self) -> Option<Reviewer> {
+        None
+    }
+
+    /// Lines added and removed by each commit, in the same order as the
+    /// input IDs. Returns error if not supported (default).
+    fn get_commit_line_stats(&self, _ids: &[String]) -> Result<Vec<(usize, usize)>> {
+        Err(crate::error::TuicrError::UnsupportedOperation(
+            "Commit line stats not supported for this VCS".into(),
+        ))
+    }
+
     /// Get a combined diff from the parent of the oldest commit through to the working tree.
     /// This shows both committed and working tree changes in a single diff.
     /// Returns error if not supported (default).
@@ -217,6 +429,53 @@ mod tests {
         assert!(info.branch_name.is_none());
     }
 
+    fn authored(id: &str, author: &str) -> CommitInfo {
+        CommitInfo {
+            id: id.to_string(),
+            short_id: id.to_string(),
+            branch_name: None,
+            summary: "Change".to_string(),
+            body: None,
+            author: author.to_string(),
+            time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn author_stats_group_commits_and_lines_by_author() {
+        let commits = [
+            authored("a1", "Alice"),
+            authored("b1", "Bob"),
+            authored("a2", "Alice"),
+            authored("c1", "Carol"),
+        ];
+
+        let stats = AuthorStats::collect(&commits, Some(&[(10, 2), (1, 1), (5, 0), (30, 4)]));
+
+        let summary: Vec<_> = stats
+            .iter()
+            .map(|s| (s.author.as_str(), s.commits, s.lines))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Alice", 2, Some((15, 2))),
+                ("Carol", 1, Some((30, 4))),
+                ("Bob", 1, Some((1, 1))),
+            ]
+        );
+    }
+
+    #[test]
+    fn author_stats_without_line_counts() {
+        let commits = [authored("b1", "Bob"), authored("a1", "Alice")];
+
+        let stats = AuthorStats::collect(&commits, None);
+
+        assert_eq!(stats[0].author, "Alice");
+        assert!(stats.iter().all(|s| s.commits == 1 && s.lines.is_none()));
+    }
+
     #[test]
     fn commit_info_clone() {
         let commit = CommitInfo {
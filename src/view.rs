@@ -0,0 +1,78 @@
+//! `tuicr view <file>`: browse a saved session, a `:session export` file or
+//! a `:export <file>.json` export read-only, such as the feedback a reviewer sent, without any
+//! chance of changing their session file.
+
+use std::path::Path;
+
+use crate::error::{Result, TuicrError};
+use crate::model::ReviewSession;
+use crate::output::json::session_from_json_review;
+use crate::persistence::portable::{PORTABLE_KEY, import_session};
+
+/// The review recorded in `path`, a session file, a portable session or a
+/// JSON export.
+pub fn load(path: &Path) -> Result<ReviewSession> {
+    let contents = std::fs::read_to_string(path)?;
+    let value: serde_json::Value =
+        serde_json::from_str(&contents).map_err(|e| TuicrError::CorruptedSession(e.to_string()))?;
+    if value.get("schema_version").is_some() {
+        return session_from_json_review(&contents);
+    }
+    if value.get(PORTABLE_KEY).is_some() {
+        return import_session(&contents, &std::env::current_dir()?).map(|(session, _)| session);
+    }
+    serde_json::from_value(value).map_err(|e| TuicrError::CorruptedSession(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::review::SessionDiffSource;
+    use crate::model::{Comment, CommentType, FileStatus, LineSide};
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_load_session_files_and_json_exports_alike() {
+        // given
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = ReviewSession::new(
+            PathBuf::from("/src/repo"),
+            "abc1234".to_string(),
+            Some("main".to_string()),
+            SessionDiffSource::CommitRange,
+        );
+        session.commit_range = Some(vec!["abc1234".to_string(), "def5678".to_string()]);
+        let path = PathBuf::from("src/lib.rs");
+        session.add_file(path.clone(), FileStatus::Added, 7);
+        let review = session.get_file_mut(&path).unwrap();
+        review.reviewed = true;
+        review.add_line_comment(
+            3,
+            Comment::new("typo".to_string(), CommentType::Issue, Some(LineSide::New)),
+        );
+        let session_path = dir.path().join("session.json");
+        let export_path = dir.path().join("review.json");
+        std::fs::write(&session_path, serde_json::to_string(&session).unwrap()).unwrap();
+        crate::output::export_json_to_file(
+            &export_path,
+            &session,
+            &[],
+            crate::output::json::DiffAnchors::default(),
+        )
+        .unwrap();
+
+        // when
+        let from_session = load(&session_path).unwrap();
+        let from_export = load(&export_path).unwrap();
+
+        // then
+        for loaded in [&from_session, &from_export] {
+            assert_eq!(loaded.id, session.id);
+            assert_eq!(loaded.commit_range, session.commit_range);
+            let review = &loaded.files[&path];
+            assert_eq!(review.status, FileStatus::Added);
+            assert!(review.reviewed);
+            assert_eq!(review.line_comments[&3][0].content, "typo");
+        }
+    }
+}